@@ -0,0 +1,71 @@
+import { useEffect, useState } from "react";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { tauriInvoke } from "~/lib/tauri";
+
+export type ReasonCodeRow = {
+  id: string;
+  code: string;
+  name: string;
+  txn_types: string[];
+  sort_no: number;
+  status: string;
+};
+
+type Props = {
+  value: string;
+  onChange: (value: string) => void;
+  /**
+   * 仅列出适用于该流水类型的原因：ADJUST / REVERSAL / SCRAP；不传则列出全部启用原因
+   */
+  txnType?: string;
+  /**
+   * 提供“未选择”选项时的显示文本，选中后回调空字符串
+   */
+  emptyLabel?: string;
+  disabled?: boolean;
+  placeholder?: string;
+};
+
+const EMPTY_VALUE = "__none__";
+
+// 原因选择：从原因字典加载启用的原因
+export function ReasonPicker({ value, onChange, txnType, emptyLabel, disabled, placeholder = "选择原因" }: Props) {
+  const [reasons, setReasons] = useState<ReasonCodeRow[]>([]);
+
+  useEffect(() => {
+    let active = true;
+    void (async () => {
+      try {
+        const result = await tauriInvoke<ReasonCodeRow[]>("list_reason_codes", {
+          input: { txn_type: txnType },
+        });
+        if (active) setReasons(result);
+      } catch {
+        if (active) setReasons([]);
+      }
+    })();
+    return () => {
+      active = false;
+    };
+  }, [txnType]);
+
+  return (
+    <Select
+      value={value || (emptyLabel ? EMPTY_VALUE : "")}
+      onValueChange={(next) => onChange(next === EMPTY_VALUE ? "" : next)}
+      disabled={disabled}
+    >
+      <SelectTrigger>
+        <SelectValue placeholder={placeholder} />
+      </SelectTrigger>
+      <SelectContent className="z-[9999]">
+        {emptyLabel ? <SelectItem value={EMPTY_VALUE}>{emptyLabel}</SelectItem> : null}
+        {reasons.map((reason) => (
+          <SelectItem key={reason.id} value={reason.id}>
+            {reason.name}
+          </SelectItem>
+        ))}
+      </SelectContent>
+    </Select>
+  );
+}
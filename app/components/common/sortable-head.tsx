@@ -0,0 +1,45 @@
+import { ArrowDownIcon, ArrowUpDownIcon, ArrowUpIcon } from "lucide-react"
+import type { ReactNode } from "react"
+
+import { TableHead } from "~/components/ui/table"
+import { cn } from "~/lib/utils"
+
+export type SortState = { by: string; dir: "asc" | "desc" } | null
+
+// 点击同一列依次切换：升序 → 降序 → 默认排序
+export function nextSort(current: SortState, field: string): SortState {
+  if (!current || current.by !== field) return { by: field, dir: "asc" }
+  if (current.dir === "asc") return { by: field, dir: "desc" }
+  return null
+}
+
+// 转为列表命令的 sort_by / sort_dir 参数
+export function sortParams(sort: SortState) {
+  return sort ? { sort_by: sort.by, sort_dir: sort.dir } : {}
+}
+
+type SortableHeadProps = {
+  field: string
+  sort: SortState
+  onSortChange: (sort: SortState) => void
+  children: ReactNode
+  className?: string
+}
+
+// 可排序表头：排序由后端完成，切换后调用方需回到第一页重新加载
+export function SortableHead({ field, sort, onSortChange, children, className }: SortableHeadProps) {
+  const active = sort?.by === field ? sort.dir : null
+  const Icon = active === "asc" ? ArrowUpIcon : active === "desc" ? ArrowDownIcon : ArrowUpDownIcon
+  return (
+    <TableHead className={className}>
+      <button
+        type="button"
+        className={cn("inline-flex items-center gap-1 hover:text-slate-900", active ? "text-slate-900" : "")}
+        onClick={() => onSortChange(nextSort(sort, field))}
+      >
+        {children}
+        <Icon className={cn("h-3.5 w-3.5", active ? "opacity-100" : "opacity-40")} />
+      </button>
+    </TableHead>
+  )
+}
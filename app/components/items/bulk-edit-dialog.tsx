@@ -0,0 +1,117 @@
+import { useEffect, useState } from "react"
+
+import { CommonDialog } from "~/components/common/common-dialogs"
+import { Button } from "~/components/ui/button"
+import { Input } from "~/components/ui/input"
+import { Label } from "~/components/ui/label"
+import { tauriInvoke } from "~/lib/tauri"
+import { toast } from "sonner"
+
+type BulkItemResult = {
+  updated: number
+}
+
+type BulkField = "model" | "spec" | "uom" | "unit_cost" | "currency" | "remark"
+
+const BULK_FIELDS: { field: BulkField; label: string; placeholder?: string }[] = [
+  { field: "model", label: "型号" },
+  { field: "spec", label: "规格" },
+  { field: "uom", label: "单位" },
+  { field: "unit_cost", label: "单位成本" },
+  { field: "currency", label: "币种", placeholder: "如 CNY" },
+  { field: "remark", label: "备注" },
+]
+
+type BulkEditDialogProps = {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  ids: string[]
+  onSaved: () => void
+}
+
+// 批量编辑：只提交勾选的字段，未勾选的保持不变；勾选后留空表示清空（单位成本除外）
+export function BulkEditDialog({ open, onOpenChange, ids, onSaved }: BulkEditDialogProps) {
+  const [enabled, setEnabled] = useState<Partial<Record<BulkField, boolean>>>({})
+  const [values, setValues] = useState<Partial<Record<BulkField, string>>>({})
+  const [saving, setSaving] = useState(false)
+
+  useEffect(() => {
+    if (!open) return
+    setEnabled({})
+    setValues({})
+  }, [open])
+
+  const handleSave = async () => {
+    const fields = BULK_FIELDS.filter(({ field }) => enabled[field])
+    if (fields.length === 0) {
+      toast.error("请至少勾选一个要修改的字段")
+      return
+    }
+    const input: Record<string, unknown> = { ids }
+    for (const { field } of fields) {
+      const value = (values[field] ?? "").trim()
+      if (field === "unit_cost") {
+        const cost = Number(value)
+        if (!value || !Number.isFinite(cost) || cost < 0) {
+          toast.error("单位成本需为非负数")
+          return
+        }
+        input.unit_cost = cost
+      } else {
+        input[field] = value
+      }
+    }
+    setSaving(true)
+    try {
+      const result = await tauriInvoke<BulkItemResult>("bulk_update_items", { input })
+      toast.success(`已更新 ${result.updated} 个物品`)
+      onOpenChange(false)
+      onSaved()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "批量编辑失败"
+      toast.error(message)
+    } finally {
+      setSaving(false)
+    }
+  }
+
+  return (
+    <CommonDialog
+      title="批量编辑物品"
+      description={`已选 ${ids.length} 个物品；勾选要修改的字段，留空表示清空`}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          {BULK_FIELDS.map(({ field, label, placeholder }) => (
+            <div key={field} className="grid grid-cols-[120px_1fr] items-center gap-3">
+              <label className="flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  className="h-4 w-4 accent-slate-900"
+                  checked={!!enabled[field]}
+                  onChange={(event) => setEnabled({ ...enabled, [field]: event.target.checked })}
+                />
+                <Label>{label}</Label>
+              </label>
+              <Input
+                type={field === "unit_cost" ? "number" : "text"}
+                min={field === "unit_cost" ? 0 : undefined}
+                step={field === "unit_cost" ? "any" : undefined}
+                placeholder={placeholder}
+                disabled={!enabled[field]}
+                value={values[field] ?? ""}
+                onChange={(event) => setValues({ ...values, [field]: event.target.value })}
+              />
+            </div>
+          ))}
+          <div className="flex justify-end">
+            <Button onClick={handleSave} disabled={saving}>
+              {saving ? "保存中..." : "保存"}
+            </Button>
+          </div>
+        </div>
+      }
+    />
+  )
+}
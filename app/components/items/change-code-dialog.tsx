@@ -0,0 +1,102 @@
+import { useEffect, useState } from "react"
+
+import { CommonDialog } from "~/components/common/common-dialogs"
+import { ConfirmButton } from "~/components/common/confirm-button"
+import { Input } from "~/components/ui/input"
+import { Label } from "~/components/ui/label"
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table"
+import { tauriInvoke } from "~/lib/tauri"
+import { toast } from "sonner"
+
+type ItemCodeHistoryRow = {
+  id: string
+  old_code: string
+  new_code: string
+  changed_at: number
+  changed_by_name?: string | null
+}
+
+type ChangeCodeDialogProps = {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  item: { id: string; item_code: string; name: string } | null
+  onChanged: () => void
+}
+
+// 修改物品编码：旧编码保留在变更历史中，扫码、按编码查询与导入仍可解析到该物品
+export function ChangeCodeDialog({ open, onOpenChange, item, onChanged }: ChangeCodeDialogProps) {
+  const [newCode, setNewCode] = useState("")
+  const [history, setHistory] = useState<ItemCodeHistoryRow[]>([])
+
+  useEffect(() => {
+    if (!open || !item) return
+    setNewCode("")
+    ;(async () => {
+      try {
+        setHistory(await tauriInvoke<ItemCodeHistoryRow[]>("list_item_code_history", { input: { id: item.id } }))
+      } catch {
+        setHistory([])
+      }
+    })()
+  }, [open, item?.id])
+
+  const handleChange = async () => {
+    if (!item) return
+    try {
+      await tauriInvoke("change_item_code", { input: { id: item.id, new_code: newCode.trim() } })
+      toast.success(`编码已修改为 ${newCode.trim()}`)
+      onOpenChange(false)
+      onChanged()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "修改失败"
+      toast.error(message)
+    }
+  }
+
+  return (
+    <CommonDialog
+      title="修改物品编码"
+      description={item ? `当前编码 ${item.item_code}（${item.name}）；旧编码仍可用于扫码与导入，已打印的标签无需更换` : ""}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="grid gap-2">
+            <Label>新编码</Label>
+            <div className="flex gap-2">
+              <Input value={newCode} onChange={(event) => setNewCode(event.target.value)} />
+              <ConfirmButton
+                label="修改"
+                confirmText="确认修改物品编码？"
+                disabled={!newCode.trim() || newCode.trim() === item?.item_code}
+                onConfirm={handleChange}
+              />
+            </div>
+          </div>
+          {history.length > 0 ? (
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>旧编码</TableHead>
+                  <TableHead>新编码</TableHead>
+                  <TableHead>修改人</TableHead>
+                  <TableHead>时间</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {history.map((row) => (
+                  <TableRow key={row.id}>
+                    <TableCell>{row.old_code}</TableCell>
+                    <TableCell>{row.new_code}</TableCell>
+                    <TableCell>{row.changed_by_name || "-"}</TableCell>
+                    <TableCell>{new Date(row.changed_at * 1000).toLocaleString()}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          ) : null}
+        </div>
+      }
+    />
+  )
+}
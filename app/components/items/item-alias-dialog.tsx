@@ -0,0 +1,154 @@
+import { useEffect, useState } from "react"
+
+import { CommonDialog } from "~/components/common/common-dialogs"
+import { ConfirmButton } from "~/components/common/confirm-button"
+import { Button } from "~/components/ui/button"
+import { Input } from "~/components/ui/input"
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select"
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table"
+import { tauriInvoke } from "~/lib/tauri"
+import { toast } from "sonner"
+
+type ItemAliasRow = {
+  id: string
+  item_id: string
+  code: string
+  kind: string
+  note?: string | null
+  created_at: number
+}
+
+const ALIAS_KINDS: { value: string; label: string }[] = [
+  { value: "manufacturer", label: "厂家编码" },
+  { value: "internal", label: "内部编码" },
+  { value: "ean", label: "EAN" },
+  { value: "supplier", label: "供应商编码" },
+  { value: "other", label: "其他" },
+]
+
+const kindLabel = (kind: string) => ALIAS_KINDS.find((option) => option.value === kind)?.label || kind
+
+type ItemAliasDialogProps = {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  item: { id: string; item_code: string; name: string } | null
+}
+
+// 物品别名：同一物品的其他条码/编码，扫码、按编码查询与导入时可解析到该物品
+export function ItemAliasDialog({ open, onOpenChange, item }: ItemAliasDialogProps) {
+  const [aliases, setAliases] = useState<ItemAliasRow[]>([])
+  const [code, setCode] = useState("")
+  const [kind, setKind] = useState("other")
+  const [note, setNote] = useState("")
+
+  const fetchAliases = async () => {
+    if (!item) return
+    try {
+      setAliases(await tauriInvoke<ItemAliasRow[]>("list_item_aliases", { input: { item_id: item.id } }))
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载别名失败"
+      toast.error(message)
+    }
+  }
+
+  useEffect(() => {
+    if (!open) return
+    setCode("")
+    setNote("")
+    void fetchAliases()
+  }, [open, item?.id])
+
+  const handleAdd = async () => {
+    if (!item) return
+    try {
+      await tauriInvoke("create_item_alias", {
+        input: { item_id: item.id, code: code.trim(), kind, note: note.trim() || null },
+      })
+      setCode("")
+      setNote("")
+      toast.success("别名已添加")
+      await fetchAliases()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "添加失败"
+      toast.error(message)
+    }
+  }
+
+  const handleDelete = async (id: string) => {
+    try {
+      await tauriInvoke("delete_item_alias", { input: { id } })
+      toast.success("别名已删除")
+      await fetchAliases()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败"
+      toast.error(message)
+    }
+  }
+
+  return (
+    <CommonDialog
+      title="物品别名"
+      description={item ? `${item.item_code} ${item.name}；别名全局唯一，不能与物品编码重复` : ""}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="flex flex-wrap gap-2">
+            <Select value={kind} onValueChange={setKind}>
+              <SelectTrigger className="w-[140px]">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {ALIAS_KINDS.map((option) => (
+                  <SelectItem key={option.value} value={option.value}>
+                    {option.label}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+            <Input className="w-[220px]" placeholder="别名 / 条码" value={code} onChange={(event) => setCode(event.target.value)} />
+            <Input className="w-[200px]" placeholder="备注（可选）" value={note} onChange={(event) => setNote(event.target.value)} />
+            <Button onClick={() => void handleAdd()} disabled={!code.trim()}>
+              添加
+            </Button>
+          </div>
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>类型</TableHead>
+                <TableHead>别名</TableHead>
+                <TableHead>备注</TableHead>
+                <TableHead className="text-right">操作</TableHead>
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {aliases.map((alias) => (
+                <TableRow key={alias.id}>
+                  <TableCell>{kindLabel(alias.kind)}</TableCell>
+                  <TableCell>{alias.code}</TableCell>
+                  <TableCell>{alias.note || "-"}</TableCell>
+                  <TableCell className="text-right">
+                    <ConfirmButton
+                      label="删除"
+                      variant="ghost"
+                      size="sm"
+                      confirmText="删除后该别名不能再扫码识别，确认删除？"
+                      onConfirm={() => handleDelete(alias.id)}
+                    />
+                  </TableCell>
+                </TableRow>
+              ))}
+              {aliases.length === 0 ? (
+                <TableRow>
+                  <TableCell colSpan={4} className="text-center text-slate-500">
+                    暂无别名
+                  </TableCell>
+                </TableRow>
+              ) : null}
+            </TableBody>
+          </Table>
+        </div>
+      }
+    />
+  )
+}
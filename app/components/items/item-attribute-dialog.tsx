@@ -0,0 +1,286 @@
+import { useEffect, useState } from "react"
+
+import { CommonDialog } from "~/components/common/common-dialogs"
+import { ConfirmButton } from "~/components/common/confirm-button"
+import { Badge } from "~/components/ui/badge"
+import { Button } from "~/components/ui/button"
+import { Input } from "~/components/ui/input"
+import { Label } from "~/components/ui/label"
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select"
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table"
+import { tauriInvoke } from "~/lib/tauri"
+import { toast } from "sonner"
+
+export type ItemAttributeDef = {
+  id: string
+  name: string
+  data_type: "text" | "number" | "date"
+  required: boolean
+  sort_no: number
+  status: "active" | "disabled"
+  created_at: number
+}
+
+type DefForm = {
+  id?: string
+  name: string
+  data_type: ItemAttributeDef["data_type"]
+  required: boolean
+  sort_no: string
+  status: ItemAttributeDef["status"]
+}
+
+const emptyForm: DefForm = { name: "", data_type: "text", required: false, sort_no: "0", status: "active" }
+
+const DATA_TYPE_OPTIONS = [
+  { value: "text", label: "文本" },
+  { value: "number", label: "数字" },
+  { value: "date", label: "日期" },
+]
+
+// 可配置为必填的内置字段，编号与名称始终必填
+const REQUIRABLE_FIELDS = [
+  { value: "model", label: "设备型号" },
+  { value: "spec", label: "规格" },
+  { value: "uom", label: "单位" },
+  { value: "unit_cost", label: "单位成本" },
+  { value: "currency", label: "币种" },
+  { value: "remark", label: "备注" },
+]
+
+type ItemAttributeDialogProps = {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  onChanged: () => void
+}
+
+// 物品字段设置：自定义属性（品牌、颜色、保质期等）与内置字段必填配置
+export function ItemAttributeDialog({ open, onOpenChange, onChanged }: ItemAttributeDialogProps) {
+  const [defs, setDefs] = useState<ItemAttributeDef[]>([])
+  const [requiredFields, setRequiredFields] = useState<string[]>([])
+  const [form, setForm] = useState<DefForm | null>(null)
+
+  const fetchDefs = async () => {
+    try {
+      setDefs(await tauriInvoke<ItemAttributeDef[]>("list_item_attribute_defs", { input: { include_disabled: true } }))
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载属性失败"
+      toast.error(message)
+    }
+  }
+
+  useEffect(() => {
+    if (!open) return
+    setForm(null)
+    void fetchDefs()
+    ;(async () => {
+      try {
+        const settings = await tauriInvoke<{ item_required_fields?: string[] }>("get_settings")
+        setRequiredFields(settings.item_required_fields ?? [])
+      } catch {
+        setRequiredFields([])
+      }
+    })()
+  }, [open])
+
+  const handleToggleRequired = async (field: string, checked: boolean) => {
+    const next = checked ? [...requiredFields, field] : requiredFields.filter((value) => value !== field)
+    try {
+      await tauriInvoke("set_settings", { input: { item_required_fields: next } })
+      setRequiredFields(next)
+      onChanged()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败"
+      toast.error(message)
+    }
+  }
+
+  const handleSave = async () => {
+    if (!form) return
+    const input = {
+      name: form.name.trim(),
+      data_type: form.data_type,
+      required: form.required,
+      sort_no: Number(form.sort_no) || 0,
+      status: form.status,
+    }
+    try {
+      if (form.id) {
+        await tauriInvoke("update_item_attribute_def", { input: { id: form.id, ...input } })
+      } else {
+        await tauriInvoke("create_item_attribute_def", { input })
+      }
+      toast.success("属性已保存")
+      setForm(null)
+      await fetchDefs()
+      onChanged()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败"
+      toast.error(message)
+    }
+  }
+
+  const handleDelete = async (id: string) => {
+    try {
+      await tauriInvoke("delete_item_attribute_def", { input: { id } })
+      toast.success("属性已删除")
+      await fetchDefs()
+      onChanged()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败"
+      toast.error(message)
+    }
+  }
+
+  return (
+    <CommonDialog
+      title="物品字段设置"
+      description="自定义属性会显示在物品列表与编辑表单中，导出时按属性名称追加列，导入时按表头名称识别"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="space-y-2">
+            <Label>必填字段</Label>
+            <div className="flex flex-wrap gap-4">
+              {REQUIRABLE_FIELDS.map((field) => (
+                <label key={field.value} className="flex items-center gap-2 text-sm">
+                  <input
+                    type="checkbox"
+                    className="h-4 w-4 accent-slate-900"
+                    checked={requiredFields.includes(field.value)}
+                    onChange={(event) => void handleToggleRequired(field.value, event.target.checked)}
+                  />
+                  {field.label}
+                </label>
+              ))}
+            </div>
+          </div>
+          {form ? (
+            <div className="grid gap-4 rounded-md border p-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>属性名称</Label>
+                <Input value={form.name} onChange={(event) => setForm({ ...form, name: event.target.value })} />
+              </div>
+              <div className="grid gap-2">
+                <Label>类型</Label>
+                <Select
+                  value={form.data_type}
+                  onValueChange={(value) => setForm({ ...form, data_type: value as DefForm["data_type"] })}
+                >
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    {DATA_TYPE_OPTIONS.map((option) => (
+                      <SelectItem key={option.value} value={option.value}>
+                        {option.label}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+              <div className="grid gap-2">
+                <Label>排序号</Label>
+                <Input
+                  type="number"
+                  value={form.sort_no}
+                  onChange={(event) => setForm({ ...form, sort_no: event.target.value })}
+                />
+              </div>
+              <div className="flex items-end gap-4 text-sm">
+                <label className="flex items-center gap-2">
+                  <input
+                    type="checkbox"
+                    className="h-4 w-4 accent-slate-900"
+                    checked={form.required}
+                    onChange={(event) => setForm({ ...form, required: event.target.checked })}
+                  />
+                  必填
+                </label>
+                <label className="flex items-center gap-2">
+                  <input
+                    type="checkbox"
+                    className="h-4 w-4 accent-slate-900"
+                    checked={form.status === "active"}
+                    onChange={(event) => setForm({ ...form, status: event.target.checked ? "active" : "disabled" })}
+                  />
+                  启用
+                </label>
+              </div>
+              <div className="flex gap-2 md:col-span-2">
+                <Button onClick={() => void handleSave()} disabled={!form.name.trim()}>
+                  保存
+                </Button>
+                <Button variant="outline" onClick={() => setForm(null)}>
+                  取消
+                </Button>
+              </div>
+            </div>
+          ) : (
+            <Button variant="outline" onClick={() => setForm({ ...emptyForm })}>
+              新增属性
+            </Button>
+          )}
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>属性</TableHead>
+                <TableHead>类型</TableHead>
+                <TableHead>必填</TableHead>
+                <TableHead>状态</TableHead>
+                <TableHead className="text-right">操作</TableHead>
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {defs.map((def) => (
+                <TableRow key={def.id}>
+                  <TableCell>{def.name}</TableCell>
+                  <TableCell>{DATA_TYPE_OPTIONS.find((option) => option.value === def.data_type)?.label}</TableCell>
+                  <TableCell>{def.required ? "是" : "否"}</TableCell>
+                  <TableCell>
+                    <Badge variant={def.status === "active" ? "secondary" : "outline"}>
+                      {def.status === "active" ? "启用" : "停用"}
+                    </Badge>
+                  </TableCell>
+                  <TableCell className="text-right">
+                    <Button
+                      variant="ghost"
+                      size="sm"
+                      onClick={() =>
+                        setForm({
+                          id: def.id,
+                          name: def.name,
+                          data_type: def.data_type,
+                          required: def.required,
+                          sort_no: String(def.sort_no),
+                          status: def.status,
+                        })
+                      }
+                    >
+                      编辑
+                    </Button>
+                    <ConfirmButton
+                      label="删除"
+                      variant="ghost"
+                      size="sm"
+                      confirmText="删除属性将同时删除所有物品的该属性取值，确认删除？"
+                      onConfirm={() => handleDelete(def.id)}
+                    />
+                  </TableCell>
+                </TableRow>
+              ))}
+              {defs.length === 0 ? (
+                <TableRow>
+                  <TableCell colSpan={5} className="text-center text-slate-500">
+                    暂无自定义属性
+                  </TableCell>
+                </TableRow>
+              ) : null}
+            </TableBody>
+          </Table>
+        </div>
+      }
+    />
+  )
+}
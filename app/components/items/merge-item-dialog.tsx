@@ -0,0 +1,79 @@
+import { useEffect, useState } from "react"
+
+import { CommonDialog } from "~/components/common/common-dialogs"
+import { ConfirmButton } from "~/components/common/confirm-button"
+import { ItemPicker } from "~/components/common/pickers/item-picker"
+import { Label } from "~/components/ui/label"
+import { tauriInvoke } from "~/lib/tauri"
+import { toast } from "sonner"
+
+type ItemMergeResult = {
+  source_code: string
+  target_code: string
+  references: {
+    stock_rows: number
+    txns: number
+    loans: number
+    photos: number
+  }
+}
+
+type MergeItemDialogProps = {
+  open: boolean
+  onOpenChange: (open: boolean) => void
+  source: { id: string; item_code: string; name: string } | null
+  onMerged: () => void
+}
+
+// 合并重复物品：当前物品的库存、流水、借用与图片改挂到选中的目标物品，当前物品停用
+export function MergeItemDialog({ open, onOpenChange, source, onMerged }: MergeItemDialogProps) {
+  const [targetId, setTargetId] = useState("")
+
+  useEffect(() => {
+    if (open) setTargetId("")
+  }, [open])
+
+  const handleMerge = async () => {
+    if (!source || !targetId) return
+    try {
+      const result = await tauriInvoke<ItemMergeResult>("merge_items", {
+        input: { source_id: source.id, target_id: targetId },
+      })
+      const { stock_rows, txns, loans, photos } = result.references
+      toast.success(`已将 ${result.source_code} 合并到 ${result.target_code}`, {
+        description: `库存 ${stock_rows} 行，流水 ${txns} 条，借用 ${loans} 条，图片 ${photos} 张`,
+      })
+      onOpenChange(false)
+      onMerged()
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "合并失败"
+      toast.error(message)
+    }
+  }
+
+  return (
+    <CommonDialog
+      title="合并物品"
+      description={source ? `将 ${source.item_code} ${source.name} 合并到其他物品；同库位库存相加，合并后该物品停用且不可撤销` : ""}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="grid gap-2">
+            <Label>合并到（保留的物品）</Label>
+            <ItemPicker value={targetId} onChange={(value) => setTargetId(value)} />
+          </div>
+          <div className="flex justify-end">
+            <ConfirmButton
+              label="合并"
+              variant="destructive"
+              confirmText="合并后不可撤销，确认合并？"
+              disabled={!targetId || targetId === source?.id}
+              onConfirm={handleMerge}
+            />
+          </div>
+        </div>
+      }
+    />
+  )
+}
@@ -0,0 +1,105 @@
+import { useEffect, useState } from "react";
+import { Button } from "~/components/ui/button";
+import { Label } from "~/components/ui/label";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+import { LABEL_TARGET_LABELS, type LabelTarget, type LabelTemplate } from "./types";
+
+type LabelPrintResult = {
+  file_path: string;
+  template_id: string;
+  labels: number;
+};
+
+type LabelPrintDialogProps = {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  target: LabelTarget;
+  // 库位标签：整个货架
+  rackId?: string;
+  // 库位编码或物品编码
+  codes?: string[];
+  description?: string;
+};
+
+export function LabelPrintDialog({ open, onOpenChange, target, rackId, codes, description }: LabelPrintDialogProps) {
+  const [templates, setTemplates] = useState<LabelTemplate[]>([]);
+  const [templateId, setTemplateId] = useState("");
+  const [printing, setPrinting] = useState(false);
+
+  useEffect(() => {
+    if (!open) return;
+    tauriInvoke<LabelTemplate[]>("list_label_templates", { input: { target } })
+      .then((result) => {
+        setTemplates(result);
+        setTemplateId(result.find((item) => item.is_default)?.id ?? result[0]?.id ?? "");
+      })
+      .catch((err) => {
+        const message = err instanceof Error ? err.message : "加载标签模板失败";
+        toast.error(message);
+      });
+  }, [open, target]);
+
+  const handleGenerate = async () => {
+    setPrinting(true);
+    try {
+      const result = await tauriInvoke<LabelPrintResult>("generate_labels", {
+        input: {
+          target,
+          template_id: templateId || undefined,
+          rack_id: rackId || undefined,
+          codes: codes && codes.length > 0 ? codes : undefined,
+        },
+      });
+      onOpenChange(false);
+      const { isMobile, shareFile, revealInFolder } = await import("~/lib/tauri");
+      if (isMobile()) {
+        await shareFile(result.file_path);
+        toast.success("已打开分享菜单");
+      } else {
+        toast.success(`已生成 ${result.labels} 张标签`);
+        await revealInFolder(result.file_path);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "生成标签失败";
+      toast.error(message);
+    } finally {
+      setPrinting(false);
+    }
+  };
+
+  return (
+    <CommonDialog
+      title={`打印${LABEL_TARGET_LABELS[target]}`}
+      description={description ?? "按所选模板的尺寸生成 PDF，每张标签一页"}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="space-y-2">
+            <Label>标签模板</Label>
+            <Select value={templateId} onValueChange={setTemplateId}>
+              <SelectTrigger>
+                <SelectValue placeholder="暂无模板，请先在系统设置中添加" />
+              </SelectTrigger>
+              <SelectContent className="z-[9999]">
+                {templates.map((template) => (
+                  <SelectItem key={template.id} value={template.id}>
+                    {template.name}（{template.width_mm}×{template.height_mm}mm）{template.is_default ? " · 默认" : ""}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="flex justify-end">
+            <Button onClick={handleGenerate} disabled={printing || !templateId}>
+              生成
+            </Button>
+          </div>
+        </div>
+      }
+    />
+  );
+}
@@ -0,0 +1,271 @@
+import { useEffect, useState } from "react";
+import { Button } from "~/components/ui/button";
+import { Badge } from "~/components/ui/badge";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+import { LABEL_FIELD_LABELS, LABEL_TARGET_LABELS, type LabelTarget, type LabelTemplate } from "./types";
+
+type TemplateForm = {
+  name: string;
+  target: LabelTarget;
+  width_mm: string;
+  height_mm: string;
+  fields: string[];
+  barcode_type: "code128" | "none";
+  font_size: string;
+  is_default: boolean;
+};
+
+const EMPTY_FORM: TemplateForm = {
+  name: "",
+  target: "slot",
+  width_mm: "60",
+  height_mm: "40",
+  fields: ["code"],
+  barcode_type: "code128",
+  font_size: "9",
+  is_default: false,
+};
+
+// 标签模板管理：不同打印机使用不同尺寸的标签，生成标签时按模板排版
+export function LabelTemplateCard({ disabled }: { disabled?: boolean }) {
+  const [templates, setTemplates] = useState<LabelTemplate[]>([]);
+  const [formOpen, setFormOpen] = useState(false);
+  const [editId, setEditId] = useState<string | null>(null);
+  const [form, setForm] = useState<TemplateForm>(EMPTY_FORM);
+
+  const fetchTemplates = async () => {
+    try {
+      const result = await tauriInvoke<LabelTemplate[]>("list_label_templates", { input: {} });
+      setTemplates(result);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载标签模板失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    void fetchTemplates();
+  }, []);
+
+  const openCreate = () => {
+    setEditId(null);
+    setForm(EMPTY_FORM);
+    setFormOpen(true);
+  };
+
+  const openEdit = (template: LabelTemplate) => {
+    setEditId(template.id);
+    setForm({
+      name: template.name,
+      target: template.target,
+      width_mm: String(template.width_mm),
+      height_mm: String(template.height_mm),
+      fields: template.fields,
+      barcode_type: template.barcode_type,
+      font_size: String(template.font_size),
+      is_default: template.is_default,
+    });
+    setFormOpen(true);
+  };
+
+  const toggleField = (field: string, checked: boolean) => {
+    // 按字段定义顺序保存，即打印顺序
+    const order = Object.keys(LABEL_FIELD_LABELS[form.target]);
+    const next = checked ? [...form.fields, field] : form.fields.filter((item) => item !== field);
+    setForm({ ...form, fields: order.filter((item) => next.includes(item)) });
+  };
+
+  const handleSave = async () => {
+    const input = {
+      name: form.name.trim(),
+      target: form.target,
+      width_mm: Number(form.width_mm),
+      height_mm: Number(form.height_mm),
+      fields: form.fields,
+      barcode_type: form.barcode_type,
+      font_size: Number(form.font_size),
+      is_default: form.is_default,
+    };
+    if (!input.name) {
+      toast.error("请输入模板名称");
+      return;
+    }
+    try {
+      if (editId) {
+        await tauriInvoke("update_label_template", { input: { id: editId, ...input } });
+        toast.success("标签模板已更新");
+      } else {
+        await tauriInvoke("create_label_template", { input });
+        toast.success("标签模板已创建");
+      }
+      setFormOpen(false);
+      await fetchTemplates();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleDelete = async (template: LabelTemplate) => {
+    try {
+      await tauriInvoke("delete_label_template", { input: { id: template.id } });
+      toast.success("标签模板已删除");
+      await fetchTemplates();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>标签模板</CardTitle>
+        <CardDescription>按标签打印机的纸张设置尺寸、打印字段与条码；每类标签可设一个默认模板</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>名称</TableHead>
+              <TableHead>类型</TableHead>
+              <TableHead>尺寸</TableHead>
+              <TableHead>字段</TableHead>
+              <TableHead className="text-center">操作</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {templates.map((template) => (
+              <TableRow key={template.id}>
+                <TableCell className="font-medium">
+                  {template.name}
+                  {template.is_default ? <Badge variant="secondary" className="ml-2">默认</Badge> : null}
+                </TableCell>
+                <TableCell>{LABEL_TARGET_LABELS[template.target]}</TableCell>
+                <TableCell>
+                  {template.width_mm}×{template.height_mm}mm
+                </TableCell>
+                <TableCell>
+                  {template.fields.map((field) => LABEL_FIELD_LABELS[template.target][field] ?? field).join("、")}
+                  {template.barcode_type === "code128" ? "、条码" : ""}
+                </TableCell>
+                <TableCell className="text-center">
+                  <Button variant="ghost" size="sm" onClick={() => openEdit(template)} disabled={disabled}>
+                    编辑
+                  </Button>
+                  <ConfirmButton
+                    label="删除"
+                    variant="ghost"
+                    size="sm"
+                    confirmText={`确认删除模板“${template.name}”？`}
+                    onConfirm={() => handleDelete(template)}
+                    disabled={disabled}
+                  />
+                </TableCell>
+              </TableRow>
+            ))}
+            {templates.length === 0 ? (
+              <TableRow>
+                <TableCell colSpan={5} className="text-center text-slate-500">
+                  暂无标签模板
+                </TableCell>
+              </TableRow>
+            ) : null}
+          </TableBody>
+        </Table>
+        <Button variant="outline" onClick={openCreate} disabled={disabled}>
+          新增模板
+        </Button>
+      </CardContent>
+      <CommonDialog
+        title={editId ? "编辑标签模板" : "新增标签模板"}
+        description="尺寸单位为毫米；中文内容受 PDF 内置字体限制不打印，编码与条码始终打印"
+        open={formOpen}
+        onOpenChange={setFormOpen}
+        content={
+          <div className="space-y-4">
+            <div className="grid gap-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>名称</Label>
+                <Input value={form.name} onChange={(event) => setForm({ ...form, name: event.target.value })} />
+              </div>
+              <div className="grid gap-2">
+                <Label>类型</Label>
+                <div className="flex gap-2">
+                  {(Object.keys(LABEL_TARGET_LABELS) as LabelTarget[]).map((target) => (
+                    <Button
+                      key={target}
+                      variant={form.target === target ? "default" : "outline"}
+                      disabled={!!editId}
+                      onClick={() => setForm({ ...form, target, fields: ["code"] })}
+                    >
+                      {LABEL_TARGET_LABELS[target]}
+                    </Button>
+                  ))}
+                </div>
+              </div>
+              <div className="grid gap-2">
+                <Label>宽度（mm）</Label>
+                <Input type="number" min={15} max={200} value={form.width_mm} onChange={(event) => setForm({ ...form, width_mm: event.target.value })} />
+              </div>
+              <div className="grid gap-2">
+                <Label>高度（mm）</Label>
+                <Input type="number" min={15} max={200} value={form.height_mm} onChange={(event) => setForm({ ...form, height_mm: event.target.value })} />
+              </div>
+              <div className="grid gap-2">
+                <Label>字号（pt）</Label>
+                <Input type="number" min={5} max={36} value={form.font_size} onChange={(event) => setForm({ ...form, font_size: event.target.value })} />
+              </div>
+              <div className="grid gap-2">
+                <Label>条码</Label>
+                <div className="flex gap-2">
+                  <Button variant={form.barcode_type === "code128" ? "default" : "outline"} onClick={() => setForm({ ...form, barcode_type: "code128" })}>
+                    Code 128
+                  </Button>
+                  <Button variant={form.barcode_type === "none" ? "default" : "outline"} onClick={() => setForm({ ...form, barcode_type: "none" })}>
+                    不打印
+                  </Button>
+                </div>
+              </div>
+            </div>
+            <div className="space-y-2">
+              <Label>打印字段</Label>
+              <div className="flex flex-wrap gap-4">
+                {Object.entries(LABEL_FIELD_LABELS[form.target]).map(([field, label]) => (
+                  <label key={field} className="flex items-center gap-2 text-sm">
+                    <input
+                      type="checkbox"
+                      className="h-4 w-4 accent-slate-900"
+                      checked={form.fields.includes(field)}
+                      onChange={(event) => toggleField(field, event.target.checked)}
+                    />
+                    {label}
+                  </label>
+                ))}
+              </div>
+            </div>
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                className="h-4 w-4 accent-slate-900"
+                checked={form.is_default}
+                onChange={(event) => setForm({ ...form, is_default: event.target.checked })}
+              />
+              设为该类标签的默认模板
+            </label>
+            <div className="flex justify-end">
+              <Button onClick={handleSave}>保存</Button>
+            </div>
+          </div>
+        }
+      />
+    </Card>
+  );
+}
@@ -0,0 +1,26 @@
+export type LabelTarget = "slot" | "item";
+
+export type LabelTemplate = {
+  id: string;
+  name: string;
+  target: LabelTarget;
+  width_mm: number;
+  height_mm: number;
+  fields: string[];
+  barcode_type: "code128" | "none";
+  font_size: number;
+  is_default: boolean;
+  created_at: number;
+  updated_at: number;
+};
+
+export const LABEL_TARGET_LABELS: Record<LabelTarget, string> = {
+  slot: "库位标签",
+  item: "物品标签",
+};
+
+// 与后端 label_service 的字段白名单一致
+export const LABEL_FIELD_LABELS: Record<LabelTarget, Record<string, string>> = {
+  slot: { code: "库位编码", warehouse: "仓库", rack: "货架", level_slot: "层-位" },
+  item: { code: "物品编码", name: "名称", model: "型号", spec: "规格", uom: "单位" },
+};
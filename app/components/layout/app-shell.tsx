@@ -1,11 +1,18 @@
-import { useRef, useState } from "react";
+import { useEffect, useRef, useState } from "react";
 import { NavLink, Outlet, Link, useLocation, useNavigate } from "react-router";
 import { cn } from "~/lib/utils";
-import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "~/components/ui/dialog";
 import { DropdownMenu, DropdownMenuTrigger, DropdownMenuContent, DropdownMenuItem } from "~/components/ui/dropdown-menu";
 import { ForceChangePasswordDialog } from "~/components/auth/force-change-password-dialog";
+import { NotificationBell } from "~/components/layout/notification-bell";
+import { OfflineQueueIndicator } from "~/components/layout/offline-queue-indicator";
+import { UndoDialog } from "~/components/layout/undo-dialog";
+import { ProfileDialog } from "~/components/layout/profile-dialog";
 import { clearSession, useSession } from "~/lib/auth";
 import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+// 会话心跳间隔：管理员强制下线后，终端最迟在一个间隔内退出登录
+const SESSION_CHECK_INTERVAL_MS = 60_000;
 
 const navSections = [
   {
@@ -17,6 +24,7 @@ const navSections = [
     items: [
       { to: "/stock", label: "库存管理" },
       { to: "/txns", label: "流水查询" },
+      { to: "/loans", label: "借用归还" },
       { to: "/items", label: "物品管理" },
     ],
   },
@@ -46,6 +54,7 @@ export function AppShell() {
   const closeTimer = useRef<number | null>(null);
   const openTimer = useRef<number | null>(null);
   const [forceChangeOpen, setForceChangeOpen] = useState(false);
+  const [undoOpen, setUndoOpen] = useState(false);
   const session = useSession();
   const location = useLocation();
   const navigate = useNavigate();
@@ -56,17 +65,26 @@ export function AppShell() {
     "/items": "物品管理",
     "/stock": "库存管理",
     "/txns": "流水查询",
+    "/loans": "借用归还",
     "/operators": "人员管理",
     "/audit": "操作日志",
     "/settings": "系统设置",
   };
   const path = location.pathname === "/" ? "/" : location.pathname.replace(/\/$/, "");
   const currentLabel = breadcrumbMap[path] ?? "页面";
+  const [readOnly, setReadOnly] = useState<{ enabled: boolean; reason?: string | null }>({ enabled: false });
+
+  // 只读维护模式提示：切换页面时刷新
+  useEffect(() => {
+    tauriInvoke<{ read_only_mode: boolean; read_only_reason?: string | null }>("get_settings")
+      .then((result) => setReadOnly({ enabled: result.read_only_mode, reason: result.read_only_reason }))
+      .catch(() => null);
+  }, [location.pathname]);
 
   const handleLogout = () => {
     const actorId = session?.actor_operator_id;
     if (actorId) {
-      tauriInvoke("logout", { actorOperatorId: actorId }).catch(() => null);
+      tauriInvoke("logout", { actorOperatorId: actorId, sessionId: session?.session_id ?? null }).catch(() => null);
     }
     clearSession();
     setAccountOpen(false);
@@ -74,6 +92,31 @@ export function AppShell() {
   };
   const displayName = session?.username || "Admin";
 
+  useEffect(() => {
+    const sessionId = session?.session_id;
+    if (!sessionId) return;
+    const check = async () => {
+      try {
+        const result = await tauriInvoke<{ active: boolean; end_reason?: string | null }>("check_session", {
+          input: { session_id: sessionId },
+        });
+        if (result.active) return;
+        const reasons: Record<string, string> = {
+          forced: "当前会话已被管理员结束，请重新登录",
+          idle: "长时间未操作，请重新登录",
+        };
+        toast.error(reasons[result.end_reason ?? ""] ?? "会话已失效，请重新登录");
+        clearSession();
+        navigate("/login", { replace: true });
+      } catch {
+        // 网络或数据库暂不可用时等待下次检查
+      }
+    };
+    void check();
+    const timer = window.setInterval(() => void check(), SESSION_CHECK_INTERVAL_MS);
+    return () => window.clearInterval(timer);
+  }, [session?.session_id]);
+
   return (
     <div className="h-screen overflow-hidden bg-[radial-gradient(circle_at_top,_#f6f1e8,_#f3f6fb_35%,_#f7f7f2_70%)] text-slate-900">
       <div className="mx-auto flex h-screen min-h-0 max-w-[1600px] gap-6 px-4 py-6 lg:px-6">
@@ -123,6 +166,8 @@ export function AppShell() {
               </div>
             </nav>
             <div className="flex items-center gap-3">
+              <OfflineQueueIndicator />
+              <NotificationBell />
               <DropdownMenu open={accountMenuOpen} onOpenChange={setAccountMenuOpen}>
                 <DropdownMenuTrigger asChild>
                   <button
@@ -182,6 +227,14 @@ export function AppShell() {
                   >
                     修改密码
                   </DropdownMenuItem>
+                  <DropdownMenuItem
+                    onSelect={() => {
+                      setAccountMenuOpen(false);
+                      setUndoOpen(true);
+                    }}
+                  >
+                    撤销最近修改
+                  </DropdownMenuItem>
                   <DropdownMenuItem
                     onSelect={() => {
                       setAccountMenuOpen(false);
@@ -213,26 +266,18 @@ export function AppShell() {
                 </NavLink>
               ))}
           </nav>
+          {readOnly.enabled ? (
+            <div className="rounded-2xl border border-amber-200 bg-amber-50 px-4 py-2 text-sm text-amber-800">
+              系统处于只读维护模式{readOnly.reason ? `（${readOnly.reason}）` : ""}，当前仅可查询与导出
+            </div>
+          ) : null}
           <main className="flex-1 overflow-y-scroll rounded-3xl border border-slate-200/70 bg-white/90 p-6 shadow-sm backdrop-blur">
             <Outlet />
           </main>
         </div>
       </div>
-      <Dialog open={accountOpen} onOpenChange={setAccountOpen}>
-        <DialogContent className="max-w-sm">
-          <DialogHeader>
-            <DialogTitle>当前账号</DialogTitle>
-            <DialogDescription>查看账号信息与角色</DialogDescription>
-          </DialogHeader>
-          <div className="rounded-2xl border border-slate-200 bg-white p-4 text-sm text-slate-600">
-            <p>当前账号：{displayName}</p>
-            <p className="mt-2">角色：全权限</p>
-          </div>
-          <button type="button" className="w-full rounded-lg border border-slate-200 px-4 py-2 text-sm font-medium text-slate-600 transition hover:border-slate-300 hover:text-slate-900" onClick={handleLogout}>
-            退出登录
-          </button>
-        </DialogContent>
-      </Dialog>
+      <ProfileDialog open={accountOpen} onOpenChange={setAccountOpen} onLogout={handleLogout} />
+      <UndoDialog open={undoOpen} onOpenChange={setUndoOpen} onUndone={() => navigate(0)} />
       {forceChangeOpen ? <ForceChangePasswordDialog open={forceChangeOpen} closable={true} onSuccess={() => setForceChangeOpen(false)} onClose={() => setForceChangeOpen(false)} /> : null}
     </div>
   );
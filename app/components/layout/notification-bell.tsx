@@ -0,0 +1,126 @@
+import { useEffect, useState } from "react";
+import { useNavigate } from "react-router";
+import { listen } from "@tauri-apps/api/event";
+import { toast } from "sonner";
+import { Popover, PopoverContent, PopoverTrigger } from "~/components/ui/popover";
+import { tauriInvoke } from "~/lib/tauri";
+import { cn } from "~/lib/utils";
+
+type NotificationItem = {
+  id: string;
+  kind: "low_stock" | "backup_failed" | "report_failed" | "approval_request" | "loan_overdue";
+  title: string;
+  body?: string | null;
+  ref_id?: string | null;
+  created_at: number;
+  read_at?: number | null;
+};
+
+type NotificationListResult = {
+  items: NotificationItem[];
+  unread_total: number;
+};
+
+// 点击通知后跳转的页面
+const KIND_PATHS: Record<NotificationItem["kind"], string> = {
+  low_stock: "/stock",
+  backup_failed: "/settings",
+  report_failed: "/settings",
+  approval_request: "/settings",
+  loan_overdue: "/loans",
+};
+
+// 通知中心：未读角标随后台推送的 notification 事件刷新
+export function NotificationBell() {
+  const navigate = useNavigate();
+  const [open, setOpen] = useState(false);
+  const [items, setItems] = useState<NotificationItem[]>([]);
+  const [unreadTotal, setUnreadTotal] = useState(0);
+
+  const fetchNotifications = async () => {
+    try {
+      const result = await tauriInvoke<NotificationListResult>("list_notifications", { input: { unread_only: false } });
+      setItems(result.items);
+      setUnreadTotal(result.unread_total);
+    } catch {
+      // 通知加载失败不影响正常使用
+    }
+  };
+
+  useEffect(() => {
+    void fetchNotifications();
+    const unlisten = listen("notification", () => {
+      void fetchNotifications();
+    });
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, []);
+
+  const markRead = async (ids: string[] | null) => {
+    try {
+      await tauriInvoke("mark_notifications_read", { input: { ids } });
+      await fetchNotifications();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "操作失败";
+      toast.error(message);
+    }
+  };
+
+  const handleOpenItem = (item: NotificationItem) => {
+    if (!item.read_at) {
+      void markRead([item.id]);
+    }
+    setOpen(false);
+    navigate(KIND_PATHS[item.kind] ?? "/");
+  };
+
+  return (
+    <Popover open={open} onOpenChange={setOpen}>
+      <PopoverTrigger asChild>
+        <button
+          type="button"
+          className="relative rounded-full border border-slate-200 bg-white px-4 py-2 text-sm font-medium text-slate-600 transition hover:border-slate-300 hover:text-slate-900"
+        >
+          通知
+          {unreadTotal > 0 ? (
+            <span className="absolute -right-1 -top-1 flex h-5 min-w-5 items-center justify-center rounded-full bg-red-500 px-1 text-xs text-white">
+              {unreadTotal > 99 ? "99+" : unreadTotal}
+            </span>
+          ) : null}
+        </button>
+      </PopoverTrigger>
+      <PopoverContent align="end" className="w-80 p-0">
+        <div className="flex items-center justify-between border-b border-slate-100 px-4 py-3">
+          <p className="text-sm font-semibold">通知</p>
+          <button
+            type="button"
+            className="text-xs text-slate-500 transition hover:text-slate-900 disabled:opacity-50"
+            disabled={unreadTotal === 0}
+            onClick={() => void markRead(null)}
+          >
+            全部已读
+          </button>
+        </div>
+        <div className="max-h-96 overflow-y-auto">
+          {items.map((item) => (
+            <button
+              key={item.id}
+              type="button"
+              className={cn(
+                "block w-full border-b border-slate-100 px-4 py-3 text-left text-sm transition hover:bg-slate-50",
+                item.read_at ? "text-slate-400" : "text-slate-700"
+              )}
+              onClick={() => handleOpenItem(item)}
+            >
+              <p className="font-medium">{item.title}</p>
+              {item.body ? <p className="mt-1 text-xs">{item.body}</p> : null}
+              <p className="mt-1 text-xs text-slate-400">{new Date(item.created_at * 1000).toLocaleString()}</p>
+            </button>
+          ))}
+          {items.length === 0 ? <p className="px-4 py-6 text-center text-sm text-slate-500">暂无通知</p> : null}
+        </div>
+      </PopoverContent>
+    </Popover>
+  );
+}
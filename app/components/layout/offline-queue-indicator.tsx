@@ -0,0 +1,94 @@
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { Popover, PopoverContent, PopoverTrigger } from "~/components/ui/popover";
+import { discardEntry, loadQueue, replayQueue, retryEntry, subscribeQueue, type QueuedEntry } from "~/lib/offline-queue";
+import { cn } from "~/lib/utils";
+
+// 补交间隔：写入暂停期间定时重试
+const REPLAY_INTERVAL_MS = 15 * 1000;
+
+// 待补交提示：有暂存提交时显示，写入恢复后自动补交并提示结果，补交被拒绝的条目可重试或放弃
+export function OfflineQueueIndicator() {
+  const [open, setOpen] = useState(false);
+  const [entries, setEntries] = useState<QueuedEntry[]>(() => loadQueue());
+  const pendingCount = entries.filter((entry) => entry.status === "pending").length;
+  const conflictCount = entries.length - pendingCount;
+  const hasPending = pendingCount > 0;
+
+  const replay = async () => {
+    const result = await replayQueue();
+    if (result.replayed > 0) {
+      toast.success(`已补交 ${result.replayed} 笔暂存提交`);
+    }
+    if (result.conflicts > 0) {
+      toast.error(`${result.conflicts} 笔暂存提交补交失败，请在待补交列表中处理`);
+    }
+  };
+
+  useEffect(() => subscribeQueue(() => setEntries(loadQueue())), []);
+
+  useEffect(() => {
+    if (!hasPending) return;
+    void replay();
+    const timer = window.setInterval(() => void replay(), REPLAY_INTERVAL_MS);
+    return () => window.clearInterval(timer);
+    // 仅在有无待补交条目切换时重建定时器
+    // eslint-disable-next-line react-hooks/exhaustive-deps
+  }, [hasPending]);
+
+  if (entries.length === 0) return null;
+
+  return (
+    <Popover open={open} onOpenChange={setOpen}>
+      <PopoverTrigger asChild>
+        <button
+          type="button"
+          className={cn(
+            "relative rounded-full border px-4 py-2 text-sm font-medium transition",
+            conflictCount > 0
+              ? "border-red-200 bg-red-50 text-red-600 hover:border-red-300"
+              : "border-amber-200 bg-amber-50 text-amber-700 hover:border-amber-300"
+          )}
+        >
+          待补交 {entries.length}
+        </button>
+      </PopoverTrigger>
+      <PopoverContent align="end" className="w-80 p-0">
+        <div className="flex items-center justify-between border-b border-slate-100 px-4 py-3">
+          <p className="text-sm font-semibold">待补交提交</p>
+          <button
+            type="button"
+            className="text-xs text-slate-500 transition hover:text-slate-900 disabled:opacity-50"
+            disabled={pendingCount === 0}
+            onClick={() => void replay()}
+          >
+            立即补交
+          </button>
+        </div>
+        <div className="max-h-96 overflow-y-auto">
+          {entries.map((entry) => (
+            <div key={entry.id} className="border-b border-slate-100 px-4 py-3 text-sm">
+              <p className="font-medium text-slate-700">{entry.label}</p>
+              <p className="mt-1 text-xs text-slate-400">暂存于 {new Date(entry.queued_at).toLocaleString()}</p>
+              {entry.status === "conflict" ? (
+                <>
+                  <p className="mt-1 text-xs text-red-600">{entry.error}</p>
+                  <div className="mt-2 flex gap-3 text-xs">
+                    <button type="button" className="text-slate-600 hover:text-slate-900" onClick={() => retryEntry(entry.id)}>
+                      重试
+                    </button>
+                    <button type="button" className="text-red-600 hover:text-red-700" onClick={() => discardEntry(entry.id)}>
+                      放弃
+                    </button>
+                  </div>
+                </>
+              ) : (
+                <p className="mt-1 text-xs text-amber-600">等待写入恢复</p>
+              )}
+            </div>
+          ))}
+        </div>
+      </PopoverContent>
+    </Popover>
+  );
+}
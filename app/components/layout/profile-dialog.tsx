@@ -0,0 +1,140 @@
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "~/components/ui/dialog";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { tauriInvoke } from "~/lib/tauri";
+
+type MyProfile = {
+  id: string;
+  username: string;
+  display_name: string;
+  role: string;
+  status: string;
+  must_change_pwd: boolean;
+  created_at: number;
+  rbac_enabled: boolean;
+  has_pin: boolean;
+  warehouses: { id: string; code: string; name: string }[];
+  recent_activity: { id: string; created_at: number; action: string; result: string }[];
+  recent_txns: { id: string; txn_no: string; txn_type: string; occurred_at: number; item_name: string; qty: number }[];
+};
+
+type ProfileDialogProps = {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  onLogout: () => void;
+};
+
+const ROLE_LABELS: Record<string, string> = {
+  admin: "管理员",
+  keeper: "库管",
+  viewer: "只读",
+  member: "成员",
+};
+
+// 个人信息：查看角色、仓库范围与最近活动，可自行修改显示名
+export function ProfileDialog({ open, onOpenChange, onLogout }: ProfileDialogProps) {
+  const [profile, setProfile] = useState<MyProfile | null>(null);
+  const [displayName, setDisplayName] = useState("");
+  const [saving, setSaving] = useState(false);
+
+  const fetchProfile = async () => {
+    try {
+      const result = await tauriInvoke<MyProfile>("get_my_profile", {});
+      setProfile(result);
+      setDisplayName(result.display_name);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载个人信息失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchProfile();
+  }, [open]);
+
+  const handleSave = async () => {
+    if (!displayName.trim()) {
+      toast.error("姓名不能为空");
+      return;
+    }
+    setSaving(true);
+    try {
+      await tauriInvoke("update_my_profile", { input: { display_name: displayName.trim() } });
+      toast.success("已保存");
+      await fetchProfile();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    } finally {
+      setSaving(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="max-w-lg">
+        <DialogHeader>
+          <DialogTitle>当前账号</DialogTitle>
+          <DialogDescription>查看账号信息、角色与最近活动</DialogDescription>
+        </DialogHeader>
+        {profile ? (
+          <div className="max-h-[60vh] space-y-4 overflow-auto text-sm text-slate-600">
+            <div className="space-y-2 rounded-2xl border border-slate-200 bg-white p-4">
+              <p>用户名：{profile.username}</p>
+              <p>
+                角色：{profile.rbac_enabled ? ROLE_LABELS[profile.role] ?? profile.role : "全权限（未启用角色权限）"}
+              </p>
+              <p>
+                仓库范围：
+                {profile.warehouses.length === 0 ? "不限" : profile.warehouses.map((warehouse) => `${warehouse.code} ${warehouse.name}`).join("、")}
+              </p>
+              <div className="flex flex-wrap gap-2">
+                {profile.must_change_pwd ? <Badge variant="destructive">需修改密码</Badge> : null}
+                <Badge variant="secondary">{profile.has_pin ? "已设置 PIN" : "未设置 PIN"}</Badge>
+              </div>
+            </div>
+            <div className="grid gap-2">
+              <Label htmlFor="profile-display-name">显示名</Label>
+              <div className="flex gap-2">
+                <Input id="profile-display-name" value={displayName} onChange={(event) => setDisplayName(event.target.value)} />
+                <Button variant="outline" onClick={handleSave} disabled={saving || displayName.trim() === profile.display_name}>
+                  保存
+                </Button>
+              </div>
+            </div>
+            <div className="space-y-1">
+              <Label>最近流水</Label>
+              {profile.recent_txns.length === 0 ? <p className="text-xs text-slate-400">暂无</p> : null}
+              {profile.recent_txns.map((txn) => (
+                <div key={txn.id} className="flex justify-between text-xs">
+                  <span>
+                    {txn.txn_no} {txn.txn_type} {txn.item_name} × {txn.qty}
+                  </span>
+                  <span className="text-slate-400">{new Date(txn.occurred_at * 1000).toLocaleString()}</span>
+                </div>
+              ))}
+            </div>
+            <div className="space-y-1">
+              <Label>最近操作</Label>
+              {profile.recent_activity.length === 0 ? <p className="text-xs text-slate-400">暂无</p> : null}
+              {profile.recent_activity.map((entry) => (
+                <div key={entry.id} className="flex justify-between text-xs">
+                  <span className={entry.result === "fail" ? "text-red-500" : undefined}>{entry.action}</span>
+                  <span className="text-slate-400">{new Date(entry.created_at * 1000).toLocaleString()}</span>
+                </div>
+              ))}
+            </div>
+          </div>
+        ) : null}
+        <button type="button" className="w-full rounded-lg border border-slate-200 px-4 py-2 text-sm font-medium text-slate-600 transition hover:border-slate-300 hover:text-slate-900" onClick={onLogout}>
+          退出登录
+        </button>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,76 @@
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "~/components/ui/dialog";
+import { Button } from "~/components/ui/button";
+import { tauriInvoke } from "~/lib/tauri";
+
+type UndoEntry = {
+  id: string;
+  action: string;
+  summary: string;
+  created_at: number;
+};
+
+type UndoDialogProps = {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  onUndone: () => void;
+};
+
+// 撤销最近一次主数据修改（物品、货架、仓库的编辑与启停），仅限本人 30 分钟内的修改
+export function UndoDialog({ open, onOpenChange, onUndone }: UndoDialogProps) {
+  const [entry, setEntry] = useState<UndoEntry | null>(null);
+  const [loading, setLoading] = useState(false);
+
+  useEffect(() => {
+    if (!open) return;
+    setEntry(null);
+    (async () => {
+      try {
+        setEntry(await tauriInvoke<UndoEntry | null>("get_last_change", {}));
+      } catch (err) {
+        const message = err instanceof Error ? err.message : "加载失败";
+        toast.error(message);
+      }
+    })();
+  }, [open]);
+
+  const handleUndo = async () => {
+    setLoading(true);
+    try {
+      await tauriInvoke("undo_last_change", {});
+      toast.success("已撤销");
+      onOpenChange(false);
+      onUndone();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "撤销失败";
+      toast.error(message);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  return (
+    <Dialog open={open} onOpenChange={onOpenChange}>
+      <DialogContent className="max-w-sm">
+        <DialogHeader>
+          <DialogTitle>撤销最近修改</DialogTitle>
+          <DialogDescription>仅可撤销本人 30 分钟内对物品、货架、仓库的修改</DialogDescription>
+        </DialogHeader>
+        <div className="rounded-2xl border border-slate-200 bg-white p-4 text-sm text-slate-600">
+          {entry ? (
+            <>
+              <p>{entry.summary}</p>
+              <p className="mt-2 text-xs text-slate-400">{new Date(entry.created_at * 1000).toLocaleString()}</p>
+            </>
+          ) : (
+            <p>没有可撤销的修改</p>
+          )}
+        </div>
+        <Button disabled={!entry || loading} onClick={() => void handleUndo()}>
+          撤销
+        </Button>
+      </DialogContent>
+    </Dialog>
+  );
+}
@@ -0,0 +1,118 @@
+import { useEffect, useState } from "react";
+import { toast } from "sonner";
+import { Badge } from "~/components/ui/badge";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { getSession } from "~/lib/auth";
+import { tauriInvoke } from "~/lib/tauri";
+
+type ActiveSession = {
+  id: string;
+  operator_id: string;
+  username: string;
+  display_name: string;
+  role: string;
+  source: "password" | "pin" | string;
+  created_at: number;
+  last_active_at: number;
+  expires_at?: number | null;
+};
+
+const SOURCE_LABELS: Record<string, string> = {
+  password: "密码登录",
+  pin: "PIN 切换",
+};
+
+const formatTime = (value: number) => new Date(value * 1000).toLocaleString();
+
+// 在线会话：列出未结束的登录，可远程结束遗留在共享终端上的会话
+export function SessionListDialog({ open, onOpenChange }: { open: boolean; onOpenChange: (open: boolean) => void }) {
+  const [sessions, setSessions] = useState<ActiveSession[]>([]);
+  const currentSessionId = getSession()?.session_id;
+
+  const fetchSessions = async () => {
+    try {
+      setSessions(await tauriInvoke<ActiveSession[]>("list_active_sessions", {}));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载会话失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchSessions();
+  }, [open]);
+
+  const handleTerminate = async (id: string) => {
+    try {
+      await tauriInvoke("terminate_session", { input: { id } });
+      toast.success("会话已结束");
+      await fetchSessions();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "结束会话失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <CommonDialog
+      title="在线会话"
+      description="被结束的终端会在一分钟内退出登录"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="max-h-[60vh] overflow-auto">
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>人员</TableHead>
+                <TableHead>方式</TableHead>
+                <TableHead>登录时间</TableHead>
+                <TableHead>最近活跃</TableHead>
+                <TableHead className="text-right">操作</TableHead>
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {sessions.map((session) => (
+                <TableRow key={session.id}>
+                  <TableCell>
+                    {session.display_name}
+                    <span className="ml-1 text-xs text-slate-500">{session.username}</span>
+                    {session.id === currentSessionId ? (
+                      <Badge variant="secondary" className="ml-2">
+                        本机
+                      </Badge>
+                    ) : null}
+                  </TableCell>
+                  <TableCell>{SOURCE_LABELS[session.source] ?? session.source}</TableCell>
+                  <TableCell>{formatTime(session.created_at)}</TableCell>
+                  <TableCell>{formatTime(session.last_active_at)}</TableCell>
+                  <TableCell className="text-right">
+                    {session.id === currentSessionId ? null : (
+                      <ConfirmButton
+                        label="强制下线"
+                        variant="ghost"
+                        size="sm"
+                        confirmText={`确认结束 ${session.display_name} 的会话？`}
+                        onConfirm={() => handleTerminate(session.id)}
+                      />
+                    )}
+                  </TableCell>
+                </TableRow>
+              ))}
+              {sessions.length === 0 ? (
+                <TableRow>
+                  <TableCell colSpan={5} className="text-center text-slate-500">
+                    暂无在线会话
+                  </TableCell>
+                </TableRow>
+              ) : null}
+            </TableBody>
+          </Table>
+        </div>
+      }
+    />
+  );
+}
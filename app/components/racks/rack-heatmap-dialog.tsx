@@ -0,0 +1,113 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type RackHeatmapSlot = {
+  slot_id: string;
+  code: string;
+  level_no: number;
+  slot_no: number;
+  status: string;
+  txn_count: number;
+  in_count: number;
+  out_count: number;
+  qty: number;
+  heat: number;
+};
+
+type RackHeatmap = {
+  rack: { id: string; code: string; name: string };
+  days: number;
+  since: number;
+  generated_at: number;
+  max_txn_count: number;
+  total_txn_count: number;
+  levels: { level_no: number; slots: RackHeatmapSlot[] }[];
+};
+
+// 活跃度着色：无流水为灰色，其余按相对最大流水数由浅到深
+const heatStyle = (slot: RackHeatmapSlot) => {
+  if (slot.status !== "active") return { backgroundColor: "#f1f5f9", color: "#94a3b8" };
+  if (slot.txn_count === 0) return { backgroundColor: "#f8fafc", color: "#64748b" };
+  const alpha = 0.15 + slot.heat * 0.85;
+  return { backgroundColor: `rgba(234, 88, 12, ${alpha.toFixed(2)})`, color: slot.heat > 0.5 ? "#ffffff" : "#7c2d12" };
+};
+
+// 货架活动热力图：按层/位展示统计期内各库位的流水次数与当前库存
+export function RackHeatmapDialog({
+  open,
+  onOpenChange,
+  rackId,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  rackId?: string;
+}) {
+  const [heatmap, setHeatmap] = useState<RackHeatmap | null>(null);
+  const [days, setDays] = useState("30");
+
+  const fetchHeatmap = async () => {
+    if (!rackId) return;
+    const value = Number(days);
+    if (!Number.isInteger(value) || value < 1 || value > 365) return;
+    try {
+      setHeatmap(await tauriInvoke<RackHeatmap>("get_rack_heatmap", { input: { id: rackId, days: value } }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载热力图失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchHeatmap();
+  }, [open, rackId, days]);
+
+  return (
+    <CommonDialog
+      title="活动热力图"
+      description={heatmap ? `${heatmap.rack.code} ${heatmap.rack.name}，颜色越深表示统计期内流水越多` : undefined}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="flex flex-wrap items-center gap-3">
+            <Label htmlFor="heatmap-days">统计天数</Label>
+            <Input id="heatmap-days" className="w-24" type="number" min={1} max={365} step={1} value={days} onChange={(event) => setDays(event.target.value)} />
+            {heatmap ? (
+              <Badge variant="secondary">
+                共 {heatmap.total_txn_count} 笔流水，单库位最多 {heatmap.max_txn_count} 笔
+              </Badge>
+            ) : null}
+          </div>
+          <div className="max-h-[60vh] space-y-2 overflow-auto">
+            {(heatmap?.levels ?? []).map((level) => (
+              <div key={level.level_no} className="flex items-stretch gap-2">
+                <div className="w-12 shrink-0 self-center text-xs text-slate-500">第 {level.level_no} 层</div>
+                <div className="flex flex-1 gap-2">
+                  {level.slots.map((slot) => (
+                    <div
+                      key={slot.slot_id}
+                      className="min-w-[72px] flex-1 rounded-md border border-slate-200/70 p-2 text-xs"
+                      style={heatStyle(slot)}
+                      title={`${slot.code}：流水 ${slot.txn_count} 笔（移入 ${slot.in_count}，移出 ${slot.out_count}），库存 ${slot.qty}`}
+                    >
+                      <div className="font-medium">{slot.code}</div>
+                      <div>{slot.txn_count} 笔</div>
+                      <div className="opacity-80">库存 {slot.qty}</div>
+                    </div>
+                  ))}
+                </div>
+              </div>
+            ))}
+            {heatmap && heatmap.levels.length === 0 ? <p className="text-center text-sm text-muted-foreground">暂无库位</p> : null}
+          </div>
+        </div>
+      }
+    />
+  );
+}
@@ -0,0 +1,232 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type ApprovalRequest = {
+  id: string;
+  kind: "reversal" | "count_adjust" | "scrap" | "restore";
+  summary: string;
+  reason?: string | null;
+  status: "pending" | "approved" | "rejected" | "executed";
+  requested_by_name?: string | null;
+  requested_at: number;
+  decided_by_name?: string | null;
+  decision_note?: string | null;
+};
+
+type ApprovalSettings = {
+  approval_reversal: boolean;
+  approval_restore: boolean;
+  approval_count_threshold: number;
+  approval_scrap_threshold: number;
+};
+
+const KIND_LABELS: Record<ApprovalRequest["kind"], string> = {
+  reversal: "冲正",
+  count_adjust: "盘点调整",
+  scrap: "报废",
+  restore: "恢复数据库",
+};
+
+const STATUS_LABELS: Record<ApprovalRequest["status"], string> = {
+  pending: "待审批",
+  approved: "已通过",
+  rejected: "已驳回",
+  executed: "已执行",
+};
+
+// 敏感操作审批：冲正、大额盘点调整、大额报废与数据库恢复需由另一名管理员审批，审批通过后由申请人重新执行原操作
+export function ApprovalCard({ disabled }: { disabled?: boolean }) {
+  const [policy, setPolicy] = useState<ApprovalSettings>({
+    approval_reversal: false,
+    approval_restore: false,
+    approval_count_threshold: 0,
+    approval_scrap_threshold: 0,
+  });
+  const [thresholdInput, setThresholdInput] = useState("0");
+  const [scrapThresholdInput, setScrapThresholdInput] = useState("0");
+  const [requests, setRequests] = useState<ApprovalRequest[]>([]);
+  const [pendingOnly, setPendingOnly] = useState(true);
+  const [decisionNote, setDecisionNote] = useState("");
+
+  const fetchPolicy = async () => {
+    try {
+      const result = await tauriInvoke<ApprovalSettings>("get_settings");
+      setPolicy(result);
+      setThresholdInput(String(result.approval_count_threshold ?? 0));
+      setScrapThresholdInput(String(result.approval_scrap_threshold ?? 0));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载设置失败";
+      toast.error(message);
+    }
+  };
+
+  const fetchRequests = async () => {
+    try {
+      const result = await tauriInvoke<ApprovalRequest[]>("list_approval_requests", {
+        input: { status: pendingOnly ? "pending" : null },
+      });
+      setRequests(result);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载审批申请失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    void fetchPolicy();
+  }, []);
+
+  useEffect(() => {
+    void fetchRequests();
+  }, [pendingOnly]);
+
+  const updatePolicy = async (input: Partial<ApprovalSettings>) => {
+    try {
+      await tauriInvoke("set_settings", { input });
+      toast.success("设置已更新");
+      await fetchPolicy();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveThreshold = async (key: "approval_count_threshold" | "approval_scrap_threshold", input: string) => {
+    const threshold = Number(input);
+    if (!Number.isInteger(threshold) || threshold < 0) {
+      toast.error("阈值需为非负整数");
+      return;
+    }
+    await updatePolicy({ [key]: threshold });
+  };
+
+  const handleDecide = async (id: string, approve: boolean) => {
+    try {
+      await tauriInvoke(approve ? "approve_approval_request" : "reject_approval_request", {
+        input: { id, note: decisionNote.trim() || null },
+      });
+      toast.success(approve ? "已通过" : "已驳回");
+      setDecisionNote("");
+      await fetchRequests();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "操作失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>敏感操作审批</CardTitle>
+        <CardDescription>开启后相应操作需先提交审批申请，由另一名管理员通过后申请人再执行；审批通过的申请仅能执行一次</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap gap-4 text-sm">
+          <label className="flex items-center gap-2">
+            <input
+              type="checkbox"
+              checked={policy.approval_reversal}
+              disabled={disabled}
+              onChange={(event) => void updatePolicy({ approval_reversal: event.target.checked })}
+            />
+            冲正需审批
+          </label>
+          <label className="flex items-center gap-2">
+            <input
+              type="checkbox"
+              checked={policy.approval_restore}
+              disabled={disabled}
+              onChange={(event) => void updatePolicy({ approval_restore: event.target.checked })}
+            />
+            恢复数据库需审批
+          </label>
+        </div>
+        <div className="grid gap-2">
+          <Label>盘点调整审批阈值（差异数量超过该值需审批，0 表示不需审批）</Label>
+          <div className="flex gap-2">
+            <Input type="number" min={0} value={thresholdInput} onChange={(event) => setThresholdInput(event.target.value)} />
+            <Button variant="outline" onClick={() => void saveThreshold("approval_count_threshold", thresholdInput)} disabled={disabled}>
+              保存
+            </Button>
+          </div>
+        </div>
+        <div className="grid gap-2">
+          <Label>报废审批阈值（报废数量超过该值需审批，0 表示不需审批）</Label>
+          <div className="flex gap-2">
+            <Input type="number" min={0} value={scrapThresholdInput} onChange={(event) => setScrapThresholdInput(event.target.value)} />
+            <Button variant="outline" onClick={() => void saveThreshold("approval_scrap_threshold", scrapThresholdInput)} disabled={disabled}>
+              保存
+            </Button>
+          </div>
+        </div>
+        <div className="flex items-center justify-between">
+          <Label>审批申请</Label>
+          <label className="flex items-center gap-2 text-sm">
+            <input type="checkbox" checked={pendingOnly} onChange={(event) => setPendingOnly(event.target.checked)} />
+            仅看待审批
+          </label>
+        </div>
+        <div className="grid gap-2">
+          <Label>审批意见（可选，通过或驳回时一并记录）</Label>
+          <Input value={decisionNote} onChange={(event) => setDecisionNote(event.target.value)} />
+        </div>
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>类型</TableHead>
+              <TableHead>内容</TableHead>
+              <TableHead>申请人</TableHead>
+              <TableHead>申请时间</TableHead>
+              <TableHead>状态</TableHead>
+              <TableHead className="text-right">操作</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {requests.map((request) => (
+              <TableRow key={request.id}>
+                <TableCell>{KIND_LABELS[request.kind] ?? request.kind}</TableCell>
+                <TableCell>
+                  <div>{request.summary}</div>
+                  {request.reason ? <div className="text-xs text-slate-500">原因：{request.reason}</div> : null}
+                  {request.decision_note ? <div className="text-xs text-slate-500">意见：{request.decision_note}</div> : null}
+                </TableCell>
+                <TableCell>{request.requested_by_name ?? "-"}</TableCell>
+                <TableCell>{new Date(request.requested_at * 1000).toLocaleString()}</TableCell>
+                <TableCell>
+                  <Badge variant={request.status === "pending" ? "outline" : "secondary"}>{STATUS_LABELS[request.status]}</Badge>
+                  {request.decided_by_name ? <div className="text-xs text-slate-500">{request.decided_by_name}</div> : null}
+                </TableCell>
+                <TableCell className="text-right">
+                  {request.status === "pending" ? (
+                    <div className="flex justify-end gap-2">
+                      <Button size="sm" onClick={() => void handleDecide(request.id, true)} disabled={disabled}>
+                        通过
+                      </Button>
+                      <Button size="sm" variant="outline" onClick={() => void handleDecide(request.id, false)} disabled={disabled}>
+                        驳回
+                      </Button>
+                    </div>
+                  ) : null}
+                </TableCell>
+              </TableRow>
+            ))}
+            {requests.length === 0 ? (
+              <TableRow>
+                <TableCell colSpan={6} className="text-center text-slate-500">
+                  暂无审批申请
+                </TableCell>
+              </TableRow>
+            ) : null}
+          </TableBody>
+        </Table>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,127 @@
+import { useEffect, useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "~/components/ui/button";
+import { Badge } from "~/components/ui/badge";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type BackupEncryptionConfig = {
+  enabled: boolean;
+  passphrase_set: boolean;
+};
+
+type BackupEncryptResult = {
+  encrypted: number;
+  skipped: string[];
+};
+
+// 备份加密：开启后本地与远程备份均以口令加密，口令加密保存在本机；也可用口令恢复其他设备的加密备份
+export function BackupEncryptionCard({ disabled }: { disabled?: boolean }) {
+  const [config, setConfig] = useState<BackupEncryptionConfig>({ enabled: false, passphrase_set: false });
+  const [passphrase, setPassphrase] = useState("");
+  const [restorePassphrase, setRestorePassphrase] = useState("");
+
+  const fetchConfig = async () => {
+    try {
+      setConfig(await tauriInvoke<BackupEncryptionConfig>("get_backup_encryption", {}));
+    } catch {
+      // 非管理员无权查看备份加密配置
+    }
+  };
+
+  useEffect(() => {
+    void fetchConfig();
+  }, []);
+
+  const saveConfig = async (enabled: boolean) => {
+    try {
+      await tauriInvoke("set_backup_encryption", { input: { enabled, passphrase: passphrase || null } });
+      setPassphrase("");
+      toast.success(enabled ? "备份加密已开启" : "备份加密已关闭");
+      await fetchConfig();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleEncryptExisting = async () => {
+    try {
+      const result = await tauriInvoke<BackupEncryptResult>("encrypt_existing_backups", {});
+      if (result.skipped.length > 0) {
+        toast.warning(`已加密 ${result.encrypted} 个备份，跳过 ${result.skipped.length} 个无效文件`);
+      } else {
+        toast.success(`已加密 ${result.encrypted} 个备份`);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加密失败";
+      toast.error(message);
+    }
+  };
+
+  const handleRestoreEncrypted = async () => {
+    const selected = await open({ multiple: false });
+    if (!selected || Array.isArray(selected)) return;
+    try {
+      await tauriInvoke("restore_db", { input: { file_path: selected, passphrase: restorePassphrase || null } });
+      setRestorePassphrase("");
+      toast.success("恢复完成");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "恢复失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>
+          备份加密
+          <Badge variant={config.enabled ? "secondary" : "outline"} className="ml-2">
+            {config.enabled ? "已开启" : "未开启"}
+          </Badge>
+        </CardTitle>
+        <CardDescription>开启后本地备份（含定时备份）与上传的远程备份均以口令加密；口令加密保存在本机，请另行妥善保管，遗失后无法恢复加密备份。数据库文件本身不加密</CardDescription>
+      </CardHeader>
+      <CardContent className="grid gap-4">
+        <div className="grid gap-2">
+          <Label>{config.passphrase_set ? "修改备份口令（留空保留原口令）" : "备份口令（至少 8 位）"}</Label>
+          <div className="flex gap-2">
+            <Input type="password" autoComplete="new-password" value={passphrase} onChange={(event) => setPassphrase(event.target.value)} />
+            <Button onClick={() => void saveConfig(true)} disabled={disabled}>
+              {config.enabled ? "保存" : "开启加密"}
+            </Button>
+            {config.enabled ? (
+              <Button variant="outline" onClick={() => void saveConfig(false)} disabled={disabled}>
+                关闭加密
+              </Button>
+            ) : null}
+          </div>
+        </div>
+        {config.enabled ? (
+          <div>
+            <ConfirmButton
+              label="加密已有备份"
+              confirmText="将备份目录中的明文备份加密并删除明文文件，升级前自动备份保持不变。确认继续？"
+              onConfirm={handleEncryptExisting}
+              disabled={disabled}
+            />
+          </div>
+        ) : null}
+        <div className="grid gap-2">
+          <Label>恢复其他设备的加密备份</Label>
+          <div className="flex gap-2">
+            <Input type="password" placeholder="备份口令" value={restorePassphrase} onChange={(event) => setRestorePassphrase(event.target.value)} />
+            <Button variant="destructive" onClick={() => void handleRestoreEncrypted()} disabled={disabled || !restorePassphrase}>
+              选择备份并恢复
+            </Button>
+          </div>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
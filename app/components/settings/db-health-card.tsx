@@ -0,0 +1,186 @@
+import { useState } from "react";
+import { Button } from "~/components/ui/button";
+import { Badge } from "~/components/ui/badge";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type DbHealthIssue = {
+  kind: string;
+  title: string;
+  count: number;
+  samples: string[];
+  repair: string | null;
+  hint: string;
+};
+
+type DbHealthReport = {
+  ok: boolean;
+  checked_at: number;
+  integrity: string[];
+  issues: DbHealthIssue[];
+  stock_diffs: unknown[];
+};
+
+type DbRepairResult = {
+  repair: string;
+  affected: number;
+};
+
+type QueryPlanCheck = {
+  name: string;
+  title: string;
+  ok: boolean;
+  plan: string[];
+  missing_indexes: string[];
+  full_scans: string[];
+};
+
+type QueryPlanReport = {
+  ok: boolean;
+  checked_at: number;
+  items: QueryPlanCheck[];
+};
+
+const REPAIR_LABELS: Record<string, string> = {
+  delete_orphan_stock: "删除无主库存",
+  reindex: "重建索引",
+  recalculate_stock: "按流水重算库存",
+};
+
+// 数据库健康检查：完整性、引用关系与库存/流水一致性，已知问题提供一键修复
+export function DbHealthCard({ disabled }: { disabled?: boolean }) {
+  const [report, setReport] = useState<DbHealthReport | null>(null);
+  const [checking, setChecking] = useState(false);
+  const [collecting, setCollecting] = useState(false);
+  const [planReport, setPlanReport] = useState<QueryPlanReport | null>(null);
+
+  const handleVerify = async () => {
+    setChecking(true);
+    try {
+      const result = await tauriInvoke<DbHealthReport>("verify_database", {});
+      setReport(result);
+      if (result.ok) {
+        toast.success("检查通过，未发现问题");
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "检查失败";
+      toast.error(message);
+    } finally {
+      setChecking(false);
+    }
+  };
+
+  const handleRepair = async (repair: string) => {
+    try {
+      const result = await tauriInvoke<DbRepairResult>("repair_database", { input: { repair } });
+      toast.success(repair === "reindex" ? "索引已重建" : `已修复 ${result.affected} 条记录`);
+      await handleVerify();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "修复失败";
+      toast.error(message);
+    }
+  };
+
+  // 查询索引自检：列表变慢时确认常用筛选是否命中索引，未命中可先尝试重建索引
+  const handleCheckPlans = async () => {
+    try {
+      const result = await tauriInvoke<QueryPlanReport>("check_query_plans", {});
+      setPlanReport(result);
+      if (result.ok) {
+        toast.success("常用查询均已命中索引");
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "自检失败";
+      toast.error(message);
+    }
+  };
+
+  // 诊断包：版本、设置、最近审计与失败记录及数据库概要，凭据与个人信息已脱敏，可直接发给技术支持
+  const handleCollect = async () => {
+    setCollecting(true);
+    try {
+      const result = await tauriInvoke<{ file_path: string }>("collect_diagnostics", {});
+      toast.success(`诊断包已生成：${result.file_path}`);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "生成诊断包失败";
+      toast.error(message);
+    } finally {
+      setCollecting(false);
+    }
+  };
+
+  // 同一修复项可能对应多个问题，只展示一次修复按钮
+  const repairs = Array.from(new Set((report?.issues ?? []).map((issue) => issue.repair).filter((repair): repair is string => !!repair)));
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>数据库健康检查</CardTitle>
+        <CardDescription>检查数据库文件完整性、流水与库存引用的物品/库位是否存在，以及库存是否与流水推算一致；修复前建议先备份</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap gap-2">
+          <Button variant="outline" onClick={() => void handleVerify()} disabled={disabled || checking}>
+            {checking ? "检查中..." : "开始检查"}
+          </Button>
+          <Button variant="outline" onClick={() => void handleCheckPlans()} disabled={disabled}>
+            查询索引自检
+          </Button>
+          <Button variant="outline" onClick={() => void handleCollect()} disabled={disabled || collecting}>
+            {collecting ? "生成中..." : "导出诊断包"}
+          </Button>
+          {repairs.map((repair) => (
+            <ConfirmButton
+              key={repair}
+              label={REPAIR_LABELS[repair] ?? repair}
+              confirmText={`确认执行“${REPAIR_LABELS[repair] ?? repair}”？该操作会修改数据库，建议先备份`}
+              onConfirm={() => handleRepair(repair)}
+              disabled={disabled || checking}
+            />
+          ))}
+        </div>
+        {report ? (
+          <div className="space-y-3 text-sm">
+            <p className="text-slate-500">检查时间：{new Date(report.checked_at * 1000).toLocaleString()}</p>
+            {report.ok ? <Badge variant="secondary">未发现问题</Badge> : null}
+            {report.issues.map((issue) => (
+              <div key={issue.kind} className="space-y-1 rounded-xl border border-slate-200/70 p-3">
+                <p className="font-medium">
+                  {issue.title}
+                  <Badge variant="destructive" className="ml-2">
+                    {issue.count}
+                  </Badge>
+                </p>
+                <p className="text-slate-500">{issue.hint}</p>
+                {issue.samples.map((sample) => (
+                  <p key={sample} className="font-mono text-xs text-slate-600">
+                    {sample}
+                  </p>
+                ))}
+              </div>
+            ))}
+          </div>
+        ) : null}
+        {planReport && !planReport.ok ? (
+          <div className="space-y-3 text-sm">
+            {planReport.items
+              .filter((item) => !item.ok)
+              .map((item) => (
+                <div key={item.name} className="space-y-1 rounded-xl border border-slate-200/70 p-3">
+                  <p className="font-medium">{item.title}</p>
+                  {item.missing_indexes.length > 0 ? <p className="text-slate-500">未使用索引：{item.missing_indexes.join("、")}</p> : null}
+                  {item.plan.map((step, index) => (
+                    <p key={index} className="font-mono text-xs text-slate-600">
+                      {step}
+                    </p>
+                  ))}
+                </div>
+              ))}
+          </div>
+        ) : null}
+      </CardContent>
+    </Card>
+  );
+}
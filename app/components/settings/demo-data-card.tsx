@@ -0,0 +1,54 @@
+import { useState } from "react";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type DemoDataResult = {
+  warehouses: number;
+  racks: number;
+  slots: number;
+  items: number;
+  operators: number;
+  txns: number;
+};
+
+// 演示数据：为试用环境生成仓库、货架、物品、人员与近 90 天流水
+export function DemoDataCard({ disabled }: { disabled?: boolean }) {
+  const [txnCount, setTxnCount] = useState("3000");
+  const [force, setForce] = useState(false);
+
+  const handleGenerate = async () => {
+    try {
+      const result = await tauriInvoke<DemoDataResult>("generate_demo_data", {
+        input: { txn_count: Number(txnCount) || null, force },
+      });
+      toast.success(`已生成 ${result.warehouses} 个仓库、${result.racks} 个货架、${result.items} 个物品、${result.operators} 名人员与 ${result.txns} 条流水`);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "生成失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>演示数据</CardTitle>
+        <CardDescription>用于试用：生成演示仓库、货架、物品、人员（初始密码 123456）与随机流水。数据库已有业务数据时需勾选强制生成</CardDescription>
+      </CardHeader>
+      <CardContent className="flex flex-wrap items-end gap-3">
+        <div className="grid gap-2">
+          <Label>流水条数（1-20000）</Label>
+          <Input type="number" min={1} max={20000} value={txnCount} onChange={(event) => setTxnCount(event.target.value)} />
+        </div>
+        <label className="flex items-center gap-2 text-sm">
+          <input type="checkbox" checked={force} onChange={(event) => setForce(event.target.checked)} />
+          强制生成
+        </label>
+        <ConfirmButton label="生成演示数据" confirmText="将向当前数据库写入演示数据，且无法一键清除，建议仅在试用环境使用。确认生成？" onConfirm={handleGenerate} disabled={disabled} />
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,76 @@
+import { useState } from "react";
+import { open } from "@tauri-apps/plugin-dialog";
+import { Button } from "~/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type ImportCount = { created: number; skipped: number };
+
+type MasterBundleImportResult = {
+  warehouses: ImportCount;
+  racks: ImportCount;
+  slots: ImportCount;
+  item_attribute_defs: ImportCount;
+  items: ImportCount;
+  reason_codes: ImportCount;
+  operators: ImportCount;
+};
+
+// 主数据包：导出本站仓库结构、物品、原因码与人员，在新站点导入以复用配置；不含库存与流水
+export function MasterBundleCard({ disabled }: { disabled?: boolean }) {
+  const [initialPassword, setInitialPassword] = useState("");
+
+  const handleExport = async () => {
+    try {
+      const result = await tauriInvoke<{ file_path: string }>("export_master_bundle", {});
+      toast.success(`主数据包已导出：${result.file_path}`);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导出失败";
+      toast.error(message);
+    }
+  };
+
+  const handleImport = async () => {
+    const selected = await open({ multiple: false, filters: [{ name: "JSON", extensions: ["json"] }] });
+    if (!selected || Array.isArray(selected)) return;
+    try {
+      const result = await tauriInvoke<MasterBundleImportResult>("import_master_bundle", {
+        input: { file_path: selected, initial_password: initialPassword || null },
+      });
+      setInitialPassword("");
+      const skipped = Object.values(result).reduce((sum, count) => sum + count.skipped, 0);
+      toast.success(
+        `导入完成：新建仓库 ${result.warehouses.created} 个、货架 ${result.racks.created} 个、库位 ${result.slots.created} 个、物品 ${result.items.created} 个、人员 ${result.operators.created} 名，已存在跳过 ${skipped} 条`
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导入失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>主数据包</CardTitle>
+        <CardDescription>
+          用于新站点初始化：导出仓库、货架、库位、物品（含别名与属性）、原因码与人员，不含库存、流水与密码。导入时已存在的编码/用户名跳过，新建的可登录账号使用下方初始密码并在首次登录时修改
+        </CardDescription>
+      </CardHeader>
+      <CardContent className="flex flex-wrap items-end gap-3">
+        <Button variant="outline" onClick={() => void handleExport()} disabled={disabled}>
+          导出主数据包
+        </Button>
+        <div className="grid gap-2">
+          <Label>新账号初始密码</Label>
+          <Input type="password" value={initialPassword} onChange={(event) => setInitialPassword(event.target.value)} />
+        </div>
+        <Button onClick={() => void handleImport()} disabled={disabled}>
+          导入主数据包
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,99 @@
+import { useState } from "react";
+import { Button } from "~/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type CommandStats = {
+  action: string;
+  count: number;
+  error_count: number;
+  total_ms: number;
+  avg_ms: number;
+  max_ms: number;
+  p50_ms: number;
+  p95_ms: number;
+};
+
+type PerformanceStats = {
+  since?: number | null;
+  items: CommandStats[];
+};
+
+// 命令耗时统计：按审计动作汇总本次运行以来的次数与耗时，用于现场定位慢操作
+export function PerformanceCard({ disabled }: { disabled?: boolean }) {
+  const [stats, setStats] = useState<PerformanceStats | null>(null);
+
+  const fetchStats = async (reset = false) => {
+    try {
+      const result = await tauriInvoke<PerformanceStats>("get_performance_stats", { query: { reset } });
+      setStats(result);
+      if (reset) {
+        toast.success("统计已清零");
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载耗时统计失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>命令耗时统计</CardTitle>
+        <CardDescription>统计保存在内存中，重启后清零；按累计耗时排序，分位数按耗时分档估算</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex flex-wrap gap-2">
+          <Button variant="outline" onClick={() => void fetchStats()} disabled={disabled}>
+            刷新
+          </Button>
+          <Button variant="ghost" onClick={() => void fetchStats(true)} disabled={disabled || !stats}>
+            清零
+          </Button>
+        </div>
+        {stats ? (
+          <div className="space-y-2 text-sm">
+            {stats.since ? <p className="text-slate-500">统计开始：{new Date(stats.since * 1000).toLocaleString()}</p> : null}
+            <div className="max-h-[360px] overflow-auto">
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>动作</TableHead>
+                    <TableHead className="text-right">次数</TableHead>
+                    <TableHead className="text-right">失败</TableHead>
+                    <TableHead className="text-right">平均(ms)</TableHead>
+                    <TableHead className="text-right">P95(ms)</TableHead>
+                    <TableHead className="text-right">最大(ms)</TableHead>
+                    <TableHead className="text-right">累计(ms)</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {stats.items.map((item) => (
+                    <TableRow key={item.action}>
+                      <TableCell className="font-mono text-xs">{item.action}</TableCell>
+                      <TableCell className="text-right">{item.count}</TableCell>
+                      <TableCell className="text-right">{item.error_count}</TableCell>
+                      <TableCell className="text-right">{item.avg_ms}</TableCell>
+                      <TableCell className="text-right">{item.p95_ms}</TableCell>
+                      <TableCell className="text-right">{item.max_ms}</TableCell>
+                      <TableCell className="text-right">{item.total_ms}</TableCell>
+                    </TableRow>
+                  ))}
+                  {stats.items.length === 0 ? (
+                    <TableRow>
+                      <TableCell colSpan={7} className="text-center text-slate-500">
+                        暂无记录
+                      </TableCell>
+                    </TableRow>
+                  ) : null}
+                </TableBody>
+              </Table>
+            </div>
+          </div>
+        ) : null}
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,73 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type ReadOnlySettings = {
+  read_only_mode: boolean;
+  read_only_reason?: string | null;
+};
+
+// 只读维护模式：审计盘查或高风险恢复前冻结数据修改，查询、导出与备份不受影响
+export function ReadOnlyCard({ disabled }: { disabled?: boolean }) {
+  const [state, setState] = useState<ReadOnlySettings>({ read_only_mode: false });
+  const [reason, setReason] = useState("");
+
+  const fetchState = async () => {
+    try {
+      const result = await tauriInvoke<ReadOnlySettings>("get_settings");
+      setState(result);
+      setReason(result.read_only_reason ?? "");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载设置失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    void fetchState();
+  }, []);
+
+  const toggle = async (enabled: boolean) => {
+    try {
+      await tauriInvoke("set_read_only_mode", { input: { enabled, reason: enabled ? reason.trim() || null : null } });
+      toast.success(enabled ? "已开启只读维护模式" : "已关闭只读维护模式");
+      await fetchState();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>只读维护模式</CardTitle>
+        <CardDescription>开启后所有人员的写操作都会被拒绝，查询、导出与备份仍可使用；设置与认证相关操作不受影响</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="flex items-center gap-2 text-sm">
+          当前状态：
+          {state.read_only_mode ? <Badge variant="destructive">只读</Badge> : <Badge variant="secondary">正常</Badge>}
+        </div>
+        <div className="grid gap-2">
+          <Label htmlFor="read-only-reason">原因（可选，会显示在被拒绝的提示中）</Label>
+          <Input
+            id="read-only-reason"
+            value={reason}
+            maxLength={100}
+            disabled={state.read_only_mode}
+            onChange={(event) => setReason(event.target.value)}
+          />
+        </div>
+        <Button variant={state.read_only_mode ? "outline" : "destructive"} onClick={() => void toggle(!state.read_only_mode)} disabled={disabled}>
+          {state.read_only_mode ? "关闭只读维护模式" : "开启只读维护模式"}
+        </Button>
+      </CardContent>
+    </Card>
+  );
+}
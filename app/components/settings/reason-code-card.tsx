@@ -0,0 +1,250 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import type { ReasonCodeRow } from "~/components/common/pickers/reason-picker";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type ReasonSettings = {
+  reason_required_types: string[];
+};
+
+type ReasonDraft = {
+  id: string;
+  code: string;
+  name: string;
+  txn_types: string[];
+  sort_no: string;
+  active: boolean;
+};
+
+const TXN_TYPE_LABELS: Record<string, string> = {
+  ADJUST: "调整",
+  REVERSAL: "冲正",
+  SCRAP: "报废",
+};
+
+const TXN_TYPES = Object.keys(TXN_TYPE_LABELS);
+
+const emptyDraft = (): ReasonDraft => ({ id: "", code: "", name: "", txn_types: [...TXN_TYPES], sort_no: "0", active: true });
+
+const toggleType = (types: string[], txnType: string, checked: boolean) =>
+  checked ? TXN_TYPES.filter((value) => value === txnType || types.includes(value)) : types.filter((value) => value !== txnType);
+
+// 原因字典：调整、冲正、报废流水的原因统一维护；已被流水引用的原因只能停用
+export function ReasonCodeCard({ disabled }: { disabled?: boolean }) {
+  const [reasons, setReasons] = useState<ReasonCodeRow[]>([]);
+  const [requiredTypes, setRequiredTypes] = useState<string[]>([]);
+  const [draft, setDraft] = useState<ReasonDraft>(emptyDraft());
+
+  const fetchReasons = async () => {
+    try {
+      const [list, settings] = await Promise.all([
+        tauriInvoke<ReasonCodeRow[]>("list_reason_codes", { input: { include_disabled: true } }),
+        tauriInvoke<ReasonSettings>("get_settings"),
+      ]);
+      setReasons(list);
+      setRequiredTypes(settings.reason_required_types ?? []);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载原因失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    void fetchReasons();
+  }, []);
+
+  const saveRequiredTypes = async (next: string[]) => {
+    try {
+      await tauriInvoke("set_settings", { input: { reason_required_types: next } });
+      toast.success("设置已更新");
+      setRequiredTypes(next);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const handleSave = async () => {
+    if (!draft.code.trim() || !draft.name.trim()) {
+      toast.error("请填写原因编码与名称");
+      return;
+    }
+    if (draft.txn_types.length === 0) {
+      toast.error("至少选择一种适用的流水类型");
+      return;
+    }
+    const sortNo = Number(draft.sort_no || 0);
+    if (!Number.isInteger(sortNo)) {
+      toast.error("排序号需为整数");
+      return;
+    }
+    const input = {
+      code: draft.code.trim(),
+      name: draft.name.trim(),
+      txn_types: draft.txn_types,
+      sort_no: sortNo,
+      status: draft.active ? "active" : "disabled",
+    };
+    try {
+      if (draft.id) {
+        await tauriInvoke("update_reason_code", { input: { id: draft.id, ...input } });
+      } else {
+        await tauriInvoke("create_reason_code", { input });
+      }
+      toast.success("原因已保存");
+      setDraft(emptyDraft());
+      await fetchReasons();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleDelete = async (id: string) => {
+    try {
+      await tauriInvoke("delete_reason_code", { input: { id } });
+      toast.success("原因已删除");
+      if (draft.id === id) setDraft(emptyDraft());
+      await fetchReasons();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>原因字典</CardTitle>
+        <CardDescription>盘点调整、冲正与报废时从字典选择原因，流水查询可按原因筛选并统计；大额报废的审批阈值见“敏感操作审批”</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="grid gap-2">
+          <Label>须填写原因（盘点调整仅在账实有差异时要求）</Label>
+          <div className="flex flex-wrap gap-4 text-sm">
+            {TXN_TYPES.map((txnType) => (
+              <label key={txnType} className="flex items-center gap-2">
+                <input
+                  type="checkbox"
+                  checked={requiredTypes.includes(txnType)}
+                  disabled={disabled}
+                  onChange={(event) => void saveRequiredTypes(toggleType(requiredTypes, txnType, event.target.checked))}
+                />
+                {TXN_TYPE_LABELS[txnType]}
+              </label>
+            ))}
+          </div>
+        </div>
+        <div className="grid gap-3 rounded-lg border border-slate-200/70 p-3 md:grid-cols-3">
+          <div className="grid gap-2">
+            <Label>编码</Label>
+            <Input value={draft.code} onChange={(event) => setDraft({ ...draft, code: event.target.value })} placeholder="如 DAMAGED" />
+          </div>
+          <div className="grid gap-2">
+            <Label>名称</Label>
+            <Input value={draft.name} onChange={(event) => setDraft({ ...draft, name: event.target.value })} placeholder="如 破损" />
+          </div>
+          <div className="grid gap-2">
+            <Label>排序号</Label>
+            <Input type="number" step={1} value={draft.sort_no} onChange={(event) => setDraft({ ...draft, sort_no: event.target.value })} />
+          </div>
+          <div className="flex flex-wrap items-center gap-4 text-sm md:col-span-3">
+            <span className="text-slate-500">适用于</span>
+            {TXN_TYPES.map((txnType) => (
+              <label key={txnType} className="flex items-center gap-2">
+                <input
+                  type="checkbox"
+                  checked={draft.txn_types.includes(txnType)}
+                  onChange={(event) => setDraft({ ...draft, txn_types: toggleType(draft.txn_types, txnType, event.target.checked) })}
+                />
+                {TXN_TYPE_LABELS[txnType]}
+              </label>
+            ))}
+            <label className="flex items-center gap-2">
+              <input type="checkbox" checked={draft.active} onChange={(event) => setDraft({ ...draft, active: event.target.checked })} />
+              启用
+            </label>
+          </div>
+          <div className="flex gap-2 md:col-span-3">
+            <Button onClick={() => void handleSave()} disabled={disabled}>
+              {draft.id ? "保存修改" : "新增原因"}
+            </Button>
+            {draft.id ? (
+              <Button variant="outline" onClick={() => setDraft(emptyDraft())}>
+                取消编辑
+              </Button>
+            ) : null}
+          </div>
+        </div>
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>编码</TableHead>
+              <TableHead>名称</TableHead>
+              <TableHead>适用于</TableHead>
+              <TableHead className="text-right">排序</TableHead>
+              <TableHead>状态</TableHead>
+              <TableHead className="text-right">操作</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {reasons.map((reason) => (
+              <TableRow key={reason.id}>
+                <TableCell className="font-medium">{reason.code}</TableCell>
+                <TableCell>{reason.name}</TableCell>
+                <TableCell>{reason.txn_types.map((txnType) => TXN_TYPE_LABELS[txnType] ?? txnType).join("、")}</TableCell>
+                <TableCell className="text-right">{reason.sort_no}</TableCell>
+                <TableCell>
+                  <Badge variant={reason.status === "active" ? "secondary" : "outline"}>{reason.status === "active" ? "启用" : "停用"}</Badge>
+                </TableCell>
+                <TableCell className="text-right">
+                  <div className="flex justify-end gap-2">
+                    <Button
+                      size="sm"
+                      variant="outline"
+                      disabled={disabled}
+                      onClick={() =>
+                        setDraft({
+                          id: reason.id,
+                          code: reason.code,
+                          name: reason.name,
+                          txn_types: reason.txn_types,
+                          sort_no: String(reason.sort_no),
+                          active: reason.status === "active",
+                        })
+                      }
+                    >
+                      编辑
+                    </Button>
+                    <ConfirmButton
+                      size="sm"
+                      variant="destructive"
+                      label="删除"
+                      confirmText="确认删除该原因？"
+                      disabled={disabled}
+                      onConfirm={() => handleDelete(reason.id)}
+                    />
+                  </div>
+                </TableCell>
+              </TableRow>
+            ))}
+            {reasons.length === 0 ? (
+              <TableRow>
+                <TableCell colSpan={6} className="text-center text-slate-500">
+                  暂无原因
+                </TableCell>
+              </TableRow>
+            ) : null}
+          </TableBody>
+        </Table>
+      </CardContent>
+    </Card>
+  );
+}
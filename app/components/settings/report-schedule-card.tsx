@@ -0,0 +1,415 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { isMobile, revealInFolder, tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type ReportSchedule = {
+  id: string;
+  name: string;
+  report_kind: string;
+  run_time: string;
+  email_to?: string | null;
+  status: string;
+  last_run_date?: string | null;
+};
+
+type ReportScheduleRun = {
+  id: string;
+  schedule_id?: string | null;
+  schedule_name: string;
+  report_kind: string;
+  trigger_type: string;
+  status: string;
+  file_path?: string | null;
+  email_status: string;
+  error?: string | null;
+  started_at: number;
+};
+
+type SmtpConfig = {
+  host: string;
+  port: number;
+  tls: string;
+  from: string;
+  username: string;
+  password_set: boolean;
+};
+
+type ScheduleDraft = {
+  id: string;
+  name: string;
+  report_kind: string;
+  run_time: string;
+  email_to: string;
+  active: boolean;
+};
+
+const REPORT_KIND_LABELS: Record<string, string> = {
+  txn_journal: "昨日流水日报",
+  low_stock: "低库存报表",
+};
+
+const EMAIL_STATUS_LABELS: Record<string, string> = {
+  none: "未发送",
+  queued: "待发送",
+  sent: "已发送",
+  failed: "发送失败",
+};
+
+const emptyDraft = (): ScheduleDraft => ({ id: "", name: "", report_kind: "txn_journal", run_time: "08:00", email_to: "", active: true });
+
+// 定时报表：每天到点生成报表到导出目录，可通过邮件服务器发送给收件人；失败时写入通知
+export function ReportScheduleCard({ disabled }: { disabled?: boolean }) {
+  const [schedules, setSchedules] = useState<ReportSchedule[]>([]);
+  const [runs, setRuns] = useState<ReportScheduleRun[]>([]);
+  const [draft, setDraft] = useState<ScheduleDraft>(emptyDraft());
+  const [smtp, setSmtp] = useState<SmtpConfig>({ host: "", port: 25, tls: "none", from: "", username: "", password_set: false });
+  const [smtpPassword, setSmtpPassword] = useState("");
+  const [testTo, setTestTo] = useState("");
+  const [running, setRunning] = useState<string | null>(null);
+
+  const fetchAll = async () => {
+    try {
+      const [list, history, config] = await Promise.all([
+        tauriInvoke<ReportSchedule[]>("list_report_schedules", {}),
+        tauriInvoke<ReportScheduleRun[]>("list_report_schedule_runs", { input: {} }),
+        tauriInvoke<SmtpConfig>("get_smtp_config", {}),
+      ]);
+      setSchedules(list);
+      setRuns(history);
+      setSmtp(config);
+    } catch {
+      // 非管理员无权查看定时报表
+    }
+  };
+
+  useEffect(() => {
+    void fetchAll();
+  }, []);
+
+  const handleSave = async () => {
+    if (!draft.name.trim()) {
+      toast.error("请填写计划名称");
+      return;
+    }
+    const input = {
+      name: draft.name.trim(),
+      report_kind: draft.report_kind,
+      run_time: draft.run_time,
+      email_to: draft.email_to.trim() || null,
+      status: draft.active ? "active" : "disabled",
+    };
+    try {
+      if (draft.id) {
+        await tauriInvoke("update_report_schedule", { input: { id: draft.id, ...input } });
+      } else {
+        await tauriInvoke("create_report_schedule", { input });
+      }
+      toast.success("定时报表已保存");
+      setDraft(emptyDraft());
+      await fetchAll();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleDelete = async (id: string) => {
+    try {
+      await tauriInvoke("delete_report_schedule", { input: { id } });
+      toast.success("定时报表已删除");
+      if (draft.id === id) setDraft(emptyDraft());
+      await fetchAll();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败";
+      toast.error(message);
+    }
+  };
+
+  const handleRun = async (id: string) => {
+    setRunning(id);
+    try {
+      const run = await tauriInvoke<ReportScheduleRun>("run_report_schedule", { input: { id } });
+      if (run.status === "success") {
+        toast.success(run.email_status === "queued" ? "报表已生成，邮件已加入发送队列" : "报表已生成");
+      } else {
+        toast.error(run.error ?? "执行失败");
+      }
+      await fetchAll();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "执行失败";
+      toast.error(message);
+    } finally {
+      setRunning(null);
+    }
+  };
+
+  const handleSaveSmtp = async () => {
+    try {
+      await tauriInvoke("set_smtp_config", {
+        input: {
+          host: smtp.host,
+          port: Number(smtp.port) || 25,
+          tls: smtp.tls,
+          from: smtp.from,
+          username: smtp.username,
+          password: smtpPassword || null,
+        },
+      });
+      setSmtpPassword("");
+      toast.success("邮件服务器配置已保存");
+      await fetchAll();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleTestEmail = async () => {
+    if (!testTo.trim()) {
+      toast.error("请填写测试收件人");
+      return;
+    }
+    try {
+      await tauriInvoke("send_test_email", { input: { to: testTo.trim() } });
+      toast.success("测试邮件已发送");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "发送失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>定时报表</CardTitle>
+        <CardDescription>每天到点生成报表到导出目录（最多延后 10 分钟），填写收件人时以附件发送；执行失败会写入通知</CardDescription>
+      </CardHeader>
+      <CardContent className="space-y-4">
+        <div className="grid gap-3 rounded-lg border border-slate-200/70 p-3 md:grid-cols-4">
+          <div className="grid gap-2">
+            <Label>名称</Label>
+            <Input value={draft.name} onChange={(event) => setDraft({ ...draft, name: event.target.value })} placeholder="如 每日流水" />
+          </div>
+          <div className="grid gap-2">
+            <Label>报表</Label>
+            <Select value={draft.report_kind} onValueChange={(report_kind) => setDraft({ ...draft, report_kind })}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {Object.entries(REPORT_KIND_LABELS).map(([value, label]) => (
+                  <SelectItem key={value} value={value}>
+                    {label}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="grid gap-2">
+            <Label>执行时间</Label>
+            <Input type="time" value={draft.run_time} onChange={(event) => setDraft({ ...draft, run_time: event.target.value })} />
+          </div>
+          <div className="grid gap-2">
+            <Label>收件人（可选）</Label>
+            <Input value={draft.email_to} onChange={(event) => setDraft({ ...draft, email_to: event.target.value })} placeholder="多个用逗号分隔" />
+          </div>
+          <div className="flex flex-wrap items-center gap-4 text-sm md:col-span-4">
+            <label className="flex items-center gap-2">
+              <input type="checkbox" checked={draft.active} onChange={(event) => setDraft({ ...draft, active: event.target.checked })} />
+              启用
+            </label>
+            <Button onClick={() => void handleSave()} disabled={disabled}>
+              {draft.id ? "保存修改" : "新增计划"}
+            </Button>
+            {draft.id ? (
+              <Button variant="outline" onClick={() => setDraft(emptyDraft())}>
+                取消编辑
+              </Button>
+            ) : null}
+          </div>
+        </div>
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>名称</TableHead>
+              <TableHead>报表</TableHead>
+              <TableHead>时间</TableHead>
+              <TableHead>收件人</TableHead>
+              <TableHead>状态</TableHead>
+              <TableHead className="text-right">操作</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {schedules.map((schedule) => (
+              <TableRow key={schedule.id}>
+                <TableCell className="font-medium">{schedule.name}</TableCell>
+                <TableCell>{REPORT_KIND_LABELS[schedule.report_kind] ?? schedule.report_kind}</TableCell>
+                <TableCell>{schedule.run_time}</TableCell>
+                <TableCell className="max-w-48 truncate">{schedule.email_to || "-"}</TableCell>
+                <TableCell>
+                  <Badge variant={schedule.status === "active" ? "secondary" : "outline"}>{schedule.status === "active" ? "启用" : "停用"}</Badge>
+                </TableCell>
+                <TableCell className="text-right">
+                  <div className="flex justify-end gap-2">
+                    <Button size="sm" variant="outline" disabled={disabled || running !== null} onClick={() => void handleRun(schedule.id)}>
+                      {running === schedule.id ? "执行中..." : "立即执行"}
+                    </Button>
+                    <Button
+                      size="sm"
+                      variant="outline"
+                      disabled={disabled}
+                      onClick={() =>
+                        setDraft({
+                          id: schedule.id,
+                          name: schedule.name,
+                          report_kind: schedule.report_kind,
+                          run_time: schedule.run_time,
+                          email_to: schedule.email_to ?? "",
+                          active: schedule.status === "active",
+                        })
+                      }
+                    >
+                      编辑
+                    </Button>
+                    <ConfirmButton
+                      size="sm"
+                      variant="destructive"
+                      label="删除"
+                      confirmText="确认删除该定时报表？执行记录会保留"
+                      disabled={disabled}
+                      onConfirm={() => handleDelete(schedule.id)}
+                    />
+                  </div>
+                </TableCell>
+              </TableRow>
+            ))}
+            {schedules.length === 0 ? (
+              <TableRow>
+                <TableCell colSpan={6} className="text-center text-slate-500">
+                  暂无定时报表
+                </TableCell>
+              </TableRow>
+            ) : null}
+          </TableBody>
+        </Table>
+
+        <div className="grid gap-3 rounded-lg border border-slate-200/70 p-3 md:grid-cols-3">
+          <p className="text-sm font-medium md:col-span-3">邮件服务器（仅支持不加密的 SMTP，适用于内网邮件中继）</p>
+          <div className="grid gap-2">
+            <Label>服务器地址</Label>
+            <Input value={smtp.host} onChange={(event) => setSmtp({ ...smtp, host: event.target.value })} placeholder="如 mail.local，留空表示不发送邮件" />
+          </div>
+          <div className="grid gap-2">
+            <Label>端口</Label>
+            <Input type="number" value={smtp.port} onChange={(event) => setSmtp({ ...smtp, port: Number(event.target.value) })} />
+          </div>
+          <div className="grid gap-2">
+            <Label>连接安全</Label>
+            <Select value={smtp.tls} onValueChange={(tls) => setSmtp({ ...smtp, tls })}>
+              <SelectTrigger>
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                <SelectItem value="none">不加密</SelectItem>
+                <SelectItem value="starttls" disabled>
+                  STARTTLS（暂不支持）
+                </SelectItem>
+                <SelectItem value="tls" disabled>
+                  SSL/TLS（暂不支持）
+                </SelectItem>
+              </SelectContent>
+            </Select>
+          </div>
+          <div className="grid gap-2">
+            <Label>发件人</Label>
+            <Input value={smtp.from} onChange={(event) => setSmtp({ ...smtp, from: event.target.value })} placeholder="如 inventory@example.com" />
+          </div>
+          <div className="grid gap-2">
+            <Label>账号（可选）</Label>
+            <Input value={smtp.username} onChange={(event) => setSmtp({ ...smtp, username: event.target.value })} />
+          </div>
+          <div className="grid gap-2">
+            <Label>密码</Label>
+            <Input
+              type="password"
+              value={smtpPassword}
+              placeholder={smtp.password_set ? "已保存，留空则不修改" : ""}
+              onChange={(event) => setSmtpPassword(event.target.value)}
+            />
+          </div>
+          <div className="flex items-end">
+            <Button onClick={() => void handleSaveSmtp()} disabled={disabled}>
+              保存邮件配置
+            </Button>
+          </div>
+          <div className="flex items-end gap-2 md:col-span-3">
+            <div className="grid flex-1 gap-2">
+              <Label>测试收件人</Label>
+              <Input value={testTo} onChange={(event) => setTestTo(event.target.value)} />
+            </div>
+            <Button variant="outline" onClick={() => void handleTestEmail()} disabled={disabled || !smtp.host}>
+              发送测试邮件
+            </Button>
+          </div>
+        </div>
+
+        <div className="space-y-2">
+          <p className="text-sm font-medium">执行记录</p>
+          <Table>
+            <TableHeader>
+              <TableRow>
+                <TableHead>时间</TableHead>
+                <TableHead>计划</TableHead>
+                <TableHead>方式</TableHead>
+                <TableHead>结果</TableHead>
+                <TableHead>邮件</TableHead>
+                <TableHead className="text-right">文件</TableHead>
+              </TableRow>
+            </TableHeader>
+            <TableBody>
+              {runs.slice(0, 20).map((run) => (
+                <TableRow key={run.id}>
+                  <TableCell>{new Date(run.started_at * 1000).toLocaleString()}</TableCell>
+                  <TableCell>{run.schedule_name}</TableCell>
+                  <TableCell>{run.trigger_type === "manual" ? "手动" : "定时"}</TableCell>
+                  <TableCell>
+                    {run.status === "success" ? (
+                      <Badge variant="secondary">成功</Badge>
+                    ) : (
+                      <span className="text-xs text-red-600">{run.error ?? "失败"}</span>
+                    )}
+                  </TableCell>
+                  <TableCell>{EMAIL_STATUS_LABELS[run.email_status] ?? run.email_status}</TableCell>
+                  <TableCell className="text-right">
+                    {run.file_path && !isMobile() ? (
+                      <Button size="sm" variant="ghost" onClick={() => void revealInFolder(run.file_path!)}>
+                        打开位置
+                      </Button>
+                    ) : (
+                      "-"
+                    )}
+                  </TableCell>
+                </TableRow>
+              ))}
+              {runs.length === 0 ? (
+                <TableRow>
+                  <TableCell colSpan={6} className="text-center text-slate-500">
+                    暂无执行记录
+                  </TableCell>
+                </TableRow>
+              ) : null}
+            </TableBody>
+          </Table>
+        </div>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,92 @@
+import { useEffect, useState } from "react";
+import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type Capability = "can_export" | "can_view_costs" | "can_view_audit";
+
+type RoleCapabilities = {
+  role: "keeper" | "viewer" | "member";
+} & Record<Capability, boolean>;
+
+const ROLE_LABELS: Record<RoleCapabilities["role"], string> = {
+  keeper: "库管",
+  viewer: "只读",
+  member: "成员",
+};
+
+const CAPABILITY_LABELS: { key: Capability; label: string }[] = [
+  { key: "can_export", label: "导出数据" },
+  { key: "can_view_costs", label: "查看成本" },
+  { key: "can_view_audit", label: "查看审计日志" },
+];
+
+// 角色能力开关：按角色开放导出、成本与审计日志，管理员始终拥有全部能力
+export function RoleCapabilityCard({ disabled }: { disabled?: boolean }) {
+  const [rows, setRows] = useState<RoleCapabilities[]>([]);
+
+  const fetchCapabilities = async () => {
+    try {
+      setRows(await tauriInvoke<RoleCapabilities[]>("list_role_capabilities", {}));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载角色能力失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    void fetchCapabilities();
+  }, []);
+
+  const toggle = async (role: RoleCapabilities["role"], key: Capability, allowed: boolean) => {
+    try {
+      await tauriInvoke("set_role_capabilities", { input: { role, [key]: allowed } });
+      toast.success("角色能力已更新");
+      await fetchCapabilities();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <Card className="border-slate-200/70">
+      <CardHeader>
+        <CardTitle>角色能力</CardTitle>
+        <CardDescription>未开放导出的角色无法导出或复制数据；未开放成本的角色看到的单价与库存价值为空；仅在启用 RBAC 后生效</CardDescription>
+      </CardHeader>
+      <CardContent>
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>角色</TableHead>
+              {CAPABILITY_LABELS.map((capability) => (
+                <TableHead key={capability.key} className="text-center">
+                  {capability.label}
+                </TableHead>
+              ))}
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {rows.map((row) => (
+              <TableRow key={row.role}>
+                <TableCell>{ROLE_LABELS[row.role] ?? row.role}</TableCell>
+                {CAPABILITY_LABELS.map((capability) => (
+                  <TableCell key={capability.key} className="text-center">
+                    <input
+                      type="checkbox"
+                      checked={row[capability.key]}
+                      disabled={disabled}
+                      onChange={(event) => void toggle(row.role, capability.key, event.target.checked)}
+                    />
+                  </TableCell>
+                ))}
+              </TableRow>
+            ))}
+          </TableBody>
+        </Table>
+      </CardContent>
+    </Card>
+  );
+}
@@ -0,0 +1,428 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { DatePicker } from "~/components/ui/date";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { Tabs, TabsContent, TabsList, TabsTrigger } from "~/components/ui/tabs";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { ItemPicker } from "~/components/common/pickers/item-picker";
+import { SlotCascaderPicker } from "~/components/common/pickers/slot-cascader-picker";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+import type { SlotPickerValue } from "./types";
+import { getTxnLocation } from "./helpers";
+
+type AsnStatus = "open" | "closed" | "cancelled";
+
+type Asn = {
+  id: string;
+  asn_no: string;
+  supplier: string;
+  eta?: number | null;
+  status: AsnStatus;
+  note?: string | null;
+  created_by_name?: string | null;
+  line_count: number;
+  received_line_count: number;
+};
+
+type AsnLine = {
+  id: string;
+  asn_no: string;
+  supplier: string;
+  asn_status: AsnStatus;
+  line_no: number;
+  item_code: string;
+  item_name: string;
+  uom?: string | null;
+  expected_qty: number;
+  received_qty: number;
+  variance: number;
+  flag: "over" | "short" | "match";
+};
+
+type AsnDetail = {
+  id: string;
+  asn_no: string;
+  supplier: string;
+  status: AsnStatus;
+  lines: AsnLine[];
+};
+
+type AsnReceived = {
+  asn_no: string;
+  txn_nos: string[];
+  closed: boolean;
+  lines: AsnLine[];
+};
+
+type DraftLine = { item_id: string; expected_qty: string };
+
+const statusLabels: Record<AsnStatus, string> = {
+  open: "待收货",
+  closed: "已结单",
+  cancelled: "已取消",
+};
+
+const flagLabels: Record<AsnLine["flag"], string> = {
+  over: "超收",
+  short: "短收",
+  match: "一致",
+};
+
+const emptySlot: SlotPickerValue = { warehouseId: "", rackId: "", levelNo: "", slotId: "" };
+
+const formatEta = (eta?: number | null) => (eta ? new Date(eta * 1000).toLocaleDateString() : "-");
+
+// 到货预报：登记供应商预计到货的物品与数量，收货时按预报明细入库并核对超收 / 短收
+export function AsnDialog({ open, onOpenChange, onChanged }: { open: boolean; onOpenChange: (open: boolean) => void; onChanged?: () => void }) {
+  const [asns, setAsns] = useState<Asn[]>([]);
+  const [openOnly, setOpenOnly] = useState(true);
+  const [supplier, setSupplier] = useState("");
+  const [eta, setEta] = useState("");
+  const [note, setNote] = useState("");
+  const [draftLines, setDraftLines] = useState<DraftLine[]>([{ item_id: "", expected_qty: "" }]);
+  const [detail, setDetail] = useState<AsnDetail | null>(null);
+  const [slot, setSlot] = useState<SlotPickerValue>(emptySlot);
+  const [receiveQty, setReceiveQty] = useState<Record<string, string>>({});
+  const [variances, setVariances] = useState<AsnLine[]>([]);
+
+  const fetchAsns = async () => {
+    try {
+      setAsns(await tauriInvoke<Asn[]>("list_asns", { input: { status: openOnly ? "open" : null } }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载到货预报失败";
+      toast.error(message);
+    }
+  };
+
+  const fetchVariances = async () => {
+    try {
+      setVariances(await tauriInvoke<AsnLine[]>("get_asn_variance_report", { input: {} }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载收货差异失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchAsns();
+  }, [open, openOnly]);
+
+  const handleCreate = async () => {
+    if (!supplier.trim()) {
+      toast.error("请填写供应商");
+      return;
+    }
+    const lines = draftLines.filter((line) => line.item_id);
+    if (lines.length === 0) {
+      toast.error("请至少添加一条预报明细");
+      return;
+    }
+    if (lines.some((line) => !(Number(line.expected_qty) > 0))) {
+      toast.error("请输入有效的预计数量");
+      return;
+    }
+    try {
+      const result = await tauriInvoke<{ asn_no: string }>("create_asn", {
+        input: {
+          supplier: supplier.trim(),
+          eta: eta ? Math.floor(new Date(`${eta}T00:00:00`).getTime() / 1000) : null,
+          note: note.trim() || null,
+          lines: lines.map((line) => ({ item_id: line.item_id, expected_qty: Number(line.expected_qty) })),
+        },
+      });
+      toast.success(`已登记到货预报 ${result.asn_no}`);
+      setSupplier("");
+      setEta("");
+      setNote("");
+      setDraftLines([{ item_id: "", expected_qty: "" }]);
+      await fetchAsns();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "登记失败";
+      toast.error(message);
+    }
+  };
+
+  const openReceive = async (id: string) => {
+    try {
+      const result = await tauriInvoke<AsnDetail>("get_asn", { input: { id } });
+      setDetail(result);
+      // 默认按剩余未收数量收货
+      setReceiveQty(
+        Object.fromEntries(result.lines.map((line) => [line.id, line.received_qty < line.expected_qty ? String(line.expected_qty - line.received_qty) : ""]))
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载预报明细失败";
+      toast.error(message);
+    }
+  };
+
+  const handleReceive = async () => {
+    if (!detail) return;
+    if (!slot.slotId) {
+      toast.error("请选择收货库位");
+      return;
+    }
+    const lines = detail.lines
+      .map((line) => ({ line_id: line.id, qty: Number(receiveQty[line.id] || 0) }))
+      .filter((line) => line.qty > 0);
+    if (lines.length === 0) {
+      toast.error("请填写本次收货数量");
+      return;
+    }
+    try {
+      const location = await getTxnLocation();
+      const result = await tauriInvoke<AsnReceived>("receive_against_asn", {
+        input: { id: detail.id, to_slot_id: slot.slotId, lines, occurred_at: Math.floor(Date.now() / 1000), location },
+      });
+      const overCount = result.lines.filter((line) => line.flag === "over").length;
+      toast.success(`已收货 ${result.txn_nos.length} 条${overCount > 0 ? `，${overCount} 条超收` : ""}${result.closed ? "，预报已结单" : ""}`);
+      setDetail(result.closed ? null : { ...detail, lines: result.lines });
+      setReceiveQty({});
+      await fetchAsns();
+      onChanged?.();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "收货失败";
+      toast.error(message);
+    }
+  };
+
+  const handleFinish = async (command: "close_asn" | "cancel_asn", id: string) => {
+    try {
+      await tauriInvoke(command, { input: { id } });
+      toast.success(command === "close_asn" ? "已结单" : "已取消");
+      if (detail?.id === id) setDetail(null);
+      await fetchAsns();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "操作失败";
+      toast.error(message);
+    }
+  };
+
+  const updateDraftLine = (index: number, patch: Partial<DraftLine>) => {
+    setDraftLines(draftLines.map((line, current) => (current === index ? { ...line, ...patch } : line)));
+  };
+
+  return (
+    <CommonDialog
+      title="到货预报"
+      description="登记供应商预计到货的物品与数量，收货时按预报核对超收 / 短收"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <Tabs defaultValue="asns" onValueChange={(value) => value === "variance" && void fetchVariances()}>
+          <TabsList>
+            <TabsTrigger value="asns">预报收货</TabsTrigger>
+            <TabsTrigger value="create">登记预报</TabsTrigger>
+            <TabsTrigger value="variance">收货差异</TabsTrigger>
+          </TabsList>
+          <TabsContent value="asns" className="space-y-4">
+            {detail ? (
+              <div className="space-y-3 rounded-md border p-4">
+                <div className="text-sm font-medium">
+                  {detail.asn_no} · {detail.supplier}
+                </div>
+                <SlotCascaderPicker label="收货库位" value={slot} onChange={setSlot} />
+                <Table>
+                  <TableHeader>
+                    <TableRow>
+                      <TableHead>物品</TableHead>
+                      <TableHead className="text-right">预计/已收</TableHead>
+                      <TableHead className="w-32">本次收货</TableHead>
+                    </TableRow>
+                  </TableHeader>
+                  <TableBody>
+                    {detail.lines.map((line) => (
+                      <TableRow key={line.id}>
+                        <TableCell>
+                          {line.item_code} {line.item_name}
+                        </TableCell>
+                        <TableCell className="text-right">
+                          {line.expected_qty} / {line.received_qty}
+                          {line.flag === "over" ? <div className="text-xs text-amber-600">超收 {line.variance}</div> : null}
+                        </TableCell>
+                        <TableCell>
+                          <Input
+                            type="number"
+                            min={0}
+                            step="any"
+                            value={receiveQty[line.id] ?? ""}
+                            onChange={(event) => setReceiveQty({ ...receiveQty, [line.id]: event.target.value })}
+                          />
+                        </TableCell>
+                      </TableRow>
+                    ))}
+                  </TableBody>
+                </Table>
+                <div className="flex gap-2">
+                  <Button onClick={() => void handleReceive()}>确认收货</Button>
+                  <Button variant="outline" onClick={() => setDetail(null)}>
+                    取消
+                  </Button>
+                </div>
+              </div>
+            ) : null}
+            <label className="flex items-center gap-2 text-sm">
+              <input type="checkbox" className="h-4 w-4 accent-slate-900" checked={openOnly} onChange={(event) => setOpenOnly(event.target.checked)} />
+              仅看待收货
+            </label>
+            <div className="max-h-[50vh] overflow-auto">
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>预报单</TableHead>
+                    <TableHead>供应商</TableHead>
+                    <TableHead>预计到货</TableHead>
+                    <TableHead className="text-right">已收足/明细</TableHead>
+                    <TableHead>状态</TableHead>
+                    <TableHead className="text-right">操作</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {asns.map((asn) => (
+                    <TableRow key={asn.id}>
+                      <TableCell>
+                        <div>{asn.asn_no}</div>
+                        <div className="text-xs text-muted-foreground">{asn.created_by_name || "-"}</div>
+                      </TableCell>
+                      <TableCell>{asn.supplier}</TableCell>
+                      <TableCell>{formatEta(asn.eta)}</TableCell>
+                      <TableCell className="text-right">
+                        {asn.received_line_count} / {asn.line_count}
+                      </TableCell>
+                      <TableCell>
+                        <Badge variant={asn.status === "open" ? "outline" : "secondary"}>{statusLabels[asn.status]}</Badge>
+                      </TableCell>
+                      <TableCell className="text-right">
+                        {asn.status === "open" ? (
+                          <div className="flex justify-end gap-2">
+                            <Button size="sm" onClick={() => void openReceive(asn.id)}>
+                              收货
+                            </Button>
+                            <ConfirmButton
+                              label="结单"
+                              variant="ghost"
+                              size="sm"
+                              confirmText="结单后未收足的数量计为短收，确认结单？"
+                              onConfirm={() => handleFinish("close_asn", asn.id)}
+                            />
+                            {asn.received_line_count === 0 ? (
+                              <ConfirmButton label="取消" variant="ghost" size="sm" confirmText="确认取消该到货预报？" onConfirm={() => handleFinish("cancel_asn", asn.id)} />
+                            ) : null}
+                          </div>
+                        ) : null}
+                      </TableCell>
+                    </TableRow>
+                  ))}
+                  {asns.length === 0 ? (
+                    <TableRow>
+                      <TableCell colSpan={6} className="text-center text-muted-foreground">
+                        暂无到货预报
+                      </TableCell>
+                    </TableRow>
+                  ) : null}
+                </TableBody>
+              </Table>
+            </div>
+          </TabsContent>
+          <TabsContent value="create" className="space-y-4">
+            <div className="grid gap-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>供应商</Label>
+                <Input value={supplier} onChange={(event) => setSupplier(event.target.value)} />
+              </div>
+              <div className="grid gap-2">
+                <Label>预计到货</Label>
+                <DatePicker value={eta} onChange={setEta} defaultNow={false} />
+              </div>
+              <div className="grid gap-2 md:col-span-2">
+                <Label>备注</Label>
+                <Input value={note} onChange={(event) => setNote(event.target.value)} />
+              </div>
+            </div>
+            <div className="space-y-2">
+              {draftLines.map((line, index) => (
+                <div key={index} className="flex items-center gap-2">
+                  <div className="flex-1">
+                    <ItemPicker value={line.item_id} onChange={(value) => updateDraftLine(index, { item_id: value })} />
+                  </div>
+                  <Input
+                    className="w-32"
+                    type="number"
+                    min={0}
+                    step="any"
+                    placeholder="预计数量"
+                    value={line.expected_qty}
+                    onChange={(event) => updateDraftLine(index, { expected_qty: event.target.value })}
+                  />
+                  <Button
+                    variant="ghost"
+                    size="sm"
+                    disabled={draftLines.length === 1}
+                    onClick={() => setDraftLines(draftLines.filter((_, current) => current !== index))}
+                  >
+                    移除
+                  </Button>
+                </div>
+              ))}
+              <Button variant="outline" size="sm" onClick={() => setDraftLines([...draftLines, { item_id: "", expected_qty: "" }])}>
+                添加明细
+              </Button>
+            </div>
+            <Button onClick={() => void handleCreate()}>登记预报</Button>
+          </TabsContent>
+          <TabsContent value="variance" className="space-y-3">
+            <p className="text-sm text-muted-foreground">已结单预报中的超收 / 短收明细，以及待收货预报中已超收的明细</p>
+            <div className="max-h-[50vh] overflow-auto">
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>预报单</TableHead>
+                    <TableHead>供应商</TableHead>
+                    <TableHead>物品</TableHead>
+                    <TableHead className="text-right">预计/已收</TableHead>
+                    <TableHead className="text-right">差异</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {variances.map((line) => (
+                    <TableRow key={line.id}>
+                      <TableCell>
+                        <div>{line.asn_no}</div>
+                        <div className="text-xs text-muted-foreground">{statusLabels[line.asn_status]}</div>
+                      </TableCell>
+                      <TableCell>{line.supplier}</TableCell>
+                      <TableCell>
+                        {line.item_code} {line.item_name}
+                      </TableCell>
+                      <TableCell className="text-right">
+                        {line.expected_qty} / {line.received_qty}
+                      </TableCell>
+                      <TableCell className="text-right">
+                        <Badge variant={line.flag === "over" ? "destructive" : "outline"}>
+                          {flagLabels[line.flag]} {line.variance > 0 ? `+${line.variance}` : line.variance}
+                        </Badge>
+                      </TableCell>
+                    </TableRow>
+                  ))}
+                  {variances.length === 0 ? (
+                    <TableRow>
+                      <TableCell colSpan={5} className="text-center text-muted-foreground">
+                        暂无收货差异
+                      </TableCell>
+                    </TableRow>
+                  ) : null}
+                </TableBody>
+              </Table>
+            </div>
+          </TabsContent>
+        </Tabs>
+      }
+    />
+  );
+}
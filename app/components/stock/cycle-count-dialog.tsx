@@ -0,0 +1,365 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { Tabs, TabsContent, TabsList, TabsTrigger } from "~/components/ui/tabs";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { WarehousePicker } from "~/components/common/pickers/warehouse-picker";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type CountTask = {
+  id: string;
+  plan_name: string;
+  warehouse_name?: string | null;
+  item_code?: string | null;
+  item_name?: string | null;
+  uom?: string | null;
+  slot_code?: string | null;
+  abc_class: string;
+  scheduled_date: string;
+  carry_over: number;
+  status: "pending" | "done" | "skipped";
+  count_txn_no?: string | null;
+  completed_by_name?: string | null;
+  expected_qty: number;
+};
+
+type TodayCountTasks = {
+  date: string;
+  total: number;
+  done: number;
+  skipped: number;
+  pending: number;
+  carried_over: number;
+  tasks: CountTask[];
+};
+
+type CycleCountPlan = {
+  id: string;
+  name: string;
+  warehouse_id: string;
+  warehouse_name?: string | null;
+  a_ratio: number;
+  b_ratio: number;
+  a_interval_days: number;
+  b_interval_days: number;
+  c_interval_days: number;
+  lookback_days: number;
+  status: "active" | "disabled";
+  last_generated_date?: string | null;
+};
+
+type PlanForm = {
+  id?: string;
+  name: string;
+  warehouse_id: string;
+  a_ratio: string;
+  b_ratio: string;
+  a_interval_days: string;
+  b_interval_days: string;
+  c_interval_days: string;
+  lookback_days: string;
+  status: "active" | "disabled";
+};
+
+const emptyForm: PlanForm = {
+  name: "",
+  warehouse_id: "",
+  a_ratio: "20",
+  b_ratio: "30",
+  a_interval_days: "30",
+  b_interval_days: "90",
+  c_interval_days: "180",
+  lookback_days: "90",
+  status: "active",
+};
+
+const statusLabels: Record<CountTask["status"], string> = {
+  pending: "待盘点",
+  done: "已完成",
+  skipped: "已跳过",
+};
+
+// 循环盘点：按计划每天生成盘点任务，登记盘点后自动完成，未完成的顺延到次日
+export function CycleCountDialog({
+  open,
+  onOpenChange,
+  warehouseId,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  warehouseId?: string;
+}) {
+  const [today, setToday] = useState<TodayCountTasks | null>(null);
+  const [plans, setPlans] = useState<CycleCountPlan[]>([]);
+  const [form, setForm] = useState<PlanForm | null>(null);
+
+  const fetchTasks = async () => {
+    try {
+      setToday(await tauriInvoke<TodayCountTasks>("get_today_count_tasks", { input: { warehouse_id: warehouseId || undefined } }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载盘点任务失败";
+      toast.error(message);
+    }
+  };
+
+  const fetchPlans = async () => {
+    try {
+      setPlans(await tauriInvoke<CycleCountPlan[]>("list_cycle_count_plans", {}));
+    } catch {
+      // 成员无权查看盘点计划
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchTasks();
+    void fetchPlans();
+  }, [open, warehouseId]);
+
+  const handleSkip = async (taskId: string) => {
+    try {
+      await tauriInvoke("skip_count_task", { input: { task_id: taskId } });
+      toast.success("已跳过");
+      await fetchTasks();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "跳过失败";
+      toast.error(message);
+    }
+  };
+
+  const handleSavePlan = async () => {
+    if (!form) return;
+    const input = {
+      name: form.name,
+      warehouse_id: form.warehouse_id,
+      a_ratio: Number(form.a_ratio),
+      b_ratio: Number(form.b_ratio),
+      a_interval_days: Number(form.a_interval_days),
+      b_interval_days: Number(form.b_interval_days),
+      c_interval_days: Number(form.c_interval_days),
+      lookback_days: Number(form.lookback_days),
+      status: form.status,
+    };
+    try {
+      if (form.id) {
+        await tauriInvoke("update_cycle_count_plan", { input: { id: form.id, ...input } });
+      } else {
+        await tauriInvoke("create_cycle_count_plan", { input });
+      }
+      toast.success("盘点计划已保存");
+      setForm(null);
+      await fetchPlans();
+      await fetchTasks();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleDeletePlan = async (id: string) => {
+    try {
+      await tauriInvoke("delete_cycle_count_plan", { input: { id } });
+      toast.success("盘点计划已删除");
+      await fetchPlans();
+      await fetchTasks();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败";
+      toast.error(message);
+    }
+  };
+
+  const editPlan = (plan: CycleCountPlan) =>
+    setForm({
+      id: plan.id,
+      name: plan.name,
+      warehouse_id: plan.warehouse_id,
+      a_ratio: String(plan.a_ratio),
+      b_ratio: String(plan.b_ratio),
+      a_interval_days: String(plan.a_interval_days),
+      b_interval_days: String(plan.b_interval_days),
+      c_interval_days: String(plan.c_interval_days),
+      lookback_days: String(plan.lookback_days),
+      status: plan.status,
+    });
+
+  const numberField = (key: keyof PlanForm, label: string) => (
+    <div className="grid gap-2">
+      <Label>{label}</Label>
+      <Input
+        type="number"
+        min={0}
+        value={form ? (form[key] as string) : ""}
+        onChange={(event) => setForm((prev) => (prev ? { ...prev, [key]: event.target.value } : prev))}
+      />
+    </div>
+  );
+
+  return (
+    <CommonDialog
+      title="循环盘点"
+      description={today ? `${today.date}：共 ${today.total} 项，已完成 ${today.done}，跳过 ${today.skipped}，待盘点 ${today.pending}` : ""}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <Tabs defaultValue="tasks">
+          <TabsList>
+            <TabsTrigger value="tasks">今日任务</TabsTrigger>
+            <TabsTrigger value="plans">盘点计划</TabsTrigger>
+          </TabsList>
+          <TabsContent value="tasks" className="space-y-3">
+            <div className="text-sm text-muted-foreground">
+              物品按近期流水次数分为 A/B/C 三类并按各自周期轮流盘点；在库存页登记盘点后任务自动完成
+              {today && today.carried_over > 0 ? `，其中 ${today.carried_over} 项为前几日顺延` : ""}
+            </div>
+            <div className="max-h-[60vh] overflow-auto">
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>分类</TableHead>
+                    <TableHead>库位</TableHead>
+                    <TableHead>物品编码</TableHead>
+                    <TableHead>物品</TableHead>
+                    <TableHead className="text-right">账面数量</TableHead>
+                    <TableHead>计划</TableHead>
+                    <TableHead>状态</TableHead>
+                    <TableHead className="text-right">操作</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {(today?.tasks ?? []).map((task) => (
+                    <TableRow key={task.id}>
+                      <TableCell>
+                        <Badge variant={task.abc_class === "A" ? "default" : "outline"}>{task.abc_class}</Badge>
+                      </TableCell>
+                      <TableCell>{task.slot_code || "-"}</TableCell>
+                      <TableCell>{task.item_code || "-"}</TableCell>
+                      <TableCell>{task.item_name || "-"}</TableCell>
+                      <TableCell className="text-right">
+                        {task.expected_qty}
+                        {task.uom ? ` ${task.uom}` : ""}
+                      </TableCell>
+                      <TableCell>
+                        {task.plan_name}
+                        {task.carry_over > 0 ? (
+                          <Badge variant="destructive" className="ml-2">
+                            顺延 {task.carry_over} 天
+                          </Badge>
+                        ) : null}
+                      </TableCell>
+                      <TableCell>
+                        <Badge variant={task.status === "pending" ? "outline" : "secondary"}>{statusLabels[task.status]}</Badge>
+                        {task.count_txn_no ? <span className="ml-2 text-xs text-muted-foreground">{task.count_txn_no}</span> : null}
+                      </TableCell>
+                      <TableCell className="text-right">
+                        {task.status === "pending" ? (
+                          <ConfirmButton
+                            label="跳过"
+                            variant="ghost"
+                            size="sm"
+                            confirmText="跳过后本周期不再安排该物品/库位，确认跳过？"
+                            onConfirm={() => handleSkip(task.id)}
+                          />
+                        ) : (
+                          <span className="text-xs text-muted-foreground">{task.completed_by_name || ""}</span>
+                        )}
+                      </TableCell>
+                    </TableRow>
+                  ))}
+                </TableBody>
+              </Table>
+            </div>
+          </TabsContent>
+          <TabsContent value="plans" className="space-y-3">
+            {form ? (
+              <div className="grid gap-4 rounded-md border p-4">
+                <div className="grid grid-cols-2 gap-4">
+                  <div className="grid gap-2">
+                    <Label>计划名称</Label>
+                    <Input value={form.name} onChange={(event) => setForm({ ...form, name: event.target.value })} />
+                  </div>
+                  <div className="grid gap-2">
+                    <Label>仓库</Label>
+                    <WarehousePicker value={form.warehouse_id} onChange={(nextId) => setForm({ ...form, warehouse_id: nextId })} />
+                  </div>
+                </div>
+                <div className="grid grid-cols-3 gap-4">
+                  {numberField("a_ratio", "A 类占比（%）")}
+                  {numberField("b_ratio", "B 类占比（%）")}
+                  {numberField("lookback_days", "分类统计天数")}
+                  {numberField("a_interval_days", "A 类周期（天）")}
+                  {numberField("b_interval_days", "B 类周期（天）")}
+                  {numberField("c_interval_days", "C 类周期（天）")}
+                </div>
+                <label className="flex items-center gap-2 text-sm">
+                  <input
+                    type="checkbox"
+                    className="h-4 w-4 accent-slate-900"
+                    checked={form.status === "active"}
+                    onChange={(event) => setForm({ ...form, status: event.target.checked ? "active" : "disabled" })}
+                  />
+                  启用
+                </label>
+                <div className="flex gap-2">
+                  <Button onClick={() => void handleSavePlan()}>保存</Button>
+                  <Button variant="outline" onClick={() => setForm(null)}>
+                    取消
+                  </Button>
+                </div>
+              </div>
+            ) : (
+              <Button variant="outline" onClick={() => setForm({ ...emptyForm, warehouse_id: warehouseId || "" })}>
+                新建计划
+              </Button>
+            )}
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>计划</TableHead>
+                  <TableHead>仓库</TableHead>
+                  <TableHead>A/B 占比</TableHead>
+                  <TableHead>A/B/C 周期（天）</TableHead>
+                  <TableHead>状态</TableHead>
+                  <TableHead className="text-right">操作</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {plans.map((plan) => (
+                  <TableRow key={plan.id}>
+                    <TableCell>{plan.name}</TableCell>
+                    <TableCell>{plan.warehouse_name || "-"}</TableCell>
+                    <TableCell>
+                      {plan.a_ratio}% / {plan.b_ratio}%
+                    </TableCell>
+                    <TableCell>
+                      {plan.a_interval_days} / {plan.b_interval_days} / {plan.c_interval_days}
+                    </TableCell>
+                    <TableCell>
+                      <Badge variant={plan.status === "active" ? "secondary" : "outline"}>{plan.status === "active" ? "启用" : "停用"}</Badge>
+                    </TableCell>
+                    <TableCell className="text-right">
+                      <Button variant="ghost" size="sm" onClick={() => editPlan(plan)}>
+                        编辑
+                      </Button>
+                      <ConfirmButton
+                        label="删除"
+                        variant="ghost"
+                        size="sm"
+                        confirmText="删除计划将同时删除其全部盘点任务，确认删除？"
+                        onConfirm={() => handleDeletePlan(plan.id)}
+                      />
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </TabsContent>
+        </Tabs>
+      }
+    />
+  );
+}
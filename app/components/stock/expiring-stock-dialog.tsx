@@ -0,0 +1,140 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type ExpiringStockItem = {
+  item_id: string;
+  item_code: string;
+  item_name: string;
+  uom?: string | null;
+  slot_id: string;
+  slot_code: string;
+  warehouse_id?: string | null;
+  warehouse_name?: string | null;
+  qty: number;
+  expires_at: number;
+  days_left: number;
+  expired: boolean;
+};
+
+type ExpiringStockReport = {
+  horizon_days: number;
+  generated_at: number;
+  expired_count: number;
+  expiring_count: number;
+  items: ExpiringStockItem[];
+};
+
+const formatDay = (timestamp: number) => new Date(timestamp * 1000).toLocaleDateString("zh-CN");
+
+// 临期报表：按先进先出推算库位现存库存所属批次，列出已过期与临期天数内到期的部分
+export function ExpiringStockDialog({
+  open,
+  onOpenChange,
+  warehouseId,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  warehouseId?: string;
+}) {
+  const [report, setReport] = useState<ExpiringStockReport | null>(null);
+  // 留空使用系统设置的临期天数
+  const [horizonDays, setHorizonDays] = useState("");
+
+  const fetchReport = async () => {
+    const days = horizonDays.trim() ? Number(horizonDays) : undefined;
+    if (days !== undefined && (!Number.isInteger(days) || days < 1)) return;
+    try {
+      setReport(
+        await tauriInvoke<ExpiringStockReport>("get_expiring_stock_report", {
+          input: { warehouse_id: warehouseId || undefined, horizon_days: days },
+        })
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载临期报表失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchReport();
+  }, [open, warehouseId, horizonDays]);
+
+  return (
+    <CommonDialog
+      title="临期库存"
+      description="入库时录入到期日的物品，按先进先出推算各库位现存批次；已过期库存出库需管理员确认"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="flex flex-wrap items-center gap-3">
+            <Label htmlFor="expiring-horizon">临期天数</Label>
+            <Input
+              id="expiring-horizon"
+              className="w-28"
+              type="number"
+              min={1}
+              step={1}
+              placeholder={report ? String(report.horizon_days) : "默认"}
+              value={horizonDays}
+              onChange={(event) => setHorizonDays(event.target.value)}
+            />
+            {report ? (
+              <Badge variant={report.expired_count > 0 ? "destructive" : "secondary"}>
+                已过期 {report.expired_count} 项，{report.horizon_days} 天内到期 {report.expiring_count} 项
+              </Badge>
+            ) : null}
+          </div>
+          <div className="max-h-[60vh] overflow-auto">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>物品</TableHead>
+                  <TableHead>库位</TableHead>
+                  <TableHead className="text-right">数量</TableHead>
+                  <TableHead>到期日</TableHead>
+                  <TableHead className="text-right">剩余天数</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {(report?.items ?? []).map((row) => (
+                  <TableRow key={`${row.item_id}-${row.slot_id}-${row.expires_at}`}>
+                    <TableCell>
+                      {row.item_code} {row.item_name}
+                    </TableCell>
+                    <TableCell>
+                      {row.warehouse_name ? `${row.warehouse_name} / ` : ""}
+                      {row.slot_code}
+                    </TableCell>
+                    <TableCell className="text-right">
+                      {row.qty}
+                      {row.uom ? ` ${row.uom}` : ""}
+                    </TableCell>
+                    <TableCell>{formatDay(row.expires_at)}</TableCell>
+                    <TableCell className="text-right">
+                      {row.expired ? <Badge variant="destructive">已过期</Badge> : <Badge variant="outline">{row.days_left} 天</Badge>}
+                    </TableCell>
+                  </TableRow>
+                ))}
+                {report && report.items.length === 0 ? (
+                  <TableRow>
+                    <TableCell colSpan={5} className="text-center text-muted-foreground">
+                      暂无临期库存
+                    </TableCell>
+                  </TableRow>
+                ) : null}
+              </TableBody>
+            </Table>
+          </div>
+        </div>
+      }
+    />
+  );
+}
@@ -9,10 +9,12 @@ import { DateTimePicker } from "~/components/ui/date-time-picker";
 import { ItemPicker } from "~/components/common/pickers/item-picker";
 import { SlotCascaderPicker } from "~/components/common/pickers/slot-cascader-picker";
 import { OperatorPicker } from "~/components/common/pickers/operator-picker";
+import { ReasonPicker } from "~/components/common/pickers/reason-picker";
 import { ImagePicker } from "~/components/common/image-picker";
 import { usePhotoList } from "~/lib/use-photo-list";
 import { useSession } from "~/lib/auth";
 import type { CountFormValues, SlotPickerValue } from "../types";
+import { registerKioskOperator } from "../helpers";
 
 import { ConfirmButton } from "~/components/common/confirm-button";
 
@@ -23,7 +25,7 @@ type Props = {
 
 export default function CountForm({ onClose, form: externalForm }: Props) {
   const actorOperatorId = useSession()?.actor_operator_id || "";
-  const form = externalForm ?? useForm<CountFormValues>({ defaultValues: { item_id: "", slot_id: "", actual_qty: "", occurred_at: "", operator_id: actorOperatorId, note: "" } });
+  const form = externalForm ?? useForm<CountFormValues>({ defaultValues: { item_id: "", slot_id: "", actual_qty: "", reason_id: "", occurred_at: "", operator_id: actorOperatorId, note: "" } });
   const [localTarget, setLocalTarget] = useState<SlotPickerValue>({ warehouseId: "", rackId: "", levelNo: "", slotId: form.getValues("slot_id") || "" });
   const target = localTarget;
 
@@ -36,7 +38,7 @@ export default function CountForm({ onClose, form: externalForm }: Props) {
       // placeholder: call create_count etc.
       if (onClose) onClose();
       toast.success("盘点提交（本地实现）");
-      form.reset({ item_id: "", slot_id: "", actual_qty: "", occurred_at: "", operator_id: "", note: "" });
+      form.reset({ item_id: "", slot_id: "", actual_qty: "", reason_id: "", occurred_at: "", operator_id: "", note: "" });
       resetSelectedPaths();
       return true;
     } catch (err) {
@@ -92,7 +94,20 @@ export default function CountForm({ onClose, form: externalForm }: Props) {
             <FormItem className="grid gap-2">
               <FormLabel>实盘数量</FormLabel>
               <FormControl>
-                <Input placeholder="请输入实际数量" type="number" {...field} />
+                <Input placeholder="请输入实际数量" type="number" step="any" {...field} />
+              </FormControl>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
+        <FormField
+          control={form.control}
+          name="reason_id"
+          render={({ field }) => (
+            <FormItem className="grid gap-2">
+              <FormLabel>差异原因</FormLabel>
+              <FormControl>
+                <ReasonPicker value={field.value} onChange={field.onChange} txnType="ADJUST" emptyLabel="不填写" />
               </FormControl>
               <FormMessage />
             </FormItem>
@@ -106,7 +121,14 @@ export default function CountForm({ onClose, form: externalForm }: Props) {
             <FormItem className="grid gap-2">
               <FormLabel>记录人</FormLabel>
               <FormControl>
-                <OperatorPicker value={field.value} onChange={field.onChange} placeholder="选择记录人" />
+                <OperatorPicker
+                value={field.value}
+                onChange={(value) => {
+                  field.onChange(value);
+                  void registerKioskOperator(value);
+                }}
+                placeholder="选择记录人"
+              />
               </FormControl>
               <FormMessage />
             </FormItem>
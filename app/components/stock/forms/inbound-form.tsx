@@ -6,15 +6,17 @@ import { Form, FormControl, FormField, FormItem, FormLabel, FormMessage } from "
 import { Input } from "~/components/ui/input";
 import { Textarea } from "~/components/ui/textarea";
 import { DateTimePicker } from "~/components/ui/date-time-picker";
+import { DatePicker } from "~/components/ui/date";
 import { ItemPicker } from "~/components/common/pickers/item-picker";
 import { SlotCascaderPicker } from "~/components/common/pickers/slot-cascader-picker";
 import { OperatorPicker } from "~/components/common/pickers/operator-picker";
 import { ImagePicker } from "~/components/common/image-picker";
-import { tauriInvoke } from "~/lib/tauri";
 import { usePhotoList } from "~/lib/use-photo-list";
+import { useIdempotencyKey } from "~/lib/use-idempotency-key";
+import { submitOrQueue } from "~/lib/offline-queue";
 import { useSession } from "~/lib/auth";
 import type { InboundFormValues, SlotPickerValue, StockSlotItem } from "../types";
-import { getItemListBySlotId } from "../helpers";
+import { getItemListBySlotId, getTxnLocation, registerKioskOperator } from "../helpers";
 
 import { ConfirmButton } from "~/components/common/confirm-button";
 
@@ -34,6 +36,7 @@ export default function InboundForm({ onClose, form: externalForm }: Props) {
   const setTarget = setLocalTarget;
 
   const { paths: selectedPaths, setPaths: setSelectedPaths, reset: resetSelectedPaths } = usePhotoList();
+  const idempotencyKey = useIdempotencyKey();
 
   const [inboundSlotItemsState, setInboundSlotItemsState] = useState<StockSlotItem[]>([]);
 
@@ -70,7 +73,9 @@ export default function InboundForm({ onClose, form: externalForm }: Props) {
         toast.error("请选择目标库位");
         return false;
       }
-      const txnNo = await tauriInvoke<string>("create_inbound", {
+      const location = await getTxnLocation();
+      // 图片随流水一并提交，保存失败时错误信息中带有已记录的流水号
+      const submitted = await submitOrQueue("create_inbound", {
         input: {
           item_id: values.item_id,
           to_slot_id: toSlotId,
@@ -78,24 +83,16 @@ export default function InboundForm({ onClose, form: externalForm }: Props) {
           occurred_at: Math.floor(new Date(values.occurred_at || Date.now()).getTime() / 1000),
           operator_id: values.operator_id || undefined,
           note: values.note || null,
+          unit_cost: values.unit_cost?.trim() ? Number(values.unit_cost) : null,
+          expires_at: values.expires_at ? Math.floor(new Date(`${values.expires_at}T00:00:00`).getTime() / 1000) : null,
+          location,
+          photo_paths: selectedPaths,
+          idempotency_key: idempotencyKey.current(),
         },
-      });
-      if (selectedPaths.length > 0) {
-        try {
-          await tauriInvoke("add_photos", {
-            input: {
-              photo_type: "txn",
-              data_id: txnNo,
-              src_paths: selectedPaths,
-            },
-          });
-          toast.success("图片上传成功");
-        } catch (_) {
-          toast.error("图片上传失败");
-        }
-      }
-      toast.success("入库成功");
-      form.reset({ item_id: "", to_slot_id: "", qty: "", occurred_at: "", operator_id: "", note: "" });
+      }, "入库");
+      idempotencyKey.renew();
+      toast.success(submitted.queued ? "写入暂停，已暂存，恢复后自动补交" : "入库成功");
+      form.reset({ item_id: "", to_slot_id: "", qty: "", unit_cost: "", expires_at: "", occurred_at: "", operator_id: "", note: "" });
       resetSelectedPaths();
       if (onClose) onClose();
       return true;
@@ -163,7 +160,25 @@ export default function InboundForm({ onClose, form: externalForm }: Props) {
           <FormItem className="grid gap-2">
             <FormLabel>数量</FormLabel>
             <FormControl>
-              <Input placeholder="请输入数量" type="number" {...field} />
+              <Input placeholder="请输入数量" type="number" step="any" {...field} />
+            </FormControl>
+            <FormMessage />
+          </FormItem>
+        )} />
+        <FormField control={form.control} name="unit_cost" rules={{ validate: (value) => (!value?.trim() || Number(value) >= 0 ? true : "单位成本需为非负数字") }} render={({ field }) => (
+          <FormItem className="grid gap-2">
+            <FormLabel>单位成本</FormLabel>
+            <FormControl>
+              <Input placeholder="留空则按物品当前成本" type="number" min={0} step="0.01" {...field} value={field.value ?? ""} />
+            </FormControl>
+            <FormMessage />
+          </FormItem>
+        )} />
+        <FormField control={form.control} name="expires_at" render={({ field }) => (
+          <FormItem className="grid gap-2">
+            <FormLabel>到期日</FormLabel>
+            <FormControl>
+              <DatePicker value={field.value ?? ""} onChange={field.onChange} defaultNow={false} placeholder="无保质期可不填" />
             </FormControl>
             <FormMessage />
           </FormItem>
@@ -172,7 +187,14 @@ export default function InboundForm({ onClose, form: externalForm }: Props) {
           <FormItem className="grid gap-2">
             <FormLabel>记录人</FormLabel>
             <FormControl>
-              <OperatorPicker value={field.value} onChange={field.onChange} placeholder="选择记录人" />
+              <OperatorPicker
+                value={field.value}
+                onChange={(value) => {
+                  field.onChange(value);
+                  void registerKioskOperator(value);
+                }}
+                placeholder="选择记录人"
+              />
             </FormControl>
             <FormMessage />
           </FormItem>
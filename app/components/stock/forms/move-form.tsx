@@ -13,7 +13,7 @@ import { ImagePicker } from "~/components/common/image-picker";
 import { usePhotoList } from "~/lib/use-photo-list";
 import { useSession } from "~/lib/auth";
 import type { MoveFormValues, SlotPickerValue } from "../types";
-import { getItemListBySlotId } from "../helpers";
+import { getItemListBySlotId, registerKioskOperator } from "../helpers";
 
 import { ConfirmButton } from "~/components/common/confirm-button";
 
@@ -99,7 +99,7 @@ export default function MoveForm({ onClose, form: externalForm  }: Props) {
           <FormItem className="grid gap-2">
             <FormLabel>数量</FormLabel>
             <FormControl>
-              <Input placeholder="请输入数量" type="number" {...field} />
+              <Input placeholder="请输入数量" type="number" step="any" {...field} />
             </FormControl>
             <FormMessage />
           </FormItem>
@@ -108,7 +108,14 @@ export default function MoveForm({ onClose, form: externalForm  }: Props) {
           <FormItem className="grid gap-2">
             <FormLabel>记录人</FormLabel>
             <FormControl>
-              <OperatorPicker value={field.value} onChange={field.onChange} placeholder="选择记录人" />
+              <OperatorPicker
+                value={field.value}
+                onChange={(value) => {
+                  field.onChange(value);
+                  void registerKioskOperator(value);
+                }}
+                placeholder="选择记录人"
+              />
             </FormControl>
             <FormMessage />
           </FormItem>
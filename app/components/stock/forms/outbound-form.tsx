@@ -11,7 +11,10 @@ import { SlotCascaderPicker } from "~/components/common/pickers/slot-cascader-pi
 import { OperatorPicker } from "~/components/common/pickers/operator-picker";
 import { ImagePicker } from "~/components/common/image-picker";
 import { usePhotoList } from "~/lib/use-photo-list";
+import { useIdempotencyKey } from "~/lib/use-idempotency-key";
+import { submitOrQueue } from "~/lib/offline-queue";
 import type { OutboundFormValues, SlotPickerValue } from "../types";
+import { getTxnLocation, registerKioskOperator } from "../helpers";
 
 import { ConfirmButton } from "~/components/common/confirm-button";
 
@@ -29,6 +32,8 @@ export default function OutboundForm({ onClose, form: externalForm }: Props) {
   const setSource = setLocalSource;
   const stockQty = Math.max(0, Number(form.getValues("qty") ?? 0));
   const [outboundQty, setOutboundQty] = useState<number>(0);
+  // 出库已过期库存需管理员确认，后端校验角色
+  const [allowExpired, setAllowExpired] = useState(false);
 
   useEffect(() => {
     // 当库存数量变化时，重置出库数量，避免沿用旧值
@@ -38,19 +43,35 @@ export default function OutboundForm({ onClose, form: externalForm }: Props) {
   }, [stockQty]);
 
   const { paths: selectedPaths, setPaths: setSelectedPaths, reset: resetSelectedPaths } = usePhotoList();
+  const idempotencyKey = useIdempotencyKey();
 
   const submitLocal = async () => {
     try {
       const ok = await form.trigger();
       if (!ok) return false;
-      // const values = form.getValues();
+      const values = form.getValues();
       // 出库数量使用本地 outboundQty，库存上限来自 form.qty
-      // implement outbound submit if needed; placeholder calls
-      // TODO: call tauriInvoke("create_outbound", ...)
-      toast.success("出库提交（本地实现）");
+      const location = await getTxnLocation();
+      const submitted = await submitOrQueue("create_outbound", {
+        input: {
+          item_id: values.item_id,
+          from_slot_id: source.slotId || values.from_slot_id,
+          qty: outboundQty,
+          occurred_at: Math.floor(new Date(values.occurred_at || Date.now()).getTime() / 1000),
+          operator_id: values.operator_id || undefined,
+          note: values.note || null,
+          location,
+          allow_expired: allowExpired,
+          photo_paths: selectedPaths,
+          idempotency_key: idempotencyKey.current(),
+        },
+      }, "出库");
+      idempotencyKey.renew();
+      toast.success(submitted.queued ? "写入暂停，已暂存，恢复后自动补交" : "出库成功");
       if (onClose) onClose();
       form.reset({ item_id: "", from_slot_id: "", qty: 0, occurred_at: "", operator_id: "", note: "" });
       setOutboundQty(0);
+      setAllowExpired(false);
       resetSelectedPaths();
       return true;
     } catch (err) {
@@ -92,7 +113,8 @@ export default function OutboundForm({ onClose, form: externalForm }: Props) {
               <Input
                 placeholder={`最多 ${stockQty}`}
                 type="number"
-                min={1}
+                min={0}
+                step="any"
                 max={stockQty}
                 value={outboundQty}
                 onChange={(event) => {
@@ -113,7 +135,14 @@ export default function OutboundForm({ onClose, form: externalForm }: Props) {
           <FormItem className="grid gap-2">
             <FormLabel>记录人</FormLabel>
             <FormControl>
-              <OperatorPicker value={field.value} onChange={field.onChange} placeholder="选择记录人" />
+              <OperatorPicker
+                value={field.value}
+                onChange={(value) => {
+                  field.onChange(value);
+                  void registerKioskOperator(value);
+                }}
+                placeholder="选择记录人"
+              />
             </FormControl>
             <FormMessage />
           </FormItem>
@@ -135,6 +164,15 @@ export default function OutboundForm({ onClose, form: externalForm }: Props) {
             </FormControl>
           </FormItem>
         )} />
+        <label className="flex items-center gap-2 text-sm md:col-span-2">
+          <input
+            type="checkbox"
+            className="h-4 w-4 accent-slate-900"
+            checked={allowExpired}
+            onChange={(event) => setAllowExpired(event.target.checked)}
+          />
+          允许出库已过期库存（仅管理员）
+        </label>
         <ImagePicker label="图片" photoType="txn" value={selectedPaths} onChange={setSelectedPaths} />
         <div className="grid gap-1 sm:grid-cols-1 md:col-span-2">
           <ConfirmButton className="w-full" label="提交" confirmText="确认提交？" onConfirm={async () => { await submitLocal(); }} />
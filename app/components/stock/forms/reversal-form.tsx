@@ -7,10 +7,12 @@ import { Input } from "~/components/ui/input";
 import { Textarea } from "~/components/ui/textarea";
 import { DateTimePicker } from "~/components/ui/date-time-picker";
 import { OperatorPicker } from "~/components/common/pickers/operator-picker";
+import { ReasonPicker } from "~/components/common/pickers/reason-picker";
 import { ImagePicker } from "~/components/common/image-picker";
 import { usePhotoList } from "~/lib/use-photo-list";
 import { useSession } from "~/lib/auth";
 import type { ReversalFormValues } from "../types";
+import { registerKioskOperator } from "../helpers";
 
 import { ConfirmButton } from "~/components/common/confirm-button";
 
@@ -18,7 +20,7 @@ type Props = { onClose?: () => void; form?: UseFormReturn<ReversalFormValues> };
 
 export default function ReversalForm({ onClose, form: externalForm }: Props) {
   const actorOperatorId = useSession()?.actor_operator_id || "";
-  const form = externalForm ?? useForm<ReversalFormValues>({ defaultValues: { txn_no: "", occurred_at: "", operator_id: actorOperatorId, note: "" } });
+  const form = externalForm ?? useForm<ReversalFormValues>({ defaultValues: { txn_no: "", reason_id: "", occurred_at: "", operator_id: actorOperatorId, note: "" } });
   const { paths: selectedPaths, setPaths: setSelectedPaths, reset: resetSelectedPaths } = usePhotoList();
 
   const submitLocal = async () => {
@@ -29,7 +31,7 @@ export default function ReversalForm({ onClose, form: externalForm }: Props) {
       // placeholder: implement reversal invocation
       toast.success("冲正提交（本地实现）");
       if (onClose) onClose();
-      form.reset({ txn_no: "", occurred_at: "", operator_id: "", note: "" });
+      form.reset({ txn_no: "", reason_id: "", occurred_at: "", operator_id: "", note: "" });
       resetSelectedPaths();
       return true;
     } catch (err) {
@@ -56,6 +58,19 @@ export default function ReversalForm({ onClose, form: externalForm }: Props) {
             </FormItem>
           )}
         />
+        <FormField
+          control={form.control}
+          name="reason_id"
+          render={({ field }) => (
+            <FormItem className="grid gap-2">
+              <FormLabel>原因分类</FormLabel>
+              <FormControl>
+                <ReasonPicker value={field.value} onChange={field.onChange} txnType="REVERSAL" emptyLabel="不填写" />
+              </FormControl>
+              <FormMessage />
+            </FormItem>
+          )}
+        />
         <FormField
           control={form.control}
           name="operator_id"
@@ -64,7 +79,14 @@ export default function ReversalForm({ onClose, form: externalForm }: Props) {
             <FormItem className="grid gap-2">
               <FormLabel>记录人</FormLabel>
               <FormControl>
-                <OperatorPicker value={field.value} onChange={field.onChange} placeholder="选择记录人" />
+                <OperatorPicker
+                value={field.value}
+                onChange={(value) => {
+                  field.onChange(value);
+                  void registerKioskOperator(value);
+                }}
+                placeholder="选择记录人"
+              />
               </FormControl>
               <FormMessage />
             </FormItem>
@@ -1,4 +1,4 @@
-import { tauriInvoke } from "~/lib/tauri";
+import { isMobile, tauriInvoke } from "~/lib/tauri";
 import type { StockSlotItem } from "./types";
 
 export async function getItemListBySlotId(slotId: string): Promise<StockSlotItem[]> {
@@ -17,3 +17,34 @@ export async function getItemListBySlotId(slotId: string): Promise<StockSlotItem
     })),
   );
 }
+
+// 共享终端模式：选择记录人后向后端登记，每笔流水需重新扫码/选择
+export async function registerKioskOperator(operatorId: string) {
+  if (!operatorId) return;
+  try {
+    const settings = await tauriInvoke<{ kiosk_mode: boolean }>("get_settings");
+    if (!settings.kiosk_mode) return;
+    await tauriInvoke("select_kiosk_operator", { input: { operator_id: operatorId } });
+  } catch {
+    // 登记失败时由提交流水时的后端校验提示
+  }
+}
+
+// 流水位置：仅移动端且开启位置采集时获取粗略位置（"纬度,经度"），获取失败不影响提交
+export async function getTxnLocation(): Promise<string | undefined> {
+  if (!isMobile() || typeof navigator === "undefined" || !navigator.geolocation) return undefined;
+  try {
+    const settings = await tauriInvoke<{ txn_location_capture: boolean }>("get_settings");
+    if (!settings.txn_location_capture) return undefined;
+    const position = await new Promise<GeolocationPosition>((resolve, reject) => {
+      navigator.geolocation.getCurrentPosition(resolve, reject, {
+        enableHighAccuracy: false,
+        timeout: 5000,
+        maximumAge: 10 * 60 * 1000,
+      });
+    });
+    return `${position.coords.latitude.toFixed(2)},${position.coords.longitude.toFixed(2)}`;
+  } catch {
+    return undefined;
+  }
+}
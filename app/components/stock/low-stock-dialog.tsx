@@ -0,0 +1,289 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { Tabs, TabsContent, TabsList, TabsTrigger } from "~/components/ui/tabs";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { ItemPicker } from "~/components/common/pickers/item-picker";
+import { WarehousePicker } from "~/components/common/pickers/warehouse-picker";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type LowStockItem = {
+  item_id: string;
+  item_code: string;
+  item_name: string;
+  uom?: string | null;
+  warehouse_id: string;
+  warehouse_name: string;
+  current_qty: number;
+  min_qty: number;
+  max_qty?: number | null;
+  breach: "below_min" | "above_max";
+  severity: number;
+};
+
+type LowStockReport = {
+  generated_at: number;
+  below_min_count: number;
+  above_max_count: number;
+  items: LowStockItem[];
+};
+
+type StockThreshold = {
+  item_id: string;
+  item_code: string;
+  item_name: string;
+  uom?: string | null;
+  warehouse_id: string;
+  warehouse_name: string;
+  min_qty: number;
+  max_qty?: number | null;
+  current_qty: number;
+  updated_by_name?: string | null;
+};
+
+// 低库存报表：按物品分仓阈值比较各仓库库存，偏离程度为低于最低库存的百分比
+export function LowStockDialog({
+  open,
+  onOpenChange,
+  warehouseId,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  warehouseId?: string;
+}) {
+  const [report, setReport] = useState<LowStockReport | null>(null);
+  const [includeAboveMax, setIncludeAboveMax] = useState(false);
+  const [thresholds, setThresholds] = useState<StockThreshold[]>([]);
+  const [itemId, setItemId] = useState("");
+  const [thresholdWarehouseId, setThresholdWarehouseId] = useState("");
+  const [minQty, setMinQty] = useState("");
+  const [maxQty, setMaxQty] = useState("");
+
+  const fetchReport = async () => {
+    try {
+      setReport(
+        await tauriInvoke<LowStockReport>("get_low_stock_report", {
+          input: { warehouse_id: warehouseId || undefined, include_above_max: includeAboveMax },
+        })
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载低库存报表失败";
+      toast.error(message);
+    }
+  };
+
+  const fetchThresholds = async () => {
+    try {
+      setThresholds(await tauriInvoke<StockThreshold[]>("list_stock_thresholds", { input: { warehouse_id: warehouseId || undefined } }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载库存阈值失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchReport();
+  }, [open, warehouseId, includeAboveMax]);
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchThresholds();
+  }, [open, warehouseId]);
+
+  const handleSave = async () => {
+    const min = Number(minQty);
+    const max = maxQty.trim() ? Number(maxQty) : null;
+    if (!itemId || !thresholdWarehouseId) {
+      toast.error("请选择物品与仓库");
+      return;
+    }
+    if (!minQty.trim() || !Number.isFinite(min) || min < 0) {
+      toast.error("请输入有效的最低库存");
+      return;
+    }
+    if (max !== null && (!Number.isFinite(max) || max < min)) {
+      toast.error("最高库存不能低于最低库存");
+      return;
+    }
+    try {
+      await tauriInvoke("set_stock_threshold", {
+        input: { item_id: itemId, warehouse_id: thresholdWarehouseId, min_qty: min, max_qty: max },
+      });
+      toast.success("已保存库存阈值");
+      setMinQty("");
+      setMaxQty("");
+      await Promise.all([fetchThresholds(), fetchReport()]);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleDelete = async (row: StockThreshold) => {
+    try {
+      await tauriInvoke("delete_stock_threshold", { input: { item_id: row.item_id, warehouse_id: row.warehouse_id } });
+      toast.success("已删除");
+      await Promise.all([fetchThresholds(), fetchReport()]);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "删除失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <CommonDialog
+      title="低库存"
+      description="按物品分仓设置最低 / 最高库存，低于最低库存的按缺口比例从高到低排列"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <Tabs defaultValue="report">
+          <TabsList>
+            <TabsTrigger value="report">低库存报表</TabsTrigger>
+            <TabsTrigger value="thresholds">阈值设置</TabsTrigger>
+          </TabsList>
+          <TabsContent value="report" className="space-y-4">
+            <div className="flex flex-wrap items-center gap-3">
+              <label className="flex items-center gap-2 text-sm">
+                <input
+                  type="checkbox"
+                  className="h-4 w-4 accent-slate-900"
+                  checked={includeAboveMax}
+                  onChange={(event) => setIncludeAboveMax(event.target.checked)}
+                />
+                包含超储
+              </label>
+              {report ? (
+                <Badge variant={report.below_min_count > 0 ? "destructive" : "secondary"}>
+                  低于最低 {report.below_min_count} 项{includeAboveMax ? `，超储 ${report.above_max_count} 项` : ""}
+                </Badge>
+              ) : null}
+            </div>
+            <div className="max-h-[60vh] overflow-auto">
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>物品</TableHead>
+                    <TableHead>仓库</TableHead>
+                    <TableHead className="text-right">当前</TableHead>
+                    <TableHead className="text-right">最低/最高</TableHead>
+                    <TableHead className="text-right">偏离</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {(report?.items ?? []).map((row) => (
+                    <TableRow key={`${row.item_id}-${row.warehouse_id}`}>
+                      <TableCell>
+                        {row.item_code} {row.item_name}
+                      </TableCell>
+                      <TableCell>{row.warehouse_name}</TableCell>
+                      <TableCell className="text-right">
+                        {row.current_qty}
+                        {row.uom ? ` ${row.uom}` : ""}
+                      </TableCell>
+                      <TableCell className="text-right">
+                        {row.min_qty} / {row.max_qty ?? "-"}
+                      </TableCell>
+                      <TableCell className="text-right">
+                        <Badge variant={row.breach === "below_min" ? "destructive" : "outline"}>
+                          {row.breach === "below_min" ? "缺" : "超"} {row.severity}%
+                        </Badge>
+                      </TableCell>
+                    </TableRow>
+                  ))}
+                  {report && report.items.length === 0 ? (
+                    <TableRow>
+                      <TableCell colSpan={5} className="text-center text-muted-foreground">
+                        暂无低库存
+                      </TableCell>
+                    </TableRow>
+                  ) : null}
+                </TableBody>
+              </Table>
+            </div>
+          </TabsContent>
+          <TabsContent value="thresholds" className="space-y-4">
+            <div className="grid gap-4 rounded-md border p-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>物品</Label>
+                <ItemPicker value={itemId} onChange={setItemId} />
+              </div>
+              <div className="grid gap-2">
+                <Label>仓库</Label>
+                <WarehousePicker value={thresholdWarehouseId} onChange={setThresholdWarehouseId} />
+              </div>
+              <div className="grid gap-2">
+                <Label>最低库存</Label>
+                <Input type="number" min={0} step="any" value={minQty} onChange={(event) => setMinQty(event.target.value)} />
+              </div>
+              <div className="grid gap-2">
+                <Label>最高库存（可选）</Label>
+                <Input type="number" min={0} step="any" value={maxQty} onChange={(event) => setMaxQty(event.target.value)} />
+              </div>
+              <Button className="md:col-span-2" onClick={() => void handleSave()}>
+                保存阈值
+              </Button>
+            </div>
+            <div className="max-h-[50vh] overflow-auto">
+              <Table>
+                <TableHeader>
+                  <TableRow>
+                    <TableHead>物品</TableHead>
+                    <TableHead>仓库</TableHead>
+                    <TableHead className="text-right">最低/最高</TableHead>
+                    <TableHead className="text-right">当前</TableHead>
+                    <TableHead className="text-right">操作</TableHead>
+                  </TableRow>
+                </TableHeader>
+                <TableBody>
+                  {thresholds.map((row) => (
+                    <TableRow key={`${row.item_id}-${row.warehouse_id}`}>
+                      <TableCell>
+                        {row.item_code} {row.item_name}
+                      </TableCell>
+                      <TableCell>{row.warehouse_name}</TableCell>
+                      <TableCell className="text-right">
+                        {row.min_qty} / {row.max_qty ?? "-"}
+                      </TableCell>
+                      <TableCell className="text-right">{row.current_qty}</TableCell>
+                      <TableCell className="text-right">
+                        <div className="flex justify-end gap-2">
+                          <Button
+                            size="sm"
+                            variant="outline"
+                            onClick={() => {
+                              setItemId(row.item_id);
+                              setThresholdWarehouseId(row.warehouse_id);
+                              setMinQty(String(row.min_qty));
+                              setMaxQty(row.max_qty == null ? "" : String(row.max_qty));
+                            }}
+                          >
+                            编辑
+                          </Button>
+                          <ConfirmButton label="删除" variant="ghost" size="sm" confirmText="确认删除该阈值？" onConfirm={() => handleDelete(row)} />
+                        </div>
+                      </TableCell>
+                    </TableRow>
+                  ))}
+                  {thresholds.length === 0 ? (
+                    <TableRow>
+                      <TableCell colSpan={5} className="text-center text-muted-foreground">
+                        暂未设置库存阈值
+                      </TableCell>
+                    </TableRow>
+                  ) : null}
+                </TableBody>
+              </Table>
+            </div>
+          </TabsContent>
+        </Tabs>
+      }
+    />
+  );
+}
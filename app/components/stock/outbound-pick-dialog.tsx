@@ -0,0 +1,292 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { ItemPicker } from "~/components/common/pickers/item-picker";
+import { SlotCascaderPicker } from "~/components/common/pickers/slot-cascader-picker";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+import type { SlotPickerValue } from "./types";
+import { getTxnLocation } from "./helpers";
+
+type OutboundPick = {
+  id: string;
+  pick_no: string;
+  item_code: string;
+  item_name: string;
+  slot_code: string;
+  planned_qty: number;
+  actual_qty?: number | null;
+  status: "pending" | "confirmed" | "cancelled";
+  note?: string | null;
+  requested_by_name?: string | null;
+  requested_at: number;
+  confirmed_by_name?: string | null;
+  short_reason?: string | null;
+  out_txn_no?: string | null;
+  short_qty?: number | null;
+  adjust_txn_no?: string | null;
+};
+
+type PickConfirmed = {
+  pick_no: string;
+  out_txn_no?: string | null;
+  short_qty: number;
+  adjust_txn_no?: string | null;
+};
+
+type ConfirmForm = {
+  id: string;
+  planned_qty: number;
+  actual_qty: string;
+  short_reason: string;
+  auto_adjust: boolean;
+};
+
+const statusLabels: Record<OutboundPick["status"], string> = {
+  pending: "待拣货",
+  confirmed: "已确认",
+  cancelled: "已取消",
+};
+
+const emptySlot: SlotPickerValue = { warehouseId: "", rackId: "", levelNo: "", slotId: "" };
+
+// 两步出库：先登记计划数量（不扣库存），拣货后按实拣数量确认出库；短拣须填写原因并记录差异
+export function OutboundPickDialog({ open, onOpenChange, onChanged }: { open: boolean; onOpenChange: (open: boolean) => void; onChanged?: () => void }) {
+  const [picks, setPicks] = useState<OutboundPick[]>([]);
+  const [pendingOnly, setPendingOnly] = useState(true);
+  const [itemId, setItemId] = useState("");
+  const [slot, setSlot] = useState<SlotPickerValue>(emptySlot);
+  const [plannedQty, setPlannedQty] = useState("");
+  const [note, setNote] = useState("");
+  const [confirmForm, setConfirmForm] = useState<ConfirmForm | null>(null);
+
+  const fetchPicks = async () => {
+    try {
+      setPicks(await tauriInvoke<OutboundPick[]>("list_outbound_picks", { input: { status: pendingOnly ? "pending" : null } }));
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载拣货单失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchPicks();
+  }, [open, pendingOnly]);
+
+  const handleCreate = async () => {
+    const qty = Number(plannedQty);
+    if (!itemId || !slot.slotId) {
+      toast.error("请选择物品与库位");
+      return;
+    }
+    if (!(qty > 0)) {
+      toast.error("请输入有效的计划数量");
+      return;
+    }
+    try {
+      const result = await tauriInvoke<{ pick_no: string }>("create_outbound_pick", {
+        input: { item_id: itemId, slot_id: slot.slotId, planned_qty: qty, note: note.trim() || null },
+      });
+      toast.success(`已登记拣货单 ${result.pick_no}`);
+      setPlannedQty("");
+      setNote("");
+      await fetchPicks();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "登记失败";
+      toast.error(message);
+    }
+  };
+
+  const handleConfirm = async () => {
+    if (!confirmForm) return;
+    const actualQty = Number(confirmForm.actual_qty);
+    if (!Number.isFinite(actualQty) || actualQty < 0) {
+      toast.error("请输入有效的实拣数量");
+      return;
+    }
+    if (actualQty < confirmForm.planned_qty && !confirmForm.short_reason.trim()) {
+      toast.error("实拣少于计划数量时须填写短拣原因");
+      return;
+    }
+    try {
+      const location = await getTxnLocation();
+      const result = await tauriInvoke<PickConfirmed>("confirm_outbound_pick", {
+        input: {
+          id: confirmForm.id,
+          actual_qty: actualQty,
+          short_reason: confirmForm.short_reason.trim() || null,
+          auto_adjust: confirmForm.auto_adjust,
+          occurred_at: Math.floor(Date.now() / 1000),
+          location,
+        },
+      });
+      toast.success(result.short_qty > 0 ? `已确认，短拣 ${result.short_qty}` : "已确认出库");
+      setConfirmForm(null);
+      await fetchPicks();
+      onChanged?.();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "确认失败";
+      toast.error(message);
+    }
+  };
+
+  const handleCancel = async (id: string) => {
+    try {
+      await tauriInvoke("cancel_outbound_pick", { input: { id } });
+      toast.success("已取消");
+      await fetchPicks();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "取消失败";
+      toast.error(message);
+    }
+  };
+
+  const confirmShort = confirmForm ? Number(confirmForm.actual_qty) < confirmForm.planned_qty : false;
+
+  return (
+    <CommonDialog
+      title="拣货出库"
+      description="先登记计划数量，拣货后按实拣数量确认出库；登记时不扣减库存"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="grid gap-4 rounded-md border p-4 md:grid-cols-2">
+            <div className="grid gap-2">
+              <Label>物品</Label>
+              <ItemPicker value={itemId} onChange={setItemId} />
+            </div>
+            <div className="grid gap-2">
+              <Label>计划数量</Label>
+              <Input type="number" min={0} step="any" value={plannedQty} onChange={(event) => setPlannedQty(event.target.value)} />
+            </div>
+            <div className="md:col-span-2">
+              <SlotCascaderPicker label="拣货库位" value={slot} onChange={setSlot} />
+            </div>
+            <div className="grid gap-2 md:col-span-2">
+              <Label>备注</Label>
+              <Input value={note} onChange={(event) => setNote(event.target.value)} />
+            </div>
+            <Button className="md:col-span-2" onClick={() => void handleCreate()}>
+              登记拣货单
+            </Button>
+          </div>
+          {confirmForm ? (
+            <div className="grid gap-4 rounded-md border p-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>实拣数量（计划 {confirmForm.planned_qty}）</Label>
+                <Input
+                  type="number"
+                  min={0}
+                  step="any"
+                  max={confirmForm.planned_qty}
+                  value={confirmForm.actual_qty}
+                  onChange={(event) => setConfirmForm({ ...confirmForm, actual_qty: event.target.value })}
+                />
+              </div>
+              <div className="grid gap-2">
+                <Label>短拣原因</Label>
+                <Input
+                  value={confirmForm.short_reason}
+                  disabled={!confirmShort}
+                  onChange={(event) => setConfirmForm({ ...confirmForm, short_reason: event.target.value })}
+                />
+              </div>
+              <label className="flex items-center gap-2 text-sm md:col-span-2">
+                <input
+                  type="checkbox"
+                  className="h-4 w-4 accent-slate-900"
+                  checked={confirmForm.auto_adjust}
+                  disabled={!confirmShort}
+                  onChange={(event) => setConfirmForm({ ...confirmForm, auto_adjust: event.target.checked })}
+                />
+                短拣数量自动调整账面库存
+              </label>
+              <div className="flex gap-2 md:col-span-2">
+                <Button onClick={() => void handleConfirm()}>确认出库</Button>
+                <Button variant="outline" onClick={() => setConfirmForm(null)}>
+                  取消
+                </Button>
+              </div>
+            </div>
+          ) : null}
+          <label className="flex items-center gap-2 text-sm">
+            <input type="checkbox" className="h-4 w-4 accent-slate-900" checked={pendingOnly} onChange={(event) => setPendingOnly(event.target.checked)} />
+            仅看待拣货
+          </label>
+          <div className="max-h-[50vh] overflow-auto">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>拣货单</TableHead>
+                  <TableHead>物品</TableHead>
+                  <TableHead>库位</TableHead>
+                  <TableHead className="text-right">计划/实拣</TableHead>
+                  <TableHead>状态</TableHead>
+                  <TableHead className="text-right">操作</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {picks.map((pick) => (
+                  <TableRow key={pick.id}>
+                    <TableCell>
+                      <div>{pick.pick_no}</div>
+                      <div className="text-xs text-muted-foreground">{pick.requested_by_name || "-"}</div>
+                    </TableCell>
+                    <TableCell>
+                      {pick.item_code} {pick.item_name}
+                    </TableCell>
+                    <TableCell>{pick.slot_code}</TableCell>
+                    <TableCell className="text-right">
+                      {pick.planned_qty} / {pick.actual_qty ?? "-"}
+                      {pick.short_qty ? <div className="text-xs text-amber-600">短拣 {pick.short_qty}：{pick.short_reason}</div> : null}
+                    </TableCell>
+                    <TableCell>
+                      <Badge variant={pick.status === "pending" ? "outline" : "secondary"}>{statusLabels[pick.status]}</Badge>
+                      {pick.out_txn_no ? <div className="text-xs text-muted-foreground">{pick.out_txn_no}</div> : null}
+                      {pick.adjust_txn_no ? <div className="text-xs text-muted-foreground">调整 {pick.adjust_txn_no}</div> : null}
+                    </TableCell>
+                    <TableCell className="text-right">
+                      {pick.status === "pending" ? (
+                        <div className="flex justify-end gap-2">
+                          <Button
+                            size="sm"
+                            onClick={() =>
+                              setConfirmForm({
+                                id: pick.id,
+                                planned_qty: pick.planned_qty,
+                                actual_qty: String(pick.planned_qty),
+                                short_reason: "",
+                                auto_adjust: false,
+                              })
+                            }
+                          >
+                            确认
+                          </Button>
+                          <ConfirmButton label="取消" variant="ghost" size="sm" confirmText="确认取消该拣货单？" onConfirm={() => handleCancel(pick.id)} />
+                        </div>
+                      ) : null}
+                    </TableCell>
+                  </TableRow>
+                ))}
+                {picks.length === 0 ? (
+                  <TableRow>
+                    <TableCell colSpan={6} className="text-center text-muted-foreground">
+                      暂无拣货单
+                    </TableCell>
+                  </TableRow>
+                ) : null}
+              </TableBody>
+            </Table>
+          </div>
+        </div>
+      }
+    />
+  );
+}
@@ -0,0 +1,184 @@
+import { useEffect, useState } from "react";
+import { Button } from "~/components/ui/button";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ReasonPicker } from "~/components/common/pickers/reason-picker";
+import { tauriInvoke } from "~/lib/tauri";
+import { useIdempotencyKey } from "~/lib/use-idempotency-key";
+import { submitOrQueue } from "~/lib/offline-queue";
+import { toast } from "sonner";
+import { getTxnLocation } from "./helpers";
+
+export type ScrapTarget = {
+  item_id: string;
+  slot_id: string;
+  label: string;
+  qty: number;
+  available_qty: number;
+};
+
+type ActiveHold = {
+  id: string;
+  hold_no: string;
+  qty: number;
+  reason: string;
+};
+
+type ScrapSettings = {
+  approval_scrap_threshold: number;
+};
+
+const NO_HOLD = "none";
+
+// 报废：破损、过期等库存核销，单独记为报废流水；可直接报废冻结中的库存，报废后该冻结解除
+export function ScrapDialog({
+  open,
+  onOpenChange,
+  target,
+  onChanged,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  target?: ScrapTarget | null;
+  onChanged?: () => void;
+}) {
+  const [settings, setSettings] = useState<ScrapSettings>({ approval_scrap_threshold: 0 });
+  const [holds, setHolds] = useState<ActiveHold[]>([]);
+  const [qty, setQty] = useState("");
+  const [reason, setReason] = useState("");
+  const [holdId, setHoldId] = useState(NO_HOLD);
+  const [note, setNote] = useState("");
+  const idempotencyKey = useIdempotencyKey();
+
+  useEffect(() => {
+    if (!open || !target) return;
+    setQty("");
+    setNote("");
+    setReason("");
+    setHoldId(NO_HOLD);
+    void (async () => {
+      try {
+        const result = await tauriInvoke<ScrapSettings>("get_settings");
+        setSettings(result);
+        setHolds(
+          await tauriInvoke<ActiveHold[]>("list_stock_holds", {
+            input: { item_id: target.item_id, slot_id: target.slot_id },
+          })
+        );
+      } catch (err) {
+        const message = err instanceof Error ? err.message : "加载报废设置失败";
+        toast.error(message);
+      }
+    })();
+  }, [open, target?.item_id, target?.slot_id]);
+
+  const selectedHold = holds.find((hold) => hold.id === holdId);
+  const maxQty = target ? target.available_qty + (selectedHold?.qty ?? 0) : 0;
+
+  const submitApproval = async (value: number) => {
+    if (!target) return;
+    try {
+      await tauriInvoke("submit_approval_request", {
+        input: { kind: "scrap", item_id: target.item_id, slot_id: target.slot_id, qty: value, reason: note || null },
+      });
+      toast.success("审批申请已提交，请等待其他管理员审批");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "提交失败";
+      toast.error(message);
+    }
+  };
+
+  const handleSubmit = async () => {
+    if (!target) return;
+    const value = Number(qty);
+    if (!Number.isFinite(value) || value <= 0) {
+      toast.error("请输入有效数量");
+      return;
+    }
+    if (value > maxQty) {
+      toast.error(`报废数量不能超过可报废库存（${maxQty}）`);
+      return;
+    }
+    try {
+      const location = await getTxnLocation();
+      const submitted = await submitOrQueue("create_scrap", {
+        input: {
+          item_id: target.item_id,
+          from_slot_id: target.slot_id,
+          qty: value,
+          occurred_at: Math.floor(Date.now() / 1000),
+          reason_id: reason || null,
+          hold_id: selectedHold?.id ?? null,
+          note: note || null,
+          location,
+          idempotency_key: idempotencyKey.current(),
+        },
+      }, "报废");
+      idempotencyKey.renew();
+      toast.success(submitted.queued ? "写入暂停，已暂存，恢复后自动补交" : `已报废（${submitted.value}）`);
+      onOpenChange(false);
+      onChanged?.();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "报废失败";
+      // 超过审批阈值时可直接提交申请，审批通过后再次报废同一数量
+      if (settings.approval_scrap_threshold > 0 && value > settings.approval_scrap_threshold) {
+        toast.error(message, {
+          action: { label: "提交审批", onClick: () => void submitApproval(value) },
+        });
+      } else {
+        toast.error(message);
+      }
+    }
+  };
+
+  return (
+    <CommonDialog
+      title="报废"
+      description={target ? `${target.label}，库存 ${target.qty}，可用 ${target.available_qty}` : undefined}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="grid gap-4">
+          <div className="grid gap-2">
+            <Label>报废原因</Label>
+            <ReasonPicker value={reason} onChange={setReason} txnType="SCRAP" placeholder="请选择" />
+          </div>
+          {holds.length > 0 ? (
+            <div className="grid gap-2">
+              <Label>报废冻结库存（报废后解除该冻结）</Label>
+              <Select value={holdId} onValueChange={setHoldId}>
+                <SelectTrigger>
+                  <SelectValue />
+                </SelectTrigger>
+                <SelectContent className="z-[9999]">
+                  <SelectItem value={NO_HOLD}>不使用冻结库存</SelectItem>
+                  {holds.map((hold) => (
+                    <SelectItem key={hold.id} value={hold.id}>
+                      {hold.hold_no}（{hold.qty}）
+                    </SelectItem>
+                  ))}
+                </SelectContent>
+              </Select>
+            </div>
+          ) : null}
+          <div className="grid gap-2">
+            <Label>报废数量</Label>
+            <Input type="number" min={0} step="any" max={maxQty} value={qty} onChange={(event) => setQty(event.target.value)} />
+            {settings.approval_scrap_threshold > 0 ? (
+              <p className="text-xs text-muted-foreground">报废数量超过 {settings.approval_scrap_threshold} 需经另一名管理员审批</p>
+            ) : null}
+          </div>
+          <div className="grid gap-2">
+            <Label>备注</Label>
+            <Input value={note} onChange={(event) => setNote(event.target.value)} placeholder="可选" />
+          </div>
+          <Button variant="destructive" onClick={() => void handleSubmit()}>
+            报废
+          </Button>
+        </div>
+      }
+    />
+  );
+}
@@ -0,0 +1,145 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { tauriInvoke, revealInFolder, isMobile, shareFile } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type StockAgingItem = {
+  item_id: string;
+  item_code: string;
+  item_name: string;
+  uom?: string | null;
+  slot_id: string;
+  slot_code: string;
+  warehouse_name?: string | null;
+  qty: number;
+  value: number;
+  last_in_at?: number | null;
+  last_move_at?: number | null;
+  days_in_stock: number;
+  stale: boolean;
+};
+
+type StockAgingReport = {
+  threshold_days: number;
+  generated_at: number;
+  total: number;
+  stale_count: number;
+  stale_value: number;
+  items: StockAgingItem[];
+};
+
+const formatDay = (value?: number | null) => (value ? new Date(value * 1000).toLocaleDateString() : "-");
+
+// 库龄报表：按最近入库时间计算每个物品/库位的库龄，超过系统设置阈值的标记为呆滞
+export function StockAgingDialog({
+  open,
+  onOpenChange,
+  warehouseId,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  warehouseId?: string;
+}) {
+  const [report, setReport] = useState<StockAgingReport | null>(null);
+  const [staleOnly, setStaleOnly] = useState(false);
+
+  const input = { warehouse_id: warehouseId || undefined, stale_only: staleOnly };
+
+  useEffect(() => {
+    if (!open) return;
+    (async () => {
+      try {
+        setReport(await tauriInvoke<StockAgingReport>("get_stock_aging", { input }));
+      } catch (err) {
+        const message = err instanceof Error ? err.message : "加载库龄失败";
+        toast.error(message);
+      }
+    })();
+  }, [open, warehouseId, staleOnly]);
+
+  const handleExport = async () => {
+    try {
+      const result = await tauriInvoke<{ file_path: string }>("export_stock_aging", { input });
+      if (isMobile()) {
+        await shareFile(result.file_path);
+        toast.success("已打开分享菜单");
+      } else {
+        toast.success("导出成功", {
+          description: result.file_path,
+          action: { label: "打开文件夹", onClick: () => void revealInFolder(result.file_path) },
+        });
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导出失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <CommonDialog
+      title="库龄报表"
+      description={report ? `库龄按最近一次入库（含移入）计算，达到 ${report.threshold_days} 天视为呆滞` : ""}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="flex flex-wrap items-center gap-3">
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                className="h-4 w-4 accent-slate-900"
+                checked={staleOnly}
+                onChange={(event) => setStaleOnly(event.target.checked)}
+              />
+              仅看呆滞库存
+            </label>
+            {report ? (
+              <Badge variant={report.stale_count > 0 ? "destructive" : "secondary"}>
+                呆滞 {report.stale_count} 项，价值 {report.stale_value.toFixed(2)}
+              </Badge>
+            ) : null}
+            <Button variant="outline" size="sm" className="ml-auto" onClick={() => void handleExport()}>
+              导出 CSV
+            </Button>
+          </div>
+          <div className="max-h-[60vh] overflow-auto">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>物品编码</TableHead>
+                  <TableHead>物品</TableHead>
+                  <TableHead>库位</TableHead>
+                  <TableHead className="text-right">数量</TableHead>
+                  <TableHead>最近入库</TableHead>
+                  <TableHead>最近变动</TableHead>
+                  <TableHead className="text-right">库龄（天）</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {(report?.items ?? []).map((row) => (
+                  <TableRow key={`${row.item_id}-${row.slot_id}`}>
+                    <TableCell>{row.item_code}</TableCell>
+                    <TableCell>{row.item_name}</TableCell>
+                    <TableCell>{row.slot_code}</TableCell>
+                    <TableCell className="text-right">
+                      {row.qty}
+                      {row.uom ? ` ${row.uom}` : ""}
+                    </TableCell>
+                    <TableCell>{formatDay(row.last_in_at)}</TableCell>
+                    <TableCell>{formatDay(row.last_move_at)}</TableCell>
+                    <TableCell className="text-right">
+                      {row.stale ? <Badge variant="destructive">{row.days_in_stock}</Badge> : row.days_in_stock}
+                    </TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </div>
+        </div>
+      }
+    />
+  );
+}
@@ -0,0 +1,219 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+export type StockHoldTarget = {
+  item_id: string;
+  slot_id: string;
+  label: string;
+  available_qty: number;
+};
+
+type StockHoldRow = {
+  id: string;
+  hold_no: string;
+  item_id: string;
+  item_code: string;
+  item_name: string;
+  uom?: string | null;
+  slot_id: string;
+  slot_code: string;
+  warehouse_name?: string | null;
+  qty: number;
+  reason: string;
+  note?: string | null;
+  created_by_name?: string | null;
+  created_at: number;
+  released_at?: number | null;
+};
+
+const HOLD_REASON_LABELS: Record<string, string> = {
+  damaged: "破损",
+  pending_qc: "待检",
+  other: "其他",
+};
+
+const formatTime = (timestamp: number) => new Date(timestamp * 1000).toLocaleString("zh-CN");
+
+// 库存冻结：冻结中的数量不可出库、移库或借出，解除后恢复为可用库存
+export function StockHoldDialog({
+  open,
+  onOpenChange,
+  warehouseId,
+  target,
+  onChanged,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  warehouseId?: string;
+  target?: StockHoldTarget | null;
+  onChanged?: () => void;
+}) {
+  const [holds, setHolds] = useState<StockHoldRow[]>([]);
+  const [qty, setQty] = useState("");
+  const [reason, setReason] = useState("pending_qc");
+  const [note, setNote] = useState("");
+
+  const fetchHolds = async () => {
+    try {
+      setHolds(
+        await tauriInvoke<StockHoldRow[]>("list_stock_holds", {
+          input: {
+            warehouse_id: target ? undefined : warehouseId || undefined,
+            item_id: target?.item_id,
+            slot_id: target?.slot_id,
+          },
+        })
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载冻结记录失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    setQty("");
+    setNote("");
+    void fetchHolds();
+  }, [open, warehouseId, target?.item_id, target?.slot_id]);
+
+  const handleHold = async () => {
+    if (!target) return;
+    const value = Number(qty);
+    if (!Number.isFinite(value) || value <= 0) {
+      toast.error("请输入有效数量");
+      return;
+    }
+    if (value > target.available_qty) {
+      toast.error(`冻结数量不能超过可用库存（${target.available_qty}）`);
+      return;
+    }
+    try {
+      const holdNo = await tauriInvoke<string>("hold_stock", {
+        input: { item_id: target.item_id, slot_id: target.slot_id, qty: value, reason, note: note || null },
+      });
+      toast.success(`已冻结（${holdNo}）`);
+      setQty("");
+      setNote("");
+      await fetchHolds();
+      onChanged?.();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "冻结失败";
+      toast.error(message);
+    }
+  };
+
+  const handleRelease = async (row: StockHoldRow) => {
+    try {
+      await tauriInvoke("release_hold", { input: { hold_id: row.id } });
+      toast.success("已解除冻结");
+      await fetchHolds();
+      onChanged?.();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "解除失败";
+      toast.error(message);
+    }
+  };
+
+  return (
+    <CommonDialog
+      title="库存冻结"
+      description={target ? `${target.label}，可用 ${target.available_qty}` : "冻结中的库存不计入可用库存，出库、移库与借出只能使用可用库存"}
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          {target ? (
+            <div className="grid gap-4 rounded-md border p-4 md:grid-cols-3">
+              <div className="grid gap-2">
+                <Label>冻结数量</Label>
+                <Input type="number" min={0} step="any" max={target.available_qty} value={qty} onChange={(event) => setQty(event.target.value)} />
+              </div>
+              <div className="grid gap-2">
+                <Label>原因</Label>
+                <Select value={reason} onValueChange={setReason}>
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent className="z-[9999]">
+                    {Object.entries(HOLD_REASON_LABELS).map(([value, label]) => (
+                      <SelectItem key={value} value={value}>
+                        {label}
+                      </SelectItem>
+                    ))}
+                  </SelectContent>
+                </Select>
+              </div>
+              <div className="grid gap-2">
+                <Label>备注</Label>
+                <Input value={note} onChange={(event) => setNote(event.target.value)} placeholder="可选" />
+              </div>
+              <Button className="md:col-span-3" onClick={() => void handleHold()}>
+                冻结
+              </Button>
+            </div>
+          ) : null}
+          <div className="max-h-[60vh] overflow-auto">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>单号</TableHead>
+                  <TableHead>物品</TableHead>
+                  <TableHead>库位</TableHead>
+                  <TableHead className="text-right">数量</TableHead>
+                  <TableHead>原因</TableHead>
+                  <TableHead>冻结人 / 时间</TableHead>
+                  <TableHead className="text-right">操作</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {holds.map((row) => (
+                  <TableRow key={row.id}>
+                    <TableCell>{row.hold_no}</TableCell>
+                    <TableCell>
+                      {row.item_code} {row.item_name}
+                    </TableCell>
+                    <TableCell>
+                      {row.warehouse_name ? `${row.warehouse_name} / ` : ""}
+                      {row.slot_code}
+                    </TableCell>
+                    <TableCell className="text-right">
+                      {row.qty}
+                      {row.uom ? ` ${row.uom}` : ""}
+                    </TableCell>
+                    <TableCell>
+                      <Badge variant={row.reason === "damaged" ? "destructive" : "secondary"}>{HOLD_REASON_LABELS[row.reason] ?? row.reason}</Badge>
+                      {row.note ? <span className="ml-2 text-xs text-muted-foreground">{row.note}</span> : null}
+                    </TableCell>
+                    <TableCell className="text-xs">
+                      {row.created_by_name || "-"} · {formatTime(row.created_at)}
+                    </TableCell>
+                    <TableCell className="text-right">
+                      <ConfirmButton label="解除" variant="ghost" size="sm" confirmText="确认解除该冻结？" onConfirm={() => handleRelease(row)} />
+                    </TableCell>
+                  </TableRow>
+                ))}
+                {holds.length === 0 ? (
+                  <TableRow>
+                    <TableCell colSpan={7} className="text-center text-muted-foreground">
+                      暂无冻结中的库存
+                    </TableCell>
+                  </TableRow>
+                ) : null}
+              </TableBody>
+            </Table>
+          </div>
+        </div>
+      }
+    />
+  );
+}
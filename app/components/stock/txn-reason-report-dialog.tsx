@@ -0,0 +1,150 @@
+import { useEffect, useState } from "react";
+import { Badge } from "~/components/ui/badge";
+import { Label } from "~/components/ui/label";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type TxnReasonReportItem = {
+  reason_id?: string | null;
+  reason_code?: string | null;
+  reason_name?: string | null;
+  txn_type: string;
+  txn_count: number;
+  item_count: number;
+  total_qty: number;
+};
+
+type TxnReasonReport = {
+  start_at: number;
+  end_at: number;
+  generated_at: number;
+  total_count: number;
+  unreasoned_count: number;
+  items: TxnReasonReportItem[];
+};
+
+const ALL_TYPES = "all";
+
+const TXN_TYPE_LABELS: Record<string, string> = {
+  ADJUST: "调整",
+  REVERSAL: "冲正",
+  SCRAP: "报废",
+};
+
+// 原因统计：按原因汇总调整、冲正、报废流水，时间范围与仓库沿用流水查询的筛选条件
+export function TxnReasonReportDialog({
+  open,
+  onOpenChange,
+  startAt,
+  endAt,
+  warehouseId,
+}: {
+  open: boolean;
+  onOpenChange: (open: boolean) => void;
+  startAt?: number;
+  endAt?: number;
+  warehouseId?: string;
+}) {
+  const [report, setReport] = useState<TxnReasonReport | null>(null);
+  const [txnType, setTxnType] = useState(ALL_TYPES);
+
+  const fetchReport = async () => {
+    try {
+      setReport(
+        await tauriInvoke<TxnReasonReport>("get_txn_reason_report", {
+          input: {
+            start_at: startAt,
+            end_at: endAt,
+            txn_type: txnType === ALL_TYPES ? undefined : txnType,
+            warehouse_id: warehouseId || undefined,
+          },
+        })
+      );
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载原因统计失败";
+      toast.error(message);
+    }
+  };
+
+  useEffect(() => {
+    if (!open) return;
+    void fetchReport();
+  }, [open, startAt, endAt, warehouseId, txnType]);
+
+  return (
+    <CommonDialog
+      title="原因统计"
+      description="调整、冲正、报废流水按原因汇总；未指定时间时统计近 30 天，已冲正的流水不计入"
+      open={open}
+      onOpenChange={onOpenChange}
+      content={
+        <div className="space-y-4">
+          <div className="flex flex-wrap items-center gap-3">
+            <Label>类型</Label>
+            <Select value={txnType} onValueChange={setTxnType}>
+              <SelectTrigger className="w-36">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent className="z-[9999]">
+                <SelectItem value={ALL_TYPES}>全部</SelectItem>
+                {Object.entries(TXN_TYPE_LABELS).map(([value, label]) => (
+                  <SelectItem key={value} value={value}>
+                    {label}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+            {report ? (
+              <Badge variant={report.unreasoned_count > 0 ? "destructive" : "secondary"}>
+                共 {report.total_count} 笔，未填写原因 {report.unreasoned_count} 笔
+              </Badge>
+            ) : null}
+          </div>
+          <div className="max-h-[60vh] overflow-auto">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>原因</TableHead>
+                  <TableHead>类型</TableHead>
+                  <TableHead className="text-right">流水数</TableHead>
+                  <TableHead className="text-right">物品数</TableHead>
+                  <TableHead className="text-right">数量合计</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {(report?.items ?? []).map((row) => (
+                  <TableRow key={`${row.reason_id ?? "none"}-${row.txn_type}`}>
+                    <TableCell>
+                      {row.reason_name ? (
+                        <>
+                          {row.reason_name}
+                          <span className="ml-1 text-xs text-slate-500">{row.reason_code}</span>
+                        </>
+                      ) : (
+                        <span className="text-muted-foreground">未填写原因</span>
+                      )}
+                    </TableCell>
+                    <TableCell>{TXN_TYPE_LABELS[row.txn_type] ?? row.txn_type}</TableCell>
+                    <TableCell className="text-right">{row.txn_count}</TableCell>
+                    <TableCell className="text-right">{row.item_count}</TableCell>
+                    <TableCell className="text-right">{row.total_qty}</TableCell>
+                  </TableRow>
+                ))}
+                {report && report.items.length === 0 ? (
+                  <TableRow>
+                    <TableCell colSpan={5} className="text-center text-muted-foreground">
+                      暂无数据
+                    </TableCell>
+                  </TableRow>
+                ) : null}
+              </TableBody>
+            </Table>
+          </div>
+        </div>
+      }
+    />
+  );
+}
@@ -65,6 +65,10 @@ export type InboundFormValues = {
   item_id: string;
   to_slot_id: string;
   qty: string;
+  // 入库单位成本，留空则按物品当前成本记录
+  unit_cost?: string;
+  // 到期日（YYYY-MM-DD），有保质期或校准有效期的物品填写
+  expires_at?: string;
   occurred_at: string;
   operator_id: string;
   note: string;
@@ -93,6 +97,8 @@ export type CountFormValues = {
   item_id: string;
   slot_id: string;
   actual_qty: string;
+  // 差异原因（原因字典 id），账实有差异时按设置必填
+  reason_id: string;
   occurred_at: string;
   operator_id: string;
   note: string;
@@ -100,6 +106,8 @@ export type CountFormValues = {
 
 export type ReversalFormValues = {
   txn_no: string;
+  // 冲正原因（原因字典 id）
+  reason_id: string;
   occurred_at: string;
   operator_id: string;
   note: string;
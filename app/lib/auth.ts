@@ -4,6 +4,8 @@ export type Session = {
   actor_operator_id: string
   must_change_pwd: boolean
   username?: string
+  // 登录会话 id，用于退出登录与会话心跳
+  session_id?: string
 }
 
 const SESSION_KEY = "inventory.session"
@@ -0,0 +1,133 @@
+import { getSession } from "./auth";
+import { isMobile, tauriInvoke, TauriError } from "./tauri";
+
+// 移动端暂存提交：存储迁移、只读维护或数据库繁忙导致写入暂停时，把流水提交暂存在本机，写入恢复后自动补交
+// 补交依赖流水的幂等键，暂存前已实际写入的提交不会重复记账
+
+const STORAGE_KEY = "offline_txn_queue";
+const CHANGE_EVENT = "offline-queue-change";
+
+// 允许暂存的命令：仅流水类提交，均支持幂等键
+const QUEUEABLE_COMMANDS = new Set([
+  "create_inbound",
+  "create_outbound",
+  "create_move",
+  "create_quick_transfer",
+  "create_count",
+  "create_scrap",
+]);
+
+export type QueuedEntry = {
+  id: string;
+  cmd: string;
+  // 提交时的说明，如“入库”
+  label: string;
+  args: Record<string, unknown>;
+  queued_at: number;
+  // pending：等待补交；conflict：补交被拒绝，需人工处理
+  status: "pending" | "conflict";
+  error?: string;
+};
+
+export type ReplayResult = {
+  replayed: number;
+  conflicts: number;
+  remaining: number;
+};
+
+export type SubmitResult = { queued: false; value: string } | { queued: true };
+
+// 写入暂停类错误：可在稍后原样重试
+export function isWriteLockedError(err: unknown) {
+  if (!(err instanceof TauriError)) return false;
+  return err.code === "READ_ONLY_MODE" || err.code === "DB_BUSY" || err.messageKey === "error.migrating";
+}
+
+export function loadQueue(): QueuedEntry[] {
+  if (typeof window === "undefined") return [];
+  try {
+    const raw = window.localStorage.getItem(STORAGE_KEY);
+    return raw ? (JSON.parse(raw) as QueuedEntry[]) : [];
+  } catch {
+    return [];
+  }
+}
+
+function saveQueue(entries: QueuedEntry[]) {
+  window.localStorage.setItem(STORAGE_KEY, JSON.stringify(entries));
+  window.dispatchEvent(new Event(CHANGE_EVENT));
+}
+
+export function subscribeQueue(listener: () => void) {
+  window.addEventListener(CHANGE_EVENT, listener);
+  window.addEventListener("storage", listener);
+  return () => {
+    window.removeEventListener(CHANGE_EVENT, listener);
+    window.removeEventListener("storage", listener);
+  };
+}
+
+// 提交流水；移动端写入暂停时暂存并返回 queued
+export async function submitOrQueue(cmd: string, args: Record<string, unknown>, label: string): Promise<SubmitResult> {
+  // 固定记录时的操作人，补交时不随当前登录人变化
+  const operatorId = getSession()?.actor_operator_id;
+  const payload = operatorId && !("actorOperatorId" in args) ? { ...args, actorOperatorId: operatorId } : args;
+  try {
+    return { queued: false, value: await tauriInvoke<string>(cmd, payload) };
+  } catch (err) {
+    if (!isMobile() || !QUEUEABLE_COMMANDS.has(cmd) || !isWriteLockedError(err)) {
+      throw err;
+    }
+    const input = (payload.input ?? {}) as Record<string, unknown>;
+    const entry: QueuedEntry = {
+      id: crypto.randomUUID(),
+      cmd,
+      label,
+      // 未带幂等键的提交补一个，保证补交最多记账一次
+      args: { ...payload, input: { ...input, idempotency_key: input.idempotency_key ?? crypto.randomUUID() } },
+      queued_at: Date.now(),
+      status: "pending",
+    };
+    saveQueue([...loadQueue(), entry]);
+    return { queued: true };
+  }
+}
+
+let replaying = false;
+
+// 按暂存顺序补交；仍处于写入暂停时保留剩余条目，其他错误标记为冲突供人工处理
+export async function replayQueue(): Promise<ReplayResult> {
+  const result: ReplayResult = { replayed: 0, conflicts: 0, remaining: 0 };
+  if (replaying) return result;
+  replaying = true;
+  try {
+    for (const entry of loadQueue()) {
+      if (entry.status !== "pending") continue;
+      try {
+        await tauriInvoke<string>(entry.cmd, entry.args);
+        saveQueue(loadQueue().filter((item) => item.id !== entry.id));
+        result.replayed += 1;
+      } catch (err) {
+        if (isWriteLockedError(err) || (err instanceof TauriError && err.code === "SESSION_EXPIRED")) {
+          break;
+        }
+        const message = err instanceof Error ? err.message : "补交失败";
+        saveQueue(loadQueue().map((item): QueuedEntry => (item.id === entry.id ? { ...item, status: "conflict", error: message } : item)));
+        result.conflicts += 1;
+      }
+    }
+  } finally {
+    replaying = false;
+  }
+  result.remaining = loadQueue().filter((item) => item.status === "pending").length;
+  return result;
+}
+
+// 冲突条目重新排队（如补足库存后重试）
+export function retryEntry(id: string) {
+  saveQueue(loadQueue().map((item): QueuedEntry => (item.id === id ? { ...item, status: "pending", error: undefined } : item)));
+}
+
+export function discardEntry(id: string) {
+  saveQueue(loadQueue().filter((item) => item.id !== id));
+}
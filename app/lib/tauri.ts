@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import { getSession } from "./auth";
+import { clearSession, getSession } from "./auth";
 
 // 平台检测
 export const isMobile = () => {
@@ -27,20 +27,40 @@ export async function shareFile(filePath: string) {
   }
 }
 
+// 保留后端错误码与消息键，供调用方区分可重试的错误（如写入暂停）
+export class TauriError extends Error {
+  code?: string;
+  messageKey?: string;
+
+  constructor(message: string, code?: string, messageKey?: string) {
+    super(message);
+    this.name = "TauriError";
+    this.code = code;
+    this.messageKey = messageKey;
+  }
+}
+
 function normalizeTauriError(err: unknown): Error {
   const raw = err instanceof Error ? err.message : typeof err === "string" ? err : err && typeof err === "object" && "message" in err ? String((err as { message?: unknown }).message) : "";
+  const source = err && typeof err === "object" ? (err as { code?: unknown; message_key?: unknown }) : {};
+  const code = typeof source.code === "string" ? source.code : undefined;
+  const messageKey = typeof source.message_key === "string" ? source.message_key : undefined;
   if (raw) {
     try {
-      const parsed = JSON.parse(raw) as { message?: unknown };
+      const parsed = JSON.parse(raw) as { message?: unknown; code?: unknown; message_key?: unknown };
       if (parsed?.message) {
-        return new Error(String(parsed.message));
+        return new TauriError(
+          String(parsed.message),
+          typeof parsed.code === "string" ? parsed.code : code,
+          typeof parsed.message_key === "string" ? parsed.message_key : messageKey,
+        );
       }
     } catch {
       // ignore json parse errors
     }
-    return new Error(raw);
+    return new TauriError(raw, code, messageKey);
   }
-  return new Error("操作失败");
+  return new TauriError("操作失败", code, messageKey);
 }
 
 export async function tauriInvoke<T>(cmd: string, args?: Record<string, unknown>) {
@@ -63,11 +83,19 @@ export async function tauriInvoke<T>(cmd: string, args?: Record<string, unknown>
         if (!Object.prototype.hasOwnProperty.call(cloned, "actorOperatorId")) {
           (cloned as any).actorOperatorId = operatorId;
         }
+        // 后端按 sessionId 校验当前会话（空闲超时、强制下线）
+        if (session.session_id && !Object.prototype.hasOwnProperty.call(cloned, "sessionId")) {
+          (cloned as any).sessionId = session.session_id;
+        }
         args = cloned;
       }
     }
     return await invoke<T>(cmd, args);
   } catch (err) {
+    // 会话空闲超时或被强制结束：清除本地会话，路由守卫随即返回登录页
+    if (err && typeof err === "object" && (err as { code?: unknown }).code === "SESSION_EXPIRED") {
+      clearSession();
+    }
     throw normalizeTauriError(err);
   }
 }
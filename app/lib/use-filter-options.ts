@@ -0,0 +1,35 @@
+import { useEffect, useState } from "react"
+import { tauriInvoke } from "~/lib/tauri"
+
+export type FilterOption = {
+  value: string
+  count: number
+}
+
+type FilterOptionsResult = {
+  entity: string
+  field: string
+  options: FilterOption[]
+}
+
+// 按实际数据加载筛选下拉选项（后端短时缓存）；enabled 为 false 时不请求，例如弹窗未打开
+export function useFilterOptions(entity: string, field: string, enabled = true) {
+  const [options, setOptions] = useState<FilterOption[]>([])
+
+  useEffect(() => {
+    if (!enabled) return
+    let cancelled = false
+    tauriInvoke<FilterOptionsResult>("get_filter_options", { input: { entity, field } })
+      .then((result) => {
+        if (!cancelled) setOptions(result.options)
+      })
+      .catch(() => {
+        if (!cancelled) setOptions([])
+      })
+    return () => {
+      cancelled = true
+    }
+  }, [entity, field, enabled])
+
+  return options
+}
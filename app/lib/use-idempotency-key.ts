@@ -0,0 +1,22 @@
+import { useCallback, useRef } from "react"
+
+// 提交幂等键：同一次填写内的重复提交（双击、重试）复用同一个键，提交成功后换新
+export function useIdempotencyKey() {
+  const keyRef = useRef<string | null>(null)
+
+  const current = useCallback(() => {
+    if (!keyRef.current) {
+      keyRef.current = crypto.randomUUID()
+    }
+    return keyRef.current
+  }, [])
+
+  const renew = useCallback(() => {
+    keyRef.current = null
+  }, [])
+
+  return {
+    current,
+    renew,
+  }
+}
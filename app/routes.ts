@@ -10,6 +10,7 @@ export default [
       route("items", "routes/items.tsx"),
       route("stock", "routes/stock.tsx"),
       route("txns", "routes/txns.tsx"),
+      route("loans", "routes/loans.tsx"),
       route("operators", "routes/operators.tsx"),
       route("audit", "routes/audit.tsx"),
       route("settings", "routes/settings.tsx"),
@@ -1,14 +1,39 @@
-import { useState } from "react";
+import { useEffect, useState } from "react";
+import { useNavigate } from "react-router";
+import { listen } from "@tauri-apps/api/event";
 import { ForceChangePasswordDialog } from "~/components/auth/force-change-password-dialog";
 import { AppShell } from "~/components/layout/app-shell";
 import { getSession, updateSession, type Session } from "~/lib/auth";
+import { tauriInvoke } from "~/lib/tauri";
 
 export default function AppLayout() {
+  const navigate = useNavigate();
   const [session, setSessionState] = useState<Session | null>(() => {
     if (typeof window === "undefined") return null;
     return getSession();
   });
 
+  // 深度链接（invctl://txn/...、invctl://item/...）：进入主界面时及收到事件时拉取待跳转路径
+  useEffect(() => {
+    const openPending = async () => {
+      try {
+        const path = await tauriInvoke<string | null>("take_pending_deep_link");
+        if (path) {
+          navigate(path);
+        }
+      } catch {
+        // 忽略深度链接错误，不影响正常使用
+      }
+    };
+    void openPending();
+    const unlisten = listen("deep_link", () => {
+      void openPending();
+    });
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, [navigate]);
+
   const handlePasswordChanged = () => {
     updateSession({ must_change_pwd: false });
     const current = getSession();
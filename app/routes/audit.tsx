@@ -8,6 +8,7 @@ import { Label } from "~/components/ui/label";
 import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
 import { Pagination, PaginationContent, PaginationItem, PaginationNext, PaginationPrevious } from "~/components/ui/pagination";
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { OperatorPicker } from "~/components/common/pickers/operator-picker";
 import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "~/components/ui/dialog";
 import { getSession } from "~/lib/auth";
 import { tauriInvoke } from "~/lib/tauri";
@@ -36,30 +37,79 @@ type AuditExportResult = {
   file_path: string;
 };
 
+type AuditArchiveResult = {
+  file_path?: string | null;
+  archived: number;
+};
+
+type AuditArchiveSearchResult = AuditListResult & {
+  scanned_files: number;
+};
+
 const actionLabels: Record<string, string> = {
   AUTH_LOGIN: "登录",
   AUTH_LOGOUT: "退出登录",
   AUTH_CHANGE_PASSWORD: "修改密码",
   AUTH_RESET_PASSWORD: "重置密码",
+  AUTH_ISSUE_RESET_CODE: "签发重置码",
+  AUTH_RESET_PASSWORD_WITH_CODE: "重置码改密",
   OPERATOR_LIST: "查询人员",
   OPERATOR_CREATE: "新增人员",
   OPERATOR_UPDATE: "更新人员",
+  OPERATOR_PROFILE_VIEW: "查看个人信息",
+  OPERATOR_PROFILE_UPDATE: "修改个人信息",
   OPERATOR_STATUS: "人员状态变更",
+  OPERATOR_ROLE_PREVIEW: "预览角色调整",
+  OPERATOR_ROLE_BULK_UPDATE: "批量调整角色",
+  ROLE_CAPABILITY_LIST: "查看角色能力",
+  ROLE_CAPABILITY_UPDATE: "修改角色能力",
+  SESSION_LIST: "查看在线会话",
+  SESSION_TERMINATE: "强制下线",
+  OPERATOR_PERMISSION_SIMULATE: "权限模拟",
   WAREHOUSE_LIST: "查询仓库",
   WAREHOUSE_CREATE: "新增仓库",
   WAREHOUSE_UPDATE: "更新仓库",
   WAREHOUSE_STATUS: "仓库状态变更",
+  WAREHOUSE_UTILIZATION: "查看库位利用率",
+  STRUCTURE_TREE_VIEW: "查看仓库结构树",
   RACK_LIST: "查询货架",
   RACK_CREATE: "新增货架",
+  RACK_CLONE: "复制货架",
   RACK_UPDATE: "更新货架",
   RACK_STATUS: "货架状态变更",
   SLOT_LIST: "查询库位",
   SLOT_REGEN: "重建库位",
   SLOT_STATUS: "库位状态变更",
+  SLOT_BULK_STATUS: "批量变更库位状态",
+  SLOT_RECODE: "库位重新编码",
   ITEM_LIST: "查询物品",
+  ITEM_DETAIL_VIEW: "查看物品详情",
   ITEM_CREATE: "新增物品",
   ITEM_UPDATE: "更新物品",
   ITEM_STATUS: "物品状态变更",
+  ITEM_BULK_STATUS: "批量变更物品状态",
+  ITEM_BULK_UPDATE: "批量编辑物品",
+  ITEM_MERGE: "合并物品",
+  ITEM_CODE_CHANGE: "修改物品编码",
+  ITEM_ALIAS_LIST: "查看物品别名",
+  ITEM_ALIAS_CREATE: "新增物品别名",
+  ITEM_ALIAS_UPDATE: "修改物品别名",
+  ITEM_ALIAS_DELETE: "删除物品别名",
+  SCAN_RESOLVE: "扫码解析",
+  ITEM_ATTRIBUTE_DEF_LIST: "查看物品属性定义",
+  ITEM_ATTRIBUTE_DEF_CREATE: "新增物品属性",
+  ITEM_ATTRIBUTE_DEF_UPDATE: "修改物品属性",
+  ITEM_ATTRIBUTE_DEF_DELETE: "删除物品属性",
+  REASON_CODE_LIST: "查看原因字典",
+  REASON_CODE_CREATE: "新增原因",
+  REASON_CODE_UPDATE: "修改原因",
+  REASON_CODE_DELETE: "删除原因",
+  UNDO_CHANGE: "撤销修改",
+  APPROVAL_LIST: "查看审批申请",
+  APPROVAL_SUBMIT: "提交审批申请",
+  APPROVAL_APPROVE: "审批通过",
+  APPROVAL_REJECT: "审批驳回",
+  NOTIFICATION_MARK_READ: "通知已读",
   MEDIA_ATTACHMENT_ITEM_ADD: "上传媒体附件（物品图片）",
   MEDIA_ATTACHMENT_ITEM_LIST: "查询媒体附件（物品图片）",
   MEDIA_ATTACHMENT_ITEM_REMOVE: "删除媒体附件（物品图片）",
@@ -73,24 +123,101 @@ const actionLabels: Record<string, string> = {
   TXN_INBOUND: "入库",
   TXN_OUTBOUND: "出库",
   TXN_MOVE: "移库",
+  TXN_QUICK_TRANSFER: "快捷调拨",
   TXN_COUNT: "盘点",
+  TXN_SCRAP: "报废",
   TXN_REVERSAL: "冲正",
   TXN_LIST: "查询流水",
   SYSTEM_SETTINGS_UPDATE: "系统设置更新",
+  SYSTEM_READ_ONLY_UPDATE: "切换只读维护模式",
   SYSTEM_SETTINGS_READ: "读取系统设置",
   SYSTEM_STORAGE_ROOT_CHANGE: "存储目录迁移",
+  MEDIA_STORAGE_SCAN: "照片存储巡检",
+  MEDIA_STORAGE_METRIC_LIST: "查看照片巡检记录",
+  PERFORMANCE_STATS_VIEW: "查看命令耗时统计",
   AUDIT_LIST: "查询审计日志",
   AUDIT_EXPORT: "导出审计日志",
+  AUDIT_ARCHIVE: "归档审计日志",
+  AUDIT_ARCHIVE_SEARCH: "检索审计归档",
   STOCK_LIST_BY_SLOT: "按库位查询库存",
   STOCK_LIST_BY_ITEM: "按物品查询库存",
   STOCK_EXPORT: "导出库存",
+  STOCK_VALUATION: "查看库存计价",
+  STOCK_RECALCULATE: "按流水重算库存",
+  STOCK_CORRECT: "库存修正",
+  STOCK_CLOSE: "库存日结",
+  STOCK_ASOF: "查询历史库存",
+  LOAN_CREATE: "借出",
+  LOAN_RETURN: "归还",
+  LOAN_LIST: "查询借用",
+  OUTBOUND_PICK_CREATE: "登记拣货单",
+  OUTBOUND_PICK_CONFIRM: "拣货确认",
+  OUTBOUND_PICK_CANCEL: "取消拣货单",
+  OUTBOUND_PICK_LIST: "查询拣货单",
+  ASN_CREATE: "登记到货预报",
+  ASN_RECEIVE: "按预报收货",
+  ASN_CLOSE: "到货预报结单",
+  ASN_CANCEL: "取消到货预报",
+  ASN_LIST: "查询到货预报",
+  ASN_VARIANCE_REPORT: "收货差异报表",
   DB_BACKUP: "数据库备份",
   DB_RESTORE: "数据库恢复",
+  DB_VERIFY: "数据库健康检查",
+  DB_QUERY_PLAN_CHECK: "查询索引自检",
+  DB_REPAIR: "数据库修复",
+  DIAGNOSTICS_COLLECT: "导出诊断包",
+  REMOTE_BACKUP_CONFIG_UPDATE: "远程备份配置",
+  REMOTE_BACKUP_LIST: "查看远程备份",
+  BACKUP_ENCRYPTION_UPDATE: "备份加密设置",
+  BACKUP_ENCRYPT_EXISTING: "加密已有备份",
+  DEMO_DATA_GENERATE: "生成演示数据",
   ITEM_EXPORT: "导出物品",
   ITEM_IMPORT: "导入物品",
   TXN_EXPORT: "导出流水",
   TXN_IMPORT: "导入流水",
+  STRUCTURE_IMPORT: "导入仓库结构",
+  MASTER_BUNDLE_EXPORT: "导出主数据包",
+  MASTER_BUNDLE_IMPORT: "导入主数据包",
+  OPENING_STOCK_IMPORT: "导入期初库存",
+  COUNT_SHEET_EXPORT: "导出盘点表",
+  COUNT_SHEET_IMPORT: "导入盘点表",
+  PICK_LIST_PRINT: "打印拣货单",
+  LABEL_TEMPLATE_LIST: "查看标签模板",
+  LABEL_TEMPLATE_CREATE: "新增标签模板",
+  LABEL_TEMPLATE_UPDATE: "修改标签模板",
+  LABEL_TEMPLATE_DELETE: "删除标签模板",
+  LABEL_PRINT: "打印标签",
+  REPORT_ITEM_STOCK_TREND: "查看库存走势",
+  REPORT_STOCK_AGING: "查看库龄报表",
+  REPORT_STOCK_AGING_EXPORT: "导出库龄报表",
+  REPORT_LOW_STOCK: "查看低库存报表",
+  REPORT_EXPIRING_STOCK: "查看临期库存报表",
+  REPORT_TXN_REASONS: "查看原因统计",
+  STOCK_THRESHOLD_LIST: "查询库存阈值",
+  STOCK_THRESHOLD_SET: "设置库存阈值",
+  STOCK_THRESHOLD_DELETE: "删除库存阈值",
+  STOCK_HOLD_LIST: "查询库存冻结",
+  STOCK_HOLD_CREATE: "冻结库存",
+  STOCK_HOLD_RELEASE: "解除库存冻结",
+  CYCLE_COUNT_PLAN_LIST: "查看循环盘点计划",
+  CYCLE_COUNT_PLAN_CREATE: "新建循环盘点计划",
+  CYCLE_COUNT_PLAN_UPDATE: "修改循环盘点计划",
+  CYCLE_COUNT_PLAN_DELETE: "删除循环盘点计划",
+  CYCLE_COUNT_TASK_LIST: "查看今日盘点任务",
+  CYCLE_COUNT_TASK_SKIP: "跳过盘点任务",
+  REPORT_SCHEDULE_CREATE: "新增定时报表",
+  REPORT_SCHEDULE_UPDATE: "更新定时报表",
+  REPORT_SCHEDULE_DELETE: "删除定时报表",
+  REPORT_SCHEDULE_RUN: "执行定时报表",
+  SMTP_CONFIG_UPDATE: "邮件服务器配置",
+  SMTP_TEST_SEND: "发送测试邮件",
   DASHBOARD_OVERVIEW: "仪表盘概览",
+  RACK_MAP_VIEW: "查看货架占用图",
+  RACK_HEATMAP_VIEW: "查看货架热力图",
+  RACK_MAP_EXPORT: "导出货架占用图",
+  TXN_COPY: "复制流水摘要",
+  STOCK_COPY: "复制库存表格",
+  KIOSK_OPERATOR_SELECT: "登记终端记录人",
 };
 
 const getActionLabel = (action: string) => actionLabels[action] ?? action;
@@ -100,6 +227,8 @@ export default function AuditPage() {
   const [loading, setLoading] = useState(false);
   const [actionFilter, setActionFilter] = useState("all");
   const [keyword, setKeyword] = useState("");
+  const [operatorFilter, setOperatorFilter] = useState("");
+  const [resultFilter, setResultFilter] = useState("all");
 
   const formatDate = (d: Date) => d.toISOString().slice(0, 10);
   const today = new Date();
@@ -112,19 +241,50 @@ export default function AuditPage() {
   const [pageIndex, setPageIndex] = useState(1);
   const [pageSize] = useState(20);
   const [total, setTotal] = useState(0);
+  // 数据来源：live 为库内日志，archive 为已归档文件
+  const [source, setSource] = useState("live");
+  const [archiveOpen, setArchiveOpen] = useState(false);
+  const [archiveBefore, setArchiveBefore] = useState<string>(formatDate(new Date(today.getTime() - 90 * 24 * 60 * 60 * 1000)));
+  const [archiving, setArchiving] = useState(false);
 
-  const fetchLogs = async (action?: string, page = pageIndex) => {
+  // 列表与导出共用同一组筛选条件
+  const buildFilters = (action?: string) => {
     const trimmedKeyword = keyword.trim();
     const startAt = startDate ? Math.floor(new Date(`${startDate}T00:00:00`).getTime() / 1000) : undefined;
     const endAt = endDate ? Math.floor(new Date(`${endDate}T23:59:59`).getTime() / 1000) : undefined;
+    return {
+      action,
+      keyword: trimmedKeyword || undefined,
+      operator_id: operatorFilter || undefined,
+      result: resultFilter === "all" ? undefined : resultFilter,
+      start_at: startAt,
+      end_at: endAt,
+    };
+  };
+
+  const fetchLogs = async (action?: string, page = pageIndex) => {
     setLoading(true);
     try {
+      if (source === "archive") {
+        // 归档检索不支持按动作与结果筛选，其余条件与列表一致
+        const { keyword: archiveKeyword, operator_id, start_at, end_at } = buildFilters();
+        const result = await tauriInvoke<AuditArchiveSearchResult>("search_audit_archives", {
+          input: {
+            keyword: archiveKeyword,
+            operator_id,
+            start_at,
+            end_at,
+            page_index: page,
+            page_size: pageSize,
+          },
+        });
+        setRows(result.items);
+        setTotal(result.total);
+        return;
+      }
       const result = await tauriInvoke<AuditListResult>("list_audit_logs", {
         input: {
-          action,
-          keyword: trimmedKeyword || undefined,
-          start_at: startAt,
-          end_at: endAt,
+          ...buildFilters(action),
           page_index: page,
           page_size: pageSize,
         },
@@ -141,7 +301,7 @@ export default function AuditPage() {
 
   useEffect(() => {
     fetchLogs(actionFilter === "all" ? undefined : actionFilter, pageIndex);
-  }, [pageIndex]);
+  }, [pageIndex, source]);
 
   const openDetail = (row: AuditRow) => {
     setActiveRow(row);
@@ -150,8 +310,10 @@ export default function AuditPage() {
 
   const handleExport = async () => {
     try {
-      const result = await tauriInvoke<AuditExportResult>("export_audit_logs");
-      
+      const result = await tauriInvoke<AuditExportResult>("export_audit_logs", {
+        input: buildFilters(actionFilter === "all" ? undefined : actionFilter),
+      });
+
       // 在移动端使用分享功能，桌面端显示文件路径
       const { isMobile, shareFile } = await import("~/lib/tauri");
       if (isMobile()) {
@@ -166,19 +328,69 @@ export default function AuditPage() {
     }
   };
 
+  const handleArchive = async () => {
+    if (!archiveBefore) {
+      toast.error("请选择归档截止日期");
+      return;
+    }
+    setArchiving(true);
+    try {
+      const result = await tauriInvoke<AuditArchiveResult>("archive_audit_logs", {
+        input: {
+          before_at: Math.floor(new Date(`${archiveBefore}T00:00:00`).getTime() / 1000),
+        },
+      });
+      if (result.archived > 0) {
+        toast.success(`已归档 ${result.archived} 条：${result.file_path}`);
+      } else {
+        toast.success("没有需要归档的日志");
+      }
+      setArchiveOpen(false);
+      await fetchLogs(actionFilter === "all" ? undefined : actionFilter, pageIndex);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "归档失败";
+      toast.error(message);
+    } finally {
+      setArchiving(false);
+    }
+  };
+
   return (
     <div className="space-y-6">
       <PageHeader
         title="操作日志"
         description="审计所有关键操作，支持导出与详情查看。"
         actions={
-          <Button variant="outline" onClick={handleExport}>
-            导出日志
-          </Button>
+          <div className="flex gap-2">
+            <Button variant="outline" onClick={() => setArchiveOpen(true)}>
+              归档日志
+            </Button>
+            <Button variant="outline" onClick={handleExport} disabled={source === "archive"}>
+              导出日志
+            </Button>
+          </div>
         }
       />
 
       <div className="flex flex-wrap items-end gap-3 rounded-2xl border border-slate-200/70 bg-slate-50/70 p-4">
+        <div className="min-w-[120px] space-y-2">
+          <Label>数据源</Label>
+          <Select
+            value={source}
+            onValueChange={(value) => {
+              setSource(value);
+              setPageIndex(1);
+            }}
+          >
+            <SelectTrigger>
+              <SelectValue placeholder="请选择" />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="live">当前日志</SelectItem>
+              <SelectItem value="archive">归档文件</SelectItem>
+            </SelectContent>
+          </Select>
+        </div>
         <div className="min-w-[140px] w-[140px] max-w-[140px] flex-1 space-y-2">
           <Label>搜索</Label>
           <Input placeholder="动作/对象/用户" value={keyword} onChange={(event) => setKeyword(event.target.value)} />
@@ -193,7 +405,7 @@ export default function AuditPage() {
         </div>
         <div className="flex-1 space-y-2">
           <Label>动作</Label>
-          <Select value={actionFilter} onValueChange={setActionFilter}>
+          <Select value={actionFilter} onValueChange={setActionFilter} disabled={source === "archive"}>
             <SelectTrigger>
               <SelectValue placeholder="请选择" />
             </SelectTrigger>
@@ -207,6 +419,23 @@ export default function AuditPage() {
             </SelectContent>
           </Select>
         </div>
+        <div className="min-w-[160px] space-y-2">
+          <Label>操作人</Label>
+          <OperatorPicker value={operatorFilter} onChange={(v) => setOperatorFilter(v || "")} placeholder="全部" />
+        </div>
+        <div className="min-w-[120px] space-y-2">
+          <Label>结果</Label>
+          <Select value={resultFilter} onValueChange={setResultFilter} disabled={source === "archive"}>
+            <SelectTrigger>
+              <SelectValue placeholder="请选择" />
+            </SelectTrigger>
+            <SelectContent>
+              <SelectItem value="all">全部</SelectItem>
+              <SelectItem value="success">成功</SelectItem>
+              <SelectItem value="fail">失败</SelectItem>
+            </SelectContent>
+          </Select>
+        </div>
         <Button
           variant="outline"
           onClick={() => {
@@ -221,6 +450,8 @@ export default function AuditPage() {
           onClick={() => {
             setActionFilter("all");
             setKeyword("");
+            setOperatorFilter("");
+            setResultFilter("all");
             setStartDate("");
             setEndDate("");
             setPageIndex(1);
@@ -299,6 +530,23 @@ export default function AuditPage() {
           </PaginationContent>
         </Pagination>
       ) : null}
+      <Dialog open={archiveOpen} onOpenChange={setArchiveOpen}>
+        <DialogContent className="max-w-md">
+          <DialogHeader>
+            <DialogTitle>归档日志</DialogTitle>
+            <DialogDescription>截止日期之前的审计日志将压缩写入归档文件并从数据库移除，归档后可切换数据源检索。</DialogDescription>
+          </DialogHeader>
+          <div className="space-y-4">
+            <div className="space-y-2">
+              <Label>归档截止日期（不含当天）</Label>
+              <DatePicker value={archiveBefore} onChange={setArchiveBefore} />
+            </div>
+            <Button className="w-full" onClick={handleArchive} disabled={archiving}>
+              {archiving ? "归档中..." : "开始归档"}
+            </Button>
+          </div>
+        </DialogContent>
+      </Dialog>
       <Dialog open={detailOpen} onOpenChange={setDetailOpen}>
         <DialogContent className="max-w-2xl">
           <DialogHeader>
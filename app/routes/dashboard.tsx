@@ -19,44 +19,108 @@ type DashboardTxnCounts = {
   outbound: number
   move_count: number
   count_count: number
+  scrap: number
   reversal: number
 }
 
+type DashboardItemOnLoan = {
+  item_id: string
+  item_code: string
+  item_name: string
+  loan_count: number
+  qty: number
+  overdue_qty: number
+}
+
+type TrendGranularity = "day" | "week" | "month"
+
 type DashboardTrendPoint = {
   day: string
   inbound: number
   outbound: number
   move_count: number
   count_count: number
+  scrap: number
 }
 
 type DashboardWarehouseStock = {
   warehouse_code?: string | null
   warehouse_name?: string | null
   total_qty: number
+  total_value: number
+  value_percent: number
+}
+
+type DashboardOperatorRank = {
+  operator_id: string
+  operator_name: string
+  txn_count: number
+  total_qty: number
 }
 
 type DashboardOverview = {
   today: DashboardTxnCounts
+  period: DashboardTxnCounts
+  range_start: number
+  range_end: number
+  granularity: TrendGranularity
   total_stock_qty: number
+  total_stock_value: number
+  yesterday_close_qty?: number | null
+  yesterday_close_value?: number | null
   active_items: number
   active_racks: number
   active_warehouses: number
   negative_stock: number
+  stale_stock: number
+  expired_stock: number
+  expiring_stock: number
+  slot_fill_rate: number
+  loans_open: number
+  loans_overdue: number
+  items_on_loan: DashboardItemOnLoan[]
   trend: DashboardTrendPoint[]
   stock_by_warehouse: DashboardWarehouseStock[]
+  operator_leaderboard?: DashboardOperatorRank[] | null
 }
 
+const rangeOptions: { value: string; label: string; granularity: TrendGranularity; days?: number }[] = [
+  { value: "7d", label: "近 7 天", granularity: "day", days: 7 },
+  { value: "30d", label: "近 30 天", granularity: "day", days: 30 },
+  { value: "12w", label: "近 12 周", granularity: "week" },
+  { value: "12m", label: "近 12 个月", granularity: "month" },
+]
+
+// 按日的区间从本地零点起算；按周/按月由后端取默认跨度
+const buildRangeQuery = (option: (typeof rangeOptions)[number]) => {
+  if (!option.days) return { granularity: option.granularity }
+  const start = new Date()
+  start.setHours(0, 0, 0, 0)
+  start.setDate(start.getDate() - (option.days - 1))
+  return { granularity: option.granularity, start_at: Math.floor(start.getTime() / 1000) }
+}
+
+// 与昨日日结对比的变化量，昨日尚未日结时不展示
+const formatCloseDiff = (current: number, close?: number | null) => {
+  if (close === null || close === undefined) return null
+  const diff = Math.round((current - close) * 1000) / 1000
+  return `较昨日日结 ${diff > 0 ? "+" : ""}${diff}`
+}
+
+const formatValue = (value: number) =>
+  value.toLocaleString("zh-CN", { minimumFractionDigits: 2, maximumFractionDigits: 2 })
+
 export default function DashboardPage() {
   const navigate = useNavigate()
   const [overview, setOverview] = useState<DashboardOverview | null>(null)
   const [loading, setLoading] = useState(false)
+  const [range, setRange] = useState(rangeOptions[0])
 
   const fetchOverview = async () => {
     setLoading(true)
     try {
       const result = await tauriInvoke<DashboardOverview>("get_dashboard_overview", {
-        query: {  },
+        query: buildRangeQuery(range),
       })
       setOverview(result)
     } catch (err) {
@@ -69,14 +133,14 @@ export default function DashboardPage() {
 
   useEffect(() => {
     fetchOverview()
-  }, [])
+  }, [range])
 
   const primaryCards = useMemo(() => {
     if (!overview) return []
     return [
       { title: "今日入库", value: overview.today.inbound },
       { title: "今日出库", value: overview.today.outbound },
-      { title: "库存总量", value: overview.total_stock_qty },
+      { title: "库存总量", value: overview.total_stock_qty, hint: formatCloseDiff(overview.total_stock_qty, overview.yesterday_close_qty) },
       { title: "库存异常", value: overview.negative_stock },
     ]
   }, [overview])
@@ -87,19 +151,28 @@ export default function DashboardPage() {
       { title: "移库次数", value: overview.today.move_count },
       { title: "盘点批次", value: overview.today.count_count },
       { title: "冲正次数", value: overview.today.reversal },
+      { title: "今日报废", value: overview.today.scrap },
+      { title: `${range.label}入库`, value: overview.period.inbound },
+      { title: `${range.label}出库`, value: overview.period.outbound },
+      { title: "库存价值", value: formatValue(overview.total_stock_value) },
       { title: "启用物品", value: overview.active_items },
       { title: "启用货架", value: overview.active_racks },
       { title: "启用仓库", value: overview.active_warehouses },
+      { title: "库位利用率", value: `${overview.slot_fill_rate.toFixed(1)}%` },
+      { title: "呆滞库存", value: overview.stale_stock },
+      { title: "临期库存", value: overview.expiring_stock },
+      { title: "已过期库存", value: overview.expired_stock },
     ]
-  }, [overview])
+  }, [overview, range])
 
-  const formatDayLabel = (value: string) => value.slice(5).replace("-", "/")
+  const formatDayLabel = (value: string, granularity: TrendGranularity) =>
+    granularity === "month" ? value.slice(0, 7).replace("-", "/") : value.slice(5).replace("-", "/")
 
   const trendData = useMemo(() => {
     if (!overview) return []
     return overview.trend.map((point) => ({
       ...point,
-      day_label: formatDayLabel(point.day),
+      day_label: formatDayLabel(point.day, overview.granularity),
     }))
   }, [overview])
 
@@ -111,6 +184,8 @@ export default function DashboardPage() {
           ? `${row.warehouse_code} · ${row.warehouse_name}`
           : row.warehouse_name || "未分配",
       total_qty: row.total_qty,
+      total_value: row.total_value,
+      value_percent: row.value_percent,
     }))
   }, [overview])
 
@@ -148,8 +223,21 @@ export default function DashboardPage() {
   return (
     <div className="space-y-8">
       <div className="flex flex-wrap items-center justify-between gap-4">
-        <div>
+        <div className="flex flex-wrap items-center gap-3">
           <p className="text-sm text-slate-500">今日概览</p>
+          <div className="flex gap-1">
+            {rangeOptions.map((option) => (
+              <Button
+                key={option.value}
+                size="sm"
+                variant={option.value === range.value ? "secondary" : "ghost"}
+                onClick={() => setRange(option)}
+                disabled={loading}
+              >
+                {option.label}
+              </Button>
+            ))}
+          </div>
         </div>
         <div className="flex flex-wrap gap-2">
           <Button variant="secondary" onClick={() => navigate("/stock")}>
@@ -178,6 +266,7 @@ export default function DashboardPage() {
                   {card.title}
                 </CardTitle>
                 <p className="text-3xl font-semibold text-slate-900">{card.value}</p>
+                {"hint" in card && card.hint ? <p className="text-xs text-slate-500">{card.hint}</p> : null}
               </CardHeader>
             </Card>
           ))
@@ -204,7 +293,7 @@ export default function DashboardPage() {
         <div className="grid gap-6">
           <Card className="border-slate-200/70 bg-white">
             <CardHeader>
-              <CardTitle>近 7 天入库 / 出库趋势</CardTitle>
+              <CardTitle>{range.label}入库 / 出库 / 报废趋势</CardTitle>
             </CardHeader>
             <CardContent>
               {overview && trendData.length > 0 ? (
@@ -213,6 +302,7 @@ export default function DashboardPage() {
                   config={{
                     inbound: { label: "入库", color: "rgb(var(--chart-1))" },
                     outbound: { label: "出库", color: "rgb(var(--chart-2))" },
+                    scrap: { label: "报废", color: "rgb(var(--chart-5))" },
                   }}
                 >
                   <BarChart data={trendData} margin={{ left: 0, right: 8 }}>
@@ -226,6 +316,7 @@ export default function DashboardPage() {
                     <ChartLegend content={<ChartLegendContent />} />
                     <Bar dataKey="inbound" fill="var(--color-inbound)" radius={[4, 4, 0, 0]} />
                     <Bar dataKey="outbound" fill="var(--color-outbound)" radius={[4, 4, 0, 0]} />
+                    <Bar dataKey="scrap" fill="var(--color-scrap)" radius={[4, 4, 0, 0]} />
                   </BarChart>
                 </ChartContainer>
               ) : (
@@ -263,6 +354,18 @@ export default function DashboardPage() {
               ) : (
                 <p className="text-sm text-slate-500">暂无库存数据</p>
               )}
+              {stockData.length > 0 ? (
+                <div className="grid gap-2 text-sm">
+                  {stockData.map((row) => (
+                    <div key={row.label} className="flex items-center justify-between">
+                      <span className="text-slate-600">{row.label}</span>
+                      <span className="text-slate-900">
+                        {row.total_qty} · ¥{formatValue(row.total_value)} · {row.value_percent.toFixed(1)}%
+                      </span>
+                    </div>
+                  ))}
+                </div>
+              ) : null}
             </CardContent>
           </Card>
         </div>
@@ -284,6 +387,59 @@ export default function DashboardPage() {
               )}
             </CardContent>
           </Card>
+          {overview?.operator_leaderboard ? (
+            <Card className="border-slate-200/70 bg-white">
+              <CardHeader>
+                <CardTitle>{range.label}人员作业排行</CardTitle>
+              </CardHeader>
+              <CardContent className="grid gap-3 text-sm">
+                {overview.operator_leaderboard.length === 0 ? (
+                  <p className="text-sm text-slate-500">暂无作业记录</p>
+                ) : (
+                  overview.operator_leaderboard.map((rank, index) => (
+                    <div key={rank.operator_id} className="flex items-center justify-between">
+                      <span className="text-slate-500">
+                        {index + 1}. {rank.operator_name}
+                      </span>
+                      <span className="font-semibold text-slate-900">
+                        {rank.txn_count} 笔 · {rank.total_qty} 件
+                      </span>
+                    </div>
+                  ))
+                )}
+              </CardContent>
+            </Card>
+          ) : null}
+          <Card className="border-slate-200/70 bg-white">
+            <CardHeader>
+              <CardTitle>借出中物品</CardTitle>
+            </CardHeader>
+            <CardContent className="grid gap-3 text-sm">
+              <div className="flex items-center justify-between">
+                <span className="text-slate-500">借出中 / 已逾期</span>
+                <span className="font-semibold text-slate-900">
+                  {overview?.loans_open ?? 0} / <span className={overview?.loans_overdue ? "text-red-600" : ""}>{overview?.loans_overdue ?? 0}</span>
+                </span>
+              </div>
+              {overview && overview.items_on_loan.length > 0 ? (
+                overview.items_on_loan.map((item) => (
+                  <div key={item.item_id} className="flex items-center justify-between">
+                    <span className="text-slate-500">
+                      {item.item_code} {item.item_name}
+                    </span>
+                    <span className="font-semibold text-slate-900">
+                      {item.qty} 件{item.overdue_qty > 0 ? <span className="text-red-600">（逾期 {item.overdue_qty}）</span> : null}
+                    </span>
+                  </div>
+                ))
+              ) : (
+                <p className="text-sm text-slate-500">暂无借出</p>
+              )}
+              <Button variant="link" className="justify-start px-0" onClick={() => navigate("/loans")}>
+                查看借用记录
+              </Button>
+            </CardContent>
+          </Card>
           <Card className="border-slate-200/70 bg-white">
             <CardHeader>
               <CardTitle>预警清单</CardTitle>
@@ -1,11 +1,13 @@
 import { useEffect, useMemo, useState } from "react"
-import { useNavigate } from "react-router"
+import { useNavigate, useSearchParams } from "react-router"
 import { useForm } from "react-hook-form"
+import { CartesianGrid, Line, LineChart, XAxis, YAxis } from "recharts"
 import { PageHeader } from "~/components/common/page-header"
 import { ImagePicker } from "~/components/common/image-picker"
 import { Badge } from "~/components/ui/badge"
 import { Button } from "~/components/ui/button"
 import { ConfirmButton } from "~/components/common/confirm-button"
+import { ChartContainer, ChartTooltip, ChartTooltipContent } from "~/components/ui/chart"
 import {
   Dialog,
   DialogContent,
@@ -41,6 +43,14 @@ import { ChevronDownIcon } from "lucide-react"
 import { getSession } from "~/lib/auth"
 import { tauriInvoke } from "~/lib/tauri"
 import { usePhotoList } from "~/lib/use-photo-list"
+import { useFilterOptions } from "~/lib/use-filter-options"
+import { LabelPrintDialog } from "~/components/labels/label-print-dialog"
+import { SortableHead, sortParams, type SortState } from "~/components/common/sortable-head"
+import { BulkEditDialog } from "~/components/items/bulk-edit-dialog"
+import { MergeItemDialog } from "~/components/items/merge-item-dialog"
+import { ChangeCodeDialog } from "~/components/items/change-code-dialog"
+import { ItemAliasDialog } from "~/components/items/item-alias-dialog"
+import { ItemAttributeDialog, type ItemAttributeDef } from "~/components/items/item-attribute-dialog"
 import { toast } from "sonner"
 
 type ItemRow = {
@@ -50,10 +60,15 @@ type ItemRow = {
   model?: string | null
   spec?: string | null
   uom?: string | null
+  unit_cost?: number | null
+  currency?: string | null
   stock_qty: number
+  qty_precision: number
   status: string
   remark?: string | null
   created_at: number
+  // 自定义属性取值：属性 ID → 值
+  attributes?: Record<string, string>
 }
 
 type ItemListResult = {
@@ -73,10 +88,52 @@ type PhotoListResult = {
   items: PhotoRow[]
 }
 
+type ItemStockTrendPoint = {
+  day: string
+  qty: number
+  increase: number
+  decrease: number
+}
+
+type ItemStockTrend = {
+  item_id: string
+  item_code: string
+  item_name: string
+  range: string
+  bucket: string
+  opening_qty: number
+  current_qty: number
+  points: ItemStockTrendPoint[]
+}
+
+const TREND_RANGE_OPTIONS = [
+  { value: "1m", label: "近 1 个月" },
+  { value: "3m", label: "近 3 个月" },
+  { value: "6m", label: "近 6 个月" },
+  { value: "1y", label: "近 1 年" },
+  { value: "2y", label: "近 2 年" },
+]
+
+const QTY_PRECISION_OPTIONS = [
+  { value: "0", label: "整数" },
+  { value: "1", label: "1 位小数" },
+  { value: "2", label: "2 位小数" },
+  { value: "3", label: "3 位小数" },
+]
+
+const TREND_BUCKET_OPTIONS = [
+  { value: "day", label: "按日" },
+  { value: "week", label: "按周" },
+  { value: "month", label: "按月" },
+]
+
 type SettingsDto = {
   storage_root: string
 }
 
+// 常用单位，与已在用的单位合并展示
+const DEFAULT_UOMS = ["件", "台", "箱", "套", "个"]
+
 const generateItemCode = () => {
   const stamp = new Date().toISOString().replace(/[-:TZ.]/g, "").slice(0, 14)
   return `T${stamp}`
@@ -88,20 +145,25 @@ type ItemFormValues = {
   model: string
   spec: string
   uom: string
+  unit_cost: string
+  currency: string
+  qty_precision: string
   remark: string
 }
 
 export default function ItemsPage() {
   const navigate = useNavigate()
+  const [searchParams] = useSearchParams()
   const [rows, setRows] = useState<ItemRow[]>([])
   const [loading, setLoading] = useState(false)
-  const [keyword, setKeyword] = useState("")
+  const [keyword, setKeyword] = useState(searchParams.get("keyword") || "")
   const [status, setStatus] = useState("all")
   const [formOpen, setFormOpen] = useState(false)
   const [editRow, setEditRow] = useState<ItemRow | null>(null)
   const [pageIndex, setPageIndex] = useState(1)
   const [pageSize] = useState(20)
   const [total, setTotal] = useState(0)
+  const [sort, setSort] = useState<SortState>(null)
   const {
     paths: selectedPhotoPaths,
     setPaths: setSelectedPhotoPaths,
@@ -110,6 +172,20 @@ export default function ItemsPage() {
   const [photoRows, setPhotoRows] = useState<PhotoRow[]>([])
   const [photoLoading, setPhotoLoading] = useState(false)
   const [storageRoot, setStorageRoot] = useState("")
+  const [trendRow, setTrendRow] = useState<ItemRow | null>(null)
+  const [labelRow, setLabelRow] = useState<ItemRow | null>(null)
+  const [mergeRow, setMergeRow] = useState<ItemRow | null>(null)
+  const [codeRow, setCodeRow] = useState<ItemRow | null>(null)
+  const [aliasRow, setAliasRow] = useState<ItemRow | null>(null)
+  const [attributeDefs, setAttributeDefs] = useState<ItemAttributeDef[]>([])
+  const [attributeValues, setAttributeValues] = useState<Record<string, string>>({})
+  const [attributeDialogOpen, setAttributeDialogOpen] = useState(false)
+  const [selectedIds, setSelectedIds] = useState<string[]>([])
+  const [bulkEditOpen, setBulkEditOpen] = useState(false)
+  const [trendRange, setTrendRange] = useState("6m")
+  const [trendBucket, setTrendBucket] = useState("week")
+  const [trend, setTrend] = useState<ItemStockTrend | null>(null)
+  const [trendLoading, setTrendLoading] = useState(false)
   const form = useForm<ItemFormValues>({
     defaultValues: {
       code: "",
@@ -117,6 +193,9 @@ export default function ItemsPage() {
       model: "",
       spec: "",
       uom: "",
+      unit_cost: "",
+      currency: "",
+      qty_precision: "0",
       remark: "",
     },
   })
@@ -129,10 +208,12 @@ export default function ItemsPage() {
           keyword: keywordValue || undefined,
           page_index: page,
           page_size: pageSize,
+          ...sortParams(sort),
         },
       })
       setRows(result.items)
       setTotal(result.total)
+      setSelectedIds([])
     } catch (err) {
       const message = err instanceof Error ? err.message : "加载失败"
       toast.error(message)
@@ -141,12 +222,77 @@ export default function ItemsPage() {
     }
   }
 
+  const fetchAttributeDefs = async () => {
+    try {
+      setAttributeDefs(await tauriInvoke<ItemAttributeDef[]>("list_item_attribute_defs", { input: {} }))
+    } catch {
+      setAttributeDefs([])
+    }
+  }
+
+  useEffect(() => {
+    void fetchAttributeDefs()
+  }, [])
+
+  // 深度链接跳转时页面可能已打开，需同步 URL 中的检索词
+  useEffect(() => {
+    const keywordParam = searchParams.get("keyword")
+    if (keywordParam !== null) {
+      setKeyword(keywordParam)
+      setPageIndex(1)
+    }
+  }, [searchParams])
+
   useEffect(() => {
     const timer = window.setTimeout(() => {
       void fetchItems(keyword, pageIndex)
     }, 300)
     return () => window.clearTimeout(timer)
-  }, [pageIndex, keyword])
+  }, [pageIndex, keyword, sort])
+
+  const handleSortChange = (next: SortState) => {
+    setSort(next)
+    setPageIndex(1)
+  }
+
+  // 库存走势：切换物品、范围或粒度时重新加载
+  useEffect(() => {
+    if (!trendRow) return
+    let cancelled = false
+    const loadTrend = async () => {
+      setTrendLoading(true)
+      try {
+        const result = await tauriInvoke<ItemStockTrend>("item_stock_trend", {
+          input: { item_id: trendRow.id, range: trendRange, bucket: trendBucket },
+        })
+        if (!cancelled) setTrend(result)
+      } catch (err) {
+        const message = err instanceof Error ? err.message : "加载失败"
+        toast.error(message)
+      } finally {
+        if (!cancelled) setTrendLoading(false)
+      }
+    }
+    void loadTrend()
+    return () => {
+      cancelled = true
+    }
+  }, [trendRow, trendRange, trendBucket])
+
+  const usedUoms = useFilterOptions("item", "uom", formOpen)
+  const uomOptions = useMemo(
+    () => Array.from(new Set([...usedUoms.map((option) => option.value), ...DEFAULT_UOMS])),
+    [usedUoms]
+  )
+
+  const trendData = useMemo(
+    () =>
+      (trend?.points || []).map((point) => ({
+        ...point,
+        day_label: trendBucket === "month" ? point.day.slice(0, 7) : point.day.slice(5),
+      })),
+    [trend, trendBucket]
+  )
 
   const resetForm = () => {
     form.reset({
@@ -155,8 +301,12 @@ export default function ItemsPage() {
       model: "",
       spec: "",
       uom: "",
+      unit_cost: "",
+      currency: "",
+      qty_precision: "0",
       remark: "",
     })
+    setAttributeValues({})
     resetSelectedPhotoPaths()
     setPhotoRows([])
   }
@@ -176,8 +326,12 @@ export default function ItemsPage() {
       model: row.model || "",
       spec: row.spec || "",
       uom: row.uom || "",
+      unit_cost: row.unit_cost != null ? String(row.unit_cost) : "",
+      currency: row.currency || "",
+      qty_precision: String(row.qty_precision ?? 0),
       remark: row.remark || "",
     })
+    setAttributeValues(row.attributes ?? {})
     resetSelectedPhotoPaths()
     setFormOpen(true)
     fetchPhotos(row.id)
@@ -189,6 +343,18 @@ export default function ItemsPage() {
     const model = values.model.trim()
     const spec = values.spec.trim()
     const uom = values.uom.trim()
+    const unitCostText = values.unit_cost.trim()
+    const unitCost = unitCostText ? Number(unitCostText) : null
+    if (unitCost !== null && (!Number.isFinite(unitCost) || unitCost < 0)) {
+      toast.error("单位成本需为非负数字")
+      return
+    }
+    const currency = values.currency.trim().toUpperCase()
+    if (currency && !/^[A-Z]{3}$/.test(currency)) {
+      toast.error("币种需为 3 位字母代码，如 CNY")
+      return
+    }
+    const qtyPrecision = Number(values.qty_precision)
     const remark = values.remark.trim()
     // if (!model.trim()) {
     //   toast.error("请输入设备型号")
@@ -208,7 +374,11 @@ export default function ItemsPage() {
             model: model || null,
             spec: spec || null,
             uom: uom || null,
+            unit_cost: unitCost,
+            currency: currency || null,
+            qty_precision: qtyPrecision,
             remark: remark || null,
+            attributes: attributeValues,
           },
         })
         toast.success("物品更新成功")
@@ -220,7 +390,11 @@ export default function ItemsPage() {
             model: model || null,
             spec: spec || null,
             uom: uom || null,
+            unit_cost: unitCost,
+            currency: currency || null,
+            qty_precision: qtyPrecision,
             remark: remark || null,
+            attributes: attributeValues,
           },
         })
         toast.success("物品创建成功")
@@ -266,11 +440,50 @@ export default function ItemsPage() {
     }
   }
 
+  const handleBulkStatus = async (nextStatus: "active" | "inactive") => {
+    try {
+      const result = await tauriInvoke<{ updated: number }>("set_items_status", {
+        input: {
+          ids: selectedIds,
+          status: nextStatus,
+        },
+      })
+      toast.success(`已${nextStatus === "active" ? "启用" : "停用"} ${result.updated} 个物品`)
+      await fetchItems(keyword)
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "批量更新失败"
+      toast.error(message)
+    }
+  }
+
+  const toggleSelected = (id: string, checked: boolean) => {
+    setSelectedIds((prev) => (checked ? [...prev, id] : prev.filter((item) => item !== id)))
+  }
+
   const handleFilter = async () => {
     setPageIndex(1)
     await fetchItems(keyword, 1)
   }
 
+  // 导出物品目录：沿用当前搜索与排序，含库存合计、图片数、创建日期与各仓库阈值
+  const handleExport = async () => {
+    try {
+      const result = await tauriInvoke<{ file_path: string }>("export_items", {
+        input: { keyword: keyword || undefined, ...sortParams(sort) },
+      })
+      const { isMobile, shareFile } = await import("~/lib/tauri")
+      if (isMobile()) {
+        await shareFile(result.file_path)
+        toast.success("已打开分享菜单")
+      } else {
+        toast.success(`导出成功：${result.file_path}`)
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导出失败"
+      toast.error(message)
+    }
+  }
+
   const filteredRows =
     status === "all" ? rows : rows.filter((row) => row.status === status)
 
@@ -360,6 +573,12 @@ export default function ItemsPage() {
         actions={
           <div className="flex flex-wrap gap-2">
             <Button variant="outline">批量导入</Button>
+            <Button variant="outline" onClick={() => void handleExport()}>
+              导出
+            </Button>
+            <Button variant="outline" onClick={() => setAttributeDialogOpen(true)}>
+              字段设置
+            </Button>
             <Dialog open={formOpen} onOpenChange={setFormOpen}>
               <DialogTrigger asChild>
                 <Button onClick={openCreate}>新增物品</Button>
@@ -469,7 +688,7 @@ export default function ItemsPage() {
                                     align="end"
                                     className="[--radius:0.95rem] z-[9999]"
                                   >
-                                    {["件", "台", "箱", "套", "个"].map((option) => (
+                                    {uomOptions.map((option) => (
                                       <DropdownMenuItem
                                         key={option}
                                         onClick={() => field.onChange(option)}
@@ -485,6 +704,60 @@ export default function ItemsPage() {
                         </FormItem>
                       )}
                     />
+                    <FormField
+                      control={form.control}
+                      name="unit_cost"
+                      render={({ field }) => (
+                        <FormItem className="grid gap-2">
+                          <FormLabel htmlFor="item-unit-cost">单位成本</FormLabel>
+                          <FormControl>
+                            <Input
+                              id="item-unit-cost"
+                              type="number"
+                              min={0}
+                              step="0.01"
+                              placeholder="用于计算库存价值，可留空"
+                              {...field}
+                            />
+                          </FormControl>
+                        </FormItem>
+                      )}
+                    />
+                    <FormField
+                      control={form.control}
+                      name="currency"
+                      render={({ field }) => (
+                        <FormItem className="grid gap-2">
+                          <FormLabel htmlFor="item-currency">币种</FormLabel>
+                          <FormControl>
+                            <Input id="item-currency" maxLength={3} placeholder="如 CNY，可留空" {...field} />
+                          </FormControl>
+                        </FormItem>
+                      )}
+                    />
+                    <FormField
+                      control={form.control}
+                      name="qty_precision"
+                      render={({ field }) => (
+                        <FormItem className="grid gap-2">
+                          <FormLabel>数量小数位数</FormLabel>
+                          <Select value={field.value} onValueChange={field.onChange}>
+                            <FormControl>
+                              <SelectTrigger>
+                                <SelectValue />
+                              </SelectTrigger>
+                            </FormControl>
+                            <SelectContent className="z-[9999]">
+                              {QTY_PRECISION_OPTIONS.map((option) => (
+                                <SelectItem key={option.value} value={option.value}>
+                                  {option.label}
+                                </SelectItem>
+                              ))}
+                            </SelectContent>
+                          </Select>
+                        </FormItem>
+                      )}
+                    />
                     <FormField
                       control={form.control}
                       name="remark"
@@ -501,6 +774,22 @@ export default function ItemsPage() {
                         </FormItem>
                       )}
                     />
+                    {attributeDefs.map((def) => (
+                      <div key={def.id} className="grid gap-2">
+                        <Label htmlFor={`item-attr-${def.id}`}>
+                          {def.name}
+                          {def.required ? <span className="ml-1 text-red-500">*</span> : null}
+                        </Label>
+                        <Input
+                          id={`item-attr-${def.id}`}
+                          type={def.data_type === "number" ? "number" : def.data_type === "date" ? "date" : "text"}
+                          value={attributeValues[def.id] ?? ""}
+                          onChange={(event) =>
+                            setAttributeValues((prev) => ({ ...prev, [def.id]: event.target.value }))
+                          }
+                        />
+                      </div>
+                    ))}
                     <ImagePicker
                       photoType="item"
                       value={selectedPhotoPaths}
@@ -581,27 +870,89 @@ export default function ItemsPage() {
         </Button>
       </div>
 
+      {selectedIds.length > 0 ? (
+        <div className="flex flex-wrap items-center gap-3 rounded-2xl border border-slate-200/70 bg-white px-4 py-3 text-sm">
+          <span className="text-slate-600">已选 {selectedIds.length} 个物品</span>
+          <ConfirmButton
+            label="批量启用"
+            variant="outline"
+            size="sm"
+            confirmText={`确认启用选中的 ${selectedIds.length} 个物品？`}
+            onConfirm={() => handleBulkStatus("active")}
+          />
+          <ConfirmButton
+            label="批量停用"
+            variant="outline"
+            size="sm"
+            confirmText={`确认停用选中的 ${selectedIds.length} 个物品？`}
+            onConfirm={() => handleBulkStatus("inactive")}
+          />
+          <Button variant="outline" size="sm" onClick={() => setBulkEditOpen(true)}>
+            批量编辑
+          </Button>
+          <Button variant="ghost" size="sm" onClick={() => setSelectedIds([])}>
+            取消选择
+          </Button>
+        </div>
+      ) : null}
+
       <div className="rounded-2xl border border-slate-200/70 bg-white">
         <Table>
           <TableHeader>
             <TableRow>
-              <TableHead>物品编号</TableHead>
-              <TableHead>名称</TableHead>
-              <TableHead>型号</TableHead>
-              <TableHead>单位</TableHead>
-              <TableHead>库存数</TableHead>
-              <TableHead>状态</TableHead>
-              <TableHead>更新时间</TableHead>
+              <TableHead className="w-12 text-center">
+                <input
+                  type="checkbox"
+                  className="h-4 w-4 accent-slate-900"
+                  checked={filteredRows.length > 0 && filteredRows.every((row) => selectedIds.includes(row.id))}
+                  onChange={(event) => setSelectedIds(event.target.checked ? filteredRows.map((row) => row.id) : [])}
+                />
+              </TableHead>
+              <SortableHead field="item_code" sort={sort} onSortChange={handleSortChange}>
+                物品编号
+              </SortableHead>
+              <SortableHead field="name" sort={sort} onSortChange={handleSortChange}>
+                名称
+              </SortableHead>
+              <SortableHead field="model" sort={sort} onSortChange={handleSortChange}>
+                型号
+              </SortableHead>
+              <SortableHead field="uom" sort={sort} onSortChange={handleSortChange}>
+                单位
+              </SortableHead>
+              {attributeDefs.map((def) => (
+                <TableHead key={def.id}>{def.name}</TableHead>
+              ))}
+              <SortableHead field="stock_qty" sort={sort} onSortChange={handleSortChange}>
+                库存数
+              </SortableHead>
+              <SortableHead field="status" sort={sort} onSortChange={handleSortChange}>
+                状态
+              </SortableHead>
+              <SortableHead field="created_at" sort={sort} onSortChange={handleSortChange}>
+                更新时间
+              </SortableHead>
               <TableHead className="text-center">操作</TableHead>
             </TableRow>
           </TableHeader>
           <TableBody>
             {filteredRows.map((row) => (
               <TableRow key={row.id}>
+                <TableCell className="text-center">
+                  <input
+                    type="checkbox"
+                    className="h-4 w-4 accent-slate-900"
+                    checked={selectedIds.includes(row.id)}
+                    onChange={(event) => toggleSelected(row.id, event.target.checked)}
+                  />
+                </TableCell>
                 <TableCell className="font-medium">{row.item_code}</TableCell>
                 <TableCell>{row.name}</TableCell>
                 <TableCell>{row.model || "-"}</TableCell>
                 <TableCell>{row.uom || "-"}</TableCell>
+                {attributeDefs.map((def) => (
+                  <TableCell key={def.id}>{row.attributes?.[def.id] || "-"}</TableCell>
+                ))}
                 <TableCell>{row.stock_qty}</TableCell>
                 <TableCell>
                   <Badge variant={row.status === "active" ? "secondary" : "outline"}>
@@ -636,6 +987,26 @@ export default function ItemsPage() {
                       >
                         查看流水
                       </DropdownMenuItem>
+                      <DropdownMenuItem
+                        onClick={() => {
+                          setTrend(null)
+                          setTrendRow(row)
+                        }}
+                      >
+                        库存走势
+                      </DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => setLabelRow(row)}>
+                        打印标签
+                      </DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => setAliasRow(row)}>
+                        别名
+                      </DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => setCodeRow(row)}>
+                        修改编码
+                      </DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => setMergeRow(row)}>
+                        合并到...
+                      </DropdownMenuItem>
                       <DropdownMenuItem
                         onClick={() => handleToggleStatus(row)}
                       >
@@ -648,7 +1019,7 @@ export default function ItemsPage() {
             ))}
             {!loading && filteredRows.length === 0 ? (
               <TableRow>
-                <TableCell colSpan={8} className="text-center text-slate-500">
+                <TableCell colSpan={9 + attributeDefs.length} className="text-center text-slate-500">
                   暂无物品数据
                 </TableCell>
               </TableRow>
@@ -685,6 +1056,116 @@ export default function ItemsPage() {
           </PaginationContent>
         </Pagination>
       ) : null}
+
+      <Dialog
+        open={!!trendRow}
+        onOpenChange={(open) => {
+          if (!open) setTrendRow(null)
+        }}
+      >
+        <DialogContent className="max-w-3xl">
+          <DialogHeader>
+            <DialogTitle>库存走势</DialogTitle>
+            <DialogDescription>
+              {trendRow ? `${trendRow.item_code} ${trendRow.name}` : ""}
+              {trend ? `，期初 ${trend.opening_qty}，当前 ${trend.current_qty}` : ""}
+            </DialogDescription>
+          </DialogHeader>
+          <div className="flex flex-wrap gap-3">
+            <Select value={trendRange} onValueChange={setTrendRange}>
+              <SelectTrigger className="w-[140px]">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {TREND_RANGE_OPTIONS.map((option) => (
+                  <SelectItem key={option.value} value={option.value}>
+                    {option.label}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+            <Select value={trendBucket} onValueChange={setTrendBucket}>
+              <SelectTrigger className="w-[120px]">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {TREND_BUCKET_OPTIONS.map((option) => (
+                  <SelectItem key={option.value} value={option.value}>
+                    {option.label}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+          </div>
+          {trendData.length > 0 ? (
+            <ChartContainer
+              className="h-64 w-full aspect-auto"
+              config={{
+                qty: { label: "库存", color: "rgb(var(--chart-1))" },
+              }}
+            >
+              <LineChart data={trendData} margin={{ left: 0, right: 8 }}>
+                <CartesianGrid vertical={false} />
+                <XAxis dataKey="day_label" tickLine={false} axisLine={false} />
+                <YAxis tickLine={false} axisLine={false} width={36} />
+                <ChartTooltip content={<ChartTooltipContent />} />
+                <Line dataKey="qty" type="stepAfter" stroke="var(--color-qty)" strokeWidth={2} dot={false} />
+              </LineChart>
+            </ChartContainer>
+          ) : (
+            <p className="py-10 text-center text-sm text-slate-500">
+              {trendLoading ? "正在加载..." : "暂无数据"}
+            </p>
+          )}
+        </DialogContent>
+      </Dialog>
+      <LabelPrintDialog
+        open={!!labelRow}
+        onOpenChange={(open) => {
+          if (!open) setLabelRow(null)
+        }}
+        target="item"
+        codes={labelRow ? [labelRow.item_code] : undefined}
+        description={labelRow ? `为物品 ${labelRow.item_code} 生成标签` : undefined}
+      />
+
+      <BulkEditDialog
+        open={bulkEditOpen}
+        onOpenChange={setBulkEditOpen}
+        ids={selectedIds}
+        onSaved={() => void fetchItems(keyword)}
+      />
+      <MergeItemDialog
+        open={!!mergeRow}
+        onOpenChange={(open) => {
+          if (!open) setMergeRow(null)
+        }}
+        source={mergeRow}
+        onMerged={() => void fetchItems(keyword)}
+      />
+      <ChangeCodeDialog
+        open={!!codeRow}
+        onOpenChange={(open) => {
+          if (!open) setCodeRow(null)
+        }}
+        item={codeRow}
+        onChanged={() => void fetchItems(keyword)}
+      />
+      <ItemAliasDialog
+        open={!!aliasRow}
+        onOpenChange={(open) => {
+          if (!open) setAliasRow(null)
+        }}
+        item={aliasRow}
+      />
+      <ItemAttributeDialog
+        open={attributeDialogOpen}
+        onOpenChange={setAttributeDialogOpen}
+        onChanged={() => {
+          void fetchAttributeDefs()
+          void fetchItems(keyword)
+        }}
+      />
     </div>
   )
 }
@@ -0,0 +1,381 @@
+import { useEffect, useState } from "react";
+import { PageHeader } from "~/components/common/page-header";
+import { Badge } from "~/components/ui/badge";
+import { Button } from "~/components/ui/button";
+import { DatePicker } from "~/components/ui/date";
+import { Input } from "~/components/ui/input";
+import { Label } from "~/components/ui/label";
+import { Pagination, PaginationContent, PaginationItem, PaginationNext, PaginationPrevious } from "~/components/ui/pagination";
+import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ItemPicker } from "~/components/common/pickers/item-picker";
+import { SlotPicker } from "~/components/common/pickers/slot-picker";
+import { OperatorPicker } from "~/components/common/pickers/operator-picker";
+import { registerKioskOperator } from "~/components/stock/helpers";
+import { useSession } from "~/lib/auth";
+import { tauriInvoke } from "~/lib/tauri";
+import { toast } from "sonner";
+
+type OpenLoanRow = {
+  id: string;
+  loan_no: string;
+  item_id: string;
+  item_code: string;
+  item_name: string;
+  slot_id: string;
+  slot_code: string;
+  qty: number;
+  borrower: string;
+  due_at?: number | null;
+  loaned_at: number;
+  operator_name: string;
+  out_txn_no: string;
+  note?: string | null;
+  overdue: boolean;
+};
+
+type OpenLoanListResult = {
+  items: OpenLoanRow[];
+  total: number;
+  overdue_total: number;
+};
+
+type LoanCreated = {
+  loan_id: string;
+  loan_no: string;
+  txn_no: string;
+};
+
+const emptyLoanForm = { item_id: "", slot_id: "", qty: "1", borrower: "", due_date: "", operator_id: "", note: "" };
+
+const formatTime = (value?: number | null) => (value ? new Date(value * 1000).toLocaleString() : "-");
+
+// 应还日期按当天结束计
+const dueDateToTimestamp = (value: string) => (value ? Math.floor(new Date(`${value}T23:59:59`).getTime() / 1000) : undefined);
+
+export default function LoansPage() {
+  const actorOperatorId = useSession()?.actor_operator_id || "";
+  const [rows, setRows] = useState<OpenLoanRow[]>([]);
+  const [loading, setLoading] = useState(false);
+  const [keyword, setKeyword] = useState("");
+  const [overdueOnly, setOverdueOnly] = useState(false);
+  const [pageIndex, setPageIndex] = useState(1);
+  const [pageSize] = useState(20);
+  const [total, setTotal] = useState(0);
+  const [overdueTotal, setOverdueTotal] = useState(0);
+  const [loanOpen, setLoanOpen] = useState(false);
+  const [loanForm, setLoanForm] = useState(emptyLoanForm);
+  const [returnTarget, setReturnTarget] = useState<OpenLoanRow | null>(null);
+  const [returnSlotId, setReturnSlotId] = useState("");
+  const [returnOperatorId, setReturnOperatorId] = useState("");
+  const [submitting, setSubmitting] = useState(false);
+
+  const fetchLoans = async (nextPage = pageIndex) => {
+    setLoading(true);
+    try {
+      const result = await tauriInvoke<OpenLoanListResult>("list_open_loans", {
+        input: {
+          keyword: keyword.trim() || undefined,
+          overdue_only: overdueOnly,
+          page_index: nextPage,
+          page_size: pageSize,
+        },
+      });
+      setRows(result.items);
+      setTotal(result.total);
+      setOverdueTotal(result.overdue_total);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载失败";
+      toast.error(message);
+    } finally {
+      setLoading(false);
+    }
+  };
+
+  useEffect(() => {
+    fetchLoans(pageIndex);
+  }, [pageIndex, overdueOnly]);
+
+  const openLoanDialog = () => {
+    setLoanForm({ ...emptyLoanForm, operator_id: actorOperatorId });
+    setLoanOpen(true);
+  };
+
+  const handleCreateLoan = async () => {
+    const qty = Number(loanForm.qty);
+    if (!loanForm.item_id || !loanForm.slot_id) {
+      toast.error("请选择物品与库位");
+      return;
+    }
+    if (!Number.isFinite(qty) || qty <= 0) {
+      toast.error("数量必须大于 0");
+      return;
+    }
+    if (!loanForm.borrower.trim()) {
+      toast.error("请输入借用人");
+      return;
+    }
+    setSubmitting(true);
+    try {
+      const result = await tauriInvoke<LoanCreated>("create_loan", {
+        input: {
+          item_id: loanForm.item_id,
+          slot_id: loanForm.slot_id,
+          qty,
+          borrower: loanForm.borrower.trim(),
+          due_at: dueDateToTimestamp(loanForm.due_date),
+          occurred_at: Math.floor(Date.now() / 1000),
+          operator_id: loanForm.operator_id || undefined,
+          note: loanForm.note.trim() || undefined,
+        },
+      });
+      toast.success(`已借出 ${result.loan_no}`);
+      setLoanOpen(false);
+      fetchLoans(1);
+      setPageIndex(1);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "借出失败";
+      toast.error(message);
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const openReturnDialog = (row: OpenLoanRow) => {
+    setReturnTarget(row);
+    setReturnSlotId(row.slot_id);
+    setReturnOperatorId(actorOperatorId);
+  };
+
+  const handleReturn = async () => {
+    if (!returnTarget) return;
+    setSubmitting(true);
+    try {
+      const txnNo = await tauriInvoke<string>("return_loan", {
+        input: {
+          id: returnTarget.id,
+          slot_id: returnSlotId || undefined,
+          occurred_at: Math.floor(Date.now() / 1000),
+          operator_id: returnOperatorId || undefined,
+        },
+      });
+      toast.success(`已归还，流水号 ${txnNo}`);
+      setReturnTarget(null);
+      fetchLoans(pageIndex);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "归还失败";
+      toast.error(message);
+    } finally {
+      setSubmitting(false);
+    }
+  };
+
+  const totalPages = Math.max(1, Math.ceil(total / pageSize));
+
+  return (
+    <div className="space-y-6">
+      <PageHeader
+        title="借用归还"
+        description={`借出中 ${total} 条${overdueTotal > 0 ? `，已逾期 ${overdueTotal} 条` : ""}`}
+        actions={<Button onClick={openLoanDialog}>借出</Button>}
+      />
+
+      <div className="flex flex-wrap items-end gap-3 rounded-2xl border border-slate-200/70 bg-slate-50/70 p-4">
+        <div className="min-w-[220px] space-y-2">
+          <Label>关键字</Label>
+          <Input placeholder="借用单号 / 借用人 / 物品" value={keyword} onChange={(e) => setKeyword(e.target.value)} />
+        </div>
+        <Button
+          variant="outline"
+          onClick={() => {
+            setPageIndex(1);
+            fetchLoans(1);
+          }}
+        >
+          查询
+        </Button>
+        <Button
+          variant={overdueOnly ? "default" : "outline"}
+          onClick={() => {
+            setPageIndex(1);
+            setOverdueOnly(!overdueOnly);
+          }}
+        >
+          仅看逾期
+        </Button>
+      </div>
+
+      <div className="rounded-2xl border border-slate-200/70 bg-white">
+        <Table>
+          <TableHeader>
+            <TableRow>
+              <TableHead>借用单号</TableHead>
+              <TableHead>物品</TableHead>
+              <TableHead>库位</TableHead>
+              <TableHead className="text-right">数量</TableHead>
+              <TableHead>借用人</TableHead>
+              <TableHead>借出时间</TableHead>
+              <TableHead>应还时间</TableHead>
+              <TableHead>记录人</TableHead>
+              <TableHead className="text-center">操作</TableHead>
+            </TableRow>
+          </TableHeader>
+          <TableBody>
+            {rows.map((row) => (
+              <TableRow key={row.id}>
+                <TableCell className="font-medium">
+                  {row.loan_no}
+                  <div className="text-xs text-slate-400">{row.out_txn_no}</div>
+                </TableCell>
+                <TableCell>
+                  {row.item_code} {row.item_name}
+                </TableCell>
+                <TableCell>{row.slot_code}</TableCell>
+                <TableCell className="text-right">{row.qty}</TableCell>
+                <TableCell>{row.borrower}</TableCell>
+                <TableCell>{formatTime(row.loaned_at)}</TableCell>
+                <TableCell>
+                  {formatTime(row.due_at)}
+                  {row.overdue ? (
+                    <Badge variant="destructive" className="ml-2">
+                      逾期
+                    </Badge>
+                  ) : null}
+                </TableCell>
+                <TableCell>{row.operator_name}</TableCell>
+                <TableCell className="text-center">
+                  <Button variant="ghost" size="sm" onClick={() => openReturnDialog(row)}>
+                    归还
+                  </Button>
+                </TableCell>
+              </TableRow>
+            ))}
+            {!loading && rows.length === 0 ? (
+              <TableRow>
+                <TableCell colSpan={9} className="text-center text-slate-500">
+                  暂无借出记录
+                </TableCell>
+              </TableRow>
+            ) : null}
+          </TableBody>
+        </Table>
+      </div>
+
+      {total > pageSize ? (
+        <Pagination className="justify-between">
+          <p className="text-xs text-slate-500">
+            共 {total} 条，当前第 {pageIndex}/{totalPages} 页
+          </p>
+          <PaginationContent>
+            <PaginationItem>
+              <PaginationPrevious
+                href="#"
+                onClick={(event) => {
+                  event.preventDefault();
+                  setPageIndex((prev) => Math.max(1, prev - 1));
+                }}
+              />
+            </PaginationItem>
+            <PaginationItem>
+              <PaginationNext
+                href="#"
+                onClick={(event) => {
+                  event.preventDefault();
+                  setPageIndex((prev) => Math.min(totalPages, prev + 1));
+                }}
+              />
+            </PaginationItem>
+          </PaginationContent>
+        </Pagination>
+      ) : null}
+
+      <CommonDialog
+        title="借出"
+        description="借出将生成出库流水并扣减库位库存"
+        open={loanOpen}
+        onOpenChange={setLoanOpen}
+        content={
+          <div className="grid gap-4 sm:grid-cols-2">
+            <div className="space-y-2">
+              <Label>物品</Label>
+              <ItemPicker value={loanForm.item_id} onChange={(value) => setLoanForm({ ...loanForm, item_id: value || "" })} />
+            </div>
+            <div className="space-y-2">
+              <Label>库位</Label>
+              <SlotPicker value={loanForm.slot_id} onChange={(value) => setLoanForm({ ...loanForm, slot_id: value || "" })} />
+            </div>
+            <div className="space-y-2">
+              <Label>数量</Label>
+              <Input type="number" min={0} step="any" value={loanForm.qty} onChange={(e) => setLoanForm({ ...loanForm, qty: e.target.value })} />
+            </div>
+            <div className="space-y-2">
+              <Label>借用人</Label>
+              <Input value={loanForm.borrower} onChange={(e) => setLoanForm({ ...loanForm, borrower: e.target.value })} />
+            </div>
+            <div className="space-y-2">
+              <Label>应还日期（可选）</Label>
+              <DatePicker value={loanForm.due_date} onChange={(value) => setLoanForm({ ...loanForm, due_date: value })} />
+            </div>
+            <div className="space-y-2">
+              <Label>记录人</Label>
+              <OperatorPicker
+                value={loanForm.operator_id}
+                onChange={(value) => {
+                  setLoanForm({ ...loanForm, operator_id: value || "" });
+                  void registerKioskOperator(value || "");
+                }}
+              />
+            </div>
+            <div className="space-y-2 sm:col-span-2">
+              <Label>备注</Label>
+              <Input value={loanForm.note} onChange={(e) => setLoanForm({ ...loanForm, note: e.target.value })} />
+            </div>
+            <div className="flex justify-end gap-2 sm:col-span-2">
+              <Button variant="outline" onClick={() => setLoanOpen(false)}>
+                取消
+              </Button>
+              <Button onClick={() => void handleCreateLoan()} disabled={submitting}>
+                确认借出
+              </Button>
+            </div>
+          </div>
+        }
+      />
+
+      <CommonDialog
+        title="归还"
+        description={returnTarget ? `${returnTarget.loan_no} · ${returnTarget.borrower} · ${returnTarget.item_code} × ${returnTarget.qty}` : ""}
+        open={!!returnTarget}
+        onOpenChange={(open) => {
+          if (!open) setReturnTarget(null);
+        }}
+        content={
+          <div className="grid gap-4 sm:grid-cols-2">
+            <div className="space-y-2">
+              <Label>归还库位</Label>
+              <SlotPicker value={returnSlotId} onChange={(value) => setReturnSlotId(value || "")} />
+            </div>
+            <div className="space-y-2">
+              <Label>记录人</Label>
+              <OperatorPicker
+                value={returnOperatorId}
+                onChange={(value) => {
+                  setReturnOperatorId(value || "");
+                  void registerKioskOperator(value || "");
+                }}
+              />
+            </div>
+            <div className="flex justify-end gap-2 sm:col-span-2">
+              <Button variant="outline" onClick={() => setReturnTarget(null)}>
+                取消
+              </Button>
+              <Button onClick={() => void handleReturn()} disabled={submitting}>
+                确认归还
+              </Button>
+            </div>
+          </div>
+        }
+      />
+    </div>
+  );
+}
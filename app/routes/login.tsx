@@ -3,8 +3,16 @@ import { useForm } from "react-hook-form"
 import { useNavigate } from "react-router"
 import { Button } from "~/components/ui/button"
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card"
+import {
+  Dialog,
+  DialogContent,
+  DialogDescription,
+  DialogHeader,
+  DialogTitle,
+} from "~/components/ui/dialog"
 import { Form, FormControl, FormField, FormItem, FormLabel, FormMessage } from "~/components/ui/form"
 import { Input } from "~/components/ui/input"
+import { Label } from "~/components/ui/label"
 import { setSession, useSession } from "~/lib/auth"
 import {
   clearCredentials,
@@ -17,6 +25,7 @@ import { toast } from "sonner"
 type LoginResponse = {
   actor_operator_id: string
   must_change_pwd: boolean
+  session_id: string
 }
 
 type LoginFormValues = {
@@ -24,11 +33,40 @@ type LoginFormValues = {
   password: string
 }
 
+// 命令错误的 message 可能是序列化后的 AppError
+function errorMessage(err: unknown, fallback: string) {
+  let message = fallback
+  if (err && typeof err === "object") {
+    const rawMessage = (err as { message?: unknown }).message
+    if (typeof rawMessage === "string") {
+      try {
+        const parsed = JSON.parse(rawMessage) as { message?: string }
+        message = parsed.message || rawMessage
+      } catch {
+        message = rawMessage
+      }
+    } else if (rawMessage) {
+      message = String(rawMessage)
+    }
+  } else if (typeof err === "string") {
+    message = err
+  }
+  return message
+}
+
 export default function LoginPage() {
   const navigate = useNavigate()
   const [showPassword, setShowPassword] = useState(false)
   const [loading, setLoading] = useState(false)
   const [remember, setRemember] = useState(false)
+  const [resetOpen, setResetOpen] = useState(false)
+  const [resetting, setResetting] = useState(false)
+  const [resetValues, setResetValues] = useState({
+    username: "",
+    code: "",
+    password: "",
+    confirm: "",
+  })
   const form = useForm<LoginFormValues>({
     defaultValues: {
       username: "",
@@ -68,6 +106,7 @@ export default function LoginPage() {
         actor_operator_id: result.actor_operator_id,
         must_change_pwd: result.must_change_pwd,
         username,
+        session_id: result.session_id,
       })
       if (remember) {
         await saveCredentials({ username, password })
@@ -77,28 +116,52 @@ export default function LoginPage() {
       toast.success("登录成功")
       navigate("/", { replace: true })
     } catch (err) {
-      let message = "登录失败"
-      if (err && typeof err === "object") {
-        const rawMessage = (err as { message?: unknown }).message
-        if (typeof rawMessage === "string") {
-          try {
-            const parsed = JSON.parse(rawMessage) as { message?: string }
-            message = parsed.message || rawMessage
-          } catch {
-            message = rawMessage
-          }
-        } else if (rawMessage) {
-          message = String(rawMessage)
-        }
-      } else if (typeof err === "string") {
-        message = err
-      }
-      toast.error(message)
+      toast.error(errorMessage(err, "登录失败"))
     } finally {
       setLoading(false)
     }
   }
 
+  const openResetWithCode = () => {
+    setResetValues({
+      username: form.getValues("username").trim(),
+      code: "",
+      password: "",
+      confirm: "",
+    })
+    setResetOpen(true)
+  }
+
+  const handleResetWithCode = async () => {
+    const username = resetValues.username.trim()
+    if (!username || !resetValues.code.trim() || !resetValues.password) {
+      toast.error("请填写账号、重置码与新密码")
+      return
+    }
+    if (resetValues.password !== resetValues.confirm) {
+      toast.error("两次输入的新密码不一致")
+      return
+    }
+    setResetting(true)
+    try {
+      await tauriInvoke("reset_password_with_code", {
+        username,
+        code: resetValues.code.trim(),
+        newPassword: resetValues.password,
+      })
+      toast.success("密码已重置，请使用新密码登录")
+      form.setValue("username", username)
+      form.setValue("password", "")
+      clearCredentials()
+      setRemember(false)
+      setResetOpen(false)
+    } catch (err) {
+      toast.error(errorMessage(err, "重置失败"))
+    } finally {
+      setResetting(false)
+    }
+  }
+
   return (
     <div className="min-h-screen bg-[radial-gradient(circle_at_top,_#fef6e5,_#edf4ff_40%,_#f7f7f2_70%)] px-4 py-10 text-slate-900">
       <div className="mx-auto flex min-h-[80vh] max-w-5xl flex-col items-center justify-center gap-10 lg:flex-row">
@@ -195,9 +258,84 @@ export default function LoginPage() {
             </Form>
             <div className="rounded-xl border border-dashed border-slate-200 bg-slate-50 px-4 py-3 text-xs text-slate-500">
               登录成功后如提示“必须修改密码”，将自动弹出改密窗口。
+              忘记密码时可向管理员索取一次性重置码，
+              <button
+                type="button"
+                className="text-slate-900 underline underline-offset-2"
+                onClick={openResetWithCode}
+              >
+                使用重置码
+              </button>
+              自行设置新密码。
             </div>
           </CardContent>
         </Card>
+        <Dialog open={resetOpen} onOpenChange={setResetOpen}>
+          <DialogContent className="max-w-md">
+            <DialogHeader>
+              <DialogTitle>使用重置码设置新密码</DialogTitle>
+              <DialogDescription>重置码由管理员在人员管理中生成，仅可使用一次。</DialogDescription>
+            </DialogHeader>
+            <form
+              className="space-y-4"
+              onSubmit={(event) => {
+                event.preventDefault()
+                void handleResetWithCode()
+              }}
+            >
+              <div className="space-y-2">
+                <Label htmlFor="reset-username">账号</Label>
+                <Input
+                  id="reset-username"
+                  autoComplete="username"
+                  value={resetValues.username}
+                  onChange={(event) =>
+                    setResetValues((prev) => ({ ...prev, username: event.target.value }))
+                  }
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="reset-code">重置码</Label>
+                <Input
+                  id="reset-code"
+                  className="font-mono uppercase tracking-widest"
+                  autoComplete="one-time-code"
+                  value={resetValues.code}
+                  onChange={(event) =>
+                    setResetValues((prev) => ({ ...prev, code: event.target.value }))
+                  }
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="reset-new-password">新密码</Label>
+                <Input
+                  id="reset-new-password"
+                  type="password"
+                  autoComplete="new-password"
+                  value={resetValues.password}
+                  onChange={(event) =>
+                    setResetValues((prev) => ({ ...prev, password: event.target.value }))
+                  }
+                />
+              </div>
+              <div className="space-y-2">
+                <Label htmlFor="reset-confirm-password">确认新密码</Label>
+                <Input
+                  id="reset-confirm-password"
+                  type="password"
+                  autoComplete="new-password"
+                  value={resetValues.confirm}
+                  onChange={(event) =>
+                    setResetValues((prev) => ({ ...prev, confirm: event.target.value }))
+                  }
+                />
+              </div>
+              <Button className="w-full" type="submit" disabled={resetting}>
+                {resetting ? "提交中..." : "设置新密码"}
+              </Button>
+            </form>
+          </DialogContent>
+        </Dialog>
       </div>
     </div>
   )
@@ -4,6 +4,7 @@ import { PageHeader } from "~/components/common/page-header"
 import { Badge } from "~/components/ui/badge"
 import { Button } from "~/components/ui/button"
 import { ConfirmButton } from "~/components/common/confirm-button"
+import { SessionListDialog } from "~/components/operators/session-list-dialog"
 import {
   Dialog,
   DialogContent,
@@ -62,6 +63,22 @@ type ResetPasswordValues = {
   password: string
 }
 
+type ResetCodeIssued = {
+  code: string
+  expires_at: number
+}
+
+type PermissionSimulation = {
+  operator_id: string
+  username: string
+  display_name: string
+  role: string
+  status: string
+  rbac_enabled: boolean
+  warehouses?: { id: string; code?: string | null; name?: string | null }[] | null
+  permissions: { permission: string; allowed: boolean; warehouse_scoped: boolean; reason: string }[]
+}
+
 export default function OperatorsPage() {
   const [rows, setRows] = useState<OperatorRow[]>([])
   const [loading, setLoading] = useState(false)
@@ -71,6 +88,10 @@ export default function OperatorsPage() {
   const [resetOpen, setResetOpen] = useState(false)
   const [editRow, setEditRow] = useState<OperatorRow | null>(null)
   const [resetTarget, setResetTarget] = useState<OperatorRow | null>(null)
+  const [simulation, setSimulation] = useState<PermissionSimulation | null>(null)
+  const [simulationOpen, setSimulationOpen] = useState(false)
+  const [sessionsOpen, setSessionsOpen] = useState(false)
+  const [resetCode, setResetCode] = useState<(ResetCodeIssued & { username: string }) | null>(null)
   const [pageIndex, setPageIndex] = useState(1)
   const [pageSize] = useState(20)
   const [total, setTotal] = useState(0)
@@ -200,6 +221,31 @@ export default function OperatorsPage() {
     setResetOpen(true)
   }
 
+  const handleIssueResetCode = async (row: OperatorRow) => {
+    try {
+      const result = await tauriInvoke<ResetCodeIssued>("issue_reset_code", {
+        input: { id: row.id },
+      })
+      setResetCode({ ...result, username: row.username })
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "生成重置码失败"
+      toast.error(message)
+    }
+  }
+
+  const openSimulation = async (row: OperatorRow) => {
+    try {
+      const result = await tauriInvoke<PermissionSimulation>("simulate_permissions", {
+        input: { operator_id: row.id },
+      })
+      setSimulation(result)
+      setSimulationOpen(true)
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载权限失败"
+      toast.error(message)
+    }
+  }
+
   const handleReset = async (values: ResetPasswordValues) => {
     if (!resetTarget) return
     try {
@@ -352,6 +398,7 @@ export default function OperatorsPage() {
           </Dialog>
         }
       />
+      <SessionListDialog open={sessionsOpen} onOpenChange={setSessionsOpen} />
 
       <div className="flex flex-wrap items-end gap-3 rounded-2xl border border-slate-200/70 bg-slate-50/70 p-4">
         <div className="min-w-[140px] w-[140px] max-w-[140px] flex-1 space-y-2">
@@ -395,6 +442,9 @@ export default function OperatorsPage() {
         >
           重置
         </Button>
+        <Button variant="outline" onClick={() => setSessionsOpen(true)}>
+          在线会话
+        </Button>
       </div>
 
       <div className="rounded-2xl border border-slate-200/70 bg-white">
@@ -440,6 +490,12 @@ export default function OperatorsPage() {
                   <Button variant="ghost" size="sm" onClick={() => openReset(row)}>
                     重置密码
                   </Button>
+                  <Button variant="ghost" size="sm" onClick={() => void handleIssueResetCode(row)}>
+                    重置码
+                  </Button>
+                  <Button variant="ghost" size="sm" onClick={() => void openSimulation(row)}>
+                    权限
+                  </Button>
                   <Button
                     variant="ghost"
                     size="sm"
@@ -461,6 +517,49 @@ export default function OperatorsPage() {
         </Table>
       </div>
 
+      <Dialog open={simulationOpen} onOpenChange={setSimulationOpen}>
+        <DialogContent className="max-w-2xl">
+          <DialogHeader>
+            <DialogTitle>权限模拟</DialogTitle>
+            <DialogDescription>
+              {simulation
+                ? `${simulation.display_name}（${simulation.username}）· ${
+                    simulation.rbac_enabled ? "RBAC 已开启" : "RBAC 未开启"
+                  } · 仓库范围：${
+                    simulation.warehouses
+                      ? simulation.warehouses.map((warehouse) => warehouse.name || warehouse.code || warehouse.id).join("、")
+                      : "不限"
+                  }`
+                : ""}
+            </DialogDescription>
+          </DialogHeader>
+          <div className="max-h-[60vh] overflow-auto">
+            <Table>
+              <TableHeader>
+                <TableRow>
+                  <TableHead>权限点</TableHead>
+                  <TableHead>结果</TableHead>
+                  <TableHead>原因</TableHead>
+                </TableRow>
+              </TableHeader>
+              <TableBody>
+                {simulation?.permissions.map((item) => (
+                  <TableRow key={item.permission}>
+                    <TableCell className="font-mono text-xs">{item.permission}</TableCell>
+                    <TableCell>
+                      <Badge variant={item.allowed ? "secondary" : "outline"}>
+                        {item.allowed ? (item.warehouse_scoped ? "限仓库" : "允许") : "禁止"}
+                      </Badge>
+                    </TableCell>
+                    <TableCell className="text-xs text-slate-500">{item.reason}</TableCell>
+                  </TableRow>
+                ))}
+              </TableBody>
+            </Table>
+          </div>
+        </DialogContent>
+      </Dialog>
+
       <Dialog open={resetOpen} onOpenChange={setResetOpen}>
         <DialogContent className="max-w-md">
           <DialogHeader>
@@ -511,6 +610,40 @@ export default function OperatorsPage() {
         </DialogContent>
       </Dialog>
 
+      <Dialog open={resetCode !== null} onOpenChange={(open) => !open && setResetCode(null)}>
+        <DialogContent className="max-w-md">
+          <DialogHeader>
+            <DialogTitle>一次性重置码</DialogTitle>
+            <DialogDescription>
+              {resetCode ? `账号：${resetCode.username}` : ""}
+            </DialogDescription>
+          </DialogHeader>
+          {resetCode ? (
+            <div className="space-y-3">
+              <p className="rounded-xl bg-slate-100 py-4 text-center font-mono text-2xl tracking-[0.3em]">
+                {resetCode.code}
+              </p>
+              <p className="text-xs text-slate-500">
+                有效期至 {new Date(resetCode.expires_at * 1000).toLocaleString()}，仅可使用一次。
+                请将重置码交给本人，在登录页点击“使用重置码”自行设置新密码；关闭后不再显示，重新生成会使旧码失效。
+              </p>
+              <Button
+                variant="outline"
+                className="w-full"
+                onClick={() => {
+                  void navigator.clipboard
+                    .writeText(resetCode.code)
+                    .then(() => toast.success("已复制"))
+                    .catch(() => toast.error("复制失败"))
+                }}
+              >
+                复制重置码
+              </Button>
+            </div>
+          ) : null}
+        </DialogContent>
+      </Dialog>
+
       {total > 0 ? (
         <Pagination className="justify-between">
           <p className="text-xs text-slate-500">
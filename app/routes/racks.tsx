@@ -14,6 +14,8 @@ import { Pagination, PaginationContent, PaginationItem, PaginationLink, Paginati
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
 import { Badge } from "~/components/ui/badge";
 import { WarehousePicker } from "~/components/common/pickers/warehouse-picker";
+import { LabelPrintDialog } from "~/components/labels/label-print-dialog";
+import { RackHeatmapDialog } from "~/components/racks/rack-heatmap-dialog";
 import { getSession } from "~/lib/auth";
 import { tauriInvoke } from "~/lib/tauri";
 import { toast } from "sonner";
@@ -49,6 +51,26 @@ type SlotListResult = {
   items: SlotRow[];
 };
 
+type SlotStatusBulkResult = {
+  updated: number;
+  unchanged: number;
+  skipped: { slot_id: string; code: string | null; reason: string }[];
+};
+
+type RackCloneResult = {
+  created: { id: string; code: string; name: string }[];
+  inactive_slots: number;
+};
+
+type CloneForm = {
+  codeFrom: string;
+  codeTo: string;
+  codes: string;
+  nameTemplate: string;
+};
+
+const EMPTY_CLONE_FORM: CloneForm = { codeFrom: "", codeTo: "", codes: "", nameTemplate: "R{code}" };
+
 type RackFormValues = {
   warehouseId: string;
   codeSuffix: string;
@@ -90,6 +112,10 @@ export default function RacksPage() {
   const [keyword, setKeyword] = useState("");
   const [status, setStatus] = useState("all");
   const [warehouseFilter, setWarehouseFilter] = useState("");
+  const [labelRack, setLabelRack] = useState<RackRow | null>(null);
+  const [heatmapRack, setHeatmapRack] = useState<RackRow | null>(null);
+  const [cloneRack, setCloneRack] = useState<RackRow | null>(null);
+  const [cloneForm, setCloneForm] = useState<CloneForm>(EMPTY_CLONE_FORM);
   const form = useForm<RackFormValues>({
     defaultValues: {
       warehouseId: "",
@@ -274,6 +300,62 @@ export default function RacksPage() {
     }
   };
 
+  const openClone = (row: RackRow) => {
+    setCloneForm(EMPTY_CLONE_FORM);
+    setCloneRack(row);
+  };
+
+  // 复制货架：填写编号列表时按列表创建，否则按起止区间创建
+  const handleClone = async () => {
+    if (!cloneRack) return;
+    const codes = cloneForm.codes
+      .split(/[,，\s]+/)
+      .map((code) => code.trim())
+      .filter(Boolean);
+    if (codes.length === 0 && (!cloneForm.codeFrom.trim() || !cloneForm.codeTo.trim())) {
+      toast.error("请填写编号区间或编号列表");
+      return;
+    }
+    try {
+      const result = await tauriInvoke<RackCloneResult>("clone_rack", {
+        input: {
+          source_rack_id: cloneRack.id,
+          codes: codes.length > 0 ? codes : undefined,
+          code_from: codes.length > 0 ? undefined : cloneForm.codeFrom.trim(),
+          code_to: codes.length > 0 ? undefined : cloneForm.codeTo.trim(),
+          name_template: cloneForm.nameTemplate.trim() || undefined,
+        },
+      });
+      toast.success(`已创建 ${result.created.length} 个货架：${result.created.map((rack) => rack.code).join("、")}`);
+      setCloneRack(null);
+      await fetchRacks();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "复制货架失败";
+      toast.error(message);
+    }
+  };
+
+  const handleExportMap = async (row: RackRow, format: "svg" | "pdf") => {
+    try {
+      const result = await tauriInvoke<{ file_path: string }>("export_rack_map", {
+        input: {
+          id: row.id,
+          format,
+        },
+      });
+      const { isMobile, shareFile } = await import("~/lib/tauri");
+      if (isMobile()) {
+        await shareFile(result.file_path);
+        toast.success("已打开分享菜单");
+      } else {
+        toast.success(`导出成功：${result.file_path}`);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导出失败";
+      toast.error(message);
+    }
+  };
+
   const fetchSlots = async (rackId: string, levelNo?: number) => {
     setSlotLoading(true);
     try {
@@ -324,6 +406,31 @@ export default function RacksPage() {
     }
   };
 
+  // 按当前层号筛选批量启用/停用；未填层号时作用于整个货架，仍有库存的库位会被跳过
+  const handleBulkSlotStatus = async (nextStatus: "active" | "inactive") => {
+    if (!activeRack) return;
+    const levelNo = slotLevel ? Number(slotLevel) : undefined;
+    try {
+      const result = await tauriInvoke<SlotStatusBulkResult>("set_slots_status", {
+        input: {
+          rack_id: activeRack.id,
+          level_no: levelNo,
+          status: nextStatus,
+        },
+      });
+      if (result.skipped.length > 0) {
+        const codes = result.skipped.map((item) => item.code ?? item.slot_id).join("、");
+        toast.warning(`已更新 ${result.updated} 个库位，跳过 ${result.skipped.length} 个：${codes}（${result.skipped[0].reason}）`);
+      } else {
+        toast.success(`已更新 ${result.updated} 个库位`);
+      }
+      await fetchSlots(activeRack.id, levelNo);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "批量更新库位失败";
+      toast.error(message);
+    }
+  };
+
   return (
     <div className="space-y-6">
       <PageHeader
@@ -571,6 +678,11 @@ export default function RacksPage() {
                       >
                         查看流水
                       </DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => setHeatmapRack(row)}>活动热力图</DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => handleExportMap(row, "svg")}>导出占用图（SVG）</DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => handleExportMap(row, "pdf")}>导出占用图（PDF）</DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => setLabelRack(row)}>打印库位标签</DropdownMenuItem>
+                      <DropdownMenuItem onClick={() => openClone(row)}>复制货架</DropdownMenuItem>
                       <DropdownMenuItem onClick={() => handleToggleStatus(row)}>{row.status === "active" ? "停用" : "启用"}</DropdownMenuItem>
                     </DropdownMenuContent>
                   </DropdownMenu>
@@ -622,6 +734,20 @@ export default function RacksPage() {
             >
               重置
             </Button>
+            <div className="ml-auto flex gap-2">
+              <ConfirmButton
+                label={slotLevel ? "停用本层" : "停用全部"}
+                variant="outline"
+                confirmText={slotLevel ? `确认停用第 ${slotLevel} 层的库位？仍有库存的库位将跳过` : "确认停用该货架的全部库位？仍有库存的库位将跳过"}
+                onConfirm={() => handleBulkSlotStatus("inactive")}
+              />
+              <ConfirmButton
+                label={slotLevel ? "启用本层" : "启用全部"}
+                variant="outline"
+                confirmText={slotLevel ? `确认启用第 ${slotLevel} 层的库位？` : "确认启用该货架的全部库位？"}
+                onConfirm={() => handleBulkSlotStatus("active")}
+              />
+            </div>
           </div>
           <div className="rounded-2xl border border-slate-200/70 bg-white">
             <Table>
@@ -714,6 +840,63 @@ export default function RacksPage() {
           </PaginationContent>
         </Pagination>
       ) : null}
+      <Dialog
+        open={!!cloneRack}
+        onOpenChange={(open) => {
+          if (!open) setCloneRack(null);
+        }}
+      >
+        <DialogContent>
+          <DialogHeader>
+            <DialogTitle>复制货架</DialogTitle>
+            <DialogDescription>
+              {cloneRack
+                ? `按货架 ${cloneRack.code} 的层数（${cloneRack.level_count}）、每层格数（${cloneRack.slots_per_level}）与停用库位创建新货架`
+                : ""}
+            </DialogDescription>
+          </DialogHeader>
+          <div className="space-y-4">
+            <div className="grid gap-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>起始编号</Label>
+                <Input placeholder="例如 R5" value={cloneForm.codeFrom} onChange={(event) => setCloneForm({ ...cloneForm, codeFrom: event.target.value })} />
+              </div>
+              <div className="grid gap-2">
+                <Label>结束编号</Label>
+                <Input placeholder="例如 R12" value={cloneForm.codeTo} onChange={(event) => setCloneForm({ ...cloneForm, codeTo: event.target.value })} />
+              </div>
+            </div>
+            <div className="grid gap-2">
+              <Label>编号列表（可选，填写后忽略区间）</Label>
+              <Input placeholder="例如 R5, R7, R9" value={cloneForm.codes} onChange={(event) => setCloneForm({ ...cloneForm, codes: event.target.value })} />
+            </div>
+            <div className="grid gap-2">
+              <Label>名称模板</Label>
+              <Input value={cloneForm.nameTemplate} onChange={(event) => setCloneForm({ ...cloneForm, nameTemplate: event.target.value })} />
+              <p className="text-xs text-slate-500">{"{code}"} 会替换为新货架编号</p>
+            </div>
+            <div className="flex justify-end">
+              <Button onClick={handleClone}>创建</Button>
+            </div>
+          </div>
+        </DialogContent>
+      </Dialog>
+      <RackHeatmapDialog
+        open={!!heatmapRack}
+        onOpenChange={(open) => {
+          if (!open) setHeatmapRack(null);
+        }}
+        rackId={heatmapRack?.id}
+      />
+      <LabelPrintDialog
+        open={!!labelRack}
+        onOpenChange={(open) => {
+          if (!open) setLabelRack(null);
+        }}
+        target="slot"
+        rackId={labelRack?.id}
+        description={labelRack ? `为货架 ${labelRack.code} 的全部库位生成标签` : undefined}
+      />
     </div>
   );
 }
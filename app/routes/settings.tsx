@@ -1,5 +1,6 @@
 import { useEffect, useState } from "react";
 import { open } from "@tauri-apps/plugin-dialog";
+import { listen } from "@tauri-apps/api/event";
 import { PageHeader } from "~/components/common/page-header";
 import { Button } from "~/components/ui/button";
 import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "~/components/ui/card";
@@ -10,6 +11,123 @@ import { tauriInvoke, openFolder } from "~/lib/tauri";
 import { toast } from "sonner";
 import { AlertDialog, AlertDialogContent, AlertDialogHeader, AlertDialogTitle, AlertDialogDescription, AlertDialogFooter, AlertDialogAction, AlertDialogCancel } from "~/components/ui/alert-dialog";
 import { copyToClipboard } from "~/lib/utils";
+import { CommonDialog } from "~/components/common/common-dialogs";
+import { ConfirmButton } from "~/components/common/confirm-button";
+import { Select, SelectContent, SelectItem, SelectTrigger, SelectValue } from "~/components/ui/select";
+import { LabelTemplateCard } from "~/components/labels/label-template-card";
+import { DbHealthCard } from "~/components/settings/db-health-card";
+import { PerformanceCard } from "~/components/settings/performance-card";
+import { BackupEncryptionCard } from "~/components/settings/backup-encryption-card";
+import { DemoDataCard } from "~/components/settings/demo-data-card";
+import { MasterBundleCard } from "~/components/settings/master-bundle-card";
+import { ApprovalCard } from "~/components/settings/approval-card";
+import { RoleCapabilityCard } from "~/components/settings/role-capability-card";
+import { ReadOnlyCard } from "~/components/settings/read-only-card";
+import { ReasonCodeCard } from "~/components/settings/reason-code-card";
+import { ReportScheduleCard } from "~/components/settings/report-schedule-card";
+
+type PhotoStorageMetric = {
+  id: string;
+  scanned_at: number;
+  db_count: number;
+  file_count: number;
+  total_bytes: number;
+  missing_count: number;
+  orphan_count: number;
+};
+
+type PhotoStorageScan = {
+  metric: PhotoStorageMetric;
+  previous?: PhotoStorageMetric | null;
+  file_count_delta: number;
+  total_bytes_delta: number;
+  db_count_delta: number;
+  drift_detected: boolean;
+  missing_samples: string[];
+  orphan_samples: string[];
+};
+
+type RbacPreviewOperator = {
+  id: string;
+  username: string;
+  display_name: string;
+  status: string;
+  current_role: string;
+  next_role: string;
+  changed: boolean;
+  permissions: string[];
+};
+
+type RbacEnablePreview = {
+  rbac_enabled: boolean;
+  operators: RbacPreviewOperator[];
+  changed_count: number;
+  active_admin_count: number;
+  blockers: string[];
+  warnings: string[];
+};
+
+const ROLE_LABELS: Record<string, string> = {
+  admin: "管理员",
+  keeper: "保管员",
+  member: "成员",
+  viewer: "只读",
+};
+
+const formatBytes = (bytes: number) => {
+  if (Math.abs(bytes) < 1024) return `${bytes} B`;
+  if (Math.abs(bytes) < 1024 * 1024) return `${(bytes / 1024).toFixed(1)} KB`;
+  return `${(bytes / 1024 / 1024).toFixed(1)} MB`;
+};
+
+type StorageMigrationProgress = {
+  step: string;
+  status: string;
+  message: string;
+  dir?: string;
+  files_done?: number;
+  files_total?: number;
+  bytes_done?: number;
+  bytes_total?: number;
+};
+
+const formatDelta = (value: number, format: (value: number) => string | number = (v) => v) =>
+  value > 0 ? `+${format(value)}` : value < 0 ? `-${format(-value)}` : "无变化";
+
+type RemoteBackupConfig = {
+  kind: string;
+  endpoint: string;
+  bucket: string;
+  region: string;
+  prefix: string;
+  username: string;
+  secret_set: boolean;
+  last_upload_at?: number | null;
+};
+
+type RemoteBackupObject = {
+  name: string;
+  size: number;
+  modified_at?: number | null;
+};
+
+const TXN_NO_SCHEME_LABELS: Record<string, string> = {
+  uuid: "随机编号（T+UUID）",
+  sequence: "顺序编号（IN-0012）",
+  warehouse: "仓库前缀（W1-IN-0012）",
+};
+
+type SlotRecodeResult = {
+  total: number;
+  changed: number;
+  dry_run: boolean;
+  samples: { slot_id: string; old_code: string; new_code: string }[];
+};
+
+const LOCALE_LABELS: Record<string, string> = {
+  "zh-CN": "简体中文",
+  "en-US": "English",
+};
 
 export default function SettingsPage() {
   const isAndroid = typeof navigator !== "undefined" && /android/i.test(navigator.userAgent || "");
@@ -21,7 +139,57 @@ export default function SettingsPage() {
     backups_dir: "",
     slot_no_pad: 2,
     low_stock_threshold: 0,
+    stale_stock_days: 90,
+    expiry_horizon_days: 30,
+    approval_restore: false,
+    kiosk_mode: false,
+    kiosk_operator_window_secs: 60,
+    session_idle_minutes: 0,
+    valuation_method: "moving_average",
+    txn_no_scheme: "uuid",
+    txn_location_capture: false,
+    locale: "zh-CN",
+    report_timezone: "local",
+    http_api_enabled: false,
+    http_api_port: 17420,
+    http_api_token_set: false,
+    close_to_tray: false,
+    start_minimized: false,
+    auto_backup_hours: 0,
+    db_pool_size: 5,
+  });
+  const [kioskWindowInput, setKioskWindowInput] = useState("60");
+  const [sessionIdleInput, setSessionIdleInput] = useState("0");
+  const [httpApiPortInput, setHttpApiPortInput] = useState("17420");
+  const [autoBackupInput, setAutoBackupInput] = useState("0");
+  const [slotPadInput, setSlotPadInput] = useState("2");
+  const [staleDaysInput, setStaleDaysInput] = useState("90");
+  const [expiryDaysInput, setExpiryDaysInput] = useState("30");
+  const [dbPoolInput, setDbPoolInput] = useState("5");
+  const [timezoneInput, setTimezoneInput] = useState("local");
+  const [slotRecode, setSlotRecode] = useState<SlotRecodeResult | null>(null);
+  // 新生成的令牌仅展示一次
+  const [httpApiToken, setHttpApiToken] = useState("");
+  const [rbacWizardOpen, setRbacWizardOpen] = useState(false);
+  const [rbacPreview, setRbacPreview] = useState<RbacEnablePreview | null>(null);
+  const [roleDraft, setRoleDraft] = useState<Record<string, string>>({});
+  const [rbacApplying, setRbacApplying] = useState(false);
+  const [scanning, setScanning] = useState(false);
+  const [photoScan, setPhotoScan] = useState<PhotoStorageScan | null>(null);
+  const [photoMetrics, setPhotoMetrics] = useState<PhotoStorageMetric[]>([]);
+  const [remoteConfig, setRemoteConfig] = useState<RemoteBackupConfig>({
+    kind: "none",
+    endpoint: "",
+    bucket: "",
+    region: "",
+    prefix: "",
+    username: "",
+    secret_set: false,
   });
+  // 留空表示保留已保存的密钥
+  const [remoteSecret, setRemoteSecret] = useState("");
+  const [remoteListOpen, setRemoteListOpen] = useState(false);
+  const [remoteBackups, setRemoteBackups] = useState<RemoteBackupObject[]>([]);
 
   const copyText = async (text: string, label: string) => {
     if (!text || text === "-") return;
@@ -38,6 +206,15 @@ export default function SettingsPage() {
     try {
       const result = await tauriInvoke<typeof settings>("get_settings");
       setSettings(result);
+      setKioskWindowInput(String(result.kiosk_operator_window_secs ?? 60));
+      setSessionIdleInput(String(result.session_idle_minutes ?? 0));
+      setHttpApiPortInput(String(result.http_api_port ?? 17420));
+      setAutoBackupInput(String(result.auto_backup_hours ?? 0));
+      setSlotPadInput(String(result.slot_no_pad ?? 2));
+      setStaleDaysInput(String(result.stale_stock_days ?? 90));
+      setExpiryDaysInput(String(result.expiry_horizon_days ?? 30));
+      setDbPoolInput(String(result.db_pool_size ?? 5));
+      setTimezoneInput(result.report_timezone ?? "local");
     } catch (err) {
       const message = err instanceof Error ? err.message : "加载失败";
       toast.error(message);
@@ -46,11 +223,123 @@ export default function SettingsPage() {
     }
   };
 
+  const fetchPhotoMetrics = async () => {
+    try {
+      const result = await tauriInvoke<PhotoStorageMetric[]>("list_photo_storage_metrics", {
+        query: { limit: 10 },
+      });
+      setPhotoMetrics(result);
+    } catch {
+      // 非管理员无权查看巡检记录，忽略
+    }
+  };
+
+  const fetchRemoteConfig = async () => {
+    try {
+      const result = await tauriInvoke<RemoteBackupConfig>("get_remote_backup_config", {});
+      setRemoteConfig(result);
+    } catch {
+      // 非管理员无权查看远程备份配置
+    }
+  };
+
   useEffect(() => {
     fetchSettings();
+    fetchPhotoMetrics();
+    fetchRemoteConfig();
   }, []);
 
+  // 存储目录迁移进度：按文件上报已迁移字节数，迁移期间可取消并回滚到原目录
+  const [migration, setMigration] = useState<StorageMigrationProgress | null>(null);
+  useEffect(() => {
+    const unlisten = listen<StorageMigrationProgress>("storage_migration_progress", (event) => {
+      setMigration((current) => (current ? event.payload : current));
+    });
+    return () => {
+      void unlisten.then((dispose) => dispose());
+    };
+  }, []);
+
+  const handleCancelMigration = async () => {
+    try {
+      await tauriInvoke("cancel_storage_migration");
+      toast.message("正在取消迁移，已迁移的文件将移回原目录");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "取消失败";
+      toast.error(message);
+    }
+  };
+
+  const handleScanPhotos = async () => {
+    setScanning(true);
+    try {
+      const result = await tauriInvoke<PhotoStorageScan>("scan_photo_storage", {});
+      setPhotoScan(result);
+      if (result.drift_detected) {
+        toast.warning(`发现 ${result.metric.missing_count} 个缺失文件、${result.metric.orphan_count} 个多余文件`);
+      } else {
+        toast.success("照片存储与记录一致");
+      }
+      await fetchPhotoMetrics();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "巡检失败";
+      toast.error(message);
+    } finally {
+      setScanning(false);
+    }
+  };
+
+  const roleAssignments = (draft: Record<string, string>) =>
+    Object.entries(draft).map(([id, role]) => ({ id, role }));
+
+  const loadRbacPreview = async (draft: Record<string, string>) => {
+    try {
+      const result = await tauriInvoke<RbacEnablePreview>("preview_rbac_enable", {
+        input: { assignments: roleAssignments(draft) },
+      });
+      setRbacPreview(result);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载预览失败";
+      toast.error(message);
+    }
+  };
+
+  const openRbacWizard = async () => {
+    setRoleDraft({});
+    setRbacPreview(null);
+    setRbacWizardOpen(true);
+    await loadRbacPreview({});
+  };
+
+  const changeDraftRole = async (id: string, role: string) => {
+    const next = { ...roleDraft, [id]: role };
+    setRoleDraft(next);
+    await loadRbacPreview(next);
+  };
+
+  const applyRbacWizard = async () => {
+    setRbacApplying(true);
+    try {
+      await tauriInvoke("bulk_set_operator_roles", {
+        input: { assignments: roleAssignments(roleDraft), enable_rbac: true },
+      });
+      toast.success("角色已更新，RBAC 已开启");
+      setRbacWizardOpen(false);
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    } finally {
+      setRbacApplying(false);
+    }
+  };
+
   const toggleRbac = async () => {
+    // 开启前先通过向导分配角色，避免所有人默认为管理员或锁死管理入口
+    if (!settings.rbac_enabled) {
+      await openRbacWizard();
+      return;
+    }
     try {
       await tauriInvoke("set_settings", {
         input: {
@@ -65,6 +354,228 @@ export default function SettingsPage() {
     }
   };
 
+  const updateKiosk = async (input: { kiosk_mode?: boolean; kiosk_operator_window_secs?: number; session_idle_minutes?: number }) => {
+    try {
+      await tauriInvoke("set_settings", { input });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const updateValuationMethod = async (valuation_method: string) => {
+    try {
+      await tauriInvoke("set_settings", { input: { valuation_method } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const updateTxnNoScheme = async (txn_no_scheme: string) => {
+    try {
+      await tauriInvoke("set_settings", { input: { txn_no_scheme } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const updateLocale = async (locale: string) => {
+    try {
+      await tauriInvoke("set_settings", { input: { locale } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const toggleLocationCapture = async () => {
+    try {
+      await tauriInvoke("set_settings", { input: { txn_location_capture: !settings.txn_location_capture } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const updateHttpApi = async (input: { http_api_enabled?: boolean; http_api_port?: number }) => {
+    try {
+      await tauriInvoke("set_settings", { input });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveHttpApiPort = async () => {
+    const port = Number(httpApiPortInput);
+    if (!Number.isInteger(port) || port < 1024 || port > 65535) {
+      toast.error("端口需为 1024-65535");
+      return;
+    }
+    await updateHttpApi({ http_api_port: port });
+  };
+
+  const regenerateHttpApiToken = async () => {
+    try {
+      const token = await tauriInvoke<string>("regenerate_http_api_token", {});
+      setHttpApiToken(token);
+      toast.success("已生成新令牌，旧令牌已失效");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "生成失败";
+      toast.error(message);
+    }
+  };
+
+  const updateWindowBehavior = async (input: { close_to_tray?: boolean; start_minimized?: boolean; auto_backup_hours?: number }) => {
+    try {
+      await tauriInvoke("set_settings", { input });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveAutoBackup = async () => {
+    const hours = Number(autoBackupInput);
+    if (!Number.isInteger(hours) || hours < 0 || hours > 720) {
+      toast.error("备份间隔需为 0-720 小时");
+      return;
+    }
+    await updateWindowBehavior({ auto_backup_hours: hours });
+  };
+
+  const saveReportTimezone = async (value: string) => {
+    const report_timezone = value.trim();
+    if (!report_timezone) {
+      toast.error("请填写时区");
+      return;
+    }
+    try {
+      await tauriInvoke("set_settings", { input: { report_timezone } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveStaleDays = async () => {
+    const days = Number(staleDaysInput);
+    if (!Number.isInteger(days) || days < 1 || days > 3650) {
+      toast.error("呆滞天数需为 1-3650");
+      return;
+    }
+    try {
+      await tauriInvoke("set_settings", { input: { stale_stock_days: days } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveExpiryDays = async () => {
+    const days = Number(expiryDaysInput);
+    if (!Number.isInteger(days) || days < 1 || days > 3650) {
+      toast.error("临期天数需为 1-3650");
+      return;
+    }
+    try {
+      await tauriInvoke("set_settings", { input: { expiry_horizon_days: days } });
+      toast.success("设置已更新");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveSlotNoPad = async () => {
+    const pad = Number(slotPadInput);
+    if (!Number.isInteger(pad) || pad < 1 || pad > 6) {
+      toast.error("补零位数需为 1-6");
+      return;
+    }
+    try {
+      await tauriInvoke("set_settings", { input: { slot_no_pad: pad } });
+      toast.success("设置已更新");
+      setSlotRecode(null);
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  // 先预览（dry_run）再执行，库位 ID 不变，库存与流水不受影响
+  const handleRecodeSlots = async (dryRun: boolean) => {
+    try {
+      const result = await tauriInvoke<SlotRecodeResult>("recode_slots", { input: { dry_run: dryRun } });
+      if (dryRun) {
+        setSlotRecode(result);
+      } else {
+        setSlotRecode(null);
+        toast.success(`已重新编码 ${result.changed} 个库位`);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "重新编码失败";
+      toast.error(message);
+    }
+  };
+
+  const saveDbPoolSize = async () => {
+    const size = Number(dbPoolInput);
+    if (!Number.isInteger(size) || size < 1 || size > 16) {
+      toast.error("连接数需为 1-16");
+      return;
+    }
+    try {
+      await tauriInvoke("set_settings", { input: { db_pool_size: size } });
+      toast.success("设置已更新，重启应用后生效");
+      await fetchSettings();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "更新失败";
+      toast.error(message);
+    }
+  };
+
+  const saveKioskWindow = async () => {
+    const secs = Number(kioskWindowInput);
+    if (!Number.isInteger(secs) || secs < 1 || secs > 3600) {
+      toast.error("有效时长需为 1-3600 秒");
+      return;
+    }
+    await updateKiosk({ kiosk_operator_window_secs: secs });
+  };
+
+  const saveSessionIdle = async () => {
+    const minutes = Number(sessionIdleInput);
+    if (!Number.isInteger(minutes) || minutes < 0 || minutes > 1440) {
+      toast.error("空闲超时需为 0-1440 分钟");
+      return;
+    }
+    await updateKiosk({ session_idle_minutes: minutes });
+  };
+
   const changeStorageRoot = async () => {
     if (isAndroid) {
       setPendingAction("storage");
@@ -126,6 +637,7 @@ export default function SettingsPage() {
     setDialogOpen(false);
     try {
       if (pendingAction === "storage") {
+        setMigration({ step: "prepare", status: "start", message: "开始迁移" });
         await tauriInvoke("set_storage_root", { input: { new_path: nextPath } });
         toast.success("迁移完成");
       } else if (pendingAction === "exports") {
@@ -140,6 +652,7 @@ export default function SettingsPage() {
       const message = err instanceof Error ? err.message : "更新失败";
       toast.error(message);
     } finally {
+      setMigration(null);
       setPendingPath(null);
       setPendingAction(null);
       setPendingManualEdit(false);
@@ -248,6 +761,69 @@ export default function SettingsPage() {
         input: { file_path: selected },
       });
       toast.success("恢复完成");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "恢复失败";
+      // 恢复需审批时可直接提交申请，审批通过后再次选择同一备份文件恢复
+      if (settings.approval_restore) {
+        toast.error(message, {
+          action: { label: "提交审批", onClick: () => void submitRestoreApproval(selected) },
+        });
+      } else {
+        toast.error(message);
+      }
+    }
+  };
+
+  const submitRestoreApproval = async (filePath: string) => {
+    try {
+      await tauriInvoke("submit_approval_request", { input: { kind: "restore", file_path: filePath } });
+      toast.success("审批申请已提交，请等待其他管理员审批");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "提交失败";
+      toast.error(message);
+    }
+  };
+
+  const saveRemoteConfig = async () => {
+    try {
+      await tauriInvoke("set_remote_backup_config", {
+        input: {
+          kind: remoteConfig.kind,
+          endpoint: remoteConfig.endpoint,
+          bucket: remoteConfig.bucket,
+          region: remoteConfig.region,
+          prefix: remoteConfig.prefix,
+          username: remoteConfig.username,
+          secret: remoteSecret || null,
+        },
+      });
+      setRemoteSecret("");
+      toast.success("远程备份配置已保存");
+      await fetchRemoteConfig();
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "保存失败";
+      toast.error(message);
+    }
+  };
+
+  const openRemoteBackups = async () => {
+    try {
+      const result = await tauriInvoke<RemoteBackupObject[]>("list_remote_backups", {});
+      setRemoteBackups(result);
+      setRemoteListOpen(true);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "加载远程备份失败";
+      toast.error(message);
+    }
+  };
+
+  const handleRemoteRestore = async (name: string) => {
+    try {
+      await tauriInvoke("restore_remote_backup", {
+        input: { name },
+      });
+      setRemoteListOpen(false);
+      toast.success("恢复完成");
     } catch (err) {
       const message = err instanceof Error ? err.message : "恢复失败";
       toast.error(message);
@@ -274,6 +850,330 @@ export default function SettingsPage() {
             </Button>
           </CardContent>
         </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>共享终端模式</CardTitle>
+            <CardDescription>多人共用一台设备时，每笔流水都需重新扫码或选择记录人</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>共享终端模式</Label>
+              <Input value={settings.kiosk_mode ? "当前：开启" : "当前：关闭"} readOnly />
+            </div>
+            <Button
+              variant="outline"
+              onClick={() => void updateKiosk({ kiosk_mode: !settings.kiosk_mode })}
+              disabled={loading}
+            >
+              切换共享终端模式
+            </Button>
+            <div className="grid gap-2">
+              <Label>记录人有效时长（秒）</Label>
+              <div className="flex gap-2">
+                <Input
+                  type="number"
+                  min={1}
+                  max={3600}
+                  value={kioskWindowInput}
+                  onChange={(e) => setKioskWindowInput(e.target.value)}
+                />
+                <Button variant="outline" onClick={() => void saveKioskWindow()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+            <div className="grid gap-2">
+              <Label>空闲自动退出（分钟，0 表示不限）</Label>
+              <div className="flex gap-2">
+                <Input
+                  type="number"
+                  min={0}
+                  max={1440}
+                  value={sessionIdleInput}
+                  onChange={(e) => setSessionIdleInput(e.target.value)}
+                />
+                <Button variant="outline" onClick={() => void saveSessionIdle()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+          </CardContent>
+        </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>库存计价</CardTitle>
+            <CardDescription>库存价值按所选方法回放入库成本计算</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>计价方法</Label>
+              <Input value={settings.valuation_method === "fifo" ? "当前：先进先出" : "当前：移动加权平均"} readOnly />
+            </div>
+            <Button
+              variant="outline"
+              onClick={() => void updateValuationMethod(settings.valuation_method === "fifo" ? "moving_average" : "fifo")}
+              disabled={loading}
+            >
+              切换为{settings.valuation_method === "fifo" ? "移动加权平均" : "先进先出"}
+            </Button>
+          </CardContent>
+        </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>流水号格式</CardTitle>
+            <CardDescription>仅影响新建流水，已有流水号保持不变</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>当前格式</Label>
+              <Input value={TXN_NO_SCHEME_LABELS[settings.txn_no_scheme] ?? settings.txn_no_scheme} readOnly />
+            </div>
+            <div className="flex flex-wrap gap-2">
+              {Object.entries(TXN_NO_SCHEME_LABELS).map(([value, label]) => (
+                <Button
+                  key={value}
+                  variant={settings.txn_no_scheme === value ? "default" : "outline"}
+                  onClick={() => void updateTxnNoScheme(value)}
+                  disabled={loading || settings.txn_no_scheme === value}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+          </CardContent>
+        </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>呆滞库存</CardTitle>
+            <CardDescription>库龄按物品在库位上最近一次入库（含移入）计算，达到设定天数的库存在库龄报表与仪表盘中计为呆滞</CardDescription>
+          </CardHeader>
+          <CardContent>
+            <div className="grid gap-2">
+              <Label>呆滞天数</Label>
+              <div className="flex gap-2">
+                <Input type="number" min={1} max={3650} value={staleDaysInput} onChange={(event) => setStaleDaysInput(event.target.value)} />
+                <Button variant="outline" onClick={() => void saveStaleDays()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+          </CardContent>
+        </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>临期库存</CardTitle>
+            <CardDescription>入库时录入到期日的库存，在设定天数内到期的计入临期报表与仪表盘；已过期库存出库需管理员确认</CardDescription>
+          </CardHeader>
+          <CardContent>
+            <div className="grid gap-2">
+              <Label>临期天数</Label>
+              <div className="flex gap-2">
+                <Input type="number" min={1} max={3650} value={expiryDaysInput} onChange={(event) => setExpiryDaysInput(event.target.value)} />
+                <Button variant="outline" onClick={() => void saveExpiryDays()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+          </CardContent>
+        </Card>
+        <ReasonCodeCard disabled={loading} />
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>库位编码</CardTitle>
+            <CardDescription>新生成的库位编码为“仓库-R货架-层-格”，层号与格号按位数补零（如 W1-R1-01-02）；修改后可对已有库位重新编码</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>层号/格号补零位数</Label>
+              <div className="flex gap-2">
+                <Input type="number" min={1} max={6} value={slotPadInput} onChange={(event) => setSlotPadInput(event.target.value)} />
+                <Button variant="outline" onClick={() => void saveSlotNoPad()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+            <div className="flex flex-wrap gap-2">
+              <Button variant="outline" onClick={() => void handleRecodeSlots(true)} disabled={loading}>
+                预览重新编码
+              </Button>
+              {slotRecode && slotRecode.changed > 0 ? (
+                <ConfirmButton
+                  label={`重新编码 ${slotRecode.changed} 个库位`}
+                  confirmText="库位 ID 不变，库存与流水不受影响；已打印的库位标签需要重新打印。确认继续？"
+                  onConfirm={() => handleRecodeSlots(false)}
+                  disabled={loading}
+                />
+              ) : null}
+            </div>
+            {slotRecode ? (
+              <div className="space-y-1 text-sm text-slate-600">
+                <p>
+                  共 {slotRecode.total} 个库位，{slotRecode.changed} 个编码将变更
+                </p>
+                {slotRecode.samples.map((sample) => (
+                  <p key={sample.slot_id} className="font-mono text-xs">
+                    {sample.old_code} → {sample.new_code}
+                  </p>
+                ))}
+              </div>
+            ) : null}
+          </CardContent>
+        </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>系统文本语言</CardTitle>
+            <CardDescription>盘点生成的调整流水备注与后端返回的常见错误提示按此语言给出，如“盘点调整：账面 10，实盘 8，盘点单 …”</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>当前语言</Label>
+              <Input value={LOCALE_LABELS[settings.locale] ?? settings.locale} readOnly />
+            </div>
+            <div className="flex flex-wrap gap-2">
+              {Object.entries(LOCALE_LABELS).map(([value, label]) => (
+                <Button
+                  key={value}
+                  variant={settings.locale === value ? "default" : "outline"}
+                  onClick={() => void updateLocale(value)}
+                  disabled={loading || settings.locale === value}
+                >
+                  {label}
+                </Button>
+              ))}
+            </div>
+          </CardContent>
+        </Card>
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>报表时区</CardTitle>
+            <CardDescription>导出中的发生时间、操作时间按此时区格式化，仪表盘与物品趋势按此时区的自然日、周、月分组；可填 local（跟随本机）或 UTC 偏移（如 +08:00），不支持夏令时</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>时区</Label>
+              <div className="flex gap-2">
+                <Input value={timezoneInput} onChange={(event) => setTimezoneInput(event.target.value)} placeholder="local 或 +08:00" />
+                <Button variant="outline" onClick={() => void saveReportTimezone(timezoneInput)} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+            <div className="flex flex-wrap gap-2">
+              <Button
+                variant={settings.report_timezone === "local" ? "default" : "outline"}
+                onClick={() => void saveReportTimezone("local")}
+                disabled={loading || settings.report_timezone === "local"}
+              >
+                跟随本机
+              </Button>
+              <Button
+                variant={settings.report_timezone === "+00:00" ? "default" : "outline"}
+                onClick={() => void saveReportTimezone("+00:00")}
+                disabled={loading || settings.report_timezone === "+00:00"}
+              >
+                UTC
+              </Button>
+            </div>
+          </CardContent>
+        </Card>
+        <LabelTemplateCard disabled={loading} />
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>流水位置</CardTitle>
+            <CardDescription>开启后移动端记录流水时附带粗略位置（约 1 公里精度），适用于车载、外勤库存</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>位置采集</Label>
+              <Input value={settings.txn_location_capture ? "当前：开启" : "当前：关闭"} readOnly />
+            </div>
+            <Button variant="outline" onClick={() => void toggleLocationCapture()} disabled={loading}>
+              切换位置采集
+            </Button>
+          </CardContent>
+        </Card>
+        {isAndroid ? null : (
+          <Card className="border-slate-200/70">
+            <CardHeader>
+              <CardTitle>窗口与后台</CardTitle>
+              <CardDescription>隐藏到托盘后程序继续运行，定时备份与 HTTP 接口不受影响；托盘菜单提供新建入库、打开仪表盘等快捷操作</CardDescription>
+            </CardHeader>
+            <CardContent className="space-y-4">
+              <div className="grid gap-2">
+                <Label>窗口行为</Label>
+                <Input
+                  value={`关闭窗口：${settings.close_to_tray ? "隐藏到托盘" : "退出程序"}；启动时：${settings.start_minimized ? "仅驻留托盘" : "显示主窗口"}`}
+                  readOnly
+                />
+              </div>
+              <div className="flex flex-wrap gap-2">
+                <Button variant="outline" onClick={() => void updateWindowBehavior({ close_to_tray: !settings.close_to_tray })} disabled={loading}>
+                  {settings.close_to_tray ? "关闭时退出程序" : "关闭时隐藏到托盘"}
+                </Button>
+                <Button variant="outline" onClick={() => void updateWindowBehavior({ start_minimized: !settings.start_minimized })} disabled={loading}>
+                  {settings.start_minimized ? "启动时显示主窗口" : "启动时仅驻留托盘"}
+                </Button>
+              </div>
+              <div className="grid gap-2">
+                <Label>定时备份间隔（小时，0 表示不自动备份）</Label>
+                <div className="flex gap-2">
+                  <Input type="number" min={0} max={720} value={autoBackupInput} onChange={(event) => setAutoBackupInput(event.target.value)} />
+                  <Button variant="outline" onClick={() => void saveAutoBackup()} disabled={loading}>
+                    保存
+                  </Button>
+                </div>
+              </div>
+            </CardContent>
+          </Card>
+        )}
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>HTTP 接口</CardTitle>
+            <CardDescription>仅监听本机 127.0.0.1，供外部脚本通过访问令牌调用查询与出入库接口，以令牌生成人的身份执行并记录审计</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <div className="grid gap-2">
+              <Label>接口状态</Label>
+              <Input
+                value={`${settings.http_api_enabled ? "当前：开启" : "当前：关闭"}${settings.http_api_token_set ? "" : "（未生成令牌）"}`}
+                readOnly
+              />
+            </div>
+            <div className="grid gap-2">
+              <Label>监听端口</Label>
+              <div className="flex gap-2">
+                <Input
+                  type="number"
+                  min={1024}
+                  max={65535}
+                  value={httpApiPortInput}
+                  onChange={(event) => setHttpApiPortInput(event.target.value)}
+                />
+                <Button variant="outline" onClick={() => void saveHttpApiPort()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
+            {httpApiToken ? (
+              <div className="grid gap-2">
+                <Label>访问令牌（仅显示一次）</Label>
+                <Input value={httpApiToken} readOnly onClick={() => void copyText(httpApiToken, "访问令牌")} />
+              </div>
+            ) : null}
+            <div className="flex flex-wrap gap-2">
+              <Button
+                variant="outline"
+                onClick={() => void updateHttpApi({ http_api_enabled: !settings.http_api_enabled })}
+                disabled={loading}
+              >
+                {settings.http_api_enabled ? "关闭接口" : "开启接口"}
+              </Button>
+              <Button variant="outline" onClick={() => void regenerateHttpApiToken()} disabled={loading}>
+                {settings.http_api_token_set ? "重新生成令牌" : "生成令牌"}
+              </Button>
+            </div>
+          </CardContent>
+        </Card>
         <Card className="border-slate-200/70">
           <CardHeader>
             <CardTitle>存储目录</CardTitle>
@@ -285,9 +1185,26 @@ export default function SettingsPage() {
               <InputGroup>
                 <InputGroupInput value={settings.storage_root || "-"} readOnly onClick={() => void copyText(settings.storage_root || "", "存储目录")} />
                 <InputGroupAddon align="inline-end">
-                  <InputGroupButton onClick={changeStorageRoot}>选择</InputGroupButton>
+                  <InputGroupButton onClick={changeStorageRoot} disabled={!!migration}>
+                    选择
+                  </InputGroupButton>
                 </InputGroupAddon>
               </InputGroup>
+              {migration ? (
+                <div className="flex items-center justify-between gap-2 rounded-xl border border-slate-200/70 p-3 text-sm">
+                  <div className="space-y-1">
+                    <p>{migration.message}</p>
+                    {migration.bytes_total !== undefined ? (
+                      <p className="text-slate-500">
+                        {migration.files_done}/{migration.files_total} 个文件 · {formatBytes(migration.bytes_done ?? 0)}/{formatBytes(migration.bytes_total)}
+                      </p>
+                    ) : null}
+                  </div>
+                  <Button variant="outline" size="sm" onClick={() => void handleCancelMigration()}>
+                    取消迁移
+                  </Button>
+                </div>
+              ) : null}
             </div>
             <div className="grid gap-2">
               <Label>导出目录</Label>
@@ -307,6 +1224,15 @@ export default function SettingsPage() {
                 </InputGroupAddon>
               </InputGroup>
             </div>
+            <div className="grid gap-2">
+              <Label>数据库连接数（1-16，重启后生效）</Label>
+              <div className="flex gap-2">
+                <Input type="number" min={1} max={16} value={dbPoolInput} onChange={(event) => setDbPoolInput(event.target.value)} />
+                <Button variant="outline" onClick={() => void saveDbPoolSize()} disabled={loading}>
+                  保存
+                </Button>
+              </div>
+            </div>
           </CardContent>
         </Card>
         {/* <Card className="border-slate-200/70">
@@ -329,6 +1255,52 @@ export default function SettingsPage() {
             </Button>
           </CardContent>
         </Card> */}
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>照片存储巡检</CardTitle>
+            <CardDescription>对比照片目录与附件记录，发现手工删改或同步工具造成的文件缺失/多余</CardDescription>
+          </CardHeader>
+          <CardContent className="space-y-4">
+            <Button variant="outline" onClick={handleScanPhotos} disabled={scanning}>
+              {scanning ? "巡检中..." : "立即巡检"}
+            </Button>
+            {photoScan ? (
+              <div className="grid gap-1 text-sm">
+                <span>
+                  附件记录 {photoScan.metric.db_count} 条，目录文件 {photoScan.metric.file_count} 个（
+                  {formatBytes(photoScan.metric.total_bytes)}）
+                </span>
+                <span className={photoScan.drift_detected ? "text-red-600" : "text-slate-500"}>
+                  缺失文件 {photoScan.metric.missing_count} 个，多余文件 {photoScan.metric.orphan_count} 个
+                </span>
+                {photoScan.previous ? (
+                  <span className="text-slate-500">
+                    较上次：文件 {formatDelta(photoScan.file_count_delta)}，大小{" "}
+                    {formatDelta(photoScan.total_bytes_delta, formatBytes)}，记录 {formatDelta(photoScan.db_count_delta)}
+                  </span>
+                ) : null}
+                {[...photoScan.missing_samples.map((path) => `缺失：${path}`), ...photoScan.orphan_samples.map((path) => `多余：${path}`)].map(
+                  (line) => (
+                    <span key={line} className="break-all text-xs text-slate-500">
+                      {line}
+                    </span>
+                  ),
+                )}
+              </div>
+            ) : null}
+            {photoMetrics.length > 0 ? (
+              <div className="grid gap-1 text-xs text-slate-500">
+                <Label>最近巡检</Label>
+                {photoMetrics.map((metric) => (
+                  <span key={metric.id}>
+                    {new Date(metric.scanned_at * 1000).toLocaleString()} · 记录 {metric.db_count} · 文件 {metric.file_count} ·{" "}
+                    {formatBytes(metric.total_bytes)} · 缺失 {metric.missing_count} · 多余 {metric.orphan_count}
+                  </span>
+                ))}
+              </div>
+            ) : null}
+          </CardContent>
+        </Card>
         <Card className="border-slate-200/70">
           <CardHeader>
             <CardTitle>备份恢复</CardTitle>
@@ -344,7 +1316,215 @@ export default function SettingsPage() {
             </Button>
           </CardContent>
         </Card>
+        <ApprovalCard disabled={loading} />
+        <RoleCapabilityCard disabled={loading} />
+        <ReadOnlyCard disabled={loading} />
+        <BackupEncryptionCard disabled={loading} />
+        <ReportScheduleCard disabled={loading} />
+        <DbHealthCard disabled={loading} />
+        <PerformanceCard disabled={loading} />
+        <DemoDataCard disabled={loading} />
+        <MasterBundleCard disabled={loading} />
+        <Card className="border-slate-200/70">
+          <CardHeader>
+            <CardTitle>远程备份</CardTitle>
+            <CardDescription>
+              每次备份（含定时备份）完成后上传到 S3 兼容存储或 WebDAV；密钥加密保存在本机，不随数据库备份上传
+            </CardDescription>
+          </CardHeader>
+          <CardContent className="grid gap-4">
+            <div className="grid gap-4 md:grid-cols-2">
+              <div className="grid gap-2">
+                <Label>存储类型</Label>
+                <Select value={remoteConfig.kind} onValueChange={(kind) => setRemoteConfig((prev) => ({ ...prev, kind }))}>
+                  <SelectTrigger>
+                    <SelectValue />
+                  </SelectTrigger>
+                  <SelectContent>
+                    <SelectItem value="none">不上传</SelectItem>
+                    <SelectItem value="s3">S3 兼容存储</SelectItem>
+                    <SelectItem value="webdav">WebDAV</SelectItem>
+                  </SelectContent>
+                </Select>
+              </div>
+              <div className="grid gap-2">
+                <Label>服务地址</Label>
+                <Input
+                  placeholder={remoteConfig.kind === "webdav" ? "https://dav.example.com/remote.php/dav/files/user" : "https://s3.example.com"}
+                  value={remoteConfig.endpoint}
+                  disabled={remoteConfig.kind === "none"}
+                  onChange={(e) => setRemoteConfig((prev) => ({ ...prev, endpoint: e.target.value }))}
+                />
+              </div>
+              {remoteConfig.kind === "s3" ? (
+                <>
+                  <div className="grid gap-2">
+                    <Label>存储桶</Label>
+                    <Input value={remoteConfig.bucket} onChange={(e) => setRemoteConfig((prev) => ({ ...prev, bucket: e.target.value }))} />
+                  </div>
+                  <div className="grid gap-2">
+                    <Label>区域（默认 us-east-1）</Label>
+                    <Input value={remoteConfig.region} onChange={(e) => setRemoteConfig((prev) => ({ ...prev, region: e.target.value }))} />
+                  </div>
+                </>
+              ) : null}
+              <div className="grid gap-2">
+                <Label>{remoteConfig.kind === "s3" ? "对象前缀" : "备份目录（一级）"}</Label>
+                <Input
+                  placeholder="inventory-backups"
+                  value={remoteConfig.prefix}
+                  disabled={remoteConfig.kind === "none"}
+                  onChange={(e) => setRemoteConfig((prev) => ({ ...prev, prefix: e.target.value }))}
+                />
+              </div>
+              <div className="grid gap-2">
+                <Label>{remoteConfig.kind === "s3" ? "Access Key" : "用户名"}</Label>
+                <Input
+                  value={remoteConfig.username}
+                  disabled={remoteConfig.kind === "none"}
+                  onChange={(e) => setRemoteConfig((prev) => ({ ...prev, username: e.target.value }))}
+                />
+              </div>
+              <div className="grid gap-2">
+                <Label>{remoteConfig.kind === "s3" ? "Secret Key" : "密码"}</Label>
+                <Input
+                  type="password"
+                  placeholder={remoteConfig.secret_set ? "已保存，留空则不修改" : ""}
+                  value={remoteSecret}
+                  disabled={remoteConfig.kind === "none"}
+                  onChange={(e) => setRemoteSecret(e.target.value)}
+                />
+              </div>
+            </div>
+            <div className="flex flex-wrap items-center gap-3">
+              <Button onClick={() => void saveRemoteConfig()}>保存配置</Button>
+              <Button variant="secondary" disabled={remoteConfig.kind === "none"} onClick={() => void openRemoteBackups()}>
+                从远程恢复
+              </Button>
+              <span className="text-xs text-slate-500">
+                最近上传：{remoteConfig.last_upload_at ? new Date(remoteConfig.last_upload_at * 1000).toLocaleString() : "-"}
+              </span>
+            </div>
+          </CardContent>
+        </Card>
       </div>
+      <CommonDialog
+        title="开启 RBAC"
+        description="为人员分配角色并预览权限变化，确认无阻断项后开启"
+        open={rbacWizardOpen}
+        onOpenChange={setRbacWizardOpen}
+        content={
+          rbacPreview ? (
+            <div className="space-y-4">
+              <div className="max-h-[50vh] overflow-auto rounded-lg border border-slate-200/70">
+                <table className="w-full text-sm">
+                  <thead className="bg-slate-50 text-left text-slate-500">
+                    <tr>
+                      <th className="px-3 py-2">人员</th>
+                      <th className="px-3 py-2">状态</th>
+                      <th className="px-3 py-2">当前角色</th>
+                      <th className="px-3 py-2">调整为</th>
+                      <th className="px-3 py-2">权限点</th>
+                    </tr>
+                  </thead>
+                  <tbody>
+                    {rbacPreview.operators.map((operator) => (
+                      <tr key={operator.id} className="border-t border-slate-100">
+                        <td className="px-3 py-2">
+                          {operator.display_name}
+                          <span className="ml-1 text-xs text-slate-400">{operator.username}</span>
+                        </td>
+                        <td className="px-3 py-2">{operator.status === "active" ? "启用" : "停用"}</td>
+                        <td className="px-3 py-2">{ROLE_LABELS[operator.current_role] ?? operator.current_role}</td>
+                        <td className="px-3 py-2">
+                          <Select value={operator.next_role} onValueChange={(role) => void changeDraftRole(operator.id, role)}>
+                            <SelectTrigger className="h-8 w-28">
+                              <SelectValue />
+                            </SelectTrigger>
+                            <SelectContent>
+                              {Object.entries(ROLE_LABELS).map(([value, label]) => (
+                                <SelectItem key={value} value={value}>
+                                  {label}
+                                </SelectItem>
+                              ))}
+                            </SelectContent>
+                          </Select>
+                        </td>
+                        <td className="px-3 py-2 text-slate-500">{operator.permissions.length}</td>
+                      </tr>
+                    ))}
+                  </tbody>
+                </table>
+              </div>
+              <div className="text-sm text-slate-600">
+                调整 {rbacPreview.changed_count} 人，启用状态的管理员 {rbacPreview.active_admin_count} 人
+              </div>
+              {rbacPreview.blockers.map((blocker) => (
+                <div key={blocker} className="text-sm text-red-600">
+                  {blocker}
+                </div>
+              ))}
+              {rbacPreview.warnings.map((warning) => (
+                <div key={warning} className="text-sm text-amber-600">
+                  {warning}
+                </div>
+              ))}
+              <div className="flex justify-end gap-2">
+                <Button variant="outline" onClick={() => setRbacWizardOpen(false)}>
+                  取消
+                </Button>
+                <Button onClick={() => void applyRbacWizard()} disabled={rbacApplying || rbacPreview.blockers.length > 0}>
+                  保存角色并开启 RBAC
+                </Button>
+              </div>
+            </div>
+          ) : (
+            <div className="text-sm text-slate-500">加载中...</div>
+          )
+        }
+      />
+      <CommonDialog
+        title="远程备份"
+        description="选择要恢复的备份，恢复将覆盖当前数据库"
+        open={remoteListOpen}
+        onOpenChange={setRemoteListOpen}
+        content={
+          remoteBackups.length ? (
+            <div className="max-h-[50vh] overflow-auto rounded-lg border border-slate-200/70">
+              <table className="w-full text-sm">
+                <thead className="bg-slate-50 text-left text-slate-500">
+                  <tr>
+                    <th className="px-3 py-2">文件</th>
+                    <th className="px-3 py-2">大小</th>
+                    <th className="px-3 py-2">上传时间</th>
+                    <th className="px-3 py-2" />
+                  </tr>
+                </thead>
+                <tbody>
+                  {remoteBackups.map((backup) => (
+                    <tr key={backup.name} className="border-t border-slate-100">
+                      <td className="px-3 py-2 font-mono text-xs">{backup.name}</td>
+                      <td className="px-3 py-2">{formatBytes(backup.size)}</td>
+                      <td className="px-3 py-2">{backup.modified_at ? new Date(backup.modified_at * 1000).toLocaleString() : "-"}</td>
+                      <td className="px-3 py-2 text-right">
+                        <ConfirmButton
+                          size="sm"
+                          variant="destructive"
+                          label="恢复"
+                          confirmText="确认用该备份覆盖当前数据库？"
+                          onConfirm={() => handleRemoteRestore(backup.name)}
+                        />
+                      </td>
+                    </tr>
+                  ))}
+                </tbody>
+              </table>
+            </div>
+          ) : (
+            <div className="text-sm text-slate-500">远程暂无备份</div>
+          )
+        }
+      />
       {/* 确认对话框：在选择目录后二次确认 */}
       <AlertDialog open={dialogOpen} onOpenChange={setDialogOpen}>
         <AlertDialogContent>
@@ -7,6 +7,7 @@ import { Badge } from "~/components/ui/badge";
 import { AlertDialog, AlertDialogAction, AlertDialogCancel, AlertDialogContent, AlertDialogDescription, AlertDialogFooter, AlertDialogHeader, AlertDialogTitle } from "~/components/ui/alert-dialog";
 import { Input } from "~/components/ui/input";
 import { Label } from "~/components/ui/label";
+import { Textarea } from "~/components/ui/textarea";
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
 import { Pagination, PaginationContent, PaginationItem, PaginationNext, PaginationPrevious } from "~/components/ui/pagination";
 import { DropdownMenu, DropdownMenuContent, DropdownMenuItem, DropdownMenuTrigger } from "~/components/ui/dropdown-menu";
@@ -17,16 +18,31 @@ import { RackPicker } from "~/components/common/pickers/rack-picker";
 import { ItemPicker } from "~/components/common/pickers/item-picker";
 /* SlotPicker replaced by SlotCascaderPicker inside dialogs; no direct import needed here */
 import { OperatorPicker } from "~/components/common/pickers/operator-picker";
+import { open } from "@tauri-apps/plugin-dialog";
 import { useSession } from "~/lib/auth";
-import { tauriInvoke, revealInFolder } from "~/lib/tauri";
+import { tauriInvoke, revealInFolder, TauriError } from "~/lib/tauri";
 import { toast } from "sonner";
 import { CommonDialog } from "~/components/common/common-dialogs";
+import { SortableHead, sortParams, type SortState } from "~/components/common/sortable-head";
 import InboundForm from "~/components/stock/forms/inbound-form";
 import OutboundForm from "~/components/stock/forms/outbound-form";
 import MoveForm from "~/components/stock/forms/move-form";
 import CountForm from "~/components/stock/forms/count-form";
+import { StockAgingDialog } from "~/components/stock/stock-aging-dialog";
+import { LowStockDialog } from "~/components/stock/low-stock-dialog";
+import { ExpiringStockDialog } from "~/components/stock/expiring-stock-dialog";
+import { StockHoldDialog, type StockHoldTarget } from "~/components/stock/stock-hold-dialog";
+import { ScrapDialog, type ScrapTarget } from "~/components/stock/scrap-dialog";
+import { CycleCountDialog } from "~/components/stock/cycle-count-dialog";
+import { OutboundPickDialog } from "~/components/stock/outbound-pick-dialog";
+import { AsnDialog } from "~/components/stock/asn-dialog";
 
 import { type OutboundFormValues, type InboundFormValues, type MoveFormValues, type CountFormValues } from "~/components/stock/types";
+type StockValuation = {
+  method: string;
+  items: { item_id: string; item_code: string; item_name: string; currency?: string | null; qty: number; unit_cost: number; total_value: number }[];
+  totals: { currency?: string | null; total_value: number }[];
+};
 type StockBySlotRow = {
   warehouse_id?: string | null;
   warehouse_code?: string | null;
@@ -41,6 +57,9 @@ type StockBySlotRow = {
   item_name: string;
   operator_name?: string | null;
   qty: number;
+  // 冻结中的数量与可用数量
+  held_qty: number;
+  available_qty: number;
 };
 
 type StockByItemRow = {
@@ -57,6 +76,9 @@ type StockByItemRow = {
   item_name: string;
   operator_name?: string | null;
   qty: number;
+  // 冻结中的数量与可用数量
+  held_qty: number;
+  available_qty: number;
 };
 
 type StockBySlotResult = {
@@ -73,6 +95,52 @@ type StockExportResult = {
   file_path: string;
 };
 
+type CountSheetExportResult = {
+  file_path: string;
+  sheet_no: string;
+  rows: number;
+};
+
+type PickListResult = {
+  file_path: string;
+  list_no: string;
+  rows: number;
+  shortages: { item_code: string; requested: number; available: number }[];
+};
+
+type CountSheetImportResult = {
+  posted: number;
+  skipped: number;
+  txn_nos: string[];
+};
+
+// 库存导出可选列，与后端 STOCK_EXPORT_COLUMNS 的列键一致
+const STOCK_EXPORT_COLUMNS = [
+  { key: "warehouse", label: "仓库" },
+  { key: "rack", label: "货架" },
+  { key: "slot", label: "库位" },
+  { key: "item_name", label: "物品" },
+  { key: "item_code", label: "物品编码" },
+  { key: "operator", label: "记录人" },
+  { key: "qty", label: "数量" },
+  { key: "held_qty", label: "冻结数量" },
+  { key: "available_qty", label: "可用数量" },
+  { key: "min_qty", label: "库存下限" },
+  { key: "max_qty", label: "库存上限" },
+  { key: "unit_cost", label: "单位成本" },
+  { key: "total_value", label: "库存价值" },
+];
+
+const DEFAULT_STOCK_EXPORT_COLUMNS = ["warehouse", "rack", "slot", "item_name", "item_code", "qty", "held_qty", "unit_cost", "total_value"];
+
+type OpeningStockImportResult = {
+  imported: number;
+  item_count: number;
+  slot_count: number;
+  preexisting_count: number;
+  report_file_path: string;
+};
+
 export default function StockPage() {
   const navigate = useNavigate();
   const [searchParams, setSearchParams] = useSearchParams();
@@ -126,6 +194,7 @@ export default function StockPage() {
       item_id: "",
       slot_id: "",
       actual_qty: "",
+      reason_id: "",
       occurred_at: "",
       operator_id: actorOperatorId,
       note: "",
@@ -137,6 +206,8 @@ export default function StockPage() {
   const [pageSize] = useState(20);
   const [totalSlot, setTotalSlot] = useState(0);
   const [totalItem, setTotalItem] = useState(0);
+  const [slotSort, setSlotSort] = useState<SortState>(null);
+  const [itemSort, setItemSort] = useState<SortState>(null);
   const [inboundOpen, setInboundOpen] = useState(false);
   const [outboundOpen, setOutboundOpen] = useState(false);
   const [moveOpen, setMoveOpen] = useState(false);
@@ -148,6 +219,31 @@ export default function StockPage() {
   const inboundConfirmResolverRef = useRef<((value: boolean) => void) | null>(null);
   const [exportDialogOpen, setExportDialogOpen] = useState(false);
   const [exportFilePath, setExportFilePath] = useState("");
+  // 已有日常流水时待确认强制导入的期初库存文件
+  const [openingForcePath, setOpeningForcePath] = useState<string | null>(null);
+  const [valuationOpen, setValuationOpen] = useState(false);
+  const [agingOpen, setAgingOpen] = useState(false);
+  const [lowStockOpen, setLowStockOpen] = useState(false);
+  const [expiringOpen, setExpiringOpen] = useState(false);
+  const [holdOpen, setHoldOpen] = useState(false);
+  const [holdTarget, setHoldTarget] = useState<StockHoldTarget | null>(null);
+  const [scrapOpen, setScrapOpen] = useState(false);
+  const [scrapTarget, setScrapTarget] = useState<ScrapTarget | null>(null);
+  const [cycleCountOpen, setCycleCountOpen] = useState(false);
+  const [pickOpen, setPickOpen] = useState(false);
+  const [asnOpen, setAsnOpen] = useState(false);
+  const [countSheetOpen, setCountSheetOpen] = useState(false);
+  const [stockExportOpen, setStockExportOpen] = useState(false);
+  const [stockExportColumns, setStockExportColumns] = useState<string[]>(DEFAULT_STOCK_EXPORT_COLUMNS);
+  const [countSheetFormat, setCountSheetFormat] = useState<"pdf" | "csv">("pdf");
+  const [countSheetIncludeEmpty, setCountSheetIncludeEmpty] = useState(false);
+  const [countSheetShowExpected, setCountSheetShowExpected] = useState(false);
+  const [pickListOpen, setPickListOpen] = useState(false);
+  const [pickListKind, setPickListKind] = useState<"pick" | "putaway">("pick");
+  const [pickListFormat, setPickListFormat] = useState<"pdf" | "html">("pdf");
+  const [pickListLines, setPickListLines] = useState("");
+  const [pickListReference, setPickListReference] = useState("");
+  const [valuation, setValuation] = useState<StockValuation | null>(null);
 
   const fetchStock = async (slotPage = pageIndexSlot, itemPage = pageIndexItem, overrides: Partial<Record<string, any>> = {}) => {
     setLoading(true);
@@ -157,6 +253,7 @@ export default function StockPage() {
       const slotFilters = {
         page_index: slotPage,
         page_size: pageSize,
+        ...sortParams(slotSort),
         keyword: (overrides.keyword ?? keyword) || undefined,
         warehouse_id: (overrides.warehouse_id ?? warehouseIdFilter) || undefined,
         rack_id: (overrides.rack_id ?? rackFilter) || undefined,
@@ -168,6 +265,7 @@ export default function StockPage() {
       const itemFilters = {
         page_index: itemPage,
         page_size: pageSize,
+        ...sortParams(itemSort),
         keyword: (overrides.keyword ?? keyword) || undefined,
         warehouse_id: (overrides.warehouse_id ?? warehouseIdFilter) || undefined,
         rack_id: (overrides.rack_id ?? rackFilter) || undefined,
@@ -239,7 +337,13 @@ export default function StockPage() {
     closeParam();
   }, [searchParams, setSearchParams, selectedStockKey]);
 
+  // 导出沿用当前页签（按库位/按物品）的筛选与排序，列按勾选顺序输出
   const handleExport = async () => {
+    if (stockExportColumns.length === 0) {
+      toast.error("请至少选择一列");
+      return;
+    }
+    const byItem = activeTab === "item";
     try {
       const result = await tauriInvoke<StockExportResult>("export_stock", {
         // pass empty args to trigger wrapper actor id injection
@@ -250,8 +354,12 @@ export default function StockPage() {
           slot_id: slotIdFilter || undefined,
           item_id: itemFilter || undefined,
           operator_id: operatorFilter || undefined,
+          ...sortParams(byItem ? itemSort : slotSort),
+          mode: byItem ? "by_item" : "by_slot",
+          columns: STOCK_EXPORT_COLUMNS.map((column) => column.key).filter((key) => stockExportColumns.includes(key)),
         },
       });
+      setStockExportOpen(false);
       
       // 在移动端使用分享功能，桌面端显示文件路径
       const { isMobile, shareFile } = await import("~/lib/tauri");
@@ -269,6 +377,159 @@ export default function StockPage() {
     }
   };
 
+  const handleExportCountSheets = async () => {
+    try {
+      // 盘点范围沿用当前的仓库/货架筛选
+      const result = await tauriInvoke<CountSheetExportResult>("export_count_sheets", {
+        input: {
+          warehouse_id: warehouseIdFilter || undefined,
+          rack_id: rackFilter || undefined,
+          include_empty_slots: countSheetIncludeEmpty,
+          show_expected: countSheetShowExpected,
+          format: countSheetFormat,
+        },
+      });
+      setCountSheetOpen(false);
+      const { isMobile, shareFile } = await import("~/lib/tauri");
+      if (isMobile()) {
+        await shareFile(result.file_path);
+        toast.success("已打开分享菜单");
+      } else {
+        toast.success(`盘点单号 ${result.sheet_no}，共 ${result.rows} 行`);
+        setExportFilePath(result.file_path);
+        setExportDialogOpen(true);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导出盘点表失败";
+      toast.error(message);
+    }
+  };
+
+  const handlePrintPickList = async () => {
+    // 每行：物品编码 数量 [库位编码]，空格或逗号分隔
+    const lines = [];
+    const rows = pickListLines.split("\n").map((line) => line.trim()).filter(Boolean);
+    for (const [index, row] of rows.entries()) {
+      const [itemCode, qtyText, slotCode] = row.split(/[\s,，]+/);
+      const qty = Number(qtyText);
+      if (!itemCode || !Number.isFinite(qty) || qty <= 0) {
+        toast.error(`第 ${index + 1} 行格式不正确`);
+        return;
+      }
+      lines.push({ item_code: itemCode, qty, slot_code: slotCode || undefined });
+    }
+    if (lines.length === 0) {
+      toast.error("请至少填写一行物品");
+      return;
+    }
+    try {
+      const result = await tauriInvoke<PickListResult>("print_pick_list", {
+        input: {
+          kind: pickListKind,
+          warehouse_id: warehouseIdFilter || undefined,
+          lines,
+          reference: pickListReference.trim() || undefined,
+          format: pickListFormat,
+        },
+      });
+      setPickListOpen(false);
+      if (result.shortages.length > 0) {
+        toast.warning(`${result.shortages.map((item) => item.item_code).join("、")} 库存不足，缺货明细见单据`);
+      }
+      const { isMobile, shareFile } = await import("~/lib/tauri");
+      if (isMobile()) {
+        await shareFile(result.file_path);
+        toast.success("已打开分享菜单");
+      } else {
+        toast.success(`单号 ${result.list_no}，共 ${result.rows} 行`);
+        setExportFilePath(result.file_path);
+        setExportDialogOpen(true);
+      }
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "生成单据失败";
+      toast.error(message);
+    }
+  };
+
+  const handleImportCountSheet = async () => {
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: "CSV", extensions: ["csv"] }],
+    });
+    if (!selected || Array.isArray(selected)) return;
+    try {
+      const result = await tauriInvoke<CountSheetImportResult>("import_count_sheet", {
+        input: { file_path: selected },
+      });
+      toast.success(`已登记 ${result.posted} 条盘点，跳过 ${result.skipped} 行未填写`);
+      fetchStock(pageIndexSlot, pageIndexItem);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导入盘点表失败";
+      toast.error(message);
+    }
+  };
+
+  // 期初库存：已有日常流水时后端返回 CONFLICT，确认后强制导入
+  const importOpeningStock = async (filePath: string, force: boolean) => {
+    try {
+      const result = await tauriInvoke<OpeningStockImportResult>("import_opening_stock", {
+        input: { file_path: filePath, force },
+      });
+      toast.success(`已导入 ${result.imported} 行期初库存（${result.item_count} 个物品、${result.slot_count} 个库位）`);
+      setExportFilePath(result.report_file_path);
+      setExportDialogOpen(true);
+      fetchStock(pageIndexSlot, pageIndexItem);
+    } catch (err) {
+      if (!force && err instanceof TauriError && err.code === "CONFLICT") {
+        setOpeningForcePath(filePath);
+        return;
+      }
+      const message = err instanceof Error ? err.message : "导入期初库存失败";
+      toast.error(message);
+    }
+  };
+
+  const handleImportOpeningStock = async () => {
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: "CSV", extensions: ["csv"] }],
+    });
+    if (!selected || Array.isArray(selected)) return;
+    await importOpeningStock(selected, false);
+  };
+
+  const handleCopy = async () => {
+    try {
+      const result = await tauriInvoke<{ text: string; rows: number; total: number; truncated: boolean }>("copy_stock", {
+        input: {
+          warehouse_id: warehouseIdFilter || undefined,
+          rack_id: rackFilter || undefined,
+          slot_id: slotIdFilter || undefined,
+          item_id: itemFilter || undefined,
+          operator_id: operatorFilter || undefined,
+        },
+      });
+      await navigator.clipboard.writeText(result.text);
+      toast.success(result.truncated ? `已复制 ${result.rows} / ${result.total} 行（超出上限已截断）` : `已复制 ${result.rows} 行`);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "复制失败";
+      toast.error(message);
+    }
+  };
+
+  const handleValuation = async () => {
+    try {
+      const result = await tauriInvoke<StockValuation>("get_stock_valuation", {
+        input: { warehouse_id: warehouseIdFilter || undefined },
+      });
+      setValuation(result);
+      setValuationOpen(true);
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "计价失败";
+      toast.error(message);
+    }
+  };
+
   const formatSlotCode = (slotCode: string) => slotCode;
   // function useWhyDidYouUpdate(name: string, props: Record<string, any>) {
   //   const previousProps = useRef<Record<string, any>>({});
@@ -298,13 +559,23 @@ export default function StockPage() {
   // useWhyDidYouUpdate("MyComponent", { pageIndexSlot, pageIndexItem, keyword, warehouseIdFilter, rackFilter, slotIdFilter, itemFilter, operatorFilter, status });
   useEffect(() => {
     fetchStock(pageIndexSlot, pageIndexItem);
-  }, [pageIndexSlot, pageIndexItem, keyword, warehouseIdFilter, rackFilter, slotIdFilter, itemFilter, operatorFilter, status]);
+  }, [pageIndexSlot, pageIndexItem, slotSort, itemSort, keyword, warehouseIdFilter, rackFilter, slotIdFilter, itemFilter, operatorFilter, status]);
   // 当筛选条件变化时，重置页码并重新请求库存数据
   useEffect(() => {
     setPageIndexSlot(1);
     setPageIndexItem(1);
   }, [keyword, warehouseIdFilter, rackFilter, slotIdFilter, itemFilter, operatorFilter, status]);
 
+  const handleSlotSortChange = (next: SortState) => {
+    setSlotSort(next);
+    setPageIndexSlot(1);
+  };
+
+  const handleItemSortChange = (next: SortState) => {
+    setItemSort(next);
+    setPageIndexItem(1);
+  };
+
   const selectedStockRow = slotRows.find((row) => `${row.item_code}|${row.slot_code}` === selectedStockKey) || null;
   const applyStockRow = async (row: StockBySlotRow | StockByItemRow, mode: "outbound" | "inbound" | "move" | "count") => {
     const levelMatch = row.slot_code.match(/-L(\d+)-S/);
@@ -315,7 +586,8 @@ export default function StockPage() {
     if (mode === "outbound") {
       outboundForm.setValue("item_id", row.item_id);
       outboundForm.setValue("from_slot_id", slotId);
-      outboundForm.setValue("qty", Math.max(0, row.qty));
+      // 出库上限为可用库存（冻结中的数量不可出库）
+      outboundForm.setValue("qty", Math.max(0, row.available_qty));
 
       setOutboundOpen(true);
     }
@@ -358,6 +630,203 @@ export default function StockPage() {
           />
         }
       />
+      <CommonDialog
+        title="库存计价"
+        description={valuation ? `计价方法：${valuation.method === "fifo" ? "先进先出" : "移动加权平均"}` : ""}
+        open={valuationOpen}
+        onOpenChange={setValuationOpen}
+        content={
+          valuation ? (
+            <div className="space-y-4">
+              <div className="flex flex-wrap gap-2">
+                {valuation.totals.length === 0 ? (
+                  <span className="text-sm text-slate-500">暂无库存</span>
+                ) : (
+                  valuation.totals.map((total) => (
+                    <Badge key={total.currency ?? "-"} variant="secondary">
+                      {total.currency ?? "未设币种"}：{total.total_value.toFixed(2)}
+                    </Badge>
+                  ))
+                )}
+              </div>
+              <div className="max-h-[60vh] overflow-auto">
+                <Table>
+                  <TableHeader>
+                    <TableRow>
+                      <TableHead>物品编码</TableHead>
+                      <TableHead>物品</TableHead>
+                      <TableHead className="text-right">数量</TableHead>
+                      <TableHead className="text-right">单位成本</TableHead>
+                      <TableHead className="text-right">库存价值</TableHead>
+                      <TableHead>币种</TableHead>
+                    </TableRow>
+                  </TableHeader>
+                  <TableBody>
+                    {valuation.items.map((row) => (
+                      <TableRow key={row.item_id}>
+                        <TableCell>{row.item_code}</TableCell>
+                        <TableCell>{row.item_name}</TableCell>
+                        <TableCell className="text-right">{row.qty}</TableCell>
+                        <TableCell className="text-right">{row.unit_cost.toFixed(2)}</TableCell>
+                        <TableCell className="text-right">{row.total_value.toFixed(2)}</TableCell>
+                        <TableCell>{row.currency ?? "-"}</TableCell>
+                      </TableRow>
+                    ))}
+                  </TableBody>
+                </Table>
+              </div>
+            </div>
+          ) : null
+        }
+      />
+      <StockAgingDialog open={agingOpen} onOpenChange={setAgingOpen} warehouseId={warehouseIdFilter} />
+      <LowStockDialog open={lowStockOpen} onOpenChange={setLowStockOpen} warehouseId={warehouseIdFilter} />
+      <ExpiringStockDialog open={expiringOpen} onOpenChange={setExpiringOpen} warehouseId={warehouseIdFilter} />
+      <StockHoldDialog
+        open={holdOpen}
+        onOpenChange={(open) => {
+          setHoldOpen(open);
+          if (!open) setHoldTarget(null);
+        }}
+        warehouseId={warehouseIdFilter}
+        target={holdTarget}
+        onChanged={() => fetchStock(pageIndexSlot, pageIndexItem)}
+      />
+      <ScrapDialog
+        open={scrapOpen}
+        onOpenChange={(open) => {
+          setScrapOpen(open);
+          if (!open) setScrapTarget(null);
+        }}
+        target={scrapTarget}
+        onChanged={() => fetchStock(pageIndexSlot, pageIndexItem)}
+      />
+      <CycleCountDialog open={cycleCountOpen} onOpenChange={setCycleCountOpen} warehouseId={warehouseIdFilter} />
+      <OutboundPickDialog open={pickOpen} onOpenChange={setPickOpen} onChanged={() => fetchStock(pageIndexSlot, pageIndexItem)} />
+      <AsnDialog open={asnOpen} onOpenChange={setAsnOpen} onChanged={() => fetchStock(pageIndexSlot, pageIndexItem)} />
+      <CommonDialog
+        title="打印盘点表"
+        description="按当前仓库/货架筛选生成盘点表，填写实盘数量后可导入 CSV 自动登记盘点"
+        open={countSheetOpen}
+        onOpenChange={setCountSheetOpen}
+        content={
+          <div className="space-y-4">
+            <div className="space-y-2">
+              <Label>格式</Label>
+              <div className="flex gap-2">
+                <Button variant={countSheetFormat === "pdf" ? "default" : "outline"} onClick={() => setCountSheetFormat("pdf")}>
+                  PDF（打印）
+                </Button>
+                <Button variant={countSheetFormat === "csv" ? "default" : "outline"} onClick={() => setCountSheetFormat("csv")}>
+                  CSV（回填导入）
+                </Button>
+              </div>
+            </div>
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                className="h-4 w-4 accent-slate-900"
+                checked={countSheetIncludeEmpty}
+                onChange={(event) => setCountSheetIncludeEmpty(event.target.checked)}
+              />
+              包含空库位
+            </label>
+            <label className="flex items-center gap-2 text-sm">
+              <input
+                type="checkbox"
+                className="h-4 w-4 accent-slate-900"
+                checked={countSheetShowExpected}
+                onChange={(event) => setCountSheetShowExpected(event.target.checked)}
+              />
+              显示账面数量（默认隐藏，避免盲盘受影响）
+            </label>
+            <div className="flex justify-end">
+              <Button onClick={handleExportCountSheets}>生成</Button>
+            </div>
+          </div>
+        }
+      />
+      <CommonDialog
+        title="导出库存"
+        description={`按当前筛选与排序导出${activeTab === "item" ? "“按物品”" : "“按库位”"}视图；下限/上限取物品在所在仓库的库存阈值`}
+        open={stockExportOpen}
+        onOpenChange={setStockExportOpen}
+        content={
+          <div className="space-y-4">
+            <div className="space-y-2">
+              <Label>导出列</Label>
+              <div className="grid grid-cols-3 gap-2">
+                {STOCK_EXPORT_COLUMNS.map((column) => (
+                  <label key={column.key} className="flex items-center gap-2 text-sm">
+                    <input
+                      type="checkbox"
+                      className="h-4 w-4 accent-slate-900"
+                      checked={stockExportColumns.includes(column.key)}
+                      onChange={(event) =>
+                        setStockExportColumns((prev) =>
+                          event.target.checked ? [...prev, column.key] : prev.filter((key) => key !== column.key)
+                        )
+                      }
+                    />
+                    {column.label}
+                  </label>
+                ))}
+              </div>
+            </div>
+            <div className="flex justify-end">
+              <Button onClick={handleExport}>导出</Button>
+            </div>
+          </div>
+        }
+      />
+      <CommonDialog
+        title="打印拣货/上架单"
+        description="按巡库路线排列库位；拣货单未填库位时按库存自动建议，可限定当前仓库筛选"
+        open={pickListOpen}
+        onOpenChange={setPickListOpen}
+        content={
+          <div className="space-y-4">
+            <div className="space-y-2">
+              <Label>类型</Label>
+              <div className="flex gap-2">
+                <Button variant={pickListKind === "pick" ? "default" : "outline"} onClick={() => setPickListKind("pick")}>
+                  拣货单
+                </Button>
+                <Button variant={pickListKind === "putaway" ? "default" : "outline"} onClick={() => setPickListKind("putaway")}>
+                  上架单
+                </Button>
+              </div>
+            </div>
+            <div className="space-y-2">
+              <Label>明细</Label>
+              <Textarea
+                rows={6}
+                placeholder={"每行：物品编码 数量 [库位编码]\n例如：T001 5\nT002 2 A-01-01"}
+                value={pickListLines}
+                onChange={(event) => setPickListLines(event.target.value)}
+              />
+            </div>
+            <div className="space-y-2">
+              <Label>关联单号</Label>
+              <Input placeholder="选填，打印在表头" value={pickListReference} onChange={(event) => setPickListReference(event.target.value)} />
+            </div>
+            <div className="space-y-2">
+              <Label>格式</Label>
+              <div className="flex gap-2">
+                <Button variant={pickListFormat === "pdf" ? "default" : "outline"} onClick={() => setPickListFormat("pdf")}>
+                  PDF
+                </Button>
+                <Button variant={pickListFormat === "html" ? "default" : "outline"} onClick={() => setPickListFormat("html")}>
+                  HTML（支持中文名称）
+                </Button>
+              </div>
+            </div>
+            <div className="flex justify-end">
+              <Button onClick={handlePrintPickList}>生成</Button>
+            </div>
+          </div>
+        }
+      />
       <CommonDialog
         title="出库"
         description="物品出库"
@@ -429,6 +898,28 @@ export default function StockPage() {
           </AlertDialogFooter>
         </AlertDialogContent>
       </AlertDialog>
+      <AlertDialog open={openingForcePath !== null} onOpenChange={(next) => !next && setOpeningForcePath(null)}>
+        <AlertDialogContent>
+          <AlertDialogHeader>
+            <AlertDialogTitle>已有日常流水</AlertDialogTitle>
+            <AlertDialogDescription>
+              期初库存一般在启用前导入。强制导入会在库位现有库存上累加期初数量，导入前后库存见对账报告。确认继续？
+            </AlertDialogDescription>
+          </AlertDialogHeader>
+          <AlertDialogFooter>
+            <AlertDialogCancel onClick={() => setOpeningForcePath(null)}>取消</AlertDialogCancel>
+            <AlertDialogAction
+              onClick={() => {
+                const filePath = openingForcePath;
+                setOpeningForcePath(null);
+                if (filePath) void importOpeningStock(filePath, true);
+              }}
+            >
+              强制导入
+            </AlertDialogAction>
+          </AlertDialogFooter>
+        </AlertDialogContent>
+      </AlertDialog>
       <AlertDialog
         open={exportDialogOpen}
         onOpenChange={(next) => {
@@ -467,9 +958,49 @@ export default function StockPage() {
         actions={
           <div className="flex flex-wrap gap-2">
             <Button onClick={() => setInboundOpen(true)}>入库</Button>
-            <Button variant="outline" onClick={handleExport}>
+            <Button variant="outline" onClick={handleCopy}>
+              复制表格
+            </Button>
+            <Button variant="outline" onClick={() => setStockExportOpen(true)}>
               导出库存
             </Button>
+            <Button variant="outline" onClick={handleValuation}>
+              库存计价
+            </Button>
+            <Button variant="outline" onClick={() => setAgingOpen(true)}>
+              库龄
+            </Button>
+            <Button variant="outline" onClick={() => setLowStockOpen(true)}>
+              低库存
+            </Button>
+            <Button variant="outline" onClick={() => setExpiringOpen(true)}>
+              临期
+            </Button>
+            <Button variant="outline" onClick={() => setHoldOpen(true)}>
+              冻结记录
+            </Button>
+            <Button variant="outline" onClick={() => setCycleCountOpen(true)}>
+              循环盘点
+            </Button>
+            <Button variant="outline" onClick={() => setPickOpen(true)}>
+              拣货出库
+            </Button>
+            <Button variant="outline" onClick={() => setAsnOpen(true)}>
+              到货预报
+            </Button>
+            <DropdownMenu>
+              <DropdownMenuTrigger asChild>
+                <Button variant="outline">盘点表</Button>
+              </DropdownMenuTrigger>
+              <DropdownMenuContent align="end">
+                <DropdownMenuItem onClick={() => setCountSheetOpen(true)}>打印盘点表</DropdownMenuItem>
+                <DropdownMenuItem onClick={handleImportCountSheet}>导入盘点结果</DropdownMenuItem>
+                <DropdownMenuItem onClick={handleImportOpeningStock}>导入期初库存</DropdownMenuItem>
+              </DropdownMenuContent>
+            </DropdownMenu>
+            <Button variant="outline" onClick={() => setPickListOpen(true)}>
+              拣货/上架单
+            </Button>
           </div>
         }
       />
@@ -558,13 +1089,27 @@ export default function StockPage() {
               <TableHeader>
                 <TableRow>
                   <TableHead className="w-16 text-center">选择</TableHead>
-                  <TableHead>仓库</TableHead>
-                  <TableHead>货架</TableHead>
-                  <TableHead>位置</TableHead>
-                  <TableHead>物品编号</TableHead>
-                  <TableHead>物品名称</TableHead>
-                  <TableHead>数量</TableHead>
-                  <TableHead>记录人</TableHead>
+                  <SortableHead field="warehouse_code" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    仓库
+                  </SortableHead>
+                  <SortableHead field="rack_code" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    货架
+                  </SortableHead>
+                  <SortableHead field="slot_code" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    位置
+                  </SortableHead>
+                  <SortableHead field="item_code" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    物品编号
+                  </SortableHead>
+                  <SortableHead field="item_name" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    物品名称
+                  </SortableHead>
+                  <SortableHead field="qty" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    数量
+                  </SortableHead>
+                  <SortableHead field="operator_name" sort={slotSort} onSortChange={handleSlotSortChange}>
+                    记录人
+                  </SortableHead>
                   <TableHead className="text-center">操作</TableHead>
                 </TableRow>
               </TableHeader>
@@ -606,7 +1151,14 @@ export default function StockPage() {
                     <TableCell>{formatSlotCode(row.slot_code)}</TableCell>
                     <TableCell>{row.item_code}</TableCell>
                     <TableCell>{row.item_name}</TableCell>
-                    <TableCell>{row.qty}</TableCell>
+                    <TableCell>
+                      {row.qty}
+                      {row.held_qty > 0 ? (
+                        <Badge variant="outline" className="ml-2">
+                          冻结 {row.held_qty}
+                        </Badge>
+                      ) : null}
+                    </TableCell>
                     <TableCell>{row.operator_name || "-"}</TableCell>
                     <TableCell className="text-center">
                       <div className="flex justify-center gap-2">
@@ -659,6 +1211,34 @@ export default function StockPage() {
                             >
                               盘点
                             </DropdownMenuItem>
+                            <DropdownMenuItem
+                              onClick={() => {
+                                setHoldTarget({
+                                  item_id: row.item_id,
+                                  slot_id: row.slot_id || "",
+                                  label: `${row.item_name} @ ${formatSlotCode(row.slot_code)}`,
+                                  available_qty: row.available_qty,
+                                });
+                                setHoldOpen(true);
+                              }}
+                            >
+                              冻结
+                            </DropdownMenuItem>
+                            <DropdownMenuItem
+                              disabled={row.qty <= 0}
+                              onClick={() => {
+                                setScrapTarget({
+                                  item_id: row.item_id,
+                                  slot_id: row.slot_id || "",
+                                  label: `${row.item_name} @ ${formatSlotCode(row.slot_code)}`,
+                                  qty: row.qty,
+                                  available_qty: row.available_qty,
+                                });
+                                setScrapOpen(true);
+                              }}
+                            >
+                              报废
+                            </DropdownMenuItem>
                             <DropdownMenuItem
                               onClick={() => {
                                 navigate(`/txns?slot_id=${encodeURIComponent(row.slot_id || "")}&rack_id=${encodeURIComponent(row.rack_id || "")}&&warehouse_id=${encodeURIComponent(row.warehouse_id || "")}`);
@@ -717,13 +1297,27 @@ export default function StockPage() {
               <TableHeader>
                 <TableRow>
                   <TableHead className="w-16 text-center">选择</TableHead>
-                  <TableHead>仓库</TableHead>
-                  <TableHead>货架</TableHead>
-                  <TableHead>物品编号</TableHead>
-                  <TableHead>物品名称</TableHead>
-                  <TableHead>位置</TableHead>
-                  <TableHead>数量</TableHead>
-                  <TableHead>记录人</TableHead>
+                  <SortableHead field="warehouse_code" sort={itemSort} onSortChange={handleItemSortChange}>
+                    仓库
+                  </SortableHead>
+                  <SortableHead field="rack_code" sort={itemSort} onSortChange={handleItemSortChange}>
+                    货架
+                  </SortableHead>
+                  <SortableHead field="item_code" sort={itemSort} onSortChange={handleItemSortChange}>
+                    物品编号
+                  </SortableHead>
+                  <SortableHead field="item_name" sort={itemSort} onSortChange={handleItemSortChange}>
+                    物品名称
+                  </SortableHead>
+                  <SortableHead field="slot_code" sort={itemSort} onSortChange={handleItemSortChange}>
+                    位置
+                  </SortableHead>
+                  <SortableHead field="qty" sort={itemSort} onSortChange={handleItemSortChange}>
+                    数量
+                  </SortableHead>
+                  <SortableHead field="operator_name" sort={itemSort} onSortChange={handleItemSortChange}>
+                    记录人
+                  </SortableHead>
                   <TableHead className="text-center">操作</TableHead>
                 </TableRow>
               </TableHeader>
@@ -765,7 +1359,14 @@ export default function StockPage() {
                     <TableCell className="font-medium">{row.item_code}</TableCell>
                     <TableCell>{row.item_name}</TableCell>
                     <TableCell>{formatSlotCode(row.slot_code)}</TableCell>
-                    <TableCell>{row.qty}</TableCell>
+                    <TableCell>
+                      {row.qty}
+                      {row.held_qty > 0 ? (
+                        <Badge variant="outline" className="ml-2">
+                          冻结 {row.held_qty}
+                        </Badge>
+                      ) : null}
+                    </TableCell>
                     <TableCell>{row.operator_name || "-"}</TableCell>
                     <TableCell className="text-center">
                       <div className="flex justify-center gap-2">
@@ -806,6 +1407,34 @@ export default function StockPage() {
                             >
                               盘点
                             </DropdownMenuItem>
+                            <DropdownMenuItem
+                              onClick={() => {
+                                setHoldTarget({
+                                  item_id: row.item_id,
+                                  slot_id: row.slot_id || "",
+                                  label: `${row.item_name} @ ${formatSlotCode(row.slot_code)}`,
+                                  available_qty: row.available_qty,
+                                });
+                                setHoldOpen(true);
+                              }}
+                            >
+                              冻结
+                            </DropdownMenuItem>
+                            <DropdownMenuItem
+                              disabled={row.qty <= 0}
+                              onClick={() => {
+                                setScrapTarget({
+                                  item_id: row.item_id,
+                                  slot_id: row.slot_id || "",
+                                  label: `${row.item_name} @ ${formatSlotCode(row.slot_code)}`,
+                                  qty: row.qty,
+                                  available_qty: row.available_qty,
+                                });
+                                setScrapOpen(true);
+                              }}
+                            >
+                              报废
+                            </DropdownMenuItem>
                             <DropdownMenuItem
                               onClick={() => {
                                 navigate(`/txns?slot_code=${encodeURIComponent(row.slot_code)}`);
@@ -15,6 +15,7 @@ import { RackPicker } from "~/components/common/pickers/rack-picker";
 import { ItemPicker } from "~/components/common/pickers/item-picker";
 import { OperatorPicker } from "~/components/common/pickers/operator-picker";
 import { SlotPicker } from "~/components/common/pickers/slot-picker";
+import { ReasonPicker } from "~/components/common/pickers/reason-picker";
 import { Pagination, PaginationContent, PaginationItem, PaginationNext, PaginationPrevious } from "~/components/ui/pagination";
 import { Table, TableBody, TableCell, TableHead, TableHeader, TableRow } from "~/components/ui/table";
 import { Dialog, DialogContent, DialogDescription, DialogHeader, DialogTitle } from "~/components/ui/dialog";
@@ -23,7 +24,9 @@ import { tauriInvoke,revealInFolder } from "~/lib/tauri";
 import { toast } from "sonner";
 import { open } from "@tauri-apps/plugin-dialog";
 import { CommonDialog } from "~/components/common/common-dialogs";
+import { SortableHead, sortParams, type SortState } from "~/components/common/sortable-head";
 import ReversalForm from "~/components/stock/forms/reversal-form";
+import { TxnReasonReportDialog } from "~/components/stock/txn-reason-report-dialog";
 import { type ReversalFormValues } from "~/components/stock/types";
 
 type TxnRow = {
@@ -41,6 +44,10 @@ type TxnRow = {
   from_slot_code?: string | null;
   to_slot_id?: string | null;
   to_slot_code?: string | null;
+  from_rack_name?: string | null;
+  from_warehouse_name?: string | null;
+  to_rack_name?: string | null;
+  to_warehouse_name?: string | null;
   qty: number;
   actual_qty?: number | null;
   ref_txn_id?: string | null;
@@ -56,6 +63,13 @@ type TxnRow = {
   ref_occurred_at?: number | null;
   ref_note?: string | null;
   note?: string | null;
+  location?: string | null;
+  reason_id?: string | null;
+  reason_code?: string | null;
+  reason_name?: string | null;
+  pick_no?: string | null;
+  planned_qty?: number | null;
+  short_reason?: string | null;
 };
 
 type TxnPhotoRow = {
@@ -97,12 +111,13 @@ export default function TxnsPage() {
   const [searchParams] = useSearchParams();
   const [rows, setRows] = useState<TxnRow[]>([]);
   const [loading, setLoading] = useState(false);
-  const [keyword, setKeyword] = useState("");
+  const [keyword, setKeyword] = useState(searchParams.get("keyword") || "");
   const [typeFilter, setTypeFilter] = useState("");
   const [rackFilter, setRackFilter] = useState(searchParams.get("rack_id") || "");
   const [slotFilter, setSlotFilter] = useState(searchParams.get("slot_id") || "");
   const [itemFilter, setItemFilter] = useState(searchParams.get("item_id") || "");
   const [operatorIdFilter, setOperatorIdFilter] = useState(searchParams.get("operator_id") || "");
+  const [reasonFilter, setReasonFilter] = useState("");
   const [warehouseIdFilter, setWarehouseIdFilter] = useState(searchParams.get("warehouse_id") || "");
   const [dateType, setDateType] = useState(searchParams.get("date_type") || "");
 
@@ -112,15 +127,18 @@ export default function TxnsPage() {
   const defaultEndDate = formatDate(today);
   const defaultStartDate = dateType == "day" ? defaultEndDate : formatDate(new Date(today.getTime() - 6 * 24 * 60 * 60 * 1000));
 
-  const [startDate, setStartDate] = useState<string>(defaultStartDate);
-  const [endDate, setEndDate] = useState<string>(defaultEndDate);
+  // 按流水号检索（深度链接）时不限定日期范围
+  const [startDate, setStartDate] = useState<string>(searchParams.get("keyword") ? "" : defaultStartDate);
+  const [endDate, setEndDate] = useState<string>(searchParams.get("keyword") ? "" : defaultEndDate);
   const [pageIndex, setPageIndex] = useState(1);
   const [pageSize] = useState(20);
   const [total, setTotal] = useState(0);
+  const [sort, setSort] = useState<SortState>(null);
   const [detailOpen, setDetailOpen] = useState(false);
   const [activeRow, setActiveRow] = useState<TxnRow | null>(null);
   const [selectedTxnId, setSelectedTxnId] = useState("");
   const [reversalOpen, setReversalOpen] = useState(false);
+  const [reasonReportOpen, setReasonReportOpen] = useState(false);
   const [exportDialogOpen, setExportDialogOpen] = useState(false);
   const [exportFilePath, setExportFilePath] = useState("");
   const [txnPhotoRows, setTxnPhotoRows] = useState<TxnPhotoRow[]>([]);
@@ -130,6 +148,7 @@ export default function TxnsPage() {
   const reversalForm = useForm<ReversalFormValues>({
     defaultValues: {
       txn_no: "",
+      reason_id: "",
       occurred_at: "",
       operator_id: actorOperatorId,
       note: "",
@@ -147,6 +166,7 @@ export default function TxnsPage() {
           warehouse_id: warehouseIdFilter === "" ? undefined : warehouseIdFilter,
           rack_id: rackFilter === "" ? undefined : rackFilter,
           operator_id: operatorIdFilter === "" ? undefined : operatorIdFilter,
+          reason_id: reasonFilter === "" ? undefined : reasonFilter,
           start_at: startDate ? Math.floor(new Date(`${startDate}T00:00:00`).getTime() / 1000) : undefined,
           end_at: endDate ? Math.floor(new Date(`${endDate}T23:59:59`).getTime() / 1000) : undefined,
         },
@@ -167,6 +187,19 @@ export default function TxnsPage() {
     }
   };
 
+  const handleCopyTxn = async (txnNo: string) => {
+    try {
+      const result = await tauriInvoke<{ text: string }>("copy_txn", {
+        input: { txn_no: txnNo },
+      });
+      await navigator.clipboard.writeText(result.text);
+      toast.success("已复制流水摘要");
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "复制失败";
+      toast.error(message);
+    }
+  };
+
   const fetchTxns = async (
     page = pageIndex,
     overrides?: {
@@ -177,6 +210,7 @@ export default function TxnsPage() {
       warehouseIdFilter?: string;
       rackFilter?: string;
       operatorFilter?: string;
+      reasonFilter?: string;
       startDate?: string;
       endDate?: string;
     },
@@ -187,6 +221,7 @@ export default function TxnsPage() {
     const nextSlot = overrides?.slotFilter ?? slotFilter;
     const nextRack = overrides?.rackFilter ?? rackFilter;
     const nextOperator = overrides?.operatorFilter ?? operatorIdFilter;
+    const nextReason = overrides?.reasonFilter ?? reasonFilter;
     const nextStartDate = overrides?.startDate ?? startDate;
     const nextEndDate = overrides?.endDate ?? endDate;
     const startAt = nextStartDate ? Math.floor(new Date(`${nextStartDate}T00:00:00`).getTime() / 1000) : undefined;
@@ -202,10 +237,12 @@ export default function TxnsPage() {
           warehouse_id: warehouseIdFilter === "" ? undefined : warehouseIdFilter,
           rack_id: nextRack === "" ? undefined : nextRack,
           operator_id: nextOperator === "" ? undefined : nextOperator,
+          reason_id: nextReason === "" ? undefined : nextReason,
           start_at: startAt,
           end_at: endAt,
           page_index: page,
           page_size: pageSize,
+          ...sortParams(sort),
         },
       });
       setRows(result.items);
@@ -220,7 +257,23 @@ export default function TxnsPage() {
 
   useEffect(() => {
     fetchTxns(pageIndex);
-  }, [pageIndex]);
+  }, [pageIndex, sort]);
+
+  const handleSortChange = (next: SortState) => {
+    setSort(next);
+    setPageIndex(1);
+  };
+
+  // 深度链接跳转时页面可能已打开，需同步 URL 中的检索词
+  const keywordParam = searchParams.get("keyword");
+  useEffect(() => {
+    if (keywordParam === null || keywordParam === keyword) return;
+    setKeyword(keywordParam);
+    setStartDate("");
+    setEndDate("");
+    setPageIndex(1);
+    void fetchTxns(1, { keyword: keywordParam, startDate: "", endDate: "" });
+  }, [keywordParam]);
 
   const openDetail = (row: TxnRow) => {
     setActiveRow(row);
@@ -374,16 +427,36 @@ export default function TxnsPage() {
         return "移库";
       case "COUNT":
         return "盘点";
+      case "SCRAP":
+        return "报废";
       case "REVERSAL":
         return "冲正";
+      case "OPENING":
+        return "期初";
       default:
         return txnType;
     }
   };
 
+  // 库位附带仓库/货架名称，如“A-01（一号仓 / 货架A）”
+  const slotLocationLabel = (slotCode?: string | null, warehouseName?: string | null, rackName?: string | null) => {
+    if (!slotCode) {
+      return "-";
+    }
+    const context = [warehouseName, rackName].filter(Boolean).join(" / ");
+    return context ? `${slotCode}（${context}）` : slotCode;
+  };
+
   return (
     <div className="space-y-6">
       <CommonDialog title="盘点" description="盘点物品" open={reversalOpen} onOpenChange={setReversalOpen} content={<ReversalForm form={reversalForm} onClose={() => setReversalOpen(false)} />} />
+      <TxnReasonReportDialog
+        open={reasonReportOpen}
+        onOpenChange={setReasonReportOpen}
+        startAt={startDate ? Math.floor(new Date(`${startDate}T00:00:00`).getTime() / 1000) : undefined}
+        endAt={endDate ? Math.floor(new Date(`${endDate}T23:59:59`).getTime() / 1000) : undefined}
+        warehouseId={warehouseIdFilter}
+      />
 
       <PageHeader
         title="流水查询"
@@ -411,6 +484,9 @@ export default function TxnsPage() {
             >
               冲正
             </Button>
+            <Button variant="outline" onClick={() => setReasonReportOpen(true)}>
+              原因统计
+            </Button>
             <Button variant="outline" onClick={handleExportTxns}>
               导出流水
             </Button>
@@ -457,6 +533,10 @@ export default function TxnsPage() {
           <Label>记录人</Label>
           <OperatorPicker value={operatorIdFilter} onChange={(v) => setOperatorIdFilter(v || "")} placeholder="全部" />
         </div>
+        <div className="min-w-[140px] max-w-[140px] space-y-2">
+          <Label>原因</Label>
+          <ReasonPicker value={reasonFilter} onChange={setReasonFilter} emptyLabel="全部" />
+        </div>
         <div className="flex-1 space-y-2">
           <Label>类型</Label>
           <Select value={typeFilter} onValueChange={setTypeFilter}>
@@ -469,7 +549,9 @@ export default function TxnsPage() {
               <SelectItem value="OUT">出库</SelectItem>
               <SelectItem value="MOVE">移库</SelectItem>
               <SelectItem value="COUNT">盘点</SelectItem>
+              <SelectItem value="SCRAP">报废</SelectItem>
               <SelectItem value="REVERSAL">冲正</SelectItem>
+              <SelectItem value="OPENING">期初</SelectItem>
             </SelectContent>
           </Select>
         </div>
@@ -494,8 +576,9 @@ export default function TxnsPage() {
             setSlotFilter("");
             setItemFilter("");
             setOperatorIdFilter("");
+            setReasonFilter("");
             setPageIndex(1);
-            void fetchTxns(1, { startDate: defaultStartDate, endDate: defaultEndDate });
+            void fetchTxns(1, { reasonFilter: "", startDate: defaultStartDate, endDate: defaultEndDate });
           }}
         >
           重置
@@ -507,13 +590,25 @@ export default function TxnsPage() {
           <TableHeader>
             <TableRow>
               <TableHead className="w-16 text-center">选择</TableHead>
-              <TableHead>流水号</TableHead>
-              <TableHead>类型</TableHead>
-              <TableHead>物品</TableHead>
+              <SortableHead field="txn_no" sort={sort} onSortChange={handleSortChange}>
+                流水号
+              </SortableHead>
+              <SortableHead field="txn_type" sort={sort} onSortChange={handleSortChange}>
+                类型
+              </SortableHead>
+              <SortableHead field="item_code" sort={sort} onSortChange={handleSortChange}>
+                物品
+              </SortableHead>
               <TableHead>库位</TableHead>
-              <TableHead>数量</TableHead>
-              <TableHead>记录人</TableHead>
-              <TableHead>发生时间</TableHead>
+              <SortableHead field="qty" sort={sort} onSortChange={handleSortChange}>
+                数量
+              </SortableHead>
+              <SortableHead field="operator_name" sort={sort} onSortChange={handleSortChange}>
+                记录人
+              </SortableHead>
+              <SortableHead field="occurred_at" sort={sort} onSortChange={handleSortChange}>
+                发生时间
+              </SortableHead>
               <TableHead>状态</TableHead>
               <TableHead className="text-center">操作</TableHead>
             </TableRow>
@@ -538,10 +633,23 @@ export default function TxnsPage() {
                   />
                 </TableCell>
                 <TableCell className="max-w-[140px] truncate font-medium">{row.txn_no}</TableCell>
-                <TableCell>{txnTypeLabel(row.txn_type)}</TableCell>
+                <TableCell>
+                  {txnTypeLabel(row.txn_type)}
+                  {row.reason_name ? <span className="ml-1 text-xs text-slate-500">（{row.reason_name}）</span> : null}
+                </TableCell>
                 <TableCell>{row.item_name}</TableCell>
-                <TableCell>{row.from_slot_code || row.to_slot_code || "-"}</TableCell>
-                <TableCell>{row.qty}</TableCell>
+                <TableCell>
+                  {row.from_slot_code || row.to_slot_code || "-"}
+                  {row.from_warehouse_name || row.to_warehouse_name ? (
+                    <span className="ml-1 text-xs text-slate-500">{row.from_slot_code ? row.from_warehouse_name : row.to_warehouse_name}</span>
+                  ) : null}
+                </TableCell>
+                <TableCell>
+                  {row.qty}
+                  {row.planned_qty != null && row.planned_qty !== row.qty ? (
+                    <span className="ml-1 text-xs text-amber-600">/ 计划 {row.planned_qty}</span>
+                  ) : null}
+                </TableCell>
                 <TableCell>{row.operator_name}</TableCell>
                 <TableCell>{new Date(row.occurred_at * 1000).toLocaleString()}</TableCell>
                 <TableCell>
@@ -631,13 +739,33 @@ export default function TxnsPage() {
                 <span>记录人：{activeRow.operator_name}</span>
               </div>
               <div className="flex flex-wrap gap-6">
-                <span>来源库位：{activeRow.from_slot_code || "-"}</span>
-                <span>目标库位：{activeRow.to_slot_code || "-"}</span>
+                <span>来源库位：{slotLocationLabel(activeRow.from_slot_code, activeRow.from_warehouse_name, activeRow.from_rack_name)}</span>
+                <span>目标库位：{slotLocationLabel(activeRow.to_slot_code, activeRow.to_warehouse_name, activeRow.to_rack_name)}</span>
               </div>
               <div className="flex flex-wrap gap-6">
                 <span>冲正关联：{activeRow.ref_txn_id || "-"}</span>
                 <span>实盘数量：{activeRow.actual_qty ?? "-"}</span>
               </div>
+              {activeRow.reason_name ? (
+                <div className="flex flex-wrap gap-6">
+                  <span>
+                    原因：{activeRow.reason_name}（{activeRow.reason_code}）
+                  </span>
+                </div>
+              ) : null}
+              {activeRow.location ? (
+                <div className="flex flex-wrap gap-6">
+                  <span>位置：{activeRow.location}</span>
+                </div>
+              ) : null}
+              {activeRow.pick_no ? (
+                <div className="flex flex-wrap gap-6">
+                  <span>拣货单：{activeRow.pick_no}</span>
+                  <span>计划数量：{activeRow.planned_qty ?? "-"}</span>
+                  <span>实拣数量：{activeRow.qty}</span>
+                  {activeRow.short_reason ? <span>短拣原因：{activeRow.short_reason}</span> : null}
+                </div>
+              ) : null}
               {activeRow.txn_type === "REVERSAL" ? (
                 <div className="rounded-xl border border-slate-200 bg-slate-50 p-3 text-xs text-slate-500">
                   <div className="mb-2 text-sm font-medium text-slate-600">关联流水详情</div>
@@ -669,6 +797,11 @@ export default function TxnsPage() {
                 </div>
               ) : null}
               <div className="rounded-xl border border-slate-200 bg-slate-50 p-3 text-xs text-slate-500">备注：{activeRow.note || "-"}</div>
+              <div>
+                <Button variant="outline" size="sm" onClick={() => handleCopyTxn(activeRow.txn_no)}>
+                  复制摘要
+                </Button>
+              </div>
               <div className="grid gap-2">
                 {txnPhotoLoading ? (
                   <div className="text-xs text-slate-500">加载中...</div>
@@ -1,5 +1,6 @@
 import { useEffect, useState } from "react"
 import { useNavigate } from "react-router"
+import { open } from "@tauri-apps/plugin-dialog"
 import { useForm } from "react-hook-form"
 import { PageHeader } from "~/components/common/page-header"
 import { Badge } from "~/components/ui/badge"
@@ -33,8 +34,13 @@ import {
   TableHeader,
   TableRow,
 } from "~/components/ui/table"
+import { ItemPicker } from "~/components/common/pickers/item-picker"
+import { SlotPicker } from "~/components/common/pickers/slot-picker"
+import { WarehousePicker } from "~/components/common/pickers/warehouse-picker"
 import { getSession } from "~/lib/auth"
 import { tauriInvoke } from "~/lib/tauri"
+import { useIdempotencyKey } from "~/lib/use-idempotency-key"
+import { submitOrQueue } from "~/lib/offline-queue"
 import { toast } from "sonner"
 
 type WarehouseRow = {
@@ -42,6 +48,8 @@ type WarehouseRow = {
   code: string
   name: string
   status: string
+  kind: string
+  base_warehouse_id?: string | null
   created_at: number
 }
 
@@ -50,9 +58,49 @@ type WarehouseListResult = {
   total: number
 }
 
+type SlotUtilization = {
+  total_slots: number
+  empty_slots: number
+  occupied_slots: number
+  inactive_slots: number
+  fill_rate: number
+}
+
+type WarehouseUtilizationReport = {
+  warehouses: (SlotUtilization & { warehouse_id?: string | null })[]
+  summary: SlotUtilization
+}
+
 type WarehouseFormValues = {
   codeSuffix: string
   name: string
+  kind: string
+  baseWarehouseId: string
+  compartments: string
+}
+
+type TransferFormValues = {
+  direction: string
+  item_id: string
+  qty: string
+  base_slot_id: string
+  vehicle_slot_id: string
+  note: string
+}
+
+const emptyTransferForm: TransferFormValues = {
+  direction: "load",
+  item_id: "",
+  qty: "",
+  base_slot_id: "",
+  vehicle_slot_id: "",
+  note: "",
+}
+
+type StructureImportResult = {
+  warehouses_created: number
+  racks_created: number
+  slots_created: number
 }
 
 export default function WarehousesPage() {
@@ -66,10 +114,18 @@ export default function WarehousesPage() {
   const [pageIndex, setPageIndex] = useState(1)
   const [pageSize] = useState(20)
   const [total, setTotal] = useState(0)
+  const [utilization, setUtilization] = useState<Record<string, SlotUtilization>>({})
+  const [transferRow, setTransferRow] = useState<WarehouseRow | null>(null)
+  const [transferForm, setTransferForm] = useState<TransferFormValues>(emptyTransferForm)
+  const [transferring, setTransferring] = useState(false)
+  const transferKey = useIdempotencyKey()
   const form = useForm<WarehouseFormValues>({
     defaultValues: {
       codeSuffix: "",
       name: "",
+      kind: "fixed",
+      baseWarehouseId: "",
+      compartments: "",
     },
   })
 
@@ -93,6 +149,16 @@ export default function WarehousesPage() {
       })
       setRows(result.items)
       setTotal(result.total)
+      const report = await tauriInvoke<WarehouseUtilizationReport>("get_warehouse_utilization", {
+        query: {},
+      })
+      setUtilization(
+        Object.fromEntries(
+          report.warehouses
+            .filter((row) => row.warehouse_id)
+            .map((row) => [row.warehouse_id as string, row]),
+        ),
+      )
     } catch (err) {
       const message = err instanceof Error ? err.message : "加载失败"
       toast.error(message)
@@ -112,6 +178,9 @@ export default function WarehousesPage() {
     form.reset({
       codeSuffix: "",
       name: "",
+      kind: "fixed",
+      baseWarehouseId: "",
+      compartments: "",
     })
   }
 
@@ -126,6 +195,9 @@ export default function WarehousesPage() {
     form.reset({
       codeSuffix: row.code,
       name: row.name,
+      kind: row.kind,
+      baseWarehouseId: row.base_warehouse_id ?? "",
+      compartments: "",
     })
     setFormOpen(true)
   }
@@ -139,6 +211,7 @@ export default function WarehousesPage() {
   }
   const formattedSuffix = formatSuffix(codeSuffix || "")
   const warehouseCode = formattedSuffix
+  const formKind = form.watch("kind")
 
   const handleSubmit = async (values: WarehouseFormValues) => {
     const formattedCode = formatSuffix(values.codeSuffix || "")
@@ -147,6 +220,11 @@ export default function WarehousesPage() {
       return
     }
     const name = values.name.trim() || formattedCode
+    const isMobile = values.kind === "mobile"
+    if (!editRow && isMobile && !values.baseWarehouseId) {
+      toast.error("移动仓库需选择归属仓库")
+      return
+    }
     try {
       if (editRow) {
         await tauriInvoke("update_warehouse", {
@@ -161,6 +239,12 @@ export default function WarehousesPage() {
           input: {
             code: formattedCode,
             name,
+            kind: values.kind,
+            base_warehouse_id: isMobile ? values.baseWarehouseId : undefined,
+            compartments:
+              isMobile && values.compartments.trim()
+                ? Number(values.compartments)
+                : undefined,
           },
         })
         toast.success("仓库创建成功")
@@ -191,6 +275,74 @@ export default function WarehousesPage() {
     }
   }
 
+  const openTransfer = (row: WarehouseRow) => {
+    setTransferRow(row)
+    setTransferForm(emptyTransferForm)
+  }
+
+  const handleTransfer = async () => {
+    if (!transferRow) return
+    const qty = Number(transferForm.qty)
+    if (!transferForm.item_id) {
+      toast.error("请选择物品")
+      return
+    }
+    if (!Number.isFinite(qty) || qty <= 0) {
+      toast.error("数量必须大于 0")
+      return
+    }
+    setTransferring(true)
+    try {
+      const submitted = await submitOrQueue("create_quick_transfer", {
+        input: {
+          vehicle_warehouse_id: transferRow.id,
+          direction: transferForm.direction,
+          item_id: transferForm.item_id,
+          qty,
+          base_slot_id: transferForm.base_slot_id || undefined,
+          vehicle_slot_id: transferForm.vehicle_slot_id || undefined,
+          occurred_at: Math.floor(Date.now() / 1000),
+          note: transferForm.note.trim() || undefined,
+          idempotency_key: transferKey.current(),
+        },
+      }, "快捷调拨")
+      transferKey.renew()
+      toast.success(
+        submitted.queued
+          ? "写入暂停，已暂存，恢复后自动补交"
+          : `${transferForm.direction === "load" ? "装车" : "卸车"}成功：${submitted.value}`
+      )
+      setTransferRow(null)
+      await fetchWarehouses(keyword, status)
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "调拨失败"
+      toast.error(message)
+    } finally {
+      setTransferring(false)
+    }
+  }
+
+  // 结构导入：每行一个货架，仓库不存在时一并新建，整个文件要么全部导入要么全部不导入
+  const handleImportStructure = async () => {
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: "CSV", extensions: ["csv"] }],
+    })
+    if (!selected || Array.isArray(selected)) return
+    try {
+      const result = await tauriInvoke<StructureImportResult>("import_structure", {
+        input: { file_path: selected },
+      })
+      toast.success(
+        `导入完成：新建仓库 ${result.warehouses_created} 个、货架 ${result.racks_created} 个、库位 ${result.slots_created} 个`
+      )
+      await fetchWarehouses(keyword, status)
+    } catch (err) {
+      const message = err instanceof Error ? err.message : "导入失败"
+      toast.error(message)
+    }
+  }
+
   const handleFilter = async () => {
     setPageIndex(1)
     await fetchWarehouses(keyword, status, 1)
@@ -202,93 +354,160 @@ export default function WarehousesPage() {
         title="仓库管理"
         description="维护仓库档案与状态，供货架绑定。"
         actions={
-          <Dialog
-            open={formOpen}
-            onOpenChange={(open) => {
-              setFormOpen(open)
-              if (!open) {
-                setEditRow(null)
-                resetForm()
-              }
-            }}
-          >
-            <DialogTrigger asChild>
-              <Button onClick={openCreate}>新增仓库</Button>
-            </DialogTrigger>
-            <DialogContent className="max-w-lg">
-              <DialogHeader>
-                <DialogTitle>{editRow ? "编辑仓库" : "新增仓库"}</DialogTitle>
-                <DialogDescription>
-                  {editRow ? "更新仓库名称" : "填写仓库基础信息"}
-                </DialogDescription>
-              </DialogHeader>
-              <Form {...form}>
-                <form
-                  onSubmit={(event) => {
-                    event.preventDefault()
-                    void form.handleSubmit(handleSubmit)()
-                  }}
-                  className="space-y-4"
-                >
-                  <FormField
-                    control={form.control}
-                    name="codeSuffix"
-                    rules={{
-                      validate: (value) => {
-                        if (editRow) return true
-                        const trimmed = value.trim().replace(/^W+/i, "")
-                        if (!trimmed) return "请输入仓库编号"
-                        if (!/^\d+$/.test(trimmed)) return "仓库编号只能输入数字"
-                        if (Number(trimmed) <= 0) return "仓库编号必须大于 0"
-                        return true
-                      },
+          <div className="flex flex-wrap gap-2">
+            <Button variant="outline" onClick={() => void handleImportStructure()}>
+              导入结构
+            </Button>
+            <Dialog
+              open={formOpen}
+              onOpenChange={(open) => {
+                setFormOpen(open)
+                if (!open) {
+                  setEditRow(null)
+                  resetForm()
+                }
+              }}
+            >
+              <DialogTrigger asChild>
+                <Button onClick={openCreate}>新增仓库</Button>
+              </DialogTrigger>
+              <DialogContent className="max-w-lg">
+                <DialogHeader>
+                  <DialogTitle>{editRow ? "编辑仓库" : "新增仓库"}</DialogTitle>
+                  <DialogDescription>
+                    {editRow ? "更新仓库名称" : "填写仓库基础信息"}
+                  </DialogDescription>
+                </DialogHeader>
+                <Form {...form}>
+                  <form
+                    onSubmit={(event) => {
+                      event.preventDefault()
+                      void form.handleSubmit(handleSubmit)()
                     }}
-                    render={({ field }) => (
-                      <FormItem className="grid gap-2">
-                        <FormLabel htmlFor="warehouse-code">仓库编号</FormLabel>
-                        <FormControl>
-                          <Input
-                            id="warehouse-code"
-                            placeholder="例如 1"
-                            disabled={!!editRow}
-                            type="number"
-                            min={1}
-                            inputMode="numeric"
-                            pattern="[0-9]*"
-                            {...field}
-                          />
-                        </FormControl>
-                        <FormMessage />
-                      </FormItem>
+                    className="space-y-4"
+                  >
+                    <FormField
+                      control={form.control}
+                      name="codeSuffix"
+                      rules={{
+                        validate: (value) => {
+                          if (editRow) return true
+                          const trimmed = value.trim().replace(/^W+/i, "")
+                          if (!trimmed) return "请输入仓库编号"
+                          if (!/^\d+$/.test(trimmed)) return "仓库编号只能输入数字"
+                          if (Number(trimmed) <= 0) return "仓库编号必须大于 0"
+                          return true
+                        },
+                      }}
+                      render={({ field }) => (
+                        <FormItem className="grid gap-2">
+                          <FormLabel htmlFor="warehouse-code">仓库编号</FormLabel>
+                          <FormControl>
+                            <Input
+                              id="warehouse-code"
+                              placeholder="例如 1"
+                              disabled={!!editRow}
+                              type="number"
+                              min={1}
+                              inputMode="numeric"
+                              pattern="[0-9]*"
+                              {...field}
+                            />
+                          </FormControl>
+                          <FormMessage />
+                        </FormItem>
+                      )}
+                    />
+                    <FormField
+                      control={form.control}
+                      name="name"
+                      rules={{
+                        validate: () => true,
+                      }}
+                      render={({ field }) => (
+                        <FormItem className="grid gap-2">
+                          <FormLabel htmlFor="warehouse-name">仓库名称</FormLabel>
+                          <FormControl>
+                            <Input id="warehouse-name" placeholder="选填，默认使用编号作为名称" {...field} />
+                          </FormControl>
+                          <FormMessage />
+                        </FormItem>
+                      )}
+                    />
+                    {editRow ? null : (
+                      <FormField
+                        control={form.control}
+                        name="kind"
+                        render={({ field }) => (
+                          <FormItem className="grid gap-2">
+                            <FormLabel>仓库类型</FormLabel>
+                            <Select value={field.value} onValueChange={field.onChange}>
+                              <FormControl>
+                                <SelectTrigger>
+                                  <SelectValue placeholder="请选择" />
+                                </SelectTrigger>
+                              </FormControl>
+                              <SelectContent>
+                                <SelectItem value="fixed">固定仓库</SelectItem>
+                                <SelectItem value="mobile">移动仓库（车辆/工具箱）</SelectItem>
+                              </SelectContent>
+                            </Select>
+                            <FormMessage />
+                          </FormItem>
+                        )}
+                      />
                     )}
-                  />
-                  <FormField
-                    control={form.control}
-                    name="name"
-                    rules={{
-                      validate: () => true,
-                    }}
-                    render={({ field }) => (
-                      <FormItem className="grid gap-2">
-                        <FormLabel htmlFor="warehouse-name">仓库名称</FormLabel>
-                        <FormControl>
-                          <Input id="warehouse-name" placeholder="选填，默认使用编号作为名称" {...field} />
-                        </FormControl>
-                        <FormMessage />
-                      </FormItem>
-                    )}
-                  />
-                  <ConfirmButton
-                    className="w-full"
-                    label="保存"
-                    confirmText={editRow ? "确认保存仓库变更？" : "确认创建仓库？"}
-                    onBeforeConfirmOpen={() => form.trigger()}
-                    onConfirm={() => form.handleSubmit(handleSubmit)()}
-                  />
-                </form>
-              </Form>
-            </DialogContent>
-          </Dialog>
+                    {!editRow && formKind === "mobile" ? (
+                      <>
+                        <FormField
+                          control={form.control}
+                          name="baseWarehouseId"
+                          render={({ field }) => (
+                            <FormItem className="grid gap-2">
+                              <FormLabel>归属仓库</FormLabel>
+                              <WarehousePicker
+                                value={field.value}
+                                onChange={(value) => field.onChange(value || "")}
+                                placeholder="选择装卸所对应的固定仓库"
+                              />
+                              <FormMessage />
+                            </FormItem>
+                          )}
+                        />
+                        <FormField
+                          control={form.control}
+                          name="compartments"
+                          render={({ field }) => (
+                            <FormItem className="grid gap-2">
+                              <FormLabel htmlFor="warehouse-compartments">格口数</FormLabel>
+                              <FormControl>
+                                <Input
+                                  id="warehouse-compartments"
+                                  placeholder="选填，默认 1 个格口"
+                                  type="number"
+                                  min={1}
+                                  inputMode="numeric"
+                                  {...field}
+                                />
+                              </FormControl>
+                              <FormMessage />
+                            </FormItem>
+                          )}
+                        />
+                      </>
+                    ) : null}
+                    <ConfirmButton
+                      className="w-full"
+                      label="保存"
+                      confirmText={editRow ? "确认保存仓库变更？" : "确认创建仓库？"}
+                      onBeforeConfirmOpen={() => form.trigger()}
+                      onConfirm={() => form.handleSubmit(handleSubmit)()}
+                    />
+                  </form>
+                </Form>
+              </DialogContent>
+            </Dialog>
+          </div>
         }
       />
 
@@ -337,6 +556,7 @@ export default function WarehousesPage() {
               <TableHead>仓库编号</TableHead>
               <TableHead>仓库名称</TableHead>
               <TableHead>状态</TableHead>
+              <TableHead>库位利用率</TableHead>
               <TableHead>创建时间</TableHead>
               <TableHead className="text-center">操作</TableHead>
             </TableRow>
@@ -345,12 +565,22 @@ export default function WarehousesPage() {
             {rows.map((row) => (
               <TableRow key={row.id}>
                 <TableCell className="font-medium">{row.code}</TableCell>
-                <TableCell>{row.name}</TableCell>
+                <TableCell>
+                  <div className="flex items-center gap-2">
+                    <span>{row.name}</span>
+                    {row.kind === "mobile" ? <Badge variant="outline">移动</Badge> : null}
+                  </div>
+                </TableCell>
                 <TableCell>
                   <Badge variant={row.status === "active" ? "secondary" : "outline"}>
                     {row.status === "active" ? "启用" : "停用"}
                   </Badge>
                 </TableCell>
+                <TableCell>
+                  {utilization[row.id]
+                    ? `${utilization[row.id].fill_rate.toFixed(1)}% · 占用 ${utilization[row.id].occupied_slots} / 空 ${utilization[row.id].empty_slots} / 停用 ${utilization[row.id].inactive_slots}`
+                    : "-"}
+                </TableCell>
                 <TableCell>
                   {new Date(row.created_at * 1000).toLocaleString()}
                 </TableCell>
@@ -381,6 +611,11 @@ export default function WarehousesPage() {
                       >
                         查看流水
                       </DropdownMenuItem>
+                      {row.kind === "mobile" ? (
+                        <DropdownMenuItem onClick={() => openTransfer(row)}>
+                          装车/卸车
+                        </DropdownMenuItem>
+                      ) : null}
                       <DropdownMenuItem
                         onClick={() => handleToggleStatus(row)}
                       >
@@ -393,7 +628,7 @@ export default function WarehousesPage() {
             ))}
             {!loading && rows.length === 0 ? (
               <TableRow>
-                <TableCell colSpan={5} className="text-center text-slate-500">
+                <TableCell colSpan={6} className="text-center text-slate-500">
                   暂无仓库数据
                 </TableCell>
               </TableRow>
@@ -402,6 +637,90 @@ export default function WarehousesPage() {
         </Table>
       </div>
 
+      <Dialog
+        open={!!transferRow}
+        onOpenChange={(open) => {
+          if (!open) setTransferRow(null)
+        }}
+      >
+        <DialogContent className="max-w-lg">
+          <DialogHeader>
+            <DialogTitle>装车/卸车</DialogTitle>
+            <DialogDescription>
+              {transferRow ? `${transferRow.name} 与归属仓库之间调拨，未指定库位时自动选择` : ""}
+            </DialogDescription>
+          </DialogHeader>
+          <div className="space-y-4">
+            <div className="grid gap-2">
+              <Label>方向</Label>
+              <Select
+                value={transferForm.direction}
+                onValueChange={(value) =>
+                  setTransferForm({ ...transferForm, direction: value, base_slot_id: "", vehicle_slot_id: "" })
+                }
+              >
+                <SelectTrigger>
+                  <SelectValue placeholder="请选择" />
+                </SelectTrigger>
+                <SelectContent>
+                  <SelectItem value="load">装车（归属仓库 → 移动仓库）</SelectItem>
+                  <SelectItem value="unload">卸车（移动仓库 → 归属仓库）</SelectItem>
+                </SelectContent>
+              </Select>
+            </div>
+            <div className="grid gap-2">
+              <Label>物品</Label>
+              <ItemPicker
+                value={transferForm.item_id}
+                onChange={(value) => setTransferForm({ ...transferForm, item_id: value || "" })}
+              />
+            </div>
+            <div className="grid gap-2">
+              <Label>数量</Label>
+              <Input
+                type="number"
+                min={0}
+                step="any"
+                inputMode="decimal"
+                value={transferForm.qty}
+                onChange={(event) => setTransferForm({ ...transferForm, qty: event.target.value })}
+              />
+            </div>
+            <div className="grid gap-2">
+              <Label>归属仓库库位（选填）</Label>
+              <SlotPicker
+                warehouseId={transferRow?.base_warehouse_id || undefined}
+                value={transferForm.base_slot_id}
+                onChange={(value) => setTransferForm({ ...transferForm, base_slot_id: value || "" })}
+              />
+            </div>
+            <div className="grid gap-2">
+              <Label>移动仓库格口（选填）</Label>
+              <SlotPicker
+                warehouseId={transferRow?.id}
+                value={transferForm.vehicle_slot_id}
+                onChange={(value) => setTransferForm({ ...transferForm, vehicle_slot_id: value || "" })}
+              />
+            </div>
+            <div className="grid gap-2">
+              <Label>备注</Label>
+              <Input
+                placeholder="选填"
+                value={transferForm.note}
+                onChange={(event) => setTransferForm({ ...transferForm, note: event.target.value })}
+              />
+            </div>
+            <ConfirmButton
+              className="w-full"
+              label={transferForm.direction === "load" ? "确认装车" : "确认卸车"}
+              disabled={transferring}
+              confirmText="确认提交调拨？"
+              onConfirm={handleTransfer}
+            />
+          </div>
+        </DialogContent>
+      </Dialog>
+
       {total > 0 ? (
         <Pagination className="justify-between">
           <p className="text-xs text-slate-500">
@@ -0,0 +1,11 @@
+-- 迁移说明：登录失败计数与账号锁定（0002_login_attempt.sql）
+-- 按用户名记录连续失败次数，达到阈值后锁定一段时间；用户名不存在时同样计数，避免枚举账号
+
+-- 登录尝试表：`failed_count` 为窗口内连续失败次数，`locked_until` 为锁定截止时间（秒级时间戳）
+CREATE TABLE IF NOT EXISTS login_attempt (
+  username TEXT PRIMARY KEY,
+  failed_count INTEGER NOT NULL DEFAULT 0,
+  first_failed_at INTEGER NOT NULL,
+  last_failed_at INTEGER NOT NULL,
+  locked_until INTEGER
+);
@@ -0,0 +1,13 @@
+-- 迁移说明：密码历史（0003_password_history.sql）
+-- 记录每次设置密码的哈希，用于密码策略中"禁止复用最近 N 次密码"的校验
+
+-- 密码历史表：仅保存哈希，不保存明文
+CREATE TABLE IF NOT EXISTS password_history (
+  id TEXT PRIMARY KEY,
+  operator_id TEXT NOT NULL,
+  password_hash TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  FOREIGN KEY(operator_id) REFERENCES operator(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_history_operator_time ON password_history(operator_id, created_at);
@@ -0,0 +1,20 @@
+-- 迁移说明：人员快捷 PIN 与会话（0004_operator_pin_session.sql）
+-- 1) operator 增加 pin_hash，用于共享终端上的快速切换人员
+-- 2) operator_session 记录登录/切换产生的会话，PIN 会话为短时会话
+
+ALTER TABLE operator ADD COLUMN pin_hash TEXT;
+
+-- 会话表：`source` 区分密码登录与 PIN 切换，`expires_at` 为空表示不限时，`ended_at` 非空表示已结束
+CREATE TABLE IF NOT EXISTS operator_session (
+  id TEXT PRIMARY KEY,
+  operator_id TEXT NOT NULL,
+  source TEXT NOT NULL CHECK(source IN ('password','pin')),
+  created_at INTEGER NOT NULL,
+  last_active_at INTEGER NOT NULL,
+  expires_at INTEGER,
+  ended_at INTEGER,
+  end_reason TEXT,
+  FOREIGN KEY(operator_id) REFERENCES operator(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_operator_session_operator ON operator_session(operator_id, ended_at);
@@ -0,0 +1,14 @@
+-- 迁移说明：人员仓库范围（0005_operator_warehouse.sql）
+-- 1) operator_warehouse 记录人员可访问的仓库，启用 RBAC 时用于限制库存/流水的查询与录入
+-- 2) 人员无任何分配记录时视为不限仓库，管理员始终不受限
+
+CREATE TABLE IF NOT EXISTS operator_warehouse (
+  operator_id TEXT NOT NULL,
+  warehouse_id TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  PRIMARY KEY(operator_id, warehouse_id),
+  FOREIGN KEY(operator_id) REFERENCES operator(id),
+  FOREIGN KEY(warehouse_id) REFERENCES warehouse(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_operator_warehouse_warehouse ON operator_warehouse(warehouse_id);
@@ -0,0 +1,4 @@
+-- 迁移说明：物品单位成本（0006_item_unit_cost.sql）
+-- 1) item 增加 unit_cost，用于计算库存价值；为空表示未设置成本，按 0 计值
+
+ALTER TABLE item ADD COLUMN unit_cost REAL;
@@ -0,0 +1,15 @@
+-- 迁移说明：照片存储巡检指标（0007_photo_storage_metric.sql）
+-- 1) photo_storage_metric 记录每次巡检时 photos 目录的文件数、总大小与数据库附件记录的对比结果
+-- 2) 通过与上一次巡检比较，发现手工删改文件或同步工具导致的附件缺失/多余
+
+CREATE TABLE IF NOT EXISTS photo_storage_metric (
+  id TEXT PRIMARY KEY,
+  scanned_at INTEGER NOT NULL,
+  db_count INTEGER NOT NULL,
+  file_count INTEGER NOT NULL,
+  total_bytes INTEGER NOT NULL,
+  missing_count INTEGER NOT NULL,
+  orphan_count INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_photo_storage_metric_scanned_at ON photo_storage_metric(scanned_at);
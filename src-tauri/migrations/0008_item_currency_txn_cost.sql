@@ -0,0 +1,6 @@
+-- 迁移说明：物品币种与入库成本（0008_item_currency_txn_cost.sql）
+-- 1) item 增加 currency，记录单位成本的币种（如 CNY），为空表示未设置
+-- 2) txn 增加 unit_cost，记录入库时的单位成本，用于移动加权平均 / 先进先出计价；历史流水为空时按物品当前成本计
+
+ALTER TABLE item ADD COLUMN currency TEXT;
+ALTER TABLE txn ADD COLUMN unit_cost REAL;
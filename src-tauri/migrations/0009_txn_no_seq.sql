@@ -0,0 +1,10 @@
+-- 迁移说明：结构化流水号序列（0009_txn_no_seq.sql）
+-- 1) txn_no_seq 按（前缀, 流水类型）记录已分配的最大序号，用于生成 IN-0012 / W1-IN-0012 形式的流水号
+-- 2) 前缀为空串表示不带仓库前缀的序列；历史 T<uuid> 流水号保持不变
+
+CREATE TABLE IF NOT EXISTS txn_no_seq (
+  prefix TEXT NOT NULL,
+  txn_type TEXT NOT NULL,
+  last_no INTEGER NOT NULL,
+  PRIMARY KEY (prefix, txn_type)
+);
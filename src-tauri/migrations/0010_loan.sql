@@ -0,0 +1,28 @@
+-- 迁移说明：工具借用/归还（0010_loan.sql）
+-- 1) loan 记录借用人、应还时间与借出/归还对应的流水，借出时生成 OUT 流水扣减库存，归还时生成 IN 流水
+-- 2) status：open（借出中）/ returned（已归还）；逾期由 due_at 与当前时间比较得出，不单独存储
+
+CREATE TABLE IF NOT EXISTS loan (
+  id TEXT PRIMARY KEY,
+  loan_no TEXT NOT NULL UNIQUE,
+  item_id TEXT NOT NULL REFERENCES item(id),
+  slot_id TEXT NOT NULL REFERENCES slot(id),
+  qty INTEGER NOT NULL CHECK(qty > 0),
+  borrower TEXT NOT NULL,
+  due_at INTEGER,
+  status TEXT NOT NULL CHECK(status IN ('open','returned')),
+  loaned_at INTEGER NOT NULL,
+  operator_id TEXT NOT NULL REFERENCES operator(id),
+  out_txn_id TEXT NOT NULL REFERENCES txn(id),
+  returned_at INTEGER,
+  return_slot_id TEXT REFERENCES slot(id),
+  return_operator_id TEXT REFERENCES operator(id),
+  return_txn_id TEXT REFERENCES txn(id),
+  note TEXT,
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_status_due ON loan(status, due_at);
+CREATE INDEX IF NOT EXISTS idx_loan_item ON loan(item_id);
+CREATE INDEX IF NOT EXISTS idx_loan_out_txn ON loan(out_txn_id);
+CREATE INDEX IF NOT EXISTS idx_loan_return_txn ON loan(return_txn_id);
@@ -0,0 +1,3 @@
+-- 迁移说明：流水位置（0011_txn_location.sql）
+-- 1) txn 增加 location，移动端开启位置采集后记录粗略位置（"纬度,经度"，保留两位小数，约 1 公里精度），为空表示未采集
+ALTER TABLE txn ADD COLUMN location TEXT;
@@ -0,0 +1,5 @@
+-- 迁移说明：移动仓库（0012_warehouse_kind.sql）
+-- 1) warehouse 增加 kind，区分固定仓库（fixed，默认）与车辆、工具箱等移动仓库（mobile）
+-- 2) warehouse 增加 base_warehouse_id，记录移动仓库的归属基地仓库，用于装车/卸车快捷调拨
+ALTER TABLE warehouse ADD COLUMN kind TEXT NOT NULL DEFAULT 'fixed' CHECK(kind IN ('fixed','mobile'));
+ALTER TABLE warehouse ADD COLUMN base_warehouse_id TEXT REFERENCES warehouse(id);
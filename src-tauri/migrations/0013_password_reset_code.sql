@@ -0,0 +1,11 @@
+-- 迁移说明：管理员签发的一次性密码重置码（0013_password_reset_code.sql）
+-- 1) 每个人员最多一条有效重置码，重新签发时覆盖旧码；仅保存哈希，明文只在签发时返回一次
+-- 2) 使用成功或过期后删除记录
+
+CREATE TABLE IF NOT EXISTS password_reset_code (
+  operator_id TEXT PRIMARY KEY REFERENCES operator(id),
+  code_hash TEXT NOT NULL,
+  issued_by TEXT NOT NULL REFERENCES operator(id),
+  issued_at INTEGER NOT NULL,
+  expires_at INTEGER NOT NULL
+);
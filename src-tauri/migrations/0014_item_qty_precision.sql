@@ -0,0 +1,5 @@
+-- 迁移说明：小数数量（0014_item_qty_precision.sql）
+-- 1) item 增加 qty_precision（0-3，默认 0 即整数），表示该物品数量保留的小数位数
+-- 2) stock.qty / txn.qty / txn.actual_qty / loan.qty 仍为整数列，按物品精度存放放大后的数量（数量 × 10^qty_precision），
+--    已有数据精度为 0，无需换算
+ALTER TABLE item ADD COLUMN qty_precision INTEGER NOT NULL DEFAULT 0 CHECK(qty_precision BETWEEN 0 AND 3);
@@ -0,0 +1,6 @@
+-- 迁移说明：筛选项索引（0015_filter_option_index.sql）
+-- 1) 为筛选下拉使用的去重查询补充索引（item.model 与 txn.type、audit_log.action 已有索引）
+CREATE INDEX IF NOT EXISTS idx_item_uom ON item(uom);
+CREATE INDEX IF NOT EXISTS idx_item_spec ON item(spec);
+CREATE INDEX IF NOT EXISTS idx_item_currency ON item(currency);
+CREATE INDEX IF NOT EXISTS idx_operator_role ON operator(role);
@@ -0,0 +1,28 @@
+-- 迁移说明：标签模板（0016_label_template.sql）
+-- 1) 新增 label_template，保存库位/物品标签的尺寸（毫米）、打印字段（JSON 数组）、条码类型与字号
+-- 2) 每种标签至多一个默认模板（is_default=1），生成标签未指定模板时使用
+-- 3) 预置库位标签 60×40mm 与物品标签 50×30mm 两个默认模板
+CREATE TABLE IF NOT EXISTS label_template (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  target TEXT NOT NULL CHECK(target IN ('slot','item')),
+  width_mm REAL NOT NULL,
+  height_mm REAL NOT NULL,
+  fields TEXT NOT NULL,
+  barcode_type TEXT NOT NULL DEFAULT 'code128' CHECK(barcode_type IN ('code128','none')),
+  font_size REAL NOT NULL DEFAULT 9,
+  is_default INTEGER NOT NULL DEFAULT 0,
+  created_at INTEGER NOT NULL,
+  updated_at INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_label_template_default
+ON label_template(target) WHERE is_default = 1;
+
+INSERT OR IGNORE INTO label_template
+  (id, name, target, width_mm, height_mm, fields, barcode_type, font_size, is_default, created_at, updated_at)
+VALUES
+  ('label-slot-default', '库位标签 60×40', 'slot', 60, 40, '["code","warehouse","rack"]', 'code128', 10, 1,
+   CAST(strftime('%s','now') AS INTEGER), CAST(strftime('%s','now') AS INTEGER)),
+  ('label-item-default', '物品标签 50×30', 'item', 50, 30, '["code","name","model"]', 'code128', 8, 1,
+   CAST(strftime('%s','now') AS INTEGER), CAST(strftime('%s','now') AS INTEGER));
@@ -0,0 +1,4 @@
+-- 迁移说明：游标分页索引（0017_keyset_index.sql）
+-- 1) 流水与审计日志按 (created_at, id) 倒序翻页，补充组合索引以便游标条件直接走索引
+CREATE INDEX IF NOT EXISTS idx_txn_created_id ON txn(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_audit_created_id ON audit_log(created_at, id);
@@ -0,0 +1,18 @@
+-- 迁移说明：日结库存快照（0018_stock_snapshot.sql）
+-- 1) 新增 stock_snapshot，按本地日期保存当日日结时每个物品/库位的库存（存储值，不含 0 库存）
+-- 2) 新增 stock_close，记录已完成日结的日期与生成时间，后台任务据此补齐漏结的日期
+CREATE TABLE IF NOT EXISTS stock_snapshot (
+  snapshot_date TEXT NOT NULL,
+  item_id TEXT NOT NULL,
+  slot_id TEXT NOT NULL,
+  qty INTEGER NOT NULL,
+  PRIMARY KEY (snapshot_date, item_id, slot_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_snapshot_item ON stock_snapshot(item_id, snapshot_date);
+
+CREATE TABLE IF NOT EXISTS stock_close (
+  close_date TEXT PRIMARY KEY,
+  row_count INTEGER NOT NULL,
+  closed_at INTEGER NOT NULL
+);
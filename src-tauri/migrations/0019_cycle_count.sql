@@ -0,0 +1,38 @@
+-- 迁移说明：循环盘点（0019_cycle_count.sql）
+-- 1) 新增 cycle_count_plan，按仓库配置 ABC 分类比例（按统计期内的流水次数排名）与各类盘点周期（天）
+-- 2) 新增 cycle_count_task，每天按计划生成待盘点的物品/库位；登记盘点后自动完成，未完成的顺延到次日
+CREATE TABLE IF NOT EXISTS cycle_count_plan (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  warehouse_id TEXT NOT NULL REFERENCES warehouse(id),
+  a_ratio REAL NOT NULL DEFAULT 20,
+  b_ratio REAL NOT NULL DEFAULT 30,
+  a_interval_days INTEGER NOT NULL DEFAULT 30,
+  b_interval_days INTEGER NOT NULL DEFAULT 90,
+  c_interval_days INTEGER NOT NULL DEFAULT 180,
+  lookback_days INTEGER NOT NULL DEFAULT 90,
+  status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active','disabled')),
+  last_generated_date TEXT,
+  created_at INTEGER NOT NULL,
+  updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cycle_count_task (
+  id TEXT PRIMARY KEY,
+  plan_id TEXT NOT NULL REFERENCES cycle_count_plan(id) ON DELETE CASCADE,
+  item_id TEXT NOT NULL REFERENCES item(id),
+  slot_id TEXT NOT NULL REFERENCES slot(id),
+  abc_class TEXT NOT NULL CHECK(abc_class IN ('A','B','C')),
+  due_date TEXT NOT NULL,
+  -- 生成时的计划日期，顺延后 due_date 变化而 scheduled_date 不变
+  scheduled_date TEXT NOT NULL,
+  carry_over INTEGER NOT NULL DEFAULT 0,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending','done','skipped')),
+  count_txn_id TEXT REFERENCES txn(id),
+  completed_at INTEGER,
+  completed_by TEXT REFERENCES operator(id),
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cycle_count_task_plan_status ON cycle_count_task(plan_id, status, due_date);
+CREATE INDEX IF NOT EXISTS idx_cycle_count_task_item_slot ON cycle_count_task(item_id, slot_id, status);
@@ -0,0 +1,5 @@
+-- 迁移说明：物品合并（0020_item_merge.sql）
+-- 1) item 增加 merged_into_id，记录重复物品合并到的目标物品；合并后原物品停用，库存、流水、借用与图片均归到目标物品
+-- 2) item 增加 merged_at，记录合并时间
+ALTER TABLE item ADD COLUMN merged_into_id TEXT REFERENCES item(id);
+ALTER TABLE item ADD COLUMN merged_at INTEGER;
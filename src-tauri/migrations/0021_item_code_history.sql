@@ -0,0 +1,14 @@
+-- 迁移说明：物品编码变更历史（0021_item_code_history.sql）
+-- 1) 新增 item_code_history，记录物品编码的每次变更（旧编码、新编码、时间与操作人）
+-- 2) 旧编码作为别名保留：扫码、按编码查询与导入找不到当前编码时按旧编码解析到物品，已贴的旧标签仍可使用
+CREATE TABLE IF NOT EXISTS item_code_history (
+  id TEXT PRIMARY KEY,
+  item_id TEXT NOT NULL REFERENCES item(id),
+  old_code TEXT NOT NULL,
+  new_code TEXT NOT NULL,
+  changed_at INTEGER NOT NULL,
+  changed_by TEXT REFERENCES operator(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_item_code_history_old_code ON item_code_history(old_code, changed_at);
+CREATE INDEX IF NOT EXISTS idx_item_code_history_item ON item_code_history(item_id, changed_at);
@@ -0,0 +1,13 @@
+-- 迁移说明：物品别名（0022_item_alias.sql）
+-- 1) 新增 item_alias，保存物品的其他条码/编码（厂家编码、内部编码、EAN、供应商编码等），别名全局唯一
+-- 2) 按编码查询与扫码解析时依次匹配：物品编码 → 别名 → 旧编码（item_code_history）
+CREATE TABLE IF NOT EXISTS item_alias (
+  id TEXT PRIMARY KEY,
+  item_id TEXT NOT NULL REFERENCES item(id) ON DELETE CASCADE,
+  code TEXT NOT NULL UNIQUE,
+  kind TEXT NOT NULL DEFAULT 'other' CHECK(kind IN ('manufacturer','internal','ean','supplier','other')),
+  note TEXT,
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_item_alias_item ON item_alias(item_id);
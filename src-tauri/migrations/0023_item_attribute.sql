@@ -0,0 +1,21 @@
+-- 迁移说明：物品自定义属性（0023_item_attribute.sql）
+-- 1) 新增 item_attribute_def，定义各现场需要的扩展字段（品牌、颜色、保质期等），名称唯一，可设为必填
+-- 2) 新增 item_attribute_value，按 物品 + 属性 保存取值，删除物品或属性定义时一并删除
+CREATE TABLE IF NOT EXISTS item_attribute_def (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  data_type TEXT NOT NULL DEFAULT 'text' CHECK(data_type IN ('text','number','date')),
+  required INTEGER NOT NULL DEFAULT 0,
+  sort_no INTEGER NOT NULL DEFAULT 0,
+  status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active','disabled')),
+  created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS item_attribute_value (
+  item_id TEXT NOT NULL REFERENCES item(id) ON DELETE CASCADE,
+  attr_id TEXT NOT NULL REFERENCES item_attribute_def(id) ON DELETE CASCADE,
+  value TEXT NOT NULL,
+  PRIMARY KEY (item_id, attr_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_item_attribute_value_attr ON item_attribute_value(attr_id);
@@ -0,0 +1,14 @@
+-- 迁移说明：主数据修改撤销（0024_undo_entry.sql）
+-- 1) 新增 undo_entry，按操作人保存最近的主数据修改（物品、货架、仓库）及其逆操作
+-- 2) payload 为 JSON，包含修改前与修改后的快照；撤销时仅在当前数据仍等于修改后快照时执行
+CREATE TABLE IF NOT EXISTS undo_entry (
+  id TEXT PRIMARY KEY,
+  operator_id TEXT NOT NULL,
+  action TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  payload TEXT NOT NULL,
+  created_at INTEGER NOT NULL,
+  undone_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_undo_entry_operator ON undo_entry(operator_id, created_at);
@@ -0,0 +1,20 @@
+-- 迁移说明：敏感操作审批（0025_approval_request.sql）
+-- 1) 新增 approval_request，冲正、大额盘点调整、数据库恢复按设置需由另一名管理员审批后才能执行
+-- 2) target 为操作对象标识（流水号、物品/库位/实盘数量、备份文件），执行时须与申请一致，审批通过的申请仅能执行一次
+CREATE TABLE IF NOT EXISTS approval_request (
+  id TEXT PRIMARY KEY,
+  kind TEXT NOT NULL CHECK(kind IN ('reversal','count_adjust','restore')),
+  target TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  reason TEXT,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending','approved','rejected','executed')),
+  requested_by TEXT NOT NULL REFERENCES operator(id),
+  requested_at INTEGER NOT NULL,
+  decided_by TEXT REFERENCES operator(id),
+  decided_at INTEGER,
+  decision_note TEXT,
+  executed_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_request_status ON approval_request(status, requested_at);
+CREATE INDEX IF NOT EXISTS idx_approval_request_requester ON approval_request(requested_by, requested_at);
@@ -0,0 +1,16 @@
+-- 迁移说明：通知中心（0026_notification.sql）
+-- 1) 新增 notification，低库存、定时备份失败、审批申请、借用逾期写入通知，前端标记已读
+-- 2) dedup_key 非空时唯一，用于同一事件只通知一次（如同一借用逾期、同一物品当天低库存）
+CREATE TABLE IF NOT EXISTS notification (
+  id TEXT PRIMARY KEY,
+  kind TEXT NOT NULL CHECK(kind IN ('low_stock','backup_failed','approval_request','loan_overdue')),
+  title TEXT NOT NULL,
+  body TEXT,
+  ref_id TEXT,
+  dedup_key TEXT,
+  created_at INTEGER NOT NULL,
+  read_at INTEGER
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notification_dedup ON notification(dedup_key) WHERE dedup_key IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_notification_created ON notification(created_at);
@@ -0,0 +1,39 @@
+-- 迁移说明：出库拣货确认（0027_outbound_pick.sql）
+-- 1) outbound_pick 记录两步出库：申请时登记计划数量（不扣库存），确认时按实拣数量生成 OUT 流水
+-- 2) status：pending（待拣货）/ confirmed（已确认）/ cancelled（已取消）；实拣少于计划时须填写短拣原因
+-- 3) pick_discrepancy 记录短拣差异，选择自动调整时生成 ADJUST 流水冲减账面库存
+CREATE TABLE IF NOT EXISTS outbound_pick (
+  id TEXT PRIMARY KEY,
+  pick_no TEXT NOT NULL UNIQUE,
+  item_id TEXT NOT NULL REFERENCES item(id),
+  slot_id TEXT NOT NULL REFERENCES slot(id),
+  planned_qty INTEGER NOT NULL CHECK(planned_qty > 0),
+  status TEXT NOT NULL CHECK(status IN ('pending','confirmed','cancelled')),
+  note TEXT,
+  requested_by TEXT NOT NULL REFERENCES operator(id),
+  requested_at INTEGER NOT NULL,
+  actual_qty INTEGER,
+  short_reason TEXT,
+  confirmed_by TEXT REFERENCES operator(id),
+  confirmed_at INTEGER,
+  out_txn_id TEXT REFERENCES txn(id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_outbound_pick_status ON outbound_pick(status, requested_at);
+CREATE INDEX IF NOT EXISTS idx_outbound_pick_out_txn ON outbound_pick(out_txn_id);
+
+CREATE TABLE IF NOT EXISTS pick_discrepancy (
+  id TEXT PRIMARY KEY,
+  pick_id TEXT NOT NULL UNIQUE REFERENCES outbound_pick(id),
+  item_id TEXT NOT NULL REFERENCES item(id),
+  slot_id TEXT NOT NULL REFERENCES slot(id),
+  planned_qty INTEGER NOT NULL,
+  actual_qty INTEGER NOT NULL,
+  short_qty INTEGER NOT NULL CHECK(short_qty > 0),
+  reason TEXT NOT NULL,
+  adjust_txn_id TEXT REFERENCES txn(id),
+  operator_id TEXT NOT NULL REFERENCES operator(id),
+  created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pick_discrepancy_created ON pick_discrepancy(created_at);
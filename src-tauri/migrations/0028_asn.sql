@@ -0,0 +1,40 @@
+-- 迁移说明：到货预报收货（0028_asn.sql）
+-- 1) asn 为到货预报（供应商、预计到货时间），asn_line 为预报明细（物品、预计数量、已收数量）
+-- 2) 按预报收货时生成 IN 流水并累加已收数量，asn_receipt 记录每次收货对应的流水
+-- 3) status：open（待收货）/ closed（已结单，未收足的数量视为短收）/ cancelled（已取消，仅未收货时可取消）
+CREATE TABLE IF NOT EXISTS asn (
+  id TEXT PRIMARY KEY,
+  asn_no TEXT NOT NULL UNIQUE,
+  supplier TEXT NOT NULL,
+  eta INTEGER,
+  status TEXT NOT NULL CHECK(status IN ('open','closed','cancelled')),
+  note TEXT,
+  created_by TEXT NOT NULL REFERENCES operator(id),
+  created_at INTEGER NOT NULL,
+  closed_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_asn_status_eta ON asn(status, eta);
+
+CREATE TABLE IF NOT EXISTS asn_line (
+  id TEXT PRIMARY KEY,
+  asn_id TEXT NOT NULL REFERENCES asn(id) ON DELETE CASCADE,
+  line_no INTEGER NOT NULL,
+  item_id TEXT NOT NULL REFERENCES item(id),
+  expected_qty INTEGER NOT NULL CHECK(expected_qty > 0),
+  received_qty INTEGER NOT NULL DEFAULT 0,
+  UNIQUE(asn_id, line_no)
+);
+
+CREATE INDEX IF NOT EXISTS idx_asn_line_item ON asn_line(item_id);
+
+CREATE TABLE IF NOT EXISTS asn_receipt (
+  id TEXT PRIMARY KEY,
+  asn_line_id TEXT NOT NULL REFERENCES asn_line(id),
+  txn_id TEXT NOT NULL REFERENCES txn(id),
+  qty INTEGER NOT NULL,
+  received_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_asn_receipt_line ON asn_receipt(asn_line_id);
+CREATE INDEX IF NOT EXISTS idx_asn_receipt_txn ON asn_receipt(txn_id);
@@ -0,0 +1,14 @@
+-- 迁移说明：物品分仓库存阈值（0029_stock_threshold.sql）
+-- 1) stock_threshold 按物品 + 仓库设置最低 / 最高库存（存储值，与 stock.qty 同口径），max_qty 为空表示不限上限
+-- 2) 低库存报表按该阈值计算各仓库的缺口与超储；全局 low_stock_threshold 仍用于通知扫描
+CREATE TABLE IF NOT EXISTS stock_threshold (
+  item_id TEXT NOT NULL REFERENCES item(id) ON DELETE CASCADE,
+  warehouse_id TEXT NOT NULL REFERENCES warehouse(id) ON DELETE CASCADE,
+  min_qty INTEGER NOT NULL CHECK(min_qty >= 0),
+  max_qty INTEGER CHECK(max_qty IS NULL OR max_qty >= min_qty),
+  updated_by TEXT REFERENCES operator(id),
+  updated_at INTEGER NOT NULL,
+  PRIMARY KEY (item_id, warehouse_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_threshold_warehouse ON stock_threshold(warehouse_id);
@@ -0,0 +1,6 @@
+-- 迁移说明：流水到期日（0030_txn_expiry.sql）
+-- 1) txn 新增 expires_at：入库时记录该批次的到期日（秒级时间戳，本地日期零点），移库时记录移出部分中最早的到期日
+-- 2) 库位内库存按先进先出归属到最近的入库 / 移入流水，据此计算已过期与临期数量
+ALTER TABLE txn ADD COLUMN expires_at INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_txn_expires_at ON txn(expires_at) WHERE expires_at IS NOT NULL;
@@ -0,0 +1,20 @@
+-- 迁移说明：库存冻结（0031_stock_hold.sql）
+-- 1) stock_hold 按物品 + 库位冻结部分库存（存储值，与 stock.qty 同口径），reason 为冻结原因（damaged 破损 / pending_qc 待检 / other 其他）
+-- 2) released_at 为空表示冻结中；冻结中的数量不计入可用库存，出库、移库、借出与拣货确认只能使用可用库存
+CREATE TABLE IF NOT EXISTS stock_hold (
+  id TEXT PRIMARY KEY,
+  hold_no TEXT NOT NULL UNIQUE,
+  item_id TEXT NOT NULL REFERENCES item(id),
+  slot_id TEXT NOT NULL REFERENCES slot(id),
+  qty INTEGER NOT NULL CHECK(qty > 0),
+  reason TEXT NOT NULL CHECK(reason IN ('damaged','pending_qc','other')),
+  note TEXT,
+  created_by TEXT NOT NULL REFERENCES operator(id),
+  created_at INTEGER NOT NULL,
+  released_by TEXT REFERENCES operator(id),
+  released_at INTEGER,
+  release_note TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_stock_hold_active ON stock_hold(item_id, slot_id) WHERE released_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_stock_hold_created_at ON stock_hold(created_at);
@@ -0,0 +1,66 @@
+-- 迁移说明：报废流水（0032_txn_scrap.sql）
+-- 1) txn.type 新增 SCRAP（报废）：从来源库位扣减库存，与出库分开统计；SQLite 无法修改 CHECK 约束，按新结构重建 txn 表并复制数据
+-- 2) txn 新增 reason_code，记录报废原因（取自设置中的报废原因列表），其他类型为空
+-- 3) approval_request.kind 新增 scrap：报废数量超过审批阈值时需另一名管理员审批，同样重建表
+-- 4) 重建期间由迁移程序关闭外键约束，表名与主键保持不变，引用 txn(id) 的借用、拣货、收货等记录不受影响
+CREATE TABLE txn_new (
+  id TEXT PRIMARY KEY,
+  txn_no TEXT NOT NULL UNIQUE,
+  type TEXT NOT NULL CHECK(type IN ('IN','OUT','MOVE','COUNT','ADJUST','REVERSAL','SCRAP')),
+  occurred_at INTEGER NOT NULL,
+  created_at INTEGER NOT NULL,
+  operator_id TEXT NOT NULL REFERENCES operator(id),
+  item_id TEXT NOT NULL REFERENCES item(id),
+  from_slot_id TEXT REFERENCES slot(id),
+  to_slot_id TEXT REFERENCES slot(id),
+  qty INTEGER NOT NULL,
+  actual_qty INTEGER,
+  ref_txn_id TEXT REFERENCES txn(id),
+  note TEXT,
+  unit_cost REAL,
+  location TEXT,
+  expires_at INTEGER,
+  reason_code TEXT
+);
+
+INSERT INTO txn_new (id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id,
+  qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at)
+SELECT id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id,
+  qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at
+FROM txn ORDER BY rowid;
+
+DROP TABLE txn;
+ALTER TABLE txn_new RENAME TO txn;
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_reversal_ref ON txn(ref_txn_id) WHERE type='REVERSAL';
+CREATE INDEX IF NOT EXISTS idx_txn_item_time ON txn(item_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_txn_type_time ON txn(type, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_txn_from_slot ON txn(from_slot_id);
+CREATE INDEX IF NOT EXISTS idx_txn_to_slot ON txn(to_slot_id);
+CREATE INDEX IF NOT EXISTS idx_txn_created_id ON txn(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_txn_expires_at ON txn(expires_at) WHERE expires_at IS NOT NULL;
+
+CREATE TABLE approval_request_new (
+  id TEXT PRIMARY KEY,
+  kind TEXT NOT NULL CHECK(kind IN ('reversal','count_adjust','restore','scrap')),
+  target TEXT NOT NULL,
+  summary TEXT NOT NULL,
+  reason TEXT,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending','approved','rejected','executed')),
+  requested_by TEXT NOT NULL REFERENCES operator(id),
+  requested_at INTEGER NOT NULL,
+  decided_by TEXT REFERENCES operator(id),
+  decided_at INTEGER,
+  decision_note TEXT,
+  executed_at INTEGER
+);
+
+INSERT INTO approval_request_new
+SELECT id, kind, target, summary, reason, status, requested_by, requested_at, decided_by, decided_at, decision_note, executed_at
+FROM approval_request;
+
+DROP TABLE approval_request;
+ALTER TABLE approval_request_new RENAME TO approval_request;
+
+CREATE INDEX IF NOT EXISTS idx_approval_request_status ON approval_request(status, requested_at);
+CREATE INDEX IF NOT EXISTS idx_approval_request_requester ON approval_request(requested_by, requested_at);
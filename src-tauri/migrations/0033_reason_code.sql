@@ -0,0 +1,52 @@
+-- 迁移说明：原因代码字典（0033_reason_code.sql）
+-- 1) 新增 reason_code，统一维护调整（ADJUST）、冲正（REVERSAL）、报废（SCRAP）流水的原因；txn_types 为适用的流水类型（逗号分隔）
+-- 2) 预置常用原因；设置中原有的报废原因列表与已记录的报废原因按名称并入字典（编码取原名称）
+-- 3) txn 新增 reason_id 引用原因字典，按原名称回填后删除 reason_code 列；app_meta 中的 scrap_reasons 不再使用
+CREATE TABLE IF NOT EXISTS reason_code (
+  id TEXT PRIMARY KEY,
+  code TEXT NOT NULL UNIQUE,
+  name TEXT NOT NULL,
+  txn_types TEXT NOT NULL,
+  sort_no INTEGER NOT NULL DEFAULT 0,
+  status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active','disabled')),
+  created_at INTEGER NOT NULL
+);
+
+INSERT OR IGNORE INTO reason_code (id, code, name, txn_types, sort_no, status, created_at)
+VALUES
+  ('reason-damaged', 'DAMAGED', '破损', 'ADJUST,SCRAP', 10, 'active', CAST(strftime('%s','now') AS INTEGER)),
+  ('reason-expired', 'EXPIRED', '过期', 'SCRAP', 20, 'active', CAST(strftime('%s','now') AS INTEGER)),
+  ('reason-quality', 'QUALITY', '质量问题', 'SCRAP', 30, 'active', CAST(strftime('%s','now') AS INTEGER)),
+  ('reason-lost', 'LOST', '遗失', 'ADJUST,SCRAP', 40, 'active', CAST(strftime('%s','now') AS INTEGER)),
+  ('reason-count-diff', 'COUNT_DIFF', '盘点差异', 'ADJUST', 50, 'active', CAST(strftime('%s','now') AS INTEGER)),
+  ('reason-entry-error', 'ENTRY_ERROR', '录入错误', 'ADJUST,REVERSAL', 60, 'active', CAST(strftime('%s','now') AS INTEGER)),
+  ('reason-other', 'OTHER', '其他', 'ADJUST,REVERSAL,SCRAP', 90, 'active', CAST(strftime('%s','now') AS INTEGER));
+
+WITH RECURSIVE split(name, rest) AS (
+  SELECT '', COALESCE((SELECT v FROM app_meta WHERE k = 'scrap_reasons'), '') || ','
+  UNION ALL
+  SELECT trim(substr(rest, 1, instr(rest, ',') - 1)), substr(rest, instr(rest, ',') + 1)
+  FROM split WHERE rest <> ''
+),
+legacy(name) AS (
+  SELECT name FROM split WHERE name <> ''
+  UNION
+  SELECT reason_code FROM txn WHERE reason_code IS NOT NULL AND trim(reason_code) <> ''
+)
+INSERT OR IGNORE INTO reason_code (id, code, name, txn_types, sort_no, status, created_at)
+SELECT 'reason-' || lower(hex(randomblob(8))), name, name, 'SCRAP', 100, 'active', CAST(strftime('%s','now') AS INTEGER)
+FROM legacy
+WHERE name NOT IN (SELECT name FROM reason_code);
+
+ALTER TABLE txn ADD COLUMN reason_id TEXT REFERENCES reason_code(id);
+
+UPDATE txn SET reason_id = (
+  SELECT reason_code.id FROM reason_code WHERE reason_code.name = txn.reason_code ORDER BY reason_code.sort_no LIMIT 1
+)
+WHERE reason_code IS NOT NULL;
+
+ALTER TABLE txn DROP COLUMN reason_code;
+
+CREATE INDEX IF NOT EXISTS idx_txn_reason ON txn(reason_id) WHERE reason_id IS NOT NULL;
+
+DELETE FROM app_meta WHERE k = 'scrap_reasons';
@@ -0,0 +1,22 @@
+-- 迁移说明：角色能力开关（0034_role_capability.sql）
+-- 1) 新增 role_capability，管理员可按角色开放导出（can_export）、查看成本（can_view_costs）与查看审计日志（can_view_audit）；管理员始终拥有全部能力
+-- 2) 预置默认值：库管可导出与查看成本，只读与成员默认均不开放（此前只读与成员可导出库存等数据）
+CREATE TABLE IF NOT EXISTS role_capability (
+  role TEXT NOT NULL CHECK(role IN ('keeper','viewer','member')),
+  capability TEXT NOT NULL CHECK(capability IN ('can_export','can_view_costs','can_view_audit')),
+  allowed INTEGER NOT NULL DEFAULT 0 CHECK(allowed IN (0,1)),
+  updated_at INTEGER NOT NULL,
+  PRIMARY KEY (role, capability)
+);
+
+INSERT OR IGNORE INTO role_capability (role, capability, allowed, updated_at)
+VALUES
+  ('keeper', 'can_export', 1, CAST(strftime('%s','now') AS INTEGER)),
+  ('keeper', 'can_view_costs', 1, CAST(strftime('%s','now') AS INTEGER)),
+  ('keeper', 'can_view_audit', 0, CAST(strftime('%s','now') AS INTEGER)),
+  ('viewer', 'can_export', 0, CAST(strftime('%s','now') AS INTEGER)),
+  ('viewer', 'can_view_costs', 0, CAST(strftime('%s','now') AS INTEGER)),
+  ('viewer', 'can_view_audit', 0, CAST(strftime('%s','now') AS INTEGER)),
+  ('member', 'can_export', 0, CAST(strftime('%s','now') AS INTEGER)),
+  ('member', 'can_view_costs', 0, CAST(strftime('%s','now') AS INTEGER)),
+  ('member', 'can_view_audit', 0, CAST(strftime('%s','now') AS INTEGER));
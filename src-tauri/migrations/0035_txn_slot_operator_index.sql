@@ -0,0 +1,10 @@
+-- 迁移说明：库存列表最近记录人查询索引（0035_txn_slot_operator_index.sql）
+-- 1) 库存列表的最近记录人改为按移入、移出库位分别取最新一条流水，原 OR 条件无法使用索引，每行需扫描全部流水
+-- 2) 分别为移入、移出库位建立覆盖索引（含时间与记录人），按 (物品, 库位) 直接定位最新流水且无需回表
+CREATE INDEX IF NOT EXISTS idx_txn_item_to_slot_time
+  ON txn(item_id, to_slot_id, occurred_at, created_at, operator_id)
+  WHERE to_slot_id IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_txn_item_from_slot_time
+  ON txn(item_id, from_slot_id, occurred_at, created_at, operator_id)
+  WHERE from_slot_id IS NOT NULL;
@@ -0,0 +1,6 @@
+-- 迁移说明：流水列表常用筛选索引（0036_hot_path_indexes.sql）
+-- 1) 按发生时间区间筛选流水时原先只能沿创建时间索引整表扫描，新增 occurred_at 索引
+-- 2) 按记录人筛选流水时无可用索引，新增 (operator_id, created_at, id)，与列表排序一致，可直接按序分页
+-- 3) 物品 + 时间（idx_txn_item_time）、库存按库位（idx_stock_slot）、审计按动作/时间（idx_audit_action_time、idx_audit_created_id）已有索引，不再重复创建
+CREATE INDEX IF NOT EXISTS idx_txn_occurred ON txn(occurred_at);
+CREATE INDEX IF NOT EXISTS idx_txn_operator_created ON txn(operator_id, created_at, id);
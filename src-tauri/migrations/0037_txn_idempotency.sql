@@ -0,0 +1,10 @@
+-- 迁移说明：流水提交幂等键（0037_txn_idempotency.sql）
+-- 1) 记录客户端生成的幂等键与首次提交生成的流水号，键唯一，重复提交时直接返回原流水号
+-- 2) created_at 索引用于清理超出有效期的键
+CREATE TABLE IF NOT EXISTS txn_idempotency (
+  idempotency_key TEXT PRIMARY KEY,
+  action TEXT NOT NULL,
+  txn_no TEXT NOT NULL,
+  created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_txn_idempotency_created ON txn_idempotency(created_at);
@@ -0,0 +1,48 @@
+-- 迁移说明：期初库存流水（0038_txn_opening.sql）
+-- 1) txn.type 新增 OPENING（期初）：导入启用前的期初库存，计入目标库位，与日常入库分开统计；SQLite 无法修改 CHECK 约束，按新结构重建 txn 表并复制数据
+-- 2) 重建期间由迁移程序关闭外键约束，表名与主键保持不变，引用 txn(id) 的借用、拣货、收货等记录不受影响；原有索引按相同定义重建
+CREATE TABLE txn_new (
+  id TEXT PRIMARY KEY,
+  txn_no TEXT NOT NULL UNIQUE,
+  type TEXT NOT NULL CHECK(type IN ('IN','OUT','MOVE','COUNT','ADJUST','REVERSAL','SCRAP','OPENING')),
+  occurred_at INTEGER NOT NULL,
+  created_at INTEGER NOT NULL,
+  operator_id TEXT NOT NULL REFERENCES operator(id),
+  item_id TEXT NOT NULL REFERENCES item(id),
+  from_slot_id TEXT REFERENCES slot(id),
+  to_slot_id TEXT REFERENCES slot(id),
+  qty INTEGER NOT NULL,
+  actual_qty INTEGER,
+  ref_txn_id TEXT REFERENCES txn(id),
+  note TEXT,
+  unit_cost REAL,
+  location TEXT,
+  expires_at INTEGER,
+  reason_id TEXT REFERENCES reason_code(id)
+);
+
+INSERT INTO txn_new (id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id,
+  qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at, reason_id)
+SELECT id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id,
+  qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at, reason_id
+FROM txn ORDER BY rowid;
+
+DROP TABLE txn;
+ALTER TABLE txn_new RENAME TO txn;
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_reversal_ref ON txn(ref_txn_id) WHERE type='REVERSAL';
+CREATE INDEX IF NOT EXISTS idx_txn_item_time ON txn(item_id, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_txn_type_time ON txn(type, occurred_at);
+CREATE INDEX IF NOT EXISTS idx_txn_from_slot ON txn(from_slot_id);
+CREATE INDEX IF NOT EXISTS idx_txn_to_slot ON txn(to_slot_id);
+CREATE INDEX IF NOT EXISTS idx_txn_created_id ON txn(created_at, id);
+CREATE INDEX IF NOT EXISTS idx_txn_expires_at ON txn(expires_at) WHERE expires_at IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_txn_reason ON txn(reason_id) WHERE reason_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_txn_item_to_slot_time
+  ON txn(item_id, to_slot_id, occurred_at, created_at, operator_id)
+  WHERE to_slot_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_txn_item_from_slot_time
+  ON txn(item_id, from_slot_id, occurred_at, created_at, operator_id)
+  WHERE from_slot_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_txn_occurred ON txn(occurred_at);
+CREATE INDEX IF NOT EXISTS idx_txn_operator_created ON txn(operator_id, created_at, id);
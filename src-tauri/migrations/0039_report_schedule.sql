@@ -0,0 +1,56 @@
+-- 迁移说明：定时报表（0039_report_schedule.sql）
+-- 1) 新增 report_schedule，按本机时间每天定时生成报表（昨日流水日报、低库存）到导出目录，可同时发送邮件
+-- 2) 新增 report_schedule_run，记录每次执行（定时或手动）的结果、文件路径与失败原因；计划删除后保留历史
+-- 3) notification.kind 新增 report_failed（定时报表失败）；SQLite 无法修改 CHECK 约束，按新结构重建 notification 表并复制数据
+CREATE TABLE IF NOT EXISTS report_schedule (
+  id TEXT PRIMARY KEY,
+  name TEXT NOT NULL UNIQUE,
+  report_kind TEXT NOT NULL CHECK(report_kind IN ('txn_journal','low_stock')),
+  -- 每天执行时间（本机时区，HH:MM）
+  run_time TEXT NOT NULL,
+  -- 收件人（逗号分隔），为空时只生成文件
+  email_to TEXT,
+  status TEXT NOT NULL DEFAULT 'active' CHECK(status IN ('active','disabled')),
+  -- 最近一次定时执行的日期（YYYY-MM-DD），同一天只执行一次
+  last_run_date TEXT,
+  created_at INTEGER NOT NULL,
+  updated_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS report_schedule_run (
+  id TEXT PRIMARY KEY,
+  schedule_id TEXT REFERENCES report_schedule(id) ON DELETE SET NULL,
+  schedule_name TEXT NOT NULL,
+  report_kind TEXT NOT NULL,
+  trigger_type TEXT NOT NULL CHECK(trigger_type IN ('schedule','manual')),
+  status TEXT NOT NULL CHECK(status IN ('success','failed')),
+  file_path TEXT,
+  -- 邮件投递：none（未配置收件人）/ sent / failed
+  email_status TEXT NOT NULL DEFAULT 'none' CHECK(email_status IN ('none','sent','failed')),
+  error TEXT,
+  started_at INTEGER NOT NULL,
+  finished_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_report_schedule_run_started ON report_schedule_run(started_at);
+CREATE INDEX IF NOT EXISTS idx_report_schedule_run_schedule ON report_schedule_run(schedule_id, started_at);
+
+CREATE TABLE notification_new (
+  id TEXT PRIMARY KEY,
+  kind TEXT NOT NULL CHECK(kind IN ('low_stock','backup_failed','approval_request','loan_overdue','report_failed')),
+  title TEXT NOT NULL,
+  body TEXT,
+  ref_id TEXT,
+  dedup_key TEXT,
+  created_at INTEGER NOT NULL,
+  read_at INTEGER
+);
+
+INSERT INTO notification_new (id, kind, title, body, ref_id, dedup_key, created_at, read_at)
+SELECT id, kind, title, body, ref_id, dedup_key, created_at, read_at FROM notification;
+
+DROP TABLE notification;
+ALTER TABLE notification_new RENAME TO notification;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_notification_dedup ON notification(dedup_key) WHERE dedup_key IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_notification_created ON notification(created_at);
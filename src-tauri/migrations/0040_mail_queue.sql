@@ -0,0 +1,49 @@
+-- 迁移说明：邮件发送队列（0040_mail_queue.sql）
+-- 1) 新增 mail_queue，待发邮件先入队再由后台发送；临时失败按退避间隔重试，超过次数或被服务器拒绝后标记失败
+-- 2) 附件只记录文件路径，发送时读取；ref_type/ref_id 记录来源（如定时报表执行记录），发送结果回写来源
+-- 3) report_schedule_run.email_status 新增 queued（已入队待发送）；SQLite 无法修改 CHECK 约束，按新结构重建表并复制数据，原有索引按相同定义重建
+CREATE TABLE IF NOT EXISTS mail_queue (
+  id TEXT PRIMARY KEY,
+  ref_type TEXT,
+  ref_id TEXT,
+  -- 收件人（逗号分隔）
+  recipients TEXT NOT NULL,
+  subject TEXT NOT NULL,
+  body TEXT NOT NULL,
+  attachment_path TEXT,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK(status IN ('pending','sent','failed')),
+  attempts INTEGER NOT NULL DEFAULT 0,
+  next_attempt_at INTEGER NOT NULL,
+  last_error TEXT,
+  created_at INTEGER NOT NULL,
+  sent_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_mail_queue_status_next ON mail_queue(status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_mail_queue_created ON mail_queue(created_at);
+
+CREATE TABLE report_schedule_run_new (
+  id TEXT PRIMARY KEY,
+  schedule_id TEXT REFERENCES report_schedule(id) ON DELETE SET NULL,
+  schedule_name TEXT NOT NULL,
+  report_kind TEXT NOT NULL,
+  trigger_type TEXT NOT NULL CHECK(trigger_type IN ('schedule','manual')),
+  status TEXT NOT NULL CHECK(status IN ('success','failed')),
+  file_path TEXT,
+  -- 邮件投递：none（未配置收件人）/ queued / sent / failed
+  email_status TEXT NOT NULL DEFAULT 'none' CHECK(email_status IN ('none','queued','sent','failed')),
+  error TEXT,
+  started_at INTEGER NOT NULL,
+  finished_at INTEGER NOT NULL
+);
+
+INSERT INTO report_schedule_run_new
+  (id, schedule_id, schedule_name, report_kind, trigger_type, status, file_path, email_status, error, started_at, finished_at)
+SELECT id, schedule_id, schedule_name, report_kind, trigger_type, status, file_path, email_status, error, started_at, finished_at
+FROM report_schedule_run;
+
+DROP TABLE report_schedule_run;
+ALTER TABLE report_schedule_run_new RENAME TO report_schedule_run;
+
+CREATE INDEX IF NOT EXISTS idx_report_schedule_run_started ON report_schedule_run(started_at);
+CREATE INDEX IF NOT EXISTS idx_report_schedule_run_schedule ON report_schedule_run(schedule_id, started_at);
@@ -1,3 +1,14 @@
+use serde::Deserialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::infra::{crypto, db};
+use crate::infra::deep_link::DeepLinkState;
+use crate::infra::startup::{StartupProgress, StartupRestore, StartupState};
+use crate::services::filter_option_service::{self, FilterOptionsResult};
+use crate::services::{auth_service, backup_encryption_service, bootstrap_service, permission_service};
+use crate::state::AppState;
+
 #[tauri::command]
 pub async fn close_splashscreen(_app: tauri::AppHandle) -> Result<(), String> {
     // Android 平台直接返回成功（不需要启动屏逻辑）
@@ -9,20 +20,264 @@ pub async fn close_splashscreen(_app: tauri::AppHandle) -> Result<(), String> {
     // 桌面平台处理启动屏
     #[cfg(not(target_os = "android"))]
     {
-        use tauri::Manager;
-        
-        // 关闭启动屏窗口
-        if let Some(splash_window) = _app.get_webview_window("splashscreen") {
-            let _ = splash_window.close();
-        }
-        
-        // 显示并聚焦主窗口
-        if let Some(main_window) = _app.get_webview_window("main") {
-            let _ = main_window.center();
-            let _ = main_window.show();
-            let _ = main_window.set_focus();
+        // 数据库尚未初始化完成时仅记录请求，完成后由启动流程关闭启动屏
+        if let Some(startup) = _app.try_state::<StartupState>() {
+            if !startup.request_close() {
+                return Ok(());
+            }
         }
-        
+        show_main_window(&_app);
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// 关闭启动屏并显示主窗口
+#[cfg(not(target_os = "android"))]
+pub fn show_main_window(app: &AppHandle) {
+    // 关闭启动屏窗口
+    if let Some(splash_window) = app.get_webview_window("splashscreen") {
+        let _ = splash_window.close();
+    }
+    
+    // 开启"启动时最小化到托盘"时主窗口保持隐藏，从托盘菜单打开
+    if crate::infra::tray::start_minimized(app) {
+        return;
+    }
+
+    // 显示并聚焦主窗口
+    if let Some(main_window) = app.get_webview_window("main") {
+        let _ = main_window.center();
+        let _ = main_window.show();
+        let _ = main_window.set_focus();
+    }
+}
+
+#[tauri::command]
+pub async fn get_startup_status(app: AppHandle) -> Result<Option<StartupProgress>, AppError> {
+    Ok(app
+        .try_state::<StartupState>()
+        .and_then(|startup| startup.progress()))
+}
+
+#[tauri::command]
+pub async fn retry_startup(app: AppHandle) -> Result<(), AppError> {
+    ensure_startup_failed(&app)?;
+    crate::start_database_init(app);
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RestoreStartupBackupInput {
+    pub file_path: String,
+    // 管理员账号密码：启动失败时尚无会话，恢复前须校验
+    pub username: String,
+    pub password: String,
+    // 加密备份的口令；为空时使用故障数据库中保存的口令
+    pub passphrase: Option<String>,
+}
+
+#[tauri::command]
+pub async fn restore_startup_backup(
+    app: AppHandle,
+    input: RestoreStartupBackupInput,
+) -> Result<(), AppError> {
+    ensure_startup_failed(&app)?;
+    let src = crate::infra::fs::normalize_path(&input.file_path)?;
+    if !src.is_file() {
+        return Err(AppError::new(ErrorCode::NotFound, "备份文件不存在"));
+    }
+    let data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| AppError::new(ErrorCode::IoError, "无法获取应用数据目录"))?;
+    let db_path = data_dir.join("db").join("db.sqlite");
+    // 加密备份先解密到数据库目录下的临时文件，之后按明文备份校验与恢复
+    let decrypted = decrypt_startup_backup(&src, &db_path, input.passphrase.as_deref()).await?;
+    let restore_src = decrypted.as_deref().unwrap_or(&src);
+    let result = restore_startup_file(&app, &data_dir, &src, restore_src, &input).await;
+    if let Some(path) = decrypted {
+        let _ = std::fs::remove_file(path);
+    }
+    result?;
+    crate::start_database_init(app);
+    Ok(())
+}
+
+/// 以 restore_src 覆盖数据库文件；src 为所选备份，记入恢复审计
+async fn restore_startup_file(
+    app: &AppHandle,
+    data_dir: &std::path::Path,
+    src: &std::path::Path,
+    restore_src: &std::path::Path,
+    input: &RestoreStartupBackupInput,
+) -> Result<(), AppError> {
+    let db_dir = data_dir.join("db");
+    let db_path = db_dir.join("db.sqlite");
+    let operator_id =
+        verify_startup_admin(app, &db_path, restore_src, &input.username, &input.password).await?;
+
+    // 覆盖前留存故障数据库，便于事后排查迁移失败原因
+    let failed_copy = if db_path.is_file() {
+        let backups_dir = data_dir.join("backups");
+        std::fs::create_dir_all(&backups_dir)
+            .map_err(|_| AppError::new(ErrorCode::IoError, "创建备份目录失败"))?;
+        let copy_path = backups_dir.join(format!(
+            "db_failed_startup_{}.sqlite",
+            chrono::Utc::now().timestamp()
+        ));
+        std::fs::copy(&db_path, &copy_path)
+            .map_err(|_| AppError::new(ErrorCode::IoError, "保存故障数据库副本失败"))?;
+        Some(copy_path.to_string_lossy().into_owned())
+    } else {
+        None
+    };
+
+    std::fs::copy(restore_src, &db_path)
+        .map_err(|_| AppError::new(ErrorCode::IoError, "恢复数据库失败"))?;
+    // 旧的 WAL/SHM 文件属于恢复前的数据库，必须一并清理
+    let _ = std::fs::remove_file(db_dir.join("db.sqlite-wal"));
+    let _ = std::fs::remove_file(db_dir.join("db.sqlite-shm"));
+    crate::infra::startup::remember_restore(
+        app,
+        StartupRestore {
+            operator_id,
+            file_path: src.to_string_lossy().into_owned(),
+            failed_copy,
+        },
+    );
+    Ok(())
+}
+
+/// 所选备份为加密备份时解密到数据库目录下的临时文件并返回其路径，明文备份返回 None；
+/// 未传口令时从故障数据库读取本机保存的口令
+async fn decrypt_startup_backup(
+    src: &std::path::Path,
+    db_path: &std::path::Path,
+    passphrase: Option<&str>,
+) -> Result<Option<std::path::PathBuf>, AppError> {
+    let data = std::fs::read(src).map_err(|_| AppError::new(ErrorCode::IoError, "读取备份文件失败"))?;
+    if !crypto::is_encrypted_backup(&data) {
+        return Ok(None);
+    }
+    let plain = match passphrase.filter(|value| !value.is_empty()) {
+        Some(passphrase) => crypto::decrypt_backup(passphrase, &data)?,
+        None => {
+            let missing = || AppError::new(ErrorCode::ValidationError, "该备份已加密，请输入备份口令");
+            let pool = db::open_read_only(db_path).await.map_err(|_| missing())?;
+            let result = backup_encryption_service::decrypt_if_encrypted(&pool, src, None).await;
+            pool.close().await;
+            result?.ok_or_else(missing)?
+        }
+    };
+    if !plain.starts_with(b"SQLite format 3\0") {
+        return Err(AppError::new(ErrorCode::ValidationError, "备份文件不是有效的数据库备份"));
+    }
+    let staged = db_path.with_file_name(format!("db_restore_{}.sqlite", uuid::Uuid::new_v4()));
+    std::fs::write(&staged, plain).map_err(|_| AppError::new(ErrorCode::IoError, "写入解密备份失败"))?;
+    Ok(Some(staged))
+}
+
+/// 校验管理员凭据：优先在故障数据库中校验，失败次数与锁定和正常登录共用；
+/// 故障数据库无法打开或读取时改在所选备份中以只读方式校验，失败次数记在启动状态中，同样达到上限后锁定
+async fn verify_startup_admin(
+    app: &AppHandle,
+    db_path: &std::path::Path,
+    backup_path: &std::path::Path,
+    username: &str,
+    password: &str,
+) -> Result<String, AppError> {
+    if db_path.is_file() {
+        if let Ok(pool) = db::open_existing(db_path).await {
+            let result = auth_service::verify_admin_password(&pool, username, password).await;
+            pool.close().await;
+            match result {
+                Err(err) if matches!(err.code, ErrorCode::DbError | ErrorCode::DbBusy) => {}
+                // 账号存在但凭据或角色不符时直接拒绝，不再尝试备份中的旧账号
+                result => return result,
+            }
+        }
+    }
+
+    let startup = app
+        .try_state::<StartupState>()
+        .ok_or_else(|| AppError::new(ErrorCode::Conflict, "当前状态不允许该操作"))?;
+    let now = chrono::Utc::now().timestamp();
+    startup.ensure_restore_unlocked(now)?;
+    let pool = db::open_read_only(backup_path).await?;
+    let result = auth_service::check_admin_password(&pool, username, password).await;
+    pool.close().await;
+    match &result {
+        Ok(_) => startup.record_restore_attempt(false, now),
+        Err(err) if matches!(err.code, ErrorCode::AuthFailed) => startup.record_restore_attempt(true, now),
+        Err(_) => {}
+    }
+    result
+}
+
+/// 仅允许在启动失败后执行重试/恢复
+fn ensure_startup_failed(app: &AppHandle) -> Result<(), AppError> {
+    let failed = app
+        .try_state::<StartupState>()
+        .map(|startup| startup.is_failed())
+        .unwrap_or(false);
+    if !failed {
+        return Err(AppError::new(ErrorCode::Conflict, "当前状态不允许该操作"));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_bootstrap(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+) -> Result<bootstrap_service::BootstrapDto, AppError> {
+    // 启动引导为只读聚合查询，不写审计，避免每次登录产生噪声
+    bootstrap_service::get_bootstrap(&state.pool(), &actor_operator_id).await
+}
+
+#[tauri::command]
+pub async fn take_pending_deep_link(app: AppHandle) -> Result<Option<String>, AppError> {
+    // 仅返回已解析的前端路由，链接本身不携带凭据，无需鉴权
+    Ok(app
+        .try_state::<DeepLinkState>()
+        .and_then(|deep_link| deep_link.take()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FilterOptionsInput {
+    // 实体：item / txn / operator / audit
+    pub entity: String,
+    // 字段：如 item.uom、txn.type
+    pub field: String,
+    pub keyword: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_filter_options(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: FilterOptionsInput,
+) -> Result<FilterOptionsResult, AppError> {
+    // 下拉选项为只读查询，频繁调用，不写审计
+    if filter_option_service::requires_admin(&input.entity) {
+        permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+    } else {
+        permission_service::require_role_by_id(
+            &state.pool(),
+            &actor_operator_id,
+            &["admin", "keeper", "viewer", "member"],
+        )
+        .await?;
+    }
+    let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+    filter_option_service::get_filter_options(
+        &state.pool(),
+        &state.filter_options_cache,
+        &input.entity,
+        &input.field,
+        input.keyword.as_deref(),
+        allowed_warehouse_ids,
+    )
+    .await
+}
@@ -0,0 +1,140 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::api::{command_guard, notification_cmd};
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::approval_repo::ApprovalRequestRow;
+use crate::services::approval_service::{self, SubmitApprovalInput};
+use crate::services::{notification_service, permission_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ListApprovalRequestsInput {
+  pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecideApprovalInput {
+  pub id: String,
+  pub note: Option<String>,
+}
+
+/// 审批申请列表：有审批权限的人员查看全部申请，其他人员仅查看本人提交的申请
+#[tauri::command]
+pub async fn list_approval_requests(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListApprovalRequestsInput,
+) -> Result<Vec<ApprovalRequestRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let can_decide = permission_service::has_permission(&state.pool(), &actor_operator_id, "approval.decide").await?;
+  let audit_request = json!({
+    "status": input.status.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ApprovalList,
+    None,
+    Some(audit_request),
+    || async {
+      let requested_by = (!can_decide).then_some(actor_operator_id.as_str());
+      approval_service::list_requests(&state.pool(), input.status.as_deref(), requested_by).await
+    },
+  )
+  .await
+}
+
+/// 提交审批申请；申请人须具备对应操作的权限，提交后通知审批人
+#[tauri::command]
+pub async fn submit_approval_request(
+  app_handle: AppHandle,
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SubmitApprovalInput,
+) -> Result<ApprovalRequestRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  if input.kind == "count_adjust" || input.kind == "scrap" {
+    // 报废仅管理员与库管可操作
+    let roles: &[&str] = if input.kind == "scrap" { &["admin", "keeper"] } else { &["admin", "keeper", "member"] };
+    permission_service::require_role_by_id(&state.pool(), &actor_operator_id, roles).await?;
+    if let Some(slot_id) = input.slot_id.as_deref() {
+      permission_service::require_slot_access(&state.pool(), &actor_operator_id, slot_id).await?;
+    }
+  } else {
+    permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  }
+  let audit_request = json!({
+    "kind": input.kind.clone(),
+    "txn_no": input.txn_no.clone(),
+    "item_id": input.item_id.clone(),
+    "slot_id": input.slot_id.clone(),
+    "actual_qty": input.actual_qty,
+    "qty": input.qty,
+    "file_path": input.file_path.clone(),
+    "remote_name": input.remote_name.clone(),
+    "reason": input.reason.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  let request = command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ApprovalSubmit,
+    None,
+    Some(audit_request),
+    || async {
+      let request = approval_service::submit_request(&state.pool(), &actor_operator_id, &input).await?;
+      notification_service::notify_approval_request(&state.pool(), &request).await?;
+      Ok(request)
+    },
+  )
+  .await?;
+  notification_cmd::emit_new(&app_handle);
+  Ok(request)
+}
+
+#[tauri::command]
+pub async fn approve_approval_request(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DecideApprovalInput,
+) -> Result<(), AppError> {
+  decide(state, actor_operator_id, input, true).await
+}
+
+#[tauri::command]
+pub async fn reject_approval_request(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DecideApprovalInput,
+) -> Result<(), AppError> {
+  decide(state, actor_operator_id, input, false).await
+}
+
+async fn decide(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DecideApprovalInput,
+  approve: bool,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "note": input.note.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  let action = if approve { AuditAction::ApprovalApprove } else { AuditAction::ApprovalReject };
+  command_guard::run_with_audit(&state.pool(), action, None, Some(audit_request), || async {
+    approval_service::decide_request(&state.pool(), &input.id, approve, &actor_operator_id, input.note.clone()).await
+  })
+  .await
+}
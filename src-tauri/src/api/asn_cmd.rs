@@ -0,0 +1,263 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::asn_repo::{AsnLineDetailRow, AsnListRow};
+use crate::services::asn_service::{self, AsnLineInput, AsnReceiveLineInput};
+use crate::services::permission_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAsnInput {
+    pub supplier: String,
+    // 预计到货时间（秒级时间戳）
+    pub eta: Option<i64>,
+    pub note: Option<String>,
+    pub lines: Vec<AsnLineInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReceiveAsnInput {
+    pub id: String,
+    pub to_slot_id: String,
+    pub lines: Vec<AsnReceiveLineInput>,
+    pub occurred_at: i64,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    pub note: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AsnIdInput {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AsnListInput {
+    // open / closed / cancelled，不填返回全部
+    pub status: Option<String>,
+    // 按预报单号或供应商模糊匹配
+    pub keyword: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AsnVarianceInput {
+    pub supplier: Option<String>,
+    pub start_at: Option<i64>,
+    pub end_at: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn create_asn(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: CreateAsnInput,
+) -> Result<asn_service::AsnCreated, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "supplier": input.supplier.clone(),
+      "eta": input.eta,
+      "line_count": input.lines.len(),
+      "actor_operator_id": actor_operator_id.clone(),
+      "note": input.note.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::AsnCreate,
+        None,
+        Some(audit_request),
+        || async {
+            asn_service::create_asn(
+                &state.pool(),
+                &input.supplier,
+                input.eta,
+                input.note.clone(),
+                input.lines.clone(),
+                &actor_operator_id,
+            )
+            .await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn receive_against_asn(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: ReceiveAsnInput,
+) -> Result<asn_service::AsnReceived, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    // 启用 RBAC 时校验收货库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.to_slot_id).await?;
+    let asn = asn_service::get_open_asn(&state.pool(), &input.id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "id": input.id.clone(),
+      "asn_no": asn.asn_no.clone(),
+      "to_slot_id": input.to_slot_id.clone(),
+      "lines": input.lines.iter().map(|line| json!({ "line_id": line.line_id.clone(), "qty": line.qty })).collect::<Vec<_>>(),
+      "occurred_at": input.occurred_at,
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "note": input.note.clone(),
+      "location": input.location.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::AsnReceive,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            asn_service::receive_against_asn(
+                &state.pool(),
+                &input.id,
+                &input.to_slot_id,
+                input.lines.clone(),
+                input.occurred_at,
+                &business_operator_id,
+                input.note.clone(),
+                input.location.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn close_asn(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: AsnIdInput,
+) -> Result<(), AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+    let asn = asn_service::get_open_asn(&state.pool(), &input.id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "id": input.id.clone(),
+      "asn_no": asn.asn_no.clone(),
+      "actor_operator_id": actor_operator_id.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::AsnClose,
+        None,
+        Some(audit_request),
+        || async { asn_service::close_asn(&state.pool(), &input.id).await },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn cancel_asn(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: AsnIdInput,
+) -> Result<(), AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+    let asn = asn_service::get_open_asn(&state.pool(), &input.id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "id": input.id.clone(),
+      "asn_no": asn.asn_no.clone(),
+      "actor_operator_id": actor_operator_id.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::AsnCancel,
+        None,
+        Some(audit_request),
+        || async { asn_service::cancel_asn(&state.pool(), &input.id).await },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_asns(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: AsnListInput,
+) -> Result<Vec<AsnListRow>, AppError> {
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "viewer", "member"],
+    )
+    .await?;
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::AsnList,
+        None,
+        Some(json!({
+          "actor_operator_id": actor_operator_id.clone(),
+          "status": input.status.clone(),
+          "keyword": input.keyword.clone()
+        })),
+        || async { asn_service::list_asns(&state.pool(), input.status.as_deref(), input.keyword.clone()).await },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn get_asn(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: AsnIdInput,
+) -> Result<asn_service::AsnDetail, AppError> {
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "viewer", "member"],
+    )
+    .await?;
+    asn_service::get_asn(&state.pool(), &input.id).await
+}
+
+#[tauri::command]
+pub async fn get_asn_variance_report(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: AsnVarianceInput,
+) -> Result<Vec<AsnLineDetailRow>, AppError> {
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "viewer"],
+    )
+    .await?;
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::AsnVarianceReport,
+        None,
+        Some(json!({
+          "actor_operator_id": actor_operator_id.clone(),
+          "supplier": input.supplier.clone(),
+          "start_at": input.start_at,
+          "end_at": input.end_at
+        })),
+        || async {
+            asn_service::variance_report(&state.pool(), input.supplier.clone(), input.start_at, input.end_at).await
+        },
+    )
+    .await
+}
@@ -5,6 +5,7 @@ use tauri::State;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
+use crate::repo::audit_repo::AuditLogFilter;
 use crate::services::{audit_service, permission_service};
 use crate::state::AppState;
 
@@ -12,50 +13,85 @@ use crate::state::AppState;
 pub struct AuditListInput {
   pub action: Option<String>,
   pub keyword: Option<String>,
+  pub operator_id: Option<String>,
+  pub result: Option<String>,
   pub start_at: Option<i64>,
   pub end_at: Option<i64>,
   // actor_operator_id provided as top-level arg
   pub page_index: i64,
   pub page_size: i64,
+  // 游标翻页：上一页返回的 next_after_created_at / next_after_id，提供时忽略 page_index
+  pub after_created_at: Option<i64>,
+  pub after_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct AuditExportInput {
+  pub action: Option<String>,
+  pub keyword: Option<String>,
+  pub operator_id: Option<String>,
+  pub result: Option<String>,
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditArchiveInput {
+  // 归档截止时间（不含），早于该时间的审计日志写入归档文件后从库中删除
+  pub before_at: i64,
   // actor_operator_id provided as top-level arg
 }
 
+#[derive(Debug, Deserialize)]
+pub struct AuditArchiveSearchInput {
+  pub keyword: Option<String>,
+  pub operator_id: Option<String>,
+  pub target_id: Option<String>,
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+  // actor_operator_id provided as top-level arg
+  pub page_index: i64,
+  pub page_size: i64,
+}
+
 #[tauri::command]
 pub async fn list_audit_logs(
   state: State<'_, AppState>,
   actor_operator_id: String,
   input: AuditListInput,
 ) -> Result<audit_service::AuditListResult, AppError> {
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
-  let action = input.action;
-  let keyword = input.keyword;
-  let start_at = input.start_at;
-  let end_at = input.end_at;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_view_audit").await?;
+  let filter = AuditLogFilter {
+    action: input.action,
+    keyword: input.keyword,
+    actor_operator_id: input.operator_id,
+    result: input.result,
+    start_at: input.start_at,
+    end_at: input.end_at,
+  };
   let audit_request = json!({
-    "action": action.clone(),
-    "keyword": keyword.clone(),
-    "start_at": start_at,
-    "end_at": end_at,
+    "action": filter.action.clone(),
+    "keyword": filter.keyword.clone(),
+    "operator_id": filter.actor_operator_id.clone(),
+    "result": filter.result.clone(),
+    "start_at": filter.start_at,
+    "end_at": filter.end_at,
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::AuditList,
     None,
     Some(audit_request),
     || async {
       audit_service::list_audit_logs(
-        &state.pool,
-        action.clone(),
-        keyword.clone(),
-        start_at,
-        end_at,
+        &state.pool(),
+        filter.clone(),
         input.page_index,
         input.page_size,
+        input.after_created_at,
+        input.after_id.clone(),
       )
       .await
     },
@@ -67,14 +103,98 @@ pub async fn list_audit_logs(
 pub async fn export_audit_logs(
   state: State<'_, AppState>,
   actor_operator_id: String,
+  input: Option<AuditExportInput>,
 ) -> Result<audit_service::AuditExportResult, AppError> {
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_view_audit").await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_export").await?;
+  let input = input.unwrap_or_default();
+  let filter = AuditLogFilter {
+    action: input.action,
+    keyword: input.keyword,
+    actor_operator_id: input.operator_id,
+    result: input.result,
+    start_at: input.start_at,
+    end_at: input.end_at,
+  };
+  let audit_request = json!({
+    "action": filter.action.clone(),
+    "keyword": filter.keyword.clone(),
+    "operator_id": filter.actor_operator_id.clone(),
+    "result": filter.result.clone(),
+    "start_at": filter.start_at,
+    "end_at": filter.end_at,
+    "actor_operator_id": actor_operator_id.clone()
+  });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::AuditExport,
     None,
-    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
-    || async { audit_service::export_audit_logs(&state.pool).await },
+    Some(audit_request),
+    || async { audit_service::export_audit_logs(&state.pool(), filter.clone()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn archive_audit_logs(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: AuditArchiveInput,
+) -> Result<audit_service::AuditArchiveResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "before_at": input.before_at,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuditArchive,
+    None,
+    Some(audit_request),
+    || async { audit_service::archive_audit_logs(&state.pool(), input.before_at).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn search_audit_archives(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: AuditArchiveSearchInput,
+) -> Result<audit_service::AuditArchiveSearchResult, AppError> {
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_view_audit").await?;
+  let query = audit_service::AuditArchiveQuery {
+    keyword: input.keyword,
+    operator_id: input.operator_id,
+    target_id: input.target_id,
+    start_at: input.start_at,
+    end_at: input.end_at,
+  };
+  let audit_request = json!({
+    "keyword": query.keyword.clone(),
+    "operator_id": query.operator_id.clone(),
+    "target_id": query.target_id.clone(),
+    "start_at": query.start_at,
+    "end_at": query.end_at,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuditArchiveSearch,
+    None,
+    Some(audit_request),
+    || async {
+      audit_service::search_audit_archives(
+        &state.pool(),
+        query.clone(),
+        input.page_index,
+        input.page_size,
+      )
+      .await
+    },
   )
   .await
 }
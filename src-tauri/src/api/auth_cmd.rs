@@ -1,10 +1,12 @@
+use serde::Deserialize;
 use serde_json::json;
 use tauri::State;
 
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
-use crate::services::auth_service::{self, LoginResult};
+use crate::services::auth_service::{self, LoginResult, SwitchOperatorResult};
+use crate::services::session_service;
 use crate::state::AppState;
 
 #[tauri::command]
@@ -15,11 +17,11 @@ pub async fn login(
 ) -> Result<LoginResult, AppError> {
   let audit_request = json!({ "username": username.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::AuthLogin,
     None,
     Some(audit_request),
-    || async { auth_service::login(&state.pool, &username, &password).await },
+    || async { auth_service::login(&state.pool(), &username, &password).await },
   )
   .await
 }
@@ -29,14 +31,125 @@ pub async fn logout(
   state: State<'_, AppState>,
   #[allow(non_snake_case)]
   actorOperatorId: String,
+  #[allow(non_snake_case)]
+  sessionId: Option<String>,
 ) -> Result<(), AppError> {
-  let audit_request = json!({ "actor_operator_id": actorOperatorId.clone() });
+  let audit_request = json!({
+    "actor_operator_id": actorOperatorId.clone(),
+    "session_id": sessionId.clone()
+  });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::AuthLogout,
     Some(actorOperatorId),
     Some(audit_request),
-    || async { Ok(()) },
+    || async {
+      // 锁屏/退出时结束当前会话
+      if let Some(session_id) = &sessionId {
+        session_service::end_session(&state.pool(), session_id, "logout").await?;
+      }
+      Ok(())
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn switch_operator(
+  state: State<'_, AppState>,
+  username: String,
+  pin: String,
+  #[allow(non_snake_case)]
+  previousSessionId: Option<String>,
+) -> Result<SwitchOperatorResult, AppError> {
+  let audit_request = json!({
+    "username": username.clone(),
+    "pin": null,
+    "previous_session_id": previousSessionId.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuthSwitchOperator,
+    None,
+    Some(audit_request),
+    || async {
+      auth_service::switch_operator(
+        &state.pool(),
+        &username,
+        &pin,
+        previousSessionId.as_deref(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn reset_password_with_code(
+  state: State<'_, AppState>,
+  username: String,
+  code: String,
+  #[allow(non_snake_case)]
+  newPassword: String,
+) -> Result<(), AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "username": username.clone(),
+    "code": null,
+    "new_password": null
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuthResetPasswordWithCode,
+    None,
+    Some(audit_request),
+    || async {
+      auth_service::reset_password_with_code(&state.pool(), &username, &code, &newPassword).await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPinInput {
+  pub password: String,
+  // 为空表示清除 PIN
+  pub pin: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn set_pin(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetPinInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "password": null,
+    "pin": null,
+    "clear": input.pin.is_none(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuthSetPin,
+    Some(actor_operator_id.clone()),
+    Some(audit_request),
+    || async {
+      auth_service::set_pin(
+        &state.pool(),
+        &actor_operator_id,
+        &input.password,
+        input.pin.as_deref(),
+      )
+      .await
+    },
   )
   .await
 }
@@ -59,13 +172,13 @@ pub async fn change_password(
     "actor_operator_id": actorOperatorId.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::AuthChangePassword,
     Some(actor_id),
     Some(audit_request),
     || async {
       auth_service::change_password(
-        &state.pool,
+        &state.pool(),
         &actorOperatorId,
         &oldPassword,
         &newPassword,
@@ -1,14 +1,71 @@
-// 审计与迁移拦截的统一入口
+// 审计、会话与迁移拦截的统一入口
+use std::sync::Arc;
+use std::time::Instant;
+
 use serde_json::Value;
 use sqlx::SqlitePool;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::Manager;
 
-use crate::domain::audit::AuditAction;
+use crate::domain::audit::{AuditAction, AuditLevel};
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::infra::metrics;
 // operator_repo 不再用于通过用户名解析 actor id
-use crate::services::audit_service;
+use crate::services::{audit_service, session_service, structure_tree_service, system_service};
 use crate::state::AppState;
 
-/// 统一执行入口：执行业务逻辑并记录审计
+/// 不校验会话的命令：登录、切换记录人与退出时原会话可能已结束，心跳自行返回会话状态
+const SESSION_EXEMPT_COMMANDS: &[&str] = &["login", "switch_operator", "logout", "check_session"];
+
+/// 命令分发入口：前端为带操作人的命令同时传入 sessionId，校验该会话有效后再交给命令处理；
+/// 会话已结束（退出、强制下线）、过期或空闲超时时直接返回 SESSION_EXPIRED，命令不会执行
+pub fn dispatch_with_session<H>(invoke: Invoke, handler: Arc<H>) -> bool
+where
+    H: Fn(Invoke) -> bool + Send + Sync + 'static,
+{
+    let Some((session_id, operator_id)) = session_args(&invoke) else {
+        return handler(invoke);
+    };
+    let webview = invoke.message.webview();
+    // 数据库初始化完成前尚无连接池，命令自身会返回未就绪
+    let Some(state) = webview.try_state::<AppState>() else {
+        return handler(invoke);
+    };
+    let pool = state.pool();
+    let app = webview.app_handle().clone();
+    tauri::async_runtime::spawn(async move {
+        match session_service::ensure_session(&pool, session_id.as_deref(), &operator_id).await {
+            Ok(()) => {
+                // 回到主线程分发，与未经校验的命令保持一致的执行线程
+                let resolver = invoke.resolver.clone();
+                let command = invoke.message.command().to_string();
+                let _ = app.run_on_main_thread(move || {
+                    if !handler(invoke) {
+                        resolver.reject(format!("Command {} not found", command));
+                    }
+                });
+            }
+            Err(err) => invoke.resolver.reject(err),
+        }
+    });
+    true
+}
+
+/// 需校验会话时返回 (sessionId, actorOperatorId)；未携带操作人的命令（如启动屏、插件命令）不校验
+fn session_args(invoke: &Invoke) -> Option<(Option<String>, String)> {
+    if SESSION_EXEMPT_COMMANDS.contains(&invoke.message.command()) {
+        return None;
+    }
+    let InvokeBody::Json(Value::Object(args)) = invoke.message.payload() else {
+        return None;
+    };
+    let operator_id = args.get("actorOperatorId").and_then(Value::as_str)?;
+    let session_id = args.get("sessionId").and_then(Value::as_str).map(str::to_string);
+    Some((session_id, operator_id.to_string()))
+}
+
+/// 统一执行入口：执行业务逻辑并记录审计与耗时统计
 pub async fn run_with_audit<T, F, Fut>(
     pool: &SqlitePool,
     action: AuditAction,
@@ -20,7 +77,29 @@ where
     F: FnOnce() -> Fut,
     Fut: std::future::Future<Output = Result<T, AppError>>,
 {
+    let started = Instant::now();
+    // 业务逻辑可能包含多次提交，这里不做占用重试；需要重试的单个事务使用 db::retry_busy
     let result = operation().await;
+    // 耗时不含审计写入
+    metrics::record(action.as_str(), started.elapsed(), result.is_ok());
+    record_audit(pool, action, actor_operator_id, request_json, result).await
+}
+
+/// 为已执行完成的操作记录审计；用于执行过程中会替换连接池、不能走 run_with_audit 的命令
+pub async fn record_audit<T>(
+    pool: &SqlitePool,
+    action: AuditAction,
+    actor_operator_id: Option<String>,
+    request_json: Option<Value>,
+    result: Result<T, AppError>,
+) -> Result<T, AppError> {
+    if result.is_ok() && changes_structure(action) {
+        structure_tree_service::invalidate();
+    }
+    // 按审计详略配置跳过不需要持久化的动作
+    if !audit_service::should_record(pool, action).await {
+        return result;
+    }
     let audit_result = result.as_ref().map(|_| ()).map_err(|err| err);
     let (target_type, target_id) = infer_audit_target(action, request_json.as_ref());
     let resolved_actor_operator_id =
@@ -49,15 +128,40 @@ where
 pub async fn ensure_not_migrating(state: &AppState) -> Result<(), AppError> {
     let migrating = state.migrating.lock().await;
     if *migrating {
-        return Err(AppError::new(
-            ErrorCode::Conflict,
-            "存储迁移中，暂不可执行该操作",
-        ));
+        return Err(AppError::keyed(ErrorCode::Conflict, MessageKey::Migrating));
+    }
+    Ok(())
+}
+
+/// 写操作入口：存储迁移期间或只读维护模式下拒绝执行；导出、备份等不修改数据的命令只需 ensure_not_migrating
+pub async fn ensure_writable(state: &AppState) -> Result<(), AppError> {
+    ensure_not_migrating(state).await?;
+    let read_only = system_service::read_only_state(&state.pool()).await?;
+    if read_only.enabled {
+        return Err(match read_only.reason {
+            Some(reason) => AppError::keyed_with(
+                ErrorCode::ReadOnlyMode,
+                MessageKey::ReadOnlyModeWithReason,
+                &[("reason", reason)],
+            ),
+            None => AppError::keyed(ErrorCode::ReadOnlyMode, MessageKey::ReadOnlyMode),
+        });
     }
     Ok(())
 }
 
 /// 根据动作与请求参数推断审计目标
+/// 成功后会改变仓库/货架/库位结构的动作：结构相关写操作，以及导入、恢复、撤销等整体数据变更
+fn changes_structure(action: AuditAction) -> bool {
+    if action.level() == AuditLevel::Read {
+        return false;
+    }
+    matches!(
+        infer_audit_target(action, None).0.as_deref(),
+        Some("warehouse" | "rack" | "slot" | "data" | "undo")
+    )
+}
+
 fn infer_audit_target(
     action: AuditAction,
     request_json: Option<&Value>,
@@ -68,25 +172,74 @@ fn infer_audit_target(
         | AuditAction::AuthLogout
         | AuditAction::AuthChangePassword
         | AuditAction::AuthResetPassword
+        | AuditAction::AuthLockout
+        | AuditAction::AuthUnlock
+        | AuditAction::AuthSwitchOperator
+        | AuditAction::AuthSetPin
+        | AuditAction::AuthIssueResetCode
+        | AuditAction::AuthResetPasswordWithCode
+        | AuditAction::KioskOperatorSelect
         | AuditAction::OperatorList
         | AuditAction::OperatorCreate
         | AuditAction::OperatorUpdate
-        | AuditAction::OperatorStatus => ("operator", &["id", "username", "actor_operator_id"][..]),
+        | AuditAction::OperatorStatus
+        | AuditAction::OperatorWarehouseUpdate
+        | AuditAction::OperatorRolePreview
+        | AuditAction::OperatorRoleBulkUpdate
+        | AuditAction::OperatorPermissionSimulate
+        | AuditAction::OperatorProfileView
+        | AuditAction::OperatorProfileUpdate => ("operator", &["id", "username", "actor_operator_id"][..]),
+        AuditAction::RoleCapabilityList | AuditAction::RoleCapabilityUpdate => ("role_capability", &["role"][..]),
+        AuditAction::SessionList | AuditAction::SessionTerminate => ("session", &["id"][..]),
         AuditAction::WarehouseList
         | AuditAction::WarehouseCreate
         | AuditAction::WarehouseUpdate
-        | AuditAction::WarehouseStatus => ("warehouse", &["id", "code"][..]),
+        | AuditAction::WarehouseStatus
+        | AuditAction::WarehouseUtilization
+        | AuditAction::StructureTreeView => ("warehouse", &["id", "code"][..]),
         AuditAction::RackList
         | AuditAction::RackCreate
+        | AuditAction::RackClone
         | AuditAction::RackUpdate
-        | AuditAction::RackStatus => ("rack", &["id", "code"][..]),
-        AuditAction::SlotList | AuditAction::SlotRegen | AuditAction::SlotStatus => {
+        | AuditAction::RackStatus
+        | AuditAction::RackMapView
+        | AuditAction::RackHeatmapView
+        | AuditAction::RackMapExport => ("rack", &["id", "code"][..]),
+        AuditAction::SlotList
+        | AuditAction::SlotRegen
+        | AuditAction::SlotStatus
+        | AuditAction::SlotBulkStatus
+        | AuditAction::SlotRecode => {
             ("slot", &["slot_id", "rack_id", "rack_code"][..])
         }
         AuditAction::ItemList
+        | AuditAction::ItemDetailView
         | AuditAction::ItemCreate
         | AuditAction::ItemUpdate
-        | AuditAction::ItemStatus => ("item", &["id", "item_code"][..]),
+        | AuditAction::ItemStatus
+        | AuditAction::ItemBulkStatus
+        | AuditAction::ItemBulkUpdate
+        | AuditAction::ItemMerge
+        | AuditAction::ItemCodeChange => ("item", &["id", "item_code"][..]),
+        AuditAction::ItemAliasList
+        | AuditAction::ItemAliasCreate
+        | AuditAction::ItemAliasUpdate
+        | AuditAction::ItemAliasDelete => ("item_alias", &["id", "item_id", "code"][..]),
+        AuditAction::ScanResolve => ("scan", &["code"][..]),
+        AuditAction::ItemAttributeDefList
+        | AuditAction::ItemAttributeDefCreate
+        | AuditAction::ItemAttributeDefUpdate
+        | AuditAction::ItemAttributeDefDelete => ("item_attribute_def", &["id", "name"][..]),
+        AuditAction::ReasonCodeList
+        | AuditAction::ReasonCodeCreate
+        | AuditAction::ReasonCodeUpdate
+        | AuditAction::ReasonCodeDelete => ("reason_code", &["id", "code"][..]),
+        AuditAction::UndoChange => ("undo", &["entry_id"][..]),
+        AuditAction::ApprovalList
+        | AuditAction::ApprovalSubmit
+        | AuditAction::ApprovalApprove
+        | AuditAction::ApprovalReject => ("approval_request", &["id", "kind"][..]),
+        AuditAction::NotificationMarkRead => ("notification", &["ids"][..]),
         AuditAction::MediaAttachmentItemAdd
         | AuditAction::MediaAttachmentItemList
         | AuditAction::MediaAttachmentItemRemove
@@ -103,14 +256,43 @@ fn infer_audit_target(
         AuditAction::TxnInbound
         | AuditAction::TxnOutbound
         | AuditAction::TxnMove
+        | AuditAction::TxnQuickTransfer
         | AuditAction::TxnCount
+        | AuditAction::TxnScrap
         | AuditAction::TxnReversal
-        | AuditAction::TxnList => ("txn", &["txn_no", "ref_txn_id"][..]),
+        | AuditAction::TxnList
+        | AuditAction::TxnCopy => ("txn", &["txn_no", "ref_txn_id"][..]),
+        AuditAction::LoanCreate | AuditAction::LoanReturn | AuditAction::LoanList => {
+            ("loan", &["loan_no", "id", "item_id"][..])
+        }
+        AuditAction::OutboundPickCreate
+        | AuditAction::OutboundPickConfirm
+        | AuditAction::OutboundPickCancel
+        | AuditAction::OutboundPickList => ("outbound_pick", &["pick_no", "id", "item_id"][..]),
+        AuditAction::AsnCreate
+        | AuditAction::AsnReceive
+        | AuditAction::AsnClose
+        | AuditAction::AsnCancel
+        | AuditAction::AsnList
+        | AuditAction::AsnVarianceReport => ("asn", &["asn_no", "id", "supplier"][..]),
         AuditAction::SystemSettingsUpdate
+        | AuditAction::SystemReadOnlyUpdate
         | AuditAction::SystemSettingsRead
-        | AuditAction::SystemStorageRootChange => ("system", &["new_path", "action"][..]),
-        AuditAction::AuditList | AuditAction::AuditExport => ("audit", &["action"][..]),
-        AuditAction::StockListBySlot | AuditAction::StockListByItem | AuditAction::StockExport => {
+        | AuditAction::SystemStorageRootChange
+        | AuditAction::MediaStorageScan
+        | AuditAction::MediaStorageMetricList
+        | AuditAction::PerformanceStatsView => ("system", &["new_path", "action"][..]),
+        AuditAction::AuditList
+        | AuditAction::AuditExport
+        | AuditAction::AuditArchive
+        | AuditAction::AuditArchiveSearch => ("audit", &["action", "target_id", "operator_id"][..]),
+        AuditAction::StockListBySlot
+        | AuditAction::StockListByItem
+        | AuditAction::StockExport
+        | AuditAction::StockValuation
+        | AuditAction::StockRecalculate
+        | AuditAction::StockCorrect
+        | AuditAction::StockCopy => {
             ("stock", &["item_code", "slot_code"][..])
         }
         AuditAction::DbBackup
@@ -118,8 +300,56 @@ fn infer_audit_target(
         | AuditAction::ItemExport
         | AuditAction::ItemImport
         | AuditAction::TxnExport
-        | AuditAction::TxnImport => ("data", &["file_path"][..]),
+        | AuditAction::TxnImport
+        | AuditAction::StructureImport
+        | AuditAction::MasterBundleExport
+        | AuditAction::MasterBundleImport
+        | AuditAction::OpeningStockImport => ("data", &["file_path"][..]),
+        AuditAction::DbVerify | AuditAction::DbQueryPlanCheck | AuditAction::DbRepair | AuditAction::DiagnosticsCollect => {
+            ("data", &["kind"][..])
+        }
+        AuditAction::CountSheetExport | AuditAction::CountSheetImport => {
+            ("count_sheet", &["rack_id", "warehouse_id", "file_path"][..])
+        }
+        AuditAction::PickListPrint => ("pick_list", &["reference", "warehouse_id"][..]),
+        AuditAction::LabelTemplateList
+        | AuditAction::LabelTemplateCreate
+        | AuditAction::LabelTemplateUpdate
+        | AuditAction::LabelTemplateDelete
+        | AuditAction::LabelPrint => ("label_template", &["id", "template_id", "name"][..]),
+        AuditAction::RemoteBackupConfigUpdate | AuditAction::RemoteBackupList => {
+            ("data", &["kind", "endpoint"][..])
+        }
+        AuditAction::BackupEncryptionUpdate
+        | AuditAction::BackupEncryptExisting
+        | AuditAction::DemoDataGenerate => ("data", &[][..]),
+        AuditAction::StockClose | AuditAction::StockAsof => ("stock", &["date"][..]),
         AuditAction::DashboardOverview => ("dashboard", &["actor_operator_id"][..]),
+        AuditAction::ReportItemStockTrend => ("report", &["item_id"][..]),
+        AuditAction::ReportStockAging
+        | AuditAction::ReportStockAgingExport
+        | AuditAction::ReportLowStock
+        | AuditAction::ReportExpiringStock
+        | AuditAction::ReportTxnReasons => {
+            ("report", &["warehouse_id"][..])
+        }
+        AuditAction::StockThresholdList
+        | AuditAction::StockThresholdSet
+        | AuditAction::StockThresholdDelete => ("stock_threshold", &["item_id", "warehouse_id"][..]),
+        AuditAction::StockHoldList | AuditAction::StockHoldCreate | AuditAction::StockHoldRelease => {
+            ("stock_hold", &["hold_id", "item_id", "slot_id"][..])
+        }
+        AuditAction::CycleCountPlanList
+        | AuditAction::CycleCountPlanCreate
+        | AuditAction::CycleCountPlanUpdate
+        | AuditAction::CycleCountPlanDelete
+        | AuditAction::CycleCountTaskList
+        | AuditAction::CycleCountTaskSkip => ("cycle_count", &["id", "task_id", "name", "warehouse_id"][..]),
+        AuditAction::ReportScheduleCreate
+        | AuditAction::ReportScheduleUpdate
+        | AuditAction::ReportScheduleDelete
+        | AuditAction::ReportScheduleRun => ("report_schedule", &["id", "name"][..]),
+        AuditAction::SmtpConfigUpdate | AuditAction::SmtpTestSend => ("data", &["host", "to"][..]),
     };
 
     let target_id = request_json
@@ -0,0 +1,104 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::services::count_service::{self, CountSheetExportResult, CountSheetImportResult, CountSheetScope};
+use crate::services::permission_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ExportCountSheetsInput {
+  pub warehouse_id: Option<String>,
+  pub rack_id: Option<String>,
+  pub include_empty_slots: Option<bool>,
+  pub show_expected: Option<bool>,
+  // csv / pdf，默认 pdf
+  pub format: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportCountSheetInput {
+  pub file_path: String,
+  // 盘点时间，默认导入时间
+  pub occurred_at: Option<i64>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn export_count_sheets(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ExportCountSheetsInput,
+) -> Result<CountSheetExportResult, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let format = input.format.clone().unwrap_or_else(|| "pdf".to_string());
+  let audit_request = json!({
+    "warehouse_id": input.warehouse_id.clone(),
+    "rack_id": input.rack_id.clone(),
+    "include_empty_slots": input.include_empty_slots,
+    "show_expected": input.show_expected,
+    "format": format.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  let scope = CountSheetScope {
+    warehouse_id: input.warehouse_id,
+    rack_id: input.rack_id,
+    include_empty_slots: input.include_empty_slots.unwrap_or(false),
+    show_expected: input.show_expected.unwrap_or(false),
+  };
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CountSheetExport,
+    None,
+    Some(audit_request),
+    || async {
+      count_service::export_count_sheets(&state.pool(), &scope, &format, allowed_warehouse_ids.clone()).await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn import_count_sheet(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ImportCountSheetInput,
+) -> Result<CountSheetImportResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "member"],
+  )
+  .await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let occurred_at = input.occurred_at.unwrap_or_else(|| chrono::Utc::now().timestamp());
+  let audit_request = json!({
+    "file_path": input.file_path.clone(),
+    "occurred_at": occurred_at,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CountSheetImport,
+    None,
+    Some(audit_request),
+    || async {
+      count_service::import_count_sheet(&state.pool(), &input.file_path, occurred_at, &actor_operator_id).await
+    },
+  )
+  .await
+}
@@ -0,0 +1,203 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::cycle_count_repo::CycleCountPlanRow;
+use crate::services::cycle_count_service::{self, CycleCountPlanInput, TodayCountTasks};
+use crate::services::permission_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCycleCountPlanInput {
+  pub id: String,
+  #[serde(flatten)]
+  pub plan: CycleCountPlanInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteCycleCountPlanInput {
+  pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TodayCountTasksInput {
+  pub warehouse_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SkipCountTaskInput {
+  pub task_id: String,
+}
+
+fn plan_audit_request(plan: &CycleCountPlanInput, actor_operator_id: &str) -> serde_json::Value {
+  json!({
+    "name": plan.name.clone(),
+    "warehouse_id": plan.warehouse_id.clone(),
+    "a_ratio": plan.a_ratio,
+    "b_ratio": plan.b_ratio,
+    "a_interval_days": plan.a_interval_days,
+    "b_interval_days": plan.b_interval_days,
+    "c_interval_days": plan.c_interval_days,
+    "lookback_days": plan.lookback_days,
+    "status": plan.status.clone(),
+    "actor_operator_id": actor_operator_id
+  })
+}
+
+#[tauri::command]
+pub async fn list_cycle_count_plans(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Vec<CycleCountPlanRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CycleCountPlanList,
+    None,
+    Some(audit_request),
+    || async { cycle_count_service::list_plans(&state.pool(), allowed_warehouse_ids.clone()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn create_cycle_count_plan(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: CycleCountPlanInput,
+) -> Result<CycleCountPlanRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CycleCountPlanCreate,
+    None,
+    Some(plan_audit_request(&input, &actor_operator_id)),
+    || async { cycle_count_service::create_plan(&state.pool(), &input).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn update_cycle_count_plan(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateCycleCountPlanInput,
+) -> Result<CycleCountPlanRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let mut audit_request = plan_audit_request(&input.plan, &actor_operator_id);
+  audit_request["id"] = json!(input.id.clone());
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CycleCountPlanUpdate,
+    None,
+    Some(audit_request),
+    || async { cycle_count_service::update_plan(&state.pool(), &input.id, &input.plan).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_cycle_count_plan(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DeleteCycleCountPlanInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CycleCountPlanDelete,
+    None,
+    Some(audit_request),
+    || async { cycle_count_service::delete_plan(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn get_today_count_tasks(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: TodayCountTasksInput,
+) -> Result<TodayCountTasks, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  // 当天首次查看时生成任务，迁移期间只读取已有任务
+  let migrating = *state.migrating.lock().await;
+  if !migrating {
+    let _guard = state.write_lock.lock().await;
+    cycle_count_service::generate_today_tasks(&state.pool()).await?;
+  }
+  let audit_request = json!({
+    "warehouse_id": input.warehouse_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CycleCountTaskList,
+    None,
+    Some(audit_request),
+    || async {
+      cycle_count_service::get_today_count_tasks(
+        &state.pool(),
+        input.warehouse_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn skip_count_task(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SkipCountTaskInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let warehouse_id = cycle_count_service::task_warehouse_id(&state.pool(), &input.task_id).await?;
+  permission_service::require_warehouse_access(&state.pool(), &actor_operator_id, Some(&warehouse_id)).await?;
+  let audit_request = json!({
+    "task_id": input.task_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::CycleCountTaskSkip,
+    None,
+    Some(audit_request),
+    || async { cycle_count_service::skip_task(&state.pool(), &input.task_id, &actor_operator_id).await },
+  )
+  .await
+}
@@ -11,29 +11,69 @@ use crate::state::AppState;
 #[derive(Debug, Deserialize)]
 pub struct DashboardOverviewQuery {
   // actor_operator_id provided as top-level arg
+  // 统计区间与趋势粒度（day/week/month），缺省为近 7 天按日
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+  pub granularity: Option<String>,
+  // 人员作业排行的统计区间与条数（仅管理员返回排行）
+  pub leaderboard_start_at: Option<i64>,
+  pub leaderboard_end_at: Option<i64>,
+  pub leaderboard_limit: Option<i64>,
 }
 
 #[tauri::command]
 pub async fn get_dashboard_overview(
   state: State<'_, AppState>,
   actor_operator_id: String,
-  _query: DashboardOverviewQuery,
+  query: DashboardOverviewQuery,
 ) -> Result<dashboard_service::DashboardOverview, AppError> {
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer"],
   )
   .await?;
+  let leaderboard =
+    if permission_service::has_permission(&state.pool(), &actor_operator_id, "dashboard.leaderboard").await? {
+      Some(dashboard_service::LeaderboardQuery {
+        start_at: query.leaderboard_start_at,
+        end_at: query.leaderboard_end_at,
+        limit: query.leaderboard_limit,
+      })
+    } else {
+      None
+    };
+  let range = dashboard_service::OverviewRange {
+    start_at: query.start_at,
+    end_at: query.end_at,
+    granularity: query.granularity.clone(),
+  };
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
   let audit_request = json!({
-    "actor_operator_id": actor_operator_id.clone()
+    "actor_operator_id": actor_operator_id.clone(),
+    "start_at": query.start_at,
+    "end_at": query.end_at,
+    "granularity": query.granularity.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::DashboardOverview,
     None,
     Some(audit_request),
-    || async { dashboard_service::get_overview(&state.pool).await },
+    || async {
+      // 缓存按区间共享，成本在返回前按角色脱敏
+      let mut overview = dashboard_service::get_overview_cached(
+        &state.pool(),
+        &state.dashboard_cache,
+        range.clone(),
+        leaderboard.clone(),
+      )
+      .await?;
+      if !show_costs {
+        overview.hide_costs();
+      }
+      Ok(overview)
+    },
   )
   .await
 }
@@ -1,11 +1,21 @@
 use serde::Deserialize;
 use serde_json::json;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
-use crate::services::{import_export_service, permission_service, system_service};
+use crate::infra::db;
+use crate::infra::remote_store::RemoteObject;
+use crate::repo::approval_repo::ApprovalRequestRow;
+use crate::services::backup_encryption_service::{
+  self, BackupEncryptResult, BackupEncryptionConfig, BackupEncryptionPatch,
+};
+use crate::services::remote_backup_service::{self, RemoteBackupConfig, RemoteBackupConfigPatch};
+use crate::services::{
+  approval_service, demo_service, diagnostics_service, health_service, import_export_service, master_bundle_service,
+  permission_service, system_service,
+};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -14,9 +24,46 @@ pub struct ImportInput {
   // actor_operator_id provided as top-level arg
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportItemsInput {
+  // 筛选与排序同 list_items
+  pub keyword: Option<String>,
+  pub sort_by: Option<String>,
+  pub sort_dir: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportOpeningStockInput {
+  pub file_path: String,
+  // 期初日期，缺省为导入时间
+  pub occurred_at: Option<i64>,
+  // 已有日常流水时仍然导入
+  pub force: Option<bool>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportMasterBundleInput {
+  pub file_path: String,
+  // 新建可登录账号的初始密码，首次登录须修改；不写入审计
+  pub initial_password: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RestoreInput {
   pub file_path: String,
+  // 加密备份的口令，未提供时使用本机保存的口令
+  pub passphrase: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoteRestoreInput {
+  // 远程备份文件名（来自 list_remote_backups）
+  pub name: String,
+  pub passphrase: Option<String>,
   // actor_operator_id provided as top-level arg
 }
 
@@ -30,6 +77,31 @@ pub struct BackupInput {
   // actor_operator_id provided as top-level arg
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RepairInput {
+  // 健康检查报告中给出的修复项
+  pub repair: String,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateDemoDataInput {
+  // 生成的流水条数，默认 3000
+  pub txn_count: Option<i64>,
+  // 数据库已有业务数据时仍然追加
+  pub force: Option<bool>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecalculateStockInput {
+  pub item_id: Option<String>,
+  pub slot_id: Option<String>,
+  // 为 true 时按流水修正库存，否则仅返回差异
+  pub apply: Option<bool>,
+  // actor_operator_id provided as top-level arg
+}
+
 #[tauri::command]
 pub async fn backup_db(
   state: State<'_, AppState>,
@@ -37,49 +109,393 @@ pub async fn backup_db(
 ) -> Result<String, AppError> {
   command_guard::ensure_not_migrating(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::DbBackup,
     None,
     Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
-    || async { system_service::backup_db(&state.pool).await },
+    || async {
+      let path = system_service::backup_db(&state.pool()).await?;
+      // 已配置远程备份时上传，失败时提示本地备份位置
+      remote_backup_service::upload_backup(&state.pool(), &path).await?;
+      Ok(path)
+    },
   )
   .await
 }
 
 #[tauri::command]
-pub async fn restore_db(state: State<'_, AppState>, actor_operator_id: String, input: RestoreInput) -> Result<(), AppError> {
+pub async fn restore_db(
+  app_handle: AppHandle,
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RestoreInput,
+) -> Result<(), AppError> {
   command_guard::ensure_not_migrating(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "file_path": input.file_path.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
+  let result = async {
+    let target = approval_service::restore_file_target(&input.file_path);
+    let approval = approval_service::ensure_restore_approved(&state.pool(), &target, &actor_operator_id).await?;
+    restore_database(&app_handle, &state, &input.file_path, input.passphrase.as_deref(), approval).await
+  }
+  .await;
+  // 恢复过程中会替换连接池，审计写入恢复后的数据库
+  command_guard::record_audit(&state.pool(), AuditAction::DbRestore, None, Some(audit_request), result).await
+}
+
+/// 恢复数据库：先在临时文件上执行迁移并记录审批已执行，再关闭连接池、替换数据库文件并重连；
+/// 替换前任一步失败时当前数据库保持不变
+async fn restore_database(
+  app_handle: &AppHandle,
+  state: &AppState,
+  file_path: &str,
+  passphrase: Option<&str>,
+  approval: Option<ApprovalRequestRow>,
+) -> Result<(), AppError> {
+  let staged = system_service::stage_restore(&state.pool(), file_path, passphrase).await?;
+  let app_version = app_handle.package_info().version.to_string();
+  if let Err(err) = prepare_staged_restore(&staged, &app_version, approval.as_ref()).await {
+    system_service::discard_staged_restore(&staged.staged_path);
+    return Err(err);
+  }
+
+  let old_pool = state.pool();
+  // 先写回 WAL 日志，关闭后数据库文件不再被占用，可直接替换
+  if let Err(err) = db::checkpoint(&old_pool).await {
+    system_service::discard_staged_restore(&staged.staged_path);
+    return Err(err);
+  }
+  old_pool.close().await;
+  let replaced = system_service::replace_db_file(&staged);
+  if replaced.is_err() {
+    system_service::discard_staged_restore(&staged.staged_path);
+  }
+  // 替换失败时同样在原路径重新打开，保证后续命令可用
+  state.replace_pool(db::connect(&staged.db_path).await?);
+  replaced
+}
+
+/// 在待恢复的临时数据库上执行迁移，并在其中把审批申请记为已执行
+async fn prepare_staged_restore(
+  staged: &system_service::StagedRestore,
+  app_version: &str,
+  approval: Option<&ApprovalRequestRow>,
+) -> Result<(), AppError> {
+  let pool = db::connect(&staged.staged_path).await?;
+  let result = async {
+    db::prepare_restored(&pool, &staged.storage_root, app_version).await?;
+    if let Some(request) = approval {
+      approval_service::mark_restore_executed(&pool, request).await?;
+    }
+    db::checkpoint(&pool).await
+  }
+  .await;
+  pool.close().await;
+  result
+}
+
+#[tauri::command]
+pub async fn verify_database(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<health_service::DbHealthReport, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::DbVerify,
+    None,
+    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    || async { health_service::verify_database(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn check_query_plans(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<health_service::QueryPlanReport, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   command_guard::run_with_audit(
-    &state.pool,
-    AuditAction::DbRestore,
+    &state.pool(),
+    AuditAction::DbQueryPlanCheck,
+    None,
+    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    || async { health_service::check_query_plans(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn collect_diagnostics(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<diagnostics_service::DiagnosticsResult, AppError> {
+  // 诊断包只读取数据，只读维护模式下同样可用
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::DiagnosticsCollect,
+    None,
+    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    || async { diagnostics_service::collect_diagnostics(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn repair_database(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RepairInput,
+) -> Result<health_service::DbRepairResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "kind": input.repair.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::DbRepair,
+    None,
+    Some(audit_request),
+    || async { health_service::repair_database(&state.pool(), &input.repair, &actor_operator_id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn recalculate_stock(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RecalculateStockInput,
+) -> Result<health_service::StockRecalcResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let apply = input.apply.unwrap_or(false);
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "slot_id": input.slot_id.clone(),
+    "apply": apply,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockRecalculate,
+    None,
+    Some(audit_request),
+    || async {
+      health_service::recalculate_stock(
+        &state.pool(),
+        input.item_id.clone(),
+        input.slot_id.clone(),
+        apply,
+        &actor_operator_id,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn get_remote_backup_config(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<RemoteBackupConfig, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  remote_backup_service::get_config(&state.pool()).await
+}
+
+#[tauri::command]
+pub async fn set_remote_backup_config(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RemoteBackupConfigPatch,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "kind": input.kind.clone(),
+    "endpoint": input.endpoint.clone(),
+    "bucket": input.bucket.clone(),
+    "prefix": input.prefix.clone(),
+    "secret": null,
+    "secret_changed": input.secret.as_deref().is_some_and(|secret| !secret.is_empty()),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RemoteBackupConfigUpdate,
     None,
     Some(audit_request),
-    || async { system_service::restore_db(&state.pool, &input.file_path).await },
+    || async { remote_backup_service::set_config(&state.pool(), &input).await },
   )
   .await
 }
 
+#[tauri::command]
+pub async fn generate_demo_data(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: GenerateDemoDataInput,
+) -> Result<demo_service::DemoDataResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let force = input.force.unwrap_or(false);
+  let audit_request = json!({
+    "txn_count": input.txn_count,
+    "force": force,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::DemoDataGenerate,
+    None,
+    Some(audit_request),
+    || async {
+      demo_service::generate_demo_data(&state.pool(), input.txn_count, force, &actor_operator_id).await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn get_backup_encryption(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<BackupEncryptionConfig, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  backup_encryption_service::get_config(&state.pool()).await
+}
+
+#[tauri::command]
+pub async fn set_backup_encryption(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: BackupEncryptionPatch,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  // 口令不写入审计
+  let audit_request = json!({
+    "enabled": input.enabled,
+    "passphrase_changed": input.passphrase.as_deref().is_some_and(|value| !value.is_empty()),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::BackupEncryptionUpdate,
+    None,
+    Some(audit_request),
+    || async { backup_encryption_service::set_config(&state.pool(), &input).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn encrypt_existing_backups(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<BackupEncryptResult, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::BackupEncryptExisting,
+    None,
+    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    || async { backup_encryption_service::encrypt_existing_backups(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn list_remote_backups(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Vec<RemoteObject>, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RemoteBackupList,
+    None,
+    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    || async { remote_backup_service::list_remote_backups(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn restore_remote_backup(
+  app_handle: AppHandle,
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RemoteRestoreInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "remote_name": input.name.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  let result = async {
+    let target = approval_service::restore_remote_target(&input.name);
+    let approval = approval_service::ensure_restore_approved(&state.pool(), &target, &actor_operator_id).await?;
+    let path = remote_backup_service::download_remote_backup(&state.pool(), &input.name).await?;
+    restore_database(&app_handle, &state, &path, input.passphrase.as_deref(), approval).await
+  }
+  .await;
+  command_guard::record_audit(&state.pool(), AuditAction::DbRestore, None, Some(audit_request), result).await
+}
+
 #[tauri::command]
 pub async fn export_items(
   state: State<'_, AppState>,
   actor_operator_id: String,
+  input: Option<ExportItemsInput>,
 ) -> Result<import_export_service::ExportResult, AppError> {
   command_guard::ensure_not_migrating(&state).await?;
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer"]).await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_export").await?;
+  let input = input.unwrap_or_default();
+  let audit_request = json!({
+    "keyword": input.keyword.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemExport,
     None,
-    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
-    || async { import_export_service::export_items(&state.pool).await },
+    Some(audit_request),
+    || async {
+      import_export_service::export_items(
+        &state.pool(),
+        input.keyword.clone(),
+        input.sort_by.clone(),
+        input.sort_dir.clone(),
+      )
+      .await
+    },
   )
   .await
 }
@@ -88,38 +504,142 @@ pub async fn export_items(
 
 #[tauri::command]
 pub async fn import_items(state: State<'_, AppState>, actor_operator_id: String, input: ImportInput) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "file_path": input.file_path.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemImport,
     None,
     Some(audit_request),
-    || async { import_export_service::import_items(&state.pool, &input.file_path).await },
+    || async { import_export_service::import_items(&state.pool(), &input.file_path).await },
   )
   .await
 }
 
 #[tauri::command]
 pub async fn import_txns(state: State<'_, AppState>, actor_operator_id: String, input: ImportInput) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "file_path": input.file_path.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::TxnImport,
     None,
     Some(audit_request),
-    || async { import_export_service::import_txns(&state.pool, &input.file_path).await },
+    || async { import_export_service::import_txns(&state.pool(), &input.file_path).await },
+  )
+  .await
+}
+
+/// 导入仓库结构（仓库、货架与库位），整个文件在同一事务内写入
+#[tauri::command]
+pub async fn import_structure(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ImportInput,
+) -> Result<import_export_service::StructureImportResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "file_path": input.file_path.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StructureImport,
+    None,
+    Some(audit_request),
+    || async { import_export_service::import_structure(&state.pool(), &input.file_path).await },
+  )
+  .await
+}
+
+/// 导入期初库存，生成 OPENING 流水并输出对账报告
+#[tauri::command]
+pub async fn import_opening_stock(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ImportOpeningStockInput,
+) -> Result<import_export_service::OpeningStockImportResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let force = input.force.unwrap_or(false);
+  let audit_request = json!({
+    "file_path": input.file_path.clone(),
+    "occurred_at": input.occurred_at,
+    "force": force,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OpeningStockImport,
+    None,
+    Some(audit_request),
+    || async {
+      import_export_service::import_opening_stock(
+        &state.pool(),
+        &input.file_path,
+        input.occurred_at,
+        force,
+        &actor_operator_id,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+/// 导出主数据包（仓库结构、物品、原因码与人员），用于新站点按现有配置初始化
+#[tauri::command]
+pub async fn export_master_bundle(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<import_export_service::ExportResult, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::MasterBundleExport,
+    None,
+    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    || async { master_bundle_service::export_master_bundle(&state.pool()).await },
+  )
+  .await
+}
+
+/// 导入主数据包：已存在的记录跳过，其余在同一事务内写入
+#[tauri::command]
+pub async fn import_master_bundle(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ImportMasterBundleInput,
+) -> Result<master_bundle_service::MasterBundleImportResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "file_path": input.file_path.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::MasterBundleImport,
+    None,
+    Some(audit_request),
+    || async {
+      master_bundle_service::import_master_bundle(&state.pool(), &input.file_path, input.initial_password.clone()).await
+    },
   )
   .await
 }
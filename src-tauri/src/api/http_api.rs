@@ -0,0 +1,264 @@
+// 本机 HTTP 接口：供外部脚本与后续 Web 客户端调用查询/新建接口
+//
+// 约定：
+// - 仅监听 127.0.0.1，端口与开关保存在 app_meta（http_api_enabled / http_api_port）
+// - 请求头携带 `Authorization: Bearer <令牌>`，令牌由管理员在设置页生成，仅保存其 SHA-256 摘要（http_api_token_hash）
+// - 接口以令牌归属人员（http_api_operator_id）的身份调用对应的 Tauri 命令，
+//   权限校验、仓库范围与审计记录与界面操作完全一致
+use axum::extract::{Query, Request, State as AxumState};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Extension, Json, Router};
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+use crate::api::{item_cmd, stock_cmd, txn_cmd, warehouse_cmd};
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::infra::crypto;
+use crate::services::{item_service, stock_service, system_service, txn_service, warehouse_service};
+use crate::state::AppState;
+
+/// 运行中的 HTTP 服务任务（启用状态或端口变化时重启）
+#[derive(Default)]
+pub struct HttpApiServer {
+  task: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// 令牌校验通过后的调用人
+#[derive(Debug, Clone)]
+struct ApiActor(String);
+
+/// 接口错误：按错误码映射 HTTP 状态，响应体与命令返回的错误结构一致
+struct ApiError(AppError);
+
+impl From<AppError> for ApiError {
+  fn from(err: AppError) -> Self {
+    Self(err)
+  }
+}
+
+impl IntoResponse for ApiError {
+  fn into_response(self) -> Response {
+    let status = match self.0.code {
+      ErrorCode::AuthFailed
+      | ErrorCode::AccountLocked
+      | ErrorCode::PwdChangeRequired
+      | ErrorCode::SessionExpired => {
+        StatusCode::UNAUTHORIZED
+      }
+      ErrorCode::Forbidden => StatusCode::FORBIDDEN,
+      ErrorCode::NotFound => StatusCode::NOT_FOUND,
+      ErrorCode::ValidationError | ErrorCode::InactiveResource | ErrorCode::InsufficientStock => {
+        StatusCode::UNPROCESSABLE_ENTITY
+      }
+      ErrorCode::Conflict => StatusCode::CONFLICT,
+      ErrorCode::ReadOnlyMode => StatusCode::LOCKED,
+      ErrorCode::DbBusy => StatusCode::SERVICE_UNAVAILABLE,
+      ErrorCode::DbError | ErrorCode::DbIncompatible | ErrorCode::IoError => {
+        StatusCode::INTERNAL_SERVER_ERROR
+      }
+    };
+    (status, Json(self.0)).into_response()
+  }
+}
+
+type ApiResult<T> = Result<Json<T>, ApiError>;
+
+#[derive(Debug, Serialize)]
+struct TxnCreated {
+  txn_no: String,
+}
+
+fn app_state(app: &AppHandle) -> Result<State<'_, AppState>, ApiError> {
+  app
+    .try_state::<AppState>()
+    .ok_or_else(|| ApiError(AppError::new(ErrorCode::DbBusy, "数据库尚未就绪")))
+}
+
+/// 按当前配置（重新）启动 HTTP 服务；未启用时仅停止已有服务
+pub async fn restart(app: &AppHandle) -> Result<(), AppError> {
+  let Some(server) = app.try_state::<HttpApiServer>() else {
+    return Ok(());
+  };
+  let mut task = server.task.lock().await;
+  if let Some(running) = task.take() {
+    // 中止任务会同时释放监听端口，便于同端口重新绑定
+    running.abort();
+    let _ = running.await;
+  }
+
+  let state = app_state(app).map_err(|err| err.0)?;
+  let config = system_service::http_api_config(&state.pool()).await?;
+  if !config.enabled {
+    return Ok(());
+  }
+  let listener = tokio::net::TcpListener::bind(("127.0.0.1", config.port))
+    .await
+    .map_err(|_| AppError::new(ErrorCode::IoError, format!("HTTP 接口端口 {} 无法监听", config.port)))?;
+  let router = router(app.clone());
+  *task = Some(tokio::spawn(async move {
+    let _ = axum::serve(listener, router).await;
+  }));
+  Ok(())
+}
+
+fn router(app: AppHandle) -> Router {
+  let api = Router::new()
+    .route("/warehouses", get(list_warehouses))
+    .route("/items", get(list_items))
+    .route("/stock/by-slot", get(list_stock_by_slot))
+    .route("/stock/by-item", get(list_stock_by_item))
+    .route("/txns", get(list_txns))
+    .route("/txns/inbound", post(create_inbound))
+    .route("/txns/outbound", post(create_outbound))
+    .route("/txns/move", post(create_move))
+    .route("/txns/count", post(create_count))
+    .route("/txns/reversal", post(reverse_txn))
+    .route_layer(middleware::from_fn_with_state(app.clone(), require_token));
+  Router::new()
+    .route("/health", get(health))
+    .nest("/api", api)
+    .with_state(app)
+}
+
+/// 校验 Bearer 令牌，并将令牌归属人员作为调用人传给后续处理
+async fn require_token(
+  AxumState(app): AxumState<AppHandle>,
+  mut request: Request,
+  next: Next,
+) -> Result<Response, ApiError> {
+  let state = app_state(&app)?;
+  let config = system_service::http_api_config(&state.pool()).await?;
+  let provided = request
+    .headers()
+    .get(header::AUTHORIZATION)
+    .and_then(|value| value.to_str().ok())
+    .and_then(|value| value.strip_prefix("Bearer "))
+    .map(|value| value.trim());
+  let authorized = match (config.token_hash.as_deref(), provided) {
+    (Some(expected), Some(provided)) => token_eq(expected, &crypto::hash_token(provided)),
+    _ => false,
+  };
+  if !config.enabled || !authorized {
+    return Err(ApiError(AppError::new(ErrorCode::AuthFailed, "访问令牌无效")));
+  }
+  let operator_id = config
+    .operator_id
+    .ok_or_else(|| ApiError(AppError::new(ErrorCode::AuthFailed, "访问令牌未绑定人员")))?;
+  request.extensions_mut().insert(ApiActor(operator_id));
+  Ok(next.run(request).await)
+}
+
+/// 定长比较，避免按前缀逐字节提前返回
+fn token_eq(expected: &str, provided: &str) -> bool {
+  let (expected, provided) = (expected.as_bytes(), provided.as_bytes());
+  expected.len() == provided.len()
+    && expected
+      .iter()
+      .zip(provided)
+      .fold(0_u8, |acc, (a, b)| acc | (a ^ b))
+      == 0
+}
+
+async fn health() -> &'static str {
+  "ok"
+}
+
+async fn list_warehouses(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Query(input): Query<warehouse_cmd::ListWarehouseQuery>,
+) -> ApiResult<warehouse_service::WarehouseListResult> {
+  let state = app_state(&app)?;
+  Ok(Json(warehouse_cmd::list_warehouses(state, actor.0, input).await?))
+}
+
+async fn list_items(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Query(query): Query<item_cmd::ListItemQuery>,
+) -> ApiResult<item_service::ItemListResult> {
+  let state = app_state(&app)?;
+  Ok(Json(item_cmd::list_items(state, actor.0, query).await?))
+}
+
+async fn list_stock_by_slot(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Query(input): Query<stock_cmd::StockQueryInput>,
+) -> ApiResult<stock_service::StockBySlotResult> {
+  let state = app_state(&app)?;
+  Ok(Json(stock_cmd::list_stock_by_slot(state, actor.0, input).await?))
+}
+
+async fn list_stock_by_item(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Query(input): Query<stock_cmd::StockQueryInput>,
+) -> ApiResult<stock_service::StockByItemResult> {
+  let state = app_state(&app)?;
+  Ok(Json(stock_cmd::list_stock_by_item(state, actor.0, input).await?))
+}
+
+async fn list_txns(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Query(input): Query<txn_cmd::TxnListInput>,
+) -> ApiResult<txn_service::TxnListResult> {
+  let state = app_state(&app)?;
+  Ok(Json(txn_cmd::list_txns(state, actor.0, input).await?))
+}
+
+async fn create_inbound(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Json(input): Json<txn_cmd::InboundInput>,
+) -> ApiResult<TxnCreated> {
+  let state = app_state(&app)?;
+  let txn_no = txn_cmd::create_inbound(state, actor.0, input).await?;
+  Ok(Json(TxnCreated { txn_no }))
+}
+
+async fn create_outbound(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Json(input): Json<txn_cmd::OutboundInput>,
+) -> ApiResult<TxnCreated> {
+  let state = app_state(&app)?;
+  let txn_no = txn_cmd::create_outbound(state, actor.0, input).await?;
+  Ok(Json(TxnCreated { txn_no }))
+}
+
+async fn create_move(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Json(input): Json<txn_cmd::MoveInput>,
+) -> ApiResult<TxnCreated> {
+  let state = app_state(&app)?;
+  let txn_no = txn_cmd::create_move(state, actor.0, input).await?;
+  Ok(Json(TxnCreated { txn_no }))
+}
+
+async fn create_count(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Json(input): Json<txn_cmd::CountInput>,
+) -> ApiResult<TxnCreated> {
+  let state = app_state(&app)?;
+  let txn_no = txn_cmd::create_count(state, actor.0, input).await?;
+  Ok(Json(TxnCreated { txn_no }))
+}
+
+async fn reverse_txn(
+  AxumState(app): AxumState<AppHandle>,
+  Extension(actor): Extension<ApiActor>,
+  Json(input): Json<txn_cmd::ReversalInput>,
+) -> ApiResult<TxnCreated> {
+  let state = app_state(&app)?;
+  let txn_no = txn_cmd::reverse_txn(state, actor.0, input).await?;
+  Ok(Json(TxnCreated { txn_no }))
+}
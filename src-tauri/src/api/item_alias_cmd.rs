@@ -0,0 +1,166 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::item_alias_repo::ItemAliasRow;
+use crate::services::item_alias_service::{self, ItemAliasInput, ScanResolution};
+use crate::services::permission_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ListItemAliasesInput {
+  pub item_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateItemAliasInput {
+  pub item_id: String,
+  #[serde(flatten)]
+  pub alias: ItemAliasInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateItemAliasInput {
+  pub id: String,
+  #[serde(flatten)]
+  pub alias: ItemAliasInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteItemAliasInput {
+  pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveScanInput {
+  pub code: String,
+}
+
+#[tauri::command]
+pub async fn list_item_aliases(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListItemAliasesInput,
+) -> Result<Vec<ItemAliasRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAliasList,
+    None,
+    Some(audit_request),
+    || async { item_alias_service::list_aliases(&state.pool(), &input.item_id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn create_item_alias(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: CreateItemAliasInput,
+) -> Result<ItemAliasRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "code": input.alias.code.clone(),
+    "kind": input.alias.kind.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAliasCreate,
+    None,
+    Some(audit_request),
+    || async { item_alias_service::create_alias(&state.pool(), &input.item_id, &input.alias).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn update_item_alias(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateItemAliasInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "code": input.alias.code.clone(),
+    "kind": input.alias.kind.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAliasUpdate,
+    None,
+    Some(audit_request),
+    || async { item_alias_service::update_alias(&state.pool(), &input.id, &input.alias).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_item_alias(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DeleteItemAliasInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAliasDelete,
+    None,
+    Some(audit_request),
+    || async { item_alias_service::delete_alias(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+/// 解析扫码内容为物品（编码/别名/旧编码）或库位
+#[tauri::command]
+pub async fn resolve_scan(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ResolveScanInput,
+) -> Result<ScanResolution, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let audit_request = json!({
+    "code": input.code.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ScanResolve,
+    None,
+    Some(audit_request),
+    || async { item_alias_service::resolve_scan(&state.pool(), &input.code).await },
+  )
+  .await
+}
@@ -0,0 +1,130 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::item_attribute_repo::ItemAttributeDefRow;
+use crate::services::item_attribute_service::{self, ItemAttributeDefInput};
+use crate::services::permission_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ListItemAttributeDefsInput {
+  // 是否包含已停用的属性（属性管理页使用）
+  pub include_disabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateItemAttributeDefInput {
+  pub id: String,
+  #[serde(flatten)]
+  pub def: ItemAttributeDefInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteItemAttributeDefInput {
+  pub id: String,
+}
+
+fn def_audit_request(def: &ItemAttributeDefInput, actor_operator_id: &str) -> serde_json::Value {
+  json!({
+    "name": def.name.clone(),
+    "data_type": def.data_type.clone(),
+    "required": def.required,
+    "sort_no": def.sort_no,
+    "status": def.status.clone(),
+    "actor_operator_id": actor_operator_id
+  })
+}
+
+#[tauri::command]
+pub async fn list_item_attribute_defs(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListItemAttributeDefsInput,
+) -> Result<Vec<ItemAttributeDefRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let include_disabled = input.include_disabled.unwrap_or(false);
+  let audit_request = json!({
+    "include_disabled": include_disabled,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAttributeDefList,
+    None,
+    Some(audit_request),
+    || async { item_attribute_service::list_defs(&state.pool(), !include_disabled).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn create_item_attribute_def(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ItemAttributeDefInput,
+) -> Result<ItemAttributeDefRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAttributeDefCreate,
+    None,
+    Some(def_audit_request(&input, &actor_operator_id)),
+    || async { item_attribute_service::create_def(&state.pool(), &input).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn update_item_attribute_def(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateItemAttributeDefInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let mut audit_request = def_audit_request(&input.def, &actor_operator_id);
+  audit_request["id"] = json!(input.id.clone());
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAttributeDefUpdate,
+    None,
+    Some(audit_request),
+    || async { item_attribute_service::update_def(&state.pool(), &input.id, &input.def).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_item_attribute_def(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DeleteItemAttributeDefInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemAttributeDefDelete,
+    None,
+    Some(audit_request),
+    || async { item_attribute_service::delete_def(&state.pool(), &input.id).await },
+  )
+  .await
+}
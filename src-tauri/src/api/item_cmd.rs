@@ -5,7 +5,9 @@ use tauri::State;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
-use crate::services::{item_service, permission_service};
+use crate::repo::item_repo::ItemPatch;
+use crate::services::item_attribute_service::ItemAttributes;
+use crate::services::{item_service, permission_service, undo_service};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -13,6 +15,9 @@ pub struct ListItemQuery {
   pub keyword: Option<String>,
   pub page_index: i64,
   pub page_size: i64,
+  // 排序字段（逗号分隔）与方向 asc/desc，见 item_repo 白名单
+  pub sort_by: Option<String>,
+  pub sort_dir: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,7 +27,15 @@ pub struct CreateItemInput {
   pub model: Option<String>,
   pub spec: Option<String>,
   pub uom: Option<String>,
+  pub unit_cost: Option<f64>,
+  // 成本币种（如 CNY）
+  pub currency: Option<String>,
+  // 数量小数位数（0-3），默认 0 即整数
+  pub qty_precision: Option<i64>,
   pub remark: Option<String>,
+  // 自定义属性取值（属性 ID → 值）
+  #[serde(default)]
+  pub attributes: ItemAttributes,
 }
 
 #[derive(Debug, Deserialize)]
@@ -32,7 +45,15 @@ pub struct UpdateItemInput {
   pub model: Option<String>,
   pub spec: Option<String>,
   pub uom: Option<String>,
+  pub unit_cost: Option<f64>,
+  // 成本币种（如 CNY）
+  pub currency: Option<String>,
+  // 数量小数位数（0-3），未提供时保持不变；修改后按新精度换算已有数量
+  pub qty_precision: Option<i64>,
   pub remark: Option<String>,
+  // 自定义属性取值（属性 ID → 值），仅更新提供的属性，空值表示清除
+  #[serde(default)]
+  pub attributes: ItemAttributes,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,27 +62,67 @@ pub struct UpdateItemStatusInput {
   pub status: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetItemsStatusInput {
+  pub ids: Vec<String>,
+  pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkUpdateItemsInput {
+  pub ids: Vec<String>,
+  // 以下字段未提供时保持不变；文本字段为空串时清空
+  pub model: Option<String>,
+  pub spec: Option<String>,
+  pub uom: Option<String>,
+  pub unit_cost: Option<f64>,
+  pub currency: Option<String>,
+  pub remark: Option<String>,
+  pub status: Option<String>,
+}
+
 #[tauri::command]
 pub async fn list_items(
   state: State<'_, AppState>,
   actor_operator_id: String,
   query: ListItemQuery,
 ) -> Result<item_service::ItemListResult, AppError> {
-  let ListItemQuery { keyword, page_index, page_size } = query;
+  let ListItemQuery {
+    keyword,
+    page_index,
+    page_size,
+    sort_by,
+    sort_dir,
+  } = query;
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
   .await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
   let audit_request = json!({ "keyword": keyword.clone(), "actor_operator_id": actor_operator_id.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemList,
     None,
     Some(audit_request),
     || async {
-      item_service::list_items(&state.pool, keyword.clone(), page_index, page_size).await
+      let mut result = item_service::list_items(
+        &state.pool(),
+        keyword.clone(),
+        page_index,
+        page_size,
+        sort_by.clone(),
+        sort_dir.clone(),
+      )
+      .await?;
+      if !show_costs {
+        for entry in result.items.iter_mut() {
+          entry.item.unit_cost = None;
+        }
+      }
+      Ok(result)
     },
   )
   .await
@@ -73,8 +134,8 @@ pub async fn create_item(
   actor_operator_id: String,
   input: CreateItemInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper"]).await?;
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
   let _guard = state.write_lock.lock().await;
   let audit_request = json!({
     "item_code": input.item_code.clone(),
@@ -82,23 +143,31 @@ pub async fn create_item(
     "model": input.model.clone(),
     "spec": input.spec.clone(),
     "uom": input.uom.clone(),
+    "unit_cost": input.unit_cost,
+    "currency": input.currency.clone(),
+    "qty_precision": input.qty_precision,
     "remark": input.remark.clone(),
+    "attributes": input.attributes.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemCreate,
     None,
     Some(audit_request),
     || async {
       item_service::create_item(
-        &state.pool,
+        &state.pool(),
         &input.item_code,
         &input.name,
         input.model.clone(),
         input.spec.clone(),
         input.uom.clone(),
+        input.unit_cost,
+        input.currency.clone(),
+        input.qty_precision.unwrap_or(0),
         input.remark.clone(),
+        &input.attributes,
       )
       .await
     },
@@ -112,8 +181,8 @@ pub async fn update_item(
   actor_operator_id: String,
   input: UpdateItemInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper"]).await?;
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
   let _guard = state.write_lock.lock().await;
   let audit_request = json!({
     "id": input.id.clone(),
@@ -121,25 +190,35 @@ pub async fn update_item(
     "model": input.model.clone(),
     "spec": input.spec.clone(),
     "uom": input.uom.clone(),
+    "unit_cost": input.unit_cost,
+    "currency": input.currency.clone(),
+    "qty_precision": input.qty_precision,
     "remark": input.remark.clone(),
+    "attributes": input.attributes.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemUpdate,
     None,
     Some(audit_request),
     || async {
+      let before = undo_service::capture_items(&state.pool(), std::slice::from_ref(&input.id)).await?;
       item_service::update_item(
-        &state.pool,
+        &state.pool(),
         &input.id,
         &input.name,
         input.model.clone(),
         input.spec.clone(),
         input.uom.clone(),
+        input.unit_cost,
+        input.currency.clone(),
+        input.qty_precision,
         input.remark.clone(),
+        &input.attributes,
       )
-      .await
+      .await?;
+      undo_service::record_items(&state.pool(), &actor_operator_id, AuditAction::ItemUpdate, before).await
     },
   )
   .await
@@ -151,8 +230,8 @@ pub async fn set_item_status(
   actor_operator_id: String,
   input: UpdateItemStatusInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper"]).await?;
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
   let _guard = state.write_lock.lock().await;
   let audit_request = json!({
     "id": input.id.clone(),
@@ -160,11 +239,184 @@ pub async fn set_item_status(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemStatus,
     None,
     Some(audit_request),
-    || async { item_service::set_item_status(&state.pool, &input.id, &input.status).await },
+    || async {
+      let before = undo_service::capture_items(&state.pool(), std::slice::from_ref(&input.id)).await?;
+      item_service::set_item_status(&state.pool(), &input.id, &input.status).await?;
+      undo_service::record_items(&state.pool(), &actor_operator_id, AuditAction::ItemStatus, before).await
+    },
+  )
+  .await
+}
+
+/// 批量启用/停用物品，单条审计记录汇总物品 ID
+#[tauri::command]
+pub async fn set_items_status(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetItemsStatusInput,
+) -> Result<item_service::BulkItemResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "ids": input.ids.clone(),
+    "count": input.ids.len(),
+    "status": input.status.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemBulkStatus,
+    None,
+    Some(audit_request),
+    || async {
+      let before = undo_service::capture_items(&state.pool(), &input.ids).await?;
+      let result = item_service::set_items_status(&state.pool(), &input.ids, &input.status).await?;
+      undo_service::record_items(&state.pool(), &actor_operator_id, AuditAction::ItemBulkStatus, before).await?;
+      Ok(result)
+    },
+  )
+  .await
+}
+
+/// 批量编辑物品公共字段，在同一事务内完成，单条审计记录汇总物品 ID
+#[tauri::command]
+pub async fn bulk_update_items(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: BulkUpdateItemsInput,
+) -> Result<item_service::BulkItemResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "ids": input.ids.clone(),
+    "count": input.ids.len(),
+    "model": input.model.clone(),
+    "spec": input.spec.clone(),
+    "uom": input.uom.clone(),
+    "unit_cost": input.unit_cost,
+    "currency": input.currency.clone(),
+    "remark": input.remark.clone(),
+    "status": input.status.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemBulkUpdate,
+    None,
+    Some(audit_request),
+    || async {
+      let patch = ItemPatch {
+        model: input.model.clone(),
+        spec: input.spec.clone(),
+        uom: input.uom.clone(),
+        unit_cost: input.unit_cost,
+        currency: input.currency.clone(),
+        remark: input.remark.clone(),
+        status: input.status.clone(),
+      };
+      let before = undo_service::capture_items(&state.pool(), &input.ids).await?;
+      let result = item_service::bulk_update_items(&state.pool(), &input.ids, patch).await?;
+      undo_service::record_items(&state.pool(), &actor_operator_id, AuditAction::ItemBulkUpdate, before).await?;
+      Ok(result)
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeItemCodeInput {
+  pub id: String,
+  pub new_code: String,
+}
+
+/// 修改物品编码，审计记录新旧编码
+#[tauri::command]
+pub async fn change_item_code(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ChangeItemCodeInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let _guard = state.write_lock.lock().await;
+  let old_code = crate::repo::item_repo::get_item_by_id(&state.pool(), &input.id)
+    .await?
+    .map(|item| item.item_code);
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "old_code": old_code,
+    "new_code": input.new_code.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemCodeChange,
+    None,
+    Some(audit_request),
+    || async { item_service::change_item_code(&state.pool(), &input.id, &input.new_code, &actor_operator_id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn list_item_code_history(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: GetItemInput,
+) -> Result<Vec<crate::repo::item_repo::ItemCodeHistoryRow>, AppError> {
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer"]).await?;
+  let audit_request = json!({ "id": input.id.clone(), "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemList,
+    None,
+    Some(audit_request),
+    || async { item_service::list_item_code_history(&state.pool(), input.id.as_deref().unwrap_or_default()).await },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeItemsInput {
+  // 被合并（停用）的重复物品
+  pub source_id: String,
+  // 保留的物品
+  pub target_id: String,
+}
+
+/// 合并重复物品：库存、流水、借用与图片改挂到目标物品，审计记录两端编码与改挂数量
+#[tauri::command]
+pub async fn merge_items(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: MergeItemsInput,
+) -> Result<item_service::ItemMergePreview, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let _guard = state.write_lock.lock().await;
+  // 预览失败时仍记录审计，便于追查
+  let preview = item_service::merge_preview(&state.pool(), &input.source_id, &input.target_id).await.ok();
+  let audit_request = json!({
+    "id": input.target_id.clone(),
+    "source_id": input.source_id.clone(),
+    "target_id": input.target_id.clone(),
+    "source_code": preview.as_ref().map(|value| value.source_code.clone()),
+    "target_code": preview.as_ref().map(|value| value.target_code.clone()),
+    "references": preview.as_ref().map(|value| json!(value.references)),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemMerge,
+    None,
+    Some(audit_request),
+    || async { item_service::merge_items(&state.pool(), &input.source_id, &input.target_id).await },
   )
   .await
 }
@@ -181,21 +433,59 @@ pub async fn get_item(
   actor_operator_id: String,
   input: GetItemInput,
 ) -> Result<Option<crate::repo::item_repo::ItemRow>, AppError> {
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
   let audit_request = json!({ "id": input.id.clone(), "code": input.code.clone(), "actor_operator_id": actor_operator_id.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::ItemList,
     None,
     Some(audit_request),
     || async {
-      if let Some(id) = input.id {
-        crate::repo::item_repo::get_item_by_id(&state.pool, &id).await
-      } else if let Some(code) = input.code {
-        crate::repo::item_repo::get_item_by_code(&state.pool, &code).await
+      let item = if let Some(id) = &input.id {
+        crate::repo::item_repo::get_item_by_id(&state.pool(), id).await?
+      } else if let Some(code) = &input.code {
+        crate::repo::item_repo::get_item_by_code(&state.pool(), code).await?
       } else {
-        Ok(None)
+        None
+      };
+      Ok(item.map(|mut item| {
+        if !show_costs {
+          item.unit_cost = None;
+        }
+        item
+      }))
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GetItemDetailInput {
+  pub id: String,
+}
+
+#[tauri::command]
+pub async fn get_item_detail(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: GetItemDetailInput,
+) -> Result<item_service::ItemDetail, AppError> {
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
+  let allowed_warehouse_ids = permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({ "id": input.id.clone(), "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ItemDetailView,
+    None,
+    Some(audit_request),
+    || async {
+      let mut detail = item_service::get_item_detail(&state.pool(), &input.id, allowed_warehouse_ids.clone()).await?;
+      if !show_costs {
+        detail.item.item.unit_cost = None;
       }
+      Ok(detail)
     },
   )
   .await
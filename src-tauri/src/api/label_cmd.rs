@@ -0,0 +1,195 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::label_template_repo::LabelTemplateRow;
+use crate::services::label_service::{self, LabelPrintResult, LabelTemplateInput};
+use crate::services::permission_service;
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ListLabelTemplatesInput {
+  // slot / item，为空返回全部
+  pub target: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateLabelTemplateInput {
+  pub id: String,
+  #[serde(flatten)]
+  pub template: LabelTemplateInput,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteLabelTemplateInput {
+  pub id: String,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateLabelsInput {
+  // slot / item
+  pub target: String,
+  // 为空时使用默认模板
+  pub template_id: Option<String>,
+  // 库位标签：整个货架
+  pub rack_id: Option<String>,
+  // 库位编码或物品编码
+  pub codes: Option<Vec<String>>,
+  // actor_operator_id provided as top-level arg
+}
+
+fn template_audit(template: &LabelTemplateInput, actor_operator_id: &str) -> serde_json::Value {
+  json!({
+    "name": template.name.clone(),
+    "target": template.target.clone(),
+    "width_mm": template.width_mm,
+    "height_mm": template.height_mm,
+    "fields": template.fields.clone(),
+    "barcode_type": template.barcode_type.clone(),
+    "font_size": template.font_size,
+    "is_default": template.is_default,
+    "actor_operator_id": actor_operator_id
+  })
+}
+
+#[tauri::command]
+pub async fn list_label_templates(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListLabelTemplatesInput,
+) -> Result<Vec<LabelTemplateRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let audit_request = json!({
+    "target": input.target.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::LabelTemplateList,
+    None,
+    Some(audit_request),
+    || async { label_service::list_templates(&state.pool(), input.target.as_deref()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn create_label_template(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: LabelTemplateInput,
+) -> Result<LabelTemplateRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let audit_request = template_audit(&input, &actor_operator_id);
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::LabelTemplateCreate,
+    None,
+    Some(audit_request),
+    || async { label_service::create_template(&state.pool(), &input).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn update_label_template(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateLabelTemplateInput,
+) -> Result<LabelTemplateRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let mut audit_request = template_audit(&input.template, &actor_operator_id);
+  audit_request["id"] = json!(input.id.clone());
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::LabelTemplateUpdate,
+    None,
+    Some(audit_request),
+    || async { label_service::update_template(&state.pool(), &input.id, &input.template).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_label_template(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DeleteLabelTemplateInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::LabelTemplateDelete,
+    None,
+    Some(audit_request),
+    || async { label_service::delete_template(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn generate_labels(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: GenerateLabelsInput,
+) -> Result<LabelPrintResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let codes = input.codes.clone().unwrap_or_default();
+  let audit_request = json!({
+    "target": input.target.clone(),
+    "template_id": input.template_id.clone(),
+    "rack_id": input.rack_id.clone(),
+    "codes": codes.len(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::LabelPrint,
+    None,
+    Some(audit_request),
+    || async {
+      label_service::generate_labels(
+        &state.pool(),
+        &input.target,
+        input.template_id.as_deref(),
+        input.rack_id.clone(),
+        &codes,
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
@@ -0,0 +1,201 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::services::{item_service, loan_service, permission_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateLoanInput {
+    pub item_id: String,
+    pub slot_id: String,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub qty: f64,
+    // 借用人（可为非系统人员，如外协、司机）
+    pub borrower: String,
+    // 应还时间（秒级时间戳），不填表示不设期限
+    pub due_at: Option<i64>,
+    pub occurred_at: i64,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReturnLoanInput {
+    pub id: String,
+    // 归还库位，默认借出库位
+    pub slot_id: Option<String>,
+    pub occurred_at: i64,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenLoanListInput {
+    pub keyword: Option<String>,
+    pub item_id: Option<String>,
+    // 仅返回已逾期记录
+    pub overdue_only: Option<bool>,
+    pub page_index: Option<i64>,
+    pub page_size: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn create_loan(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: CreateLoanInput,
+) -> Result<loan_service::LoanCreated, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.slot_id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "item_id": input.item_id.clone(),
+      "slot_id": input.slot_id.clone(),
+      "qty": input.qty,
+      "borrower": input.borrower.clone(),
+      "due_at": input.due_at,
+      "occurred_at": input.occurred_at,
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "note": input.note.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::LoanCreate,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+            loan_service::create_loan(
+                &state.pool(),
+                &input.item_id,
+                &input.slot_id,
+                qty,
+                &input.borrower,
+                input.due_at,
+                input.occurred_at,
+                &business_operator_id,
+                input.note.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn return_loan(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: ReturnLoanInput,
+) -> Result<String, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    // 借出库位与归还库位均需在可访问范围内
+    let loan = loan_service::get_open_loan(&state.pool(), &input.id).await?;
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &loan.slot_id).await?;
+    if let Some(slot_id) = input.slot_id.as_deref().filter(|id| !id.trim().is_empty()) {
+        permission_service::require_slot_access(&state.pool(), &actor_operator_id, slot_id.trim()).await?;
+    }
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "id": input.id.clone(),
+      "loan_no": loan.loan_no.clone(),
+      "slot_id": input.slot_id.clone(),
+      "occurred_at": input.occurred_at,
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "note": input.note.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::LoanReturn,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            loan_service::return_loan(
+                &state.pool(),
+                &input.id,
+                input.slot_id.clone(),
+                input.occurred_at,
+                &business_operator_id,
+                input.note.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_open_loans(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: Option<OpenLoanListInput>,
+) -> Result<loan_service::OpenLoanListResult, AppError> {
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "viewer", "member"],
+    )
+    .await?;
+    let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+    let input = input.unwrap_or(OpenLoanListInput {
+        keyword: None,
+        item_id: None,
+        overdue_only: None,
+        page_index: None,
+        page_size: None,
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::LoanList,
+        None,
+        Some(json!({
+          "actor_operator_id": actor_operator_id.clone(),
+          "keyword": input.keyword.clone(),
+          "item_id": input.item_id.clone(),
+          "overdue_only": input.overdue_only
+        })),
+        || async {
+            loan_service::list_open_loans(
+                &state.pool(),
+                input.overdue_only.unwrap_or(false),
+                input.keyword.clone(),
+                input.item_id.clone(),
+                allowed_warehouse_ids.clone(),
+                input.page_index.unwrap_or(1),
+                input.page_size.unwrap_or(20),
+            )
+            .await
+        },
+    )
+    .await
+}
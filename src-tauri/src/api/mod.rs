@@ -1,15 +1,34 @@
 pub mod auth_cmd;
 pub mod app_cmd;
+pub mod approval_cmd;
+pub mod asn_cmd;
 pub mod audit_cmd;
 pub mod command_guard;
+pub mod count_cmd;
+pub mod cycle_count_cmd;
 pub mod dashboard_cmd;
 pub mod data_cmd;
+pub mod http_api;
 pub mod item_cmd;
+pub mod item_alias_cmd;
+pub mod item_attribute_cmd;
+pub mod label_cmd;
+pub mod loan_cmd;
+pub mod notification_cmd;
 pub mod operator_cmd;
+pub mod outbound_pick_cmd;
 pub mod paging;
 pub mod photo_cmd;
+pub mod pick_list_cmd;
 pub mod rack_cmd;
+pub mod reason_code_cmd;
+pub mod report_cmd;
+pub mod report_schedule_cmd;
+pub mod session_cmd;
 pub mod stock_cmd;
+pub mod stock_hold_cmd;
+pub mod stock_threshold_cmd;
 pub mod system_cmd;
 pub mod txn_cmd;
+pub mod undo_cmd;
 pub mod warehouse_cmd;
@@ -0,0 +1,73 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::{AppHandle, Emitter, State};
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::services::{notification_service, permission_service};
+use crate::state::AppState;
+
+/// 新通知事件名（无负载，前端收到后重新拉取通知列表与未读数）
+pub const NOTIFICATION_EVENT: &str = "notification";
+
+#[derive(Debug, Deserialize)]
+pub struct ListNotificationsInput {
+  pub unread_only: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MarkNotificationsReadInput {
+  // 为空时标记全部未读通知
+  pub ids: Option<Vec<String>>,
+}
+
+/// 推送新通知事件
+pub fn emit_new(handle: &AppHandle) {
+  let _ = handle.emit(NOTIFICATION_EVENT, ());
+}
+
+/// 通知列表（最近 200 条）与未读数
+#[tauri::command]
+pub async fn list_notifications(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListNotificationsInput,
+) -> Result<notification_service::NotificationListResult, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  notification_service::list_notifications(&state.pool(), input.unread_only.unwrap_or(false)).await
+}
+
+/// 标记通知已读，返回更新条数
+#[tauri::command]
+pub async fn mark_notifications_read(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: MarkNotificationsReadInput,
+) -> Result<u64, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let audit_request = json!({
+    "ids": input.ids.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::NotificationMarkRead,
+    None,
+    Some(audit_request),
+    || async { notification_service::mark_read(&state.pool(), input.ids.clone()).await },
+  )
+  .await
+}
@@ -5,7 +5,8 @@ use tauri::State;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
-use crate::services::{operator_service, permission_service};
+use crate::services::operator_service::{self, ResetCodeIssued};
+use crate::services::permission_service;
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -49,6 +50,32 @@ pub struct ResetOperatorPasswordInput {
   // actor_operator_id provided as top-level arg
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UnlockOperatorInput {
+  pub id: String,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueResetCodeInput {
+  pub id: String,
+  // 有效期（分钟），为空使用默认 30 分钟
+  pub ttl_minutes: Option<i64>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OperatorWarehousesInput {
+  pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetOperatorWarehousesInput {
+  pub id: String,
+  pub warehouse_ids: Vec<String>,
+  // actor_operator_id provided as top-level arg
+}
+
 #[tauri::command]
 pub async fn list_operators(
   state: State<'_, AppState>,
@@ -60,15 +87,15 @@ pub async fn list_operators(
     "status": status.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::OperatorList,
     None,
     Some(audit_request),
     || async {
       operator_service::list_operators(
-        &state.pool,
+        &state.pool(),
         query.keyword.clone(),
         status.clone(),
         query.page_index,
@@ -86,10 +113,10 @@ pub async fn create_operator(
   actor_operator_id: String,
   input: CreateOperatorInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   // 写锁保护写操作
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "username": input.username.clone(),
     "display_name": input.display_name.clone(),
@@ -99,13 +126,13 @@ pub async fn create_operator(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::OperatorCreate,
     None,
     Some(audit_request),
     || async {
       operator_service::create_operator(
-        &state.pool,
+        &state.pool(),
         &input.username,
         &input.display_name,
         input.role.clone(),
@@ -124,10 +151,10 @@ pub async fn update_operator(
   actor_operator_id: String,
   input: UpdateOperatorInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   // 写锁保护写操作
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "display_name": input.display_name.clone(),
@@ -135,13 +162,13 @@ pub async fn update_operator(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::OperatorUpdate,
     None,
     Some(audit_request),
     || async {
       operator_service::update_operator(
-        &state.pool,
+        &state.pool(),
         &input.id,
         &input.display_name,
         input.role.clone(),
@@ -158,22 +185,22 @@ pub async fn set_operator_status(
   actor_operator_id: String,
   input: UpdateOperatorStatusInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   // 写锁保护写操作
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "status": input.status.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::OperatorStatus,
     None,
     Some(audit_request),
     || async {
-      operator_service::set_operator_status(&state.pool, &input.id, &input.status).await
+      operator_service::set_operator_status(&state.pool(), &input.id, &input.status).await
     },
   )
   .await
@@ -185,23 +212,23 @@ pub async fn reset_operator_password(
   actor_operator_id: String,
   input: ResetOperatorPasswordInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   // 写锁保护写操作
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "new_password": null,
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::AuthResetPassword,
     None,
     Some(audit_request),
     || async {
       operator_service::reset_operator_password(
-        &state.pool,
+        &state.pool(),
         &input.id,
         &input.new_password,
       )
@@ -211,6 +238,302 @@ pub async fn reset_operator_password(
   .await
 }
 
+#[tauri::command]
+pub async fn issue_reset_code(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: IssueResetCodeInput,
+) -> Result<ResetCodeIssued, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "ttl_minutes": input.ttl_minutes,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuthIssueResetCode,
+    None,
+    Some(audit_request),
+    || async {
+      operator_service::issue_reset_code(
+        &state.pool(),
+        &input.id,
+        &actor_operator_id,
+        input.ttl_minutes,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn unlock_operator(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UnlockOperatorInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::AuthUnlock,
+    None,
+    Some(audit_request),
+    || async { operator_service::unlock_operator(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn get_operator_warehouses(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: OperatorWarehousesInput,
+) -> Result<Vec<String>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorList,
+    None,
+    Some(audit_request),
+    || async { operator_service::get_operator_warehouses(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn set_operator_warehouses(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetOperatorWarehousesInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "warehouse_ids": input.warehouse_ids.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorWarehouseUpdate,
+    None,
+    Some(audit_request),
+    || async {
+      operator_service::set_operator_warehouses(&state.pool(), &input.id, &input.warehouse_ids).await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoleAssignmentInput {
+  pub id: String,
+  pub role: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PreviewRbacEnableInput {
+  #[serde(default)]
+  pub assignments: Vec<RoleAssignmentInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BulkSetOperatorRolesInput {
+  pub assignments: Vec<RoleAssignmentInput>,
+  // 为 true 时在同一事务内开启 RBAC
+  pub enable_rbac: Option<bool>,
+  // actor_operator_id provided as top-level arg
+}
+
+fn to_role_assignments(assignments: &[RoleAssignmentInput]) -> Vec<operator_service::RoleAssignment> {
+  assignments
+    .iter()
+    .map(|assignment| operator_service::RoleAssignment {
+      id: assignment.id.trim().to_string(),
+      role: assignment.role.trim().to_string(),
+    })
+    .collect()
+}
+
+fn role_assignments_json(assignments: &[RoleAssignmentInput]) -> serde_json::Value {
+  assignments
+    .iter()
+    .map(|assignment| json!({ "id": assignment.id.clone(), "role": assignment.role.clone() }))
+    .collect()
+}
+
+/// RBAC 启用向导：预览调整角色后的权限与阻断项
+#[tauri::command]
+pub async fn preview_rbac_enable(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: Option<PreviewRbacEnableInput>,
+) -> Result<operator_service::RbacEnablePreview, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let assignments = input.map(|input| input.assignments).unwrap_or_default();
+  let audit_request = json!({
+    "assignments": role_assignments_json(&assignments),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  let assignments = to_role_assignments(&assignments);
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorRolePreview,
+    None,
+    Some(audit_request),
+    || async {
+      operator_service::preview_rbac_enable(&state.pool(), &actor_operator_id, &assignments).await
+    },
+  )
+  .await
+}
+
+/// 批量调整人员角色，可选同时开启 RBAC
+#[tauri::command]
+pub async fn bulk_set_operator_roles(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: BulkSetOperatorRolesInput,
+) -> Result<operator_service::RbacEnablePreview, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let enable_rbac = input.enable_rbac.unwrap_or(false);
+  let audit_request = json!({
+    "assignments": role_assignments_json(&input.assignments),
+    "enable_rbac": enable_rbac,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  let assignments = to_role_assignments(&input.assignments);
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorRoleBulkUpdate,
+    None,
+    Some(audit_request),
+    || async {
+      operator_service::bulk_set_operator_roles(
+        &state.pool(),
+        &actor_operator_id,
+        &assignments,
+        enable_rbac,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SimulatePermissionsInput {
+  pub operator_id: String,
+}
+
+/// 权限模拟：列出人员在当前 RBAC 配置下可/不可执行的操作及原因
+#[tauri::command]
+pub async fn simulate_permissions(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SimulatePermissionsInput,
+) -> Result<permission_service::PermissionSimulation, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.operator_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorPermissionSimulate,
+    None,
+    Some(audit_request),
+    || async { permission_service::simulate_permissions(&state.pool(), &input.operator_id).await },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetRoleCapabilitiesInput {
+  pub role: String,
+  // 未传的能力保持不变
+  pub can_export: Option<bool>,
+  pub can_view_costs: Option<bool>,
+  pub can_view_audit: Option<bool>,
+}
+
+/// 角色能力开关：导出、查看成本、查看审计日志
+#[tauri::command]
+pub async fn list_role_capabilities(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Vec<permission_service::RoleCapabilities>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({ "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RoleCapabilityList,
+    None,
+    Some(audit_request),
+    || async { permission_service::list_role_capabilities(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn set_role_capabilities(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetRoleCapabilitiesInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "role": input.role.clone(),
+    "can_export": input.can_export,
+    "can_view_costs": input.can_view_costs,
+    "can_view_audit": input.can_view_audit,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RoleCapabilityUpdate,
+    None,
+    Some(audit_request),
+    || async {
+      permission_service::set_role_capabilities(
+        &state.pool(),
+        &input.role,
+        &[
+          ("can_export", input.can_export),
+          ("can_view_costs", input.can_view_costs),
+          ("can_view_audit", input.can_view_audit),
+        ],
+      )
+      .await
+    },
+  )
+  .await
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GetOperatorInput {
   pub id: String,
@@ -224,7 +547,7 @@ pub async fn get_operator(
 ) -> Result<Option<crate::repo::operator_repo::OperatorRow>, AppError> {
   // 允许常规角色读取（供选择器使用）
   crate::services::permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
@@ -232,11 +555,67 @@ pub async fn get_operator(
 
   let audit_request = json!({ "id": input.id.clone(), "actor_operator_id": actor_operator_id.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::OperatorList,
     None,
     Some(audit_request),
-    || async { crate::repo::operator_repo::get_operator_by_id(&state.pool, &input.id).await },
+    || async { crate::repo::operator_repo::get_operator_by_id(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn get_my_profile(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<operator_service::MyProfile, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let audit_request = json!({ "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorProfileView,
+    Some(actor_operator_id.clone()),
+    Some(audit_request),
+    || async { operator_service::get_my_profile(&state.pool(), &actor_operator_id).await },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateMyProfileInput {
+  pub display_name: String,
+}
+
+#[tauri::command]
+pub async fn update_my_profile(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateMyProfileInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let audit_request = json!({
+    "display_name": input.display_name.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::OperatorProfileUpdate,
+    Some(actor_operator_id.clone()),
+    Some(audit_request),
+    || async { operator_service::update_my_profile(&state.pool(), &actor_operator_id, &input.display_name).await },
   )
   .await
 }
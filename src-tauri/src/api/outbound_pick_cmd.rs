@@ -0,0 +1,211 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::outbound_pick_repo::OutboundPickListRow;
+use crate::services::{item_service, outbound_pick_service, permission_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePickInput {
+    pub item_id: String,
+    pub slot_id: String,
+    // 计划数量（显示数量），可含小数（位数不超过物品 qty_precision）
+    pub planned_qty: f64,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConfirmPickInput {
+    pub id: String,
+    // 实拣数量（显示数量），不超过计划数量
+    pub actual_qty: f64,
+    // 实拣少于计划时必填
+    pub short_reason: Option<String>,
+    // 短拣时自动生成 ADJUST 流水冲减账面库存
+    pub auto_adjust: Option<bool>,
+    pub occurred_at: i64,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CancelPickInput {
+    pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PickListInput {
+    // pending / confirmed / cancelled，不填返回全部
+    pub status: Option<String>,
+}
+
+#[tauri::command]
+pub async fn create_outbound_pick(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: CreatePickInput,
+) -> Result<outbound_pick_service::PickCreated, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.slot_id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "item_id": input.item_id.clone(),
+      "slot_id": input.slot_id.clone(),
+      "planned_qty": input.planned_qty,
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "note": input.note.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::OutboundPickCreate,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            let planned_qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.planned_qty).await?;
+            outbound_pick_service::create_pick(
+                &state.pool(),
+                &input.item_id,
+                &input.slot_id,
+                planned_qty,
+                &business_operator_id,
+                input.note.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn confirm_outbound_pick(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: ConfirmPickInput,
+) -> Result<outbound_pick_service::PickConfirmed, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    let pick = outbound_pick_service::get_pending_pick(&state.pool(), &input.id).await?;
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &pick.slot_id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "id": input.id.clone(),
+      "pick_no": pick.pick_no.clone(),
+      "actual_qty": input.actual_qty,
+      "short_reason": input.short_reason.clone(),
+      "auto_adjust": input.auto_adjust,
+      "occurred_at": input.occurred_at,
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "location": input.location.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::OutboundPickConfirm,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            let actual_qty = item_service::qty_to_base(&state.pool(), &pick.item_id, input.actual_qty).await?;
+            outbound_pick_service::confirm_pick(
+                &state.pool(),
+                &input.id,
+                actual_qty,
+                input.short_reason.clone(),
+                input.auto_adjust.unwrap_or(false),
+                input.occurred_at,
+                &business_operator_id,
+                input.location.clone(),
+            )
+            .await
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn cancel_outbound_pick(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: CancelPickInput,
+) -> Result<(), AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    let pick = outbound_pick_service::get_pending_pick(&state.pool(), &input.id).await?;
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &pick.slot_id).await?;
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "id": input.id.clone(),
+      "pick_no": pick.pick_no.clone(),
+      "actor_operator_id": actor_operator_id.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::OutboundPickCancel,
+        None,
+        Some(audit_request),
+        || async { outbound_pick_service::cancel_pick(&state.pool(), &input.id).await },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn list_outbound_picks(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: PickListInput,
+) -> Result<Vec<OutboundPickListRow>, AppError> {
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "viewer", "member"],
+    )
+    .await?;
+    let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::OutboundPickList,
+        None,
+        Some(json!({
+          "actor_operator_id": actor_operator_id.clone(),
+          "status": input.status.clone()
+        })),
+        || async {
+            outbound_pick_service::list_picks(&state.pool(), input.status.as_deref(), allowed_warehouse_ids.clone()).await
+        },
+    )
+    .await
+}
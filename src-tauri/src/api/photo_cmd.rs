@@ -104,7 +104,7 @@ pub async fn list_photos(
   query: PhotoListQuery,
 ) -> Result<photo_service::PhotoListResult, AppError> {
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
@@ -115,13 +115,13 @@ pub async fn list_photos(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     query.photo_type.audit_list(),
     None,
     Some(audit_request),
     || async {
       photo_service::list_photos(
-        &state.pool,
+        &state.pool(),
         query.photo_type.as_str(),
         &query.data_id,
       )
@@ -137,9 +137,9 @@ pub async fn add_photos(
   actor_operator_id: String,
   input: AddPhotosInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "member"],
   )
@@ -152,13 +152,13 @@ pub async fn add_photos(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     input.photo_type.audit_add(),
     None,
     Some(audit_request),
     || async {
       photo_service::add_photos(
-        &state.pool,
+        &state.pool(),
         input.photo_type.as_str(),
         &input.data_id,
         input.src_paths.clone(),
@@ -175,9 +175,9 @@ pub async fn remove_photo(
   actor_operator_id: String,
   input: RemovePhotoInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "member"],
   )
@@ -190,13 +190,13 @@ pub async fn remove_photo(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     input.photo_type.audit_remove(),
     None,
     Some(audit_request),
     || async {
       photo_service::remove_photo(
-        &state.pool,
+        &state.pool(),
         input.photo_type.as_str(),
         &input.data_id,
         &input.photo_id,
@@ -214,7 +214,7 @@ pub async fn read_photo_bytes(
   input: ReadPhotoInput,
 ) -> Result<Vec<u8>, AppError> {
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
@@ -228,9 +228,9 @@ pub async fn stage_photo_bytes(
   actor_operator_id: String,
   input: StagePhotoBytesInput,
 ) -> Result<String, AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "member"],
   )
@@ -243,13 +243,13 @@ pub async fn stage_photo_bytes(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     input.photo_type.audit_add(),
     None,
     Some(audit_request),
     || async {
       photo_service::stage_photo_bytes(
-        &state.pool,
+        &state.pool(),
         input.photo_type.as_str(),
         &input.extension,
         input.bytes.clone(),
@@ -266,9 +266,9 @@ pub async fn reorder_photos(
   actor_operator_id: String,
   input: ReorderPhotosInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "member"],
   )
@@ -281,13 +281,13 @@ pub async fn reorder_photos(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     input.photo_type.audit_reorder(),
     None,
     Some(audit_request),
     || async {
       photo_service::reorder_photos(
-        &state.pool,
+        &state.pool(),
         input.photo_type.as_str(),
         &input.data_id,
         input.photo_ids_in_order.clone(),
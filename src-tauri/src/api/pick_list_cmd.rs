@@ -0,0 +1,74 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::services::permission_service;
+use crate::services::pick_list_service::{self, PickListKind, PickListLine, PickListResult};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct PrintPickListInput {
+  // pick（拣货单，默认）/ putaway（上架单）
+  pub kind: Option<String>,
+  // 自动建议拣货库位时限定的仓库
+  pub warehouse_id: Option<String>,
+  pub lines: Vec<PickListLine>,
+  // 关联单号或备注，打印在表头
+  pub reference: Option<String>,
+  // pdf / html，默认 pdf
+  pub format: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn print_pick_list(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: PrintPickListInput,
+) -> Result<PickListResult, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let kind_text = input.kind.clone().unwrap_or_else(|| "pick".to_string());
+  let kind = PickListKind::parse(&kind_text)?;
+  let format = input.format.clone().unwrap_or_else(|| "pdf".to_string());
+  let reference = input
+    .reference
+    .as_deref()
+    .map(str::trim)
+    .filter(|value| !value.is_empty());
+  let audit_request = json!({
+    "kind": kind_text,
+    "warehouse_id": input.warehouse_id.clone(),
+    "lines": input.lines.len(),
+    "reference": reference,
+    "format": format.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::PickListPrint,
+    None,
+    Some(audit_request),
+    || async {
+      pick_list_service::print_pick_list(
+        &state.pool(),
+        kind,
+        input.warehouse_id.clone(),
+        &input.lines,
+        reference,
+        &format,
+        &actor_operator_id,
+      )
+      .await
+    },
+  )
+  .await
+}
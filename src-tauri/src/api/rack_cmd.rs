@@ -5,7 +5,7 @@ use tauri::State;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
-use crate::services::{permission_service, rack_service};
+use crate::services::{permission_service, rack_map_service, rack_service, undo_service};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -44,6 +44,16 @@ pub struct UpdateSlotStatusInput {
   // actor_operator_id provided as top-level arg
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SetSlotsStatusInput {
+  // 按货架（可限定层号）批量选择；提供 slot_ids 时忽略 rack_id/level_no
+  pub rack_id: Option<String>,
+  pub level_no: Option<i64>,
+  pub slot_ids: Option<Vec<String>>,
+  pub status: String,
+  // actor_operator_id provided as top-level arg
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListRackQuery {
   pub page_index: i64,
@@ -70,6 +80,25 @@ pub struct RegenSlotInput {
   // actor_operator_id provided as top-level arg
 }
 
+#[derive(Debug, Deserialize)]
+pub struct CloneRackInput {
+  pub source_rack_id: String,
+  pub codes: Option<Vec<String>>,
+  pub code_from: Option<String>,
+  pub code_to: Option<String>,
+  // 新货架名称模板，{code} 替换为编号，缺省为 R{code}
+  pub name_template: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecodeSlotsInput {
+  pub warehouse_id: Option<String>,
+  pub rack_id: Option<String>,
+  pub dry_run: Option<bool>,
+  // actor_operator_id provided as top-level arg
+}
+
 #[tauri::command]
 pub async fn list_racks(
   state: State<'_, AppState>,
@@ -77,13 +106,13 @@ pub async fn list_racks(
   input: ListRackQuery,
 ) -> Result<rack_service::RackListResult, AppError> {
   let audit_request = json!({ "actor_operator_id": actor_operator_id.clone() });
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::RackList,
     None,
     Some(audit_request),
-    || async { rack_service::list_racks(&state.pool, input.page_index, input.page_size, input.keyword.clone(), input.warehouse_id.clone()).await },
+    || async { rack_service::list_racks(&state.pool(), input.page_index, input.page_size, input.keyword.clone(), input.warehouse_id.clone()).await },
   )
   .await
 }
@@ -94,9 +123,9 @@ pub async fn create_rack(
   actor_operator_id: String,
   input: CreateRackInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "code": input.code.clone(),
     "name": input.name.clone(),
@@ -107,13 +136,13 @@ pub async fn create_rack(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::RackCreate,
     None,
     Some(audit_request),
     || async {
       rack_service::create_rack(
-        &state.pool,
+        &state.pool(),
         &input.code,
         &input.name,
         input.warehouse_id.clone(),
@@ -127,15 +156,52 @@ pub async fn create_rack(
   .await
 }
 
+#[tauri::command]
+pub async fn clone_rack(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: CloneRackInput,
+) -> Result<rack_service::RackCloneResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.source_rack_id.clone(),
+    "codes": input.codes.clone(),
+    "code_from": input.code_from.clone(),
+    "code_to": input.code_to.clone(),
+    "name_template": input.name_template.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RackClone,
+    None,
+    Some(audit_request),
+    || async {
+      rack_service::clone_rack(
+        &state.pool(),
+        &input.source_rack_id,
+        input.codes.clone(),
+        input.code_from.clone(),
+        input.code_to.clone(),
+        input.name_template.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
 #[tauri::command]
 pub async fn update_rack(
   state: State<'_, AppState>,
   actor_operator_id: String,
   input: UpdateRackInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "name": input.name.clone(),
@@ -146,13 +212,14 @@ pub async fn update_rack(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::RackUpdate,
     None,
     Some(audit_request),
     || async {
+      let before = undo_service::capture_rack(&state.pool(), &input.id).await?;
       rack_service::update_rack(
-        &state.pool,
+        &state.pool(),
         &input.id,
         &input.name,
         input.warehouse_id.clone(),
@@ -160,7 +227,8 @@ pub async fn update_rack(
         input.level_count,
         input.slots_per_level,
       )
-      .await
+      .await?;
+      undo_service::record_rack(&state.pool(), &actor_operator_id, AuditAction::RackUpdate, before).await
     },
   )
   .await
@@ -172,20 +240,24 @@ pub async fn set_rack_status(
   actor_operator_id: String,
   input: UpdateRackStatusInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "status": input.status.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::RackStatus,
     None,
     Some(audit_request),
-    || async { rack_service::set_rack_status(&state.pool, &input.id, &input.status).await },
+    || async {
+      let before = undo_service::capture_rack(&state.pool(), &input.id).await?;
+      rack_service::set_rack_status(&state.pool(), &input.id, &input.status).await?;
+      undo_service::record_rack(&state.pool(), &actor_operator_id, AuditAction::RackStatus, before).await
+    },
   )
   .await
 }
@@ -196,21 +268,57 @@ pub async fn set_slot_status(
   actor_operator_id: String,
   input: UpdateSlotStatusInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "slot_id": input.slot_id.clone(),
     "status": input.status.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SlotStatus,
     None,
     Some(audit_request),
     || async {
-      rack_service::set_slot_status(&state.pool, &input.slot_id, &input.status).await
+      rack_service::set_slot_status(&state.pool(), &input.slot_id, &input.status).await
+    },
+  )
+  .await
+}
+
+/// 批量启用/停用库位（整层或整个货架），跳过仍有库存的库位
+#[tauri::command]
+pub async fn set_slots_status(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetSlotsStatusInput,
+) -> Result<rack_service::SlotStatusBulkResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "rack_id": input.rack_id.clone(),
+    "level_no": input.level_no,
+    "slot_ids": input.slot_ids.clone(),
+    "status": input.status.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SlotBulkStatus,
+    None,
+    Some(audit_request),
+    || async {
+      rack_service::set_slots_status(
+        &state.pool(),
+        input.rack_id.clone(),
+        input.level_no,
+        input.slot_ids.clone(),
+        &input.status,
+      )
+      .await
     },
   )
   .await
@@ -228,13 +336,13 @@ pub async fn list_slots(
     "level_no": query.level_no,
     "actor_operator_id": actor_operator_id.clone()
   });
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SlotList,
     None,
     Some(audit_request),
-    || async { rack_service::list_slots(&state.pool, query.rack_id.clone(), query.warehouse_id.clone(), query.level_no).await },
+    || async { rack_service::list_slots(&state.pool(), query.rack_id.clone(), query.warehouse_id.clone(), query.level_no).await },
   )
   .await
 }
@@ -245,9 +353,9 @@ pub async fn regenerate_slots(
   actor_operator_id: String,
   input: RegenSlotInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let now = chrono::Utc::now().timestamp();
   let audit_request = json!({
     "rack_id": input.rack_id.clone(),
@@ -257,13 +365,13 @@ pub async fn regenerate_slots(
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SlotRegen,
     None,
     Some(audit_request),
     || async {
       rack_service::regenerate_slots(
-        &state.pool,
+        &state.pool(),
         &input.rack_id,
         &input.rack_code,
         None,
@@ -278,6 +386,32 @@ pub async fn regenerate_slots(
   .await
 }
 
+#[tauri::command]
+pub async fn recode_slots(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RecodeSlotsInput,
+) -> Result<rack_service::SlotRecodeResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let dry_run = input.dry_run.unwrap_or(false);
+  let audit_request = json!({
+    "warehouse_id": input.warehouse_id.clone(),
+    "rack_id": input.rack_id.clone(),
+    "dry_run": dry_run,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SlotRecode,
+    None,
+    Some(audit_request),
+    || async { rack_service::recode_slots(&state.pool(), input.warehouse_id.clone(), input.rack_id.clone(), dry_run).await },
+  )
+  .await
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GetRackInput {
   pub id: Option<String>,
@@ -291,21 +425,21 @@ pub async fn get_rack(
   actor_operator_id: String,
   input: GetRackInput,
 ) -> Result<Option<crate::repo::rack_repo::RackRow>, AppError> {
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
   let audit_request = json!({ "id": input.id.clone(), "code": input.code.clone(), "warehouse_id": input.warehouse_id.clone(), "actor_operator_id": actor_operator_id.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::RackList,
     None,
     Some(audit_request),
     || async {
-      if let Some(id) = input.id {
-        crate::repo::rack_repo::get_rack_by_id(&state.pool, &id).await
-      } else if let Some(code) = input.code {
-        if let Some(warehouse_id) = input.warehouse_id {
-          crate::repo::rack_repo::get_rack_by_code_and_warehouse(&state.pool, &code, &warehouse_id).await
+      if let Some(id) = &input.id {
+        crate::repo::rack_repo::get_rack_by_id(&state.pool(), id).await
+      } else if let Some(code) = &input.code {
+        if let Some(warehouse_id) = &input.warehouse_id {
+          crate::repo::rack_repo::get_rack_by_code_and_warehouse(&state.pool(), code, warehouse_id).await
         } else {
-          crate::repo::rack_repo::get_rack_by_code(&state.pool, &code).await
+          crate::repo::rack_repo::get_rack_by_code(&state.pool(), code).await
         }
       } else {
         Ok(None)
@@ -327,18 +461,18 @@ pub async fn get_slot(
   actor_operator_id: String,
   input: GetSlotInput,
 ) -> Result<Option<crate::repo::rack_repo::SlotRow>, AppError> {
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
   let audit_request = json!({ "id": input.id.clone(), "code": input.code.clone(), "actor_operator_id": actor_operator_id.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SlotList,
     None,
     Some(audit_request),
     || async {
-      if let Some(id) = input.id {
-        crate::repo::rack_repo::get_slot_by_id(&state.pool, &id).await
-      } else if let Some(code) = input.code {
-        crate::repo::rack_repo::get_slot_by_code(&state.pool, &code).await
+      if let Some(id) = &input.id {
+        crate::repo::rack_repo::get_slot_by_id(&state.pool(), id).await
+      } else if let Some(code) = &input.code {
+        crate::repo::rack_repo::get_slot_by_code(&state.pool(), code).await
       } else {
         Ok(None)
       }
@@ -346,3 +480,89 @@ pub async fn get_slot(
   )
   .await
 }
+
+#[derive(Debug, Deserialize)]
+pub struct RackMapInput {
+  pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportRackMapInput {
+  pub id: String,
+  // svg / pdf
+  pub format: String,
+}
+
+#[tauri::command]
+pub async fn get_rack_map(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RackMapInput,
+) -> Result<rack_map_service::RackMap, AppError> {
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let audit_request = json!({ "id": input.id.clone(), "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RackMapView,
+    None,
+    Some(audit_request),
+    || async {
+      let map = rack_map_service::get_rack_map(&state.pool(), &input.id).await?;
+      permission_service::require_warehouse_access(&state.pool(), &actor_operator_id, map.rack.warehouse_id.as_deref()).await?;
+      Ok(map)
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn export_rack_map(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ExportRackMapInput,
+) -> Result<crate::services::import_export_service::ExportResult, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let audit_request = json!({ "id": input.id.clone(), "format": input.format.clone(), "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RackMapExport,
+    None,
+    Some(audit_request),
+    || async {
+      let map = rack_map_service::get_rack_map(&state.pool(), &input.id).await?;
+      permission_service::require_warehouse_access(&state.pool(), &actor_operator_id, map.rack.warehouse_id.as_deref()).await?;
+      rack_map_service::export_rack_map(&state.pool(), &map, &input.format).await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RackHeatmapInput {
+  pub id: String,
+  // 统计天数，缺省 30 天
+  pub days: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn get_rack_heatmap(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: RackHeatmapInput,
+) -> Result<rack_map_service::RackHeatmap, AppError> {
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let audit_request = json!({ "id": input.id.clone(), "days": input.days, "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::RackHeatmapView,
+    None,
+    Some(audit_request),
+    || async {
+      let heatmap = rack_map_service::get_rack_heatmap(&state.pool(), &input.id, input.days).await?;
+      permission_service::require_warehouse_access(&state.pool(), &actor_operator_id, heatmap.rack.warehouse_id.as_deref()).await?;
+      Ok(heatmap)
+    },
+  )
+  .await
+}
@@ -0,0 +1,133 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::reason_code_repo::ReasonCodeRow;
+use crate::services::permission_service;
+use crate::services::reason_code_service::{self, ReasonCodeInput};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ListReasonCodesInput {
+  // 是否包含已停用的原因（原因管理使用）
+  pub include_disabled: Option<bool>,
+  // 仅返回适用于该流水类型的原因（ADJUST / REVERSAL / SCRAP）
+  pub txn_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateReasonCodeInput {
+  pub id: String,
+  #[serde(flatten)]
+  pub reason: ReasonCodeInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteReasonCodeInput {
+  pub id: String,
+}
+
+fn reason_audit_request(reason: &ReasonCodeInput, actor_operator_id: &str) -> serde_json::Value {
+  json!({
+    "code": reason.code.clone(),
+    "name": reason.name.clone(),
+    "txn_types": reason.txn_types.clone(),
+    "sort_no": reason.sort_no,
+    "status": reason.status.clone(),
+    "actor_operator_id": actor_operator_id
+  })
+}
+
+#[tauri::command]
+pub async fn list_reason_codes(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListReasonCodesInput,
+) -> Result<Vec<ReasonCodeRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let include_disabled = input.include_disabled.unwrap_or(false);
+  let audit_request = json!({
+    "include_disabled": include_disabled,
+    "txn_type": input.txn_type.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReasonCodeList,
+    None,
+    Some(audit_request),
+    || async { reason_code_service::list_reasons(&state.pool(), !include_disabled, input.txn_type.clone()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn create_reason_code(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ReasonCodeInput,
+) -> Result<ReasonCodeRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReasonCodeCreate,
+    None,
+    Some(reason_audit_request(&input, &actor_operator_id)),
+    || async { reason_code_service::create_reason(&state.pool(), &input).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn update_reason_code(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateReasonCodeInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let mut audit_request = reason_audit_request(&input.reason, &actor_operator_id);
+  audit_request["id"] = json!(input.id.clone());
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReasonCodeUpdate,
+    None,
+    Some(audit_request),
+    || async { reason_code_service::update_reason(&state.pool(), &input.id, &input.reason).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_reason_code(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DeleteReasonCodeInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReasonCodeDelete,
+    None,
+    Some(audit_request),
+    || async { reason_code_service::delete_reason(&state.pool(), &input.id).await },
+  )
+  .await
+}
@@ -0,0 +1,287 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::services::{expiry_service, import_export_service, permission_service, report_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct ItemStockTrendInput {
+  pub item_id: String,
+  // 时间范围（1m/3m/6m/1y/2y），缺省近 6 个月
+  pub range: Option<String>,
+  // 分组粒度（day/week/month），缺省按周
+  pub bucket: Option<String>,
+}
+
+#[tauri::command]
+pub async fn item_stock_trend(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ItemStockTrendInput,
+) -> Result<report_service::ItemStockTrend, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  // 启用 RBAC 时仅统计可访问仓库内的库存
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "range": input.range.clone(),
+    "bucket": input.bucket.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportItemStockTrend,
+    None,
+    Some(audit_request),
+    || async {
+      report_service::item_stock_trend(
+        &state.pool(),
+        &input.item_id,
+        input.range.as_deref(),
+        input.bucket.as_deref(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StockAgingInput {
+  pub warehouse_id: Option<String>,
+  // 仅返回呆滞库存
+  pub stale_only: Option<bool>,
+  // 呆滞阈值（天），缺省使用系统设置
+  pub threshold_days: Option<i64>,
+}
+
+impl StockAgingInput {
+  fn to_query(&self) -> report_service::StockAgingQuery {
+    report_service::StockAgingQuery {
+      warehouse_id: self.warehouse_id.clone(),
+      stale_only: self.stale_only.unwrap_or(false),
+      threshold_days: self.threshold_days,
+    }
+  }
+
+  fn audit_request(&self, actor_operator_id: &str) -> serde_json::Value {
+    json!({
+      "warehouse_id": self.warehouse_id.clone(),
+      "stale_only": self.stale_only,
+      "threshold_days": self.threshold_days,
+      "actor_operator_id": actor_operator_id
+    })
+  }
+}
+
+#[tauri::command]
+pub async fn get_stock_aging(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: StockAgingInput,
+) -> Result<report_service::StockAgingReport, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportStockAging,
+    None,
+    Some(input.audit_request(&actor_operator_id)),
+    || async {
+      let mut report =
+        report_service::get_stock_aging(&state.pool(), input.to_query(), allowed_warehouse_ids.clone()).await?;
+      if !show_costs {
+        report.hide_costs();
+      }
+      Ok(report)
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LowStockReportInput {
+  pub warehouse_id: Option<String>,
+  // 同时列出超过最高库存的记录
+  pub include_above_max: Option<bool>,
+}
+
+#[tauri::command]
+pub async fn get_low_stock_report(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: LowStockReportInput,
+) -> Result<report_service::LowStockReport, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "warehouse_id": input.warehouse_id.clone(),
+    "include_above_max": input.include_above_max,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportLowStock,
+    None,
+    Some(audit_request),
+    || async {
+      report_service::get_low_stock_report(
+        &state.pool(),
+        input.warehouse_id.clone(),
+        input.include_above_max.unwrap_or(false),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiringStockReportInput {
+  pub warehouse_id: Option<String>,
+  // 临期天数，缺省使用系统设置 expiry_horizon_days
+  pub horizon_days: Option<i64>,
+}
+
+#[tauri::command]
+pub async fn get_expiring_stock_report(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ExpiringStockReportInput,
+) -> Result<expiry_service::ExpiringStockReport, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "warehouse_id": input.warehouse_id.clone(),
+    "horizon_days": input.horizon_days,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportExpiringStock,
+    None,
+    Some(audit_request),
+    || async {
+      expiry_service::get_expiring_report(
+        &state.pool(),
+        input.horizon_days,
+        input.warehouse_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn export_stock_aging(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: StockAgingInput,
+) -> Result<import_export_service::ExportResult, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_export").await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportStockAgingExport,
+    None,
+    Some(input.audit_request(&actor_operator_id)),
+    || async {
+      report_service::export_stock_aging(&state.pool(), input.to_query(), allowed_warehouse_ids.clone(), show_costs)
+        .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TxnReasonReportInput {
+  // 缺省统计近 30 天
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+  // ADJUST / REVERSAL / SCRAP，缺省全部
+  pub txn_type: Option<String>,
+  pub warehouse_id: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_txn_reason_report(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: TxnReasonReportInput,
+) -> Result<report_service::TxnReasonReport, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "start_at": input.start_at,
+    "end_at": input.end_at,
+    "txn_type": input.txn_type.clone(),
+    "warehouse_id": input.warehouse_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportTxnReasons,
+    None,
+    Some(audit_request),
+    || async {
+      report_service::get_txn_reason_report(
+        &state.pool(),
+        input.start_at,
+        input.end_at,
+        input.txn_type.clone(),
+        input.warehouse_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
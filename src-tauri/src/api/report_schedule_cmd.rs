@@ -0,0 +1,160 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::api::{command_guard, notification_cmd, system_cmd};
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::report_schedule_repo::{ReportScheduleRow, ReportScheduleRunRow};
+use crate::services::permission_service;
+use crate::services::report_schedule_service::{self, ReportScheduleInput};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateReportScheduleInput {
+  pub id: String,
+  #[serde(flatten)]
+  pub schedule: ReportScheduleInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportScheduleIdInput {
+  pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListReportScheduleRunsInput {
+  // 为空时返回全部计划的执行记录
+  pub schedule_id: Option<String>,
+}
+
+fn schedule_audit_request(schedule: &ReportScheduleInput, actor_operator_id: &str) -> serde_json::Value {
+  json!({
+    "name": schedule.name.clone(),
+    "report_kind": schedule.report_kind.clone(),
+    "run_time": schedule.run_time.clone(),
+    "email_to": schedule.email_to.clone(),
+    "status": schedule.status.clone(),
+    "actor_operator_id": actor_operator_id
+  })
+}
+
+#[tauri::command]
+pub async fn list_report_schedules(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Vec<ReportScheduleRow>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  report_schedule_service::list_schedules(&state.pool()).await
+}
+
+#[tauri::command]
+pub async fn create_report_schedule(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ReportScheduleInput,
+) -> Result<ReportScheduleRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportScheduleCreate,
+    None,
+    Some(schedule_audit_request(&input, &actor_operator_id)),
+    || async { report_schedule_service::create_schedule(&state.pool(), &input).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn update_report_schedule(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: UpdateReportScheduleInput,
+) -> Result<ReportScheduleRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let mut audit_request = schedule_audit_request(&input.schedule, &actor_operator_id);
+  audit_request["id"] = json!(input.id.clone());
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportScheduleUpdate,
+    None,
+    Some(audit_request),
+    || async { report_schedule_service::update_schedule(&state.pool(), &input.id, &input.schedule).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_report_schedule(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ReportScheduleIdInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportScheduleDelete,
+    None,
+    Some(audit_request),
+    || async { report_schedule_service::delete_schedule(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+/// 立即执行一次计划（不影响当天的定时执行），返回执行记录；生成报表与发信期间不占用写锁，报表邮件入队后立即尝试发送
+#[tauri::command]
+pub async fn run_report_schedule(
+  app_handle: AppHandle,
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ReportScheduleIdInput,
+) -> Result<ReportScheduleRunRow, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::ReportScheduleRun,
+    None,
+    Some(audit_request),
+    || async {
+      let schedule = report_schedule_service::get_schedule(&state.pool(), &input.id).await?;
+      let mut run = report_schedule_service::run_schedule(&state.pool(), &schedule, "manual").await;
+      {
+        let _guard = state.write_lock.lock().await;
+        if report_schedule_service::record_run(&state.pool(), &schedule, &mut run).await? {
+          notification_cmd::emit_new(&app_handle);
+        }
+      }
+      if run.email_status == "queued" {
+        system_cmd::deliver_due_mails(&app_handle, &state).await;
+      }
+      Ok(run)
+    },
+  )
+  .await
+}
+
+/// 执行记录（最近 200 条）
+#[tauri::command]
+pub async fn list_report_schedule_runs(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ListReportScheduleRunsInput,
+) -> Result<Vec<ReportScheduleRunRow>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  report_schedule_service::list_runs(&state.pool(), input.schedule_id).await
+}
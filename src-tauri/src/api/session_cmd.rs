@@ -0,0 +1,76 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::session_repo::{self, ActiveSessionRow, SessionRow};
+use crate::services::{permission_service, session_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct SessionIdInput {
+  pub id: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckSessionInput {
+  pub session_id: String,
+}
+
+#[tauri::command]
+pub async fn list_active_sessions(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Vec<ActiveSessionRow>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({ "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SessionList,
+    None,
+    Some(audit_request),
+    || async { session_service::list_active_sessions(&state.pool()).await },
+  )
+  .await
+}
+
+/// 强制结束会话（如共享终端遗留的登录），对应终端下次检查会话时退出登录
+#[tauri::command]
+pub async fn terminate_session(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SessionIdInput,
+) -> Result<SessionRow, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let session_operator_id = session_repo::get_session_by_id(&state.pool(), &input.id)
+    .await?
+    .map(|session| session.operator_id);
+  let audit_request = json!({
+    "id": input.id.clone(),
+    "operator_id": session_operator_id,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SessionTerminate,
+    None,
+    Some(audit_request),
+    || async { session_service::terminate_session(&state.pool(), &input.id).await },
+  )
+  .await
+}
+
+/// 会话心跳：前端定时调用，不写审计
+#[tauri::command]
+pub async fn check_session(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: CheckSessionInput,
+) -> Result<session_service::SessionCheck, AppError> {
+  session_service::check_session(&state.pool(), &input.session_id, &actor_operator_id).await
+}
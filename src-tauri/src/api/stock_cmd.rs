@@ -4,7 +4,7 @@ use tauri::State;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
 use crate::api::command_guard;
-use crate::services::{permission_service, stock_service};
+use crate::services::{copy_service, permission_service, stock_close_service, stock_service, valuation_service};
 use crate::state::AppState;
 
 #[derive(Debug, serde::Deserialize)]
@@ -17,6 +17,12 @@ pub struct StockQueryInput {
   pub slot_id: Option<String>,
   pub item_id: Option<String>,
   pub operator_id: Option<String>,
+  // 排序字段（逗号分隔）与方向 asc/desc，仅列表使用，见 stock_query_repo 白名单
+  pub sort_by: Option<String>,
+  pub sort_dir: Option<String>,
+  // 仅导出使用：by_slot / by_item 与导出列键，见 stock_service::STOCK_EXPORT_COLUMNS
+  pub mode: Option<String>,
+  pub columns: Option<Vec<String>>,
 }
 
 #[tauri::command]
@@ -26,18 +32,20 @@ pub async fn list_stock_by_slot(
   input: StockQueryInput,
 ) -> Result<stock_service::StockBySlotResult, AppError> {
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
   .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::StockListBySlot,
     None,
     Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
     || async {
-      stock_service::list_stock_by_slot(&state.pool, input.page_index.clone().unwrap_or(1), input.page_size.clone().unwrap_or(20), input.warehouse_id.clone(), input.rack_id.clone(), input.slot_id.clone(), input.item_id.clone(), input.operator_id.clone()).await
+      stock_service::list_stock_by_slot(&state.pool(), input.page_index.clone().unwrap_or(1), input.page_size.clone().unwrap_or(20), input.warehouse_id.clone(), input.rack_id.clone(), input.slot_id.clone(), input.item_id.clone(), input.operator_id.clone(), allowed_warehouse_ids.clone(), input.sort_by.clone(), input.sort_dir.clone()).await
     },
   )
   .await
@@ -50,18 +58,20 @@ pub async fn list_stock_by_item(
   input: StockQueryInput,
 ) -> Result<stock_service::StockByItemResult, AppError> {
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
   .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::StockListByItem,
     None,
     Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
     || async {
-      stock_service::list_stock_by_item(&state.pool, input.page_index.clone().unwrap_or(1), input.page_size.clone().unwrap_or(20), input.warehouse_id.clone(), input.rack_id.clone(), input.slot_id.clone(), input.item_id.clone(), input.operator_id.clone()).await
+      stock_service::list_stock_by_item(&state.pool(), input.page_index.clone().unwrap_or(1), input.page_size.clone().unwrap_or(20), input.warehouse_id.clone(), input.rack_id.clone(), input.slot_id.clone(), input.item_id.clone(), input.operator_id.clone(), allowed_warehouse_ids.clone(), input.sort_by.clone(), input.sort_dir.clone()).await
     },
   )
   .await
@@ -75,27 +85,178 @@ pub async fn export_stock(
 ) -> Result<stock_service::StockExportResult, AppError> {
   command_guard::ensure_not_migrating(&state).await?;
   permission_service::require_role_by_id(
-    &state.pool,
+    &state.pool(),
     &actor_operator_id,
     &["admin", "keeper", "viewer", "member"],
   )
   .await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_export").await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::StockExport,
     None,
-    Some(json!({ "actor_operator_id": actor_operator_id.clone() })),
+    Some(json!({
+      "mode": input.mode.clone(),
+      "columns": input.columns.clone(),
+      "actor_operator_id": actor_operator_id.clone()
+    })),
     || async {
       stock_service::export_stock(
-        &state.pool,
+        &state.pool(),
         input.warehouse_id.clone(),
         input.rack_id.clone(),
         input.slot_id.clone(),
         input.item_id.clone(),
         input.operator_id.clone(),
+        allowed_warehouse_ids.clone(),
+        show_costs,
+        input.mode.clone(),
+        input.columns.clone(),
+        input.sort_by.clone(),
+        input.sort_dir.clone(),
       )
       .await
     },
   )
   .await
 }
+
+#[tauri::command]
+pub async fn copy_stock(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: StockQueryInput,
+) -> Result<copy_service::CopyPayload, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_export").await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "warehouse_id": input.warehouse_id.clone(),
+    "rack_id": input.rack_id.clone(),
+    "slot_id": input.slot_id.clone(),
+    "item_id": input.item_id.clone(),
+    "operator_id": input.operator_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockCopy,
+    None,
+    Some(audit_request),
+    || async {
+      copy_service::copy_stock(
+        &state.pool(),
+        input.warehouse_id.clone(),
+        input.rack_id.clone(),
+        input.slot_id.clone(),
+        input.item_id.clone(),
+        input.operator_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StockValuationInput {
+  // actor_operator_id provided as top-level arg
+  pub warehouse_id: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_stock_valuation(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: Option<StockValuationInput>,
+) -> Result<valuation_service::StockValuation, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  permission_service::require_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let warehouse_id = input.and_then(|input| input.warehouse_id);
+  let audit_request = json!({
+    "warehouse_id": warehouse_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockValuation,
+    None,
+    Some(audit_request),
+    || async {
+      valuation_service::get_stock_valuation(
+        &state.pool(),
+        warehouse_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct StockAsofInput {
+  // actor_operator_id provided as top-level arg
+  // 本地日期 YYYY-MM-DD
+  pub date: String,
+  pub warehouse_id: Option<String>,
+}
+
+#[tauri::command]
+pub async fn get_stock_asof(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: StockAsofInput,
+) -> Result<stock_close_service::StockAsofResult, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let show_costs = permission_service::has_capability(&state.pool(), &actor_operator_id, "can_view_costs").await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "date": input.date.clone(),
+    "warehouse_id": input.warehouse_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockAsof,
+    None,
+    Some(audit_request),
+    || async {
+      let mut result = stock_close_service::get_stock_asof(
+        &state.pool(),
+        &input.date,
+        input.warehouse_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await?;
+      if !show_costs {
+        result.hide_costs();
+      }
+      Ok(result)
+    },
+  )
+  .await
+}
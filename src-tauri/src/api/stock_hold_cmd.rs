@@ -0,0 +1,144 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::stock_hold_repo::StockHoldListRow;
+use crate::services::{item_service, permission_service, stock_hold_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct StockHoldListInput {
+  pub item_id: Option<String>,
+  pub slot_id: Option<String>,
+  pub warehouse_id: Option<String>,
+  // 仅返回冻结中的记录，缺省为 true
+  pub active_only: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HoldStockInput {
+  pub item_id: String,
+  pub slot_id: String,
+  // 显示数量，可含小数（位数不超过物品 qty_precision）
+  pub qty: f64,
+  // damaged（破损）/ pending_qc（待检）/ other（其他）
+  pub reason: String,
+  pub note: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReleaseHoldInput {
+  pub hold_id: String,
+  pub note: Option<String>,
+}
+
+#[tauri::command]
+pub async fn list_stock_holds(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: StockHoldListInput,
+) -> Result<Vec<StockHoldListRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockHoldList,
+    None,
+    Some(json!({
+      "item_id": input.item_id.clone(),
+      "slot_id": input.slot_id.clone(),
+      "warehouse_id": input.warehouse_id.clone(),
+      "active_only": input.active_only,
+      "actor_operator_id": actor_operator_id.clone()
+    })),
+    || async {
+      stock_hold_service::list_holds(
+        &state.pool(),
+        input.item_id.clone(),
+        input.slot_id.clone(),
+        input.warehouse_id.clone(),
+        input.active_only.unwrap_or(true),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn hold_stock(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: HoldStockInput,
+) -> Result<String, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.slot_id).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "slot_id": input.slot_id.clone(),
+    "qty": input.qty,
+    "reason": input.reason.clone(),
+    "note": input.note.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockHoldCreate,
+    None,
+    Some(audit_request),
+    || async {
+      let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+      stock_hold_service::hold_stock(
+        &state.pool(),
+        &input.item_id,
+        &input.slot_id,
+        qty,
+        &input.reason,
+        input.note.clone(),
+        &actor_operator_id,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn release_hold(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: ReleaseHoldInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  let slot_id = stock_hold_service::hold_slot_id(&state.pool(), &input.hold_id).await?;
+  permission_service::require_slot_access(&state.pool(), &actor_operator_id, &slot_id).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "hold_id": input.hold_id.clone(),
+    "note": input.note.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockHoldRelease,
+    None,
+    Some(audit_request),
+    || async {
+      stock_hold_service::release_hold(&state.pool(), &input.hold_id, input.note.clone(), &actor_operator_id).await
+    },
+  )
+  .await
+}
@@ -0,0 +1,129 @@
+use serde::Deserialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::stock_threshold_repo::StockThresholdRow;
+use crate::services::{permission_service, stock_threshold_service};
+use crate::state::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct StockThresholdListInput {
+  pub item_id: Option<String>,
+  pub warehouse_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetStockThresholdInput {
+  pub item_id: String,
+  pub warehouse_id: String,
+  // 最低 / 最高库存（显示数量），最高库存不填表示不限
+  pub min_qty: f64,
+  pub max_qty: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteStockThresholdInput {
+  pub item_id: String,
+  pub warehouse_id: String,
+}
+
+#[tauri::command]
+pub async fn list_stock_thresholds(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: StockThresholdListInput,
+) -> Result<Vec<StockThresholdRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer"],
+  )
+  .await?;
+  let allowed_warehouse_ids =
+    permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockThresholdList,
+    None,
+    Some(json!({
+      "item_id": input.item_id.clone(),
+      "warehouse_id": input.warehouse_id.clone(),
+      "actor_operator_id": actor_operator_id.clone()
+    })),
+    || async {
+      stock_threshold_service::list_thresholds(
+        &state.pool(),
+        input.item_id.clone(),
+        input.warehouse_id.clone(),
+        allowed_warehouse_ids.clone(),
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn set_stock_threshold(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetStockThresholdInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  permission_service::require_warehouse_access(&state.pool(), &actor_operator_id, Some(&input.warehouse_id)).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "warehouse_id": input.warehouse_id.clone(),
+    "min_qty": input.min_qty,
+    "max_qty": input.max_qty,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockThresholdSet,
+    None,
+    Some(audit_request),
+    || async {
+      stock_threshold_service::set_threshold(
+        &state.pool(),
+        &input.item_id,
+        &input.warehouse_id,
+        input.min_qty,
+        input.max_qty,
+        &actor_operator_id,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn delete_stock_threshold(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: DeleteStockThresholdInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+  permission_service::require_warehouse_access(&state.pool(), &actor_operator_id, Some(&input.warehouse_id)).await?;
+  let _guard = state.write_lock.lock().await;
+  let audit_request = json!({
+    "item_id": input.item_id.clone(),
+    "warehouse_id": input.warehouse_id.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StockThresholdDelete,
+    None,
+    Some(audit_request),
+    || async { stock_threshold_service::delete_threshold(&state.pool(), &input.item_id, &input.warehouse_id).await },
+  )
+  .await
+}
@@ -1,20 +1,58 @@
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
 use serde::Deserialize;
 use serde_json::json;
 use tauri::{AppHandle, Emitter, State};
 
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::api::command_guard;
-use crate::services::{permission_service, system_service};
+use crate::api::{command_guard, http_api, notification_cmd};
+use crate::services::{permission_service, photo_service, system_service};
 use crate::state::AppState;
-use crate::infra::fs;
-use crate::repo::meta_repo;
+use crate::infra::metrics::{self, PerformanceStats};
+use crate::infra::{db, fs, tray};
+use crate::repo::mail_queue_repo::MailQueueRow;
+use crate::repo::{meta_repo, photo_repo};
 
 #[derive(Debug, Deserialize)]
 pub struct SetSettingsInput {
   pub rbac_enabled: Option<bool>,
   pub slot_no_pad: Option<i64>,
   pub low_stock_threshold: Option<i64>,
+  pub stale_stock_days: Option<i64>,
+  pub expiry_horizon_days: Option<i64>,
+  pub item_required_fields: Option<Vec<String>>,
+  pub approval_reversal: Option<bool>,
+  pub approval_restore: Option<bool>,
+  pub approval_count_threshold: Option<i64>,
+  pub approval_scrap_threshold: Option<i64>,
+  pub reason_required_types: Option<Vec<String>>,
+  pub login_max_failures: Option<i64>,
+  pub login_fail_window_minutes: Option<i64>,
+  pub login_lockout_minutes: Option<i64>,
+  pub pwd_min_length: Option<i64>,
+  pub pwd_min_char_classes: Option<i64>,
+  pub pwd_disallow_username: Option<bool>,
+  pub pwd_history_count: Option<i64>,
+  pub pin_session_minutes: Option<i64>,
+  pub idle_lock_minutes: Option<i64>,
+  // 会话空闲超时（分钟，0 表示不限）
+  pub session_idle_minutes: Option<i64>,
+  pub audit_verbosity: Option<String>,
+  pub kiosk_mode: Option<bool>,
+  pub kiosk_operator_window_secs: Option<i64>,
+  pub valuation_method: Option<String>,
+  pub txn_no_scheme: Option<String>,
+  pub txn_location_capture: Option<bool>,
+  pub locale: Option<String>,
+  pub report_timezone: Option<String>,
+  pub http_api_enabled: Option<bool>,
+  pub http_api_port: Option<i64>,
+  pub close_to_tray: Option<bool>,
+  pub start_minimized: Option<bool>,
+  pub auto_backup_hours: Option<i64>,
+  pub db_pool_size: Option<i64>,
   // actor_operator_id provided as top-level arg
 }
 
@@ -28,38 +66,165 @@ pub struct SetStorageRootInput {
 pub async fn get_settings(
   state: State<'_, AppState>,
 ) -> Result<system_service::SettingsDto, AppError> {
-  system_service::get_settings(&state.pool).await
+  system_service::get_settings(&state.pool()).await
 }
 
 #[tauri::command]
 pub async fn set_settings(
+  app_handle: AppHandle,
   state: State<'_, AppState>,
   actor_operator_id: String,
   input: SetSettingsInput,
 ) -> Result<(), AppError> {
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "rbac_enabled": input.rbac_enabled,
     "slot_no_pad": input.slot_no_pad,
     "low_stock_threshold": input.low_stock_threshold,
+    "stale_stock_days": input.stale_stock_days,
+    "expiry_horizon_days": input.expiry_horizon_days,
+    "item_required_fields": input.item_required_fields.clone(),
+    "approval_reversal": input.approval_reversal,
+    "approval_restore": input.approval_restore,
+    "approval_count_threshold": input.approval_count_threshold,
+    "approval_scrap_threshold": input.approval_scrap_threshold,
+    "reason_required_types": input.reason_required_types.clone(),
+    "login_max_failures": input.login_max_failures,
+    "login_fail_window_minutes": input.login_fail_window_minutes,
+    "login_lockout_minutes": input.login_lockout_minutes,
+    "pwd_min_length": input.pwd_min_length,
+    "pwd_min_char_classes": input.pwd_min_char_classes,
+    "pwd_disallow_username": input.pwd_disallow_username,
+    "pwd_history_count": input.pwd_history_count,
+    "pin_session_minutes": input.pin_session_minutes,
+    "idle_lock_minutes": input.idle_lock_minutes,
+    "session_idle_minutes": input.session_idle_minutes,
+    "audit_verbosity": input.audit_verbosity.clone(),
+    "kiosk_mode": input.kiosk_mode,
+    "kiosk_operator_window_secs": input.kiosk_operator_window_secs,
+    "valuation_method": input.valuation_method.clone(),
+    "txn_no_scheme": input.txn_no_scheme.clone(),
+    "txn_location_capture": input.txn_location_capture,
+    "locale": input.locale.clone(),
+    "report_timezone": input.report_timezone.clone(),
+    "http_api_enabled": input.http_api_enabled,
+    "http_api_port": input.http_api_port,
+    "close_to_tray": input.close_to_tray,
+    "start_minimized": input.start_minimized,
+    "auto_backup_hours": input.auto_backup_hours,
+    "db_pool_size": input.db_pool_size,
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SystemSettingsUpdate,
     None,
     Some(audit_request),
     || async {
-      system_service::set_settings(
-        &state.pool,
-        input.rbac_enabled,
-        input.slot_no_pad,
-        input.low_stock_threshold,
-      )
-      .await
+      let patch = system_service::SettingsPatch {
+        rbac_enabled: input.rbac_enabled,
+        slot_no_pad: input.slot_no_pad,
+        low_stock_threshold: input.low_stock_threshold,
+        stale_stock_days: input.stale_stock_days,
+        expiry_horizon_days: input.expiry_horizon_days,
+        item_required_fields: input.item_required_fields.clone(),
+        approval_reversal: input.approval_reversal,
+        approval_restore: input.approval_restore,
+        approval_count_threshold: input.approval_count_threshold,
+        approval_scrap_threshold: input.approval_scrap_threshold,
+        reason_required_types: input.reason_required_types.clone(),
+        login_max_failures: input.login_max_failures,
+        login_fail_window_minutes: input.login_fail_window_minutes,
+        login_lockout_minutes: input.login_lockout_minutes,
+        pwd_min_length: input.pwd_min_length,
+        pwd_min_char_classes: input.pwd_min_char_classes,
+        pwd_disallow_username: input.pwd_disallow_username,
+        pwd_history_count: input.pwd_history_count,
+        pin_session_minutes: input.pin_session_minutes,
+        idle_lock_minutes: input.idle_lock_minutes,
+        session_idle_minutes: input.session_idle_minutes,
+        audit_verbosity: input.audit_verbosity.clone(),
+        kiosk_mode: input.kiosk_mode,
+        kiosk_operator_window_secs: input.kiosk_operator_window_secs,
+        valuation_method: input.valuation_method.clone(),
+        txn_no_scheme: input.txn_no_scheme.clone(),
+        txn_location_capture: input.txn_location_capture,
+        locale: input.locale.clone(),
+        report_timezone: input.report_timezone.clone(),
+        http_api_enabled: input.http_api_enabled,
+        http_api_port: input.http_api_port,
+        close_to_tray: input.close_to_tray,
+        start_minimized: input.start_minimized,
+        auto_backup_hours: input.auto_backup_hours,
+        db_pool_size: input.db_pool_size,
+      };
+      system_service::set_settings(&state.pool(), patch).await
     },
   )
+  .await?;
+  // 开关或端口变化后按新配置重启 HTTP 接口
+  if input.http_api_enabled.is_some() || input.http_api_port.is_some() {
+    http_api::restart(&app_handle).await?;
+  }
+  if input.close_to_tray.is_some() || input.start_minimized.is_some() {
+    tray::reload_prefs(&app_handle, &state.pool()).await?;
+  }
+  Ok(())
+}
+
+/// 重新生成 HTTP 接口访问令牌，仅在本次返回明文，审计中不记录令牌
+#[tauri::command]
+pub async fn regenerate_http_api_token(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<String, AppError> {
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "action": "regenerate_http_api_token",
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SystemSettingsUpdate,
+    None,
+    Some(audit_request),
+    || async { system_service::regenerate_http_api_token(&state.pool(), &actor_operator_id).await },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetReadOnlyModeInput {
+  pub enabled: bool,
+  // 开启时展示给其他人员的原因（可选）
+  pub reason: Option<String>,
+}
+
+/// 开启或关闭只读维护模式：开启后写操作统一被拒绝，查询与导出不受影响
+#[tauri::command]
+pub async fn set_read_only_mode(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SetReadOnlyModeInput,
+) -> Result<system_service::ReadOnlyState, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "action": "set_read_only_mode",
+    "enabled": input.enabled,
+    "reason": input.reason.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SystemReadOnlyUpdate,
+    None,
+    Some(audit_request),
+    || async { system_service::set_read_only_mode(&state.pool(), input.enabled, input.reason.clone()).await },
+  )
   .await
 }
 
@@ -71,7 +236,7 @@ pub async fn set_storage_root(
   input: SetStorageRootInput,
 ) -> Result<(), AppError> {
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   emit_migration_progress(&app_handle, "prepare", "start", "开始迁移");
   {
     let mut migrating = state.migrating.lock().await;
@@ -84,26 +249,23 @@ pub async fn set_storage_root(
     "actor_operator_id": actor_operator_id.clone()
   });
   emit_migration_progress(&app_handle, "migrate", "start", "开始迁移文件");
-  let result = command_guard::run_with_audit(
-    &state.pool,
+  state.migration_cancel.store(false, Ordering::SeqCst);
+  let result =
+    migrate_storage_root(&app_handle, &state, &input.new_path, &actor_operator_id).await;
+  // 迁移过程中会替换连接池，审计写入迁移后的数据库
+  let result = command_guard::record_audit(
+    &state.pool(),
     AuditAction::SystemStorageRootChange,
     None,
     Some(audit_request),
-    || async {
-      system_service::set_storage_root(
-        &state.pool,
-        &input.new_path,
-        &actor_operator_id,
-      )
-      .await
-    },
+    result,
   )
   .await;
-  if result.is_err() {
+  if result.is_err() && state.migration_cancel.load(Ordering::SeqCst) {
+    emit_migration_progress(&app_handle, "migrate", "cancelled", "已取消，已回滚到原存储目录");
+  } else if result.is_err() {
     emit_migration_progress(&app_handle, "migrate", "error", "迁移失败");
   } else {
-    emit_migration_progress(&app_handle, "verify", "done", "迁移完成并校验");
-    emit_migration_progress(&app_handle, "reconnect", "done", "已重连数据库");
     emit_migration_progress(&app_handle, "finish", "done", "迁移结束");
   }
 
@@ -113,6 +275,92 @@ pub async fn set_storage_root(
   result
 }
 
+/// 请求取消进行中的存储目录迁移；已迁移的目录会被移回原存储目录。
+/// 数据库目录迁移期间连接池处于关闭状态，这里不访问数据库，只设置取消标志
+#[tauri::command]
+pub async fn cancel_storage_migration(state: State<'_, AppState>) -> Result<bool, AppError> {
+  if !*state.migrating.lock().await {
+    return Ok(false);
+  }
+  state.migration_cancel.store(true, Ordering::SeqCst);
+  Ok(true)
+}
+
+/// 先迁移数据库目录并在新路径重连，再迁移照片等其余目录；其余目录失败或取消时数据库目录一并移回
+async fn migrate_storage_root(
+  app_handle: &AppHandle,
+  state: &AppState,
+  new_path: &str,
+  actor_operator_id: &str,
+) -> Result<(), AppError> {
+  let Some((old_root, new_root)) =
+    system_service::prepare_storage_root(&state.pool(), new_path).await?
+  else {
+    return Ok(());
+  };
+  relocate_database(state, &old_root, &new_root, Some(app_handle)).await?;
+  emit_migration_progress(app_handle, "reconnect", "done", "已重连数据库");
+
+  let mut on_progress =
+    |dir: &str, progress: fs::CopyProgress| emit_copy_progress(app_handle, dir, progress);
+  let moved = system_service::migrate_file_dirs(
+    &old_root,
+    &new_root,
+    &state.migration_cancel,
+    &mut on_progress,
+  );
+  if let Err(err) = moved {
+    let _ = relocate_database(state, &new_root, &old_root, None).await;
+    return Err(err);
+  }
+
+  system_service::finish_storage_root(&state.pool(), &old_root, &new_root, actor_operator_id)
+    .await?;
+  emit_migration_progress(app_handle, "verify", "done", "迁移完成并校验");
+  Ok(())
+}
+
+/// 关闭连接池后迁移数据库目录，并在目标路径重建连接池替换到 AppState；
+/// 迁移或重连失败时把数据库目录移回并在原路径重新打开，保证后续命令可用。
+/// 传入 app_handle 时上报进度并响应取消，回滚时不传
+async fn relocate_database(
+  state: &AppState,
+  from_root: &Path,
+  to_root: &Path,
+  app_handle: Option<&AppHandle>,
+) -> Result<(), AppError> {
+  let old_pool = state.pool();
+  // 先写回 WAL 日志，关闭后数据库文件不再被占用，可整体移动
+  db::checkpoint(&old_pool).await?;
+  old_pool.close().await;
+
+  let no_cancel = AtomicBool::new(false);
+  let cancel = if app_handle.is_some() { &state.migration_cancel } else { &no_cancel };
+  let mut on_progress = |dir: &str, progress: fs::CopyProgress| {
+    if let Some(app_handle) = app_handle {
+      emit_copy_progress(app_handle, dir, progress);
+    }
+  };
+  let moved = system_service::migrate_db_dir(from_root, to_root, cancel, &mut on_progress);
+
+  let db_file = |root: &Path| root.join("db").join("db.sqlite");
+  let result = match moved {
+    Ok(()) => match db::connect(&db_file(to_root)).await {
+      Ok(pool) => {
+        state.replace_pool(pool);
+        return Ok(());
+      }
+      Err(err) => {
+        let _ = system_service::migrate_db_dir(to_root, from_root, &no_cancel, &mut |_, _| {});
+        Err(err)
+      }
+    },
+    Err(err) => Err(err),
+  };
+  state.replace_pool(db::connect(&db_file(from_root)).await?);
+  result
+}
+
 #[derive(Debug, Deserialize)]
 pub struct SetDirInput {
   pub new_path: String,
@@ -125,7 +373,7 @@ pub async fn set_exports_dir(
   input: SetDirInput,
 ) -> Result<(), AppError> {
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
 
   let new_root = fs::normalize_path(&input.new_path)?;
   fs::ensure_not_sensitive_dir(&new_root)?;
@@ -136,12 +384,12 @@ pub async fn set_exports_dir(
 
   let audit_request = json!({"new_path": input.new_path.clone(), "actor_operator_id": actor_operator_id.clone()});
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SystemSettingsUpdate,
     None,
     Some(audit_request),
     || async {
-      meta_repo::set_meta_value(&state.pool, "exports_dir", &input.new_path).await
+      meta_repo::set_meta_value(&state.pool(), "exports_dir", &input.new_path).await
     },
   )
   .await
@@ -154,7 +402,7 @@ pub async fn set_backups_dir(
   input: SetDirInput,
 ) -> Result<(), AppError> {
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
 
   let new_root = fs::normalize_path(&input.new_path)?;
   fs::ensure_not_sensitive_dir(&new_root)?;
@@ -165,12 +413,91 @@ pub async fn set_backups_dir(
 
   let audit_request = json!({"new_path": input.new_path.clone(), "actor_operator_id": actor_operator_id.clone()});
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::SystemSettingsUpdate,
     None,
     Some(audit_request),
     || async {
-      meta_repo::set_meta_value(&state.pool, "backups_dir", &input.new_path).await
+      meta_repo::set_meta_value(&state.pool(), "backups_dir", &input.new_path).await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PhotoStorageMetricQuery {
+  pub limit: Option<i64>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn scan_photo_storage(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<photo_service::PhotoStorageScan, AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  // 写锁保护写操作
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+
+  let audit_request = json!({"action": "photo_storage_scan", "actor_operator_id": actor_operator_id.clone()});
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::MediaStorageScan,
+    None,
+    Some(audit_request),
+    || async { photo_service::scan_photo_storage(&state.pool()).await },
+  )
+  .await
+}
+
+#[tauri::command]
+pub async fn list_photo_storage_metrics(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  query: Option<PhotoStorageMetricQuery>,
+) -> Result<Vec<photo_repo::PhotoStorageMetricRow>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let limit = query.and_then(|query| query.limit);
+
+  let audit_request = json!({"action": "photo_storage_metrics", "actor_operator_id": actor_operator_id.clone()});
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::MediaStorageMetricList,
+    None,
+    Some(audit_request),
+    || async { photo_service::list_photo_storage_metrics(&state.pool(), limit).await },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PerformanceStatsQuery {
+  // 返回当前统计后清零，便于观察某段操作的耗时
+  pub reset: Option<bool>,
+}
+
+#[tauri::command]
+pub async fn get_performance_stats(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  query: Option<PerformanceStatsQuery>,
+) -> Result<PerformanceStats, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let reset = query.and_then(|query| query.reset).unwrap_or(false);
+
+  let audit_request = json!({"action": "performance_stats", "reset": reset, "actor_operator_id": actor_operator_id.clone()});
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::PerformanceStatsView,
+    None,
+    Some(audit_request),
+    || async {
+      let stats = metrics::snapshot();
+      if reset {
+        metrics::reset();
+      }
+      Ok(stats)
     },
   )
   .await
@@ -186,4 +513,116 @@ fn emit_migration_progress(
     "storage_migration_progress",
     json!({ "step": step, "status": status, "message": message }),
   );
-}
\ No newline at end of file
+}
+
+/// 目录复制进度，每迁移完一个文件上报一次
+fn emit_copy_progress(app_handle: &AppHandle, dir: &str, progress: fs::CopyProgress) {
+  let _ = app_handle.emit(
+    "storage_migration_progress",
+    json!({
+      "step": "migrate",
+      "status": "progress",
+      "message": format!("正在迁移 {}", dir),
+      "dir": dir,
+      "current": progress.current.to_string_lossy(),
+      "files_done": progress.files_done,
+      "files_total": progress.files_total,
+      "bytes_done": progress.bytes_done,
+      "bytes_total": progress.bytes_total
+    }),
+  );
+}
+#[derive(Debug, Deserialize)]
+pub struct SendTestEmailInput {
+  pub to: String,
+}
+
+#[tauri::command]
+pub async fn get_smtp_config(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<system_service::SmtpConfig, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  system_service::get_smtp_config(&state.pool()).await
+}
+
+#[tauri::command]
+pub async fn set_smtp_config(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: system_service::SmtpConfigPatch,
+) -> Result<(), AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  // 审计中不记录密码
+  let audit_request = json!({
+    "host": input.host.clone(),
+    "port": input.port,
+    "tls": input.tls.clone(),
+    "from": input.from.clone(),
+    "username": input.username.clone(),
+    "password_changed": input.password.as_deref().is_some_and(|password| !password.is_empty()),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SmtpConfigUpdate,
+    None,
+    Some(audit_request),
+    || async { system_service::set_smtp_config(&state.pool(), &input).await },
+  )
+  .await
+}
+
+/// 发送测试邮件（直接发送，不经队列）
+#[tauri::command]
+pub async fn send_test_email(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  input: SendTestEmailInput,
+) -> Result<(), AppError> {
+  command_guard::ensure_not_migrating(&state).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  let audit_request = json!({
+    "to": input.to.clone(),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::SmtpTestSend,
+    None,
+    Some(audit_request),
+    || async { system_service::send_test_email(&state.pool(), &input.to).await },
+  )
+  .await
+}
+
+/// 邮件队列（最近 200 条）
+#[tauri::command]
+pub async fn list_mail_queue(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Vec<MailQueueRow>, AppError> {
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+  system_service::list_mail_queue(&state.pool()).await
+}
+
+// 队列发送进行中标记，避免后台任务与手动执行同时发送同一批邮件
+static MAIL_DELIVERING: AtomicBool = AtomicBool::new(false);
+
+/// 发送到期的队列邮件：发信期间不占用写锁，逐封加锁记录结果，有新通知时推送事件
+pub async fn deliver_due_mails(handle: &AppHandle, state: &AppState) {
+  if MAIL_DELIVERING.swap(true, Ordering::SeqCst) {
+    return;
+  }
+  let pool = state.pool();
+  for mail in system_service::due_mails(&pool).await.unwrap_or_default() {
+    let result = system_service::deliver_mail(&pool, &mail).await;
+    let _guard = state.write_lock.lock().await;
+    if system_service::record_delivery(&pool, &mail, result).await.unwrap_or(false) {
+      notification_cmd::emit_new(handle);
+    }
+  }
+  MAIL_DELIVERING.store(false, Ordering::SeqCst);
+}
@@ -5,29 +5,56 @@ use tauri::State;
 use crate::api::command_guard;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
-use crate::services::{permission_service, txn_service};
+use crate::services::idempotency_service::IdempotencyKey;
+use crate::services::{
+    approval_service, copy_service, idempotency_service, item_service, permission_service,
+    txn_service, warehouse_service,
+};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
 pub struct InboundInput {
     pub item_id: String,
     pub to_slot_id: String,
-    pub qty: i64,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub qty: f64,
     pub occurred_at: i64,
     // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
     pub operator_id: Option<String>,
     pub note: Option<String>,
+    // 入库单位成本，未填写时记录物品当前成本
+    pub unit_cost: Option<f64>,
+    // 到期日（当日零点时间戳），有保质期或校准有效期的物品录入
+    pub expires_at: Option<i64>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+    // 随流水一并保存的图片来源路径
+    #[serde(default)]
+    pub photo_paths: Vec<String>,
+    // 客户端生成的幂等键，有效期内重复提交同一键时直接返回首次生成的流水号
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct OutboundInput {
     pub item_id: String,
     pub from_slot_id: String,
-    pub qty: i64,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub qty: f64,
     pub occurred_at: i64,
     // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
     pub operator_id: Option<String>,
     pub note: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+    // 允许出库已过期库存（仅管理员）
+    #[serde(default)]
+    pub allow_expired: bool,
+    // 随流水一并保存的图片来源路径
+    #[serde(default)]
+    pub photo_paths: Vec<String>,
+    // 客户端生成的幂等键，有效期内重复提交同一键时直接返回首次生成的流水号
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -35,22 +62,79 @@ pub struct MoveInput {
     pub item_id: String,
     pub from_slot_id: String,
     pub to_slot_id: String,
-    pub qty: i64,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub qty: f64,
     pub occurred_at: i64,
     // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
     pub operator_id: Option<String>,
     pub note: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+    // 客户端生成的幂等键，有效期内重复提交同一键时直接返回首次生成的流水号
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuickTransferInput {
+    // 移动仓库（车辆、工具箱）id，另一端为其归属仓库
+    pub vehicle_warehouse_id: String,
+    // load：装车（归属仓库 → 移动仓库）/ unload：卸车（移动仓库 → 归属仓库）
+    pub direction: String,
+    pub item_id: String,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub qty: f64,
+    // 可选：归属仓库一侧与移动仓库一侧的库位，未指定时自动选择
+    pub base_slot_id: Option<String>,
+    pub vehicle_slot_id: Option<String>,
+    pub occurred_at: i64,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    pub note: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+    // 客户端生成的幂等键，有效期内重复提交同一键时直接返回首次生成的流水号
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct CountInput {
     pub item_id: String,
     pub slot_id: String,
-    pub actual_qty: i64,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub actual_qty: f64,
     pub occurred_at: i64,
     // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
     pub operator_id: Option<String>,
     pub note: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+    // 差异原因（reason_code.id），记录在调整流水上；设置要求时账实有差异必须填写
+    pub reason_id: Option<String>,
+    // 客户端生成的幂等键，有效期内重复提交同一键时直接返回首次生成的流水号
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ScrapInput {
+    pub item_id: String,
+    pub from_slot_id: String,
+    // 显示数量，可含小数（位数不超过物品 qty_precision）
+    pub qty: f64,
+    pub occurred_at: i64,
+    // 报废原因（reason_code.id），须为适用于报废的启用原因
+    pub reason_id: Option<String>,
+    // 报废冻结中的库存时传入冻结记录，报废后该冻结解除
+    pub hold_id: Option<String>,
+    // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
+    pub operator_id: Option<String>,
+    pub note: Option<String>,
+    // 粗略位置（纬度,经度），仅移动端开启位置采集时提交
+    pub location: Option<String>,
+    // 随流水一并保存的图片来源路径
+    #[serde(default)]
+    pub photo_paths: Vec<String>,
+    // 客户端生成的幂等键，有效期内重复提交同一键时直接返回首次生成的流水号
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -60,6 +144,8 @@ pub struct ReversalInput {
     // 可选的业务记录操作人（operator.id），若未提供则使用顶层的 actor_operator_id
     pub operator_id: Option<String>,
     pub note: Option<String>,
+    // 冲正原因（reason_code.id）
+    pub reason_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -72,10 +158,28 @@ pub struct TxnListInput {
     pub warehouse_id: Option<String>,
     pub rack_id: Option<String>,
     pub operator_id: Option<String>,
+    // 按原因（reason_code.id）筛选
+    pub reason_id: Option<String>,
     pub start_at: Option<i64>,
     pub end_at: Option<i64>,
     pub page_index: Option<i64>,
     pub page_size: Option<i64>,
+    // 排序字段（逗号分隔）与方向 asc/desc，见 txn_repo 白名单
+    pub sort_by: Option<String>,
+    pub sort_dir: Option<String>,
+    // 游标翻页：上一页返回的 next_after_created_at / next_after_id，提供时忽略 page_index
+    pub after_created_at: Option<i64>,
+    pub after_id: Option<String>,
+}
+
+/// 物品参数可传 id 或物品编码，统一解析为 id 后再做权限校验与记账
+async fn resolve_item_id(pool: &sqlx::SqlitePool, reference: &str) -> Result<String, AppError> {
+    Ok(txn_service::resolve_item(pool, reference).await?.id)
+}
+
+/// 库位参数可传 id 或库位编码，统一解析为 id
+async fn resolve_slot_id(pool: &sqlx::SqlitePool, reference: &str) -> Result<String, AppError> {
+    Ok(txn_service::resolve_slot(pool, reference).await?.id)
 }
 
 #[tauri::command]
@@ -84,14 +188,24 @@ pub async fn create_inbound(
     actor_operator_id: String,
     input: InboundInput,
 ) -> Result<String, AppError> {
-    command_guard::ensure_not_migrating(&state).await?;
+    command_guard::ensure_writable(&state).await?;
     permission_service::require_role_by_id(
-        &state.pool,
+        &state.pool(),
         &actor_operator_id,
         &["admin", "keeper", "member"],
     )
     .await?;
+    let mut input = input;
+    input.item_id = resolve_item_id(&state.pool(), &input.item_id).await?;
+    input.to_slot_id = resolve_slot_id(&state.pool(), &input.to_slot_id).await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.to_slot_id).await?;
     let _guard = state.write_lock.lock().await;
+    let idempotency_key = idempotency_service::normalize_key(input.idempotency_key.as_deref())?;
+    let action = AuditAction::TxnInbound.as_str();
+    if let Some(txn_no) = idempotency_service::find_replay(&state.pool(), idempotency_key.as_deref(), action).await? {
+        return Ok(txn_no);
+    }
     let audit_request = json!({
       "item_id": input.item_id.clone(),
       "to_slot_id": input.to_slot_id.clone(),
@@ -99,10 +213,14 @@ pub async fn create_inbound(
       "occurred_at": input.occurred_at,
       "actor_operator_id": actor_operator_id.clone(),
       "operator_id": input.operator_id.clone(),
-      "note": input.note.clone()
+      "note": input.note.clone(),
+      "location": input.location.clone(),
+      "unit_cost": input.unit_cost,
+      "expires_at": input.expires_at,
+      "photo_paths": input.photo_paths.clone()
     });
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnInbound,
         None,
         Some(audit_request),
@@ -112,16 +230,23 @@ pub async fn create_inbound(
                 .operator_id
                 .clone()
                 .unwrap_or_else(|| actor_operator_id.clone());
-            txn_service::create_inbound(
-                &state.pool,
+            let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+            let txn_no = txn_service::create_inbound(
+                &state.pool(),
                 &input.item_id,
                 &input.to_slot_id,
-                input.qty,
+                qty,
                 input.occurred_at,
                 &business_operator_id,
                 input.note.clone(),
+                input.unit_cost,
+                input.location.clone(),
+                input.expires_at,
+                &input.photo_paths,
+                IdempotencyKey::new(idempotency_key.as_deref(), action),
             )
-            .await
+            .await?;
+            Ok(txn_no)
         },
     )
     .await
@@ -133,14 +258,28 @@ pub async fn create_outbound(
     actor_operator_id: String,
     input: OutboundInput,
 ) -> Result<String, AppError> {
-    command_guard::ensure_not_migrating(&state).await?;
+    command_guard::ensure_writable(&state).await?;
     permission_service::require_role_by_id(
-        &state.pool,
+        &state.pool(),
         &actor_operator_id,
         &["admin", "keeper", "member"],
     )
     .await?;
+    let mut input = input;
+    input.item_id = resolve_item_id(&state.pool(), &input.item_id).await?;
+    input.from_slot_id = resolve_slot_id(&state.pool(), &input.from_slot_id).await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.from_slot_id).await?;
+    // 出库已过期库存需管理员确认
+    if input.allow_expired {
+        permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
+    }
     let _guard = state.write_lock.lock().await;
+    let idempotency_key = idempotency_service::normalize_key(input.idempotency_key.as_deref())?;
+    let action = AuditAction::TxnOutbound.as_str();
+    if let Some(txn_no) = idempotency_service::find_replay(&state.pool(), idempotency_key.as_deref(), action).await? {
+        return Ok(txn_no);
+    }
     let audit_request = json!({
       "item_id": input.item_id.clone(),
       "from_slot_id": input.from_slot_id.clone(),
@@ -148,10 +287,13 @@ pub async fn create_outbound(
       "occurred_at": input.occurred_at,
       "actor_operator_id": actor_operator_id.clone(),
       "operator_id": input.operator_id.clone(),
-      "note": input.note.clone()
+      "note": input.note.clone(),
+      "location": input.location.clone(),
+      "allow_expired": input.allow_expired,
+      "photo_paths": input.photo_paths.clone()
     });
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnOutbound,
         None,
         Some(audit_request),
@@ -160,16 +302,22 @@ pub async fn create_outbound(
                 .operator_id
                 .clone()
                 .unwrap_or_else(|| actor_operator_id.clone());
-            txn_service::create_outbound(
-                &state.pool,
+            let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+            let txn_no = txn_service::create_outbound(
+                &state.pool(),
                 &input.item_id,
                 &input.from_slot_id,
-                input.qty,
+                qty,
                 input.occurred_at,
                 &business_operator_id,
                 input.note.clone(),
+                input.location.clone(),
+                input.allow_expired,
+                &input.photo_paths,
+                IdempotencyKey::new(idempotency_key.as_deref(), action),
             )
-            .await
+            .await?;
+            Ok(txn_no)
         },
     )
     .await
@@ -181,14 +329,26 @@ pub async fn create_move(
     actor_operator_id: String,
     input: MoveInput,
 ) -> Result<String, AppError> {
-    command_guard::ensure_not_migrating(&state).await?;
+    command_guard::ensure_writable(&state).await?;
     permission_service::require_role_by_id(
-        &state.pool,
+        &state.pool(),
         &actor_operator_id,
         &["admin", "keeper", "member"],
     )
     .await?;
+    let mut input = input;
+    input.item_id = resolve_item_id(&state.pool(), &input.item_id).await?;
+    input.from_slot_id = resolve_slot_id(&state.pool(), &input.from_slot_id).await?;
+    input.to_slot_id = resolve_slot_id(&state.pool(), &input.to_slot_id).await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.from_slot_id).await?;
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.to_slot_id).await?;
     let _guard = state.write_lock.lock().await;
+    let idempotency_key = idempotency_service::normalize_key(input.idempotency_key.as_deref())?;
+    let action = AuditAction::TxnMove.as_str();
+    if let Some(txn_no) = idempotency_service::find_replay(&state.pool(), idempotency_key.as_deref(), action).await? {
+        return Ok(txn_no);
+    }
     let audit_request = json!({
       "item_id": input.item_id.clone(),
       "from_slot_id": input.from_slot_id.clone(),
@@ -197,10 +357,11 @@ pub async fn create_move(
       "occurred_at": input.occurred_at,
       "actor_operator_id": actor_operator_id.clone(),
       "operator_id": input.operator_id.clone(),
-      "note": input.note.clone()
+      "note": input.note.clone(),
+      "location": input.location.clone()
     });
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnMove,
         None,
         Some(audit_request),
@@ -209,17 +370,109 @@ pub async fn create_move(
                 .operator_id
                 .clone()
                 .unwrap_or_else(|| actor_operator_id.clone());
-            txn_service::create_move(
-                &state.pool,
+            let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+            let txn_no = txn_service::create_move(
+                &state.pool(),
                 &input.item_id,
                 &input.from_slot_id,
                 &input.to_slot_id,
-                input.qty,
+                qty,
                 input.occurred_at,
                 &business_operator_id,
                 input.note.clone(),
+                input.location.clone(),
+                IdempotencyKey::new(idempotency_key.as_deref(), action),
             )
-            .await
+            .await?;
+            Ok(txn_no)
+        },
+    )
+    .await
+}
+
+/// 移动仓库快捷调拨：按装车/卸车方向解析库位后生成移库流水
+#[tauri::command]
+pub async fn create_quick_transfer(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: QuickTransferInput,
+) -> Result<String, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    let mut input = input;
+    input.item_id = resolve_item_id(&state.pool(), &input.item_id).await?;
+    if let Some(slot) = input.base_slot_id.as_deref() {
+        input.base_slot_id = Some(resolve_slot_id(&state.pool(), slot).await?);
+    }
+    if let Some(slot) = input.vehicle_slot_id.as_deref() {
+        input.vehicle_slot_id = Some(resolve_slot_id(&state.pool(), slot).await?);
+    }
+    let _guard = state.write_lock.lock().await;
+    let idempotency_key = idempotency_service::normalize_key(input.idempotency_key.as_deref())?;
+    let action = AuditAction::TxnQuickTransfer.as_str();
+    if let Some(txn_no) = idempotency_service::find_replay(&state.pool(), idempotency_key.as_deref(), action).await? {
+        return Ok(txn_no);
+    }
+    let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+    let plan = warehouse_service::resolve_quick_transfer(
+        &state.pool(),
+        &input.vehicle_warehouse_id,
+        &input.direction,
+        &input.item_id,
+        qty,
+        input.base_slot_id.clone(),
+        input.vehicle_slot_id.clone(),
+    )
+    .await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &plan.from_slot_id).await?;
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &plan.to_slot_id).await?;
+    let note = match input.note.as_deref().map(str::trim).filter(|note| !note.is_empty()) {
+        Some(note) => format!("{}（{}）", plan.note, note),
+        None => plan.note.clone(),
+    };
+    let audit_request = json!({
+      "vehicle_warehouse_id": input.vehicle_warehouse_id.clone(),
+      "direction": input.direction.clone(),
+      "item_id": input.item_id.clone(),
+      "from_slot_id": plan.from_slot_id.clone(),
+      "to_slot_id": plan.to_slot_id.clone(),
+      "qty": input.qty,
+      "occurred_at": input.occurred_at,
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "note": note.clone(),
+      "location": input.location.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::TxnQuickTransfer,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            let txn_no = txn_service::create_move(
+                &state.pool(),
+                &input.item_id,
+                &plan.from_slot_id,
+                &plan.to_slot_id,
+                qty,
+                input.occurred_at,
+                &business_operator_id,
+                Some(note.clone()),
+                input.location.clone(),
+                IdempotencyKey::new(idempotency_key.as_deref(), action),
+            )
+            .await?;
+            Ok(txn_no)
         },
     )
     .await
@@ -231,14 +484,24 @@ pub async fn create_count(
     actor_operator_id: String,
     input: CountInput,
 ) -> Result<String, AppError> {
-    command_guard::ensure_not_migrating(&state).await?;
+    command_guard::ensure_writable(&state).await?;
     permission_service::require_role_by_id(
-        &state.pool,
+        &state.pool(),
         &actor_operator_id,
         &["admin", "keeper", "member"],
     )
     .await?;
+    let mut input = input;
+    input.item_id = resolve_item_id(&state.pool(), &input.item_id).await?;
+    input.slot_id = resolve_slot_id(&state.pool(), &input.slot_id).await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.slot_id).await?;
     let _guard = state.write_lock.lock().await;
+    let idempotency_key = idempotency_service::normalize_key(input.idempotency_key.as_deref())?;
+    let action = AuditAction::TxnCount.as_str();
+    if let Some(txn_no) = idempotency_service::find_replay(&state.pool(), idempotency_key.as_deref(), action).await? {
+        return Ok(txn_no);
+    }
     let audit_request = json!({
       "item_id": input.item_id.clone(),
       "slot_id": input.slot_id.clone(),
@@ -246,10 +509,12 @@ pub async fn create_count(
       "occurred_at": input.occurred_at,
       "actor_operator_id": actor_operator_id.clone(),
       "operator_id": input.operator_id.clone(),
-      "note": input.note.clone()
+      "note": input.note.clone(),
+      "location": input.location.clone(),
+      "reason_id": input.reason_id.clone()
     });
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnCount,
         None,
         Some(audit_request),
@@ -258,16 +523,112 @@ pub async fn create_count(
                 .operator_id
                 .clone()
                 .unwrap_or_else(|| actor_operator_id.clone());
-            txn_service::create_count(
-                &state.pool,
+            let actual_qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.actual_qty).await?;
+            // 盘点差异超过审批阈值时须有已通过的审批申请
+            let approval_id = if approval_service::count_requires_approval(
+                &state.pool(),
+                &input.item_id,
+                &input.slot_id,
+                actual_qty,
+            )
+            .await?
+            {
+                let target = approval_service::count_target(&input.item_id, &input.slot_id, actual_qty);
+                Some(approval_service::ensure_approved(&state.pool(), "count_adjust", &target, &actor_operator_id).await?)
+            } else {
+                None
+            };
+            let txn_no = txn_service::create_count(
+                &state.pool(),
                 &input.item_id,
                 &input.slot_id,
-                input.actual_qty,
+                actual_qty,
                 input.occurred_at,
                 &business_operator_id,
                 input.note.clone(),
+                input.location.clone(),
+                input.reason_id.clone(),
+                IdempotencyKey::new(idempotency_key.as_deref(), action),
             )
-            .await
+            .await?;
+            if let Some(approval_id) = approval_id {
+                approval_service::mark_executed(&state.pool(), &approval_id).await?;
+            }
+            Ok(txn_no)
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn create_scrap(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: ScrapInput,
+) -> Result<String, AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+    let mut input = input;
+    input.item_id = resolve_item_id(&state.pool(), &input.item_id).await?;
+    input.from_slot_id = resolve_slot_id(&state.pool(), &input.from_slot_id).await?;
+    // 启用 RBAC 时校验库位所属仓库在操作人可访问范围内
+    permission_service::require_slot_access(&state.pool(), &actor_operator_id, &input.from_slot_id).await?;
+    let _guard = state.write_lock.lock().await;
+    let idempotency_key = idempotency_service::normalize_key(input.idempotency_key.as_deref())?;
+    let action = AuditAction::TxnScrap.as_str();
+    if let Some(txn_no) = idempotency_service::find_replay(&state.pool(), idempotency_key.as_deref(), action).await? {
+        return Ok(txn_no);
+    }
+    let audit_request = json!({
+      "item_id": input.item_id.clone(),
+      "from_slot_id": input.from_slot_id.clone(),
+      "qty": input.qty,
+      "occurred_at": input.occurred_at,
+      "reason_id": input.reason_id.clone(),
+      "hold_id": input.hold_id.clone(),
+      "actor_operator_id": actor_operator_id.clone(),
+      "operator_id": input.operator_id.clone(),
+      "note": input.note.clone(),
+      "location": input.location.clone(),
+      "photo_paths": input.photo_paths.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::TxnScrap,
+        None,
+        Some(audit_request),
+        || async {
+            let business_operator_id = input
+                .operator_id
+                .clone()
+                .unwrap_or_else(|| actor_operator_id.clone());
+            let qty = item_service::qty_to_base(&state.pool(), &input.item_id, input.qty).await?;
+            // 报废数量超过审批阈值时须有已通过的审批申请
+            let approval_id = if approval_service::scrap_requires_approval(&state.pool(), &input.item_id, qty).await? {
+                let target = approval_service::scrap_target(&input.item_id, &input.from_slot_id, qty);
+                Some(approval_service::ensure_approved(&state.pool(), "scrap", &target, &actor_operator_id).await?)
+            } else {
+                None
+            };
+            let txn_no = txn_service::create_scrap(
+                &state.pool(),
+                &input.item_id,
+                &input.from_slot_id,
+                qty,
+                input.occurred_at,
+                &business_operator_id,
+                input.reason_id.clone(),
+                input.note.clone(),
+                input.location.clone(),
+                input.hold_id.clone(),
+                &input.photo_paths,
+                IdempotencyKey::new(idempotency_key.as_deref(), action),
+            )
+            .await?;
+            if let Some(approval_id) = approval_id {
+                approval_service::mark_executed(&state.pool(), &approval_id).await?;
+            }
+            Ok(txn_no)
         },
     )
     .await
@@ -279,18 +640,19 @@ pub async fn reverse_txn(
     actor_operator_id: String,
     input: ReversalInput,
 ) -> Result<String, AppError> {
-    command_guard::ensure_not_migrating(&state).await?;
-    permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
     let _guard = state.write_lock.lock().await;
     let audit_request = json!({
       "txn_no": input.txn_no.clone(),
       "occurred_at": input.occurred_at,
       "actor_operator_id": actor_operator_id.clone(),
       "operator_id": input.operator_id.clone(),
-      "note": input.note.clone()
+      "note": input.note.clone(),
+      "reason_id": input.reason_id.clone()
     });
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnReversal,
         None,
         Some(audit_request),
@@ -299,14 +661,24 @@ pub async fn reverse_txn(
                 .operator_id
                 .clone()
                 .unwrap_or_else(|| actor_operator_id.clone());
-            txn_service::reverse_txn(
-                &state.pool,
+            let approval_id = if approval_service::reversal_requires_approval(&state.pool()).await? {
+                Some(approval_service::ensure_approved(&state.pool(), "reversal", &input.txn_no, &actor_operator_id).await?)
+            } else {
+                None
+            };
+            let txn_no = txn_service::reverse_txn(
+                &state.pool(),
                 &input.txn_no,
                 input.occurred_at,
                 &business_operator_id,
                 input.note.clone(),
+                input.reason_id.clone(),
             )
-            .await
+            .await?;
+            if let Some(approval_id) = approval_id {
+                approval_service::mark_executed(&state.pool(), &approval_id).await?;
+            }
+            Ok(txn_no)
         },
     )
     .await
@@ -319,13 +691,15 @@ pub async fn list_txns(
     input: TxnListInput,
 ) -> Result<txn_service::TxnListResult, AppError> {
     permission_service::require_role_by_id(
-        &state.pool,
+        &state.pool(),
         &actor_operator_id,
         &["admin", "keeper", "viewer", "member"],
     )
     .await?;
+    let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnList,
         None,
         Some(json!({
@@ -337,12 +711,15 @@ pub async fn list_txns(
           "warehouse_id": input.warehouse_id.clone(),
           "rack_id": input.rack_id.clone(),
           "operator_id": input.operator_id.clone(),
+          "reason_id": input.reason_id.clone(),
           "start_at": input.start_at,
-          "end_at": input.end_at
+          "end_at": input.end_at,
+          "sort_by": input.sort_by.clone(),
+          "sort_dir": input.sort_dir.clone()
         })),
         || async {
             txn_service::list_txns(
-                &state.pool,
+                &state.pool(),
                 input.txn_type.clone(),
                 input.keyword.clone(),
                 input.item_id.clone(),
@@ -350,10 +727,16 @@ pub async fn list_txns(
                 input.warehouse_id.clone(),
                 input.rack_id.clone(),
                 input.operator_id.clone(),
+                input.reason_id.clone(),
                 input.start_at,
                 input.end_at,
+                allowed_warehouse_ids.clone(),
                 input.page_index.clone().unwrap_or(1),
                 input.page_size.clone().unwrap_or(20),
+                input.sort_by.clone(),
+                input.sort_dir.clone(),
+                input.after_created_at,
+                input.after_id.clone(),
             )
             .await
         },
@@ -369,13 +752,16 @@ pub async fn export_txns(
 ) -> Result<txn_service::TxnExportResult, AppError> {
     command_guard::ensure_not_migrating(&state).await?;
     permission_service::require_role_by_id(
-        &state.pool,
+        &state.pool(),
         &actor_operator_id,
         &["admin", "keeper", "viewer"],
     )
     .await?;
+    permission_service::require_capability(&state.pool(), &actor_operator_id, "can_export").await?;
+    let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
     command_guard::run_with_audit(
-        &state.pool,
+        &state.pool(),
         AuditAction::TxnExport,
         None,
         Some(json!({
@@ -387,12 +773,13 @@ pub async fn export_txns(
           "warehouse_id": input.warehouse_id.clone(),
           "rack_id": input.rack_id.clone(),
           "operator_id": input.operator_id.clone(),
+          "reason_id": input.reason_id.clone(),
           "start_at": input.start_at,
           "end_at": input.end_at,
         })),
         || async {
             txn_service::export_txns(
-                &state.pool,
+                &state.pool(),
                 input.txn_type.clone(),
                 input.keyword.clone(),
                 input.item_id.clone(),
@@ -400,11 +787,84 @@ pub async fn export_txns(
                 input.warehouse_id.clone(),
                 input.rack_id.clone(),
                 input.operator_id.clone(),
+                input.reason_id.clone(),
                 input.start_at,
-                input.end_at
+                input.end_at,
+                allowed_warehouse_ids.clone(),
             )
             .await
         },
     )
     .await
 }
+
+#[derive(Debug, Deserialize)]
+pub struct CopyTxnInput {
+    pub txn_no: String,
+    // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn copy_txn(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: CopyTxnInput,
+) -> Result<copy_service::CopyPayload, AppError> {
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "viewer", "member"],
+    )
+    .await?;
+    let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::TxnCopy,
+        None,
+        Some(json!({
+          "actor_operator_id": actor_operator_id.clone(),
+          "txn_no": input.txn_no.clone()
+        })),
+        || async {
+            copy_service::copy_txn(&state.pool(), &input.txn_no, allowed_warehouse_ids.clone()).await
+        },
+    )
+    .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SelectKioskOperatorInput {
+    // 扫码或手动选择的业务记录人
+    pub operator_id: String,
+    // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn select_kiosk_operator(
+    state: State<'_, AppState>,
+    actor_operator_id: String,
+    input: SelectKioskOperatorInput,
+) -> Result<(), AppError> {
+    command_guard::ensure_writable(&state).await?;
+    permission_service::require_role_by_id(
+        &state.pool(),
+        &actor_operator_id,
+        &["admin", "keeper", "member"],
+    )
+    .await?;
+    // 写锁保护写操作
+    let _guard = state.write_lock.lock().await;
+    let audit_request = json!({
+      "operator_id": input.operator_id.clone(),
+      "actor_operator_id": actor_operator_id.clone()
+    });
+    command_guard::run_with_audit(
+        &state.pool(),
+        AuditAction::KioskOperatorSelect,
+        None,
+        Some(audit_request),
+        || async { txn_service::select_kiosk_operator(&state.pool(), &input.operator_id).await },
+    )
+    .await
+}
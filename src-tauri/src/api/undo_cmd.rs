@@ -0,0 +1,57 @@
+use serde_json::json;
+use tauri::State;
+
+use crate::api::command_guard;
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::AppError;
+use crate::repo::undo_repo::UndoEntryRow;
+use crate::services::{permission_service, undo_service};
+use crate::state::AppState;
+
+/// 当前操作人最近一次可撤销的主数据修改，无则返回 null
+#[tauri::command]
+pub async fn get_last_change(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<Option<UndoEntryRow>, AppError> {
+  permission_service::require_role_by_id(
+    &state.pool(),
+    &actor_operator_id,
+    &["admin", "keeper", "viewer", "member"],
+  )
+  .await?;
+  undo_service::last_change(&state.pool(), &actor_operator_id).await
+}
+
+/// 撤销当前操作人最近一次主数据修改，审计记录被撤销的原操作与摘要
+#[tauri::command]
+pub async fn undo_last_change(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+) -> Result<undo_service::UndoResult, AppError> {
+  command_guard::ensure_writable(&state).await?;
+  let _guard = state.write_lock.lock().await;
+  let entry = undo_service::last_change(&state.pool(), &actor_operator_id).await?;
+  // 按原操作所需权限校验：物品修改允许 keeper，货架/仓库修改仅管理员
+  match entry.as_ref() {
+    Some(entry) if entry.action.starts_with("ITEM_") => {
+      permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper"]).await?;
+    }
+    Some(_) => permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?,
+    None => {}
+  }
+  let audit_request = json!({
+    "entry_id": entry.as_ref().map(|value| value.id.clone()),
+    "undo_action": entry.as_ref().map(|value| value.action.clone()),
+    "summary": entry.as_ref().map(|value| value.summary.clone()),
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::UndoChange,
+    None,
+    Some(audit_request),
+    || async { undo_service::undo_last_change(&state.pool(), &actor_operator_id).await },
+  )
+  .await
+}
@@ -5,13 +5,18 @@ use tauri::State;
 use crate::api::command_guard;
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::AppError;
-use crate::services::{permission_service, warehouse_service};
+use crate::services::{permission_service, structure_tree_service, undo_service, warehouse_service};
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
 pub struct CreateWarehouseInput {
   pub code: String,
   pub name: String,
+  // 仓库类型：fixed（默认）/ mobile
+  pub kind: Option<String>,
+  // 移动仓库的归属仓库与格口数
+  pub base_warehouse_id: Option<String>,
+  pub compartments: Option<i64>,
   // actor_operator_id provided as top-level arg
 }
 
@@ -45,15 +50,15 @@ pub async fn list_warehouses(
   input: ListWarehouseQuery,
 ) -> Result<warehouse_service::WarehouseListResult, AppError> {
   let audit_request = json!({ "actor_operator_id": actor_operator_id.clone() });
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::WarehouseList,
     None,
     Some(audit_request),
     || async {
       warehouse_service::list_warehouses(
-        &state.pool,
+        &state.pool(),
         input.keyword.clone(),
         input.status.clone(),
         input.page_index,
@@ -71,20 +76,33 @@ pub async fn create_warehouse(
   actor_operator_id: String,
   input: CreateWarehouseInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "code": input.code.clone(),
     "name": input.name.clone(),
+    "kind": input.kind.clone(),
+    "base_warehouse_id": input.base_warehouse_id.clone(),
+    "compartments": input.compartments,
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::WarehouseCreate,
     None,
     Some(audit_request),
-    || async { warehouse_service::create_warehouse(&state.pool, &input.code, &input.name).await },
+    || async {
+      warehouse_service::create_warehouse(
+        &state.pool(),
+        &input.code,
+        &input.name,
+        input.kind.as_deref(),
+        input.base_warehouse_id.clone(),
+        input.compartments,
+      )
+      .await
+    },
   )
   .await
 }
@@ -95,20 +113,24 @@ pub async fn update_warehouse(
   actor_operator_id: String,
   input: UpdateWarehouseInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "name": input.name.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::WarehouseUpdate,
     None,
     Some(audit_request),
-    || async { warehouse_service::update_warehouse(&state.pool, &input.id, &input.name).await },
+    || async {
+      let before = undo_service::capture_warehouse(&state.pool(), &input.id).await?;
+      warehouse_service::update_warehouse(&state.pool(), &input.id, &input.name).await?;
+      undo_service::record_warehouse(&state.pool(), &actor_operator_id, AuditAction::WarehouseUpdate, before).await
+    },
   )
   .await
 }
@@ -119,21 +141,23 @@ pub async fn set_warehouse_status(
   actor_operator_id: String,
   input: UpdateWarehouseStatusInput,
 ) -> Result<(), AppError> {
-  command_guard::ensure_not_migrating(&state).await?;
+  command_guard::ensure_writable(&state).await?;
   let _guard = state.write_lock.lock().await;
-  permission_service::require_admin_by_id(&state.pool, &actor_operator_id).await?;
+  permission_service::require_admin_by_id(&state.pool(), &actor_operator_id).await?;
   let audit_request = json!({
     "id": input.id.clone(),
     "status": input.status.clone(),
     "actor_operator_id": actor_operator_id.clone()
   });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::WarehouseStatus,
     None,
     Some(audit_request),
     || async {
-      warehouse_service::set_warehouse_status(&state.pool, &input.id, &input.status).await
+      let before = undo_service::capture_warehouse(&state.pool(), &input.id).await?;
+      warehouse_service::set_warehouse_status(&state.pool(), &input.id, &input.status).await?;
+      undo_service::record_warehouse(&state.pool(), &actor_operator_id, AuditAction::WarehouseStatus, before).await
     },
   )
   .await
@@ -151,18 +175,18 @@ pub async fn get_warehouse(
   actor_operator_id: String,
   input: GetWarehouseInput,
 ) -> Result<Option<crate::repo::warehouse_repo::WarehouseRow>, AppError> {
-  permission_service::require_role_by_id(&state.pool, &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
   let audit_request = json!({ "id": input.id.clone(), "code": input.code.clone(), "actor_operator_id": actor_operator_id.clone() });
   command_guard::run_with_audit(
-    &state.pool,
+    &state.pool(),
     AuditAction::WarehouseList,
     None,
     Some(audit_request),
     || async {
-      if let Some(id) = input.id {
-        crate::repo::warehouse_repo::get_warehouse_by_id(&state.pool, &id).await
-      } else if let Some(code) = input.code {
-        crate::repo::warehouse_repo::get_warehouse_by_code(&state.pool, &code).await
+      if let Some(id) = &input.id {
+        crate::repo::warehouse_repo::get_warehouse_by_id(&state.pool(), id).await
+      } else if let Some(code) = &input.code {
+        crate::repo::warehouse_repo::get_warehouse_by_code(&state.pool(), code).await
       } else {
         Ok(None)
       }
@@ -170,3 +194,80 @@ pub async fn get_warehouse(
   )
   .await
 }
+
+#[derive(Debug, Deserialize)]
+pub struct WarehouseUtilizationQuery {
+  pub warehouse_id: Option<String>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn get_warehouse_utilization(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  query: Option<WarehouseUtilizationQuery>,
+) -> Result<warehouse_service::WarehouseUtilizationReport, AppError> {
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let warehouse_id = query.and_then(|query| query.warehouse_id);
+  let audit_request = json!({ "id": warehouse_id.clone(), "actor_operator_id": actor_operator_id.clone() });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::WarehouseUtilization,
+    None,
+    Some(audit_request),
+    || async {
+      let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+      warehouse_service::get_warehouse_utilization(
+        &state.pool(),
+        warehouse_id.clone(),
+        allowed_warehouse_ids,
+      )
+      .await
+    },
+  )
+  .await
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StructureTreeQuery {
+  pub warehouse_id: Option<String>,
+  // 是否返回各货架下的库位明细，默认仅返回库位数
+  pub include_slots: Option<bool>,
+  // actor_operator_id provided as top-level arg
+}
+
+#[tauri::command]
+pub async fn get_structure_tree(
+  state: State<'_, AppState>,
+  actor_operator_id: String,
+  query: Option<StructureTreeQuery>,
+) -> Result<structure_tree_service::StructureTree, AppError> {
+  permission_service::require_role_by_id(&state.pool(), &actor_operator_id, &["admin", "keeper", "viewer", "member"]).await?;
+  let warehouse_id = query.as_ref().and_then(|query| query.warehouse_id.clone());
+  let include_slots = query.and_then(|query| query.include_slots).unwrap_or(false);
+  let audit_request = json!({
+    "id": warehouse_id.clone(),
+    "include_slots": include_slots,
+    "actor_operator_id": actor_operator_id.clone()
+  });
+  command_guard::run_with_audit(
+    &state.pool(),
+    AuditAction::StructureTreeView,
+    None,
+    Some(audit_request),
+    || async {
+      let allowed_warehouse_ids =
+        permission_service::allowed_warehouse_ids(&state.pool(), &actor_operator_id).await?;
+      structure_tree_service::get_structure_tree(
+        &state.pool(),
+        &state.structure_tree_cache,
+        warehouse_id.clone(),
+        include_slots,
+        allowed_warehouse_ids,
+      )
+      .await
+    },
+  )
+  .await
+}
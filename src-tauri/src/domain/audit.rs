@@ -1,28 +1,85 @@
+/// 审计级别（用于按审计详略配置决定是否记录）
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AuditLevel {
+  Read,
+  Write,
+  Critical,
+}
+
 #[derive(Clone, Copy)]
 pub enum AuditAction {
   AuthLogin,
   AuthLogout,
   AuthChangePassword,
   AuthResetPassword,
+  AuthLockout,
+  AuthUnlock,
+  AuthSwitchOperator,
+  AuthSetPin,
+  AuthIssueResetCode,
+  AuthResetPasswordWithCode,
+  KioskOperatorSelect,
   OperatorList,
   OperatorCreate,
   OperatorUpdate,
   OperatorStatus,
+  OperatorWarehouseUpdate,
+  OperatorRolePreview,
+  OperatorRoleBulkUpdate,
+  OperatorPermissionSimulate,
+  OperatorProfileView,
+  OperatorProfileUpdate,
+  RoleCapabilityList,
+  RoleCapabilityUpdate,
+  SessionList,
+  SessionTerminate,
   WarehouseList,
   WarehouseCreate,
   WarehouseUpdate,
   WarehouseStatus,
+  WarehouseUtilization,
+  StructureTreeView,
   RackList,
   RackCreate,
+  RackClone,
   RackUpdate,
   RackStatus,
+  RackMapView,
+  RackHeatmapView,
+  RackMapExport,
   SlotList,
   SlotRegen,
   SlotStatus,
+  SlotBulkStatus,
+  SlotRecode,
   ItemList,
+  ItemDetailView,
   ItemCreate,
   ItemUpdate,
   ItemStatus,
+  ItemBulkStatus,
+  ItemBulkUpdate,
+  ItemMerge,
+  ItemCodeChange,
+  ItemAliasList,
+  ItemAliasCreate,
+  ItemAliasUpdate,
+  ItemAliasDelete,
+  ScanResolve,
+  ItemAttributeDefList,
+  ItemAttributeDefCreate,
+  ItemAttributeDefUpdate,
+  ItemAttributeDefDelete,
+  ReasonCodeList,
+  ReasonCodeCreate,
+  ReasonCodeUpdate,
+  ReasonCodeDelete,
+  UndoChange,
+  ApprovalList,
+  ApprovalSubmit,
+  ApprovalApprove,
+  ApprovalReject,
+  NotificationMarkRead,
   MediaAttachmentItemAdd,
   MediaAttachmentItemList,
   MediaAttachmentItemRemove,
@@ -35,24 +92,97 @@ pub enum AuditAction {
   TxnInbound,
   TxnOutbound,
   TxnMove,
+  TxnQuickTransfer,
   TxnCount,
+  TxnScrap,
   TxnReversal,
   TxnList,
+  LoanCreate,
+  LoanReturn,
+  LoanList,
+  OutboundPickCreate,
+  OutboundPickConfirm,
+  OutboundPickCancel,
+  OutboundPickList,
+  AsnCreate,
+  AsnReceive,
+  AsnClose,
+  AsnCancel,
+  AsnList,
+  AsnVarianceReport,
   SystemSettingsUpdate,
+  SystemReadOnlyUpdate,
   SystemSettingsRead,
   SystemStorageRootChange,
+  MediaStorageScan,
+  MediaStorageMetricList,
+  PerformanceStatsView,
   AuditList,
   AuditExport,
+  AuditArchive,
+  AuditArchiveSearch,
   StockListBySlot,
   StockListByItem,
   StockExport,
+  StockValuation,
+  StockRecalculate,
+  StockCorrect,
+  StockClose,
+  StockAsof,
   DbBackup,
   DbRestore,
+  DbVerify,
+  DbQueryPlanCheck,
+  DbRepair,
+  DiagnosticsCollect,
+  RemoteBackupConfigUpdate,
+  RemoteBackupList,
+  BackupEncryptionUpdate,
+  BackupEncryptExisting,
+  DemoDataGenerate,
   ItemExport,
   ItemImport,
   TxnExport,
   TxnImport,
+  StructureImport,
+  MasterBundleExport,
+  MasterBundleImport,
+  OpeningStockImport,
+  CountSheetExport,
+  CountSheetImport,
+  PickListPrint,
+  LabelTemplateList,
+  LabelTemplateCreate,
+  LabelTemplateUpdate,
+  LabelTemplateDelete,
+  LabelPrint,
+  TxnCopy,
+  StockCopy,
   DashboardOverview,
+  ReportItemStockTrend,
+  ReportStockAging,
+  ReportStockAgingExport,
+  ReportLowStock,
+  ReportExpiringStock,
+  ReportTxnReasons,
+  StockThresholdList,
+  StockThresholdSet,
+  StockThresholdDelete,
+  StockHoldList,
+  StockHoldCreate,
+  StockHoldRelease,
+  CycleCountPlanList,
+  CycleCountPlanCreate,
+  CycleCountPlanUpdate,
+  CycleCountPlanDelete,
+  CycleCountTaskList,
+  CycleCountTaskSkip,
+  ReportScheduleCreate,
+  ReportScheduleUpdate,
+  ReportScheduleDelete,
+  ReportScheduleRun,
+  SmtpConfigUpdate,
+  SmtpTestSend,
 }
 
 impl AuditAction {
@@ -63,25 +193,74 @@ impl AuditAction {
       AuditAction::AuthLogout => "AUTH_LOGOUT",
       AuditAction::AuthChangePassword => "AUTH_CHANGE_PASSWORD",
       AuditAction::AuthResetPassword => "AUTH_RESET_PASSWORD",
+      AuditAction::AuthLockout => "AUTH_LOCKOUT",
+      AuditAction::AuthUnlock => "AUTH_UNLOCK",
+      AuditAction::AuthSwitchOperator => "AUTH_SWITCH_OPERATOR",
+      AuditAction::AuthSetPin => "AUTH_SET_PIN",
+      AuditAction::AuthIssueResetCode => "AUTH_ISSUE_RESET_CODE",
+      AuditAction::AuthResetPasswordWithCode => "AUTH_RESET_PASSWORD_WITH_CODE",
+      AuditAction::KioskOperatorSelect => "KIOSK_OPERATOR_SELECT",
       AuditAction::OperatorList => "OPERATOR_LIST",
       AuditAction::OperatorCreate => "OPERATOR_CREATE",
       AuditAction::OperatorUpdate => "OPERATOR_UPDATE",
       AuditAction::OperatorStatus => "OPERATOR_STATUS",
+      AuditAction::OperatorWarehouseUpdate => "OPERATOR_WAREHOUSE_UPDATE",
+      AuditAction::OperatorRolePreview => "OPERATOR_ROLE_PREVIEW",
+      AuditAction::OperatorRoleBulkUpdate => "OPERATOR_ROLE_BULK_UPDATE",
+      AuditAction::OperatorPermissionSimulate => "OPERATOR_PERMISSION_SIMULATE",
+      AuditAction::OperatorProfileView => "OPERATOR_PROFILE_VIEW",
+      AuditAction::OperatorProfileUpdate => "OPERATOR_PROFILE_UPDATE",
+      AuditAction::RoleCapabilityList => "ROLE_CAPABILITY_LIST",
+      AuditAction::RoleCapabilityUpdate => "ROLE_CAPABILITY_UPDATE",
+      AuditAction::SessionList => "SESSION_LIST",
+      AuditAction::SessionTerminate => "SESSION_TERMINATE",
       AuditAction::WarehouseList => "WAREHOUSE_LIST",
       AuditAction::WarehouseCreate => "WAREHOUSE_CREATE",
       AuditAction::WarehouseUpdate => "WAREHOUSE_UPDATE",
       AuditAction::WarehouseStatus => "WAREHOUSE_STATUS",
+      AuditAction::WarehouseUtilization => "WAREHOUSE_UTILIZATION",
+      AuditAction::StructureTreeView => "STRUCTURE_TREE_VIEW",
       AuditAction::RackList => "RACK_LIST",
       AuditAction::RackCreate => "RACK_CREATE",
+      AuditAction::RackClone => "RACK_CLONE",
       AuditAction::RackUpdate => "RACK_UPDATE",
       AuditAction::RackStatus => "RACK_STATUS",
+      AuditAction::RackMapView => "RACK_MAP_VIEW",
+      AuditAction::RackHeatmapView => "RACK_HEATMAP_VIEW",
+      AuditAction::RackMapExport => "RACK_MAP_EXPORT",
       AuditAction::SlotList => "SLOT_LIST",
       AuditAction::SlotRegen => "SLOT_REGEN",
       AuditAction::SlotStatus => "SLOT_STATUS",
+      AuditAction::SlotBulkStatus => "SLOT_BULK_STATUS",
+      AuditAction::SlotRecode => "SLOT_RECODE",
       AuditAction::ItemList => "ITEM_LIST",
+      AuditAction::ItemDetailView => "ITEM_DETAIL_VIEW",
       AuditAction::ItemCreate => "ITEM_CREATE",
       AuditAction::ItemUpdate => "ITEM_UPDATE",
       AuditAction::ItemStatus => "ITEM_STATUS",
+      AuditAction::ItemBulkStatus => "ITEM_BULK_STATUS",
+      AuditAction::ItemBulkUpdate => "ITEM_BULK_UPDATE",
+      AuditAction::ItemMerge => "ITEM_MERGE",
+      AuditAction::ItemCodeChange => "ITEM_CODE_CHANGE",
+      AuditAction::ItemAliasList => "ITEM_ALIAS_LIST",
+      AuditAction::ItemAliasCreate => "ITEM_ALIAS_CREATE",
+      AuditAction::ItemAliasUpdate => "ITEM_ALIAS_UPDATE",
+      AuditAction::ItemAliasDelete => "ITEM_ALIAS_DELETE",
+      AuditAction::ScanResolve => "SCAN_RESOLVE",
+      AuditAction::ItemAttributeDefList => "ITEM_ATTRIBUTE_DEF_LIST",
+      AuditAction::ItemAttributeDefCreate => "ITEM_ATTRIBUTE_DEF_CREATE",
+      AuditAction::ItemAttributeDefUpdate => "ITEM_ATTRIBUTE_DEF_UPDATE",
+      AuditAction::ItemAttributeDefDelete => "ITEM_ATTRIBUTE_DEF_DELETE",
+      AuditAction::ReasonCodeList => "REASON_CODE_LIST",
+      AuditAction::ReasonCodeCreate => "REASON_CODE_CREATE",
+      AuditAction::ReasonCodeUpdate => "REASON_CODE_UPDATE",
+      AuditAction::ReasonCodeDelete => "REASON_CODE_DELETE",
+      AuditAction::UndoChange => "UNDO_CHANGE",
+      AuditAction::ApprovalList => "APPROVAL_LIST",
+      AuditAction::ApprovalSubmit => "APPROVAL_SUBMIT",
+      AuditAction::ApprovalApprove => "APPROVAL_APPROVE",
+      AuditAction::ApprovalReject => "APPROVAL_REJECT",
+      AuditAction::NotificationMarkRead => "NOTIFICATION_MARK_READ",
       AuditAction::MediaAttachmentItemAdd => "MEDIA_ATTACHMENT_ITEM_ADD",
       AuditAction::MediaAttachmentItemList => "MEDIA_ATTACHMENT_ITEM_LIST",
       AuditAction::MediaAttachmentItemRemove => "MEDIA_ATTACHMENT_ITEM_REMOVE",
@@ -94,24 +273,279 @@ impl AuditAction {
       AuditAction::TxnInbound => "TXN_INBOUND",
       AuditAction::TxnOutbound => "TXN_OUTBOUND",
       AuditAction::TxnMove => "TXN_MOVE",
+      AuditAction::TxnQuickTransfer => "TXN_QUICK_TRANSFER",
       AuditAction::TxnCount => "TXN_COUNT",
+      AuditAction::TxnScrap => "TXN_SCRAP",
       AuditAction::TxnReversal => "TXN_REVERSAL",
       AuditAction::TxnList => "TXN_LIST",
+      AuditAction::LoanCreate => "LOAN_CREATE",
+      AuditAction::LoanReturn => "LOAN_RETURN",
+      AuditAction::LoanList => "LOAN_LIST",
+      AuditAction::OutboundPickCreate => "OUTBOUND_PICK_CREATE",
+      AuditAction::OutboundPickConfirm => "OUTBOUND_PICK_CONFIRM",
+      AuditAction::OutboundPickCancel => "OUTBOUND_PICK_CANCEL",
+      AuditAction::OutboundPickList => "OUTBOUND_PICK_LIST",
+      AuditAction::AsnCreate => "ASN_CREATE",
+      AuditAction::AsnReceive => "ASN_RECEIVE",
+      AuditAction::AsnClose => "ASN_CLOSE",
+      AuditAction::AsnCancel => "ASN_CANCEL",
+      AuditAction::AsnList => "ASN_LIST",
+      AuditAction::AsnVarianceReport => "ASN_VARIANCE_REPORT",
       AuditAction::SystemSettingsUpdate => "SYSTEM_SETTINGS_UPDATE",
+      AuditAction::SystemReadOnlyUpdate => "SYSTEM_READ_ONLY_UPDATE",
       AuditAction::SystemSettingsRead => "SYSTEM_SETTINGS_READ",
       AuditAction::SystemStorageRootChange => "SYSTEM_STORAGE_ROOT_CHANGE",
+      AuditAction::MediaStorageScan => "MEDIA_STORAGE_SCAN",
+      AuditAction::MediaStorageMetricList => "MEDIA_STORAGE_METRIC_LIST",
+      AuditAction::PerformanceStatsView => "PERFORMANCE_STATS_VIEW",
       AuditAction::AuditList => "AUDIT_LIST",
       AuditAction::AuditExport => "AUDIT_EXPORT",
+      AuditAction::AuditArchive => "AUDIT_ARCHIVE",
+      AuditAction::AuditArchiveSearch => "AUDIT_ARCHIVE_SEARCH",
       AuditAction::StockListBySlot => "STOCK_LIST_BY_SLOT",
       AuditAction::StockListByItem => "STOCK_LIST_BY_ITEM",
       AuditAction::StockExport => "STOCK_EXPORT",
+      AuditAction::StockValuation => "STOCK_VALUATION",
+      AuditAction::StockRecalculate => "STOCK_RECALCULATE",
+      AuditAction::StockCorrect => "STOCK_CORRECT",
+      AuditAction::StockClose => "STOCK_CLOSE",
+      AuditAction::StockAsof => "STOCK_ASOF",
       AuditAction::DbBackup => "DB_BACKUP",
       AuditAction::DbRestore => "DB_RESTORE",
+      AuditAction::DbVerify => "DB_VERIFY",
+      AuditAction::DbQueryPlanCheck => "DB_QUERY_PLAN_CHECK",
+      AuditAction::DbRepair => "DB_REPAIR",
+      AuditAction::DiagnosticsCollect => "DIAGNOSTICS_COLLECT",
+      AuditAction::RemoteBackupConfigUpdate => "REMOTE_BACKUP_CONFIG_UPDATE",
+      AuditAction::RemoteBackupList => "REMOTE_BACKUP_LIST",
+      AuditAction::BackupEncryptionUpdate => "BACKUP_ENCRYPTION_UPDATE",
+      AuditAction::BackupEncryptExisting => "BACKUP_ENCRYPT_EXISTING",
+      AuditAction::DemoDataGenerate => "DEMO_DATA_GENERATE",
       AuditAction::ItemExport => "ITEM_EXPORT",
       AuditAction::ItemImport => "ITEM_IMPORT",
       AuditAction::TxnExport => "TXN_EXPORT",
       AuditAction::TxnImport => "TXN_IMPORT",
+      AuditAction::StructureImport => "STRUCTURE_IMPORT",
+      AuditAction::MasterBundleExport => "MASTER_BUNDLE_EXPORT",
+      AuditAction::MasterBundleImport => "MASTER_BUNDLE_IMPORT",
+      AuditAction::OpeningStockImport => "OPENING_STOCK_IMPORT",
+      AuditAction::CountSheetExport => "COUNT_SHEET_EXPORT",
+      AuditAction::CountSheetImport => "COUNT_SHEET_IMPORT",
+      AuditAction::PickListPrint => "PICK_LIST_PRINT",
+      AuditAction::LabelTemplateList => "LABEL_TEMPLATE_LIST",
+      AuditAction::LabelTemplateCreate => "LABEL_TEMPLATE_CREATE",
+      AuditAction::LabelTemplateUpdate => "LABEL_TEMPLATE_UPDATE",
+      AuditAction::LabelTemplateDelete => "LABEL_TEMPLATE_DELETE",
+      AuditAction::LabelPrint => "LABEL_PRINT",
+      AuditAction::TxnCopy => "TXN_COPY",
+      AuditAction::StockCopy => "STOCK_COPY",
       AuditAction::DashboardOverview => "DASHBOARD_OVERVIEW",
+      AuditAction::ReportItemStockTrend => "REPORT_ITEM_STOCK_TREND",
+      AuditAction::ReportStockAging => "REPORT_STOCK_AGING",
+      AuditAction::ReportStockAgingExport => "REPORT_STOCK_AGING_EXPORT",
+      AuditAction::ReportLowStock => "REPORT_LOW_STOCK",
+      AuditAction::ReportExpiringStock => "REPORT_EXPIRING_STOCK",
+      AuditAction::ReportTxnReasons => "REPORT_TXN_REASONS",
+      AuditAction::StockThresholdList => "STOCK_THRESHOLD_LIST",
+      AuditAction::StockThresholdSet => "STOCK_THRESHOLD_SET",
+      AuditAction::StockThresholdDelete => "STOCK_THRESHOLD_DELETE",
+      AuditAction::StockHoldList => "STOCK_HOLD_LIST",
+      AuditAction::StockHoldCreate => "STOCK_HOLD_CREATE",
+      AuditAction::StockHoldRelease => "STOCK_HOLD_RELEASE",
+      AuditAction::CycleCountPlanList => "CYCLE_COUNT_PLAN_LIST",
+      AuditAction::CycleCountPlanCreate => "CYCLE_COUNT_PLAN_CREATE",
+      AuditAction::CycleCountPlanUpdate => "CYCLE_COUNT_PLAN_UPDATE",
+      AuditAction::CycleCountPlanDelete => "CYCLE_COUNT_PLAN_DELETE",
+      AuditAction::CycleCountTaskList => "CYCLE_COUNT_TASK_LIST",
+      AuditAction::CycleCountTaskSkip => "CYCLE_COUNT_TASK_SKIP",
+      AuditAction::ReportScheduleCreate => "REPORT_SCHEDULE_CREATE",
+      AuditAction::ReportScheduleUpdate => "REPORT_SCHEDULE_UPDATE",
+      AuditAction::ReportScheduleDelete => "REPORT_SCHEDULE_DELETE",
+      AuditAction::ReportScheduleRun => "REPORT_SCHEDULE_RUN",
+      AuditAction::SmtpConfigUpdate => "SMTP_CONFIG_UPDATE",
+      AuditAction::SmtpTestSend => "SMTP_TEST_SEND",
+    }
+  }
+
+  /// 审计级别：查询类、一般写操作、关键操作（认证/人员/系统/冲正/导入/备份恢复）
+  pub fn level(&self) -> AuditLevel {
+    match self {
+      AuditAction::OperatorList
+      | AuditAction::OperatorRolePreview
+      | AuditAction::OperatorPermissionSimulate
+      | AuditAction::OperatorProfileView
+      | AuditAction::RoleCapabilityList
+      | AuditAction::SessionList
+      | AuditAction::WarehouseList
+      | AuditAction::WarehouseUtilization
+      | AuditAction::StructureTreeView
+      | AuditAction::RackList
+      | AuditAction::RackMapView
+      | AuditAction::RackHeatmapView
+      | AuditAction::SlotList
+      | AuditAction::ItemList
+      | AuditAction::ItemDetailView
+      | AuditAction::MediaAttachmentItemList
+      | AuditAction::MediaAttachmentTxnList
+      | AuditAction::TxnList
+      | AuditAction::LoanList
+      | AuditAction::OutboundPickList
+      | AuditAction::AsnList
+      | AuditAction::AsnVarianceReport
+      | AuditAction::SystemSettingsRead
+      | AuditAction::MediaStorageMetricList
+      | AuditAction::PerformanceStatsView
+      | AuditAction::AuditList
+      | AuditAction::AuditArchiveSearch
+      | AuditAction::StockListBySlot
+      | AuditAction::StockListByItem
+      | AuditAction::StockValuation
+      | AuditAction::StockAsof
+      | AuditAction::DashboardOverview
+      | AuditAction::ReportItemStockTrend
+      | AuditAction::ReportStockAging
+      | AuditAction::ReportLowStock
+      | AuditAction::ReportExpiringStock
+      | AuditAction::ReportTxnReasons
+      | AuditAction::StockThresholdList
+      | AuditAction::StockHoldList
+      | AuditAction::CycleCountPlanList
+      | AuditAction::CycleCountTaskList
+      | AuditAction::ItemAliasList
+      | AuditAction::ScanResolve
+      | AuditAction::ItemAttributeDefList
+      | AuditAction::ReasonCodeList
+      | AuditAction::ApprovalList
+      | AuditAction::DbVerify
+      | AuditAction::DbQueryPlanCheck
+      | AuditAction::RemoteBackupList
+      | AuditAction::LabelTemplateList => AuditLevel::Read,
+      AuditAction::WarehouseCreate
+      | AuditAction::WarehouseUpdate
+      | AuditAction::WarehouseStatus
+      | AuditAction::RackCreate
+      | AuditAction::RackClone
+      | AuditAction::RackUpdate
+      | AuditAction::RackStatus
+      | AuditAction::RackMapExport
+      | AuditAction::SlotRegen
+      | AuditAction::SlotStatus
+      | AuditAction::SlotBulkStatus
+      | AuditAction::SlotRecode
+      | AuditAction::ItemCreate
+      | AuditAction::ItemUpdate
+      | AuditAction::ItemStatus
+      | AuditAction::ItemBulkStatus
+      | AuditAction::ItemBulkUpdate
+      | AuditAction::ItemCodeChange
+      | AuditAction::ItemAliasCreate
+      | AuditAction::ItemAliasUpdate
+      | AuditAction::ItemAliasDelete
+      | AuditAction::ItemAttributeDefCreate
+      | AuditAction::ItemAttributeDefUpdate
+      | AuditAction::ItemAttributeDefDelete
+      | AuditAction::ReasonCodeCreate
+      | AuditAction::ReasonCodeUpdate
+      | AuditAction::ReasonCodeDelete
+      | AuditAction::UndoChange
+      | AuditAction::ApprovalSubmit
+      | AuditAction::NotificationMarkRead
+      | AuditAction::MediaAttachmentItemAdd
+      | AuditAction::MediaAttachmentItemRemove
+      | AuditAction::MediaAttachmentItemReorder
+      | AuditAction::MediaAttachmentItemPathRewrite
+      | AuditAction::MediaAttachmentTxnAdd
+      | AuditAction::MediaAttachmentTxnRemove
+      | AuditAction::MediaAttachmentTxnPathRewrite
+      | AuditAction::MediaStorageScan
+      | AuditAction::TxnInbound
+      | AuditAction::TxnOutbound
+      | AuditAction::TxnMove
+      | AuditAction::TxnQuickTransfer
+      | AuditAction::TxnCount
+      | AuditAction::TxnScrap
+      | AuditAction::LoanCreate
+      | AuditAction::LoanReturn
+      | AuditAction::OutboundPickCreate
+      | AuditAction::OutboundPickConfirm
+      | AuditAction::OutboundPickCancel
+      | AuditAction::AsnCreate
+      | AuditAction::AsnReceive
+      | AuditAction::AsnClose
+      | AuditAction::AsnCancel
+      | AuditAction::KioskOperatorSelect
+      | AuditAction::StockExport
+      | AuditAction::StockClose
+      | AuditAction::ReportStockAgingExport
+      | AuditAction::StockThresholdSet
+      | AuditAction::StockThresholdDelete
+      | AuditAction::StockHoldCreate
+      | AuditAction::StockHoldRelease
+      | AuditAction::CycleCountPlanCreate
+      | AuditAction::CycleCountPlanUpdate
+      | AuditAction::CycleCountPlanDelete
+      | AuditAction::CycleCountTaskSkip
+      | AuditAction::ReportScheduleCreate
+      | AuditAction::ReportScheduleUpdate
+      | AuditAction::ReportScheduleDelete
+      | AuditAction::ReportScheduleRun
+      | AuditAction::SmtpTestSend
+      | AuditAction::ItemExport
+      | AuditAction::MasterBundleExport
+      | AuditAction::TxnExport
+      | AuditAction::CountSheetExport
+      | AuditAction::PickListPrint
+      | AuditAction::LabelTemplateCreate
+      | AuditAction::LabelTemplateUpdate
+      | AuditAction::LabelTemplateDelete
+      | AuditAction::LabelPrint
+      // 复制与导出同属数据外带，按写级别保留
+      | AuditAction::TxnCopy
+      | AuditAction::StockCopy => AuditLevel::Write,
+      AuditAction::AuthLogin
+      | AuditAction::AuthLogout
+      | AuditAction::AuthChangePassword
+      | AuditAction::AuthResetPassword
+      | AuditAction::AuthLockout
+      | AuditAction::AuthUnlock
+      | AuditAction::AuthSwitchOperator
+      | AuditAction::AuthSetPin
+      | AuditAction::AuthIssueResetCode
+      | AuditAction::AuthResetPasswordWithCode
+      | AuditAction::OperatorCreate
+      | AuditAction::OperatorUpdate
+      | AuditAction::OperatorProfileUpdate
+      | AuditAction::OperatorStatus
+      | AuditAction::OperatorWarehouseUpdate
+      | AuditAction::OperatorRoleBulkUpdate
+      | AuditAction::RoleCapabilityUpdate
+      | AuditAction::SessionTerminate
+      | AuditAction::TxnReversal
+      | AuditAction::ApprovalApprove
+      | AuditAction::ApprovalReject
+      | AuditAction::SystemSettingsUpdate
+      | AuditAction::SystemReadOnlyUpdate
+      | AuditAction::SystemStorageRootChange
+      | AuditAction::AuditExport
+      | AuditAction::DiagnosticsCollect
+      | AuditAction::AuditArchive
+      | AuditAction::DbBackup
+      | AuditAction::DbRestore
+      | AuditAction::DbRepair
+      | AuditAction::StockRecalculate
+      | AuditAction::StockCorrect
+      | AuditAction::RemoteBackupConfigUpdate
+      | AuditAction::SmtpConfigUpdate
+      | AuditAction::BackupEncryptionUpdate
+      | AuditAction::BackupEncryptExisting
+      | AuditAction::DemoDataGenerate
+      | AuditAction::ItemImport
+      | AuditAction::ItemMerge
+      | AuditAction::TxnImport
+      | AuditAction::StructureImport
+      | AuditAction::MasterBundleImport
+      | AuditAction::OpeningStockImport
+      | AuditAction::CountSheetImport => AuditLevel::Critical,
     }
   }
 }
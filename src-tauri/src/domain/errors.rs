@@ -1,11 +1,20 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 use thiserror::Error;
 
+use crate::domain::messages::{self, MessageKey};
+
 #[derive(Debug, Serialize, Clone, Copy)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ErrorCode {
   AuthFailed,
+  AccountLocked,
   PwdChangeRequired,
+  // 会话空闲超时或被强制结束，前端需返回登录页
+  SessionExpired,
+  // 系统处于只读维护模式，写操作被拒绝
+  ReadOnlyMode,
   ValidationError,
   NotFound,
   InactiveResource,
@@ -13,6 +22,8 @@ pub enum ErrorCode {
   Conflict,
   Forbidden,
   DbError,
+  DbBusy,
+  DbIncompatible,
   IoError,
 }
 
@@ -21,6 +32,11 @@ pub enum ErrorCode {
 pub struct AppError {
   pub code: ErrorCode,
   pub message: String,
+  // 消息键与参数（见 messages::MessageKey），前端可据此按界面语言重新渲染；未编目的错误不返回
+  #[serde(skip_serializing_if = "Option::is_none")]
+  pub message_key: Option<&'static str>,
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub params: BTreeMap<String, String>,
 }
 
 impl AppError {
@@ -28,12 +44,50 @@ impl AppError {
     Self {
       code,
       message: message.into(),
+      message_key: None,
+      params: BTreeMap::new(),
+    }
+  }
+
+  /// 按当前语言渲染目录中的错误信息
+  pub fn keyed(code: ErrorCode, key: MessageKey) -> Self {
+    Self::keyed_with(code, key, &[])
+  }
+
+  /// 带参数的目录错误信息，args 为（占位符名, 值）
+  pub fn keyed_with(code: ErrorCode, key: MessageKey, args: &[(&str, String)]) -> Self {
+    Self {
+      code,
+      message: messages::render(messages::current_locale(), key, args),
+      message_key: Some(key.as_str()),
+      params: args
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect(),
     }
   }
 }
 
 impl From<sqlx::Error> for AppError {
-  fn from(_err: sqlx::Error) -> Self {
-    AppError::new(ErrorCode::DbError, "数据库操作失败")
+  fn from(err: sqlx::Error) -> Self {
+    if is_busy_error(&err) {
+      return AppError::keyed(ErrorCode::DbBusy, MessageKey::DbBusy);
+    }
+    AppError::keyed(ErrorCode::DbError, MessageKey::DbError)
+  }
+}
+
+/// 判断是否为 SQLite 占用/锁定错误（SQLITE_BUSY / SQLITE_LOCKED，含扩展码）
+fn is_busy_error(err: &sqlx::Error) -> bool {
+  match err {
+    sqlx::Error::Database(db_err) => {
+      let by_code = db_err
+        .code()
+        .and_then(|code| code.parse::<i64>().ok())
+        .map(|code| matches!(code & 0xff, 5 | 6))
+        .unwrap_or(false);
+      by_code || db_err.message().contains("database is locked")
+    }
+    _ => false,
   }
 }
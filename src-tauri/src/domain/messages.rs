@@ -0,0 +1,240 @@
+// 消息目录：系统自动生成、写入业务数据的文本（如调整流水备注）与后端错误信息按语言取模板
+//
+// 模板中的 `{name}` 占位符由 render 时传入的参数替换；未知语言回退到 zh-CN。
+// 错误信息按“当前语言”渲染（启动与修改设置时同步 app_meta.locale），并随 AppError
+// 返回消息键与参数，前端可自行翻译；未编入目录的错误仍直接给出中文文本。
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 支持的语言标签（app_meta.locale）
+pub const LOCALES: [&str; 2] = ["zh-CN", "en-US"];
+
+/// 默认语言
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+  ZhCn,
+  EnUs,
+}
+
+impl Locale {
+  /// 由语言标签解析，未知标签回退到默认语言
+  pub fn from_tag(tag: &str) -> Self {
+    match tag {
+      "en-US" => Locale::EnUs,
+      _ => Locale::ZhCn,
+    }
+  }
+}
+
+/// 当前语言（0 = zh-CN，1 = en-US），用于错误信息等无法访问数据库的场景
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(0);
+
+/// 同步当前语言，数据库就绪与修改 locale 设置时调用
+pub fn set_current_locale(tag: &str) {
+  let value = match Locale::from_tag(tag) {
+    Locale::ZhCn => 0,
+    Locale::EnUs => 1,
+  };
+  CURRENT_LOCALE.store(value, Ordering::Relaxed);
+}
+
+pub fn current_locale() -> Locale {
+  match CURRENT_LOCALE.load(Ordering::Relaxed) {
+    1 => Locale::EnUs,
+    _ => Locale::ZhCn,
+  }
+}
+
+/// 消息键
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageKey {
+  // 盘点生成的调整流水备注，参数：expected / actual / session
+  CountAdjustNote,
+  // 以下为错误信息；AccountLocked 参数：minutes
+  DbBusy,
+  DbError,
+  Migrating,
+  ReadOnlyMode,
+  ReadOnlyModeWithReason,
+  Forbidden,
+  WarehouseForbidden,
+  InvalidCredentials,
+  WrongPassword,
+  WrongOldPassword,
+  AccountLocked,
+  AccountInactive,
+  OperatorNotFound,
+  ActorNotFound,
+  ActorInactive,
+  SessionIdleTimeout,
+  SessionTerminated,
+  SessionEnded,
+  ItemNotFound,
+  ItemInactive,
+  WarehouseNotFound,
+  RackNotFound,
+  SlotNotFound,
+  SlotInactive,
+  TxnNotFound,
+  InsufficientStock,
+  QtyRequired,
+  QtyMustBePositive,
+  InvalidPage,
+  InvalidStatus,
+  InvalidRole,
+  InvalidTimeRange,
+  // 参数：field
+  InvalidSortBy,
+  InvalidSortDir,
+  InvalidCursor,
+  StorageRootMissing,
+  ExportDirFailed,
+  ExportFileFailed,
+  ExportWriteFailed,
+  ImportReadFailed,
+  PdfFontFailed,
+  PdfRenderFailed,
+}
+
+impl MessageKey {
+  /// 返回给前端的稳定键名
+  pub fn as_str(self) -> &'static str {
+    match self {
+      MessageKey::CountAdjustNote => "note.count_adjust",
+      MessageKey::DbBusy => "error.db_busy",
+      MessageKey::DbError => "error.db_error",
+      MessageKey::Migrating => "error.migrating",
+      MessageKey::ReadOnlyMode => "error.read_only_mode",
+      MessageKey::ReadOnlyModeWithReason => "error.read_only_mode_with_reason",
+      MessageKey::Forbidden => "error.forbidden",
+      MessageKey::WarehouseForbidden => "error.warehouse_forbidden",
+      MessageKey::InvalidCredentials => "error.invalid_credentials",
+      MessageKey::WrongPassword => "error.wrong_password",
+      MessageKey::WrongOldPassword => "error.wrong_old_password",
+      MessageKey::AccountLocked => "error.account_locked",
+      MessageKey::AccountInactive => "error.account_inactive",
+      MessageKey::OperatorNotFound => "error.operator_not_found",
+      MessageKey::ActorNotFound => "error.actor_not_found",
+      MessageKey::ActorInactive => "error.actor_inactive",
+      MessageKey::SessionIdleTimeout => "error.session_idle_timeout",
+      MessageKey::SessionTerminated => "error.session_terminated",
+      MessageKey::SessionEnded => "error.session_ended",
+      MessageKey::ItemNotFound => "error.item_not_found",
+      MessageKey::ItemInactive => "error.item_inactive",
+      MessageKey::WarehouseNotFound => "error.warehouse_not_found",
+      MessageKey::RackNotFound => "error.rack_not_found",
+      MessageKey::SlotNotFound => "error.slot_not_found",
+      MessageKey::SlotInactive => "error.slot_inactive",
+      MessageKey::TxnNotFound => "error.txn_not_found",
+      MessageKey::InsufficientStock => "error.insufficient_stock",
+      MessageKey::QtyRequired => "error.qty_required",
+      MessageKey::QtyMustBePositive => "error.qty_must_be_positive",
+      MessageKey::InvalidPage => "error.invalid_page",
+      MessageKey::InvalidStatus => "error.invalid_status",
+      MessageKey::InvalidRole => "error.invalid_role",
+      MessageKey::InvalidTimeRange => "error.invalid_time_range",
+      MessageKey::InvalidSortBy => "error.invalid_sort_by",
+      MessageKey::InvalidSortDir => "error.invalid_sort_dir",
+      MessageKey::InvalidCursor => "error.invalid_cursor",
+      MessageKey::StorageRootMissing => "error.storage_root_missing",
+      MessageKey::ExportDirFailed => "error.export_dir_failed",
+      MessageKey::ExportFileFailed => "error.export_file_failed",
+      MessageKey::ExportWriteFailed => "error.export_write_failed",
+      MessageKey::ImportReadFailed => "error.import_read_failed",
+      MessageKey::PdfFontFailed => "error.pdf_font_failed",
+      MessageKey::PdfRenderFailed => "error.pdf_render_failed",
+    }
+  }
+}
+
+/// （zh-CN, en-US）模板
+fn texts(key: MessageKey) -> (&'static str, &'static str) {
+  match key {
+    MessageKey::CountAdjustNote => (
+      "盘点调整：账面 {expected}，实盘 {actual}，盘点单 {session}",
+      "Count adjustment: expected {expected}, actual {actual}, session {session}",
+    ),
+    MessageKey::DbBusy => (
+      "数据库文件被其他程序占用，请关闭其他程序或实例后重试",
+      "The database file is in use by another program; close other programs or instances and try again",
+    ),
+    MessageKey::DbError => ("数据库操作失败", "Database operation failed"),
+    MessageKey::Migrating => (
+      "存储迁移中，暂不可执行该操作",
+      "Storage migration in progress; this operation is temporarily unavailable",
+    ),
+    MessageKey::ReadOnlyMode => (
+      "系统处于只读维护模式，暂不允许修改数据",
+      "The system is in read-only maintenance mode; changes are not allowed",
+    ),
+    MessageKey::ReadOnlyModeWithReason => (
+      "系统处于只读维护模式（{reason}），暂不允许修改数据",
+      "The system is in read-only maintenance mode ({reason}); changes are not allowed",
+    ),
+    MessageKey::Forbidden => ("无权限执行该操作", "You do not have permission to perform this operation"),
+    MessageKey::WarehouseForbidden => ("无权访问该仓库", "You do not have access to this warehouse"),
+    MessageKey::InvalidCredentials => ("账号或密码错误", "Incorrect username or password"),
+    MessageKey::WrongPassword => ("密码错误", "Incorrect password"),
+    MessageKey::WrongOldPassword => ("旧密码错误", "Incorrect old password"),
+    MessageKey::AccountLocked => (
+      "登录失败次数过多，账号已锁定，请 {minutes} 分钟后重试",
+      "Too many failed logins; the account is locked, try again in {minutes} minute(s)",
+    ),
+    MessageKey::AccountInactive => ("账号已停用", "The account is disabled"),
+    MessageKey::OperatorNotFound => ("人员不存在", "Operator not found"),
+    MessageKey::ActorNotFound => ("操作人不存在", "Acting operator not found"),
+    MessageKey::ActorInactive => ("操作人已停用", "Acting operator is disabled"),
+    MessageKey::SessionIdleTimeout => ("长时间未操作，请重新登录", "Signed out after a period of inactivity, please sign in again"),
+    MessageKey::SessionTerminated => ("当前会话已被管理员结束，请重新登录", "Your session was ended by an administrator, please sign in again"),
+    MessageKey::SessionEnded => ("登录已失效，请重新登录", "Your session has ended, please sign in again"),
+    MessageKey::ItemNotFound => ("物品不存在", "Item not found"),
+    MessageKey::ItemInactive => ("物品已停用", "Item is disabled"),
+    MessageKey::WarehouseNotFound => ("仓库不存在", "Warehouse not found"),
+    MessageKey::RackNotFound => ("货架不存在", "Rack not found"),
+    MessageKey::SlotNotFound => ("库位不存在", "Slot not found"),
+    MessageKey::SlotInactive => ("库位已停用", "Slot is disabled"),
+    MessageKey::TxnNotFound => ("流水不存在", "Transaction not found"),
+    MessageKey::InsufficientStock => ("库存不足", "Insufficient stock"),
+    MessageKey::QtyRequired => ("数量不能为空", "Quantity is required"),
+    MessageKey::QtyMustBePositive => ("数量必须大于 0", "Quantity must be greater than 0"),
+    MessageKey::InvalidPage => ("分页参数非法", "Invalid pagination parameters"),
+    MessageKey::InvalidStatus => ("状态非法", "Invalid status"),
+    MessageKey::InvalidRole => ("角色非法", "Invalid role"),
+    MessageKey::InvalidTimeRange => ("开始时间不能晚于结束时间", "Start time must not be later than end time"),
+    MessageKey::InvalidSortBy => ("不支持按 {field} 排序", "Sorting by {field} is not supported"),
+    MessageKey::InvalidSortDir => (
+      "排序方向仅支持 asc/desc",
+      "Sort direction must be asc or desc",
+    ),
+    MessageKey::InvalidCursor => (
+      "游标参数 after_created_at 与 after_id 需同时提供",
+      "Cursor parameters after_created_at and after_id must be provided together",
+    ),
+    MessageKey::StorageRootMissing => ("存储根目录未配置", "Storage root directory is not configured"),
+    MessageKey::ExportDirFailed => ("创建导出目录失败", "Failed to create export directory"),
+    MessageKey::ExportFileFailed => ("创建导出文件失败", "Failed to create export file"),
+    MessageKey::ExportWriteFailed => ("写入导出文件失败", "Failed to write export file"),
+    MessageKey::ImportReadFailed => ("读取导入文件失败", "Failed to read import file"),
+    MessageKey::PdfFontFailed => ("加载 PDF 字体失败", "Failed to load PDF font"),
+    MessageKey::PdfRenderFailed => ("生成 PDF 失败", "Failed to generate PDF"),
+  }
+}
+
+fn template(locale: Locale, key: MessageKey) -> &'static str {
+  let (zh_cn, en_us) = texts(key);
+  match locale {
+    Locale::ZhCn => zh_cn,
+    Locale::EnUs => en_us,
+  }
+}
+
+/// 按语言渲染消息，args 为（占位符名, 值）
+pub fn render(locale: Locale, key: MessageKey, args: &[(&str, String)]) -> String {
+  args
+    .iter()
+    .fold(template(locale, key).to_string(), |text, (name, value)| {
+      text.replace(&format!("{{{}}}", name), value)
+    })
+}
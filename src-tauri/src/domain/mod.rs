@@ -1,2 +1,6 @@
 pub mod errors;
 pub mod audit;
+pub mod messages;
+pub mod quantity;
+pub mod sort;
+pub mod timezone;
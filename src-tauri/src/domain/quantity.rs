@@ -0,0 +1,69 @@
+// 数量精度：物品可配置小数位数（qty_precision，0-3），库存与流水以放大后的整数存放
+//
+// 约定：
+// - 存储值（基本单位）= 显示数量 × 10^qty_precision，库存计算全部在整数上完成，避免浮点误差
+// - 命令入参与列表/导出中的数量均为显示数量，在接口层与查询中换算
+
+use crate::domain::errors::{AppError, ErrorCode};
+
+/// 最大小数位数
+pub const MAX_QTY_PRECISION: i64 = 3;
+
+/// SQL 片段：按精度列（如 item.qty_precision）得到放大倍数（REAL），用于在查询中换算为显示数量
+pub fn scale_sql(precision_column: &str) -> String {
+  format!(
+    "(CASE {} WHEN 1 THEN 10.0 WHEN 2 THEN 100.0 WHEN 3 THEN 1000.0 ELSE 1.0 END)",
+    precision_column
+  )
+}
+
+/// 校验小数位数
+pub fn validate_precision(precision: i64) -> Result<(), AppError> {
+  if !(0..=MAX_QTY_PRECISION).contains(&precision) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("数量小数位数必须在 0-{} 之间", MAX_QTY_PRECISION),
+    ));
+  }
+  Ok(())
+}
+
+/// 放大倍数 10^precision
+pub fn scale(precision: i64) -> i64 {
+  10_i64.pow(precision.clamp(0, MAX_QTY_PRECISION) as u32)
+}
+
+/// 显示数量换算为存储值；小数位超出物品精度时返回校验错误
+pub fn to_base(qty: f64, precision: i64) -> Result<i64, AppError> {
+  if !qty.is_finite() {
+    return Err(AppError::new(ErrorCode::ValidationError, "数量格式不正确"));
+  }
+  let scaled = qty * scale(precision) as f64;
+  let rounded = scaled.round();
+  if (scaled - rounded).abs() > 1e-6 {
+    let message = if precision == 0 {
+      "该物品数量必须为整数".to_string()
+    } else {
+      format!("该物品数量最多保留 {} 位小数", precision)
+    };
+    return Err(AppError::new(ErrorCode::ValidationError, message));
+  }
+  if rounded.abs() >= i64::MAX as f64 {
+    return Err(AppError::new(ErrorCode::ValidationError, "数量超出范围"));
+  }
+  Ok(rounded as i64)
+}
+
+/// 存储值换算为显示数量
+pub fn to_display(base: i64, precision: i64) -> f64 {
+  base as f64 / scale(precision) as f64
+}
+
+/// 存储值格式化为文本（按精度补足小数位），用于导出与提示信息
+pub fn format(base: i64, precision: i64) -> String {
+  let precision = precision.clamp(0, MAX_QTY_PRECISION);
+  if precision == 0 {
+    return base.to_string();
+  }
+  format!("{:.*}", precision as usize, to_display(base, precision))
+}
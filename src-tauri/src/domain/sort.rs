@@ -0,0 +1,75 @@
+// 列表排序：sort_by / sort_dir 参数按各 repo 的白名单解析为 ORDER BY 子句
+//
+// 约定：
+// - sort_by 为逗号分隔的字段名（按优先级），如 `qty,item_code`
+// - sort_dir 为 asc/desc，可为单个值（作用于全部字段）或与字段一一对应的逗号列表
+// - 白名单为（字段名, SQL 表达式），字段名不在白名单内时返回校验错误，不拼接任何外部输入
+// - 默认排序始终追加在最后作为次序依据，保证分页稳定
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+
+/// 单次排序最多字段数
+const MAX_SORT_COLUMNS: usize = 3;
+
+/// 解析排序参数，返回以空格开头的 ` ORDER BY ...` 子句；未指定 sort_by 时仅使用默认排序
+pub fn order_by_clause(
+  columns: &[(&str, &str)],
+  sort_by: Option<&str>,
+  sort_dir: Option<&str>,
+  default_order: &str,
+) -> Result<String, AppError> {
+  let fields: Vec<&str> = sort_by
+    .unwrap_or("")
+    .split(',')
+    .map(str::trim)
+    .filter(|field| !field.is_empty())
+    .collect();
+  if fields.is_empty() {
+    return Ok(format!(" ORDER BY {}", default_order));
+  }
+  if fields.len() > MAX_SORT_COLUMNS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("排序字段最多 {} 个", MAX_SORT_COLUMNS),
+    ));
+  }
+
+  let dirs: Vec<&str> = sort_dir
+    .unwrap_or("")
+    .split(',')
+    .map(str::trim)
+    .filter(|dir| !dir.is_empty())
+    .collect();
+  if dirs.len() > 1 && dirs.len() != fields.len() {
+    return Err(AppError::keyed(
+      ErrorCode::ValidationError,
+      MessageKey::InvalidSortDir,
+    ));
+  }
+
+  let mut parts = Vec::with_capacity(fields.len() + 1);
+  for (index, field) in fields.iter().enumerate() {
+    let Some((_, expr)) = columns.iter().find(|(name, _)| name == field) else {
+      return Err(AppError::keyed_with(
+        ErrorCode::ValidationError,
+        MessageKey::InvalidSortBy,
+        &[("field", field.to_string())],
+      ));
+    };
+    let dir = dirs.get(index).or(dirs.first()).copied().unwrap_or("asc");
+    let dir = match dir.to_ascii_lowercase().as_str() {
+      "asc" => "ASC",
+      "desc" => "DESC",
+      _ => {
+        return Err(AppError::keyed(
+          ErrorCode::ValidationError,
+          MessageKey::InvalidSortDir,
+        ))
+      }
+    };
+    parts.push(format!("{} {}", expr, dir));
+  }
+  parts.push(default_order.to_string());
+  Ok(format!(" ORDER BY {}", parts.join(", ")))
+}
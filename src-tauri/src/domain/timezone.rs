@@ -0,0 +1,124 @@
+// 报表时区：导出中的时间格式化与仪表盘/报表的按日、周、月分组使用同一时区
+//
+// 未集成时区数据库，仅支持跟随本机（local）或固定 UTC 偏移（如 +08:00）；固定偏移不处理夏令时。
+
+use chrono::{FixedOffset, Local, NaiveDate, TimeZone, Utc};
+
+/// 默认跟随本机时区
+pub const DEFAULT_REPORT_TIMEZONE: &str = "local";
+
+/// 导出时间格式
+const EXPORT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// UTC 偏移的最大绝对值（分钟）
+const MAX_OFFSET_MINUTES: i32 = 14 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportTimezone {
+  Local,
+  Fixed(FixedOffset),
+}
+
+impl ReportTimezone {
+  /// 解析设置值：local，或 UTC、+08:00、-0530、UTC+8 等固定偏移
+  pub fn parse(value: &str) -> Option<Self> {
+    let value = value.trim();
+    if value.is_empty() || !value.is_ascii() {
+      return None;
+    }
+    if value.eq_ignore_ascii_case("local") {
+      return Some(Self::Local);
+    }
+    let upper = value.to_ascii_uppercase();
+    let offset = upper
+      .strip_prefix("UTC")
+      .or_else(|| upper.strip_prefix("GMT"))
+      .unwrap_or(&upper);
+    if offset.is_empty() || offset == "Z" {
+      return FixedOffset::east_opt(0).map(Self::Fixed);
+    }
+    let (sign, rest) = match offset.as_bytes()[0] {
+      b'+' => (1, &offset[1..]),
+      b'-' => (-1, &offset[1..]),
+      _ => return None,
+    };
+    if !rest.bytes().all(|b| b.is_ascii_digit() || b == b':') {
+      return None;
+    }
+    let (hours, minutes) = match rest.split_once(':') {
+      Some((hours, minutes)) => (hours, minutes),
+      None if rest.len() > 2 => rest.split_at(rest.len() - 2),
+      None => (rest, "0"),
+    };
+    if hours.is_empty() || hours.len() > 2 || minutes.len() > 2 {
+      return None;
+    }
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    let total = hours * 60 + minutes;
+    if minutes >= 60 || total > MAX_OFFSET_MINUTES {
+      return None;
+    }
+    FixedOffset::east_opt(sign * total * 60).map(Self::Fixed)
+  }
+
+  /// 规范化后的设置值：local 或 ±HH:MM
+  pub fn as_setting(&self) -> String {
+    match self {
+      Self::Local => DEFAULT_REPORT_TIMEZONE.to_string(),
+      Self::Fixed(offset) => {
+        let seconds = offset.local_minus_utc();
+        let sign = if seconds < 0 { '-' } else { '+' };
+        let minutes = seconds.abs() / 60;
+        format!("{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+      }
+    }
+  }
+
+  /// SQL 片段：SQLite 日期函数的修饰符，把 unixepoch 时间换算到该时区
+  pub fn sqlite_modifier(&self) -> String {
+    match self {
+      Self::Local => "'localtime'".to_string(),
+      Self::Fixed(offset) => format!("'{:+} minutes'", offset.local_minus_utc() / 60),
+    }
+  }
+
+  /// 时间戳格式化为该时区的 YYYY-MM-DD HH:MM:SS
+  pub fn format_timestamp(&self, timestamp: i64) -> String {
+    let formatted = match self {
+      Self::Local => Local
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.format(EXPORT_TIME_FORMAT).to_string()),
+      Self::Fixed(offset) => offset
+        .timestamp_opt(timestamp, 0)
+        .single()
+        .map(|time| time.format(EXPORT_TIME_FORMAT).to_string()),
+    };
+    formatted.unwrap_or_else(|| timestamp.to_string())
+  }
+
+  /// 时间戳在该时区的日期
+  pub fn date_of(&self, timestamp: i64) -> Option<NaiveDate> {
+    match self {
+      Self::Local => Local.timestamp_opt(timestamp, 0).single().map(|time| time.date_naive()),
+      Self::Fixed(offset) => offset.timestamp_opt(timestamp, 0).single().map(|time| time.date_naive()),
+    }
+  }
+
+  /// 该时区的今天
+  pub fn today(&self) -> NaiveDate {
+    let now = Utc::now().timestamp();
+    self.date_of(now).unwrap_or_else(|| Local::now().date_naive())
+  }
+
+  /// 该时区某天 0 点的时间戳；本机时区 0 点因夏令时切换不存在时按 UTC 0 点换算
+  pub fn day_start(&self, day: NaiveDate) -> i64 {
+    let naive = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    let start = match self {
+      Self::Local => Local.from_local_datetime(&naive).earliest().map(|time| time.timestamp()),
+      Self::Fixed(offset) => offset.from_local_datetime(&naive).single().map(|time| time.timestamp()),
+    };
+    start.unwrap_or_else(|| naive.and_utc().timestamp())
+  }
+}
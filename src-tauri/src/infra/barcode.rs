@@ -0,0 +1,42 @@
+// 条码编码：Code 128（B 字符集），供打印盘点表等场景在 PDF 中绘制
+//
+// 仅负责把文本编码为条/空模块宽度序列，绘制由调用方完成。
+
+/// 码值 0-105 的条空宽度（条、空交替，共 6 段，每个码值 11 个模块）
+const PATTERNS: [&str; 106] = [
+  "212222", "222122", "222221", "121223", "121322", "131222", "122213", "122312", "132212", "221213",
+  "221312", "231212", "112232", "122132", "122231", "113222", "123122", "123221", "223211", "221132",
+  "221231", "213212", "223112", "312131", "311222", "321122", "321221", "312212", "322112", "322211",
+  "212123", "212321", "232121", "111323", "131123", "131321", "112313", "132113", "132311", "211313",
+  "231113", "231311", "112133", "112331", "132131", "113123", "113321", "133121", "313121", "211331",
+  "231131", "213113", "213311", "213131", "311123", "311321", "331121", "312113", "312311", "332111",
+  "314111", "221411", "431111", "111224", "111422", "121124", "121421", "141122", "141221", "112214",
+  "112412", "122114", "122411", "142112", "142211", "241211", "221114", "413111", "241112", "134111",
+  "111242", "121142", "121241", "114212", "124112", "124211", "411212", "421112", "421211", "212141",
+  "214121", "412121", "111143", "111341", "131141", "114113", "114311", "411113", "411311", "113141",
+  "114131", "311141", "411131", "211412", "211214", "211232",
+];
+/// 终止符（7 段，13 个模块）
+const STOP: &str = "2331112";
+const START_B: usize = 104;
+
+/// 将文本编码为 Code 128-B 的条空宽度序列（以条开始，条空交替，单位为模块）；
+/// 仅支持可打印 ASCII，其他字符返回 None
+pub fn code128_widths(value: &str) -> Option<Vec<u8>> {
+  if value.is_empty() || !value.bytes().all(|byte| (32..=126).contains(&byte)) {
+    return None;
+  }
+  let codes: Vec<usize> = value.bytes().map(|byte| (byte - 32) as usize).collect();
+  let checksum = codes
+    .iter()
+    .enumerate()
+    .fold(START_B, |sum, (index, code)| sum + (index + 1) * code)
+    % 103;
+
+  let mut widths = Vec::with_capacity((codes.len() + 2) * 6 + 7);
+  for code in std::iter::once(START_B).chain(codes).chain(std::iter::once(checksum)) {
+    widths.extend(PATTERNS[code].bytes().map(|digit| digit - b'0'));
+  }
+  widths.extend(STOP.bytes().map(|digit| digit - b'0'));
+  Some(widths)
+}
@@ -1,6 +1,15 @@
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
 use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
 use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rand::distributions::{Alphanumeric, DistString};
 use rand::rngs::OsRng;
+use rand::{Rng, RngCore};
+use sha2::{Digest, Sha256};
 
 use crate::domain::errors::{AppError, ErrorCode};
 
@@ -19,3 +28,116 @@ pub fn verify_password(hash: &str, plain: &str) -> Result<bool, AppError> {
   let argon2 = Argon2::default();
   Ok(argon2.verify_password(plain.as_bytes(), &parsed).is_ok())
 }
+
+/// 生成随机访问令牌（字母数字）
+pub fn generate_token(len: usize) -> String {
+  Alphanumeric.sample_string(&mut OsRng, len)
+}
+
+/// 访问令牌的 SHA-256 摘要（十六进制），只保存摘要，校验时比较摘要
+pub fn hash_token(token: &str) -> String {
+  Sha256::digest(token.as_bytes())
+    .iter()
+    .map(|byte| format!("{:02x}", byte))
+    .collect()
+}
+
+/// 生成一次性重置码：大写字母与数字，去掉易混淆的 0/O/1/I
+pub fn generate_reset_code(len: usize) -> String {
+  const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+  (0..len)
+    .map(|_| CHARSET[OsRng.gen_range(0..CHARSET.len())] as char)
+    .collect()
+}
+
+/// 读取本机密钥文件（32 字节），不存在时生成；密钥不进入数据库，数据库备份中的密文无法单独解密
+pub fn load_or_create_key(path: &Path) -> Result<[u8; 32], AppError> {
+  if let Ok(bytes) = std::fs::read(path) {
+    return <[u8; 32]>::try_from(bytes.as_slice())
+      .map_err(|_| AppError::new(ErrorCode::IoError, "密钥文件已损坏"));
+  }
+  let mut key = [0_u8; 32];
+  OsRng.fill_bytes(&mut key);
+  std::fs::write(path, key).map_err(|_| AppError::new(ErrorCode::IoError, "写入密钥文件失败"))?;
+  Ok(key)
+}
+
+/// AES-256-GCM 加密，返回 base64(nonce || 密文)
+pub fn encrypt_secret(key: &[u8; 32], plain: &str) -> Result<String, AppError> {
+  let cipher = Aes256Gcm::new(key.into());
+  let mut nonce = [0_u8; 12];
+  OsRng.fill_bytes(&mut nonce);
+  let encrypted = cipher
+    .encrypt(Nonce::from_slice(&nonce), plain.as_bytes())
+    .map_err(|_| AppError::new(ErrorCode::IoError, "加密失败"))?;
+  let mut payload = nonce.to_vec();
+  payload.extend_from_slice(&encrypted);
+  Ok(BASE64.encode(payload))
+}
+
+/// 解密 encrypt_secret 的结果
+pub fn decrypt_secret(key: &[u8; 32], encoded: &str) -> Result<String, AppError> {
+  let failed = || AppError::new(ErrorCode::IoError, "凭据解密失败，请重新填写");
+  let payload = BASE64.decode(encoded).map_err(|_| failed())?;
+  if payload.len() < 12 {
+    return Err(failed());
+  }
+  let (nonce, encrypted) = payload.split_at(12);
+  let cipher = Aes256Gcm::new(key.into());
+  let plain = cipher
+    .decrypt(Nonce::from_slice(nonce), encrypted)
+    .map_err(|_| failed())?;
+  String::from_utf8(plain).map_err(|_| failed())
+}
+
+/// 加密备份文件头：魔数 + 盐（16 字节）+ nonce（12 字节），其后为 AES-256-GCM 密文
+const BACKUP_MAGIC: &[u8; 8] = b"ICBKENC1";
+const BACKUP_SALT_LEN: usize = 16;
+const BACKUP_NONCE_LEN: usize = 12;
+
+/// 是否为 encrypt_backup 生成的加密备份
+pub fn is_encrypted_backup(data: &[u8]) -> bool {
+  data.starts_with(BACKUP_MAGIC)
+}
+
+/// 用口令加密备份内容：Argon2id 按随机盐派生密钥，每个文件的盐与 nonce 都不同
+pub fn encrypt_backup(passphrase: &str, plain: &[u8]) -> Result<Vec<u8>, AppError> {
+  let mut salt = [0_u8; BACKUP_SALT_LEN];
+  OsRng.fill_bytes(&mut salt);
+  let mut nonce = [0_u8; BACKUP_NONCE_LEN];
+  OsRng.fill_bytes(&mut nonce);
+  let key = derive_backup_key(passphrase, &salt)?;
+  let encrypted = Aes256Gcm::new((&key).into())
+    .encrypt(Nonce::from_slice(&nonce), plain)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "加密备份失败"))?;
+
+  let mut payload = Vec::with_capacity(BACKUP_MAGIC.len() + salt.len() + nonce.len() + encrypted.len());
+  payload.extend_from_slice(BACKUP_MAGIC);
+  payload.extend_from_slice(&salt);
+  payload.extend_from_slice(&nonce);
+  payload.extend_from_slice(&encrypted);
+  Ok(payload)
+}
+
+/// 解密 encrypt_backup 的结果；口令错误与文件损坏无法区分，统一提示
+pub fn decrypt_backup(passphrase: &str, data: &[u8]) -> Result<Vec<u8>, AppError> {
+  let failed = || AppError::new(ErrorCode::ValidationError, "备份口令错误或备份文件已损坏");
+  let header_len = BACKUP_MAGIC.len() + BACKUP_SALT_LEN + BACKUP_NONCE_LEN;
+  if !is_encrypted_backup(data) || data.len() < header_len {
+    return Err(failed());
+  }
+  let (salt, rest) = data[BACKUP_MAGIC.len()..].split_at(BACKUP_SALT_LEN);
+  let (nonce, encrypted) = rest.split_at(BACKUP_NONCE_LEN);
+  let key = derive_backup_key(passphrase, salt)?;
+  Aes256Gcm::new((&key).into())
+    .decrypt(Nonce::from_slice(nonce), encrypted)
+    .map_err(|_| failed())
+}
+
+fn derive_backup_key(passphrase: &str, salt: &[u8]) -> Result<[u8; 32], AppError> {
+  let mut key = [0_u8; 32];
+  Argon2::default()
+    .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "派生备份密钥失败"))?;
+  Ok(key)
+}
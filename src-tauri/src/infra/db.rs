@@ -1,13 +1,27 @@
-use std::path::PathBuf;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 use chrono::Utc;
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::SqlitePool;
+use sqlx::migrate::Migrate;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::{ConnectOptions, Connection, SqlitePool};
 use tauri::{AppHandle, Manager};
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 use crate::infra::crypto;
+use crate::infra::startup::{self, StartupProgress};
+use crate::repo::meta_repo;
+
+/// 连接池大小默认值与上限，可通过 app_meta 的 db_pool_size 调整（重启后生效）
+pub const DEFAULT_POOL_SIZE: i64 = 5;
+pub const MAX_POOL_SIZE: i64 = 16;
+/// 遇到写锁时的等待时长，避免并发读写时直接返回 SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+/// 超过等待时长仍被占用时的重试退避间隔（毫秒）
+const BUSY_RETRY_DELAYS_MS: &[u64] = &[100, 250, 500, 1000, 2000];
 
 pub async fn init_db(app: &AppHandle) -> Result<(SqlitePool, PathBuf), AppError> {
   let storage_root = app
@@ -15,6 +29,8 @@ pub async fn init_db(app: &AppHandle) -> Result<(SqlitePool, PathBuf), AppError>
     .app_data_dir()
     .map_err(|_| AppError::new(ErrorCode::IoError, "无法获取应用数据目录"))?;
 
+  startup::report(app, StartupProgress::running("prepare", "正在准备数据目录"));
+
   // 按规格创建固定子目录
   let db_dir = storage_root.join("db");
   let photos_dir = storage_root.join("photos");
@@ -26,31 +42,355 @@ pub async fn init_db(app: &AppHandle) -> Result<(SqlitePool, PathBuf), AppError>
   std::fs::create_dir_all(&photos_dir)
     .map_err(|_| AppError::new(ErrorCode::IoError, "创建照片目录失败"))?;
   std::fs::create_dir_all(&exports_dir)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出目录失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
   std::fs::create_dir_all(&backups_dir)
     .map_err(|_| AppError::new(ErrorCode::IoError, "创建备份目录失败"))?;
 
   let db_path = db_dir.join("db.sqlite");
 
+  startup::report(app, StartupProgress::running("connect", "正在打开数据库"));
+  let pool = connect(&db_path).await?;
+
+  // 执行迁移；失败时关闭连接池，便于从备份恢复数据库文件后重试
+  let app_version = app.package_info().version.to_string();
+  let migration_backup = match run_migrations(Some(app), &pool, &backups_dir, &app_version).await {
+    Ok(backup) => backup,
+    Err(err) => {
+      pool.close().await;
+      return Err(err);
+    }
+  };
+
+  startup::report(app, StartupProgress::running("meta", "正在初始化配置"));
+  init_app_meta(&pool, &storage_root).await?;
+  record_app_version(&pool, &app_version, migration_backup.as_deref()).await?;
+  init_admin_operator(&pool).await?;
+
+  Ok((pool, storage_root))
+}
+
+/// 恢复备份时在替换前对待恢复的数据库文件执行迁移并补齐配置，与启动初始化一致；
+/// 存储目录以当前实际位置为准，不沿用备份中记录的路径
+pub async fn prepare_restored(
+  pool: &SqlitePool,
+  storage_root: &PathBuf,
+  app_version: &str,
+) -> Result<(), AppError> {
+  run_migrations(None, pool, &storage_root.join("backups"), app_version).await?;
+  init_app_meta(pool, storage_root).await?;
+  meta_repo::set_meta_value(pool, "storage_root", &storage_root.to_string_lossy()).await?;
+  record_app_version(pool, app_version, None).await?;
+  init_admin_operator(pool).await?;
+  Ok(())
+}
+
+/// 按统一的连接参数打开数据库连接池，启动与存储目录迁移后重连共用
+pub async fn connect(db_path: &Path) -> Result<SqlitePool, AppError> {
+  // WAL 模式下读写互不阻塞，synchronous=NORMAL 在 WAL 下仍保证崩溃后数据库一致
   let options = SqliteConnectOptions::new()
-    .filename(&db_path)
-    .create_if_missing(true);
+    .filename(db_path)
+    .create_if_missing(true)
+    .journal_mode(SqliteJournalMode::Wal)
+    .synchronous(SqliteSynchronous::Normal)
+    .busy_timeout(BUSY_TIMEOUT)
+    .foreign_keys(true);
 
+  let pool_size = configured_pool_size(&options).await;
   let pool = SqlitePoolOptions::new()
-    .max_connections(5)
+    .max_connections(pool_size)
     .connect_with(options)
     .await?;
+  Ok(pool)
+}
 
-  // 执行初始化迁移
-  sqlx::migrate!("./migrations")
-    .run(&pool)
+/// 读取 app_meta 中配置的连接池大小；首次启动（表尚不存在）或读取失败时使用默认值
+async fn configured_pool_size(options: &SqliteConnectOptions) -> u32 {
+  let Ok(mut conn) = options.connect().await else {
+    return DEFAULT_POOL_SIZE as u32;
+  };
+  let value: Option<String> = sqlx::query_scalar("SELECT v FROM app_meta WHERE k = 'db_pool_size'")
+    .fetch_optional(&mut conn)
     .await
-    .map_err(|err| AppError::new(ErrorCode::DbError, format!("数据库迁移失败: {}", err)))?;
+    .ok()
+    .flatten();
+  let _ = conn.close().await;
+  value
+    .and_then(|value| value.parse::<i64>().ok())
+    .filter(|value| (1..=MAX_POOL_SIZE).contains(value))
+    .unwrap_or(DEFAULT_POOL_SIZE) as u32
+}
 
-  init_app_meta(&pool, &storage_root).await?;
-  init_admin_operator(&pool).await?;
+/// 打开已有数据库文件（不创建、不执行迁移），启动失败时校验管理员凭据并记录失败次数使用
+pub async fn open_existing(db_path: &Path) -> Result<SqlitePool, AppError> {
+  let options = SqliteConnectOptions::new()
+    .filename(db_path)
+    .busy_timeout(BUSY_TIMEOUT);
+  let pool = SqlitePoolOptions::new()
+    .max_connections(1)
+    .connect_with(options)
+    .await?;
+  Ok(pool)
+}
 
-  Ok((pool, storage_root))
+/// 以只读方式打开数据库文件，启动失败时读取备份中的管理员凭据与口令使用，不执行迁移
+pub async fn open_read_only(db_path: &Path) -> Result<SqlitePool, AppError> {
+  let options = SqliteConnectOptions::new()
+    .filename(db_path)
+    .read_only(true)
+    .busy_timeout(BUSY_TIMEOUT);
+  let pool = SqlitePoolOptions::new()
+    .max_connections(1)
+    .connect_with(options)
+    .await?;
+  Ok(pool)
+}
+
+/// WAL 模式下把日志写回主数据库文件并清空日志，直接复制数据库文件前调用
+pub async fn checkpoint(pool: &SqlitePool) -> Result<(), AppError> {
+  sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)").execute(pool).await?;
+  Ok(())
+}
+
+/// 数据库被其他进程占用时按退避间隔重试；operation 只能包含一个完整事务，
+/// 失败的事务会整体回滚，重试不会重复写入。事务提交后的其他写操作不要放进来
+pub async fn retry_busy<T, F, Fut>(operation: F) -> Result<T, AppError>
+where
+  F: Fn() -> Fut,
+  Fut: std::future::Future<Output = Result<T, AppError>>,
+{
+  let mut result = operation().await;
+  for delay_ms in BUSY_RETRY_DELAYS_MS {
+    match &result {
+      Err(err) if matches!(err.code, ErrorCode::DbBusy) => {
+        tokio::time::sleep(Duration::from_millis(*delay_ms)).await;
+        result = operation().await;
+      }
+      _ => break,
+    }
+  }
+  result
+}
+
+/// 逐个执行待应用的迁移并上报进度；升级已有数据库前先备份，返回备份文件路径。
+/// app 为 None 时用于恢复备份：不上报启动进度，恢复所用的备份文件即为恢复点，不再另行备份
+async fn run_migrations(
+  app: Option<&AppHandle>,
+  pool: &SqlitePool,
+  default_backups_dir: &Path,
+  app_version: &str,
+) -> Result<Option<String>, AppError> {
+  let migrator = sqlx::migrate!("./migrations");
+  let mut conn = pool.acquire().await?;
+  conn.ensure_migrations_table().await.map_err(migrate_error)?;
+  let applied: HashSet<i64> = conn
+    .list_applied_migrations()
+    .await
+    .map_err(migrate_error)?
+    .into_iter()
+    .map(|migration| migration.version)
+    .collect();
+  let pending: Vec<_> = migrator
+    .iter()
+    .filter(|migration| !migration.migration_type.is_down_migration())
+    .filter(|migration| !applied.contains(&migration.version))
+    .collect();
+
+  // 降级保护：数据库结构版本高于当前程序支持的版本时拒绝打开
+  let supported_version = migrator
+    .iter()
+    .filter(|migration| !migration.migration_type.is_down_migration())
+    .map(|migration| migration.version)
+    .max()
+    .unwrap_or(0);
+  if !applied.is_empty() {
+    drop(conn);
+    ensure_schema_compatible(pool, &applied, supported_version, app_version).await?;
+    conn = pool.acquire().await?;
+  }
+
+  // 已有数据库且存在待执行迁移时，先生成升级前备份，确保升级失败时有恢复点
+  let mut backup = None;
+  if let Some(app) = app.filter(|_| !applied.is_empty() && !pending.is_empty()) {
+    startup::report(app, StartupProgress::running("backup", "正在备份数据库"));
+    drop(conn);
+    let path = backup_before_migrate(pool, default_backups_dir, app_version).await?;
+    startup::remember_backup(app, &path);
+    backup = Some(path);
+    conn = pool.acquire().await?;
+  }
+
+  // 修改列约束（如流水类型的 CHECK）需重建表，删除旧表时外键会阻止被引用的表被替换；
+  // 迁移期间关闭外键约束（事务内无法切换，需在连接上设置），全部执行后统一做外键一致性检查
+  let total = pending.len() as i64;
+  let has_pending = total > 0;
+  if has_pending {
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await?;
+  }
+  let mut applied_result = Ok(());
+  for (index, migration) in pending.into_iter().enumerate() {
+    if let Some(app) = app {
+      startup::report(
+        app,
+        StartupProgress::step(
+          "migrate",
+          format!("正在升级数据库：{}", migration.description),
+          index as i64 + 1,
+          total,
+        ),
+      );
+    }
+    if let Err(err) = conn.apply(migration).await {
+      if let Some(app) = app {
+        startup::remember_failed_migration(app, migration.version, &migration.description);
+      }
+      applied_result = Err(migrate_error(err));
+      break;
+    }
+  }
+  if has_pending {
+    let violations = sqlx::query("PRAGMA foreign_key_check").fetch_all(&mut *conn).await?;
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await?;
+    applied_result?;
+    if !violations.is_empty() {
+      return Err(AppError::new(
+        ErrorCode::DbError,
+        format!("数据库迁移失败: 外键检查发现 {} 条不一致记录", violations.len()),
+      ));
+    }
+  }
+  drop(conn);
+
+  // 统一校验已应用迁移的完整性（校验和、未完成迁移）
+  migrator.run(pool).await.map_err(migrate_error)?;
+  meta_repo::set_meta_value(pool, "schema_version", &supported_version.to_string()).await?;
+  Ok(backup)
+}
+
+/// 校验数据库结构版本不高于当前程序支持的版本
+async fn ensure_schema_compatible(
+  pool: &SqlitePool,
+  applied: &HashSet<i64>,
+  supported_version: i64,
+  app_version: &str,
+) -> Result<(), AppError> {
+  let applied_version = applied.iter().copied().max().unwrap_or(0);
+  let stored_version = meta_repo::get_meta_i64(pool, "schema_version").await?.unwrap_or(0);
+  let db_version = applied_version.max(stored_version);
+  if db_version <= supported_version {
+    return Ok(());
+  }
+  let written_by = meta_repo::get_meta_value(pool, "app_version")
+    .await?
+    .map(|version| format!("，由 v{} 写入", version))
+    .unwrap_or_default();
+  Err(AppError::new(
+    ErrorCode::DbIncompatible,
+    format!(
+      "数据库版本（{}{}）高于当前程序 v{} 支持的版本（{}）",
+      db_version, written_by, app_version, supported_version
+    ),
+  ))
+}
+
+/// 升级前备份：按时间戳与版本变化命名，写入配置的备份目录；
+/// 已开启备份加密时与其他本地备份一样以口令加密为 .sqlite.enc，不留明文副本
+async fn backup_before_migrate(
+  pool: &SqlitePool,
+  default_backups_dir: &Path,
+  app_version: &str,
+) -> Result<String, AppError> {
+  let backups_dir = match meta_repo::get_meta_value(pool, "backups_dir").await? {
+    Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+    _ => default_backups_dir.to_path_buf(),
+  };
+  std::fs::create_dir_all(&backups_dir)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建备份目录失败"))?;
+
+  let from_version = meta_repo::get_meta_value(pool, "app_version")
+    .await?
+    .unwrap_or_else(|| "unknown".to_string());
+  let now = Utc::now().timestamp();
+  let backup_path = backups_dir.join(format!(
+    "db_pre_migrate_{}_to_{}_{}.sqlite",
+    sanitize_version(&from_version),
+    sanitize_version(app_version),
+    now
+  ));
+  let backup_str = backup_path.to_string_lossy().into_owned();
+  let passphrase = backup_passphrase(pool).await?;
+
+  // VACUUM INTO 生成一致的独立数据库文件，不受未落盘日志影响
+  sqlx::query("VACUUM INTO ?")
+    .bind(&backup_str)
+    .execute(pool)
+    .await
+    .map_err(|err| {
+      AppError::new(ErrorCode::IoError, format!("升级前备份数据库失败: {}", err))
+    })?;
+
+  let Some(passphrase) = passphrase else {
+    return Ok(backup_str);
+  };
+  let encrypted_path = backup_path.with_extension("sqlite.enc");
+  let encrypted = std::fs::read(&backup_path)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "升级前备份数据库失败"))
+    .and_then(|plain| crypto::encrypt_backup(&passphrase, &plain))
+    .and_then(|data| {
+      std::fs::write(&encrypted_path, data)
+        .map_err(|_| AppError::new(ErrorCode::IoError, "写入加密备份失败"))
+    });
+  // 无论加密是否成功都删除明文文件，加密失败时中止升级而不是留下明文备份
+  let _ = std::fs::remove_file(&backup_path);
+  encrypted?;
+  Ok(encrypted_path.to_string_lossy().into_owned())
+}
+
+/// 已开启备份加密时读取备份口令（以本机密钥加密保存在 app_meta，与 backup_encryption_service 一致）
+async fn backup_passphrase(pool: &SqlitePool) -> Result<Option<String>, AppError> {
+  if meta_repo::get_meta_value(pool, "backup_encryption").await?.as_deref() != Some("1") {
+    return Ok(None);
+  }
+  let encrypted = meta_repo::get_meta_value(pool, "backup_passphrase")
+    .await?
+    .filter(|value| !value.is_empty())
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "已开启备份加密但未设置备份口令"))?;
+  let storage_root = meta_repo::get_meta_value(pool, "storage_root")
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
+  let key = crypto::load_or_create_key(&PathBuf::from(storage_root).join("db").join("secret.key"))?;
+  Ok(Some(crypto::decrypt_secret(&key, &encrypted)?))
+}
+
+fn sanitize_version(version: &str) -> String {
+  version
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '.' { c } else { '-' })
+    .collect()
+}
+
+/// 记录应用版本变化（上一版本、升级时间与升级前备份）
+async fn record_app_version(
+  pool: &SqlitePool,
+  app_version: &str,
+  migration_backup: Option<&str>,
+) -> Result<(), AppError> {
+  let previous = meta_repo::get_meta_value(pool, "app_version").await?;
+  if previous.as_deref() != Some(app_version) {
+    if let Some(previous) = previous.as_deref() {
+      meta_repo::set_meta_value(pool, "app_version_prev", previous).await?;
+    }
+    meta_repo::set_meta_value(pool, "app_version", app_version).await?;
+    meta_repo::set_meta_value(pool, "app_version_updated_at", &Utc::now().timestamp().to_string())
+      .await?;
+  }
+  if let Some(path) = migration_backup {
+    meta_repo::set_meta_value(pool, "last_migration_backup", path).await?;
+  }
+  Ok(())
+}
+
+fn migrate_error(err: sqlx::migrate::MigrateError) -> AppError {
+  AppError::new(ErrorCode::DbError, format!("数据库迁移失败: {}", err))
 }
 
 async fn init_app_meta(pool: &SqlitePool, storage_root: &PathBuf) -> Result<(), AppError> {
@@ -96,6 +436,128 @@ async fn init_app_meta(pool: &SqlitePool, storage_root: &PathBuf) -> Result<(),
     .execute(pool)
     .await?;
 
+  // 登录失败锁定策略：连续失败次数阈值、计数窗口与锁定时长（分钟）
+  for (key, value) in [
+    ("login_max_failures", "5"),
+    ("login_fail_window_minutes", "15"),
+    ("login_lockout_minutes", "15"),
+  ] {
+    sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+      .bind(key)
+      .bind(value)
+      .execute(pool)
+      .await?;
+  }
+
+  // 密码策略：最小长度、字符类别数、禁止包含用户名、禁止复用最近 N 次密码
+  for (key, value) in [
+    ("pwd_min_length", "6"),
+    ("pwd_min_char_classes", "1"),
+    ("pwd_disallow_username", "1"),
+    ("pwd_history_count", "3"),
+  ] {
+    sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+      .bind(key)
+      .bind(value)
+      .execute(pool)
+      .await?;
+  }
+
+  // 共享终端：PIN 切换会话有效期、空闲自动锁屏与会话空闲超时（分钟，0 表示不启用），
+  // 以及每笔流水重新选择记录人的终端模式（默认关闭，复用窗口 60 秒）
+  for (key, value) in [
+    ("pin_session_minutes", "30"),
+    ("idle_lock_minutes", "0"),
+    ("session_idle_minutes", "0"),
+    ("kiosk_mode", "0"),
+    ("kiosk_operator_window_secs", "60"),
+  ] {
+    sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+      .bind(key)
+      .bind(value)
+      .execute(pool)
+      .await?;
+  }
+
+  // 审计详略：默认记录全部，可调整为仅写操作或仅关键操作
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("audit_verbosity")
+    .bind("all")
+    .execute(pool)
+    .await?;
+
+  // 库存计价：默认移动加权平均，可切换为先进先出
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("valuation_method")
+    .bind("moving_average")
+    .execute(pool)
+    .await?;
+
+  // 流水号格式：默认沿用 T+UUID，可切换为结构化序号（可带仓库前缀）
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("txn_no_scheme")
+    .bind("uuid")
+    .execute(pool)
+    .await?;
+
+  // 系统生成文本（如盘点调整备注）的语言
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("locale")
+    .bind("zh-CN")
+    .execute(pool)
+    .await?;
+
+  // 流水位置：默认不采集，开启后移动端记录流水时附带粗略位置
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("txn_location_capture")
+    .bind("0")
+    .execute(pool)
+    .await?;
+
+  // HTTP 接口：默认关闭，仅监听本机地址，令牌由管理员生成后只保存摘要（http_api_token_hash）
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("http_api_enabled")
+    .bind("0")
+    .execute(pool)
+    .await?;
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("http_api_port")
+    .bind("17420")
+    .execute(pool)
+    .await?;
+  // 旧版本明文保存的令牌换算为摘要后删除，已发放的令牌继续有效
+  if let Some(token) = meta_repo::get_meta_value(pool, "http_api_token").await? {
+    if !token.is_empty() {
+      meta_repo::set_meta_value(pool, "http_api_token_hash", &crypto::hash_token(&token)).await?;
+    }
+    sqlx::query("DELETE FROM app_meta WHERE k = 'http_api_token'")
+      .execute(pool)
+      .await?;
+  }
+
+  // 窗口与后台任务：默认关闭主窗口即退出、启动时显示主窗口、不自动备份
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("close_to_tray")
+    .bind("0")
+    .execute(pool)
+    .await?;
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("start_minimized")
+    .bind("0")
+    .execute(pool)
+    .await?;
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("auto_backup_hours")
+    .bind("0")
+    .execute(pool)
+    .await?;
+  // 远程备份：默认不上传（none/s3/webdav）
+  sqlx::query("INSERT OR IGNORE INTO app_meta (k, v) VALUES (?, ?)")
+    .bind("remote_backup_kind")
+    .bind("none")
+    .execute(pool)
+    .await?;
+
   Ok(())
 }
 
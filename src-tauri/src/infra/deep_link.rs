@@ -0,0 +1,114 @@
+// 深度链接：处理 invctl:// 链接（邮件中的流水号、打印的二维码等），聚焦窗口并通知前端跳转
+//
+// 支持的链接：
+// - invctl://txn/<流水号>   → 流水查询页按流水号检索
+// - invctl://item/<物品编码> → 物品页按编码检索
+//
+// 前端可能尚未就绪（启动屏、未登录），因此链接先保存为待处理路径，
+// 再推送事件；前端在就绪或收到事件时通过 take_pending_deep_link 取走。
+use parking_lot::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+/// 注册的 URI scheme
+pub const SCHEME: &str = "invctl";
+
+/// 深度链接事件名（无负载，前端收到后拉取待处理路径）
+pub const DEEP_LINK_EVENT: &str = "deep_link";
+
+const MAX_VALUE_LEN: usize = 64;
+
+/// 待处理的跳转路径（在 setup 中注册）
+#[derive(Default)]
+pub struct DeepLinkState {
+  pending: Mutex<Option<String>>,
+}
+
+impl DeepLinkState {
+  /// 取走待处理路径（只返回一次）
+  pub fn take(&self) -> Option<String> {
+    self.pending.lock().take()
+  }
+}
+
+/// 从启动参数中查找深度链接（Windows/Linux 由系统以参数形式传入）
+pub fn find_in_args<I>(args: I) -> Option<String>
+where
+  I: IntoIterator<Item = String>,
+{
+  let prefix = format!("{}://", SCHEME);
+  args.into_iter().find(|arg| arg.starts_with(&prefix))
+}
+
+/// 将深度链接解析为前端路由，不支持的链接返回 None
+pub fn resolve_path(url: &str) -> Option<String> {
+  let rest = url.strip_prefix(SCHEME)?.strip_prefix("://")?;
+  // 忽略查询串与锚点
+  let rest = rest.split(['?', '#']).next().unwrap_or("");
+  let (kind, value) = rest.trim_end_matches('/').split_once('/')?;
+  let value = percent_decode(value)?;
+  let value = value.trim();
+  if value.is_empty()
+    || value.chars().count() > MAX_VALUE_LEN
+    || value.chars().any(|c| c.is_control() || c == '/')
+  {
+    return None;
+  }
+  let route = match kind.to_ascii_lowercase().as_str() {
+    "txn" => "/txns",
+    "item" => "/items",
+    _ => return None,
+  };
+  Some(format!("{}?keyword={}", route, percent_encode(value)))
+}
+
+/// 处理深度链接：保存待处理路径、聚焦窗口并通知前端
+pub fn dispatch(handle: &AppHandle, url: &str) {
+  if let Some(path) = resolve_path(url) {
+    open_path(handle, &path);
+  }
+}
+
+/// 跳转到前端路由（深度链接、托盘快捷操作共用）
+pub fn open_path(handle: &AppHandle, path: &str) {
+  if let Some(state) = handle.try_state::<DeepLinkState>() {
+    *state.pending.lock() = Some(path.to_string());
+  }
+  // 启动屏仍在时不切换窗口，主窗口显示后前端会主动拉取
+  if handle.get_webview_window("splashscreen").is_none() {
+    if let Some(window) = handle.get_webview_window("main") {
+      let _ = window.unminimize();
+      let _ = window.show();
+      let _ = window.set_focus();
+    }
+  }
+  let _ = handle.emit(DEEP_LINK_EVENT, ());
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+  let bytes = value.as_bytes();
+  let mut out = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+  while index < bytes.len() {
+    if bytes[index] == b'%' {
+      let hex = value.get(index + 1..index + 3)?;
+      out.push(u8::from_str_radix(hex, 16).ok()?);
+      index += 3;
+    } else {
+      out.push(bytes[index]);
+      index += 1;
+    }
+  }
+  String::from_utf8(out).ok()
+}
+
+fn percent_encode(value: &str) -> String {
+  let mut out = String::with_capacity(value.len());
+  for byte in value.bytes() {
+    if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.' | b'~') {
+      out.push(byte as char);
+    } else {
+      out.push_str(&format!("%{:02X}", byte));
+    }
+  }
+  out
+}
@@ -1,5 +1,6 @@
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(not(target_os = "android"))]
 use std::process::Command;
@@ -29,16 +30,127 @@ pub fn is_dir_writable(path: &Path) -> Result<bool, AppError> {
   }
 }
 
+/// 目录迁移进度，每处理完一个文件回调一次
+#[derive(Debug, Clone, Copy)]
+pub struct CopyProgress<'a> {
+  pub current: &'a Path,
+  pub files_done: u64,
+  pub files_total: u64,
+  pub bytes_done: u64,
+  pub bytes_total: u64,
+}
+
 pub fn move_or_copy_dir(src: &Path, dest: &Path) -> Result<(), AppError> {
+  move_dir_with_progress(src, dest, &AtomicBool::new(false), &mut |_| {})
+}
+
+/// 带进度、可取消的目录迁移：同盘直接 rename；跨盘逐个复制文件，复制前检查取消标志，
+/// 全部复制后逐个校验文件大小，一致才删除源目录；取消或失败时删除已复制的文件，源目录保持不变
+pub fn move_dir_with_progress(
+  src: &Path,
+  dest: &Path,
+  cancel: &AtomicBool,
+  on_progress: &mut dyn FnMut(CopyProgress),
+) -> Result<(), AppError> {
+  let mut files = Vec::new();
+  collect_files(src, Path::new(""), &mut files)?;
+  let files_total = files.len() as u64;
+  let bytes_total = files.iter().map(|(_, size)| size).sum();
+  if cancel.load(Ordering::SeqCst) {
+    return Err(AppError::new(ErrorCode::Conflict, "迁移已取消"));
+  }
+
   if let Ok(()) = fs::rename(src, dest) {
+    on_progress(CopyProgress {
+      current: dest,
+      files_done: files_total,
+      files_total,
+      bytes_done: bytes_total,
+      bytes_total,
+    });
     return Ok(());
   }
 
-  copy_dir_recursive(src, dest)?;
+  let mut copied: Vec<PathBuf> = Vec::with_capacity(files.len());
+  let result = copy_files(src, dest, &files, cancel, on_progress, &mut copied)
+    .and_then(|()| verify_copied_sizes(dest, &files));
+  if let Err(err) = result {
+    for path in copied {
+      let _ = fs::remove_file(path);
+    }
+    return Err(err);
+  }
   remove_dir_recursive(src)?;
   Ok(())
 }
 
+/// 递归列出目录下的文件（相对路径, 字节数）
+fn collect_files(root: &Path, relative: &Path, out: &mut Vec<(PathBuf, u64)>) -> Result<(), AppError> {
+  let dir = root.join(relative);
+  for entry in fs::read_dir(&dir).map_err(|_| AppError::new(ErrorCode::IoError, "读取目录失败"))? {
+    let entry = entry.map_err(|_| AppError::new(ErrorCode::IoError, "读取目录失败"))?;
+    let path = relative.join(entry.file_name());
+    let metadata = entry
+      .metadata()
+      .map_err(|_| AppError::new(ErrorCode::IoError, "读取文件信息失败"))?;
+    if metadata.is_dir() {
+      collect_files(root, &path, out)?;
+    } else {
+      out.push((path, metadata.len()));
+    }
+  }
+  Ok(())
+}
+
+fn copy_files(
+  src: &Path,
+  dest: &Path,
+  files: &[(PathBuf, u64)],
+  cancel: &AtomicBool,
+  on_progress: &mut dyn FnMut(CopyProgress),
+  copied: &mut Vec<PathBuf>,
+) -> Result<(), AppError> {
+  let files_total = files.len() as u64;
+  let bytes_total = files.iter().map(|(_, size)| size).sum();
+  let mut bytes_done = 0;
+  ensure_dir(dest)?;
+  for (index, (relative, size)) in files.iter().enumerate() {
+    if cancel.load(Ordering::SeqCst) {
+      return Err(AppError::new(ErrorCode::Conflict, "迁移已取消"));
+    }
+    let target = dest.join(relative);
+    if let Some(parent) = target.parent() {
+      ensure_dir(parent)?;
+    }
+    fs::copy(src.join(relative), &target)
+      .map_err(|_| AppError::new(ErrorCode::IoError, "复制文件失败"))?;
+    copied.push(target.clone());
+    bytes_done += size;
+    on_progress(CopyProgress {
+      current: &target,
+      files_done: index as u64 + 1,
+      files_total,
+      bytes_done,
+      bytes_total,
+    });
+  }
+  Ok(())
+}
+
+/// 逐个对比目标文件与源文件大小，不一致时不删除源目录
+fn verify_copied_sizes(dest: &Path, files: &[(PathBuf, u64)]) -> Result<(), AppError> {
+  for (relative, size) in files {
+    let copied_size = fs::metadata(dest.join(relative)).map(|metadata| metadata.len()).ok();
+    if copied_size != Some(*size) {
+      return Err(AppError::new(
+        ErrorCode::IoError,
+        format!("复制校验失败：{}", relative.to_string_lossy()),
+      ));
+    }
+  }
+  Ok(())
+}
+
 pub fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<(), AppError> {
   ensure_dir(dest)?;
   for entry in fs::read_dir(src).map_err(|_| AppError::new(ErrorCode::IoError, "读取目录失败"))? {
@@ -0,0 +1,278 @@
+// 单实例锁：防止多个进程同时写入同一数据目录（SQLite 文件与照片目录）
+//
+// 协议：首个实例对锁文件加系统文件锁（进程退出时由系统释放），在 127.0.0.1 随机端口监听，
+// 并将端口与进程号写入地址文件，另生成随机口令写入仅当前用户可读的口令文件。
+// 后续实例加锁失败时读取地址文件与口令并连接，收到握手标识后发送一行「口令 指令」，口令不符的连接直接关闭：
+// - FOCUS：请求已运行实例显示并聚焦主窗口，本进程随后退出
+// - OPEN <链接>：携带深度链接启动时，交由已运行实例处理跳转，本进程随后退出
+// - TAKEOVER：请求已运行实例安全退出，本进程等到文件锁释放后接管数据目录
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::infra::crypto;
+
+const LOCK_FILE_NAME: &str = "instance.lock";
+// Windows 下加锁的文件不能被其他进程读取，监听地址单独存放
+const ADDR_FILE_NAME: &str = "instance.addr";
+// 每次启动重新生成的指令口令，防止本机其他用户或程序冒充后续实例发送指令
+const NONCE_FILE_NAME: &str = "instance.nonce";
+const NONCE_LEN: usize = 32;
+const HANDSHAKE_PREFIX: &str = "INVENTORY_CONTROL";
+const TAKEOVER_WAIT: Duration = Duration::from_secs(15);
+// 持锁实例可能尚未写入地址文件，连接前等待的时长
+const ADDR_WAIT: Duration = Duration::from_secs(3);
+
+/// 其他实例发来的请求
+#[derive(Debug, Clone)]
+pub enum InstanceRequest {
+  Focus,
+  // 打开深度链接
+  Open(String),
+  Takeover,
+}
+
+/// 实例锁（进程存活期间持有文件锁，释放时删除地址文件）
+pub struct InstanceLock {
+  _file: File,
+  addr_path: PathBuf,
+  nonce_path: PathBuf,
+}
+
+impl Drop for InstanceLock {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_file(&self.addr_path);
+    let _ = std::fs::remove_file(&self.nonce_path);
+  }
+}
+
+/// 获取数据目录的单实例锁
+///
+/// 返回 `Ok(None)` 表示已有实例在运行且已请求其聚焦（或处理深度链接），本进程应直接退出。
+pub fn acquire<F>(
+  dir: &Path,
+  takeover: bool,
+  deep_link: Option<&str>,
+  on_request: F,
+) -> Result<Option<InstanceLock>, AppError>
+where
+  F: Fn(InstanceRequest) + Send + 'static,
+{
+  std::fs::create_dir_all(dir)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建应用数据目录失败"))?;
+  let addr_path = dir.join(ADDR_FILE_NAME);
+  let nonce_path = dir.join(NONCE_FILE_NAME);
+  let file = OpenOptions::new()
+    .read(true)
+    .write(true)
+    .create(true)
+    .truncate(false)
+    .open(dir.join(LOCK_FILE_NAME))
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建实例锁失败"))?;
+
+  // 文件锁由系统保证原子性：同一时刻只有一个进程能加锁成功，进程异常退出后自动释放
+  if !try_lock(&file)? {
+    let Some((addr, pid)) = wait_for_instance(&addr_path) else {
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        "程序正在其他进程中启动或退出，请稍后再试",
+      ));
+    };
+    // 口令在地址文件之前写入，能探测到实例时口令已就绪
+    let nonce = std::fs::read_to_string(&nonce_path).unwrap_or_default();
+    let nonce = nonce.trim();
+    if !takeover {
+      let command = match deep_link {
+        Some(url) => format!("OPEN {}", url),
+        None => "FOCUS".to_string(),
+      };
+      if send_request(&addr, nonce, &command) {
+        return Ok(None);
+      }
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        format!("程序已在运行（进程 {}），同一数据目录不允许同时打开多个实例", pid),
+      ));
+    }
+    if !send_request(&addr, nonce, "TAKEOVER") || !wait_for_exit(&file)? {
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        format!("原实例（进程 {}）未能退出，接管失败", pid),
+      ));
+    }
+  }
+
+  // 已持有文件锁，先写入口令再发布本实例的监听地址
+  let nonce = crypto::generate_token(NONCE_LEN);
+  write_nonce(&nonce_path, &nonce)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "写入实例锁文件失败"))?;
+  let listener = TcpListener::bind("127.0.0.1:0")
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建实例锁失败"))?;
+  let port = listener
+    .local_addr()
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建实例锁失败"))?
+    .port();
+  std::fs::write(&addr_path, format!("{}\n{}\n", port, std::process::id()))
+    .map_err(|_| AppError::new(ErrorCode::IoError, "写入实例锁文件失败"))?;
+
+  std::thread::spawn(move || serve_requests(listener, nonce, on_request));
+
+  Ok(Some(InstanceLock {
+    _file: file,
+    addr_path,
+    nonce_path,
+  }))
+}
+
+/// 写入口令文件：先删除旧文件再新建，Unix 下权限为 0600；
+/// Windows 下应用数据目录默认仅当前用户可访问
+fn write_nonce(path: &Path, nonce: &str) -> std::io::Result<()> {
+  let _ = std::fs::remove_file(path);
+  let mut options = OpenOptions::new();
+  options.write(true).create_new(true);
+  #[cfg(unix)]
+  {
+    use std::os::unix::fs::OpenOptionsExt;
+    options.mode(0o600);
+  }
+  let mut file = options.open(path)?;
+  writeln!(file, "{}", nonce)
+}
+
+/// 尝试加排他文件锁，返回是否成功；被其他进程持有时返回 false
+fn try_lock(file: &File) -> Result<bool, AppError> {
+  match file.try_lock() {
+    Ok(()) => Ok(true),
+    Err(TryLockError::WouldBlock) => Ok(false),
+    Err(TryLockError::Error(_)) => Err(AppError::new(ErrorCode::IoError, "创建实例锁失败")),
+  }
+}
+
+/// 等待持锁实例发布监听地址并响应握手
+fn wait_for_instance(addr_path: &Path) -> Option<(SocketAddr, String)> {
+  let started = Instant::now();
+  loop {
+    if let Some(found) = probe_running_instance(addr_path) {
+      return Some(found);
+    }
+    if started.elapsed() >= ADDR_WAIT {
+      return None;
+    }
+    std::thread::sleep(Duration::from_millis(200));
+  }
+}
+
+/// 探测地址文件记录的实例是否仍在运行，返回其地址与进程号
+fn probe_running_instance(path: &Path) -> Option<(SocketAddr, String)> {
+  let content = std::fs::read_to_string(path).ok()?;
+  let port = content.lines().next()?.trim().parse::<u16>().ok()?;
+  let addr = SocketAddr::from(([127, 0, 0, 1], port));
+  let stream = connect(&addr)?;
+  let pid = read_handshake(&mut BufReader::new(stream))?;
+  Some((addr, pid))
+}
+
+/// 向已运行实例发送带口令的指令，返回对方是否确认
+fn send_request(addr: &SocketAddr, nonce: &str, command: &str) -> bool {
+  let Some(mut stream) = connect(addr) else {
+    return false;
+  };
+  let Ok(reader_stream) = stream.try_clone() else {
+    return false;
+  };
+  let mut reader = BufReader::new(reader_stream);
+  if read_handshake(&mut reader).is_none() {
+    return false;
+  }
+  if writeln!(stream, "{} {}", nonce, command).is_err() {
+    return false;
+  }
+  let mut reply = String::new();
+  reader.read_line(&mut reply).is_ok() && reply.trim() == "OK"
+}
+
+/// 等待原实例退出并释放文件锁，成功时本进程已持有锁
+fn wait_for_exit(file: &File) -> Result<bool, AppError> {
+  let started = Instant::now();
+  while started.elapsed() < TAKEOVER_WAIT {
+    if try_lock(file)? {
+      return Ok(true);
+    }
+    std::thread::sleep(Duration::from_millis(200));
+  }
+  Ok(false)
+}
+
+fn connect(addr: &SocketAddr) -> Option<TcpStream> {
+  let stream = TcpStream::connect_timeout(addr, Duration::from_millis(500)).ok()?;
+  let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+  Some(stream)
+}
+
+/// 读取握手行，端口可能已被其他程序复用，需校验标识
+fn read_handshake(reader: &mut BufReader<TcpStream>) -> Option<String> {
+  let mut line = String::new();
+  reader.read_line(&mut line).ok()?;
+  line
+    .trim()
+    .strip_prefix(HANDSHAKE_PREFIX)
+    .map(|pid| pid.trim().to_string())
+}
+
+/// 响应其他实例的握手与指令
+fn serve_requests<F>(listener: TcpListener, nonce: String, on_request: F)
+where
+  F: Fn(InstanceRequest),
+{
+  for stream in listener.incoming() {
+    let Ok(mut stream) = stream else {
+      continue;
+    };
+    let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+    if writeln!(stream, "{} {}", HANDSHAKE_PREFIX, std::process::id()).is_err() {
+      continue;
+    }
+    let Ok(reader_stream) = stream.try_clone() else {
+      continue;
+    };
+    let mut command = String::new();
+    // 仅探测时对方不会发送指令，读取超时后忽略
+    if BufReader::new(reader_stream).read_line(&mut command).is_err() {
+      continue;
+    }
+    // 首个字段须为本实例口令，不符时不回复直接关闭
+    let Some(command) = command
+      .trim()
+      .split_once(' ')
+      .filter(|(provided, _)| nonce_eq(&nonce, provided))
+      .map(|(_, command)| command.trim())
+    else {
+      continue;
+    };
+    let request = match command {
+      "FOCUS" => InstanceRequest::Focus,
+      "TAKEOVER" => InstanceRequest::Takeover,
+      _ => match command.strip_prefix("OPEN ") {
+        Some(url) => InstanceRequest::Open(url.to_string()),
+        None => continue,
+      },
+    };
+    let _ = writeln!(stream, "OK");
+    drop(stream);
+    on_request(request);
+  }
+}
+
+/// 定长比较口令，避免按前缀逐字节提前返回
+fn nonce_eq(expected: &str, provided: &str) -> bool {
+  let (expected, provided) = (expected.as_bytes(), provided.as_bytes());
+  expected.len() == provided.len()
+    && expected
+      .iter()
+      .zip(provided)
+      .fold(0_u8, |acc, (a, b)| acc | (a ^ b))
+      == 0
+}
@@ -0,0 +1,228 @@
+// 邮件发送：最小化的 SMTP 客户端与发送队列的重试策略
+//
+// 仅支持明文连接（不含 STARTTLS/SMTPS），适用于内网邮件中继；配置了账号时使用 AUTH PLAIN 登录。
+// 正文与附件按 MIME multipart/mixed 组织，标题与文件名按 RFC 2047 编码，均使用 base64 传输。
+// 服务器以 5xx 拒绝时视为永久失败不再重试，连接失败、超时与 4xx 等临时错误按退避间隔重试。
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::Local;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+
+/// 连接与单次读写超时
+const SMTP_TIMEOUT_SECS: u64 = 30;
+
+/// 队列中邮件的重试间隔（秒），依次为第 1、2、3、4 次失败后；用完后标记为失败
+const RETRY_DELAYS_SECS: [i64; 4] = [5 * 60, 15 * 60, 60 * 60, 4 * 60 * 60];
+
+/// 连接安全方式：目前仅支持 none（不加密）
+pub const SMTP_TLS_MODES: [&str; 3] = ["none", "starttls", "tls"];
+
+#[derive(Debug, Clone)]
+pub struct SmtpServer {
+  pub host: String,
+  pub port: u16,
+  // 为空时不登录
+  pub username: String,
+  pub password: String,
+  pub from: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MailAttachment {
+  pub file_name: String,
+  pub content: Vec<u8>,
+}
+
+/// 发送失败：permanent 为 true 时重试无意义（如收件人被拒、认证失败）
+#[derive(Debug, Clone)]
+pub struct SendError {
+  pub permanent: bool,
+  pub message: String,
+}
+
+impl From<SendError> for AppError {
+  fn from(err: SendError) -> Self {
+    AppError::new(ErrorCode::IoError, err.message)
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+  pub to: Vec<String>,
+  pub subject: String,
+  pub body: String,
+  pub attachments: Vec<MailAttachment>,
+}
+
+/// 已失败 attempts 次后的下一次重试间隔（秒），None 表示不再重试
+pub fn next_retry_delay(attempts: i64) -> Option<i64> {
+  usize::try_from(attempts - 1).ok().and_then(|index| RETRY_DELAYS_SECS.get(index).copied())
+}
+
+/// 发送邮件；网络读写为阻塞操作，放到阻塞线程池执行
+pub async fn send_mail(server: SmtpServer, message: MailMessage) -> Result<(), SendError> {
+  tokio::task::spawn_blocking(move || send_blocking(&server, &message))
+    .await
+    .map_err(|_| transient("邮件发送中断"))?
+}
+
+fn send_blocking(server: &SmtpServer, message: &MailMessage) -> Result<(), SendError> {
+  if message.to.is_empty() {
+    return Err(SendError { permanent: true, message: "未填写收件人".to_string() });
+  }
+  let timeout = Duration::from_secs(SMTP_TIMEOUT_SECS);
+  let address = (server.host.as_str(), server.port)
+    .to_socket_addrs()
+    .map_err(|_| transient(format!("无法解析邮件服务器地址 {}", server.host)))?
+    .next()
+    .ok_or_else(|| transient(format!("无法解析邮件服务器地址 {}", server.host)))?;
+  let stream = TcpStream::connect_timeout(&address, timeout)
+    .map_err(|err| transient(format!("连接邮件服务器失败：{}", err)))?;
+  stream.set_read_timeout(Some(timeout)).map_err(io_error)?;
+  stream.set_write_timeout(Some(timeout)).map_err(io_error)?;
+  let mut session = SmtpSession {
+    reader: BufReader::new(stream.try_clone().map_err(io_error)?),
+    writer: stream,
+  };
+
+  session.expect(&[220])?;
+  session.command("EHLO localhost", &[250])?;
+  if !server.username.is_empty() {
+    let token = BASE64.encode(format!("\0{}\0{}", server.username, server.password));
+    session
+      .command(&format!("AUTH PLAIN {}", token), &[235])
+      .map_err(|err| SendError {
+        permanent: err.permanent,
+        message: format!("邮件服务器登录失败：{}", err.message),
+      })?;
+  }
+  session.command(&format!("MAIL FROM:<{}>", server.from), &[250])?;
+  for to in &message.to {
+    session.command(&format!("RCPT TO:<{}>", to), &[250, 251])?;
+  }
+  session.command("DATA", &[354])?;
+  let data = build_message(&server.from, message);
+  session.write(&dot_stuff(&data))?;
+  session.command(".", &[250])?;
+  // 邮件已投递，QUIT 失败不影响结果
+  let _ = session.command("QUIT", &[221]);
+  Ok(())
+}
+
+struct SmtpSession {
+  reader: BufReader<TcpStream>,
+  writer: TcpStream,
+}
+
+impl SmtpSession {
+  fn write(&mut self, data: &str) -> Result<(), SendError> {
+    self.writer.write_all(data.as_bytes()).map_err(io_error)
+  }
+
+  fn command(&mut self, line: &str, codes: &[u16]) -> Result<(), SendError> {
+    self.write(&format!("{}\r\n", line))?;
+    self.expect(codes)
+  }
+
+  /// 读取一条（可能多行的）应答，状态码不在预期范围内时返回服务器的说明；5xx 为永久失败
+  fn expect(&mut self, codes: &[u16]) -> Result<(), SendError> {
+    let mut text = String::new();
+    loop {
+      let mut line = String::new();
+      let read = self.reader.read_line(&mut line).map_err(io_error)?;
+      if read == 0 {
+        return Err(transient("邮件服务器断开连接"));
+      }
+      let line = line.trim_end();
+      text.push_str(line.get(4..).unwrap_or_default());
+      // “250-” 表示还有后续行，“250 ” 为最后一行
+      if line.as_bytes().get(3) != Some(&b'-') {
+        let code = line.get(..3).and_then(|value| value.parse::<u16>().ok());
+        return match code {
+          Some(code) if codes.contains(&code) => Ok(()),
+          Some(code) => Err(SendError {
+            permanent: (500..600).contains(&code),
+            message: format!("邮件服务器返回 {}：{}", code, text),
+          }),
+          None => Err(transient("邮件服务器应答无效")),
+        };
+      }
+      text.push(' ');
+    }
+  }
+}
+
+fn build_message(from: &str, message: &MailMessage) -> String {
+  let boundary = format!("----=_Part_{}", Uuid::new_v4().simple());
+  let mut data = String::new();
+  data.push_str(&format!("From: <{}>\r\n", from));
+  data.push_str(&format!(
+    "To: {}\r\n",
+    message.to.iter().map(|to| format!("<{}>", to)).collect::<Vec<_>>().join(", ")
+  ));
+  data.push_str(&format!("Subject: {}\r\n", encode_header(&message.subject)));
+  data.push_str(&format!("Date: {}\r\n", Local::now().to_rfc2822()));
+  data.push_str(&format!("Message-ID: <{}@inventory-control>\r\n", Uuid::new_v4()));
+  data.push_str("MIME-Version: 1.0\r\n");
+  data.push_str(&format!("Content-Type: multipart/mixed; boundary=\"{}\"\r\n\r\n", boundary));
+
+  data.push_str(&format!("--{}\r\n", boundary));
+  data.push_str("Content-Type: text/plain; charset=UTF-8\r\nContent-Transfer-Encoding: base64\r\n\r\n");
+  data.push_str(&wrap_base64(message.body.as_bytes()));
+  for attachment in &message.attachments {
+    let name = encode_header(&attachment.file_name);
+    data.push_str(&format!("--{}\r\n", boundary));
+    data.push_str(&format!("Content-Type: application/octet-stream; name=\"{}\"\r\n", name));
+    data.push_str("Content-Transfer-Encoding: base64\r\n");
+    data.push_str(&format!("Content-Disposition: attachment; filename=\"{}\"\r\n\r\n", name));
+    data.push_str(&wrap_base64(&attachment.content));
+  }
+  data.push_str(&format!("--{}--\r\n", boundary));
+  data
+}
+
+/// 非 ASCII 的标题/文件名按 RFC 2047 编码
+fn encode_header(value: &str) -> String {
+  if value.is_ascii() {
+    value.to_string()
+  } else {
+    format!("=?UTF-8?B?{}?=", BASE64.encode(value))
+  }
+}
+
+/// base64 按每行 76 个字符折行
+fn wrap_base64(content: &[u8]) -> String {
+  let encoded = BASE64.encode(content);
+  let mut wrapped = String::with_capacity(encoded.len() + encoded.len() / 76 * 2 + 2);
+  for chunk in encoded.as_bytes().chunks(76) {
+    wrapped.push_str(std::str::from_utf8(chunk).unwrap_or_default());
+    wrapped.push_str("\r\n");
+  }
+  wrapped
+}
+
+/// 以 “.” 开头的行前补一个 “.”，避免被当作 DATA 结束标记
+fn dot_stuff(data: &str) -> String {
+  data
+    .split("\r\n")
+    .map(|line| if line.starts_with('.') { format!(".{}", line) } else { line.to_string() })
+    .collect::<Vec<_>>()
+    .join("\r\n")
+}
+
+fn io_error(err: std::io::Error) -> SendError {
+  transient(format!("邮件服务器通信失败：{}", err))
+}
+
+fn transient(message: impl Into<String>) -> SendError {
+  SendError {
+    permanent: false,
+    message: message.into(),
+  }
+}
@@ -0,0 +1,141 @@
+// 命令耗时统计：按审计动作在内存中累计次数与耗时分布，进程重启后清零
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use parking_lot::Mutex;
+
+/// 耗时分桶上界（毫秒），超过最后一档的计入溢出桶
+const BUCKET_BOUNDS_MS: [u64; 10] = [5, 10, 25, 50, 100, 250, 500, 1000, 2500, 5000];
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+  since: 0,
+  commands: BTreeMap::new(),
+});
+
+struct Metrics {
+  since: i64,
+  commands: BTreeMap<&'static str, CommandMetrics>,
+}
+
+#[derive(Default)]
+struct CommandMetrics {
+  count: u64,
+  error_count: u64,
+  total_us: u64,
+  max_us: u64,
+  // 与 BUCKET_BOUNDS_MS 对应，末位为溢出桶
+  buckets: [u64; BUCKET_BOUNDS_MS.len() + 1],
+}
+
+/// 耗时分桶
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LatencyBucket {
+  // 桶上界（毫秒），溢出桶为空
+  pub le_ms: Option<u64>,
+  pub count: u64,
+}
+
+/// 单个动作的耗时统计
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CommandStats {
+  pub action: String,
+  pub count: u64,
+  pub error_count: u64,
+  pub total_ms: f64,
+  pub avg_ms: f64,
+  pub max_ms: f64,
+  // 按分桶估算的分位数（取所在桶的上界，落入溢出桶时取最大值）
+  pub p50_ms: f64,
+  pub p95_ms: f64,
+  pub buckets: Vec<LatencyBucket>,
+}
+
+/// 耗时统计快照
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PerformanceStats {
+  // 统计开始时间（首次记录或上次清零）
+  pub since: Option<i64>,
+  // 按累计耗时从高到低排序
+  pub items: Vec<CommandStats>,
+}
+
+/// 记录一次命令执行耗时
+pub fn record(action: &'static str, elapsed: Duration, ok: bool) {
+  let elapsed_us = elapsed.as_micros().min(u64::MAX as u128) as u64;
+  let bucket = BUCKET_BOUNDS_MS
+    .iter()
+    .position(|bound| elapsed_us <= bound * 1000)
+    .unwrap_or(BUCKET_BOUNDS_MS.len());
+  let mut metrics = METRICS.lock();
+  if metrics.since == 0 {
+    metrics.since = Utc::now().timestamp();
+  }
+  let entry = metrics.commands.entry(action).or_default();
+  entry.count += 1;
+  if !ok {
+    entry.error_count += 1;
+  }
+  entry.total_us = entry.total_us.saturating_add(elapsed_us);
+  entry.max_us = entry.max_us.max(elapsed_us);
+  entry.buckets[bucket] += 1;
+}
+
+/// 当前统计快照
+pub fn snapshot() -> PerformanceStats {
+  let metrics = METRICS.lock();
+  let mut items: Vec<CommandStats> = metrics
+    .commands
+    .iter()
+    .map(|(action, entry)| CommandStats {
+      action: action.to_string(),
+      count: entry.count,
+      error_count: entry.error_count,
+      total_ms: to_ms(entry.total_us),
+      avg_ms: to_ms(entry.total_us / entry.count.max(1)),
+      max_ms: to_ms(entry.max_us),
+      p50_ms: percentile(entry, 0.5),
+      p95_ms: percentile(entry, 0.95),
+      buckets: entry
+        .buckets
+        .iter()
+        .enumerate()
+        .map(|(index, count)| LatencyBucket {
+          le_ms: BUCKET_BOUNDS_MS.get(index).copied(),
+          count: *count,
+        })
+        .collect(),
+    })
+    .collect();
+  items.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+  PerformanceStats {
+    since: (metrics.since > 0).then_some(metrics.since),
+    items,
+  }
+}
+
+/// 清零统计
+pub fn reset() {
+  let mut metrics = METRICS.lock();
+  metrics.since = Utc::now().timestamp();
+  metrics.commands.clear();
+}
+
+fn percentile(entry: &CommandMetrics, ratio: f64) -> f64 {
+  let target = ((entry.count as f64) * ratio).ceil().max(1.0) as u64;
+  let mut seen = 0;
+  for (index, count) in entry.buckets.iter().enumerate() {
+    seen += count;
+    if seen >= target {
+      return match BUCKET_BOUNDS_MS.get(index) {
+        Some(bound) => (*bound as f64).min(to_ms(entry.max_us)),
+        None => to_ms(entry.max_us),
+      };
+    }
+  }
+  to_ms(entry.max_us)
+}
+
+fn to_ms(us: u64) -> f64 {
+  (us as f64 / 1000.0 * 100.0).round() / 100.0
+}
@@ -1,3 +1,12 @@
+pub mod barcode;
 pub mod crypto;
 pub mod db;
+pub mod deep_link;
 pub mod fs;
+pub mod instance;
+pub mod mailer;
+pub mod metrics;
+pub mod remote_store;
+pub mod startup;
+pub mod tray;
+pub mod zip;
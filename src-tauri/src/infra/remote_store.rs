@@ -0,0 +1,371 @@
+// 远程备份存储：S3 兼容对象存储与 WebDAV
+//
+// 仅实现备份需要的上传、列举与下载：
+// - S3 使用路径风格地址（endpoint/bucket/key）与 SigV4 签名，兼容 MinIO 等自建服务
+// - WebDAV 使用 Basic 认证，上传前创建目标目录（仅一级），PROPFIND 列举
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method, Response, Url};
+use sha2::{Digest, Sha256};
+
+use crate::domain::errors::{AppError, ErrorCode};
+
+/// 单次请求超时（备份文件可能较大）
+const REQUEST_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Debug, Clone)]
+pub enum RemoteStore {
+  S3 {
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    // 对象键前缀，为空或以 / 结尾
+    prefix: String,
+    access_key: String,
+    secret_key: String,
+  },
+  WebDav {
+    // 备份目录地址，以 / 结尾
+    base: Url,
+    username: String,
+    password: String,
+  },
+}
+
+/// 远程文件
+#[derive(Debug, serde::Serialize)]
+pub struct RemoteObject {
+  pub name: String,
+  pub size: i64,
+  pub modified_at: Option<i64>,
+}
+
+impl RemoteStore {
+  /// 上传文件（同名覆盖）
+  pub async fn put(&self, name: &str, body: Vec<u8>) -> Result<(), AppError> {
+    match self {
+      RemoteStore::S3 { prefix, .. } => {
+        let key = format!("{}{}", prefix, name);
+        self.s3_send(Method::PUT, Some(&key), &[], body).await?;
+      }
+      RemoteStore::WebDav { base, .. } => {
+        // 目录已存在时返回 405，忽略
+        let mkcol = Method::from_bytes(b"MKCOL").expect("valid method");
+        let response = self.webdav_request(mkcol, base.clone()).send().await.map_err(request_error)?;
+        if !response.status().is_success() && response.status().as_u16() != 405 {
+          return Err(status_error(response.status().as_u16()));
+        }
+        let url = webdav_file_url(base, name)?;
+        let response = self
+          .webdav_request(Method::PUT, url)
+          .body(body)
+          .send()
+          .await
+          .map_err(request_error)?;
+        ensure_success(response)?;
+      }
+    }
+    Ok(())
+  }
+
+  /// 列举备份目录下的文件
+  pub async fn list(&self) -> Result<Vec<RemoteObject>, AppError> {
+    match self {
+      RemoteStore::S3 { prefix, .. } => {
+        let mut objects = Vec::new();
+        let mut token: Option<String> = None;
+        loop {
+          let mut query = vec![("list-type", "2".to_string()), ("prefix", prefix.clone())];
+          if let Some(token) = token.take() {
+            query.push(("continuation-token", token));
+          }
+          let body = self.s3_send(Method::GET, None, &query, Vec::new()).await?.text().await.map_err(request_error)?;
+          for block in xml_elements(&body, "Contents") {
+            let Some(key) = xml_elements(block, "Key").first().map(|value| xml_unescape(value)) else {
+              continue;
+            };
+            objects.push(RemoteObject {
+              name: key.strip_prefix(prefix.as_str()).unwrap_or(&key).to_string(),
+              size: xml_elements(block, "Size").first().and_then(|value| value.trim().parse().ok()).unwrap_or(0),
+              modified_at: xml_elements(block, "LastModified")
+                .first()
+                .and_then(|value| DateTime::parse_from_rfc3339(value.trim()).ok())
+                .map(|value| value.timestamp()),
+            });
+          }
+          let truncated = xml_elements(&body, "IsTruncated").first().map(|value| value.trim()) == Some("true");
+          token = xml_elements(&body, "NextContinuationToken").first().map(|value| xml_unescape(value));
+          if !truncated || token.is_none() {
+            break;
+          }
+        }
+        Ok(objects)
+      }
+      RemoteStore::WebDav { base, .. } => {
+        let propfind = Method::from_bytes(b"PROPFIND").expect("valid method");
+        let response = self
+          .webdav_request(propfind, base.clone())
+          .header("Depth", "1")
+          .header("Content-Type", "application/xml")
+          .body(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\
+             <d:propfind xmlns:d=\"DAV:\"><d:prop><d:getcontentlength/><d:getlastmodified/></d:prop></d:propfind>",
+          )
+          .send()
+          .await
+          .map_err(request_error)?;
+        // 目录尚未创建视为没有备份
+        if response.status().as_u16() == 404 {
+          return Ok(Vec::new());
+        }
+        let body = ensure_success(response)?.text().await.map_err(request_error)?;
+        let mut objects = Vec::new();
+        for block in xml_elements(&body, "response") {
+          let Some(href) = xml_elements(block, "href").first().map(|value| xml_unescape(value)) else {
+            continue;
+          };
+          // 目录自身及子目录以 / 结尾
+          if href.ends_with('/') {
+            continue;
+          }
+          let Some(name) = href.rsplit('/').next().filter(|name| !name.is_empty()) else {
+            continue;
+          };
+          objects.push(RemoteObject {
+            name: name.to_string(),
+            size: xml_elements(block, "getcontentlength")
+              .first()
+              .and_then(|value| value.trim().parse().ok())
+              .unwrap_or(0),
+            modified_at: xml_elements(block, "getlastmodified")
+              .first()
+              .and_then(|value| DateTime::parse_from_rfc2822(value.trim()).ok())
+              .map(|value| value.timestamp()),
+          });
+        }
+        Ok(objects)
+      }
+    }
+  }
+
+  /// 下载文件内容
+  pub async fn get(&self, name: &str) -> Result<Vec<u8>, AppError> {
+    let response = match self {
+      RemoteStore::S3 { prefix, .. } => {
+        let key = format!("{}{}", prefix, name);
+        self.s3_send(Method::GET, Some(&key), &[], Vec::new()).await?
+      }
+      RemoteStore::WebDav { base, .. } => {
+        let url = webdav_file_url(base, name)?;
+        ensure_success(self.webdav_request(Method::GET, url).send().await.map_err(request_error)?)?
+      }
+    };
+    let bytes = response.bytes().await.map_err(request_error)?;
+    Ok(bytes.to_vec())
+  }
+
+  fn webdav_request(&self, method: Method, url: Url) -> reqwest::RequestBuilder {
+    let client = http_client();
+    match self {
+      RemoteStore::WebDav { username, password, .. } => {
+        client.request(method, url).basic_auth(username, Some(password))
+      }
+      RemoteStore::S3 { .. } => client.request(method, url),
+    }
+  }
+
+  /// 发送 SigV4 签名的 S3 请求；key 为空时请求桶本身（列举）
+  async fn s3_send(
+    &self,
+    method: Method,
+    key: Option<&str>,
+    query: &[(&str, String)],
+    body: Vec<u8>,
+  ) -> Result<Response, AppError> {
+    let RemoteStore::S3 {
+      endpoint,
+      bucket,
+      region,
+      access_key,
+      secret_key,
+      ..
+    } = self
+    else {
+      return Err(AppError::new(ErrorCode::ValidationError, "远程存储类型不匹配"));
+    };
+
+    let base_path = endpoint.path().trim_end_matches('/');
+    let mut path = format!("{}/{}", base_path, uri_encode(bucket, true));
+    if let Some(key) = key {
+      path.push('/');
+      path.push_str(&uri_encode(key, false));
+    }
+    let mut pairs: Vec<(String, String)> = query
+      .iter()
+      .map(|(name, value)| (uri_encode(name, true), uri_encode(value, true)))
+      .collect();
+    pairs.sort();
+    let canonical_query = pairs
+      .iter()
+      .map(|(name, value)| format!("{}={}", name, value))
+      .collect::<Vec<_>>()
+      .join("&");
+
+    let host = match endpoint.port() {
+      Some(port) => format!("{}:{}", endpoint.host_str().unwrap_or_default(), port),
+      None => endpoint.host_str().unwrap_or_default().to_string(),
+    };
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+    let payload_hash = hex(&Sha256::digest(&body));
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+      "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+      method.as_str(),
+      path,
+      canonical_query,
+      host,
+      payload_hash,
+      amz_date,
+      signed_headers,
+      payload_hash
+    );
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+      "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+      amz_date,
+      scope,
+      hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+    let mut signing_key = hmac_sha256(format!("AWS4{}", secret_key).as_bytes(), date.as_bytes());
+    for part in [region.as_str(), "s3", "aws4_request"] {
+      signing_key = hmac_sha256(&signing_key, part.as_bytes());
+    }
+    let signature = hex(&hmac_sha256(&signing_key, string_to_sign.as_bytes()));
+    let authorization = format!(
+      "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+      access_key, scope, signed_headers, signature
+    );
+
+    let mut url = format!("{}{}", endpoint.origin().ascii_serialization(), path);
+    if !canonical_query.is_empty() {
+      url.push('?');
+      url.push_str(&canonical_query);
+    }
+    let url = Url::parse(&url).map_err(|_| AppError::new(ErrorCode::ValidationError, "远程地址无效"))?;
+    let response = http_client()
+      .request(method, url)
+      .header("x-amz-content-sha256", payload_hash)
+      .header("x-amz-date", amz_date)
+      .header("Authorization", authorization)
+      .body(body)
+      .send()
+      .await
+      .map_err(request_error)?;
+    ensure_success(response)
+  }
+}
+
+fn http_client() -> Client {
+  Client::builder()
+    .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+    .build()
+    .unwrap_or_default()
+}
+
+fn webdav_file_url(base: &Url, name: &str) -> Result<Url, AppError> {
+  base
+    .join(&uri_encode(name, true))
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "远程地址无效"))
+}
+
+fn ensure_success(response: Response) -> Result<Response, AppError> {
+  if response.status().is_success() {
+    Ok(response)
+  } else {
+    Err(status_error(response.status().as_u16()))
+  }
+}
+
+fn status_error(status: u16) -> AppError {
+  let message = match status {
+    401 | 403 => "远程存储认证失败，请检查账号与密钥".to_string(),
+    404 => "远程路径不存在".to_string(),
+    _ => format!("远程存储请求失败（HTTP {}）", status),
+  };
+  AppError::new(ErrorCode::IoError, message)
+}
+
+fn request_error(err: reqwest::Error) -> AppError {
+  AppError::new(ErrorCode::IoError, format!("连接远程存储失败：{}", err))
+}
+
+/// SigV4 约定的 URI 编码：仅保留非保留字符，encode_slash 为 false 时保留路径分隔符
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+  let mut encoded = String::with_capacity(value.len());
+  for byte in value.bytes() {
+    match byte {
+      b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+      b'/' if !encode_slash => encoded.push('/'),
+      _ => encoded.push_str(&format!("%{:02X}", byte)),
+    }
+  }
+  encoded
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+  let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("hmac accepts any key length");
+  mac.update(data);
+  mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+  bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// 按本地名（忽略命名空间前缀）取出元素内容；备份列表结构简单，不处理同名嵌套
+fn xml_elements<'a>(body: &'a str, name: &str) -> Vec<&'a str> {
+  let mut values = Vec::new();
+  let mut rest = body;
+  while let Some(start) = rest.find('<') {
+    rest = &rest[start + 1..];
+    let tag_end = rest
+      .find(|c: char| c == '>' || c == '/' || c.is_whitespace())
+      .unwrap_or(rest.len());
+    let tag = &rest[..tag_end];
+    let local = tag.rsplit(':').next().unwrap_or(tag);
+    if tag.is_empty() || local != name {
+      continue;
+    }
+    let Some(open_end) = rest.find('>') else {
+      break;
+    };
+    // 自闭合元素视为空内容
+    if rest[..open_end].ends_with('/') {
+      values.push("");
+      rest = &rest[open_end + 1..];
+      continue;
+    }
+    let content = &rest[open_end + 1..];
+    let close = format!("</{}>", tag);
+    let Some(close_start) = content.find(&close) else {
+      break;
+    };
+    values.push(&content[..close_start]);
+    rest = &content[close_start + close.len()..];
+  }
+  values
+}
+
+fn xml_unescape(value: &str) -> String {
+  value
+    .trim()
+    .replace("&lt;", "<")
+    .replace("&gt;", ">")
+    .replace("&quot;", "\"")
+    .replace("&apos;", "'")
+    .replace("&amp;", "&")
+}
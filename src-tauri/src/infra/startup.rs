@@ -0,0 +1,256 @@
+// 启动进度：数据库初始化在后台执行，进度同时推送事件并保存最新状态供启动屏查询
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use parking_lot::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+
+/// 以备份校验管理员凭据时的失败次数上限与锁定时长（故障数据库不可用时，仅记在内存中）
+const RESTORE_MAX_FAILURES: i64 = 5;
+const RESTORE_LOCKOUT_SECS: i64 = 15 * 60;
+
+/// 启动进度事件名
+pub const STARTUP_PROGRESS_EVENT: &str = "startup_progress";
+
+/// 启动进度
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct StartupProgress {
+  // 阶段：prepare/connect/backup/migrate/meta/ready/failed
+  pub stage: String,
+  // 状态：running/done/failed
+  pub status: String,
+  pub message: String,
+  // 当前步骤与总步骤（迁移阶段为迁移序号）
+  pub current: i64,
+  pub total: i64,
+  // 失败时的错误码
+  pub error_code: Option<String>,
+  // 失败时可执行的操作：retry/restore_backup
+  pub actions: Vec<String>,
+  // 可用于恢复的备份文件（新的在前）
+  pub backups: Vec<String>,
+  // 迁移失败时出错的迁移版本与说明
+  pub migration_version: Option<i64>,
+  pub migration_description: Option<String>,
+  // 本次启动生成的升级前备份
+  pub migration_backup: Option<String>,
+}
+
+impl StartupProgress {
+  pub fn running(stage: &str, message: impl Into<String>) -> Self {
+    Self::step(stage, message, 0, 0)
+  }
+
+  pub fn step(stage: &str, message: impl Into<String>, current: i64, total: i64) -> Self {
+    Self {
+      stage: stage.to_string(),
+      status: "running".to_string(),
+      message: message.into(),
+      current,
+      total,
+      error_code: None,
+      actions: Vec::new(),
+      backups: Vec::new(),
+      migration_version: None,
+      migration_description: None,
+      migration_backup: None,
+    }
+  }
+}
+
+/// 启动屏从备份恢复的记录
+#[derive(Debug, Clone)]
+pub struct StartupRestore {
+  pub operator_id: String,
+  pub file_path: String,
+  // 恢复前留存的故障数据库副本
+  pub failed_copy: Option<String>,
+}
+
+/// 启动状态（在 setup 中注册）
+#[derive(Default)]
+pub struct StartupState {
+  progress: Mutex<Option<StartupProgress>>,
+  // 本次启动生成的升级前备份
+  migration_backup: Mutex<Option<String>>,
+  // 执行失败的迁移（版本、说明）
+  failed_migration: Mutex<Option<(i64, String)>>,
+  // 启动屏恢复备份的记录，初始化成功后写入恢复后数据库的审计
+  restore: Mutex<Option<StartupRestore>>,
+  // 以备份校验管理员凭据的连续失败次数与锁定截止时间
+  restore_failures: Mutex<(i64, Option<i64>)>,
+  running: AtomicBool,
+  ready: AtomicBool,
+  close_requested: AtomicBool,
+}
+
+impl StartupState {
+  /// 标记开始初始化；已在执行或已完成时返回 false
+  pub fn begin(&self) -> bool {
+    if self.ready.load(Ordering::SeqCst) {
+      return false;
+    }
+    let started = !self.running.swap(true, Ordering::SeqCst);
+    if started {
+      *self.failed_migration.lock() = None;
+    }
+    started
+  }
+
+  pub fn is_ready(&self) -> bool {
+    self.ready.load(Ordering::SeqCst)
+  }
+
+  pub fn is_failed(&self) -> bool {
+    !self.running.load(Ordering::SeqCst) && !self.is_ready() && self.progress().is_some()
+  }
+
+  pub fn progress(&self) -> Option<StartupProgress> {
+    self.progress.lock().clone()
+  }
+
+  /// 以备份校验管理员凭据前检查是否处于锁定期
+  pub fn ensure_restore_unlocked(&self, now: i64) -> Result<(), AppError> {
+    match self.restore_failures.lock().1 {
+      Some(locked_until) if locked_until > now => Err(AppError::keyed_with(
+        ErrorCode::AccountLocked,
+        MessageKey::AccountLocked,
+        &[("minutes", (((locked_until - now) + 59) / 60).max(1).to_string())],
+      )),
+      _ => Ok(()),
+    }
+  }
+
+  /// 记录一次以备份校验失败，达到次数上限时锁定；校验成功时传 false 清零
+  pub fn record_restore_attempt(&self, failed: bool, now: i64) {
+    let mut failures = self.restore_failures.lock();
+    if !failed {
+      *failures = (0, None);
+      return;
+    }
+    if failures.1.is_some_and(|locked_until| locked_until <= now) {
+      *failures = (0, None);
+    }
+    failures.0 += 1;
+    if failures.0 >= RESTORE_MAX_FAILURES {
+      failures.1 = Some(now + RESTORE_LOCKOUT_SECS);
+    }
+  }
+
+  /// 记录前端的关闭启动屏请求，返回初始化是否已完成
+  pub fn request_close(&self) -> bool {
+    self.close_requested.store(true, Ordering::SeqCst);
+    self.is_ready()
+  }
+}
+
+/// 上报启动进度
+pub fn report(app: &AppHandle, progress: StartupProgress) {
+  if let Some(state) = app.try_state::<StartupState>() {
+    *state.progress.lock() = Some(progress.clone());
+  }
+  let _ = app.emit(STARTUP_PROGRESS_EVENT, progress);
+}
+
+/// 记录本次升级前生成的备份，失败时优先提供给恢复操作
+pub fn remember_backup(app: &AppHandle, path: &str) {
+  if let Some(state) = app.try_state::<StartupState>() {
+    *state.migration_backup.lock() = Some(path.to_string());
+  }
+}
+
+/// 记录执行失败的迁移，随失败状态上报给启动屏
+pub fn remember_failed_migration(app: &AppHandle, version: i64, description: &str) {
+  if let Some(state) = app.try_state::<StartupState>() {
+    *state.failed_migration.lock() = Some((version, description.to_string()));
+  }
+}
+
+/// 记录启动屏的恢复操作，重新初始化成功后补写审计
+pub fn remember_restore(app: &AppHandle, restore: StartupRestore) {
+  if let Some(state) = app.try_state::<StartupState>() {
+    *state.restore.lock() = Some(restore);
+  }
+}
+
+/// 取出待补写审计的恢复记录
+pub fn take_restore(app: &AppHandle) -> Option<StartupRestore> {
+  app
+    .try_state::<StartupState>()
+    .and_then(|state| state.restore.lock().take())
+}
+
+/// 初始化成功，返回前端是否已请求关闭启动屏
+pub fn finish_ok(app: &AppHandle) -> bool {
+  report(app, StartupProgress {
+    status: "done".to_string(),
+    ..StartupProgress::running("ready", "启动完成")
+  });
+  let Some(state) = app.try_state::<StartupState>() else {
+    return false;
+  };
+  state.ready.store(true, Ordering::SeqCst);
+  state.running.store(false, Ordering::SeqCst);
+  state.close_requested.load(Ordering::SeqCst)
+}
+
+/// 初始化失败，上报错误与可选操作（重试/从备份恢复）
+pub fn finish_err(app: &AppHandle, err: &AppError) {
+  let mut backups = list_backups(app);
+  let migration_backup = app
+    .try_state::<StartupState>()
+    .and_then(|state| state.migration_backup.lock().clone());
+  if let Some(path) = migration_backup.as_ref() {
+    backups.retain(|existing| existing != path);
+    backups.insert(0, path.clone());
+  }
+  let failed_migration = app
+    .try_state::<StartupState>()
+    .and_then(|state| state.failed_migration.lock().clone());
+  let mut actions = vec!["retry".to_string()];
+  if !backups.is_empty() {
+    actions.push("restore_backup".to_string());
+  }
+  let error_code = serde_json::to_value(err.code)
+    .ok()
+    .and_then(|value| value.as_str().map(|code| code.to_string()));
+  report(app, StartupProgress {
+    status: "failed".to_string(),
+    error_code,
+    actions,
+    backups,
+    migration_version: failed_migration.as_ref().map(|(version, _)| *version),
+    migration_description: failed_migration.map(|(_, description)| description),
+    migration_backup,
+    ..StartupProgress::running("failed", err.message.clone())
+  });
+  if let Some(state) = app.try_state::<StartupState>() {
+    state.running.store(false, Ordering::SeqCst);
+  }
+}
+
+/// 列出应用数据目录下的数据库备份（含加密备份，新的在前）
+fn list_backups(app: &AppHandle) -> Vec<String> {
+  let Ok(data_dir) = app.path().app_data_dir() else {
+    return Vec::new();
+  };
+  let Ok(entries) = std::fs::read_dir(data_dir.join("backups")) else {
+    return Vec::new();
+  };
+  let mut backups: Vec<(std::time::SystemTime, String)> = entries
+    .filter_map(|entry| entry.ok())
+    .map(|entry| entry.path())
+    .filter(|path| {
+      let name = path.file_name().and_then(|name| name.to_str()).unwrap_or("");
+      name.ends_with(".sqlite") || name.ends_with(".sqlite.enc")
+    })
+    .filter_map(|path| {
+      let modified = path.metadata().and_then(|meta| meta.modified()).ok()?;
+      Some((modified, path.to_string_lossy().to_string()))
+    })
+    .collect();
+  backups.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+  backups.into_iter().take(10).map(|(_, path)| path).collect()
+}
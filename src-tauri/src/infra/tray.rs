@@ -0,0 +1,127 @@
+// 系统托盘：常驻托盘图标与快捷菜单（显示主窗口、新建入库、打开仪表盘、退出）
+//
+// 偏好保存在 app_meta（close_to_tray / start_minimized），数据库就绪后载入并缓存在 TrayPrefs；
+// 窗口关闭事件为同步回调，不便查询数据库，因此读取缓存值。
+// 主窗口隐藏到托盘时进程与后台任务（定时备份、HTTP 接口）继续运行。
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use sqlx::SqlitePool;
+use tauri::{AppHandle, Manager, Window, WindowEvent};
+
+use crate::domain::errors::AppError;
+use crate::infra::deep_link;
+use crate::repo::meta_repo;
+
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const MENU_SHOW: &str = "show";
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const MENU_NEW_INBOUND: &str = "new_inbound";
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const MENU_DASHBOARD: &str = "dashboard";
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+const MENU_QUIT: &str = "quit";
+
+/// 窗口行为偏好缓存（在 setup 中注册）
+#[derive(Default)]
+pub struct TrayPrefs {
+  close_to_tray: AtomicBool,
+  start_minimized: AtomicBool,
+}
+
+impl TrayPrefs {
+  pub fn close_to_tray(&self) -> bool {
+    self.close_to_tray.load(Ordering::Relaxed)
+  }
+
+  pub fn start_minimized(&self) -> bool {
+    self.start_minimized.load(Ordering::Relaxed)
+  }
+}
+
+/// 从 app_meta 重新载入窗口行为偏好（启动时与设置变更后调用）
+pub async fn reload_prefs(handle: &AppHandle, pool: &SqlitePool) -> Result<(), AppError> {
+  let Some(prefs) = handle.try_state::<TrayPrefs>() else {
+    return Ok(());
+  };
+  let close_to_tray = meta_repo::get_meta_value(pool, "close_to_tray").await?.as_deref() == Some("1");
+  let start_minimized =
+    meta_repo::get_meta_value(pool, "start_minimized").await?.as_deref() == Some("1");
+  prefs.close_to_tray.store(close_to_tray, Ordering::Relaxed);
+  prefs.start_minimized.store(start_minimized, Ordering::Relaxed);
+  Ok(())
+}
+
+/// 是否启动时不显示主窗口（仅驻留托盘）
+pub fn start_minimized(handle: &AppHandle) -> bool {
+  handle
+    .try_state::<TrayPrefs>()
+    .is_some_and(|prefs| prefs.start_minimized())
+}
+
+/// 创建托盘图标与菜单
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+  use tauri::menu::{Menu, MenuItem, PredefinedMenuItem};
+  use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
+
+  let show = MenuItem::with_id(app, MENU_SHOW, "显示主窗口", true, None::<&str>)?;
+  let new_inbound = MenuItem::with_id(app, MENU_NEW_INBOUND, "新建入库", true, None::<&str>)?;
+  let dashboard = MenuItem::with_id(app, MENU_DASHBOARD, "打开仪表盘", true, None::<&str>)?;
+  let separator = PredefinedMenuItem::separator(app)?;
+  let quit = MenuItem::with_id(app, MENU_QUIT, "退出", true, None::<&str>)?;
+  let menu = Menu::with_items(app, &[&show, &new_inbound, &dashboard, &separator, &quit])?;
+
+  let mut builder = TrayIconBuilder::with_id("main")
+    .tooltip("出入库登记系统")
+    .menu(&menu)
+    .show_menu_on_left_click(false)
+    .on_menu_event(|app, event| match event.id().as_ref() {
+      MENU_SHOW => show_main_window(app),
+      // 快捷操作复用深度链接的待跳转机制，未登录时登录后再跳转
+      MENU_NEW_INBOUND => deep_link::open_path(app, "/stock?open=inbound"),
+      MENU_DASHBOARD => deep_link::open_path(app, "/"),
+      MENU_QUIT => app.exit(0),
+      _ => {}
+    })
+    .on_tray_icon_event(|tray, event| {
+      if let TrayIconEvent::Click {
+        button: MouseButton::Left,
+        button_state: MouseButtonState::Up,
+        ..
+      } = event
+      {
+        show_main_window(tray.app_handle());
+      }
+    });
+  if let Some(icon) = app.default_window_icon() {
+    builder = builder.icon(icon.clone());
+  }
+  builder.build(app)?;
+  Ok(())
+}
+
+/// 从托盘显示并聚焦主窗口（启动屏仍在时不处理）
+pub fn show_main_window(handle: &AppHandle) {
+  if handle.get_webview_window("splashscreen").is_some() {
+    return;
+  }
+  if let Some(window) = handle.get_webview_window("main") {
+    let _ = window.unminimize();
+    let _ = window.show();
+    let _ = window.set_focus();
+  }
+}
+
+/// 开启"关闭到托盘"时，关闭主窗口改为隐藏
+pub fn on_window_event(window: &Window, event: &WindowEvent) {
+  if let WindowEvent::CloseRequested { api, .. } = event {
+    let close_to_tray = window
+      .app_handle()
+      .try_state::<TrayPrefs>()
+      .is_some_and(|prefs| prefs.close_to_tray());
+    if window.label() == "main" && close_to_tray {
+      api.prevent_close();
+      let _ = window.hide();
+    }
+  }
+}
@@ -0,0 +1,125 @@
+// 最小 ZIP 打包：仅支持在内存中写入少量 deflate 压缩文件（诊断包等），不支持读取与 ZIP64
+use std::io::Write;
+
+use chrono::{Datelike, Local, Timelike};
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+
+use crate::domain::errors::{AppError, ErrorCode};
+
+// 文件名使用 UTF-8 编码（通用标志位 11）
+const FLAG_UTF8: u16 = 0x0800;
+const METHOD_DEFLATE: u16 = 8;
+const VERSION: u16 = 20;
+
+struct CentralEntry {
+  name: String,
+  crc: u32,
+  compressed_size: u32,
+  size: u32,
+  offset: u32,
+}
+
+pub struct ZipBuilder {
+  buf: Vec<u8>,
+  entries: Vec<CentralEntry>,
+  dos_time: u16,
+  dos_date: u16,
+}
+
+impl ZipBuilder {
+  pub fn new() -> Self {
+    let now = Local::now();
+    Self {
+      buf: Vec::new(),
+      entries: Vec::new(),
+      dos_time: ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16,
+      dos_date: (((now.year().max(1980) - 1980) as u32) << 9 | (now.month() << 5) | now.day()) as u16,
+    }
+  }
+
+  /// 添加一个文件，name 为包内相对路径
+  pub fn add(&mut self, name: &str, data: &[u8]) -> Result<(), AppError> {
+    let write_error = || AppError::new(ErrorCode::IoError, "生成压缩包失败");
+    let mut crc = Crc::new();
+    crc.update(data);
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).map_err(|_| write_error())?;
+    let compressed = encoder.finish().map_err(|_| write_error())?;
+
+    let to_u32 = |value: usize| u32::try_from(value).map_err(|_| write_error());
+    let entry = CentralEntry {
+      name: name.to_string(),
+      crc: crc.sum(),
+      compressed_size: to_u32(compressed.len())?,
+      size: to_u32(data.len())?,
+      offset: to_u32(self.buf.len())?,
+    };
+
+    put_u32(&mut self.buf, 0x0403_4b50);
+    put_u16(&mut self.buf, VERSION);
+    put_u16(&mut self.buf, FLAG_UTF8);
+    put_u16(&mut self.buf, METHOD_DEFLATE);
+    put_u16(&mut self.buf, self.dos_time);
+    put_u16(&mut self.buf, self.dos_date);
+    put_u32(&mut self.buf, entry.crc);
+    put_u32(&mut self.buf, entry.compressed_size);
+    put_u32(&mut self.buf, entry.size);
+    put_u16(&mut self.buf, entry.name.len() as u16);
+    put_u16(&mut self.buf, 0);
+    self.buf.extend_from_slice(entry.name.as_bytes());
+    self.buf.extend_from_slice(&compressed);
+    self.entries.push(entry);
+    Ok(())
+  }
+
+  /// 写出中央目录并返回完整的压缩包内容
+  pub fn finish(mut self) -> Vec<u8> {
+    let central_offset = self.buf.len() as u32;
+    for entry in &self.entries {
+      put_u32(&mut self.buf, 0x0201_4b50);
+      put_u16(&mut self.buf, VERSION);
+      put_u16(&mut self.buf, VERSION);
+      put_u16(&mut self.buf, FLAG_UTF8);
+      put_u16(&mut self.buf, METHOD_DEFLATE);
+      put_u16(&mut self.buf, self.dos_time);
+      put_u16(&mut self.buf, self.dos_date);
+      put_u32(&mut self.buf, entry.crc);
+      put_u32(&mut self.buf, entry.compressed_size);
+      put_u32(&mut self.buf, entry.size);
+      put_u16(&mut self.buf, entry.name.len() as u16);
+      // 扩展字段、注释长度、起始磁盘号、内部属性
+      put_u16(&mut self.buf, 0);
+      put_u16(&mut self.buf, 0);
+      put_u16(&mut self.buf, 0);
+      put_u16(&mut self.buf, 0);
+      put_u32(&mut self.buf, 0);
+      put_u32(&mut self.buf, entry.offset);
+      self.buf.extend_from_slice(entry.name.as_bytes());
+    }
+    let central_size = self.buf.len() as u32 - central_offset;
+    put_u32(&mut self.buf, 0x0605_4b50);
+    put_u16(&mut self.buf, 0);
+    put_u16(&mut self.buf, 0);
+    put_u16(&mut self.buf, self.entries.len() as u16);
+    put_u16(&mut self.buf, self.entries.len() as u16);
+    put_u32(&mut self.buf, central_size);
+    put_u32(&mut self.buf, central_offset);
+    put_u16(&mut self.buf, 0);
+    self.buf
+  }
+}
+
+impl Default for ZipBuilder {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+  buf.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buf: &mut Vec<u8>, value: u32) {
+  buf.extend_from_slice(&value.to_le_bytes());
+}
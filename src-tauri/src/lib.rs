@@ -5,18 +5,70 @@ pub mod repo;
 pub mod services;
 pub mod state;
 
-use api::{app_cmd, audit_cmd, auth_cmd, dashboard_cmd, data_cmd, item_cmd, operator_cmd, photo_cmd, rack_cmd, stock_cmd, system_cmd, txn_cmd, warehouse_cmd};
+use api::{app_cmd, approval_cmd, asn_cmd, audit_cmd, auth_cmd, count_cmd, cycle_count_cmd, dashboard_cmd, data_cmd, item_alias_cmd, item_attribute_cmd, item_cmd, label_cmd, loan_cmd, notification_cmd, operator_cmd, outbound_pick_cmd, photo_cmd, pick_list_cmd, rack_cmd, reason_code_cmd, report_cmd, report_schedule_cmd, session_cmd, stock_cmd, stock_hold_cmd, stock_threshold_cmd, system_cmd, txn_cmd, undo_cmd, warehouse_cmd};
+use domain::errors::AppError;
+use infra::startup::{self, StartupState};
 use infra::{db, fs};
 use state::AppState;
 use tauri::Manager;
-use tokio::sync::Mutex;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_share::init())
+        .plugin(tauri_plugin_deep_link::init())
         .setup(|app| {
+            app.manage(infra::deep_link::DeepLinkState::default());
+            let launch_deep_link = infra::deep_link::find_in_args(std::env::args());
+
+            // 桌面端单实例检测：同一数据目录只允许一个进程写入
+            // 默认聚焦已运行的实例；携带 --takeover 启动时请求原实例退出后接管
+            #[cfg(not(any(target_os = "android", target_os = "ios")))]
+            {
+                let data_dir = app.path().app_data_dir().map_err(|err| err.to_string())?;
+                let takeover = std::env::args().any(|arg| arg == "--takeover");
+                let request_handle = app.handle().clone();
+                match infra::instance::acquire(
+                    &data_dir,
+                    takeover,
+                    launch_deep_link.as_deref(),
+                    move |request| handle_instance_request(&request_handle, request),
+                ) {
+                    Ok(Some(lock)) => {
+                        app.manage(lock);
+                    }
+                    Ok(None) => {
+                        std::process::exit(0);
+                    }
+                    Err(err) => {
+                        // setup 运行在主线程上，事件循环尚未启动，不能阻塞等待对话框；
+                        // 跳过后续初始化，对话框关闭后退出
+                        show_startup_error(app.handle(), &err, true);
+                        return Ok(());
+                    }
+                }
+            }
+
+            // 深度链接：启动参数携带的链接先记为待处理，macOS/移动端通过 open-url 事件送达
+            {
+                use tauri_plugin_deep_link::DeepLinkExt;
+
+                // Linux 与 Windows 开发环境需在运行时注册 scheme（安装包由打包配置注册）
+                #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+                let _ = app.deep_link().register_all();
+
+                let open_handle = app.handle().clone();
+                app.deep_link().on_open_url(move |event| {
+                    for url in event.urls() {
+                        infra::deep_link::dispatch(&open_handle, url.as_str());
+                    }
+                });
+                if let Some(url) = launch_deep_link.as_deref() {
+                    infra::deep_link::dispatch(app.handle(), url);
+                }
+            }
+
             // Android 平台不需要启动屏幕
             #[cfg(not(target_os = "android"))]
             {
@@ -34,96 +86,448 @@ pub fn run() {
                 )
                 .decorations(false)
                 .resizable(false)
-                .inner_size(480.0, 320.0)
+                .inner_size(480.0, 380.0)
                 .center()
                 .build()
                 .map_err(|err| err.to_string())?;
             }
 
-            let handle = app.handle().clone();
-            let (pool, _storage_root) =
-                tauri::async_runtime::block_on(db::init_db(&handle))
-                    .map_err(|err| err.to_string())?;
+            // 数据库在后台初始化，启动屏通过 startup_progress 事件/get_startup_status 展示进度
+            app.manage(StartupState::default());
+            app.manage(api::http_api::HttpApiServer::default());
+            app.manage(infra::tray::TrayPrefs::default());
 
-            app.manage(AppState {
-                pool,
-                write_lock: Mutex::new(()),
-                migrating: Mutex::new(false),
-            });
+            // 桌面端常驻托盘图标，提供快捷操作
+            #[cfg(not(any(target_os = "android", target_os = "ios")))]
+            infra::tray::init(app.handle()).map_err(|err| err.to_string())?;
+            start_database_init(app.handle().clone());
 
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
-            // 文件系统相关命令
-            fs::open_folder,
-            fs::reveal_in_folder,
-            // 审计查询相关命令
-            audit_cmd::list_audit_logs,
-            audit_cmd::export_audit_logs,
-            // 备份/导入导出相关命令
-            data_cmd::backup_db,
-            data_cmd::restore_db,
-            data_cmd::export_items,
-            txn_cmd::export_txns,
-            data_cmd::import_items,
-            data_cmd::import_txns,
-            // 认证相关命令
-            auth_cmd::login,
-            auth_cmd::change_password,
-            // 人员管理相关命令
-            operator_cmd::list_operators,
-            operator_cmd::get_operator,
-            operator_cmd::create_operator,
-            operator_cmd::update_operator,
-            operator_cmd::set_operator_status,
-            operator_cmd::reset_operator_password,
-            // 结构管理相关命令
-            warehouse_cmd::list_warehouses,
-            warehouse_cmd::get_warehouse,
-            warehouse_cmd::create_warehouse,
-            warehouse_cmd::update_warehouse,
-            warehouse_cmd::set_warehouse_status,
-            rack_cmd::list_racks,
-            rack_cmd::get_rack,
-            rack_cmd::get_slot,
-            rack_cmd::create_rack,
-            rack_cmd::update_rack,
-            rack_cmd::set_rack_status,
-            rack_cmd::set_slot_status,
-            rack_cmd::list_slots,
-            rack_cmd::regenerate_slots,
-            // 物品与照片相关命令
-            item_cmd::list_items,
-            item_cmd::get_item,
-            item_cmd::create_item,
-            item_cmd::update_item,
-            item_cmd::set_item_status,
-            photo_cmd::list_photos,
-            photo_cmd::add_photos,
-            photo_cmd::stage_photo_bytes,
-            photo_cmd::read_photo_bytes,
-            photo_cmd::remove_photo,
-            photo_cmd::reorder_photos,
-            // 交易相关命令
-            txn_cmd::create_inbound,
-            txn_cmd::create_outbound,
-            txn_cmd::create_move,
-            txn_cmd::create_count,
-            txn_cmd::reverse_txn,
-            txn_cmd::list_txns,
-            dashboard_cmd::get_dashboard_overview,
-            // 系统设置相关命令
-            system_cmd::get_settings,
-            system_cmd::set_settings,
-            system_cmd::set_storage_root,
-            system_cmd::set_exports_dir,
-            system_cmd::set_backups_dir,
-            // 库存管理相关命令
-            stock_cmd::list_stock_by_slot,
-            stock_cmd::list_stock_by_item,
-            stock_cmd::export_stock,
-            app_cmd::close_splashscreen
-        ])
+        .on_window_event(infra::tray::on_window_event)
+        .invoke_handler({
+            // 所有命令先经会话校验再分发
+            let handler = std::sync::Arc::new(tauri::generate_handler![
+                // 文件系统相关命令
+                fs::open_folder,
+                fs::reveal_in_folder,
+                // 审计查询相关命令
+                audit_cmd::list_audit_logs,
+                audit_cmd::export_audit_logs,
+                audit_cmd::archive_audit_logs,
+                audit_cmd::search_audit_archives,
+                // 备份/导入导出相关命令
+                data_cmd::backup_db,
+                data_cmd::restore_db,
+                data_cmd::get_backup_encryption,
+                data_cmd::set_backup_encryption,
+                data_cmd::encrypt_existing_backups,
+                data_cmd::generate_demo_data,
+                data_cmd::verify_database,
+                data_cmd::check_query_plans,
+                data_cmd::collect_diagnostics,
+                data_cmd::repair_database,
+                data_cmd::recalculate_stock,
+                data_cmd::get_remote_backup_config,
+                data_cmd::set_remote_backup_config,
+                data_cmd::list_remote_backups,
+                data_cmd::restore_remote_backup,
+                data_cmd::export_items,
+                txn_cmd::export_txns,
+                data_cmd::import_items,
+                data_cmd::import_txns,
+                data_cmd::import_structure,
+                data_cmd::export_master_bundle,
+                data_cmd::import_master_bundle,
+                data_cmd::import_opening_stock,
+                // 认证相关命令
+                auth_cmd::login,
+                auth_cmd::logout,
+                auth_cmd::change_password,
+                auth_cmd::switch_operator,
+                auth_cmd::set_pin,
+                auth_cmd::reset_password_with_code,
+                // 人员管理相关命令
+                operator_cmd::list_operators,
+                operator_cmd::get_operator,
+                operator_cmd::get_my_profile,
+                operator_cmd::update_my_profile,
+                session_cmd::list_active_sessions,
+                session_cmd::terminate_session,
+                session_cmd::check_session,
+                operator_cmd::create_operator,
+                operator_cmd::update_operator,
+                operator_cmd::set_operator_status,
+                operator_cmd::reset_operator_password,
+                operator_cmd::unlock_operator,
+                operator_cmd::issue_reset_code,
+                operator_cmd::get_operator_warehouses,
+                operator_cmd::set_operator_warehouses,
+                operator_cmd::preview_rbac_enable,
+                operator_cmd::bulk_set_operator_roles,
+                operator_cmd::simulate_permissions,
+                operator_cmd::list_role_capabilities,
+                operator_cmd::set_role_capabilities,
+                // 结构管理相关命令
+                warehouse_cmd::list_warehouses,
+                warehouse_cmd::get_warehouse,
+                warehouse_cmd::get_warehouse_utilization,
+                warehouse_cmd::get_structure_tree,
+                warehouse_cmd::create_warehouse,
+                warehouse_cmd::update_warehouse,
+                warehouse_cmd::set_warehouse_status,
+                rack_cmd::list_racks,
+                rack_cmd::get_rack,
+                rack_cmd::get_slot,
+                rack_cmd::create_rack,
+                rack_cmd::clone_rack,
+                rack_cmd::update_rack,
+                rack_cmd::set_rack_status,
+                rack_cmd::set_slot_status,
+                rack_cmd::set_slots_status,
+                rack_cmd::list_slots,
+                rack_cmd::regenerate_slots,
+                rack_cmd::recode_slots,
+                rack_cmd::get_rack_map,
+                rack_cmd::get_rack_heatmap,
+                rack_cmd::export_rack_map,
+                // 物品与照片相关命令
+                item_cmd::list_items,
+                item_cmd::get_item,
+                item_cmd::get_item_detail,
+                item_cmd::create_item,
+                item_cmd::update_item,
+                item_cmd::set_item_status,
+                item_cmd::set_items_status,
+                item_cmd::bulk_update_items,
+                item_cmd::merge_items,
+                item_cmd::change_item_code,
+                item_cmd::list_item_code_history,
+                item_alias_cmd::list_item_aliases,
+                item_alias_cmd::create_item_alias,
+                item_alias_cmd::update_item_alias,
+                item_alias_cmd::delete_item_alias,
+                item_alias_cmd::resolve_scan,
+                item_attribute_cmd::list_item_attribute_defs,
+                item_attribute_cmd::create_item_attribute_def,
+                item_attribute_cmd::update_item_attribute_def,
+                item_attribute_cmd::delete_item_attribute_def,
+                reason_code_cmd::list_reason_codes,
+                reason_code_cmd::create_reason_code,
+                reason_code_cmd::update_reason_code,
+                reason_code_cmd::delete_reason_code,
+                undo_cmd::get_last_change,
+                undo_cmd::undo_last_change,
+                approval_cmd::list_approval_requests,
+                approval_cmd::submit_approval_request,
+                approval_cmd::approve_approval_request,
+                approval_cmd::reject_approval_request,
+                notification_cmd::list_notifications,
+                notification_cmd::mark_notifications_read,
+                photo_cmd::list_photos,
+                photo_cmd::add_photos,
+                photo_cmd::stage_photo_bytes,
+                photo_cmd::read_photo_bytes,
+                photo_cmd::remove_photo,
+                photo_cmd::reorder_photos,
+                // 交易相关命令
+                txn_cmd::create_inbound,
+                txn_cmd::create_outbound,
+                txn_cmd::create_move,
+                txn_cmd::create_quick_transfer,
+                txn_cmd::create_count,
+                txn_cmd::create_scrap,
+                count_cmd::export_count_sheets,
+                count_cmd::import_count_sheet,
+                pick_list_cmd::print_pick_list,
+                label_cmd::list_label_templates,
+                label_cmd::create_label_template,
+                label_cmd::update_label_template,
+                label_cmd::delete_label_template,
+                label_cmd::generate_labels,
+                txn_cmd::reverse_txn,
+                txn_cmd::list_txns,
+                txn_cmd::copy_txn,
+                txn_cmd::select_kiosk_operator,
+                loan_cmd::create_loan,
+                loan_cmd::return_loan,
+                loan_cmd::list_open_loans,
+                outbound_pick_cmd::create_outbound_pick,
+                outbound_pick_cmd::confirm_outbound_pick,
+                outbound_pick_cmd::cancel_outbound_pick,
+                outbound_pick_cmd::list_outbound_picks,
+                asn_cmd::create_asn,
+                asn_cmd::receive_against_asn,
+                asn_cmd::close_asn,
+                asn_cmd::cancel_asn,
+                asn_cmd::list_asns,
+                asn_cmd::get_asn,
+                asn_cmd::get_asn_variance_report,
+                dashboard_cmd::get_dashboard_overview,
+                report_cmd::item_stock_trend,
+                report_cmd::get_stock_aging,
+                report_cmd::get_low_stock_report,
+                report_cmd::get_expiring_stock_report,
+                report_cmd::get_txn_reason_report,
+                report_cmd::export_stock_aging,
+                report_schedule_cmd::list_report_schedules,
+                report_schedule_cmd::create_report_schedule,
+                report_schedule_cmd::update_report_schedule,
+                report_schedule_cmd::delete_report_schedule,
+                report_schedule_cmd::run_report_schedule,
+                report_schedule_cmd::list_report_schedule_runs,
+                stock_threshold_cmd::list_stock_thresholds,
+                stock_threshold_cmd::set_stock_threshold,
+                stock_threshold_cmd::delete_stock_threshold,
+                stock_hold_cmd::list_stock_holds,
+                stock_hold_cmd::hold_stock,
+                stock_hold_cmd::release_hold,
+                cycle_count_cmd::list_cycle_count_plans,
+                cycle_count_cmd::create_cycle_count_plan,
+                cycle_count_cmd::update_cycle_count_plan,
+                cycle_count_cmd::delete_cycle_count_plan,
+                cycle_count_cmd::get_today_count_tasks,
+                cycle_count_cmd::skip_count_task,
+                // 系统设置相关命令
+                system_cmd::get_settings,
+                system_cmd::set_settings,
+                system_cmd::regenerate_http_api_token,
+                system_cmd::set_read_only_mode,
+                system_cmd::set_storage_root,
+                system_cmd::cancel_storage_migration,
+                system_cmd::set_exports_dir,
+                system_cmd::set_backups_dir,
+                system_cmd::scan_photo_storage,
+                system_cmd::list_photo_storage_metrics,
+                system_cmd::get_performance_stats,
+                system_cmd::get_smtp_config,
+                system_cmd::set_smtp_config,
+                system_cmd::send_test_email,
+                system_cmd::list_mail_queue,
+                // 库存管理相关命令
+                stock_cmd::list_stock_by_slot,
+                stock_cmd::list_stock_by_item,
+                stock_cmd::export_stock,
+                stock_cmd::get_stock_valuation,
+                stock_cmd::get_stock_asof,
+                stock_cmd::copy_stock,
+                app_cmd::close_splashscreen,
+                app_cmd::get_startup_status,
+                app_cmd::retry_startup,
+                app_cmd::restore_startup_backup,
+                app_cmd::get_bootstrap,
+                app_cmd::take_pending_deep_link,
+                app_cmd::get_filter_options
+            ]);
+            move |invoke| api::command_guard::dispatch_with_session(invoke, handler.clone())
+        })
         .run(tauri::generate_context!())
         .expect("tauri runtime error");
 }
+
+/// 后台初始化数据库，成功后注册 AppState；失败时保留启动屏并提供重试/恢复操作
+pub(crate) fn start_database_init(handle: tauri::AppHandle) {
+    let Some(startup_state) = handle.try_state::<StartupState>() else {
+        return;
+    };
+    if !startup_state.begin() {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        match db::init_db(&handle).await {
+            Ok((pool, _storage_root)) => {
+                handle.manage(AppState::new(pool));
+                // 已启用 HTTP 接口时随数据库就绪启动；失败不影响主程序
+                let _ = api::http_api::restart(&handle).await;
+                // 载入窗口行为偏好与界面语言，需在显示主窗口前完成
+                if let Some(state) = handle.try_state::<AppState>() {
+                    let _ = infra::tray::reload_prefs(&handle, &state.pool()).await;
+                    // 错误信息按设置的语言渲染
+                    if let Ok(locale) = services::txn_service::locale(&state.pool()).await {
+                        domain::messages::set_current_locale(&locale);
+                    }
+                    // 启动屏恢复备份的操作在数据库可写后补记审计
+                    if let Some(restore) = startup::take_restore(&handle) {
+                        let _ = services::audit_service::write_audit(
+                            &state.pool(),
+                            domain::audit::AuditAction::DbRestore,
+                            Some(restore.operator_id),
+                            Some("data".to_string()),
+                            Some(restore.file_path.clone()),
+                            Some(serde_json::json!({
+                                "file_path": restore.file_path,
+                                "trigger": "startup",
+                                "failed_copy": restore.failed_copy,
+                            })),
+                            Ok(()),
+                        )
+                        .await;
+                    }
+                }
+                start_background_jobs(handle.clone());
+                // 前端已请求关闭启动屏时，由此处完成切换
+                if startup::finish_ok(&handle) {
+                    #[cfg(not(target_os = "android"))]
+                    app_cmd::show_main_window(&handle);
+                }
+            }
+            Err(err) => {
+                startup::finish_err(&handle, &err);
+                // 无启动屏（移动端）时直接弹窗提示
+                if handle.get_webview_window("splashscreen").is_none() {
+                    show_startup_error(&handle, &err, false);
+                }
+            }
+        }
+    });
+}
+
+/// 后台定时任务：与窗口状态无关，主窗口隐藏到托盘时继续运行
+fn start_background_jobs(handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(600));
+        loop {
+            interval.tick().await;
+            let Some(state) = handle.try_state::<AppState>() else {
+                continue;
+            };
+            if *state.migrating.lock().await {
+                continue;
+            }
+            // 日结：昨日及之前有未日结的日期时补齐库存快照，加写锁避免与业务写入交错
+            if services::stock_close_service::close_due(&state.pool())
+                .await
+                .unwrap_or(false)
+            {
+                let _guard = state.write_lock.lock().await;
+                let _ = api::command_guard::run_with_audit(
+                    &state.pool(),
+                    domain::audit::AuditAction::StockClose,
+                    None,
+                    Some(serde_json::json!({ "trigger": "schedule" })),
+                    || async { services::stock_close_service::run_pending_closes(&state.pool()).await },
+                )
+                .await;
+            }
+            // 循环盘点：跨日后生成当天的盘点任务并顺延未完成的任务，当天已生成的计划会跳过
+            {
+                let _guard = state.write_lock.lock().await;
+                let _ = services::cycle_count_service::generate_today_tasks(&state.pool()).await;
+            }
+            // 通知：扫描低库存与借用逾期，有新通知时推送事件供前端刷新角标
+            {
+                let _guard = state.write_lock.lock().await;
+                if services::notification_service::scan(&state.pool())
+                    .await
+                    .unwrap_or(0)
+                    > 0
+                {
+                    api::notification_cmd::emit_new(&handle);
+                }
+            }
+            // 定时报表：到点生成报表并把邮件放入队列，生成期间不占用写锁，执行记录写入后失败的推送通知
+            for schedule in services::report_schedule_service::due_schedules(&state.pool())
+                .await
+                .unwrap_or_default()
+            {
+                let mut run =
+                    services::report_schedule_service::run_schedule(&state.pool(), &schedule, "schedule").await;
+                let _guard = state.write_lock.lock().await;
+                if services::report_schedule_service::record_run(&state.pool(), &schedule, &mut run)
+                    .await
+                    .unwrap_or(false)
+                {
+                    api::notification_cmd::emit_new(&handle);
+                }
+            }
+            // 邮件队列：发送到期的邮件（含失败重试）
+            api::system_cmd::deliver_due_mails(&handle, &state).await;
+            // 定时备份：到期时按手动备份的方式加写锁复制数据库并记录审计
+            if !services::system_service::scheduled_backup_due(&state.pool())
+                .await
+                .unwrap_or(false)
+            {
+                continue;
+            }
+            let _guard = state.write_lock.lock().await;
+            let result = api::command_guard::run_with_audit(
+                &state.pool(),
+                domain::audit::AuditAction::DbBackup,
+                None,
+                Some(serde_json::json!({ "trigger": "schedule" })),
+                || async { services::system_service::run_scheduled_backup(&state.pool()).await },
+            )
+            .await;
+            // 定时备份失败（含远程上传失败）写入通知
+            if let Err(err) = result {
+                if services::notification_service::notify_backup_failed(&state.pool(), &err)
+                    .await
+                    .unwrap_or(false)
+                {
+                    api::notification_cmd::emit_new(&handle);
+                }
+            }
+        }
+    });
+}
+
+/// 处理其他实例发来的聚焦/深度链接/接管请求
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+fn handle_instance_request(handle: &tauri::AppHandle, request: infra::instance::InstanceRequest) {
+    match request {
+        infra::instance::InstanceRequest::Open(url) => {
+            infra::deep_link::dispatch(handle, &url);
+        }
+        infra::instance::InstanceRequest::Focus => {
+            // 启动屏仍在时聚焦启动屏，否则聚焦主窗口
+            let window = handle
+                .get_webview_window("splashscreen")
+                .or_else(|| handle.get_webview_window("main"));
+            if let Some(window) = window {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        infra::instance::InstanceRequest::Takeover => {
+            // 等待进行中的写操作结束并关闭连接池，再退出进程交出数据目录
+            if let Some(state) = handle.try_state::<AppState>() {
+                tauri::async_runtime::block_on(async {
+                    let _guard = state.write_lock.lock().await;
+                    state.pool().close().await;
+                });
+            }
+            handle.exit(0);
+        }
+    }
+}
+
+/// 启动失败时弹出原生错误对话框（此时前端尚未就绪）；不阻塞调用线程，exit 为 true 时关闭后退出程序
+fn show_startup_error(handle: &tauri::AppHandle, err: &AppError, exit: bool) {
+    use tauri_plugin_dialog::{DialogExt, MessageDialogKind};
+
+    let message = match err.code {
+        domain::errors::ErrorCode::DbBusy => format!(
+            "{}\n\n请确认没有其他程序（如数据库查看工具）正在打开数据库文件。",
+            err.message
+        ),
+        domain::errors::ErrorCode::DbIncompatible => format!(
+            "{}\n\n为避免数据损坏，请安装新版本程序后再打开，或从备份恢复旧版本数据库。",
+            err.message
+        ),
+        _ => err.message.clone(),
+    };
+    handle
+        .dialog()
+        .message(message)
+        .title("启动失败")
+        .kind(MessageDialogKind::Error)
+        .show({
+            let handle = handle.clone();
+            move |_| {
+                if exit {
+                    handle.exit(1);
+                }
+            }
+        });
+}
@@ -0,0 +1,189 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct ApprovalRequestRow {
+  pub id: String,
+  // reversal / count_adjust / scrap / restore
+  pub kind: String,
+  // 操作对象标识，执行时须与申请一致
+  pub target: String,
+  pub summary: String,
+  pub reason: Option<String>,
+  // pending / approved / rejected / executed
+  pub status: String,
+  pub requested_by: String,
+  pub requested_by_name: Option<String>,
+  pub requested_at: i64,
+  pub decided_by: Option<String>,
+  pub decided_by_name: Option<String>,
+  pub decided_at: Option<i64>,
+  pub decision_note: Option<String>,
+  pub executed_at: Option<i64>,
+}
+
+const SELECT_REQUEST: &str = "SELECT req.id, req.kind, req.target, req.summary, req.reason, req.status, \
+   req.requested_by, requester.display_name AS requested_by_name, req.requested_at, \
+   req.decided_by, decider.display_name AS decided_by_name, req.decided_at, req.decision_note, req.executed_at \
+   FROM approval_request AS req \
+   LEFT JOIN \"operator\" AS requester ON req.requested_by = requester.id \
+   LEFT JOIN \"operator\" AS decider ON req.decided_by = decider.id";
+
+fn map_request(row: &sqlx::sqlite::SqliteRow) -> ApprovalRequestRow {
+  ApprovalRequestRow {
+    id: row.get("id"),
+    kind: row.get("kind"),
+    target: row.get("target"),
+    summary: row.get("summary"),
+    reason: row.get("reason"),
+    status: row.get("status"),
+    requested_by: row.get("requested_by"),
+    requested_by_name: row.get("requested_by_name"),
+    requested_at: row.get("requested_at"),
+    decided_by: row.get("decided_by"),
+    decided_by_name: row.get("decided_by_name"),
+    decided_at: row.get("decided_at"),
+    decision_note: row.get("decision_note"),
+    executed_at: row.get("executed_at"),
+  }
+}
+
+pub async fn insert_request(pool: &SqlitePool, request: &ApprovalRequestRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO approval_request (id, kind, target, summary, reason, status, requested_by, requested_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&request.id)
+  .bind(&request.kind)
+  .bind(&request.target)
+  .bind(&request.summary)
+  .bind(&request.reason)
+  .bind(&request.status)
+  .bind(&request.requested_by)
+  .bind(request.requested_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_request(pool: &SqlitePool, id: &str) -> Result<Option<ApprovalRequestRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE req.id = ?", SELECT_REQUEST))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_request))
+}
+
+/// 申请人对同一对象尚未结束（待审批或已通过未执行）的申请，返回 (ID, 状态)，优先返回已通过的申请
+pub async fn find_open_request(
+  pool: &SqlitePool,
+  kind: &str,
+  target: &str,
+  requested_by: &str,
+) -> Result<Option<(String, String)>, AppError> {
+  let row: Option<(String, String)> = sqlx::query_as(
+    "SELECT id, status FROM approval_request \
+     WHERE kind = ? AND target = ? AND requested_by = ? AND status IN ('pending', 'approved') \
+     ORDER BY status = 'approved' DESC, requested_at DESC LIMIT 1",
+  )
+  .bind(kind)
+  .bind(target)
+  .bind(requested_by)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row)
+}
+
+/// 审批申请列表，按申请时间倒序；requested_by 为 Some 时仅返回该人员的申请
+pub async fn list_requests(
+  pool: &SqlitePool,
+  status: Option<&str>,
+  requested_by: Option<&str>,
+) -> Result<Vec<ApprovalRequestRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(SELECT_REQUEST);
+  builder.push(" WHERE 1 = 1");
+  if let Some(status) = status {
+    builder.push(" AND req.status = ").push_bind(status);
+  }
+  if let Some(requested_by) = requested_by {
+    builder.push(" AND req.requested_by = ").push_bind(requested_by);
+  }
+  builder.push(" ORDER BY req.requested_at DESC LIMIT 500");
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(rows.iter().map(map_request).collect())
+}
+
+/// 待审批的申请数
+pub async fn count_pending(pool: &SqlitePool) -> Result<i64, AppError> {
+  let (count,): (i64,) = sqlx::query_as("SELECT COUNT(1) FROM approval_request WHERE status = 'pending'")
+    .fetch_one(pool)
+    .await?;
+  Ok(count)
+}
+
+/// 审批通过或驳回，仅更新待审批的申请
+pub async fn decide_request(
+  pool: &SqlitePool,
+  id: &str,
+  status: &str,
+  decided_by: &str,
+  decided_at: i64,
+  decision_note: Option<String>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE approval_request SET status = ?, decided_by = ?, decided_at = ?, decision_note = ? \
+     WHERE id = ? AND status = 'pending'",
+  )
+  .bind(status)
+  .bind(decided_by)
+  .bind(decided_at)
+  .bind(decision_note)
+  .bind(id)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn mark_executed(pool: &SqlitePool, id: &str, executed_at: i64) -> Result<(), AppError> {
+  sqlx::query("UPDATE approval_request SET status = 'executed', executed_at = ? WHERE id = ? AND status = 'approved'")
+    .bind(executed_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+/// 在恢复后的数据库中写入已执行的申请（备份可能早于申请，缺少时补录）；
+/// 申请人或审批人在该数据库中不存在时无法满足外键，跳过
+pub async fn upsert_executed(
+  pool: &SqlitePool,
+  request: &ApprovalRequestRow,
+  executed_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO approval_request \
+     (id, kind, target, summary, reason, status, requested_by, requested_at, decided_by, decided_at, decision_note, executed_at) \
+     SELECT ?, ?, ?, ?, ?, 'executed', ?, ?, ?, ?, ?, ? \
+     WHERE EXISTS (SELECT 1 FROM \"operator\" WHERE id = ?) \
+       AND (? IS NULL OR EXISTS (SELECT 1 FROM \"operator\" WHERE id = ?)) \
+     ON CONFLICT(id) DO UPDATE SET status = 'executed', executed_at = excluded.executed_at",
+  )
+  .bind(&request.id)
+  .bind(&request.kind)
+  .bind(&request.target)
+  .bind(&request.summary)
+  .bind(&request.reason)
+  .bind(&request.requested_by)
+  .bind(request.requested_at)
+  .bind(&request.decided_by)
+  .bind(request.decided_at)
+  .bind(&request.decision_note)
+  .bind(executed_at)
+  .bind(&request.requested_by)
+  .bind(&request.decided_by)
+  .bind(&request.decided_by)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
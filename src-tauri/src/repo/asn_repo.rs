@@ -0,0 +1,314 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+use crate::domain::quantity;
+
+#[derive(Debug, Clone)]
+pub struct AsnRow {
+  pub id: String,
+  pub asn_no: String,
+  pub supplier: String,
+  pub eta: Option<i64>,
+  // open / closed / cancelled
+  pub status: String,
+  pub note: Option<String>,
+  pub created_by: String,
+  pub created_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct AsnLineRow {
+  pub id: String,
+  pub asn_id: String,
+  pub line_no: i64,
+  pub item_id: String,
+  pub expected_qty: i64,
+  pub received_qty: i64,
+}
+
+/// 到货预报列表行，数量合计为显示数量
+#[derive(Debug, serde::Serialize)]
+pub struct AsnListRow {
+  pub id: String,
+  pub asn_no: String,
+  pub supplier: String,
+  pub eta: Option<i64>,
+  pub status: String,
+  pub note: Option<String>,
+  pub created_by_name: Option<String>,
+  pub created_at: i64,
+  pub closed_at: Option<i64>,
+  pub line_count: i64,
+  // 已收足的明细数
+  pub received_line_count: i64,
+}
+
+/// 预报明细及收货差异（显示数量）：variance = 已收 - 预计，flag 为 over / short / match
+#[derive(Debug, serde::Serialize)]
+pub struct AsnLineDetailRow {
+  pub id: String,
+  pub asn_id: String,
+  pub asn_no: String,
+  pub supplier: String,
+  pub eta: Option<i64>,
+  pub asn_status: String,
+  pub line_no: i64,
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub expected_qty: f64,
+  pub received_qty: f64,
+  pub variance: f64,
+  pub flag: String,
+}
+
+const SELECT_LINE_DETAIL: &str = "SELECT line.id, line.asn_id, asn.asn_no, asn.supplier, asn.eta, asn.status AS asn_status, \
+   line.line_no, line.item_id, item.item_code, item.name AS item_name, item.uom, \
+   line.expected_qty / {scale} AS expected_qty, line.received_qty / {scale} AS received_qty, \
+   (line.received_qty - line.expected_qty) / {scale} AS variance, \
+   CASE WHEN line.received_qty > line.expected_qty THEN 'over' \
+        WHEN line.received_qty < line.expected_qty THEN 'short' ELSE 'match' END AS flag \
+   FROM asn_line AS line \
+   JOIN asn ON line.asn_id = asn.id \
+   JOIN item ON line.item_id = item.id";
+
+fn line_detail_sql() -> String {
+  SELECT_LINE_DETAIL.replace("{scale}", &quantity::scale_sql("item.qty_precision"))
+}
+
+fn map_line_detail(row: &sqlx::sqlite::SqliteRow) -> AsnLineDetailRow {
+  AsnLineDetailRow {
+    id: row.get("id"),
+    asn_id: row.get("asn_id"),
+    asn_no: row.get("asn_no"),
+    supplier: row.get("supplier"),
+    eta: row.get("eta"),
+    asn_status: row.get("asn_status"),
+    line_no: row.get("line_no"),
+    item_id: row.get("item_id"),
+    item_code: row.get("item_code"),
+    item_name: row.get("item_name"),
+    uom: row.get("uom"),
+    expected_qty: row.get("expected_qty"),
+    received_qty: row.get("received_qty"),
+    variance: row.get("variance"),
+    flag: row.get("flag"),
+  }
+}
+
+pub async fn insert_asn_tx(tx: &mut Transaction<'_, Sqlite>, row: &AsnRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO asn (id, asn_no, supplier, eta, status, note, created_by, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.asn_no)
+  .bind(&row.supplier)
+  .bind(row.eta)
+  .bind(&row.status)
+  .bind(&row.note)
+  .bind(&row.created_by)
+  .bind(row.created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn insert_line_tx(tx: &mut Transaction<'_, Sqlite>, row: &AsnLineRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO asn_line (id, asn_id, line_no, item_id, expected_qty, received_qty) VALUES (?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.asn_id)
+  .bind(row.line_no)
+  .bind(&row.item_id)
+  .bind(row.expected_qty)
+  .bind(row.received_qty)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_asn(pool: &SqlitePool, id: &str) -> Result<Option<AsnRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, asn_no, supplier, eta, status, note, created_by, created_at FROM asn WHERE id = ?",
+  )
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.map(|row| AsnRow {
+    id: row.get("id"),
+    asn_no: row.get("asn_no"),
+    supplier: row.get("supplier"),
+    eta: row.get("eta"),
+    status: row.get("status"),
+    note: row.get("note"),
+    created_by: row.get("created_by"),
+    created_at: row.get("created_at"),
+  }))
+}
+
+pub async fn list_lines(pool: &SqlitePool, asn_id: &str) -> Result<Vec<AsnLineRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT id, asn_id, line_no, item_id, expected_qty, received_qty FROM asn_line WHERE asn_id = ? ORDER BY line_no",
+  )
+  .bind(asn_id)
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| AsnLineRow {
+        id: row.get("id"),
+        asn_id: row.get("asn_id"),
+        line_no: row.get("line_no"),
+        item_id: row.get("item_id"),
+        expected_qty: row.get("expected_qty"),
+        received_qty: row.get("received_qty"),
+      })
+      .collect(),
+  )
+}
+
+/// 到货预报列表，按预计到货时间排序（无预计时间的排在最后）
+pub async fn list_asns(
+  pool: &SqlitePool,
+  status: Option<&str>,
+  keyword: Option<&str>,
+  limit: i64,
+) -> Result<Vec<AsnListRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT asn.id, asn.asn_no, asn.supplier, asn.eta, asn.status, asn.note, op.display_name AS created_by_name, \
+     asn.created_at, asn.closed_at, \
+     (SELECT COUNT(1) FROM asn_line AS line WHERE line.asn_id = asn.id) AS line_count, \
+     (SELECT COUNT(1) FROM asn_line AS line WHERE line.asn_id = asn.id AND line.received_qty >= line.expected_qty) AS received_line_count \
+     FROM asn \
+     LEFT JOIN \"operator\" AS op ON asn.created_by = op.id \
+     WHERE 1 = 1",
+  );
+  if let Some(status) = status {
+    builder.push(" AND asn.status = ").push_bind(status);
+  }
+  if let Some(keyword) = keyword {
+    let like = format!("%{}%", keyword);
+    builder
+      .push(" AND (asn.asn_no LIKE ")
+      .push_bind(like.clone())
+      .push(" OR asn.supplier LIKE ")
+      .push_bind(like)
+      .push(")");
+  }
+  builder
+    .push(" ORDER BY asn.eta IS NULL, asn.eta ASC, asn.created_at DESC LIMIT ")
+    .push_bind(limit);
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| AsnListRow {
+        id: row.get("id"),
+        asn_no: row.get("asn_no"),
+        supplier: row.get("supplier"),
+        eta: row.get("eta"),
+        status: row.get("status"),
+        note: row.get("note"),
+        created_by_name: row.get("created_by_name"),
+        created_at: row.get("created_at"),
+        closed_at: row.get("closed_at"),
+        line_count: row.get("line_count"),
+        received_line_count: row.get("received_line_count"),
+      })
+      .collect(),
+  )
+}
+
+pub async fn list_line_details(pool: &SqlitePool, asn_id: &str) -> Result<Vec<AsnLineDetailRow>, AppError> {
+  let rows = sqlx::query(&format!("{} WHERE line.asn_id = ? ORDER BY line.line_no", line_detail_sql()))
+    .bind(asn_id)
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.iter().map(map_line_detail).collect())
+}
+
+/// 收货差异：已结单预报中已收与预计不一致的明细，以及待收货预报中已超收的明细
+pub async fn list_variances(
+  pool: &SqlitePool,
+  supplier: Option<&str>,
+  start_at: Option<i64>,
+  end_at: Option<i64>,
+  limit: i64,
+) -> Result<Vec<AsnLineDetailRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(line_detail_sql());
+  builder.push(
+    " WHERE ((asn.status = 'closed' AND line.received_qty <> line.expected_qty) \
+     OR (asn.status = 'open' AND line.received_qty > line.expected_qty))",
+  );
+  if let Some(supplier) = supplier {
+    builder.push(" AND asn.supplier LIKE ").push_bind(format!("%{}%", supplier));
+  }
+  if let Some(start_at) = start_at {
+    builder.push(" AND asn.created_at >= ").push_bind(start_at);
+  }
+  if let Some(end_at) = end_at {
+    builder.push(" AND asn.created_at <= ").push_bind(end_at);
+  }
+  builder
+    .push(" ORDER BY asn.created_at DESC, line.line_no LIMIT ")
+    .push_bind(limit);
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(rows.iter().map(map_line_detail).collect())
+}
+
+pub async fn add_received_tx(tx: &mut Transaction<'_, Sqlite>, line_id: &str, qty: i64) -> Result<(), AppError> {
+  sqlx::query("UPDATE asn_line SET received_qty = received_qty + ? WHERE id = ?")
+    .bind(qty)
+    .bind(line_id)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+pub async fn insert_receipt_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  asn_line_id: &str,
+  txn_id: &str,
+  qty: i64,
+  received_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO asn_receipt (id, asn_line_id, txn_id, qty, received_at) VALUES (?, ?, ?, ?, ?)")
+    .bind(id)
+    .bind(asn_line_id)
+    .bind(txn_id)
+    .bind(qty)
+    .bind(received_at)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+/// 预报明细是否均已收足
+pub async fn all_lines_received_tx(tx: &mut Transaction<'_, Sqlite>, asn_id: &str) -> Result<bool, AppError> {
+  let (pending,): (i64,) =
+    sqlx::query_as("SELECT COUNT(1) FROM asn_line WHERE asn_id = ? AND received_qty < expected_qty")
+      .bind(asn_id)
+      .fetch_one(&mut **tx)
+      .await?;
+  Ok(pending == 0)
+}
+
+/// 结单或取消，仅更新待收货的预报，返回更新条数
+pub async fn finish_asn_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  status: &str,
+  closed_at: i64,
+) -> Result<u64, AppError> {
+  let result = sqlx::query("UPDATE asn SET status = ?, closed_at = ? WHERE id = ? AND status = 'open'")
+    .bind(status)
+    .bind(closed_at)
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+  Ok(result.rows_affected())
+}
@@ -2,7 +2,7 @@ use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
 
 use crate::domain::errors::AppError;
 
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct AuditLogRow {
   // 审计日志落库字段
   pub id: String,
@@ -40,20 +40,23 @@ pub async fn insert_audit_log(pool: &SqlitePool, row: AuditLogRow) -> Result<(),
   Ok(())
 }
 
-pub async fn list_audit_logs(
-  pool: &SqlitePool,
-  action: Option<String>,
-  keyword: Option<String>,
-  start_at: Option<i64>,
-  end_at: Option<i64>,
-  page_index: i64,
-  page_size: i64,
-) -> Result<Vec<AuditLogRow>, AppError> {
-  let offset = (page_index - 1) * page_size;
-  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
-    "SELECT id, created_at, actor_operator_id, action, target_type, target_id, request_json, result, error_code, error_detail \
-     FROM audit_log",
-  );
+/// 审计日志筛选条件（列表与导出共用）
+#[derive(Debug, Clone, Default)]
+pub struct AuditLogFilter {
+  pub action: Option<String>,
+  pub keyword: Option<String>,
+  pub actor_operator_id: Option<String>,
+  // success / fail
+  pub result: Option<String>,
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+}
+
+const AUDIT_COLUMNS: &str =
+  "SELECT id, created_at, actor_operator_id, action, target_type, target_id, request_json, result, error_code, error_detail \
+   FROM audit_log";
+
+fn push_filters(builder: &mut QueryBuilder<Sqlite>, filter: &AuditLogFilter) -> bool {
   let mut has_where = false;
   let mut push_where = |builder: &mut QueryBuilder<Sqlite>| {
     if has_where {
@@ -64,15 +67,15 @@ pub async fn list_audit_logs(
     }
   };
 
-  if let Some(action) = action {
-    push_where(&mut builder);
+  if let Some(action) = filter.action.clone() {
+    push_where(builder);
     builder.push("action = ");
     builder.push_bind(action);
   }
 
-  if let Some(keyword) = keyword {
+  if let Some(keyword) = filter.keyword.as_ref() {
     let like = format!("%{}%", keyword);
-    push_where(&mut builder);
+    push_where(builder);
     builder.push("(");
     builder.push("actor_operator_id LIKE ");
     builder.push_bind(like.clone());
@@ -85,138 +88,112 @@ pub async fn list_audit_logs(
     builder.push(")");
   }
 
-  if let Some(start_at) = start_at {
-    push_where(&mut builder);
+  if let Some(actor_operator_id) = filter.actor_operator_id.clone() {
+    push_where(builder);
+    builder.push("actor_operator_id = ");
+    builder.push_bind(actor_operator_id);
+  }
+
+  if let Some(result) = filter.result.clone() {
+    push_where(builder);
+    builder.push("result = ");
+    builder.push_bind(result);
+  }
+
+  if let Some(start_at) = filter.start_at {
+    push_where(builder);
     builder.push("created_at >= ");
     builder.push_bind(start_at);
   }
 
-  if let Some(end_at) = end_at {
-    push_where(&mut builder);
+  if let Some(end_at) = filter.end_at {
+    push_where(builder);
     builder.push("created_at <= ");
     builder.push_bind(end_at);
   }
 
-  builder.push(" ORDER BY created_at DESC LIMIT ");
+  has_where
+}
+
+fn map_audit_row(row: sqlx::sqlite::SqliteRow) -> AuditLogRow {
+  AuditLogRow {
+    id: row.get("id"),
+    created_at: row.get("created_at"),
+    actor_operator_id: row.get("actor_operator_id"),
+    actor_operator_name: None,
+    action: row.get("action"),
+    target_type: row.get("target_type"),
+    target_id: row.get("target_id"),
+    request_json: row.get("request_json"),
+    result: row.get("result"),
+    error_code: row.get("error_code"),
+    error_detail: row.get("error_detail"),
+  }
+}
+
+pub async fn list_audit_logs(
+  pool: &SqlitePool,
+  filter: &AuditLogFilter,
+  page_index: i64,
+  page_size: i64,
+) -> Result<Vec<AuditLogRow>, AppError> {
+  let offset = (page_index - 1) * page_size;
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(AUDIT_COLUMNS);
+  push_filters(&mut builder, filter);
+
+  // 与游标分页保持同一顺序，偏移翻页得到的最后一条可直接作为游标
+  builder.push(" ORDER BY created_at DESC, id DESC LIMIT ");
   builder.push_bind(page_size);
   builder.push(" OFFSET ");
   builder.push_bind(offset);
 
   let rows = builder.build().fetch_all(pool).await?;
-
-  let items = rows
-    .into_iter()
-    .map(|row| AuditLogRow {
-      id: row.get("id"),
-      created_at: row.get("created_at"),
-      actor_operator_id: row.get("actor_operator_id"),
-      actor_operator_name: None,
-      action: row.get("action"),
-      target_type: row.get("target_type"),
-      target_id: row.get("target_id"),
-      request_json: row.get("request_json"),
-      result: row.get("result"),
-      error_code: row.get("error_code"),
-      error_detail: row.get("error_detail"),
-    })
-    .collect();
-
-  Ok(items)
+  Ok(rows.into_iter().map(map_audit_row).collect())
 }
 
-pub async fn list_audit_logs_all(
+/// 按游标分批读取审计日志（created_at DESC, id DESC），供列表游标翻页与导出逐批写出，
+/// 避免 OFFSET 在大表上逐行跳过；cursor 为上一批最后一条的 (created_at, id)
+pub async fn list_audit_logs_after(
   pool: &SqlitePool,
-  action: Option<String>,
+  filter: &AuditLogFilter,
+  cursor: Option<(i64, String)>,
+  limit: i64,
 ) -> Result<Vec<AuditLogRow>, AppError> {
-  let rows = if let Some(action) = action {
-    sqlx::query(
-      "SELECT id, created_at, actor_operator_id, action, target_type, target_id, request_json, result, error_code, error_detail \
-       FROM audit_log WHERE action = ? ORDER BY created_at DESC",
-    )
-    .bind(action)
-    .fetch_all(pool)
-    .await?
-  } else {
-    sqlx::query(
-      "SELECT id, created_at, actor_operator_id, action, target_type, target_id, request_json, result, error_code, error_detail \
-       FROM audit_log ORDER BY created_at DESC",
-    )
-    .fetch_all(pool)
-    .await?
-  };
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(AUDIT_COLUMNS);
+  let has_where = push_filters(&mut builder, filter);
+
+  if let Some((created_at, id)) = cursor {
+    builder.push(if has_where { " AND " } else { " WHERE " });
+    builder.push("(created_at < ");
+    builder.push_bind(created_at);
+    builder.push(" OR (created_at = ");
+    builder.push_bind(created_at);
+    builder.push(" AND id < ");
+    builder.push_bind(id);
+    builder.push("))");
+  }
+
+  builder.push(" ORDER BY created_at DESC, id DESC LIMIT ");
+  builder.push_bind(limit);
 
-  let items = rows
-    .into_iter()
-    .map(|row| AuditLogRow {
-      id: row.get("id"),
-      created_at: row.get("created_at"),
-      actor_operator_id: row.get("actor_operator_id"),
-      actor_operator_name: None,
-      action: row.get("action"),
-      target_type: row.get("target_type"),
-      target_id: row.get("target_id"),
-      request_json: row.get("request_json"),
-      result: row.get("result"),
-      error_code: row.get("error_code"),
-      error_detail: row.get("error_detail"),
-    })
-    .collect();
-
-  Ok(items)
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(rows.into_iter().map(map_audit_row).collect())
 }
 
-pub async fn count_audit_logs(
-  pool: &SqlitePool,
-  action: Option<String>,
-  keyword: Option<String>,
-  start_at: Option<i64>,
-  end_at: Option<i64>,
-) -> Result<i64, AppError> {
+pub async fn count_audit_logs(pool: &SqlitePool, filter: &AuditLogFilter) -> Result<i64, AppError> {
   let mut builder: QueryBuilder<Sqlite> =
     QueryBuilder::new("SELECT COUNT(1) FROM audit_log");
-  let mut has_where = false;
-  let mut push_where = |builder: &mut QueryBuilder<Sqlite>| {
-    if has_where {
-      builder.push(" AND ");
-    } else {
-      builder.push(" WHERE ");
-      has_where = true;
-    }
-  };
-
-  if let Some(action) = action {
-    push_where(&mut builder);
-    builder.push("action = ");
-    builder.push_bind(action);
-  }
-
-  if let Some(keyword) = keyword {
-    let like = format!("%{}%", keyword);
-    push_where(&mut builder);
-    builder.push("(");
-    builder.push("actor_operator_id LIKE ");
-    builder.push_bind(like.clone());
-    builder.push(" OR target_id LIKE ");
-    builder.push_bind(like.clone());
-    builder.push(" OR action LIKE ");
-    builder.push_bind(like.clone());
-    builder.push(" OR target_type LIKE ");
-    builder.push_bind(like);
-    builder.push(")");
-  }
-
-  if let Some(start_at) = start_at {
-    push_where(&mut builder);
-    builder.push("created_at >= ");
-    builder.push_bind(start_at);
-  }
-
-  if let Some(end_at) = end_at {
-    push_where(&mut builder);
-    builder.push("created_at <= ");
-    builder.push_bind(end_at);
-  }
+  push_filters(&mut builder, filter);
 
   let (count,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
   Ok(count)
 }
+
+/// 删除指定时间之前的审计日志（归档写出后调用），返回删除条数
+pub async fn delete_audit_logs_before(pool: &SqlitePool, before_at: i64) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM audit_log WHERE created_at < ?")
+    .bind(before_at)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
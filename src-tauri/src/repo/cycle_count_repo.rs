@@ -0,0 +1,442 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CycleCountPlanRow {
+  pub id: String,
+  pub name: String,
+  pub warehouse_id: String,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  // A/B 类物品占比（%），其余为 C 类
+  pub a_ratio: f64,
+  pub b_ratio: f64,
+  // 各类物品的盘点周期（天）
+  pub a_interval_days: i64,
+  pub b_interval_days: i64,
+  pub c_interval_days: i64,
+  // ABC 分类统计流水次数的天数
+  pub lookback_days: i64,
+  pub status: String,
+  pub last_generated_date: Option<String>,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+/// 生成任务的候选物品/库位：计划仓库内有库存的启用物品
+#[derive(Debug)]
+pub struct CountCandidateRow {
+  pub item_id: String,
+  pub item_code: String,
+  pub slot_id: String,
+  // 统计期内该物品在本仓库的流水次数
+  pub movement_count: i64,
+  // 最近一次盘点（或跳过盘点任务）的时间
+  pub last_counted_at: Option<i64>,
+}
+
+/// 待插入的盘点任务
+#[derive(Debug)]
+pub struct NewCountTask {
+  pub id: String,
+  pub item_id: String,
+  pub slot_id: String,
+  pub abc_class: String,
+  pub due_date: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct CountTaskRow {
+  pub id: String,
+  pub plan_id: String,
+  pub plan_name: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub item_id: String,
+  pub item_code: Option<String>,
+  pub item_name: Option<String>,
+  pub uom: Option<String>,
+  pub qty_precision: i64,
+  pub slot_id: String,
+  pub slot_code: Option<String>,
+  pub abc_class: String,
+  pub due_date: String,
+  pub scheduled_date: String,
+  // 顺延次数
+  pub carry_over: i64,
+  pub status: String,
+  pub count_txn_no: Option<String>,
+  pub completed_at: Option<i64>,
+  pub completed_by_name: Option<String>,
+  // 当前账面数量（存储值，由服务层换算）
+  pub stock_qty: i64,
+}
+
+const PLAN_COLUMNS: &str = "SELECT plan.id, plan.name, plan.warehouse_id, warehouse.code AS warehouse_code, \
+   warehouse.name AS warehouse_name, plan.a_ratio, plan.b_ratio, plan.a_interval_days, plan.b_interval_days, \
+   plan.c_interval_days, plan.lookback_days, plan.status, plan.last_generated_date, plan.created_at, plan.updated_at \
+   FROM cycle_count_plan AS plan LEFT JOIN warehouse ON plan.warehouse_id = warehouse.id";
+
+fn map_plan(row: &sqlx::sqlite::SqliteRow) -> CycleCountPlanRow {
+  CycleCountPlanRow {
+    id: row.get("id"),
+    name: row.get("name"),
+    warehouse_id: row.get("warehouse_id"),
+    warehouse_code: row.get("warehouse_code"),
+    warehouse_name: row.get("warehouse_name"),
+    a_ratio: row.get("a_ratio"),
+    b_ratio: row.get("b_ratio"),
+    a_interval_days: row.get("a_interval_days"),
+    b_interval_days: row.get("b_interval_days"),
+    c_interval_days: row.get("c_interval_days"),
+    lookback_days: row.get("lookback_days"),
+    status: row.get("status"),
+    last_generated_date: row.get("last_generated_date"),
+    created_at: row.get("created_at"),
+    updated_at: row.get("updated_at"),
+  }
+}
+
+pub async fn list_plans(
+  pool: &SqlitePool,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<Vec<CycleCountPlanRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(PLAN_COLUMNS);
+  if let Some(ids) = allowed_warehouse_ids {
+    builder.push(" WHERE ");
+    push_id_list(&mut builder, "plan.warehouse_id", ids);
+  }
+  builder.push(" ORDER BY warehouse.code, plan.name");
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(rows.iter().map(map_plan).collect())
+}
+
+pub async fn list_active_plans(pool: &SqlitePool) -> Result<Vec<CycleCountPlanRow>, AppError> {
+  let rows = sqlx::query(&format!("{} WHERE plan.status = 'active' ORDER BY plan.name", PLAN_COLUMNS))
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.iter().map(map_plan).collect())
+}
+
+pub async fn get_plan(pool: &SqlitePool, id: &str) -> Result<Option<CycleCountPlanRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE plan.id = ?", PLAN_COLUMNS))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_plan))
+}
+
+pub async fn name_exists(pool: &SqlitePool, name: &str, exclude_id: Option<&str>) -> Result<bool, AppError> {
+  let count: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM cycle_count_plan WHERE name = ? AND id <> ?")
+    .bind(name)
+    .bind(exclude_id.unwrap_or(""))
+    .fetch_one(pool)
+    .await?;
+  Ok(count > 0)
+}
+
+/// 新增或更新计划（生成日期不随计划修改变化）
+pub async fn save_plan(pool: &SqlitePool, plan: &CycleCountPlanRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO cycle_count_plan \
+     (id, name, warehouse_id, a_ratio, b_ratio, a_interval_days, b_interval_days, c_interval_days, \
+      lookback_days, status, created_at, updated_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+     ON CONFLICT(id) DO UPDATE SET name = excluded.name, warehouse_id = excluded.warehouse_id, \
+     a_ratio = excluded.a_ratio, b_ratio = excluded.b_ratio, a_interval_days = excluded.a_interval_days, \
+     b_interval_days = excluded.b_interval_days, c_interval_days = excluded.c_interval_days, \
+     lookback_days = excluded.lookback_days, status = excluded.status, updated_at = excluded.updated_at",
+  )
+  .bind(&plan.id)
+  .bind(&plan.name)
+  .bind(&plan.warehouse_id)
+  .bind(plan.a_ratio)
+  .bind(plan.b_ratio)
+  .bind(plan.a_interval_days)
+  .bind(plan.b_interval_days)
+  .bind(plan.c_interval_days)
+  .bind(plan.lookback_days)
+  .bind(&plan.status)
+  .bind(plan.created_at)
+  .bind(plan.updated_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 删除计划，任务随计划级联删除
+pub async fn delete_plan(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM cycle_count_plan WHERE id = ?")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+/// 计划变更仓库后清除未完成的任务，次日按新仓库重新生成
+pub async fn delete_pending_tasks(pool: &SqlitePool, plan_id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM cycle_count_task WHERE plan_id = ? AND status = 'pending'")
+    .bind(plan_id)
+    .execute(pool)
+    .await?;
+  sqlx::query("UPDATE cycle_count_plan SET last_generated_date = NULL WHERE id = ?")
+    .bind(plan_id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+/// 任务生成后登记过盘点（COUNT 流水）的物品/库位自动完成，返回完成条数
+pub async fn complete_counted_tasks(pool: &SqlitePool) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE cycle_count_task SET status = 'done', \
+     count_txn_id = counted.txn_id, completed_at = counted.created_at, completed_by = counted.operator_id \
+     FROM (SELECT task.id AS task_id, txn.id AS txn_id, txn.created_at, txn.operator_id, \
+           ROW_NUMBER() OVER (PARTITION BY task.id ORDER BY txn.created_at) AS rn \
+           FROM cycle_count_task AS task \
+           JOIN txn ON txn.item_id = task.item_id AND txn.from_slot_id = task.slot_id \
+           AND txn.\"type\" = 'COUNT' AND txn.created_at >= task.created_at \
+           WHERE task.status = 'pending') AS counted \
+     WHERE cycle_count_task.id = counted.task_id AND counted.rn = 1",
+  )
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 早于指定日期仍未完成的任务顺延到该日期，返回顺延条数
+pub async fn carry_over_tasks(pool: &SqlitePool, plan_id: &str, due_date: &str) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE cycle_count_task SET due_date = ?, carry_over = carry_over + 1 \
+     WHERE plan_id = ? AND status = 'pending' AND due_date < ?",
+  )
+  .bind(due_date)
+  .bind(plan_id)
+  .bind(due_date)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 计划仓库内有库存的启用物品/库位，及物品在统计期内的流水次数与库位最近盘点时间
+pub async fn list_candidates(
+  pool: &SqlitePool,
+  warehouse_id: &str,
+  movement_since: i64,
+) -> Result<Vec<CountCandidateRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT stock.item_id, item.item_code, stock.slot_id, \
+     COALESCE(moves.movement_count, 0) AS movement_count, \
+     MAX(COALESCE((SELECT MAX(txn.created_at) FROM txn WHERE txn.item_id = stock.item_id \
+       AND txn.from_slot_id = stock.slot_id AND txn.\"type\" = 'COUNT'), 0), \
+       COALESCE((SELECT MAX(task.completed_at) FROM cycle_count_task AS task WHERE task.item_id = stock.item_id \
+       AND task.slot_id = stock.slot_id AND task.status <> 'pending'), 0)) AS last_counted_at \
+     FROM stock \
+     JOIN item ON stock.item_id = item.id \
+     JOIN slot ON stock.slot_id = slot.id \
+     LEFT JOIN (SELECT txn.item_id, COUNT(1) AS movement_count FROM txn \
+       LEFT JOIN slot AS from_slot ON txn.from_slot_id = from_slot.id \
+       LEFT JOIN slot AS to_slot ON txn.to_slot_id = to_slot.id \
+       WHERE txn.occurred_at >= ? AND txn.\"type\" IN ('IN', 'OUT', 'MOVE', 'SCRAP') \
+       AND (from_slot.warehouse_id = ? OR to_slot.warehouse_id = ?) \
+       GROUP BY txn.item_id) AS moves ON moves.item_id = stock.item_id \
+     WHERE stock.qty > 0 AND item.status = 'active' AND slot.status = 'active' AND slot.warehouse_id = ? \
+     ORDER BY item.item_code, slot.code",
+  )
+  .bind(movement_since)
+  .bind(warehouse_id)
+  .bind(warehouse_id)
+  .bind(warehouse_id)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| CountCandidateRow {
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        slot_id: row.get("slot_id"),
+        movement_count: row.get("movement_count"),
+        last_counted_at: Some(row.get::<i64, _>("last_counted_at")).filter(|value| *value > 0),
+      })
+      .collect(),
+  )
+}
+
+/// 计划中未完成任务的（物品, 库位, 分类）
+pub async fn list_pending_keys(pool: &SqlitePool, plan_id: &str) -> Result<Vec<(String, String, String)>, AppError> {
+  let rows: Vec<(String, String, String)> = sqlx::query_as(
+    "SELECT item_id, slot_id, abc_class FROM cycle_count_task WHERE plan_id = ? AND status = 'pending'",
+  )
+  .bind(plan_id)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows)
+}
+
+/// 写入当日任务并记录计划的生成日期
+pub async fn insert_tasks(
+  pool: &SqlitePool,
+  plan_id: &str,
+  generated_date: &str,
+  tasks: &[NewCountTask],
+  created_at: i64,
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  for task in tasks {
+    sqlx::query(
+      "INSERT INTO cycle_count_task \
+       (id, plan_id, item_id, slot_id, abc_class, due_date, scheduled_date, carry_over, status, created_at) \
+       VALUES (?, ?, ?, ?, ?, ?, ?, 0, 'pending', ?)",
+    )
+    .bind(&task.id)
+    .bind(plan_id)
+    .bind(&task.item_id)
+    .bind(&task.slot_id)
+    .bind(&task.abc_class)
+    .bind(&task.due_date)
+    .bind(&task.due_date)
+    .bind(created_at)
+    .execute(&mut *tx)
+    .await?;
+  }
+  sqlx::query("UPDATE cycle_count_plan SET last_generated_date = ? WHERE id = ?")
+    .bind(generated_date)
+    .bind(plan_id)
+    .execute(&mut *tx)
+    .await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 截至指定日期仍待执行的盘点任务数，可按仓库范围过滤
+pub async fn count_pending_tasks(
+  pool: &SqlitePool,
+  due_date: &str,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<i64, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT COUNT(1) FROM cycle_count_task AS task \
+     JOIN cycle_count_plan AS plan ON task.plan_id = plan.id \
+     WHERE task.status = 'pending' AND task.due_date <= ",
+  );
+  builder.push_bind(due_date.to_string());
+  if let Some(ids) = allowed_warehouse_ids {
+    builder.push(" AND ");
+    push_id_list(&mut builder, "plan.warehouse_id", ids);
+  }
+  let (count,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
+  Ok(count)
+}
+
+/// 指定日期的盘点任务（含当日已完成的），可按仓库与仓库范围过滤
+pub async fn list_tasks_by_date(
+  pool: &SqlitePool,
+  due_date: &str,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<Vec<CountTaskRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT task.id, task.plan_id, plan.name AS plan_name, plan.warehouse_id, warehouse.code AS warehouse_code, \
+     warehouse.name AS warehouse_name, task.item_id, item.item_code, item.name AS item_name, item.uom, \
+     COALESCE(item.qty_precision, 0) AS qty_precision, task.slot_id, slot.code AS slot_code, task.abc_class, \
+     task.due_date, task.scheduled_date, task.carry_over, task.status, txn.txn_no AS count_txn_no, \
+     task.completed_at, op.display_name AS completed_by_name, COALESCE(stock.qty, 0) AS stock_qty \
+     FROM cycle_count_task AS task \
+     JOIN cycle_count_plan AS plan ON task.plan_id = plan.id \
+     LEFT JOIN warehouse ON plan.warehouse_id = warehouse.id \
+     LEFT JOIN item ON task.item_id = item.id \
+     LEFT JOIN slot ON task.slot_id = slot.id \
+     LEFT JOIN stock ON stock.item_id = task.item_id AND stock.slot_id = task.slot_id \
+     LEFT JOIN txn ON task.count_txn_id = txn.id \
+     LEFT JOIN \"operator\" AS op ON task.completed_by = op.id \
+     WHERE task.due_date = ",
+  );
+  builder.push_bind(due_date.to_string());
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND plan.warehouse_id = ").push_bind(warehouse_id.to_string());
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    builder.push(" AND ");
+    push_id_list(&mut builder, "plan.warehouse_id", ids);
+  }
+  builder.push(" ORDER BY warehouse.code, task.status DESC, task.abc_class, slot.code");
+  let rows = builder.build().fetch_all(pool).await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| CountTaskRow {
+        id: row.get("id"),
+        plan_id: row.get("plan_id"),
+        plan_name: row.get("plan_name"),
+        warehouse_id: row.get("warehouse_id"),
+        warehouse_code: row.get("warehouse_code"),
+        warehouse_name: row.get("warehouse_name"),
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        uom: row.get("uom"),
+        qty_precision: row.get("qty_precision"),
+        slot_id: row.get("slot_id"),
+        slot_code: row.get("slot_code"),
+        abc_class: row.get("abc_class"),
+        due_date: row.get("due_date"),
+        scheduled_date: row.get("scheduled_date"),
+        carry_over: row.get("carry_over"),
+        status: row.get("status"),
+        count_txn_no: row.get("count_txn_no"),
+        completed_at: row.get("completed_at"),
+        completed_by_name: row.get("completed_by_name"),
+        stock_qty: row.get("stock_qty"),
+      })
+      .collect(),
+  )
+}
+
+/// 任务所属计划的仓库与状态
+pub async fn get_task_scope(pool: &SqlitePool, task_id: &str) -> Result<Option<(String, String)>, AppError> {
+  let row: Option<(String, String)> = sqlx::query_as(
+    "SELECT plan.warehouse_id, task.status FROM cycle_count_task AS task \
+     JOIN cycle_count_plan AS plan ON task.plan_id = plan.id WHERE task.id = ?",
+  )
+  .bind(task_id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row)
+}
+
+/// 跳过未完成的任务，返回更新条数
+pub async fn skip_task(
+  pool: &SqlitePool,
+  task_id: &str,
+  operator_id: &str,
+  completed_at: i64,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE cycle_count_task SET status = 'skipped', completed_at = ?, completed_by = ? \
+     WHERE id = ? AND status = 'pending'",
+  )
+  .bind(completed_at)
+  .bind(operator_id)
+  .bind(task_id)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+fn push_id_list(builder: &mut QueryBuilder<Sqlite>, column: &str, ids: &[String]) {
+  if ids.is_empty() {
+    builder.push("1 = 0");
+    return;
+  }
+  builder.push(column).push(" IN (");
+  let mut separated = builder.separated(", ");
+  for id in ids {
+    separated.push_bind(id.clone());
+  }
+  separated.push_unseparated(")");
+}
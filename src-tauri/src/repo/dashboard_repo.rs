@@ -1,6 +1,8 @@
 use sqlx::{Row, SqlitePool};
 
 use crate::domain::errors::AppError;
+use crate::domain::quantity;
+use crate::domain::timezone::ReportTimezone;
 
 #[derive(Debug)]
 pub struct TxnTypeCountRow {
@@ -10,16 +12,32 @@ pub struct TxnTypeCountRow {
 
 #[derive(Debug)]
 pub struct TxnTrendRow {
-  pub day: String,
-  pub txn_type: String,
-  pub total: i64,
+  // 分组起始日（YYYY-MM-DD，本地时间）
+  pub bucket: String,
+  pub inbound: i64,
+  pub outbound: i64,
+  pub move_count: i64,
+  pub count_count: i64,
+  pub scrap: i64,
 }
 
 #[derive(Debug)]
 pub struct WarehouseStockRow {
   pub warehouse_code: Option<String>,
   pub warehouse_name: Option<String>,
-  pub total_qty: i64,
+  // 显示数量合计
+  pub total_qty: f64,
+  // 库存价值（数量 × 物品单位成本，未设置成本按 0 计）
+  pub total_value: f64,
+}
+
+#[derive(Debug)]
+pub struct OperatorLeaderboardRow {
+  pub operator_id: String,
+  pub operator_name: String,
+  pub txn_count: i64,
+  // 显示数量合计
+  pub total_qty: f64,
 }
 
 pub async fn count_txns_by_type(
@@ -49,42 +67,68 @@ pub async fn count_txns_by_type(
   )
 }
 
+/// 按粒度（day/week/month）分组统计流水趋势，分组与按类型汇总均在 SQL 中完成
 pub async fn list_txn_trend(
   pool: &SqlitePool,
   start_at: i64,
   end_at: i64,
+  granularity: &str,
+  timezone: &ReportTimezone,
 ) -> Result<Vec<TxnTrendRow>, AppError> {
-  let rows = sqlx::query(
-    "SELECT strftime('%Y-%m-%d', occurred_at, 'unixepoch', 'localtime') AS day, \
-     txn.\"type\" AS txn_type, COUNT(1) AS total \
+  let bucket_expr = trend_bucket_expr(granularity, timezone);
+  let sql = format!(
+    "SELECT {} AS bucket, \
+     SUM(CASE WHEN txn.\"type\" = 'IN' THEN 1 ELSE 0 END) AS inbound, \
+     SUM(CASE WHEN txn.\"type\" = 'OUT' THEN 1 ELSE 0 END) AS outbound, \
+     SUM(CASE WHEN txn.\"type\" = 'MOVE' THEN 1 ELSE 0 END) AS move_count, \
+     SUM(CASE WHEN txn.\"type\" = 'COUNT' THEN 1 ELSE 0 END) AS count_count, \
+     SUM(CASE WHEN txn.\"type\" = 'SCRAP' THEN 1 ELSE 0 END) AS scrap \
      FROM txn \
      WHERE occurred_at >= ? AND occurred_at <= ? \
-     GROUP BY day, txn.\"type\" \
-     ORDER BY day ASC",
-  )
-  .bind(start_at)
-  .bind(end_at)
-  .fetch_all(pool)
-  .await?;
+     GROUP BY bucket \
+     ORDER BY bucket ASC",
+    bucket_expr
+  );
+  let rows = sqlx::query(&sql)
+    .bind(start_at)
+    .bind(end_at)
+    .fetch_all(pool)
+    .await?;
 
   Ok(
     rows
       .into_iter()
       .map(|row| TxnTrendRow {
-        day: row.get("day"),
-        txn_type: row.get("txn_type"),
-        total: row.get("total"),
+        bucket: row.get("bucket"),
+        inbound: row.get::<Option<i64>, _>("inbound").unwrap_or(0),
+        outbound: row.get::<Option<i64>, _>("outbound").unwrap_or(0),
+        move_count: row.get::<Option<i64>, _>("move_count").unwrap_or(0),
+        count_count: row.get::<Option<i64>, _>("count_count").unwrap_or(0),
+        scrap: row.get::<Option<i64>, _>("scrap").unwrap_or(0),
       })
       .collect(),
   )
 }
 
-pub async fn sum_stock_qty(pool: &SqlitePool) -> Result<i64, AppError> {
-  let (total,): (Option<i64>,) =
-    sqlx::query_as("SELECT SUM(qty) FROM stock")
-      .fetch_one(pool)
-      .await?;
-  Ok(total.unwrap_or(0))
+/// 分组起始日表达式（按报表时区）：day 为当天，week 为所在周的周一，month 为当月 1 日
+pub fn trend_bucket_expr(granularity: &str, timezone: &ReportTimezone) -> String {
+  let modifier = timezone.sqlite_modifier();
+  match granularity {
+    "week" => format!("date(occurred_at, 'unixepoch', {}, 'weekday 0', '-6 days')", modifier),
+    "month" => format!("strftime('%Y-%m-01', occurred_at, 'unixepoch', {})", modifier),
+    _ => format!("strftime('%Y-%m-%d', occurred_at, 'unixepoch', {})", modifier),
+  }
+}
+
+/// 库存总量（各物品按精度换算为显示数量后求和）
+pub async fn sum_stock_qty(pool: &SqlitePool) -> Result<f64, AppError> {
+  let (total,): (Option<f64>,) = sqlx::query_as(&format!(
+    "SELECT SUM(stock.qty / {}) FROM stock JOIN item ON stock.item_id = item.id",
+    quantity::scale_sql("item.qty_precision")
+  ))
+  .fetch_one(pool)
+  .await?;
+  Ok(total.unwrap_or(0.0))
 }
 
 pub async fn count_active_items(pool: &SqlitePool) -> Result<i64, AppError> {
@@ -127,16 +171,19 @@ pub async fn count_negative_stock(pool: &SqlitePool) -> Result<i64, AppError> {
 pub async fn list_stock_by_warehouse(
   pool: &SqlitePool,
 ) -> Result<Vec<WarehouseStockRow>, AppError> {
-  let rows = sqlx::query(
+  let rows = sqlx::query(&format!(
     "SELECT warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, \
-     SUM(stock.qty) AS total_qty \
+     SUM(stock.qty / {scale}) AS total_qty, \
+     SUM(stock.qty / {scale} * COALESCE(item.unit_cost, 0)) AS total_value \
      FROM stock \
+     JOIN item ON stock.item_id = item.id \
      JOIN slot ON stock.slot_id = slot.id \
      JOIN rack ON slot.rack_id = rack.id \
      LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
      GROUP BY warehouse.code, warehouse.name \
      ORDER BY total_qty DESC",
-  )
+    scale = quantity::scale_sql("item.qty_precision")
+  ))
   .fetch_all(pool)
   .await?;
 
@@ -147,8 +194,51 @@ pub async fn list_stock_by_warehouse(
         warehouse_code: row.get("warehouse_code"),
         warehouse_name: row.get("warehouse_name"),
         total_qty: row
-          .get::<Option<i64>, _>("total_qty")
-          .unwrap_or(0),
+          .get::<Option<f64>, _>("total_qty")
+          .unwrap_or(0.0),
+        total_value: row
+          .get::<Option<f64>, _>("total_value")
+          .unwrap_or(0.0),
+      })
+      .collect(),
+  )
+}
+
+pub async fn list_operator_leaderboard(
+  pool: &SqlitePool,
+  start_at: i64,
+  end_at: i64,
+  limit: i64,
+) -> Result<Vec<OperatorLeaderboardRow>, AppError> {
+  // 冲正不计入作业量
+  let rows = sqlx::query(&format!(
+    "SELECT op.id AS operator_id, op.display_name AS operator_name, \
+     COUNT(1) AS txn_count, SUM(ABS(txn.qty) / {scale}) AS total_qty \
+     FROM txn \
+     JOIN \"operator\" AS op ON txn.operator_id = op.id \
+     JOIN item ON txn.item_id = item.id \
+     WHERE txn.occurred_at >= ? AND txn.occurred_at <= ? AND txn.\"type\" <> 'REVERSAL' \
+     GROUP BY op.id, op.display_name \
+     ORDER BY txn_count DESC, total_qty DESC \
+     LIMIT ?",
+    scale = quantity::scale_sql("item.qty_precision")
+  ))
+  .bind(start_at)
+  .bind(end_at)
+  .bind(limit)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| OperatorLeaderboardRow {
+        operator_id: row.get("operator_id"),
+        operator_name: row.get("operator_name"),
+        txn_count: row.get("txn_count"),
+        total_qty: row
+          .get::<Option<f64>, _>("total_qty")
+          .unwrap_or(0.0),
       })
       .collect(),
   )
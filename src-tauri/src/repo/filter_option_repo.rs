@@ -0,0 +1,85 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct FilterOptionRow {
+  pub value: String,
+  pub count: i64,
+}
+
+/// 统计某列的去重取值及出现次数（忽略空值），按次数降序；
+/// table/column 由服务层白名单给出，不接受外部输入
+pub async fn list_distinct_values(
+  pool: &SqlitePool,
+  table: &str,
+  column: &str,
+  keyword: Option<&str>,
+  limit: i64,
+) -> Result<Vec<FilterOptionRow>, AppError> {
+  let mut builder = QueryBuilder::new(format!(
+    "SELECT {column} AS value, COUNT(*) AS count FROM {table} \
+     WHERE {column} IS NOT NULL AND {column} <> ''"
+  ));
+  push_keyword(&mut builder, column, keyword);
+  push_group_limit(&mut builder, column, limit);
+  fetch(pool, builder).await
+}
+
+/// 流水类型取值，按仓库范围过滤（来源或目标库位属于可访问仓库）
+pub async fn list_txn_types(
+  pool: &SqlitePool,
+  keyword: Option<&str>,
+  limit: i64,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<FilterOptionRow>, AppError> {
+  let mut builder = QueryBuilder::new(
+    "SELECT txn.type AS value, COUNT(*) AS count FROM txn \
+     LEFT JOIN slot fr ON txn.from_slot_id = fr.id \
+     LEFT JOIN slot tr ON txn.to_slot_id = tr.id WHERE 1 = 1",
+  );
+  push_keyword(&mut builder, "txn.type", keyword);
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      return Ok(Vec::new());
+    }
+    builder.push(" AND (fr.warehouse_id IN (");
+    let mut separated = builder.separated(", ");
+    for id in &ids {
+      separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(") OR tr.warehouse_id IN (");
+    let mut separated = builder.separated(", ");
+    for id in &ids {
+      separated.push_bind(id.clone());
+    }
+    separated.push_unseparated("))");
+  }
+  push_group_limit(&mut builder, "txn.type", limit);
+  fetch(pool, builder).await
+}
+
+fn push_keyword(builder: &mut QueryBuilder<Sqlite>, column: &str, keyword: Option<&str>) {
+  if let Some(keyword) = keyword.map(str::trim).filter(|value| !value.is_empty()) {
+    builder.push(format!(" AND {} LIKE ", column));
+    builder.push_bind(format!("%{}%", keyword));
+  }
+}
+
+fn push_group_limit(builder: &mut QueryBuilder<Sqlite>, column: &str, limit: i64) {
+  builder.push(format!(" GROUP BY {} ORDER BY count DESC, value LIMIT ", column));
+  builder.push_bind(limit);
+}
+
+async fn fetch(pool: &SqlitePool, mut builder: QueryBuilder<'_, Sqlite>) -> Result<Vec<FilterOptionRow>, AppError> {
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| FilterOptionRow {
+        value: row.get("value"),
+        count: row.get("count"),
+      })
+      .collect(),
+  )
+}
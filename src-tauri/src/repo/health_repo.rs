@@ -0,0 +1,286 @@
+use sqlx::sqlite::SqliteRow;
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+
+/// 引用完整性检查项：（类型, 样例列, 查询主体），样例列用于在报告中展示具体记录
+const ORPHAN_CHECKS: [(&str, &str, &str); 8] = [
+  ("txn_missing_item", "txn_no", "txn WHERE item_id NOT IN (SELECT id FROM item)"),
+  (
+    "txn_missing_slot",
+    "txn_no",
+    "txn WHERE (from_slot_id IS NOT NULL AND from_slot_id NOT IN (SELECT id FROM slot)) \
+     OR (to_slot_id IS NOT NULL AND to_slot_id NOT IN (SELECT id FROM slot))",
+  ),
+  (
+    "reversal_missing_ref",
+    "txn_no",
+    "txn WHERE \"type\" = 'REVERSAL' AND (ref_txn_id IS NULL OR ref_txn_id NOT IN (SELECT id FROM txn))",
+  ),
+  ("stock_missing_item", "id", "stock WHERE item_id NOT IN (SELECT id FROM item)"),
+  ("stock_missing_slot", "id", "stock WHERE slot_id NOT IN (SELECT id FROM slot)"),
+  ("stock_negative", "id", "stock WHERE qty < 0"),
+  ("slot_missing_rack", "code", "slot WHERE rack_id NOT IN (SELECT id FROM rack)"),
+  ("loan_missing_txn", "id", "loan WHERE out_txn_id NOT IN (SELECT id FROM txn)"),
+];
+
+/// 按流水推算库存：每条流水拆成来源/目标库位的变动（与报表的 leg 规则一致），冲正按原流水类型反向
+const LEDGER_STOCK_SQL: &str = "SELECT item_id, slot_id, SUM(delta) AS qty FROM (\
+  SELECT txn.item_id, txn.from_slot_id AS slot_id, txn.qty * CASE \
+  WHEN txn.\"type\" IN ('OUT', 'MOVE', 'SCRAP') THEN -1 \
+  WHEN txn.\"type\" = 'ADJUST' THEN 1 \
+  WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" IN ('OUT', 'MOVE', 'SCRAP') THEN 1 \
+  WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" = 'ADJUST' THEN -1 \
+  ELSE 0 END AS delta \
+  FROM txn LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+  WHERE txn.from_slot_id IS NOT NULL \
+  UNION ALL \
+  SELECT txn.item_id, txn.to_slot_id AS slot_id, txn.qty * CASE \
+  WHEN txn.\"type\" IN ('IN', 'MOVE', 'OPENING') THEN 1 \
+  WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" IN ('IN', 'MOVE', 'OPENING') THEN -1 \
+  ELSE 0 END AS delta \
+  FROM txn LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+  WHERE txn.to_slot_id IS NOT NULL\
+  ) AS leg GROUP BY item_id, slot_id";
+
+/// 常用查询的执行计划自检项：（名称, 期望使用的索引, 查询），条件取值不影响索引选择，统一用空值占位
+pub const QUERY_PLAN_CHECKS: [(&str, &[&str], &str); 7] = [
+  (
+    "txn_by_time",
+    &["idx_txn_occurred"],
+    "SELECT id FROM txn WHERE occurred_at >= 0 AND occurred_at <= 0 ORDER BY created_at DESC, id DESC LIMIT 20",
+  ),
+  (
+    "txn_by_item",
+    &["idx_txn_item_time"],
+    "SELECT id FROM txn WHERE item_id = '' ORDER BY created_at DESC, id DESC LIMIT 20",
+  ),
+  (
+    "txn_by_operator",
+    &["idx_txn_operator_created"],
+    "SELECT id FROM txn WHERE operator_id = '' ORDER BY created_at DESC, id DESC LIMIT 20",
+  ),
+  (
+    "txn_by_slot",
+    &["idx_txn_from_slot", "idx_txn_to_slot"],
+    "SELECT id FROM txn WHERE (from_slot_id = '' OR to_slot_id = '') ORDER BY created_at DESC, id DESC LIMIT 20",
+  ),
+  ("stock_by_slot", &["idx_stock_slot"], "SELECT id FROM stock WHERE slot_id = ''"),
+  (
+    "audit_by_action",
+    &["idx_audit_action_time"],
+    "SELECT id FROM audit_log WHERE action = '' AND created_at >= 0 ORDER BY created_at DESC, id DESC LIMIT 20",
+  ),
+  (
+    "audit_by_time",
+    &["idx_audit_created_id"],
+    "SELECT id FROM audit_log WHERE created_at >= 0 AND created_at <= 0 ORDER BY created_at DESC, id DESC LIMIT 20",
+  ),
+];
+
+/// 单项引用完整性检查结果
+#[derive(Debug)]
+pub struct OrphanCheckRow {
+  pub kind: &'static str,
+  pub total: i64,
+  pub samples: Vec<String>,
+}
+
+/// 外键检查（PRAGMA foreign_key_check）发现的违规记录
+#[derive(Debug)]
+pub struct ForeignKeyViolationRow {
+  pub table: String,
+  pub rowid: Option<i64>,
+  pub parent: String,
+}
+
+/// 库存表与流水推算结果不一致的物品/库位
+#[derive(Debug)]
+pub struct StockLedgerDiffRow {
+  pub item_id: String,
+  pub item_code: Option<String>,
+  pub slot_id: String,
+  pub slot_code: Option<String>,
+  pub qty_precision: i64,
+  pub stock_qty: i64,
+  pub ledger_qty: i64,
+}
+
+/// 已应用的迁移（_sqlx_migrations）
+#[derive(Debug, serde::Serialize)]
+pub struct AppliedMigrationRow {
+  pub version: i64,
+  pub description: String,
+  pub success: bool,
+  pub installed_on: String,
+}
+
+/// PRAGMA integrity_check，数据库完好时只返回一行 "ok"
+pub async fn integrity_check(pool: &SqlitePool) -> Result<Vec<String>, AppError> {
+  let rows = sqlx::query("PRAGMA integrity_check").fetch_all(pool).await?;
+  Ok(rows.into_iter().map(|row| row.get::<String, _>(0)).collect())
+}
+
+pub async fn foreign_key_violations(pool: &SqlitePool) -> Result<Vec<ForeignKeyViolationRow>, AppError> {
+  let rows = sqlx::query("PRAGMA foreign_key_check").fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| ForeignKeyViolationRow {
+        table: row.get("table"),
+        rowid: row.get("rowid"),
+        parent: row.get("parent"),
+      })
+      .collect(),
+  )
+}
+
+pub async fn list_orphan_checks(pool: &SqlitePool, sample_limit: i64) -> Result<Vec<OrphanCheckRow>, AppError> {
+  let mut result = Vec::with_capacity(ORPHAN_CHECKS.len());
+  for (kind, sample_column, body) in ORPHAN_CHECKS {
+    let (total,): (i64,) = sqlx::query_as(&format!("SELECT COUNT(1) FROM {}", body))
+      .fetch_one(pool)
+      .await?;
+    let samples = if total > 0 {
+      sqlx::query_scalar::<_, String>(&format!("SELECT {} FROM {} LIMIT ?", sample_column, body))
+        .bind(sample_limit)
+        .fetch_all(pool)
+        .await?
+    } else {
+      Vec::new()
+    };
+    result.push(OrphanCheckRow { kind, total, samples });
+  }
+  Ok(result)
+}
+
+/// 对比库存表与流水推算结果，返回不一致的记录（任一侧缺失按 0 计）
+pub async fn list_stock_ledger_diffs(pool: &SqlitePool) -> Result<Vec<StockLedgerDiffRow>, AppError> {
+  let rows = stock_ledger_diff_query(None, None).build().fetch_all(pool).await?;
+  Ok(rows.into_iter().map(map_stock_ledger_diff).collect())
+}
+
+/// 同上，在事务内执行并可按物品、库位过滤，用于重算库存时读取与修正在同一快照上
+pub async fn list_stock_ledger_diffs_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  item_id: Option<&str>,
+  slot_id: Option<&str>,
+) -> Result<Vec<StockLedgerDiffRow>, AppError> {
+  let rows = stock_ledger_diff_query(item_id, slot_id)
+    .build()
+    .fetch_all(&mut **tx)
+    .await?;
+  Ok(rows.into_iter().map(map_stock_ledger_diff).collect())
+}
+
+fn stock_ledger_diff_query<'a>(item_id: Option<&str>, slot_id: Option<&str>) -> QueryBuilder<'a, Sqlite> {
+  let mut builder = QueryBuilder::new(format!(
+    "WITH ledger AS ({}) \
+     SELECT diff.item_id, item.item_code, diff.slot_id, slot.code AS slot_code, \
+     COALESCE(item.qty_precision, 0) AS qty_precision, diff.stock_qty, diff.ledger_qty FROM (\
+     SELECT stock.item_id, stock.slot_id, stock.qty AS stock_qty, COALESCE(ledger.qty, 0) AS ledger_qty \
+     FROM stock LEFT JOIN ledger ON ledger.item_id = stock.item_id AND ledger.slot_id = stock.slot_id \
+     UNION ALL \
+     SELECT ledger.item_id, ledger.slot_id, 0 AS stock_qty, ledger.qty AS ledger_qty FROM ledger \
+     WHERE NOT EXISTS (SELECT 1 FROM stock WHERE stock.item_id = ledger.item_id AND stock.slot_id = ledger.slot_id)\
+     ) AS diff \
+     LEFT JOIN item ON item.id = diff.item_id LEFT JOIN slot ON slot.id = diff.slot_id \
+     WHERE diff.stock_qty <> diff.ledger_qty",
+    LEDGER_STOCK_SQL
+  ));
+  if let Some(item_id) = item_id {
+    builder.push(" AND diff.item_id = ");
+    builder.push_bind(item_id.to_string());
+  }
+  if let Some(slot_id) = slot_id {
+    builder.push(" AND diff.slot_id = ");
+    builder.push_bind(slot_id.to_string());
+  }
+  builder.push(" ORDER BY item.item_code, slot.code");
+  builder
+}
+
+fn map_stock_ledger_diff(row: SqliteRow) -> StockLedgerDiffRow {
+  StockLedgerDiffRow {
+    item_id: row.get("item_id"),
+    item_code: row.get("item_code"),
+    slot_id: row.get("slot_id"),
+    slot_code: row.get("slot_code"),
+    qty_precision: row.get("qty_precision"),
+    stock_qty: row.get("stock_qty"),
+    ledger_qty: row.get("ledger_qty"),
+  }
+}
+
+/// 删除物品或库位已不存在的库存行，返回删除条数
+pub async fn delete_orphan_stock(pool: &SqlitePool) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "DELETE FROM stock WHERE item_id NOT IN (SELECT id FROM item) OR slot_id NOT IN (SELECT id FROM slot)",
+  )
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 重建全部索引，用于修复 integrity_check 报告的索引损坏
+pub async fn reindex(pool: &SqlitePool) -> Result<(), AppError> {
+  sqlx::query("REINDEX").execute(pool).await?;
+  Ok(())
+}
+
+/// 各业务表的行数（不含 SQLite 内部表），用于诊断包的数据库概要
+pub async fn table_row_counts(pool: &SqlitePool) -> Result<Vec<(String, i64)>, AppError> {
+  let tables: Vec<(String,)> = sqlx::query_as(
+    "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%' ORDER BY name",
+  )
+  .fetch_all(pool)
+  .await?;
+  let mut counts = Vec::with_capacity(tables.len());
+  for (name,) in tables {
+    let sql = format!("SELECT COUNT(*) FROM \"{}\"", name.replace('"', "\"\""));
+    let count: i64 = sqlx::query_scalar(&sql).fetch_one(pool).await?;
+    counts.push((name, count));
+  }
+  Ok(counts)
+}
+
+/// 读取单值 PRAGMA（page_size/page_count/freelist_count/journal_mode 等）
+pub async fn pragma_value(pool: &SqlitePool, name: &str) -> Result<String, AppError> {
+  let row = sqlx::query(&format!("PRAGMA {}", name)).fetch_one(pool).await?;
+  Ok(
+    row
+      .try_get::<i64, _>(0)
+      .map(|value| value.to_string())
+      .or_else(|_| row.try_get::<String, _>(0))
+      .unwrap_or_default(),
+  )
+}
+
+/// 已应用的迁移记录
+pub async fn applied_migrations(pool: &SqlitePool) -> Result<Vec<AppliedMigrationRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT version, description, success, CAST(installed_on AS TEXT) AS installed_on \
+     FROM _sqlx_migrations ORDER BY version",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| AppliedMigrationRow {
+        version: row.get("version"),
+        description: row.get("description"),
+        success: row.get("success"),
+        installed_on: row.get("installed_on"),
+      })
+      .collect(),
+  )
+}
+
+/// EXPLAIN QUERY PLAN 的各步骤说明（detail 列）
+pub async fn explain_query_plan(pool: &SqlitePool, sql: &str) -> Result<Vec<String>, AppError> {
+  let rows = sqlx::query(&format!("EXPLAIN QUERY PLAN {}", sql))
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.into_iter().map(|row| row.get::<String, _>("detail")).collect())
+}
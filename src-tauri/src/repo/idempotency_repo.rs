@@ -0,0 +1,50 @@
+// 流水提交幂等键：键唯一，记录首次提交的动作与流水号
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone)]
+pub struct IdempotencyRow {
+  pub action: String,
+  pub txn_no: String,
+  pub created_at: i64,
+}
+
+pub async fn get_key(pool: &SqlitePool, key: &str) -> Result<Option<IdempotencyRow>, AppError> {
+  let row = sqlx::query("SELECT action, txn_no, created_at FROM txn_idempotency WHERE idempotency_key = ?")
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.map(|row| IdempotencyRow {
+    action: row.get("action"),
+    txn_no: row.get("txn_no"),
+    created_at: row.get("created_at"),
+  }))
+}
+
+/// 在事务内写入幂等键；已过期的同名键由调用方先行清理
+pub async fn insert_key_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  key: &str,
+  action: &str,
+  txn_no: &str,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO txn_idempotency (idempotency_key, action, txn_no, created_at) VALUES (?, ?, ?, ?)")
+    .bind(key)
+    .bind(action)
+    .bind(txn_no)
+    .bind(created_at)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+/// 在事务内删除早于 before 的幂等键
+pub async fn delete_before_tx(tx: &mut Transaction<'_, Sqlite>, before: i64) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM txn_idempotency WHERE created_at < ?")
+    .bind(before)
+    .execute(&mut **tx)
+    .await?;
+  Ok(result.rows_affected())
+}
@@ -0,0 +1,98 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct ItemAliasRow {
+  pub id: String,
+  pub item_id: String,
+  pub code: String,
+  // manufacturer / internal / ean / supplier / other
+  pub kind: String,
+  pub note: Option<String>,
+  pub created_at: i64,
+}
+
+fn map_alias(row: &sqlx::sqlite::SqliteRow) -> ItemAliasRow {
+  ItemAliasRow {
+    id: row.get("id"),
+    item_id: row.get("item_id"),
+    code: row.get("code"),
+    kind: row.get("kind"),
+    note: row.get("note"),
+    created_at: row.get("created_at"),
+  }
+}
+
+pub async fn list_aliases(pool: &SqlitePool, item_id: &str) -> Result<Vec<ItemAliasRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT id, item_id, code, kind, note, created_at FROM item_alias WHERE item_id = ? ORDER BY kind, code",
+  )
+  .bind(item_id)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.iter().map(map_alias).collect())
+}
+
+/// 全部别名（导出物品时按物品分组）
+pub async fn list_all_aliases(pool: &SqlitePool) -> Result<Vec<ItemAliasRow>, AppError> {
+  let rows = sqlx::query("SELECT id, item_id, code, kind, note, created_at FROM item_alias ORDER BY item_id, kind, code")
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.iter().map(map_alias).collect())
+}
+
+pub async fn get_alias(pool: &SqlitePool, id: &str) -> Result<Option<ItemAliasRow>, AppError> {
+  let row = sqlx::query("SELECT id, item_id, code, kind, note, created_at FROM item_alias WHERE id = ?")
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_alias))
+}
+
+/// 别名所属的物品 ID
+pub async fn get_item_id_by_alias(pool: &SqlitePool, code: &str) -> Result<Option<String>, AppError> {
+  let row: Option<(String,)> = sqlx::query_as("SELECT item_id FROM item_alias WHERE code = ?")
+    .bind(code)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.map(|(item_id,)| item_id))
+}
+
+pub async fn insert_alias(pool: &SqlitePool, alias: &ItemAliasRow) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO item_alias (id, item_id, code, kind, note, created_at) VALUES (?, ?, ?, ?, ?, ?)")
+    .bind(&alias.id)
+    .bind(&alias.item_id)
+    .bind(&alias.code)
+    .bind(&alias.kind)
+    .bind(&alias.note)
+    .bind(alias.created_at)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+pub async fn update_alias(
+  pool: &SqlitePool,
+  id: &str,
+  code: &str,
+  kind: &str,
+  note: Option<String>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query("UPDATE item_alias SET code = ?, kind = ?, note = ? WHERE id = ?")
+    .bind(code)
+    .bind(kind)
+    .bind(note)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn delete_alias(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM item_alias WHERE id = ?")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
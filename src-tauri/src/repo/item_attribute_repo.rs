@@ -0,0 +1,188 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct ItemAttributeDefRow {
+  pub id: String,
+  pub name: String,
+  // text / number / date
+  pub data_type: String,
+  pub required: bool,
+  pub sort_no: i64,
+  // active / disabled
+  pub status: String,
+  pub created_at: i64,
+}
+
+/// 物品属性取值（物品 ID、属性 ID、值）
+#[derive(Debug)]
+pub struct ItemAttributeValueRow {
+  pub item_id: String,
+  pub attr_id: String,
+  pub value: String,
+}
+
+fn map_def(row: &sqlx::sqlite::SqliteRow) -> ItemAttributeDefRow {
+  ItemAttributeDefRow {
+    id: row.get("id"),
+    name: row.get("name"),
+    data_type: row.get("data_type"),
+    required: row.get::<i64, _>("required") != 0,
+    sort_no: row.get("sort_no"),
+    status: row.get("status"),
+    created_at: row.get("created_at"),
+  }
+}
+
+/// 属性定义列表，按排序号与名称排列；only_active 时仅返回启用的属性
+pub async fn list_defs(pool: &SqlitePool, only_active: bool) -> Result<Vec<ItemAttributeDefRow>, AppError> {
+  let sql = if only_active {
+    "SELECT id, name, data_type, required, sort_no, status, created_at FROM item_attribute_def \
+     WHERE status = 'active' ORDER BY sort_no, name"
+  } else {
+    "SELECT id, name, data_type, required, sort_no, status, created_at FROM item_attribute_def \
+     ORDER BY sort_no, name"
+  };
+  let rows = sqlx::query(sql).fetch_all(pool).await?;
+  Ok(rows.iter().map(map_def).collect())
+}
+
+pub async fn get_def(pool: &SqlitePool, id: &str) -> Result<Option<ItemAttributeDefRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, name, data_type, required, sort_no, status, created_at FROM item_attribute_def WHERE id = ?",
+  )
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.as_ref().map(map_def))
+}
+
+pub async fn get_def_id_by_name(pool: &SqlitePool, name: &str) -> Result<Option<String>, AppError> {
+  let row: Option<(String,)> = sqlx::query_as("SELECT id FROM item_attribute_def WHERE name = ?")
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.map(|(id,)| id))
+}
+
+pub async fn insert_def(pool: &SqlitePool, def: &ItemAttributeDefRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO item_attribute_def (id, name, data_type, required, sort_no, status, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&def.id)
+  .bind(&def.name)
+  .bind(&def.data_type)
+  .bind(if def.required { 1 } else { 0 })
+  .bind(def.sort_no)
+  .bind(&def.status)
+  .bind(def.created_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_def(pool: &SqlitePool, def: &ItemAttributeDefRow) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE item_attribute_def SET name = ?, data_type = ?, required = ?, sort_no = ?, status = ? WHERE id = ?",
+  )
+  .bind(&def.name)
+  .bind(&def.data_type)
+  .bind(if def.required { 1 } else { 0 })
+  .bind(def.sort_no)
+  .bind(&def.status)
+  .bind(&def.id)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 删除属性定义，已有取值随外键级联删除
+pub async fn delete_def(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM item_attribute_def WHERE id = ?")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+/// 属性的全部已有取值（修改类型前校验）
+pub async fn list_values_by_attr(pool: &SqlitePool, attr_id: &str) -> Result<Vec<String>, AppError> {
+  let rows: Vec<(String,)> = sqlx::query_as("SELECT value FROM item_attribute_value WHERE attr_id = ?")
+    .bind(attr_id)
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.into_iter().map(|(value,)| value).collect())
+}
+
+/// 指定物品的属性取值；item_ids 为 None 时返回全部物品的取值（导出用）
+pub async fn list_values(pool: &SqlitePool, item_ids: Option<&[String]>) -> Result<Vec<ItemAttributeValueRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("SELECT item_id, attr_id, value FROM item_attribute_value");
+  if let Some(item_ids) = item_ids {
+    if item_ids.is_empty() {
+      return Ok(Vec::new());
+    }
+    builder.push(" WHERE item_id IN (");
+    let mut separated = builder.separated(", ");
+    for item_id in item_ids {
+      separated.push_bind(item_id);
+    }
+    separated.push_unseparated(")");
+  }
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .iter()
+      .map(|row| ItemAttributeValueRow {
+        item_id: row.get("item_id"),
+        attr_id: row.get("attr_id"),
+        value: row.get("value"),
+      })
+      .collect(),
+  )
+}
+
+/// 在同一事务内写入物品的属性取值，值为空时删除该属性的取值
+pub async fn set_values(
+  pool: &SqlitePool,
+  item_id: &str,
+  values: &[(String, Option<String>)],
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  set_values_tx(&mut tx, item_id, values).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 在事务内写入物品属性取值；取值为 None 时删除
+pub async fn set_values_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  item_id: &str,
+  values: &[(String, Option<String>)],
+) -> Result<(), AppError> {
+  for (attr_id, value) in values {
+    match value {
+      Some(value) => {
+        sqlx::query(
+          "INSERT INTO item_attribute_value (item_id, attr_id, value) VALUES (?, ?, ?) \
+           ON CONFLICT(item_id, attr_id) DO UPDATE SET value = excluded.value",
+        )
+        .bind(item_id)
+        .bind(attr_id)
+        .bind(value)
+        .execute(&mut **tx)
+        .await?;
+      }
+      None => {
+        sqlx::query("DELETE FROM item_attribute_value WHERE item_id = ? AND attr_id = ?")
+          .bind(item_id)
+          .bind(attr_id)
+          .execute(&mut **tx)
+          .await?;
+      }
+    }
+  }
+  Ok(())
+}
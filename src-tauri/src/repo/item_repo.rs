@@ -1,6 +1,22 @@
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::domain::sort;
+
+/// 物品列表可排序字段（sort_by → SQL 表达式）
+const ITEM_SORT_COLUMNS: &[(&str, &str)] = &[
+  ("item_code", "item.item_code"),
+  ("name", "item.name"),
+  ("model", "item.model"),
+  ("spec", "item.spec"),
+  ("uom", "item.uom"),
+  ("unit_cost", "item.unit_cost"),
+  ("stock_qty", "stock_qty"),
+  ("status", "item.status"),
+  ("created_at", "item.created_at"),
+];
 
 #[derive(Debug, serde::Serialize)]
 pub struct ItemRow {
@@ -10,7 +26,14 @@ pub struct ItemRow {
   pub model: Option<String>,
   pub spec: Option<String>,
   pub uom: Option<String>,
-  pub stock_qty: i64,
+  // 单位成本，未设置时为空
+  pub unit_cost: Option<f64>,
+  // 成本币种（如 CNY），未设置时为空
+  pub currency: Option<String>,
+  // 数量小数位数（0-3）
+  pub qty_precision: i64,
+  // 库存合计（显示数量）
+  pub stock_qty: f64,
   pub status: String,
   pub remark: Option<String>,
   pub created_at: i64,
@@ -21,35 +44,45 @@ pub async fn list_items(
   keyword: Option<String>,
   page_index: i64,
   page_size: i64,
+  sort_by: Option<&str>,
+  sort_dir: Option<&str>,
 ) -> Result<Vec<ItemRow>, AppError> {
   let offset = (page_index - 1) * page_size;
+  let order_by =
+    sort::order_by_clause(ITEM_SORT_COLUMNS, sort_by, sort_dir, "item.created_at DESC")?;
   let rows = if let Some(keyword) = keyword {
     let like = format!("%{}%", keyword);
-    sqlx::query(
-      "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, \
-       COALESCE(SUM(stock.qty), 0) AS stock_qty, item.status, item.remark, item.created_at \
+    sqlx::query(&format!(
+      "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, item.unit_cost, item.currency, item.qty_precision, \
+       COALESCE(SUM(stock.qty), 0) / {} AS stock_qty, item.status, item.remark, item.created_at \
        FROM item \
        LEFT JOIN stock ON stock.item_id = item.id \
        WHERE item.item_code LIKE ? OR item.name LIKE ? OR item.model LIKE ? \
-       GROUP BY item.id \
-       ORDER BY item.created_at DESC LIMIT ? OFFSET ?",
-    )
+       OR item.id IN (SELECT item_id FROM item_alias WHERE code = ? \
+         UNION SELECT item_id FROM item_code_history WHERE old_code = ?) \
+       GROUP BY item.id{} LIMIT ? OFFSET ?",
+      quantity::scale_sql("item.qty_precision"),
+      order_by
+    ))
     .bind(&like)
     .bind(&like)
     .bind(&like)
+    .bind(&keyword)
+    .bind(&keyword)
     .bind(page_size)
     .bind(offset)
     .fetch_all(pool)
     .await?
   } else {
-    sqlx::query(
-      "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, \
-       COALESCE(SUM(stock.qty), 0) AS stock_qty, item.status, item.remark, item.created_at \
+    sqlx::query(&format!(
+      "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, item.unit_cost, item.currency, item.qty_precision, \
+       COALESCE(SUM(stock.qty), 0) / {} AS stock_qty, item.status, item.remark, item.created_at \
        FROM item \
        LEFT JOIN stock ON stock.item_id = item.id \
-       GROUP BY item.id \
-       ORDER BY item.created_at DESC LIMIT ? OFFSET ?",
-    )
+       GROUP BY item.id{} LIMIT ? OFFSET ?",
+      quantity::scale_sql("item.qty_precision"),
+      order_by
+    ))
     .bind(page_size)
     .bind(offset)
     .fetch_all(pool)
@@ -65,6 +98,9 @@ pub async fn list_items(
       model: row.get("model"),
       spec: row.get("spec"),
       uom: row.get("uom"),
+      unit_cost: row.get("unit_cost"),
+      currency: row.get("currency"),
+      qty_precision: row.get("qty_precision"),
       stock_qty: row.get("stock_qty"),
       status: row.get("status"),
       remark: row.get("remark"),
@@ -76,14 +112,15 @@ pub async fn list_items(
 }
 
 pub async fn list_items_all(pool: &SqlitePool) -> Result<Vec<ItemRow>, AppError> {
-  let rows = sqlx::query(
-    "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, \
-     COALESCE(SUM(stock.qty), 0) AS stock_qty, item.status, item.remark, item.created_at \
+  let rows = sqlx::query(&format!(
+    "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, item.unit_cost, item.currency, item.qty_precision, \
+     COALESCE(SUM(stock.qty), 0) / {} AS stock_qty, item.status, item.remark, item.created_at \
      FROM item \
      LEFT JOIN stock ON stock.item_id = item.id \
      GROUP BY item.id \
      ORDER BY item.created_at DESC",
-  )
+    quantity::scale_sql("item.qty_precision")
+  ))
   .fetch_all(pool)
   .await?;
 
@@ -96,6 +133,9 @@ pub async fn list_items_all(pool: &SqlitePool) -> Result<Vec<ItemRow>, AppError>
       model: row.get("model"),
       spec: row.get("spec"),
       uom: row.get("uom"),
+      unit_cost: row.get("unit_cost"),
+      currency: row.get("currency"),
+      qty_precision: row.get("qty_precision"),
       stock_qty: row.get("stock_qty"),
       status: row.get("status"),
       remark: row.get("remark"),
@@ -113,11 +153,15 @@ pub async fn count_items(
   if let Some(keyword) = keyword {
     let like = format!("%{}%", keyword);
     let (count,): (i64,) = sqlx::query_as(
-      "SELECT COUNT(1) FROM item WHERE item_code LIKE ? OR name LIKE ? OR model LIKE ?",
+      "SELECT COUNT(1) FROM item WHERE item_code LIKE ? OR name LIKE ? OR model LIKE ? \
+       OR id IN (SELECT item_id FROM item_alias WHERE code = ? \
+         UNION SELECT item_id FROM item_code_history WHERE old_code = ?)",
     )
     .bind(&like)
     .bind(&like)
     .bind(&like)
+    .bind(&keyword)
+    .bind(&keyword)
     .fetch_one(pool)
     .await?;
     Ok(count)
@@ -130,14 +174,15 @@ pub async fn count_items(
 }
 
 pub async fn get_item_by_id(pool: &SqlitePool, id: &str) -> Result<Option<ItemRow>, AppError> {
-  let row = sqlx::query(
-    "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, \
-     COALESCE(SUM(stock.qty), 0) AS stock_qty, item.status, item.remark, item.created_at \
+  let row = sqlx::query(&format!(
+    "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, item.unit_cost, item.currency, item.qty_precision, \
+     COALESCE(SUM(stock.qty), 0) / {} AS stock_qty, item.status, item.remark, item.created_at \
      FROM item \
      LEFT JOIN stock ON stock.item_id = item.id \
      WHERE item.id = ? \
      GROUP BY item.id",
-  )
+    quantity::scale_sql("item.qty_precision")
+  ))
   .bind(id)
   .fetch_optional(pool)
   .await?;
@@ -149,6 +194,9 @@ pub async fn get_item_by_id(pool: &SqlitePool, id: &str) -> Result<Option<ItemRo
     model: row.get("model"),
     spec: row.get("spec"),
     uom: row.get("uom"),
+    unit_cost: row.get("unit_cost"),
+    currency: row.get("currency"),
+    qty_precision: row.get("qty_precision"),
     stock_qty: row.get("stock_qty"),
     status: row.get("status"),
     remark: row.get("remark"),
@@ -156,6 +204,18 @@ pub async fn get_item_by_id(pool: &SqlitePool, id: &str) -> Result<Option<ItemRo
   }))
 }
 
+/// 事务内查询物品状态，物品不存在时返回 None
+pub async fn get_item_status_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+) -> Result<Option<String>, AppError> {
+  let status: Option<(String,)> = sqlx::query_as("SELECT status FROM item WHERE id = ?")
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+  Ok(status.map(|(status,)| status))
+}
+
 pub async fn count_by_item_code(pool: &SqlitePool, item_code: &str) -> Result<i64, AppError> {
   let (count,): (i64,) = sqlx::query_as("SELECT COUNT(1) FROM item WHERE item_code = ?")
     .bind(item_code)
@@ -164,18 +224,22 @@ pub async fn count_by_item_code(pool: &SqlitePool, item_code: &str) -> Result<i6
   Ok(count)
 }
 
+/// 按编码查询物品，当前编码不存在时依次按别名、变更前的旧编码查找（旧编码取最近一次变更）
 pub async fn get_item_by_code(
   pool: &SqlitePool,
   item_code: &str,
 ) -> Result<Option<ItemRow>, AppError> {
-  let row = sqlx::query(
-    "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, \
-     COALESCE(SUM(stock.qty), 0) AS stock_qty, item.status, item.remark, item.created_at \
+  let row = sqlx::query(&format!(
+    "SELECT item.id, item.item_code, item.name, item.model, item.spec, item.uom, item.unit_cost, item.currency, item.qty_precision, \
+     COALESCE(SUM(stock.qty), 0) / {} AS stock_qty, item.status, item.remark, item.created_at \
      FROM item \
      LEFT JOIN stock ON stock.item_id = item.id \
-     WHERE item.item_code = ? \
+     WHERE item.id = COALESCE((SELECT id FROM item WHERE item_code = ?1), \
+       (SELECT item_id FROM item_alias WHERE code = ?1), \
+       (SELECT item_id FROM item_code_history WHERE old_code = ?1 ORDER BY changed_at DESC LIMIT 1)) \
      GROUP BY item.id",
-  )
+    quantity::scale_sql("item.qty_precision")
+  ))
   .bind(item_code)
   .fetch_optional(pool)
   .await?;
@@ -187,6 +251,9 @@ pub async fn get_item_by_code(
     model: row.get("model"),
     spec: row.get("spec"),
     uom: row.get("uom"),
+    unit_cost: row.get("unit_cost"),
+    currency: row.get("currency"),
+    qty_precision: row.get("qty_precision"),
     stock_qty: row.get("stock_qty"),
     status: row.get("status"),
     remark: row.get("remark"),
@@ -202,13 +269,16 @@ pub async fn insert_item(
   model: Option<String>,
   spec: Option<String>,
   uom: Option<String>,
+  unit_cost: Option<f64>,
+  currency: Option<String>,
+  qty_precision: i64,
   status: &str,
   remark: Option<String>,
   created_at: i64,
 ) -> Result<(), AppError> {
   sqlx::query(
-    "INSERT INTO item (id, item_code, name, model, spec, uom, status, remark, created_at) \
-     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    "INSERT INTO item (id, item_code, name, model, spec, uom, unit_cost, currency, qty_precision, status, remark, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
   )
   .bind(id)
   .bind(item_code)
@@ -216,6 +286,9 @@ pub async fn insert_item(
   .bind(model)
   .bind(spec)
   .bind(uom)
+  .bind(unit_cost)
+  .bind(currency)
+  .bind(qty_precision)
   .bind(status)
   .bind(remark)
   .bind(created_at)
@@ -232,37 +305,455 @@ pub async fn update_item(
   model: Option<String>,
   spec: Option<String>,
   uom: Option<String>,
+  unit_cost: Option<f64>,
+  currency: Option<String>,
+  remark: Option<String>,
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  update_item_tx(&mut tx, id, name, model, spec, uom, unit_cost, currency, remark).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 在事务内更新物品基本信息
+pub async fn update_item_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  name: &str,
+  model: Option<String>,
+  spec: Option<String>,
+  uom: Option<String>,
+  unit_cost: Option<f64>,
+  currency: Option<String>,
   remark: Option<String>,
 ) -> Result<(), AppError> {
   let result = sqlx::query(
-    "UPDATE item SET name = ?, model = ?, spec = ?, uom = ?, remark = ? WHERE id = ?",
+    "UPDATE item SET name = ?, model = ?, spec = ?, uom = ?, unit_cost = ?, currency = ?, remark = ? WHERE id = ?",
   )
   .bind(name)
   .bind(model)
   .bind(spec)
   .bind(uom)
+  .bind(unit_cost)
+  .bind(currency)
   .bind(remark)
   .bind(id)
-  .execute(pool)
+  .execute(&mut **tx)
   .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "物品不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound));
   }
 
   Ok(())
 }
 
 pub async fn set_item_status(pool: &SqlitePool, id: &str, status: &str) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  set_item_status_tx(&mut tx, id, status).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 在事务内设置物品状态
+pub async fn set_item_status_tx(tx: &mut Transaction<'_, Sqlite>, id: &str, status: &str) -> Result<(), AppError> {
   let result = sqlx::query("UPDATE item SET status = ? WHERE id = ?")
     .bind(status)
     .bind(id)
-    .execute(pool)
+    .execute(&mut **tx)
     .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "物品不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound));
+  }
+
+  Ok(())
+}
+
+/// 批量编辑补丁：None 表示保持不变，文本字段为空串时清空
+#[derive(Debug, Default)]
+pub struct ItemPatch {
+  pub model: Option<String>,
+  pub spec: Option<String>,
+  pub uom: Option<String>,
+  pub unit_cost: Option<f64>,
+  pub currency: Option<String>,
+  pub remark: Option<String>,
+  pub status: Option<String>,
+}
+
+/// 在同一事务内对多个物品应用补丁，任一物品不存在时整体回滚；返回更新条数
+pub async fn bulk_update_items(
+  pool: &SqlitePool,
+  ids: &[String],
+  patch: &ItemPatch,
+) -> Result<u64, AppError> {
+  let mut tx = pool.begin().await?;
+  let mut updated = 0;
+  for id in ids {
+    let result = sqlx::query(
+      "UPDATE item SET \
+       model = CASE WHEN ? IS NULL THEN model ELSE NULLIF(?, '') END, \
+       spec = CASE WHEN ? IS NULL THEN spec ELSE NULLIF(?, '') END, \
+       uom = CASE WHEN ? IS NULL THEN uom ELSE NULLIF(?, '') END, \
+       unit_cost = COALESCE(?, unit_cost), \
+       currency = CASE WHEN ? IS NULL THEN currency ELSE NULLIF(?, '') END, \
+       remark = CASE WHEN ? IS NULL THEN remark ELSE NULLIF(?, '') END, \
+       status = COALESCE(?, status) \
+       WHERE id = ?",
+    )
+    .bind(&patch.model)
+    .bind(&patch.model)
+    .bind(&patch.spec)
+    .bind(&patch.spec)
+    .bind(&patch.uom)
+    .bind(&patch.uom)
+    .bind(patch.unit_cost)
+    .bind(&patch.currency)
+    .bind(&patch.currency)
+    .bind(&patch.remark)
+    .bind(&patch.remark)
+    .bind(&patch.status)
+    .bind(id)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+      return Err(AppError::keyed_with(
+        ErrorCode::NotFound,
+        MessageKey::ItemNotFound,
+        &[("id", id.clone())],
+      ));
+    }
+    updated += result.rows_affected();
+  }
+  tx.commit().await?;
+  Ok(updated)
+}
+
+/// 以物品存储值保存数量的表：(表名, 数量列, 按物品筛选的条件，?1 为物品 ID)；
+/// 新增此类数量列时须加入此处，修改数量精度时一并换算
+const STORED_QTY_COLUMNS: &[(&str, &[&str], &str)] = &[
+  ("stock", &["qty"], "item_id = ?1"),
+  ("txn", &["qty", "actual_qty"], "item_id = ?1"),
+  ("loan", &["qty"], "item_id = ?1"),
+  ("stock_snapshot", &["qty"], "item_id = ?1"),
+  ("outbound_pick", &["planned_qty", "actual_qty"], "item_id = ?1"),
+  ("pick_discrepancy", &["planned_qty", "actual_qty", "short_qty"], "item_id = ?1"),
+  ("asn_line", &["expected_qty", "received_qty"], "item_id = ?1"),
+  ("asn_receipt", &["qty"], "asn_line_id IN (SELECT id FROM asn_line WHERE item_id = ?1)"),
+  ("stock_threshold", &["min_qty", "max_qty"], "item_id = ?1"),
+  ("stock_hold", &["qty"], "item_id = ?1"),
+];
+
+/// 修改物品数量精度，并按新旧精度换算该物品的库存、流水、借用、日结快照等存储数量
+///
+/// 降低精度时要求已有数量都能整除，否则返回校验错误，避免截断。
+pub async fn change_qty_precision(
+  pool: &SqlitePool,
+  id: &str,
+  from_precision: i64,
+  to_precision: i64,
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  change_qty_precision_tx(&mut tx, id, from_precision, to_precision).await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 在事务内修改物品数量精度（规则同 change_qty_precision）
+pub async fn change_qty_precision_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  from_precision: i64,
+  to_precision: i64,
+) -> Result<(), AppError> {
+  if to_precision != from_precision {
+    let factor = quantity::scale((to_precision - from_precision).abs());
+    if to_precision < from_precision {
+      let mut indivisible = 0;
+      for (table, columns, filter) in STORED_QTY_COLUMNS {
+        let condition = columns
+          .iter()
+          .map(|column| format!("COALESCE({}, 0) % ?2 <> 0", column))
+          .collect::<Vec<_>>()
+          .join(" OR ");
+        let (count,): (i64,) = sqlx::query_as(&format!(
+          "SELECT COUNT(1) FROM {} WHERE {} AND ({})",
+          table, filter, condition
+        ))
+        .bind(id)
+        .bind(factor)
+        .fetch_one(&mut **tx)
+        .await?;
+        indivisible += count;
+      }
+      if indivisible > 0 {
+        return Err(AppError::new(
+          ErrorCode::ValidationError,
+          format!("已有库存或流水数量超出 {} 位小数，无法降低精度", to_precision),
+        ));
+      }
+    }
+    let op = if to_precision > from_precision { "*" } else { "/" };
+    for (table, columns, filter) in STORED_QTY_COLUMNS {
+      let assignments = columns
+        .iter()
+        .map(|column| format!("{0} = {0} {1} ?2", column, op))
+        .collect::<Vec<_>>()
+        .join(", ");
+      sqlx::query(&format!("UPDATE {} SET {} WHERE {}", table, assignments, filter))
+        .bind(id)
+        .bind(factor)
+        .execute(&mut **tx)
+        .await?;
+    }
   }
+  sqlx::query("UPDATE item SET qty_precision = ? WHERE id = ?")
+    .bind(to_precision)
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+/// 物品数量精度（物品不存在时返回 NOT_FOUND）
+pub async fn get_qty_precision(pool: &SqlitePool, id: &str) -> Result<i64, AppError> {
+  let row: Option<(i64,)> = sqlx::query_as("SELECT qty_precision FROM item WHERE id = ?")
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  row
+    .map(|(precision,)| precision)
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))
+}
+
+/// 物品被引用的数量：库存行、流水、借用与图片（合并前预览与审计）
+#[derive(Debug, Default, serde::Serialize)]
+pub struct ItemReferenceCounts {
+  pub stock_rows: i64,
+  pub txns: i64,
+  pub loans: i64,
+  pub photos: i64,
+}
+
+pub async fn count_item_references(pool: &SqlitePool, id: &str) -> Result<ItemReferenceCounts, AppError> {
+  let (stock_rows, txns, loans, photos): (i64, i64, i64, i64) = sqlx::query_as(
+    "SELECT (SELECT COUNT(1) FROM stock WHERE item_id = ?1), \
+     (SELECT COUNT(1) FROM txn WHERE item_id = ?1), \
+     (SELECT COUNT(1) FROM loan WHERE item_id = ?1), \
+     (SELECT COUNT(1) FROM media_attachment WHERE type = 'item' AND data_id = ?1)",
+  )
+  .bind(id)
+  .fetch_one(pool)
+  .await?;
+  Ok(ItemReferenceCounts {
+    stock_rows,
+    txns,
+    loans,
+    photos,
+  })
+}
+
+/// 物品已合并到的目标物品 ID，未合并时为空
+pub async fn get_merged_into_id(pool: &SqlitePool, id: &str) -> Result<Option<String>, AppError> {
+  let row: Option<(Option<String>,)> = sqlx::query_as("SELECT merged_into_id FROM item WHERE id = ?")
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.and_then(|(merged_into_id,)| merged_into_id))
+}
+
+/// 在事务内将源物品合并到目标物品：同库位库存相加，流水、借用、图片、日结快照与盘点任务改挂目标物品，
+/// 源物品停用并记录合并去向（两者数量精度需一致，由调用方校验）
+pub async fn merge_items(
+  pool: &SqlitePool,
+  source_id: &str,
+  target_id: &str,
+  merged_at: i64,
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  sqlx::query(
+    "UPDATE stock SET qty = qty + (SELECT source.qty FROM stock AS source \
+     WHERE source.item_id = ?2 AND source.slot_id = stock.slot_id), updated_at = ?3 \
+     WHERE item_id = ?1 AND slot_id IN (SELECT slot_id FROM stock WHERE item_id = ?2)",
+  )
+  .bind(target_id)
+  .bind(source_id)
+  .bind(merged_at)
+  .execute(&mut *tx)
+  .await?;
+  sqlx::query("DELETE FROM stock WHERE item_id = ?2 AND slot_id IN (SELECT slot_id FROM stock WHERE item_id = ?1)")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  sqlx::query("UPDATE stock SET item_id = ?, updated_at = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(merged_at)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  sqlx::query("UPDATE txn SET item_id = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  sqlx::query("UPDATE loan SET item_id = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  // 源物品图片排在目标物品已有图片之后
+  sqlx::query(
+    "UPDATE media_attachment SET data_id = ?1, \
+     sort_no = sort_no + (SELECT COALESCE(MAX(sort_no), -1) + 1 FROM media_attachment WHERE type = 'item' AND data_id = ?1) \
+     WHERE type = 'item' AND data_id = ?2",
+  )
+  .bind(target_id)
+  .bind(source_id)
+  .execute(&mut *tx)
+  .await?;
+  sqlx::query(
+    "INSERT INTO stock_snapshot (snapshot_date, item_id, slot_id, qty) \
+     SELECT snapshot_date, ?1, slot_id, qty FROM stock_snapshot WHERE item_id = ?2 \
+     ON CONFLICT(snapshot_date, item_id, slot_id) DO UPDATE SET qty = stock_snapshot.qty + excluded.qty",
+  )
+  .bind(target_id)
+  .bind(source_id)
+  .execute(&mut *tx)
+  .await?;
+  sqlx::query("DELETE FROM stock_snapshot WHERE item_id = ?")
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  // 冻结随库存转移到目标物品，同库位的冻结数量按物品 + 库位合计，合并后不会变为可用
+  sqlx::query("UPDATE stock_hold SET item_id = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  // 库存阈值：目标物品在该仓库已有阈值时以目标为准，否则沿用源物品的阈值
+  sqlx::query(
+    "INSERT OR IGNORE INTO stock_threshold (item_id, warehouse_id, min_qty, max_qty, updated_by, updated_at) \
+     SELECT ?1, warehouse_id, min_qty, max_qty, updated_by, ?3 FROM stock_threshold WHERE item_id = ?2",
+  )
+  .bind(target_id)
+  .bind(source_id)
+  .bind(merged_at)
+  .execute(&mut *tx)
+  .await?;
+  sqlx::query("DELETE FROM stock_threshold WHERE item_id = ?")
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  // 别名与旧编码随物品转移，扫码仍能解析到目标物品
+  sqlx::query("UPDATE item_alias SET item_id = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  sqlx::query("UPDATE item_code_history SET item_id = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  // 未完成的盘点任务按目标物品重新生成，已完成的保留记录
+  sqlx::query("DELETE FROM cycle_count_task WHERE item_id = ? AND status = 'pending'")
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  sqlx::query("UPDATE cycle_count_task SET item_id = ? WHERE item_id = ?")
+    .bind(target_id)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  sqlx::query("UPDATE item SET status = 'inactive', merged_into_id = ?, merged_at = ? WHERE id = ?")
+    .bind(target_id)
+    .bind(merged_at)
+    .bind(source_id)
+    .execute(&mut *tx)
+    .await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 物品编码变更记录
+#[derive(Debug, serde::Serialize)]
+pub struct ItemCodeHistoryRow {
+  pub id: String,
+  pub old_code: String,
+  pub new_code: String,
+  pub changed_at: i64,
+  pub changed_by_name: Option<String>,
+}
+
+/// 旧编码最近一次所属的物品 ID
+pub async fn get_item_id_by_old_code(pool: &SqlitePool, code: &str) -> Result<Option<String>, AppError> {
+  let row: Option<(String,)> = sqlx::query_as(
+    "SELECT item_id FROM item_code_history WHERE old_code = ? ORDER BY changed_at DESC LIMIT 1",
+  )
+  .bind(code)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.map(|(item_id,)| item_id))
+}
 
+/// 在事务内修改物品编码并记录变更历史
+pub async fn change_item_code(
+  pool: &SqlitePool,
+  id: &str,
+  old_code: &str,
+  new_code: &str,
+  changed_at: i64,
+  changed_by: &str,
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+  let result = sqlx::query("UPDATE item SET item_code = ? WHERE id = ? AND item_code = ?")
+    .bind(new_code)
+    .bind(id)
+    .bind(old_code)
+    .execute(&mut *tx)
+    .await?;
+  if result.rows_affected() == 0 {
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound));
+  }
+  sqlx::query(
+    "INSERT INTO item_code_history (id, item_id, old_code, new_code, changed_at, changed_by) \
+     VALUES (?, ?, ?, ?, ?, ?)",
+  )
+  .bind(uuid::Uuid::new_v4().to_string())
+  .bind(id)
+  .bind(old_code)
+  .bind(new_code)
+  .bind(changed_at)
+  .bind(changed_by)
+  .execute(&mut *tx)
+  .await?;
+  tx.commit().await?;
   Ok(())
 }
+
+pub async fn list_item_code_history(pool: &SqlitePool, item_id: &str) -> Result<Vec<ItemCodeHistoryRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT history.id, history.old_code, history.new_code, history.changed_at, op.display_name AS changed_by_name \
+     FROM item_code_history AS history \
+     LEFT JOIN \"operator\" AS op ON history.changed_by = op.id \
+     WHERE history.item_id = ? \
+     ORDER BY history.changed_at DESC",
+  )
+  .bind(item_id)
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| ItemCodeHistoryRow {
+        id: row.get("id"),
+        old_code: row.get("old_code"),
+        new_code: row.get("new_code"),
+        changed_at: row.get("changed_at"),
+        changed_by_name: row.get("changed_by_name"),
+      })
+      .collect(),
+  )
+}
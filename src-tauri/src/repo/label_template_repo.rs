@@ -0,0 +1,138 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::{AppError, ErrorCode};
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LabelTemplateRow {
+  pub id: String,
+  pub name: String,
+  // 标签对象：slot（库位）/ item（物品）
+  pub target: String,
+  pub width_mm: f64,
+  pub height_mm: f64,
+  // 打印字段（按顺序逐行打印）
+  pub fields: Vec<String>,
+  // 条码类型：code128 / none
+  pub barcode_type: String,
+  pub font_size: f64,
+  pub is_default: bool,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, name, target, width_mm, height_mm, fields, barcode_type, font_size, \
+   is_default, created_at, updated_at FROM label_template";
+
+fn map_row(row: &sqlx::sqlite::SqliteRow) -> LabelTemplateRow {
+  let fields: String = row.get("fields");
+  LabelTemplateRow {
+    id: row.get("id"),
+    name: row.get("name"),
+    target: row.get("target"),
+    width_mm: row.get("width_mm"),
+    height_mm: row.get("height_mm"),
+    fields: serde_json::from_str(&fields).unwrap_or_default(),
+    barcode_type: row.get("barcode_type"),
+    font_size: row.get("font_size"),
+    is_default: row.get::<i64, _>("is_default") == 1,
+    created_at: row.get("created_at"),
+    updated_at: row.get("updated_at"),
+  }
+}
+
+pub async fn list_label_templates(
+  pool: &SqlitePool,
+  target: Option<&str>,
+) -> Result<Vec<LabelTemplateRow>, AppError> {
+  let rows = match target {
+    Some(target) => {
+      sqlx::query(&format!("{} WHERE target = ? ORDER BY target, is_default DESC, name", SELECT_COLUMNS))
+        .bind(target)
+        .fetch_all(pool)
+        .await?
+    }
+    None => {
+      sqlx::query(&format!("{} ORDER BY target, is_default DESC, name", SELECT_COLUMNS))
+        .fetch_all(pool)
+        .await?
+    }
+  };
+  Ok(rows.iter().map(map_row).collect())
+}
+
+pub async fn get_label_template(pool: &SqlitePool, id: &str) -> Result<Option<LabelTemplateRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE id = ?", SELECT_COLUMNS))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_row))
+}
+
+/// 该标签对象的默认模板；未设置默认时取名称排序的第一个
+pub async fn get_default_template(
+  pool: &SqlitePool,
+  target: &str,
+) -> Result<Option<LabelTemplateRow>, AppError> {
+  let row = sqlx::query(&format!(
+    "{} WHERE target = ? ORDER BY is_default DESC, name LIMIT 1",
+    SELECT_COLUMNS
+  ))
+  .bind(target)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.as_ref().map(map_row))
+}
+
+pub async fn name_exists(pool: &SqlitePool, name: &str, exclude_id: Option<&str>) -> Result<bool, AppError> {
+  let count: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM label_template WHERE name = ? AND id <> ?")
+    .bind(name)
+    .bind(exclude_id.unwrap_or(""))
+    .fetch_one(pool)
+    .await?;
+  Ok(count > 0)
+}
+
+/// 新增或更新模板；设为默认时同一事务内取消同类模板的默认标记
+pub async fn save_label_template(pool: &SqlitePool, template: &LabelTemplateRow) -> Result<(), AppError> {
+  let fields = serde_json::to_string(&template.fields)
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "标签字段格式不正确"))?;
+  let mut tx = pool.begin().await?;
+  if template.is_default {
+    sqlx::query("UPDATE label_template SET is_default = 0 WHERE target = ? AND id <> ?")
+      .bind(&template.target)
+      .bind(&template.id)
+      .execute(&mut *tx)
+      .await?;
+  }
+  sqlx::query(
+    "INSERT INTO label_template \
+     (id, name, target, width_mm, height_mm, fields, barcode_type, font_size, is_default, created_at, updated_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+     ON CONFLICT(id) DO UPDATE SET name = excluded.name, width_mm = excluded.width_mm, \
+     height_mm = excluded.height_mm, fields = excluded.fields, barcode_type = excluded.barcode_type, \
+     font_size = excluded.font_size, is_default = excluded.is_default, updated_at = excluded.updated_at",
+  )
+  .bind(&template.id)
+  .bind(&template.name)
+  .bind(&template.target)
+  .bind(template.width_mm)
+  .bind(template.height_mm)
+  .bind(fields)
+  .bind(&template.barcode_type)
+  .bind(template.font_size)
+  .bind(if template.is_default { 1 } else { 0 })
+  .bind(template.created_at)
+  .bind(template.updated_at)
+  .execute(&mut *tx)
+  .await?;
+  tx.commit().await?;
+  Ok(())
+}
+
+pub async fn delete_label_template(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM label_template WHERE id = ?")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
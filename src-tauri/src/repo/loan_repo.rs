@@ -0,0 +1,326 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::quantity;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LoanRow {
+  pub id: String,
+  pub loan_no: String,
+  pub item_id: String,
+  pub slot_id: String,
+  pub qty: i64,
+  pub borrower: String,
+  pub due_at: Option<i64>,
+  pub status: String,
+  pub loaned_at: i64,
+  pub operator_id: String,
+  pub out_txn_id: String,
+  pub returned_at: Option<i64>,
+  pub return_slot_id: Option<String>,
+  pub return_operator_id: Option<String>,
+  pub return_txn_id: Option<String>,
+  pub note: Option<String>,
+  pub created_at: i64,
+}
+
+/// 借出中记录（含物品、库位与流水号）
+#[derive(Debug, serde::Serialize)]
+pub struct OpenLoanRow {
+  pub id: String,
+  pub loan_no: String,
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub warehouse_id: Option<String>,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+  pub borrower: String,
+  pub due_at: Option<i64>,
+  pub loaned_at: i64,
+  pub operator_id: String,
+  pub operator_name: String,
+  pub out_txn_no: String,
+  pub note: Option<String>,
+  // 已过应还时间
+  pub overdue: bool,
+}
+
+/// 按物品汇总的借出数量
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ItemOnLoanRow {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub loan_count: i64,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+  pub overdue_qty: f64,
+}
+
+pub async fn insert_loan_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  row: &LoanRow,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO loan (id, loan_no, item_id, slot_id, qty, borrower, due_at, status, loaned_at, operator_id, out_txn_id, note, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.loan_no)
+  .bind(&row.item_id)
+  .bind(&row.slot_id)
+  .bind(row.qty)
+  .bind(&row.borrower)
+  .bind(row.due_at)
+  .bind(&row.status)
+  .bind(row.loaned_at)
+  .bind(&row.operator_id)
+  .bind(&row.out_txn_id)
+  .bind(&row.note)
+  .bind(row.created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_loan_by_id(pool: &SqlitePool, id: &str) -> Result<Option<LoanRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, loan_no, item_id, slot_id, qty, borrower, due_at, status, loaned_at, operator_id, out_txn_id, \
+     returned_at, return_slot_id, return_operator_id, return_txn_id, note, created_at \
+     FROM loan WHERE id = ?",
+  )
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+
+  Ok(row.map(|row| LoanRow {
+    id: row.get("id"),
+    loan_no: row.get("loan_no"),
+    item_id: row.get("item_id"),
+    slot_id: row.get("slot_id"),
+    qty: row.get("qty"),
+    borrower: row.get("borrower"),
+    due_at: row.get("due_at"),
+    status: row.get("status"),
+    loaned_at: row.get("loaned_at"),
+    operator_id: row.get("operator_id"),
+    out_txn_id: row.get("out_txn_id"),
+    returned_at: row.get("returned_at"),
+    return_slot_id: row.get("return_slot_id"),
+    return_operator_id: row.get("return_operator_id"),
+    return_txn_id: row.get("return_txn_id"),
+    note: row.get("note"),
+    created_at: row.get("created_at"),
+  }))
+}
+
+/// 标记归还；仅借出中的记录可归还
+pub async fn mark_returned_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  returned_at: i64,
+  return_slot_id: &str,
+  return_operator_id: &str,
+  return_txn_id: &str,
+) -> Result<(), AppError> {
+  let result = sqlx::query(
+    "UPDATE loan SET status = 'returned', returned_at = ?, return_slot_id = ?, return_operator_id = ?, return_txn_id = ? \
+     WHERE id = ? AND status = 'open'",
+  )
+  .bind(returned_at)
+  .bind(return_slot_id)
+  .bind(return_operator_id)
+  .bind(return_txn_id)
+  .bind(id)
+  .execute(&mut **tx)
+  .await?;
+  if result.rows_affected() == 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "借用记录已归还"));
+  }
+  Ok(())
+}
+
+/// 流水是否由借出/归还生成
+pub async fn is_loan_txn(pool: &SqlitePool, txn_id: &str) -> Result<bool, AppError> {
+  let (count,): (i64,) =
+    sqlx::query_as("SELECT COUNT(1) FROM loan WHERE out_txn_id = ? OR return_txn_id = ?")
+      .bind(txn_id)
+      .bind(txn_id)
+      .fetch_one(pool)
+      .await?;
+  Ok(count > 0)
+}
+
+fn push_open_loan_filters(
+  builder: &mut QueryBuilder<Sqlite>,
+  now: i64,
+  overdue_only: bool,
+  keyword: Option<String>,
+  item_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) {
+  builder.push(" WHERE loan.status = 'open'");
+  if overdue_only {
+    builder
+      .push(" AND loan.due_at IS NOT NULL AND loan.due_at < ")
+      .push_bind(now);
+  }
+  if let Some(keyword) = keyword {
+    let like = format!("%{}%", keyword);
+    builder
+      .push(" AND (loan.loan_no LIKE ")
+      .push_bind(like.clone())
+      .push(" OR loan.borrower LIKE ")
+      .push_bind(like.clone())
+      .push(" OR item.item_code LIKE ")
+      .push_bind(like.clone())
+      .push(" OR item.name LIKE ")
+      .push_bind(like)
+      .push(")");
+  }
+  if let Some(item_id) = item_id {
+    builder.push(" AND loan.item_id = ").push_bind(item_id);
+  }
+  // 仓库范围：按借出库位所属仓库
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND slot.warehouse_id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id);
+      }
+      separated.push_unseparated(")");
+    }
+  }
+}
+
+pub async fn list_open_loans(
+  pool: &SqlitePool,
+  now: i64,
+  overdue_only: bool,
+  keyword: Option<String>,
+  item_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  page_index: i64,
+  page_size: i64,
+) -> Result<Vec<OpenLoanRow>, AppError> {
+  let offset = (page_index - 1) * page_size;
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+    "SELECT loan.id, loan.loan_no, loan.item_id, item.item_code, item.name AS item_name, \
+     loan.slot_id, slot.code AS slot_code, slot.warehouse_id, loan.qty / {scale} AS qty, loan.borrower, loan.due_at, \
+     loan.loaned_at, loan.operator_id, op.display_name AS operator_name, txn.txn_no AS out_txn_no, loan.note \
+     FROM loan \
+     JOIN item ON loan.item_id = item.id \
+     JOIN slot ON loan.slot_id = slot.id \
+     JOIN \"operator\" AS op ON loan.operator_id = op.id \
+     JOIN txn ON loan.out_txn_id = txn.id",
+    scale = quantity::scale_sql("item.qty_precision")
+  ));
+  push_open_loan_filters(
+    &mut builder,
+    now,
+    overdue_only,
+    keyword,
+    item_id,
+    allowed_warehouse_ids,
+  );
+  // 逾期优先，其次按应还时间，无应还时间的排在最后
+  builder
+    .push(" ORDER BY loan.due_at IS NULL, loan.due_at ASC, loan.loaned_at ASC LIMIT ")
+    .push_bind(page_size)
+    .push(" OFFSET ")
+    .push_bind(offset);
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        let due_at: Option<i64> = row.get("due_at");
+        OpenLoanRow {
+          id: row.get("id"),
+          loan_no: row.get("loan_no"),
+          item_id: row.get("item_id"),
+          item_code: row.get("item_code"),
+          item_name: row.get("item_name"),
+          slot_id: row.get("slot_id"),
+          slot_code: row.get("slot_code"),
+          warehouse_id: row.get("warehouse_id"),
+          qty: row.get("qty"),
+          borrower: row.get("borrower"),
+          due_at,
+          loaned_at: row.get("loaned_at"),
+          operator_id: row.get("operator_id"),
+          operator_name: row.get("operator_name"),
+          out_txn_no: row.get("out_txn_no"),
+          note: row.get("note"),
+          overdue: due_at.map(|due_at| due_at < now).unwrap_or(false),
+        }
+      })
+      .collect(),
+  )
+}
+
+pub async fn count_open_loans(
+  pool: &SqlitePool,
+  now: i64,
+  overdue_only: bool,
+  keyword: Option<String>,
+  item_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<i64, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT COUNT(1) FROM loan JOIN item ON loan.item_id = item.id JOIN slot ON loan.slot_id = slot.id",
+  );
+  push_open_loan_filters(
+    &mut builder,
+    now,
+    overdue_only,
+    keyword,
+    item_id,
+    allowed_warehouse_ids,
+  );
+  let (count,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
+  Ok(count)
+}
+
+/// 借出中物品汇总（仪表盘），按借出数量倒序
+pub async fn list_items_on_loan(
+  pool: &SqlitePool,
+  now: i64,
+  limit: i64,
+) -> Result<Vec<ItemOnLoanRow>, AppError> {
+  let rows = sqlx::query(&format!(
+    "SELECT loan.item_id, item.item_code, item.name AS item_name, COUNT(1) AS loan_count, \
+     SUM(loan.qty) / {scale} AS qty, \
+     SUM(CASE WHEN loan.due_at IS NOT NULL AND loan.due_at < ? THEN loan.qty ELSE 0 END) / {scale} AS overdue_qty \
+     FROM loan JOIN item ON loan.item_id = item.id \
+     WHERE loan.status = 'open' \
+     GROUP BY loan.item_id, item.item_code, item.name \
+     ORDER BY qty DESC, item.item_code ASC LIMIT ?",
+    scale = quantity::scale_sql("item.qty_precision")
+  ))
+  .bind(now)
+  .bind(limit)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| ItemOnLoanRow {
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        loan_count: row.get("loan_count"),
+        qty: row.get("qty"),
+        overdue_qty: row.get("overdue_qty"),
+      })
+      .collect(),
+  )
+}
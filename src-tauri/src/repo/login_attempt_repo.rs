@@ -0,0 +1,61 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct LoginAttemptRow {
+  pub username: String,
+  pub failed_count: i64,
+  pub first_failed_at: i64,
+  pub last_failed_at: i64,
+  pub locked_until: Option<i64>,
+}
+
+pub async fn get_attempt(
+  pool: &SqlitePool,
+  username: &str,
+) -> Result<Option<LoginAttemptRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT username, failed_count, first_failed_at, last_failed_at, locked_until \
+     FROM login_attempt WHERE username = ?",
+  )
+  .bind(username)
+  .fetch_optional(pool)
+  .await?;
+
+  Ok(row.map(|row| LoginAttemptRow {
+    username: row.get("username"),
+    failed_count: row.get("failed_count"),
+    first_failed_at: row.get("first_failed_at"),
+    last_failed_at: row.get("last_failed_at"),
+    locked_until: row.get("locked_until"),
+  }))
+}
+
+pub async fn upsert_attempt(pool: &SqlitePool, row: &LoginAttemptRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO login_attempt (username, failed_count, first_failed_at, last_failed_at, locked_until) \
+     VALUES (?, ?, ?, ?, ?) \
+     ON CONFLICT(username) DO UPDATE SET \
+       failed_count = excluded.failed_count, \
+       first_failed_at = excluded.first_failed_at, \
+       last_failed_at = excluded.last_failed_at, \
+       locked_until = excluded.locked_until",
+  )
+  .bind(&row.username)
+  .bind(row.failed_count)
+  .bind(row.first_failed_at)
+  .bind(row.last_failed_at)
+  .bind(row.locked_until)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn clear_attempt(pool: &SqlitePool, username: &str) -> Result<bool, AppError> {
+  let result = sqlx::query("DELETE FROM login_attempt WHERE username = ?")
+    .bind(username)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected() > 0)
+}
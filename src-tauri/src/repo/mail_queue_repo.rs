@@ -0,0 +1,126 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MailQueueRow {
+  pub id: String,
+  // 来源类型（如 report_run），发送结果按来源回写
+  pub ref_type: Option<String>,
+  pub ref_id: Option<String>,
+  // 收件人（逗号分隔）
+  pub recipients: String,
+  pub subject: String,
+  #[serde(skip_serializing)]
+  pub body: String,
+  pub attachment_path: Option<String>,
+  // pending / sent / failed
+  pub status: String,
+  // 已尝试发送的次数
+  pub attempts: i64,
+  pub next_attempt_at: i64,
+  pub last_error: Option<String>,
+  pub created_at: i64,
+  pub sent_at: Option<i64>,
+}
+
+const MAIL_COLUMNS: &str = "SELECT id, ref_type, ref_id, recipients, subject, body, attachment_path, status, attempts, \
+   next_attempt_at, last_error, created_at, sent_at FROM mail_queue";
+
+fn map_mail(row: &sqlx::sqlite::SqliteRow) -> MailQueueRow {
+  MailQueueRow {
+    id: row.get("id"),
+    ref_type: row.get("ref_type"),
+    ref_id: row.get("ref_id"),
+    recipients: row.get("recipients"),
+    subject: row.get("subject"),
+    body: row.get("body"),
+    attachment_path: row.get("attachment_path"),
+    status: row.get("status"),
+    attempts: row.get("attempts"),
+    next_attempt_at: row.get("next_attempt_at"),
+    last_error: row.get("last_error"),
+    created_at: row.get("created_at"),
+    sent_at: row.get("sent_at"),
+  }
+}
+
+pub async fn insert_mail(pool: &SqlitePool, mail: &MailQueueRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO mail_queue \
+     (id, ref_type, ref_id, recipients, subject, body, attachment_path, status, attempts, next_attempt_at, \
+      last_error, created_at, sent_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&mail.id)
+  .bind(&mail.ref_type)
+  .bind(&mail.ref_id)
+  .bind(&mail.recipients)
+  .bind(&mail.subject)
+  .bind(&mail.body)
+  .bind(&mail.attachment_path)
+  .bind(&mail.status)
+  .bind(mail.attempts)
+  .bind(mail.next_attempt_at)
+  .bind(&mail.last_error)
+  .bind(mail.created_at)
+  .bind(mail.sent_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 到期待发送的邮件，按入队顺序
+pub async fn list_due(pool: &SqlitePool, now: i64, limit: i64) -> Result<Vec<MailQueueRow>, AppError> {
+  let rows = sqlx::query(&format!(
+    "{} WHERE status = 'pending' AND next_attempt_at <= ? ORDER BY created_at, id LIMIT ?",
+    MAIL_COLUMNS
+  ))
+  .bind(now)
+  .bind(limit)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.iter().map(map_mail).collect())
+}
+
+/// 队列列表，按入队时间倒序
+pub async fn list_mails(pool: &SqlitePool, limit: i64) -> Result<Vec<MailQueueRow>, AppError> {
+  let rows = sqlx::query(&format!("{} ORDER BY created_at DESC, id LIMIT ?", MAIL_COLUMNS))
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.iter().map(map_mail).collect())
+}
+
+/// 更新发送结果（status 为 pending 时按 next_attempt_at 重试）
+pub async fn update_delivery(
+  pool: &SqlitePool,
+  id: &str,
+  status: &str,
+  attempts: i64,
+  next_attempt_at: i64,
+  last_error: Option<&str>,
+  sent_at: Option<i64>,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "UPDATE mail_queue SET status = ?, attempts = ?, next_attempt_at = ?, last_error = ?, sent_at = ? WHERE id = ?",
+  )
+  .bind(status)
+  .bind(attempts)
+  .bind(next_attempt_at)
+  .bind(last_error)
+  .bind(sent_at)
+  .bind(id)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 清理早于指定时间且已结束（已发送或失败）的邮件
+pub async fn prune_finished(pool: &SqlitePool, before: i64) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM mail_queue WHERE status <> 'pending' AND created_at < ?")
+    .bind(before)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
@@ -0,0 +1,498 @@
+// 主数据包：按业务编码读取与写入仓库结构、物品、原因码与人员，不涉及库存与流水
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleWarehouse {
+  pub code: String,
+  pub name: String,
+  pub status: String,
+  pub kind: String,
+  // 移动仓库的归属仓库编码
+  pub base_warehouse_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleRack {
+  pub warehouse_code: String,
+  pub code: String,
+  pub name: String,
+  pub location: Option<String>,
+  pub status: String,
+  pub level_count: i64,
+  pub slots_per_level: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleSlot {
+  pub warehouse_code: String,
+  pub rack_code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub code: String,
+  pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleAttributeDef {
+  pub name: String,
+  pub data_type: String,
+  pub required: bool,
+  pub sort_no: i64,
+  pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleItemAlias {
+  pub code: String,
+  pub kind: String,
+  pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleItem {
+  pub item_code: String,
+  pub name: String,
+  pub model: Option<String>,
+  pub spec: Option<String>,
+  pub uom: Option<String>,
+  pub unit_cost: Option<f64>,
+  pub currency: Option<String>,
+  pub qty_precision: i64,
+  pub status: String,
+  pub remark: Option<String>,
+  #[serde(default)]
+  pub aliases: Vec<BundleItemAlias>,
+  // 属性名称 → 取值
+  #[serde(default)]
+  pub attributes: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleReasonCode {
+  pub code: String,
+  pub name: String,
+  pub txn_types: String,
+  pub sort_no: i64,
+  pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleOperator {
+  pub username: String,
+  pub display_name: String,
+  pub role: String,
+  pub status: String,
+  // RBAC 仓库范围（仓库编码），为空表示不限
+  #[serde(default)]
+  pub warehouse_codes: Vec<String>,
+}
+
+pub async fn list_warehouses(pool: &SqlitePool) -> Result<Vec<BundleWarehouse>, AppError> {
+  let rows = sqlx::query(
+    "SELECT warehouse.code, warehouse.name, warehouse.status, warehouse.kind, base.code AS base_code \
+     FROM warehouse \
+     LEFT JOIN warehouse base ON base.id = warehouse.base_warehouse_id \
+     ORDER BY warehouse.kind, warehouse.code",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| BundleWarehouse {
+        code: row.get("code"),
+        name: row.get("name"),
+        status: row.get("status"),
+        kind: row.get("kind"),
+        base_warehouse_code: row.get("base_code"),
+      })
+      .collect(),
+  )
+}
+
+/// 仅导出已绑定仓库的货架
+pub async fn list_racks(pool: &SqlitePool) -> Result<Vec<BundleRack>, AppError> {
+  let rows = sqlx::query(
+    "SELECT warehouse.code AS warehouse_code, rack.code, rack.name, rack.location, rack.status, \
+     rack.level_count, rack.slots_per_level \
+     FROM rack \
+     JOIN warehouse ON warehouse.id = rack.warehouse_id \
+     ORDER BY warehouse.code, rack.code",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| BundleRack {
+        warehouse_code: row.get("warehouse_code"),
+        code: row.get("code"),
+        name: row.get("name"),
+        location: row.get("location"),
+        status: row.get("status"),
+        level_count: row.get("level_count"),
+        slots_per_level: row.get("slots_per_level"),
+      })
+      .collect(),
+  )
+}
+
+pub async fn list_slots(pool: &SqlitePool) -> Result<Vec<BundleSlot>, AppError> {
+  let rows = sqlx::query(
+    "SELECT warehouse.code AS warehouse_code, rack.code AS rack_code, slot.level_no, slot.slot_no, slot.code, slot.status \
+     FROM slot \
+     JOIN rack ON rack.id = slot.rack_id \
+     JOIN warehouse ON warehouse.id = rack.warehouse_id \
+     ORDER BY warehouse.code, rack.code, slot.level_no, slot.slot_no",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| BundleSlot {
+        warehouse_code: row.get("warehouse_code"),
+        rack_code: row.get("rack_code"),
+        level_no: row.get("level_no"),
+        slot_no: row.get("slot_no"),
+        code: row.get("code"),
+        status: row.get("status"),
+      })
+      .collect(),
+  )
+}
+
+pub async fn list_attribute_defs(pool: &SqlitePool) -> Result<Vec<BundleAttributeDef>, AppError> {
+  let rows = sqlx::query("SELECT name, data_type, required, sort_no, status FROM item_attribute_def ORDER BY sort_no, name")
+    .fetch_all(pool)
+    .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| BundleAttributeDef {
+        name: row.get("name"),
+        data_type: row.get("data_type"),
+        required: row.get::<i64, _>("required") != 0,
+        sort_no: row.get("sort_no"),
+        status: row.get("status"),
+      })
+      .collect(),
+  )
+}
+
+/// 物品（不含已合并的物品），返回 (物品 id, 物品)；别名与属性由调用方按 id 归并
+pub async fn list_items(pool: &SqlitePool) -> Result<Vec<(String, BundleItem)>, AppError> {
+  let rows = sqlx::query(
+    "SELECT id, item_code, name, model, spec, uom, unit_cost, currency, qty_precision, status, remark \
+     FROM item WHERE merged_into_id IS NULL ORDER BY item_code",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        (
+          row.get("id"),
+          BundleItem {
+            item_code: row.get("item_code"),
+            name: row.get("name"),
+            model: row.get("model"),
+            spec: row.get("spec"),
+            uom: row.get("uom"),
+            unit_cost: row.get("unit_cost"),
+            currency: row.get("currency"),
+            qty_precision: row.get("qty_precision"),
+            status: row.get("status"),
+            remark: row.get("remark"),
+            aliases: Vec::new(),
+            attributes: Vec::new(),
+          },
+        )
+      })
+      .collect(),
+  )
+}
+
+/// 全部别名，返回 (物品 id, 别名)
+pub async fn list_item_aliases(pool: &SqlitePool) -> Result<Vec<(String, BundleItemAlias)>, AppError> {
+  let rows = sqlx::query("SELECT item_id, code, kind, note FROM item_alias ORDER BY code")
+    .fetch_all(pool)
+    .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        (
+          row.get("item_id"),
+          BundleItemAlias {
+            code: row.get("code"),
+            kind: row.get("kind"),
+            note: row.get("note"),
+          },
+        )
+      })
+      .collect(),
+  )
+}
+
+/// 全部属性取值，返回 (物品 id, 属性名称, 取值)
+pub async fn list_item_attribute_values(pool: &SqlitePool) -> Result<Vec<(String, String, String)>, AppError> {
+  let rows = sqlx::query(
+    "SELECT item_attribute_value.item_id, item_attribute_def.name, item_attribute_value.value \
+     FROM item_attribute_value \
+     JOIN item_attribute_def ON item_attribute_def.id = item_attribute_value.attr_id \
+     ORDER BY item_attribute_def.sort_no, item_attribute_def.name",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| (row.get("item_id"), row.get("name"), row.get("value")))
+      .collect(),
+  )
+}
+
+pub async fn list_reason_codes(pool: &SqlitePool) -> Result<Vec<BundleReasonCode>, AppError> {
+  let rows = sqlx::query("SELECT code, name, txn_types, sort_no, status FROM reason_code ORDER BY sort_no, code")
+    .fetch_all(pool)
+    .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| BundleReasonCode {
+        code: row.get("code"),
+        name: row.get("name"),
+        txn_types: row.get("txn_types"),
+        sort_no: row.get("sort_no"),
+        status: row.get("status"),
+      })
+      .collect(),
+  )
+}
+
+/// 人员（不含密码与 PIN），返回 (人员 id, 人员)
+pub async fn list_operators(pool: &SqlitePool) -> Result<Vec<(String, BundleOperator)>, AppError> {
+  let rows = sqlx::query("SELECT id, username, display_name, role, status FROM operator ORDER BY username")
+    .fetch_all(pool)
+    .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| {
+        (
+          row.get("id"),
+          BundleOperator {
+            username: row.get("username"),
+            display_name: row.get("display_name"),
+            role: row.get("role"),
+            status: row.get("status"),
+            warehouse_codes: Vec::new(),
+          },
+        )
+      })
+      .collect(),
+  )
+}
+
+/// 人员仓库范围，返回 (人员 id, 仓库编码)
+pub async fn list_operator_warehouses(pool: &SqlitePool) -> Result<Vec<(String, String)>, AppError> {
+  let rows = sqlx::query(
+    "SELECT operator_warehouse.operator_id, warehouse.code \
+     FROM operator_warehouse \
+     JOIN warehouse ON warehouse.id = operator_warehouse.warehouse_id \
+     ORDER BY warehouse.code",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.into_iter().map(|row| (row.get("operator_id"), row.get("code"))).collect())
+}
+
+async fn find_id_tx(tx: &mut Transaction<'_, Sqlite>, sql: &str, keys: &[&str]) -> Result<Option<String>, AppError> {
+  let mut query = sqlx::query_as::<_, (String,)>(sql);
+  for key in keys {
+    query = query.bind(key.to_string());
+  }
+  Ok(query.fetch_optional(&mut **tx).await?.map(|(id,)| id))
+}
+
+pub async fn warehouse_id_by_code_tx(tx: &mut Transaction<'_, Sqlite>, code: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM warehouse WHERE code = ?", &[code]).await
+}
+
+pub async fn rack_id_by_code_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  warehouse_id: &str,
+  code: &str,
+) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM rack WHERE warehouse_id = ? AND code = ?", &[warehouse_id, code]).await
+}
+
+pub async fn slot_id_by_code_tx(tx: &mut Transaction<'_, Sqlite>, code: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM slot WHERE code = ?", &[code]).await
+}
+
+pub async fn attribute_def_id_by_name_tx(tx: &mut Transaction<'_, Sqlite>, name: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM item_attribute_def WHERE name = ?", &[name]).await
+}
+
+pub async fn item_id_by_code_tx(tx: &mut Transaction<'_, Sqlite>, item_code: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM item WHERE item_code = ?", &[item_code]).await
+}
+
+pub async fn alias_id_by_code_tx(tx: &mut Transaction<'_, Sqlite>, code: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM item_alias WHERE code = ?", &[code]).await
+}
+
+pub async fn reason_code_id_by_code_tx(tx: &mut Transaction<'_, Sqlite>, code: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM reason_code WHERE code = ?", &[code]).await
+}
+
+pub async fn operator_id_by_username_tx(tx: &mut Transaction<'_, Sqlite>, username: &str) -> Result<Option<String>, AppError> {
+  find_id_tx(tx, "SELECT id FROM operator WHERE username = ?", &[username]).await
+}
+
+pub async fn insert_attribute_def_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  def: &BundleAttributeDef,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO item_attribute_def (id, name, data_type, required, sort_no, status, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(id)
+  .bind(&def.name)
+  .bind(&def.data_type)
+  .bind(def.required as i64)
+  .bind(def.sort_no)
+  .bind(&def.status)
+  .bind(created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn insert_item_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  item: &BundleItem,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO item (id, item_code, name, model, spec, uom, unit_cost, currency, qty_precision, status, remark, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(id)
+  .bind(&item.item_code)
+  .bind(&item.name)
+  .bind(&item.model)
+  .bind(&item.spec)
+  .bind(&item.uom)
+  .bind(item.unit_cost)
+  .bind(&item.currency)
+  .bind(item.qty_precision)
+  .bind(&item.status)
+  .bind(&item.remark)
+  .bind(created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn insert_item_alias_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  item_id: &str,
+  alias: &BundleItemAlias,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO item_alias (id, item_id, code, kind, note, created_at) VALUES (?, ?, ?, ?, ?, ?)")
+    .bind(id)
+    .bind(item_id)
+    .bind(&alias.code)
+    .bind(&alias.kind)
+    .bind(&alias.note)
+    .bind(created_at)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+pub async fn insert_item_attribute_value_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  item_id: &str,
+  attr_id: &str,
+  value: &str,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO item_attribute_value (item_id, attr_id, value) VALUES (?, ?, ?)")
+    .bind(item_id)
+    .bind(attr_id)
+    .bind(value)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+pub async fn insert_reason_code_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  reason: &BundleReasonCode,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO reason_code (id, code, name, txn_types, sort_no, status, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)")
+    .bind(id)
+    .bind(&reason.code)
+    .bind(&reason.name)
+    .bind(&reason.txn_types)
+    .bind(reason.sort_no)
+    .bind(&reason.status)
+    .bind(created_at)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+pub async fn insert_operator_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  operator: &BundleOperator,
+  password_hash: &str,
+  must_change_pwd: bool,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO operator (id, username, display_name, role, status, password_hash, must_change_pwd, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(id)
+  .bind(&operator.username)
+  .bind(&operator.display_name)
+  .bind(&operator.role)
+  .bind(&operator.status)
+  .bind(password_hash)
+  .bind(must_change_pwd as i64)
+  .bind(created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn insert_operator_warehouse_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  operator_id: &str,
+  warehouse_id: &str,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT OR IGNORE INTO operator_warehouse (operator_id, warehouse_id, created_at) VALUES (?, ?, ?)")
+    .bind(operator_id)
+    .bind(warehouse_id)
+    .bind(created_at)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
@@ -1,4 +1,4 @@
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
 
 use crate::domain::errors::AppError;
 
@@ -19,3 +19,22 @@ pub async fn set_meta_value(pool: &SqlitePool, key: &str, value: &str) -> Result
     .await?;
   Ok(())
 }
+
+/// 在事务内写入配置（与其他写操作一同提交）
+pub async fn set_meta_value_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  key: &str,
+  value: &str,
+) -> Result<(), AppError> {
+  sqlx::query("INSERT INTO app_meta (k, v) VALUES (?, ?) ON CONFLICT(k) DO UPDATE SET v = excluded.v")
+    .bind(key)
+    .bind(value)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
+
+pub async fn get_meta_i64(pool: &SqlitePool, key: &str) -> Result<Option<i64>, AppError> {
+  let value = get_meta_value(pool, key).await?;
+  Ok(value.and_then(|value| value.parse::<i64>().ok()))
+}
@@ -1,11 +1,38 @@
+pub mod approval_repo;
+pub mod asn_repo;
 pub mod audit_repo;
+pub mod cycle_count_repo;
 pub mod dashboard_repo;
+pub mod filter_option_repo;
+pub mod health_repo;
+pub mod idempotency_repo;
 pub mod meta_repo;
 pub mod item_repo;
+pub mod item_alias_repo;
+pub mod item_attribute_repo;
+pub mod label_template_repo;
+pub mod loan_repo;
+pub mod login_attempt_repo;
+pub mod mail_queue_repo;
+pub mod master_bundle_repo;
 pub mod operator_repo;
+pub mod operator_warehouse_repo;
+pub mod outbound_pick_repo;
 pub mod photo_repo;
 pub mod rack_repo;
+pub mod reason_code_repo;
+pub mod report_repo;
+pub mod report_schedule_repo;
+pub mod reset_code_repo;
+pub mod role_capability_repo;
+pub mod session_repo;
 pub mod stock_repo;
+pub mod stock_hold_repo;
+pub mod stock_snapshot_repo;
 pub mod stock_query_repo;
+pub mod stock_threshold_repo;
+pub mod structure_repo;
 pub mod txn_repo;
+pub mod notification_repo;
+pub mod undo_repo;
 pub mod warehouse_repo;
@@ -0,0 +1,103 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct NotificationRow {
+  pub id: String,
+  // low_stock / backup_failed / report_failed / approval_request / loan_overdue
+  pub kind: String,
+  pub title: String,
+  pub body: Option<String>,
+  // 关联对象（物品 ID、审批申请 ID、借用单 ID 等）
+  pub ref_id: Option<String>,
+  #[serde(skip_serializing)]
+  pub dedup_key: Option<String>,
+  pub created_at: i64,
+  pub read_at: Option<i64>,
+}
+
+fn map_notification(row: &sqlx::sqlite::SqliteRow) -> NotificationRow {
+  NotificationRow {
+    id: row.get("id"),
+    kind: row.get("kind"),
+    title: row.get("title"),
+    body: row.get("body"),
+    ref_id: row.get("ref_id"),
+    dedup_key: row.get("dedup_key"),
+    created_at: row.get("created_at"),
+    read_at: row.get("read_at"),
+  }
+}
+
+/// 写入通知；dedup_key 已存在时忽略，返回是否新增
+pub async fn insert_notification(pool: &SqlitePool, notification: &NotificationRow) -> Result<bool, AppError> {
+  let result = sqlx::query(
+    "INSERT OR IGNORE INTO notification (id, kind, title, body, ref_id, dedup_key, created_at, read_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, NULL)",
+  )
+  .bind(&notification.id)
+  .bind(&notification.kind)
+  .bind(&notification.title)
+  .bind(&notification.body)
+  .bind(&notification.ref_id)
+  .bind(&notification.dedup_key)
+  .bind(notification.created_at)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// 通知列表，按创建时间倒序
+pub async fn list_notifications(
+  pool: &SqlitePool,
+  unread_only: bool,
+  limit: i64,
+) -> Result<Vec<NotificationRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT id, kind, title, body, ref_id, dedup_key, created_at, read_at FROM notification",
+  );
+  if unread_only {
+    builder.push(" WHERE read_at IS NULL");
+  }
+  builder
+    .push(" ORDER BY created_at DESC, rowid DESC LIMIT ")
+    .push_bind(limit);
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(rows.iter().map(map_notification).collect())
+}
+
+pub async fn count_unread(pool: &SqlitePool) -> Result<i64, AppError> {
+  let (count,): (i64,) = sqlx::query_as("SELECT COUNT(1) FROM notification WHERE read_at IS NULL")
+    .fetch_one(pool)
+    .await?;
+  Ok(count)
+}
+
+/// 标记已读；ids 为 None 时标记全部未读通知，返回更新条数
+pub async fn mark_read(pool: &SqlitePool, ids: Option<&[String]>, read_at: i64) -> Result<u64, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("UPDATE notification SET read_at = ");
+  builder.push_bind(read_at).push(" WHERE read_at IS NULL");
+  if let Some(ids) = ids {
+    if ids.is_empty() {
+      return Ok(0);
+    }
+    builder.push(" AND id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+      separated.push_bind(id);
+    }
+    separated.push_unseparated(")");
+  }
+  let result = builder.build().execute(pool).await?;
+  Ok(result.rows_affected())
+}
+
+/// 清理早于 before 的已读通知
+pub async fn prune_read(pool: &SqlitePool, before: i64) -> Result<(), AppError> {
+  sqlx::query("DELETE FROM notification WHERE read_at IS NOT NULL AND created_at < ?")
+    .bind(before)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
@@ -1,7 +1,8 @@
-use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
 use std::collections::HashMap;
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 
 #[derive(Debug, serde::Serialize)]
 pub struct OperatorRow {
@@ -190,6 +191,48 @@ pub async fn get_operator_by_id(
   }))
 }
 
+/// 列出全部人员（按创建时间正序，用于批量调整角色）
+pub async fn list_all_operators(pool: &SqlitePool) -> Result<Vec<OperatorRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT id, username, display_name, role, status, must_change_pwd, created_at \
+     FROM operator ORDER BY created_at ASC",
+  )
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| OperatorRow {
+        id: row.get("id"),
+        username: row.get("username"),
+        display_name: row.get("display_name"),
+        role: row.get("role"),
+        status: row.get("status"),
+        must_change_pwd: row.get::<i64, _>("must_change_pwd") == 1,
+        created_at: row.get("created_at"),
+      })
+      .collect(),
+  )
+}
+
+/// 在事务内更新人员角色
+pub async fn set_operator_role_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  role: &str,
+) -> Result<(), AppError> {
+  let result = sqlx::query("UPDATE operator SET role = ? WHERE id = ?")
+    .bind(role)
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+  if result.rows_affected() == 0 {
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
+  }
+  Ok(())
+}
+
 pub async fn list_operator_names_by_ids(
   pool: &SqlitePool,
   ids: &[String],
@@ -256,13 +299,13 @@ pub async fn update_operator(
       .execute(pool)
       .await?;
     if result.rows_affected() == 0 {
-      return Err(AppError::new(ErrorCode::NotFound, "人员不存在"));
+      return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
     }
     return Ok(());
   };
 
   if !matches!(role.as_str(), "admin" | "keeper" | "viewer" | "member") {
-    return Err(AppError::new(ErrorCode::ValidationError, "角色非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidRole));
   }
 
   let result = sqlx::query("UPDATE operator SET display_name = ?, role = ? WHERE id = ?")
@@ -272,7 +315,7 @@ pub async fn update_operator(
     .execute(pool)
     .await?;
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "人员不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
   }
 
   Ok(())
@@ -284,7 +327,7 @@ pub async fn set_operator_status(
   status: &str,
 ) -> Result<(), AppError> {
   if !matches!(status, "active" | "inactive") {
-    return Err(AppError::new(ErrorCode::ValidationError, "状态非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
   }
 
   let result = sqlx::query("UPDATE operator SET status = ? WHERE id = ?")
@@ -294,7 +337,7 @@ pub async fn set_operator_status(
     .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "人员不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
   }
 
   Ok(())
@@ -316,7 +359,76 @@ pub async fn reset_operator_password(
   .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "人员不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
+  }
+
+  Ok(())
+}
+
+pub async fn get_password_hash(pool: &SqlitePool, id: &str) -> Result<Option<String>, AppError> {
+  let row = sqlx::query("SELECT password_hash FROM operator WHERE id = ?")
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.map(|row| row.get::<String, _>("password_hash")))
+}
+
+pub async fn list_recent_password_hashes(
+  pool: &SqlitePool,
+  operator_id: &str,
+  limit: i64,
+) -> Result<Vec<String>, AppError> {
+  let rows = sqlx::query(
+    "SELECT password_hash FROM password_history WHERE operator_id = ? \
+     ORDER BY created_at DESC LIMIT ?",
+  )
+  .bind(operator_id)
+  .bind(limit)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows.into_iter().map(|row| row.get("password_hash")).collect())
+}
+
+pub async fn insert_password_history(
+  pool: &SqlitePool,
+  id: &str,
+  operator_id: &str,
+  password_hash: &str,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO password_history (id, operator_id, password_hash, created_at) VALUES (?, ?, ?, ?)",
+  )
+  .bind(id)
+  .bind(operator_id)
+  .bind(password_hash)
+  .bind(created_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_pin_hash(pool: &SqlitePool, id: &str) -> Result<Option<String>, AppError> {
+  let row = sqlx::query("SELECT pin_hash FROM operator WHERE id = ?")
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.and_then(|row| row.get::<Option<String>, _>("pin_hash")))
+}
+
+pub async fn set_pin_hash(
+  pool: &SqlitePool,
+  id: &str,
+  pin_hash: Option<&str>,
+) -> Result<(), AppError> {
+  let result = sqlx::query("UPDATE operator SET pin_hash = ? WHERE id = ?")
+    .bind(pin_hash)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+  if result.rows_affected() == 0 {
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
   }
 
   Ok(())
@@ -0,0 +1,46 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+pub async fn list_warehouse_ids(
+  pool: &SqlitePool,
+  operator_id: &str,
+) -> Result<Vec<String>, AppError> {
+  let rows = sqlx::query(
+    "SELECT warehouse_id FROM operator_warehouse WHERE operator_id = ? ORDER BY created_at, warehouse_id",
+  )
+  .bind(operator_id)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(rows.into_iter().map(|row| row.get("warehouse_id")).collect())
+}
+
+/// 整体替换人员的仓库分配
+pub async fn replace_warehouse_ids(
+  pool: &SqlitePool,
+  operator_id: &str,
+  warehouse_ids: &[String],
+  created_at: i64,
+) -> Result<(), AppError> {
+  let mut tx = pool.begin().await?;
+
+  sqlx::query("DELETE FROM operator_warehouse WHERE operator_id = ?")
+    .bind(operator_id)
+    .execute(&mut *tx)
+    .await?;
+
+  for warehouse_id in warehouse_ids {
+    sqlx::query(
+      "INSERT INTO operator_warehouse (operator_id, warehouse_id, created_at) VALUES (?, ?, ?)",
+    )
+    .bind(operator_id)
+    .bind(warehouse_id)
+    .bind(created_at)
+    .execute(&mut *tx)
+    .await?;
+  }
+
+  tx.commit().await?;
+  Ok(())
+}
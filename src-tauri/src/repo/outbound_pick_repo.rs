@@ -0,0 +1,225 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+use crate::domain::quantity;
+
+#[derive(Debug, Clone)]
+pub struct OutboundPickRow {
+  pub id: String,
+  pub pick_no: String,
+  pub item_id: String,
+  pub slot_id: String,
+  pub planned_qty: i64,
+  // pending / confirmed / cancelled
+  pub status: String,
+  pub note: Option<String>,
+  pub requested_by: String,
+  pub requested_at: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct PickDiscrepancyRow {
+  pub id: String,
+  pub pick_id: String,
+  pub item_id: String,
+  pub slot_id: String,
+  pub planned_qty: i64,
+  pub actual_qty: i64,
+  pub short_qty: i64,
+  pub reason: String,
+  pub adjust_txn_id: Option<String>,
+  pub operator_id: String,
+  pub created_at: i64,
+}
+
+/// 拣货单列表行（含物品、库位、流水号与短拣差异），数量为显示数量
+#[derive(Debug, serde::Serialize)]
+pub struct OutboundPickListRow {
+  pub id: String,
+  pub pick_no: String,
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub planned_qty: f64,
+  pub actual_qty: Option<f64>,
+  pub status: String,
+  pub note: Option<String>,
+  pub requested_by_name: Option<String>,
+  pub requested_at: i64,
+  pub confirmed_by_name: Option<String>,
+  pub confirmed_at: Option<i64>,
+  pub short_reason: Option<String>,
+  pub out_txn_no: Option<String>,
+  pub short_qty: Option<f64>,
+  pub adjust_txn_no: Option<String>,
+}
+
+pub async fn insert_pick_tx(tx: &mut Transaction<'_, Sqlite>, row: &OutboundPickRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO outbound_pick (id, pick_no, item_id, slot_id, planned_qty, status, note, requested_by, requested_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.pick_no)
+  .bind(&row.item_id)
+  .bind(&row.slot_id)
+  .bind(row.planned_qty)
+  .bind(&row.status)
+  .bind(&row.note)
+  .bind(&row.requested_by)
+  .bind(row.requested_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_pick_by_id(pool: &SqlitePool, id: &str) -> Result<Option<OutboundPickRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, pick_no, item_id, slot_id, planned_qty, status, note, requested_by, requested_at \
+     FROM outbound_pick WHERE id = ?",
+  )
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.map(|row| OutboundPickRow {
+    id: row.get("id"),
+    pick_no: row.get("pick_no"),
+    item_id: row.get("item_id"),
+    slot_id: row.get("slot_id"),
+    planned_qty: row.get("planned_qty"),
+    status: row.get("status"),
+    note: row.get("note"),
+    requested_by: row.get("requested_by"),
+    requested_at: row.get("requested_at"),
+  }))
+}
+
+/// 确认拣货，仅更新待拣货的拣货单，返回更新条数
+pub async fn confirm_pick_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  actual_qty: i64,
+  short_reason: Option<&str>,
+  confirmed_by: &str,
+  confirmed_at: i64,
+  out_txn_id: Option<&str>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE outbound_pick SET status = 'confirmed', actual_qty = ?, short_reason = ?, confirmed_by = ?, \
+     confirmed_at = ?, out_txn_id = ? WHERE id = ? AND status = 'pending'",
+  )
+  .bind(actual_qty)
+  .bind(short_reason)
+  .bind(confirmed_by)
+  .bind(confirmed_at)
+  .bind(out_txn_id)
+  .bind(id)
+  .execute(&mut **tx)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 取消拣货单，仅更新待拣货的拣货单，返回更新条数
+pub async fn cancel_pick(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("UPDATE outbound_pick SET status = 'cancelled' WHERE id = ? AND status = 'pending'")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn insert_discrepancy_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  row: &PickDiscrepancyRow,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO pick_discrepancy (id, pick_id, item_id, slot_id, planned_qty, actual_qty, short_qty, reason, \
+     adjust_txn_id, operator_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.pick_id)
+  .bind(&row.item_id)
+  .bind(&row.slot_id)
+  .bind(row.planned_qty)
+  .bind(row.actual_qty)
+  .bind(row.short_qty)
+  .bind(&row.reason)
+  .bind(&row.adjust_txn_id)
+  .bind(&row.operator_id)
+  .bind(row.created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+/// 拣货单列表，按申请时间倒序；status 为空时返回全部，allowed_warehouse_ids 按库位所属仓库过滤
+pub async fn list_picks(
+  pool: &SqlitePool,
+  status: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  limit: i64,
+) -> Result<Vec<OutboundPickListRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+    "SELECT pick.id, pick.pick_no, pick.item_id, item.item_code, item.name AS item_name, pick.slot_id, \
+     slot.code AS slot_code, pick.planned_qty / {scale} AS planned_qty, pick.actual_qty / {scale} AS actual_qty, \
+     pick.status, pick.note, requester.display_name AS requested_by_name, pick.requested_at, \
+     confirmer.display_name AS confirmed_by_name, pick.confirmed_at, pick.short_reason, out_txn.txn_no AS out_txn_no, \
+     diff.short_qty / {scale} AS short_qty, adjust_txn.txn_no AS adjust_txn_no \
+     FROM outbound_pick AS pick \
+     JOIN item ON pick.item_id = item.id \
+     JOIN slot ON pick.slot_id = slot.id \
+     LEFT JOIN \"operator\" AS requester ON pick.requested_by = requester.id \
+     LEFT JOIN \"operator\" AS confirmer ON pick.confirmed_by = confirmer.id \
+     LEFT JOIN txn AS out_txn ON pick.out_txn_id = out_txn.id \
+     LEFT JOIN pick_discrepancy AS diff ON diff.pick_id = pick.id \
+     LEFT JOIN txn AS adjust_txn ON diff.adjust_txn_id = adjust_txn.id \
+     WHERE 1 = 1",
+    scale = quantity::scale_sql("item.qty_precision")
+  ));
+  if let Some(status) = status {
+    builder.push(" AND pick.status = ").push_bind(status);
+  }
+  // 仓库范围：按拣货库位所属仓库
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND slot.warehouse_id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id);
+      }
+      separated.push_unseparated(")");
+    }
+  }
+  builder.push(" ORDER BY pick.requested_at DESC LIMIT ").push_bind(limit);
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| OutboundPickListRow {
+        id: row.get("id"),
+        pick_no: row.get("pick_no"),
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        slot_id: row.get("slot_id"),
+        slot_code: row.get("slot_code"),
+        planned_qty: row.get("planned_qty"),
+        actual_qty: row.get("actual_qty"),
+        status: row.get("status"),
+        note: row.get("note"),
+        requested_by_name: row.get("requested_by_name"),
+        requested_at: row.get("requested_at"),
+        confirmed_by_name: row.get("confirmed_by_name"),
+        confirmed_at: row.get("confirmed_at"),
+        short_reason: row.get("short_reason"),
+        out_txn_no: row.get("out_txn_no"),
+        short_qty: row.get("short_qty"),
+        adjust_txn_no: row.get("adjust_txn_no"),
+      })
+      .collect(),
+  )
+}
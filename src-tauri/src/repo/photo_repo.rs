@@ -1,4 +1,4 @@
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
 
 use crate::domain::errors::{AppError, ErrorCode};
 
@@ -43,6 +43,17 @@ pub async fn list_photos(
   Ok(items)
 }
 
+/// 按关联数据统计图片数量，返回 (data_id, 数量)
+pub async fn count_photos_by_data(pool: &SqlitePool, photo_type: &str) -> Result<Vec<(String, i64)>, AppError> {
+  let rows: Vec<(String, i64)> = sqlx::query_as(
+    "SELECT data_id, COUNT(1) FROM media_attachment WHERE type = ? GROUP BY data_id",
+  )
+  .bind(photo_type)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows)
+}
+
 pub async fn list_all_photos(pool: &SqlitePool) -> Result<Vec<PhotoRow>, AppError> {
   let rows = sqlx::query(
     "SELECT id, data_id, type, file_path, mime, sort_no, created_at FROM media_attachment",
@@ -106,6 +117,32 @@ pub async fn insert_photo(
   Ok(())
 }
 
+/// 在事务内写入图片记录（随业务数据一同提交）
+pub async fn insert_photo_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  photo_type: &str,
+  data_id: &str,
+  file_path: &str,
+  sort_no: i64,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO media_attachment (id, data_id, type, file_path, mime, sort_no, created_at) \
+     VALUES (?, ?, ?, ?, NULL, ?, ?)",
+  )
+  .bind(id)
+  .bind(data_id)
+  .bind(photo_type)
+  .bind(file_path)
+  .bind(sort_no)
+  .bind(created_at)
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
+
 pub async fn delete_photo(pool: &SqlitePool, photo_id: &str) -> Result<PhotoRow, AppError> {
   let row = sqlx::query(
     "SELECT id, data_id, type, file_path, mime, sort_no, created_at \
@@ -159,3 +196,67 @@ pub async fn update_photo_sort(
     .await?;
   Ok(())
 }
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct PhotoStorageMetricRow {
+  pub id: String,
+  pub scanned_at: i64,
+  // 数据库中的附件记录数
+  pub db_count: i64,
+  // photos 目录下的文件数（不含 staging 临时文件）
+  pub file_count: i64,
+  pub total_bytes: i64,
+  // 有记录但文件缺失
+  pub missing_count: i64,
+  // 有文件但无记录
+  pub orphan_count: i64,
+}
+
+pub async fn insert_storage_metric(
+  pool: &SqlitePool,
+  metric: &PhotoStorageMetricRow,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO photo_storage_metric \
+     (id, scanned_at, db_count, file_count, total_bytes, missing_count, orphan_count) \
+     VALUES (?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&metric.id)
+  .bind(metric.scanned_at)
+  .bind(metric.db_count)
+  .bind(metric.file_count)
+  .bind(metric.total_bytes)
+  .bind(metric.missing_count)
+  .bind(metric.orphan_count)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn list_storage_metrics(
+  pool: &SqlitePool,
+  limit: i64,
+) -> Result<Vec<PhotoStorageMetricRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT id, scanned_at, db_count, file_count, total_bytes, missing_count, orphan_count \
+     FROM photo_storage_metric ORDER BY scanned_at DESC, id DESC LIMIT ?",
+  )
+  .bind(limit)
+  .fetch_all(pool)
+  .await?;
+
+  let items = rows
+    .into_iter()
+    .map(|row| PhotoStorageMetricRow {
+      id: row.get("id"),
+      scanned_at: row.get("scanned_at"),
+      db_count: row.get("db_count"),
+      file_count: row.get("file_count"),
+      total_bytes: row.get("total_bytes"),
+      missing_count: row.get("missing_count"),
+      orphan_count: row.get("orphan_count"),
+    })
+    .collect();
+
+  Ok(items)
+}
@@ -1,6 +1,8 @@
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
 
 #[derive(Debug, serde::Serialize)]
 pub struct RackRow {
@@ -223,6 +225,27 @@ pub async fn insert_rack(
   Ok(())
 }
 
+/// 事务内新建货架（结构导入）
+pub async fn insert_rack_tx(tx: &mut Transaction<'_, Sqlite>, rack: &RackRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO rack (id, code, name, warehouse_id, location, status, level_count, slots_per_level, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&rack.id)
+  .bind(&rack.code)
+  .bind(&rack.name)
+  .bind(&rack.warehouse_id)
+  .bind(&rack.location)
+  .bind(&rack.status)
+  .bind(rack.level_count)
+  .bind(rack.slots_per_level)
+  .bind(rack.created_at)
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
+
 pub async fn update_rack(
   pool: &SqlitePool,
   id: &str,
@@ -245,7 +268,7 @@ pub async fn update_rack(
   .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "货架不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound));
   }
 
   Ok(())
@@ -263,7 +286,7 @@ pub async fn set_rack_status(
     .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "货架不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound));
   }
 
   Ok(())
@@ -289,15 +312,111 @@ pub async fn set_slot_status(
     .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "库位不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound));
   }
 
   Ok(())
 }
 
+/// 在同一事务内批量更新库位状态，返回更新条数
+pub async fn set_slots_status(
+  pool: &SqlitePool,
+  slot_ids: &[String],
+  status: &str,
+) -> Result<u64, AppError> {
+  let mut tx = pool.begin().await?;
+  let mut updated = 0;
+  for slot_id in slot_ids {
+    let result = sqlx::query("UPDATE slot SET status = ? WHERE id = ?")
+      .bind(status)
+      .bind(slot_id)
+      .execute(&mut *tx)
+      .await?;
+    updated += result.rows_affected();
+  }
+  tx.commit().await?;
+  Ok(updated)
+}
+
+/// 重新编码库位时所需的库位与所属货架、仓库编码
+#[derive(Debug)]
+pub struct SlotCodeSourceRow {
+  pub slot_id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub rack_code: String,
+  pub warehouse_code: Option<String>,
+}
+
+pub async fn list_slot_code_sources(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  rack_id: Option<String>,
+) -> Result<Vec<SlotCodeSourceRow>, AppError> {
+  let mut builder = sqlx::QueryBuilder::new(
+    "SELECT slot.id AS slot_id, slot.code AS code, slot.level_no AS level_no, slot.slot_no AS slot_no, \
+     rack.code AS rack_code, warehouse.code AS warehouse_code \
+     FROM slot JOIN rack ON rack.id = slot.rack_id \
+     LEFT JOIN warehouse ON warehouse.id = COALESCE(slot.warehouse_id, rack.warehouse_id) WHERE 1 = 1",
+  );
+  if let Some(wid) = warehouse_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND COALESCE(slot.warehouse_id, rack.warehouse_id) = ");
+    builder.push_bind(wid.to_string());
+  }
+  if let Some(rid) = rack_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND slot.rack_id = ");
+    builder.push_bind(rid.to_string());
+  }
+  builder.push(" ORDER BY warehouse.code, rack.code, slot.level_no, slot.slot_no");
+  let rows = builder.build().fetch_all(pool).await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| SlotCodeSourceRow {
+        slot_id: row.get("slot_id"),
+        code: row.get("code"),
+        level_no: row.get("level_no"),
+        slot_no: row.get("slot_no"),
+        rack_code: row.get("rack_code"),
+        warehouse_code: row.get("warehouse_code"),
+      })
+      .collect(),
+  )
+}
+
+/// 在同一事务内批量修改库位编码（库位 ID 不变），返回更新条数；
+/// 先改为按 ID 生成的临时编码，避免新旧编码互换时触发唯一约束
+pub async fn update_slot_codes(pool: &SqlitePool, changes: &[(String, String)]) -> Result<u64, AppError> {
+  let mut tx = pool.begin().await?;
+  for (slot_id, _) in changes {
+    sqlx::query("UPDATE slot SET code = '~recode~' || id WHERE id = ?")
+      .bind(slot_id)
+      .execute(&mut *tx)
+      .await?;
+  }
+  let mut updated = 0;
+  for (slot_id, code) in changes {
+    let result = sqlx::query("UPDATE slot SET code = ? WHERE id = ?")
+      .bind(code)
+      .bind(slot_id)
+      .execute(&mut *tx)
+      .await?;
+    updated += result.rows_affected();
+  }
+  tx.commit().await?;
+  Ok(updated)
+}
+
 pub async fn insert_slots(pool: &SqlitePool, slots: Vec<SlotRow>) -> Result<(), AppError> {
   let mut tx = pool.begin().await?;
+  insert_slots_tx(&mut tx, slots).await?;
+  tx.commit().await?;
+  Ok(())
+}
 
+pub async fn insert_slots_tx(tx: &mut Transaction<'_, Sqlite>, slots: Vec<SlotRow>) -> Result<(), AppError> {
   for slot in slots {
     sqlx::query(
       "INSERT INTO slot (id, rack_id, warehouse_id, level_no, slot_no, code, status, created_at) \
@@ -311,11 +430,10 @@ pub async fn insert_slots(pool: &SqlitePool, slots: Vec<SlotRow>) -> Result<(),
     .bind(slot.code)
     .bind(slot.status)
     .bind(slot.created_at)
-    .execute(&mut *tx)
+    .execute(&mut **tx)
     .await?;
   }
 
-  tx.commit().await?;
   Ok(())
 }
 
@@ -415,3 +533,199 @@ pub async fn get_slot_by_id(
     created_at: row.get("created_at"),
   }))
 }
+
+/// 事务内查询库位状态，库位不存在时返回 None
+pub async fn get_slot_status_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+) -> Result<Option<String>, AppError> {
+  let status: Option<(String,)> = sqlx::query_as("SELECT status FROM slot WHERE id = ?")
+    .bind(id)
+    .fetch_optional(&mut **tx)
+    .await?;
+  Ok(status.map(|(status,)| status))
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SlotOccupancyRow {
+  pub slot_id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub status: String,
+  // 库位内有库存记录的物品数
+  pub item_count: i64,
+  // 显示数量合计（按物品精度换算）
+  pub total_qty: f64,
+  // 数量最多的物品编码
+  pub top_item_code: Option<String>,
+}
+
+pub async fn list_slot_occupancy(
+  pool: &SqlitePool,
+  rack_id: &str,
+) -> Result<Vec<SlotOccupancyRow>, AppError> {
+  let rows = sqlx::query(&format!(
+    "SELECT slot.id AS slot_id, slot.code AS code, slot.level_no AS level_no, slot.slot_no AS slot_no, \
+     slot.status AS status, \
+     (SELECT COUNT(1) FROM stock WHERE stock.slot_id = slot.id AND stock.qty <> 0) AS item_count, \
+     (SELECT COALESCE(SUM(stock.qty / {scale}), 0.0) FROM stock JOIN item ON stock.item_id = item.id \
+        WHERE stock.slot_id = slot.id) AS total_qty, \
+     (SELECT item.item_code FROM stock JOIN item ON stock.item_id = item.id \
+        WHERE stock.slot_id = slot.id AND stock.qty > 0 \
+        ORDER BY stock.qty / {scale} DESC, item.item_code LIMIT 1) AS top_item_code \
+     FROM slot WHERE slot.rack_id = ? \
+     ORDER BY slot.level_no, slot.slot_no",
+    scale = quantity::scale_sql("item.qty_precision")
+  ))
+  .bind(rack_id)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| SlotOccupancyRow {
+        slot_id: row.get("slot_id"),
+        code: row.get("code"),
+        level_no: row.get("level_no"),
+        slot_no: row.get("slot_no"),
+        status: row.get("status"),
+        item_count: row.get("item_count"),
+        total_qty: row.get("total_qty"),
+        top_item_code: row.get("top_item_code"),
+      })
+      .collect(),
+  )
+}
+
+/// 货架热力图数据：库位的流水次数与当前库存
+#[derive(Debug, Clone)]
+pub struct SlotActivityRow {
+  pub slot_id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub status: String,
+  // 统计期内涉及该库位的流水数（移入、移出均计）
+  pub txn_count: i64,
+  // 其中移入该库位的流水数
+  pub in_count: i64,
+  // 其中移出该库位的流水数
+  pub out_count: i64,
+  // 当前库存显示数量合计
+  pub qty: f64,
+}
+
+/// 货架各库位在 since 之后的流水次数与当前库存，单条分组查询
+pub async fn list_slot_activity(
+  pool: &SqlitePool,
+  rack_id: &str,
+  since: i64,
+) -> Result<Vec<SlotActivityRow>, AppError> {
+  let rows = sqlx::query(&format!(
+    "SELECT slot.id AS slot_id, slot.code AS code, slot.level_no AS level_no, slot.slot_no AS slot_no, \
+     slot.status AS status, \
+     COUNT(txn.id) AS txn_count, \
+     COALESCE(SUM(CASE WHEN txn.to_slot_id = slot.id THEN 1 ELSE 0 END), 0) AS in_count, \
+     COALESCE(SUM(CASE WHEN txn.from_slot_id = slot.id THEN 1 ELSE 0 END), 0) AS out_count, \
+     COALESCE(MAX(slot_qty.qty), 0.0) AS qty \
+     FROM slot \
+     LEFT JOIN (SELECT stock.slot_id, SUM(stock.qty / {scale}) AS qty FROM stock \
+        JOIN item ON stock.item_id = item.id GROUP BY stock.slot_id) AS slot_qty ON slot_qty.slot_id = slot.id \
+     LEFT JOIN txn ON (txn.from_slot_id = slot.id OR txn.to_slot_id = slot.id) AND txn.occurred_at >= ? \
+     WHERE slot.rack_id = ? \
+     GROUP BY slot.id \
+     ORDER BY slot.level_no, slot.slot_no",
+    scale = quantity::scale_sql("item.qty_precision")
+  ))
+  .bind(since)
+  .bind(rack_id)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| SlotActivityRow {
+        slot_id: row.get("slot_id"),
+        code: row.get("code"),
+        level_no: row.get("level_no"),
+        slot_no: row.get("slot_no"),
+        status: row.get("status"),
+        txn_count: row.get("txn_count"),
+        in_count: row.get("in_count"),
+        out_count: row.get("out_count"),
+        qty: row.get("qty"),
+      })
+      .collect(),
+  )
+}
+
+/// 库位标签数据：库位及所属货架、仓库的编码与名称
+#[derive(Debug, Clone)]
+pub struct SlotLabelRow {
+  pub slot_code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub rack_code: String,
+  pub rack_name: String,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+}
+
+/// 按货架或库位编码列出库位标签数据（按货架、层、位排序），allowed_warehouse_ids 限定仓库范围
+pub async fn list_slot_labels(
+  pool: &SqlitePool,
+  rack_id: Option<String>,
+  slot_codes: &[String],
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<SlotLabelRow>, AppError> {
+  let mut builder = sqlx::QueryBuilder::new(
+    "SELECT slot.code AS slot_code, slot.level_no AS level_no, slot.slot_no AS slot_no, \
+     rack.code AS rack_code, rack.name AS rack_name, \
+     warehouse.code AS warehouse_code, warehouse.name AS warehouse_name FROM slot \
+     JOIN rack ON slot.rack_id = rack.id \
+     LEFT JOIN warehouse ON slot.warehouse_id = warehouse.id WHERE 1 = 1",
+  );
+  if let Some(rid) = rack_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND slot.rack_id = ");
+    builder.push_bind(rid.to_string());
+  }
+  if !slot_codes.is_empty() {
+    builder.push(" AND slot.code IN (");
+    let mut separated = builder.separated(", ");
+    for code in slot_codes {
+      separated.push_bind(code.clone());
+    }
+    separated.push_unseparated(")");
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      return Ok(Vec::new());
+    }
+    builder.push(" AND slot.warehouse_id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+      separated.push_bind(id);
+    }
+    separated.push_unseparated(")");
+  }
+  builder.push(" ORDER BY rack.code, slot.level_no, slot.slot_no");
+  let rows = builder.build().fetch_all(pool).await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| SlotLabelRow {
+        slot_code: row.get("slot_code"),
+        level_no: row.get("level_no"),
+        slot_no: row.get("slot_no"),
+        rack_code: row.get("rack_code"),
+        rack_name: row.get("rack_name"),
+        warehouse_code: row.get("warehouse_code"),
+        warehouse_name: row.get("warehouse_name"),
+      })
+      .collect(),
+  )
+}
@@ -0,0 +1,111 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReasonCodeRow {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  // 适用的流水类型：ADJUST / REVERSAL / SCRAP 的子集
+  pub txn_types: Vec<String>,
+  pub sort_no: i64,
+  // active / disabled
+  pub status: String,
+  pub created_at: i64,
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, code, name, txn_types, sort_no, status, created_at FROM reason_code";
+
+fn map_row(row: &sqlx::sqlite::SqliteRow) -> ReasonCodeRow {
+  ReasonCodeRow {
+    id: row.get("id"),
+    code: row.get("code"),
+    name: row.get("name"),
+    txn_types: row
+      .get::<String, _>("txn_types")
+      .split(',')
+      .map(str::trim)
+      .filter(|value| !value.is_empty())
+      .map(str::to_string)
+      .collect(),
+    sort_no: row.get("sort_no"),
+    status: row.get("status"),
+    created_at: row.get("created_at"),
+  }
+}
+
+/// 原因列表，按排序号与编码排列；only_active 时仅返回启用的原因
+pub async fn list_reasons(pool: &SqlitePool, only_active: bool) -> Result<Vec<ReasonCodeRow>, AppError> {
+  let sql = if only_active {
+    format!("{} WHERE status = 'active' ORDER BY sort_no, code", SELECT_COLUMNS)
+  } else {
+    format!("{} ORDER BY sort_no, code", SELECT_COLUMNS)
+  };
+  let rows = sqlx::query(&sql).fetch_all(pool).await?;
+  Ok(rows.iter().map(map_row).collect())
+}
+
+pub async fn get_reason(pool: &SqlitePool, id: &str) -> Result<Option<ReasonCodeRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE id = ?", SELECT_COLUMNS))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_row))
+}
+
+pub async fn get_reason_by_code(pool: &SqlitePool, code: &str) -> Result<Option<ReasonCodeRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE code = ?", SELECT_COLUMNS))
+    .bind(code)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_row))
+}
+
+pub async fn insert_reason(pool: &SqlitePool, reason: &ReasonCodeRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO reason_code (id, code, name, txn_types, sort_no, status, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&reason.id)
+  .bind(&reason.code)
+  .bind(&reason.name)
+  .bind(reason.txn_types.join(","))
+  .bind(reason.sort_no)
+  .bind(&reason.status)
+  .bind(reason.created_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn update_reason(pool: &SqlitePool, reason: &ReasonCodeRow) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE reason_code SET code = ?, name = ?, txn_types = ?, sort_no = ?, status = ? WHERE id = ?",
+  )
+  .bind(&reason.code)
+  .bind(&reason.name)
+  .bind(reason.txn_types.join(","))
+  .bind(reason.sort_no)
+  .bind(&reason.status)
+  .bind(&reason.id)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn delete_reason(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM reason_code WHERE id = ?")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+/// 引用该原因的流水数量（已被引用的原因只能停用，不能删除）
+pub async fn count_txn_refs(pool: &SqlitePool, id: &str) -> Result<i64, AppError> {
+  let (count,): (i64,) = sqlx::query_as("SELECT COUNT(1) FROM txn WHERE reason_id = ?")
+    .bind(id)
+    .fetch_one(pool)
+    .await?;
+  Ok(count)
+}
@@ -0,0 +1,341 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+use crate::domain::quantity;
+use crate::domain::timezone::ReportTimezone;
+use crate::repo::dashboard_repo;
+
+/// 单个分组内的库存变动
+#[derive(Debug)]
+pub struct StockDeltaRow {
+  pub bucket: String,
+  pub increase: i64,
+  pub decrease: i64,
+}
+
+/// 将物品流水拆成按库位的变动记录（leg）：来源库位与目标库位各一条，
+/// 移库在总量上相互抵消，但按仓库范围过滤时需分别计入
+fn push_item_delta_legs(builder: &mut QueryBuilder<Sqlite>, item_id: &str) {
+  builder
+    .push(
+      " FROM (\
+       SELECT txn.occurred_at, txn.from_slot_id AS slot_id, txn.qty * CASE \
+       WHEN txn.\"type\" IN ('OUT', 'MOVE', 'SCRAP') THEN -1 \
+       WHEN txn.\"type\" = 'ADJUST' THEN 1 \
+       WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" IN ('OUT', 'MOVE', 'SCRAP') THEN 1 \
+       WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" = 'ADJUST' THEN -1 \
+       ELSE 0 END AS delta \
+       FROM txn LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+       WHERE txn.item_id = ",
+    )
+    .push_bind(item_id.to_string())
+    .push(
+      " AND txn.from_slot_id IS NOT NULL \
+       UNION ALL \
+       SELECT txn.occurred_at, txn.to_slot_id AS slot_id, txn.qty * CASE \
+       WHEN txn.\"type\" IN ('IN', 'MOVE', 'OPENING') THEN 1 \
+       WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" IN ('IN', 'MOVE', 'OPENING') THEN -1 \
+       ELSE 0 END AS delta \
+       FROM txn LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+       WHERE txn.item_id = ",
+    )
+    .push_bind(item_id.to_string())
+    .push(
+      " AND txn.to_slot_id IS NOT NULL\
+       ) AS leg LEFT JOIN slot ON leg.slot_id = slot.id WHERE leg.delta <> 0",
+    );
+}
+
+fn push_warehouse_scope(builder: &mut QueryBuilder<Sqlite>, allowed_warehouse_ids: Option<Vec<String>>) {
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND slot.warehouse_id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id);
+      }
+      separated.push_unseparated(")");
+    }
+  }
+}
+
+/// 物品当前库存合计（可按仓库范围过滤）
+pub async fn sum_item_stock(
+  pool: &SqlitePool,
+  item_id: &str,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<i64, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT COALESCE(SUM(stock.qty), 0) FROM stock JOIN slot ON stock.slot_id = slot.id WHERE stock.item_id = ",
+  );
+  builder.push_bind(item_id.to_string());
+  push_warehouse_scope(&mut builder, allowed_warehouse_ids);
+  let (total,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
+  Ok(total)
+}
+
+/// 指定时间之后发生的库存净变动，用于由当前库存倒推历史库存
+pub async fn sum_item_delta_after(
+  pool: &SqlitePool,
+  item_id: &str,
+  after: i64,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<i64, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new("SELECT COALESCE(SUM(leg.delta), 0)");
+  push_item_delta_legs(&mut builder, item_id);
+  builder.push(" AND leg.occurred_at > ").push_bind(after);
+  push_warehouse_scope(&mut builder, allowed_warehouse_ids);
+  let (total,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
+  Ok(total)
+}
+
+/// 按粒度（day/week/month）分组统计物品库存增减，分组口径与仪表盘趋势一致
+pub async fn list_item_delta_by_bucket(
+  pool: &SqlitePool,
+  item_id: &str,
+  start_at: i64,
+  end_at: i64,
+  granularity: &str,
+  timezone: &ReportTimezone,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<StockDeltaRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+    "SELECT {} AS bucket, \
+     SUM(CASE WHEN leg.delta > 0 THEN leg.delta ELSE 0 END) AS increase, \
+     SUM(CASE WHEN leg.delta < 0 THEN -leg.delta ELSE 0 END) AS decrease",
+    dashboard_repo::trend_bucket_expr(granularity, timezone)
+  ));
+  push_item_delta_legs(&mut builder, item_id);
+  builder
+    .push(" AND leg.occurred_at >= ")
+    .push_bind(start_at)
+    .push(" AND leg.occurred_at <= ")
+    .push_bind(end_at);
+  push_warehouse_scope(&mut builder, allowed_warehouse_ids);
+  builder.push(" GROUP BY bucket ORDER BY bucket ASC");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| StockDeltaRow {
+        bucket: row.get("bucket"),
+        increase: row.get::<Option<i64>, _>("increase").unwrap_or(0),
+        decrease: row.get::<Option<i64>, _>("decrease").unwrap_or(0),
+      })
+      .collect(),
+  )
+}
+
+/// 库龄明细：有库存的物品/库位及其最近入库、最近变动时间（数量为存储值）
+#[derive(Debug)]
+pub struct StockAgingRow {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub qty_precision: i64,
+  pub unit_cost: Option<f64>,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub qty: i64,
+  // 最近一次入库/移入该库位的时间（已冲正的流水不计）
+  pub last_in_at: Option<i64>,
+  // 最近一次涉及该物品/库位的流水时间
+  pub last_move_at: Option<i64>,
+  pub stock_updated_at: i64,
+}
+
+/// 列出有库存的物品/库位的库龄信息（可按仓库与仓库范围过滤）
+pub async fn list_stock_aging(
+  pool: &SqlitePool,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<StockAgingRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT stock.item_id, item.item_code, item.name AS item_name, item.uom, item.qty_precision, item.unit_cost, \
+     stock.slot_id, slot.code AS slot_code, warehouse.id AS warehouse_id, warehouse.code AS warehouse_code, \
+     warehouse.name AS warehouse_name, stock.qty, stock.updated_at AS stock_updated_at, \
+     (SELECT MAX(t.occurred_at) FROM txn AS t \
+        WHERE t.item_id = stock.item_id AND t.to_slot_id = stock.slot_id AND t.\"type\" IN ('IN', 'MOVE', 'OPENING') \
+        AND NOT EXISTS (SELECT 1 FROM txn AS r WHERE r.ref_txn_id = t.id AND r.\"type\" = 'REVERSAL')) AS last_in_at, \
+     (SELECT MAX(t.occurred_at) FROM txn AS t \
+        WHERE t.item_id = stock.item_id AND (t.to_slot_id = stock.slot_id OR t.from_slot_id = stock.slot_id)) AS last_move_at \
+     FROM stock \
+     JOIN item ON stock.item_id = item.id \
+     JOIN slot ON stock.slot_id = slot.id \
+     LEFT JOIN warehouse ON slot.warehouse_id = warehouse.id \
+     WHERE stock.qty > 0",
+  );
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND slot.warehouse_id = ").push_bind(warehouse_id.to_string());
+  }
+  push_warehouse_scope(&mut builder, allowed_warehouse_ids);
+  builder.push(" ORDER BY item.item_code, slot.code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| StockAgingRow {
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        uom: row.get("uom"),
+        qty_precision: row.get("qty_precision"),
+        unit_cost: row.get("unit_cost"),
+        slot_id: row.get("slot_id"),
+        slot_code: row.get("slot_code"),
+        warehouse_id: row.get("warehouse_id"),
+        warehouse_code: row.get("warehouse_code"),
+        warehouse_name: row.get("warehouse_name"),
+        qty: row.get("qty"),
+        last_in_at: row.get("last_in_at"),
+        last_move_at: row.get("last_move_at"),
+        stock_updated_at: row.get("stock_updated_at"),
+      })
+      .collect(),
+  )
+}
+
+/// 有到期日记录的物品在库位上的库存（数量为存储值）
+#[derive(Debug)]
+pub struct ExpiryStockRow {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub qty_precision: i64,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub qty: i64,
+}
+
+/// 入库时录入过到期日的物品的现存库存（可按仓库与仓库范围过滤）
+pub async fn list_expiry_stock(
+  pool: &SqlitePool,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<ExpiryStockRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT stock.item_id, item.item_code, item.name AS item_name, item.uom, item.qty_precision, \
+     stock.slot_id, slot.code AS slot_code, warehouse.id AS warehouse_id, warehouse.name AS warehouse_name, stock.qty \
+     FROM stock \
+     JOIN item ON stock.item_id = item.id \
+     JOIN slot ON stock.slot_id = slot.id \
+     LEFT JOIN warehouse ON slot.warehouse_id = warehouse.id \
+     WHERE stock.qty > 0 \
+     AND EXISTS (SELECT 1 FROM txn WHERE txn.item_id = stock.item_id AND txn.expires_at IS NOT NULL)",
+  );
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND slot.warehouse_id = ").push_bind(warehouse_id.to_string());
+  }
+  push_warehouse_scope(&mut builder, allowed_warehouse_ids);
+  builder.push(" ORDER BY item.item_code, slot.code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| ExpiryStockRow {
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        uom: row.get("uom"),
+        qty_precision: row.get("qty_precision"),
+        slot_id: row.get("slot_id"),
+        slot_code: row.get("slot_code"),
+        warehouse_id: row.get("warehouse_id"),
+        warehouse_name: row.get("warehouse_name"),
+        qty: row.get("qty"),
+      })
+      .collect(),
+  )
+}
+
+/// 入库 / 移入流水（已冲正的不计），仅含录入过到期日的物品，按发生时间倒序，
+/// 返回 (物品 ID, 库位 ID, 数量, 到期日)
+pub async fn list_expiry_inbound_legs(pool: &SqlitePool) -> Result<Vec<(String, String, i64, Option<i64>)>, AppError> {
+  let rows: Vec<(String, String, i64, Option<i64>)> = sqlx::query_as(
+    "SELECT t.item_id, t.to_slot_id, t.qty, t.expires_at FROM txn AS t \
+     WHERE t.to_slot_id IS NOT NULL AND t.\"type\" IN ('IN', 'MOVE', 'OPENING') \
+     AND t.item_id IN (SELECT DISTINCT item_id FROM txn WHERE expires_at IS NOT NULL) \
+     AND NOT EXISTS (SELECT 1 FROM txn AS r WHERE r.ref_txn_id = t.id AND r.\"type\" = 'REVERSAL') \
+     ORDER BY t.occurred_at DESC, t.created_at DESC",
+  )
+  .fetch_all(pool)
+  .await?;
+  Ok(rows)
+}
+
+/// 按原因与流水类型汇总的调整、冲正、报废流水（数量为显示数量的绝对值合计）
+#[derive(Debug)]
+pub struct TxnReasonSummaryRow {
+  pub reason_id: Option<String>,
+  pub reason_code: Option<String>,
+  pub reason_name: Option<String>,
+  pub txn_type: String,
+  pub txn_count: i64,
+  pub item_count: i64,
+  pub total_qty: f64,
+}
+
+/// 时间范围内的调整、冲正、报废流水按原因汇总；未填写原因的单独成组，
+/// 数量为 0 的调整（盘点无差异）与已被冲正的流水不计
+pub async fn list_txn_reason_summary(
+  pool: &SqlitePool,
+  start_at: i64,
+  end_at: i64,
+  txn_type: Option<&str>,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<TxnReasonSummaryRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+    "SELECT txn.reason_id, rc.code AS reason_code, rc.name AS reason_name, txn.\"type\" AS txn_type, \
+     COUNT(1) AS txn_count, COUNT(DISTINCT txn.item_id) AS item_count, \
+     SUM(ABS(txn.qty) / {scale}) AS total_qty \
+     FROM txn \
+     JOIN item ON txn.item_id = item.id \
+     LEFT JOIN reason_code AS rc ON txn.reason_id = rc.id \
+     LEFT JOIN slot ON slot.id = COALESCE(txn.from_slot_id, txn.to_slot_id) \
+     WHERE txn.\"type\" IN ('ADJUST', 'REVERSAL', 'SCRAP') AND txn.qty <> 0 \
+     AND NOT EXISTS (SELECT 1 FROM txn AS rev WHERE rev.ref_txn_id = txn.id AND rev.\"type\" = 'REVERSAL')",
+    scale = quantity::scale_sql("item.qty_precision"),
+  ));
+  builder
+    .push(" AND txn.occurred_at >= ")
+    .push_bind(start_at)
+    .push(" AND txn.occurred_at <= ")
+    .push_bind(end_at);
+  if let Some(txn_type) = txn_type {
+    builder.push(" AND txn.\"type\" = ").push_bind(txn_type.to_string());
+  }
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND slot.warehouse_id = ").push_bind(warehouse_id.to_string());
+  }
+  push_warehouse_scope(&mut builder, allowed_warehouse_ids);
+  builder.push(" GROUP BY txn.reason_id, txn.\"type\" ORDER BY txn_count DESC, rc.sort_no, rc.code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| TxnReasonSummaryRow {
+        reason_id: row.get("reason_id"),
+        reason_code: row.get("reason_code"),
+        reason_name: row.get("reason_name"),
+        txn_type: row.get("txn_type"),
+        txn_count: row.get("txn_count"),
+        item_count: row.get("item_count"),
+        total_qty: row.get::<Option<f64>, _>("total_qty").unwrap_or(0.0),
+      })
+      .collect(),
+  )
+}
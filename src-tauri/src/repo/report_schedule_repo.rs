@@ -0,0 +1,223 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReportScheduleRow {
+  pub id: String,
+  pub name: String,
+  // txn_journal（昨日流水日报）/ low_stock（低库存）
+  pub report_kind: String,
+  // 每天执行时间（本机时区，HH:MM）
+  pub run_time: String,
+  // 收件人（逗号分隔），为空时只生成文件
+  pub email_to: Option<String>,
+  pub status: String,
+  pub last_run_date: Option<String>,
+  pub created_at: i64,
+  pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReportScheduleRunRow {
+  pub id: String,
+  // 计划删除后为空
+  pub schedule_id: Option<String>,
+  pub schedule_name: String,
+  pub report_kind: String,
+  // schedule（定时）/ manual（手动执行）
+  pub trigger_type: String,
+  // success / failed
+  pub status: String,
+  pub file_path: Option<String>,
+  // none / sent / failed
+  pub email_status: String,
+  pub error: Option<String>,
+  pub started_at: i64,
+  pub finished_at: i64,
+}
+
+const SCHEDULE_COLUMNS: &str = "SELECT id, name, report_kind, run_time, email_to, status, last_run_date, \
+   created_at, updated_at FROM report_schedule";
+
+const RUN_COLUMNS: &str = "SELECT id, schedule_id, schedule_name, report_kind, trigger_type, status, file_path, \
+   email_status, error, started_at, finished_at FROM report_schedule_run";
+
+fn map_schedule(row: &sqlx::sqlite::SqliteRow) -> ReportScheduleRow {
+  ReportScheduleRow {
+    id: row.get("id"),
+    name: row.get("name"),
+    report_kind: row.get("report_kind"),
+    run_time: row.get("run_time"),
+    email_to: row.get("email_to"),
+    status: row.get("status"),
+    last_run_date: row.get("last_run_date"),
+    created_at: row.get("created_at"),
+    updated_at: row.get("updated_at"),
+  }
+}
+
+fn map_run(row: &sqlx::sqlite::SqliteRow) -> ReportScheduleRunRow {
+  ReportScheduleRunRow {
+    id: row.get("id"),
+    schedule_id: row.get("schedule_id"),
+    schedule_name: row.get("schedule_name"),
+    report_kind: row.get("report_kind"),
+    trigger_type: row.get("trigger_type"),
+    status: row.get("status"),
+    file_path: row.get("file_path"),
+    email_status: row.get("email_status"),
+    error: row.get("error"),
+    started_at: row.get("started_at"),
+    finished_at: row.get("finished_at"),
+  }
+}
+
+pub async fn list_schedules(pool: &SqlitePool) -> Result<Vec<ReportScheduleRow>, AppError> {
+  let rows = sqlx::query(&format!("{} ORDER BY run_time, name", SCHEDULE_COLUMNS))
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.iter().map(map_schedule).collect())
+}
+
+pub async fn list_active_schedules(pool: &SqlitePool) -> Result<Vec<ReportScheduleRow>, AppError> {
+  let rows = sqlx::query(&format!("{} WHERE status = 'active' ORDER BY run_time, name", SCHEDULE_COLUMNS))
+    .fetch_all(pool)
+    .await?;
+  Ok(rows.iter().map(map_schedule).collect())
+}
+
+pub async fn get_schedule(pool: &SqlitePool, id: &str) -> Result<Option<ReportScheduleRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE id = ?", SCHEDULE_COLUMNS))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_schedule))
+}
+
+pub async fn name_exists(pool: &SqlitePool, name: &str, exclude_id: Option<&str>) -> Result<bool, AppError> {
+  let count: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM report_schedule WHERE name = ? AND id <> ?")
+    .bind(name)
+    .bind(exclude_id.unwrap_or(""))
+    .fetch_one(pool)
+    .await?;
+  Ok(count > 0)
+}
+
+/// 新增或更新计划
+pub async fn save_schedule(pool: &SqlitePool, schedule: &ReportScheduleRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO report_schedule \
+     (id, name, report_kind, run_time, email_to, status, last_run_date, created_at, updated_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?) \
+     ON CONFLICT(id) DO UPDATE SET name = excluded.name, report_kind = excluded.report_kind, \
+     run_time = excluded.run_time, email_to = excluded.email_to, status = excluded.status, \
+     last_run_date = excluded.last_run_date, updated_at = excluded.updated_at",
+  )
+  .bind(&schedule.id)
+  .bind(&schedule.name)
+  .bind(&schedule.report_kind)
+  .bind(&schedule.run_time)
+  .bind(&schedule.email_to)
+  .bind(&schedule.status)
+  .bind(&schedule.last_run_date)
+  .bind(schedule.created_at)
+  .bind(schedule.updated_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 删除计划，执行记录保留（schedule_id 置空）
+pub async fn delete_schedule(pool: &SqlitePool, id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM report_schedule WHERE id = ?")
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+pub async fn set_last_run_date(pool: &SqlitePool, id: &str, date: &str) -> Result<(), AppError> {
+  sqlx::query("UPDATE report_schedule SET last_run_date = ? WHERE id = ?")
+    .bind(date)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+pub async fn insert_run(pool: &SqlitePool, run: &ReportScheduleRunRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO report_schedule_run \
+     (id, schedule_id, schedule_name, report_kind, trigger_type, status, file_path, email_status, error, \
+      started_at, finished_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&run.id)
+  .bind(&run.schedule_id)
+  .bind(&run.schedule_name)
+  .bind(&run.report_kind)
+  .bind(&run.trigger_type)
+  .bind(&run.status)
+  .bind(&run.file_path)
+  .bind(&run.email_status)
+  .bind(&run.error)
+  .bind(run.started_at)
+  .bind(run.finished_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_run(pool: &SqlitePool, id: &str) -> Result<Option<ReportScheduleRunRow>, AppError> {
+  let row = sqlx::query(&format!("{} WHERE id = ?", RUN_COLUMNS))
+    .bind(id)
+    .fetch_optional(pool)
+    .await?;
+  Ok(row.as_ref().map(map_run))
+}
+
+/// 回写执行结果（邮件队列投递后更新状态、邮件状态与错误信息）
+pub async fn update_run_result(pool: &SqlitePool, run: &ReportScheduleRunRow) -> Result<(), AppError> {
+  sqlx::query("UPDATE report_schedule_run SET status = ?, email_status = ?, error = ? WHERE id = ?")
+    .bind(&run.status)
+    .bind(&run.email_status)
+    .bind(&run.error)
+    .bind(&run.id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+/// 执行记录，按开始时间倒序；schedule_id 为 None 时返回全部计划的记录
+pub async fn list_runs(
+  pool: &SqlitePool,
+  schedule_id: Option<&str>,
+  limit: i64,
+) -> Result<Vec<ReportScheduleRunRow>, AppError> {
+  let rows = match schedule_id {
+    Some(schedule_id) => {
+      sqlx::query(&format!("{} WHERE schedule_id = ? ORDER BY started_at DESC, id LIMIT ?", RUN_COLUMNS))
+        .bind(schedule_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+    }
+    None => {
+      sqlx::query(&format!("{} ORDER BY started_at DESC, id LIMIT ?", RUN_COLUMNS))
+        .bind(limit)
+        .fetch_all(pool)
+        .await?
+    }
+  };
+  Ok(rows.iter().map(map_run).collect())
+}
+
+/// 清理早于指定时间的执行记录
+pub async fn prune_runs(pool: &SqlitePool, before: i64) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM report_schedule_run WHERE started_at < ?")
+    .bind(before)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
@@ -0,0 +1,60 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug)]
+pub struct ResetCodeRow {
+  pub operator_id: String,
+  pub code_hash: String,
+  pub issued_by: String,
+  pub issued_at: i64,
+  pub expires_at: i64,
+}
+
+/// 写入重置码，已有记录时覆盖（旧码随即失效）
+pub async fn upsert_reset_code(pool: &SqlitePool, row: &ResetCodeRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO password_reset_code (operator_id, code_hash, issued_by, issued_at, expires_at) \
+     VALUES (?, ?, ?, ?, ?) \
+     ON CONFLICT(operator_id) DO UPDATE SET \
+     code_hash = excluded.code_hash, issued_by = excluded.issued_by, \
+     issued_at = excluded.issued_at, expires_at = excluded.expires_at",
+  )
+  .bind(&row.operator_id)
+  .bind(&row.code_hash)
+  .bind(&row.issued_by)
+  .bind(row.issued_at)
+  .bind(row.expires_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_reset_code(
+  pool: &SqlitePool,
+  operator_id: &str,
+) -> Result<Option<ResetCodeRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT operator_id, code_hash, issued_by, issued_at, expires_at \
+     FROM password_reset_code WHERE operator_id = ?",
+  )
+  .bind(operator_id)
+  .fetch_optional(pool)
+  .await?;
+
+  Ok(row.map(|row| ResetCodeRow {
+    operator_id: row.get("operator_id"),
+    code_hash: row.get("code_hash"),
+    issued_by: row.get("issued_by"),
+    issued_at: row.get("issued_at"),
+    expires_at: row.get("expires_at"),
+  }))
+}
+
+pub async fn delete_reset_code(pool: &SqlitePool, operator_id: &str) -> Result<(), AppError> {
+  sqlx::query("DELETE FROM password_reset_code WHERE operator_id = ?")
+    .bind(operator_id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
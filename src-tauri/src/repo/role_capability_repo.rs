@@ -0,0 +1,58 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone)]
+pub struct RoleCapabilityRow {
+  pub role: String,
+  pub capability: String,
+  pub allowed: bool,
+  pub updated_at: i64,
+}
+
+pub async fn list_capabilities(pool: &SqlitePool) -> Result<Vec<RoleCapabilityRow>, AppError> {
+  let rows = sqlx::query("SELECT role, capability, allowed, updated_at FROM role_capability ORDER BY role, capability")
+    .fetch_all(pool)
+    .await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| RoleCapabilityRow {
+        role: row.get("role"),
+        capability: row.get("capability"),
+        allowed: row.get::<i64, _>("allowed") == 1,
+        updated_at: row.get("updated_at"),
+      })
+      .collect(),
+  )
+}
+
+/// 角色是否开放指定能力，未配置时返回 None
+pub async fn get_capability(pool: &SqlitePool, role: &str, capability: &str) -> Result<Option<bool>, AppError> {
+  let allowed: Option<(i64,)> = sqlx::query_as("SELECT allowed FROM role_capability WHERE role = ? AND capability = ?")
+    .bind(role)
+    .bind(capability)
+    .fetch_optional(pool)
+    .await?;
+  Ok(allowed.map(|(allowed,)| allowed == 1))
+}
+
+pub async fn set_capability(
+  pool: &SqlitePool,
+  role: &str,
+  capability: &str,
+  allowed: bool,
+  updated_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO role_capability (role, capability, allowed, updated_at) VALUES (?, ?, ?, ?) \
+     ON CONFLICT(role, capability) DO UPDATE SET allowed = excluded.allowed, updated_at = excluded.updated_at",
+  )
+  .bind(role)
+  .bind(capability)
+  .bind(if allowed { 1 } else { 0 })
+  .bind(updated_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,124 @@
+use sqlx::{Row, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct SessionRow {
+  pub id: String,
+  pub operator_id: String,
+  pub source: String,
+  pub created_at: i64,
+  pub last_active_at: i64,
+  pub expires_at: Option<i64>,
+  pub ended_at: Option<i64>,
+  pub end_reason: Option<String>,
+}
+
+pub async fn insert_session(pool: &SqlitePool, row: &SessionRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO operator_session (id, operator_id, source, created_at, last_active_at, expires_at, ended_at, end_reason) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.operator_id)
+  .bind(&row.source)
+  .bind(row.created_at)
+  .bind(row.last_active_at)
+  .bind(row.expires_at)
+  .bind(row.ended_at)
+  .bind(&row.end_reason)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_session_by_id(pool: &SqlitePool, id: &str) -> Result<Option<SessionRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, operator_id, source, created_at, last_active_at, expires_at, ended_at, end_reason \
+     FROM operator_session WHERE id = ?",
+  )
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+
+  Ok(row.map(|row| SessionRow {
+    id: row.get("id"),
+    operator_id: row.get("operator_id"),
+    source: row.get("source"),
+    created_at: row.get("created_at"),
+    last_active_at: row.get("last_active_at"),
+    expires_at: row.get("expires_at"),
+    ended_at: row.get("ended_at"),
+    end_reason: row.get("end_reason"),
+  }))
+}
+
+pub async fn end_session(
+  pool: &SqlitePool,
+  id: &str,
+  ended_at: i64,
+  end_reason: &str,
+) -> Result<bool, AppError> {
+  let result = sqlx::query(
+    "UPDATE operator_session SET ended_at = ?, end_reason = ? WHERE id = ? AND ended_at IS NULL",
+  )
+  .bind(ended_at)
+  .bind(end_reason)
+  .bind(id)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected() > 0)
+}
+
+/// 活跃会话（附操作人信息）
+#[derive(Debug, serde::Serialize)]
+pub struct ActiveSessionRow {
+  pub id: String,
+  pub operator_id: String,
+  pub username: String,
+  pub display_name: String,
+  pub role: String,
+  pub source: String,
+  pub created_at: i64,
+  pub last_active_at: i64,
+  pub expires_at: Option<i64>,
+}
+
+/// 未结束且未过期的会话，按最近活跃倒序
+pub async fn list_active_sessions(pool: &SqlitePool, now: i64) -> Result<Vec<ActiveSessionRow>, AppError> {
+  let rows = sqlx::query(
+    "SELECT s.id, s.operator_id, o.username, o.display_name, o.role, s.source, s.created_at, s.last_active_at, s.expires_at \
+     FROM operator_session s JOIN operator o ON o.id = s.operator_id \
+     WHERE s.ended_at IS NULL AND (s.expires_at IS NULL OR s.expires_at > ?) \
+     ORDER BY s.last_active_at DESC, s.id",
+  )
+  .bind(now)
+  .fetch_all(pool)
+  .await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| ActiveSessionRow {
+        id: row.get("id"),
+        operator_id: row.get("operator_id"),
+        username: row.get("username"),
+        display_name: row.get("display_name"),
+        role: row.get("role"),
+        source: row.get("source"),
+        created_at: row.get("created_at"),
+        last_active_at: row.get("last_active_at"),
+        expires_at: row.get("expires_at"),
+      })
+      .collect(),
+  )
+}
+
+pub async fn touch_session(pool: &SqlitePool, id: &str, last_active_at: i64) -> Result<(), AppError> {
+  sqlx::query("UPDATE operator_session SET last_active_at = ? WHERE id = ? AND ended_at IS NULL")
+    .bind(last_active_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
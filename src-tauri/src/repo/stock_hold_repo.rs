@@ -0,0 +1,220 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+use crate::domain::quantity;
+
+#[derive(Debug, Clone)]
+pub struct StockHoldRow {
+  pub id: String,
+  pub hold_no: String,
+  pub item_id: String,
+  pub slot_id: String,
+  pub qty: i64,
+  // damaged / pending_qc / other
+  pub reason: String,
+  pub note: Option<String>,
+  pub created_by: String,
+  pub created_at: i64,
+  pub released_at: Option<i64>,
+}
+
+/// 冻结记录列表行，数量为显示数量
+#[derive(Debug, serde::Serialize)]
+pub struct StockHoldListRow {
+  pub id: String,
+  pub hold_no: String,
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub qty: f64,
+  pub reason: String,
+  pub note: Option<String>,
+  pub created_by_name: Option<String>,
+  pub created_at: i64,
+  pub released_by_name: Option<String>,
+  pub released_at: Option<i64>,
+  pub release_note: Option<String>,
+}
+
+/// 库存行上冻结中的数量合计（存储值），供库存列表拼接
+pub const ACTIVE_HELD_QTY_SQL: &str = "(SELECT COALESCE(SUM(hold.qty), 0) FROM stock_hold AS hold \
+   WHERE hold.item_id = stock.item_id AND hold.slot_id = stock.slot_id AND hold.released_at IS NULL)";
+
+pub async fn insert_hold_tx(tx: &mut Transaction<'_, Sqlite>, row: &StockHoldRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO stock_hold (id, hold_no, item_id, slot_id, qty, reason, note, created_by, created_at) \
+     VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(&row.id)
+  .bind(&row.hold_no)
+  .bind(&row.item_id)
+  .bind(&row.slot_id)
+  .bind(row.qty)
+  .bind(&row.reason)
+  .bind(&row.note)
+  .bind(&row.created_by)
+  .bind(row.created_at)
+  .execute(&mut **tx)
+  .await?;
+  Ok(())
+}
+
+pub async fn get_hold(pool: &SqlitePool, id: &str) -> Result<Option<StockHoldRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, hold_no, item_id, slot_id, qty, reason, note, created_by, created_at, released_at \
+     FROM stock_hold WHERE id = ?",
+  )
+  .bind(id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.map(|row| StockHoldRow {
+    id: row.get("id"),
+    hold_no: row.get("hold_no"),
+    item_id: row.get("item_id"),
+    slot_id: row.get("slot_id"),
+    qty: row.get("qty"),
+    reason: row.get("reason"),
+    note: row.get("note"),
+    created_by: row.get("created_by"),
+    created_at: row.get("created_at"),
+    released_at: row.get("released_at"),
+  }))
+}
+
+/// 物品在库位上冻结中的数量合计（存储值）
+pub async fn held_qty_tx(tx: &mut Transaction<'_, Sqlite>, item_id: &str, slot_id: &str) -> Result<i64, AppError> {
+  let (qty,): (i64,) = sqlx::query_as(
+    "SELECT COALESCE(SUM(qty), 0) FROM stock_hold WHERE item_id = ? AND slot_id = ? AND released_at IS NULL",
+  )
+  .bind(item_id)
+  .bind(slot_id)
+  .fetch_one(&mut **tx)
+  .await?;
+  Ok(qty)
+}
+
+/// 事务内解除冻结（如报废冻结中的库存），仅更新冻结中的记录，返回更新条数
+pub async fn release_hold_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  released_by: &str,
+  released_at: i64,
+  release_note: Option<&str>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE stock_hold SET released_by = ?, released_at = ?, release_note = ? \
+     WHERE id = ? AND released_at IS NULL",
+  )
+  .bind(released_by)
+  .bind(released_at)
+  .bind(release_note)
+  .bind(id)
+  .execute(&mut **tx)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 解除冻结，仅更新冻结中的记录，返回更新条数
+pub async fn release_hold(
+  pool: &SqlitePool,
+  id: &str,
+  released_by: &str,
+  released_at: i64,
+  release_note: Option<&str>,
+) -> Result<u64, AppError> {
+  let result = sqlx::query(
+    "UPDATE stock_hold SET released_by = ?, released_at = ?, release_note = ? \
+     WHERE id = ? AND released_at IS NULL",
+  )
+  .bind(released_by)
+  .bind(released_at)
+  .bind(release_note)
+  .bind(id)
+  .execute(pool)
+  .await?;
+  Ok(result.rows_affected())
+}
+
+/// 冻结记录列表，可按物品、库位、仓库与仓库范围过滤；active_only 时仅返回冻结中的记录
+pub async fn list_holds(
+  pool: &SqlitePool,
+  item_id: Option<&str>,
+  slot_id: Option<&str>,
+  warehouse_id: Option<&str>,
+  active_only: bool,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  limit: i64,
+) -> Result<Vec<StockHoldListRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+    "SELECT hold.id, hold.hold_no, hold.item_id, item.item_code, item.name AS item_name, item.uom, \
+     hold.slot_id, slot.code AS slot_code, warehouse.id AS warehouse_id, warehouse.name AS warehouse_name, \
+     hold.qty / {scale} AS qty, hold.reason, hold.note, creator.display_name AS created_by_name, hold.created_at, \
+     releaser.display_name AS released_by_name, hold.released_at, hold.release_note \
+     FROM stock_hold AS hold \
+     JOIN item ON hold.item_id = item.id \
+     JOIN slot ON hold.slot_id = slot.id \
+     LEFT JOIN warehouse ON slot.warehouse_id = warehouse.id \
+     LEFT JOIN \"operator\" AS creator ON hold.created_by = creator.id \
+     LEFT JOIN \"operator\" AS releaser ON hold.released_by = releaser.id \
+     WHERE 1 = 1",
+    scale = quantity::scale_sql("item.qty_precision")
+  ));
+  if let Some(item_id) = item_id {
+    builder.push(" AND hold.item_id = ").push_bind(item_id.to_string());
+  }
+  if let Some(slot_id) = slot_id {
+    builder.push(" AND hold.slot_id = ").push_bind(slot_id.to_string());
+  }
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND slot.warehouse_id = ").push_bind(warehouse_id.to_string());
+  }
+  if active_only {
+    builder.push(" AND hold.released_at IS NULL");
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND slot.warehouse_id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id);
+      }
+      separated.push_unseparated(")");
+    }
+  }
+  builder
+    .push(" ORDER BY hold.released_at IS NOT NULL, hold.created_at DESC LIMIT ")
+    .push_bind(limit);
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| StockHoldListRow {
+        id: row.get("id"),
+        hold_no: row.get("hold_no"),
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        uom: row.get("uom"),
+        slot_id: row.get("slot_id"),
+        slot_code: row.get("slot_code"),
+        warehouse_id: row.get("warehouse_id"),
+        warehouse_name: row.get("warehouse_name"),
+        qty: row.get("qty"),
+        reason: row.get("reason"),
+        note: row.get("note"),
+        created_by_name: row.get("created_by_name"),
+        created_at: row.get("created_at"),
+        released_by_name: row.get("released_by_name"),
+        released_at: row.get("released_at"),
+        release_note: row.get("release_note"),
+      })
+      .collect(),
+  )
+}
@@ -1,6 +1,34 @@
-use sqlx::{Row, SqlitePool, QueryBuilder};
+use sqlx::{Row, Sqlite, SqlitePool, QueryBuilder};
 
 use crate::domain::errors::AppError;
+use crate::domain::quantity;
+use crate::domain::sort;
+use crate::repo::stock_hold_repo;
+
+/// 库存列表（按库位/按物品）可排序字段（sort_by → SQL 表达式）
+const STOCK_SORT_COLUMNS: &[(&str, &str)] = &[
+  ("warehouse_code", "warehouse.code"),
+  ("rack_code", "rack.code"),
+  ("slot_code", "slot.code"),
+  ("item_code", "item.item_code"),
+  ("item_name", "item.name"),
+  ("operator_name", "operator_name"),
+  ("qty", "qty"),
+];
+
+/// 物品在库位上最近一笔流水的记录人。移入、移出库位分别按 0035 的覆盖索引各取最新一条再比较，
+/// 避免 OR 条件导致每行扫描全部流水；分页时只对当页行求值
+const LAST_SLOT_OPERATOR_SQL: &str = "(SELECT op.display_name FROM ( \
+     SELECT * FROM (SELECT t.operator_id, t.occurred_at, t.created_at FROM txn AS t \
+       WHERE t.item_id = stock.item_id AND t.to_slot_id = stock.slot_id \
+       ORDER BY t.occurred_at DESC, t.created_at DESC LIMIT 1) \
+     UNION ALL \
+     SELECT * FROM (SELECT t.operator_id, t.occurred_at, t.created_at FROM txn AS t \
+       WHERE t.item_id = stock.item_id AND t.from_slot_id = stock.slot_id \
+       ORDER BY t.occurred_at DESC, t.created_at DESC LIMIT 1) \
+   ) AS last_txn \
+   JOIN \"operator\" AS op ON last_txn.operator_id = op.id \
+   ORDER BY last_txn.occurred_at DESC, last_txn.created_at DESC LIMIT 1)";
 
 #[derive(Debug, serde::Serialize)]
 pub struct StockBySlotRow {
@@ -16,7 +44,11 @@ pub struct StockBySlotRow {
   pub item_code: String,
   pub item_name: String,
   pub operator_name: Option<String>,
-  pub qty: i64,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+  // 冻结中的数量与可用数量（qty - held_qty）
+  pub held_qty: f64,
+  pub available_qty: f64,
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -33,7 +65,11 @@ pub struct StockByItemRow {
   pub item_code: String,
   pub item_name: String,
   pub operator_name: Option<String>,
-  pub qty: i64,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+  // 冻结中的数量与可用数量（qty - held_qty）
+  pub held_qty: f64,
+  pub available_qty: f64,
 }
 
 #[allow(unused_assignments)]
@@ -46,23 +82,27 @@ pub async fn list_stock_by_slot(
   slot_id: Option<String>,
   item_id: Option<String>,
   operator_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  sort_by: Option<&str>,
+  sort_dir: Option<&str>,
 ) -> Result<Vec<StockBySlotRow>, AppError> {
   let offset = (page_index - 1) * page_size;
-  let mut builder = QueryBuilder::new(
+  let order_by =
+    sort::order_by_clause(STOCK_SORT_COLUMNS, sort_by, sort_dir, "rack.code, slot.code")?;
+  let mut builder = QueryBuilder::new(format!(
     "SELECT warehouse.id AS warehouse_id, warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, rack.id AS rack_id, rack.code AS rack_code, \
      rack.name AS rack_name, slot.id AS slot_id, slot.code AS slot_code, \
      item.id AS item_id, item.item_code AS item_code, item.name AS item_name, \
-     (SELECT op.display_name FROM txn AS t \
-        JOIN \"operator\" AS op ON t.operator_id = op.id \
-        WHERE t.item_id = stock.item_id \
-          AND (t.to_slot_id = stock.slot_id OR t.from_slot_id = stock.slot_id) \
-        ORDER BY t.occurred_at DESC, t.created_at DESC LIMIT 1) AS operator_name, \
-     stock.qty AS qty FROM stock \
+     {last_operator} AS operator_name, \
+     stock.qty / {scale} AS qty, {held} / {scale} AS held_qty, (stock.qty - {held}) / {scale} AS available_qty FROM stock \
      JOIN slot ON stock.slot_id = slot.id \
      JOIN rack ON slot.rack_id = rack.id \
      LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
      JOIN item ON stock.item_id = item.id",
-  );
+    scale = quantity::scale_sql("item.qty_precision"),
+    held = stock_hold_repo::ACTIVE_HELD_QTY_SQL,
+    last_operator = LAST_SLOT_OPERATOR_SQL
+  ));
   let mut has_where = false;
   if let Some(wid) = warehouse_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
     builder.push(" WHERE warehouse.id = ");
@@ -91,7 +131,12 @@ pub async fn list_stock_by_slot(
     builder.push_bind(opid.to_string());
     builder.push(")");
   }
-  builder.push(" ORDER BY rack.code, slot.code LIMIT ");
+  if let Some(ids) = allowed_warehouse_ids {
+    if has_where { builder.push(" AND "); } else { builder.push(" WHERE "); has_where = true; }
+    push_warehouse_scope(&mut builder, &ids);
+  }
+  builder.push(order_by);
+  builder.push(" LIMIT ");
   builder.push_bind(page_size);
   builder.push(" OFFSET ");
   builder.push_bind(offset);
@@ -113,6 +158,8 @@ pub async fn list_stock_by_slot(
       item_name: row.get("item_name"),
       operator_name: row.get("operator_name"),
       qty: row.get("qty"),
+      held_qty: row.get("held_qty"),
+      available_qty: row.get("available_qty"),
     })
     .collect();
 
@@ -129,23 +176,27 @@ pub async fn list_stock_by_item_filtered(
   slot_id: Option<String>,
   item_id: Option<String>,
   operator_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  sort_by: Option<&str>,
+  sort_dir: Option<&str>,
 ) -> Result<Vec<StockByItemRow>, AppError> {
   let offset = (page_index - 1) * page_size;
-  let mut builder = QueryBuilder::new(
+  let order_by =
+    sort::order_by_clause(STOCK_SORT_COLUMNS, sort_by, sort_dir, "item.item_code, slot.code")?;
+  let mut builder = QueryBuilder::new(format!(
     "SELECT warehouse.id AS warehouse_id, warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, rack.id AS rack_id, rack.code AS rack_code, \
      rack.name AS rack_name, item.id AS item_id, item.item_code AS item_code, \
      item.name AS item_name, slot.id AS slot_id, slot.code AS slot_code, \
-     (SELECT op.display_name FROM txn AS t \
-        JOIN \"operator\" AS op ON t.operator_id = op.id \
-        WHERE t.item_id = stock.item_id \
-          AND (t.to_slot_id = stock.slot_id OR t.from_slot_id = stock.slot_id) \
-        ORDER BY t.occurred_at DESC, t.created_at DESC LIMIT 1) AS operator_name, \
-     stock.qty AS qty FROM stock \
+     {last_operator} AS operator_name, \
+     stock.qty / {scale} AS qty, {held} / {scale} AS held_qty, (stock.qty - {held}) / {scale} AS available_qty FROM stock \
      JOIN item ON stock.item_id = item.id \
      JOIN slot ON stock.slot_id = slot.id \
      JOIN rack ON slot.rack_id = rack.id \
      LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id",
-  );
+    scale = quantity::scale_sql("item.qty_precision"),
+    held = stock_hold_repo::ACTIVE_HELD_QTY_SQL,
+    last_operator = LAST_SLOT_OPERATOR_SQL
+  ));
   let mut has_where = false;
   if let Some(wid) = warehouse_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
     builder.push(" WHERE warehouse.id = ");
@@ -174,7 +225,12 @@ pub async fn list_stock_by_item_filtered(
     builder.push_bind(opid.to_string());
     builder.push(")");
   }
-  builder.push(" ORDER BY item.item_code, slot.code LIMIT ");
+  if let Some(ids) = allowed_warehouse_ids {
+    if has_where { builder.push(" AND "); } else { builder.push(" WHERE "); has_where = true; }
+    push_warehouse_scope(&mut builder, &ids);
+  }
+  builder.push(order_by);
+  builder.push(" LIMIT ");
   builder.push_bind(page_size);
   builder.push(" OFFSET ");
   builder.push_bind(offset);
@@ -196,6 +252,8 @@ pub async fn list_stock_by_item_filtered(
       item_name: row.get("item_name"),
       operator_name: row.get("operator_name"),
       qty: row.get("qty"),
+      held_qty: row.get("held_qty"),
+      available_qty: row.get("available_qty"),
     })
     .collect();
 
@@ -205,23 +263,22 @@ pub async fn list_stock_by_item_filtered(
 pub async fn list_stock_by_slot_all(
   pool: &SqlitePool,
 ) -> Result<Vec<StockBySlotRow>, AppError> {
-  let rows = sqlx::query(
+  let rows = sqlx::query(&format!(
     "SELECT  warehouse.id AS warehouse_id, warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, rack.id AS rack_id, rack.code AS rack_code, \
      rack.name AS rack_name, slot.id AS slot_id, slot.code AS slot_code, \
      item.id AS item_id, item.item_code AS item_code,  item.name AS item_name, \
-     (SELECT op.display_name FROM txn AS t \
-        JOIN \"operator\" AS op ON t.operator_id = op.id \
-        WHERE t.item_id = stock.item_id \
-          AND (t.to_slot_id = stock.slot_id OR t.from_slot_id = stock.slot_id) \
-        ORDER BY t.occurred_at DESC, t.created_at DESC LIMIT 1) AS operator_name, \
-     stock.qty AS qty \
+     {last_operator} AS operator_name, \
+     stock.qty / {scale} AS qty, {held} / {scale} AS held_qty, (stock.qty - {held}) / {scale} AS available_qty \
      FROM stock \
      JOIN slot ON stock.slot_id = slot.id \
      JOIN rack ON slot.rack_id = rack.id \
      LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
      JOIN item ON stock.item_id = item.id \
      ORDER BY rack.code, slot.code",
-  )
+    scale = quantity::scale_sql("item.qty_precision"),
+    held = stock_hold_repo::ACTIVE_HELD_QTY_SQL,
+    last_operator = LAST_SLOT_OPERATOR_SQL
+  ))
   .fetch_all(pool)
   .await?;
 
@@ -241,6 +298,8 @@ pub async fn list_stock_by_slot_all(
       item_name: row.get("item_name"),
       operator_name: row.get("operator_name"),
       qty: row.get("qty"),
+      held_qty: row.get("held_qty"),
+      available_qty: row.get("available_qty"),
     })
     .collect();
 
@@ -253,23 +312,22 @@ pub async fn list_stock_by_item(
   page_size: i64,
 ) -> Result<Vec<StockByItemRow>, AppError> {
   let offset = (page_index - 1) * page_size;
-  let rows = sqlx::query(
+  let rows = sqlx::query(&format!(
     "SELECT  warehouse.id AS warehouse_id, warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, rack.id AS rack_id, rack.code AS rack_code, \
      rack.name AS rack_name, item.id AS item_id, item.item_code AS item_code, \
      item.name AS item_name, slot.id AS slot_id, slot.code AS slot_code, \
-     (SELECT op.display_name FROM txn AS t \
-        JOIN \"operator\" AS op ON t.operator_id = op.id \
-        WHERE t.item_id = stock.item_id \
-          AND (t.to_slot_id = stock.slot_id OR t.from_slot_id = stock.slot_id) \
-        ORDER BY t.occurred_at DESC, t.created_at DESC LIMIT 1) AS operator_name, \
-     stock.qty AS qty \
+     {last_operator} AS operator_name, \
+     stock.qty / {scale} AS qty, {held} / {scale} AS held_qty, (stock.qty - {held}) / {scale} AS available_qty \
      FROM stock \
      JOIN item ON stock.item_id = item.id \
      JOIN slot ON stock.slot_id = slot.id \
      JOIN rack ON slot.rack_id = rack.id \
      LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
      ORDER BY item.item_code, slot.code LIMIT ? OFFSET ?",
-  )
+    scale = quantity::scale_sql("item.qty_precision"),
+    held = stock_hold_repo::ACTIVE_HELD_QTY_SQL,
+    last_operator = LAST_SLOT_OPERATOR_SQL
+  ))
   .bind(page_size)
   .bind(offset)
   .fetch_all(pool)
@@ -291,6 +349,8 @@ pub async fn list_stock_by_item(
       item_name: row.get("item_name"),
       operator_name: row.get("operator_name"),
       qty: row.get("qty"),
+      held_qty: row.get("held_qty"),
+      available_qty: row.get("available_qty"),
     })
     .collect();
 
@@ -331,6 +391,7 @@ pub async fn count_stock_by_slot_filtered(
   slot_id: Option<String>,
   item_id: Option<String>,
   operator_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
 ) -> Result<i64, AppError> {
   let mut builder = QueryBuilder::new("SELECT COUNT(1) FROM stock JOIN slot ON stock.slot_id = slot.id JOIN rack ON slot.rack_id = rack.id JOIN item ON stock.item_id = item.id LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id");
   let mut has_where = false;
@@ -356,6 +417,10 @@ pub async fn count_stock_by_slot_filtered(
     builder.push_bind(opid.to_string());
     builder.push(")");
   }
+  if let Some(ids) = allowed_warehouse_ids {
+    if has_where { builder.push(" AND "); } else { builder.push(" WHERE "); has_where = true; }
+    push_warehouse_scope(&mut builder, &ids);
+  }
   let (count,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
   Ok(count)
 }
@@ -369,6 +434,7 @@ pub async fn count_stock_by_item_filtered(
   slot_id: Option<String>,
   item_id: Option<String>,
   operator_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
 ) -> Result<i64, AppError> {
   let mut builder = QueryBuilder::new("SELECT COUNT(1) FROM stock JOIN item ON stock.item_id = item.id JOIN slot ON stock.slot_id = slot.id JOIN rack ON slot.rack_id = rack.id LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id");
   let mut has_where = false;
@@ -394,6 +460,241 @@ pub async fn count_stock_by_item_filtered(
     builder.push_bind(opid.to_string());
     builder.push(")");
   }
+  if let Some(ids) = allowed_warehouse_ids {
+    if has_where { builder.push(" AND "); } else { builder.push(" WHERE "); has_where = true; }
+    push_warehouse_scope(&mut builder, &ids);
+  }
   let (count,): (i64,) = builder.build_query_as().fetch_one(pool).await?;
   Ok(count)
 }
+
+// 仓库范围限制，空列表表示无可访问仓库
+#[derive(Debug)]
+pub struct ItemStockQtyRow {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  // 物品当前成本，无入库记录时作为计价回退
+  pub unit_cost: Option<f64>,
+  pub currency: Option<String>,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+}
+
+/// 按物品汇总库存数量（计价用），可按仓库与可访问范围过滤
+pub async fn list_stock_qty_by_item(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<ItemStockQtyRow>, AppError> {
+  let mut builder = QueryBuilder::new(format!(
+    "SELECT item.id AS item_id, item.item_code AS item_code, item.name AS item_name, \
+     item.unit_cost AS unit_cost, item.currency AS currency, SUM(stock.qty) / {} AS qty FROM stock \
+     JOIN item ON stock.item_id = item.id \
+     JOIN slot ON stock.slot_id = slot.id \
+     JOIN rack ON slot.rack_id = rack.id \
+     LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
+     WHERE stock.qty <> 0",
+    quantity::scale_sql("item.qty_precision")
+  ));
+  if let Some(wid) = warehouse_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND warehouse.id = ");
+    builder.push_bind(wid.to_string());
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    builder.push(" AND ");
+    push_warehouse_scope(&mut builder, &ids);
+  }
+  builder.push(" GROUP BY item.id, item.item_code, item.name, item.unit_cost, item.currency ORDER BY item.item_code");
+  let rows = builder.build().fetch_all(pool).await?;
+
+  let items = rows
+    .into_iter()
+    .map(|row| ItemStockQtyRow {
+      item_id: row.get("item_id"),
+      item_code: row.get("item_code"),
+      item_name: row.get("item_name"),
+      unit_cost: row.get("unit_cost"),
+      currency: row.get("currency"),
+      qty: row.get::<Option<f64>, _>("qty").unwrap_or(0.0),
+    })
+    .collect();
+
+  Ok(items)
+}
+
+/// 盘点表行：库位及其中的物品，空库位的物品字段为空
+#[derive(Debug)]
+pub struct CountSheetRow {
+  pub warehouse_code: Option<String>,
+  pub rack_code: String,
+  pub slot_code: String,
+  pub item_code: Option<String>,
+  pub item_name: Option<String>,
+  pub uom: Option<String>,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+}
+
+/// 按仓库/货架列出启用库位内的库存（按货架、层、位排序），include_empty_slots 时包含空库位
+pub async fn list_count_sheet_rows(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  rack_id: Option<String>,
+  include_empty_slots: bool,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<CountSheetRow>, AppError> {
+  let mut builder = QueryBuilder::new(format!(
+    "SELECT warehouse.code AS warehouse_code, rack.code AS rack_code, slot.code AS slot_code, \
+     item.item_code AS item_code, item.name AS item_name, item.uom AS uom, \
+     COALESCE(stock.qty, 0) / {} AS qty FROM slot \
+     JOIN rack ON slot.rack_id = rack.id \
+     LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
+     LEFT JOIN stock ON stock.slot_id = slot.id AND stock.qty <> 0 \
+     LEFT JOIN item ON stock.item_id = item.id \
+     WHERE slot.status = 'active' AND rack.status = 'active'",
+    quantity::scale_sql("item.qty_precision")
+  ));
+  if let Some(wid) = warehouse_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND warehouse.id = ");
+    builder.push_bind(wid.to_string());
+  }
+  if let Some(rid) = rack_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND rack.id = ");
+    builder.push_bind(rid.to_string());
+  }
+  if !include_empty_slots {
+    builder.push(" AND stock.item_id IS NOT NULL");
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    builder.push(" AND ");
+    push_warehouse_scope(&mut builder, &ids);
+  }
+  builder.push(" ORDER BY rack.code, slot.level_no, slot.slot_no, item.item_code");
+  let rows = builder.build().fetch_all(pool).await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| CountSheetRow {
+        warehouse_code: row.get("warehouse_code"),
+        rack_code: row.get("rack_code"),
+        slot_code: row.get("slot_code"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        uom: row.get("uom"),
+        qty: row.get("qty"),
+      })
+      .collect(),
+  )
+}
+
+fn push_warehouse_scope(builder: &mut QueryBuilder<Sqlite>, ids: &[String]) {
+  if ids.is_empty() {
+    builder.push("1 = 0");
+    return;
+  }
+  builder.push("warehouse.id IN (");
+  let mut separated = builder.separated(", ");
+  for id in ids {
+    separated.push_bind(id.clone());
+  }
+  separated.push_unseparated(")");
+}
+
+/// 拣货/上架单中的库位（含仓库/货架编码与层、位序号，用于按巡库路线排序）
+#[derive(Debug, Clone)]
+pub struct PickSlotRow {
+  pub slot_id: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub rack_code: String,
+  pub slot_code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  // 该物品在库位中的库存（基本单位）
+  pub qty: i64,
+}
+
+const PICK_SLOT_SELECT: &str = "SELECT slot.id AS slot_id, warehouse.id AS warehouse_id, \
+   warehouse.code AS warehouse_code, rack.code AS rack_code, slot.code AS slot_code, \
+   slot.level_no AS level_no, slot.slot_no AS slot_no, COALESCE(stock.qty, 0) AS qty FROM slot \
+   JOIN rack ON slot.rack_id = rack.id \
+   LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id";
+
+fn map_pick_slot_row(row: &sqlx::sqlite::SqliteRow) -> PickSlotRow {
+  PickSlotRow {
+    slot_id: row.get("slot_id"),
+    warehouse_id: row.get("warehouse_id"),
+    warehouse_code: row.get("warehouse_code"),
+    rack_code: row.get("rack_code"),
+    slot_code: row.get("slot_code"),
+    level_no: row.get("level_no"),
+    slot_no: row.get("slot_no"),
+    qty: row.get("qty"),
+  }
+}
+
+/// 列出存放该物品（库存大于 0）的启用库位，按仓库、货架、层、位排序
+pub async fn list_pick_slots_for_item(
+  pool: &SqlitePool,
+  item_id: &str,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<PickSlotRow>, AppError> {
+  let mut builder = QueryBuilder::new(format!(
+    "{} JOIN stock ON stock.slot_id = slot.id AND stock.qty > 0 \
+     WHERE slot.status = 'active' AND rack.status = 'active' AND stock.item_id = ",
+    PICK_SLOT_SELECT
+  ));
+  builder.push_bind(item_id.to_string());
+  if let Some(wid) = warehouse_id.as_ref().map(|s| s.trim()).filter(|s| !s.is_empty()) {
+    builder.push(" AND warehouse.id = ");
+    builder.push_bind(wid.to_string());
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    builder.push(" AND ");
+    push_warehouse_scope(&mut builder, &ids);
+  }
+  builder.push(" ORDER BY warehouse.code, rack.code, slot.level_no, slot.slot_no");
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(rows.iter().map(map_pick_slot_row).collect())
+}
+
+/// 按库位编码查询库位及该物品在其中的库存
+pub async fn get_pick_slot(
+  pool: &SqlitePool,
+  slot_code: &str,
+  item_id: &str,
+) -> Result<Option<PickSlotRow>, AppError> {
+  let row = sqlx::query(&format!(
+    "{} LEFT JOIN stock ON stock.slot_id = slot.id AND stock.item_id = ? WHERE slot.code = ?",
+    PICK_SLOT_SELECT
+  ))
+  .bind(item_id)
+  .bind(slot_code)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.as_ref().map(map_pick_slot_row))
+}
+
+/// 低库存物品：启用物品中有库存记录且合计显示数量低于 threshold 的物品，返回 (物品 ID, 编码, 名称, 显示数量)
+pub async fn list_low_stock_items(
+  pool: &SqlitePool,
+  threshold: i64,
+) -> Result<Vec<(String, String, String, f64)>, AppError> {
+  let scale = quantity::scale_sql("item.qty_precision");
+  let rows: Vec<(String, String, String, f64)> = sqlx::query_as(&format!(
+    "SELECT item.id, item.item_code, item.name, SUM(stock.qty) / {scale} AS qty FROM item \
+     JOIN stock ON stock.item_id = item.id \
+     WHERE item.status = 'active' \
+     GROUP BY item.id, item.item_code, item.name, item.qty_precision \
+     HAVING SUM(stock.qty) < ? * {scale} \
+     ORDER BY item.item_code",
+    scale = scale
+  ))
+  .bind(threshold)
+  .fetch_all(pool)
+  .await?;
+  Ok(rows)
+}
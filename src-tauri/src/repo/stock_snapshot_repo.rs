@@ -0,0 +1,210 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+use crate::domain::quantity;
+
+// 指定时间（含）之后的流水对各物品/库位的库存变动，口径与健康检查的流水推算一致；
+// since 为内部计算的时间戳，直接拼入 SQL
+fn legs_since_sql(since: i64) -> String {
+  format!(
+    "SELECT item_id, slot_id, SUM(delta) AS delta FROM (\
+     SELECT txn.item_id, txn.from_slot_id AS slot_id, txn.qty * CASE \
+     WHEN txn.\"type\" IN ('OUT', 'MOVE', 'SCRAP') THEN -1 \
+     WHEN txn.\"type\" = 'ADJUST' THEN 1 \
+     WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" IN ('OUT', 'MOVE', 'SCRAP') THEN 1 \
+     WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" = 'ADJUST' THEN -1 \
+     ELSE 0 END AS delta \
+     FROM txn LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+     WHERE txn.from_slot_id IS NOT NULL AND txn.occurred_at >= {since} \
+     UNION ALL \
+     SELECT txn.item_id, txn.to_slot_id AS slot_id, txn.qty * CASE \
+     WHEN txn.\"type\" IN ('IN', 'MOVE', 'OPENING') THEN 1 \
+     WHEN txn.\"type\" = 'REVERSAL' AND ref.\"type\" IN ('IN', 'MOVE', 'OPENING') THEN -1 \
+     ELSE 0 END AS delta \
+     FROM txn LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+     WHERE txn.to_slot_id IS NOT NULL AND txn.occurred_at >= {since}\
+     ) AS leg GROUP BY item_id, slot_id",
+    since = since
+  )
+}
+
+// 按当前库存回推到 since 之前的库存
+fn stock_before_sql(since: i64) -> String {
+  format!(
+    "SELECT item_id, slot_id, SUM(qty) AS qty FROM (\
+     SELECT item_id, slot_id, qty FROM stock \
+     UNION ALL \
+     SELECT item_id, slot_id, -delta AS qty FROM ({legs})\
+     ) GROUP BY item_id, slot_id",
+    legs = legs_since_sql(since)
+  )
+}
+
+/// 某日日结的库存明细（数量为存储值）
+#[derive(Debug)]
+pub struct StockAsofRow {
+  pub item_id: String,
+  pub item_code: Option<String>,
+  pub item_name: Option<String>,
+  pub qty_precision: i64,
+  pub unit_cost: Option<f64>,
+  pub slot_id: String,
+  pub slot_code: Option<String>,
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub qty: i64,
+}
+
+/// 日结合计（数量与金额为显示值）
+#[derive(Debug, Default)]
+pub struct SnapshotTotals {
+  pub total_qty: f64,
+  pub total_value: f64,
+}
+
+/// 最近一次日结的日期（YYYY-MM-DD）
+pub async fn last_close_date(pool: &SqlitePool) -> Result<Option<String>, AppError> {
+  let (date,): (Option<String>,) = sqlx::query_as("SELECT MAX(close_date) FROM stock_close")
+    .fetch_one(pool)
+    .await?;
+  Ok(date)
+}
+
+pub async fn has_close(pool: &SqlitePool, close_date: &str) -> Result<bool, AppError> {
+  let (count,): (i64,) = sqlx::query_as("SELECT COUNT(1) FROM stock_close WHERE close_date = ?")
+    .bind(close_date)
+    .fetch_one(pool)
+    .await?;
+  Ok(count > 0)
+}
+
+/// 生成某日日结快照：当前库存减去 before 之后发生的流水变动，在同一事务内替换该日已有快照；
+/// 返回快照行数
+pub async fn write_close(
+  pool: &SqlitePool,
+  close_date: &str,
+  before: i64,
+  closed_at: i64,
+) -> Result<i64, AppError> {
+  let mut tx = pool.begin().await?;
+  sqlx::query("DELETE FROM stock_snapshot WHERE snapshot_date = ?")
+    .bind(close_date)
+    .execute(&mut *tx)
+    .await?;
+  let result = sqlx::query(&format!(
+    "INSERT INTO stock_snapshot (snapshot_date, item_id, slot_id, qty) \
+     SELECT ?, item_id, slot_id, qty FROM ({source}) WHERE qty <> 0",
+    source = stock_before_sql(before)
+  ))
+  .bind(close_date)
+  .execute(&mut *tx)
+  .await?;
+  let row_count = result.rows_affected() as i64;
+  sqlx::query(
+    "INSERT INTO stock_close (close_date, row_count, closed_at) VALUES (?, ?, ?) \
+     ON CONFLICT(close_date) DO UPDATE SET row_count = excluded.row_count, closed_at = excluded.closed_at",
+  )
+  .bind(close_date)
+  .bind(row_count)
+  .bind(closed_at)
+  .execute(&mut *tx)
+  .await?;
+  tx.commit().await?;
+  Ok(row_count)
+}
+
+/// 某日日结的库存明细：有快照时读快照，否则按当前库存回推（before 为次日零点）
+pub async fn list_stock_asof(
+  pool: &SqlitePool,
+  snapshot_date: Option<&str>,
+  before: i64,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<Vec<StockAsofRow>, AppError> {
+  let mut builder = QueryBuilder::<Sqlite>::new(
+    "SELECT asof.item_id, asof.slot_id, asof.qty, item.item_code, item.name AS item_name, \
+     COALESCE(item.qty_precision, 0) AS qty_precision, item.unit_cost, slot.code AS slot_code, \
+     warehouse.id AS warehouse_id, warehouse.code AS warehouse_code, warehouse.name AS warehouse_name FROM (",
+  );
+  push_asof_source(&mut builder, snapshot_date, before);
+  builder.push(
+    ") AS asof \
+     LEFT JOIN item ON asof.item_id = item.id \
+     LEFT JOIN slot ON asof.slot_id = slot.id \
+     LEFT JOIN rack ON slot.rack_id = rack.id \
+     LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
+     WHERE asof.qty <> 0",
+  );
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND warehouse.id = ");
+    builder.push_bind(warehouse_id.to_string());
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND warehouse.id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id.clone());
+      }
+      separated.push_unseparated(")");
+    }
+  }
+  builder.push(" ORDER BY warehouse.code, item.item_code, slot.code");
+  let rows = builder.build().fetch_all(pool).await?;
+
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| StockAsofRow {
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        qty_precision: row.get("qty_precision"),
+        unit_cost: row.get("unit_cost"),
+        slot_id: row.get("slot_id"),
+        slot_code: row.get("slot_code"),
+        warehouse_id: row.get("warehouse_id"),
+        warehouse_code: row.get("warehouse_code"),
+        warehouse_name: row.get("warehouse_name"),
+        qty: row.get("qty"),
+      })
+      .collect(),
+  )
+}
+
+/// 某日日结快照的库存合计；该日未日结时返回 None
+pub async fn sum_snapshot(pool: &SqlitePool, snapshot_date: &str) -> Result<Option<SnapshotTotals>, AppError> {
+  if !has_close(pool, snapshot_date).await? {
+    return Ok(None);
+  }
+  let (total_qty, total_value): (Option<f64>, Option<f64>) = sqlx::query_as(&format!(
+    "SELECT SUM(stock_snapshot.qty / {scale}), \
+     SUM(stock_snapshot.qty / {scale} * COALESCE(item.unit_cost, 0)) \
+     FROM stock_snapshot JOIN item ON stock_snapshot.item_id = item.id \
+     WHERE stock_snapshot.snapshot_date = ?",
+    scale = quantity::scale_sql("item.qty_precision")
+  ))
+  .bind(snapshot_date)
+  .fetch_one(pool)
+  .await?;
+  Ok(Some(SnapshotTotals {
+    total_qty: total_qty.unwrap_or(0.0),
+    total_value: total_value.unwrap_or(0.0),
+  }))
+}
+
+
+fn push_asof_source(builder: &mut QueryBuilder<Sqlite>, snapshot_date: Option<&str>, before: i64) {
+  match snapshot_date {
+    Some(snapshot_date) => {
+      builder.push("SELECT item_id, slot_id, qty FROM stock_snapshot WHERE snapshot_date = ");
+      builder.push_bind(snapshot_date.to_string());
+    }
+    None => {
+      builder.push(stock_before_sql(before));
+    }
+  }
+}
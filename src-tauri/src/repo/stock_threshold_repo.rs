@@ -0,0 +1,121 @@
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+use crate::domain::quantity;
+
+/// 物品分仓阈值及该仓库当前库存合计，数量为显示数量
+#[derive(Debug, serde::Serialize)]
+pub struct StockThresholdRow {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub warehouse_id: String,
+  pub warehouse_code: String,
+  pub warehouse_name: String,
+  pub min_qty: f64,
+  // 为空表示不限上限
+  pub max_qty: Option<f64>,
+  pub current_qty: f64,
+  pub updated_by_name: Option<String>,
+  pub updated_at: i64,
+}
+
+pub async fn upsert_threshold(
+  pool: &SqlitePool,
+  item_id: &str,
+  warehouse_id: &str,
+  min_qty: i64,
+  max_qty: Option<i64>,
+  updated_by: &str,
+  updated_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO stock_threshold (item_id, warehouse_id, min_qty, max_qty, updated_by, updated_at) \
+     VALUES (?, ?, ?, ?, ?, ?) \
+     ON CONFLICT(item_id, warehouse_id) DO UPDATE SET \
+     min_qty = excluded.min_qty, max_qty = excluded.max_qty, \
+     updated_by = excluded.updated_by, updated_at = excluded.updated_at",
+  )
+  .bind(item_id)
+  .bind(warehouse_id)
+  .bind(min_qty)
+  .bind(max_qty)
+  .bind(updated_by)
+  .bind(updated_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 删除阈值，返回删除条数
+pub async fn delete_threshold(pool: &SqlitePool, item_id: &str, warehouse_id: &str) -> Result<u64, AppError> {
+  let result = sqlx::query("DELETE FROM stock_threshold WHERE item_id = ? AND warehouse_id = ?")
+    .bind(item_id)
+    .bind(warehouse_id)
+    .execute(pool)
+    .await?;
+  Ok(result.rows_affected())
+}
+
+/// 阈值列表（含仓库当前库存合计），可按物品、仓库与仓库范围过滤
+pub async fn list_thresholds(
+  pool: &SqlitePool,
+  item_id: Option<&str>,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<StockThresholdRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(format!(
+    "SELECT threshold.item_id, item.item_code, item.name AS item_name, item.uom, threshold.warehouse_id, \
+     warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, \
+     threshold.min_qty / {scale} AS min_qty, threshold.max_qty / {scale} AS max_qty, \
+     (SELECT COALESCE(SUM(stock.qty), 0) FROM stock JOIN slot ON stock.slot_id = slot.id \
+        WHERE stock.item_id = threshold.item_id AND slot.warehouse_id = threshold.warehouse_id) / {scale} AS current_qty, \
+     op.display_name AS updated_by_name, threshold.updated_at \
+     FROM stock_threshold AS threshold \
+     JOIN item ON threshold.item_id = item.id \
+     JOIN warehouse ON threshold.warehouse_id = warehouse.id \
+     LEFT JOIN \"operator\" AS op ON threshold.updated_by = op.id \
+     WHERE 1 = 1",
+    scale = quantity::scale_sql("item.qty_precision")
+  ));
+  if let Some(item_id) = item_id {
+    builder.push(" AND threshold.item_id = ").push_bind(item_id.to_string());
+  }
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND threshold.warehouse_id = ").push_bind(warehouse_id.to_string());
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND threshold.warehouse_id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id);
+      }
+      separated.push_unseparated(")");
+    }
+  }
+  builder.push(" ORDER BY item.item_code, warehouse.code");
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| StockThresholdRow {
+        item_id: row.get("item_id"),
+        item_code: row.get("item_code"),
+        item_name: row.get("item_name"),
+        uom: row.get("uom"),
+        warehouse_id: row.get("warehouse_id"),
+        warehouse_code: row.get("warehouse_code"),
+        warehouse_name: row.get("warehouse_name"),
+        min_qty: row.get("min_qty"),
+        max_qty: row.get("max_qty"),
+        current_qty: row.get("current_qty"),
+        updated_by_name: row.get("updated_by_name"),
+        updated_at: row.get("updated_at"),
+      })
+      .collect(),
+  )
+}
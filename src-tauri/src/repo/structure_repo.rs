@@ -0,0 +1,147 @@
+// 仓库结构树查询：仓库、货架（含库位数）与库位的平铺列表，由服务层组装为嵌套结构
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, Clone)]
+pub struct TreeWarehouseRow {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  pub status: String,
+  pub kind: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeRackRow {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  pub warehouse_id: Option<String>,
+  pub status: String,
+  pub slot_count: i64,
+  pub active_slot_count: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct TreeSlotRow {
+  pub id: String,
+  pub rack_id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub status: String,
+}
+
+pub async fn list_warehouses(
+  pool: &SqlitePool,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<Vec<TreeWarehouseRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> =
+    QueryBuilder::new("SELECT id, code, name, status, kind FROM warehouse WHERE 1 = 1");
+  push_scope(&mut builder, "warehouse.id", warehouse_id, allowed_warehouse_ids);
+  builder.push(" ORDER BY code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| TreeWarehouseRow {
+        id: row.get("id"),
+        code: row.get("code"),
+        name: row.get("name"),
+        status: row.get("status"),
+        kind: row.get("kind"),
+      })
+      .collect(),
+  )
+}
+
+/// 货架及其库位数；未限定仓库且不限仓库范围时包含未分配仓库的货架
+pub async fn list_racks(
+  pool: &SqlitePool,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<Vec<TreeRackRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT rack.id, rack.code, rack.name, rack.warehouse_id, rack.status, \
+     COUNT(slot.id) AS slot_count, \
+     SUM(CASE WHEN slot.status = 'active' THEN 1 ELSE 0 END) AS active_slot_count \
+     FROM rack \
+     LEFT JOIN slot ON slot.rack_id = rack.id \
+     WHERE 1 = 1",
+  );
+  push_scope(&mut builder, "rack.warehouse_id", warehouse_id, allowed_warehouse_ids);
+  builder.push(" GROUP BY rack.id ORDER BY rack.code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| TreeRackRow {
+        id: row.get("id"),
+        code: row.get("code"),
+        name: row.get("name"),
+        warehouse_id: row.get("warehouse_id"),
+        status: row.get("status"),
+        slot_count: row.get("slot_count"),
+        active_slot_count: row.get::<Option<i64>, _>("active_slot_count").unwrap_or(0),
+      })
+      .collect(),
+  )
+}
+
+/// 库位按所属货架的仓库过滤，与货架列表口径一致
+pub async fn list_slots(
+  pool: &SqlitePool,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<&[String]>,
+) -> Result<Vec<TreeSlotRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT slot.id, slot.rack_id, slot.code, slot.level_no, slot.slot_no, slot.status \
+     FROM slot \
+     JOIN rack ON slot.rack_id = rack.id \
+     WHERE 1 = 1",
+  );
+  push_scope(&mut builder, "rack.warehouse_id", warehouse_id, allowed_warehouse_ids);
+  builder.push(" ORDER BY slot.level_no, slot.slot_no, slot.code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+  Ok(
+    rows
+      .into_iter()
+      .map(|row| TreeSlotRow {
+        id: row.get("id"),
+        rack_id: row.get("rack_id"),
+        code: row.get("code"),
+        level_no: row.get("level_no"),
+        slot_no: row.get("slot_no"),
+        status: row.get("status"),
+      })
+      .collect(),
+  )
+}
+
+fn push_scope(
+  builder: &mut QueryBuilder<Sqlite>,
+  column: &str,
+  warehouse_id: Option<&str>,
+  allowed_warehouse_ids: Option<&[String]>,
+) {
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(format!(" AND {} = ", column)).push_bind(warehouse_id.to_string());
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(format!(" AND {} IN (", column));
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id.clone());
+      }
+      separated.push_unseparated(")");
+    }
+  }
+}
@@ -2,6 +2,23 @@
 use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::domain::sort;
+
+/// 流水列表可排序字段（sort_by → SQL 表达式），数量按显示数量排序
+const TXN_SORT_COLUMNS: &[(&str, &str)] = &[
+    ("occurred_at", "txn.occurred_at"),
+    ("created_at", "txn.created_at"),
+    ("txn_no", "txn.txn_no"),
+    ("txn_type", "txn.\"type\""),
+    ("item_code", "it.item_code"),
+    ("item_name", "it.name"),
+    ("qty", "qty"),
+    ("operator_name", "op.display_name"),
+    ("from_slot_code", "fs.code"),
+    ("to_slot_code", "ts.code"),
+];
 
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct TxnRow {
@@ -18,6 +35,14 @@ pub struct TxnRow {
     pub actual_qty: Option<i64>,
     pub ref_txn_id: Option<String>,
     pub note: Option<String>,
+    // 入库单位成本（仅入库流水记录）
+    pub unit_cost: Option<f64>,
+    // 粗略位置（纬度,经度），仅移动端开启采集时记录
+    pub location: Option<String>,
+    // 到期日（入库时录入，移库时继承移出部分最早的到期日）
+    pub expires_at: Option<i64>,
+    // 原因（reason_code.id），调整、冲正、报废流水记录
+    pub reason_id: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Serialize)]
@@ -36,8 +61,18 @@ pub struct TxnListRow {
     pub from_slot_code: Option<String>,
     pub to_slot_id: Option<String>,
     pub to_slot_code: Option<String>,
-    pub qty: i64,
-    pub actual_qty: Option<i64>,
+    // 来源/目标库位所属货架与仓库；仓库优先取货架所属仓库，未挂货架时取库位自身的仓库
+    pub from_rack_id: Option<String>,
+    pub from_rack_name: Option<String>,
+    pub from_warehouse_id: Option<String>,
+    pub from_warehouse_name: Option<String>,
+    pub to_rack_id: Option<String>,
+    pub to_rack_name: Option<String>,
+    pub to_warehouse_id: Option<String>,
+    pub to_warehouse_name: Option<String>,
+    // 显示数量（按物品精度换算）
+    pub qty: f64,
+    pub actual_qty: Option<f64>,
     pub ref_txn_id: Option<String>,
     pub has_reversal: bool,
     pub ref_txn_no: Option<String>,
@@ -50,11 +85,20 @@ pub struct TxnListRow {
     pub ref_from_slot_code: Option<String>,
     pub ref_to_slot_id: Option<String>,
     pub ref_to_slot_code: Option<String>,
-    pub ref_qty: Option<i64>,
-    pub ref_actual_qty: Option<i64>,
+    pub ref_qty: Option<f64>,
+    pub ref_actual_qty: Option<f64>,
     pub ref_occurred_at: Option<i64>,
     pub ref_note: Option<String>,
     pub note: Option<String>,
+    pub location: Option<String>,
+    // 原因（调整、冲正、报废流水），reason_code 为原因编码
+    pub reason_id: Option<String>,
+    pub reason_code: Option<String>,
+    pub reason_name: Option<String>,
+    // 经拣货确认生成的出库流水：拣货单号、计划数量（显示数量）与短拣原因，qty 为实拣数量
+    pub pick_no: Option<String>,
+    pub planned_qty: Option<f64>,
+    pub short_reason: Option<String>,
 }
 
 pub async fn insert_txn(
@@ -62,7 +106,7 @@ pub async fn insert_txn(
     row: &TxnRow,
 ) -> Result<(), AppError> {
     sqlx::query(
-        "INSERT INTO txn (id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id, qty, actual_qty, ref_txn_id, note) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        "INSERT INTO txn (id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id, qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at, reason_id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
     )
     .bind(&row.id)
     .bind(&row.txn_no)
@@ -77,15 +121,71 @@ pub async fn insert_txn(
     .bind(row.actual_qty)
     .bind(&row.ref_txn_id)
     .bind(&row.note)
+    .bind(row.unit_cost)
+    .bind(&row.location)
+    .bind(row.expires_at)
+    .bind(&row.reason_id)
     .execute(&mut **tx)
     .await?;
 
     Ok(())
 }
 
+/// 流入库位的入库 / 移入流水（已冲正的不计），按发生时间倒序，返回 (数量, 到期日)；
+/// 用于按先进先出推算库位现存库存来自哪些批次
+pub async fn list_inbound_legs_tx(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    item_id: &str,
+    slot_id: &str,
+) -> Result<Vec<(i64, Option<i64>)>, AppError> {
+    let rows: Vec<(i64, Option<i64>)> = sqlx::query_as(
+        "SELECT t.qty, t.expires_at FROM txn AS t \
+         WHERE t.item_id = ? AND t.to_slot_id = ? AND t.\"type\" IN ('IN', 'MOVE', 'OPENING') \
+         AND NOT EXISTS (SELECT 1 FROM txn AS r WHERE r.ref_txn_id = t.id AND r.\"type\" = 'REVERSAL') \
+         ORDER BY t.occurred_at DESC, t.created_at DESC"
+    )
+    .bind(item_id)
+    .bind(slot_id)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(rows)
+}
+
+/// 分配下一个流水序号（按前缀与类型独立递增）
+pub async fn next_txn_seq_tx(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    prefix: &str,
+    txn_type: &str,
+) -> Result<i64, AppError> {
+    let (last_no,): (i64,) = sqlx::query_as(
+        "INSERT INTO txn_no_seq (prefix, txn_type, last_no) VALUES (?, ?, 1) \
+         ON CONFLICT(prefix, txn_type) DO UPDATE SET last_no = last_no + 1 \
+         RETURNING last_no"
+    )
+    .bind(prefix)
+    .bind(txn_type)
+    .fetch_one(&mut **tx)
+    .await?;
+    Ok(last_no)
+}
+
+/// 库位所属仓库编码（库位未归属仓库时为 None）
+pub async fn get_slot_warehouse_code_tx(
+    tx: &mut Transaction<'_, sqlx::Sqlite>,
+    slot_id: &str,
+) -> Result<Option<String>, AppError> {
+    let row: Option<(Option<String>,)> = sqlx::query_as(
+        "SELECT warehouse.code FROM slot LEFT JOIN warehouse ON slot.warehouse_id = warehouse.id WHERE slot.id = ?"
+    )
+    .bind(slot_id)
+    .fetch_optional(&mut **tx)
+    .await?;
+    Ok(row.and_then(|(code,)| code))
+}
+
 pub async fn get_txn_by_no(pool: &SqlitePool, txn_no: &str) -> Result<Option<TxnRow>, AppError> {
     let row = sqlx::query(
-        "SELECT id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id, qty, actual_qty, ref_txn_id, note FROM txn WHERE txn_no = ?"
+        "SELECT id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id, qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at, reason_id FROM txn WHERE txn_no = ?"
     )
     .bind(txn_no)
     .fetch_optional(pool)
@@ -105,6 +205,10 @@ pub async fn get_txn_by_no(pool: &SqlitePool, txn_no: &str) -> Result<Option<Txn
         actual_qty: row.get("actual_qty"),
         ref_txn_id: row.get("ref_txn_id"),
         note: row.get("note"),
+        unit_cost: row.get("unit_cost"),
+        location: row.get("location"),
+        expires_at: row.get("expires_at"),
+        reason_id: row.get("reason_id"),
     }))
 }
 
@@ -118,14 +222,14 @@ pub async fn has_reversal(pool: &SqlitePool, ref_txn_id: &str) -> Result<bool, A
 
 pub async fn get_txn_by_id(pool: &SqlitePool, id: &str) -> Result<TxnRow, AppError> {
     let row = sqlx::query(
-        "SELECT id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id, qty, actual_qty, ref_txn_id, note FROM txn WHERE id = ?"
+        "SELECT id, txn_no, type, occurred_at, created_at, operator_id, item_id, from_slot_id, to_slot_id, qty, actual_qty, ref_txn_id, note, unit_cost, location, expires_at, reason_id FROM txn WHERE id = ?"
     )
     .bind(id)
     .fetch_optional(pool)
     .await?;
 
     let Some(row) = row else {
-        return Err(AppError::new(ErrorCode::NotFound, "流水不存在"));
+        return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::TxnNotFound));
     };
 
     Ok(TxnRow {
@@ -142,6 +246,10 @@ pub async fn get_txn_by_id(pool: &SqlitePool, id: &str) -> Result<TxnRow, AppErr
         actual_qty: row.get("actual_qty"),
         ref_txn_id: row.get("ref_txn_id"),
         note: row.get("note"),
+        unit_cost: row.get("unit_cost"),
+        location: row.get("location"),
+        expires_at: row.get("expires_at"),
+        reason_id: row.get("reason_id"),
     })
 }
 
@@ -154,23 +262,39 @@ pub async fn list_txns(
     warehouse_id: Option<String>,
     rack_id: Option<String>,
     operator_id: Option<String>,
+    reason_id: Option<String>,
     start_at: Option<i64>,
     end_at: Option<i64>,
+    allowed_warehouse_ids: Option<Vec<String>>,
     page_index: i64,
     page_size: i64,
+    sort_by: Option<&str>,
+    sort_dir: Option<&str>,
+    cursor: Option<(i64, String)>,
 ) -> Result<Vec<TxnListRow>, AppError> {
     let offset = (page_index - 1) * page_size;
-
-    let sql = r#"SELECT txn.id, txn.txn_no, txn."type" AS txn_type, txn.occurred_at, txn.created_at,
+    let order_by = sort::order_by_clause(
+        TXN_SORT_COLUMNS,
+        sort_by,
+        sort_dir,
+        "txn.created_at DESC, txn.id DESC",
+    )?;
+
+    // 数量按物品精度换算为显示数量
+    let sql = format!(
+        r#"SELECT txn.id, txn.txn_no, txn."type" AS txn_type, txn.occurred_at, txn.created_at,
      op.id AS operator_id, op.display_name AS operator_name, it.id AS item_id, it.item_code AS item_code, it.name AS item_name,
      fs.id AS from_slot_id, fs.code AS from_slot_code, ts.id AS to_slot_id, ts.code AS to_slot_code,
-     txn.qty, txn.actual_qty, txn.ref_txn_id,
+     fr.id AS from_rack_id, fr.name AS from_rack_name, fw.id AS from_warehouse_id, fw.name AS from_warehouse_name,
+     tr.id AS to_rack_id, tr.name AS to_rack_name, tw.id AS to_warehouse_id, tw.name AS to_warehouse_name,
+     txn.qty / {scale} AS qty, txn.actual_qty / {scale} AS actual_qty, txn.ref_txn_id,
      EXISTS (SELECT 1 FROM txn AS rev WHERE rev.ref_txn_id = txn.id AND rev.type = 'REVERSAL') AS has_reversal,
      ref.txn_no AS ref_txn_no, ref."type" AS ref_txn_type, ref_it.id AS ref_item_id, ref_it.name AS ref_item_name,
      ref_op.id AS ref_operator_id, ref_op.display_name AS ref_operator_name, ref_fs.id AS ref_from_slot_id,
      ref_fs.code AS ref_from_slot_code, ref_ts.id AS ref_to_slot_id, ref_ts.code AS ref_to_slot_code,
-     ref.qty AS ref_qty, ref.actual_qty AS ref_actual_qty, ref.occurred_at AS ref_occurred_at, ref.note AS ref_note,
-     txn.note
+     ref.qty / {ref_scale} AS ref_qty, ref.actual_qty / {ref_scale} AS ref_actual_qty, ref.occurred_at AS ref_occurred_at, ref.note AS ref_note,
+     txn.note, txn.location, txn.reason_id, rc.code AS reason_code, rc.name AS reason_name,
+     pick.pick_no, pick.planned_qty / {scale} AS planned_qty, pick.short_reason
      FROM txn
      JOIN "operator" AS op ON txn.operator_id = op.id
      JOIN item AS it ON txn.item_id = it.id
@@ -178,11 +302,18 @@ pub async fn list_txns(
      LEFT JOIN slot AS ts ON txn.to_slot_id = ts.id
      LEFT JOIN rack AS fr ON fs.rack_id = fr.id
      LEFT JOIN rack AS tr ON ts.rack_id = tr.id
+     LEFT JOIN warehouse AS fw ON fw.id = COALESCE(fr.warehouse_id, fs.warehouse_id)
+     LEFT JOIN warehouse AS tw ON tw.id = COALESCE(tr.warehouse_id, ts.warehouse_id)
+     LEFT JOIN outbound_pick AS pick ON pick.out_txn_id = txn.id
+     LEFT JOIN reason_code AS rc ON txn.reason_id = rc.id
      LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id
      LEFT JOIN "operator" AS ref_op ON ref.operator_id = ref_op.id
      LEFT JOIN item AS ref_it ON ref.item_id = ref_it.id
      LEFT JOIN slot AS ref_fs ON ref.from_slot_id = ref_fs.id
-     LEFT JOIN slot AS ref_ts ON ref.to_slot_id = ref_ts.id"#;
+     LEFT JOIN slot AS ref_ts ON ref.to_slot_id = ref_ts.id"#,
+        scale = quantity::scale_sql("it.qty_precision"),
+        ref_scale = quantity::scale_sql("ref_it.qty_precision"),
+    );
 
     let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(sql);
     let mut has_where = false;
@@ -216,27 +347,37 @@ pub async fn list_txns(
         builder.push(" OR fs.code LIKE ");
         builder.push_bind(like.clone());
         builder.push(" OR ts.code LIKE ");
+        builder.push_bind(like.clone());
+        builder.push(" OR rc.name LIKE ");
+        builder.push_bind(like.clone());
+        builder.push(" OR rc.code LIKE ");
         builder.push_bind(like);
         builder.push(")");
     }
 
     if let Some(item_id) = item_id {
         push_where(&mut builder);
-        builder.push("it.id = ");
+        builder.push("txn.item_id = ");
         builder.push_bind(item_id);
     }
 
     if let Some(operator_id) = operator_id {
         push_where(&mut builder);
-        builder.push("op.id = ");
+        builder.push("txn.operator_id = ");
         builder.push_bind(operator_id);
     }
 
+    if let Some(reason_id) = reason_id {
+        push_where(&mut builder);
+        builder.push("txn.reason_id = ");
+        builder.push_bind(reason_id);
+    }
+
     if let Some(slot_id) = slot_id {
         push_where(&mut builder);
-        builder.push("(fs.id = ");
+        builder.push("(txn.from_slot_id = ");
         builder.push_bind(slot_id.clone());
-        builder.push(" OR ts.id = ");
+        builder.push(" OR txn.to_slot_id = ");
         builder.push_bind(slot_id);
         builder.push(")");
     }
@@ -259,6 +400,12 @@ pub async fn list_txns(
         builder.push(")");
     }
 
+    // 仓库范围限制：来源或目标库位任一落在可访问仓库内即可见
+    if let Some(ids) = allowed_warehouse_ids {
+        push_where(&mut builder);
+        push_warehouse_scope(&mut builder, &ids);
+    }
+
     if let Some(start_at) = start_at {
         push_where(&mut builder);
         builder.push("txn.occurred_at >= ");
@@ -271,10 +418,26 @@ pub async fn list_txns(
         builder.push_bind(end_at);
     }
 
-    builder.push(" ORDER BY txn.created_at DESC LIMIT ");
+    // 游标翻页：取 (created_at, id) 严格小于上一页最后一条的记录，不再使用 OFFSET
+    let keyset = cursor.is_some();
+    if let Some((created_at, id)) = cursor {
+        push_where(&mut builder);
+        builder.push("(txn.created_at < ");
+        builder.push_bind(created_at);
+        builder.push(" OR (txn.created_at = ");
+        builder.push_bind(created_at);
+        builder.push(" AND txn.id < ");
+        builder.push_bind(id);
+        builder.push("))");
+    }
+
+    builder.push(order_by);
+    builder.push(" LIMIT ");
     builder.push_bind(page_size);
-    builder.push(" OFFSET ");
-    builder.push_bind(offset);
+    if !keyset {
+        builder.push(" OFFSET ");
+        builder.push_bind(offset);
+    }
 
     let rows = builder.build().fetch_all(pool).await?;
 
@@ -295,6 +458,14 @@ pub async fn list_txns(
             from_slot_code: row.get("from_slot_code"),
             to_slot_id: row.get("to_slot_id"),
             to_slot_code: row.get("to_slot_code"),
+            from_rack_id: row.get("from_rack_id"),
+            from_rack_name: row.get("from_rack_name"),
+            from_warehouse_id: row.get("from_warehouse_id"),
+            from_warehouse_name: row.get("from_warehouse_name"),
+            to_rack_id: row.get("to_rack_id"),
+            to_rack_name: row.get("to_rack_name"),
+            to_warehouse_id: row.get("to_warehouse_id"),
+            to_warehouse_name: row.get("to_warehouse_name"),
             qty: row.get("qty"),
             actual_qty: row.get("actual_qty"),
             ref_txn_id: row.get("ref_txn_id"),
@@ -314,6 +485,13 @@ pub async fn list_txns(
             ref_occurred_at: row.get("ref_occurred_at"),
             ref_note: row.get("ref_note"),
             note: row.get("note"),
+            location: row.get("location"),
+            reason_id: row.get("reason_id"),
+            reason_code: row.get("reason_code"),
+            reason_name: row.get("reason_name"),
+            pick_no: row.get("pick_no"),
+            planned_qty: row.get("planned_qty"),
+            short_reason: row.get("short_reason"),
         })
         .collect();
 
@@ -329,8 +507,10 @@ pub async fn count_txns_filtered(
     warehouse_id: Option<String>,
     rack_id: Option<String>,
     operator_id: Option<String>,
+    reason_id: Option<String>,
     start_at: Option<i64>,
     end_at: Option<i64>,
+    allowed_warehouse_ids: Option<Vec<String>>,
 ) -> Result<i64, AppError> {
     let sql = r#"SELECT COUNT(1) FROM txn
      JOIN "operator" AS op ON txn.operator_id = op.id
@@ -338,7 +518,8 @@ pub async fn count_txns_filtered(
      LEFT JOIN slot AS fs ON txn.from_slot_id = fs.id
      LEFT JOIN slot AS ts ON txn.to_slot_id = ts.id
      LEFT JOIN rack AS fr ON fs.rack_id = fr.id
-     LEFT JOIN rack AS tr ON ts.rack_id = tr.id"#;
+     LEFT JOIN rack AS tr ON ts.rack_id = tr.id
+     LEFT JOIN reason_code AS rc ON txn.reason_id = rc.id"#;
 
     let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(sql);
     let mut has_where = false;
@@ -372,27 +553,37 @@ pub async fn count_txns_filtered(
         builder.push(" OR fs.code LIKE ");
         builder.push_bind(like.clone());
         builder.push(" OR ts.code LIKE ");
+        builder.push_bind(like.clone());
+        builder.push(" OR rc.name LIKE ");
+        builder.push_bind(like.clone());
+        builder.push(" OR rc.code LIKE ");
         builder.push_bind(like);
         builder.push(")");
     }
 
     if let Some(item_id) = item_id {
         push_where(&mut builder);
-        builder.push("it.id = ");
+        builder.push("txn.item_id = ");
         builder.push_bind(item_id);
     }
 
     if let Some(operator_id) = operator_id {
         push_where(&mut builder);
-        builder.push("op.id = ");
+        builder.push("txn.operator_id = ");
         builder.push_bind(operator_id);
     }
 
+    if let Some(reason_id) = reason_id {
+        push_where(&mut builder);
+        builder.push("txn.reason_id = ");
+        builder.push_bind(reason_id);
+    }
+
     if let Some(slot_id) = slot_id {
         push_where(&mut builder);
-        builder.push("(fs.id = ");
+        builder.push("(txn.from_slot_id = ");
         builder.push_bind(slot_id.clone());
-        builder.push(" OR ts.id = ");
+        builder.push(" OR txn.to_slot_id = ");
         builder.push_bind(slot_id);
         builder.push(")");
     }
@@ -415,6 +606,12 @@ pub async fn count_txns_filtered(
         builder.push(")");
     }
 
+    // 仓库范围限制：来源或目标库位任一落在可访问仓库内即可见
+    if let Some(ids) = allowed_warehouse_ids {
+        push_where(&mut builder);
+        push_warehouse_scope(&mut builder, &ids);
+    }
+
     if let Some(start_at) = start_at {
         push_where(&mut builder);
         builder.push("txn.occurred_at >= ");
@@ -432,7 +629,33 @@ pub async fn count_txns_filtered(
 }
 
 pub async fn count_txns(pool: &SqlitePool) -> Result<i64, AppError> {
-    count_txns_filtered(pool, None, None, None, None, None, None, None, None, None).await
+    count_txns_filtered(pool, None, None, None, None, None, None, None, None, None, None, None).await
+}
+
+/// 期初流水以外的流水数量，用于判断是否已开始日常记账
+pub async fn count_regular_txns_tx(tx: &mut Transaction<'_, sqlx::Sqlite>) -> Result<i64, AppError> {
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(1) FROM txn WHERE type <> 'OPENING'")
+        .fetch_one(&mut **tx)
+        .await?;
+    Ok(count)
+}
+
+fn push_warehouse_scope(builder: &mut QueryBuilder<Sqlite>, ids: &[String]) {
+    if ids.is_empty() {
+        builder.push("1 = 0");
+        return;
+    }
+    builder.push("(fr.warehouse_id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated(") OR tr.warehouse_id IN (");
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(id.clone());
+    }
+    separated.push_unseparated("))");
 }
 
 #[derive(Debug)]
@@ -442,8 +665,9 @@ pub struct TxnExportRow {
     pub item_name: String,
     pub from_slot_code: Option<String>,
     pub to_slot_code: Option<String>,
-    pub qty: i64,
-    pub actual_qty: Option<i64>,
+    // 显示数量（按物品精度换算）
+    pub qty: f64,
+    pub actual_qty: Option<f64>,
     pub occurred_at: i64,
     pub operator_display_name: String,
     pub note: Option<String>,
@@ -452,9 +676,10 @@ pub struct TxnExportRow {
 
 pub async fn list_txns_export(pool: &SqlitePool) -> Result<Vec<TxnExportRow>, AppError> {
     // 导出时使用物品显示名与操作员显示名，库位仍使用 code 作为可读值
-    let rows = sqlx::query(
-        "SELECT txn.type AS txn_type, item.name AS item_name, from_slot.code AS from_slot_code, to_slot.code AS to_slot_code, txn.qty AS qty, txn.actual_qty AS actual_qty, txn.occurred_at AS occurred_at, operator.display_name AS operator_display_name, txn.note AS note, ref.txn_no AS ref_txn_no FROM txn JOIN item ON txn.item_id = item.id JOIN operator ON txn.operator_id = operator.id LEFT JOIN slot AS from_slot ON txn.from_slot_id = from_slot.id LEFT JOIN slot AS to_slot ON txn.to_slot_id = to_slot.id LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id ORDER BY txn.created_at DESC",
-    )
+    let rows = sqlx::query(&format!(
+        "SELECT txn.type AS txn_type, item.name AS item_name, from_slot.code AS from_slot_code, to_slot.code AS to_slot_code, txn.qty / {scale} AS qty, txn.actual_qty / {scale} AS actual_qty, txn.occurred_at AS occurred_at, operator.display_name AS operator_display_name, txn.note AS note, ref.txn_no AS ref_txn_no FROM txn JOIN item ON txn.item_id = item.id JOIN operator ON txn.operator_id = operator.id LEFT JOIN slot AS from_slot ON txn.from_slot_id = from_slot.id LEFT JOIN slot AS to_slot ON txn.to_slot_id = to_slot.id LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id ORDER BY txn.created_at DESC",
+        scale = quantity::scale_sql("item.qty_precision")
+    ))
     .fetch_all(pool)
     .await?;
 
@@ -476,3 +701,44 @@ pub async fn list_txns_export(pool: &SqlitePool) -> Result<Vec<TxnExportRow>, Ap
 
     Ok(items)
 }
+
+#[derive(Debug)]
+pub struct ValuationTxnRow {
+    pub id: String,
+    pub txn_type: String,
+    pub item_id: String,
+    pub qty: i64,
+    // 入库成本；历史入库未记录时回退为物品当前成本
+    pub unit_cost: Option<f64>,
+    pub ref_txn_id: Option<String>,
+    pub ref_txn_type: Option<String>,
+}
+
+pub async fn list_valuation_txns(pool: &SqlitePool) -> Result<Vec<ValuationTxnRow>, AppError> {
+    // 按发生时间回放，影响物品总量的流水才参与计价
+    let rows = sqlx::query(
+        "SELECT txn.id AS id, txn.type AS txn_type, txn.item_id AS item_id, txn.qty AS qty, \
+         COALESCE(txn.unit_cost, item.unit_cost) AS unit_cost, txn.ref_txn_id AS ref_txn_id, ref.type AS ref_txn_type \
+         FROM txn JOIN item ON txn.item_id = item.id \
+         LEFT JOIN txn AS ref ON txn.ref_txn_id = ref.id \
+         WHERE txn.type IN ('IN', 'OPENING', 'OUT', 'SCRAP', 'ADJUST', 'REVERSAL') \
+         ORDER BY txn.occurred_at ASC, txn.created_at ASC, txn.rowid ASC",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let items = rows
+        .into_iter()
+        .map(|row| ValuationTxnRow {
+            id: row.get("id"),
+            txn_type: row.get("txn_type"),
+            item_id: row.get("item_id"),
+            qty: row.get("qty"),
+            unit_cost: row.get("unit_cost"),
+            ref_txn_id: row.get("ref_txn_id"),
+            ref_txn_type: row.get("ref_txn_type"),
+        })
+        .collect();
+
+    Ok(items)
+}
@@ -0,0 +1,90 @@
+use sqlx::{Row, Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::AppError;
+
+#[derive(Debug, serde::Serialize)]
+pub struct UndoEntryRow {
+  pub id: String,
+  pub operator_id: String,
+  // 原操作的审计动作（如 ITEM_UPDATE）
+  pub action: String,
+  pub summary: String,
+  #[serde(skip_serializing)]
+  pub payload: String,
+  pub created_at: i64,
+  pub undone_at: Option<i64>,
+}
+
+fn map_entry(row: &sqlx::sqlite::SqliteRow) -> UndoEntryRow {
+  UndoEntryRow {
+    id: row.get("id"),
+    operator_id: row.get("operator_id"),
+    action: row.get("action"),
+    summary: row.get("summary"),
+    payload: row.get("payload"),
+    created_at: row.get("created_at"),
+    undone_at: row.get("undone_at"),
+  }
+}
+
+pub async fn insert_entry(pool: &SqlitePool, entry: &UndoEntryRow) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO undo_entry (id, operator_id, action, summary, payload, created_at, undone_at) \
+     VALUES (?, ?, ?, ?, ?, ?, NULL)",
+  )
+  .bind(&entry.id)
+  .bind(&entry.operator_id)
+  .bind(&entry.action)
+  .bind(&entry.summary)
+  .bind(&entry.payload)
+  .bind(entry.created_at)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 仅保留操作人最近 keep 条记录
+pub async fn prune_entries(pool: &SqlitePool, operator_id: &str, keep: i64) -> Result<(), AppError> {
+  sqlx::query(
+    "DELETE FROM undo_entry WHERE operator_id = ? AND id NOT IN ( \
+       SELECT id FROM undo_entry WHERE operator_id = ? ORDER BY created_at DESC, rowid DESC LIMIT ? \
+     )",
+  )
+  .bind(operator_id)
+  .bind(operator_id)
+  .bind(keep)
+  .execute(pool)
+  .await?;
+  Ok(())
+}
+
+/// 操作人最近一条未撤销的记录
+pub async fn latest_pending(pool: &SqlitePool, operator_id: &str) -> Result<Option<UndoEntryRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, operator_id, action, summary, payload, created_at, undone_at FROM undo_entry \
+     WHERE operator_id = ? AND undone_at IS NULL ORDER BY created_at DESC, rowid DESC LIMIT 1",
+  )
+  .bind(operator_id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.as_ref().map(map_entry))
+}
+
+pub async fn mark_undone(pool: &SqlitePool, id: &str, undone_at: i64) -> Result<(), AppError> {
+  sqlx::query("UPDATE undo_entry SET undone_at = ? WHERE id = ?")
+    .bind(undone_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+  Ok(())
+}
+
+/// 在事务内标记已撤销，与恢复数据一同提交
+pub async fn mark_undone_tx(tx: &mut Transaction<'_, Sqlite>, id: &str, undone_at: i64) -> Result<(), AppError> {
+  sqlx::query("UPDATE undo_entry SET undone_at = ? WHERE id = ?")
+    .bind(undone_at)
+    .bind(id)
+    .execute(&mut **tx)
+    .await?;
+  Ok(())
+}
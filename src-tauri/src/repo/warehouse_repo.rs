@@ -1,6 +1,7 @@
-use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool};
+use sqlx::{QueryBuilder, Row, Sqlite, SqlitePool, Transaction};
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 
 #[derive(Debug, serde::Serialize)]
 pub struct WarehouseRow {
@@ -8,6 +9,10 @@ pub struct WarehouseRow {
   pub code: String,
   pub name: String,
   pub status: String,
+  // 仓库类型：fixed（固定仓库）/ mobile（车辆、工具箱等移动仓库）
+  pub kind: String,
+  // 移动仓库的归属基地仓库
+  pub base_warehouse_id: Option<String>,
   pub created_at: i64,
 }
 
@@ -20,7 +25,7 @@ pub async fn list_warehouses(
 ) -> Result<Vec<WarehouseRow>, AppError> {
   let offset = (page_index - 1) * page_size;
   let mut builder: QueryBuilder<Sqlite> =
-    QueryBuilder::new("SELECT id, code, name, status, created_at FROM warehouse");
+    QueryBuilder::new("SELECT id, code, name, status, kind, base_warehouse_id, created_at FROM warehouse");
   let mut has_where = false;
   if let Some(status) = status {
     builder.push(" WHERE status = ").push_bind(status);
@@ -55,6 +60,8 @@ pub async fn list_warehouses(
       code: row.get("code"),
       name: row.get("name"),
       status: row.get("status"),
+      kind: row.get("kind"),
+      base_warehouse_id: row.get("base_warehouse_id"),
       created_at: row.get("created_at"),
     })
     .collect();
@@ -101,7 +108,7 @@ pub async fn get_warehouse_by_id(
   id: &str,
 ) -> Result<Option<WarehouseRow>, AppError> {
   let row = sqlx::query(
-    "SELECT id, code, name, status, created_at \
+    "SELECT id, code, name, status, kind, base_warehouse_id, created_at \
      FROM warehouse WHERE id = ?",
   )
   .bind(id)
@@ -113,6 +120,8 @@ pub async fn get_warehouse_by_id(
     code: row.get("code"),
     name: row.get("name"),
     status: row.get("status"),
+    kind: row.get("kind"),
+    base_warehouse_id: row.get("base_warehouse_id"),
     created_at: row.get("created_at"),
   }))
 }
@@ -122,7 +131,7 @@ pub async fn get_warehouse_by_code(
   code: &str,
 ) -> Result<Option<WarehouseRow>, AppError> {
   let row = sqlx::query(
-    "SELECT id, code, name, status, created_at \
+    "SELECT id, code, name, status, kind, base_warehouse_id, created_at \
      FROM warehouse WHERE code = ?",
   )
   .bind(code)
@@ -134,6 +143,8 @@ pub async fn get_warehouse_by_code(
     code: row.get("code"),
     name: row.get("name"),
     status: row.get("status"),
+    kind: row.get("kind"),
+    base_warehouse_id: row.get("base_warehouse_id"),
     created_at: row.get("created_at"),
   }))
 }
@@ -144,15 +155,19 @@ pub async fn insert_warehouse(
   code: &str,
   name: &str,
   status: &str,
+  kind: &str,
+  base_warehouse_id: Option<&str>,
   created_at: i64,
 ) -> Result<(), AppError> {
   sqlx::query(
-    "INSERT INTO warehouse (id, code, name, status, created_at) VALUES (?, ?, ?, ?, ?)",
+    "INSERT INTO warehouse (id, code, name, status, kind, base_warehouse_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
   )
   .bind(id)
   .bind(code)
   .bind(name)
   .bind(status)
+  .bind(kind)
+  .bind(base_warehouse_id)
   .bind(created_at)
   .execute(pool)
   .await?;
@@ -160,6 +175,33 @@ pub async fn insert_warehouse(
   Ok(())
 }
 
+/// 事务内新建仓库（结构导入、主数据包导入）
+pub async fn insert_warehouse_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  id: &str,
+  code: &str,
+  name: &str,
+  status: &str,
+  kind: &str,
+  base_warehouse_id: Option<&str>,
+  created_at: i64,
+) -> Result<(), AppError> {
+  sqlx::query(
+    "INSERT INTO warehouse (id, code, name, status, kind, base_warehouse_id, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)",
+  )
+  .bind(id)
+  .bind(code)
+  .bind(name)
+  .bind(status)
+  .bind(kind)
+  .bind(base_warehouse_id)
+  .bind(created_at)
+  .execute(&mut **tx)
+  .await?;
+
+  Ok(())
+}
+
 pub async fn update_warehouse(
   pool: &SqlitePool,
   id: &str,
@@ -172,7 +214,7 @@ pub async fn update_warehouse(
     .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "仓库不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound));
   }
 
   Ok(())
@@ -190,8 +232,133 @@ pub async fn set_warehouse_status(
     .await?;
 
   if result.rows_affected() == 0 {
-    return Err(AppError::new(ErrorCode::NotFound, "仓库不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound));
   }
 
   Ok(())
 }
+
+pub async fn get_first_active_warehouse(
+  pool: &SqlitePool,
+) -> Result<Option<WarehouseRow>, AppError> {
+  let row = sqlx::query(
+    "SELECT id, code, name, status, kind, base_warehouse_id, created_at \
+     FROM warehouse WHERE status = 'active' ORDER BY code ASC LIMIT 1",
+  )
+  .fetch_optional(pool)
+  .await?;
+
+  Ok(row.map(|row| WarehouseRow {
+    id: row.get("id"),
+    code: row.get("code"),
+    name: row.get("name"),
+    status: row.get("status"),
+    kind: row.get("kind"),
+    base_warehouse_id: row.get("base_warehouse_id"),
+    created_at: row.get("created_at"),
+  }))
+}
+
+#[derive(Debug)]
+pub struct RackUtilizationRow {
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub rack_id: String,
+  pub rack_code: String,
+  pub rack_name: String,
+  pub total_slots: i64,
+  // 停用库位数（不计入利用率分母）
+  pub inactive_slots: i64,
+  // 启用且有非零库存的库位数
+  pub occupied_slots: i64,
+}
+
+/// 按货架汇总库位占用情况，可按仓库与可访问仓库范围过滤
+pub async fn list_rack_utilization(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<RackUtilizationRow>, AppError> {
+  let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
+    "SELECT rack.warehouse_id AS warehouse_id, warehouse.code AS warehouse_code, warehouse.name AS warehouse_name, \
+     rack.id AS rack_id, rack.code AS rack_code, rack.name AS rack_name, \
+     COUNT(slot.id) AS total_slots, \
+     SUM(CASE WHEN slot.status <> 'active' THEN 1 ELSE 0 END) AS inactive_slots, \
+     SUM(CASE WHEN slot.status = 'active' AND EXISTS ( \
+       SELECT 1 FROM stock WHERE stock.slot_id = slot.id AND stock.qty <> 0) THEN 1 ELSE 0 END) AS occupied_slots \
+     FROM rack \
+     LEFT JOIN warehouse ON rack.warehouse_id = warehouse.id \
+     LEFT JOIN slot ON slot.rack_id = rack.id \
+     WHERE rack.status = 'active'",
+  );
+  if let Some(warehouse_id) = warehouse_id {
+    builder.push(" AND rack.warehouse_id = ").push_bind(warehouse_id);
+  }
+  if let Some(ids) = allowed_warehouse_ids {
+    if ids.is_empty() {
+      builder.push(" AND 1 = 0");
+    } else {
+      builder.push(" AND rack.warehouse_id IN (");
+      let mut separated = builder.separated(", ");
+      for id in ids {
+        separated.push_bind(id);
+      }
+      separated.push_unseparated(")");
+    }
+  }
+  builder.push(" GROUP BY rack.id ORDER BY warehouse.code, rack.code");
+
+  let rows = builder.build().fetch_all(pool).await?;
+
+  let items = rows
+    .into_iter()
+    .map(|row| RackUtilizationRow {
+      warehouse_id: row.get("warehouse_id"),
+      warehouse_code: row.get("warehouse_code"),
+      warehouse_name: row.get("warehouse_name"),
+      rack_id: row.get("rack_id"),
+      rack_code: row.get("rack_code"),
+      rack_name: row.get("rack_name"),
+      total_slots: row.get("total_slots"),
+      inactive_slots: row.get::<Option<i64>, _>("inactive_slots").unwrap_or(0),
+      occupied_slots: row.get::<Option<i64>, _>("occupied_slots").unwrap_or(0),
+    })
+    .collect();
+
+  Ok(items)
+}
+
+/// 仓库内存放该物品且数量不少于 min_qty 的库位，按数量倒序取第一个
+pub async fn find_item_slot_in_warehouse(
+  pool: &SqlitePool,
+  warehouse_id: &str,
+  item_id: &str,
+  min_qty: i64,
+) -> Result<Option<String>, AppError> {
+  let row: Option<(String,)> = sqlx::query_as(
+    "SELECT stock.slot_id FROM stock JOIN slot ON stock.slot_id = slot.id \
+     WHERE slot.warehouse_id = ? AND stock.item_id = ? AND stock.qty >= ? AND stock.qty > 0 AND slot.status = 'active' \
+     ORDER BY stock.qty DESC, slot.code ASC LIMIT 1",
+  )
+  .bind(warehouse_id)
+  .bind(item_id)
+  .bind(min_qty)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.map(|(slot_id,)| slot_id))
+}
+
+/// 仓库内第一个启用库位（按编码排序）
+pub async fn first_active_slot_in_warehouse(
+  pool: &SqlitePool,
+  warehouse_id: &str,
+) -> Result<Option<String>, AppError> {
+  let row: Option<(String,)> = sqlx::query_as(
+    "SELECT id FROM slot WHERE warehouse_id = ? AND status = 'active' ORDER BY code ASC LIMIT 1",
+  )
+  .bind(warehouse_id)
+  .fetch_optional(pool)
+  .await?;
+  Ok(row.map(|(slot_id,)| slot_id))
+}
@@ -0,0 +1,319 @@
+// 敏感操作审批：冲正、大额盘点调整、大额报废与数据库恢复按设置需由另一名管理员审批后才能执行
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::repo::approval_repo::{self, ApprovalRequestRow};
+use crate::repo::{item_repo, meta_repo, rack_repo, stock_repo, txn_repo};
+
+pub const APPROVAL_STATUSES: [&str; 4] = ["pending", "approved", "rejected", "executed"];
+
+/// 审批设置（app_meta 中的 approval_*）
+#[derive(Debug, Clone, Copy)]
+pub struct ApprovalPolicy {
+  // 冲正需审批
+  pub reversal: bool,
+  // 数据库恢复需审批
+  pub restore: bool,
+  // 盘点调整差异超过该数量（按物品显示数量）需审批，0 表示不需审批
+  pub count_threshold: i64,
+  // 报废数量超过该数量（按物品显示数量）需审批，0 表示不需审批
+  pub scrap_threshold: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SubmitApprovalInput {
+  pub kind: String,
+  // reversal：被冲正的流水号
+  pub txn_no: Option<String>,
+  // count_adjust：物品、库位与实盘数量（显示数量）
+  pub item_id: Option<String>,
+  pub slot_id: Option<String>,
+  pub actual_qty: Option<f64>,
+  // scrap：物品、库位（同上）与报废数量（显示数量）
+  pub qty: Option<f64>,
+  // restore：本地备份文件或远程备份名称（二选一）
+  pub file_path: Option<String>,
+  pub remote_name: Option<String>,
+  pub reason: Option<String>,
+}
+
+pub async fn load_policy(pool: &SqlitePool) -> Result<ApprovalPolicy, AppError> {
+  Ok(ApprovalPolicy {
+    reversal: meta_repo::get_meta_value(pool, "approval_reversal").await?.as_deref() == Some("1"),
+    restore: meta_repo::get_meta_value(pool, "approval_restore").await?.as_deref() == Some("1"),
+    count_threshold: meta_repo::get_meta_i64(pool, "approval_count_threshold")
+      .await?
+      .filter(|value| *value > 0)
+      .unwrap_or(0),
+    scrap_threshold: meta_repo::get_meta_i64(pool, "approval_scrap_threshold")
+      .await?
+      .filter(|value| *value > 0)
+      .unwrap_or(0),
+  })
+}
+
+pub async fn reversal_requires_approval(pool: &SqlitePool) -> Result<bool, AppError> {
+  Ok(load_policy(pool).await?.reversal)
+}
+
+pub async fn restore_requires_approval(pool: &SqlitePool) -> Result<bool, AppError> {
+  Ok(load_policy(pool).await?.restore)
+}
+
+/// 盘点差异（实盘与账面之差的绝对值）是否超过审批阈值；actual_qty 为基本单位数量
+pub async fn count_requires_approval(
+  pool: &SqlitePool,
+  item_id: &str,
+  slot_id: &str,
+  actual_qty: i64,
+) -> Result<bool, AppError> {
+  let current = stock_repo::get_stock(pool, item_id, slot_id).await?.map(|stock| stock.qty).unwrap_or(0);
+  adjust_requires_approval(pool, item_id, actual_qty - current).await
+}
+
+/// 库存调整量（基本单位）的绝对值是否超过盘点调整审批阈值
+pub async fn adjust_requires_approval(pool: &SqlitePool, item_id: &str, delta: i64) -> Result<bool, AppError> {
+  let threshold = load_policy(pool).await?.count_threshold;
+  if threshold == 0 {
+    return Ok(false);
+  }
+  let precision = item_repo::get_qty_precision(pool, item_id).await?;
+  Ok(delta.abs() > threshold * quantity::scale(precision))
+}
+
+/// 报废数量（基本单位）是否超过报废审批阈值
+pub async fn scrap_requires_approval(pool: &SqlitePool, item_id: &str, qty: i64) -> Result<bool, AppError> {
+  let threshold = load_policy(pool).await?.scrap_threshold;
+  if threshold == 0 {
+    return Ok(false);
+  }
+  let precision = item_repo::get_qty_precision(pool, item_id).await?;
+  Ok(qty > threshold * quantity::scale(precision))
+}
+
+pub fn scrap_target(item_id: &str, slot_id: &str, qty: i64) -> String {
+  format!("{}/{}/{}", item_id, slot_id, qty)
+}
+
+pub fn count_target(item_id: &str, slot_id: &str, actual_qty: i64) -> String {
+  format!("{}/{}/{}", item_id, slot_id, actual_qty)
+}
+
+pub fn restore_file_target(file_path: &str) -> String {
+  format!("file:{}", file_path)
+}
+
+pub fn restore_remote_target(name: &str) -> String {
+  format!("remote:{}", name)
+}
+
+pub async fn submit_request(
+  pool: &SqlitePool,
+  requested_by: &str,
+  input: &SubmitApprovalInput,
+) -> Result<ApprovalRequestRow, AppError> {
+  let policy = load_policy(pool).await?;
+  let (target, summary) = match input.kind.as_str() {
+    "reversal" => {
+      if !policy.reversal {
+        return Err(AppError::new(ErrorCode::ValidationError, "当前设置下冲正无需审批"));
+      }
+      let txn_no = required_text(input.txn_no.as_deref(), "流水号")?;
+      let txn = txn_repo::get_txn_by_no(pool, txn_no)
+        .await?
+        .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::TxnNotFound))?;
+      (txn.txn_no.clone(), format!("冲正流水 {}", txn.txn_no))
+    }
+    "count_adjust" => {
+      let item_id = required_text(input.item_id.as_deref(), "物品")?;
+      let slot_id = required_text(input.slot_id.as_deref(), "库位")?;
+      let actual = input
+        .actual_qty
+        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "实盘数量不能为空"))?;
+      let item = item_repo::get_item_by_id(pool, item_id)
+        .await?
+        .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+      let slot = rack_repo::get_slot_by_id(pool, slot_id)
+        .await?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "库位不存在"))?;
+      let actual_qty = quantity::to_base(actual, item.qty_precision)?;
+      if !count_requires_approval(pool, item_id, slot_id, actual_qty).await? {
+        return Err(AppError::new(ErrorCode::ValidationError, "盘点差异未超过审批阈值，无需审批"));
+      }
+      let current = stock_repo::get_stock(pool, item_id, slot_id).await?.map(|stock| stock.qty).unwrap_or(0);
+      (
+        count_target(item_id, slot_id, actual_qty),
+        format!(
+          "盘点 {} @ {}：账面 {} → 实盘 {}",
+          item.item_code,
+          slot.code,
+          quantity::format(current, item.qty_precision),
+          quantity::format(actual_qty, item.qty_precision)
+        ),
+      )
+    }
+    "scrap" => {
+      let item_id = required_text(input.item_id.as_deref(), "物品")?;
+      let slot_id = required_text(input.slot_id.as_deref(), "库位")?;
+      let qty = input
+        .qty
+        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "报废数量不能为空"))?;
+      let item = item_repo::get_item_by_id(pool, item_id)
+        .await?
+        .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+      let slot = rack_repo::get_slot_by_id(pool, slot_id)
+        .await?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, "库位不存在"))?;
+      let qty = quantity::to_base(qty, item.qty_precision)?;
+      if !scrap_requires_approval(pool, item_id, qty).await? {
+        return Err(AppError::new(ErrorCode::ValidationError, "报废数量未超过审批阈值，无需审批"));
+      }
+      (
+        scrap_target(item_id, slot_id, qty),
+        format!(
+          "报废 {} @ {}：{}",
+          item.item_code,
+          slot.code,
+          quantity::format(qty, item.qty_precision)
+        ),
+      )
+    }
+    "restore" => {
+      if !policy.restore {
+        return Err(AppError::new(ErrorCode::ValidationError, "当前设置下恢复数据库无需审批"));
+      }
+      match (input.file_path.as_deref(), input.remote_name.as_deref()) {
+        (Some(file_path), _) if !file_path.trim().is_empty() => {
+          (restore_file_target(file_path), format!("从备份文件 {} 恢复数据库", file_path))
+        }
+        (_, Some(name)) if !name.trim().is_empty() => {
+          (restore_remote_target(name), format!("从远程备份 {} 恢复数据库", name))
+        }
+        _ => return Err(AppError::new(ErrorCode::ValidationError, "请选择备份文件")),
+      }
+    }
+    _ => {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "审批类型仅支持 reversal/count_adjust/scrap/restore",
+      ))
+    }
+  };
+  if approval_repo::find_open_request(pool, &input.kind, &target, requested_by).await?.is_some() {
+    return Err(AppError::new(ErrorCode::Conflict, "已有相同的审批申请（待审批或已通过未执行）"));
+  }
+  let id = Uuid::new_v4().to_string();
+  approval_repo::insert_request(
+    pool,
+    &ApprovalRequestRow {
+      id: id.clone(),
+      kind: input.kind.clone(),
+      target,
+      summary,
+      reason: input.reason.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string),
+      status: "pending".to_string(),
+      requested_by: requested_by.to_string(),
+      requested_by_name: None,
+      requested_at: Utc::now().timestamp(),
+      decided_by: None,
+      decided_by_name: None,
+      decided_at: None,
+      decision_note: None,
+      executed_at: None,
+    },
+  )
+  .await?;
+  approval_repo::get_request(pool, &id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "审批申请不存在"))
+}
+
+pub async fn list_requests(
+  pool: &SqlitePool,
+  status: Option<&str>,
+  requested_by: Option<&str>,
+) -> Result<Vec<ApprovalRequestRow>, AppError> {
+  if let Some(status) = status {
+    if !APPROVAL_STATUSES.contains(&status) {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
+    }
+  }
+  approval_repo::list_requests(pool, status, requested_by).await
+}
+
+/// 审批通过或驳回待审批的申请；申请人不能审批本人的申请
+pub async fn decide_request(
+  pool: &SqlitePool,
+  id: &str,
+  approve: bool,
+  decided_by: &str,
+  note: Option<String>,
+) -> Result<(), AppError> {
+  let request = approval_repo::get_request(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "审批申请不存在"))?;
+  if request.requested_by == decided_by {
+    return Err(AppError::new(ErrorCode::Forbidden, "不能审批本人提交的申请"));
+  }
+  let status = if approve { "approved" } else { "rejected" };
+  let note = note.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  if approval_repo::decide_request(pool, id, status, decided_by, Utc::now().timestamp(), note).await? == 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "审批申请已处理"));
+  }
+  Ok(())
+}
+
+/// 敏感操作执行前校验：须有申请人本人对同一对象已通过且未执行的申请，返回申请 ID
+pub async fn ensure_approved(
+  pool: &SqlitePool,
+  kind: &str,
+  target: &str,
+  requested_by: &str,
+) -> Result<String, AppError> {
+  match approval_repo::find_open_request(pool, kind, target, requested_by).await? {
+    Some((id, status)) if status == "approved" => Ok(id),
+    Some(_) => Err(AppError::new(ErrorCode::Forbidden, "审批申请尚未通过，请等待审批")),
+    None => Err(AppError::new(
+      ErrorCode::Forbidden,
+      "该操作需经另一名管理员审批，请先提交审批申请",
+    )),
+  }
+}
+
+/// 操作执行成功后将申请标记为已执行，同一申请不能重复使用
+pub async fn mark_executed(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  approval_repo::mark_executed(pool, id, Utc::now().timestamp()).await
+}
+
+/// 数据库恢复前校验审批，需审批时返回已通过的申请；恢复会替换数据库，申请须在恢复前读出
+pub async fn ensure_restore_approved(
+  pool: &SqlitePool,
+  target: &str,
+  requested_by: &str,
+) -> Result<Option<ApprovalRequestRow>, AppError> {
+  if !restore_requires_approval(pool).await? {
+    return Ok(None);
+  }
+  let id = ensure_approved(pool, "restore", target, requested_by).await?;
+  approval_repo::get_request(pool, &id)
+    .await?
+    .map(Some)
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "审批申请不存在"))
+}
+
+/// 在恢复后的数据库中把申请记为已执行，避免同一申请再次用于恢复
+pub async fn mark_restore_executed(pool: &SqlitePool, request: &ApprovalRequestRow) -> Result<(), AppError> {
+  approval_repo::upsert_executed(pool, request, Utc::now().timestamp()).await
+}
+
+fn required_text<'a>(value: Option<&'a str>, label: &str) -> Result<&'a str, AppError> {
+  value
+    .map(str::trim)
+    .filter(|value| !value.is_empty())
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, format!("{}不能为空", label)))
+}
@@ -0,0 +1,334 @@
+// 到货预报（ASN）：登记供应商预计到货的物品与数量，收货时按预报明细生成 IN 流水并累加已收数量；
+// 已收与预计不一致的明细记为超收 / 短收，在收货差异报表中展示
+use std::collections::HashSet;
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::asn_repo::{self, AsnLineDetailRow, AsnLineRow, AsnListRow, AsnRow};
+use crate::repo::{item_repo, rack_repo, stock_repo, txn_repo};
+use crate::services::{item_service, txn_service};
+
+pub const ASN_STATUSES: [&str; 3] = ["open", "closed", "cancelled"];
+
+const MAX_SUPPLIER_LEN: usize = 64;
+/// 单张预报最多的明细数
+const MAX_LINES: usize = 200;
+/// 列表最多返回的条数
+const MAX_LIST: i64 = 500;
+
+/// 预报明细（预计数量为显示数量）
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AsnLineInput {
+  pub item_id: String,
+  pub expected_qty: f64,
+}
+
+/// 收货明细（本次收货数量为显示数量）
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct AsnReceiveLineInput {
+  pub line_id: String,
+  pub qty: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AsnCreated {
+  pub asn_id: String,
+  pub asn_no: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AsnReceived {
+  pub asn_no: String,
+  pub txn_nos: Vec<String>,
+  // 所有明细均已收足时自动结单
+  pub closed: bool,
+  // 收货后各明细的预计 / 已收与差异
+  pub lines: Vec<AsnLineDetailRow>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct AsnDetail {
+  pub id: String,
+  pub asn_no: String,
+  pub supplier: String,
+  pub eta: Option<i64>,
+  pub status: String,
+  pub note: Option<String>,
+  pub created_at: i64,
+  pub lines: Vec<AsnLineDetailRow>,
+}
+
+/// 登记到货预报：校验供应商与明细，明细中同一物品只能出现一次
+pub async fn create_asn(
+  pool: &SqlitePool,
+  supplier: &str,
+  eta: Option<i64>,
+  note: Option<String>,
+  lines: Vec<AsnLineInput>,
+  actor_operator_id: &str,
+) -> Result<AsnCreated, AppError> {
+  let supplier = supplier.trim();
+  if supplier.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "供应商不能为空"));
+  }
+  if supplier.chars().count() > MAX_SUPPLIER_LEN {
+    return Err(AppError::new(ErrorCode::ValidationError, "供应商名称过长"));
+  }
+  if lines.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "预报明细不能为空"));
+  }
+  if lines.len() > MAX_LINES {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("预报明细不能超过 {} 条", MAX_LINES),
+    ));
+  }
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+
+  let mut seen = HashSet::new();
+  let mut base_lines = Vec::with_capacity(lines.len());
+  for line in &lines {
+    if !seen.insert(line.item_id.as_str()) {
+      return Err(AppError::new(ErrorCode::ValidationError, "同一物品不能在预报中重复出现"));
+    }
+    let item = item_repo::get_item_by_id(pool, &line.item_id)
+      .await?
+      .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+    if item.status != "active" {
+      return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ItemInactive));
+    }
+    let qty = item_service::qty_to_base(pool, &line.item_id, line.expected_qty).await?;
+    if qty <= 0 {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+    }
+    base_lines.push((line.item_id.clone(), qty));
+  }
+
+  let now = Utc::now().timestamp();
+  let asn_id = Uuid::new_v4().to_string();
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+  let mut tx = pool.begin().await?;
+  let asn_no = format!("ASN-{:04}", txn_repo::next_txn_seq_tx(&mut tx, "", "ASN").await?);
+  asn_repo::insert_asn_tx(
+    &mut tx,
+    &AsnRow {
+      id: asn_id.clone(),
+      asn_no: asn_no.clone(),
+      supplier: supplier.to_string(),
+      eta,
+      status: "open".to_string(),
+      note,
+      created_by: operator.id.clone(),
+      created_at: now,
+    },
+  )
+  .await?;
+  for (index, (item_id, qty)) in base_lines.into_iter().enumerate() {
+    asn_repo::insert_line_tx(
+      &mut tx,
+      &AsnLineRow {
+        id: Uuid::new_v4().to_string(),
+        asn_id: asn_id.clone(),
+        line_no: index as i64 + 1,
+        item_id,
+        expected_qty: qty,
+        received_qty: 0,
+      },
+    )
+    .await?;
+  }
+  tx.commit().await?;
+  Ok(AsnCreated { asn_id, asn_no })
+}
+
+/// 按预报收货：每条明细生成一条 IN 流水（成本取物品当前成本）并累加已收数量，允许超收；
+/// 所有明细收足后自动结单
+pub async fn receive_against_asn(
+  pool: &SqlitePool,
+  asn_id: &str,
+  to_slot_id: &str,
+  lines: Vec<AsnReceiveLineInput>,
+  occurred_at: i64,
+  actor_operator_id: &str,
+  note: Option<String>,
+  location: Option<String>,
+) -> Result<AsnReceived, AppError> {
+  let asn = get_open_asn(pool, asn_id).await?;
+  if lines.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "收货明细不能为空"));
+  }
+  rack_repo::get_slot_by_id(pool, to_slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+  let asn_lines = asn_repo::list_lines(pool, asn_id).await?;
+
+  let mut seen = HashSet::new();
+  let mut receipts = Vec::with_capacity(lines.len());
+  for line in &lines {
+    let asn_line = asn_lines
+      .iter()
+      .find(|candidate| candidate.id == line.line_id)
+      .ok_or_else(|| AppError::new(ErrorCode::NotFound, "预报明细不存在"))?;
+    if !seen.insert(asn_line.id.as_str()) {
+      return Err(AppError::new(ErrorCode::ValidationError, "同一明细不能重复收货"));
+    }
+    let qty = item_service::qty_to_base(pool, &asn_line.item_id, line.qty).await?;
+    if qty <= 0 {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+    }
+    let item = item_repo::get_item_by_id(pool, &asn_line.item_id)
+      .await?
+      .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+    receipts.push((asn_line, qty, item.unit_cost));
+  }
+
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+  txn_service::ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = txn_service::normalize_location(pool, location).await?;
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+  let txn_note = match &note {
+    Some(note) => format!("到货 {}（{}）：{}", asn.asn_no, asn.supplier, note),
+    None => format!("到货 {}（{}）", asn.asn_no, asn.supplier),
+  };
+
+  let now = Utc::now().timestamp();
+  let scheme = txn_service::txn_no_scheme(pool).await?;
+
+  let mut tx = pool.begin().await?;
+  let mut txn_nos = Vec::with_capacity(receipts.len());
+  for (asn_line, qty, unit_cost) in receipts {
+    let txn_id = Uuid::new_v4().to_string();
+    let txn_no = txn_service::next_txn_no(&mut tx, &scheme, "IN", Some(to_slot_id)).await?;
+    txn_repo::insert_txn(
+      &mut tx,
+      &txn_repo::TxnRow {
+        id: txn_id.clone(),
+        txn_no: txn_no.clone(),
+        txn_type: "IN".to_string(),
+        occurred_at,
+        created_at: now,
+        operator_id: operator.id.clone(),
+        item_id: asn_line.item_id.clone(),
+        from_slot_id: None,
+        to_slot_id: Some(to_slot_id.to_string()),
+        qty,
+        actual_qty: None,
+        ref_txn_id: None,
+        note: Some(txn_note.clone()),
+        unit_cost,
+        location: location.clone(),
+        expires_at: None,
+        reason_id: None,
+      },
+    )
+    .await?;
+    let current = stock_repo::get_stock_tx(&mut tx, &asn_line.item_id, to_slot_id).await?;
+    let next_qty = current.map(|s| s.qty).unwrap_or(0) + qty;
+    stock_repo::upsert_stock_tx(&mut tx, &asn_line.item_id, to_slot_id, next_qty, now).await?;
+    asn_repo::add_received_tx(&mut tx, &asn_line.id, qty).await?;
+    asn_repo::insert_receipt_tx(&mut tx, &Uuid::new_v4().to_string(), &asn_line.id, &txn_id, qty, now).await?;
+    txn_nos.push(txn_no);
+  }
+  let closed = if asn_repo::all_lines_received_tx(&mut tx, asn_id).await? {
+    asn_repo::finish_asn_tx(&mut tx, asn_id, "closed", now).await? > 0
+  } else {
+    false
+  };
+  tx.commit().await?;
+  txn_service::touch_kiosk_operator(pool, &operator.id).await?;
+
+  Ok(AsnReceived {
+    asn_no: asn.asn_no,
+    txn_nos,
+    closed,
+    lines: asn_repo::list_line_details(pool, asn_id).await?,
+  })
+}
+
+/// 手动结单：未收足的明细按短收计入差异报表
+pub async fn close_asn(pool: &SqlitePool, asn_id: &str) -> Result<(), AppError> {
+  get_open_asn(pool, asn_id).await?;
+  let mut tx = pool.begin().await?;
+  if asn_repo::finish_asn_tx(&mut tx, asn_id, "closed", Utc::now().timestamp()).await? == 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "到货预报已处理"));
+  }
+  tx.commit().await?;
+  Ok(())
+}
+
+/// 取消预报，仅未收货的预报可取消
+pub async fn cancel_asn(pool: &SqlitePool, asn_id: &str) -> Result<(), AppError> {
+  get_open_asn(pool, asn_id).await?;
+  let lines = asn_repo::list_lines(pool, asn_id).await?;
+  if lines.iter().any(|line| line.received_qty > 0) {
+    return Err(AppError::new(ErrorCode::Conflict, "预报已有收货记录，不能取消，请改为结单"));
+  }
+  let mut tx = pool.begin().await?;
+  if asn_repo::finish_asn_tx(&mut tx, asn_id, "cancelled", Utc::now().timestamp()).await? == 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "到货预报已处理"));
+  }
+  tx.commit().await?;
+  Ok(())
+}
+
+pub async fn list_asns(
+  pool: &SqlitePool,
+  status: Option<&str>,
+  keyword: Option<String>,
+) -> Result<Vec<AsnListRow>, AppError> {
+  if let Some(status) = status {
+    if !ASN_STATUSES.contains(&status) {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
+    }
+  }
+  let keyword = keyword.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  asn_repo::list_asns(pool, status, keyword.as_deref(), MAX_LIST).await
+}
+
+pub async fn get_asn(pool: &SqlitePool, asn_id: &str) -> Result<AsnDetail, AppError> {
+  let asn = asn_repo::get_asn(pool, asn_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "到货预报不存在"))?;
+  Ok(AsnDetail {
+    lines: asn_repo::list_line_details(pool, asn_id).await?,
+    id: asn.id,
+    asn_no: asn.asn_no,
+    supplier: asn.supplier,
+    eta: asn.eta,
+    status: asn.status,
+    note: asn.note,
+    created_at: asn.created_at,
+  })
+}
+
+/// 收货差异报表：已结单的超收 / 短收明细，以及待收货预报中已超收的明细
+pub async fn variance_report(
+  pool: &SqlitePool,
+  supplier: Option<String>,
+  start_at: Option<i64>,
+  end_at: Option<i64>,
+) -> Result<Vec<AsnLineDetailRow>, AppError> {
+  if let (Some(start_at), Some(end_at)) = (start_at, end_at) {
+    if start_at > end_at {
+      return Err(AppError::new(ErrorCode::ValidationError, "开始时间不能晚于结束时间"));
+    }
+  }
+  let supplier = supplier.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  asn_repo::list_variances(pool, supplier.as_deref(), start_at, end_at, MAX_LIST).await
+}
+
+/// 到货预报（须为待收货状态）
+pub async fn get_open_asn(pool: &SqlitePool, asn_id: &str) -> Result<AsnRow, AppError> {
+  let asn = asn_repo::get_asn(pool, asn_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "到货预报不存在"))?;
+  if asn.status != "open" {
+    return Err(AppError::new(ErrorCode::Conflict, "到货预报已处理"));
+  }
+  Ok(asn)
+}
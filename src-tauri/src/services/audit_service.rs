@@ -1,15 +1,42 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+
 use chrono::Utc;
+use csv::WriterBuilder;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use serde_json::Value;
 use uuid::Uuid;
 
-use crate::domain::audit::AuditAction;
+use crate::domain::audit::{AuditAction, AuditLevel};
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::repo::audit_repo::{self, AuditLogRow};
-use crate::repo::operator_repo;
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
-use crate::repo::meta_repo;
+use crate::domain::messages::MessageKey;
+use crate::infra::fs;
+use crate::repo::audit_repo::{self, AuditLogFilter, AuditLogRow};
+use crate::repo::{meta_repo, operator_repo};
+use crate::services::system_service;
 use sqlx::SqlitePool;
 
+/// 审计详略：all 记录全部，writes 跳过查询类，critical 仅记录关键操作
+pub const AUDIT_VERBOSITY_VALUES: &[&str] = &["all", "writes", "critical"];
+
+/// 按审计详略配置判断该动作是否需要落库（读取失败时按全部记录处理）
+pub async fn should_record(pool: &SqlitePool, action: AuditAction) -> bool {
+  let verbosity = meta_repo::get_meta_value(pool, "audit_verbosity")
+    .await
+    .ok()
+    .flatten()
+    .unwrap_or_else(|| "all".to_string());
+  let min_level = match verbosity.as_str() {
+    "writes" => AuditLevel::Write,
+    "critical" => AuditLevel::Critical,
+    _ => AuditLevel::Read,
+  };
+  action.level() >= min_level
+}
+
 /// 写入审计日志并统一格式化结果
 pub async fn write_audit(
   pool: &SqlitePool,
@@ -57,6 +84,9 @@ pub struct AuditListResult {
   pub items: Vec<AuditLogRow>,
   // 总数
   pub total: i64,
+  // 下一页游标：本页已满时为最后一条的 created_at/id，传入 after_created_at/after_id 继续翻页
+  pub next_after_created_at: Option<i64>,
+  pub next_after_id: Option<String>,
 }
 
 /// 审计导出返回结构
@@ -66,43 +96,81 @@ pub struct AuditExportResult {
   pub file_path: String,
 }
 
-/// 查询审计列表
+/// 导出时每批读取的记录数
+const EXPORT_BATCH_SIZE: i64 = 500;
+
+/// 查询审计列表：提供游标（after_created_at + after_id）时按游标翻页，否则按页码偏移
 pub async fn list_audit_logs(
   pool: &SqlitePool,
-  action: Option<String>,
-  keyword: Option<String>,
-  start_at: Option<i64>,
-  end_at: Option<i64>,
+  filter: AuditLogFilter,
   page_index: i64,
   page_size: i64,
+  after_created_at: Option<i64>,
+  after_id: Option<String>,
 ) -> Result<AuditListResult, AppError> {
   let (page_index, page_size) = normalize_page(page_index, page_size)?;
-  let total =
-    audit_repo::count_audit_logs(pool, action.clone(), keyword.clone(), start_at, end_at)
-      .await?;
-  let mut items = audit_repo::list_audit_logs(
-    pool,
-    action,
-    keyword,
-    start_at,
-    end_at,
-    page_index,
-    page_size,
-  )
-  .await?;
+  let cursor = keyset_cursor(after_created_at, after_id)?;
+  validate_filter(&filter)?;
+  let total = audit_repo::count_audit_logs(pool, &filter).await?;
+  let mut items = match cursor {
+    Some(cursor) => {
+      audit_repo::list_audit_logs_after(pool, &filter, Some(cursor), page_size).await?
+    }
+    None => audit_repo::list_audit_logs(pool, &filter, page_index, page_size).await?,
+  };
   attach_actor_names(pool, &mut items).await?;
-  Ok(AuditListResult { items, total })
+  let next = items
+    .last()
+    .filter(|_| items.len() as i64 == page_size)
+    .map(|row| (row.created_at, row.id.clone()));
+  Ok(AuditListResult {
+    items,
+    total,
+    next_after_created_at: next.as_ref().map(|(created_at, _)| *created_at),
+    next_after_id: next.map(|(_, id)| id),
+  })
+}
+
+/// 游标参数需成对提供
+fn keyset_cursor(
+  after_created_at: Option<i64>,
+  after_id: Option<String>,
+) -> Result<Option<(i64, String)>, AppError> {
+  match (after_created_at, after_id) {
+    (Some(created_at), Some(id)) => Ok(Some((created_at, id))),
+    (None, None) => Ok(None),
+    _ => Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidCursor)),
+  }
 }
 
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
   if page_index < 1 || page_size < 1 {
-    return Err(AppError::new(ErrorCode::ValidationError, "分页参数非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
   }
   Ok((page_index, page_size))
 }
 
-/// 导出审计日志为 CSV
-pub async fn export_audit_logs(pool: &SqlitePool) -> Result<AuditExportResult, AppError> {
+fn validate_filter(filter: &AuditLogFilter) -> Result<(), AppError> {
+  if let Some(result) = filter.result.as_deref() {
+    if !matches!(result, "success" | "fail") {
+      return Err(AppError::new(ErrorCode::ValidationError, "审计结果筛选值非法"));
+    }
+  }
+  if let (Some(start_at), Some(end_at)) = (filter.start_at, filter.end_at) {
+    if start_at > end_at {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidTimeRange));
+    }
+  }
+  Ok(())
+}
+
+/// 按筛选条件导出审计日志为 CSV（分批读取并逐行写出）
+pub async fn export_audit_logs(
+  pool: &SqlitePool,
+  filter: AuditLogFilter,
+) -> Result<AuditExportResult, AppError> {
+  validate_filter(&filter)?;
+
   // 在移动端使用临时文件，桌面端使用导出目录
   #[cfg(any(target_os = "android", target_os = "ios"))]
   let file_path = {
@@ -115,46 +183,302 @@ pub async fn export_audit_logs(pool: &SqlitePool) -> Result<AuditExportResult, A
   let file_path = {
       let storage_root = meta_repo::get_meta_value(pool, "storage_root")
           .await?
-          .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+          .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
       let export_dir = match meta_repo::get_meta_value(pool, "exports_dir").await? {
           Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
           _ => std::path::PathBuf::from(storage_root).join("exports"),
       };
       std::fs::create_dir_all(&export_dir)
-          .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出目录失败"))?;
+          .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
       let now = Utc::now().timestamp();
       export_dir.join(format!("audit_logs_{}.csv", now))
   };
-  let mut lines = Vec::new();
-  lines.push("id,created_at,actor_operator_id,actor_operator_name,action,target_type,target_id,request_json,result,error_code,error_detail".to_string());
 
-  let mut items = audit_repo::list_audit_logs_all(pool, None).await?;
-  attach_actor_names(pool, &mut items).await?;
-  for item in items {
-    lines.push(format!(
-      "{},{},{},{},{},{},{},{},{},{},{}",
-      escape_csv(&item.id),
-      item.created_at,
-      escape_csv(item.actor_operator_id.as_deref().unwrap_or("")),
-      escape_csv(item.actor_operator_name.as_deref().unwrap_or("")),
-      escape_csv(&item.action),
-      escape_csv(item.target_type.as_deref().unwrap_or("")),
-      escape_csv(item.target_id.as_deref().unwrap_or("")),
-      escape_csv(item.request_json.as_deref().unwrap_or("")),
-      escape_csv(&item.result),
-      escape_csv(item.error_code.as_deref().unwrap_or("")),
-      escape_csv(item.error_detail.as_deref().unwrap_or(""))
-    ));
-  }
-
-  std::fs::write(&file_path, lines.join("\n"))
-    .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+  let mut writer = WriterBuilder::new()
+    .has_headers(true)
+    .from_path(&file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
+  writer
+    .write_record([
+      "id",
+      "created_at",
+      "actor_operator_id",
+      "actor_operator_name",
+      "action",
+      "target_type",
+      "target_id",
+      "request_json",
+      "result",
+      "error_code",
+      "error_detail",
+    ])
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  // created_at 按报表时区格式化
+  let timezone = system_service::report_timezone(pool).await?;
+  let mut cursor: Option<(i64, String)> = None;
+  loop {
+    let mut items =
+      audit_repo::list_audit_logs_after(pool, &filter, cursor.take(), EXPORT_BATCH_SIZE).await?;
+    if items.is_empty() {
+      break;
+    }
+    attach_actor_names(pool, &mut items).await?;
+    for item in &items {
+      writer
+        .write_record([
+          item.id.as_str(),
+          &timezone.format_timestamp(item.created_at),
+          item.actor_operator_id.as_deref().unwrap_or(""),
+          item.actor_operator_name.as_deref().unwrap_or(""),
+          item.action.as_str(),
+          item.target_type.as_deref().unwrap_or(""),
+          item.target_id.as_deref().unwrap_or(""),
+          item.request_json.as_deref().unwrap_or(""),
+          item.result.as_str(),
+          item.error_code.as_deref().unwrap_or(""),
+          item.error_detail.as_deref().unwrap_or(""),
+        ])
+        .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+    }
+    let reached_end = (items.len() as i64) < EXPORT_BATCH_SIZE;
+    if let Some(last) = items.last() {
+      cursor = Some((last.created_at, last.id.clone()));
+    }
+    if reached_end {
+      break;
+    }
+  }
+
+  writer
+    .flush()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
 
   Ok(AuditExportResult {
     file_path: file_path.to_string_lossy().to_string(),
   })
 }
 
+/// 审计归档返回结构
+#[derive(Debug, serde::Serialize)]
+pub struct AuditArchiveResult {
+  // 归档文件路径，无可归档记录时为空
+  pub file_path: Option<String>,
+  // 归档条数
+  pub archived: i64,
+}
+
+/// 归档检索条件
+#[derive(Debug, Clone, Default)]
+pub struct AuditArchiveQuery {
+  pub keyword: Option<String>,
+  pub operator_id: Option<String>,
+  pub target_id: Option<String>,
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+}
+
+/// 归档检索返回结构
+#[derive(Debug, serde::Serialize)]
+pub struct AuditArchiveSearchResult {
+  pub items: Vec<AuditLogRow>,
+  // 命中总数
+  pub total: i64,
+  // 实际扫描的归档文件数（时间范围不相交的文件直接跳过）
+  pub scanned_files: i64,
+}
+
+/// 归档检索单页上限
+const ARCHIVE_SEARCH_MAX_PAGE_SIZE: i64 = 200;
+
+/// 审计归档目录：<storage_root>/archives/audit
+async fn audit_archive_dir(pool: &SqlitePool) -> Result<PathBuf, AppError> {
+  let storage_root = meta_repo::get_meta_value(pool, "storage_root")
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
+  Ok(PathBuf::from(storage_root).join("archives").join("audit"))
+}
+
+/// 解析归档文件名 audit_<最早>_<最晚>_<归档时间>.jsonl.gz 中的时间范围
+fn parse_archive_range(file_name: &str) -> Option<(i64, i64)> {
+  let stem = file_name.strip_prefix("audit_")?.strip_suffix(".jsonl.gz")?;
+  let mut parts = stem.split('_');
+  let first = parts.next()?.parse().ok()?;
+  let last = parts.next()?.parse().ok()?;
+  Some((first, last))
+}
+
+/// 将 before_at 之前的审计日志按行写入 gzip 压缩的 JSON Lines 文件，落盘后从库中删除
+pub async fn archive_audit_logs(
+  pool: &SqlitePool,
+  before_at: i64,
+) -> Result<AuditArchiveResult, AppError> {
+  let now = Utc::now().timestamp();
+  if before_at > now {
+    return Err(AppError::new(ErrorCode::ValidationError, "归档截止时间不能晚于当前时间"));
+  }
+  let archive_dir = audit_archive_dir(pool).await?;
+  fs::ensure_dir(&archive_dir)?;
+
+  // 先写临时文件，完整写出后再按时间范围重命名
+  let temp_path = archive_dir.join(format!("audit_{}.jsonl.gz.tmp", now));
+  let file = File::create(&temp_path)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建归档文件失败"))?;
+  let mut encoder = GzEncoder::new(BufWriter::new(file), Compression::default());
+  let write_error = || AppError::new(ErrorCode::IoError, "写入归档文件失败");
+
+  let filter = AuditLogFilter {
+    end_at: Some(before_at - 1),
+    ..AuditLogFilter::default()
+  };
+  let mut cursor: Option<(i64, String)> = None;
+  let mut archived = 0_i64;
+  let mut range: Option<(i64, i64)> = None;
+  loop {
+    let mut items =
+      audit_repo::list_audit_logs_after(pool, &filter, cursor.take(), EXPORT_BATCH_SIZE).await?;
+    if items.is_empty() {
+      break;
+    }
+    attach_actor_names(pool, &mut items).await?;
+    for item in &items {
+      let line = serde_json::to_string(item).map_err(|_| write_error())?;
+      writeln!(encoder, "{}", line).map_err(|_| write_error())?;
+      range = Some(match range {
+        Some((first, last)) => (first.min(item.created_at), last.max(item.created_at)),
+        None => (item.created_at, item.created_at),
+      });
+    }
+    archived += items.len() as i64;
+    let reached_end = (items.len() as i64) < EXPORT_BATCH_SIZE;
+    if let Some(last) = items.last() {
+      cursor = Some((last.created_at, last.id.clone()));
+    }
+    if reached_end {
+      break;
+    }
+  }
+
+  let writer = encoder.finish().map_err(|_| write_error())?;
+  writer.into_inner().map_err(|_| write_error())?;
+
+  let Some((first, last)) = range else {
+    let _ = std::fs::remove_file(&temp_path);
+    return Ok(AuditArchiveResult {
+      file_path: None,
+      archived: 0,
+    });
+  };
+  let file_path = archive_dir.join(format!("audit_{}_{}_{}.jsonl.gz", first, last, now));
+  std::fs::rename(&temp_path, &file_path).map_err(|_| write_error())?;
+
+  audit_repo::delete_audit_logs_before(pool, before_at).await?;
+
+  Ok(AuditArchiveResult {
+    file_path: Some(file_path.to_string_lossy().to_string()),
+    archived,
+  })
+}
+
+fn archive_row_matches(row: &AuditLogRow, query: &AuditArchiveQuery, keyword: Option<&str>) -> bool {
+  if query.start_at.is_some_and(|start_at| row.created_at < start_at)
+    || query.end_at.is_some_and(|end_at| row.created_at > end_at)
+  {
+    return false;
+  }
+  if let Some(operator_id) = query.operator_id.as_deref() {
+    if row.actor_operator_id.as_deref() != Some(operator_id) {
+      return false;
+    }
+  }
+  if let Some(target_id) = query.target_id.as_deref() {
+    if row.target_id.as_deref() != Some(target_id) {
+      return false;
+    }
+  }
+  match keyword {
+    Some(keyword) => [
+      Some(row.action.as_str()),
+      row.target_type.as_deref(),
+      row.target_id.as_deref(),
+      row.actor_operator_id.as_deref(),
+      row.actor_operator_name.as_deref(),
+      row.request_json.as_deref(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|value| value.to_lowercase().contains(keyword)),
+    None => true,
+  }
+}
+
+/// 检索审计归档：按文件名中的时间范围筛选归档文件，逐行解压匹配，
+/// 仅保留当前页的记录，不会将整个归档载入内存
+pub async fn search_audit_archives(
+  pool: &SqlitePool,
+  query: AuditArchiveQuery,
+  page_index: i64,
+  page_size: i64,
+) -> Result<AuditArchiveSearchResult, AppError> {
+  let (page_index, page_size) = normalize_page(page_index, page_size)?;
+  let page_size = page_size.min(ARCHIVE_SEARCH_MAX_PAGE_SIZE);
+  if let (Some(start_at), Some(end_at)) = (query.start_at, query.end_at) {
+    if start_at > end_at {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidTimeRange));
+    }
+  }
+  let keyword = query
+    .keyword
+    .as_deref()
+    .map(|value| value.trim().to_lowercase())
+    .filter(|value| !value.is_empty());
+
+  let mut result = AuditArchiveSearchResult {
+    items: Vec::new(),
+    total: 0,
+    scanned_files: 0,
+  };
+  let archive_dir = audit_archive_dir(pool).await?;
+  if !archive_dir.exists() {
+    return Ok(result);
+  }
+  let entries = std::fs::read_dir(&archive_dir)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "读取归档目录失败"))?;
+  let mut files: Vec<(i64, i64, PathBuf)> = entries
+    .filter_map(|entry| entry.ok())
+    .filter_map(|entry| {
+      let name = entry.file_name().to_string_lossy().to_string();
+      parse_archive_range(&name).map(|(first, last)| (first, last, entry.path()))
+    })
+    .filter(|(first, last, _)| {
+      query.start_at.unwrap_or(i64::MIN) <= *last && *first <= query.end_at.unwrap_or(i64::MAX)
+    })
+    .collect();
+  // 新归档在前，与审计列表的倒序一致
+  files.sort_by(|a, b| b.1.cmp(&a.1).then(b.0.cmp(&a.0)));
+
+  let skip = (page_index - 1) * page_size;
+  for (_, _, path) in files {
+    result.scanned_files += 1;
+    let file = File::open(&path)
+      .map_err(|_| AppError::new(ErrorCode::IoError, "读取归档文件失败"))?;
+    for line in BufReader::new(GzDecoder::new(file)).lines() {
+      let line = line.map_err(|_| AppError::new(ErrorCode::IoError, "读取归档文件失败"))?;
+      let Ok(row) = serde_json::from_str::<AuditLogRow>(&line) else {
+        continue;
+      };
+      if !archive_row_matches(&row, &query, keyword.as_deref()) {
+        continue;
+      }
+      if result.total >= skip && (result.items.len() as i64) < page_size {
+        result.items.push(row);
+      }
+      result.total += 1;
+    }
+  }
+  Ok(result)
+}
+
 /// 截断错误详情，防止审计记录过长
 fn truncate_error(message: &str) -> String {
   let max_len = 200;
@@ -171,16 +495,6 @@ trait ErrorCodeStr {
   fn as_str(&self) -> &'static str;
 }
 
-/// CSV 字段转义
-fn escape_csv(value: &str) -> String {
-  let needs_wrap = value.contains(',') || value.contains('"') || value.contains('\n');
-  if !needs_wrap {
-    return value.to_string();
-  }
-  let escaped = value.replace('"', "\"\"");
-  format!("\"{}\"", escaped)
-}
-
 async fn attach_actor_names(
   pool: &SqlitePool,
   items: &mut [AuditLogRow],
@@ -204,7 +518,10 @@ impl ErrorCodeStr for ErrorCode {
   fn as_str(&self) -> &'static str {
     match self {
       ErrorCode::AuthFailed => "AUTH_FAILED",
+      ErrorCode::AccountLocked => "ACCOUNT_LOCKED",
       ErrorCode::PwdChangeRequired => "PWD_CHANGE_REQUIRED",
+      ErrorCode::SessionExpired => "SESSION_EXPIRED",
+      ErrorCode::ReadOnlyMode => "READ_ONLY_MODE",
       ErrorCode::ValidationError => "VALIDATION_ERROR",
       ErrorCode::NotFound => "NOT_FOUND",
       ErrorCode::InactiveResource => "INACTIVE_RESOURCE",
@@ -212,6 +529,8 @@ impl ErrorCodeStr for ErrorCode {
       ErrorCode::Conflict => "CONFLICT",
       ErrorCode::Forbidden => "FORBIDDEN",
       ErrorCode::DbError => "DB_ERROR",
+      ErrorCode::DbBusy => "DB_BUSY",
+      ErrorCode::DbIncompatible => "DB_INCOMPATIBLE",
       ErrorCode::IoError => "IO_ERROR",
     }
   }
@@ -1,8 +1,13 @@
 use chrono::Utc;
 use sqlx::{Row, SqlitePool};
 
+use crate::domain::audit::AuditAction;
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 use crate::infra::crypto;
+use crate::repo::login_attempt_repo::{self, LoginAttemptRow};
+use crate::repo::{meta_repo, operator_repo, reset_code_repo};
+use crate::services::{audit_service, password_policy_service, session_service};
 
 /// 登录返回结构
 #[derive(Debug, serde::Serialize)]
@@ -15,6 +20,34 @@ pub struct LoginResult {
   pub role: String,
   // 是否必须改密
   pub must_change_pwd: bool,
+  // 会话 id
+  pub session_id: String,
+}
+
+/// PIN 切换人员返回结构
+#[derive(Debug, serde::Serialize)]
+pub struct SwitchOperatorResult {
+  // 操作人 id
+  pub actor_operator_id: String,
+  // 操作人用户名
+  pub username: String,
+  // 显示名
+  pub display_name: String,
+  // 角色
+  pub role: String,
+  // 是否必须改密
+  pub must_change_pwd: bool,
+  // 短时会话 id
+  pub session_id: String,
+  // 会话过期时间
+  pub expires_at: i64,
+}
+
+/// 登录失败锁定策略
+struct LockoutPolicy {
+  max_failures: i64,
+  window_secs: i64,
+  lockout_secs: i64,
 }
 
 pub async fn login(
@@ -22,6 +55,9 @@ pub async fn login(
   username: &str,
   password: &str,
 ) -> Result<LoginResult, AppError> {
+  let now = Utc::now().timestamp();
+  ensure_not_locked(pool, username, now).await?;
+
   // 按用户名查找并校验密码
   let row = sqlx::query(
     "SELECT id, username, role, password_hash, must_change_pwd, status \
@@ -32,40 +68,307 @@ pub async fn login(
   .await?;
 
   let Some(row) = row else {
-    return Err(AppError::new(ErrorCode::AuthFailed, "账号或密码错误"));
+    return Err(record_failure(pool, username, now).await?);
   };
 
   let status: String = row.get("status");
   if status != "active" {
-    return Err(AppError::new(ErrorCode::InactiveResource, "账号已停用"));
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::AccountInactive));
   }
 
   let password_hash: String = row.get("password_hash");
   let ok = crypto::verify_password(&password_hash, password)?;
   if !ok {
-    return Err(AppError::new(ErrorCode::AuthFailed, "账号或密码错误"));
+    return Err(record_failure(pool, username, now).await?);
   }
+  login_attempt_repo::clear_attempt(pool, username).await?;
 
   let must_change_pwd: i64 = row.get("must_change_pwd");
   let id: String = row.get("id");
   let username: String = row.get("username");
   let role: String = row.get("role");
+  let session = session_service::create_session(pool, &id, "password", None).await?;
 
   Ok(LoginResult {
     actor_operator_id: id,
     username,
     role,
     must_change_pwd: must_change_pwd == 1,
+    session_id: session.id,
+  })
+}
+
+/// 校验管理员账号密码并返回其 id，不创建会话；与登录共用失败计数与锁定（pool 须可写）。
+/// 用于启动失败时从启动屏恢复备份
+pub async fn verify_admin_password(
+  pool: &SqlitePool,
+  username: &str,
+  password: &str,
+) -> Result<String, AppError> {
+  let now = Utc::now().timestamp();
+  ensure_not_locked(pool, username, now).await?;
+  match check_admin_password(pool, username, password).await {
+    Err(err) if matches!(err.code, ErrorCode::AuthFailed) => Err(record_failure(pool, username, now).await?),
+    Err(err) => Err(err),
+    Ok(id) => {
+      login_attempt_repo::clear_attempt(pool, username).await?;
+      Ok(id)
+    }
+  }
+}
+
+/// 仅校验管理员账号密码，不读写失败计数；数据库只能以只读方式打开时由调用方自行限制重试次数
+pub async fn check_admin_password(
+  pool: &SqlitePool,
+  username: &str,
+  password: &str,
+) -> Result<String, AppError> {
+  let row = sqlx::query("SELECT id, role, password_hash, status FROM operator WHERE username = ?")
+    .bind(username)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::AuthFailed, MessageKey::InvalidCredentials))?;
+  let password_hash: String = row.get("password_hash");
+  if !crypto::verify_password(&password_hash, password)? {
+    return Err(AppError::keyed(ErrorCode::AuthFailed, MessageKey::InvalidCredentials));
+  }
+  let role: String = row.get("role");
+  let status: String = row.get("status");
+  if role != "admin" || status != "active" {
+    return Err(AppError::keyed(ErrorCode::Forbidden, MessageKey::Forbidden));
+  }
+  Ok(row.get("id"))
+}
+
+/// 使用 PIN 快速切换人员，返回短时会话
+pub async fn switch_operator(
+  pool: &SqlitePool,
+  username: &str,
+  pin: &str,
+  previous_session_id: Option<&str>,
+) -> Result<SwitchOperatorResult, AppError> {
+  let now = Utc::now().timestamp();
+  ensure_not_locked(pool, username, now).await?;
+
+  let Some(operator) = operator_repo::get_operator_by_username(pool, username).await? else {
+    return Err(record_failure(pool, username, now).await?);
+  };
+  if operator.status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::AccountInactive));
+  }
+  let Some(pin_hash) = operator_repo::get_pin_hash(pool, &operator.id).await? else {
+    return Err(AppError::new(ErrorCode::AuthFailed, "该账号未设置 PIN，请使用密码登录"));
+  };
+  if !crypto::verify_password(&pin_hash, pin)? {
+    return Err(record_failure(pool, username, now).await?);
+  }
+  login_attempt_repo::clear_attempt(pool, username).await?;
+
+  if let Some(previous_session_id) = previous_session_id {
+    session_service::end_session(pool, previous_session_id, "switched").await?;
+  }
+  let ttl_minutes = meta_repo::get_meta_i64(pool, "pin_session_minutes")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(30);
+  let session =
+    session_service::create_session(pool, &operator.id, "pin", Some(ttl_minutes * 60)).await?;
+
+  Ok(SwitchOperatorResult {
+    actor_operator_id: operator.id,
+    username: operator.username,
+    display_name: operator.display_name,
+    role: operator.role,
+    must_change_pwd: operator.must_change_pwd,
+    session_id: session.id,
+    expires_at: session.expires_at.unwrap_or(now),
+  })
+}
+
+/// 设置或清除本人 PIN（需校验登录密码）
+pub async fn set_pin(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  password: &str,
+  pin: Option<&str>,
+) -> Result<(), AppError> {
+  let hash = operator_repo::get_password_hash(pool, actor_operator_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "用户不存在"))?;
+  if !crypto::verify_password(&hash, password)? {
+    return Err(AppError::keyed(ErrorCode::AuthFailed, MessageKey::WrongPassword));
+  }
+
+  let Some(pin) = pin else {
+    return operator_repo::set_pin_hash(pool, actor_operator_id, None).await;
+  };
+  if !(4..=8).contains(&pin.len()) || !pin.chars().all(|c| c.is_ascii_digit()) {
+    return Err(AppError::new(ErrorCode::ValidationError, "PIN 必须为 4-8 位数字"));
+  }
+  let pin_hash = crypto::hash_password(pin)?;
+  operator_repo::set_pin_hash(pool, actor_operator_id, Some(&pin_hash)).await
+}
+
+/// 凭管理员签发的一次性重置码设置新密码（登录页使用）；
+/// 失败计入登录失败次数，与密码登录共用锁定策略
+pub async fn reset_password_with_code(
+  pool: &SqlitePool,
+  username: &str,
+  code: &str,
+  new_password: &str,
+) -> Result<(), AppError> {
+  let now = Utc::now().timestamp();
+  ensure_not_locked(pool, username, now).await?;
+
+  let Some(operator) = operator_repo::get_operator_by_username(pool, username).await? else {
+    return Err(record_failure(pool, username, now).await?);
+  };
+  if operator.status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::AccountInactive));
+  }
+  let Some(reset_code) = reset_code_repo::get_reset_code(pool, &operator.id).await? else {
+    return Err(record_failure(pool, username, now).await?);
+  };
+  // 输入时允许小写与分隔符
+  let code: String = code
+    .chars()
+    .filter(|c| c.is_ascii_alphanumeric())
+    .map(|c| c.to_ascii_uppercase())
+    .collect();
+  if !crypto::verify_password(&reset_code.code_hash, &code)? {
+    return Err(record_failure(pool, username, now).await?);
+  }
+  if reset_code.expires_at <= now {
+    reset_code_repo::delete_reset_code(pool, &operator.id).await?;
+    return Err(AppError::new(ErrorCode::AuthFailed, "重置码已过期，请联系管理员重新签发"));
+  }
+
+  password_policy_service::validate_new_password(pool, Some(&operator.id), username, new_password)
+    .await?;
+  let new_hash = crypto::hash_password(new_password)?;
+  sqlx::query(
+    "UPDATE operator SET password_hash = ?, must_change_pwd = 0, pwd_changed_at = ? WHERE id = ?",
+  )
+  .bind(&new_hash)
+  .bind(now)
+  .bind(&operator.id)
+  .execute(pool)
+  .await?;
+  password_policy_service::record_history(pool, &operator.id, &new_hash, now).await?;
+  reset_code_repo::delete_reset_code(pool, &operator.id).await?;
+  login_attempt_repo::clear_attempt(pool, username).await?;
+  Ok(())
+}
+
+/// 解除账号登录锁定，返回是否存在锁定/失败记录
+pub async fn unlock_login(pool: &SqlitePool, username: &str) -> Result<bool, AppError> {
+  login_attempt_repo::clear_attempt(pool, username).await
+}
+
+/// 校验账号是否处于锁定期
+async fn ensure_not_locked(pool: &SqlitePool, username: &str, now: i64) -> Result<(), AppError> {
+  let Some(attempt) = login_attempt_repo::get_attempt(pool, username).await? else {
+    return Ok(());
+  };
+  match attempt.locked_until {
+    Some(locked_until) if locked_until > now => Err(locked_error(locked_until, now)),
+    _ => Ok(()),
+  }
+}
+
+/// 记录一次登录失败，达到阈值时锁定账号并写审计；返回应抛出的错误
+async fn record_failure(pool: &SqlitePool, username: &str, now: i64) -> Result<AppError, AppError> {
+  let failed = AppError::keyed(ErrorCode::AuthFailed, MessageKey::InvalidCredentials);
+  let policy = load_lockout_policy(pool).await?;
+  if policy.max_failures == 0 {
+    return Ok(failed);
+  }
+
+  // 超出计数窗口或上次锁定已过期时重新计数
+  let previous = login_attempt_repo::get_attempt(pool, username)
+    .await?
+    .filter(|row| now - row.first_failed_at <= policy.window_secs)
+    .filter(|row| row.locked_until.map(|until| until > now).unwrap_or(true));
+  let (failed_count, first_failed_at) = match previous {
+    Some(row) => (row.failed_count + 1, row.first_failed_at),
+    None => (1, now),
+  };
+  let locked_until = if failed_count >= policy.max_failures {
+    Some(now + policy.lockout_secs)
+  } else {
+    None
+  };
+
+  login_attempt_repo::upsert_attempt(
+    pool,
+    &LoginAttemptRow {
+      username: username.to_string(),
+      failed_count,
+      first_failed_at,
+      last_failed_at: now,
+      locked_until,
+    },
+  )
+  .await?;
+
+  let Some(locked_until) = locked_until else {
+    return Ok(failed);
+  };
+  let audit_request = serde_json::json!({
+    "username": username,
+    "failed_count": failed_count,
+    "locked_until": locked_until
+  });
+  let _ = audit_service::write_audit(
+    pool,
+    AuditAction::AuthLockout,
+    None,
+    Some("operator".to_string()),
+    Some(username.to_string()),
+    Some(audit_request),
+    Ok(()),
+  )
+  .await;
+  Ok(locked_error(locked_until, now))
+}
+
+/// 读取登录锁定策略
+async fn load_lockout_policy(pool: &SqlitePool) -> Result<LockoutPolicy, AppError> {
+  let max_failures = meta_repo::get_meta_i64(pool, "login_max_failures")
+    .await?
+    .filter(|value| *value >= 0)
+    .unwrap_or(5);
+  let window_minutes = meta_repo::get_meta_i64(pool, "login_fail_window_minutes")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(15);
+  let lockout_minutes = meta_repo::get_meta_i64(pool, "login_lockout_minutes")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(15);
+  Ok(LockoutPolicy {
+    max_failures,
+    window_secs: window_minutes * 60,
+    lockout_secs: lockout_minutes * 60,
   })
 }
 
+fn locked_error(locked_until: i64, now: i64) -> AppError {
+  let minutes = ((locked_until - now) + 59) / 60;
+  AppError::keyed_with(
+    ErrorCode::AccountLocked,
+    MessageKey::AccountLocked,
+    &[("minutes", minutes.max(1).to_string())],
+  )
+}
+
 pub async fn change_password(
   pool: &SqlitePool,
   actor_operator_id: &str,
   old_password: &str,
   new_password: &str,
 ) -> Result<(), AppError> {
-  let row = sqlx::query("SELECT password_hash FROM operator WHERE id = ?")
+  let row = sqlx::query("SELECT username, password_hash FROM operator WHERE id = ?")
     .bind(actor_operator_id)
     .fetch_optional(pool)
     .await?;
@@ -76,20 +379,30 @@ pub async fn change_password(
 
   let hash: String = row.get("password_hash");
   if !crypto::verify_password(&hash, old_password)? {
-    return Err(AppError::new(ErrorCode::AuthFailed, "旧密码错误"));
+    return Err(AppError::keyed(ErrorCode::AuthFailed, MessageKey::WrongOldPassword));
   }
 
+  let username: String = row.get("username");
+  password_policy_service::validate_new_password(
+    pool,
+    Some(actor_operator_id),
+    &username,
+    new_password,
+  )
+  .await?;
+
   let new_hash = crypto::hash_password(new_password)?;
   let now = Utc::now().timestamp();
 
   sqlx::query(
     "UPDATE operator SET password_hash = ?, must_change_pwd = 0, pwd_changed_at = ? WHERE id = ?",
   )
-  .bind(new_hash)
+  .bind(&new_hash)
   .bind(now)
   .bind(actor_operator_id)
   .execute(pool)
   .await?;
+  password_policy_service::record_history(pool, actor_operator_id, &new_hash, now).await?;
 
   Ok(())
 }
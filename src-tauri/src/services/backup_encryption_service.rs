@@ -0,0 +1,170 @@
+// 备份加密：开启后本地备份与随后上传的远程备份均以口令加密（AES-256-GCM，Argon2id 派生密钥）
+//
+// 口令以本机密钥（<storage_root>/db/secret.key）加密保存在 app_meta（backup_passphrase），
+// 定时备份无需人工输入；在其他设备上恢复时需手动输入口令。数据库文件本身仍为明文。
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::infra::crypto;
+use crate::repo::meta_repo;
+use crate::services::remote_backup_service;
+
+/// 备份口令最小长度
+pub const MIN_PASSPHRASE_LEN: usize = 8;
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// 备份加密配置（不含口令）
+#[derive(Debug, Serialize)]
+pub struct BackupEncryptionConfig {
+  pub enabled: bool,
+  // 是否已保存口令
+  pub passphrase_set: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BackupEncryptionPatch {
+  pub enabled: bool,
+  // 为空表示保留已保存的口令
+  pub passphrase: Option<String>,
+}
+
+/// 已有明文备份的加密结果
+#[derive(Debug, Serialize)]
+pub struct BackupEncryptResult {
+  pub encrypted: i64,
+  // 无法读取或不是有效数据库备份、已跳过的文件
+  pub skipped: Vec<String>,
+}
+
+pub async fn get_config(pool: &SqlitePool) -> Result<BackupEncryptionConfig, AppError> {
+  Ok(BackupEncryptionConfig {
+    enabled: meta_repo::get_meta_value(pool, "backup_encryption").await?.as_deref() == Some("1"),
+    passphrase_set: meta_repo::get_meta_value(pool, "backup_passphrase")
+      .await?
+      .is_some_and(|value| !value.is_empty()),
+  })
+}
+
+pub async fn set_config(pool: &SqlitePool, patch: &BackupEncryptionPatch) -> Result<(), AppError> {
+  let passphrase = patch.passphrase.as_deref().filter(|value| !value.is_empty());
+  if let Some(passphrase) = passphrase {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("备份口令至少 {} 位", MIN_PASSPHRASE_LEN),
+      ));
+    }
+  }
+  if patch.enabled && passphrase.is_none() && !get_config(pool).await?.passphrase_set {
+    return Err(AppError::new(ErrorCode::ValidationError, "请设置备份口令"));
+  }
+
+  // 关闭加密时保留口令，用于恢复此前生成的加密备份
+  if let Some(passphrase) = passphrase {
+    let key = remote_backup_service::load_key(pool).await?;
+    let encrypted = crypto::encrypt_secret(&key, passphrase)?;
+    meta_repo::set_meta_value(pool, "backup_passphrase", &encrypted).await?;
+  }
+  meta_repo::set_meta_value(pool, "backup_encryption", if patch.enabled { "1" } else { "0" }).await?;
+  Ok(())
+}
+
+/// 已开启备份加密时返回口令，生成备份时使用；未开启时返回 None
+pub async fn active_passphrase(pool: &SqlitePool) -> Result<Option<String>, AppError> {
+  if !get_config(pool).await?.enabled {
+    return Ok(None);
+  }
+  stored_passphrase(pool).await
+}
+
+/// 读取备份文件：加密备份用传入的口令（未传时用本机保存的口令）解密并返回内容，
+/// 明文备份返回 None，由调用方直接复制文件
+pub async fn decrypt_if_encrypted(
+  pool: &SqlitePool,
+  path: &Path,
+  passphrase: Option<&str>,
+) -> Result<Option<Vec<u8>>, AppError> {
+  let read_failed = || AppError::new(ErrorCode::IoError, "读取备份文件失败");
+  let mut header = [0_u8; 8];
+  let mut file = std::fs::File::open(path).map_err(|_| read_failed())?;
+  if file.read_exact(&mut header).is_err() || !crypto::is_encrypted_backup(&header) {
+    return Ok(None);
+  }
+
+  let passphrase = match passphrase.filter(|value| !value.is_empty()) {
+    Some(value) => value.to_string(),
+    None => stored_passphrase(pool)
+      .await?
+      .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "该备份已加密，请输入备份口令"))?,
+  };
+  let data = std::fs::read(path).map_err(|_| read_failed())?;
+  let plain = crypto::decrypt_backup(&passphrase, &data)?;
+  if !plain.starts_with(SQLITE_HEADER) {
+    return Err(AppError::new(ErrorCode::ValidationError, "备份文件不是有效的数据库备份"));
+  }
+  Ok(Some(plain))
+}
+
+/// 加密备份目录中已有的明文备份（db_backup_*.sqlite）为 .sqlite.enc 并删除明文文件；
+/// 开启加密后生成的升级前自动备份已是加密文件，此前的明文升级前备份（db_pre_migrate_*）保持不变
+pub async fn encrypt_existing_backups(pool: &SqlitePool) -> Result<BackupEncryptResult, AppError> {
+  let passphrase = active_passphrase(pool)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "请先开启备份加密"))?;
+  let backups_dir = backups_dir(pool).await?;
+  let Ok(entries) = std::fs::read_dir(&backups_dir) else {
+    return Ok(BackupEncryptResult {
+      encrypted: 0,
+      skipped: Vec::new(),
+    });
+  };
+
+  let mut encrypted = 0;
+  let mut skipped = Vec::new();
+  for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()).map(|name| name.to_string()) else {
+      continue;
+    };
+    if !name.starts_with("db_backup_") || !name.ends_with(".sqlite") {
+      continue;
+    }
+    let plain = match std::fs::read(&path) {
+      Ok(plain) if plain.starts_with(SQLITE_HEADER) => plain,
+      _ => {
+        skipped.push(name);
+        continue;
+      }
+    };
+    let target = backups_dir.join(format!("{}.enc", name));
+    std::fs::write(&target, crypto::encrypt_backup(&passphrase, &plain)?)
+      .map_err(|_| AppError::new(ErrorCode::IoError, "写入加密备份失败"))?;
+    std::fs::remove_file(&path).map_err(|_| AppError::new(ErrorCode::IoError, "删除明文备份失败"))?;
+    encrypted += 1;
+  }
+  skipped.sort();
+  Ok(BackupEncryptResult { encrypted, skipped })
+}
+
+async fn stored_passphrase(pool: &SqlitePool) -> Result<Option<String>, AppError> {
+  let Some(encrypted) = meta_repo::get_meta_value(pool, "backup_passphrase")
+    .await?
+    .filter(|value| !value.is_empty())
+  else {
+    return Ok(None);
+  };
+  let key = remote_backup_service::load_key(pool).await?;
+  Ok(Some(crypto::decrypt_secret(&key, &encrypted)?))
+}
+
+async fn backups_dir(pool: &SqlitePool) -> Result<PathBuf, AppError> {
+  let storage_root = meta_repo::get_meta_value(pool, "storage_root")
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
+  Ok(PathBuf::from(storage_root).join("backups"))
+}
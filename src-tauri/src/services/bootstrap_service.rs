@@ -0,0 +1,103 @@
+use chrono::Local;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::{approval_repo, cycle_count_repo, notification_repo, operator_repo, warehouse_repo};
+use crate::services::{permission_service, system_service};
+
+/// 启动引导：当前操作人信息
+#[derive(Debug, serde::Serialize)]
+pub struct BootstrapOperator {
+  pub id: String,
+  pub username: String,
+  pub display_name: String,
+  pub role: String,
+  pub must_change_pwd: bool,
+}
+
+/// 启动引导：默认仓库上下文
+#[derive(Debug, serde::Serialize)]
+pub struct BootstrapWarehouse {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+}
+
+/// 启动引导返回结构（登录后一次性加载外壳所需数据）
+#[derive(Debug, serde::Serialize)]
+pub struct BootstrapDto {
+  pub operator: BootstrapOperator,
+  pub settings: system_service::SettingsDto,
+  pub permissions: Vec<String>,
+  pub default_warehouse: Option<BootstrapWarehouse>,
+  pub unread_notification_count: i64,
+  pub pending_task_count: i64,
+}
+
+/// 查询启动引导数据
+pub async fn get_bootstrap(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+) -> Result<BootstrapDto, AppError> {
+  let operator = operator_repo::get_operator_by_id(pool, actor_operator_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
+  if operator.status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ActorInactive));
+  }
+
+  let settings = system_service::get_settings(pool).await?;
+  let permissions = permission_service::list_permissions_by_id(pool, actor_operator_id).await?;
+  let allowed_warehouse_ids = permission_service::allowed_warehouse_ids(pool, actor_operator_id).await?;
+  // 受仓库范围限制时，默认仓库取可访问仓库中的第一个启用仓库
+  let default_row = match &allowed_warehouse_ids {
+    Some(ids) => {
+      let mut found = None;
+      for id in ids {
+        if let Some(row) = warehouse_repo::get_warehouse_by_id(pool, id).await? {
+          if row.status == "active" {
+            found = Some(row);
+            break;
+          }
+        }
+      }
+      found
+    }
+    None => warehouse_repo::get_first_active_warehouse(pool).await?,
+  };
+  let default_warehouse = default_row
+    .map(|row| BootstrapWarehouse {
+      id: row.id,
+      code: row.code,
+      name: row.name,
+    });
+
+  // 通知为全员共享，未读数与通知列表一致
+  let unread_notification_count = notification_repo::count_unread(pool).await?;
+  // 待办：有审批权限时计待审批的申请，可执行盘点的角色计截至今天未完成的盘点任务（限可访问仓库）
+  let mut pending_task_count = 0;
+  if permissions.iter().any(|permission| permission == "approval.decide") {
+    pending_task_count += approval_repo::count_pending(pool).await?;
+  }
+  if matches!(operator.role.as_str(), "admin" | "keeper") {
+    let today = Local::now().date_naive().format("%Y-%m-%d").to_string();
+    pending_task_count +=
+      cycle_count_repo::count_pending_tasks(pool, &today, allowed_warehouse_ids.as_deref()).await?;
+  }
+
+  Ok(BootstrapDto {
+    operator: BootstrapOperator {
+      id: operator.id,
+      username: operator.username,
+      display_name: operator.display_name,
+      role: operator.role,
+      must_change_pwd: operator.must_change_pwd,
+    },
+    settings,
+    permissions,
+    default_warehouse,
+    unread_notification_count,
+    pending_task_count,
+  })
+}
@@ -0,0 +1,191 @@
+// 复制文本：生成可直接粘贴到聊天/邮件的文本块，无需导出文件
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::txn_repo;
+use crate::services::{stock_service, system_service, txn_service};
+
+/// 表格复制的最大行数
+const MAX_COPY_ROWS: i64 = 500;
+/// 复制文本的最大字符数（超出部分截断）
+const MAX_COPY_CHARS: usize = 20_000;
+
+/// 复制文本返回结构
+#[derive(Debug, serde::Serialize)]
+pub struct CopyPayload {
+  // 可直接写入剪贴板的文本
+  pub text: String,
+  // 实际包含的数据行数
+  pub rows: i64,
+  // 符合条件的总行数
+  pub total: i64,
+  // 是否因行数或长度上限被截断
+  pub truncated: bool,
+}
+
+/// 单条流水的可读摘要
+pub async fn copy_txn(
+  pool: &SqlitePool,
+  txn_no: &str,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<CopyPayload, AppError> {
+  let txn_no = txn_no.trim();
+  if txn_no.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "流水号不能为空"));
+  }
+  // 复用列表查询以获得名称信息并套用仓库范围
+  let txn = txn_repo::list_txns(
+    pool,
+    None,
+    Some(txn_no.to_string()),
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    allowed_warehouse_ids,
+    1,
+    20,
+    None,
+    None,
+    None,
+  )
+  .await?
+  .into_iter()
+  .find(|row| row.txn_no == txn_no)
+  .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::TxnNotFound))?;
+
+  let timezone = system_service::report_timezone(pool).await?;
+  let mut lines = vec![
+    format!("流水号：{}", txn.txn_no),
+    format!("类型：{}", txn_service::txn_type_label(&txn.txn_type)),
+    format!("发生时间：{}", timezone.format_timestamp(txn.occurred_at)),
+    format!("物品：{} {}", txn.item_code, one_line(&txn.item_name)),
+  ];
+  match (&txn.from_slot_code, &txn.to_slot_code) {
+    (Some(from), Some(to)) => lines.push(format!("库位：{} → {}", from, to)),
+    (Some(from), None) => lines.push(format!("库位：{}", from)),
+    (None, Some(to)) => lines.push(format!("库位：{}", to)),
+    (None, None) => {}
+  }
+  match txn.actual_qty {
+    Some(actual_qty) => lines.push(format!("数量：{}（实盘 {}）", txn.qty, actual_qty)),
+    None => lines.push(format!("数量：{}", txn.qty)),
+  }
+  lines.push(format!("记录人：{}", one_line(&txn.operator_name)));
+  if let Some(reason_name) = &txn.reason_name {
+    lines.push(format!("原因：{}", one_line(reason_name)));
+  }
+  if let Some(note) = txn.note.as_deref().filter(|note| !note.trim().is_empty()) {
+    lines.push(format!("备注：{}", one_line(note)));
+  }
+  if let Some(location) = &txn.location {
+    lines.push(format!("位置：{}", location));
+  }
+  if let Some(ref_txn_no) = &txn.ref_txn_no {
+    lines.push(format!("关联流水：{}", ref_txn_no));
+  }
+  if txn.has_reversal {
+    lines.push("状态：已冲正".to_string());
+  }
+
+  let (text, truncated) = cap_text(lines.join("\n"));
+  Ok(CopyPayload {
+    text,
+    rows: 1,
+    total: 1,
+    truncated,
+  })
+}
+
+/// 库存查询结果（按库位）的制表符分隔表格，可直接粘贴到表格软件
+pub async fn copy_stock(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  rack_id: Option<String>,
+  slot_id: Option<String>,
+  item_id: Option<String>,
+  operator_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<CopyPayload, AppError> {
+  let mut lines = vec!["仓库\t货架\t库位\t物品编码\t物品\t数量".to_string()];
+  let page_size = 100;
+  let mut page = 1;
+  let mut rows = 0;
+  let mut total = 0;
+  while rows < MAX_COPY_ROWS {
+    let res = stock_service::list_stock_by_slot(
+      pool,
+      page,
+      page_size,
+      warehouse_id.clone(),
+      rack_id.clone(),
+      slot_id.clone(),
+      item_id.clone(),
+      operator_id.clone(),
+      allowed_warehouse_ids.clone(),
+      None,
+      None,
+    )
+    .await?;
+    total = res.total;
+    if res.items.is_empty() {
+      break;
+    }
+    let fetched_count = res.items.len() as i64;
+    for item in res.items.into_iter().take((MAX_COPY_ROWS - rows) as usize) {
+      lines.push(format!(
+        "{}\t{}\t{}\t{}\t{}\t{}",
+        one_line(item.warehouse_name.as_deref().unwrap_or("")),
+        one_line(&item.rack_name),
+        one_line(&item.slot_code),
+        one_line(&item.item_code),
+        one_line(&item.item_name),
+        item.qty
+      ));
+      rows += 1;
+    }
+    if page.saturating_mul(page_size) >= res.total || fetched_count < page_size {
+      break;
+    }
+    page += 1;
+  }
+
+  let mut truncated = rows < total;
+  if truncated {
+    lines.push(format!("（仅复制前 {} 行，共 {} 行）", rows, total));
+  }
+  let (text, text_truncated) = cap_text(lines.join("\n"));
+  truncated |= text_truncated;
+  Ok(CopyPayload {
+    text,
+    rows,
+    total,
+    truncated,
+  })
+}
+
+/// 按字符数截断，尽量保留整行
+fn cap_text(text: String) -> (String, bool) {
+  if text.chars().count() <= MAX_COPY_CHARS {
+    return (text, false);
+  }
+  let mut capped: String = text.chars().take(MAX_COPY_CHARS).collect();
+  if let Some(pos) = capped.rfind('\n') {
+    capped.truncate(pos);
+  }
+  capped.push_str("\n（内容过长，已截断）");
+  (capped, true)
+}
+
+/// 去除制表符与换行，避免破坏表格结构
+fn one_line(value: &str) -> String {
+  value
+    .chars()
+    .map(|c| if c == '\t' || c == '\n' || c == '\r' { ' ' } else { c })
+    .collect()
+}
@@ -0,0 +1,490 @@
+// 纸质盘点：导出可打印的盘点表（CSV/PDF，实盘数量留空、每行附条码），回填后导入生成盘点流水
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use csv::{ReaderBuilder, WriterBuilder};
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, IndirectFontRef, Mm, PdfDocument, PdfLayerReference, Rect, Rgb};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::infra::barcode;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::repo::meta_repo;
+use crate::repo::stock_query_repo::{self, CountSheetRow};
+use crate::repo::{item_repo, rack_repo, stock_repo};
+use crate::services::{approval_service, permission_service, reason_code_service, txn_service};
+
+/// 盘点表 CSV 表头（导入时按列名定位，可调整列顺序）
+const CSV_HEADERS: [&str; 13] = [
+  "sheet_no",
+  "row_no",
+  "warehouse_code",
+  "rack_code",
+  "slot_code",
+  "item_code",
+  "item_name",
+  "uom",
+  "expected_qty",
+  "actual_qty",
+  "note",
+  "reason_code",
+  "barcode",
+];
+/// 单张盘点表最多行数
+const MAX_SHEET_ROWS: usize = 5000;
+
+/// 盘点表范围
+#[derive(Debug)]
+pub struct CountSheetScope {
+  pub warehouse_id: Option<String>,
+  pub rack_id: Option<String>,
+  // 包含空库位（供现场补记未登记的物品）
+  pub include_empty_slots: bool,
+  // 打印账面数量；默认不打印，避免盲盘受干扰
+  pub show_expected: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountSheetExportResult {
+  pub file_path: String,
+  pub sheet_no: String,
+  pub rows: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountSheetImportResult {
+  // 已生成盘点流水的行数
+  pub posted: usize,
+  // 实盘数量留空而跳过的行数
+  pub skipped: usize,
+  pub txn_nos: Vec<String>,
+}
+
+/// 导出盘点表，format 支持 csv / pdf
+pub async fn export_count_sheets(
+  pool: &SqlitePool,
+  scope: &CountSheetScope,
+  format: &str,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<CountSheetExportResult, AppError> {
+  if !matches!(format, "csv" | "pdf") {
+    return Err(AppError::new(ErrorCode::ValidationError, "导出格式仅支持 csv/pdf"));
+  }
+  let rows = stock_query_repo::list_count_sheet_rows(
+    pool,
+    scope.warehouse_id.clone(),
+    scope.rack_id.clone(),
+    scope.include_empty_slots,
+    allowed_warehouse_ids,
+  )
+  .await?;
+  if rows.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "所选范围内没有可盘点的库位"));
+  }
+  if rows.len() > MAX_SHEET_ROWS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("盘点表超过 {} 行，请按货架分批导出", MAX_SHEET_ROWS),
+    ));
+  }
+
+  let now = Utc::now();
+  let sheet_no = format!("CS{}", now.format("%Y%m%d%H%M%S"));
+  let file_path = export_dir(pool).await?.join(format!("count_sheet_{}.{}", sheet_no, format));
+  if format == "csv" {
+    write_csv(&file_path, &sheet_no, &rows, scope.show_expected)?;
+  } else {
+    let bytes = render_pdf(&sheet_no, &rows, scope.show_expected)?;
+    std::fs::write(&file_path, bytes)
+      .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+  }
+
+  Ok(CountSheetExportResult {
+    file_path: file_path.to_string_lossy().to_string(),
+    sheet_no,
+    rows: rows.len(),
+  })
+}
+
+/// 导入回填的盘点表（CSV）：先校验全部行，再逐行生成盘点流水；实盘数量为空的行跳过
+pub async fn import_count_sheet(
+  pool: &SqlitePool,
+  file_path: &str,
+  occurred_at: i64,
+  actor_operator_id: &str,
+) -> Result<CountSheetImportResult, AppError> {
+  let mut reader = ReaderBuilder::new()
+    .has_headers(true)
+    .flexible(true)
+    .from_path(file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+  let headers = reader
+    .headers()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?
+    .clone();
+  let column = |name: &str| {
+    headers
+      .iter()
+      .position(|header| header.trim().trim_start_matches('\u{feff}') == name)
+      .ok_or_else(|| AppError::new(ErrorCode::ValidationError, format!("盘点表缺少 {} 列", name)))
+  };
+  let sheet_col = column("sheet_no").ok();
+  let slot_col = column("slot_code")?;
+  let item_col = column("item_code")?;
+  let actual_col = column("actual_qty")?;
+  let note_col = column("note").ok();
+  // 可选的差异原因列（原因编码），设置要求调整须填原因时有差异的行必须填写
+  let reason_col = column("reason_code").ok();
+  let reason_required = reason_code_service::is_required(pool, "ADJUST").await?;
+
+  struct CountLine {
+    item_id: String,
+    slot_id: String,
+    actual_qty: i64,
+    note: Option<String>,
+    reason_id: Option<String>,
+  }
+
+  let mut lines = Vec::new();
+  let mut skipped = 0;
+  let mut seen = HashSet::new();
+  let mut slot_cache: HashMap<String, String> = HashMap::new();
+  for (index, record) in reader.records().enumerate() {
+    // 表头占第 1 行
+    let line_no = index + 2;
+    let record = record.map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+    let cell = |col: usize| record.get(col).unwrap_or("").trim().to_string();
+    let actual = cell(actual_col);
+    if actual.is_empty() {
+      skipped += 1;
+      continue;
+    }
+    let line_error = |message: &str| {
+      AppError::new(ErrorCode::ValidationError, format!("第 {} 行：{}", line_no, message))
+    };
+    let actual = actual
+      .parse::<f64>()
+      .ok()
+      .filter(|qty| *qty >= 0.0)
+      .ok_or_else(|| line_error("实盘数量需为非负数"))?;
+    let slot_code = cell(slot_col);
+    let item_code = cell(item_col);
+    if slot_code.is_empty() || item_code.is_empty() {
+      return Err(line_error("库位编码与物品编码不能为空"));
+    }
+    if !seen.insert((slot_code.clone(), item_code.clone())) {
+      return Err(line_error("同一库位与物品重复出现"));
+    }
+
+    let slot_id = match slot_cache.get(&slot_code) {
+      Some(slot_id) => slot_id.clone(),
+      None => {
+        let slot = rack_repo::get_slot_by_code(pool, &slot_code)
+          .await?
+          .ok_or_else(|| line_error("库位不存在"))?;
+        permission_service::require_slot_access(pool, actor_operator_id, &slot.id).await?;
+        slot_cache.insert(slot_code.clone(), slot.id.clone());
+        slot.id
+      }
+    };
+    let item = item_repo::get_item_by_code(pool, &item_code)
+      .await?
+      .ok_or_else(|| line_error("物品不存在"))?;
+    let actual_qty = quantity::to_base(actual, item.qty_precision)
+      .map_err(|err| line_error(&err.message))?;
+    if approval_service::count_requires_approval(pool, &item.id, &slot_id, actual_qty).await? {
+      return Err(line_error("盘点差异超过审批阈值，请单独提交审批后录入"));
+    }
+    let reason_code = reason_col.map(cell);
+    let reason_id = reason_code_service::resolve_reason_by_code(pool, "ADJUST", reason_code.as_deref())
+      .await
+      .map_err(|err| line_error(&err.message))?;
+    if reason_required && reason_id.is_none() {
+      let current_qty = stock_repo::get_stock(pool, &item.id, &slot_id).await?.map(|stock| stock.qty).unwrap_or(0);
+      if current_qty != actual_qty {
+        return Err(line_error("盘点有差异，须填写原因编码"));
+      }
+    }
+    let sheet_no = sheet_col.map(cell).filter(|value| !value.is_empty());
+    let note = note_col
+      .map(cell)
+      .filter(|value| !value.is_empty())
+      .or_else(|| sheet_no.map(|sheet_no| format!("纸质盘点 {}", sheet_no)));
+    lines.push(CountLine {
+      item_id: item.id,
+      slot_id,
+      actual_qty,
+      note,
+      reason_id,
+    });
+  }
+  if lines.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "盘点表中没有填写实盘数量的行"));
+  }
+
+  let mut txn_nos = Vec::with_capacity(lines.len());
+  for line in lines {
+    let txn_no = txn_service::create_count(
+      pool,
+      &line.item_id,
+      &line.slot_id,
+      line.actual_qty,
+      occurred_at,
+      actor_operator_id,
+      line.note,
+      None,
+      line.reason_id,
+      None,
+    )
+    .await?;
+    txn_nos.push(txn_no);
+  }
+
+  Ok(CountSheetImportResult {
+    posted: txn_nos.len(),
+    skipped,
+    txn_nos,
+  })
+}
+
+/// 行条码内容：库位编码|物品编码，空库位仅库位编码
+fn row_barcode(row: &CountSheetRow) -> String {
+  match &row.item_code {
+    Some(item_code) => format!("{}|{}", row.slot_code, item_code),
+    None => row.slot_code.clone(),
+  }
+}
+
+/// 导出目录（拣货单等打印文档共用）
+pub async fn export_dir(pool: &SqlitePool) -> Result<std::path::PathBuf, AppError> {
+  // 移动端使用临时目录，桌面端使用配置的导出目录
+  #[cfg(any(target_os = "android", target_os = "ios"))]
+  let export_dir = {
+    let _ = pool;
+    std::env::temp_dir()
+  };
+
+  #[cfg(not(any(target_os = "android", target_os = "ios")))]
+  let export_dir = {
+    let storage_root = meta_repo::get_meta_value(pool, "storage_root")
+      .await?
+      .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
+    match meta_repo::get_meta_value(pool, "exports_dir").await? {
+      Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
+      _ => std::path::PathBuf::from(&storage_root).join("exports"),
+    }
+  };
+
+  std::fs::create_dir_all(&export_dir)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
+  Ok(export_dir)
+}
+
+fn write_csv(
+  file_path: &std::path::Path,
+  sheet_no: &str,
+  rows: &[CountSheetRow],
+  show_expected: bool,
+) -> Result<(), AppError> {
+  let write_error = |_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed);
+  let mut writer = WriterBuilder::new()
+    .has_headers(true)
+    .from_path(file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
+  writer.write_record(CSV_HEADERS).map_err(write_error)?;
+  for (index, row) in rows.iter().enumerate() {
+    writer
+      .write_record([
+        sheet_no.to_string(),
+        (index + 1).to_string(),
+        row.warehouse_code.clone().unwrap_or_default(),
+        row.rack_code.clone(),
+        row.slot_code.clone(),
+        row.item_code.clone().unwrap_or_default(),
+        row.item_name.clone().unwrap_or_default(),
+        row.uom.clone().unwrap_or_default(),
+        if show_expected && row.item_code.is_some() {
+          row.qty.to_string()
+        } else {
+          String::new()
+        },
+        String::new(),
+        String::new(),
+        String::new(),
+        row_barcode(row),
+      ])
+      .map_err(write_error)?;
+  }
+  writer.flush().map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))
+}
+
+const PAGE_W: f32 = 210.0;
+const PAGE_H: f32 = 297.0;
+const MARGIN: f32 = 12.0;
+const HEADER_H: f32 = 26.0;
+const ROW_H: f32 = 13.0;
+
+/// 渲染 PDF（A4 纵向，每行附 Code 128 条码）。内置 Helvetica 字体不支持中文，
+/// 物品名称含非 ASCII 字符时不打印（CSV 中保留完整名称）
+fn render_pdf(sheet_no: &str, rows: &[CountSheetRow], show_expected: bool) -> Result<Vec<u8>, AppError> {
+  let per_page = ((PAGE_H - MARGIN * 2.0 - HEADER_H) / ROW_H).floor().max(1.0) as usize;
+  let pages = rows.len().div_ceil(per_page);
+  let (doc, first_page, first_layer) =
+    PdfDocument::new(format!("Count sheet {}", sheet_no), Mm(PAGE_W), Mm(PAGE_H), "Layer 1");
+  let font = doc
+    .add_builtin_font(BuiltinFont::Helvetica)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+  let bold = doc
+    .add_builtin_font(BuiltinFont::HelveticaBold)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+
+  // 列：序号、库位、物品编码、名称、账面、实盘（留空）、条码
+  let columns: [(&str, f32); 7] = [
+    ("No", 9.0),
+    ("Slot", 24.0),
+    ("Item", 28.0),
+    ("Name", 32.0),
+    (if show_expected { "Book" } else { "" }, 14.0),
+    ("Actual", 22.0),
+    ("Barcode", PAGE_W - MARGIN * 2.0 - 129.0),
+  ];
+  let black = Color::Rgb(Rgb::new(0.06, 0.09, 0.16, None));
+  let grey = Color::Rgb(Rgb::new(0.58, 0.64, 0.72, None));
+
+  for (page_index, chunk) in rows.chunks(per_page).enumerate() {
+    let layer = if page_index == 0 {
+      doc.get_page(first_page).get_layer(first_layer)
+    } else {
+      let (page, layer) = doc.add_page(Mm(PAGE_W), Mm(PAGE_H), "Layer 1");
+      doc.get_page(page).get_layer(layer)
+    };
+
+    let top = PAGE_H - MARGIN;
+    layer.set_fill_color(black.clone());
+    layer.use_text(format!("Count sheet {}", sheet_no), 14.0, Mm(MARGIN), Mm(top - 5.0), &bold);
+    layer.use_text(
+      format!(
+        "Printed {}  -  Page {}/{}",
+        Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        page_index + 1,
+        pages
+      ),
+      8.0,
+      Mm(MARGIN),
+      Mm(top - 11.0),
+      &font,
+    );
+    layer.use_text(
+      "Counted by: ____________    Date: ____________    Checked by: ____________",
+      9.0,
+      Mm(MARGIN),
+      Mm(top - 18.0),
+      &font,
+    );
+
+    let header_y = top - HEADER_H + 2.0;
+    let mut x = MARGIN;
+    for (label, width) in columns {
+      layer.use_text(label, 8.0, Mm(x + 1.0), Mm(header_y), &bold);
+      x += width;
+    }
+
+    layer.set_outline_color(grey.clone());
+    layer.set_outline_thickness(0.4);
+    for (row_index, row) in chunk.iter().enumerate() {
+      let row_top = top - HEADER_H - row_index as f32 * ROW_H;
+      let row_bottom = row_top - ROW_H;
+      let text_y = row_bottom + ROW_H / 2.0 - 1.2;
+      layer.add_rect(
+        Rect::new(Mm(MARGIN), Mm(row_bottom), Mm(PAGE_W - MARGIN), Mm(row_top)).with_mode(PaintMode::Stroke),
+      );
+
+      let cells = [
+        (page_index * per_page + row_index + 1).to_string(),
+        pdf_text(&row.slot_code),
+        row.item_code.as_deref().map(pdf_text).unwrap_or_default(),
+        row.item_name.clone().filter(|name| name.is_ascii()).unwrap_or_default(),
+        if show_expected && row.item_code.is_some() {
+          row.qty.to_string()
+        } else {
+          String::new()
+        },
+      ];
+      layer.set_fill_color(black.clone());
+      let mut x = MARGIN;
+      for (cell, (_, width)) in cells.iter().zip(columns) {
+        layer.use_text(fit_text(cell, width), 8.0, Mm(x + 1.0), Mm(text_y), &font);
+        x += width;
+      }
+      // 实盘数量填写框
+      let actual_x = MARGIN + columns[..5].iter().map(|(_, width)| width).sum::<f32>();
+      layer.add_rect(
+        Rect::new(
+          Mm(actual_x + 1.5),
+          Mm(row_bottom + 2.0),
+          Mm(actual_x + columns[5].1 - 1.5),
+          Mm(row_top - 2.0),
+        )
+        .with_mode(PaintMode::Stroke),
+      );
+      let barcode_area = (actual_x + columns[5].1 + 2.0, row_bottom + 1.5, columns[6].1 - 4.0, ROW_H - 3.0);
+      draw_barcode(&layer, &font, &row_barcode(row), barcode_area);
+    }
+  }
+
+  doc
+    .save_to_bytes()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfRenderFailed))
+}
+
+/// 在指定区域（左、下、宽、高）内绘制 Code 128 条码，下方附可读文本；无法编码时仅输出文本
+pub fn draw_barcode(
+  layer: &PdfLayerReference,
+  font: &IndirectFontRef,
+  value: &str,
+  (left, bottom, width, height): (f32, f32, f32, f32),
+) {
+  let text_h = 2.5;
+  layer.set_fill_color(Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None)));
+  let Some(widths) = barcode::code128_widths(value) else {
+    layer.use_text(fit_text(&pdf_text(value), width), 6.0, Mm(left), Mm(bottom + height / 2.0), font);
+    return;
+  };
+  // 两侧各留 10 个模块的静区，模块宽度不超过 0.4mm
+  let total: u32 = widths.iter().map(|width| *width as u32).sum::<u32>() + 20;
+  let module = (width / total as f32).min(0.4);
+  let mut x = left + module * 10.0;
+  for (index, bar) in widths.iter().enumerate() {
+    let bar_w = *bar as f32 * module;
+    if index % 2 == 0 {
+      layer.add_rect(
+        Rect::new(Mm(x), Mm(bottom + text_h), Mm(x + bar_w), Mm(bottom + height)).with_mode(PaintMode::Fill),
+      );
+    }
+    x += bar_w;
+  }
+  layer.use_text(value, 5.0, Mm(left + module * 10.0), Mm(bottom), font);
+}
+
+/// 内置字体不支持的字符替换为 ?
+pub fn pdf_text(value: &str) -> String {
+  value
+    .chars()
+    .map(|c| if c.is_ascii() && !c.is_ascii_control() { c } else { '?' })
+    .collect()
+}
+
+/// 按列宽粗略截断（Helvetica 8pt 平均字宽约 1.6mm）
+pub fn fit_text(value: &str, width: f32) -> String {
+  let max_chars = ((width - 2.0) / 1.6).max(1.0) as usize;
+  if value.chars().count() <= max_chars {
+    return value.to_string();
+  }
+  let mut text: String = value.chars().take(max_chars.saturating_sub(1)).collect();
+  text.push('~');
+  text
+}
@@ -0,0 +1,317 @@
+// 循环盘点：按仓库计划，每天从有库存的物品/库位中挑出一批生成盘点任务
+//
+// 物品按统计期内的流水次数排名分为 A/B/C 三类，各类按盘点周期均摊到每天：
+// 每日配额 = ceil(该类物品/库位数 / 周期天数)，优先挑选最久未盘点的；未完成的任务顺延到次日并占用配额。
+// 登记盘点（COUNT 流水）后对应任务自动完成，也可手动跳过。日期均为本机时区的自然日。
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, Local, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::quantity;
+use crate::repo::cycle_count_repo::{self, CountTaskRow, CycleCountPlanRow, NewCountTask};
+use crate::repo::warehouse_repo;
+
+const PLAN_STATUSES: [&str; 2] = ["active", "disabled"];
+const MAX_INTERVAL_DAYS: i64 = 3650;
+const MAX_LOOKBACK_DAYS: i64 = 730;
+
+#[derive(Debug, Deserialize)]
+pub struct CycleCountPlanInput {
+  pub name: String,
+  pub warehouse_id: String,
+  pub a_ratio: Option<f64>,
+  pub b_ratio: Option<f64>,
+  pub a_interval_days: Option<i64>,
+  pub b_interval_days: Option<i64>,
+  pub c_interval_days: Option<i64>,
+  pub lookback_days: Option<i64>,
+  pub status: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CountTask {
+  #[serde(flatten)]
+  pub row: CountTaskRow,
+  // 账面数量（显示值）
+  pub expected_qty: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TodayCountTasks {
+  pub date: String,
+  pub total: i64,
+  pub done: i64,
+  pub skipped: i64,
+  pub pending: i64,
+  // 由前几日顺延而来的任务数
+  pub carried_over: i64,
+  pub tasks: Vec<CountTask>,
+}
+
+pub async fn list_plans(
+  pool: &SqlitePool,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<CycleCountPlanRow>, AppError> {
+  cycle_count_repo::list_plans(pool, allowed_warehouse_ids.as_deref()).await
+}
+
+pub async fn create_plan(pool: &SqlitePool, input: &CycleCountPlanInput) -> Result<CycleCountPlanRow, AppError> {
+  let now = Utc::now().timestamp();
+  let plan = build_plan(pool, input, Uuid::new_v4().to_string(), now).await?;
+  if cycle_count_repo::name_exists(pool, &plan.name, None).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "盘点计划名称已存在"));
+  }
+  cycle_count_repo::save_plan(pool, &plan).await?;
+  get_plan(pool, &plan.id).await
+}
+
+pub async fn update_plan(
+  pool: &SqlitePool,
+  id: &str,
+  input: &CycleCountPlanInput,
+) -> Result<CycleCountPlanRow, AppError> {
+  let existing = get_plan(pool, id).await?;
+  let mut plan = build_plan(pool, input, existing.id.clone(), existing.created_at).await?;
+  plan.updated_at = Utc::now().timestamp();
+  if cycle_count_repo::name_exists(pool, &plan.name, Some(id)).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "盘点计划名称已存在"));
+  }
+  cycle_count_repo::save_plan(pool, &plan).await?;
+  // 更换仓库后原仓库的未完成任务不再有意义，当天重新生成
+  if plan.warehouse_id != existing.warehouse_id {
+    cycle_count_repo::delete_pending_tasks(pool, id).await?;
+  }
+  get_plan(pool, id).await
+}
+
+pub async fn delete_plan(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  if cycle_count_repo::delete_plan(pool, id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "盘点计划不存在"));
+  }
+  Ok(())
+}
+
+pub async fn get_plan(pool: &SqlitePool, id: &str) -> Result<CycleCountPlanRow, AppError> {
+  cycle_count_repo::get_plan(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "盘点计划不存在"))
+}
+
+/// 为所有启用的计划生成当天任务：先完成已登记盘点的任务、顺延未完成的任务，
+/// 当天已生成过的计划不再补充；返回新生成的任务数
+pub async fn generate_today_tasks(pool: &SqlitePool) -> Result<i64, AppError> {
+  cycle_count_repo::complete_counted_tasks(pool).await?;
+  let today = Local::now().date_naive();
+  let today_key = today.format("%Y-%m-%d").to_string();
+  let now = Utc::now().timestamp();
+  let mut generated = 0;
+  for plan in cycle_count_repo::list_active_plans(pool).await? {
+    cycle_count_repo::carry_over_tasks(pool, &plan.id, &today_key).await?;
+    if plan.last_generated_date.as_deref() == Some(today_key.as_str()) {
+      continue;
+    }
+    let tasks = plan_tasks(pool, &plan, &today_key, now).await?;
+    generated += tasks.len() as i64;
+    cycle_count_repo::insert_tasks(pool, &plan.id, &today_key, &tasks, now).await?;
+  }
+  Ok(generated)
+}
+
+/// 今日盘点任务（含今日已完成与跳过的），调用方需先生成当天任务
+pub async fn get_today_count_tasks(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<TodayCountTasks, AppError> {
+  let date = Local::now().date_naive().format("%Y-%m-%d").to_string();
+  let warehouse_id = warehouse_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let rows = cycle_count_repo::list_tasks_by_date(
+    pool,
+    &date,
+    warehouse_id.as_deref(),
+    allowed_warehouse_ids.as_deref(),
+  )
+  .await?;
+
+  let count_status = |status: &str| rows.iter().filter(|row| row.status == status).count() as i64;
+  let (done, skipped, pending) = (count_status("done"), count_status("skipped"), count_status("pending"));
+  let carried_over = rows.iter().filter(|row| row.carry_over > 0).count() as i64;
+  let tasks = rows
+    .into_iter()
+    .map(|row| CountTask {
+      expected_qty: quantity::to_display(row.stock_qty, row.qty_precision),
+      row,
+    })
+    .collect::<Vec<_>>();
+  Ok(TodayCountTasks {
+    date,
+    total: tasks.len() as i64,
+    done,
+    skipped,
+    pending,
+    carried_over,
+    tasks,
+  })
+}
+
+/// 任务所属仓库，用于校验操作人的仓库范围
+pub async fn task_warehouse_id(pool: &SqlitePool, task_id: &str) -> Result<String, AppError> {
+  cycle_count_repo::get_task_scope(pool, task_id)
+    .await?
+    .map(|(warehouse_id, _)| warehouse_id)
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "盘点任务不存在"))
+}
+
+/// 跳过未完成的任务，跳过后该物品/库位按已盘点处理，至下个周期再安排
+pub async fn skip_task(pool: &SqlitePool, task_id: &str, actor_operator_id: &str) -> Result<(), AppError> {
+  let (_, status) = cycle_count_repo::get_task_scope(pool, task_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "盘点任务不存在"))?;
+  if status != "pending" {
+    return Err(AppError::new(ErrorCode::Conflict, "盘点任务已完成或已跳过"));
+  }
+  cycle_count_repo::skip_task(pool, task_id, actor_operator_id, Utc::now().timestamp()).await?;
+  Ok(())
+}
+
+/// 按 ABC 分类与周期挑选计划当天的新任务
+async fn plan_tasks(
+  pool: &SqlitePool,
+  plan: &CycleCountPlanRow,
+  today_key: &str,
+  now: i64,
+) -> Result<Vec<NewCountTask>, AppError> {
+  let movement_since = (Local::now() - Duration::days(plan.lookback_days)).timestamp();
+  let candidates = cycle_count_repo::list_candidates(pool, &plan.warehouse_id, movement_since).await?;
+  if candidates.is_empty() {
+    return Ok(Vec::new());
+  }
+
+  // 物品按流水次数从多到少排名，前 a_ratio% 为 A 类，其后 b_ratio% 为 B 类
+  let mut items: Vec<(&str, &str, i64)> = Vec::new();
+  for candidate in &candidates {
+    if !items.iter().any(|(item_id, _, _)| *item_id == candidate.item_id) {
+      items.push((&candidate.item_id, &candidate.item_code, candidate.movement_count));
+    }
+  }
+  items.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.1.cmp(b.1)));
+  let a_count = (items.len() as f64 * plan.a_ratio / 100.0).ceil() as usize;
+  let b_count = (items.len() as f64 * plan.b_ratio / 100.0).ceil() as usize;
+  let classes: HashMap<&str, &str> = items
+    .iter()
+    .enumerate()
+    .map(|(rank, (item_id, _, _))| {
+      let class = if rank < a_count {
+        "A"
+      } else if rank < a_count + b_count {
+        "B"
+      } else {
+        "C"
+      };
+      (*item_id, class)
+    })
+    .collect();
+
+  let pending = cycle_count_repo::list_pending_keys(pool, &plan.id).await?;
+  let pending_keys: HashSet<(&str, &str)> = pending
+    .iter()
+    .map(|(item_id, slot_id, _)| (item_id.as_str(), slot_id.as_str()))
+    .collect();
+
+  let mut tasks = Vec::new();
+  for (class, interval_days) in [
+    ("A", plan.a_interval_days),
+    ("B", plan.b_interval_days),
+    ("C", plan.c_interval_days),
+  ] {
+    let in_class: Vec<_> = candidates
+      .iter()
+      .filter(|candidate| classes.get(candidate.item_id.as_str()) == Some(&class))
+      .collect();
+    let quota = (in_class.len() as i64 + interval_days - 1) / interval_days;
+    let pending_in_class = pending.iter().filter(|(_, _, abc)| abc == class).count() as i64;
+    let remaining = (quota - pending_in_class).max(0) as usize;
+
+    // 周期内盘点过的不再安排；其余按最近盘点时间从早到晚（从未盘点的优先）
+    let counted_after = now - interval_days * 86400;
+    let mut due: Vec<_> = in_class
+      .into_iter()
+      .filter(|candidate| !pending_keys.contains(&(candidate.item_id.as_str(), candidate.slot_id.as_str())))
+      .filter(|candidate| candidate.last_counted_at.is_none_or(|at| at <= counted_after))
+      .collect();
+    due.sort_by_key(|candidate| candidate.last_counted_at.unwrap_or(0));
+    tasks.extend(due.into_iter().take(remaining).map(|candidate| NewCountTask {
+      id: Uuid::new_v4().to_string(),
+      item_id: candidate.item_id.clone(),
+      slot_id: candidate.slot_id.clone(),
+      abc_class: class.to_string(),
+      due_date: today_key.to_string(),
+    }));
+  }
+  Ok(tasks)
+}
+
+async fn build_plan(
+  pool: &SqlitePool,
+  input: &CycleCountPlanInput,
+  id: String,
+  created_at: i64,
+) -> Result<CycleCountPlanRow, AppError> {
+  let name = input.name.trim();
+  if name.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "盘点计划名称不能为空"));
+  }
+  let warehouse_id = input.warehouse_id.trim();
+  if warehouse_repo::get_warehouse_by_id(pool, warehouse_id).await?.is_none() {
+    return Err(AppError::new(ErrorCode::NotFound, "仓库不存在"));
+  }
+  let a_ratio = input.a_ratio.unwrap_or(20.0);
+  let b_ratio = input.b_ratio.unwrap_or(30.0);
+  if !a_ratio.is_finite() || !b_ratio.is_finite() || a_ratio < 0.0 || b_ratio < 0.0 || a_ratio + b_ratio > 100.0 {
+    return Err(AppError::new(ErrorCode::ValidationError, "A/B 类占比需为非负数且合计不超过 100%"));
+  }
+  let a_interval_days = input.a_interval_days.unwrap_or(30);
+  let b_interval_days = input.b_interval_days.unwrap_or(90);
+  let c_interval_days = input.c_interval_days.unwrap_or(180);
+  for days in [a_interval_days, b_interval_days, c_interval_days] {
+    if !(1..=MAX_INTERVAL_DAYS).contains(&days) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("盘点周期需在 1-{} 天之间", MAX_INTERVAL_DAYS),
+      ));
+    }
+  }
+  let lookback_days = input.lookback_days.unwrap_or(90);
+  if !(1..=MAX_LOOKBACK_DAYS).contains(&lookback_days) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("分类统计天数需在 1-{} 天之间", MAX_LOOKBACK_DAYS),
+    ));
+  }
+  let status = input.status.as_deref().map(str::trim).unwrap_or("active");
+  if !PLAN_STATUSES.contains(&status) {
+    return Err(AppError::new(ErrorCode::ValidationError, "计划状态仅支持 active/disabled"));
+  }
+
+  Ok(CycleCountPlanRow {
+    id,
+    name: name.to_string(),
+    warehouse_id: warehouse_id.to_string(),
+    warehouse_code: None,
+    warehouse_name: None,
+    a_ratio,
+    b_ratio,
+    a_interval_days,
+    b_interval_days,
+    c_interval_days,
+    lookback_days,
+    status: status.to_string(),
+    last_generated_date: None,
+    created_at,
+    updated_at: created_at,
+  })
+}
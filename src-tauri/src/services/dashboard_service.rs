@@ -1,105 +1,296 @@
 use std::collections::HashMap;
+use std::time::{Duration as StdDuration, Instant};
 
-use chrono::{Duration, Local, NaiveDate, TimeZone};
+use chrono::{Datelike, Duration, Local, NaiveDate, TimeZone};
+use parking_lot::Mutex;
 use serde::Serialize;
 use sqlx::SqlitePool;
 
-use crate::domain::errors::AppError;
-use crate::repo::dashboard_repo;
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::{dashboard_repo, loan_repo};
+use crate::services::{expiry_service, report_service, stock_close_service, system_service, warehouse_service};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DashboardTxnCounts {
   pub inbound: i64,
   pub outbound: i64,
   pub move_count: i64,
   pub count_count: i64,
+  pub scrap: i64,
   pub reversal: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DashboardTrendPoint {
+  // 分组起始日（按周为周一，按月为 1 日）
   pub day: String,
   pub inbound: i64,
   pub outbound: i64,
   pub move_count: i64,
   pub count_count: i64,
+  pub scrap: i64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct DashboardWarehouseStock {
   pub warehouse_code: Option<String>,
   pub warehouse_name: Option<String>,
-  pub total_qty: i64,
+  pub total_qty: f64,
+  pub total_value: f64,
+  // 占全部库存价值的百分比（0-100）
+  pub value_percent: f64,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardOperatorRank {
+  pub operator_id: String,
+  pub operator_name: String,
+  pub txn_count: i64,
+  pub total_qty: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct DashboardOverview {
   pub today: DashboardTxnCounts,
-  pub total_stock_qty: i64,
+  // 统计区间内的流水数量
+  pub period: DashboardTxnCounts,
+  pub range_start: i64,
+  pub range_end: i64,
+  pub granularity: String,
+  pub total_stock_qty: f64,
+  pub total_stock_value: f64,
+  // 昨日日结的库存总量与价值，昨日尚未日结时为空
+  pub yesterday_close_qty: Option<f64>,
+  pub yesterday_close_value: Option<f64>,
   pub active_items: i64,
   pub active_racks: i64,
   pub active_warehouses: i64,
   pub negative_stock: i64,
+  // 库龄达到呆滞阈值的物品/库位数
+  pub stale_stock: i64,
+  // 已过期 / 临期（按系统设置天数）的库存批次数
+  pub expired_stock: i64,
+  pub expiring_stock: i64,
+  // 库位填充率（0-100）：有库存的启用库位 / 启用库位
+  pub slot_fill_rate: f64,
+  // 借出中 / 已逾期的借用记录数
+  pub loans_open: i64,
+  pub loans_overdue: i64,
+  // 借出中物品（按借出数量取前 5）
+  pub items_on_loan: Vec<loan_repo::ItemOnLoanRow>,
   pub trend: Vec<DashboardTrendPoint>,
   pub stock_by_warehouse: Vec<DashboardWarehouseStock>,
+  // 人员作业排行（仅有权限时返回）
+  pub operator_leaderboard: Option<Vec<DashboardOperatorRank>>,
+}
+
+impl DashboardOverview {
+  /// 未开放成本查看的角色：库存价值清零
+  pub fn hide_costs(&mut self) {
+    self.total_stock_value = 0.0;
+    self.yesterday_close_value = None;
+    for warehouse in &mut self.stock_by_warehouse {
+      warehouse.total_value = 0.0;
+      warehouse.value_percent = 0.0;
+    }
+  }
+}
+
+/// 趋势统计粒度
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrendGranularity {
+  Day,
+  Week,
+  Month,
+}
+
+impl TrendGranularity {
+  pub fn parse(value: Option<&str>) -> Result<Self, AppError> {
+    match value.map(|value| value.trim()).unwrap_or("day") {
+      "" | "day" => Ok(TrendGranularity::Day),
+      "week" => Ok(TrendGranularity::Week),
+      "month" => Ok(TrendGranularity::Month),
+      _ => Err(AppError::new(ErrorCode::ValidationError, "统计粒度仅支持 day/week/month")),
+    }
+  }
+
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      TrendGranularity::Day => "day",
+      TrendGranularity::Week => "week",
+      TrendGranularity::Month => "month",
+    }
+  }
+
+  /// 所在分组的起始日，需与 dashboard_repo 中的 SQL 分组保持一致
+  pub fn bucket_start(&self, day: NaiveDate) -> NaiveDate {
+    match self {
+      TrendGranularity::Day => day,
+      TrendGranularity::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+      TrendGranularity::Month => day.with_day(1).unwrap_or(day),
+    }
+  }
+
+  pub fn next_bucket(&self, start: NaiveDate) -> Option<NaiveDate> {
+    match self {
+      TrendGranularity::Day => start.succ_opt(),
+      TrendGranularity::Week => start.checked_add_signed(Duration::days(7)),
+      TrendGranularity::Month => {
+        if start.month() == 12 {
+          NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+        } else {
+          NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+        }
+      }
+    }
+  }
+
+  /// 缺省区间：按日近 7 天，按周近 12 周，按月近 12 个月
+  fn default_start(&self, end_day: NaiveDate) -> NaiveDate {
+    let start = match self {
+      TrendGranularity::Day => end_day.checked_sub_signed(Duration::days(6)),
+      TrendGranularity::Week => end_day.checked_sub_signed(Duration::weeks(11)),
+      TrendGranularity::Month => {
+        let months = end_day.year() * 12 + end_day.month0() as i32 - 11;
+        NaiveDate::from_ymd_opt(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1)
+      }
+    };
+    self.bucket_start(start.unwrap_or(end_day))
+  }
+}
+
+/// 仪表盘统计区间，缺省截止到今天、起点按粒度取默认跨度
+#[derive(Debug, Clone, Default)]
+pub struct OverviewRange {
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+  pub granularity: Option<String>,
+}
+
+/// 人员作业排行查询参数，时间范围缺省为仪表盘统计区间
+#[derive(Debug, Clone, Default)]
+pub struct LeaderboardQuery {
+  pub start_at: Option<i64>,
+  pub end_at: Option<i64>,
+  pub limit: Option<i64>,
+}
+
+/// 趋势最多分组数，避免按日查询过长区间
+pub const MAX_TREND_BUCKETS: usize = 366;
+
+/// 仪表盘缓存有效期
+const DASHBOARD_CACHE_TTL: StdDuration = StdDuration::from_secs(15);
+
+/// 仪表盘短时缓存：频繁刷新时复用聚合结果
+#[derive(Default)]
+pub struct DashboardCache {
+  entries: Mutex<HashMap<String, (Instant, DashboardOverview)>>,
+}
+
+impl DashboardCache {
+  fn get(&self, key: &str) -> Option<DashboardOverview> {
+    let entries = self.entries.lock();
+    entries
+      .get(key)
+      .filter(|(cached_at, _)| cached_at.elapsed() < DASHBOARD_CACHE_TTL)
+      .map(|(_, value)| value.clone())
+  }
+
+  fn put(&self, key: String, value: DashboardOverview) {
+    let mut entries = self.entries.lock();
+    entries.retain(|_, (cached_at, _)| cached_at.elapsed() < DASHBOARD_CACHE_TTL);
+    entries.insert(key, (Instant::now(), value));
+  }
 }
 
-pub async fn get_overview(pool: &SqlitePool) -> Result<DashboardOverview, AppError> {
-  let now = Local::now();
-  let today = now.date_naive();
-  let today_start = to_local_timestamp(today);
+/// 带缓存的仪表盘概览，缓存按统计区间与排行参数区分
+pub async fn get_overview_cached(
+  pool: &SqlitePool,
+  cache: &DashboardCache,
+  range: OverviewRange,
+  leaderboard: Option<LeaderboardQuery>,
+) -> Result<DashboardOverview, AppError> {
+  let key = format!("overview:{:?}:{:?}", range, leaderboard);
+  if let Some(cached) = cache.get(&key) {
+    return Ok(cached);
+  }
+  let overview = get_overview(pool, range, leaderboard).await?;
+  cache.put(key, overview.clone());
+  Ok(overview)
+}
+
+pub async fn get_overview(
+  pool: &SqlitePool,
+  range: OverviewRange,
+  leaderboard: Option<LeaderboardQuery>,
+) -> Result<DashboardOverview, AppError> {
+  let granularity = TrendGranularity::parse(range.granularity.as_deref())?;
+  // 今日与趋势分组均按报表时区划分
+  let timezone = system_service::report_timezone(pool).await?;
+  let today = timezone.today();
+  let today_start = timezone.day_start(today);
   let tomorrow = today.succ_opt().unwrap_or(today);
-  let tomorrow_start = to_local_timestamp(tomorrow);
+  let tomorrow_start = timezone.day_start(tomorrow);
   let today_end = if tomorrow == today {
     today_start + 86_399
   } else {
     tomorrow_start - 1
   };
 
-  let mut today_counts = DashboardTxnCounts {
-    inbound: 0,
-    outbound: 0,
-    move_count: 0,
-    count_count: 0,
-    reversal: 0,
+  let today_counts = count_txns(pool, today_start, today_end).await?;
+
+  let trend_end = range.end_at.unwrap_or(today_end);
+  let zone_day = |timestamp: i64| {
+    timezone
+      .date_of(timestamp)
+      .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "时间参数无效"))
   };
-  let type_rows = dashboard_repo::count_txns_by_type(pool, today_start, today_end).await?;
-  for row in type_rows {
-    match row.txn_type.as_str() {
-      "IN" => today_counts.inbound = row.total,
-      "OUT" => today_counts.outbound = row.total,
-      "MOVE" => today_counts.move_count = row.total,
-      "COUNT" => today_counts.count_count = row.total,
-      "REVERSAL" => today_counts.reversal = row.total,
-      _ => {}
-    }
+  let end_day = zone_day(trend_end)?;
+  let trend_start = match range.start_at {
+    Some(start_at) => start_at,
+    None => timezone.day_start(granularity.default_start(end_day)),
+  };
+  if trend_start > trend_end {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidTimeRange));
   }
+  let period_counts = count_txns(pool, trend_start, trend_end).await?;
 
-  let start_day = today.checked_sub_signed(Duration::days(6)).unwrap_or(today);
-  let trend_start = to_local_timestamp(start_day);
-  let trend_end = today_end;
-  let trend_rows = dashboard_repo::list_txn_trend(pool, trend_start, trend_end).await?;
-  let mut trend_map: HashMap<(String, String), i64> = HashMap::new();
-  for row in trend_rows {
-    trend_map.insert((row.day, row.txn_type), row.total);
-  }
+  let trend_rows =
+    dashboard_repo::list_txn_trend(pool, trend_start, trend_end, granularity.as_str(), &timezone).await?;
+  let mut trend_map: HashMap<String, dashboard_repo::TxnTrendRow> = trend_rows
+    .into_iter()
+    .map(|row| (row.bucket.clone(), row))
+    .collect();
+  // 补齐无流水的分组，保证图表横轴连续
+  let last_bucket = granularity.bucket_start(end_day);
+  let mut bucket = Some(granularity.bucket_start(zone_day(trend_start)?));
   let mut trend = Vec::new();
-  for offset in 0..7 {
-    let day = start_day
-      .checked_add_signed(Duration::days(offset))
-      .unwrap_or(today);
+  while let Some(day) = bucket.filter(|day| *day <= last_bucket) {
+    if trend.len() >= MAX_TREND_BUCKETS {
+      return Err(AppError::new(ErrorCode::ValidationError, "统计区间过长，请缩短区间或调大统计粒度"));
+    }
     let day_key = day.format("%Y-%m-%d").to_string();
-    let inbound = *trend_map.get(&(day_key.clone(), "IN".to_string())).unwrap_or(&0);
-    let outbound = *trend_map.get(&(day_key.clone(), "OUT".to_string())).unwrap_or(&0);
-    let move_count = *trend_map.get(&(day_key.clone(), "MOVE".to_string())).unwrap_or(&0);
-    let count_count = *trend_map.get(&(day_key.clone(), "COUNT".to_string())).unwrap_or(&0);
-    trend.push(DashboardTrendPoint {
-      day: day_key,
-      inbound,
-      outbound,
-      move_count,
-      count_count,
-    });
+    let point = match trend_map.remove(&day_key) {
+      Some(row) => DashboardTrendPoint {
+        day: day_key,
+        inbound: row.inbound,
+        outbound: row.outbound,
+        move_count: row.move_count,
+        count_count: row.count_count,
+        scrap: row.scrap,
+      },
+      None => DashboardTrendPoint {
+        day: day_key,
+        inbound: 0,
+        outbound: 0,
+        move_count: 0,
+        count_count: 0,
+        scrap: 0,
+      },
+    };
+    trend.push(point);
+    bucket = granularity.next_bucket(day);
   }
 
   let total_stock_qty = dashboard_repo::sum_stock_qty(pool).await?;
@@ -107,29 +298,119 @@ pub async fn get_overview(pool: &SqlitePool) -> Result<DashboardOverview, AppErr
   let active_racks = dashboard_repo::count_active_racks(pool).await?;
   let active_warehouses = dashboard_repo::count_active_warehouses(pool).await?;
   let negative_stock = dashboard_repo::count_negative_stock(pool).await?;
+  let stale_stock = report_service::count_stale_stock(pool).await?;
+  let (expired_stock, expiring_stock) = expiry_service::count_expiring_stock(pool).await?;
+  let slot_fill_rate = warehouse_service::get_warehouse_utilization(pool, None, None)
+    .await?
+    .summary
+    .fill_rate;
+  let now = Local::now().timestamp();
+  let loans_open = loan_repo::count_open_loans(pool, now, false, None, None, None).await?;
+  let loans_overdue = loan_repo::count_open_loans(pool, now, true, None, None, None).await?;
+  let items_on_loan = loan_repo::list_items_on_loan(pool, now, 5).await?;
   let stock_rows = dashboard_repo::list_stock_by_warehouse(pool).await?;
+  let total_stock_value: f64 = stock_rows.iter().map(|row| row.total_value).sum();
+  let yesterday_close = stock_close_service::yesterday_close(pool).await?;
   let stock_by_warehouse = stock_rows
     .into_iter()
     .map(|row| DashboardWarehouseStock {
       warehouse_code: row.warehouse_code,
       warehouse_name: row.warehouse_name,
       total_qty: row.total_qty,
+      total_value: row.total_value,
+      value_percent: if total_stock_value > 0.0 {
+        row.total_value / total_stock_value * 100.0
+      } else {
+        0.0
+      },
     })
     .collect();
 
+  let operator_leaderboard = match leaderboard {
+    Some(query) => {
+      let start_at = query.start_at.unwrap_or(trend_start);
+      let end_at = query.end_at.unwrap_or(trend_end);
+      let limit = query.limit.filter(|value| *value > 0).unwrap_or(10).min(50);
+      let rows = dashboard_repo::list_operator_leaderboard(pool, start_at, end_at, limit).await?;
+      Some(
+        rows
+          .into_iter()
+          .map(|row| DashboardOperatorRank {
+            operator_id: row.operator_id,
+            operator_name: row.operator_name,
+            txn_count: row.txn_count,
+            total_qty: row.total_qty,
+          })
+          .collect(),
+      )
+    }
+    None => None,
+  };
+
   Ok(DashboardOverview {
     today: today_counts,
+    period: period_counts,
+    range_start: trend_start,
+    range_end: trend_end,
+    granularity: granularity.as_str().to_string(),
     total_stock_qty,
+    total_stock_value,
+    yesterday_close_qty: yesterday_close.as_ref().map(|close| close.total_qty),
+    yesterday_close_value: yesterday_close.as_ref().map(|close| close.total_value),
     active_items,
     active_racks,
     active_warehouses,
     negative_stock,
+    stale_stock,
+    expired_stock,
+    expiring_stock,
+    slot_fill_rate,
+    loans_open,
+    loans_overdue,
+    items_on_loan,
     trend,
     stock_by_warehouse,
+    operator_leaderboard,
   })
 }
 
-fn to_local_timestamp(day: NaiveDate) -> i64 {
+async fn count_txns(
+  pool: &SqlitePool,
+  start_at: i64,
+  end_at: i64,
+) -> Result<DashboardTxnCounts, AppError> {
+  let mut counts = DashboardTxnCounts {
+    inbound: 0,
+    outbound: 0,
+    move_count: 0,
+    count_count: 0,
+    scrap: 0,
+    reversal: 0,
+  };
+  let type_rows = dashboard_repo::count_txns_by_type(pool, start_at, end_at).await?;
+  for row in type_rows {
+    match row.txn_type.as_str() {
+      "IN" => counts.inbound = row.total,
+      "OUT" => counts.outbound = row.total,
+      "MOVE" => counts.move_count = row.total,
+      "COUNT" => counts.count_count = row.total,
+      "SCRAP" => counts.scrap = row.total,
+      "REVERSAL" => counts.reversal = row.total,
+      _ => {}
+    }
+  }
+  Ok(counts)
+}
+
+pub fn local_day(timestamp: i64) -> Result<NaiveDate, AppError> {
+  Local
+    .timestamp_opt(timestamp, 0)
+    .single()
+    .map(|time| time.date_naive())
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "时间参数无效"))
+}
+
+pub fn to_local_timestamp(day: NaiveDate) -> i64 {
   let naive = day.and_hms_opt(0, 0, 0).unwrap_or_else(|| day.and_hms_opt(0, 0, 0).unwrap());
   Local.from_local_datetime(&naive).unwrap().timestamp()
 }
@@ -0,0 +1,316 @@
+// 演示数据：为试用环境生成仓库、货架、物品、人员与近期流水，使仪表盘和各列表有数据可看
+//
+// 主数据按现有编码规则生成并避开已占用的编码；流水在单个事务内按时间顺序生成，
+// 出库、移库只从有库存的库位发出，库存按流水结果写入，与健康检查的流水推算口径一致。
+use std::collections::HashMap;
+
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::infra::crypto;
+use crate::repo::rack_repo::{self, SlotRow};
+use crate::repo::txn_repo::{self, TxnRow};
+use crate::repo::{item_repo, operator_repo, stock_repo, warehouse_repo};
+use crate::services::{rack_service, system_service, txn_service};
+
+/// 默认生成的流水条数与上限
+pub const DEFAULT_DEMO_TXN_COUNT: i64 = 3000;
+pub const MAX_DEMO_TXN_COUNT: i64 = 20000;
+/// 流水分布的天数（截至当前时间）
+const DEMO_DAYS: i64 = 90;
+const DEMO_WAREHOUSES: usize = 2;
+const DEMO_RACKS_PER_WAREHOUSE: usize = 3;
+const DEMO_LEVELS: i64 = 4;
+const DEMO_SLOTS_PER_LEVEL: i64 = 5;
+/// 演示人员的初始密码，首次登录需修改
+const DEMO_PASSWORD: &str = "123456";
+
+/// 演示物品：（名称, 型号, 单位, 单位成本）
+const DEMO_ITEMS: &[(&str, &str, &str, f64)] = &[
+  ("内六角螺丝", "M4x12", "个", 0.12),
+  ("内六角螺丝", "M6x20", "个", 0.25),
+  ("六角螺母", "M6", "个", 0.08),
+  ("平垫圈", "M8", "个", 0.05),
+  ("深沟球轴承", "6204-2RS", "个", 12.5),
+  ("深沟球轴承", "6305-ZZ", "个", 18.0),
+  ("尼龙扎带", "4x200mm", "包", 6.8),
+  ("电缆", "RVV 3x1.5", "米", 4.2),
+  ("网线", "CAT6", "米", 2.6),
+  ("保险丝", "5A", "个", 0.9),
+  ("中间继电器", "24VDC", "个", 28.0),
+  ("接近开关", "M12 NPN", "个", 45.0),
+  ("劳保手套", "L", "双", 3.5),
+  ("防护眼镜", "通用", "副", 15.0),
+  ("电工胶带", "18mm", "卷", 2.2),
+  ("液压油滤芯", "HF-10", "个", 86.0),
+  ("空气滤芯", "AF-25", "个", 64.0),
+  ("同步带", "HTD 5M-450", "条", 38.0),
+  ("气管", "PU 8mm", "米", 3.1),
+  ("快插接头", "PC8-02", "个", 4.5),
+];
+
+#[derive(Debug, Default, serde::Serialize)]
+pub struct DemoDataResult {
+  pub warehouses: i64,
+  pub racks: i64,
+  pub slots: i64,
+  pub items: i64,
+  pub operators: i64,
+  pub txns: i64,
+}
+
+struct DemoSlot {
+  id: String,
+  warehouse: usize,
+}
+
+struct DemoItem {
+  id: String,
+  warehouse: usize,
+  unit_cost: f64,
+  // 常用库位（slots 下标），入库只放到这些库位
+  home_slots: Vec<usize>,
+}
+
+/// 生成演示数据；数据库已有物品、货架或流水时拒绝，force 时在现有数据旁追加
+pub async fn generate_demo_data(
+  pool: &SqlitePool,
+  txn_count: Option<i64>,
+  force: bool,
+  actor_operator_id: &str,
+) -> Result<DemoDataResult, AppError> {
+  let txn_count = txn_count.unwrap_or(DEFAULT_DEMO_TXN_COUNT);
+  if !(1..=MAX_DEMO_TXN_COUNT).contains(&txn_count) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("流水条数需在 1-{} 之间", MAX_DEMO_TXN_COUNT),
+    ));
+  }
+  let has_data = item_repo::count_items(pool, None).await? > 0
+    || rack_repo::count_racks(pool, None, None).await? > 0
+    || txn_repo::count_txns(pool).await? > 0;
+  if has_data && !force {
+    return Err(AppError::new(
+      ErrorCode::Conflict,
+      "数据库已有业务数据，如需追加演示数据请勾选强制生成",
+    ));
+  }
+
+  let mut rng = StdRng::from_entropy();
+  let now = Utc::now().timestamp();
+  let start = now - DEMO_DAYS * 86400;
+  let pad = system_service::slot_no_pad(pool).await?;
+  let mut result = DemoDataResult::default();
+
+  // 仓库、货架与库位
+  let mut slots: Vec<DemoSlot> = Vec::new();
+  let mut warehouse_code_no = 0;
+  for warehouse in 0..DEMO_WAREHOUSES {
+    let code = loop {
+      warehouse_code_no += 1;
+      let code = warehouse_code_no.to_string();
+      if warehouse_repo::get_warehouse_by_code(pool, &code).await?.is_none() {
+        break code;
+      }
+    };
+    let warehouse_id = Uuid::new_v4().to_string();
+    let name = format!("演示仓库 {}", code);
+    warehouse_repo::insert_warehouse(pool, &warehouse_id, &code, &name, "active", "fixed", None, start).await?;
+    result.warehouses += 1;
+
+    for rack_no in 1..=DEMO_RACKS_PER_WAREHOUSE {
+      let rack_id = Uuid::new_v4().to_string();
+      let rack_code = rack_no.to_string();
+      rack_repo::insert_rack(
+        pool,
+        &rack_id,
+        &rack_code,
+        &format!("R{}", rack_code),
+        Some(warehouse_id.clone()),
+        Some(format!("{} 区", (b'A' + rack_no as u8 - 1) as char)),
+        "active",
+        DEMO_LEVELS,
+        DEMO_SLOTS_PER_LEVEL,
+        start,
+      )
+      .await?;
+      let mut rows = Vec::new();
+      for level in 1..=DEMO_LEVELS {
+        for slot_no in 1..=DEMO_SLOTS_PER_LEVEL {
+          let id = Uuid::new_v4().to_string();
+          slots.push(DemoSlot {
+            id: id.clone(),
+            warehouse,
+          });
+          rows.push(SlotRow {
+            id,
+            rack_id: rack_id.clone(),
+            level_no: level,
+            slot_no,
+            warehouse_id: Some(warehouse_id.clone()),
+            code: rack_service::slot_code(&code, &rack_code, level, slot_no, pad),
+            status: "active".to_string(),
+            created_at: start,
+          });
+        }
+      }
+      result.slots += rows.len() as i64;
+      rack_repo::insert_slots(pool, rows).await?;
+      result.racks += 1;
+    }
+  }
+
+  // 物品：每种物品在每个仓库各建一条，分配 1-3 个常用库位
+  let mut items: Vec<DemoItem> = Vec::new();
+  let mut item_code_no = 0;
+  for warehouse in 0..DEMO_WAREHOUSES {
+    let warehouse_slots: Vec<usize> = (0..slots.len()).filter(|index| slots[*index].warehouse == warehouse).collect();
+    for (name, model, uom, unit_cost) in DEMO_ITEMS {
+      let item_code = loop {
+        item_code_no += 1;
+        let code = format!("DEMO-{:04}", item_code_no);
+        if item_repo::count_by_item_code(pool, &code).await? == 0 {
+          break code;
+        }
+      };
+      let id = Uuid::new_v4().to_string();
+      item_repo::insert_item(
+        pool,
+        &id,
+        &item_code,
+        name,
+        Some(model.to_string()),
+        None,
+        Some(uom.to_string()),
+        Some(*unit_cost),
+        Some("CNY".to_string()),
+        0,
+        "active",
+        Some("演示数据".to_string()),
+        start,
+      )
+      .await?;
+      let home_count = rng.gen_range(1..=3);
+      items.push(DemoItem {
+        id,
+        warehouse,
+        unit_cost: *unit_cost,
+        home_slots: warehouse_slots.choose_multiple(&mut rng, home_count).copied().collect(),
+      });
+      result.items += 1;
+    }
+  }
+
+  // 人员：两名保管员、一名成员与一名只读人员，记录流水时在管理员与保管员间随机
+  let mut txn_operators = vec![actor_operator_id.to_string()];
+  let password_hash = crypto::hash_password(DEMO_PASSWORD)?;
+  let mut username_no = 0;
+  for (role, display_name) in [
+    ("keeper", "演示保管员甲"),
+    ("keeper", "演示保管员乙"),
+    ("member", "演示成员"),
+    ("viewer", "演示只读"),
+  ] {
+    let username = loop {
+      username_no += 1;
+      let username = format!("demo{}", username_no);
+      if operator_repo::count_by_username(pool, &username).await? == 0 {
+        break username;
+      }
+    };
+    let id = Uuid::new_v4().to_string();
+    operator_repo::insert_operator(pool, &id, &username, display_name, role, "active", &password_hash, true, start)
+      .await?;
+    if role == "keeper" {
+      txn_operators.push(id);
+    }
+    result.operators += 1;
+  }
+
+  // 流水：先为每个物品入库一次，其余按时间随机生成入库/出库/移库
+  let mut occurred: Vec<i64> = (0..txn_count).map(|_| rng.gen_range(start..now)).collect();
+  occurred.sort_unstable();
+  let mut stock: HashMap<(usize, usize), i64> = HashMap::new();
+  let scheme = txn_service::txn_no_scheme(pool).await?;
+  let mut tx = pool.begin().await?;
+  for (index, occurred_at) in occurred.into_iter().enumerate() {
+    let item_index = if index < items.len() { index } else { rng.gen_range(0..items.len()) };
+    let item = &items[item_index];
+    let stocked: Vec<usize> = item
+      .home_slots
+      .iter()
+      .chain(stock.keys().filter(|(i, _)| *i == item_index).map(|(_, slot)| slot))
+      .copied()
+      .filter(|slot| stock.get(&(item_index, *slot)).copied().unwrap_or(0) > 0)
+      .collect();
+    let roll = rng.gen_range(0..100);
+    let (txn_type, from_slot, to_slot, qty) = match stocked.choose(&mut rng) {
+      Some(&from) if index >= items.len() && roll < 45 => {
+        let available = stock[&(item_index, from)];
+        ("OUT", Some(from), None, rng.gen_range(1..=available.min(20)))
+      }
+      Some(&from) if index >= items.len() && roll < 60 => {
+        let warehouse_slots: Vec<usize> = (0..slots.len())
+          .filter(|slot| slots[*slot].warehouse == item.warehouse && *slot != from)
+          .collect();
+        let to = *warehouse_slots.choose(&mut rng).unwrap_or(&from);
+        let available = stock[&(item_index, from)];
+        ("MOVE", Some(from), Some(to), rng.gen_range(1..=available))
+      }
+      _ => {
+        let to = *item.home_slots.choose(&mut rng).unwrap_or(&0);
+        ("IN", None, Some(to), rng.gen_range(10..=100))
+      }
+    };
+    if let Some(from) = from_slot {
+      *stock.entry((item_index, from)).or_insert(0) -= qty;
+    }
+    if let Some(to) = to_slot {
+      *stock.entry((item_index, to)).or_insert(0) += qty;
+    }
+
+    let from_slot_id = from_slot.map(|slot| slots[slot].id.clone());
+    let to_slot_id = to_slot.map(|slot| slots[slot].id.clone());
+    let txn_no = txn_service::next_txn_no(
+      &mut tx,
+      &scheme,
+      txn_type,
+      to_slot_id.as_deref().or(from_slot_id.as_deref()),
+    )
+    .await?;
+    let row = TxnRow {
+      id: Uuid::new_v4().to_string(),
+      txn_no,
+      txn_type: txn_type.to_string(),
+      occurred_at,
+      created_at: occurred_at,
+      operator_id: txn_operators.choose(&mut rng).cloned().unwrap_or_default(),
+      item_id: item.id.clone(),
+      from_slot_id,
+      to_slot_id,
+      qty,
+      actual_qty: None,
+      ref_txn_id: None,
+      note: Some("演示数据".to_string()),
+      unit_cost: (txn_type == "IN").then_some(item.unit_cost),
+      location: None,
+      expires_at: None,
+      reason_id: None,
+    };
+    txn_repo::insert_txn(&mut tx, &row).await?;
+    result.txns += 1;
+  }
+  for ((item_index, slot_index), qty) in stock {
+    if qty > 0 {
+      stock_repo::upsert_stock_tx(&mut tx, &items[item_index].id, &slots[slot_index].id, qty, now).await?;
+    }
+  }
+  tx.commit().await?;
+
+  Ok(result)
+}
@@ -0,0 +1,216 @@
+use std::path::PathBuf;
+
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::infra::zip::ZipBuilder;
+use crate::repo::audit_repo::{self, AuditLogFilter, AuditLogRow};
+use crate::repo::{health_repo, meta_repo};
+use crate::services::{count_service, system_service};
+
+/// 诊断包中的最近审计条数与最近失败条数
+const AUDIT_LIMIT: i64 = 200;
+const ERROR_LIMIT: i64 = 100;
+
+/// 按字段名整体脱敏的键：凭据与个人信息（完整匹配）
+const REDACTED_KEYS: [&str; 12] = [
+  "pin",
+  "new_pin",
+  "code",
+  "username",
+  "display_name",
+  "borrower",
+  "phone",
+  "email",
+  "contact",
+  "note",
+  "remark",
+  "keyword",
+];
+/// 字段名包含以下片段时脱敏（如 password/new_password/password_hash/backup_passphrase）
+const REDACTED_FRAGMENTS: [&str; 4] = ["password", "passphrase", "secret", "token"];
+const REDACTED: &str = "***";
+
+/// 诊断包返回结构
+#[derive(Debug, serde::Serialize)]
+pub struct DiagnosticsResult {
+  // 压缩包路径
+  pub file_path: String,
+  // 包内文件
+  pub files: Vec<String>,
+}
+
+/// 生成诊断包：版本、设置、最近审计与失败记录、数据库概要，写入导出目录的 zip 文件；
+/// 不包含密码哈希与业务数据明细，审计参数中的凭据与个人信息统一替换为 ***
+pub async fn collect_diagnostics(pool: &SqlitePool) -> Result<DiagnosticsResult, AppError> {
+  let now = Utc::now().timestamp();
+  let home = home_dir();
+
+  let app = json!({
+    "generated_at": now,
+    "app_version": meta_repo::get_meta_value(pool, "app_version").await?,
+    "app_version_prev": meta_repo::get_meta_value(pool, "app_version_prev").await?,
+    "app_version_updated_at": meta_repo::get_meta_value(pool, "app_version_updated_at").await?,
+    "schema_version": meta_repo::get_meta_value(pool, "schema_version").await?,
+    "os": std::env::consts::OS,
+    "arch": std::env::consts::ARCH,
+    "migrations": health_repo::applied_migrations(pool).await?,
+    "redaction": "密码哈希与个人信息已移除，审计参数中的凭据、姓名、备注等字段替换为 ***，路径中的用户目录替换为 ~",
+  });
+
+  let settings = serde_json::to_value(system_service::get_settings(pool).await?)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "序列化设置失败"))?;
+
+  let audit = audit_repo::list_audit_logs(pool, &AuditLogFilter::default(), 1, AUDIT_LIMIT).await?;
+  let errors = audit_repo::list_audit_logs(
+    pool,
+    &AuditLogFilter {
+      result: Some("fail".to_string()),
+      ..AuditLogFilter::default()
+    },
+    1,
+    ERROR_LIMIT,
+  )
+  .await?;
+
+  let db_summary = db_summary(pool).await?;
+
+  // 每个诊断包使用独立的随机盐，包内同一目标的哈希一致，不同诊断包之间无法关联
+  let salt = Uuid::new_v4().to_string();
+  let entries = [
+    ("app.json", pretty(&app)?),
+    ("settings.json", pretty(&settings)?),
+    ("audit.jsonl", audit_lines(&audit, &salt)?),
+    ("errors.jsonl", audit_lines(&errors, &salt)?),
+    ("db_summary.json", pretty(&db_summary)?),
+  ];
+  let mut zip = ZipBuilder::new();
+  let mut files = Vec::with_capacity(entries.len());
+  for (name, content) in entries {
+    // JSON 中的 Windows 路径分隔符已转义，两种写法都需替换
+    let content = match home.as_deref() {
+      Some(home) => content.replace(&home.replace('\\', "\\\\"), "~").replace(home, "~"),
+      None => content,
+    };
+    zip.add(name, content.as_bytes())?;
+    files.push(name.to_string());
+  }
+
+  let file_path = count_service::export_dir(pool)
+    .await?
+    .join(format!("diagnostics_{}.zip", now));
+  std::fs::write(&file_path, zip.finish())
+    .map_err(|_| AppError::new(ErrorCode::IoError, "写入诊断包失败"))?;
+
+  Ok(DiagnosticsResult {
+    file_path: file_path.to_string_lossy().to_string(),
+    files,
+  })
+}
+
+/// 数据库概要：文件大小、页统计与各表行数，不读取任何业务数据内容
+async fn db_summary(pool: &SqlitePool) -> Result<Value, AppError> {
+  let db_path = meta_repo::get_meta_value(pool, "storage_root")
+    .await?
+    .map(|root| PathBuf::from(root).join("db").join("db.sqlite"));
+  let file_size = |suffix: &str| {
+    db_path.as_ref().and_then(|path| {
+      let mut path = path.clone().into_os_string();
+      path.push(suffix);
+      std::fs::metadata(path).ok().map(|meta| meta.len())
+    })
+  };
+
+  let mut tables = Map::new();
+  for (name, count) in health_repo::table_row_counts(pool).await? {
+    tables.insert(name, json!(count));
+  }
+  Ok(json!({
+    "db_size": file_size(""),
+    "wal_size": file_size("-wal"),
+    "page_size": health_repo::pragma_value(pool, "page_size").await?,
+    "page_count": health_repo::pragma_value(pool, "page_count").await?,
+    "freelist_count": health_repo::pragma_value(pool, "freelist_count").await?,
+    "journal_mode": health_repo::pragma_value(pool, "journal_mode").await?,
+    "tables": tables,
+  }))
+}
+
+/// 审计记录逐行输出，请求参数按字段脱敏；不附带操作人姓名，仅保留操作人 id
+fn audit_lines(rows: &[AuditLogRow], salt: &str) -> Result<String, AppError> {
+  let mut lines = String::new();
+  for row in rows {
+    let request = row
+      .request_json
+      .as_deref()
+      .map(|raw| serde_json::from_str::<Value>(raw).map(redact).unwrap_or(Value::Null));
+    let line = json!({
+      "id": row.id,
+      "created_at": row.created_at,
+      "actor_operator_id": row.actor_operator_id,
+      "action": row.action,
+      "target_type": row.target_type,
+      "target_id": redact_target(row.target_type.as_deref(), row.target_id.as_deref(), salt),
+      "request": request,
+      "result": row.result,
+      "error_code": row.error_code,
+      "error_detail": row.error_detail,
+    });
+    lines.push_str(&line.to_string());
+    lines.push('\n');
+  }
+  Ok(lines)
+}
+
+/// 操作人与登录类审计的目标标识可能是用户名，按盐取 SHA-256 前 16 位输出，其他目标原样保留
+fn redact_target(target_type: Option<&str>, target_id: Option<&str>, salt: &str) -> Option<String> {
+  let target_id = target_id?;
+  if !matches!(target_type, Some("operator" | "auth")) {
+    return Some(target_id.to_string());
+  }
+  let digest = Sha256::digest(format!("{}:{}", salt, target_id).as_bytes());
+  let hex: String = digest.iter().take(8).map(|byte| format!("{:02x}", byte)).collect();
+  Some(format!("sha256:{}", hex))
+}
+
+/// 递归替换敏感字段的值
+fn redact(value: Value) -> Value {
+  match value {
+    Value::Object(map) => Value::Object(
+      map
+        .into_iter()
+        .map(|(key, value)| {
+          if is_sensitive_key(&key) && !value.is_null() {
+            (key, Value::String(REDACTED.to_string()))
+          } else {
+            (key, redact(value))
+          }
+        })
+        .collect(),
+    ),
+    Value::Array(items) => Value::Array(items.into_iter().map(redact).collect()),
+    other => other,
+  }
+}
+
+fn is_sensitive_key(key: &str) -> bool {
+  let key = key.to_lowercase();
+  REDACTED_KEYS.contains(&key.as_str())
+    || REDACTED_FRAGMENTS.iter().any(|fragment| key.contains(fragment))
+}
+
+fn pretty(value: &Value) -> Result<String, AppError> {
+  serde_json::to_string_pretty(value).map_err(|_| AppError::new(ErrorCode::IoError, "序列化诊断信息失败"))
+}
+
+/// 用户目录（路径中出现时替换为 ~，避免暴露系统用户名）
+fn home_dir() -> Option<String> {
+  std::env::var("HOME")
+    .or_else(|_| std::env::var("USERPROFILE"))
+    .ok()
+    .filter(|home| home.len() > 1)
+}
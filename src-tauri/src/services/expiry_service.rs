@@ -0,0 +1,235 @@
+// 到期日：入库时按流水录入到期日，库位现存库存按先进先出归属到最近的入库 / 移入流水；
+// 出库将消耗已过期库存时拦截（管理员确认后放行），并按可配置天数统计临期库存
+use std::collections::HashMap;
+
+use chrono::{Local, TimeZone, Utc};
+use serde::Serialize;
+use sqlx::{SqlitePool, Transaction};
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::quantity;
+use crate::repo::{report_repo, txn_repo};
+use crate::services::{dashboard_service, system_service};
+
+const DAY_SECONDS: i64 = 86_400;
+
+/// 批次：(数量, 到期日)
+type Batch = (i64, Option<i64>);
+
+/// 按先进先出推算库位现存库存的批次构成：legs 为按时间倒序的 (数量, 到期日)，
+/// 现存库存归属最近流入的部分；流水不足以覆盖的部分（导入、盘盈等）视为最早且无到期日。
+/// 返回按从旧到新排列的 (数量, 到期日)
+pub fn attribute_fifo(stock_qty: i64, legs: &[Batch]) -> Vec<Batch> {
+  let mut remaining = stock_qty.max(0);
+  let mut portions = Vec::new();
+  for (qty, expires_at) in legs {
+    if remaining <= 0 {
+      break;
+    }
+    let take = (*qty).min(remaining);
+    if take > 0 {
+      portions.push((take, *expires_at));
+      remaining -= take;
+    }
+  }
+  if remaining > 0 {
+    portions.push((remaining, None));
+  }
+  portions.reverse();
+  portions
+}
+
+/// 按先进先出从最早的批次开始取出 qty，返回被消耗部分的 (数量, 到期日)
+fn take_oldest(portions: &[Batch], qty: i64) -> Vec<Batch> {
+  let mut remaining = qty;
+  let mut taken = Vec::new();
+  for (portion_qty, expires_at) in portions {
+    if remaining <= 0 {
+      break;
+    }
+    let take = (*portion_qty).min(remaining);
+    taken.push((take, *expires_at));
+    remaining -= take;
+  }
+  taken
+}
+
+async fn consumed_portions_tx(
+  tx: &mut Transaction<'_, sqlx::Sqlite>,
+  item_id: &str,
+  slot_id: &str,
+  stock_qty: i64,
+  qty: i64,
+) -> Result<Vec<Batch>, AppError> {
+  let legs = txn_repo::list_inbound_legs_tx(tx, item_id, slot_id).await?;
+  Ok(take_oldest(&attribute_fifo(stock_qty, &legs), qty))
+}
+
+/// 今日零点（本地时间）；到期日当天仍可使用，早于今日零点的视为已过期
+fn today_start() -> i64 {
+  dashboard_service::to_local_timestamp(Local::now().date_naive())
+}
+
+fn format_day(timestamp: i64) -> String {
+  Local
+    .timestamp_opt(timestamp, 0)
+    .single()
+    .map(|value| value.format("%Y-%m-%d").to_string())
+    .unwrap_or_default()
+}
+
+/// 出库前校验：按先进先出本次出库将消耗已过期的库存时拒绝，allow_expired（管理员确认）时放行
+pub async fn ensure_not_expired_tx(
+  tx: &mut Transaction<'_, sqlx::Sqlite>,
+  item_id: &str,
+  slot_id: &str,
+  stock_qty: i64,
+  qty: i64,
+  allow_expired: bool,
+) -> Result<(), AppError> {
+  if allow_expired {
+    return Ok(());
+  }
+  let today_start = today_start();
+  let expired = consumed_portions_tx(tx, item_id, slot_id, stock_qty, qty)
+    .await?
+    .into_iter()
+    .filter_map(|(_, expires_at)| expires_at)
+    .filter(|expires_at| *expires_at < today_start)
+    .min();
+  if let Some(expires_at) = expired {
+    return Err(AppError::new(
+      ErrorCode::Forbidden,
+      format!("出库将消耗已过期库存（到期日 {}），需管理员确认后出库", format_day(expires_at)),
+    ));
+  }
+  Ok(())
+}
+
+/// 移库时目标库位记录的到期日：移出部分中最早的到期日
+pub async fn move_expiry_tx(
+  tx: &mut Transaction<'_, sqlx::Sqlite>,
+  item_id: &str,
+  slot_id: &str,
+  stock_qty: i64,
+  qty: i64,
+) -> Result<Option<i64>, AppError> {
+  Ok(
+    consumed_portions_tx(tx, item_id, slot_id, stock_qty, qty)
+      .await?
+      .into_iter()
+      .filter_map(|(_, expires_at)| expires_at)
+      .min(),
+  )
+}
+
+/// 校验入库录入的到期日
+pub fn validate_expires_at(expires_at: Option<i64>) -> Result<Option<i64>, AppError> {
+  match expires_at {
+    Some(value) if value <= 0 => Err(AppError::new(ErrorCode::ValidationError, "到期日无效")),
+    other => Ok(other),
+  }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpiringStockItem {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_name: Option<String>,
+  // 该批次在库位上的数量（显示数量）
+  pub qty: f64,
+  pub expires_at: i64,
+  // 距到期日的天数，已过期为负数
+  pub days_left: i64,
+  pub expired: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpiringStockReport {
+  pub horizon_days: i64,
+  pub generated_at: i64,
+  pub expired_count: i64,
+  pub expiring_count: i64,
+  pub items: Vec<ExpiringStockItem>,
+}
+
+/// 临期报表：列出已过期及 horizon_days 天内到期的库存批次（按到期日升序），
+/// horizon_days 为空时使用系统设置的临期天数
+pub async fn get_expiring_report(
+  pool: &SqlitePool,
+  horizon_days: Option<i64>,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<ExpiringStockReport, AppError> {
+  let horizon_days = match horizon_days {
+    Some(days) => {
+      system_service::validate_expiry_horizon_days(days)?;
+      days
+    }
+    None => system_service::expiry_horizon_days(pool).await?,
+  };
+  let warehouse_id = warehouse_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let stock_rows = report_repo::list_expiry_stock(pool, warehouse_id.as_deref(), allowed_warehouse_ids).await?;
+
+  let mut legs_by_slot: HashMap<(String, String), Vec<Batch>> = HashMap::new();
+  for (item_id, slot_id, qty, expires_at) in report_repo::list_expiry_inbound_legs(pool).await? {
+    legs_by_slot.entry((item_id, slot_id)).or_default().push((qty, expires_at));
+  }
+
+  let today_start = today_start();
+  let horizon_end = today_start + (horizon_days + 1) * DAY_SECONDS;
+  let mut items = Vec::new();
+  for row in stock_rows {
+    let legs = legs_by_slot
+      .get(&(row.item_id.clone(), row.slot_id.clone()))
+      .map(Vec::as_slice)
+      .unwrap_or(&[]);
+    // 同一到期日的批次合并展示
+    let mut by_expiry: Vec<(i64, i64)> = Vec::new();
+    for (qty, expires_at) in attribute_fifo(row.qty, legs) {
+      let Some(expires_at) = expires_at.filter(|value| *value < horizon_end) else {
+        continue;
+      };
+      match by_expiry.iter_mut().find(|(value, _)| *value == expires_at) {
+        Some(entry) => entry.1 += qty,
+        None => by_expiry.push((expires_at, qty)),
+      }
+    }
+    for (expires_at, qty) in by_expiry {
+      items.push(ExpiringStockItem {
+        item_id: row.item_id.clone(),
+        item_code: row.item_code.clone(),
+        item_name: row.item_name.clone(),
+        uom: row.uom.clone(),
+        slot_id: row.slot_id.clone(),
+        slot_code: row.slot_code.clone(),
+        warehouse_id: row.warehouse_id.clone(),
+        warehouse_name: row.warehouse_name.clone(),
+        qty: quantity::to_display(qty, row.qty_precision),
+        expires_at,
+        days_left: (expires_at - today_start).div_euclid(DAY_SECONDS),
+        expired: expires_at < today_start,
+      });
+    }
+  }
+  items.sort_by(|a, b| a.expires_at.cmp(&b.expires_at).then(a.item_code.cmp(&b.item_code)));
+  let expired_count = items.iter().filter(|item| item.expired).count() as i64;
+  Ok(ExpiringStockReport {
+    horizon_days,
+    generated_at: Utc::now().timestamp(),
+    expired_count,
+    expiring_count: items.len() as i64 - expired_count,
+    items,
+  })
+}
+
+/// 已过期及临期（按系统设置天数）的库存批次数，供首页统计
+pub async fn count_expiring_stock(pool: &SqlitePool) -> Result<(i64, i64), AppError> {
+  let report = get_expiring_report(pool, None, None, None).await?;
+  Ok((report.expired_count, report.expiring_count))
+}
@@ -0,0 +1,103 @@
+// 筛选下拉选项：按实际数据返回某字段的去重取值与出现次数，替代前端写死的选项列表
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::repo::filter_option_repo::{self, FilterOptionRow};
+
+/// 缓存有效期：下拉选项允许短暂滞后，避免每次打开下拉都扫描
+const FILTER_OPTIONS_CACHE_TTL: Duration = Duration::from_secs(60);
+/// 每个字段最多返回的取值数
+const MAX_OPTIONS: i64 = 200;
+
+/// 支持的实体与字段（白名单，对应的表与列）
+const FIELDS: &[(&str, &str, &str, &str)] = &[
+  ("item", "uom", "item", "uom"),
+  ("item", "model", "item", "model"),
+  ("item", "spec", "item", "spec"),
+  ("item", "currency", "item", "currency"),
+  ("item", "status", "item", "status"),
+  ("txn", "type", "txn", "type"),
+  ("operator", "role", "operator", "role"),
+  ("audit", "action", "audit_log", "action"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterOptionsResult {
+  pub entity: String,
+  pub field: String,
+  pub options: Vec<FilterOptionRow>,
+}
+
+/// 筛选项短时缓存，按实体、字段、关键字与仓库范围区分
+#[derive(Default)]
+pub struct FilterOptionsCache {
+  entries: Mutex<HashMap<String, (Instant, Vec<FilterOptionRow>)>>,
+}
+
+impl FilterOptionsCache {
+  fn get(&self, key: &str) -> Option<Vec<FilterOptionRow>> {
+    let entries = self.entries.lock();
+    entries
+      .get(key)
+      .filter(|(cached_at, _)| cached_at.elapsed() < FILTER_OPTIONS_CACHE_TTL)
+      .map(|(_, value)| value.clone())
+  }
+
+  fn put(&self, key: String, value: Vec<FilterOptionRow>) {
+    let mut entries = self.entries.lock();
+    entries.retain(|_, (cached_at, _)| cached_at.elapsed() < FILTER_OPTIONS_CACHE_TTL);
+    entries.insert(key, (Instant::now(), value));
+  }
+}
+
+/// 人员角色与审计动作仅管理员可查询
+pub fn requires_admin(entity: &str) -> bool {
+  matches!(entity, "operator" | "audit")
+}
+
+pub async fn get_filter_options(
+  pool: &SqlitePool,
+  cache: &FilterOptionsCache,
+  entity: &str,
+  field: &str,
+  keyword: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<FilterOptionsResult, AppError> {
+  let (_, _, table, column) = FIELDS
+    .iter()
+    .find(|(e, f, _, _)| *e == entity && *f == field)
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, format!("不支持的筛选字段：{}.{}", entity, field)))?;
+  let keyword = keyword.map(str::trim).filter(|value| !value.is_empty());
+
+  let key = format!(
+    "{}:{}:{}:{:?}",
+    entity,
+    field,
+    keyword.unwrap_or_default(),
+    allowed_warehouse_ids
+  );
+  let options = match cache.get(&key) {
+    Some(options) => options,
+    None => {
+      // 仅流水按仓库范围统计，其余字段不涉及仓库
+      let options = if *table == "txn" {
+        filter_option_repo::list_txn_types(pool, keyword, MAX_OPTIONS, allowed_warehouse_ids).await?
+      } else {
+        filter_option_repo::list_distinct_values(pool, table, column, keyword, MAX_OPTIONS).await?
+      };
+      cache.put(key, options.clone());
+      options
+    }
+  };
+
+  Ok(FilterOptionsResult {
+    entity: entity.to_string(),
+    field: field.to_string(),
+    options,
+  })
+}
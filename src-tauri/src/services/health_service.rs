@@ -0,0 +1,351 @@
+use chrono::Utc;
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::quantity;
+use crate::repo::health_repo::{self, StockLedgerDiffRow};
+use crate::repo::stock_repo;
+use crate::services::audit_service;
+
+/// 每项问题在报告中展示的样例条数
+const SAMPLE_LIMIT: i64 = 20;
+/// 报告中返回的库存不一致明细上限
+const STOCK_DIFF_LIMIT: usize = 200;
+
+/// 可执行的修复项
+pub const REPAIR_DELETE_ORPHAN_STOCK: &str = "delete_orphan_stock";
+pub const REPAIR_REINDEX: &str = "reindex";
+pub const REPAIR_RECALCULATE_STOCK: &str = "recalculate_stock";
+
+#[derive(Debug, serde::Serialize)]
+pub struct DbHealthIssue {
+  pub kind: String,
+  pub title: String,
+  pub count: i64,
+  pub samples: Vec<String>,
+  // 可通过 repair_database 执行的修复项，None 表示需要人工处理
+  pub repair: Option<String>,
+  pub hint: String,
+}
+
+/// 库存表与流水推算数量不一致的明细（数量为展示值）
+#[derive(Debug, serde::Serialize)]
+pub struct StockLedgerDiff {
+  pub item_id: String,
+  pub item_code: Option<String>,
+  pub slot_id: String,
+  pub slot_code: Option<String>,
+  pub stock_qty: f64,
+  pub ledger_qty: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DbHealthReport {
+  pub ok: bool,
+  pub checked_at: i64,
+  // PRAGMA integrity_check 的原始输出
+  pub integrity: Vec<String>,
+  pub issues: Vec<DbHealthIssue>,
+  pub stock_diffs: Vec<StockLedgerDiff>,
+}
+
+/// 单条常用查询的执行计划自检结果
+#[derive(Debug, serde::Serialize)]
+pub struct QueryPlanCheck {
+  pub name: String,
+  pub title: String,
+  pub ok: bool,
+  // EXPLAIN QUERY PLAN 的各步骤
+  pub plan: Vec<String>,
+  pub expected_indexes: Vec<String>,
+  // 计划中未出现的期望索引
+  pub missing_indexes: Vec<String>,
+  // 不经索引的整表扫描步骤
+  pub full_scans: Vec<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct QueryPlanReport {
+  pub ok: bool,
+  pub checked_at: i64,
+  pub items: Vec<QueryPlanCheck>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct StockRecalcResult {
+  pub applied: bool,
+  // 库存与流水推算不一致的物品/库位数
+  pub total: i64,
+  // 已按流水修正的条数（仅 applied 时）
+  pub corrected: i64,
+  // 流水推算为负数或物品/库位已不存在、无法修正的条数
+  pub skipped: i64,
+  pub diffs: Vec<StockLedgerDiff>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct DbRepairResult {
+  pub repair: String,
+  pub affected: i64,
+}
+
+/// 数据库健康检查：PRAGMA integrity_check、外键检查、引用完整性与库存/流水一致性，只读不修改数据
+pub async fn verify_database(pool: &SqlitePool) -> Result<DbHealthReport, AppError> {
+  let mut issues = Vec::new();
+
+  let integrity = health_repo::integrity_check(pool).await?;
+  let integrity_ok = integrity.len() == 1 && integrity[0] == "ok";
+  if !integrity_ok {
+    issues.push(DbHealthIssue {
+      kind: "integrity".to_string(),
+      title: "数据库文件完整性检查未通过".to_string(),
+      count: integrity.len() as i64,
+      samples: integrity.iter().take(SAMPLE_LIMIT as usize).cloned().collect(),
+      repair: Some(REPAIR_REINDEX.to_string()),
+      hint: "索引损坏可通过重建索引修复；若仍未通过，请从备份恢复".to_string(),
+    });
+  }
+
+  let violations = health_repo::foreign_key_violations(pool).await?;
+  if !violations.is_empty() {
+    issues.push(DbHealthIssue {
+      kind: "foreign_key".to_string(),
+      title: "存在引用不存在记录的数据".to_string(),
+      count: violations.len() as i64,
+      samples: violations
+        .iter()
+        .take(SAMPLE_LIMIT as usize)
+        .map(|row| match row.rowid {
+          Some(rowid) => format!("{} #{} → {}", row.table, rowid, row.parent),
+          None => format!("{} → {}", row.table, row.parent),
+        })
+        .collect(),
+      repair: None,
+      hint: "请结合下方各项明细处理，或从备份恢复".to_string(),
+    });
+  }
+
+  for check in health_repo::list_orphan_checks(pool, SAMPLE_LIMIT).await? {
+    if check.total == 0 {
+      continue;
+    }
+    let (title, repair, hint) = describe_orphan(check.kind);
+    issues.push(DbHealthIssue {
+      kind: check.kind.to_string(),
+      title: title.to_string(),
+      count: check.total,
+      samples: check.samples,
+      repair: repair.map(|value| value.to_string()),
+      hint: hint.to_string(),
+    });
+  }
+
+  let diffs = health_repo::list_stock_ledger_diffs(pool).await?;
+  if !diffs.is_empty() {
+    issues.push(DbHealthIssue {
+      kind: "stock_ledger_mismatch".to_string(),
+      title: "库存与流水推算数量不一致".to_string(),
+      count: diffs.len() as i64,
+      samples: diffs
+        .iter()
+        .take(SAMPLE_LIMIT as usize)
+        .map(|row| {
+          format!(
+            "{} @ {}：库存 {}，流水 {}",
+            row.item_code.as_deref().unwrap_or(&row.item_id),
+            row.slot_code.as_deref().unwrap_or(&row.slot_id),
+            quantity::format(row.stock_qty, row.qty_precision),
+            quantity::format(row.ledger_qty, row.qty_precision)
+          )
+        })
+        .collect(),
+      repair: Some(REPAIR_RECALCULATE_STOCK.to_string()),
+      hint: "可按流水重算库存；若流水本身有误，请先冲正或盘点".to_string(),
+    });
+  }
+  let stock_diffs = diffs.into_iter().take(STOCK_DIFF_LIMIT).map(to_stock_diff).collect();
+
+  Ok(DbHealthReport {
+    ok: issues.is_empty(),
+    checked_at: Utc::now().timestamp(),
+    integrity,
+    issues,
+    stock_diffs,
+  })
+}
+
+/// 常用查询执行计划自检：对流水、库存、审计的常用筛选执行 EXPLAIN QUERY PLAN，
+/// 确认命中期望的索引且没有整表扫描，用于升级或重建索引后排查列表变慢
+pub async fn check_query_plans(pool: &SqlitePool) -> Result<QueryPlanReport, AppError> {
+  let mut items = Vec::with_capacity(health_repo::QUERY_PLAN_CHECKS.len());
+  for (name, expected, sql) in health_repo::QUERY_PLAN_CHECKS {
+    let plan = health_repo::explain_query_plan(pool, sql).await?;
+    let missing_indexes: Vec<String> = expected
+      .iter()
+      .filter(|index| !plan.iter().any(|step| step.contains(&format!("INDEX {}", index))))
+      .map(|index| index.to_string())
+      .collect();
+    // 按索引顺序遍历（SCAN x USING INDEX）不算整表扫描
+    let full_scans: Vec<String> = plan
+      .iter()
+      .filter(|step| step.starts_with("SCAN ") && !step.contains(" USING "))
+      .cloned()
+      .collect();
+    items.push(QueryPlanCheck {
+      name: name.to_string(),
+      title: describe_query_plan(name).to_string(),
+      ok: missing_indexes.is_empty() && full_scans.is_empty(),
+      plan,
+      expected_indexes: expected.iter().map(|index| index.to_string()).collect(),
+      missing_indexes,
+      full_scans,
+    });
+  }
+  Ok(QueryPlanReport {
+    ok: items.iter().all(|item| item.ok),
+    checked_at: Utc::now().timestamp(),
+    items,
+  })
+}
+
+/// 按流水重算库存：在同一事务内回放流水（可按物品、库位限定）并与库存表对比；
+/// apply 时将库存修正为流水推算值，每条修正单独记一条审计，否则仅返回差异
+pub async fn recalculate_stock(
+  pool: &SqlitePool,
+  item_id: Option<String>,
+  slot_id: Option<String>,
+  apply: bool,
+  actor_operator_id: &str,
+) -> Result<StockRecalcResult, AppError> {
+  let item_id = item_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let slot_id = slot_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+  let mut tx = pool.begin().await?;
+  let rows = health_repo::list_stock_ledger_diffs_tx(&mut tx, item_id.as_deref(), slot_id.as_deref()).await?;
+  let (fixable, skipped): (Vec<_>, Vec<_>) = rows
+    .into_iter()
+    .partition(|row| row.ledger_qty >= 0 && row.item_code.is_some() && row.slot_code.is_some());
+
+  let mut corrected = 0;
+  if apply {
+    let now = Utc::now().timestamp();
+    for row in &fixable {
+      stock_repo::upsert_stock_tx(&mut tx, &row.item_id, &row.slot_id, row.ledger_qty, now).await?;
+    }
+    tx.commit().await?;
+    for row in &fixable {
+      let audit_request = json!({
+        "item_id": row.item_id.clone(),
+        "item_code": row.item_code.clone(),
+        "slot_id": row.slot_id.clone(),
+        "slot_code": row.slot_code.clone(),
+        "stock_qty": quantity::format(row.stock_qty, row.qty_precision),
+        "ledger_qty": quantity::format(row.ledger_qty, row.qty_precision),
+        "actor_operator_id": actor_operator_id
+      });
+      let _ = audit_service::write_audit(
+        pool,
+        AuditAction::StockCorrect,
+        Some(actor_operator_id.to_string()),
+        Some("stock".to_string()),
+        row.item_code.clone(),
+        Some(audit_request),
+        Ok(()),
+      )
+      .await;
+    }
+    corrected = fixable.len() as i64;
+  } else {
+    tx.rollback().await?;
+  }
+
+  let total = (fixable.len() + skipped.len()) as i64;
+  let skipped_count = skipped.len() as i64;
+  let diffs = fixable
+    .into_iter()
+    .chain(skipped)
+    .take(STOCK_DIFF_LIMIT)
+    .map(to_stock_diff)
+    .collect();
+  Ok(StockRecalcResult {
+    applied: apply,
+    total,
+    corrected,
+    skipped: skipped_count,
+    diffs,
+  })
+}
+
+/// 执行健康检查报告中给出的修复项
+pub async fn repair_database(
+  pool: &SqlitePool,
+  repair: &str,
+  actor_operator_id: &str,
+) -> Result<DbRepairResult, AppError> {
+  let affected = match repair {
+    REPAIR_DELETE_ORPHAN_STOCK => health_repo::delete_orphan_stock(pool).await? as i64,
+    REPAIR_RECALCULATE_STOCK => {
+      recalculate_stock(pool, None, None, true, actor_operator_id)
+        .await?
+        .corrected
+    }
+    REPAIR_REINDEX => {
+      health_repo::reindex(pool).await?;
+      0
+    }
+    _ => return Err(AppError::new(ErrorCode::ValidationError, "不支持的修复项")),
+  };
+  Ok(DbRepairResult {
+    repair: repair.to_string(),
+    affected,
+  })
+}
+
+fn to_stock_diff(row: StockLedgerDiffRow) -> StockLedgerDiff {
+  StockLedgerDiff {
+    stock_qty: quantity::to_display(row.stock_qty, row.qty_precision),
+    ledger_qty: quantity::to_display(row.ledger_qty, row.qty_precision),
+    item_id: row.item_id,
+    item_code: row.item_code,
+    slot_id: row.slot_id,
+    slot_code: row.slot_code,
+  }
+}
+
+/// 引用完整性检查项的说明：（标题, 修复项, 处理建议）
+fn describe_orphan(kind: &str) -> (&'static str, Option<&'static str>, &'static str) {
+  match kind {
+    "txn_missing_item" => ("流水引用的物品不存在", None, "流水为历史记录不自动删除，请从备份恢复物品数据"),
+    "txn_missing_slot" => ("流水引用的库位不存在", None, "流水为历史记录不自动删除，请从备份恢复库位数据"),
+    "reversal_missing_ref" => ("冲正流水的原流水不存在", None, "请从备份恢复原流水"),
+    "stock_missing_item" => (
+      "库存引用的物品不存在",
+      Some(REPAIR_DELETE_ORPHAN_STOCK),
+      "可删除这些无主库存行",
+    ),
+    "stock_missing_slot" => (
+      "库存引用的库位不存在",
+      Some(REPAIR_DELETE_ORPHAN_STOCK),
+      "可删除这些无主库存行",
+    ),
+    "stock_negative" => ("库存数量为负数", None, "请通过盘点修正库存"),
+    "slot_missing_rack" => ("库位所属货架不存在", None, "请从备份恢复货架数据"),
+    "loan_missing_txn" => ("借用记录的借出流水不存在", None, "请从备份恢复流水数据"),
+    _ => ("未知问题", None, "请联系管理员处理"),
+  }
+}
+
+fn describe_query_plan(name: &str) -> &'static str {
+  match name {
+    "txn_by_time" => "流水按发生时间筛选",
+    "txn_by_item" => "流水按物品筛选",
+    "txn_by_operator" => "流水按记录人筛选",
+    "txn_by_slot" => "流水按库位筛选",
+    "stock_by_slot" => "库存按库位查询",
+    "audit_by_action" => "审计日志按动作筛选",
+    "audit_by_time" => "审计日志按时间筛选",
+    _ => "未知查询",
+  }
+}
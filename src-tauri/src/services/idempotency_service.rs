@@ -0,0 +1,70 @@
+// 流水提交幂等：同一幂等键在有效期内重复提交时返回首次生成的流水号，不再重复记账
+use chrono::Utc;
+use sqlx::{Sqlite, SqlitePool, Transaction};
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::repo::idempotency_repo;
+
+/// 幂等键有效期：覆盖双击、网络重试等短时间内的重复提交
+pub const IDEMPOTENCY_WINDOW_SECS: i64 = 24 * 60 * 60;
+
+const MAX_KEY_LEN: usize = 128;
+
+/// 规范化幂等键，空白视为未提供
+pub fn normalize_key(key: Option<&str>) -> Result<Option<String>, AppError> {
+  let key = match key.map(str::trim).filter(|value| !value.is_empty()) {
+    Some(key) => key,
+    None => return Ok(None),
+  };
+  if key.len() > MAX_KEY_LEN {
+    return Err(AppError::new(ErrorCode::ValidationError, "幂等键过长"));
+  }
+  Ok(Some(key.to_string()))
+}
+
+/// 查找有效期内已提交的流水号；同一键用于其他动作时返回冲突
+pub async fn find_replay(pool: &SqlitePool, key: Option<&str>, action: &str) -> Result<Option<String>, AppError> {
+  let key = match key {
+    Some(key) => key,
+    None => return Ok(None),
+  };
+  let row = match idempotency_repo::get_key(pool, key).await? {
+    Some(row) => row,
+    None => return Ok(None),
+  };
+  if row.created_at < Utc::now().timestamp() - IDEMPOTENCY_WINDOW_SECS {
+    return Ok(None);
+  }
+  if row.action != action {
+    return Err(AppError::new(ErrorCode::Conflict, "幂等键已用于其他操作"));
+  }
+  Ok(Some(row.txn_no))
+}
+
+/// 随流水写入的幂等键：键与提交动作
+#[derive(Debug, Clone, Copy)]
+pub struct IdempotencyKey<'a> {
+  pub key: &'a str,
+  pub action: &'a str,
+}
+
+impl<'a> IdempotencyKey<'a> {
+  /// 未提供幂等键时返回 None
+  pub fn new(key: Option<&'a str>, action: &'a str) -> Option<Self> {
+    key.map(|key| Self { key, action })
+  }
+}
+
+/// 在写入流水的同一事务内记录流水号并顺带清理过期的键，流水与幂等键一同提交或回滚
+pub async fn remember_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  key: Option<IdempotencyKey<'_>>,
+  txn_no: &str,
+) -> Result<(), AppError> {
+  let Some(key) = key else {
+    return Ok(());
+  };
+  let now = Utc::now().timestamp();
+  idempotency_repo::delete_before_tx(tx, now - IDEMPOTENCY_WINDOW_SECS).await?;
+  idempotency_repo::insert_key_tx(tx, key.key, key.action, txn_no, now).await
+}
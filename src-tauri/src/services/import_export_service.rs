@@ -1,20 +1,38 @@
-use chrono::Utc;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Local, TimeZone, Utc};
 use csv::{ReaderBuilder, WriterBuilder};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::repo::{item_repo, operator_repo};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::repo::{
+  item_alias_repo, item_attribute_repo, item_repo, operator_repo, photo_repo, rack_repo, stock_threshold_repo, warehouse_repo,
+};
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 use crate::repo::meta_repo;
-use crate::services::txn_service;
+use crate::services::item_attribute_service::{self, ItemAttributes};
+use crate::services::{
+  approval_service, count_service, item_alias_service, item_service, rack_service, reason_code_service, system_service,
+  txn_service, warehouse_service,
+};
 
 #[derive(Debug, serde::Serialize)]
 pub struct ExportResult {
   pub file_path: String,
 }
 
-pub async fn export_items(pool: &SqlitePool) -> Result<ExportResult, AppError> {
+/// 导出物品目录快照：筛选与排序同物品列表（keyword、sort_by/sort_dir），
+/// 固定列、别名列与属性列之后追加库存合计、图片数量、创建日期以及各仓库的库存下限/上限；
+/// 追加列不在导入识别范围内，导出文件可直接回导
+pub async fn export_items(
+  pool: &SqlitePool,
+  keyword: Option<String>,
+  sort_by: Option<String>,
+  sort_dir: Option<String>,
+) -> Result<ExportResult, AppError> {
   // 移动端使用临时目录，桌面端使用配置的导出目录
   #[cfg(any(target_os = "android", target_os = "ios"))]
   let export_dir = std::env::temp_dir();
@@ -23,7 +41,7 @@ pub async fn export_items(pool: &SqlitePool) -> Result<ExportResult, AppError> {
   let export_dir = {
     let storage_root = meta_repo::get_meta_value(pool, "storage_root")
       .await?
-      .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+      .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
     // 优先使用可配置的 exports_dir，否则回退到 storage_root/exports
     match meta_repo::get_meta_value(pool, "exports_dir").await? {
       Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
@@ -32,29 +50,100 @@ pub async fn export_items(pool: &SqlitePool) -> Result<ExportResult, AppError> {
   };
   
   std::fs::create_dir_all(&export_dir)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出目录失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
 
   let now = Utc::now().timestamp();
   let file_path = export_dir.join(format!("items_export_{}.csv", now));
   let mut writer = WriterBuilder::new()
     .has_headers(true)
     .from_path(&file_path)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
 
+  // 启用的自定义属性按属性名称追加在别名列之后
+  let attribute_defs = item_attribute_repo::list_defs(pool, true).await?;
+  // 阈值按仓库展开为“下限@仓库编码 / 上限@仓库编码”两列，只列出设置过阈值的仓库
+  let mut threshold_warehouses: Vec<String> = Vec::new();
+  let mut thresholds: HashMap<(String, String), (f64, Option<f64>)> = HashMap::new();
+  for row in stock_threshold_repo::list_thresholds(pool, None, None, None).await? {
+    if !threshold_warehouses.contains(&row.warehouse_code) {
+      threshold_warehouses.push(row.warehouse_code.clone());
+    }
+    thresholds.insert((row.item_id, row.warehouse_code), (row.min_qty, row.max_qty));
+  }
+  threshold_warehouses.sort();
+  let threshold_headers: Vec<String> = threshold_warehouses
+    .iter()
+    .flat_map(|code| [format!("min_qty@{}", code), format!("max_qty@{}", code)])
+    .collect();
   writer
-    .write_record([
-      "item_code",
-      "name",
-      "model",
-      "spec",
-      "uom",
-      "status",
-      "remark",
-    ])
-    .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
-
-  let items = item_repo::list_items_all(pool).await?;
+    .write_record(
+      [
+        "item_code",
+        "name",
+        "model",
+        "spec",
+        "uom",
+        "status",
+        "remark",
+        "qty_precision",
+        "alias_manufacturer",
+        "alias_internal",
+        "alias_ean",
+        "alias_supplier",
+        "alias_other",
+      ]
+      .into_iter()
+      .chain(attribute_defs.iter().map(|def| def.name.as_str()))
+      .chain(["total_qty", "photo_count", "created_at"])
+      .chain(threshold_headers.iter().map(String::as_str)),
+    )
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  // 别名按物品与类型分组，多个别名以分号分隔
+  let mut aliases: HashMap<(String, String), Vec<String>> = HashMap::new();
+  for alias in item_alias_repo::list_all_aliases(pool).await? {
+    aliases.entry((alias.item_id, alias.kind)).or_default().push(alias.code);
+  }
+  let mut attributes = item_attribute_service::values_by_item(pool, None).await?;
+  let photo_counts: HashMap<String, i64> = photo_repo::count_photos_by_data(pool, "item").await?.into_iter().collect();
+  let keyword = keyword.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let total = item_repo::count_items(pool, keyword.clone()).await?;
+  let mut items = Vec::new();
+  // 分页读取，沿用物品列表的查询与排序
+  let page_size = 500;
+  let mut page = 1;
+  while ((page - 1) * page_size) < total {
+    let rows = item_repo::list_items(pool, keyword.clone(), page, page_size, sort_by.as_deref(), sort_dir.as_deref()).await?;
+    if rows.is_empty() {
+      break;
+    }
+    items.extend(rows);
+    page += 1;
+  }
   for item in items {
+    let item_attributes = attributes.remove(&item.id).unwrap_or_default();
+    let attribute_cells: Vec<String> = attribute_defs
+      .iter()
+      .map(|def| item_attributes.get(&def.id).cloned().unwrap_or_default())
+      .collect();
+    let alias_cells = item_alias_service::ALIAS_KINDS.map(|kind| {
+      aliases
+        .get(&(item.id.clone(), kind.to_string()))
+        .map(|codes| codes.join(";"))
+        .unwrap_or_default()
+    });
+    let snapshot_cells = [
+      item.stock_qty.to_string(),
+      photo_counts.get(&item.id).copied().unwrap_or(0).to_string(),
+      format_day(item.created_at),
+    ];
+    let threshold_cells: Vec<String> = threshold_warehouses
+      .iter()
+      .flat_map(|code| match thresholds.get(&(item.id.clone(), code.clone())) {
+        Some((min_qty, max_qty)) => [min_qty.to_string(), max_qty.map(|value| value.to_string()).unwrap_or_default()],
+        None => [String::new(), String::new()],
+      })
+      .collect();
     writer
       .write_record([
         item.item_code,
@@ -64,13 +153,19 @@ pub async fn export_items(pool: &SqlitePool) -> Result<ExportResult, AppError> {
         item.uom.unwrap_or_default(),
         item.status,
         item.remark.unwrap_or_default(),
-      ])
-      .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+        item.qty_precision.to_string(),
+      ]
+      .into_iter()
+      .chain(alias_cells)
+      .chain(attribute_cells)
+      .chain(snapshot_cells)
+      .chain(threshold_cells))
+      .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
   }
 
   writer
     .flush()
-    .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
 
   Ok(ExportResult {
     file_path: file_path.to_string_lossy().to_string(),
@@ -83,10 +178,36 @@ pub async fn import_items(pool: &SqlitePool, file_path: &str) -> Result<(), AppE
   let mut reader = ReaderBuilder::new()
     .has_headers(true)
     .from_path(file_path)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "读取导入文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+
+  // 固定列之后按表头识别别名列（alias_<类型>、alias/别名 等），单元格内多个别名以分号分隔
+  let headers = reader
+    .headers()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?
+    .clone();
+  let alias_columns: Vec<(usize, &str)> = headers
+    .iter()
+    .enumerate()
+    .skip(ITEM_IMPORT_FIXED_COLUMNS)
+    .filter_map(|(index, header)| alias_kind_for_header(header).map(|kind| (index, kind)))
+    .collect();
+  // 其余表头与启用的自定义属性名称一致时作为属性列
+  let attribute_defs = item_attribute_repo::list_defs(pool, true).await?;
+  let attribute_columns: Vec<(usize, String)> = headers
+    .iter()
+    .enumerate()
+    .skip(ITEM_IMPORT_FIXED_COLUMNS)
+    .filter(|(_, header)| alias_kind_for_header(header).is_none())
+    .filter_map(|(index, header)| {
+      attribute_defs
+        .iter()
+        .find(|def| def.name == header.trim())
+        .map(|def| (index, def.id.clone()))
+    })
+    .collect();
 
   for record in reader.records() {
-    let record = record.map_err(|_| AppError::new(ErrorCode::IoError, "读取导入文件失败"))?;
+    let record = record.map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
     let item_code = record.get(0).unwrap_or("").trim().to_string();
     let name = record.get(1).unwrap_or("").trim().to_string();
     let model = empty_to_none(record.get(2));
@@ -94,6 +215,9 @@ pub async fn import_items(pool: &SqlitePool, file_path: &str) -> Result<(), AppE
     let uom = empty_to_none(record.get(4));
     let status = record.get(5).unwrap_or("active").trim().to_string();
     let remark = empty_to_none(record.get(6));
+    // 数量小数位数列为后续新增，旧文件缺失时按整数处理
+    let qty_precision = parse_i64_optional(record.get(7))?.unwrap_or(0);
+    quantity::validate_precision(qty_precision)?;
 
     if item_code.is_empty() || name.is_empty() {
       return Err(AppError::new(ErrorCode::ValidationError, "物品编码或名称不能为空"));
@@ -102,10 +226,31 @@ pub async fn import_items(pool: &SqlitePool, file_path: &str) -> Result<(), AppE
       return Err(AppError::new(ErrorCode::ValidationError, "物品状态非法"));
     }
 
-    if item_repo::count_by_item_code(pool, &item_code).await? > 0 {
+    // 已存在（含按别名、旧编码匹配到）的物品不再新建，仅补充别名与非空的属性值
+    if let Some(item) = item_repo::get_item_by_code(pool, &item_code).await? {
+      import_item_aliases(pool, &item.id, &record, &alias_columns).await?;
+      let attributes = import_attribute_cells(&record, &attribute_columns, true);
+      let values = item_attribute_service::normalize_values(pool, &attributes, false).await?;
+      item_attribute_service::save_values(pool, &item.id, &values).await?;
       continue;
     }
 
+    let filled = |value: &Option<String>| value.is_some();
+    item_attribute_service::ensure_required_item_fields(
+      pool,
+      &[
+        ("model", filled(&model)),
+        ("spec", filled(&spec)),
+        ("uom", filled(&uom)),
+        ("unit_cost", false),
+        ("currency", false),
+        ("remark", filled(&remark)),
+      ],
+    )
+    .await?;
+    let attributes = import_attribute_cells(&record, &attribute_columns, false);
+    let attribute_values = item_attribute_service::normalize_values(pool, &attributes, true).await?;
+
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().timestamp();
     item_repo::insert_item(
@@ -116,30 +261,88 @@ pub async fn import_items(pool: &SqlitePool, file_path: &str) -> Result<(), AppE
       model,
       spec,
       uom,
+      None,
+      None,
+      qty_precision,
       &status,
       remark,
       now,
     )
     .await?;
+    import_item_aliases(pool, &id, &record, &alias_columns).await?;
+    item_attribute_service::save_values(pool, &id, &attribute_values).await?;
   }
 
   Ok(())
 }
 
+/// 物品导入的固定列数（item_code 至 qty_precision），其后为可选的别名列
+const ITEM_IMPORT_FIXED_COLUMNS: usize = 8;
+
+fn alias_kind_for_header(header: &str) -> Option<&'static str> {
+  let header = header.trim().to_ascii_lowercase();
+  if let Some(kind) = header.strip_prefix("alias_") {
+    return item_alias_service::ALIAS_KINDS.iter().copied().find(|value| *value == kind);
+  }
+  match header.as_str() {
+    "alias" | "aliases" | "别名" => Some("other"),
+    "厂家编码" => Some("manufacturer"),
+    "内部编码" => Some("internal"),
+    "ean" | "条码" => Some("ean"),
+    "供应商编码" => Some("supplier"),
+    _ => None,
+  }
+}
+
+async fn import_item_aliases(
+  pool: &SqlitePool,
+  item_id: &str,
+  record: &csv::StringRecord,
+  alias_columns: &[(usize, &str)],
+) -> Result<(), AppError> {
+  for (index, kind) in alias_columns {
+    let cell = record.get(*index).unwrap_or("");
+    for code in cell.split([';', '；']).map(str::trim).filter(|code| !code.is_empty()) {
+      item_alias_service::import_alias(pool, item_id, code, kind).await?;
+    }
+  }
+  Ok(())
+}
+
+/// 读取属性列；skip_empty 为 true 时忽略空单元格（已存在的物品不清除原有取值）
+fn import_attribute_cells(
+  record: &csv::StringRecord,
+  attribute_columns: &[(usize, String)],
+  skip_empty: bool,
+) -> ItemAttributes {
+  attribute_columns
+    .iter()
+    .map(|(index, attr_id)| (attr_id.clone(), record.get(*index).unwrap_or("").trim().to_string()))
+    .filter(|(_, value)| !(skip_empty && value.is_empty()))
+    .collect()
+}
+
 pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppError> {
   let mut reader = ReaderBuilder::new()
     .has_headers(true)
     .from_path(file_path)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "读取导入文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
 
   for record in reader.records() {
-    let record = record.map_err(|_| AppError::new(ErrorCode::IoError, "读取导入文件失败"))?;
+    let record = record.map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
     let txn_type = record.get(0).unwrap_or("").trim();
     let item_code = record.get(1).unwrap_or("").trim();
     let from_slot_code = record.get(2).unwrap_or("").trim();
     let to_slot_code = record.get(3).unwrap_or("").trim();
-    let qty = parse_i64_optional(record.get(4))?;
-    let actual_qty = parse_i64_optional(record.get(5))?.unwrap_or(0);
+    // 数量按物品精度换算为存储值，允许小数
+    let qty = match parse_f64_optional(record.get(4))? {
+      Some(qty) => Some(item_service::qty_to_base(pool, item_code, qty).await?),
+      None => None,
+    };
+    let actual_qty = match parse_f64_optional(record.get(5))? {
+      Some(actual_qty) => item_service::qty_to_base(pool, item_code, actual_qty).await?,
+      None => 0,
+    };
     let occurred_at = parse_i64(record.get(6))?;
     let operator_username = record.get(7).unwrap_or("").trim();
     let operator_id = if operator_username.is_empty() {
@@ -152,10 +355,12 @@ pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppEr
     };
     let note = empty_to_none(record.get(8));
     let ref_txn_no = record.get(9).unwrap_or("").trim();
+    // 第 11 列为原因编码（可选），用于盘点差异调整与冲正
+    let reason_code = empty_to_none(record.get(10));
 
     match txn_type {
       "IN" => {
-        let qty = qty.ok_or_else(|| AppError::new(ErrorCode::ValidationError, "数量不能为空"))?;
+        let qty = qty.ok_or_else(|| AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyRequired))?;
         txn_service::create_inbound(
           pool,
           item_code,
@@ -164,11 +369,16 @@ pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppEr
           occurred_at,
           &operator_id,
           note,
+          None,
+          None,
+          None,
+          &[],
+          None,
         )
         .await?;
       }
       "OUT" => {
-        let qty = qty.ok_or_else(|| AppError::new(ErrorCode::ValidationError, "数量不能为空"))?;
+        let qty = qty.ok_or_else(|| AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyRequired))?;
         txn_service::create_outbound(
           pool,
           item_code,
@@ -177,11 +387,15 @@ pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppEr
           occurred_at,
           &operator_id,
           note,
+          None,
+          false,
+          &[],
+          None,
         )
         .await?;
       }
       "MOVE" => {
-        let qty = qty.ok_or_else(|| AppError::new(ErrorCode::ValidationError, "数量不能为空"))?;
+        let qty = qty.ok_or_else(|| AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyRequired))?;
         txn_service::create_move(
           pool,
           item_code,
@@ -191,10 +405,18 @@ pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppEr
           occurred_at,
           &operator_id,
           note,
+          None,
+          None,
         )
         .await?;
       }
       "COUNT" => {
+        if approval_service::count_requires_approval(pool, item_code, from_slot_code, actual_qty).await? {
+          return Err(AppError::new(
+            ErrorCode::ValidationError,
+            "盘点差异超过审批阈值，请单独提交审批后录入",
+          ));
+        }
         txn_service::create_count(
           pool,
           item_code,
@@ -203,16 +425,23 @@ pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppEr
           occurred_at,
           &operator_id,
           note,
+          None,
+          reason_code_service::resolve_reason_by_code(pool, "ADJUST", reason_code.as_deref()).await?,
+          None,
         )
         .await?;
       }
       "REVERSAL" => {
+        if approval_service::reversal_requires_approval(pool).await? {
+          return Err(AppError::new(ErrorCode::ValidationError, "冲正需经审批，不能通过导入执行"));
+        }
         txn_service::reverse_txn(
           pool,
           ref_txn_no,
           occurred_at,
           &operator_id,
           note,
+          reason_code_service::resolve_reason_by_code(pool, "REVERSAL", reason_code.as_deref()).await?,
         )
         .await?;
       }
@@ -225,6 +454,288 @@ pub async fn import_txns(pool: &SqlitePool, file_path: &str) -> Result<(), AppEr
   Ok(())
 }
 
+/// 仓库结构导入结果
+#[derive(Debug, serde::Serialize)]
+pub struct StructureImportResult {
+  pub warehouses_created: i64,
+  pub racks_created: i64,
+  pub slots_created: i64,
+}
+
+/// 导入仓库结构，每行一个货架：
+/// warehouse_code, warehouse_name, rack_code, rack_name, level_count, slots_per_level, slot_codes, location；
+/// 仓库编码不存在时按该行名称新建固定仓库，rack_code 为空的行只建仓库；
+/// slot_codes 以分号分隔、按层优先顺序列出全部库位编码，留空则按标准规则生成。
+/// 先校验全部行再在同一事务内写入，任一行有误则整个文件不导入
+pub async fn import_structure(pool: &SqlitePool, file_path: &str) -> Result<StructureImportResult, AppError> {
+  let mut reader = ReaderBuilder::new()
+    .has_headers(true)
+    .from_path(file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+
+  let pad = system_service::slot_no_pad(pool).await?;
+  let now = Utc::now().timestamp();
+  // 仓库编码 → (仓库 id, 是否移动仓库)，含本次新建的仓库
+  let mut warehouses: HashMap<String, (String, bool)> = HashMap::new();
+  let mut new_warehouses: Vec<(String, String, String)> = Vec::new();
+  let mut racks: Vec<rack_repo::RackRow> = Vec::new();
+  let mut rack_keys: HashSet<(String, String)> = HashSet::new();
+  let mut slot_codes: HashSet<String> = HashSet::new();
+  let mut slots: Vec<rack_repo::SlotRow> = Vec::new();
+
+  for (index, record) in reader.records().enumerate() {
+    // 表头占第 1 行
+    let line = index + 2;
+    let record = record.map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+    let at_line = |err: AppError| AppError::new(err.code, format!("第 {} 行：{}", line, err.message));
+
+    let warehouse_code = warehouse_service::normalize_warehouse_code(record.get(0).unwrap_or("")).map_err(at_line)?;
+    let (warehouse_id, mobile) = match warehouses.get(&warehouse_code) {
+      Some(found) => found.clone(),
+      None => {
+        let resolved = match warehouse_repo::get_warehouse_by_code(pool, &warehouse_code).await? {
+          Some(existing) => (existing.id, existing.kind == "mobile"),
+          None => {
+            let name = record.get(1).unwrap_or("").trim();
+            if name.is_empty() {
+              return Err(at_line(AppError::new(ErrorCode::ValidationError, "新建仓库须填写仓库名称")));
+            }
+            let id = Uuid::new_v4().to_string();
+            new_warehouses.push((id.clone(), warehouse_code.clone(), name.to_string()));
+            (id, false)
+          }
+        };
+        warehouses.insert(warehouse_code.clone(), resolved.clone());
+        resolved
+      }
+    };
+
+    if record.get(2).unwrap_or("").trim().is_empty() {
+      continue;
+    }
+    if mobile {
+      return Err(at_line(AppError::new(ErrorCode::ValidationError, "移动仓库的货架按格口数自动维护，不能导入")));
+    }
+    let rack_code = rack_service::normalize_rack_code(record.get(2).unwrap_or("")).map_err(at_line)?;
+    let rack_name = empty_to_none(record.get(3)).unwrap_or_else(|| format!("R{}", rack_code));
+    let level_count = parse_i64(record.get(4)).map_err(at_line)?;
+    let slots_per_level = parse_i64(record.get(5)).map_err(at_line)?;
+    if !(1..=MAX_IMPORT_LEVELS).contains(&level_count) || !(1..=MAX_IMPORT_SLOTS_PER_LEVEL).contains(&slots_per_level) {
+      return Err(at_line(AppError::new(ErrorCode::ValidationError, "层数或格数非法")));
+    }
+    if !rack_keys.insert((warehouse_id.clone(), rack_code.clone()))
+      || rack_repo::get_rack_by_code_and_warehouse(pool, &rack_code, &warehouse_id)
+        .await?
+        .is_some()
+    {
+      return Err(at_line(AppError::new(ErrorCode::Conflict, "该仓库下的货架编号已存在")));
+    }
+
+    let listed: Vec<String> = record
+      .get(6)
+      .unwrap_or("")
+      .split([';', '；'])
+      .map(str::trim)
+      .filter(|code| !code.is_empty())
+      .map(str::to_string)
+      .collect();
+    let expected = level_count * slots_per_level;
+    if !listed.is_empty() && listed.len() as i64 != expected {
+      return Err(at_line(AppError::new(
+        ErrorCode::ValidationError,
+        format!("库位编码数量应为 {}（层数 × 每层格数）", expected),
+      )));
+    }
+
+    let rack_id = Uuid::new_v4().to_string();
+    let mut listed = listed.into_iter();
+    for level_no in 1..=level_count {
+      for slot_no in 1..=slots_per_level {
+        let code = listed
+          .next()
+          .unwrap_or_else(|| rack_service::slot_code(&warehouse_code, &rack_code, level_no, slot_no, pad));
+        if !slot_codes.insert(code.clone()) || rack_repo::get_slot_by_code(pool, &code).await?.is_some() {
+          return Err(at_line(AppError::new(ErrorCode::Conflict, format!("库位编码已存在：{}", code))));
+        }
+        slots.push(rack_repo::SlotRow {
+          id: Uuid::new_v4().to_string(),
+          rack_id: rack_id.clone(),
+          warehouse_id: Some(warehouse_id.clone()),
+          level_no,
+          slot_no,
+          code,
+          status: "active".to_string(),
+          created_at: now,
+        });
+      }
+    }
+    racks.push(rack_repo::RackRow {
+      id: rack_id,
+      code: rack_code,
+      name: rack_name,
+      warehouse_id: Some(warehouse_id),
+      location: empty_to_none(record.get(7)),
+      status: "active".to_string(),
+      level_count,
+      slots_per_level,
+      created_at: now,
+    });
+  }
+
+  if new_warehouses.is_empty() && racks.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "文件中没有需要新建的仓库或货架"));
+  }
+
+  let result = StructureImportResult {
+    warehouses_created: new_warehouses.len() as i64,
+    racks_created: racks.len() as i64,
+    slots_created: slots.len() as i64,
+  };
+  let mut tx = pool.begin().await?;
+  for (id, code, name) in &new_warehouses {
+    warehouse_repo::insert_warehouse_tx(&mut tx, id, code, name, "active", "fixed", None, now).await?;
+  }
+  for rack in &racks {
+    rack_repo::insert_rack_tx(&mut tx, rack).await?;
+  }
+  rack_repo::insert_slots_tx(&mut tx, slots).await?;
+  tx.commit().await?;
+  Ok(result)
+}
+
+/// 期初库存导入结果；对账报告逐行列出导入前后库位库存，便于与盘点表核对
+#[derive(Debug, serde::Serialize)]
+pub struct OpeningStockImportResult {
+  pub imported: i64,
+  pub item_count: i64,
+  pub slot_count: i64,
+  // 导入前库位已有库存的行数（仅强制导入时可能出现）
+  pub preexisting_count: i64,
+  pub report_file_path: String,
+}
+
+/// 导入期初库存，每行 item_code, slot_code, qty, unit_cost（可选，缺省取物品当前成本）；
+/// 同一物品与库位只能出现一次，先校验全部行再在同一事务内生成期初流水，并输出对账报告
+pub async fn import_opening_stock(
+  pool: &SqlitePool,
+  file_path: &str,
+  occurred_at: Option<i64>,
+  force: bool,
+  actor_operator_id: &str,
+) -> Result<OpeningStockImportResult, AppError> {
+  let mut reader = ReaderBuilder::new()
+    .has_headers(true)
+    .from_path(file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+
+  // (行号, 物品, 库位编码)，与 lines 一一对应，供对账报告使用
+  let mut sources: Vec<(usize, item_repo::ItemRow, String)> = Vec::new();
+  let mut lines: Vec<txn_service::OpeningLine> = Vec::new();
+  let mut keys: HashSet<(String, String)> = HashSet::new();
+  for (index, record) in reader.records().enumerate() {
+    // 表头占第 1 行
+    let line = index + 2;
+    let record = record.map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+    let at_line = |err: AppError| AppError::new(err.code, format!("第 {} 行：{}", line, err.message));
+
+    let item_code = record.get(0).unwrap_or("").trim();
+    let slot_code = record.get(1).unwrap_or("").trim();
+    if item_code.is_empty() && slot_code.is_empty() {
+      continue;
+    }
+    let item = item_repo::get_item_by_code(pool, item_code)
+      .await?
+      .ok_or_else(|| at_line(AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound)))?;
+    if item.status != "active" {
+      return Err(at_line(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ItemInactive)));
+    }
+    let slot = rack_repo::get_slot_by_code(pool, slot_code)
+      .await?
+      .ok_or_else(|| at_line(AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound)))?;
+    if slot.status != "active" {
+      return Err(at_line(AppError::keyed(ErrorCode::InactiveResource, MessageKey::SlotInactive)));
+    }
+    let qty = parse_f64_optional(record.get(2))
+      .map_err(at_line)?
+      .ok_or_else(|| at_line(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyRequired)))?;
+    let qty = quantity::to_base(qty, item.qty_precision).map_err(at_line)?;
+    if qty <= 0 {
+      return Err(at_line(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive)));
+    }
+    let unit_cost = match parse_f64_optional(record.get(3)).map_err(at_line)? {
+      Some(cost) => {
+        item_service::validate_unit_cost(Some(cost)).map_err(at_line)?;
+        Some(cost)
+      }
+      None => item.unit_cost,
+    };
+    if !keys.insert((item.id.clone(), slot.id.clone())) {
+      return Err(at_line(AppError::new(ErrorCode::Conflict, "同一物品与库位重复出现")));
+    }
+    lines.push(txn_service::OpeningLine {
+      item_id: item.id.clone(),
+      slot_id: slot.id,
+      qty,
+      unit_cost,
+    });
+    sources.push((line, item, slot.code));
+  }
+  if lines.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "文件中没有期初库存行"));
+  }
+
+  let occurred_at = occurred_at.unwrap_or_else(|| Utc::now().timestamp());
+  let posted = txn_service::create_opening_balances(pool, &lines, occurred_at, actor_operator_id, force).await?;
+
+  let now = Utc::now().timestamp();
+  let report_path = count_service::export_dir(pool)
+    .await?
+    .join(format!("opening_stock_report_{}.csv", now));
+  let write_error = |_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed);
+  let mut writer = WriterBuilder::new()
+    .has_headers(true)
+    .from_path(&report_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
+  writer
+    .write_record(["line", "item_code", "item_name", "slot_code", "opening_qty", "before_qty", "after_qty", "txn_no"])
+    .map_err(write_error)?;
+  for ((line, item, slot_code), (opening, result)) in sources.iter().zip(lines.iter().zip(posted.iter())) {
+    writer
+      .write_record([
+        line.to_string(),
+        item.item_code.clone(),
+        item.name.clone(),
+        slot_code.clone(),
+        quantity::format(opening.qty, item.qty_precision),
+        quantity::format(result.before_qty, item.qty_precision),
+        quantity::format(result.after_qty, item.qty_precision),
+        result.txn_no.clone(),
+      ])
+      .map_err(write_error)?;
+  }
+  writer.flush().map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  Ok(OpeningStockImportResult {
+    imported: posted.len() as i64,
+    item_count: lines.iter().map(|line| &line.item_id).collect::<HashSet<_>>().len() as i64,
+    slot_count: lines.iter().map(|line| &line.slot_id).collect::<HashSet<_>>().len() as i64,
+    preexisting_count: posted.iter().filter(|result| result.before_qty != 0).count() as i64,
+    report_file_path: report_path.to_string_lossy().to_string(),
+  })
+}
+
+/// 结构导入单个货架的层数与每层格数上限，防止误填生成海量库位
+const MAX_IMPORT_LEVELS: i64 = 50;
+const MAX_IMPORT_SLOTS_PER_LEVEL: i64 = 200;
+
+fn format_day(timestamp: i64) -> String {
+  Local
+    .timestamp_opt(timestamp, 0)
+    .single()
+    .map(|time| time.format("%Y-%m-%d").to_string())
+    .unwrap_or_default()
+}
+
 fn empty_to_none(value: Option<&str>) -> Option<String> {
   value.map(|v| v.trim()).filter(|v| !v.is_empty()).map(|v| v.to_string())
 }
@@ -239,6 +750,17 @@ fn parse_i64(value: Option<&str>) -> Result<i64, AppError> {
     .map_err(|_| AppError::new(ErrorCode::ValidationError, "数值字段非法"))
 }
 
+fn parse_f64_optional(value: Option<&str>) -> Result<Option<f64>, AppError> {
+  let value = value.unwrap_or("").trim();
+  if value.is_empty() {
+    return Ok(None);
+  }
+  value
+    .parse::<f64>()
+    .map(Some)
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "数值字段非法"))
+}
+
 fn parse_i64_optional(value: Option<&str>) -> Result<Option<i64>, AppError> {
   let value = value.unwrap_or("").trim();
   if value.is_empty() {
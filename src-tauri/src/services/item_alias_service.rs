@@ -0,0 +1,170 @@
+// 物品别名：同一物品的厂家编码、内部编码、EAN 等其他条码，扫码、按编码查询与导入时可解析到物品
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::item_alias_repo::{self, ItemAliasRow};
+use crate::repo::item_repo::{self, ItemRow};
+use crate::repo::rack_repo::{self, SlotRow};
+
+pub const ALIAS_KINDS: [&str; 5] = ["manufacturer", "internal", "ean", "supplier", "other"];
+
+#[derive(Debug, Deserialize)]
+pub struct ItemAliasInput {
+  pub code: String,
+  // 缺省为 other
+  pub kind: Option<String>,
+  pub note: Option<String>,
+}
+
+/// 扫码解析结果：先按物品（编码/别名/旧编码），再按库位编码
+#[derive(Debug, Serialize)]
+pub struct ScanResolution {
+  // item / slot / unknown
+  pub kind: String,
+  // 物品命中方式：code / alias / history
+  pub matched_by: Option<String>,
+  pub item: Option<ItemRow>,
+  pub slot: Option<SlotRow>,
+}
+
+pub async fn list_aliases(pool: &SqlitePool, item_id: &str) -> Result<Vec<ItemAliasRow>, AppError> {
+  item_alias_repo::list_aliases(pool, item_id).await
+}
+
+pub async fn create_alias(pool: &SqlitePool, item_id: &str, input: &ItemAliasInput) -> Result<ItemAliasRow, AppError> {
+  if item_repo::get_item_by_id(pool, item_id).await?.is_none() {
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound));
+  }
+  let (code, kind, note) = normalize_input(input)?;
+  ensure_alias_available(pool, &code, item_id, None).await?;
+  let alias = ItemAliasRow {
+    id: Uuid::new_v4().to_string(),
+    item_id: item_id.to_string(),
+    code,
+    kind,
+    note,
+    created_at: Utc::now().timestamp(),
+  };
+  item_alias_repo::insert_alias(pool, &alias).await?;
+  Ok(alias)
+}
+
+pub async fn update_alias(pool: &SqlitePool, id: &str, input: &ItemAliasInput) -> Result<(), AppError> {
+  let alias = item_alias_repo::get_alias(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "别名不存在"))?;
+  let (code, kind, note) = normalize_input(input)?;
+  ensure_alias_available(pool, &code, &alias.item_id, Some(id)).await?;
+  item_alias_repo::update_alias(pool, id, &code, &kind, note).await?;
+  Ok(())
+}
+
+pub async fn delete_alias(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  if item_alias_repo::delete_alias(pool, id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "别名不存在"));
+  }
+  Ok(())
+}
+
+/// 导入时为物品补充别名：已是该物品别名的跳过，与其他物品冲突时返回错误
+pub async fn import_alias(pool: &SqlitePool, item_id: &str, code: &str, kind: &str) -> Result<bool, AppError> {
+  if item_alias_repo::get_item_id_by_alias(pool, code).await?.as_deref() == Some(item_id) {
+    return Ok(false);
+  }
+  let input = ItemAliasInput {
+    code: code.to_string(),
+    kind: Some(kind.to_string()),
+    note: None,
+  };
+  create_alias(pool, item_id, &input).await?;
+  Ok(true)
+}
+
+/// 解析扫码内容：物品编码、别名、旧编码依次匹配，均未命中时按库位编码匹配
+pub async fn resolve_scan(pool: &SqlitePool, code: &str) -> Result<ScanResolution, AppError> {
+  let code = code.trim();
+  if code.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "扫码内容不能为空"));
+  }
+  if let Some(item) = item_repo::get_item_by_code(pool, code).await? {
+    let matched_by = if item.item_code == code {
+      "code"
+    } else if item_alias_repo::get_item_id_by_alias(pool, code).await?.is_some() {
+      "alias"
+    } else {
+      "history"
+    };
+    return Ok(ScanResolution {
+      kind: "item".to_string(),
+      matched_by: Some(matched_by.to_string()),
+      item: Some(item),
+      slot: None,
+    });
+  }
+  if let Some(slot) = rack_repo::get_slot_by_code(pool, code).await? {
+    return Ok(ScanResolution {
+      kind: "slot".to_string(),
+      matched_by: None,
+      item: None,
+      slot: Some(slot),
+    });
+  }
+  Ok(ScanResolution {
+    kind: "unknown".to_string(),
+    matched_by: None,
+    item: None,
+    slot: None,
+  })
+}
+
+fn normalize_input(input: &ItemAliasInput) -> Result<(String, String, Option<String>), AppError> {
+  let code = input.code.trim().to_string();
+  if code.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "别名不能为空"));
+  }
+  let kind = input.kind.as_deref().map(str::trim).filter(|value| !value.is_empty()).unwrap_or("other");
+  if !ALIAS_KINDS.contains(&kind) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      "别名类型仅支持 manufacturer/internal/ean/supplier/other",
+    ));
+  }
+  let note = input.note.as_deref().map(str::trim).filter(|value| !value.is_empty()).map(str::to_string);
+  Ok((code, kind.to_string(), note))
+}
+
+/// 别名全局唯一，且不能与物品编码或其他物品的旧编码相同
+async fn ensure_alias_available(
+  pool: &SqlitePool,
+  code: &str,
+  item_id: &str,
+  alias_id: Option<&str>,
+) -> Result<(), AppError> {
+  if item_repo::count_by_item_code(pool, code).await? > 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "别名与物品编码重复"));
+  }
+  if let Some(existing) = item_alias_repo::get_item_id_by_alias(pool, code).await? {
+    let same_alias = match alias_id {
+      Some(alias_id) => item_alias_repo::get_alias(pool, alias_id)
+        .await?
+        .is_some_and(|alias| alias.code == code),
+      None => false,
+    };
+    if !same_alias {
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        if existing == item_id { "该物品已有此别名" } else { "别名已被其他物品使用" },
+      ));
+    }
+  }
+  if let Some(owner_id) = item_repo::get_item_id_by_old_code(pool, code).await? {
+    if owner_id != item_id {
+      return Err(AppError::new(ErrorCode::Conflict, "该编码是其他物品的旧编码"));
+    }
+  }
+  Ok(())
+}
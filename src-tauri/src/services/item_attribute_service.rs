@@ -0,0 +1,225 @@
+// 物品自定义属性：各现场按需定义扩展字段（品牌、颜色、保质期等），并可配置物品必填字段
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::repo::item_attribute_repo::{self, ItemAttributeDefRow};
+use crate::repo::meta_repo;
+
+pub const ATTRIBUTE_DATA_TYPES: [&str; 3] = ["text", "number", "date"];
+
+/// 可配置为必填的物品内置字段（编码与名称始终必填）
+pub const REQUIRABLE_ITEM_FIELDS: [&str; 6] = ["model", "spec", "uom", "unit_cost", "currency", "remark"];
+
+/// 属性值上限（字符数）
+const MAX_ATTRIBUTE_VALUE_LEN: usize = 200;
+
+/// 物品的属性取值：属性 ID → 值
+pub type ItemAttributes = BTreeMap<String, String>;
+
+#[derive(Debug, Deserialize)]
+pub struct ItemAttributeDefInput {
+  pub name: String,
+  // 缺省为 text
+  pub data_type: Option<String>,
+  pub required: Option<bool>,
+  pub sort_no: Option<i64>,
+  // 缺省为 active
+  pub status: Option<String>,
+}
+
+pub async fn list_defs(pool: &SqlitePool, only_active: bool) -> Result<Vec<ItemAttributeDefRow>, AppError> {
+  item_attribute_repo::list_defs(pool, only_active).await
+}
+
+pub async fn create_def(pool: &SqlitePool, input: &ItemAttributeDefInput) -> Result<ItemAttributeDefRow, AppError> {
+  let def = build_def(Uuid::new_v4().to_string(), input, Utc::now().timestamp())?;
+  if item_attribute_repo::get_def_id_by_name(pool, &def.name).await?.is_some() {
+    return Err(AppError::new(ErrorCode::Conflict, "属性名称已存在"));
+  }
+  item_attribute_repo::insert_def(pool, &def).await?;
+  Ok(def)
+}
+
+pub async fn update_def(pool: &SqlitePool, id: &str, input: &ItemAttributeDefInput) -> Result<(), AppError> {
+  let current = item_attribute_repo::get_def(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "属性不存在"))?;
+  let def = build_def(id.to_string(), input, current.created_at)?;
+  if let Some(existing) = item_attribute_repo::get_def_id_by_name(pool, &def.name).await? {
+    if existing != id {
+      return Err(AppError::new(ErrorCode::Conflict, "属性名称已存在"));
+    }
+  }
+  // 修改类型时已有取值须符合新类型
+  if def.data_type != current.data_type {
+    for value in item_attribute_repo::list_values_by_attr(pool, id).await? {
+      if validate_value(&def.data_type, &value).is_err() {
+        return Err(AppError::new(
+          ErrorCode::ValidationError,
+          format!("已有取值“{}”不符合新类型，无法修改属性类型", value),
+        ));
+      }
+    }
+  }
+  item_attribute_repo::update_def(pool, &def).await?;
+  Ok(())
+}
+
+pub async fn delete_def(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  if item_attribute_repo::delete_def(pool, id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "属性不存在"));
+  }
+  Ok(())
+}
+
+/// 按物品分组的属性取值；item_ids 为 None 时返回全部物品
+pub async fn values_by_item(
+  pool: &SqlitePool,
+  item_ids: Option<&[String]>,
+) -> Result<HashMap<String, ItemAttributes>, AppError> {
+  let mut grouped: HashMap<String, ItemAttributes> = HashMap::new();
+  for row in item_attribute_repo::list_values(pool, item_ids).await? {
+    grouped.entry(row.item_id).or_default().insert(row.attr_id, row.value);
+  }
+  Ok(grouped)
+}
+
+/// 校验并整理物品的属性取值：仅处理提供的属性，空值表示清除；
+/// is_new 为 true（新建物品）时必填属性必须提供
+pub async fn normalize_values(
+  pool: &SqlitePool,
+  attributes: &ItemAttributes,
+  is_new: bool,
+) -> Result<Vec<(String, Option<String>)>, AppError> {
+  let defs = item_attribute_repo::list_defs(pool, true).await?;
+  for attr_id in attributes.keys() {
+    if !defs.iter().any(|def| &def.id == attr_id) {
+      return Err(AppError::new(ErrorCode::ValidationError, "属性不存在或已停用"));
+    }
+  }
+  let mut values = Vec::new();
+  for def in &defs {
+    let value = attributes.get(&def.id).map(|value| value.trim());
+    match value {
+      Some(value) if !value.is_empty() => {
+        validate_value(&def.data_type, value)
+          .map_err(|message| AppError::new(ErrorCode::ValidationError, format!("{}：{}", def.name, message)))?;
+        values.push((def.id.clone(), Some(value.to_string())));
+      }
+      Some(_) if def.required => {
+        return Err(AppError::new(ErrorCode::ValidationError, format!("{} 为必填项", def.name)));
+      }
+      Some(_) => values.push((def.id.clone(), None)),
+      None if def.required && is_new => {
+        return Err(AppError::new(ErrorCode::ValidationError, format!("{} 为必填项", def.name)));
+      }
+      None => {}
+    }
+  }
+  Ok(values)
+}
+
+pub async fn save_values(
+  pool: &SqlitePool,
+  item_id: &str,
+  values: &[(String, Option<String>)],
+) -> Result<(), AppError> {
+  if values.is_empty() {
+    return Ok(());
+  }
+  item_attribute_repo::set_values(pool, item_id, values).await
+}
+
+/// 配置为必填的物品内置字段
+pub async fn required_item_fields(pool: &SqlitePool) -> Result<Vec<String>, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "item_required_fields")
+      .await?
+      .unwrap_or_default()
+      .split(',')
+      .map(str::trim)
+      .filter(|field| REQUIRABLE_ITEM_FIELDS.contains(field))
+      .map(str::to_string)
+      .collect(),
+  )
+}
+
+pub async fn set_required_item_fields(pool: &SqlitePool, fields: &[String]) -> Result<(), AppError> {
+  for field in fields {
+    if !REQUIRABLE_ITEM_FIELDS.contains(&field.as_str()) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "item_required_fields 仅支持 model/spec/uom/unit_cost/currency/remark",
+      ));
+    }
+  }
+  meta_repo::set_meta_value(pool, "item_required_fields", &fields.join(",")).await
+}
+
+/// 校验内置必填字段；filled 为 字段名 → 是否已填写
+pub async fn ensure_required_item_fields(pool: &SqlitePool, filled: &[(&str, bool)]) -> Result<(), AppError> {
+  let required = required_item_fields(pool).await?;
+  for (field, is_filled) in filled {
+    if !is_filled && required.iter().any(|value| value == field) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("{} 为必填项", item_field_label(field)),
+      ));
+    }
+  }
+  Ok(())
+}
+
+fn item_field_label(field: &str) -> &str {
+  match field {
+    "model" => "型号",
+    "spec" => "规格",
+    "uom" => "单位",
+    "unit_cost" => "单位成本",
+    "currency" => "币种",
+    "remark" => "备注",
+    _ => field,
+  }
+}
+
+fn build_def(id: String, input: &ItemAttributeDefInput, created_at: i64) -> Result<ItemAttributeDefRow, AppError> {
+  let name = input.name.trim().to_string();
+  if name.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "属性名称不能为空"));
+  }
+  let data_type = input.data_type.as_deref().map(str::trim).filter(|value| !value.is_empty()).unwrap_or("text");
+  if !ATTRIBUTE_DATA_TYPES.contains(&data_type) {
+    return Err(AppError::new(ErrorCode::ValidationError, "属性类型仅支持 text/number/date"));
+  }
+  let status = input.status.as_deref().unwrap_or("active");
+  if !matches!(status, "active" | "disabled") {
+    return Err(AppError::new(ErrorCode::ValidationError, "属性状态非法"));
+  }
+  Ok(ItemAttributeDefRow {
+    id,
+    name,
+    data_type: data_type.to_string(),
+    required: input.required.unwrap_or(false),
+    sort_no: input.sort_no.unwrap_or(0),
+    status: status.to_string(),
+    created_at,
+  })
+}
+
+fn validate_value(data_type: &str, value: &str) -> Result<(), String> {
+  if value.chars().count() > MAX_ATTRIBUTE_VALUE_LEN {
+    return Err(format!("长度不能超过 {} 个字符", MAX_ATTRIBUTE_VALUE_LEN));
+  }
+  match data_type {
+    "number" if value.parse::<f64>().map(|number| !number.is_finite()).unwrap_or(true) => {
+      Err("必须为数字".to_string())
+    }
+    "date" if NaiveDate::parse_from_str(value, "%Y-%m-%d").is_err() => Err("日期格式应为 YYYY-MM-DD".to_string()),
+    _ => Ok(()),
+  }
+}
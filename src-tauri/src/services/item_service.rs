@@ -3,26 +3,167 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::repo::item_repo::{self, ItemRow};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::repo::item_alias_repo;
+use crate::repo::item_repo::{self, ItemCodeHistoryRow, ItemPatch, ItemReferenceCounts, ItemRow};
+use crate::repo::loan_repo::{self, OpenLoanRow};
+use crate::repo::photo_repo::{self, PhotoRow};
+use crate::repo::stock_hold_repo::{self, StockHoldListRow};
+use crate::repo::stock_query_repo::{self, StockByItemRow};
+use crate::repo::stock_threshold_repo::{self, StockThresholdRow};
+use crate::repo::txn_repo::{self, TxnListRow};
+use crate::services::item_attribute_service::{self, ItemAttributes};
+
+/// 单次批量操作最多物品数
+pub const MAX_BULK_ITEMS: usize = 500;
+/// 物品详情中的最近流水条数
+const DETAIL_TXN_LIMIT: i64 = 10;
+/// 物品详情中库存分布、借出与冻结记录的上限
+const DETAIL_ROW_LIMIT: i64 = 500;
 
 #[derive(Debug, serde::Serialize)]
 pub struct ItemListResult {
-  pub items: Vec<ItemRow>,
+  pub items: Vec<ItemListEntry>,
   pub total: i64,
 }
 
+/// 物品列表行：物品字段 + 自定义属性取值（属性 ID → 值）
+#[derive(Debug, serde::Serialize)]
+pub struct ItemListEntry {
+  #[serde(flatten)]
+  pub item: ItemRow,
+  pub attributes: ItemAttributes,
+}
+
+/// 物品详情：物品页一次取齐照片、各库位库存、最近流水、借出中、冻结与库存阈值；
+/// 库存与单据按操作人的仓库范围过滤
+#[derive(Debug, serde::Serialize)]
+pub struct ItemDetail {
+  #[serde(flatten)]
+  pub item: ItemListEntry,
+  pub photos: Vec<PhotoRow>,
+  pub stock: Vec<StockByItemRow>,
+  pub recent_txns: Vec<TxnListRow>,
+  pub open_loans: Vec<OpenLoanRow>,
+  // 冻结中的库存（stock_hold）
+  pub holds: Vec<StockHoldListRow>,
+  pub thresholds: Vec<StockThresholdRow>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct BulkItemResult {
+  // 实际更新的物品数
+  pub updated: i64,
+}
+
 pub async fn list_items(
   pool: &SqlitePool,
   keyword: Option<String>,
   page_index: i64,
   page_size: i64,
+  sort_by: Option<String>,
+  sort_dir: Option<String>,
 ) -> Result<ItemListResult, AppError> {
   let (page_index, page_size) = normalize_page(page_index, page_size)?;
   let total = item_repo::count_items(pool, keyword.clone()).await?;
-  let items = item_repo::list_items(pool, keyword, page_index, page_size).await?;
+  let items = item_repo::list_items(
+    pool,
+    keyword,
+    page_index,
+    page_size,
+    sort_by.as_deref(),
+    sort_dir.as_deref(),
+  )
+  .await?;
+  let item_ids: Vec<String> = items.iter().map(|item| item.id.clone()).collect();
+  let mut attributes = item_attribute_service::values_by_item(pool, Some(&item_ids)).await?;
+  let items = items
+    .into_iter()
+    .map(|item| ItemListEntry {
+      attributes: attributes.remove(&item.id).unwrap_or_default(),
+      item,
+    })
+    .collect();
   Ok(ItemListResult { items, total })
 }
 
+pub async fn get_item_detail(
+  pool: &SqlitePool,
+  id: &str,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<ItemDetail, AppError> {
+  let item = item_repo::get_item_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  let item_ids = [item.id.clone()];
+  let attributes = item_attribute_service::values_by_item(pool, Some(&item_ids))
+    .await?
+    .remove(&item.id)
+    .unwrap_or_default();
+
+  let photos = photo_repo::list_photos(pool, "item", id).await?;
+  let stock = stock_query_repo::list_stock_by_item_filtered(
+    pool,
+    1,
+    DETAIL_ROW_LIMIT,
+    None,
+    None,
+    None,
+    Some(id.to_string()),
+    None,
+    allowed_warehouse_ids.clone(),
+    None,
+    None,
+  )
+  .await?;
+  let recent_txns = txn_repo::list_txns(
+    pool,
+    None,
+    None,
+    Some(id.to_string()),
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    allowed_warehouse_ids.clone(),
+    1,
+    DETAIL_TXN_LIMIT,
+    None,
+    None,
+    None,
+  )
+  .await?;
+  let open_loans = loan_repo::list_open_loans(
+    pool,
+    Utc::now().timestamp(),
+    false,
+    None,
+    Some(id.to_string()),
+    allowed_warehouse_ids.clone(),
+    1,
+    DETAIL_ROW_LIMIT,
+  )
+  .await?;
+  let holds =
+    stock_hold_repo::list_holds(pool, Some(id), None, None, true, allowed_warehouse_ids.clone(), DETAIL_ROW_LIMIT)
+      .await?;
+  let thresholds = stock_threshold_repo::list_thresholds(pool, Some(id), None, allowed_warehouse_ids).await?;
+
+  Ok(ItemDetail {
+    item: ItemListEntry { item, attributes },
+    photos,
+    stock,
+    recent_txns,
+    open_loans,
+    holds,
+    thresholds,
+  })
+}
+
 pub async fn create_item(
   pool: &SqlitePool,
   item_code: &str,
@@ -30,15 +171,22 @@ pub async fn create_item(
   model: Option<String>,
   spec: Option<String>,
   uom: Option<String>,
+  unit_cost: Option<f64>,
+  currency: Option<String>,
+  qty_precision: i64,
   remark: Option<String>,
+  attributes: &ItemAttributes,
 ) -> Result<(), AppError> {
   if item_code.trim().is_empty() || name.trim().is_empty() {
     return Err(AppError::new(ErrorCode::ValidationError, "物品编码或名称不能为空"));
   }
+  validate_unit_cost(unit_cost)?;
+  quantity::validate_precision(qty_precision)?;
+  let currency = normalize_currency(currency)?;
+  ensure_required_fields(pool, &model, &spec, &uom, unit_cost, &currency, &remark).await?;
+  let attribute_values = item_attribute_service::normalize_values(pool, attributes, true).await?;
 
-  if item_repo::count_by_item_code(pool, item_code).await? > 0 {
-    return Err(AppError::new(ErrorCode::Conflict, "物品编码已存在"));
-  }
+  ensure_code_available(pool, item_code, None).await?;
 
   let id = Uuid::new_v4().to_string();
   let now = Utc::now().timestamp();
@@ -50,11 +198,15 @@ pub async fn create_item(
     model,
     spec,
     uom,
+    unit_cost,
+    currency,
+    qty_precision,
     "active",
     remark,
     now,
   )
   .await?;
+  item_attribute_service::save_values(pool, &id, &attribute_values).await?;
 
   Ok(())
 }
@@ -66,28 +218,285 @@ pub async fn update_item(
   model: Option<String>,
   spec: Option<String>,
   uom: Option<String>,
+  unit_cost: Option<f64>,
+  currency: Option<String>,
+  qty_precision: Option<i64>,
   remark: Option<String>,
+  attributes: &ItemAttributes,
 ) -> Result<(), AppError> {
   if name.trim().is_empty() {
     return Err(AppError::new(ErrorCode::ValidationError, "物品名称不能为空"));
   }
+  validate_unit_cost(unit_cost)?;
+  let currency = normalize_currency(currency)?;
+  ensure_required_fields(pool, &model, &spec, &uom, unit_cost, &currency, &remark).await?;
+  let attribute_values = item_attribute_service::normalize_values(pool, attributes, false).await?;
 
-  item_repo::update_item(pool, id, name, model, spec, uom, remark).await?;
+  item_repo::update_item(pool, id, name, model, spec, uom, unit_cost, currency, remark).await?;
+  item_attribute_service::save_values(pool, id, &attribute_values).await?;
+  // 精度变化时同步换算该物品已有的库存、流水与借用数量
+  if let Some(qty_precision) = qty_precision {
+    quantity::validate_precision(qty_precision)?;
+    let current = item_repo::get_qty_precision(pool, id).await?;
+    if current != qty_precision {
+      item_repo::change_qty_precision(pool, id, current, qty_precision).await?;
+    }
+  }
   Ok(())
 }
 
+/// 按物品精度将显示数量换算为存储值（命令入参统一经此换算）
+pub async fn qty_to_base(pool: &SqlitePool, item_id: &str, qty: f64) -> Result<i64, AppError> {
+  let precision = item_repo::get_qty_precision(pool, item_id).await?;
+  quantity::to_base(qty, precision)
+}
+
 pub async fn set_item_status(pool: &SqlitePool, id: &str, status: &str) -> Result<(), AppError> {
   if !matches!(status, "active" | "inactive") {
-    return Err(AppError::new(ErrorCode::ValidationError, "状态非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
   }
 
   item_repo::set_item_status(pool, id, status).await?;
   Ok(())
 }
 
+/// 批量启用/停用物品，在同一事务内完成
+pub async fn set_items_status(
+  pool: &SqlitePool,
+  ids: &[String],
+  status: &str,
+) -> Result<BulkItemResult, AppError> {
+  if !matches!(status, "active" | "inactive") {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
+  }
+  let ids = normalize_bulk_ids(ids)?;
+  let patch = ItemPatch {
+    status: Some(status.to_string()),
+    ..Default::default()
+  };
+  let updated = item_repo::bulk_update_items(pool, &ids, &patch).await?;
+  Ok(BulkItemResult {
+    updated: updated as i64,
+  })
+}
+
+/// 批量编辑物品的公共字段（型号/规格/单位/单位成本/币种/备注/状态），未提供的字段保持不变
+pub async fn bulk_update_items(
+  pool: &SqlitePool,
+  ids: &[String],
+  mut patch: ItemPatch,
+) -> Result<BulkItemResult, AppError> {
+  let ids = normalize_bulk_ids(ids)?;
+  if patch.model.is_none()
+    && patch.spec.is_none()
+    && patch.uom.is_none()
+    && patch.unit_cost.is_none()
+    && patch.currency.is_none()
+    && patch.remark.is_none()
+    && patch.status.is_none()
+  {
+    return Err(AppError::new(ErrorCode::ValidationError, "请至少填写一个要修改的字段"));
+  }
+  if let Some(status) = patch.status.as_deref() {
+    if !matches!(status, "active" | "inactive") {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
+    }
+  }
+  validate_unit_cost(patch.unit_cost)?;
+  // 币种为空串表示清空，其余按代码规范化
+  if let Some(currency) = patch.currency.take() {
+    patch.currency = Some(normalize_currency(Some(currency))?.unwrap_or_default());
+  }
+  for value in [&mut patch.model, &mut patch.spec, &mut patch.uom, &mut patch.remark] {
+    if let Some(text) = value.as_mut() {
+      *text = text.trim().to_string();
+    }
+  }
+  // 批量清空的字段不能是必填字段
+  let kept = |value: &Option<String>| !value.as_deref().is_some_and(str::is_empty);
+  item_attribute_service::ensure_required_item_fields(
+    pool,
+    &[
+      ("model", kept(&patch.model)),
+      ("spec", kept(&patch.spec)),
+      ("uom", kept(&patch.uom)),
+      ("currency", kept(&patch.currency)),
+      ("remark", kept(&patch.remark)),
+    ],
+  )
+  .await?;
+  let updated = item_repo::bulk_update_items(pool, &ids, &patch).await?;
+  Ok(BulkItemResult {
+    updated: updated as i64,
+  })
+}
+
+/// 修改物品编码，旧编码记入变更历史并继续可用于扫码、查询与导入
+pub async fn change_item_code(
+  pool: &SqlitePool,
+  id: &str,
+  new_code: &str,
+  actor_operator_id: &str,
+) -> Result<(), AppError> {
+  let new_code = new_code.trim();
+  if new_code.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "物品编码不能为空"));
+  }
+  let item = item_repo::get_item_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  if item.item_code == new_code {
+    return Err(AppError::new(ErrorCode::ValidationError, "新编码与当前编码相同"));
+  }
+  ensure_code_available(pool, new_code, Some(id)).await?;
+  item_repo::change_item_code(pool, id, &item.item_code, new_code, Utc::now().timestamp(), actor_operator_id).await
+}
+
+pub async fn list_item_code_history(pool: &SqlitePool, id: &str) -> Result<Vec<ItemCodeHistoryRow>, AppError> {
+  item_repo::list_item_code_history(pool, id).await
+}
+
+/// 编码既不能与现有物品重复，也不能是其他物品的别名或用过的旧编码（旧标签仍按旧编码解析）
+async fn ensure_code_available(pool: &SqlitePool, code: &str, item_id: Option<&str>) -> Result<(), AppError> {
+  if item_repo::count_by_item_code(pool, code).await? > 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "物品编码已存在"));
+  }
+  if let Some(owner_id) = item_alias_repo::get_item_id_by_alias(pool, code).await? {
+    if Some(owner_id.as_str()) != item_id {
+      return Err(AppError::new(ErrorCode::Conflict, "该编码是其他物品的别名"));
+    }
+  }
+  if let Some(owner_id) = item_repo::get_item_id_by_old_code(pool, code).await? {
+    if Some(owner_id.as_str()) != item_id {
+      return Err(AppError::new(ErrorCode::Conflict, "该编码是其他物品的旧编码"));
+    }
+  }
+  Ok(())
+}
+
+/// 合并预览：源/目标物品及源物品将被改挂的库存、流水、借用与图片数量
+#[derive(Debug, serde::Serialize)]
+pub struct ItemMergePreview {
+  pub source_code: String,
+  pub target_code: String,
+  pub references: ItemReferenceCounts,
+}
+
+/// 校验两个物品可以合并并返回预览：不能合并到自身，已合并的物品不能再参与合并，
+/// 数量精度需一致（存储值按精度放大，不一致时库存无法直接相加）
+pub async fn merge_preview(
+  pool: &SqlitePool,
+  source_id: &str,
+  target_id: &str,
+) -> Result<ItemMergePreview, AppError> {
+  if source_id == target_id {
+    return Err(AppError::new(ErrorCode::ValidationError, "不能将物品合并到自身"));
+  }
+  let source = item_repo::get_item_by_id(pool, source_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  let target = item_repo::get_item_by_id(pool, target_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  for item in [&source, &target] {
+    if item_repo::get_merged_into_id(pool, &item.id).await?.is_some() {
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        format!("物品 {} 已合并到其他物品", item.item_code),
+      ));
+    }
+  }
+  if source.qty_precision != target.qty_precision {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      "两个物品的数量小数位数不同，请先调整为一致后再合并",
+    ));
+  }
+  Ok(ItemMergePreview {
+    source_code: source.item_code,
+    target_code: target.item_code,
+    references: item_repo::count_item_references(pool, source_id).await?,
+  })
+}
+
+/// 将重复物品合并到目标物品，在同一事务内完成
+pub async fn merge_items(pool: &SqlitePool, source_id: &str, target_id: &str) -> Result<ItemMergePreview, AppError> {
+  let preview = merge_preview(pool, source_id, target_id).await?;
+  item_repo::merge_items(pool, source_id, target_id, Utc::now().timestamp()).await?;
+  Ok(preview)
+}
+
+/// 批量操作的物品 ID：去空、去重并限制数量
+fn normalize_bulk_ids(ids: &[String]) -> Result<Vec<String>, AppError> {
+  let mut result: Vec<String> = Vec::with_capacity(ids.len());
+  for id in ids.iter().map(|id| id.trim()).filter(|id| !id.is_empty()) {
+    if !result.iter().any(|existing| existing == id) {
+      result.push(id.to_string());
+    }
+  }
+  if result.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "请选择物品"));
+  }
+  if result.len() > MAX_BULK_ITEMS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("单次最多批量处理 {} 个物品", MAX_BULK_ITEMS),
+    ));
+  }
+  Ok(result)
+}
+
+/// 币种统一为 3 位大写字母代码（ISO 4217），空值视为未设置
+fn normalize_currency(currency: Option<String>) -> Result<Option<String>, AppError> {
+  let Some(currency) = currency.map(|value| value.trim().to_ascii_uppercase()) else {
+    return Ok(None);
+  };
+  if currency.is_empty() {
+    return Ok(None);
+  }
+  if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_uppercase()) {
+    return Err(AppError::new(ErrorCode::ValidationError, "币种需为 3 位字母代码，如 CNY"));
+  }
+  Ok(Some(currency))
+}
+
+/// 校验系统设置中配置为必填的内置字段
+async fn ensure_required_fields(
+  pool: &SqlitePool,
+  model: &Option<String>,
+  spec: &Option<String>,
+  uom: &Option<String>,
+  unit_cost: Option<f64>,
+  currency: &Option<String>,
+  remark: &Option<String>,
+) -> Result<(), AppError> {
+  let filled = |value: &Option<String>| value.as_deref().is_some_and(|text| !text.trim().is_empty());
+  item_attribute_service::ensure_required_item_fields(
+    pool,
+    &[
+      ("model", filled(model)),
+      ("spec", filled(spec)),
+      ("uom", filled(uom)),
+      ("unit_cost", unit_cost.is_some()),
+      ("currency", filled(currency)),
+      ("remark", filled(remark)),
+    ],
+  )
+  .await
+}
+
+pub fn validate_unit_cost(unit_cost: Option<f64>) -> Result<(), AppError> {
+  if let Some(cost) = unit_cost {
+    if !cost.is_finite() || cost < 0.0 {
+      return Err(AppError::new(ErrorCode::ValidationError, "单位成本不能为负数"));
+    }
+  }
+  Ok(())
+}
+
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
   if page_index < 1 || page_size < 1 {
-    return Err(AppError::new(ErrorCode::ValidationError, "分页参数非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
   }
   Ok((page_index, page_size))
 }
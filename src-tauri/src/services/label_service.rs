@@ -0,0 +1,325 @@
+// 标签模板与标签生成：模板保存标签尺寸、打印字段与条码类型，生成时每张标签输出为一页 PDF，
+// 页面尺寸即标签尺寸，适配不同规格的标签打印机
+use chrono::Utc;
+use printpdf::{BuiltinFont, Color, Mm, PdfDocument, Rgb};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::label_template_repo::{self, LabelTemplateRow};
+use crate::repo::{item_repo, rack_repo};
+use crate::services::count_service::{draw_barcode, export_dir, pdf_text};
+
+/// 标签对象
+pub const LABEL_TARGETS: [&str; 2] = ["slot", "item"];
+/// 条码类型（none 表示只打印文字）
+pub const BARCODE_TYPES: [&str; 2] = ["code128", "none"];
+/// 库位标签可打印字段：库位编码、仓库、货架、层-位
+pub const SLOT_FIELDS: [&str; 4] = ["code", "warehouse", "rack", "level_slot"];
+/// 物品标签可打印字段
+pub const ITEM_FIELDS: [&str; 5] = ["code", "name", "model", "spec", "uom"];
+
+const MIN_SIZE_MM: f64 = 15.0;
+const MAX_SIZE_MM: f64 = 200.0;
+const MIN_FONT_SIZE: f64 = 5.0;
+const MAX_FONT_SIZE: f64 = 36.0;
+/// 单次最多生成的标签数
+const MAX_LABELS: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+pub struct LabelTemplateInput {
+  pub name: String,
+  pub target: String,
+  pub width_mm: f64,
+  pub height_mm: f64,
+  pub fields: Vec<String>,
+  pub barcode_type: Option<String>,
+  pub font_size: Option<f64>,
+  pub is_default: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LabelPrintResult {
+  pub file_path: String,
+  pub template_id: String,
+  pub labels: usize,
+}
+
+/// 单张标签内容：条码值与逐行文字
+struct LabelContent {
+  barcode: String,
+  lines: Vec<String>,
+}
+
+pub async fn list_templates(
+  pool: &SqlitePool,
+  target: Option<&str>,
+) -> Result<Vec<LabelTemplateRow>, AppError> {
+  if let Some(target) = target {
+    validate_target(target)?;
+  }
+  label_template_repo::list_label_templates(pool, target).await
+}
+
+pub async fn create_template(pool: &SqlitePool, input: &LabelTemplateInput) -> Result<LabelTemplateRow, AppError> {
+  let now = Utc::now().timestamp();
+  let template = build_template(input, Uuid::new_v4().to_string(), now)?;
+  if label_template_repo::name_exists(pool, &template.name, None).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "模板名称已存在"));
+  }
+  label_template_repo::save_label_template(pool, &template).await?;
+  Ok(template)
+}
+
+pub async fn update_template(
+  pool: &SqlitePool,
+  id: &str,
+  input: &LabelTemplateInput,
+) -> Result<LabelTemplateRow, AppError> {
+  let existing = label_template_repo::get_label_template(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "标签模板不存在"))?;
+  if existing.target != input.target.trim() {
+    return Err(AppError::new(ErrorCode::ValidationError, "模板的标签对象不可修改"));
+  }
+  let mut template = build_template(input, existing.id.clone(), existing.created_at)?;
+  template.updated_at = Utc::now().timestamp();
+  if label_template_repo::name_exists(pool, &template.name, Some(id)).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "模板名称已存在"));
+  }
+  label_template_repo::save_label_template(pool, &template).await?;
+  Ok(template)
+}
+
+pub async fn delete_template(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  if label_template_repo::delete_label_template(pool, id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "标签模板不存在"));
+  }
+  Ok(())
+}
+
+fn validate_target(target: &str) -> Result<(), AppError> {
+  if !LABEL_TARGETS.contains(&target) {
+    return Err(AppError::new(ErrorCode::ValidationError, "标签对象仅支持 slot/item"));
+  }
+  Ok(())
+}
+
+fn build_template(input: &LabelTemplateInput, id: String, created_at: i64) -> Result<LabelTemplateRow, AppError> {
+  let name = input.name.trim();
+  if name.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "模板名称不能为空"));
+  }
+  let target = input.target.trim();
+  validate_target(target)?;
+  for size in [input.width_mm, input.height_mm] {
+    if !size.is_finite() || !(MIN_SIZE_MM..=MAX_SIZE_MM).contains(&size) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("标签宽高需在 {}-{} 毫米之间", MIN_SIZE_MM, MAX_SIZE_MM),
+      ));
+    }
+  }
+  let allowed_fields: &[&str] = if target == "slot" { &SLOT_FIELDS } else { &ITEM_FIELDS };
+  let mut fields: Vec<String> = Vec::new();
+  for field in &input.fields {
+    let field = field.trim();
+    if !allowed_fields.contains(&field) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("不支持的标签字段：{}", field),
+      ));
+    }
+    if !fields.iter().any(|existing| existing == field) {
+      fields.push(field.to_string());
+    }
+  }
+  let barcode_type = input.barcode_type.as_deref().map(str::trim).unwrap_or("code128");
+  if !BARCODE_TYPES.contains(&barcode_type) {
+    return Err(AppError::new(ErrorCode::ValidationError, "条码类型仅支持 code128/none"));
+  }
+  if fields.is_empty() && barcode_type == "none" {
+    return Err(AppError::new(ErrorCode::ValidationError, "请至少选择一个打印字段或条码"));
+  }
+  let font_size = input.font_size.unwrap_or(9.0);
+  if !font_size.is_finite() || !(MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&font_size) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("字号需在 {}-{} 之间", MIN_FONT_SIZE, MAX_FONT_SIZE),
+    ));
+  }
+  Ok(LabelTemplateRow {
+    id,
+    name: name.to_string(),
+    target: target.to_string(),
+    width_mm: input.width_mm,
+    height_mm: input.height_mm,
+    fields,
+    barcode_type: barcode_type.to_string(),
+    font_size,
+    is_default: input.is_default.unwrap_or(false),
+    created_at,
+    updated_at: created_at,
+  })
+}
+
+/// 生成标签 PDF：库位标签可按货架（rack_id）或库位编码批量生成，物品标签按物品编码生成；
+/// 未指定模板时使用该对象的默认模板
+pub async fn generate_labels(
+  pool: &SqlitePool,
+  target: &str,
+  template_id: Option<&str>,
+  rack_id: Option<String>,
+  codes: &[String],
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<LabelPrintResult, AppError> {
+  validate_target(target)?;
+  let template = match template_id.map(str::trim).filter(|id| !id.is_empty()) {
+    Some(id) => label_template_repo::get_label_template(pool, id)
+      .await?
+      .ok_or_else(|| AppError::new(ErrorCode::NotFound, "标签模板不存在"))?,
+    None => label_template_repo::get_default_template(pool, target)
+      .await?
+      .ok_or_else(|| AppError::new(ErrorCode::NotFound, "尚未配置该类标签的模板"))?,
+  };
+  if template.target != target {
+    return Err(AppError::new(ErrorCode::ValidationError, "模板与标签对象不一致"));
+  }
+  let codes: Vec<String> = codes
+    .iter()
+    .map(|code| code.trim().to_string())
+    .filter(|code| !code.is_empty())
+    .collect();
+
+  let labels = if target == "slot" {
+    if rack_id.as_deref().map(str::trim).unwrap_or("").is_empty() && codes.is_empty() {
+      return Err(AppError::new(ErrorCode::ValidationError, "请选择货架或填写库位编码"));
+    }
+    rack_repo::list_slot_labels(pool, rack_id, &codes, allowed_warehouse_ids)
+      .await?
+      .into_iter()
+      .map(|slot| LabelContent {
+        lines: template
+          .fields
+          .iter()
+          .filter_map(|field| match field.as_str() {
+            "code" => Some(slot.slot_code.clone()),
+            "warehouse" => slot.warehouse_code.clone().map(|code| {
+              label_with_name(&code, slot.warehouse_name.as_deref())
+            }),
+            "rack" => Some(label_with_name(&slot.rack_code, Some(slot.rack_name.as_str()))),
+            "level_slot" => Some(format!("L{} - S{}", slot.level_no, slot.slot_no)),
+            _ => None,
+          })
+          .collect(),
+        barcode: slot.slot_code,
+      })
+      .collect::<Vec<_>>()
+  } else {
+    if codes.is_empty() {
+      return Err(AppError::new(ErrorCode::ValidationError, "请填写物品编码"));
+    }
+    let mut labels = Vec::with_capacity(codes.len());
+    for code in &codes {
+      let item = item_repo::get_item_by_code(pool, code)
+        .await?
+        .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("物品不存在：{}", code)))?;
+      labels.push(LabelContent {
+        lines: template
+          .fields
+          .iter()
+          .filter_map(|field| match field.as_str() {
+            "code" => Some(item.item_code.clone()),
+            "name" => Some(item.name.clone()),
+            "model" => item.model.clone(),
+            "spec" => item.spec.clone(),
+            "uom" => item.uom.clone(),
+            _ => None,
+          })
+          .collect(),
+        barcode: item.item_code,
+      });
+    }
+    labels
+  };
+  if labels.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "没有可生成标签的记录"));
+  }
+  if labels.len() > MAX_LABELS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("单次最多生成 {} 张标签", MAX_LABELS),
+    ));
+  }
+
+  let bytes = render_labels(&template, &labels)?;
+  let file_path = export_dir(pool)
+    .await?
+    .join(format!("labels_{}_{}.pdf", target, Utc::now().format("%Y%m%d%H%M%S")));
+  std::fs::write(&file_path, bytes).map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+  Ok(LabelPrintResult {
+    file_path: file_path.to_string_lossy().to_string(),
+    template_id: template.id,
+    labels: labels.len(),
+  })
+}
+
+/// 编码后附名称（名称含内置字体不支持的字符时仅打印编码）
+fn label_with_name(code: &str, name: Option<&str>) -> String {
+  match name.filter(|name| !name.is_empty() && name.is_ascii() && *name != code) {
+    Some(name) => format!("{} {}", code, name),
+    None => code.to_string(),
+  }
+}
+
+/// 每张标签一页；文字自上而下逐行打印（首行加粗），条码占用剩余高度。
+/// 内置 Helvetica 字体不支持中文，含非 ASCII 字符的行不打印
+fn render_labels(template: &LabelTemplateRow, labels: &[LabelContent]) -> Result<Vec<u8>, AppError> {
+  let width = template.width_mm as f32;
+  let height = template.height_mm as f32;
+  let font_size = template.font_size as f32;
+  let margin = 2.0;
+  // pt 转 mm，行距 1.25 倍
+  let line_h = font_size * 0.3528 * 1.25;
+  let with_barcode = template.barcode_type == "code128";
+  // 条码至少 8mm 高，文字放不下的行省略
+  let barcode_h = if with_barcode { (height * 0.45).max(8.0).min(height - margin * 2.0) } else { 0.0 };
+  let max_lines = ((height - margin * 2.0 - barcode_h) / line_h).floor().max(0.0) as usize;
+
+  let (doc, first_page, first_layer) = PdfDocument::new(template.name.clone(), Mm(width), Mm(height), "Layer 1");
+  let font = doc
+    .add_builtin_font(BuiltinFont::Helvetica)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+  let bold = doc
+    .add_builtin_font(BuiltinFont::HelveticaBold)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+  let black = Color::Rgb(Rgb::new(0.0, 0.0, 0.0, None));
+  // Helvetica 平均字宽约为字号的 0.55 倍
+  let max_chars = ((width - margin * 2.0) / (font_size * 0.3528 * 0.55)).floor().max(1.0) as usize;
+
+  for (index, label) in labels.iter().enumerate() {
+    let layer = if index == 0 {
+      doc.get_page(first_page).get_layer(first_layer)
+    } else {
+      let (page, layer) = doc.add_page(Mm(width), Mm(height), "Layer 1");
+      doc.get_page(page).get_layer(layer)
+    };
+    layer.set_fill_color(black.clone());
+    let lines = label.lines.iter().filter(|line| line.is_ascii()).take(max_lines);
+    for (line_index, line) in lines.enumerate() {
+      let y = height - margin - line_h * (line_index as f32 + 1.0) + line_h * 0.25;
+      let text: String = pdf_text(line).chars().take(max_chars).collect();
+      let line_font = if line_index == 0 { &bold } else { &font };
+      layer.use_text(text, font_size, Mm(margin), Mm(y), line_font);
+    }
+    if with_barcode {
+      draw_barcode(&layer, &font, &label.barcode, (margin, margin, width - margin * 2.0, barcode_h));
+    }
+  }
+
+  doc
+    .save_to_bytes()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfRenderFailed))
+}
@@ -0,0 +1,262 @@
+// 工具借用/归还：借出生成 OUT 流水扣减库存，归还生成 IN 流水回补库存，借用记录关联两笔流水
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::{item_repo, loan_repo, rack_repo, stock_repo, txn_repo};
+use crate::services::{expiry_service, stock_hold_service, txn_service};
+
+const MAX_BORROWER_LEN: usize = 64;
+
+#[derive(Debug, serde::Serialize)]
+pub struct LoanCreated {
+  pub loan_id: String,
+  pub loan_no: String,
+  pub txn_no: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct OpenLoanListResult {
+  pub items: Vec<loan_repo::OpenLoanRow>,
+  pub total: i64,
+  // 不受筛选影响的逾期数量，便于列表页提示
+  pub overdue_total: i64,
+}
+
+/// 借出：扣减库位库存并记录借用人与应还时间
+pub async fn create_loan(
+  pool: &SqlitePool,
+  item_id: &str,
+  slot_id: &str,
+  qty: i64,
+  borrower: &str,
+  due_at: Option<i64>,
+  occurred_at: i64,
+  actor_operator_id: &str,
+  note: Option<String>,
+) -> Result<LoanCreated, AppError> {
+  if qty <= 0 {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+  }
+  let borrower = borrower.trim();
+  if borrower.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "借用人不能为空"));
+  }
+  if borrower.chars().count() > MAX_BORROWER_LEN {
+    return Err(AppError::new(ErrorCode::ValidationError, "借用人过长"));
+  }
+  if due_at.is_some_and(|due_at| due_at < occurred_at) {
+    return Err(AppError::new(ErrorCode::ValidationError, "应还时间不能早于借出时间"));
+  }
+  let item = item_repo::get_item_by_id(pool, item_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  if item.status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ItemInactive));
+  }
+  rack_repo::get_slot_by_id(pool, slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+  txn_service::ensure_kiosk_operator(pool, &operator.id).await?;
+
+  let now = Utc::now().timestamp();
+  let loan_id = Uuid::new_v4().to_string();
+  let txn_id = Uuid::new_v4().to_string();
+  let scheme = txn_service::txn_no_scheme(pool).await?;
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+  let mut tx = pool.begin().await?;
+
+  let current = stock_repo::get_stock_tx(&mut tx, item_id, slot_id).await?;
+  let current_qty = current.map(|s| s.qty).unwrap_or(0);
+  if current_qty < qty {
+    return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+  }
+  stock_hold_service::ensure_available_tx(&mut tx, item_id, slot_id, current_qty, qty).await?;
+  expiry_service::ensure_not_expired_tx(&mut tx, item_id, slot_id, current_qty, qty, false).await?;
+
+  let loan_no = format!("LOAN-{:04}", txn_repo::next_txn_seq_tx(&mut tx, "", "LOAN").await?);
+  let txn_no = txn_service::next_txn_no(&mut tx, &scheme, "OUT", Some(slot_id)).await?;
+  let txn_note = match &note {
+    Some(note) => format!("借出 {}：{}（{}）", loan_no, borrower, note),
+    None => format!("借出 {}：{}", loan_no, borrower),
+  };
+  let row = txn_repo::TxnRow {
+    id: txn_id.clone(),
+    txn_no: txn_no.clone(),
+    txn_type: "OUT".to_string(),
+    occurred_at,
+    created_at: now,
+    operator_id: operator.id.clone(),
+    item_id: item_id.to_string(),
+    from_slot_id: Some(slot_id.to_string()),
+    to_slot_id: None,
+    qty,
+    actual_qty: None,
+    ref_txn_id: None,
+    note: Some(txn_note),
+    unit_cost: None,
+    location: None,
+    expires_at: None,
+    reason_id: None,
+  };
+  txn_repo::insert_txn(&mut tx, &row).await?;
+  stock_repo::upsert_stock_tx(&mut tx, item_id, slot_id, current_qty - qty, now).await?;
+
+  let loan = loan_repo::LoanRow {
+    id: loan_id.clone(),
+    loan_no: loan_no.clone(),
+    item_id: item_id.to_string(),
+    slot_id: slot_id.to_string(),
+    qty,
+    borrower: borrower.to_string(),
+    due_at,
+    status: "open".to_string(),
+    loaned_at: occurred_at,
+    operator_id: operator.id.clone(),
+    out_txn_id: txn_id,
+    returned_at: None,
+    return_slot_id: None,
+    return_operator_id: None,
+    return_txn_id: None,
+    note,
+    created_at: now,
+  };
+  loan_repo::insert_loan_tx(&mut tx, &loan).await?;
+
+  tx.commit().await?;
+  txn_service::touch_kiosk_operator(pool, &operator.id).await?;
+  Ok(LoanCreated {
+    loan_id,
+    loan_no,
+    txn_no,
+  })
+}
+
+/// 归还：回补到指定库位（默认借出库位），返回归还流水号
+pub async fn return_loan(
+  pool: &SqlitePool,
+  loan_id: &str,
+  return_slot_id: Option<String>,
+  occurred_at: i64,
+  actor_operator_id: &str,
+  note: Option<String>,
+) -> Result<String, AppError> {
+  let loan = get_open_loan(pool, loan_id).await?;
+  let slot_id = return_slot_id
+    .map(|id| id.trim().to_string())
+    .filter(|id| !id.is_empty())
+    .unwrap_or_else(|| loan.slot_id.clone());
+  rack_repo::get_slot_by_id(pool, &slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+  txn_service::ensure_kiosk_operator(pool, &operator.id).await?;
+
+  let now = Utc::now().timestamp();
+  let txn_id = Uuid::new_v4().to_string();
+  let scheme = txn_service::txn_no_scheme(pool).await?;
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+  let mut tx = pool.begin().await?;
+
+  let txn_no = txn_service::next_txn_no(&mut tx, &scheme, "IN", Some(&slot_id)).await?;
+  let txn_note = match &note {
+    Some(note) => format!("归还 {}：{}（{}）", loan.loan_no, loan.borrower, note),
+    None => format!("归还 {}：{}", loan.loan_no, loan.borrower),
+  };
+  let row = txn_repo::TxnRow {
+    id: txn_id.clone(),
+    txn_no: txn_no.clone(),
+    txn_type: "IN".to_string(),
+    occurred_at,
+    created_at: now,
+    operator_id: operator.id.clone(),
+    item_id: loan.item_id.clone(),
+    from_slot_id: None,
+    to_slot_id: Some(slot_id.clone()),
+    qty: loan.qty,
+    actual_qty: None,
+    ref_txn_id: None,
+    note: Some(txn_note),
+    // 归还不形成新的成本来源，沿用物品当前成本
+    unit_cost: item_repo::get_item_by_id(pool, &loan.item_id)
+      .await?
+      .and_then(|item| item.unit_cost),
+    location: None,
+    expires_at: None,
+    reason_id: None,
+  };
+  txn_repo::insert_txn(&mut tx, &row).await?;
+
+  let current = stock_repo::get_stock_tx(&mut tx, &loan.item_id, &slot_id).await?;
+  let next_qty = current.map(|s| s.qty).unwrap_or(0) + loan.qty;
+  stock_repo::upsert_stock_tx(&mut tx, &loan.item_id, &slot_id, next_qty, now).await?;
+
+  loan_repo::mark_returned_tx(&mut tx, &loan.id, occurred_at, &slot_id, &operator.id, &txn_id)
+    .await?;
+
+  tx.commit().await?;
+  txn_service::touch_kiosk_operator(pool, &operator.id).await?;
+  Ok(txn_no)
+}
+
+/// 读取借出中的记录（用于归还前的库位权限校验）
+pub async fn get_open_loan(pool: &SqlitePool, loan_id: &str) -> Result<loan_repo::LoanRow, AppError> {
+  let loan = loan_repo::get_loan_by_id(pool, loan_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "借用记录不存在"))?;
+  if loan.status != "open" {
+    return Err(AppError::new(ErrorCode::Conflict, "借用记录已归还"));
+  }
+  Ok(loan)
+}
+
+/// 借出中列表，overdue_only 仅返回已逾期记录
+pub async fn list_open_loans(
+  pool: &SqlitePool,
+  overdue_only: bool,
+  keyword: Option<String>,
+  item_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  page_index: i64,
+  page_size: i64,
+) -> Result<OpenLoanListResult, AppError> {
+  if page_index < 1 || page_size < 1 {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
+  }
+  let now = Utc::now().timestamp();
+  let keyword = keyword.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let total = loan_repo::count_open_loans(
+    pool,
+    now,
+    overdue_only,
+    keyword.clone(),
+    item_id.clone(),
+    allowed_warehouse_ids.clone(),
+  )
+  .await?;
+  let overdue_total =
+    loan_repo::count_open_loans(pool, now, true, None, None, allowed_warehouse_ids.clone()).await?;
+  let items = loan_repo::list_open_loans(
+    pool,
+    now,
+    overdue_only,
+    keyword,
+    item_id,
+    allowed_warehouse_ids,
+    page_index,
+    page_size,
+  )
+  .await?;
+  Ok(OpenLoanListResult {
+    items,
+    total,
+    overdue_total,
+  })
+}
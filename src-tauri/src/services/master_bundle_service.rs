@@ -0,0 +1,367 @@
+// 主数据包：导出仓库、货架、库位、物品（含别名与属性）、原因码与人员为单个 JSON 文件，供新站点按现有配置初始化
+use std::collections::HashMap;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::infra::crypto;
+use crate::repo::master_bundle_repo::{
+  self, BundleAttributeDef, BundleItem, BundleOperator, BundleRack, BundleReasonCode, BundleSlot, BundleWarehouse,
+};
+use crate::repo::{operator_repo, rack_repo, warehouse_repo};
+use crate::services::count_service;
+use crate::services::import_export_service::ExportResult;
+use crate::services::password_policy_service;
+
+pub const BUNDLE_FORMAT: &str = "inventory-control.master-bundle";
+pub const BUNDLE_VERSION: i64 = 1;
+
+/// 主数据包内容；不含库存、流水、照片与密码
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MasterBundle {
+  pub format: String,
+  pub version: i64,
+  pub exported_at: i64,
+  #[serde(default)]
+  pub warehouses: Vec<BundleWarehouse>,
+  #[serde(default)]
+  pub racks: Vec<BundleRack>,
+  #[serde(default)]
+  pub slots: Vec<BundleSlot>,
+  #[serde(default)]
+  pub item_attribute_defs: Vec<BundleAttributeDef>,
+  #[serde(default)]
+  pub items: Vec<BundleItem>,
+  #[serde(default)]
+  pub reason_codes: Vec<BundleReasonCode>,
+  #[serde(default)]
+  pub operators: Vec<BundleOperator>,
+}
+
+/// 单类数据的导入计数：已存在（按编码/名称/用户名匹配）的记录跳过
+#[derive(Debug, Default, Serialize)]
+pub struct BundleImportCount {
+  pub created: i64,
+  pub skipped: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MasterBundleImportResult {
+  pub warehouses: BundleImportCount,
+  pub racks: BundleImportCount,
+  pub slots: BundleImportCount,
+  pub item_attribute_defs: BundleImportCount,
+  pub items: BundleImportCount,
+  pub reason_codes: BundleImportCount,
+  pub operators: BundleImportCount,
+}
+
+pub async fn export_master_bundle(pool: &SqlitePool) -> Result<ExportResult, AppError> {
+  let mut aliases: HashMap<String, Vec<master_bundle_repo::BundleItemAlias>> = HashMap::new();
+  for (item_id, alias) in master_bundle_repo::list_item_aliases(pool).await? {
+    aliases.entry(item_id).or_default().push(alias);
+  }
+  let mut attributes: HashMap<String, Vec<(String, String)>> = HashMap::new();
+  for (item_id, name, value) in master_bundle_repo::list_item_attribute_values(pool).await? {
+    attributes.entry(item_id).or_default().push((name, value));
+  }
+  let items = master_bundle_repo::list_items(pool)
+    .await?
+    .into_iter()
+    .map(|(item_id, mut item)| {
+      item.aliases = aliases.remove(&item_id).unwrap_or_default();
+      item.attributes = attributes.remove(&item_id).unwrap_or_default();
+      item
+    })
+    .collect();
+
+  let mut scopes: HashMap<String, Vec<String>> = HashMap::new();
+  for (operator_id, warehouse_code) in master_bundle_repo::list_operator_warehouses(pool).await? {
+    scopes.entry(operator_id).or_default().push(warehouse_code);
+  }
+  let operators = master_bundle_repo::list_operators(pool)
+    .await?
+    .into_iter()
+    .map(|(operator_id, mut operator)| {
+      operator.warehouse_codes = scopes.remove(&operator_id).unwrap_or_default();
+      operator
+    })
+    .collect();
+
+  let now = Utc::now().timestamp();
+  let bundle = MasterBundle {
+    format: BUNDLE_FORMAT.to_string(),
+    version: BUNDLE_VERSION,
+    exported_at: now,
+    warehouses: master_bundle_repo::list_warehouses(pool).await?,
+    racks: master_bundle_repo::list_racks(pool).await?,
+    slots: master_bundle_repo::list_slots(pool).await?,
+    item_attribute_defs: master_bundle_repo::list_attribute_defs(pool).await?,
+    items,
+    reason_codes: master_bundle_repo::list_reason_codes(pool).await?,
+    operators,
+  };
+
+  let file_path = count_service::export_dir(pool)
+    .await?
+    .join(format!("master_bundle_{}.json", now));
+  let content = serde_json::to_vec_pretty(&bundle)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+  std::fs::write(&file_path, content).map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
+
+  Ok(ExportResult {
+    file_path: file_path.to_string_lossy().to_string(),
+  })
+}
+
+/// 导入主数据包：按编码/名称/用户名合并，已存在的记录保持不变，已存在货架的库位一并跳过；
+/// 可登录的新账号统一使用 initial_password 并在首次登录时强制改密，记录人账号不设密码；
+/// 全部写入在同一事务内完成，任一记录有误则整体不导入
+pub async fn import_master_bundle(
+  pool: &SqlitePool,
+  file_path: &str,
+  initial_password: Option<String>,
+) -> Result<MasterBundleImportResult, AppError> {
+  let content = std::fs::read(file_path).map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ImportReadFailed))?;
+  let bundle: MasterBundle = serde_json::from_slice(&content)
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "主数据包格式无法识别"))?;
+  if bundle.format != BUNDLE_FORMAT {
+    return Err(AppError::new(ErrorCode::ValidationError, "所选文件不是主数据包"));
+  }
+  if bundle.version > BUNDLE_VERSION {
+    return Err(AppError::new(ErrorCode::ValidationError, "主数据包版本高于当前程序，请先升级"));
+  }
+
+  // 密码校验与哈希较慢且需读取密码策略，在事务开始前完成
+  let initial_password = initial_password
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty());
+  let mut password_hashes: HashMap<String, (String, bool)> = HashMap::new();
+  for operator in &bundle.operators {
+    if !matches!(operator.role.as_str(), "admin" | "keeper" | "viewer" | "member") {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidRole));
+    }
+    validate_status(&operator.status, &["active", "inactive"])?;
+    if operator_repo::count_by_username(pool, &operator.username).await? > 0 {
+      continue;
+    }
+    let entry = if operator.role == "member" {
+      (crypto::hash_password(&Uuid::new_v4().to_string())?, false)
+    } else {
+      let password = initial_password
+        .as_deref()
+        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "主数据包包含可登录的新账号，请设置初始密码"))?;
+      password_policy_service::validate_new_password(pool, None, &operator.username, password).await?;
+      (crypto::hash_password(password)?, true)
+    };
+    password_hashes.insert(operator.username.clone(), entry);
+  }
+
+  let now = Utc::now().timestamp();
+  let mut result = MasterBundleImportResult::default();
+  let mut tx = pool.begin().await?;
+
+  // 仓库：先固定仓库后移动仓库，保证归属仓库已就绪
+  let mut warehouse_ids: HashMap<String, String> = HashMap::new();
+  let mut warehouses: Vec<&BundleWarehouse> = bundle.warehouses.iter().collect();
+  warehouses.sort_by_key(|warehouse| warehouse.kind == "mobile");
+  for warehouse in warehouses {
+    validate_status(&warehouse.status, &["active", "inactive"])?;
+    if let Some(id) = master_bundle_repo::warehouse_id_by_code_tx(&mut tx, &warehouse.code).await? {
+      warehouse_ids.insert(warehouse.code.clone(), id);
+      result.warehouses.skipped += 1;
+      continue;
+    }
+    let base_warehouse_id = match (warehouse.kind.as_str(), warehouse.base_warehouse_code.as_deref()) {
+      ("fixed", _) => None,
+      ("mobile", Some(base_code)) => Some(
+        warehouse_ids
+          .get(base_code)
+          .cloned()
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, format!("移动仓库 {} 的归属仓库不存在", warehouse.code)))?,
+      ),
+      ("mobile", None) => {
+        return Err(AppError::new(ErrorCode::ValidationError, format!("移动仓库 {} 缺少归属仓库", warehouse.code)));
+      }
+      _ => return Err(AppError::new(ErrorCode::ValidationError, "仓库类型仅支持 fixed/mobile")),
+    };
+    let id = Uuid::new_v4().to_string();
+    warehouse_repo::insert_warehouse_tx(
+      &mut tx,
+      &id,
+      &warehouse.code,
+      &warehouse.name,
+      &warehouse.status,
+      &warehouse.kind,
+      base_warehouse_id.as_deref(),
+      now,
+    )
+    .await?;
+    warehouse_ids.insert(warehouse.code.clone(), id);
+    result.warehouses.created += 1;
+  }
+
+  // 货架：已存在的货架连同其库位跳过，不改动现有布局
+  let mut new_racks: HashMap<(String, String), (String, String)> = HashMap::new();
+  for rack in &bundle.racks {
+    let warehouse_id = lookup_warehouse(&mut tx, &mut warehouse_ids, &rack.warehouse_code).await?;
+    if master_bundle_repo::rack_id_by_code_tx(&mut tx, &warehouse_id, &rack.code).await?.is_some() {
+      result.racks.skipped += 1;
+      continue;
+    }
+    validate_status(&rack.status, &["active", "inactive"])?;
+    if rack.level_count < 1 || rack.slots_per_level < 1 {
+      return Err(AppError::new(ErrorCode::ValidationError, "层数或格数非法"));
+    }
+    let id = Uuid::new_v4().to_string();
+    rack_repo::insert_rack_tx(
+      &mut tx,
+      &rack_repo::RackRow {
+        id: id.clone(),
+        code: rack.code.clone(),
+        name: rack.name.clone(),
+        warehouse_id: Some(warehouse_id.clone()),
+        location: rack.location.clone(),
+        status: rack.status.clone(),
+        level_count: rack.level_count,
+        slots_per_level: rack.slots_per_level,
+        created_at: now,
+      },
+    )
+    .await?;
+    new_racks.insert((rack.warehouse_code.clone(), rack.code.clone()), (id, warehouse_id));
+    result.racks.created += 1;
+  }
+
+  let mut slots = Vec::new();
+  for slot in &bundle.slots {
+    let Some((rack_id, warehouse_id)) = new_racks.get(&(slot.warehouse_code.clone(), slot.rack_code.clone())) else {
+      result.slots.skipped += 1;
+      continue;
+    };
+    validate_status(&slot.status, &["active", "inactive"])?;
+    if master_bundle_repo::slot_id_by_code_tx(&mut tx, &slot.code).await?.is_some() {
+      return Err(AppError::new(ErrorCode::Conflict, format!("库位编码已存在：{}", slot.code)));
+    }
+    slots.push(rack_repo::SlotRow {
+      id: Uuid::new_v4().to_string(),
+      rack_id: rack_id.clone(),
+      warehouse_id: Some(warehouse_id.clone()),
+      level_no: slot.level_no,
+      slot_no: slot.slot_no,
+      code: slot.code.clone(),
+      status: slot.status.clone(),
+      created_at: now,
+    });
+  }
+  result.slots.created = slots.len() as i64;
+  rack_repo::insert_slots_tx(&mut tx, slots).await?;
+
+  let mut attribute_ids: HashMap<String, String> = HashMap::new();
+  for def in &bundle.item_attribute_defs {
+    if let Some(id) = master_bundle_repo::attribute_def_id_by_name_tx(&mut tx, &def.name).await? {
+      attribute_ids.insert(def.name.clone(), id);
+      result.item_attribute_defs.skipped += 1;
+      continue;
+    }
+    if !matches!(def.data_type.as_str(), "text" | "number" | "date") {
+      return Err(AppError::new(ErrorCode::ValidationError, "属性类型仅支持 text/number/date"));
+    }
+    validate_status(&def.status, &["active", "disabled"])?;
+    let id = Uuid::new_v4().to_string();
+    master_bundle_repo::insert_attribute_def_tx(&mut tx, &id, def, now).await?;
+    attribute_ids.insert(def.name.clone(), id);
+    result.item_attribute_defs.created += 1;
+  }
+
+  // 物品：已存在的物品不补充别名与属性；别名编码已被占用时跳过该别名
+  for item in &bundle.items {
+    if master_bundle_repo::item_id_by_code_tx(&mut tx, &item.item_code).await?.is_some() {
+      result.items.skipped += 1;
+      continue;
+    }
+    if item.item_code.trim().is_empty() || item.name.trim().is_empty() {
+      return Err(AppError::new(ErrorCode::ValidationError, "物品编码或名称不能为空"));
+    }
+    quantity::validate_precision(item.qty_precision)?;
+    validate_status(&item.status, &["active", "inactive"])?;
+    let id = Uuid::new_v4().to_string();
+    master_bundle_repo::insert_item_tx(&mut tx, &id, item, now).await?;
+    for alias in &item.aliases {
+      if master_bundle_repo::alias_id_by_code_tx(&mut tx, &alias.code).await?.is_some() {
+        continue;
+      }
+      master_bundle_repo::insert_item_alias_tx(&mut tx, &Uuid::new_v4().to_string(), &id, alias, now).await?;
+    }
+    for (name, value) in &item.attributes {
+      let attr_id = match attribute_ids.get(name) {
+        Some(attr_id) => Some(attr_id.clone()),
+        None => master_bundle_repo::attribute_def_id_by_name_tx(&mut tx, name).await?,
+      };
+      if let Some(attr_id) = attr_id {
+        master_bundle_repo::insert_item_attribute_value_tx(&mut tx, &id, &attr_id, value).await?;
+      }
+    }
+    result.items.created += 1;
+  }
+
+  for reason in &bundle.reason_codes {
+    if master_bundle_repo::reason_code_id_by_code_tx(&mut tx, &reason.code).await?.is_some() {
+      result.reason_codes.skipped += 1;
+      continue;
+    }
+    validate_status(&reason.status, &["active", "disabled"])?;
+    master_bundle_repo::insert_reason_code_tx(&mut tx, &Uuid::new_v4().to_string(), reason, now).await?;
+    result.reason_codes.created += 1;
+  }
+
+  for operator in &bundle.operators {
+    let Some((password_hash, must_change_pwd)) = password_hashes.get(&operator.username) else {
+      result.operators.skipped += 1;
+      continue;
+    };
+    if master_bundle_repo::operator_id_by_username_tx(&mut tx, &operator.username).await?.is_some() {
+      result.operators.skipped += 1;
+      continue;
+    }
+    let id = Uuid::new_v4().to_string();
+    master_bundle_repo::insert_operator_tx(&mut tx, &id, operator, password_hash, *must_change_pwd, now).await?;
+    for warehouse_code in &operator.warehouse_codes {
+      let warehouse_id = lookup_warehouse(&mut tx, &mut warehouse_ids, warehouse_code).await?;
+      master_bundle_repo::insert_operator_warehouse_tx(&mut tx, &id, &warehouse_id, now).await?;
+    }
+    result.operators.created += 1;
+  }
+
+  tx.commit().await?;
+  Ok(result)
+}
+
+/// 按编码查找仓库 id：优先取本次导入已解析的仓库，其次查询现有数据
+async fn lookup_warehouse(
+  tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+  warehouse_ids: &mut HashMap<String, String>,
+  code: &str,
+) -> Result<String, AppError> {
+  if let Some(id) = warehouse_ids.get(code) {
+    return Ok(id.clone());
+  }
+  let id = master_bundle_repo::warehouse_id_by_code_tx(tx, code)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, format!("仓库不存在：{}", code)))?;
+  warehouse_ids.insert(code.to_string(), id.clone());
+  Ok(id)
+}
+
+fn validate_status(status: &str, allowed: &[&str]) -> Result<(), AppError> {
+  if allowed.contains(&status) {
+    Ok(())
+  } else {
+    Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus))
+  }
+}
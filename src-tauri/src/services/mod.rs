@@ -1,13 +1,47 @@
 pub mod auth_service;
 pub mod audit_service;
+pub mod asn_service;
 pub mod dashboard_service;
+pub mod expiry_service;
+pub mod filter_option_service;
 pub mod item_service;
+pub mod item_alias_service;
+pub mod item_attribute_service;
+pub mod label_service;
 pub mod operator_service;
 pub mod photo_service;
 pub mod rack_service;
+pub mod rack_map_service;
+pub mod structure_tree_service;
 pub mod warehouse_service;
 pub mod txn_service;
+pub mod idempotency_service;
+pub mod loan_service;
+pub mod outbound_pick_service;
 pub mod system_service;
+pub mod health_service;
+pub mod diagnostics_service;
+pub mod demo_service;
+pub mod remote_backup_service;
+pub mod backup_encryption_service;
 pub mod stock_service;
+pub mod stock_close_service;
+pub mod stock_hold_service;
+pub mod reason_code_service;
+pub mod stock_threshold_service;
+pub mod count_service;
+pub mod cycle_count_service;
+pub mod pick_list_service;
 pub mod import_export_service;
+pub mod master_bundle_service;
+pub mod copy_service;
+pub mod valuation_service;
+pub mod report_service;
+pub mod report_schedule_service;
 pub mod permission_service;
+pub mod bootstrap_service;
+pub mod password_policy_service;
+pub mod session_service;
+pub mod undo_service;
+pub mod approval_service;
+pub mod notification_service;
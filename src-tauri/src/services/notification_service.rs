@@ -0,0 +1,142 @@
+// 通知中心：低库存、定时备份失败、定时报表失败、审批申请、借用逾期写入通知，前端通过事件刷新未读角标
+use chrono::{Local, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::AppError;
+use crate::repo::approval_repo::ApprovalRequestRow;
+use crate::repo::notification_repo::{self, NotificationRow};
+use crate::repo::report_schedule_repo::ReportScheduleRunRow;
+use crate::repo::{loan_repo, meta_repo, stock_query_repo};
+
+/// 列表最多返回的条数
+const MAX_LIST: i64 = 200;
+/// 已读通知保留天数
+const READ_RETENTION_DAYS: i64 = 30;
+/// 每次扫描最多处理的逾期借用数
+const MAX_OVERDUE_SCAN: i64 = 500;
+
+#[derive(Debug, serde::Serialize)]
+pub struct NotificationListResult {
+  pub items: Vec<NotificationRow>,
+  pub unread_total: i64,
+}
+
+/// 写入通知，dedup_key 相同的通知只写入一次；返回是否新增
+pub async fn notify(
+  pool: &SqlitePool,
+  kind: &str,
+  title: String,
+  body: Option<String>,
+  ref_id: Option<String>,
+  dedup_key: Option<String>,
+) -> Result<bool, AppError> {
+  notification_repo::insert_notification(
+    pool,
+    &NotificationRow {
+      id: Uuid::new_v4().to_string(),
+      kind: kind.to_string(),
+      title,
+      body,
+      ref_id,
+      dedup_key,
+      created_at: Utc::now().timestamp(),
+      read_at: None,
+    },
+  )
+  .await
+}
+
+/// 新的审批申请通知审批人
+pub async fn notify_approval_request(pool: &SqlitePool, request: &ApprovalRequestRow) -> Result<bool, AppError> {
+  let body = match request.reason.as_deref() {
+    Some(reason) => format!("{}（原因：{}）", request.summary, reason),
+    None => request.summary.clone(),
+  };
+  notify(
+    pool,
+    "approval_request",
+    "新的审批申请".to_string(),
+    Some(body),
+    Some(request.id.clone()),
+    Some(format!("approval_request:{}", request.id)),
+  )
+  .await
+}
+
+/// 定时备份失败通知
+pub async fn notify_backup_failed(pool: &SqlitePool, error: &AppError) -> Result<bool, AppError> {
+  notify(pool, "backup_failed", "定时备份失败".to_string(), Some(error.message.clone()), None, None).await
+}
+
+/// 定时报表执行失败通知（每次执行一次）
+pub async fn notify_report_failed(pool: &SqlitePool, run: &ReportScheduleRunRow) -> Result<bool, AppError> {
+  notify(
+    pool,
+    "report_failed",
+    format!("定时报表失败：{}", run.schedule_name),
+    run.error.clone(),
+    run.schedule_id.clone(),
+    Some(format!("report_failed:{}", run.id)),
+  )
+  .await
+}
+
+/// 定时扫描：低库存（每个物品每天最多一次）与借用逾期（每笔借用一次），并清理过期的已读通知；返回新增条数
+pub async fn scan(pool: &SqlitePool) -> Result<usize, AppError> {
+  let mut created = 0;
+  let threshold = meta_repo::get_meta_i64(pool, "low_stock_threshold")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(0);
+  if threshold > 0 {
+    let today = Local::now().format("%Y-%m-%d").to_string();
+    for (item_id, item_code, item_name, qty) in stock_query_repo::list_low_stock_items(pool, threshold).await? {
+      let inserted = notify(
+        pool,
+        "low_stock",
+        format!("低库存：{}", item_code),
+        Some(format!("{} 当前库存 {}，低于阈值 {}", item_name, qty, threshold)),
+        Some(item_id.clone()),
+        Some(format!("low_stock:{}:{}", item_id, today)),
+      )
+      .await?;
+      created += usize::from(inserted);
+    }
+  }
+
+  let now = Utc::now().timestamp();
+  let loans = loan_repo::list_open_loans(pool, now, true, None, None, None, 1, MAX_OVERDUE_SCAN).await?;
+  for loan in loans {
+    let inserted = notify(
+      pool,
+      "loan_overdue",
+      format!("借用逾期：{}", loan.loan_no),
+      Some(format!(
+        "{} 借用 {} {} {}，已超过应还时间",
+        loan.borrower,
+        loan.item_code,
+        loan.item_name,
+        loan.qty
+      )),
+      Some(loan.id.clone()),
+      Some(format!("loan_overdue:{}", loan.id)),
+    )
+    .await?;
+    created += usize::from(inserted);
+  }
+
+  notification_repo::prune_read(pool, now - READ_RETENTION_DAYS * 86400).await?;
+  Ok(created)
+}
+
+pub async fn list_notifications(pool: &SqlitePool, unread_only: bool) -> Result<NotificationListResult, AppError> {
+  let items = notification_repo::list_notifications(pool, unread_only, MAX_LIST).await?;
+  let unread_total = notification_repo::count_unread(pool).await?;
+  Ok(NotificationListResult { items, unread_total })
+}
+
+/// 标记已读；ids 为 None 时标记全部，返回更新条数
+pub async fn mark_read(pool: &SqlitePool, ids: Option<Vec<String>>) -> Result<u64, AppError> {
+  notification_repo::mark_read(pool, ids.as_deref(), Utc::now().timestamp()).await
+}
@@ -3,9 +3,32 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 use crate::infra::crypto;
+use crate::repo::audit_repo::{self, AuditLogFilter, AuditLogRow};
 use crate::repo::operator_repo::{self, OperatorRow};
-use crate::repo::meta_repo;
+use crate::repo::reset_code_repo::{self, ResetCodeRow};
+use crate::repo::txn_repo::{self, TxnListRow};
+use crate::repo::{meta_repo, operator_warehouse_repo, warehouse_repo};
+use crate::services::{auth_service, password_policy_service, permission_service};
+
+const ROLES: [&str; 4] = ["admin", "keeper", "viewer", "member"];
+
+/// 重置码长度与默认/最长有效期（分钟）
+const RESET_CODE_LEN: usize = 8;
+const DEFAULT_RESET_CODE_MINUTES: i64 = 30;
+const MAX_RESET_CODE_MINUTES: i64 = 24 * 60;
+
+/// 本人资料中最近操作记录与最近流水的条数
+const PROFILE_ACTIVITY_LIMIT: i64 = 20;
+const PROFILE_TXN_LIMIT: i64 = 10;
+
+/// 签发重置码返回结构（明文仅返回这一次）
+#[derive(Debug, serde::Serialize)]
+pub struct ResetCodeIssued {
+  pub code: String,
+  pub expires_at: i64,
+}
 
 #[derive(Debug, serde::Serialize)]
 pub struct OperatorListResult {
@@ -44,12 +67,12 @@ pub async fn create_operator(
     "admin".to_string()
   };
   if !matches!(role.as_str(), "admin" | "keeper" | "viewer" | "member") {
-    return Err(AppError::new(ErrorCode::ValidationError, "角色非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidRole));
   }
 
   let status = status.unwrap_or_else(|| "active".to_string());
   if !matches!(status.as_str(), "active" | "inactive") {
-    return Err(AppError::new(ErrorCode::ValidationError, "状态非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
   }
 
   let count = operator_repo::count_by_username(pool, username).await?;
@@ -63,6 +86,9 @@ pub async fn create_operator(
   if role.as_str() != "member" && password_trimmed.is_empty() {
     return Err(AppError::new(ErrorCode::ValidationError, "初始密码不能为空"));
   }
+  if !password_trimmed.is_empty() {
+    password_policy_service::validate_new_password(pool, None, username, password_trimmed).await?;
+  }
   let (password_hash, must_change_pwd) = if password_trimmed.is_empty() {
     (crypto::hash_password(&Uuid::new_v4().to_string())?, false)
   } else {
@@ -81,6 +107,9 @@ pub async fn create_operator(
     now,
   )
   .await?;
+  if must_change_pwd {
+    password_policy_service::record_history(pool, &id, &password_hash, now).await?;
+  }
 
   Ok(())
 }
@@ -117,13 +146,328 @@ pub async fn reset_operator_password(
   if new_password.trim().is_empty() {
     return Err(AppError::new(ErrorCode::ValidationError, "新密码不能为空"));
   }
+  let operator = operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound))?;
+  password_policy_service::validate_new_password(pool, Some(id), &operator.username, new_password)
+    .await?;
 
   let now = Utc::now().timestamp();
   let password_hash = crypto::hash_password(new_password)?;
   operator_repo::reset_operator_password(pool, id, &password_hash, now).await?;
+  password_policy_service::record_history(pool, id, &password_hash, now).await?;
+  Ok(())
+}
+
+/// 为人员签发一次性重置码，人员在登录页凭码自行设置新密码，管理员不接触新密码
+pub async fn issue_reset_code(
+  pool: &SqlitePool,
+  id: &str,
+  issued_by: &str,
+  ttl_minutes: Option<i64>,
+) -> Result<ResetCodeIssued, AppError> {
+  let ttl_minutes = ttl_minutes.unwrap_or(DEFAULT_RESET_CODE_MINUTES);
+  if !(1..=MAX_RESET_CODE_MINUTES).contains(&ttl_minutes) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("有效期需在 1-{} 分钟之间", MAX_RESET_CODE_MINUTES),
+    ));
+  }
+  let operator = operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound))?;
+  if operator.status != "active" {
+    return Err(AppError::new(ErrorCode::InactiveResource, "人员已停用"));
+  }
+
+  let now = Utc::now().timestamp();
+  let code = crypto::generate_reset_code(RESET_CODE_LEN);
+  let expires_at = now + ttl_minutes * 60;
+  reset_code_repo::upsert_reset_code(
+    pool,
+    &ResetCodeRow {
+      operator_id: operator.id,
+      code_hash: crypto::hash_password(&code)?,
+      issued_by: issued_by.to_string(),
+      issued_at: now,
+      expires_at,
+    },
+  )
+  .await?;
+  Ok(ResetCodeIssued { code, expires_at })
+}
+
+/// 解除人员的登录失败锁定
+pub async fn unlock_operator(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  let operator = operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound))?;
+  auth_service::unlock_login(pool, &operator.username).await?;
   Ok(())
 }
 
+/// 读取人员的仓库分配（空列表表示不限仓库）
+pub async fn get_operator_warehouses(pool: &SqlitePool, id: &str) -> Result<Vec<String>, AppError> {
+  operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound))?;
+  operator_warehouse_repo::list_warehouse_ids(pool, id).await
+}
+
+/// 设置人员的仓库分配，传空列表即取消限制
+pub async fn set_operator_warehouses(
+  pool: &SqlitePool,
+  id: &str,
+  warehouse_ids: &[String],
+) -> Result<(), AppError> {
+  operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound))?;
+
+  let mut normalized: Vec<String> = Vec::new();
+  for warehouse_id in warehouse_ids {
+    let warehouse_id = warehouse_id.trim();
+    if warehouse_id.is_empty() || normalized.iter().any(|existing| existing == warehouse_id) {
+      continue;
+    }
+    warehouse_repo::get_warehouse_by_id(pool, warehouse_id)
+      .await?
+      .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound))?;
+    normalized.push(warehouse_id.to_string());
+  }
+
+  let now = Utc::now().timestamp();
+  operator_warehouse_repo::replace_warehouse_ids(pool, id, &normalized, now).await
+}
+
+/// 本人资料中的仓库范围
+#[derive(Debug, serde::Serialize)]
+pub struct ProfileWarehouse {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+}
+
+/// 本人资料：账号信息、仓库范围与最近活动，无需管理员权限
+#[derive(Debug, serde::Serialize)]
+pub struct MyProfile {
+  #[serde(flatten)]
+  pub operator: OperatorRow,
+  pub rbac_enabled: bool,
+  pub has_pin: bool,
+  // 空列表表示不限仓库
+  pub warehouses: Vec<ProfileWarehouse>,
+  pub recent_activity: Vec<AuditLogRow>,
+  pub recent_txns: Vec<TxnListRow>,
+}
+
+pub async fn get_my_profile(pool: &SqlitePool, id: &str) -> Result<MyProfile, AppError> {
+  let operator = operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
+  let has_pin = operator_repo::get_pin_hash(pool, id).await?.is_some();
+
+  let mut warehouses = Vec::new();
+  for warehouse_id in operator_warehouse_repo::list_warehouse_ids(pool, id).await? {
+    if let Some(warehouse) = warehouse_repo::get_warehouse_by_id(pool, &warehouse_id).await? {
+      warehouses.push(ProfileWarehouse {
+        id: warehouse.id,
+        code: warehouse.code,
+        name: warehouse.name,
+      });
+    }
+  }
+
+  let filter = AuditLogFilter {
+    actor_operator_id: Some(id.to_string()),
+    ..Default::default()
+  };
+  let recent_activity = audit_repo::list_audit_logs(pool, &filter, 1, PROFILE_ACTIVITY_LIMIT).await?;
+  let recent_txns = txn_repo::list_txns(
+    pool,
+    None,
+    None,
+    None,
+    None,
+    None,
+    None,
+    Some(id.to_string()),
+    None,
+    None,
+    None,
+    None,
+    1,
+    PROFILE_TXN_LIMIT,
+    None,
+    None,
+    None,
+  )
+  .await?;
+
+  Ok(MyProfile {
+    operator,
+    rbac_enabled: rbac_enabled(pool).await?,
+    has_pin,
+    warehouses,
+    recent_activity,
+    recent_txns,
+  })
+}
+
+/// 本人修改显示名（角色、状态与仓库范围仍需管理员调整）
+pub async fn update_my_profile(pool: &SqlitePool, id: &str, display_name: &str) -> Result<(), AppError> {
+  let display_name = display_name.trim();
+  if display_name.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "姓名不能为空"));
+  }
+  let operator = operator_repo::get_operator_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
+  if operator.status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ActorInactive));
+  }
+  operator_repo::update_operator(pool, id, display_name, None).await
+}
+
+/// 批量调整角色的单项（未列出的人员保持原角色）
+#[derive(Debug, Clone)]
+pub struct RoleAssignment {
+  pub id: String,
+  pub role: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RbacPreviewOperator {
+  pub id: String,
+  pub username: String,
+  pub display_name: String,
+  pub status: String,
+  pub current_role: String,
+  pub next_role: String,
+  pub changed: bool,
+  // 按调整后角色在 RBAC 开启时拥有的权限点（停用人员为空）
+  pub permissions: Vec<String>,
+}
+
+/// RBAC 启用预览：调整后的角色、权限与阻断原因
+#[derive(Debug, serde::Serialize)]
+pub struct RbacEnablePreview {
+  pub rbac_enabled: bool,
+  pub operators: Vec<RbacPreviewOperator>,
+  pub changed_count: i64,
+  pub active_admin_count: i64,
+  // 阻断项：存在时不允许应用（避免锁死管理入口）
+  pub blockers: Vec<String>,
+  // 提示项：不影响应用
+  pub warnings: Vec<String>,
+}
+
+/// 预览批量调整角色并开启 RBAC 后的效果
+pub async fn preview_rbac_enable(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  assignments: &[RoleAssignment],
+) -> Result<RbacEnablePreview, AppError> {
+  let operators = operator_repo::list_all_operators(pool).await?;
+  for assignment in assignments {
+    if !ROLES.contains(&assignment.role.as_str()) {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidRole));
+    }
+    if !operators.iter().any(|operator| operator.id == assignment.id) {
+      return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound));
+    }
+  }
+
+  let mut items = Vec::with_capacity(operators.len());
+  for operator in operators {
+    // 同一人员出现多次时以最后一项为准
+    let next_role = assignments
+      .iter()
+      .rev()
+      .find(|assignment| assignment.id == operator.id)
+      .map(|assignment| assignment.role.clone())
+      .unwrap_or_else(|| operator.role.clone());
+    let permissions = if operator.status == "active" {
+      permission_service::effective_permissions_for_role(pool, &next_role).await?
+    } else {
+      Vec::new()
+    };
+    items.push(RbacPreviewOperator {
+      changed: next_role != operator.role,
+      id: operator.id,
+      username: operator.username,
+      display_name: operator.display_name,
+      status: operator.status,
+      current_role: operator.role,
+      next_role,
+      permissions,
+    });
+  }
+
+  let active: Vec<&RbacPreviewOperator> =
+    items.iter().filter(|operator| operator.status == "active").collect();
+  let active_admin_count = active
+    .iter()
+    .filter(|operator| operator.next_role == "admin")
+    .count() as i64;
+
+  let mut blockers = Vec::new();
+  if active_admin_count == 0 {
+    blockers.push("调整后没有启用状态的管理员".to_string());
+  }
+  if !active
+    .iter()
+    .any(|operator| operator.id == actor_operator_id && operator.next_role == "admin")
+  {
+    blockers.push("当前操作人调整后不再是管理员，将无法继续管理".to_string());
+  }
+
+  let mut warnings = Vec::new();
+  if !active.is_empty() && active.iter().all(|operator| operator.next_role == "admin") {
+    warnings.push("所有启用人员仍为管理员，开启 RBAC 不会限制任何操作".to_string());
+  }
+  let unassigned = active
+    .iter()
+    .filter(|operator| operator.next_role == "member")
+    .count();
+  if unassigned > 0 {
+    warnings.push(format!("{} 名人员为 member 角色，仅可录入流水与查看", unassigned));
+  }
+
+  Ok(RbacEnablePreview {
+    rbac_enabled: rbac_enabled(pool).await?,
+    changed_count: items.iter().filter(|operator| operator.changed).count() as i64,
+    operators: items,
+    active_admin_count,
+    blockers,
+    warnings,
+  })
+}
+
+/// 批量调整角色，可选在同一事务内开启 RBAC；存在阻断项时拒绝执行
+pub async fn bulk_set_operator_roles(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  assignments: &[RoleAssignment],
+  enable_rbac: bool,
+) -> Result<RbacEnablePreview, AppError> {
+  let preview = preview_rbac_enable(pool, actor_operator_id, assignments).await?;
+  if let Some(blocker) = preview.blockers.first() {
+    return Err(AppError::new(ErrorCode::ValidationError, blocker.clone()));
+  }
+
+  let mut tx = pool.begin().await?;
+  for operator in preview.operators.iter().filter(|operator| operator.changed) {
+    operator_repo::set_operator_role_tx(&mut tx, &operator.id, &operator.next_role).await?;
+  }
+  if enable_rbac {
+    meta_repo::set_meta_value_tx(&mut tx, "rbac_enabled", "1").await?;
+  }
+  tx.commit().await?;
+
+  preview_rbac_enable(pool, actor_operator_id, &[]).await
+}
+
 async fn rbac_enabled(pool: &SqlitePool) -> Result<bool, AppError> {
   let rbac = meta_repo::get_meta_value(pool, "rbac_enabled")
     .await?
@@ -133,7 +477,7 @@ async fn rbac_enabled(pool: &SqlitePool) -> Result<bool, AppError> {
 
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
   if page_index < 1 || page_size < 1 {
-    return Err(AppError::new(ErrorCode::ValidationError, "分页参数非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
   }
   Ok((page_index, page_size))
 }
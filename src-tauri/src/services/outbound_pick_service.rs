@@ -0,0 +1,288 @@
+// 出库拣货确认：先登记计划数量（不扣库存），拣货后按实拣数量生成 OUT 流水；
+// 实拣少于计划时记录短拣差异，可选自动生成 ADJUST 流水冲减账面库存
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::repo::outbound_pick_repo::{self, OutboundPickListRow, OutboundPickRow, PickDiscrepancyRow};
+use crate::repo::{item_repo, rack_repo, stock_repo, txn_repo};
+use crate::services::{approval_service, expiry_service, stock_hold_service, txn_service};
+
+pub const PICK_STATUSES: [&str; 3] = ["pending", "confirmed", "cancelled"];
+
+const MAX_SHORT_REASON_LEN: usize = 200;
+/// 列表最多返回的条数
+const MAX_LIST: i64 = 500;
+
+#[derive(Debug, serde::Serialize)]
+pub struct PickCreated {
+  pub pick_id: String,
+  pub pick_no: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct PickConfirmed {
+  pub pick_no: String,
+  // 实拣为 0 时不生成出库流水
+  pub out_txn_no: Option<String>,
+  // 短拣数量（显示数量），无短拣时为 0
+  pub short_qty: f64,
+  // 自动调整生成的 ADJUST 流水号
+  pub adjust_txn_no: Option<String>,
+}
+
+/// 出库申请：校验物品、库位与当前库存，登记计划数量
+pub async fn create_pick(
+  pool: &SqlitePool,
+  item_id: &str,
+  slot_id: &str,
+  planned_qty: i64,
+  actor_operator_id: &str,
+  note: Option<String>,
+) -> Result<PickCreated, AppError> {
+  if planned_qty <= 0 {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+  }
+  let item = item_repo::get_item_by_id(pool, item_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  if item.status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ItemInactive));
+  }
+  rack_repo::get_slot_by_id(pool, slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+
+  let now = Utc::now().timestamp();
+  let pick_id = Uuid::new_v4().to_string();
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+  let mut tx = pool.begin().await?;
+  let current_qty = stock_repo::get_stock_tx(&mut tx, item_id, slot_id).await?.map(|s| s.qty).unwrap_or(0);
+  if current_qty < planned_qty {
+    return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+  }
+  stock_hold_service::ensure_available_tx(&mut tx, item_id, slot_id, current_qty, planned_qty).await?;
+  let pick_no = format!("PICK-{:04}", txn_repo::next_txn_seq_tx(&mut tx, "", "PICK").await?);
+  outbound_pick_repo::insert_pick_tx(
+    &mut tx,
+    &OutboundPickRow {
+      id: pick_id.clone(),
+      pick_no: pick_no.clone(),
+      item_id: item_id.to_string(),
+      slot_id: slot_id.to_string(),
+      planned_qty,
+      status: "pending".to_string(),
+      note,
+      requested_by: operator.id.clone(),
+      requested_at: now,
+    },
+  )
+  .await?;
+  tx.commit().await?;
+  Ok(PickCreated { pick_id, pick_no })
+}
+
+/// 拣货确认：按实拣数量出库；短拣须填写原因并记录差异，auto_adjust 时按短拣数量冲减账面库存
+pub async fn confirm_pick(
+  pool: &SqlitePool,
+  pick_id: &str,
+  actual_qty: i64,
+  short_reason: Option<String>,
+  auto_adjust: bool,
+  occurred_at: i64,
+  actor_operator_id: &str,
+  location: Option<String>,
+) -> Result<PickConfirmed, AppError> {
+  let pick = get_pending_pick(pool, pick_id).await?;
+  if actual_qty < 0 {
+    return Err(AppError::new(ErrorCode::ValidationError, "实拣数量不能为负数"));
+  }
+  if actual_qty > pick.planned_qty {
+    return Err(AppError::new(ErrorCode::ValidationError, "实拣数量不能超过计划数量"));
+  }
+  let short_qty = pick.planned_qty - actual_qty;
+  let short_reason = short_reason.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let short_reason = if short_qty > 0 {
+    let reason = short_reason.ok_or_else(|| AppError::new(ErrorCode::ValidationError, "实拣少于计划数量时须填写短拣原因"))?;
+    if reason.chars().count() > MAX_SHORT_REASON_LEN {
+      return Err(AppError::new(ErrorCode::ValidationError, "短拣原因过长"));
+    }
+    Some(reason)
+  } else {
+    None
+  };
+  // 自动调整与盘点调整共用审批阈值，超过阈值时须关闭自动调整后按盘点流程审批
+  if auto_adjust && short_qty > 0 && approval_service::adjust_requires_approval(pool, &pick.item_id, -short_qty).await? {
+    return Err(AppError::new(
+      ErrorCode::Forbidden,
+      "短拣调整超过盘点调整审批阈值，请关闭自动调整后按盘点流程提交审批",
+    ));
+  }
+
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+  txn_service::ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = txn_service::normalize_location(pool, location).await?;
+  let precision = item_repo::get_qty_precision(pool, &pick.item_id).await?;
+
+  let now = Utc::now().timestamp();
+  let scheme = txn_service::txn_no_scheme(pool).await?;
+  let item_id = pick.item_id.as_str();
+  let slot_id = pick.slot_id.as_str();
+
+  let mut tx = pool.begin().await?;
+  let mut stock_qty = stock_repo::get_stock_tx(&mut tx, item_id, slot_id).await?.map(|s| s.qty).unwrap_or(0);
+
+  let mut out_txn = None;
+  if actual_qty > 0 {
+    if stock_qty < actual_qty {
+      return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+    }
+    stock_hold_service::ensure_available_tx(&mut tx, item_id, slot_id, stock_qty, actual_qty).await?;
+    expiry_service::ensure_not_expired_tx(&mut tx, item_id, slot_id, stock_qty, actual_qty, false).await?;
+    let txn_id = Uuid::new_v4().to_string();
+    let txn_no = txn_service::next_txn_no(&mut tx, &scheme, "OUT", Some(slot_id)).await?;
+    let note = match &pick.note {
+      Some(note) => format!("拣货 {}：{}", pick.pick_no, note),
+      None => format!("拣货 {}", pick.pick_no),
+    };
+    txn_repo::insert_txn(
+      &mut tx,
+      &txn_repo::TxnRow {
+        id: txn_id.clone(),
+        txn_no: txn_no.clone(),
+        txn_type: "OUT".to_string(),
+        occurred_at,
+        created_at: now,
+        operator_id: operator.id.clone(),
+        item_id: item_id.to_string(),
+        from_slot_id: Some(slot_id.to_string()),
+        to_slot_id: None,
+        qty: actual_qty,
+        actual_qty: None,
+        ref_txn_id: None,
+        note: Some(note),
+        unit_cost: None,
+        location: location.clone(),
+        expires_at: None,
+        reason_id: None,
+      },
+    )
+    .await?;
+    stock_qty -= actual_qty;
+    stock_repo::upsert_stock_tx(&mut tx, item_id, slot_id, stock_qty, now).await?;
+    out_txn = Some((txn_id, txn_no));
+  }
+
+  let mut adjust_txn = None;
+  if let Some(reason) = short_reason.as_deref() {
+    // 自动调整：账面按短拣数量冲减，不低于 0
+    let delta = -short_qty.min(stock_qty);
+    if auto_adjust && delta != 0 {
+      let txn_id = Uuid::new_v4().to_string();
+      let txn_no = txn_service::next_txn_no(&mut tx, &scheme, "ADJUST", Some(slot_id)).await?;
+      txn_repo::insert_txn(
+        &mut tx,
+        &txn_repo::TxnRow {
+          id: txn_id.clone(),
+          txn_no: txn_no.clone(),
+          txn_type: "ADJUST".to_string(),
+          occurred_at,
+          created_at: now,
+          operator_id: operator.id.clone(),
+          item_id: item_id.to_string(),
+          from_slot_id: Some(slot_id.to_string()),
+          to_slot_id: None,
+          qty: delta,
+          actual_qty: None,
+          ref_txn_id: None,
+          note: Some(format!("拣货短拣 {}：{}", pick.pick_no, reason)),
+          unit_cost: None,
+          location: location.clone(),
+          expires_at: None,
+          reason_id: None,
+        },
+      )
+      .await?;
+      stock_qty += delta;
+      stock_repo::upsert_stock_tx(&mut tx, item_id, slot_id, stock_qty, now).await?;
+      adjust_txn = Some((txn_id, txn_no));
+    }
+    outbound_pick_repo::insert_discrepancy_tx(
+      &mut tx,
+      &PickDiscrepancyRow {
+        id: Uuid::new_v4().to_string(),
+        pick_id: pick.id.clone(),
+        item_id: item_id.to_string(),
+        slot_id: slot_id.to_string(),
+        planned_qty: pick.planned_qty,
+        actual_qty,
+        short_qty,
+        reason: reason.to_string(),
+        adjust_txn_id: adjust_txn.as_ref().map(|(id, _)| id.clone()),
+        operator_id: operator.id.clone(),
+        created_at: now,
+      },
+    )
+    .await?;
+  }
+
+  let updated = outbound_pick_repo::confirm_pick_tx(
+    &mut tx,
+    &pick.id,
+    actual_qty,
+    short_reason.as_deref(),
+    &operator.id,
+    now,
+    out_txn.as_ref().map(|(id, _)| id.as_str()),
+  )
+  .await?;
+  if updated == 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "拣货单已处理"));
+  }
+  tx.commit().await?;
+  txn_service::touch_kiosk_operator(pool, &operator.id).await?;
+  Ok(PickConfirmed {
+    pick_no: pick.pick_no,
+    out_txn_no: out_txn.map(|(_, txn_no)| txn_no),
+    short_qty: quantity::to_display(short_qty, precision),
+    adjust_txn_no: adjust_txn.map(|(_, txn_no)| txn_no),
+  })
+}
+
+/// 取消待拣货的拣货单
+pub async fn cancel_pick(pool: &SqlitePool, pick_id: &str) -> Result<(), AppError> {
+  get_pending_pick(pool, pick_id).await?;
+  if outbound_pick_repo::cancel_pick(pool, pick_id).await? == 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "拣货单已处理"));
+  }
+  Ok(())
+}
+
+pub async fn list_picks(
+  pool: &SqlitePool,
+  status: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<OutboundPickListRow>, AppError> {
+  if let Some(status) = status {
+    if !PICK_STATUSES.contains(&status) {
+      return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
+    }
+  }
+  outbound_pick_repo::list_picks(pool, status, allowed_warehouse_ids, MAX_LIST).await
+}
+
+/// 拣货单（须为待拣货状态）
+pub async fn get_pending_pick(pool: &SqlitePool, pick_id: &str) -> Result<OutboundPickRow, AppError> {
+  let pick = outbound_pick_repo::get_pick_by_id(pool, pick_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "拣货单不存在"))?;
+  if pick.status != "pending" {
+    return Err(AppError::new(ErrorCode::Conflict, "拣货单已处理"));
+  }
+  Ok(pick)
+}
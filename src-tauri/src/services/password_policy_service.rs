@@ -0,0 +1,120 @@
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::infra::crypto;
+use crate::repo::{meta_repo, operator_repo};
+
+/// 密码策略
+#[derive(Debug, serde::Serialize)]
+pub struct PasswordPolicy {
+  // 最小长度
+  pub min_length: i64,
+  // 至少包含的字符类别数（小写/大写/数字/符号，1-4）
+  pub min_char_classes: i64,
+  // 禁止包含用户名
+  pub disallow_username: bool,
+  // 禁止复用最近 N 次密码（0 表示不限制）
+  pub history_count: i64,
+}
+
+/// 读取密码策略
+pub async fn load_policy(pool: &SqlitePool) -> Result<PasswordPolicy, AppError> {
+  let min_length = meta_repo::get_meta_i64(pool, "pwd_min_length")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(6);
+  let min_char_classes = meta_repo::get_meta_i64(pool, "pwd_min_char_classes")
+    .await?
+    .filter(|value| (1..=4).contains(value))
+    .unwrap_or(1);
+  let disallow_username = meta_repo::get_meta_value(pool, "pwd_disallow_username")
+    .await?
+    .map(|value| value == "1")
+    .unwrap_or(true);
+  let history_count = meta_repo::get_meta_i64(pool, "pwd_history_count")
+    .await?
+    .filter(|value| *value >= 0)
+    .unwrap_or(3);
+  Ok(PasswordPolicy {
+    min_length,
+    min_char_classes,
+    disallow_username,
+    history_count,
+  })
+}
+
+/// 按策略校验新密码（operator_id 为空表示新建人员，跳过历史校验）
+pub async fn validate_new_password(
+  pool: &SqlitePool,
+  operator_id: Option<&str>,
+  username: &str,
+  password: &str,
+) -> Result<(), AppError> {
+  let policy = load_policy(pool).await?;
+  if (password.chars().count() as i64) < policy.min_length {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("密码长度不能少于 {} 位", policy.min_length),
+    ));
+  }
+
+  let classes = [
+    password.chars().any(|c| c.is_ascii_lowercase()),
+    password.chars().any(|c| c.is_ascii_uppercase()),
+    password.chars().any(|c| c.is_ascii_digit()),
+    password.chars().any(|c| !c.is_ascii_alphanumeric()),
+  ]
+  .iter()
+  .filter(|hit| **hit)
+  .count() as i64;
+  if classes < policy.min_char_classes {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!(
+        "密码需至少包含小写字母、大写字母、数字、符号中的 {} 类",
+        policy.min_char_classes
+      ),
+    ));
+  }
+
+  let username = username.trim().to_lowercase();
+  if policy.disallow_username
+    && !username.is_empty()
+    && password.to_lowercase().contains(&username)
+  {
+    return Err(AppError::new(ErrorCode::ValidationError, "密码不能包含用户名"));
+  }
+
+  let Some(operator_id) = operator_id else {
+    return Ok(());
+  };
+  if policy.history_count == 0 {
+    return Ok(());
+  }
+  // 当前密码与最近 N 次历史密码均不可复用
+  let mut hashes = operator_repo::list_recent_password_hashes(pool, operator_id, policy.history_count).await?;
+  if let Some(current) = operator_repo::get_password_hash(pool, operator_id).await? {
+    hashes.push(current);
+  }
+  for hash in hashes {
+    if crypto::verify_password(&hash, password)? {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("新密码不能与最近 {} 次使用过的密码相同", policy.history_count),
+      ));
+    }
+  }
+  Ok(())
+}
+
+/// 记录密码历史
+pub async fn record_history(
+  pool: &SqlitePool,
+  operator_id: &str,
+  password_hash: &str,
+  now: i64,
+) -> Result<(), AppError> {
+  let id = Uuid::new_v4().to_string();
+  operator_repo::insert_password_history(pool, &id, operator_id, password_hash, now).await
+}
@@ -1,7 +1,101 @@
+use chrono::Utc;
 use sqlx::SqlitePool;
 
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::repo::{meta_repo, operator_repo};
+use crate::domain::messages::MessageKey;
+use crate::repo::{meta_repo, operator_repo, operator_warehouse_repo, rack_repo, role_capability_repo, warehouse_repo};
+
+/// 权限点与允许角色对照（需与各命令的角色校验保持一致）
+pub const PERMISSION_TABLE: &[(&str, &[&str])] = &[
+  ("structure.read", &["admin", "keeper", "viewer", "member"]),
+  ("structure.write", &["admin"]),
+  ("item.read", &["admin", "keeper", "viewer", "member"]),
+  ("item.write", &["admin", "keeper"]),
+  ("item.export", &["admin", "keeper", "viewer"]),
+  ("item.import", &["admin"]),
+  ("photo.read", &["admin", "keeper", "viewer", "member"]),
+  ("photo.write", &["admin", "keeper", "member"]),
+  ("txn.read", &["admin", "keeper", "viewer", "member"]),
+  ("txn.create", &["admin", "keeper", "member"]),
+  ("txn.reverse", &["admin"]),
+  ("approval.decide", &["admin"]),
+  ("txn.export", &["admin", "keeper", "viewer"]),
+  ("txn.import", &["admin"]),
+  ("stock.read", &["admin", "keeper", "viewer", "member"]),
+  ("stock.export", &["admin", "keeper", "viewer", "member"]),
+  ("cost.read", &["admin", "keeper", "viewer", "member"]),
+  ("dashboard.read", &["admin", "keeper", "viewer"]),
+  ("dashboard.leaderboard", &["admin"]),
+  ("operator.read", &["admin", "keeper", "viewer", "member"]),
+  ("operator.manage", &["admin"]),
+  ("audit.read", &["admin", "keeper", "viewer", "member"]),
+  ("system.manage", &["admin"]),
+  ("data.backup", &["admin"]),
+];
+
+/// 可由管理员按角色开放的能力（管理员始终拥有）
+pub const CAPABILITIES: &[&str] = &["can_export", "can_view_costs", "can_view_audit"];
+
+/// 可配置能力的角色
+pub const CAPABILITY_ROLES: &[&str] = &["keeper", "viewer", "member"];
+
+/// 受角色能力开关控制的权限点：角色允许且该角色开放对应能力时才拥有
+pub const CAPABILITY_PERMISSIONS: &[(&str, &str)] = &[
+  ("item.export", "can_export"),
+  ("txn.export", "can_export"),
+  ("stock.export", "can_export"),
+  ("cost.read", "can_view_costs"),
+  ("audit.read", "can_view_audit"),
+];
+
+/// 受仓库范围限制的权限点（非管理员分配仓库后仅对所分配仓库生效）
+pub const WAREHOUSE_SCOPED_PERMISSIONS: &[&str] = &[
+  "structure.read",
+  "txn.read",
+  "txn.create",
+  "txn.export",
+  "stock.read",
+  "stock.export",
+];
+
+#[derive(Debug, serde::Serialize)]
+pub struct ScopedWarehouse {
+  pub id: String,
+  pub code: Option<String>,
+  pub name: Option<String>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct PermissionDecision {
+  pub permission: String,
+  pub allowed: bool,
+  // 是否仅限分配的仓库
+  pub warehouse_scoped: bool,
+  pub reason: String,
+}
+
+/// 权限模拟结果：人员在当前 RBAC 配置下可/不可执行的操作
+#[derive(Debug, serde::Serialize)]
+pub struct PermissionSimulation {
+  pub operator_id: String,
+  pub username: String,
+  pub display_name: String,
+  pub role: String,
+  pub status: String,
+  pub rbac_enabled: bool,
+  // None 表示不限仓库
+  pub warehouses: Option<Vec<ScopedWarehouse>>,
+  pub permissions: Vec<PermissionDecision>,
+}
+
+/// 角色能力开关
+#[derive(Debug, serde::Serialize)]
+pub struct RoleCapabilities {
+  pub role: String,
+  pub can_export: bool,
+  pub can_view_costs: bool,
+  pub can_view_audit: bool,
+}
 
 /// 按 operator id 要求管理员权限
 pub async fn require_admin_by_id(pool: &SqlitePool, actor_operator_id: &str) -> Result<(), AppError> {
@@ -19,16 +113,302 @@ pub async fn require_role_by_id(
   }
   let operator = operator_repo::get_operator_by_id(pool, actor_operator_id)
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "操作人不存在"))?;
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
   if operator.status != "active" {
-    return Err(AppError::new(ErrorCode::InactiveResource, "操作人已停用"));
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ActorInactive));
   }
   if !allow_roles.iter().any(|role| *role == operator.role) {
-    return Err(AppError::new(ErrorCode::Forbidden, "无权限执行该操作"));
+    return Err(AppError::keyed(ErrorCode::Forbidden, MessageKey::Forbidden));
+  }
+  Ok(())
+}
+
+/// 按角色列出权限点
+pub fn permissions_for_role(role: &str) -> Vec<String> {
+  PERMISSION_TABLE
+    .iter()
+    .filter(|(_, roles)| roles.contains(&role))
+    .map(|(key, _)| key.to_string())
+    .collect()
+}
+
+/// 按角色及其能力开关列出权限点
+pub async fn effective_permissions_for_role(pool: &SqlitePool, role: &str) -> Result<Vec<String>, AppError> {
+  let mut permissions = Vec::new();
+  for key in permissions_for_role(role) {
+    let gated = CAPABILITY_PERMISSIONS.iter().find(|(permission, _)| *permission == key);
+    if let Some((_, capability)) = gated {
+      if !role_has_capability(pool, role, capability).await? {
+        continue;
+      }
+    }
+    permissions.push(key);
+  }
+  Ok(permissions)
+}
+
+/// 列出操作人当前拥有的权限点（RBAC 关闭时拥有全部权限）
+pub async fn list_permissions_by_id(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+) -> Result<Vec<String>, AppError> {
+  if !rbac_enabled(pool).await? {
+    return Ok(PERMISSION_TABLE.iter().map(|(key, _)| key.to_string()).collect());
+  }
+  let operator = operator_repo::get_operator_by_id(pool, actor_operator_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
+  if operator.status != "active" {
+    return Ok(Vec::new());
+  }
+  effective_permissions_for_role(pool, &operator.role).await
+}
+
+/// 判断操作人是否拥有指定权限点（用于按权限裁剪返回数据）
+pub async fn has_permission(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  permission: &str,
+) -> Result<bool, AppError> {
+  let permissions = list_permissions_by_id(pool, actor_operator_id).await?;
+  Ok(permissions.iter().any(|key| key == permission))
+}
+
+/// 判断操作人是否拥有角色能力（RBAC 关闭或管理员时始终拥有）
+pub async fn has_capability(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  capability: &str,
+) -> Result<bool, AppError> {
+  if !rbac_enabled(pool).await? {
+    return Ok(true);
+  }
+  let operator = operator_repo::get_operator_by_id(pool, actor_operator_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
+  if operator.status != "active" {
+    return Ok(false);
+  }
+  role_has_capability(pool, &operator.role, capability).await
+}
+
+/// 要求操作人拥有角色能力，未开放时返回 FORBIDDEN
+pub async fn require_capability(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  capability: &str,
+) -> Result<(), AppError> {
+  if has_capability(pool, actor_operator_id, capability).await? {
+    return Ok(());
+  }
+  Err(AppError::new(
+    ErrorCode::Forbidden,
+    format!("当前角色未开放{}", capability_label(capability)),
+  ))
+}
+
+/// 各可配置角色的能力开关
+pub async fn list_role_capabilities(pool: &SqlitePool) -> Result<Vec<RoleCapabilities>, AppError> {
+  let rows = role_capability_repo::list_capabilities(pool).await?;
+  let allowed = |role: &str, capability: &str| {
+    rows
+      .iter()
+      .any(|row| row.role == role && row.capability == capability && row.allowed)
+  };
+  Ok(
+    CAPABILITY_ROLES
+      .iter()
+      .map(|role| RoleCapabilities {
+        role: role.to_string(),
+        can_export: allowed(role, "can_export"),
+        can_view_costs: allowed(role, "can_view_costs"),
+        can_view_audit: allowed(role, "can_view_audit"),
+      })
+      .collect(),
+  )
+}
+
+/// 更新角色能力开关，未传的能力保持不变
+pub async fn set_role_capabilities(
+  pool: &SqlitePool,
+  role: &str,
+  changes: &[(&str, Option<bool>)],
+) -> Result<(), AppError> {
+  if !CAPABILITY_ROLES.contains(&role) {
+    return Err(AppError::new(ErrorCode::ValidationError, "仅可配置 keeper/viewer/member 角色的能力"));
+  }
+  let now = Utc::now().timestamp();
+  for (capability, allowed) in changes {
+    if !CAPABILITIES.contains(capability) {
+      return Err(AppError::new(ErrorCode::ValidationError, "能力仅支持 can_export/can_view_costs/can_view_audit"));
+    }
+    if let Some(allowed) = allowed {
+      role_capability_repo::set_capability(pool, role, capability, *allowed, now).await?;
+    }
   }
   Ok(())
 }
 
+/// 操作人可访问的仓库范围，返回 None 表示不限（RBAC 关闭、管理员或未分配仓库）
+pub async fn allowed_warehouse_ids(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+) -> Result<Option<Vec<String>>, AppError> {
+  if !rbac_enabled(pool).await? {
+    return Ok(None);
+  }
+  let operator = operator_repo::get_operator_by_id(pool, actor_operator_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ActorNotFound))?;
+  if operator.role == "admin" {
+    return Ok(None);
+  }
+  let ids = operator_warehouse_repo::list_warehouse_ids(pool, actor_operator_id).await?;
+  if ids.is_empty() {
+    return Ok(None);
+  }
+  Ok(Some(ids))
+}
+
+/// 要求操作人可访问指定仓库
+pub async fn require_warehouse_access(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  warehouse_id: Option<&str>,
+) -> Result<(), AppError> {
+  let Some(allowed) = allowed_warehouse_ids(pool, actor_operator_id).await? else {
+    return Ok(());
+  };
+  match warehouse_id {
+    Some(id) if allowed.iter().any(|allowed_id| allowed_id == id) => Ok(()),
+    _ => Err(AppError::keyed(ErrorCode::Forbidden, MessageKey::WarehouseForbidden)),
+  }
+}
+
+/// 要求操作人可访问库位所在仓库（录入流水前校验）
+pub async fn require_slot_access(
+  pool: &SqlitePool,
+  actor_operator_id: &str,
+  slot_id: &str,
+) -> Result<(), AppError> {
+  if allowed_warehouse_ids(pool, actor_operator_id).await?.is_none() {
+    return Ok(());
+  }
+  let slot = rack_repo::get_slot_by_id(pool, slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+  require_warehouse_access(pool, actor_operator_id, slot.warehouse_id.as_deref()).await
+}
+
+/// 模拟人员的权限：逐个权限点给出是否允许及原因（角色、状态、仓库范围）
+pub async fn simulate_permissions(
+  pool: &SqlitePool,
+  operator_id: &str,
+) -> Result<PermissionSimulation, AppError> {
+  let operator = operator_repo::get_operator_by_id(pool, operator_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::OperatorNotFound))?;
+  let rbac = rbac_enabled(pool).await?;
+  let active = operator.status == "active";
+
+  let warehouses = if rbac && operator.role != "admin" {
+    let ids = operator_warehouse_repo::list_warehouse_ids(pool, &operator.id).await?;
+    if ids.is_empty() {
+      None
+    } else {
+      let mut scoped = Vec::with_capacity(ids.len());
+      for id in ids {
+        let warehouse = warehouse_repo::get_warehouse_by_id(pool, &id).await?;
+        scoped.push(ScopedWarehouse {
+          code: warehouse.as_ref().map(|warehouse| warehouse.code.clone()),
+          name: warehouse.map(|warehouse| warehouse.name),
+          id,
+        });
+      }
+      Some(scoped)
+    }
+  } else {
+    None
+  };
+  let scope_label = warehouses.as_ref().map(|warehouses| {
+    warehouses
+      .iter()
+      .map(|warehouse| warehouse.code.clone().unwrap_or_else(|| warehouse.id.clone()))
+      .collect::<Vec<_>>()
+      .join("、")
+  });
+
+  // 角色允许但能力未开放的权限点
+  let mut denied_capabilities: Vec<(&str, &str)> = Vec::new();
+  if operator.role != "admin" {
+    for (permission, capability) in CAPABILITY_PERMISSIONS {
+      if !role_has_capability(pool, &operator.role, capability).await? {
+        denied_capabilities.push((permission, capability));
+      }
+    }
+  }
+
+  let permissions = PERMISSION_TABLE
+    .iter()
+    .map(|(key, roles)| {
+      let scoped = scope_label.is_some() && WAREHOUSE_SCOPED_PERMISSIONS.contains(key);
+      let (allowed, reason) = if !rbac {
+        (true, "RBAC 未开启，全部允许".to_string())
+      } else if !active {
+        (false, "人员已停用".to_string())
+      } else if let Some((_, capability)) = roles
+        .contains(&operator.role.as_str())
+        .then(|| denied_capabilities.iter().find(|(permission, _)| permission == key))
+        .flatten()
+      {
+        (false, format!("角色 {} 未开放{}", operator.role, capability_label(capability)))
+      } else if roles.contains(&operator.role.as_str()) {
+        let reason = match (&scope_label, scoped) {
+          (Some(scope), true) => format!("角色 {} 允许，仅限仓库：{}", operator.role, scope),
+          _ => format!("角色 {} 允许", operator.role),
+        };
+        (true, reason)
+      } else {
+        (false, format!("角色 {} 不允许，需 {}", operator.role, roles.join("/")))
+      };
+      PermissionDecision {
+        permission: key.to_string(),
+        allowed,
+        warehouse_scoped: allowed && scoped,
+        reason,
+      }
+    })
+    .collect();
+
+  Ok(PermissionSimulation {
+    operator_id: operator.id,
+    username: operator.username,
+    display_name: operator.display_name,
+    role: operator.role,
+    status: operator.status,
+    rbac_enabled: rbac,
+    warehouses,
+    permissions,
+  })
+}
+
+/// 角色是否开放能力：管理员始终拥有，未配置视为未开放
+async fn role_has_capability(pool: &SqlitePool, role: &str, capability: &str) -> Result<bool, AppError> {
+  if role == "admin" {
+    return Ok(true);
+  }
+  Ok(role_capability_repo::get_capability(pool, role, capability).await?.unwrap_or(false))
+}
+
+fn capability_label(capability: &str) -> &str {
+  match capability {
+    "can_export" => "导出权限",
+    "can_view_costs" => "成本查看权限",
+    "can_view_audit" => "审计日志查看权限",
+    other => other,
+  }
+}
+
 /// 读取 RBAC 开关
 async fn rbac_enabled(pool: &SqlitePool) -> Result<bool, AppError> {
   let rbac = meta_repo::get_meta_value(pool, "rbac_enabled")
@@ -1,10 +1,12 @@
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 
 use chrono::Utc;
-use sqlx::SqlitePool;
+use sqlx::{Sqlite, SqlitePool, Transaction};
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 use crate::repo::{meta_repo, photo_repo};
 
 #[derive(Debug, serde::Serialize)]
@@ -86,6 +88,95 @@ pub async fn add_photos(
   Ok(())
 }
 
+/// 在事务内复制的图片文件：事务提交后调用 keep 保留并清理暂存来源，
+/// 否则离开作用域时删除已复制的文件，避免留下没有记录的图片
+pub struct CopiedPhotos {
+  copied: Vec<PathBuf>,
+  staged_sources: Vec<PathBuf>,
+}
+
+impl CopiedPhotos {
+  pub fn keep(mut self) {
+    self.copied.clear();
+    for src in self.staged_sources.drain(..) {
+      let _ = std::fs::remove_file(src);
+    }
+  }
+}
+
+impl Drop for CopiedPhotos {
+  fn drop(&mut self) {
+    for path in &self.copied {
+      let _ = std::fs::remove_file(path);
+    }
+  }
+}
+
+/// 为事务内新建的数据挂接图片：复制文件并在同一事务写入记录，图片与业务数据一同提交或回滚；
+/// 来源文件在 keep 时才删除，事务回滚后可直接重试
+pub async fn add_photos_tx(
+  tx: &mut Transaction<'_, Sqlite>,
+  storage_root: &Path,
+  photo_type: &str,
+  data_id: &str,
+  src_paths: &[String],
+) -> Result<CopiedPhotos, AppError> {
+  let mut photos = CopiedPhotos {
+    copied: Vec::new(),
+    staged_sources: Vec::new(),
+  };
+  if src_paths.is_empty() {
+    return Ok(photos);
+  }
+  let staging_root = storage_root.join("photos").join("staging").join(photo_type);
+  let photo_dir = storage_root.join("photos").join(photo_type).join(data_id);
+  std::fs::create_dir_all(&photo_dir)
+    .map_err(|_| AppError::new(ErrorCode::IoError, "创建照片目录失败"))?;
+
+  let now = Utc::now().timestamp();
+  for (sort_no, src) in src_paths.iter().enumerate() {
+    let src_path = Path::new(src);
+    if !src_path.exists() {
+      return Err(AppError::new(ErrorCode::ValidationError, "照片路径不存在"));
+    }
+    let ext = src_path
+      .extension()
+      .and_then(|ext| ext.to_str())
+      .unwrap_or("bin");
+    let file_name = format!("{}.{}", Uuid::new_v4(), ext);
+    let dest_path = photo_dir.join(&file_name);
+    std::fs::copy(src_path, &dest_path)
+      .map_err(|_| AppError::new(ErrorCode::IoError, "复制照片失败"))?;
+    photos.copied.push(dest_path);
+    if src_path.starts_with(&staging_root) {
+      photos.staged_sources.push(src_path.to_path_buf());
+    }
+
+    let relative_path = format!("photos/{}/{}/{}", photo_type, data_id, file_name);
+    photo_repo::insert_photo_tx(
+      tx,
+      &Uuid::new_v4().to_string(),
+      photo_type,
+      data_id,
+      &relative_path,
+      sort_no as i64,
+      now,
+    )
+    .await?;
+  }
+  Ok(photos)
+}
+
+/// 提前校验来源图片均存在，用于在写入业务数据前拒绝无效路径
+pub fn validate_src_paths(src_paths: &[String]) -> Result<(), AppError> {
+  for src in src_paths {
+    if !Path::new(src).is_file() {
+      return Err(AppError::new(ErrorCode::ValidationError, "照片路径不存在"));
+    }
+  }
+  Ok(())
+}
+
 pub async fn stage_photo_bytes(
   pool: &SqlitePool,
   photo_type: &str,
@@ -145,10 +236,141 @@ pub async fn reorder_photos(
   Ok(())
 }
 
-async fn get_storage_root(pool: &SqlitePool) -> Result<PathBuf, AppError> {
+/// 巡检结果中列出的缺失/多余文件样例数上限
+const SCAN_SAMPLE_LIMIT: usize = 20;
+
+/// 照片存储巡检结果
+#[derive(Debug, serde::Serialize)]
+pub struct PhotoStorageScan {
+  pub metric: photo_repo::PhotoStorageMetricRow,
+  // 上一次巡检记录（首次巡检为空）
+  pub previous: Option<photo_repo::PhotoStorageMetricRow>,
+  // 与上一次相比的变化量
+  pub file_count_delta: i64,
+  pub total_bytes_delta: i64,
+  pub db_count_delta: i64,
+  // 存在缺失或多余文件
+  pub drift_detected: bool,
+  pub missing_samples: Vec<String>,
+  pub orphan_samples: Vec<String>,
+}
+
+/// 巡检照片目录：对比文件数、总大小与附件记录，并记录本次指标
+pub async fn scan_photo_storage(pool: &SqlitePool) -> Result<PhotoStorageScan, AppError> {
+  let storage_root = get_storage_root(pool).await?;
+  let photos = photo_repo::list_all_photos(pool).await?;
+  let expected: HashSet<String> = photos
+    .iter()
+    .map(|photo| normalize_photo_path(&storage_root, &photo.file_path))
+    .collect();
+
+  let mut files = Vec::new();
+  let photos_root = storage_root.join("photos");
+  if photos_root.is_dir() {
+    collect_photo_files(&photos_root, &photos_root, &mut files)?;
+  }
+  let total_bytes: u64 = files.iter().map(|(_, size)| *size).sum();
+  let actual: HashSet<String> = files.into_iter().map(|(path, _)| path).collect();
+
+  let mut missing: Vec<String> = expected.difference(&actual).cloned().collect();
+  let mut orphan: Vec<String> = actual.difference(&expected).cloned().collect();
+  missing.sort();
+  orphan.sort();
+
+  let previous = photo_repo::list_storage_metrics(pool, 1).await?.into_iter().next();
+  let metric = photo_repo::PhotoStorageMetricRow {
+    id: Uuid::new_v4().to_string(),
+    scanned_at: Utc::now().timestamp(),
+    db_count: photos.len() as i64,
+    file_count: actual.len() as i64,
+    total_bytes: total_bytes.min(i64::MAX as u64) as i64,
+    missing_count: missing.len() as i64,
+    orphan_count: orphan.len() as i64,
+  };
+  photo_repo::insert_storage_metric(pool, &metric).await?;
+
+  let (file_count_delta, total_bytes_delta, db_count_delta) = match &previous {
+    Some(prev) => (
+      metric.file_count - prev.file_count,
+      metric.total_bytes - prev.total_bytes,
+      metric.db_count - prev.db_count,
+    ),
+    None => (0, 0, 0),
+  };
+  let drift_detected = metric.missing_count > 0 || metric.orphan_count > 0;
+  missing.truncate(SCAN_SAMPLE_LIMIT);
+  orphan.truncate(SCAN_SAMPLE_LIMIT);
+
+  Ok(PhotoStorageScan {
+    metric,
+    previous,
+    file_count_delta,
+    total_bytes_delta,
+    db_count_delta,
+    drift_detected,
+    missing_samples: missing,
+    orphan_samples: orphan,
+  })
+}
+
+/// 历史巡检指标（按时间倒序）
+pub async fn list_photo_storage_metrics(
+  pool: &SqlitePool,
+  limit: Option<i64>,
+) -> Result<Vec<photo_repo::PhotoStorageMetricRow>, AppError> {
+  let limit = limit.filter(|value| *value > 0).unwrap_or(30).min(365);
+  photo_repo::list_storage_metrics(pool, limit).await
+}
+
+/// 递归收集 photos 目录下的文件（相对路径, 大小），跳过 staging 临时目录
+fn collect_photo_files(
+  photos_root: &Path,
+  dir: &Path,
+  out: &mut Vec<(String, u64)>,
+) -> Result<(), AppError> {
+  let entries =
+    std::fs::read_dir(dir).map_err(|_| AppError::new(ErrorCode::IoError, "读取照片目录失败"))?;
+  for entry in entries {
+    let entry = entry.map_err(|_| AppError::new(ErrorCode::IoError, "读取照片目录失败"))?;
+    let path = entry.path();
+    let file_type = entry
+      .file_type()
+      .map_err(|_| AppError::new(ErrorCode::IoError, "读取照片目录失败"))?;
+    if file_type.is_dir() {
+      if dir == photos_root && entry.file_name() == "staging" {
+        continue;
+      }
+      collect_photo_files(photos_root, &path, out)?;
+    } else if file_type.is_file() {
+      let relative = path.strip_prefix(photos_root).unwrap_or(&path);
+      let size = entry.metadata().map(|meta| meta.len()).unwrap_or(0);
+      out.push((format!("photos/{}", to_slash_path(relative)), size));
+    }
+  }
+  Ok(())
+}
+
+/// 附件路径统一为相对存储根目录、以 / 分隔的形式
+fn normalize_photo_path(storage_root: &Path, file_path: &str) -> String {
+  let path = Path::new(file_path);
+  match path.strip_prefix(storage_root) {
+    Ok(relative) => to_slash_path(relative),
+    Err(_) => file_path.replace('\\', "/"),
+  }
+}
+
+fn to_slash_path(path: &Path) -> String {
+  path
+    .components()
+    .map(|component| component.as_os_str().to_string_lossy())
+    .collect::<Vec<_>>()
+    .join("/")
+}
+
+pub async fn get_storage_root(pool: &SqlitePool) -> Result<PathBuf, AppError> {
   let root = meta_repo::get_meta_value(pool, "storage_root")
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
   Ok(PathBuf::from(root))
 }
 
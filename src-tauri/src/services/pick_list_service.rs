@@ -0,0 +1,460 @@
+// 拣货单/上架单：按巡库路线（仓库、货架、层、位）排列库位，生成带勾选框的可打印文档（PDF/HTML）
+//
+// 拣货单未指定库位的行按路线顺序从有库存的库位中建议拣货位置，库存不足的部分记为缺货；
+// 上架单未指定库位时建议放到该物品已存放的第一个库位。仅生成文档，不产生流水。
+use chrono::Utc;
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, Mm, PdfDocument, Rect, Rgb};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::repo::item_repo;
+use crate::repo::stock_query_repo::{self, PickSlotRow};
+use crate::services::count_service::{draw_barcode, export_dir, fit_text, pdf_text};
+use crate::services::permission_service;
+
+/// 单据最多行数（请求行）
+const MAX_LINES: usize = 500;
+
+#[derive(Debug, Deserialize)]
+pub struct PickListLine {
+  pub item_code: String,
+  // 显示数量（按物品精度）
+  pub qty: f64,
+  // 指定库位；为空时自动建议
+  pub slot_code: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PickShortage {
+  pub item_code: String,
+  pub requested: f64,
+  pub available: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PickListResult {
+  pub file_path: String,
+  pub list_no: String,
+  pub rows: usize,
+  pub shortages: Vec<PickShortage>,
+}
+
+/// 单据种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickListKind {
+  Pick,
+  Putaway,
+}
+
+impl PickListKind {
+  pub fn parse(value: &str) -> Result<Self, AppError> {
+    match value {
+      "pick" => Ok(Self::Pick),
+      "putaway" => Ok(Self::Putaway),
+      _ => Err(AppError::new(ErrorCode::ValidationError, "单据类型仅支持 pick/putaway")),
+    }
+  }
+
+  fn prefix(self) -> &'static str {
+    match self {
+      Self::Pick => "PK",
+      Self::Putaway => "PA",
+    }
+  }
+
+  fn title(self) -> &'static str {
+    match self {
+      Self::Pick => "拣货单",
+      Self::Putaway => "上架单",
+    }
+  }
+
+  fn pdf_title(self) -> &'static str {
+    match self {
+      Self::Pick => "Pick list",
+      Self::Putaway => "Putaway list",
+    }
+  }
+}
+
+struct PickRow {
+  slot: PickSlotRow,
+  item_code: String,
+  item_name: String,
+  uom: Option<String>,
+  qty: String,
+}
+
+/// 生成拣货单/上架单，format 支持 pdf / html；reference 为关联单号或备注，打印在表头
+pub async fn print_pick_list(
+  pool: &SqlitePool,
+  kind: PickListKind,
+  warehouse_id: Option<String>,
+  lines: &[PickListLine],
+  reference: Option<&str>,
+  format: &str,
+  actor_operator_id: &str,
+) -> Result<PickListResult, AppError> {
+  if !matches!(format, "pdf" | "html") {
+    return Err(AppError::new(ErrorCode::ValidationError, "导出格式仅支持 pdf/html"));
+  }
+  if lines.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "请至少填写一行物品"));
+  }
+  if lines.len() > MAX_LINES {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("单据超过 {} 行，请分批打印", MAX_LINES),
+    ));
+  }
+  let allowed_warehouse_ids = permission_service::allowed_warehouse_ids(pool, actor_operator_id).await?;
+
+  let mut rows = Vec::new();
+  let mut shortages = Vec::new();
+  for (index, line) in lines.iter().enumerate() {
+    let line_error = |message: &str| {
+      AppError::new(ErrorCode::ValidationError, format!("第 {} 行：{}", index + 1, message))
+    };
+    let item_code = line.item_code.trim();
+    let item = item_repo::get_item_by_code(pool, item_code)
+      .await?
+      .ok_or_else(|| line_error("物品不存在"))?;
+    let qty = quantity::to_base(line.qty, item.qty_precision).map_err(|err| line_error(&err.message))?;
+    if qty <= 0 {
+      return Err(line_error("数量必须大于 0"));
+    }
+    let mut push_row = |slot: PickSlotRow, qty: i64| {
+      rows.push(PickRow {
+        slot,
+        item_code: item.item_code.clone(),
+        item_name: item.name.clone(),
+        uom: item.uom.clone(),
+        qty: quantity::format(qty, item.qty_precision),
+      });
+    };
+
+    let slot_code = line.slot_code.as_deref().map(str::trim).filter(|code| !code.is_empty());
+    let (slots, explicit_slot) = match slot_code {
+      Some(slot_code) => {
+        let slot = stock_query_repo::get_pick_slot(pool, slot_code, &item.id)
+          .await?
+          .ok_or_else(|| line_error("库位不存在"))?;
+        permission_service::require_slot_access(pool, actor_operator_id, &slot.slot_id).await?;
+        (vec![slot.clone()], Some(slot))
+      }
+      None => (
+        stock_query_repo::list_pick_slots_for_item(
+          pool,
+          &item.id,
+          warehouse_id.clone(),
+          allowed_warehouse_ids.clone(),
+        )
+        .await?,
+        None,
+      ),
+    };
+
+    match kind {
+      PickListKind::Putaway => {
+        let slot = slots
+          .into_iter()
+          .next()
+          .ok_or_else(|| line_error("物品暂无存放库位，请指定上架库位"))?;
+        push_row(slot, qty);
+      }
+      PickListKind::Pick => {
+        // 按路线顺序依次从各库位拣货，直至满足数量
+        let mut remaining = qty;
+        for slot in slots {
+          if remaining == 0 {
+            break;
+          }
+          let take = remaining.min(slot.qty.max(0));
+          if take > 0 {
+            remaining -= take;
+            push_row(slot, take);
+          }
+        }
+        if remaining > 0 {
+          shortages.push(PickShortage {
+            item_code: item.item_code.clone(),
+            requested: quantity::to_display(qty, item.qty_precision),
+            available: quantity::to_display(qty - remaining, item.qty_precision),
+          });
+          // 指定库位无库存时仍打印该行，便于现场核对
+          if let Some(slot) = explicit_slot.filter(|_| remaining == qty) {
+            push_row(slot, 0);
+          }
+        }
+      }
+    }
+  }
+
+  rows.sort_by(|a, b| {
+    (&a.slot.warehouse_code, &a.slot.rack_code, a.slot.level_no, a.slot.slot_no, &a.item_code).cmp(&(
+      &b.slot.warehouse_code,
+      &b.slot.rack_code,
+      b.slot.level_no,
+      b.slot.slot_no,
+      &b.item_code,
+    ))
+  });
+
+  let list_no = format!("{}{}", kind.prefix(), Utc::now().format("%Y%m%d%H%M%S"));
+  let file_path = export_dir(pool).await?.join(format!("pick_list_{}.{}", list_no, format));
+  let bytes = if format == "pdf" {
+    render_pdf(kind, &list_no, reference, &rows, &shortages)?
+  } else {
+    render_html(kind, &list_no, reference, &rows, &shortages).into_bytes()
+  };
+  std::fs::write(&file_path, bytes).map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  Ok(PickListResult {
+    file_path: file_path.to_string_lossy().to_string(),
+    list_no,
+    rows: rows.len(),
+    shortages,
+  })
+}
+
+fn location(slot: &PickSlotRow) -> String {
+  match &slot.warehouse_code {
+    Some(warehouse_code) => format!("{} / {}", warehouse_code, slot.rack_code),
+    None => slot.rack_code.clone(),
+  }
+}
+
+fn escape_html(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+/// 渲染 HTML（支持中文，浏览器打开后直接打印）
+fn render_html(
+  kind: PickListKind,
+  list_no: &str,
+  reference: Option<&str>,
+  rows: &[PickRow],
+  shortages: &[PickShortage],
+) -> String {
+  let mut html = String::new();
+  html.push_str("<!DOCTYPE html><html lang=\"zh-CN\"><head><meta charset=\"utf-8\">");
+  html.push_str(&format!("<title>{} {}</title>", kind.title(), list_no));
+  html.push_str(
+    "<style>body{font-family:sans-serif;font-size:13px;margin:16px;color:#0f172a}\
+     h1{font-size:20px;margin:0 0 4px}.meta{color:#475569;margin-bottom:12px}\
+     table{width:100%;border-collapse:collapse}th,td{border:1px solid #94a3b8;padding:6px 8px;text-align:left}\
+     th{background:#f1f5f9}.box{display:inline-block;width:14px;height:14px;border:1.5px solid #0f172a}\
+     .qty{text-align:right;font-weight:600}.short{margin-top:16px;color:#b91c1c}\
+     .sign{margin-top:24px}@media print{body{margin:0}thead{display:table-header-group}}</style>",
+  );
+  html.push_str("</head><body>");
+  html.push_str(&format!("<h1>{} {}</h1>", kind.title(), escape_html(list_no)));
+  html.push_str(&format!(
+    "<div class=\"meta\">打印时间 {}{}</div>",
+    Utc::now().format("%Y-%m-%d %H:%M UTC"),
+    reference
+      .map(|reference| format!("　关联单号 {}", escape_html(reference)))
+      .unwrap_or_default()
+  ));
+  html.push_str(
+    "<table><thead><tr><th>完成</th><th>序号</th><th>库位</th><th>位置</th><th>物品编码</th>\
+     <th>物品名称</th><th>数量</th><th>单位</th></tr></thead><tbody>",
+  );
+  for (index, row) in rows.iter().enumerate() {
+    html.push_str(&format!(
+      "<tr><td><span class=\"box\"></span></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td>\
+       <td>{}</td><td class=\"qty\">{}</td><td>{}</td></tr>",
+      index + 1,
+      escape_html(&row.slot.slot_code),
+      escape_html(&location(&row.slot)),
+      escape_html(&row.item_code),
+      escape_html(&row.item_name),
+      escape_html(&row.qty),
+      escape_html(row.uom.as_deref().unwrap_or(""))
+    ));
+  }
+  html.push_str("</tbody></table>");
+  if !shortages.is_empty() {
+    html.push_str("<div class=\"short\"><strong>缺货</strong><ul>");
+    for shortage in shortages {
+      html.push_str(&format!(
+        "<li>{}：需要 {}，可拣 {}</li>",
+        escape_html(&shortage.item_code),
+        shortage.requested,
+        shortage.available
+      ));
+    }
+    html.push_str("</ul></div>");
+  }
+  html.push_str(
+    "<div class=\"sign\">作业人：____________　　日期：____________　　复核人：____________</div>",
+  );
+  html.push_str("</body></html>");
+  html
+}
+
+const PAGE_W: f32 = 210.0;
+const PAGE_H: f32 = 297.0;
+const MARGIN: f32 = 12.0;
+const HEADER_H: f32 = 26.0;
+const ROW_H: f32 = 11.0;
+
+/// 渲染 PDF（A4 纵向，每行附库位条码便于扫码核对）。内置字体不支持中文，非 ASCII 名称不打印
+fn render_pdf(
+  kind: PickListKind,
+  list_no: &str,
+  reference: Option<&str>,
+  rows: &[PickRow],
+  shortages: &[PickShortage],
+) -> Result<Vec<u8>, AppError> {
+  let per_page = ((PAGE_H - MARGIN * 2.0 - HEADER_H) / ROW_H).floor().max(1.0) as usize;
+  let pages = rows.len().div_ceil(per_page).max(1);
+  let (doc, first_page, first_layer) = PdfDocument::new(
+    format!("{} {}", kind.pdf_title(), list_no),
+    Mm(PAGE_W),
+    Mm(PAGE_H),
+    "Layer 1",
+  );
+  let font = doc
+    .add_builtin_font(BuiltinFont::Helvetica)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+  let bold = doc
+    .add_builtin_font(BuiltinFont::HelveticaBold)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+
+  // 列：勾选框、序号、库位、物品编码、名称、数量、单位、库位条码
+  let columns: [(&str, f32); 8] = [
+    ("Done", 11.0),
+    ("No", 9.0),
+    ("Slot", 24.0),
+    ("Item", 28.0),
+    ("Name", 34.0),
+    ("Qty", 16.0),
+    ("Uom", 12.0),
+    ("Slot barcode", PAGE_W - MARGIN * 2.0 - 134.0),
+  ];
+  let black = Color::Rgb(Rgb::new(0.06, 0.09, 0.16, None));
+  let grey = Color::Rgb(Rgb::new(0.58, 0.64, 0.72, None));
+
+  let chunks: Vec<&[PickRow]> = if rows.is_empty() { vec![&[]] } else { rows.chunks(per_page).collect() };
+  for (page_index, chunk) in chunks.iter().enumerate() {
+    let layer = if page_index == 0 {
+      doc.get_page(first_page).get_layer(first_layer)
+    } else {
+      let (page, layer) = doc.add_page(Mm(PAGE_W), Mm(PAGE_H), "Layer 1");
+      doc.get_page(page).get_layer(layer)
+    };
+
+    let top = PAGE_H - MARGIN;
+    layer.set_fill_color(black.clone());
+    layer.use_text(format!("{} {}", kind.pdf_title(), list_no), 14.0, Mm(MARGIN), Mm(top - 5.0), &bold);
+    layer.use_text(
+      format!(
+        "Printed {}{}  -  Page {}/{}",
+        Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        reference
+          .map(|reference| format!("  -  Ref {}", pdf_text(reference)))
+          .unwrap_or_default(),
+        page_index + 1,
+        pages
+      ),
+      8.0,
+      Mm(MARGIN),
+      Mm(top - 11.0),
+      &font,
+    );
+    layer.use_text(
+      "Operator: ____________    Date: ____________    Checked by: ____________",
+      9.0,
+      Mm(MARGIN),
+      Mm(top - 18.0),
+      &font,
+    );
+
+    let header_y = top - HEADER_H + 2.0;
+    let mut x = MARGIN;
+    for (label, width) in columns {
+      layer.use_text(label, 8.0, Mm(x + 1.0), Mm(header_y), &bold);
+      x += width;
+    }
+
+    layer.set_outline_color(grey.clone());
+    layer.set_outline_thickness(0.4);
+    for (row_index, row) in chunk.iter().enumerate() {
+      let row_top = top - HEADER_H - row_index as f32 * ROW_H;
+      let row_bottom = row_top - ROW_H;
+      let text_y = row_bottom + ROW_H / 2.0 - 1.2;
+      layer.add_rect(
+        Rect::new(Mm(MARGIN), Mm(row_bottom), Mm(PAGE_W - MARGIN), Mm(row_top)).with_mode(PaintMode::Stroke),
+      );
+      // 勾选框
+      let box_size = 4.5;
+      let box_bottom = row_bottom + (ROW_H - box_size) / 2.0;
+      layer.add_rect(
+        Rect::new(
+          Mm(MARGIN + 3.0),
+          Mm(box_bottom),
+          Mm(MARGIN + 3.0 + box_size),
+          Mm(box_bottom + box_size),
+        )
+        .with_mode(PaintMode::Stroke),
+      );
+
+      let cells = [
+        String::new(),
+        (page_index * per_page + row_index + 1).to_string(),
+        pdf_text(&row.slot.slot_code),
+        pdf_text(&row.item_code),
+        Some(row.item_name.clone()).filter(|name| name.is_ascii()).unwrap_or_default(),
+        row.qty.clone(),
+        row.uom.clone().filter(|uom| uom.is_ascii()).unwrap_or_default(),
+      ];
+      layer.set_fill_color(black.clone());
+      let mut x = MARGIN;
+      for (cell, (_, width)) in cells.iter().zip(columns) {
+        layer.use_text(fit_text(cell, width), 8.0, Mm(x + 1.0), Mm(text_y), &font);
+        x += width;
+      }
+      let barcode_x = MARGIN + columns[..7].iter().map(|(_, width)| width).sum::<f32>();
+      let barcode_area = (barcode_x + 2.0, row_bottom + 1.5, columns[7].1 - 4.0, ROW_H - 3.0);
+      draw_barcode(&layer, &font, &row.slot.slot_code, barcode_area);
+    }
+
+    // 缺货汇总打印在最后一页表格下方
+    if page_index + 1 == pages && !shortages.is_empty() {
+      let mut y = top - HEADER_H - chunk.len() as f32 * ROW_H - 6.0;
+      layer.set_fill_color(black.clone());
+      layer.use_text("Shortage", 9.0, Mm(MARGIN), Mm(y), &bold);
+      for shortage in shortages {
+        y -= 4.5;
+        if y < MARGIN {
+          break;
+        }
+        layer.use_text(
+          format!(
+            "{}: requested {}, available {}",
+            pdf_text(&shortage.item_code),
+            shortage.requested,
+            shortage.available
+          ),
+          8.0,
+          Mm(MARGIN),
+          Mm(y),
+          &font,
+        );
+      }
+    }
+  }
+
+  doc
+    .save_to_bytes()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfRenderFailed))
+}
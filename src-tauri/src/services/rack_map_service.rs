@@ -0,0 +1,538 @@
+use chrono::Utc;
+use printpdf::path::PaintMode;
+use printpdf::{BuiltinFont, Color, Mm, PdfDocument, Rect, Rgb};
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::repo::meta_repo;
+use crate::repo::rack_repo::{self, RackRow};
+use crate::services::import_export_service::ExportResult;
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackMapSlot {
+  pub slot_id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub status: String,
+  pub item_count: i64,
+  pub total_qty: f64,
+  pub top_item_code: Option<String>,
+  // 展示状态：empty / occupied / negative / inactive
+  pub occupancy: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackMapLevel {
+  pub level_no: i64,
+  pub slots: Vec<RackMapSlot>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackMap {
+  pub rack: RackRow,
+  // 按层号倒序，最高层在前，与货架实物摆放一致
+  pub levels: Vec<RackMapLevel>,
+  pub total_slots: i64,
+  pub occupied_slots: i64,
+}
+
+/// 获取货架占用图：按层/位组织库位及其库存占用情况
+pub async fn get_rack_map(pool: &SqlitePool, rack_id: &str) -> Result<RackMap, AppError> {
+  let rack = rack_repo::get_rack_by_id(pool, rack_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound))?;
+  let rows = rack_repo::list_slot_occupancy(pool, rack_id).await?;
+
+  let mut levels: Vec<RackMapLevel> = Vec::new();
+  let mut occupied_slots = 0;
+  let total_slots = rows.len() as i64;
+  for row in rows {
+    let occupancy = if row.status != "active" {
+      "inactive"
+    } else if row.total_qty < 0.0 {
+      "negative"
+    } else if row.item_count > 0 {
+      "occupied"
+    } else {
+      "empty"
+    };
+    if row.item_count > 0 {
+      occupied_slots += 1;
+    }
+    let slot = RackMapSlot {
+      slot_id: row.slot_id,
+      code: row.code,
+      level_no: row.level_no,
+      slot_no: row.slot_no,
+      status: row.status,
+      item_count: row.item_count,
+      total_qty: row.total_qty,
+      top_item_code: row.top_item_code,
+      occupancy: occupancy.to_string(),
+    };
+    match levels.last_mut() {
+      Some(level) if level.level_no == slot.level_no => level.slots.push(slot),
+      _ => levels.push(RackMapLevel {
+        level_no: slot.level_no,
+        slots: vec![slot],
+      }),
+    }
+  }
+  levels.reverse();
+
+  Ok(RackMap {
+    rack,
+    levels,
+    total_slots,
+    occupied_slots,
+  })
+}
+
+/// 热力图缺省统计天数
+const HEATMAP_DEFAULT_DAYS: i64 = 30;
+/// 热力图统计天数上限
+const HEATMAP_MAX_DAYS: i64 = 365;
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackHeatmapSlot {
+  pub slot_id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub status: String,
+  pub txn_count: i64,
+  pub in_count: i64,
+  pub out_count: i64,
+  pub qty: f64,
+  // 活跃度：流水数 / 货架内最大流水数，0 ~ 1，供前端着色
+  pub heat: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackHeatmapLevel {
+  pub level_no: i64,
+  pub slots: Vec<RackHeatmapSlot>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackHeatmap {
+  pub rack: RackRow,
+  pub days: i64,
+  pub since: i64,
+  pub generated_at: i64,
+  pub max_txn_count: i64,
+  pub total_txn_count: i64,
+  // 按层号倒序，与货架占用图一致
+  pub levels: Vec<RackHeatmapLevel>,
+}
+
+/// 货架活动热力图：统计近 days 天（缺省 30 天）各库位的流水次数与当前库存
+pub async fn get_rack_heatmap(pool: &SqlitePool, rack_id: &str, days: Option<i64>) -> Result<RackHeatmap, AppError> {
+  let days = days.unwrap_or(HEATMAP_DEFAULT_DAYS);
+  if !(1..=HEATMAP_MAX_DAYS).contains(&days) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("统计天数需为 1-{}", HEATMAP_MAX_DAYS),
+    ));
+  }
+  let rack = rack_repo::get_rack_by_id(pool, rack_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound))?;
+  let now = Utc::now().timestamp();
+  let since = now - days * 86_400;
+  let rows = rack_repo::list_slot_activity(pool, rack_id, since).await?;
+
+  let max_txn_count = rows.iter().map(|row| row.txn_count).max().unwrap_or(0);
+  let total_txn_count = rows.iter().map(|row| row.txn_count).sum();
+  let mut levels: Vec<RackHeatmapLevel> = Vec::new();
+  for row in rows {
+    let heat = if max_txn_count > 0 {
+      row.txn_count as f64 / max_txn_count as f64
+    } else {
+      0.0
+    };
+    let slot = RackHeatmapSlot {
+      slot_id: row.slot_id,
+      code: row.code,
+      level_no: row.level_no,
+      slot_no: row.slot_no,
+      status: row.status,
+      txn_count: row.txn_count,
+      in_count: row.in_count,
+      out_count: row.out_count,
+      qty: row.qty,
+      heat,
+    };
+    match levels.last_mut() {
+      Some(level) if level.level_no == slot.level_no => level.slots.push(slot),
+      _ => levels.push(RackHeatmapLevel {
+        level_no: slot.level_no,
+        slots: vec![slot],
+      }),
+    }
+  }
+  levels.reverse();
+
+  Ok(RackHeatmap {
+    rack,
+    days,
+    since,
+    generated_at: now,
+    max_txn_count,
+    total_txn_count,
+    levels,
+  })
+}
+
+/// 导出货架占用图，format 支持 svg / pdf
+pub async fn export_rack_map(
+  pool: &SqlitePool,
+  map: &RackMap,
+  format: &str,
+) -> Result<ExportResult, AppError> {
+  let (ext, bytes) = match format {
+    "svg" => ("svg", render_svg(map).into_bytes()),
+    "pdf" => ("pdf", render_pdf(map)?),
+    _ => return Err(AppError::new(ErrorCode::ValidationError, "导出格式仅支持 svg/pdf")),
+  };
+
+  // 移动端使用临时目录，桌面端使用配置的导出目录
+  #[cfg(any(target_os = "android", target_os = "ios"))]
+  let export_dir = {
+    let _ = pool;
+    std::env::temp_dir()
+  };
+
+  #[cfg(not(any(target_os = "android", target_os = "ios")))]
+  let export_dir = {
+    let storage_root = meta_repo::get_meta_value(pool, "storage_root")
+      .await?
+      .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
+    match meta_repo::get_meta_value(pool, "exports_dir").await? {
+      Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
+      _ => std::path::PathBuf::from(&storage_root).join("exports"),
+    }
+  };
+
+  std::fs::create_dir_all(&export_dir)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
+
+  let now = Utc::now().timestamp();
+  let file_path = export_dir.join(format!(
+    "rack_map_{}_{}.{}",
+    safe_file_part(&map.rack.code),
+    now,
+    ext
+  ));
+  std::fs::write(&file_path, bytes)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  Ok(ExportResult {
+    file_path: file_path.to_string_lossy().to_string(),
+  })
+}
+
+const SVG_CELL_W: i64 = 120;
+const SVG_CELL_H: i64 = 64;
+const SVG_LABEL_W: i64 = 56;
+const SVG_HEADER_H: i64 = 64;
+const SVG_LEGEND_H: i64 = 40;
+const SVG_MARGIN: i64 = 16;
+
+fn occupancy_color(occupancy: &str) -> (u8, u8, u8) {
+  match occupancy {
+    "occupied" => (187, 247, 208),
+    "negative" => (254, 202, 202),
+    "inactive" => (226, 232, 240),
+    _ => (248, 250, 252),
+  }
+}
+
+fn occupancy_label(occupancy: &str) -> &'static str {
+  match occupancy {
+    "occupied" => "占用",
+    "negative" => "负库存",
+    "inactive" => "停用",
+    _ => "空闲",
+  }
+}
+
+const LEGEND: [&str; 4] = ["empty", "occupied", "negative", "inactive"];
+
+fn max_slots(map: &RackMap) -> i64 {
+  map
+    .levels
+    .iter()
+    .map(|level| level.slots.iter().map(|slot| slot.slot_no).max().unwrap_or(0))
+    .max()
+    .unwrap_or(0)
+    .max(map.rack.slots_per_level)
+    .max(1)
+}
+
+/// 渲染 SVG：最高层在上，每格显示库位编码、数量与主要物品
+pub fn render_svg(map: &RackMap) -> String {
+  let columns = max_slots(map);
+  let rows = map.levels.len().max(1) as i64;
+  let width = SVG_MARGIN * 2 + SVG_LABEL_W + columns * SVG_CELL_W;
+  let height = SVG_MARGIN * 2 + SVG_HEADER_H + rows * SVG_CELL_H + SVG_LEGEND_H;
+
+  let mut out = String::new();
+  out.push_str(&format!(
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\">\n",
+    w = width,
+    h = height
+  ));
+  out.push_str(&format!(
+    "<rect x=\"0\" y=\"0\" width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>\n",
+    width, height
+  ));
+  out.push_str(&format!(
+    "<text x=\"{}\" y=\"{}\" font-size=\"16\" font-weight=\"bold\" fill=\"#0f172a\">{} · {}</text>\n",
+    SVG_MARGIN,
+    SVG_MARGIN + 18,
+    escape_xml(&map.rack.code),
+    escape_xml(&map.rack.name)
+  ));
+  out.push_str(&format!(
+    "<text x=\"{}\" y=\"{}\" font-size=\"12\" fill=\"#64748b\">占用 {}/{} 个库位</text>\n",
+    SVG_MARGIN,
+    SVG_MARGIN + 38,
+    map.occupied_slots,
+    map.total_slots
+  ));
+
+  let grid_x = SVG_MARGIN + SVG_LABEL_W;
+  let grid_y = SVG_MARGIN + SVG_HEADER_H;
+  for column in 1..=columns {
+    out.push_str(&format!(
+      "<text x=\"{}\" y=\"{}\" font-size=\"11\" fill=\"#64748b\" text-anchor=\"middle\">{:02}</text>\n",
+      grid_x + (column - 1) * SVG_CELL_W + SVG_CELL_W / 2,
+      grid_y - 6,
+      column
+    ));
+  }
+
+  for (row_index, level) in map.levels.iter().enumerate() {
+    let y = grid_y + row_index as i64 * SVG_CELL_H;
+    out.push_str(&format!(
+      "<text x=\"{}\" y=\"{}\" font-size=\"12\" fill=\"#334155\">L{:02}</text>\n",
+      SVG_MARGIN,
+      y + SVG_CELL_H / 2 + 4,
+      level.level_no
+    ));
+    for slot in &level.slots {
+      let x = grid_x + (slot.slot_no - 1).max(0) * SVG_CELL_W;
+      let (r, g, b) = occupancy_color(&slot.occupancy);
+      out.push_str(&format!(
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"rgb({},{},{})\" stroke=\"#94a3b8\"/>\n",
+        x,
+        y,
+        SVG_CELL_W,
+        SVG_CELL_H,
+        r,
+        g,
+        b
+      ));
+      out.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" font-size=\"11\" fill=\"#0f172a\">{}</text>\n",
+        x + 6,
+        y + 16,
+        escape_xml(&slot.code)
+      ));
+      if slot.item_count > 0 || slot.total_qty != 0.0 {
+        out.push_str(&format!(
+          "<text x=\"{}\" y=\"{}\" font-size=\"12\" font-weight=\"bold\" fill=\"#0f172a\">{} 件 / {} 种</text>\n",
+          x + 6,
+          y + 36,
+          slot.total_qty,
+          slot.item_count
+        ));
+      }
+      if let Some(item_code) = &slot.top_item_code {
+        out.push_str(&format!(
+          "<text x=\"{}\" y=\"{}\" font-size=\"10\" fill=\"#475569\">{}</text>\n",
+          x + 6,
+          y + 54,
+          escape_xml(item_code)
+        ));
+      }
+    }
+  }
+
+  let legend_y = grid_y + rows * SVG_CELL_H + 16;
+  for (index, occupancy) in LEGEND.iter().enumerate() {
+    let x = SVG_MARGIN + index as i64 * 96;
+    let (r, g, b) = occupancy_color(occupancy);
+    out.push_str(&format!(
+      "<rect x=\"{}\" y=\"{}\" width=\"14\" height=\"14\" fill=\"rgb({},{},{})\" stroke=\"#94a3b8\"/>\n",
+      x, legend_y, r, g, b
+    ));
+    out.push_str(&format!(
+      "<text x=\"{}\" y=\"{}\" font-size=\"11\" fill=\"#334155\">{}</text>\n",
+      x + 20,
+      legend_y + 11,
+      occupancy_label(occupancy)
+    ));
+  }
+
+  out.push_str("</svg>\n");
+  out
+}
+
+/// 渲染 PDF（A4 横向）。内置 Helvetica 字体不支持中文，因此只输出编码与数量，
+/// 非 ASCII 字符以 ? 代替
+pub fn render_pdf(map: &RackMap) -> Result<Vec<u8>, AppError> {
+  let page_w = 297.0_f32;
+  let page_h = 210.0_f32;
+  let margin = 12.0_f32;
+  let label_w = 14.0_f32;
+  let header_h = 22.0_f32;
+  let legend_h = 12.0_f32;
+
+  let columns = max_slots(map) as f32;
+  let rows = map.levels.len().max(1) as f32;
+  let cell_w = ((page_w - margin * 2.0 - label_w) / columns).min(40.0);
+  let cell_h = ((page_h - margin * 2.0 - header_h - legend_h) / rows).min(24.0);
+  let font_size = (cell_h * 0.9).clamp(4.0, 8.0).min(cell_w * 0.35);
+
+  let title = format!("Rack {}", pdf_text(&map.rack.code));
+  let (doc, page, layer) = PdfDocument::new(&title, Mm(page_w), Mm(page_h), "Layer 1");
+  let layer = doc.get_page(page).get_layer(layer);
+  let font = doc
+    .add_builtin_font(BuiltinFont::Helvetica)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+  let bold = doc
+    .add_builtin_font(BuiltinFont::HelveticaBold)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfFontFailed))?;
+
+  let black = Color::Rgb(Rgb::new(0.06, 0.09, 0.16, None));
+  let grey = Color::Rgb(Rgb::new(0.58, 0.64, 0.72, None));
+
+  layer.set_fill_color(black.clone());
+  layer.use_text(title, 14.0, Mm(margin), Mm(page_h - margin - 5.0), &bold);
+  layer.use_text(
+    format!(
+      "Occupied {}/{} slots  -  {}",
+      map.occupied_slots,
+      map.total_slots,
+      Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ),
+    9.0,
+    Mm(margin),
+    Mm(page_h - margin - 11.0),
+    &font,
+  );
+
+  let grid_left = margin + label_w;
+  let grid_top = page_h - margin - header_h;
+  for column in 1..=(columns as i64) {
+    layer.use_text(
+      format!("{:02}", column),
+      7.0,
+      Mm(grid_left + (column as f32 - 1.0) * cell_w + cell_w / 2.0 - 2.0),
+      Mm(grid_top + 2.0),
+      &font,
+    );
+  }
+
+  layer.set_outline_color(grey);
+  layer.set_outline_thickness(0.5);
+  for (row_index, level) in map.levels.iter().enumerate() {
+    let top = grid_top - row_index as f32 * cell_h;
+    let bottom = top - cell_h;
+    layer.set_fill_color(black.clone());
+    layer.use_text(
+      format!("L{:02}", level.level_no),
+      8.0,
+      Mm(margin),
+      Mm(bottom + cell_h / 2.0 - 1.5),
+      &font,
+    );
+    for slot in &level.slots {
+      let left = grid_left + (slot.slot_no - 1).max(0) as f32 * cell_w;
+      layer.set_fill_color(pdf_color(&slot.occupancy));
+      layer.add_rect(
+        Rect::new(Mm(left), Mm(bottom), Mm(left + cell_w), Mm(top))
+          .with_mode(PaintMode::FillStroke),
+      );
+      layer.set_fill_color(black.clone());
+      layer.use_text(
+        pdf_text(&slot.code),
+        font_size,
+        Mm(left + 1.0),
+        Mm(top - font_size * 0.45),
+        &font,
+      );
+      if slot.item_count > 0 || slot.total_qty != 0.0 {
+        layer.use_text(
+          format!("{} / {}", slot.total_qty, slot.item_count),
+          font_size,
+          Mm(left + 1.0),
+          Mm(top - font_size * 0.9),
+          &bold,
+        );
+      }
+      if let Some(item_code) = &slot.top_item_code {
+        layer.use_text(
+          pdf_text(item_code),
+          font_size,
+          Mm(left + 1.0),
+          Mm(top - font_size * 1.35),
+          &font,
+        );
+      }
+    }
+  }
+
+  let legend_y = margin;
+  for (index, occupancy) in LEGEND.iter().enumerate() {
+    let x = margin + index as f32 * 30.0;
+    layer.set_fill_color(pdf_color(occupancy));
+    layer.add_rect(
+      Rect::new(Mm(x), Mm(legend_y), Mm(x + 4.0), Mm(legend_y + 4.0))
+        .with_mode(PaintMode::FillStroke),
+    );
+    layer.set_fill_color(black.clone());
+    layer.use_text(*occupancy, 8.0, Mm(x + 6.0), Mm(legend_y + 1.0), &font);
+  }
+
+  doc
+    .save_to_bytes()
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::PdfRenderFailed))
+}
+
+fn pdf_color(occupancy: &str) -> Color {
+  let (r, g, b) = occupancy_color(occupancy);
+  Color::Rgb(Rgb::new(
+    r as f32 / 255.0,
+    g as f32 / 255.0,
+    b as f32 / 255.0,
+    None,
+  ))
+}
+
+fn pdf_text(value: &str) -> String {
+  value
+    .chars()
+    .map(|c| if c.is_ascii() && !c.is_ascii_control() { c } else { '?' })
+    .collect()
+}
+
+fn escape_xml(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+fn safe_file_part(value: &str) -> String {
+  value
+    .chars()
+    .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+    .collect()
+}
@@ -3,10 +3,11 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 use crate::repo::rack_repo::{RackRow, SlotRow};
 use crate::repo::{rack_repo, stock_repo};
 use crate::repo::warehouse_repo;
-use crate::services::warehouse_service;
+use crate::services::{system_service, warehouse_service};
 
 #[derive(Debug, serde::Serialize)]
 pub struct RackListResult {
@@ -19,6 +20,60 @@ pub struct SlotListResult {
   pub items: Vec<SlotRow>,
 }
 
+/// 批量修改库位状态时被跳过的库位
+#[derive(Debug, serde::Serialize)]
+pub struct SkippedSlot {
+  pub slot_id: String,
+  pub code: Option<String>,
+  pub reason: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SlotStatusBulkResult {
+  // 实际更新的库位数
+  pub updated: i64,
+  // 已是目标状态的库位数
+  pub unchanged: i64,
+  pub skipped: Vec<SkippedSlot>,
+}
+
+/// 重新编码预览中返回的样例条数
+const RECODE_SAMPLE_LIMIT: usize = 20;
+
+#[derive(Debug, serde::Serialize)]
+pub struct SlotRecode {
+  pub slot_id: String,
+  pub old_code: String,
+  pub new_code: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct SlotRecodeResult {
+  // 范围内的库位总数
+  pub total: i64,
+  // 编码需要变更（预览）或已变更的库位数
+  pub changed: i64,
+  pub dry_run: bool,
+  pub samples: Vec<SlotRecode>,
+}
+
+/// 单次复制货架的数量上限
+pub const MAX_CLONE_RACKS: usize = 100;
+
+#[derive(Debug, serde::Serialize)]
+pub struct ClonedRack {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackCloneResult {
+  pub created: Vec<ClonedRack>,
+  // 复制源货架上被停用、在新货架上同样停用的库位数（按每个新货架计）
+  pub inactive_slots: i64,
+}
+
 pub async fn list_racks(
   pool: &SqlitePool,
   page_index: i64,
@@ -44,10 +99,7 @@ pub async fn list_slots(
 
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
   if page_index < 1 || page_size < 1 {
-    return Err(AppError::new(
-      ErrorCode::ValidationError,
-      "分页参数非法",
-    ));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
   }
   Ok((page_index, page_size))
 }
@@ -161,7 +213,7 @@ pub async fn update_rack(
 
 pub async fn set_rack_status(pool: &SqlitePool, id: &str, status: &str) -> Result<(), AppError> {
   if !matches!(status, "active" | "inactive") {
-    return Err(AppError::new(ErrorCode::ValidationError, "状态非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
   }
   if status == "inactive" {
     let count = stock_repo::count_stock_by_rack(pool, id).await?;
@@ -180,7 +232,7 @@ pub async fn set_slot_status(
   status: &str,
 ) -> Result<(), AppError> {
   if !matches!(status, "active" | "inactive") {
-    return Err(AppError::new(ErrorCode::ValidationError, "状态非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
   }
   if status == "inactive" {
     let count = stock_repo::count_stock_by_slot(pool, slot_id).await?;
@@ -193,7 +245,296 @@ pub async fn set_slot_status(
   Ok(())
 }
 
-fn normalize_rack_code(code: &str) -> Result<String, AppError> {
+/// 批量启用/停用库位：按货架（可限定层号）或显式库位 ID 列表选取；
+/// 停用时逐个校验库存，仍有库存或不存在的库位跳过并在结果中说明原因
+pub async fn set_slots_status(
+  pool: &SqlitePool,
+  rack_id: Option<String>,
+  level_no: Option<i64>,
+  slot_ids: Option<Vec<String>>,
+  status: &str,
+) -> Result<SlotStatusBulkResult, AppError> {
+  if !matches!(status, "active" | "inactive") {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
+  }
+
+  let mut skipped = Vec::new();
+  let mut slots = Vec::new();
+  let slot_ids: Vec<String> = slot_ids
+    .unwrap_or_default()
+    .into_iter()
+    .map(|id| id.trim().to_string())
+    .filter(|id| !id.is_empty())
+    .collect();
+  if !slot_ids.is_empty() {
+    for slot_id in slot_ids {
+      if slots.iter().any(|slot: &SlotRow| slot.id == slot_id) {
+        continue;
+      }
+      match rack_repo::get_slot_by_id(pool, &slot_id).await? {
+        Some(slot) => slots.push(slot),
+        None => skipped.push(SkippedSlot {
+          slot_id,
+          code: None,
+          reason: "库位不存在".to_string(),
+        }),
+      }
+    }
+  } else {
+    let rack_id = rack_id
+      .map(|value| value.trim().to_string())
+      .filter(|value| !value.is_empty())
+      .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "请指定货架或库位"))?;
+    if rack_repo::get_rack_by_id(pool, &rack_id).await?.is_none() {
+      return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound));
+    }
+    if let Some(level_no) = level_no {
+      if level_no < 1 {
+        return Err(AppError::new(ErrorCode::ValidationError, "层号非法"));
+      }
+    }
+    slots = rack_repo::list_slots(pool, Some(rack_id), None, level_no).await?;
+  }
+
+  let mut unchanged = 0;
+  let mut targets = Vec::new();
+  for slot in slots {
+    if slot.status == status {
+      unchanged += 1;
+      continue;
+    }
+    if status == "inactive" && stock_repo::count_stock_by_slot(pool, &slot.id).await? > 0 {
+      skipped.push(SkippedSlot {
+        slot_id: slot.id,
+        code: Some(slot.code),
+        reason: "库位仍有库存".to_string(),
+      });
+      continue;
+    }
+    targets.push(slot.id);
+  }
+
+  let updated = if targets.is_empty() {
+    0
+  } else {
+    rack_repo::set_slots_status(pool, &targets, status).await?
+  };
+  Ok(SlotStatusBulkResult {
+    updated: updated as i64,
+    unchanged,
+    skipped,
+  })
+}
+
+/// 复制货架：沿用源货架的仓库、位置、层数与每层格数，为每个新编号按标准规则生成库位，
+/// 源货架上停用的库位在新货架的同一层/格上同样停用。
+/// 新编号可以是显式列表，也可以是起止区间（如 R5 到 R12）；任一编号已存在则整体不创建。
+pub async fn clone_rack(
+  pool: &SqlitePool,
+  source_rack_id: &str,
+  codes: Option<Vec<String>>,
+  code_from: Option<String>,
+  code_to: Option<String>,
+  name_template: Option<String>,
+) -> Result<RackCloneResult, AppError> {
+  let source = rack_repo::get_rack_by_id(pool, source_rack_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound))?;
+  let warehouse_id = source
+    .warehouse_id
+    .clone()
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "源货架未绑定仓库，无法复制"))?;
+  warehouse_service::ensure_warehouse_exists(pool, &warehouse_id).await?;
+  let warehouse_code = warehouse_repo::get_warehouse_by_id(pool, &warehouse_id)
+    .await?
+    .map(|warehouse| warehouse.code);
+
+  let codes = resolve_clone_codes(codes, code_from, code_to)?;
+  for code in &codes {
+    if rack_repo::get_rack_by_code_and_warehouse(pool, code, &warehouse_id)
+      .await?
+      .is_some()
+    {
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        format!("该仓库下的货架编号已存在：R{}", code),
+      ));
+    }
+  }
+
+  let template = name_template
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty())
+    .unwrap_or_else(|| "R{code}".to_string());
+  let inactive: Vec<(i64, i64)> = rack_repo::list_slots(pool, Some(source.id.clone()), None, None)
+    .await?
+    .into_iter()
+    .filter(|slot| slot.status == "inactive")
+    .map(|slot| (slot.level_no, slot.slot_no))
+    .collect();
+
+  let mut created = Vec::with_capacity(codes.len());
+  for code in codes {
+    let id = Uuid::new_v4().to_string();
+    let name = template.replace("{code}", &code);
+    let now = Utc::now().timestamp();
+    rack_repo::insert_rack(
+      pool,
+      &id,
+      &code,
+      &name,
+      Some(warehouse_id.clone()),
+      source.location.clone(),
+      "active",
+      source.level_count,
+      source.slots_per_level,
+      now,
+    )
+    .await?;
+    regenerate_slots(
+      pool,
+      &id,
+      &code,
+      Some(&warehouse_id),
+      warehouse_code.as_deref(),
+      source.level_count,
+      source.slots_per_level,
+      now,
+    )
+    .await?;
+
+    if !inactive.is_empty() {
+      let targets: Vec<String> = rack_repo::list_slots(pool, Some(id.clone()), None, None)
+        .await?
+        .into_iter()
+        .filter(|slot| inactive.contains(&(slot.level_no, slot.slot_no)))
+        .map(|slot| slot.id)
+        .collect();
+      rack_repo::set_slots_status(pool, &targets, "inactive").await?;
+    }
+    created.push(ClonedRack { id, code, name });
+  }
+
+  Ok(RackCloneResult {
+    created,
+    inactive_slots: inactive.len() as i64,
+  })
+}
+
+/// 汇总复制目标编号：显式列表优先，否则按起止区间展开；
+/// 区间起始编号带前导零时按其位数补零（如 05 到 12）
+fn resolve_clone_codes(
+  codes: Option<Vec<String>>,
+  code_from: Option<String>,
+  code_to: Option<String>,
+) -> Result<Vec<String>, AppError> {
+  let mut result: Vec<String> = Vec::new();
+  let listed: Vec<String> = codes
+    .unwrap_or_default()
+    .into_iter()
+    .filter(|code| !code.trim().is_empty())
+    .collect();
+  if !listed.is_empty() {
+    for code in listed {
+      let normalized = normalize_rack_code(&code)?;
+      if !result.contains(&normalized) {
+        result.push(normalized);
+      }
+    }
+  } else {
+    let (from, to) = match (code_from, code_to) {
+      (Some(from), Some(to)) => (normalize_rack_code(&from)?, normalize_rack_code(&to)?),
+      _ => return Err(AppError::new(ErrorCode::ValidationError, "请填写新货架编号或编号区间")),
+    };
+    let start: u64 = from
+      .parse()
+      .map_err(|_| AppError::new(ErrorCode::ValidationError, "货架编号区间非法"))?;
+    let end: u64 = to
+      .parse()
+      .map_err(|_| AppError::new(ErrorCode::ValidationError, "货架编号区间非法"))?;
+    if start > end {
+      return Err(AppError::new(ErrorCode::ValidationError, "货架编号区间非法"));
+    }
+    if end - start >= MAX_CLONE_RACKS as u64 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("单次最多复制 {} 个货架", MAX_CLONE_RACKS),
+      ));
+    }
+    let width = if from.starts_with('0') { from.len() } else { 0 };
+    for value in start..=end {
+      result.push(format!("{:0width$}", value, width = width));
+    }
+  }
+
+  if result.len() > MAX_CLONE_RACKS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("单次最多复制 {} 个货架", MAX_CLONE_RACKS),
+    ));
+  }
+  Ok(result)
+}
+
+/// 标准库位编码：仓库编码-R货架编号-层号-格号，层号与格号按 slot_no_pad 补零（如 W1-R1-01-02）
+pub fn slot_code(warehouse_code: &str, rack_code: &str, level_no: i64, slot_no: i64, pad: i64) -> String {
+  let width = pad.max(1) as usize;
+  format!(
+    "{}-R{}-{:0width$}-{:0width$}",
+    warehouse_code,
+    rack_code,
+    level_no,
+    slot_no,
+    width = width
+  )
+}
+
+/// 按当前 slot_no_pad 重新编码已有库位，库位 ID 与库存、流水关联保持不变；
+/// dry_run 时只返回将要变更的数量与样例
+pub async fn recode_slots(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  rack_id: Option<String>,
+  dry_run: bool,
+) -> Result<SlotRecodeResult, AppError> {
+  let pad = system_service::slot_no_pad(pool).await?;
+  let sources = rack_repo::list_slot_code_sources(pool, warehouse_id, rack_id).await?;
+  let total = sources.len() as i64;
+
+  let mut changes = Vec::new();
+  let mut samples = Vec::new();
+  for source in sources {
+    let warehouse_code = source
+      .warehouse_code
+      .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "仓库缺失，无法生成库位编码"))?;
+    let new_code = slot_code(&warehouse_code, &source.rack_code, source.level_no, source.slot_no, pad);
+    if new_code == source.code {
+      continue;
+    }
+    if samples.len() < RECODE_SAMPLE_LIMIT {
+      samples.push(SlotRecode {
+        slot_id: source.slot_id.clone(),
+        old_code: source.code,
+        new_code: new_code.clone(),
+      });
+    }
+    changes.push((source.slot_id, new_code));
+  }
+
+  let changed = if dry_run || changes.is_empty() {
+    changes.len() as i64
+  } else {
+    rack_repo::update_slot_codes(pool, &changes).await? as i64
+  };
+  Ok(SlotRecodeResult {
+    total,
+    changed,
+    dry_run,
+    samples,
+  })
+}
+
+pub fn normalize_rack_code(code: &str) -> Result<String, AppError> {
   let trimmed = code.trim();
   let suffix = trimmed.trim_start_matches(|value: char| value == 'R' || value == 'r');
   if suffix.is_empty() {
@@ -245,16 +586,11 @@ pub async fn regenerate_slots(
     AppError::new(ErrorCode::ValidationError, "仓库缺失，无法生成库位编码")
   })?;
 
+  let pad = system_service::slot_no_pad(pool).await?;
   let mut slots = Vec::new();
   for level in 1..=level_count {
     for slot_no in 1..=slots_per_level {
-      let base_code = format!(
-        "{}-{}-{}",
-        rack_code,
-        level,
-        slot_no
-      );
-      let code = format!("{}-{}", resolved_warehouse_code, base_code);
+      let code = slot_code(&resolved_warehouse_code, rack_code, level, slot_no, pad);
       slots.push(SlotRow {
         id: Uuid::new_v4().to_string(),
         rack_id: rack_id.to_string(),
@@ -0,0 +1,207 @@
+// 原因代码字典：调整、冲正、报废流水的原因统一维护，按流水类型配置是否必填，流水查询与报表可按原因筛选统计
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::repo::meta_repo;
+use crate::repo::reason_code_repo::{self, ReasonCodeRow};
+use crate::services::txn_service;
+
+/// 可记录原因的流水类型
+pub const REASON_TXN_TYPES: [&str; 3] = ["ADJUST", "REVERSAL", "SCRAP"];
+
+/// 未配置时须填写原因的流水类型（报废始终需要原因）
+const DEFAULT_REQUIRED_TYPES: &str = "SCRAP";
+
+/// 原因编码与名称的字符数上限
+const MAX_REASON_LEN: usize = 32;
+
+#[derive(Debug, Deserialize)]
+pub struct ReasonCodeInput {
+  pub code: String,
+  pub name: String,
+  // 适用的流水类型，缺省为全部（ADJUST / REVERSAL / SCRAP）
+  pub txn_types: Option<Vec<String>>,
+  pub sort_no: Option<i64>,
+  // 缺省为 active
+  pub status: Option<String>,
+}
+
+/// 原因列表；txn_type 指定时仅返回适用于该类型的原因
+pub async fn list_reasons(
+  pool: &SqlitePool,
+  only_active: bool,
+  txn_type: Option<String>,
+) -> Result<Vec<ReasonCodeRow>, AppError> {
+  let reasons = reason_code_repo::list_reasons(pool, only_active).await?;
+  let txn_type = txn_type.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  Ok(match txn_type {
+    Some(txn_type) => reasons.into_iter().filter(|reason| reason.txn_types.contains(&txn_type)).collect(),
+    None => reasons,
+  })
+}
+
+pub async fn create_reason(pool: &SqlitePool, input: &ReasonCodeInput) -> Result<ReasonCodeRow, AppError> {
+  let reason = build_reason(Uuid::new_v4().to_string(), input, Utc::now().timestamp())?;
+  if reason_code_repo::get_reason_by_code(pool, &reason.code).await?.is_some() {
+    return Err(AppError::new(ErrorCode::Conflict, "原因编码已存在"));
+  }
+  reason_code_repo::insert_reason(pool, &reason).await?;
+  Ok(reason)
+}
+
+pub async fn update_reason(pool: &SqlitePool, id: &str, input: &ReasonCodeInput) -> Result<(), AppError> {
+  let current = reason_code_repo::get_reason(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "原因不存在"))?;
+  let reason = build_reason(id.to_string(), input, current.created_at)?;
+  if let Some(existing) = reason_code_repo::get_reason_by_code(pool, &reason.code).await? {
+    if existing.id != id {
+      return Err(AppError::new(ErrorCode::Conflict, "原因编码已存在"));
+    }
+  }
+  reason_code_repo::update_reason(pool, &reason).await?;
+  Ok(())
+}
+
+/// 删除原因；已被流水引用的原因只能停用
+pub async fn delete_reason(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  if reason_code_repo::count_txn_refs(pool, id).await? > 0 {
+    return Err(AppError::new(ErrorCode::Conflict, "该原因已被流水引用，只能停用"));
+  }
+  if reason_code_repo::delete_reason(pool, id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "原因不存在"));
+  }
+  Ok(())
+}
+
+/// 须填写原因的流水类型（app_meta 中以逗号分隔保存，空字符串表示均不强制）
+pub async fn required_txn_types(pool: &SqlitePool) -> Result<Vec<String>, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "reason_required_types")
+      .await?
+      .unwrap_or_else(|| DEFAULT_REQUIRED_TYPES.to_string())
+      .split(',')
+      .map(str::trim)
+      .filter(|value| REASON_TXN_TYPES.contains(value))
+      .map(str::to_string)
+      .collect(),
+  )
+}
+
+pub async fn set_required_txn_types(pool: &SqlitePool, txn_types: &[String]) -> Result<(), AppError> {
+  let txn_types = normalize_txn_types(txn_types)
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "reason_required_types 仅支持 ADJUST/REVERSAL/SCRAP"))?;
+  meta_repo::set_meta_value(pool, "reason_required_types", &txn_types.join(",")).await
+}
+
+pub async fn is_required(pool: &SqlitePool, txn_type: &str) -> Result<bool, AppError> {
+  Ok(required_txn_types(pool).await?.iter().any(|value| value == txn_type))
+}
+
+/// 校验流水所选原因：须存在、已启用且适用于该流水类型，返回原因 id；未选择时返回 None
+pub async fn resolve_reason(
+  pool: &SqlitePool,
+  txn_type: &str,
+  reason_id: Option<&str>,
+) -> Result<Option<String>, AppError> {
+  let Some(reason_id) = reason_id.map(str::trim).filter(|value| !value.is_empty()) else {
+    return Ok(None);
+  };
+  let reason = reason_code_repo::get_reason(pool, reason_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "原因不存在"))?;
+  ensure_applicable(&reason, txn_type)?;
+  Ok(Some(reason.id))
+}
+
+/// 按原因编码解析（导入使用），校验规则同 resolve_reason
+pub async fn resolve_reason_by_code(
+  pool: &SqlitePool,
+  txn_type: &str,
+  code: Option<&str>,
+) -> Result<Option<String>, AppError> {
+  let Some(code) = code.map(str::trim).filter(|value| !value.is_empty()) else {
+    return Ok(None);
+  };
+  let reason = reason_code_repo::get_reason_by_code(pool, code)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, format!("原因编码 {} 不存在", code)))?;
+  ensure_applicable(&reason, txn_type)?;
+  Ok(Some(reason.id))
+}
+
+/// 流水类型配置为必填原因但未选择时报错
+pub fn ensure_present(required: bool, txn_type: &str, reason_id: Option<&String>) -> Result<(), AppError> {
+  if required && reason_id.is_none() {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("{}须选择原因", txn_service::txn_type_label(txn_type)),
+    ));
+  }
+  Ok(())
+}
+
+fn ensure_applicable(reason: &ReasonCodeRow, txn_type: &str) -> Result<(), AppError> {
+  if reason.status != "active" {
+    return Err(AppError::new(ErrorCode::ValidationError, format!("原因“{}”已停用", reason.name)));
+  }
+  if !reason.txn_types.iter().any(|value| value == txn_type) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("原因“{}”不适用于{}", reason.name, txn_service::txn_type_label(txn_type)),
+    ));
+  }
+  Ok(())
+}
+
+fn normalize_txn_types(txn_types: &[String]) -> Result<Vec<String>, ()> {
+  let mut normalized: Vec<String> = Vec::new();
+  for txn_type in txn_types {
+    let txn_type = txn_type.trim().to_ascii_uppercase();
+    if !REASON_TXN_TYPES.contains(&txn_type.as_str()) {
+      return Err(());
+    }
+    if !normalized.contains(&txn_type) {
+      normalized.push(txn_type);
+    }
+  }
+  Ok(normalized)
+}
+
+fn build_reason(id: String, input: &ReasonCodeInput, created_at: i64) -> Result<ReasonCodeRow, AppError> {
+  let code = input.code.trim().to_string();
+  let name = input.name.trim().to_string();
+  if code.is_empty() || name.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "原因编码与名称不能为空"));
+  }
+  if code.chars().count() > MAX_REASON_LEN || name.chars().count() > MAX_REASON_LEN {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("原因编码与名称不超过 {} 个字符", MAX_REASON_LEN),
+    ));
+  }
+  let txn_types = match input.txn_types.as_deref() {
+    Some(txn_types) => normalize_txn_types(txn_types)
+      .map_err(|_| AppError::new(ErrorCode::ValidationError, "适用类型仅支持 ADJUST/REVERSAL/SCRAP"))?,
+    None => REASON_TXN_TYPES.iter().map(|value| value.to_string()).collect(),
+  };
+  if txn_types.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "至少选择一种适用的流水类型"));
+  }
+  let status = input.status.as_deref().unwrap_or("active");
+  if !matches!(status, "active" | "disabled") {
+    return Err(AppError::new(ErrorCode::ValidationError, "原因状态非法"));
+  }
+  Ok(ReasonCodeRow {
+    id,
+    code,
+    name,
+    txn_types,
+    sort_no: input.sort_no.unwrap_or(0),
+    status: status.to_string(),
+    created_at,
+  })
+}
@@ -0,0 +1,246 @@
+// 远程备份：备份完成后上传到 S3 兼容存储或 WebDAV，并支持从远程列表恢复
+//
+// 配置保存在 app_meta（remote_backup_*），密钥/密码以 AES-GCM 加密保存，
+// 加密密钥位于 <storage_root>/db/secret.key，不随数据库备份上传。
+use std::path::{Path, PathBuf};
+
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::infra::remote_store::{RemoteObject, RemoteStore};
+use crate::infra::{crypto, fs};
+use crate::repo::meta_repo;
+
+/// 远程备份配置（不含明文密钥）
+#[derive(Debug, Serialize)]
+pub struct RemoteBackupConfig {
+  // none/s3/webdav
+  pub kind: String,
+  // 服务地址；WebDAV 为备份目录的上级地址
+  pub endpoint: String,
+  // S3 桶名
+  pub bucket: String,
+  // S3 区域
+  pub region: String,
+  // S3 对象前缀或 WebDAV 目录名
+  pub prefix: String,
+  // S3 Access Key 或 WebDAV 用户名
+  pub username: String,
+  // 是否已保存密钥/密码
+  pub secret_set: bool,
+  // 最近一次成功上传时间
+  pub last_upload_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoteBackupConfigPatch {
+  pub kind: String,
+  pub endpoint: Option<String>,
+  pub bucket: Option<String>,
+  pub region: Option<String>,
+  pub prefix: Option<String>,
+  pub username: Option<String>,
+  // 为空表示保留已保存的密钥
+  pub secret: Option<String>,
+}
+
+pub async fn get_config(pool: &SqlitePool) -> Result<RemoteBackupConfig, AppError> {
+  let value = |key: &'static str| async move {
+    Ok::<_, AppError>(meta_repo::get_meta_value(pool, key).await?.unwrap_or_default())
+  };
+  let kind = Some(value("remote_backup_kind").await?)
+    .filter(|kind| matches!(kind.as_str(), "s3" | "webdav"))
+    .unwrap_or_else(|| "none".to_string());
+  Ok(RemoteBackupConfig {
+    kind,
+    endpoint: value("remote_backup_endpoint").await?,
+    bucket: value("remote_backup_bucket").await?,
+    region: value("remote_backup_region").await?,
+    prefix: value("remote_backup_prefix").await?,
+    username: value("remote_backup_username").await?,
+    secret_set: !value("remote_backup_secret").await?.is_empty(),
+    last_upload_at: meta_repo::get_meta_i64(pool, "last_remote_backup_at").await?,
+  })
+}
+
+pub async fn set_config(pool: &SqlitePool, patch: &RemoteBackupConfigPatch) -> Result<(), AppError> {
+  let kind = patch.kind.trim();
+  if !matches!(kind, "none" | "s3" | "webdav") {
+    return Err(AppError::new(ErrorCode::ValidationError, "远程备份类型仅支持 none/s3/webdav"));
+  }
+  let trimmed = |value: &Option<String>| value.as_deref().unwrap_or_default().trim().to_string();
+  let endpoint = trimmed(&patch.endpoint);
+  let bucket = trimmed(&patch.bucket);
+  let region = trimmed(&patch.region);
+  let prefix = trimmed(&patch.prefix);
+  let username = trimmed(&patch.username);
+  let secret = patch.secret.as_deref().filter(|secret| !secret.is_empty());
+
+  if kind != "none" {
+    parse_endpoint(&endpoint)?;
+    if username.is_empty() {
+      return Err(AppError::new(ErrorCode::ValidationError, "请填写账号或 Access Key"));
+    }
+    let secret_saved = meta_repo::get_meta_value(pool, "remote_backup_secret")
+      .await?
+      .is_some_and(|value| !value.is_empty());
+    if secret.is_none() && !secret_saved {
+      return Err(AppError::new(ErrorCode::ValidationError, "请填写密码或 Secret Key"));
+    }
+  }
+  if kind == "s3" && bucket.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "请填写存储桶名称"));
+  }
+  if prefix.split('/').any(|part| part == "..") {
+    return Err(AppError::new(ErrorCode::ValidationError, "目录不能包含 .."));
+  }
+
+  if let Some(secret) = secret {
+    let key = load_key(pool).await?;
+    let encrypted = crypto::encrypt_secret(&key, secret)?;
+    meta_repo::set_meta_value(pool, "remote_backup_secret", &encrypted).await?;
+  }
+  meta_repo::set_meta_value(pool, "remote_backup_kind", kind).await?;
+  meta_repo::set_meta_value(pool, "remote_backup_endpoint", &endpoint).await?;
+  meta_repo::set_meta_value(pool, "remote_backup_bucket", &bucket).await?;
+  meta_repo::set_meta_value(pool, "remote_backup_region", &region).await?;
+  meta_repo::set_meta_value(pool, "remote_backup_prefix", &prefix).await?;
+  meta_repo::set_meta_value(pool, "remote_backup_username", &username).await?;
+  Ok(())
+}
+
+/// 上传本地备份文件；未配置远程备份时不处理，返回是否已上传
+pub async fn upload_backup(pool: &SqlitePool, backup_path: &str) -> Result<bool, AppError> {
+  let Some(store) = load_store(pool).await? else {
+    return Ok(false);
+  };
+  let path = Path::new(backup_path);
+  let name = path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "备份文件名无效"))?;
+  let body = std::fs::read(path).map_err(|_| AppError::new(ErrorCode::IoError, "读取备份文件失败"))?;
+  store.put(name, body).await.map_err(|err| {
+    AppError::new(
+      err.code,
+      format!("本地备份已保存至 {}，上传远程备份失败：{}", backup_path, err.message),
+    )
+  })?;
+  meta_repo::set_meta_value(pool, "last_remote_backup_at", &chrono::Utc::now().timestamp().to_string())
+    .await?;
+  Ok(true)
+}
+
+/// 列出远程备份（新的在前）
+pub async fn list_remote_backups(pool: &SqlitePool) -> Result<Vec<RemoteObject>, AppError> {
+  let store = load_store(pool)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "未配置远程备份"))?;
+  let mut items: Vec<RemoteObject> = store
+    .list()
+    .await?
+    .into_iter()
+    .filter(|item| is_backup_name(&item.name))
+    .collect();
+  items.sort_by(|a, b| b.name.cmp(&a.name));
+  Ok(items)
+}
+
+/// 下载远程备份到本地备份目录，返回本地路径（随后按本地文件恢复）
+pub async fn download_remote_backup(pool: &SqlitePool, name: &str) -> Result<String, AppError> {
+  if !is_backup_name(name) {
+    return Err(AppError::new(ErrorCode::ValidationError, "备份文件名无效"));
+  }
+  let store = load_store(pool)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "未配置远程备份"))?;
+  let body = store.get(name).await?;
+  if !body.starts_with(b"SQLite format 3\0") && !crypto::is_encrypted_backup(&body) {
+    return Err(AppError::new(ErrorCode::ValidationError, "远程文件不是有效的数据库备份"));
+  }
+
+  // 移动端使用临时目录，桌面端使用备份目录
+  #[cfg(any(target_os = "android", target_os = "ios"))]
+  let backups_dir = std::env::temp_dir();
+
+  #[cfg(not(any(target_os = "android", target_os = "ios")))]
+  let backups_dir = storage_root(pool).await?.join("backups");
+
+  fs::ensure_dir(&backups_dir)?;
+  let local_path = backups_dir.join(format!("remote_{}", name));
+  std::fs::write(&local_path, body).map_err(|_| AppError::new(ErrorCode::IoError, "保存远程备份失败"))?;
+  Ok(local_path.to_string_lossy().to_string())
+}
+
+/// 备份文件名：db_backup_<时间戳>.sqlite（加密备份另有 .enc 后缀），同时防止路径穿越
+fn is_backup_name(name: &str) -> bool {
+  name
+    .strip_prefix("db_backup_")
+    .and_then(|rest| rest.strip_suffix(".sqlite.enc").or_else(|| rest.strip_suffix(".sqlite")))
+    .is_some_and(|stamp| !stamp.is_empty() && stamp.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn parse_endpoint(endpoint: &str) -> Result<Url, AppError> {
+  Url::parse(endpoint)
+    .ok()
+    .filter(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some())
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "服务地址需为 http(s):// 开头的完整地址"))
+}
+
+async fn storage_root(pool: &SqlitePool) -> Result<PathBuf, AppError> {
+  let storage_root = meta_repo::get_meta_value(pool, "storage_root")
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
+  Ok(PathBuf::from(storage_root))
+}
+
+/// 本机密钥，用于加密保存远程备份密钥与备份口令
+pub async fn load_key(pool: &SqlitePool) -> Result<[u8; 32], AppError> {
+  let db_dir = storage_root(pool).await?.join("db");
+  fs::ensure_dir(&db_dir)?;
+  crypto::load_or_create_key(&db_dir.join("secret.key"))
+}
+
+/// 按配置构造远程存储，未启用时返回 None
+async fn load_store(pool: &SqlitePool) -> Result<Option<RemoteStore>, AppError> {
+  let config = get_config(pool).await?;
+  if config.kind == "none" {
+    return Ok(None);
+  }
+  let encrypted = meta_repo::get_meta_value(pool, "remote_backup_secret")
+    .await?
+    .filter(|value| !value.is_empty())
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "远程备份密钥未配置"))?;
+  let secret = crypto::decrypt_secret(&load_key(pool).await?, &encrypted)?;
+  let endpoint = parse_endpoint(&config.endpoint)?;
+  let prefix = config.prefix.trim_matches('/').to_string();
+
+  let store = if config.kind == "s3" {
+    RemoteStore::S3 {
+      endpoint,
+      bucket: config.bucket,
+      region: Some(config.region).filter(|region| !region.is_empty()).unwrap_or_else(|| "us-east-1".to_string()),
+      prefix: if prefix.is_empty() { prefix } else { format!("{}/", prefix) },
+      access_key: config.username,
+      secret_key: secret,
+    }
+  } else {
+    let mut base = endpoint.to_string();
+    if !base.ends_with('/') {
+      base.push('/');
+    }
+    if !prefix.is_empty() {
+      base.push_str(&prefix);
+      base.push('/');
+    }
+    RemoteStore::WebDav {
+      base: Url::parse(&base).map_err(|_| AppError::new(ErrorCode::ValidationError, "远程地址无效"))?,
+      username: config.username,
+      password: secret,
+    }
+  };
+  Ok(Some(store))
+}
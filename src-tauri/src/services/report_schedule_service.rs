@@ -0,0 +1,288 @@
+// 定时报表：按本机时间每天生成报表文件到导出目录，配置收件人时把报表作为附件放入邮件队列
+//
+// 后台任务每 10 分钟检查一次，到达执行时间且当天尚未执行的计划会被执行（最多延后一个检查周期）；
+// 每次执行（含手动执行）写入执行记录，邮件由队列发送后回写投递结果，生成或投递失败时写入通知。
+use chrono::{Duration, Local, NaiveTime, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::repo::report_schedule_repo::{self, ReportScheduleRow, ReportScheduleRunRow};
+use crate::services::system_service::{self, NewMail};
+use crate::services::{notification_service, report_service, txn_service};
+
+const REPORT_KINDS: [&str; 2] = ["txn_journal", "low_stock"];
+const SCHEDULE_STATUSES: [&str; 2] = ["active", "disabled"];
+/// 执行记录列表最多返回的条数
+const MAX_RUN_LIST: i64 = 200;
+/// 执行记录保留天数
+const RUN_RETENTION_DAYS: i64 = 180;
+
+#[derive(Debug, Deserialize)]
+pub struct ReportScheduleInput {
+  pub name: String,
+  pub report_kind: String,
+  pub run_time: String,
+  // 逗号或分号分隔，为空时只生成文件
+  pub email_to: Option<String>,
+  pub status: Option<String>,
+}
+
+pub fn report_kind_label(report_kind: &str) -> &str {
+  match report_kind {
+    "txn_journal" => "流水日报",
+    "low_stock" => "低库存报表",
+    other => other,
+  }
+}
+
+pub async fn list_schedules(pool: &SqlitePool) -> Result<Vec<ReportScheduleRow>, AppError> {
+  report_schedule_repo::list_schedules(pool).await
+}
+
+pub async fn create_schedule(pool: &SqlitePool, input: &ReportScheduleInput) -> Result<ReportScheduleRow, AppError> {
+  let now = Utc::now().timestamp();
+  let mut schedule = build_schedule(input, Uuid::new_v4().to_string(), now)?;
+  if report_schedule_repo::name_exists(pool, &schedule.name, None).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "定时报表名称已存在"));
+  }
+  // 当天执行时间已过的不补执行，从次日开始
+  schedule.last_run_date = passed_today(&schedule.run_time);
+  report_schedule_repo::save_schedule(pool, &schedule).await?;
+  Ok(schedule)
+}
+
+pub async fn update_schedule(
+  pool: &SqlitePool,
+  id: &str,
+  input: &ReportScheduleInput,
+) -> Result<ReportScheduleRow, AppError> {
+  let existing = report_schedule_repo::get_schedule(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "定时报表不存在"))?;
+  let mut schedule = build_schedule(input, existing.id.clone(), existing.created_at)?;
+  if report_schedule_repo::name_exists(pool, &schedule.name, Some(id)).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "定时报表名称已存在"));
+  }
+  schedule.updated_at = Utc::now().timestamp();
+  schedule.last_run_date = if schedule.run_time == existing.run_time {
+    existing.last_run_date
+  } else {
+    passed_today(&schedule.run_time)
+  };
+  report_schedule_repo::save_schedule(pool, &schedule).await?;
+  Ok(schedule)
+}
+
+pub async fn delete_schedule(pool: &SqlitePool, id: &str) -> Result<(), AppError> {
+  if report_schedule_repo::delete_schedule(pool, id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "定时报表不存在"));
+  }
+  Ok(())
+}
+
+pub async fn get_schedule(pool: &SqlitePool, id: &str) -> Result<ReportScheduleRow, AppError> {
+  report_schedule_repo::get_schedule(pool, id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "定时报表不存在"))
+}
+
+/// 执行记录（最近 200 条）
+pub async fn list_runs(pool: &SqlitePool, schedule_id: Option<String>) -> Result<Vec<ReportScheduleRunRow>, AppError> {
+  let schedule_id = schedule_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  report_schedule_repo::list_runs(pool, schedule_id.as_deref(), MAX_RUN_LIST).await
+}
+
+/// 到期的计划：已启用、当天已到执行时间且当天尚未定时执行
+pub async fn due_schedules(pool: &SqlitePool) -> Result<Vec<ReportScheduleRow>, AppError> {
+  let now = Local::now();
+  let today = now.format("%Y-%m-%d").to_string();
+  let now_time = now.format("%H:%M").to_string();
+  Ok(
+    report_schedule_repo::list_active_schedules(pool)
+      .await?
+      .into_iter()
+      .filter(|schedule| schedule.run_time <= now_time && schedule.last_run_date.as_deref() != Some(today.as_str()))
+      .collect(),
+  )
+}
+
+/// 执行计划：生成报表文件；失败不返回错误，结果记在执行记录中（尚未保存）
+pub async fn run_schedule(pool: &SqlitePool, schedule: &ReportScheduleRow, trigger_type: &str) -> ReportScheduleRunRow {
+  let started_at = Utc::now().timestamp();
+  let mut run = ReportScheduleRunRow {
+    id: Uuid::new_v4().to_string(),
+    schedule_id: Some(schedule.id.clone()),
+    schedule_name: schedule.name.clone(),
+    report_kind: schedule.report_kind.clone(),
+    trigger_type: trigger_type.to_string(),
+    status: "success".to_string(),
+    file_path: None,
+    email_status: "none".to_string(),
+    error: None,
+    started_at,
+    finished_at: started_at,
+  };
+
+  match generate_report(pool, &schedule.report_kind).await {
+    Ok(file_path) => run.file_path = Some(file_path),
+    Err(err) => {
+      run.status = "failed".to_string();
+      run.error = Some(err.message);
+    }
+  }
+  run.finished_at = Utc::now().timestamp();
+  run
+}
+
+/// 保存执行记录；报表已生成且配置了收件人时把报表放入邮件队列，定时执行同时记录执行日期，失败时写入通知。
+/// 返回是否新增了通知
+pub async fn record_run(
+  pool: &SqlitePool,
+  schedule: &ReportScheduleRow,
+  run: &mut ReportScheduleRunRow,
+) -> Result<bool, AppError> {
+  let recipients = system_service::parse_recipients(schedule.email_to.as_deref()).unwrap_or_default();
+  if run.status == "success" && !recipients.is_empty() {
+    match queue_report_mail(pool, schedule, run, recipients).await {
+      Ok(()) => run.email_status = "queued".to_string(),
+      Err(err) => {
+        run.status = "failed".to_string();
+        run.email_status = "failed".to_string();
+        run.error = Some(format!("报表已生成，邮件未发送：{}", err.message));
+      }
+    }
+  }
+  report_schedule_repo::insert_run(pool, run).await?;
+  if run.trigger_type == "schedule" {
+    if let Some(schedule_id) = run.schedule_id.as_deref() {
+      let today = Local::now().format("%Y-%m-%d").to_string();
+      report_schedule_repo::set_last_run_date(pool, schedule_id, &today).await?;
+    }
+  }
+  report_schedule_repo::prune_runs(pool, Utc::now().timestamp() - RUN_RETENTION_DAYS * 86400).await?;
+  if run.status == "failed" {
+    return notification_service::notify_report_failed(pool, run).await;
+  }
+  Ok(false)
+}
+
+/// 邮件队列回写投递结果：error 为 None 表示已发送，否则标记执行失败并写入通知；返回是否新增了通知
+pub async fn apply_mail_result(pool: &SqlitePool, run_id: &str, error: Option<&str>) -> Result<bool, AppError> {
+  let Some(mut run) = report_schedule_repo::get_run(pool, run_id).await? else {
+    return Ok(false);
+  };
+  match error {
+    None => {
+      run.email_status = "sent".to_string();
+      report_schedule_repo::update_run_result(pool, &run).await?;
+      Ok(false)
+    }
+    Some(error) => {
+      run.status = "failed".to_string();
+      run.email_status = "failed".to_string();
+      run.error = Some(format!("报表已生成，邮件发送失败：{}", error));
+      report_schedule_repo::update_run_result(pool, &run).await?;
+      notification_service::notify_report_failed(pool, &run).await
+    }
+  }
+}
+
+fn build_schedule(input: &ReportScheduleInput, id: String, created_at: i64) -> Result<ReportScheduleRow, AppError> {
+  let name = input.name.trim();
+  if name.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "定时报表名称不能为空"));
+  }
+  let report_kind = input.report_kind.trim();
+  if !REPORT_KINDS.contains(&report_kind) {
+    return Err(AppError::new(ErrorCode::ValidationError, "报表类型仅支持 txn_journal/low_stock"));
+  }
+  let run_time = NaiveTime::parse_from_str(input.run_time.trim(), "%H:%M")
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "执行时间格式应为 HH:MM"))?
+    .format("%H:%M")
+    .to_string();
+  let recipients = system_service::parse_recipients(input.email_to.as_deref())?;
+  let status = input.status.as_deref().map(str::trim).unwrap_or("active");
+  if !SCHEDULE_STATUSES.contains(&status) {
+    return Err(AppError::new(ErrorCode::ValidationError, "计划状态仅支持 active/disabled"));
+  }
+  Ok(ReportScheduleRow {
+    id,
+    name: name.to_string(),
+    report_kind: report_kind.to_string(),
+    run_time,
+    email_to: Some(recipients.join(", ")).filter(|value| !value.is_empty()),
+    status: status.to_string(),
+    last_run_date: None,
+    created_at,
+    updated_at: created_at,
+  })
+}
+
+/// 执行时间已过时返回当天日期，用于跳过当天的执行
+fn passed_today(run_time: &str) -> Option<String> {
+  let now = Local::now();
+  (run_time <= now.format("%H:%M").to_string().as_str()).then(|| now.format("%Y-%m-%d").to_string())
+}
+
+/// 生成报表文件，返回文件路径
+async fn generate_report(pool: &SqlitePool, report_kind: &str) -> Result<String, AppError> {
+  match report_kind {
+    "txn_journal" => {
+      // 昨日（报表时区）0 点至今日 0 点前的全部流水
+      let timezone = system_service::report_timezone(pool).await?;
+      let today = timezone.today();
+      let start_at = timezone.day_start(today - Duration::days(1));
+      let end_at = timezone.day_start(today) - 1;
+      let result = txn_service::export_txns(
+        pool,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        Some(start_at),
+        Some(end_at),
+        None,
+      )
+      .await?;
+      Ok(result.file_path)
+    }
+    "low_stock" => Ok(report_service::export_low_stock_report(pool).await?.file_path),
+    _ => Err(AppError::new(ErrorCode::ValidationError, "报表类型仅支持 txn_journal/low_stock")),
+  }
+}
+
+async fn queue_report_mail(
+  pool: &SqlitePool,
+  schedule: &ReportScheduleRow,
+  run: &ReportScheduleRunRow,
+  recipients: Vec<String>,
+) -> Result<(), AppError> {
+  if !system_service::smtp_configured(pool).await? {
+    return Err(AppError::new(ErrorCode::ValidationError, "未配置邮件服务器"));
+  }
+  let date = Local::now().format("%Y-%m-%d").to_string();
+  system_service::enqueue_mail(
+    pool,
+    NewMail {
+      ref_type: Some("report_run".to_string()),
+      ref_id: Some(run.id.clone()),
+      recipients,
+      subject: format!("{} {}", schedule.name, date),
+      body: format!(
+        "定时报表“{}”（{}）已于 {} 生成，详见附件。",
+        schedule.name,
+        report_kind_label(&schedule.report_kind),
+        Local::now().format("%Y-%m-%d %H:%M")
+      ),
+      attachment_path: run.file_path.clone(),
+    },
+  )
+  .await?;
+  Ok(())
+}
@@ -0,0 +1,534 @@
+// 报表：由流水历史倒推物品库存走势；按最近入库时间统计库龄；按分仓阈值统计低库存；按原因统计调整、冲正与报废
+use std::collections::HashMap;
+
+use chrono::{Months, Utc};
+use csv::WriterBuilder;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::domain::quantity;
+use crate::domain::timezone::ReportTimezone;
+use crate::repo::{item_repo, report_repo, stock_threshold_repo};
+use crate::services::dashboard_service::{self, TrendGranularity};
+use crate::services::import_export_service::ExportResult;
+use crate::services::{count_service, reason_code_service, system_service};
+
+/// 支持的时间范围（月数）
+pub const TREND_RANGES: [(&str, u32); 5] = [("1m", 1), ("3m", 3), ("6m", 6), ("1y", 12), ("2y", 24)];
+
+#[derive(Debug, Serialize)]
+pub struct ItemStockTrendPoint {
+  // 分组起始日（按周为周一，按月为 1 日）
+  pub day: String,
+  // 分组结束时的库存合计（显示数量，下同）
+  pub qty: f64,
+  pub increase: f64,
+  pub decrease: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemStockTrend {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub range: String,
+  pub bucket: String,
+  pub range_start: i64,
+  pub range_end: i64,
+  // 区间开始前的库存合计
+  pub opening_qty: f64,
+  pub current_qty: f64,
+  pub points: Vec<ItemStockTrendPoint>,
+}
+
+/// 物品库存走势：以当前库存为终点，逐个分组减去区间内的增减倒推历史库存；
+/// allowed_warehouse_ids 为 Some 时仅统计可访问仓库内的库位
+pub async fn item_stock_trend(
+  pool: &SqlitePool,
+  item_id: &str,
+  range: Option<&str>,
+  bucket: Option<&str>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<ItemStockTrend, AppError> {
+  let range = range.map(|value| value.trim()).filter(|value| !value.is_empty()).unwrap_or("6m");
+  let months = TREND_RANGES
+    .iter()
+    .find(|(key, _)| *key == range)
+    .map(|(_, months)| *months)
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "时间范围仅支持 1m/3m/6m/1y/2y"))?;
+  let granularity = TrendGranularity::parse(Some(bucket.unwrap_or("week")))?;
+  let item = item_repo::get_item_by_id(pool, item_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+
+  // 分组与起始日按报表时区，与仪表盘趋势一致
+  let timezone = system_service::report_timezone(pool).await?;
+  let range_end = Utc::now().timestamp();
+  let today = timezone.today();
+  let start_day = granularity.bucket_start(today.checked_sub_months(Months::new(months)).unwrap_or(today));
+  let range_start = timezone.day_start(start_day);
+
+  let current_qty = report_repo::sum_item_stock(pool, item_id, allowed_warehouse_ids.clone()).await?;
+  // 补录的流水发生时间可能晚于当前，先扣除
+  let future_delta =
+    report_repo::sum_item_delta_after(pool, item_id, range_end, allowed_warehouse_ids.clone()).await?;
+  let rows = report_repo::list_item_delta_by_bucket(
+    pool,
+    item_id,
+    range_start,
+    range_end,
+    granularity.as_str(),
+    &timezone,
+    allowed_warehouse_ids,
+  )
+  .await?;
+  let mut delta_map: HashMap<String, report_repo::StockDeltaRow> =
+    rows.into_iter().map(|row| (row.bucket.clone(), row)).collect();
+
+  // 倒推在存储值（整数）上进行，输出时按物品精度换算
+  let display = |qty: i64| quantity::to_display(qty, item.qty_precision);
+  let mut buckets: Vec<(String, i64, i64)> = Vec::new();
+  let last_bucket = granularity.bucket_start(today);
+  let mut bucket = Some(start_day);
+  while let Some(day) = bucket.filter(|day| *day <= last_bucket) {
+    if buckets.len() >= dashboard_service::MAX_TREND_BUCKETS {
+      return Err(AppError::new(ErrorCode::ValidationError, "统计区间过长，请调大统计粒度"));
+    }
+    let day_key = day.format("%Y-%m-%d").to_string();
+    let (increase, decrease) = delta_map
+      .remove(&day_key)
+      .map(|row| (row.increase, row.decrease))
+      .unwrap_or((0, 0));
+    buckets.push((day_key, increase, decrease));
+    bucket = granularity.next_bucket(day);
+  }
+
+  // 从最后一个分组往前倒推
+  let mut running = current_qty - future_delta;
+  let mut points = Vec::with_capacity(buckets.len());
+  for (day, increase, decrease) in buckets.into_iter().rev() {
+    points.push(ItemStockTrendPoint {
+      day,
+      qty: display(running),
+      increase: display(increase),
+      decrease: display(decrease),
+    });
+    running -= increase - decrease;
+  }
+  points.reverse();
+
+  Ok(ItemStockTrend {
+    item_id: item.id,
+    item_code: item.item_code,
+    item_name: item.name,
+    range: range.to_string(),
+    bucket: granularity.as_str().to_string(),
+    range_start,
+    range_end,
+    opening_qty: display(running),
+    current_qty: display(current_qty),
+    points,
+  })
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockAgingItem {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub slot_id: String,
+  pub slot_code: String,
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub qty: f64,
+  pub value: f64,
+  // 最近入库/移入时间，无对应流水（如直接导入的库存）时为空，库龄按库存更新时间计算
+  pub last_in_at: Option<i64>,
+  pub last_move_at: Option<i64>,
+  pub days_in_stock: i64,
+  // 库龄达到呆滞阈值
+  pub stale: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockAgingReport {
+  pub threshold_days: i64,
+  pub generated_at: i64,
+  pub total: i64,
+  pub stale_count: i64,
+  pub stale_value: f64,
+  pub items: Vec<StockAgingItem>,
+}
+
+impl StockAgingReport {
+  /// 未开放成本查看的角色：库存价值清零
+  pub fn hide_costs(&mut self) {
+    self.stale_value = 0.0;
+    for item in &mut self.items {
+      item.value = 0.0;
+    }
+  }
+}
+
+/// 库龄报表筛选条件
+#[derive(Debug, Clone, Default)]
+pub struct StockAgingQuery {
+  pub warehouse_id: Option<String>,
+  // 仅返回呆滞库存
+  pub stale_only: bool,
+  // 临时指定呆滞阈值（天），缺省使用系统设置
+  pub threshold_days: Option<i64>,
+}
+
+/// 库龄报表：每个有库存的物品/库位按最近一次入库（含移入）距今的天数计算库龄，
+/// 达到阈值的标记为呆滞；按库龄从长到短排序
+pub async fn get_stock_aging(
+  pool: &SqlitePool,
+  query: StockAgingQuery,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<StockAgingReport, AppError> {
+  let threshold_days = match query.threshold_days {
+    Some(days) => {
+      system_service::validate_stale_stock_days(days)?;
+      days
+    }
+    None => system_service::stale_stock_days(pool).await?,
+  };
+  let warehouse_id = query
+    .warehouse_id
+    .map(|value| value.trim().to_string())
+    .filter(|value| !value.is_empty());
+  let now = Utc::now().timestamp();
+
+  let rows = report_repo::list_stock_aging(pool, warehouse_id.as_deref(), allowed_warehouse_ids).await?;
+  let mut items: Vec<StockAgingItem> = rows
+    .into_iter()
+    .map(|row| {
+      let since = row.last_in_at.unwrap_or(row.stock_updated_at);
+      let days_in_stock = ((now - since) / 86400).max(0);
+      let qty = quantity::to_display(row.qty, row.qty_precision);
+      StockAgingItem {
+        value: qty * row.unit_cost.unwrap_or(0.0),
+        qty,
+        last_in_at: row.last_in_at,
+        last_move_at: row.last_move_at,
+        days_in_stock,
+        stale: days_in_stock >= threshold_days,
+        item_id: row.item_id,
+        item_code: row.item_code,
+        item_name: row.item_name,
+        uom: row.uom,
+        slot_id: row.slot_id,
+        slot_code: row.slot_code,
+        warehouse_id: row.warehouse_id,
+        warehouse_code: row.warehouse_code,
+        warehouse_name: row.warehouse_name,
+      }
+    })
+    .filter(|item| !query.stale_only || item.stale)
+    .collect();
+  items.sort_by_key(|item| std::cmp::Reverse(item.days_in_stock));
+
+  let stale: Vec<&StockAgingItem> = items.iter().filter(|item| item.stale).collect();
+  Ok(StockAgingReport {
+    threshold_days,
+    generated_at: now,
+    total: items.len() as i64,
+    stale_count: stale.len() as i64,
+    stale_value: stale.iter().map(|item| item.value).sum(),
+    items,
+  })
+}
+
+/// 呆滞库存的物品/库位数，仪表盘使用
+pub async fn count_stale_stock(pool: &SqlitePool) -> Result<i64, AppError> {
+  let query = StockAgingQuery {
+    stale_only: true,
+    ..StockAgingQuery::default()
+  };
+  Ok(get_stock_aging(pool, query, None).await?.stale_count)
+}
+
+#[derive(Debug, Serialize)]
+pub struct LowStockItem {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub uom: Option<String>,
+  pub warehouse_id: String,
+  pub warehouse_code: String,
+  pub warehouse_name: String,
+  pub current_qty: f64,
+  pub min_qty: f64,
+  pub max_qty: Option<f64>,
+  // below_min（低于最低库存）/ above_max（超过最高库存）
+  pub breach: String,
+  // 偏离程度（%）：低于最低库存时为 (最低 - 当前) / 最低，超储时为 (当前 - 最高) / 最高
+  pub severity: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LowStockReport {
+  pub generated_at: i64,
+  pub below_min_count: i64,
+  pub above_max_count: i64,
+  pub items: Vec<LowStockItem>,
+}
+
+/// 偏离百分比，保留一位小数；基数为 0 时按 100% 计
+fn breach_percent(diff: f64, base: f64) -> f64 {
+  if base <= 0.0 {
+    return 100.0;
+  }
+  (diff / base * 1000.0).round() / 10.0
+}
+
+/// 低库存报表：按物品分仓阈值比较各仓库库存合计，低于最低库存的按偏离程度从高到低排在前面；
+/// include_above_max 时同时列出超过最高库存的记录
+pub async fn get_low_stock_report(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  include_above_max: bool,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<LowStockReport, AppError> {
+  let warehouse_id = warehouse_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let rows = stock_threshold_repo::list_thresholds(pool, None, warehouse_id.as_deref(), allowed_warehouse_ids).await?;
+  let mut items: Vec<LowStockItem> = rows
+    .into_iter()
+    .filter_map(|row| {
+      let (breach, severity) = if row.current_qty < row.min_qty {
+        ("below_min", breach_percent(row.min_qty - row.current_qty, row.min_qty))
+      } else {
+        match row.max_qty {
+          Some(max_qty) if include_above_max && row.current_qty > max_qty => {
+            ("above_max", breach_percent(row.current_qty - max_qty, max_qty))
+          }
+          _ => return None,
+        }
+      };
+      Some(LowStockItem {
+        item_id: row.item_id,
+        item_code: row.item_code,
+        item_name: row.item_name,
+        uom: row.uom,
+        warehouse_id: row.warehouse_id,
+        warehouse_code: row.warehouse_code,
+        warehouse_name: row.warehouse_name,
+        current_qty: row.current_qty,
+        min_qty: row.min_qty,
+        max_qty: row.max_qty,
+        breach: breach.to_string(),
+        severity,
+      })
+    })
+    .collect();
+  items.sort_by(|a, b| {
+    (a.breach != "below_min")
+      .cmp(&(b.breach != "below_min"))
+      .then(b.severity.total_cmp(&a.severity))
+  });
+  let below_min_count = items.iter().filter(|item| item.breach == "below_min").count() as i64;
+  Ok(LowStockReport {
+    generated_at: Utc::now().timestamp(),
+    below_min_count,
+    above_max_count: items.len() as i64 - below_min_count,
+    items,
+  })
+}
+
+#[derive(Debug, Serialize)]
+pub struct TxnReasonReportItem {
+  // 未填写原因的流水为 None
+  pub reason_id: Option<String>,
+  pub reason_code: Option<String>,
+  pub reason_name: Option<String>,
+  pub txn_type: String,
+  pub txn_count: i64,
+  pub item_count: i64,
+  // 数量绝对值合计（显示数量）
+  pub total_qty: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TxnReasonReport {
+  pub start_at: i64,
+  pub end_at: i64,
+  pub generated_at: i64,
+  pub total_count: i64,
+  // 未填写原因的流水数
+  pub unreasoned_count: i64,
+  pub items: Vec<TxnReasonReportItem>,
+}
+
+/// 原因统计缺省时间范围（天）
+const REASON_REPORT_DEFAULT_DAYS: i64 = 30;
+
+/// 原因统计：时间范围内调整、冲正、报废流水按原因与类型汇总，缺省统计近 30 天
+pub async fn get_txn_reason_report(
+  pool: &SqlitePool,
+  start_at: Option<i64>,
+  end_at: Option<i64>,
+  txn_type: Option<String>,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<TxnReasonReport, AppError> {
+  let now = Utc::now().timestamp();
+  let end_at = end_at.unwrap_or(now);
+  let start_at = start_at.unwrap_or(end_at - REASON_REPORT_DEFAULT_DAYS * 86_400);
+  if start_at > end_at {
+    return Err(AppError::new(ErrorCode::ValidationError, "开始时间不能晚于结束时间"));
+  }
+  let normalize = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let txn_type = normalize(txn_type);
+  if let Some(txn_type) = txn_type.as_deref() {
+    if !reason_code_service::REASON_TXN_TYPES.contains(&txn_type) {
+      return Err(AppError::new(ErrorCode::ValidationError, "流水类型仅支持 ADJUST/REVERSAL/SCRAP"));
+    }
+  }
+  let warehouse_id = normalize(warehouse_id);
+  let rows = report_repo::list_txn_reason_summary(
+    pool,
+    start_at,
+    end_at,
+    txn_type.as_deref(),
+    warehouse_id.as_deref(),
+    allowed_warehouse_ids,
+  )
+  .await?;
+  let total_count = rows.iter().map(|row| row.txn_count).sum();
+  let unreasoned_count = rows.iter().filter(|row| row.reason_id.is_none()).map(|row| row.txn_count).sum();
+  Ok(TxnReasonReport {
+    start_at,
+    end_at,
+    generated_at: now,
+    total_count,
+    unreasoned_count,
+    items: rows
+      .into_iter()
+      .map(|row| TxnReasonReportItem {
+        reason_id: row.reason_id,
+        reason_code: row.reason_code,
+        reason_name: row.reason_name,
+        txn_type: row.txn_type,
+        txn_count: row.txn_count,
+        item_count: row.item_count,
+        total_qty: row.total_qty,
+      })
+      .collect(),
+  })
+}
+
+/// 导出库龄报表 CSV（筛选条件与 get_stock_aging 一致），show_costs 为 false 时库存价值列留空
+pub async fn export_stock_aging(
+  pool: &SqlitePool,
+  query: StockAgingQuery,
+  allowed_warehouse_ids: Option<Vec<String>>,
+  show_costs: bool,
+) -> Result<ExportResult, AppError> {
+  let report = get_stock_aging(pool, query, allowed_warehouse_ids).await?;
+  let timezone = system_service::report_timezone(pool).await?;
+  let file_path = count_service::export_dir(pool)
+    .await?
+    .join(format!("库龄报表_{}.csv", report.generated_at));
+  let write_error = |_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed);
+  let mut writer = WriterBuilder::new()
+    .has_headers(true)
+    .from_path(&file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
+  writer
+    .write_record([
+      "仓库",
+      "库位",
+      "物品编码",
+      "物品",
+      "单位",
+      "数量",
+      "库存价值",
+      "最近入库",
+      "最近变动",
+      "库龄（天）",
+      "呆滞",
+    ])
+    .map_err(write_error)?;
+  for item in &report.items {
+    writer
+      .write_record([
+        item.warehouse_name.clone().unwrap_or_default(),
+        item.slot_code.clone(),
+        item.item_code.clone(),
+        item.item_name.clone(),
+        item.uom.clone().unwrap_or_default(),
+        item.qty.to_string(),
+        if show_costs { format!("{:.2}", item.value) } else { String::new() },
+        format_day(&timezone, item.last_in_at),
+        format_day(&timezone, item.last_move_at),
+        item.days_in_stock.to_string(),
+        if item.stale { "是".to_string() } else { String::new() },
+      ])
+      .map_err(write_error)?;
+  }
+  writer.flush().map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  Ok(ExportResult {
+    file_path: file_path.to_string_lossy().to_string(),
+  })
+}
+
+/// 导出低库存报表 CSV（含超过最高库存的记录），供定时报表使用
+pub async fn export_low_stock_report(pool: &SqlitePool) -> Result<ExportResult, AppError> {
+  let report = get_low_stock_report(pool, None, true, None).await?;
+  let file_path = count_service::export_dir(pool)
+    .await?
+    .join(format!("低库存报表_{}.csv", report.generated_at));
+  let write_error = |_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed);
+  let mut writer = WriterBuilder::new()
+    .has_headers(true)
+    .from_path(&file_path)
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
+  writer
+    .write_record([
+      "仓库编码",
+      "仓库",
+      "物品编码",
+      "物品",
+      "单位",
+      "当前库存",
+      "最低库存",
+      "最高库存",
+      "状态",
+      "偏离（%）",
+    ])
+    .map_err(write_error)?;
+  for item in &report.items {
+    writer
+      .write_record([
+        item.warehouse_code.clone(),
+        item.warehouse_name.clone(),
+        item.item_code.clone(),
+        item.item_name.clone(),
+        item.uom.clone().unwrap_or_default(),
+        item.current_qty.to_string(),
+        item.min_qty.to_string(),
+        item.max_qty.map(|value| value.to_string()).unwrap_or_default(),
+        if item.breach == "below_min" { "低于最低库存" } else { "超过最高库存" }.to_string(),
+        item.severity.to_string(),
+      ])
+      .map_err(write_error)?;
+  }
+  writer.flush().map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+
+  Ok(ExportResult {
+    file_path: file_path.to_string_lossy().to_string(),
+  })
+}
+
+fn format_day(timezone: &ReportTimezone, timestamp: Option<i64>) -> String {
+  timestamp
+    .and_then(|value| timezone.date_of(value))
+    .map(|day| day.format("%Y-%m-%d").to_string())
+    .unwrap_or_default()
+}
@@ -0,0 +1,129 @@
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::session_repo::{self, ActiveSessionRow, SessionRow};
+use crate::services::system_service;
+
+/// 会话活跃时间的最小刷新间隔（秒），避免每条命令都写库
+const TOUCH_INTERVAL_SECS: i64 = 30;
+
+/// 会话状态检查结果：active 为 false 时前端需退出登录，end_reason 说明原因（forced 表示被管理员强制下线）
+#[derive(Debug, serde::Serialize)]
+pub struct SessionCheck {
+  pub active: bool,
+  pub end_reason: Option<String>,
+}
+
+/// 创建会话（ttl_secs 为空表示不限时）
+pub async fn create_session(
+  pool: &SqlitePool,
+  operator_id: &str,
+  source: &str,
+  ttl_secs: Option<i64>,
+) -> Result<SessionRow, AppError> {
+  let now = Utc::now().timestamp();
+  let row = SessionRow {
+    id: Uuid::new_v4().to_string(),
+    operator_id: operator_id.to_string(),
+    source: source.to_string(),
+    created_at: now,
+    last_active_at: now,
+    expires_at: ttl_secs.map(|ttl| now + ttl),
+    ended_at: None,
+    end_reason: None,
+  };
+  session_repo::insert_session(pool, &row).await?;
+  Ok(row)
+}
+
+/// 结束会话（已结束或不存在时忽略）
+pub async fn end_session(pool: &SqlitePool, session_id: &str, reason: &str) -> Result<(), AppError> {
+  let now = Utc::now().timestamp();
+  session_repo::end_session(pool, session_id, now, reason).await?;
+  Ok(())
+}
+
+/// 当前活跃会话（未结束且未过期）
+pub async fn list_active_sessions(pool: &SqlitePool) -> Result<Vec<ActiveSessionRow>, AppError> {
+  let now = Utc::now().timestamp();
+  session_repo::list_active_sessions(pool, now).await
+}
+
+/// 管理员强制结束会话，返回被结束的会话
+pub async fn terminate_session(pool: &SqlitePool, session_id: &str) -> Result<SessionRow, AppError> {
+  let session = session_repo::get_session_by_id(pool, session_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "会话不存在"))?;
+  let now = Utc::now().timestamp();
+  if !session_repo::end_session(pool, session_id, now, "forced").await? {
+    return Err(AppError::new(ErrorCode::Conflict, "会话已结束"));
+  }
+  Ok(SessionRow {
+    ended_at: Some(now),
+    end_reason: Some("forced".to_string()),
+    ..session
+  })
+}
+
+/// 命令入口的会话校验：校验调用方所在的会话，已结束（退出、强制下线）、过期或空闲超时时拒绝命令，
+/// 过期与空闲超时的会话在此结束；通过时按间隔刷新活跃时间
+pub async fn ensure_session(pool: &SqlitePool, session_id: Option<&str>, operator_id: &str) -> Result<(), AppError> {
+  let Some(session_id) = session_id else {
+    return Err(AppError::keyed(ErrorCode::SessionExpired, MessageKey::SessionEnded));
+  };
+  let Some(session) = session_repo::get_session_by_id(pool, session_id).await? else {
+    return Err(AppError::keyed(ErrorCode::SessionExpired, MessageKey::SessionEnded));
+  };
+  if session.operator_id != operator_id {
+    return Err(AppError::new(ErrorCode::Forbidden, "会话不属于当前操作人"));
+  }
+  if session.ended_at.is_some() {
+    let key = match session.end_reason.as_deref() {
+      Some("forced") => MessageKey::SessionTerminated,
+      Some("idle") => MessageKey::SessionIdleTimeout,
+      _ => MessageKey::SessionEnded,
+    };
+    return Err(AppError::keyed(ErrorCode::SessionExpired, key));
+  }
+  let now = Utc::now().timestamp();
+  if session.expires_at.is_some_and(|expires_at| expires_at <= now) {
+    session_repo::end_session(pool, session_id, now, "expired").await?;
+    return Err(AppError::keyed(ErrorCode::SessionExpired, MessageKey::SessionEnded));
+  }
+  let idle_minutes = system_service::session_idle_minutes(pool).await?;
+  if idle_minutes > 0 && now - session.last_active_at > idle_minutes * 60 {
+    session_repo::end_session(pool, session_id, now, "idle").await?;
+    return Err(AppError::keyed(ErrorCode::SessionExpired, MessageKey::SessionIdleTimeout));
+  }
+  if now - session.last_active_at >= TOUCH_INTERVAL_SECS {
+    session_repo::touch_session(pool, session_id, now).await?;
+  }
+  Ok(())
+}
+
+/// 前端定时检查会话是否仍有效；心跳本身不计入活跃，空闲超时或过期的会话在此结束
+pub async fn check_session(pool: &SqlitePool, session_id: &str, operator_id: &str) -> Result<SessionCheck, AppError> {
+  let Some(session) = session_repo::get_session_by_id(pool, session_id).await? else {
+    return Ok(SessionCheck { active: false, end_reason: None });
+  };
+  if session.operator_id != operator_id {
+    return Err(AppError::new(ErrorCode::Forbidden, "会话不属于当前操作人"));
+  }
+  if session.ended_at.is_some() {
+    return Ok(SessionCheck { active: false, end_reason: session.end_reason });
+  }
+  let now = Utc::now().timestamp();
+  if session.expires_at.is_some_and(|expires_at| expires_at <= now) {
+    session_repo::end_session(pool, session_id, now, "expired").await?;
+    return Ok(SessionCheck { active: false, end_reason: Some("expired".to_string()) });
+  }
+  let idle_minutes = system_service::session_idle_minutes(pool).await?;
+  if idle_minutes > 0 && now - session.last_active_at > idle_minutes * 60 {
+    session_repo::end_session(pool, session_id, now, "idle").await?;
+    return Ok(SessionCheck { active: false, end_reason: Some("idle".to_string()) });
+  }
+  Ok(SessionCheck { active: true, end_reason: None })
+}
@@ -0,0 +1,168 @@
+// 日结：每天为前一日生成物品/库位库存快照（stock_snapshot），用于按日期查询库存与日/月环比
+//
+// 快照按「当前库存减去该日之后的流水变动」生成，后台任务晚于零点运行或漏结数日时结果不变；
+// 未日结的日期查询时按同样口径实时回推。日期均为报表时区的自然日。
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::quantity;
+use crate::domain::timezone::ReportTimezone;
+use crate::repo::stock_snapshot_repo::{self, SnapshotTotals};
+use crate::services::system_service;
+
+/// 漏结时最多补齐的天数
+const MAX_BACKFILL_DAYS: i64 = 31;
+
+#[derive(Debug, Serialize)]
+pub struct StockCloseResult {
+  pub close_date: String,
+  pub row_count: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockAsofItem {
+  pub item_id: String,
+  pub item_code: Option<String>,
+  pub item_name: Option<String>,
+  pub slot_id: String,
+  pub slot_code: Option<String>,
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  pub qty: f64,
+  pub value: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StockAsofResult {
+  pub date: String,
+  // snapshot：读取日结快照；ledger：未日结，按流水回推；live：当天，取当前库存
+  pub source: String,
+  pub total_qty: f64,
+  pub total_value: f64,
+  pub items: Vec<StockAsofItem>,
+}
+
+impl StockAsofResult {
+  /// 未开放成本查看的角色：库存价值清零
+  pub fn hide_costs(&mut self) {
+    self.total_value = 0.0;
+    for item in &mut self.items {
+      item.value = 0.0;
+    }
+  }
+}
+
+/// 生成指定日期的日结快照，已日结时覆盖
+pub async fn close_day(pool: &SqlitePool, date: NaiveDate) -> Result<StockCloseResult, AppError> {
+  let close_date = date.format("%Y-%m-%d").to_string();
+  let timezone = system_service::report_timezone(pool).await?;
+  let before = next_day_start(&timezone, date);
+  let row_count = stock_snapshot_repo::write_close(pool, &close_date, before, Utc::now().timestamp()).await?;
+  Ok(StockCloseResult { close_date, row_count })
+}
+
+/// 是否有待日结的日期（昨日及之前尚未日结）
+pub async fn close_due(pool: &SqlitePool) -> Result<bool, AppError> {
+  Ok(!pending_dates(pool).await?.is_empty())
+}
+
+/// 补齐截至昨日未日结的日期，首次运行只结昨日
+pub async fn run_pending_closes(pool: &SqlitePool) -> Result<Vec<StockCloseResult>, AppError> {
+  let mut results = Vec::new();
+  for date in pending_dates(pool).await? {
+    results.push(close_day(pool, date).await?);
+  }
+  Ok(results)
+}
+
+/// 查询指定日期日结时的库存（YYYY-MM-DD）；当天返回当前库存
+pub async fn get_stock_asof(
+  pool: &SqlitePool,
+  date: &str,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<StockAsofResult, AppError> {
+  let day = NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "日期格式应为 YYYY-MM-DD"))?;
+  let timezone = system_service::report_timezone(pool).await?;
+  let today = timezone.today();
+  if day > today {
+    return Err(AppError::new(ErrorCode::ValidationError, "不能查询未来日期的库存"));
+  }
+  let date = day.format("%Y-%m-%d").to_string();
+  let warehouse_id = warehouse_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+
+  let source = if day == today {
+    "live"
+  } else if stock_snapshot_repo::has_close(pool, &date).await? {
+    "snapshot"
+  } else {
+    "ledger"
+  };
+  let rows = stock_snapshot_repo::list_stock_asof(
+    pool,
+    (source == "snapshot").then_some(date.as_str()),
+    next_day_start(&timezone, day),
+    warehouse_id.as_deref(),
+    allowed_warehouse_ids.as_deref(),
+  )
+  .await?;
+
+  let items: Vec<StockAsofItem> = rows
+    .into_iter()
+    .map(|row| {
+      let qty = quantity::to_display(row.qty, row.qty_precision);
+      StockAsofItem {
+        value: qty * row.unit_cost.unwrap_or(0.0),
+        qty,
+        item_id: row.item_id,
+        item_code: row.item_code,
+        item_name: row.item_name,
+        slot_id: row.slot_id,
+        slot_code: row.slot_code,
+        warehouse_id: row.warehouse_id,
+        warehouse_code: row.warehouse_code,
+        warehouse_name: row.warehouse_name,
+      }
+    })
+    .collect();
+  Ok(StockAsofResult {
+    date,
+    source: source.to_string(),
+    total_qty: items.iter().map(|item| item.qty).sum(),
+    total_value: items.iter().map(|item| item.value).sum(),
+    items,
+  })
+}
+
+/// 昨日日结的库存合计，仪表盘环比使用；昨日尚未日结时返回 None
+pub async fn yesterday_close(pool: &SqlitePool) -> Result<Option<SnapshotTotals>, AppError> {
+  let yesterday = system_service::report_timezone(pool).await?.today() - Duration::days(1);
+  stock_snapshot_repo::sum_snapshot(pool, &yesterday.format("%Y-%m-%d").to_string()).await
+}
+
+async fn pending_dates(pool: &SqlitePool) -> Result<Vec<NaiveDate>, AppError> {
+  let yesterday = system_service::report_timezone(pool).await?.today() - Duration::days(1);
+  let earliest = yesterday - Duration::days(MAX_BACKFILL_DAYS - 1);
+  let start = match stock_snapshot_repo::last_close_date(pool).await? {
+    Some(last) => NaiveDate::parse_from_str(&last, "%Y-%m-%d")
+      .map(|last| last + Duration::days(1))
+      .unwrap_or(yesterday)
+      .max(earliest),
+    None => yesterday,
+  };
+  let mut dates = Vec::new();
+  let mut date = start;
+  while date <= yesterday {
+    dates.push(date);
+    date += Duration::days(1);
+  }
+  Ok(dates)
+}
+
+fn next_day_start(timezone: &ReportTimezone, date: NaiveDate) -> i64 {
+  timezone.day_start(date + Duration::days(1))
+}
@@ -0,0 +1,163 @@
+// 库存冻结：按物品 + 库位冻结部分库存（破损、待检等），冻结中的数量不可出库、移库或借出，解除冻结后恢复可用
+use chrono::Utc;
+use sqlx::{SqlitePool, Transaction};
+use uuid::Uuid;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::stock_hold_repo::{self, StockHoldListRow, StockHoldRow};
+use crate::repo::{item_repo, rack_repo, stock_repo, txn_repo};
+use crate::services::txn_service;
+
+/// 冻结原因
+pub const HOLD_REASONS: [&str; 3] = ["damaged", "pending_qc", "other"];
+
+/// 校验扣减库存时仅使用可用库存（库存 - 冻结中数量）
+pub async fn ensure_available_tx(
+  tx: &mut Transaction<'_, sqlx::Sqlite>,
+  item_id: &str,
+  slot_id: &str,
+  stock_qty: i64,
+  qty: i64,
+) -> Result<(), AppError> {
+  let held_qty = stock_hold_repo::held_qty_tx(tx, item_id, slot_id).await?;
+  if held_qty > 0 && stock_qty - held_qty < qty {
+    return Err(AppError::new(
+      ErrorCode::InsufficientStock,
+      "可用库存不足：部分库存已冻结，需先解除冻结",
+    ));
+  }
+  Ok(())
+}
+
+/// 报废冻结中的库存时在同一事务内解除该冻结；冻结须属于同一物品与库位且尚未解除
+pub async fn release_for_scrap_tx(
+  tx: &mut Transaction<'_, sqlx::Sqlite>,
+  hold: &StockHoldRow,
+  item_id: &str,
+  slot_id: &str,
+  released_by: &str,
+  scrap_txn_no: &str,
+) -> Result<(), AppError> {
+  if hold.item_id != item_id || hold.slot_id != slot_id {
+    return Err(AppError::new(ErrorCode::ValidationError, "冻结记录与报废的物品或库位不一致"));
+  }
+  let note = format!("报废 {}", scrap_txn_no);
+  if stock_hold_repo::release_hold_tx(tx, &hold.id, released_by, Utc::now().timestamp(), Some(&note)).await? == 0 {
+    return Err(AppError::new(ErrorCode::ValidationError, "该冻结已解除"));
+  }
+  Ok(())
+}
+
+/// 冻结物品在库位上的部分库存（qty 为存储值），返回冻结单号
+pub async fn hold_stock(
+  pool: &SqlitePool,
+  item_id: &str,
+  slot_id: &str,
+  qty: i64,
+  reason: &str,
+  note: Option<String>,
+  actor_operator_id: &str,
+) -> Result<String, AppError> {
+  if qty <= 0 {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+  }
+  let reason = reason.trim();
+  if !HOLD_REASONS.contains(&reason) {
+    return Err(AppError::new(ErrorCode::ValidationError, "冻结原因需为 damaged / pending_qc / other"));
+  }
+  item_repo::get_item_by_id(pool, item_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  rack_repo::get_slot_by_id(pool, slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+
+  let mut tx = pool.begin().await?;
+  let stock_qty = stock_repo::get_stock_tx(&mut tx, item_id, slot_id).await?.map(|s| s.qty).unwrap_or(0);
+  let held_qty = stock_hold_repo::held_qty_tx(&mut tx, item_id, slot_id).await?;
+  if stock_qty - held_qty < qty {
+    return Err(AppError::new(ErrorCode::InsufficientStock, "冻结数量超过可用库存"));
+  }
+  let hold_no = format!("HOLD-{:04}", txn_repo::next_txn_seq_tx(&mut tx, "", "HOLD").await?);
+  stock_hold_repo::insert_hold_tx(
+    &mut tx,
+    &StockHoldRow {
+      id: Uuid::new_v4().to_string(),
+      hold_no: hold_no.clone(),
+      item_id: item_id.to_string(),
+      slot_id: slot_id.to_string(),
+      qty,
+      reason: reason.to_string(),
+      note,
+      created_by: operator.id,
+      created_at: Utc::now().timestamp(),
+      released_at: None,
+    },
+  )
+  .await?;
+  tx.commit().await?;
+  Ok(hold_no)
+}
+
+/// 解除冻结，冻结的数量恢复为可用库存
+pub async fn release_hold(
+  pool: &SqlitePool,
+  hold_id: &str,
+  note: Option<String>,
+  actor_operator_id: &str,
+) -> Result<(), AppError> {
+  let hold = stock_hold_repo::get_hold(pool, hold_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "冻结记录不存在"))?;
+  if hold.released_at.is_some() {
+    return Err(AppError::new(ErrorCode::ValidationError, "该冻结已解除"));
+  }
+  let operator = txn_service::require_active_operator_by_id(pool, actor_operator_id).await?;
+  let note = note.map(|note| note.trim().to_string()).filter(|note| !note.is_empty());
+  if stock_hold_repo::release_hold(pool, &hold.id, &operator.id, Utc::now().timestamp(), note.as_deref()).await? == 0 {
+    return Err(AppError::new(ErrorCode::ValidationError, "该冻结已解除"));
+  }
+  Ok(())
+}
+
+/// 按 id 读取冻结记录
+pub async fn get_hold(pool: &SqlitePool, hold_id: &str) -> Result<StockHoldRow, AppError> {
+  stock_hold_repo::get_hold(pool, hold_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "冻结记录不存在"))
+}
+
+/// 冻结记录所在库位，供命令层校验仓库权限
+pub async fn hold_slot_id(pool: &SqlitePool, hold_id: &str) -> Result<String, AppError> {
+  stock_hold_repo::get_hold(pool, hold_id)
+    .await?
+    .map(|hold| hold.slot_id)
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "冻结记录不存在"))
+}
+
+pub async fn list_holds(
+  pool: &SqlitePool,
+  item_id: Option<String>,
+  slot_id: Option<String>,
+  warehouse_id: Option<String>,
+  active_only: bool,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<StockHoldListRow>, AppError> {
+  let normalize = |value: Option<String>| value.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let item_id = normalize(item_id);
+  let slot_id = normalize(slot_id);
+  let warehouse_id = normalize(warehouse_id);
+  stock_hold_repo::list_holds(
+    pool,
+    item_id.as_deref(),
+    slot_id.as_deref(),
+    warehouse_id.as_deref(),
+    active_only,
+    allowed_warehouse_ids,
+    500,
+  )
+  .await
+}
@@ -1,8 +1,12 @@
+use std::collections::HashMap;
+
 use chrono::Utc;
 use sqlx::SqlitePool;
 
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::repo::stock_query_repo;
+use crate::domain::messages::MessageKey;
+use crate::repo::{stock_query_repo, stock_threshold_repo};
+use crate::services::valuation_service;
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 use crate::repo::meta_repo;
 
@@ -32,6 +36,9 @@ pub async fn list_stock_by_slot(
     slot_id: Option<String>,
     item_id: Option<String>,
     operator_id: Option<String>,
+    allowed_warehouse_ids: Option<Vec<String>>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
 ) -> Result<StockBySlotResult, AppError> {
     let (page_index, page_size) = normalize_page(page_index, page_size)?;
     let total = stock_query_repo::count_stock_by_slot_filtered(
@@ -41,6 +48,7 @@ pub async fn list_stock_by_slot(
         slot_id.clone(),
         item_id.clone(),
         operator_id.clone(),
+        allowed_warehouse_ids.clone(),
     )
     .await?;
     let items = stock_query_repo::list_stock_by_slot(
@@ -52,6 +60,9 @@ pub async fn list_stock_by_slot(
         slot_id,
         item_id,
         operator_id,
+        allowed_warehouse_ids,
+        sort_by.as_deref(),
+        sort_dir.as_deref(),
     )
     .await?;
     Ok(StockBySlotResult { items, total })
@@ -66,6 +77,9 @@ pub async fn list_stock_by_item(
     slot_id: Option<String>,
     item_id: Option<String>,
     operator_id: Option<String>,
+    allowed_warehouse_ids: Option<Vec<String>>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
 ) -> Result<StockByItemResult, AppError> {
     let (page_index, page_size) = normalize_page(page_index, page_size)?;
     let total = stock_query_repo::count_stock_by_item_filtered(
@@ -75,6 +89,7 @@ pub async fn list_stock_by_item(
         slot_id.clone(),
         item_id.clone(),
         operator_id.clone(),
+        allowed_warehouse_ids.clone(),
     )
     .await?;
     let items = stock_query_repo::list_stock_by_item_filtered(
@@ -86,11 +101,98 @@ pub async fn list_stock_by_item(
         slot_id,
         item_id,
         operator_id,
+        allowed_warehouse_ids,
+        sort_by.as_deref(),
+        sort_dir.as_deref(),
     )
     .await?;
     Ok(StockByItemResult { items, total })
 }
 
+/// 库存导出可选列：（列键, 表头）
+pub const STOCK_EXPORT_COLUMNS: [(&str, &str); 13] = [
+    ("warehouse", "仓库"),
+    ("rack", "货架"),
+    ("slot", "库位"),
+    ("item_name", "物品"),
+    ("item_code", "物品编码"),
+    ("operator", "记录人"),
+    ("qty", "数量"),
+    ("held_qty", "冻结数量"),
+    ("available_qty", "可用数量"),
+    ("min_qty", "库存下限"),
+    ("max_qty", "库存上限"),
+    ("unit_cost", "单位成本"),
+    ("total_value", "库存价值"),
+];
+
+/// 未指定列时的默认导出列（与早期版本的固定列一致）
+const DEFAULT_STOCK_EXPORT_COLUMNS: [&str; 9] = [
+    "warehouse",
+    "rack",
+    "slot",
+    "item_name",
+    "item_code",
+    "qty",
+    "held_qty",
+    "unit_cost",
+    "total_value",
+];
+
+/// 导出行：按库位与按物品两种视图的查询结果字段相同，统一转换后输出
+struct StockExportLine {
+    warehouse_id: Option<String>,
+    warehouse_name: Option<String>,
+    rack_name: String,
+    slot_code: String,
+    item_id: String,
+    item_code: String,
+    item_name: String,
+    operator_name: Option<String>,
+    qty: f64,
+    held_qty: f64,
+    available_qty: f64,
+}
+
+impl From<stock_query_repo::StockBySlotRow> for StockExportLine {
+    fn from(row: stock_query_repo::StockBySlotRow) -> Self {
+        StockExportLine {
+            warehouse_id: row.warehouse_id,
+            warehouse_name: row.warehouse_name,
+            rack_name: row.rack_name,
+            slot_code: row.slot_code,
+            item_id: row.item_id,
+            item_code: row.item_code,
+            item_name: row.item_name,
+            operator_name: row.operator_name,
+            qty: row.qty,
+            held_qty: row.held_qty,
+            available_qty: row.available_qty,
+        }
+    }
+}
+
+impl From<stock_query_repo::StockByItemRow> for StockExportLine {
+    fn from(row: stock_query_repo::StockByItemRow) -> Self {
+        StockExportLine {
+            warehouse_id: row.warehouse_id,
+            warehouse_name: row.warehouse_name,
+            rack_name: row.rack_name,
+            slot_code: row.slot_code,
+            item_id: row.item_id,
+            item_code: row.item_code,
+            item_name: row.item_name,
+            operator_name: row.operator_name,
+            qty: row.qty,
+            held_qty: row.held_qty,
+            available_qty: row.available_qty,
+        }
+    }
+}
+
+/// 导出库存：mode 为 by_slot（默认，按库位排序）或 by_item（与“按物品”页签相同的查询与排序）；
+/// columns 为导出列键（见 STOCK_EXPORT_COLUMNS），按给定顺序输出，未指定时使用默认列；
+/// 下限/上限取物品在所在仓库的库存阈值，未设置时留空
 pub async fn export_stock(
     pool: &SqlitePool,
     warehouse_id: Option<String>,
@@ -98,7 +200,31 @@ pub async fn export_stock(
     slot_id: Option<String>,
     item_id: Option<String>,
     operator_id: Option<String>,
+    allowed_warehouse_ids: Option<Vec<String>>,
+    show_costs: bool,
+    mode: Option<String>,
+    columns: Option<Vec<String>>,
+    sort_by: Option<String>,
+    sort_dir: Option<String>,
 ) -> Result<StockExportResult, AppError> {
+    let by_item = match mode.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+        None | Some("by_slot") => false,
+        Some("by_item") => true,
+        Some(_) => return Err(AppError::new(ErrorCode::ValidationError, "导出方式仅支持 by_slot/by_item")),
+    };
+    let columns: Vec<String> = match columns.filter(|columns| !columns.is_empty()) {
+        Some(columns) => columns.into_iter().map(|column| column.trim().to_string()).collect(),
+        None => DEFAULT_STOCK_EXPORT_COLUMNS.iter().map(|column| column.to_string()).collect(),
+    };
+    let mut headers = Vec::with_capacity(columns.len());
+    for column in &columns {
+        let (_, header) = STOCK_EXPORT_COLUMNS
+            .iter()
+            .find(|(key, _)| key == column)
+            .ok_or_else(|| AppError::new(ErrorCode::ValidationError, format!("导出列非法：{}", column)))?;
+        headers.push(*header);
+    }
+
     // 在移动端使用临时文件，桌面端使用导出目录
     #[cfg(any(target_os = "android", target_os = "ios"))]
     let file_path = {
@@ -111,63 +237,124 @@ pub async fn export_stock(
     let file_path = {
         let storage_root = meta_repo::get_meta_value(pool, "storage_root")
             .await?
-            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+            .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
         let export_dir = match meta_repo::get_meta_value(pool, "exports_dir").await? {
             Some(dir) if !dir.is_empty() => std::path::PathBuf::from(dir),
             _ => std::path::PathBuf::from(storage_root).join("exports"),
         };
         std::fs::create_dir_all(&export_dir)
-            .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出目录失败"))?;
+            .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
         let now = Utc::now().timestamp();
         export_dir.join(format!("库存导出数据_{}.csv", now))
     };
     let mut lines = Vec::new();
-    lines.push("仓库,货架,库位,物品,物品编码,数量".to_string());
+    lines.push(headers.join(","));
+
+    // 单位成本按当前计价方法计算，未设置成本的物品留空；未开放成本查看的角色两列均留空
+    let needs_costs = columns.iter().any(|column| column == "unit_cost" || column == "total_value");
+    let unit_costs = if show_costs && needs_costs {
+        let method = valuation_service::valuation_method(pool).await?;
+        valuation_service::item_unit_costs(pool, &method).await?
+    } else {
+        Default::default()
+    };
+    // 阈值按（物品, 仓库）设置，只在导出下限/上限列时查询
+    let needs_thresholds = columns.iter().any(|column| column == "min_qty" || column == "max_qty");
+    let thresholds: HashMap<(String, String), (f64, Option<f64>)> = if needs_thresholds {
+        stock_threshold_repo::list_thresholds(pool, None, None, allowed_warehouse_ids.clone())
+            .await?
+            .into_iter()
+            .map(|row| ((row.item_id, row.warehouse_id), (row.min_qty, row.max_qty)))
+            .collect()
+    } else {
+        HashMap::new()
+    };
 
     // 分页查询，避免一次性加载过多数据
     let page_size = 100;
     let mut page = 1;
-    let (_start_page, _page_size_check) = normalize_page(page, page_size)?;
     loop {
-        let res = list_stock_by_slot(
-            pool,
-            page,
-            page_size,
-            warehouse_id.clone(),
-            rack_id.clone(),
-            slot_id.clone(),
-            item_id.clone(),
-            operator_id.clone(),
-        )
-        .await?;
-
-        if res.items.is_empty() {
+        let (rows, total): (Vec<StockExportLine>, i64) = if by_item {
+            let res = list_stock_by_item(
+                pool,
+                page,
+                page_size,
+                warehouse_id.clone(),
+                rack_id.clone(),
+                slot_id.clone(),
+                item_id.clone(),
+                operator_id.clone(),
+                allowed_warehouse_ids.clone(),
+                sort_by.clone(),
+                sort_dir.clone(),
+            )
+            .await?;
+            (res.items.into_iter().map(StockExportLine::from).collect(), res.total)
+        } else {
+            let res = list_stock_by_slot(
+                pool,
+                page,
+                page_size,
+                warehouse_id.clone(),
+                rack_id.clone(),
+                slot_id.clone(),
+                item_id.clone(),
+                operator_id.clone(),
+                allowed_warehouse_ids.clone(),
+                sort_by.clone(),
+                sort_dir.clone(),
+            )
+            .await?;
+            (res.items.into_iter().map(StockExportLine::from).collect(), res.total)
+        };
+
+        if rows.is_empty() {
             break;
         }
 
-        let fetched_count = res.items.len() as i64;
-        for item in res.items {
-            lines.push(format!(
-                "{},{},{},{},{},{}",
-                escape_csv(item.warehouse_name.as_deref().unwrap_or("")),
-                escape_csv(&item.rack_name),
-                escape_csv(&item.slot_code),
-                escape_csv(&item.item_name),
-                escape_csv(&item.item_code),
-                item.qty
-            ));
+        let fetched_count = rows.len() as i64;
+        for row in rows {
+            let unit_cost = unit_costs.get(&row.item_id);
+            let threshold = row
+                .warehouse_id
+                .as_ref()
+                .and_then(|warehouse_id| thresholds.get(&(row.item_id.clone(), warehouse_id.clone())));
+            let cells: Vec<String> = columns
+                .iter()
+                .map(|column| match column.as_str() {
+                    "warehouse" => escape_csv(row.warehouse_name.as_deref().unwrap_or("")),
+                    "rack" => escape_csv(&row.rack_name),
+                    "slot" => escape_csv(&row.slot_code),
+                    "item_name" => escape_csv(&row.item_name),
+                    "item_code" => escape_csv(&row.item_code),
+                    "operator" => escape_csv(row.operator_name.as_deref().unwrap_or("")),
+                    "qty" => row.qty.to_string(),
+                    "held_qty" => row.held_qty.to_string(),
+                    "available_qty" => row.available_qty.to_string(),
+                    "min_qty" => threshold.map(|(min_qty, _)| min_qty.to_string()).unwrap_or_default(),
+                    "max_qty" => threshold
+                        .and_then(|(_, max_qty)| max_qty.map(|max_qty| max_qty.to_string()))
+                        .unwrap_or_default(),
+                    "unit_cost" => unit_cost.map(|cost| format!("{:.2}", cost)).unwrap_or_default(),
+                    "total_value" => unit_cost
+                        .map(|cost| format!("{:.2}", row.qty.max(0.0) * cost))
+                        .unwrap_or_default(),
+                    _ => String::new(),
+                })
+                .collect();
+            lines.push(cells.join(","));
         }
 
         // 如果已到达最后一页则停止
         let fetched_until = page.saturating_mul(page_size);
-        if fetched_until >= res.total || fetched_count < page_size {
+        if fetched_until >= total || fetched_count < page_size {
             break;
         }
         page += 1;
     }
 
     std::fs::write(&file_path, lines.join("\n"))
-        .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+        .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
 
     Ok(StockExportResult {
         file_path: file_path.to_string_lossy().to_string(),
@@ -176,7 +363,7 @@ pub async fn export_stock(
 
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
     if page_index < 1 || page_size < 1 {
-        return Err(AppError::new(ErrorCode::ValidationError, "分页参数非法"));
+        return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
     }
     Ok((page_index, page_size))
 }
@@ -0,0 +1,67 @@
+// 物品分仓库存阈值：按物品 + 仓库设置最低 / 最高库存，供低库存报表使用
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::stock_threshold_repo::{self, StockThresholdRow};
+use crate::repo::{item_repo, warehouse_repo};
+use crate::services::item_service;
+
+/// 设置（新增或覆盖）物品在指定仓库的阈值，数量为显示数量
+pub async fn set_threshold(
+  pool: &SqlitePool,
+  item_id: &str,
+  warehouse_id: &str,
+  min_qty: f64,
+  max_qty: Option<f64>,
+  actor_operator_id: &str,
+) -> Result<(), AppError> {
+  item_repo::get_item_by_id(pool, item_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  warehouse_repo::get_warehouse_by_id(pool, warehouse_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound))?;
+  let min_qty = item_service::qty_to_base(pool, item_id, min_qty).await?;
+  if min_qty < 0 {
+    return Err(AppError::new(ErrorCode::ValidationError, "最低库存不能为负数"));
+  }
+  let max_qty = match max_qty {
+    Some(max_qty) => Some(item_service::qty_to_base(pool, item_id, max_qty).await?),
+    None => None,
+  };
+  if let Some(max_qty) = max_qty {
+    if max_qty < min_qty {
+      return Err(AppError::new(ErrorCode::ValidationError, "最高库存不能低于最低库存"));
+    }
+  }
+  stock_threshold_repo::upsert_threshold(
+    pool,
+    item_id,
+    warehouse_id,
+    min_qty,
+    max_qty,
+    actor_operator_id,
+    Utc::now().timestamp(),
+  )
+  .await
+}
+
+pub async fn delete_threshold(pool: &SqlitePool, item_id: &str, warehouse_id: &str) -> Result<(), AppError> {
+  if stock_threshold_repo::delete_threshold(pool, item_id, warehouse_id).await? == 0 {
+    return Err(AppError::new(ErrorCode::NotFound, "库存阈值不存在"));
+  }
+  Ok(())
+}
+
+pub async fn list_thresholds(
+  pool: &SqlitePool,
+  item_id: Option<String>,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<Vec<StockThresholdRow>, AppError> {
+  let item_id = item_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let warehouse_id = warehouse_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  stock_threshold_repo::list_thresholds(pool, item_id.as_deref(), warehouse_id.as_deref(), allowed_warehouse_ids).await
+}
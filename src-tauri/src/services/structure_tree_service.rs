@@ -0,0 +1,166 @@
+// 仓库结构树：仓库 → 货架 → 库位的嵌套结构，供级联选择器一次加载
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::domain::errors::AppError;
+use crate::repo::structure_repo::{self, TreeRackRow, TreeSlotRow};
+
+/// 缓存兜底有效期：结构变更会主动失效，过期仅用于覆盖绕过命令层的修改
+const STRUCTURE_TREE_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// 结构版本号，仓库/货架/库位变更后递增，旧版本的缓存即失效
+static STRUCTURE_VERSION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureSlot {
+  pub id: String,
+  pub code: String,
+  pub level_no: i64,
+  pub slot_no: i64,
+  pub status: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureRack {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  pub status: String,
+  pub slot_count: i64,
+  pub active_slot_count: i64,
+  // 仅 include_slots 时返回，否则为空
+  pub slots: Vec<StructureSlot>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureWarehouse {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  pub status: String,
+  pub kind: String,
+  pub racks: Vec<StructureRack>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StructureTree {
+  pub warehouses: Vec<StructureWarehouse>,
+  // 未分配仓库的货架（仅未限定仓库且不限仓库范围时返回）
+  pub unassigned_racks: Vec<StructureRack>,
+}
+
+/// 结构树缓存，按仓库、是否含库位与仓库范围区分
+#[derive(Default)]
+pub struct StructureTreeCache {
+  entries: Mutex<HashMap<String, (u64, Instant, StructureTree)>>,
+}
+
+impl StructureTreeCache {
+  fn get(&self, key: &str) -> Option<StructureTree> {
+    let version = STRUCTURE_VERSION.load(Ordering::Acquire);
+    let entries = self.entries.lock();
+    entries
+      .get(key)
+      .filter(|(cached_version, cached_at, _)| {
+        *cached_version == version && cached_at.elapsed() < STRUCTURE_TREE_CACHE_TTL
+      })
+      .map(|(_, _, value)| value.clone())
+  }
+
+  fn put(&self, key: String, version: u64, value: StructureTree) {
+    let current = STRUCTURE_VERSION.load(Ordering::Acquire);
+    let mut entries = self.entries.lock();
+    entries.retain(|_, (cached_version, cached_at, _)| {
+      *cached_version == current && cached_at.elapsed() < STRUCTURE_TREE_CACHE_TTL
+    });
+    entries.insert(key, (version, Instant::now(), value));
+  }
+}
+
+/// 仓库/货架/库位发生变更（含导入、恢复、撤销）后调用，使所有结构树缓存失效
+pub fn invalidate() {
+  STRUCTURE_VERSION.fetch_add(1, Ordering::AcqRel);
+}
+
+pub async fn get_structure_tree(
+  pool: &SqlitePool,
+  cache: &StructureTreeCache,
+  warehouse_id: Option<String>,
+  include_slots: bool,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<StructureTree, AppError> {
+  let warehouse_id = warehouse_id.map(|value| value.trim().to_string()).filter(|value| !value.is_empty());
+  let key = format!("{:?}:{}:{:?}", warehouse_id, include_slots, allowed_warehouse_ids);
+  if let Some(cached) = cache.get(&key) {
+    return Ok(cached);
+  }
+  // 先取版本再查询：查询期间发生的变更会使本次结果在下次读取时失效
+  let version = STRUCTURE_VERSION.load(Ordering::Acquire);
+
+  let warehouse_id = warehouse_id.as_deref();
+  let allowed = allowed_warehouse_ids.as_deref();
+  let warehouses = structure_repo::list_warehouses(pool, warehouse_id, allowed).await?;
+  let racks = structure_repo::list_racks(pool, warehouse_id, allowed).await?;
+  let mut slots_by_rack: HashMap<String, Vec<StructureSlot>> = HashMap::new();
+  if include_slots {
+    for slot in structure_repo::list_slots(pool, warehouse_id, allowed).await? {
+      let rack_id = slot.rack_id.clone();
+      slots_by_rack.entry(rack_id).or_default().push(to_slot(slot));
+    }
+  }
+
+  let mut racks_by_warehouse: HashMap<String, Vec<StructureRack>> = HashMap::new();
+  let mut unassigned_racks = Vec::new();
+  for rack in racks {
+    let warehouse = rack.warehouse_id.clone();
+    let rack = to_rack(rack, &mut slots_by_rack);
+    match warehouse {
+      Some(warehouse) => racks_by_warehouse.entry(warehouse).or_default().push(rack),
+      None => unassigned_racks.push(rack),
+    }
+  }
+
+  let tree = StructureTree {
+    warehouses: warehouses
+      .into_iter()
+      .map(|warehouse| StructureWarehouse {
+        racks: racks_by_warehouse.remove(&warehouse.id).unwrap_or_default(),
+        id: warehouse.id,
+        code: warehouse.code,
+        name: warehouse.name,
+        status: warehouse.status,
+        kind: warehouse.kind,
+      })
+      .collect(),
+    unassigned_racks,
+  };
+  cache.put(key, version, tree.clone());
+  Ok(tree)
+}
+
+fn to_rack(rack: TreeRackRow, slots_by_rack: &mut HashMap<String, Vec<StructureSlot>>) -> StructureRack {
+  StructureRack {
+    slots: slots_by_rack.remove(&rack.id).unwrap_or_default(),
+    id: rack.id,
+    code: rack.code,
+    name: rack.name,
+    status: rack.status,
+    slot_count: rack.slot_count,
+    active_slot_count: rack.active_slot_count,
+  }
+}
+
+fn to_slot(slot: TreeSlotRow) -> StructureSlot {
+  StructureSlot {
+    id: slot.id,
+    code: slot.code,
+    level_no: slot.level_no,
+    slot_no: slot.slot_no,
+    status: slot.status,
+  }
+}
@@ -1,13 +1,22 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
 
 use chrono::Utc;
 use sqlx::SqlitePool;
+use uuid::Uuid;
 
 use crate::domain::audit::AuditAction;
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::infra::fs;
+use crate::domain::messages::{self, MessageKey};
+use crate::domain::timezone::ReportTimezone;
+use crate::infra::mailer::{self, MailAttachment, MailMessage, SendError, SmtpServer};
+use crate::infra::{crypto, db, fs};
+use crate::repo::mail_queue_repo::{self, MailQueueRow};
 use crate::repo::{meta_repo, photo_repo};
-use crate::services::audit_service;
+use crate::services::{
+  approval_service, audit_service, backup_encryption_service, item_attribute_service, password_policy_service,
+  reason_code_service, remote_backup_service, report_schedule_service, txn_service, valuation_service,
+};
 
 /// 系统设置返回结构
 #[derive(Debug, serde::Serialize)]
@@ -24,6 +33,212 @@ pub struct SettingsDto {
   pub slot_no_pad: i64,
   // 低库存阈值
   pub low_stock_threshold: i64,
+  // 呆滞库存阈值（库龄天数）
+  pub stale_stock_days: i64,
+  // 临期预警天数：到期日在该天数内的库存计入临期
+  pub expiry_horizon_days: i64,
+  // 配置为必填的物品内置字段（model/spec/uom/unit_cost/currency/remark）
+  pub item_required_fields: Vec<String>,
+  // 冲正需经另一名管理员审批
+  pub approval_reversal: bool,
+  // 数据库恢复需经另一名管理员审批
+  pub approval_restore: bool,
+  // 盘点调整差异超过该数量需审批（0 表示不需审批）
+  pub approval_count_threshold: i64,
+  // 报废数量超过该数量需审批（0 表示不需审批）
+  pub approval_scrap_threshold: i64,
+  // 须选择原因的流水类型（ADJUST / REVERSAL / SCRAP），原因取自原因代码字典
+  pub reason_required_types: Vec<String>,
+  // 登录连续失败锁定阈值（0 表示不锁定）
+  pub login_max_failures: i64,
+  // 登录失败计数窗口（分钟）
+  pub login_fail_window_minutes: i64,
+  // 账号锁定时长（分钟）
+  pub login_lockout_minutes: i64,
+  // 密码最小长度
+  pub pwd_min_length: i64,
+  // 密码至少包含的字符类别数（1-4）
+  pub pwd_min_char_classes: i64,
+  // 密码禁止包含用户名
+  pub pwd_disallow_username: bool,
+  // 禁止复用最近 N 次密码（0 表示不限制）
+  pub pwd_history_count: i64,
+  // PIN 切换会话有效期（分钟）
+  pub pin_session_minutes: i64,
+  // 空闲自动锁屏时间（分钟，0 表示不锁屏）
+  pub idle_lock_minutes: i64,
+  // 会话空闲超时（分钟，0 表示不限）：超时后命令被拒绝并需重新登录
+  pub session_idle_minutes: i64,
+  // 审计详略：all/writes/critical
+  pub audit_verbosity: String,
+  // 共享终端模式：每笔流水需重新扫码/选择记录人
+  pub kiosk_mode: bool,
+  // 共享终端模式下同一记录人可连续复用的时间窗口（秒）
+  pub kiosk_operator_window_secs: i64,
+  // 库存计价方法：moving_average/fifo
+  pub valuation_method: String,
+  // 流水号格式：uuid/sequence/warehouse
+  pub txn_no_scheme: String,
+  // 移动端记录流水时附带粗略位置
+  pub txn_location_capture: bool,
+  // 系统生成文本（如盘点调整备注）的语言：zh-CN/en-US
+  pub locale: String,
+  // 报表时区：local 或固定 UTC 偏移（±HH:MM），用于导出时间与趋势分组
+  pub report_timezone: String,
+  // 是否启用本机 HTTP 接口
+  pub http_api_enabled: bool,
+  // HTTP 接口监听端口（仅绑定 127.0.0.1）
+  pub http_api_port: i64,
+  // 是否已生成访问令牌（令牌本身不在设置中返回）
+  pub http_api_token_set: bool,
+  // 关闭主窗口时隐藏到托盘（桌面端）
+  pub close_to_tray: bool,
+  // 启动时不显示主窗口，仅驻留托盘（桌面端）
+  pub start_minimized: bool,
+  // 定时备份间隔（小时，0 表示不自动备份）
+  pub auto_backup_hours: i64,
+  // 数据库连接池大小（重启后生效）
+  pub db_pool_size: i64,
+  // 只读维护模式及原因
+  pub read_only_mode: bool,
+  pub read_only_reason: Option<String>,
+}
+
+/// 只读维护模式状态
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ReadOnlyState {
+  pub enabled: bool,
+  pub reason: Option<String>,
+}
+
+/// 系统设置更新参数（仅更新提供的字段）
+#[derive(Debug, Default)]
+pub struct SettingsPatch {
+  pub rbac_enabled: Option<bool>,
+  pub slot_no_pad: Option<i64>,
+  pub low_stock_threshold: Option<i64>,
+  pub stale_stock_days: Option<i64>,
+  pub expiry_horizon_days: Option<i64>,
+  pub item_required_fields: Option<Vec<String>>,
+  pub approval_reversal: Option<bool>,
+  pub approval_restore: Option<bool>,
+  pub approval_count_threshold: Option<i64>,
+  pub approval_scrap_threshold: Option<i64>,
+  pub reason_required_types: Option<Vec<String>>,
+  pub login_max_failures: Option<i64>,
+  pub login_fail_window_minutes: Option<i64>,
+  pub login_lockout_minutes: Option<i64>,
+  pub pwd_min_length: Option<i64>,
+  pub pwd_min_char_classes: Option<i64>,
+  pub pwd_disallow_username: Option<bool>,
+  pub pwd_history_count: Option<i64>,
+  pub pin_session_minutes: Option<i64>,
+  pub idle_lock_minutes: Option<i64>,
+  pub session_idle_minutes: Option<i64>,
+  pub audit_verbosity: Option<String>,
+  pub kiosk_mode: Option<bool>,
+  pub kiosk_operator_window_secs: Option<i64>,
+  pub valuation_method: Option<String>,
+  pub txn_no_scheme: Option<String>,
+  pub txn_location_capture: Option<bool>,
+  pub locale: Option<String>,
+  pub report_timezone: Option<String>,
+  pub http_api_enabled: Option<bool>,
+  pub http_api_port: Option<i64>,
+  pub close_to_tray: Option<bool>,
+  pub start_minimized: Option<bool>,
+  pub auto_backup_hours: Option<i64>,
+  pub db_pool_size: Option<i64>,
+}
+
+/// HTTP 接口配置（app_meta 中的 http_api_*）
+#[derive(Debug, Clone)]
+pub struct HttpApiConfig {
+  pub enabled: bool,
+  pub port: u16,
+  // 访问令牌的 SHA-256 摘要，明文仅在生成时返回一次
+  pub token_hash: Option<String>,
+  // 令牌归属的人员，接口调用以该人员身份执行并做权限校验
+  pub operator_id: Option<String>,
+}
+
+/// HTTP 接口默认端口
+pub const DEFAULT_HTTP_API_PORT: u16 = 17420;
+
+/// 定时备份最长间隔（小时）
+pub const MAX_AUTO_BACKUP_HOURS: i64 = 24 * 30;
+
+/// 呆滞库存阈值（天）的默认值与上限
+pub const DEFAULT_STALE_STOCK_DAYS: i64 = 90;
+pub const MAX_STALE_STOCK_DAYS: i64 = 3650;
+pub const DEFAULT_EXPIRY_HORIZON_DAYS: i64 = 30;
+pub const MAX_EXPIRY_HORIZON_DAYS: i64 = 3650;
+
+/// 会话空闲超时上限（分钟）
+pub const MAX_SESSION_IDLE_MINUTES: i64 = 24 * 60;
+
+/// 存储目录下除数据库外随迁移移动的子目录
+const STORAGE_FILE_DIRS: [&str; 3] = ["photos", "exports", "backups"];
+
+/// 库位编码中层号、格号的补零位数，未设置时为 2
+pub async fn slot_no_pad(pool: &SqlitePool) -> Result<i64, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "slot_no_pad")
+      .await?
+      .and_then(|value| value.parse::<i64>().ok())
+      .filter(|value| *value > 0)
+      .unwrap_or(2),
+  )
+}
+
+/// 呆滞库存阈值：库龄达到该天数的库存视为呆滞，未设置时为 90 天
+pub async fn stale_stock_days(pool: &SqlitePool) -> Result<i64, AppError> {
+  Ok(
+    meta_repo::get_meta_i64(pool, "stale_stock_days")
+      .await?
+      .filter(|value| (1..=MAX_STALE_STOCK_DAYS).contains(value))
+      .unwrap_or(DEFAULT_STALE_STOCK_DAYS),
+  )
+}
+
+pub fn validate_stale_stock_days(days: i64) -> Result<(), AppError> {
+  if !(1..=MAX_STALE_STOCK_DAYS).contains(&days) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("stale_stock_days 必须在 1-{} 之间", MAX_STALE_STOCK_DAYS),
+    ));
+  }
+  Ok(())
+}
+
+/// 临期预警天数：到期日在今天起该天数内的库存视为临期，未设置时为 30 天
+pub async fn expiry_horizon_days(pool: &SqlitePool) -> Result<i64, AppError> {
+  Ok(
+    meta_repo::get_meta_i64(pool, "expiry_horizon_days")
+      .await?
+      .filter(|value| (1..=MAX_EXPIRY_HORIZON_DAYS).contains(value))
+      .unwrap_or(DEFAULT_EXPIRY_HORIZON_DAYS),
+  )
+}
+
+pub fn validate_expiry_horizon_days(days: i64) -> Result<(), AppError> {
+  if !(1..=MAX_EXPIRY_HORIZON_DAYS).contains(&days) {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("expiry_horizon_days 必须在 1-{} 之间", MAX_EXPIRY_HORIZON_DAYS),
+    ));
+  }
+  Ok(())
+}
+
+/// 会话空闲超时（分钟），0 表示不限
+pub async fn session_idle_minutes(pool: &SqlitePool) -> Result<i64, AppError> {
+  Ok(
+    meta_repo::get_meta_i64(pool, "session_idle_minutes")
+      .await?
+      .filter(|value| (0..=MAX_SESSION_IDLE_MINUTES).contains(value))
+      .unwrap_or(0),
+  )
 }
 
 /// 查询系统设置
@@ -34,16 +249,17 @@ pub async fn get_settings(pool: &SqlitePool) -> Result<SettingsDto, AppError> {
   let storage_root = meta_repo::get_meta_value(pool, "storage_root")
     .await?
     .unwrap_or_default();
-  let slot_no_pad = meta_repo::get_meta_value(pool, "slot_no_pad")
-    .await?
-    .and_then(|value| value.parse::<i64>().ok())
-    .filter(|value| *value > 0)
-    .unwrap_or(2);
+  let slot_no_pad = slot_no_pad(pool).await?;
   let low_stock_threshold = meta_repo::get_meta_value(pool, "low_stock_threshold")
     .await?
     .and_then(|value| value.parse::<i64>().ok())
     .filter(|value| *value >= 0)
     .unwrap_or(0);
+  let stale_stock_days = stale_stock_days(pool).await?;
+  let expiry_horizon_days = expiry_horizon_days(pool).await?;
+  let item_required_fields = item_attribute_service::required_item_fields(pool).await?;
+  let approval_policy = approval_service::load_policy(pool).await?;
+  let reason_required_types = reason_code_service::required_txn_types(pool).await?;
 
   let exports_dir = meta_repo::get_meta_value(pool, "exports_dir")
     .await?
@@ -53,6 +269,55 @@ pub async fn get_settings(pool: &SqlitePool) -> Result<SettingsDto, AppError> {
     .await?
     .unwrap_or_default();
 
+  let login_max_failures = meta_repo::get_meta_i64(pool, "login_max_failures")
+    .await?
+    .filter(|value| *value >= 0)
+    .unwrap_or(5);
+  let login_fail_window_minutes = meta_repo::get_meta_i64(pool, "login_fail_window_minutes")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(15);
+  let login_lockout_minutes = meta_repo::get_meta_i64(pool, "login_lockout_minutes")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(15);
+  let password_policy = password_policy_service::load_policy(pool).await?;
+  let pin_session_minutes = meta_repo::get_meta_i64(pool, "pin_session_minutes")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(30);
+  let idle_lock_minutes = meta_repo::get_meta_i64(pool, "idle_lock_minutes")
+    .await?
+    .filter(|value| *value >= 0)
+    .unwrap_or(0);
+  let session_idle_minutes = session_idle_minutes(pool).await?;
+  let audit_verbosity = meta_repo::get_meta_value(pool, "audit_verbosity")
+    .await?
+    .filter(|value| audit_service::AUDIT_VERBOSITY_VALUES.contains(&value.as_str()))
+    .unwrap_or_else(|| "all".to_string());
+  let kiosk_mode = meta_repo::get_meta_value(pool, "kiosk_mode")
+    .await?
+    .map(|value| value == "1")
+    .unwrap_or(false);
+  let kiosk_operator_window_secs = meta_repo::get_meta_i64(pool, "kiosk_operator_window_secs")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(60);
+  let valuation_method = valuation_service::valuation_method(pool).await?;
+  let txn_no_scheme = txn_service::txn_no_scheme(pool).await?;
+  let txn_location_capture = txn_service::location_capture_enabled(pool).await?;
+  let locale = txn_service::locale(pool).await?;
+  let report_timezone = report_timezone(pool).await?.as_setting();
+  let http_api = http_api_config(pool).await?;
+  let close_to_tray = meta_repo::get_meta_value(pool, "close_to_tray").await?.as_deref() == Some("1");
+  let start_minimized = meta_repo::get_meta_value(pool, "start_minimized").await?.as_deref() == Some("1");
+  let auto_backup_hours = auto_backup_hours(pool).await?;
+  let db_pool_size = meta_repo::get_meta_i64(pool, "db_pool_size")
+    .await?
+    .filter(|value| (1..=db::MAX_POOL_SIZE).contains(value))
+    .unwrap_or(db::DEFAULT_POOL_SIZE);
+  let read_only = read_only_state(pool).await?;
+
   Ok(SettingsDto {
     rbac_enabled: rbac == "1",
     storage_root,
@@ -60,30 +325,455 @@ pub async fn get_settings(pool: &SqlitePool) -> Result<SettingsDto, AppError> {
     backups_dir,
     slot_no_pad,
     low_stock_threshold,
+    stale_stock_days,
+    expiry_horizon_days,
+    item_required_fields,
+    approval_reversal: approval_policy.reversal,
+    approval_restore: approval_policy.restore,
+    approval_count_threshold: approval_policy.count_threshold,
+    approval_scrap_threshold: approval_policy.scrap_threshold,
+    reason_required_types,
+    login_max_failures,
+    login_fail_window_minutes,
+    login_lockout_minutes,
+    pwd_min_length: password_policy.min_length,
+    pwd_min_char_classes: password_policy.min_char_classes,
+    pwd_disallow_username: password_policy.disallow_username,
+    pwd_history_count: password_policy.history_count,
+    pin_session_minutes,
+    idle_lock_minutes,
+    session_idle_minutes,
+    audit_verbosity,
+    kiosk_mode,
+    kiosk_operator_window_secs,
+    valuation_method,
+    txn_no_scheme,
+    txn_location_capture,
+    locale,
+    report_timezone,
+    http_api_enabled: http_api.enabled,
+    http_api_port: http_api.port as i64,
+    http_api_token_set: http_api.token_hash.is_some(),
+    close_to_tray,
+    start_minimized,
+    auto_backup_hours,
+    db_pool_size,
+    read_only_mode: read_only.enabled,
+    read_only_reason: read_only.reason,
   })
 }
 
-/// 更新系统设置
-pub async fn set_settings(
+/// 读取只读维护模式（存于 app_meta，随数据库持久化）
+pub async fn read_only_state(pool: &SqlitePool) -> Result<ReadOnlyState, AppError> {
+  let enabled = meta_repo::get_meta_value(pool, "read_only_mode").await?.as_deref() == Some("1");
+  let reason = if enabled {
+    meta_repo::get_meta_value(pool, "read_only_reason")
+      .await?
+      .filter(|value| !value.trim().is_empty())
+  } else {
+    None
+  };
+  Ok(ReadOnlyState { enabled, reason })
+}
+
+/// 开启或关闭只读维护模式，关闭时清空原因
+pub async fn set_read_only_mode(
   pool: &SqlitePool,
-  rbac_enabled: Option<bool>,
-  slot_no_pad: Option<i64>,
-  low_stock_threshold: Option<i64>,
-) -> Result<(), AppError> {
-  if let Some(rbac_enabled) = rbac_enabled {
+  enabled: bool,
+  reason: Option<String>,
+) -> Result<ReadOnlyState, AppError> {
+  let reason = if enabled {
+    reason.map(|value| value.trim().to_string()).filter(|value| !value.is_empty())
+  } else {
+    None
+  };
+  if reason.as_ref().is_some_and(|value| value.chars().count() > 100) {
+    return Err(AppError::new(ErrorCode::ValidationError, "只读原因不能超过 100 个字符"));
+  }
+  meta_repo::set_meta_value(pool, "read_only_mode", if enabled { "1" } else { "0" }).await?;
+  meta_repo::set_meta_value(pool, "read_only_reason", reason.as_deref().unwrap_or("")).await?;
+  Ok(ReadOnlyState { enabled, reason })
+}
+
+/// 报表时区，未设置或无法解析时跟随本机
+pub async fn report_timezone(pool: &SqlitePool) -> Result<ReportTimezone, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "report_timezone")
+      .await?
+      .and_then(|value| ReportTimezone::parse(&value))
+      .unwrap_or(ReportTimezone::Local),
+  )
+}
+
+/// 定时备份间隔（小时），0 表示关闭
+pub async fn auto_backup_hours(pool: &SqlitePool) -> Result<i64, AppError> {
+  Ok(
+    meta_repo::get_meta_i64(pool, "auto_backup_hours")
+      .await?
+      .filter(|value| (0..=MAX_AUTO_BACKUP_HOURS).contains(value))
+      .unwrap_or(0),
+  )
+}
+
+/// 定时备份是否到期：距上次自动备份已超过设定间隔
+pub async fn scheduled_backup_due(pool: &SqlitePool) -> Result<bool, AppError> {
+  let hours = auto_backup_hours(pool).await?;
+  if hours == 0 {
+    return Ok(false);
+  }
+  let last = meta_repo::get_meta_i64(pool, "last_auto_backup_at").await?.unwrap_or(0);
+  Ok(Utc::now().timestamp() - last >= hours * 3600)
+}
+
+/// 执行定时备份并记录备份时间；已配置远程备份时随后上传
+pub async fn run_scheduled_backup(pool: &SqlitePool) -> Result<String, AppError> {
+  let path = backup_db(pool).await?;
+  meta_repo::set_meta_value(pool, "last_auto_backup_at", &Utc::now().timestamp().to_string()).await?;
+  remote_backup_service::upload_backup(pool, &path).await?;
+  Ok(path)
+}
+
+/// 读取 HTTP 接口配置
+pub async fn http_api_config(pool: &SqlitePool) -> Result<HttpApiConfig, AppError> {
+  let enabled = meta_repo::get_meta_value(pool, "http_api_enabled").await?.as_deref() == Some("1");
+  let port = meta_repo::get_meta_i64(pool, "http_api_port")
+    .await?
+    .and_then(|value| u16::try_from(value).ok())
+    .filter(|value| *value >= 1024)
+    .unwrap_or(DEFAULT_HTTP_API_PORT);
+  let token_hash = meta_repo::get_meta_value(pool, "http_api_token_hash")
+    .await?
+    .filter(|value| !value.is_empty());
+  let operator_id = meta_repo::get_meta_value(pool, "http_api_operator_id")
+    .await?
+    .filter(|value| !value.is_empty());
+  Ok(HttpApiConfig {
+    enabled,
+    port,
+    token_hash,
+    operator_id,
+  })
+}
+
+/// 重新生成 HTTP 接口访问令牌，旧令牌立即失效；令牌归属于生成人，只保存摘要，明文仅返回这一次
+pub async fn regenerate_http_api_token(pool: &SqlitePool, operator_id: &str) -> Result<String, AppError> {
+  let token = crypto::generate_token(40);
+  meta_repo::set_meta_value(pool, "http_api_token_hash", &crypto::hash_token(&token)).await?;
+  meta_repo::set_meta_value(pool, "http_api_operator_id", operator_id).await?;
+  Ok(token)
+}
+
+/// 邮件服务器配置（app_meta 中的 smtp_*，不含明文密码）
+#[derive(Debug, serde::Serialize)]
+pub struct SmtpConfig {
+  pub host: String,
+  pub port: u16,
+  // 连接安全方式，目前仅支持 none
+  pub tls: String,
+  pub from: String,
+  pub username: String,
+  // 是否已保存密码
+  pub password_set: bool,
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub struct SmtpConfigPatch {
+  pub host: Option<String>,
+  pub port: Option<i64>,
+  pub tls: Option<String>,
+  pub from: Option<String>,
+  pub username: Option<String>,
+  // 为空表示保留已保存的密码
+  pub password: Option<String>,
+}
+
+/// 待入队的邮件
+#[derive(Debug)]
+pub struct NewMail {
+  pub ref_type: Option<String>,
+  pub ref_id: Option<String>,
+  pub recipients: Vec<String>,
+  pub subject: String,
+  pub body: String,
+  pub attachment_path: Option<String>,
+}
+
+/// 邮件服务器默认端口
+pub const DEFAULT_SMTP_PORT: u16 = 25;
+
+/// 单个邮件最多的收件人数
+pub const MAX_MAIL_RECIPIENTS: usize = 20;
+
+/// 每次最多发送的队列邮件数
+const MAIL_BATCH_SIZE: i64 = 20;
+/// 已结束的队列邮件保留天数
+const MAIL_RETENTION_DAYS: i64 = 30;
+/// 队列列表最多返回的条数
+const MAX_MAIL_LIST: i64 = 200;
+
+pub async fn get_smtp_config(pool: &SqlitePool) -> Result<SmtpConfig, AppError> {
+  let value = |key: &'static str| async move {
+    Ok::<_, AppError>(meta_repo::get_meta_value(pool, key).await?.unwrap_or_default())
+  };
+  Ok(SmtpConfig {
+    host: value("smtp_host").await?,
+    port: meta_repo::get_meta_i64(pool, "smtp_port")
+      .await?
+      .and_then(|value| u16::try_from(value).ok())
+      .filter(|value| *value > 0)
+      .unwrap_or(DEFAULT_SMTP_PORT),
+    tls: Some(value("smtp_tls").await?)
+      .filter(|tls| mailer::SMTP_TLS_MODES.contains(&tls.as_str()))
+      .unwrap_or_else(|| "none".to_string()),
+    from: value("smtp_from").await?,
+    username: value("smtp_username").await?,
+    password_set: !value("smtp_password").await?.is_empty(),
+  })
+}
+
+/// 保存邮件服务器配置；host 为空表示不发送邮件，清空账号时一并清除密码
+pub async fn set_smtp_config(pool: &SqlitePool, patch: &SmtpConfigPatch) -> Result<(), AppError> {
+  let trimmed = |value: &Option<String>| value.as_deref().unwrap_or_default().trim().to_string();
+  let host = trimmed(&patch.host);
+  let from = trimmed(&patch.from);
+  let username = trimmed(&patch.username);
+  let port = patch.port.unwrap_or(i64::from(DEFAULT_SMTP_PORT));
+  if !(1..=65535).contains(&port) {
+    return Err(AppError::new(ErrorCode::ValidationError, "端口需在 1-65535 之间"));
+  }
+  if host.contains(char::is_whitespace) || host.contains('/') {
+    return Err(AppError::new(ErrorCode::ValidationError, "邮件服务器地址只需填写主机名或 IP"));
+  }
+  let tls = patch.tls.as_deref().map(str::trim).filter(|tls| !tls.is_empty()).unwrap_or("none");
+  if !mailer::SMTP_TLS_MODES.contains(&tls) {
+    return Err(AppError::new(ErrorCode::ValidationError, "连接安全方式仅支持 none/starttls/tls"));
+  }
+  // 当前构建未集成 TLS 实现，加密连接留待后续版本
+  if tls != "none" {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      "当前版本暂不支持 STARTTLS/SSL 加密连接，请使用内网邮件中继（none）",
+    ));
+  }
+  if !host.is_empty() && !is_email(&from) {
+    return Err(AppError::new(ErrorCode::ValidationError, "请填写有效的发件人地址"));
+  }
+  if let Some(password) = patch.password.as_deref().filter(|password| !password.is_empty()) {
+    let key = remote_backup_service::load_key(pool).await?;
+    let encrypted = crypto::encrypt_secret(&key, password)?;
+    meta_repo::set_meta_value(pool, "smtp_password", &encrypted).await?;
+  }
+  if username.is_empty() {
+    meta_repo::set_meta_value(pool, "smtp_password", "").await?;
+  }
+  meta_repo::set_meta_value(pool, "smtp_host", &host).await?;
+  meta_repo::set_meta_value(pool, "smtp_port", &port.to_string()).await?;
+  meta_repo::set_meta_value(pool, "smtp_tls", tls).await?;
+  meta_repo::set_meta_value(pool, "smtp_from", &from).await?;
+  meta_repo::set_meta_value(pool, "smtp_username", &username).await?;
+  Ok(())
+}
+
+/// 是否已配置邮件服务器
+pub async fn smtp_configured(pool: &SqlitePool) -> Result<bool, AppError> {
+  Ok(!get_smtp_config(pool).await?.host.is_empty())
+}
+
+/// 直接发送测试邮件（不经队列），验证邮件服务器配置
+pub async fn send_test_email(pool: &SqlitePool, to: &str) -> Result<(), AppError> {
+  let recipients = parse_recipients(Some(to))?;
+  if recipients.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "请填写收件人"));
+  }
+  let server = load_smtp_server(pool).await?;
+  mailer::send_mail(
+    server,
+    MailMessage {
+      to: recipients,
+      subject: "库存管理测试邮件".to_string(),
+      body: "这是一封测试邮件，收到即表示邮件服务器配置可用。".to_string(),
+      attachments: Vec::new(),
+    },
+  )
+  .await?;
+  Ok(())
+}
+
+/// 解析收件人列表（逗号、分号或空白分隔）
+pub fn parse_recipients(value: Option<&str>) -> Result<Vec<String>, AppError> {
+  let recipients: Vec<String> = value
+    .unwrap_or_default()
+    .split([',', ';', '，', '；', ' ', '\n'])
+    .map(str::trim)
+    .filter(|value| !value.is_empty())
+    .map(str::to_string)
+    .collect();
+  if let Some(invalid) = recipients.iter().find(|value| !is_email(value)) {
+    return Err(AppError::new(ErrorCode::ValidationError, format!("收件人地址无效：{}", invalid)));
+  }
+  if recipients.len() > MAX_MAIL_RECIPIENTS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("收件人不能超过 {} 个", MAX_MAIL_RECIPIENTS),
+    ));
+  }
+  Ok(recipients)
+}
+
+fn is_email(value: &str) -> bool {
+  let Some((local, domain)) = value.split_once('@') else {
+    return false;
+  };
+  !local.is_empty()
+    && domain.contains('.')
+    && !domain.starts_with('.')
+    && !domain.ends_with('.')
+    && !domain.contains('@')
+    && !value.chars().any(|c| c.is_whitespace() || c.is_control() || matches!(c, '<' | '>'))
+}
+
+async fn load_smtp_server(pool: &SqlitePool) -> Result<SmtpServer, AppError> {
+  let config = get_smtp_config(pool).await?;
+  if config.host.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "未配置邮件服务器"));
+  }
+  let password = match meta_repo::get_meta_value(pool, "smtp_password")
+    .await?
+    .filter(|value| !value.is_empty())
+  {
+    Some(encrypted) => crypto::decrypt_secret(&remote_backup_service::load_key(pool).await?, &encrypted)?,
+    None => String::new(),
+  };
+  Ok(SmtpServer {
+    host: config.host,
+    port: config.port,
+    username: config.username,
+    password,
+    from: config.from,
+  })
+}
+
+/// 邮件入队，由后台按队列发送；返回队列 ID
+pub async fn enqueue_mail(pool: &SqlitePool, mail: NewMail) -> Result<String, AppError> {
+  if mail.recipients.is_empty() {
+    return Err(AppError::new(ErrorCode::ValidationError, "请填写收件人"));
+  }
+  let now = Utc::now().timestamp();
+  let row = MailQueueRow {
+    id: uuid::Uuid::new_v4().to_string(),
+    ref_type: mail.ref_type,
+    ref_id: mail.ref_id,
+    recipients: mail.recipients.join(", "),
+    subject: mail.subject,
+    body: mail.body,
+    attachment_path: mail.attachment_path,
+    status: "pending".to_string(),
+    attempts: 0,
+    next_attempt_at: now,
+    last_error: None,
+    created_at: now,
+    sent_at: None,
+  };
+  mail_queue_repo::insert_mail(pool, &row).await?;
+  Ok(row.id)
+}
+
+/// 到期待发送的队列邮件
+pub async fn due_mails(pool: &SqlitePool) -> Result<Vec<MailQueueRow>, AppError> {
+  mail_queue_repo::list_due(pool, Utc::now().timestamp(), MAIL_BATCH_SIZE).await
+}
+
+/// 发送一封队列邮件（不更新队列状态）；配置缺失或附件丢失视为永久失败
+pub async fn deliver_mail(pool: &SqlitePool, mail: &MailQueueRow) -> Result<(), SendError> {
+  let permanent = |message: String| SendError { permanent: true, message };
+  let server = load_smtp_server(pool).await.map_err(|err| permanent(err.message))?;
+  let mut attachments = Vec::new();
+  if let Some(path) = mail.attachment_path.as_deref() {
+    let path = Path::new(path);
+    let content = std::fs::read(path).map_err(|_| permanent("读取附件失败，文件可能已被移动或删除".to_string()))?;
+    let file_name = path
+      .file_name()
+      .and_then(|name| name.to_str())
+      .unwrap_or("attachment")
+      .to_string();
+    attachments.push(MailAttachment { file_name, content });
+  }
+  let to = mail
+    .recipients
+    .split(',')
+    .map(str::trim)
+    .filter(|value| !value.is_empty())
+    .map(str::to_string)
+    .collect();
+  mailer::send_mail(
+    server,
+    MailMessage {
+      to,
+      subject: mail.subject.clone(),
+      body: mail.body.clone(),
+      attachments,
+    },
+  )
+  .await
+}
+
+/// 记录发送结果：成功标记已发送；临时失败按退避间隔重试，永久失败或重试用完标记失败，并回写来源。
+/// 返回是否新增了通知
+pub async fn record_delivery(
+  pool: &SqlitePool,
+  mail: &MailQueueRow,
+  result: Result<(), SendError>,
+) -> Result<bool, AppError> {
+  let now = Utc::now().timestamp();
+  let attempts = mail.attempts + 1;
+  let (status, error) = match result {
+    Ok(()) => {
+      mail_queue_repo::update_delivery(pool, &mail.id, "sent", attempts, mail.next_attempt_at, None, Some(now)).await?;
+      ("sent", None)
+    }
+    Err(err) => match mailer::next_retry_delay(attempts).filter(|_| !err.permanent) {
+      Some(delay) => {
+        mail_queue_repo::update_delivery(pool, &mail.id, "pending", attempts, now + delay, Some(&err.message), None)
+          .await?;
+        ("pending", Some(err.message))
+      }
+      None => {
+        mail_queue_repo::update_delivery(pool, &mail.id, "failed", attempts, mail.next_attempt_at, Some(&err.message), None)
+          .await?;
+        ("failed", Some(err.message))
+      }
+    },
+  };
+  mail_queue_repo::prune_finished(pool, now - MAIL_RETENTION_DAYS * 86400).await?;
+  match (mail.ref_type.as_deref(), mail.ref_id.as_deref()) {
+    (Some("report_run"), Some(run_id)) if status != "pending" => {
+      report_schedule_service::apply_mail_result(pool, run_id, error.as_deref()).await
+    }
+    _ => Ok(false),
+  }
+}
+
+/// 邮件队列（最近 200 条）
+pub async fn list_mail_queue(pool: &SqlitePool) -> Result<Vec<MailQueueRow>, AppError> {
+  mail_queue_repo::list_mails(pool, MAX_MAIL_LIST).await
+}
+
+/// 更新系统设置
+pub async fn set_settings(pool: &SqlitePool, patch: SettingsPatch) -> Result<(), AppError> {
+  if let Some(rbac_enabled) = patch.rbac_enabled {
     let value = if rbac_enabled { "1" } else { "0" };
     meta_repo::set_meta_value(pool, "rbac_enabled", value).await?;
   }
-  if let Some(slot_no_pad) = slot_no_pad {
-    if slot_no_pad < 1 {
+  if let Some(slot_no_pad) = patch.slot_no_pad {
+    if !(1..=6).contains(&slot_no_pad) {
       return Err(AppError::new(
         ErrorCode::ValidationError,
-        "slot_no_pad 必须大于 0",
+        "slot_no_pad 必须在 1-6 之间",
       ));
     }
     meta_repo::set_meta_value(pool, "slot_no_pad", &slot_no_pad.to_string()).await?;
   }
-  if let Some(low_stock_threshold) = low_stock_threshold {
+  if let Some(low_stock_threshold) = patch.low_stock_threshold {
     if low_stock_threshold < 0 {
       return Err(AppError::new(
         ErrorCode::ValidationError,
@@ -97,15 +787,265 @@ pub async fn set_settings(
     )
     .await?;
   }
+  if let Some(stale_stock_days) = patch.stale_stock_days {
+    validate_stale_stock_days(stale_stock_days)?;
+    meta_repo::set_meta_value(pool, "stale_stock_days", &stale_stock_days.to_string()).await?;
+  }
+  if let Some(expiry_horizon_days) = patch.expiry_horizon_days {
+    validate_expiry_horizon_days(expiry_horizon_days)?;
+    meta_repo::set_meta_value(pool, "expiry_horizon_days", &expiry_horizon_days.to_string()).await?;
+  }
+  if let Some(item_required_fields) = patch.item_required_fields.as_deref() {
+    item_attribute_service::set_required_item_fields(pool, item_required_fields).await?;
+  }
+  if let Some(approval_reversal) = patch.approval_reversal {
+    meta_repo::set_meta_value(pool, "approval_reversal", if approval_reversal { "1" } else { "0" }).await?;
+  }
+  if let Some(approval_restore) = patch.approval_restore {
+    meta_repo::set_meta_value(pool, "approval_restore", if approval_restore { "1" } else { "0" }).await?;
+  }
+  if let Some(approval_count_threshold) = patch.approval_count_threshold {
+    if approval_count_threshold < 0 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "approval_count_threshold 不能为负数",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "approval_count_threshold", &approval_count_threshold.to_string()).await?;
+  }
+  if let Some(approval_scrap_threshold) = patch.approval_scrap_threshold {
+    if approval_scrap_threshold < 0 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "approval_scrap_threshold 不能为负数",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "approval_scrap_threshold", &approval_scrap_threshold.to_string()).await?;
+  }
+  if let Some(reason_required_types) = patch.reason_required_types.as_deref() {
+    reason_code_service::set_required_txn_types(pool, reason_required_types).await?;
+  }
+  if let Some(login_max_failures) = patch.login_max_failures {
+    if login_max_failures < 0 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "login_max_failures 不能为负数",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "login_max_failures", &login_max_failures.to_string()).await?;
+  }
+  if let Some(login_fail_window_minutes) = patch.login_fail_window_minutes {
+    if login_fail_window_minutes < 1 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "login_fail_window_minutes 必须大于 0",
+      ));
+    }
+    meta_repo::set_meta_value(
+      pool,
+      "login_fail_window_minutes",
+      &login_fail_window_minutes.to_string(),
+    )
+    .await?;
+  }
+  if let Some(login_lockout_minutes) = patch.login_lockout_minutes {
+    if login_lockout_minutes < 1 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "login_lockout_minutes 必须大于 0",
+      ));
+    }
+    meta_repo::set_meta_value(
+      pool,
+      "login_lockout_minutes",
+      &login_lockout_minutes.to_string(),
+    )
+    .await?;
+  }
+  if let Some(pwd_min_length) = patch.pwd_min_length {
+    if pwd_min_length < 1 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "pwd_min_length 必须大于 0",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "pwd_min_length", &pwd_min_length.to_string()).await?;
+  }
+  if let Some(pwd_min_char_classes) = patch.pwd_min_char_classes {
+    if !(1..=4).contains(&pwd_min_char_classes) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "pwd_min_char_classes 必须在 1-4 之间",
+      ));
+    }
+    meta_repo::set_meta_value(
+      pool,
+      "pwd_min_char_classes",
+      &pwd_min_char_classes.to_string(),
+    )
+    .await?;
+  }
+  if let Some(pwd_disallow_username) = patch.pwd_disallow_username {
+    let value = if pwd_disallow_username { "1" } else { "0" };
+    meta_repo::set_meta_value(pool, "pwd_disallow_username", value).await?;
+  }
+  if let Some(pwd_history_count) = patch.pwd_history_count {
+    if pwd_history_count < 0 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "pwd_history_count 不能为负数",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "pwd_history_count", &pwd_history_count.to_string()).await?;
+  }
+  if let Some(pin_session_minutes) = patch.pin_session_minutes {
+    if pin_session_minutes < 1 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "pin_session_minutes 必须大于 0",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "pin_session_minutes", &pin_session_minutes.to_string()).await?;
+  }
+  if let Some(idle_lock_minutes) = patch.idle_lock_minutes {
+    if idle_lock_minutes < 0 {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "idle_lock_minutes 不能为负数",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "idle_lock_minutes", &idle_lock_minutes.to_string()).await?;
+  }
+  if let Some(session_idle_minutes) = patch.session_idle_minutes {
+    if !(0..=MAX_SESSION_IDLE_MINUTES).contains(&session_idle_minutes) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("session_idle_minutes 必须在 0-{} 之间", MAX_SESSION_IDLE_MINUTES),
+      ));
+    }
+    meta_repo::set_meta_value(pool, "session_idle_minutes", &session_idle_minutes.to_string()).await?;
+  }
+  if let Some(audit_verbosity) = patch.audit_verbosity {
+    if !audit_service::AUDIT_VERBOSITY_VALUES.contains(&audit_verbosity.as_str()) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "audit_verbosity 仅支持 all/writes/critical",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "audit_verbosity", &audit_verbosity).await?;
+  }
+  if let Some(kiosk_mode) = patch.kiosk_mode {
+    let value = if kiosk_mode { "1" } else { "0" };
+    meta_repo::set_meta_value(pool, "kiosk_mode", value).await?;
+  }
+  if let Some(kiosk_operator_window_secs) = patch.kiosk_operator_window_secs {
+    if !(1..=3600).contains(&kiosk_operator_window_secs) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "kiosk_operator_window_secs 必须在 1-3600 之间",
+      ));
+    }
+    meta_repo::set_meta_value(
+      pool,
+      "kiosk_operator_window_secs",
+      &kiosk_operator_window_secs.to_string(),
+    )
+    .await?;
+  }
+  if let Some(valuation_method) = patch.valuation_method {
+    if !valuation_service::VALUATION_METHODS.contains(&valuation_method.as_str()) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "valuation_method 仅支持 moving_average/fifo",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "valuation_method", &valuation_method).await?;
+  }
+  if let Some(txn_no_scheme) = patch.txn_no_scheme {
+    if !txn_service::TXN_NO_SCHEMES.contains(&txn_no_scheme.as_str()) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "txn_no_scheme 仅支持 uuid/sequence/warehouse",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "txn_no_scheme", &txn_no_scheme).await?;
+  }
+  if let Some(txn_location_capture) = patch.txn_location_capture {
+    let value = if txn_location_capture { "1" } else { "0" };
+    meta_repo::set_meta_value(pool, "txn_location_capture", value).await?;
+  }
+  if let Some(locale) = patch.locale {
+    if !messages::LOCALES.contains(&locale.as_str()) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "locale 仅支持 zh-CN/en-US",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "locale", &locale).await?;
+    messages::set_current_locale(&locale);
+  }
+  if let Some(report_timezone) = patch.report_timezone {
+    let timezone = ReportTimezone::parse(&report_timezone).ok_or_else(|| {
+      AppError::new(
+        ErrorCode::ValidationError,
+        "report_timezone 仅支持 local 或 UTC 偏移（如 +08:00）",
+      )
+    })?;
+    meta_repo::set_meta_value(pool, "report_timezone", &timezone.as_setting()).await?;
+  }
+  if let Some(close_to_tray) = patch.close_to_tray {
+    let value = if close_to_tray { "1" } else { "0" };
+    meta_repo::set_meta_value(pool, "close_to_tray", value).await?;
+  }
+  if let Some(start_minimized) = patch.start_minimized {
+    let value = if start_minimized { "1" } else { "0" };
+    meta_repo::set_meta_value(pool, "start_minimized", value).await?;
+  }
+  if let Some(auto_backup_hours) = patch.auto_backup_hours {
+    if !(0..=MAX_AUTO_BACKUP_HOURS).contains(&auto_backup_hours) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("auto_backup_hours 必须在 0-{} 之间", MAX_AUTO_BACKUP_HOURS),
+      ));
+    }
+    meta_repo::set_meta_value(pool, "auto_backup_hours", &auto_backup_hours.to_string()).await?;
+  }
+  if let Some(db_pool_size) = patch.db_pool_size {
+    if !(1..=db::MAX_POOL_SIZE).contains(&db_pool_size) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        format!("db_pool_size 必须在 1-{} 之间", db::MAX_POOL_SIZE),
+      ));
+    }
+    meta_repo::set_meta_value(pool, "db_pool_size", &db_pool_size.to_string()).await?;
+  }
+  if let Some(http_api_port) = patch.http_api_port {
+    if !(1024..=65535).contains(&http_api_port) {
+      return Err(AppError::new(
+        ErrorCode::ValidationError,
+        "http_api_port 必须在 1024-65535 之间",
+      ));
+    }
+    meta_repo::set_meta_value(pool, "http_api_port", &http_api_port.to_string()).await?;
+  }
+  if let Some(http_api_enabled) = patch.http_api_enabled {
+    let has_token = meta_repo::get_meta_value(pool, "http_api_token_hash")
+      .await?
+      .is_some_and(|value| !value.is_empty());
+    if http_api_enabled && !has_token {
+      return Err(AppError::new(ErrorCode::ValidationError, "请先生成 HTTP 接口访问令牌"));
+    }
+    let value = if http_api_enabled { "1" } else { "0" };
+    meta_repo::set_meta_value(pool, "http_api_enabled", value).await?;
+  }
   Ok(())
 }
 
-/// 迁移存储根目录并更新配置
-pub async fn set_storage_root(
+/// 校验新存储目录并创建固定子目录，返回（旧目录, 新目录）；与当前目录相同时返回 None
+pub async fn prepare_storage_root(
   pool: &SqlitePool,
   new_path: &str,
-  actor_operator_id: &str,
-) -> Result<(), AppError> {
+) -> Result<Option<(PathBuf, PathBuf)>, AppError> {
   let new_root = fs::normalize_path(new_path)?;
   fs::ensure_not_sensitive_dir(&new_root)?;
   fs::ensure_dir_ready(&new_root)?;
@@ -119,26 +1059,56 @@ pub async fn set_storage_root(
     .ok_or_else(|| AppError::new(ErrorCode::NotFound, "旧存储目录不存在"))?;
   let old_root = PathBuf::from(old_root_str);
   if new_root == old_root {
-    return Ok(());
+    return Ok(None);
   }
 
-  let new_db = new_root.join("db");
-  let new_photos = new_root.join("photos");
-  let new_exports = new_root.join("exports");
-  let new_backups = new_root.join("backups");
-  fs::ensure_dir(&new_db)?;
-  fs::ensure_dir(&new_photos)?;
-  fs::ensure_dir(&new_exports)?;
-  fs::ensure_dir(&new_backups)?;
+  fs::ensure_dir(&new_root.join("db"))?;
+  fs::ensure_dir(&new_root.join("photos"))?;
+  fs::ensure_dir(&new_root.join("exports"))?;
+  fs::ensure_dir(&new_root.join("backups"))?;
+  Ok(Some((old_root, new_root)))
+}
 
-  migrate_dir(&old_root.join("db"), &new_db)?;
-  migrate_dir(&old_root.join("photos"), &new_photos)?;
-  migrate_dir(&old_root.join("exports"), &new_exports)?;
-  migrate_dir(&old_root.join("backups"), &new_backups)?;
+/// 迁移数据库目录；调用前须关闭连接池，否则数据库文件仍被占用
+pub fn migrate_db_dir(
+  from_root: &Path,
+  to_root: &Path,
+  cancel: &AtomicBool,
+  on_progress: &mut dyn FnMut(&str, fs::CopyProgress),
+) -> Result<(), AppError> {
+  migrate_dir("db", from_root, to_root, cancel, on_progress)
+}
 
-  rewrite_photo_paths(pool, &old_root, &new_root, actor_operator_id).await?;
-  meta_repo::set_meta_value(pool, "storage_root", &new_root.to_string_lossy()).await?;
+/// 迁移照片、导出与备份目录；任一目录失败或被取消时，把已迁移的目录移回旧目录（回滚过程不可取消）
+pub fn migrate_file_dirs(
+  old_root: &Path,
+  new_root: &Path,
+  cancel: &AtomicBool,
+  on_progress: &mut dyn FnMut(&str, fs::CopyProgress),
+) -> Result<(), AppError> {
+  let mut moved = Vec::new();
+  for name in STORAGE_FILE_DIRS {
+    if let Err(err) = migrate_dir(name, old_root, new_root, cancel, on_progress) {
+      let no_cancel = AtomicBool::new(false);
+      for name in moved.into_iter().rev() {
+        let _ = migrate_dir(name, new_root, old_root, &no_cancel, &mut |_, _| {});
+      }
+      return Err(err);
+    }
+    moved.push(name);
+  }
+  Ok(())
+}
 
+/// 文件目录迁移完成后，重写照片路径并记录新的存储目录
+pub async fn finish_storage_root(
+  pool: &SqlitePool,
+  old_root: &PathBuf,
+  new_root: &PathBuf,
+  actor_operator_id: &str,
+) -> Result<(), AppError> {
+  rewrite_photo_paths(pool, old_root, new_root, actor_operator_id).await?;
+  meta_repo::set_meta_value(pool, "storage_root", &new_root.to_string_lossy()).await?;
   Ok(())
 }
 
@@ -146,7 +1116,7 @@ pub async fn set_storage_root(
 pub async fn backup_db(pool: &SqlitePool) -> Result<String, AppError> {
   let storage_root = meta_repo::get_meta_value(pool, "storage_root")
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
   let root = PathBuf::from(storage_root);
   let db_path = root.join("db").join("db.sqlite");
   if !db_path.exists() {
@@ -162,41 +1132,109 @@ pub async fn backup_db(pool: &SqlitePool) -> Result<String, AppError> {
   
   fs::ensure_dir(&backups_dir)?;
   let now = Utc::now().timestamp();
-  let backup_path = backups_dir.join(format!("db_backup_{}.sqlite", now));
-  std::fs::copy(&db_path, &backup_path)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "备份数据库失败"))?;
+  // WAL 模式下最近的写入可能仍在日志中，复制前先写回主文件
+  db::checkpoint(pool).await?;
+  let backup_path = match backup_encryption_service::active_passphrase(pool).await? {
+    Some(passphrase) => {
+      let backup_path = backups_dir.join(format!("db_backup_{}.sqlite.enc", now));
+      let plain = std::fs::read(&db_path)
+        .map_err(|_| AppError::new(ErrorCode::IoError, "备份数据库失败"))?;
+      std::fs::write(&backup_path, crypto::encrypt_backup(&passphrase, &plain)?)
+        .map_err(|_| AppError::new(ErrorCode::IoError, "备份数据库失败"))?;
+      backup_path
+    }
+    None => {
+      let backup_path = backups_dir.join(format!("db_backup_{}.sqlite", now));
+      std::fs::copy(&db_path, &backup_path)
+        .map_err(|_| AppError::new(ErrorCode::IoError, "备份数据库失败"))?;
+      backup_path
+    }
+  };
 
   Ok(backup_path.to_string_lossy().to_string())
 }
 
-/// 从备份文件恢复数据库
-pub async fn restore_db(pool: &SqlitePool, src_path: &str) -> Result<(), AppError> {
+/// 待恢复的数据库文件：已写入数据库目录下的临时文件，替换前可在其上执行迁移
+pub struct StagedRestore {
+  pub staged_path: PathBuf,
+  pub db_path: PathBuf,
+  pub storage_root: PathBuf,
+}
+
+/// 把备份写入数据库目录下的临时文件（加密备份需口令，未提供时使用本机保存的口令）；
+/// 与数据库文件同目录，替换时可直接重命名，不会出现写了一半的数据库文件
+pub async fn stage_restore(
+  pool: &SqlitePool,
+  src_path: &str,
+  passphrase: Option<&str>,
+) -> Result<StagedRestore, AppError> {
   let storage_root = meta_repo::get_meta_value(pool, "storage_root")
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
   let root = PathBuf::from(storage_root);
   let db_path = root.join("db").join("db.sqlite");
+  let staged_path = root.join("db").join(format!("db_restore_{}.sqlite", Uuid::new_v4()));
 
   let src = fs::normalize_path(src_path)?;
   if !src.exists() {
     return Err(AppError::new(ErrorCode::NotFound, "备份文件不存在"));
   }
 
-  std::fs::copy(&src, &db_path)
+  let decrypted = backup_encryption_service::decrypt_if_encrypted(pool, &src, passphrase).await?;
+  let written = match decrypted {
+    Some(data) => std::fs::write(&staged_path, data),
+    None => std::fs::copy(&src, &staged_path).map(|_| ()),
+  }
+  // 落盘后再替换，避免断电后重命名到位的数据库文件内容不完整
+  .and_then(|_| std::fs::OpenOptions::new().write(true).open(&staged_path)?.sync_all());
+  if written.is_err() {
+    discard_staged_restore(&staged_path);
+    return Err(AppError::new(ErrorCode::IoError, "恢复数据库失败"));
+  }
+  Ok(StagedRestore {
+    staged_path,
+    db_path,
+    storage_root: root,
+  })
+}
+
+/// 连接池关闭后用临时文件替换数据库文件；旧的 WAL/SHM 属于恢复前的数据库，一并清理
+pub fn replace_db_file(staged: &StagedRestore) -> Result<(), AppError> {
+  std::fs::rename(&staged.staged_path, &staged.db_path)
     .map_err(|_| AppError::new(ErrorCode::IoError, "恢复数据库失败"))?;
+  remove_sqlite_sidecars(&staged.db_path);
+  remove_sqlite_sidecars(&staged.staged_path);
   Ok(())
 }
 
-/// 迁移目录（同盘移动/跨盘拷贝）
-fn migrate_dir(from: &PathBuf, to: &PathBuf) -> Result<(), AppError> {
-  if !from.exists() {
-    return Ok(());
+/// 放弃恢复时删除临时文件
+pub fn discard_staged_restore(staged_path: &Path) {
+  let _ = std::fs::remove_file(staged_path);
+  remove_sqlite_sidecars(staged_path);
+}
+
+fn remove_sqlite_sidecars(db_path: &Path) {
+  for suffix in ["-wal", "-shm"] {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push(suffix);
+    let _ = std::fs::remove_file(PathBuf::from(path));
   }
-  if from == to {
+}
+
+/// 迁移存储目录下的子目录（同盘移动/跨盘拷贝并校验）
+fn migrate_dir(
+  name: &str,
+  from_root: &Path,
+  to_root: &Path,
+  cancel: &AtomicBool,
+  on_progress: &mut dyn FnMut(&str, fs::CopyProgress),
+) -> Result<(), AppError> {
+  let from = from_root.join(name);
+  let to = to_root.join(name);
+  if !from.exists() || from == to {
     return Ok(());
   }
-  fs::move_or_copy_dir(from, to)?;
-  Ok(())
+  fs::move_dir_with_progress(&from, &to, cancel, &mut |progress| on_progress(name, progress))
 }
 
 /// 重写照片路径为相对路径并写入审计
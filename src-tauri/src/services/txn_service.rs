@@ -3,9 +3,13 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
-use crate::repo::{item_repo, operator_repo, rack_repo, stock_repo, txn_repo, warehouse_repo};
-#[cfg(not(any(target_os = "android", target_os = "ios")))]
+use crate::domain::messages::{self, Locale, MessageKey};
+use crate::domain::quantity;
+use crate::infra::db;
+use crate::repo::{item_repo, loan_repo, operator_repo, rack_repo, stock_repo, txn_repo};
 use crate::repo::meta_repo;
+use crate::services::idempotency_service::{self, IdempotencyKey};
+use crate::services::{expiry_service, item_service, photo_service, reason_code_service, stock_hold_service, system_service};
 #[cfg(not(any(target_os = "android", target_os = "ios")))]
 use std::path::PathBuf;
 use csv::WriterBuilder;
@@ -18,45 +22,167 @@ pub async fn create_inbound(
   occurred_at: i64,
   actor_operator_id: &str,
   note: Option<String>,
+  unit_cost: Option<f64>,
+  location: Option<String>,
+  expires_at: Option<i64>,
+  photo_paths: &[String],
+  idempotency: Option<IdempotencyKey<'_>>,
 ) -> Result<String, AppError> {
   if qty <= 0 {
-    return Err(AppError::new(ErrorCode::ValidationError, "数量必须为正整数"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
   }
+  item_service::validate_unit_cost(unit_cost)?;
+  let expires_at = expiry_service::validate_expires_at(expires_at)?;
 
   let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = normalize_location(pool, location).await?;
+
+  // 未填写入库成本时记录物品当前成本，保证后续计价不受成本调整影响
+  let unit_cost = match unit_cost {
+    Some(cost) => Some(cost),
+    None => item_repo::get_item_by_id(pool, item_id)
+      .await?
+      .and_then(|item| item.unit_cost),
+  };
 
   let now = Utc::now().timestamp();
   let item_id = item_id.to_string();
   let operator_id = operator.id.clone();
   let slot_id = to_slot_id.to_string();
   let txn_id = Uuid::new_v4().to_string();
-  let txn_no = format!("T{}", Uuid::new_v4());
-
-  let mut tx = pool.begin().await?;
-
-  let row = txn_repo::TxnRow {
-    id: txn_id,
-    txn_no: txn_no.clone(),
-    txn_type: "IN".to_string(),
-    occurred_at,
-    created_at: now,
-    operator_id: operator_id.clone(),
-    item_id: item_id.clone(),
-    from_slot_id: None,
-    to_slot_id: Some(slot_id.clone()),
-    qty,
-    actual_qty: None,
-    ref_txn_id: None,
-    note,
-  };
-  txn_repo::insert_txn(&mut tx, &row).await?;
+  let scheme = txn_no_scheme(pool).await?;
+  // 图片与流水在同一事务写入，流水失败时不会留下图片，图片失败时流水回滚
+  photo_service::validate_src_paths(photo_paths)?;
+  let storage_root = photo_service::get_storage_root(pool).await?;
+
+  let txn_no = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    require_item(&mut tx, &item_id, true).await?;
+    require_active_slot(&mut tx, to_slot_id).await?;
+    let txn_no = next_txn_no(&mut tx, &scheme, "IN", Some(&slot_id)).await?;
+
+    let row = txn_repo::TxnRow {
+      id: txn_id.clone(),
+      txn_no: txn_no.clone(),
+      txn_type: "IN".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: item_id.clone(),
+      from_slot_id: None,
+      to_slot_id: Some(slot_id.clone()),
+      qty,
+      actual_qty: None,
+      ref_txn_id: None,
+      note: note.clone(),
+      unit_cost,
+      location: location.clone(),
+      expires_at,
+      reason_id: None,
+    };
+    txn_repo::insert_txn(&mut tx, &row).await?;
+    idempotency_service::remember_tx(&mut tx, idempotency, &txn_no).await?;
+    let photos = photo_service::add_photos_tx(&mut tx, &storage_root, "txn", &txn_no, photo_paths).await?;
+
+    let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id).await?;
+    let next_qty = current.map(|s| s.qty).unwrap_or(0) + qty;
+    stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id, next_qty, now).await?;
+
+    tx.commit().await?;
+    photos.keep();
+    Ok(txn_no)
+  })
+  .await?;
+  touch_kiosk_operator(pool, &operator.id).await?;
+  Ok(txn_no)
+}
 
-  let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id).await?;
-  let next_qty = current.map(|s| s.qty).unwrap_or(0) + qty;
-  stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id, next_qty, now).await?;
+/// 期初库存行：数量为存储值，成本为空时记为无成本
+#[derive(Debug)]
+pub struct OpeningLine {
+  pub item_id: String,
+  pub slot_id: String,
+  pub qty: i64,
+  pub unit_cost: Option<f64>,
+}
 
-  tx.commit().await?;
-  Ok(txn_no)
+/// 期初流水的记账结果：导入前后库位库存（存储值）
+#[derive(Debug)]
+pub struct OpeningPosted {
+  pub txn_no: String,
+  pub before_qty: i64,
+  pub after_qty: i64,
+}
+
+/// 批量记录期初库存（OPENING 流水），全部行在同一事务内写入；
+/// 已有期初以外的流水时拒绝，force 时仍按增量计入库位现有库存
+pub async fn create_opening_balances(
+  pool: &SqlitePool,
+  lines: &[OpeningLine],
+  occurred_at: i64,
+  actor_operator_id: &str,
+  force: bool,
+) -> Result<Vec<OpeningPosted>, AppError> {
+  if lines.iter().any(|line| line.qty <= 0) {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+  }
+  let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  let now = Utc::now().timestamp();
+  let scheme = txn_no_scheme(pool).await?;
+
+  let posted = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    if !force && txn_repo::count_regular_txns_tx(&mut tx).await? > 0 {
+      return Err(AppError::new(
+        ErrorCode::Conflict,
+        "已有日常流水，期初库存仅用于启用前导入；确需补录请选择强制导入",
+      ));
+    }
+    let mut posted = Vec::with_capacity(lines.len());
+    for line in lines {
+      require_item(&mut tx, &line.item_id, true).await?;
+      require_active_slot(&mut tx, &line.slot_id).await?;
+      let txn_no = next_txn_no(&mut tx, &scheme, "OPENING", Some(&line.slot_id)).await?;
+      let row = txn_repo::TxnRow {
+        id: Uuid::new_v4().to_string(),
+        txn_no: txn_no.clone(),
+        txn_type: "OPENING".to_string(),
+        occurred_at,
+        created_at: now,
+        operator_id: operator.id.clone(),
+        item_id: line.item_id.clone(),
+        from_slot_id: None,
+        to_slot_id: Some(line.slot_id.clone()),
+        qty: line.qty,
+        actual_qty: None,
+        ref_txn_id: None,
+        note: Some("期初库存导入".to_string()),
+        unit_cost: line.unit_cost,
+        location: None,
+        expires_at: None,
+        reason_id: None,
+      };
+      txn_repo::insert_txn(&mut tx, &row).await?;
+
+      let before_qty = stock_repo::get_stock_tx(&mut tx, &line.item_id, &line.slot_id)
+        .await?
+        .map(|s| s.qty)
+        .unwrap_or(0);
+      let after_qty = before_qty + line.qty;
+      stock_repo::upsert_stock_tx(&mut tx, &line.item_id, &line.slot_id, after_qty, now).await?;
+      posted.push(OpeningPosted {
+        txn_no,
+        before_qty,
+        after_qty,
+      });
+    }
+
+    tx.commit().await?;
+    Ok(posted)
+  })
+  .await?;
+  Ok(posted)
 }
 
 pub async fn create_outbound(
@@ -67,48 +193,74 @@ pub async fn create_outbound(
   occurred_at: i64,
   actor_operator_id: &str,
   note: Option<String>,
+  location: Option<String>,
+  allow_expired: bool,
+  photo_paths: &[String],
+  idempotency: Option<IdempotencyKey<'_>>,
 ) -> Result<String, AppError> {
   if qty <= 0 {
-    return Err(AppError::new(ErrorCode::ValidationError, "数量必须为正整数"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
   }
 
   let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = normalize_location(pool, location).await?;
 
   let now = Utc::now().timestamp();
   let item_id = item_id.to_string();
   let operator_id = operator.id.clone();
   let slot_id = from_slot_id.to_string();
   let txn_id = Uuid::new_v4().to_string();
-  let txn_no = format!("T{}", Uuid::new_v4());
-
-  let mut tx = pool.begin().await?;
-
-  let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id).await?;
-  let current_qty = current.map(|s| s.qty).unwrap_or(0);
-  if current_qty < qty {
-    return Err(AppError::new(ErrorCode::InsufficientStock, "库存不足"));
-  }
-  let next_qty = current_qty - qty;
-
-  let row = txn_repo::TxnRow {
-    id: txn_id,
-    txn_no: txn_no.clone(),
-    txn_type: "OUT".to_string(),
-    occurred_at,
-    created_at: now,
-    operator_id: operator_id.clone(),
-    item_id: item_id.clone(),
-    from_slot_id: Some(slot_id.clone()),
-    to_slot_id: None,
-    qty,
-    actual_qty: None,
-    ref_txn_id: None,
-    note,
-  };
-  txn_repo::insert_txn(&mut tx, &row).await?;
-  stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id, next_qty, now).await?;
-
-  tx.commit().await?;
+  let scheme = txn_no_scheme(pool).await?;
+  // 图片与流水在同一事务写入，流水失败时不会留下图片，图片失败时流水回滚
+  photo_service::validate_src_paths(photo_paths)?;
+  let storage_root = photo_service::get_storage_root(pool).await?;
+
+  let txn_no = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    require_item(&mut tx, &item_id, false).await?;
+    require_active_slot(&mut tx, from_slot_id).await?;
+    let txn_no = next_txn_no(&mut tx, &scheme, "OUT", Some(&slot_id)).await?;
+
+    let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id).await?;
+    let current_qty = current.map(|s| s.qty).unwrap_or(0);
+    if current_qty < qty {
+      return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+    }
+    stock_hold_service::ensure_available_tx(&mut tx, &item_id, &slot_id, current_qty, qty).await?;
+    expiry_service::ensure_not_expired_tx(&mut tx, &item_id, &slot_id, current_qty, qty, allow_expired).await?;
+    let next_qty = current_qty - qty;
+
+    let row = txn_repo::TxnRow {
+      id: txn_id.clone(),
+      txn_no: txn_no.clone(),
+      txn_type: "OUT".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: item_id.clone(),
+      from_slot_id: Some(slot_id.clone()),
+      to_slot_id: None,
+      qty,
+      actual_qty: None,
+      ref_txn_id: None,
+      note: note.clone(),
+      unit_cost: None,
+      location: location.clone(),
+      expires_at: None,
+      reason_id: None,
+    };
+    txn_repo::insert_txn(&mut tx, &row).await?;
+    idempotency_service::remember_tx(&mut tx, idempotency, &txn_no).await?;
+    let photos = photo_service::add_photos_tx(&mut tx, &storage_root, "txn", &txn_no, photo_paths).await?;
+    stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id, next_qty, now).await?;
+
+    tx.commit().await?;
+    photos.keep();
+    Ok(txn_no)
+  })
+  .await?;
+  touch_kiosk_operator(pool, &operator.id).await?;
   Ok(txn_no)
 }
 
@@ -121,15 +273,19 @@ pub async fn create_move(
   occurred_at: i64,
   actor_operator_id: &str,
   note: Option<String>,
+  location: Option<String>,
+  idempotency: Option<IdempotencyKey<'_>>,
 ) -> Result<String, AppError> {
   if qty <= 0 {
-    return Err(AppError::new(ErrorCode::ValidationError, "数量必须为正整数"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
   }
   if from_slot_id == to_slot_id {
     return Err(AppError::new(ErrorCode::ValidationError, "来源与目标库位不能相同"));
   }
 
   let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = normalize_location(pool, location).await?;
 
   let now = Utc::now().timestamp();
   let item_id = item_id.to_string();
@@ -137,40 +293,57 @@ pub async fn create_move(
   let from_slot_id_local = from_slot_id.to_string();
   let to_slot_id_local = to_slot_id.to_string();
   let txn_id = Uuid::new_v4().to_string();
-  let txn_no = format!("T{}", Uuid::new_v4());
-
-  let mut tx = pool.begin().await?;
-
-  let current = stock_repo::get_stock_tx(&mut tx, &item_id, &from_slot_id_local).await?;
-  let current_qty = current.map(|s| s.qty).unwrap_or(0);
-  if current_qty < qty {
-    return Err(AppError::new(ErrorCode::InsufficientStock, "库存不足"));
-  }
-
-  let row = txn_repo::TxnRow {
-    id: txn_id,
-    txn_no: txn_no.clone(),
-    txn_type: "MOVE".to_string(),
-    occurred_at,
-    created_at: now,
-    operator_id: operator_id.clone(),
-    item_id: item_id.clone(),
-    from_slot_id: Some(from_slot_id_local.clone()),
-    to_slot_id: Some(to_slot_id_local.clone()),
-    qty,
-    actual_qty: None,
-    ref_txn_id: None,
-    note,
-  };
-  txn_repo::insert_txn(&mut tx, &row).await?;
-
-  let from_next = current_qty - qty;
-  stock_repo::upsert_stock_tx(&mut tx, &item_id, &from_slot_id_local, from_next, now).await?;
-  let to_current = stock_repo::get_stock_tx(&mut tx, &item_id, &to_slot_id_local).await?;
-  let to_next = to_current.map(|s| s.qty).unwrap_or(0) + qty;
-  stock_repo::upsert_stock_tx(&mut tx, &item_id, &to_slot_id_local, to_next, now).await?;
-
-  tx.commit().await?;
+  let scheme = txn_no_scheme(pool).await?;
+
+  let txn_no = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    require_item(&mut tx, &item_id, false).await?;
+    require_active_slot(&mut tx, from_slot_id).await?;
+    require_active_slot(&mut tx, to_slot_id).await?;
+    // 移库以来源库位所属仓库作为前缀
+    let txn_no = next_txn_no(&mut tx, &scheme, "MOVE", Some(&from_slot_id_local)).await?;
+
+    let current = stock_repo::get_stock_tx(&mut tx, &item_id, &from_slot_id_local).await?;
+    let current_qty = current.map(|s| s.qty).unwrap_or(0);
+    if current_qty < qty {
+      return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+    }
+    stock_hold_service::ensure_available_tx(&mut tx, &item_id, &from_slot_id_local, current_qty, qty).await?;
+    let expires_at = expiry_service::move_expiry_tx(&mut tx, &item_id, &from_slot_id_local, current_qty, qty).await?;
+
+    let row = txn_repo::TxnRow {
+      id: txn_id.clone(),
+      txn_no: txn_no.clone(),
+      txn_type: "MOVE".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: item_id.clone(),
+      from_slot_id: Some(from_slot_id_local.clone()),
+      to_slot_id: Some(to_slot_id_local.clone()),
+      qty,
+      actual_qty: None,
+      ref_txn_id: None,
+      note: note.clone(),
+      unit_cost: None,
+      location: location.clone(),
+      expires_at,
+      reason_id: None,
+    };
+    txn_repo::insert_txn(&mut tx, &row).await?;
+    idempotency_service::remember_tx(&mut tx, idempotency, &txn_no).await?;
+
+    let from_next = current_qty - qty;
+    stock_repo::upsert_stock_tx(&mut tx, &item_id, &from_slot_id_local, from_next, now).await?;
+    let to_current = stock_repo::get_stock_tx(&mut tx, &item_id, &to_slot_id_local).await?;
+    let to_next = to_current.map(|s| s.qty).unwrap_or(0) + qty;
+    stock_repo::upsert_stock_tx(&mut tx, &item_id, &to_slot_id_local, to_next, now).await?;
+
+    tx.commit().await?;
+    Ok(txn_no)
+  })
+  .await?;
+  touch_kiosk_operator(pool, &operator.id).await?;
   Ok(txn_no)
 }
 
@@ -182,12 +355,20 @@ pub async fn create_count(
   occurred_at: i64,
   actor_operator_id: &str,
   note: Option<String>,
+  location: Option<String>,
+  reason_id: Option<String>,
+  idempotency: Option<IdempotencyKey<'_>>,
 ) -> Result<String, AppError> {
   if actual_qty < 0 {
     return Err(AppError::new(ErrorCode::ValidationError, "实盘数量不能为负数"));
   }
+  // 原因记录在调整流水上，仅账实存在差异时要求填写
+  let reason_id = reason_code_service::resolve_reason(pool, "ADJUST", reason_id.as_deref()).await?;
+  let reason_required = reason_code_service::is_required(pool, "ADJUST").await?;
 
   let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = normalize_location(pool, location).await?;
 
   let now = Utc::now().timestamp();
   let item_id = item_id.to_string();
@@ -195,53 +376,179 @@ pub async fn create_count(
   let slot_id_local = slot_id.to_string();
   let count_txn_id = Uuid::new_v4().to_string();
   let adjust_txn_id = Uuid::new_v4().to_string();
-  let count_txn_no = format!("T{}", Uuid::new_v4());
-  let adjust_txn_no = format!("T{}", Uuid::new_v4());
-
-  let mut tx = pool.begin().await?;
+  let scheme = txn_no_scheme(pool).await?;
+  let locale = Locale::from_tag(&locale(pool).await?);
+  let qty_precision = item_repo::get_qty_precision(pool, &item_id).await?;
+
+  let count_txn_no = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    require_item(&mut tx, &item_id, false).await?;
+    require_active_slot(&mut tx, slot_id).await?;
+    let count_txn_no = next_txn_no(&mut tx, &scheme, "COUNT", Some(&slot_id_local)).await?;
+    let adjust_txn_no = next_txn_no(&mut tx, &scheme, "ADJUST", Some(&slot_id_local)).await?;
+
+    let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id_local).await?;
+    let current_qty = current.map(|s| s.qty).unwrap_or(0);
+    let delta = actual_qty - current_qty;
+    if delta != 0 {
+      reason_code_service::ensure_present(reason_required, "ADJUST", reason_id.as_ref())?;
+    }
+    // 调整流水备注按模板生成，盘点单取本次盘点流水号；盘点时填写的备注保留在 COUNT 流水上
+    let adjust_note = messages::render(
+      locale,
+      MessageKey::CountAdjustNote,
+      &[
+        ("expected", quantity::format(current_qty, qty_precision)),
+        ("actual", quantity::format(actual_qty, qty_precision)),
+        ("session", count_txn_no.clone()),
+      ],
+    );
+
+    let count_row = txn_repo::TxnRow {
+      id: count_txn_id.clone(),
+      txn_no: count_txn_no.clone(),
+      txn_type: "COUNT".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: item_id.clone(),
+      from_slot_id: Some(slot_id_local.clone()),
+      to_slot_id: None,
+      qty: 0,
+      actual_qty: Some(actual_qty),
+      ref_txn_id: None,
+      note: note.clone(),
+      unit_cost: None,
+      location: location.clone(),
+      expires_at: None,
+      reason_id: None,
+    };
+    txn_repo::insert_txn(&mut tx, &count_row).await?;
+
+    let adjust_row = txn_repo::TxnRow {
+      id: adjust_txn_id.clone(),
+      txn_no: adjust_txn_no,
+      txn_type: "ADJUST".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: item_id.clone(),
+      from_slot_id: Some(slot_id_local.clone()),
+      to_slot_id: None,
+      qty: delta,
+      actual_qty: None,
+      ref_txn_id: None,
+      note: Some(adjust_note),
+      unit_cost: None,
+      location: location.clone(),
+      expires_at: None,
+      reason_id: reason_id.clone(),
+    };
+    txn_repo::insert_txn(&mut tx, &adjust_row).await?;
+    idempotency_service::remember_tx(&mut tx, idempotency, &count_txn_no).await?;
+
+    stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id_local, actual_qty, now).await?;
+
+    tx.commit().await?;
+    Ok(count_txn_no)
+  })
+  .await?;
+  touch_kiosk_operator(pool, &operator.id).await?;
+  Ok(count_txn_no)
+}
 
-  let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id_local).await?;
-  let current_qty = current.map(|s| s.qty).unwrap_or(0);
-  let delta = actual_qty - current_qty;
-
-  let count_row = txn_repo::TxnRow {
-    id: count_txn_id,
-    txn_no: count_txn_no.clone(),
-    txn_type: "COUNT".to_string(),
-    occurred_at,
-    created_at: now,
-    operator_id: operator_id.clone(),
-    item_id: item_id.clone(),
-    from_slot_id: Some(slot_id_local.clone()),
-    to_slot_id: None,
-    qty: 0,
-    actual_qty: Some(actual_qty),
-    ref_txn_id: None,
-    note: note.clone(),
-  };
-  txn_repo::insert_txn(&mut tx, &count_row).await?;
-
-  let adjust_row = txn_repo::TxnRow {
-    id: adjust_txn_id,
-    txn_no: adjust_txn_no,
-    txn_type: "ADJUST".to_string(),
-    occurred_at,
-    created_at: now,
-    operator_id: operator_id.clone(),
-    item_id: item_id.clone(),
-    from_slot_id: Some(slot_id_local.clone()),
-    to_slot_id: None,
-    qty: delta,
-    actual_qty: None,
-    ref_txn_id: None,
-    note,
+/// 报废（破损、过期等核销）：从库位扣减库存并记录原因，与出库分开统计；
+/// 指定 hold_id 时报废冻结中的库存并解除该冻结，否则只能使用可用库存。已过期库存可直接报废
+pub async fn create_scrap(
+  pool: &SqlitePool,
+  item_id: &str,
+  from_slot_id: &str,
+  qty: i64,
+  occurred_at: i64,
+  actor_operator_id: &str,
+  reason_id: Option<String>,
+  note: Option<String>,
+  location: Option<String>,
+  hold_id: Option<String>,
+  photo_paths: &[String],
+  idempotency: Option<IdempotencyKey<'_>>,
+) -> Result<String, AppError> {
+  if qty <= 0 {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+  }
+  let reason_id = reason_code_service::resolve_reason(pool, "SCRAP", reason_id.as_deref()).await?;
+  reason_code_service::ensure_present(
+    reason_code_service::is_required(pool, "SCRAP").await?,
+    "SCRAP",
+    reason_id.as_ref(),
+  )?;
+  let hold = match hold_id.as_deref().map(str::trim).filter(|value| !value.is_empty()) {
+    Some(hold_id) => Some(stock_hold_service::get_hold(pool, hold_id).await?),
+    None => None,
   };
-  txn_repo::insert_txn(&mut tx, &adjust_row).await?;
 
-  stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id_local, actual_qty, now).await?;
+  let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  ensure_kiosk_operator(pool, &operator.id).await?;
+  let location = normalize_location(pool, location).await?;
 
-  tx.commit().await?;
-  Ok(count_txn_no)
+  let now = Utc::now().timestamp();
+  let item_id = item_id.to_string();
+  let operator_id = operator.id.clone();
+  let slot_id = from_slot_id.to_string();
+  let txn_id = Uuid::new_v4().to_string();
+  let scheme = txn_no_scheme(pool).await?;
+  // 图片与流水在同一事务写入，流水失败时不会留下图片，图片失败时流水回滚
+  photo_service::validate_src_paths(photo_paths)?;
+  let storage_root = photo_service::get_storage_root(pool).await?;
+
+  let txn_no = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    require_item(&mut tx, &item_id, false).await?;
+    require_active_slot(&mut tx, from_slot_id).await?;
+    let txn_no = next_txn_no(&mut tx, &scheme, "SCRAP", Some(&slot_id)).await?;
+
+    let current = stock_repo::get_stock_tx(&mut tx, &item_id, &slot_id).await?;
+    let current_qty = current.map(|s| s.qty).unwrap_or(0);
+    if current_qty < qty {
+      return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+    }
+    if let Some(hold) = hold.as_ref() {
+      stock_hold_service::release_for_scrap_tx(&mut tx, hold, &item_id, &slot_id, &operator_id, &txn_no).await?;
+    }
+    stock_hold_service::ensure_available_tx(&mut tx, &item_id, &slot_id, current_qty, qty).await?;
+    let next_qty = current_qty - qty;
+
+    let row = txn_repo::TxnRow {
+      id: txn_id.clone(),
+      txn_no: txn_no.clone(),
+      txn_type: "SCRAP".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: item_id.clone(),
+      from_slot_id: Some(slot_id.clone()),
+      to_slot_id: None,
+      qty,
+      actual_qty: None,
+      ref_txn_id: None,
+      note: note.clone(),
+      unit_cost: None,
+      location: location.clone(),
+      expires_at: None,
+      reason_id: reason_id.clone(),
+    };
+    txn_repo::insert_txn(&mut tx, &row).await?;
+    idempotency_service::remember_tx(&mut tx, idempotency, &txn_no).await?;
+    let photos = photo_service::add_photos_tx(&mut tx, &storage_root, "txn", &txn_no, photo_paths).await?;
+    stock_repo::upsert_stock_tx(&mut tx, &item_id, &slot_id, next_qty, now).await?;
+
+    tx.commit().await?;
+    photos.keep();
+    Ok(txn_no)
+  })
+  .await?;
+  touch_kiosk_operator(pool, &operator.id).await?;
+  Ok(txn_no)
 }
 
 pub async fn reverse_txn(
@@ -250,11 +557,19 @@ pub async fn reverse_txn(
   occurred_at: i64,
   actor_operator_id: &str,
   note: Option<String>,
+  reason_id: Option<String>,
 ) -> Result<String, AppError> {
+  let reason_id = reason_code_service::resolve_reason(pool, "REVERSAL", reason_id.as_deref()).await?;
+  reason_code_service::ensure_present(
+    reason_code_service::is_required(pool, "REVERSAL").await?,
+    "REVERSAL",
+    reason_id.as_ref(),
+  )?;
   let operator = require_active_operator_by_id(pool, actor_operator_id).await?;
+  ensure_kiosk_operator(pool, &operator.id).await?;
   let target = txn_repo::get_txn_by_no(pool, txn_no).await?;
   let Some(target) = target else {
-    return Err(AppError::new(ErrorCode::NotFound, "流水不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::TxnNotFound));
   };
 
   if target.txn_type == "REVERSAL" || target.txn_type == "COUNT" {
@@ -263,71 +578,88 @@ pub async fn reverse_txn(
   if txn_repo::has_reversal(pool, &target.id).await? {
     return Err(AppError::new(ErrorCode::Conflict, "该流水已冲正"));
   }
+  // 借出/归还流水与借用记录关联，冲正会导致借用状态与库存不一致
+  if loan_repo::is_loan_txn(pool, &target.id).await? {
+    return Err(AppError::new(ErrorCode::Conflict, "借用相关流水不允许冲正，请通过借用记录处理"));
+  }
 
   let now = Utc::now().timestamp();
   let operator_id = operator.id.clone();
   let reversal_id = Uuid::new_v4().to_string();
-  let reversal_no = format!("T{}", Uuid::new_v4());
-
-  let mut tx = pool.begin().await?;
-
-  match target.txn_type.as_str() {
-    "IN" => {
-      let to_slot = target
-        .to_slot_id
-        .as_ref()
-        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "入库流水缺少目标库位"))?;
-      apply_stock_delta(&mut tx, &target.item_id, to_slot, -target.qty, now).await?;
-    }
-    "OUT" => {
-      let from_slot = target
-        .from_slot_id
-        .as_ref()
-        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "出库流水缺少来源库位"))?;
-      apply_stock_delta(&mut tx, &target.item_id, from_slot, target.qty, now).await?;
-    }
-    "MOVE" => {
-      let from_slot = target
-        .from_slot_id
-        .as_ref()
-        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "移库流水缺少来源库位"))?;
-      let to_slot = target
-        .to_slot_id
-        .as_ref()
-        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "移库流水缺少目标库位"))?;
-      apply_stock_delta(&mut tx, &target.item_id, from_slot, target.qty, now).await?;
-      apply_stock_delta(&mut tx, &target.item_id, to_slot, -target.qty, now).await?;
-    }
-    "ADJUST" => {
-      let slot = target
-        .from_slot_id
-        .as_ref()
-        .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "调整流水缺少库位"))?;
-      apply_stock_delta(&mut tx, &target.item_id, slot, -target.qty, now).await?;
-    }
-    _ => {
-      return Err(AppError::new(ErrorCode::ValidationError, "该流水不允许冲正"));
+  let scheme = txn_no_scheme(pool).await?;
+
+  let reversal_no = db::retry_busy(|| async {
+    let mut tx = pool.begin().await?;
+    // 冲正沿用原流水的前缀规则（移库取来源库位）
+    let reversal_slot_id = target.from_slot_id.clone().or_else(|| target.to_slot_id.clone());
+    let reversal_no =
+      next_txn_no(&mut tx, &scheme, "REVERSAL", reversal_slot_id.as_deref()).await?;
+
+    match target.txn_type.as_str() {
+      "IN" | "OPENING" => {
+        let to_slot = target
+          .to_slot_id
+          .as_ref()
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "入库流水缺少目标库位"))?;
+        apply_stock_delta(&mut tx, &target.item_id, to_slot, -target.qty, now).await?;
+      }
+      "OUT" | "SCRAP" => {
+        let from_slot = target
+          .from_slot_id
+          .as_ref()
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "出库流水缺少来源库位"))?;
+        apply_stock_delta(&mut tx, &target.item_id, from_slot, target.qty, now).await?;
+      }
+      "MOVE" => {
+        let from_slot = target
+          .from_slot_id
+          .as_ref()
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "移库流水缺少来源库位"))?;
+        let to_slot = target
+          .to_slot_id
+          .as_ref()
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "移库流水缺少目标库位"))?;
+        apply_stock_delta(&mut tx, &target.item_id, from_slot, target.qty, now).await?;
+        apply_stock_delta(&mut tx, &target.item_id, to_slot, -target.qty, now).await?;
+      }
+      "ADJUST" => {
+        let slot = target
+          .from_slot_id
+          .as_ref()
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "调整流水缺少库位"))?;
+        apply_stock_delta(&mut tx, &target.item_id, slot, -target.qty, now).await?;
+      }
+      _ => {
+        return Err(AppError::new(ErrorCode::ValidationError, "该流水不允许冲正"));
+      }
     }
-  }
 
-  let reversal_row = txn_repo::TxnRow {
-    id: reversal_id,
-    txn_no: reversal_no.clone(),
-    txn_type: "REVERSAL".to_string(),
-    occurred_at,
-    created_at: now,
-    operator_id: operator_id.clone(),
-    item_id: target.item_id,
-    from_slot_id: target.from_slot_id,
-    to_slot_id: target.to_slot_id,
-    qty: target.qty,
-    actual_qty: None,
-    ref_txn_id: Some(target.id),
-    note,
-  };
-  txn_repo::insert_txn(&mut tx, &reversal_row).await?;
-
-  tx.commit().await?;
+    let reversal_row = txn_repo::TxnRow {
+      id: reversal_id.clone(),
+      txn_no: reversal_no.clone(),
+      txn_type: "REVERSAL".to_string(),
+      occurred_at,
+      created_at: now,
+      operator_id: operator_id.clone(),
+      item_id: target.item_id.clone(),
+      from_slot_id: target.from_slot_id.clone(),
+      to_slot_id: target.to_slot_id.clone(),
+      qty: target.qty,
+      actual_qty: None,
+      ref_txn_id: Some(target.id.clone()),
+      note: note.clone(),
+      unit_cost: None,
+      location: None,
+      expires_at: None,
+      reason_id: reason_id.clone(),
+    };
+    txn_repo::insert_txn(&mut tx, &reversal_row).await?;
+
+    tx.commit().await?;
+    Ok(reversal_no)
+  })
+  .await?;
+  touch_kiosk_operator(pool, &operator.id).await?;
   Ok(reversal_no)
 }
 
@@ -335,6 +667,9 @@ pub async fn reverse_txn(
 pub struct TxnListResult {
   pub items: Vec<txn_repo::TxnListRow>,
   pub total: i64,
+  // 下一页游标：默认排序且本页已满时为最后一条的 created_at/id，传入 after_created_at/after_id 继续翻页
+  pub next_after_created_at: Option<i64>,
+  pub next_after_id: Option<String>,
 }
 
 pub async fn list_txns(
@@ -346,12 +681,27 @@ pub async fn list_txns(
   warehouse_id: Option<String>,
   rack_id: Option<String>,
   operator_id: Option<String>,
+  reason_id: Option<String>,
   start_at: Option<i64>,
   end_at: Option<i64>,
+  allowed_warehouse_ids: Option<Vec<String>>,
   page_index: i64,
   page_size: i64,
+  sort_by: Option<String>,
+  sort_dir: Option<String>,
+  after_created_at: Option<i64>,
+  after_id: Option<String>,
 ) -> Result<TxnListResult, AppError> {
   let (page_index, page_size) = normalize_page(page_index, page_size)?;
+  let cursor = keyset_cursor(after_created_at, after_id)?;
+  // 游标基于默认排序（created_at DESC, id DESC），自定义排序时只能按页码翻页
+  let custom_sort = sort_by.as_deref().is_some_and(|value| !value.trim().is_empty());
+  if cursor.is_some() && custom_sort {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      "游标翻页不支持自定义排序",
+    ));
+  }
   let items = txn_repo::list_txns(
     pool,
     txn_type.clone(),
@@ -361,10 +711,15 @@ pub async fn list_txns(
     warehouse_id.clone(),
     rack_id.clone(),
     operator_id.clone(),
+    reason_id.clone(),
     start_at,
     end_at,
+    allowed_warehouse_ids.clone(),
     page_index,
     page_size,
+    sort_by.as_deref(),
+    sort_dir.as_deref(),
+    cursor,
   )
   .await?;
   let total = txn_repo::count_txns_filtered(
@@ -376,11 +731,49 @@ pub async fn list_txns(
     warehouse_id,
     rack_id,
     operator_id,
+    reason_id,
     start_at,
     end_at,
+    allowed_warehouse_ids,
   )
   .await?;
-  Ok(TxnListResult { items, total })
+  let next = items
+    .last()
+    .filter(|_| !custom_sort && items.len() as i64 == page_size)
+    .map(|row| (row.created_at, row.id.clone()));
+  Ok(TxnListResult {
+    items,
+    total,
+    next_after_created_at: next.as_ref().map(|(created_at, _)| *created_at),
+    next_after_id: next.map(|(_, id)| id),
+  })
+}
+
+/// 游标参数需成对提供
+fn keyset_cursor(
+  after_created_at: Option<i64>,
+  after_id: Option<String>,
+) -> Result<Option<(i64, String)>, AppError> {
+  match (after_created_at, after_id) {
+    (Some(created_at), Some(id)) => Ok(Some((created_at, id))),
+    (None, None) => Ok(None),
+    _ => Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidCursor)),
+  }
+}
+
+/// 流水类型显示名
+pub fn txn_type_label(txn_type: &str) -> &str {
+  match txn_type {
+    "IN" => "入库",
+    "OUT" => "出库",
+    "MOVE" => "移库",
+    "COUNT" => "盘点",
+    "ADJUST" => "调整",
+    "REVERSAL" => "冲正",
+    "SCRAP" => "报废",
+    "OPENING" => "期初",
+    other => other,
+  }
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -397,8 +790,10 @@ pub async fn export_txns(
   warehouse_id: Option<String>,
   rack_id: Option<String>,
   operator_id: Option<String>,
+  reason_id: Option<String>,
   start_at: Option<i64>,
-  end_at: Option<i64>
+  end_at: Option<i64>,
+  allowed_warehouse_ids: Option<Vec<String>>,
 ) -> Result<TxnExportResult, AppError> {
   // 在移动端使用临时文件，桌面端使用导出目录
   #[cfg(any(target_os = "android", target_os = "ios"))]
@@ -412,20 +807,20 @@ pub async fn export_txns(
   let file_path = {
       let storage_root = meta_repo::get_meta_value(pool, "storage_root")
           .await?
-          .ok_or_else(|| AppError::new(ErrorCode::NotFound, "存储根目录未配置"))?;
+          .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::StorageRootMissing))?;
       let export_dir = match meta_repo::get_meta_value(pool, "exports_dir").await? {
           Some(dir) if !dir.is_empty() => PathBuf::from(dir),
           _ => PathBuf::from(storage_root).join("exports"),
       };
       std::fs::create_dir_all(&export_dir)
-          .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出目录失败"))?;
+          .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportDirFailed))?;
       let now = Utc::now().timestamp();
       export_dir.join(format!("流水导出数据_{}.csv", now))
   };
   let mut writer = WriterBuilder::new()
     .has_headers(true)
     .from_path(&file_path)
-    .map_err(|_| AppError::new(ErrorCode::IoError, "创建导出文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportFileFailed))?;
 
   writer
     .write_record([
@@ -442,12 +837,17 @@ pub async fn export_txns(
       "记录人",
       "备注",
       "关联流水号",
+      "位置",
+      "原因",
     ])
-    .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
+  // 发生时间按报表时区格式化
+  let timezone = system_service::report_timezone(pool).await?;
   let page_size  = 100;
-  // 使用分页查询 list_txns 导出，避免一次性加载所有数据
+  // 按游标分批查询 list_txns 导出，避免一次性加载所有数据，也避免 OFFSET 逐页变慢
   let (_start_page, _ps) = normalize_page(1, page_size)?;
-  let mut page = 1;
+  let mut after_created_at: Option<i64> = None;
+  let mut after_id: Option<String> = None;
   loop {
     let res = list_txns(
       pool,
@@ -458,10 +858,16 @@ pub async fn export_txns(
       warehouse_id.clone(),
       rack_id.clone(),
       operator_id.clone(),
+      reason_id.clone(),
       start_at,
       end_at,
-      page,
+      allowed_warehouse_ids.clone(),
+      1,
       page_size,
+      None,
+      None,
+      after_created_at.take(),
+      after_id.take(),
     )
     .await?;
 
@@ -469,56 +875,17 @@ pub async fn export_txns(
       break;
     }
 
-    let fetched_count = res.items.len() as i64;
+    after_created_at = res.next_after_created_at;
+    after_id = res.next_after_id;
     for txn in res.items {
-      // 映射类型显示名
-      let txn_type_display = match txn.txn_type.as_str() {
-        "IN" => "入库",
-        "OUT" => "出库",
-        "MOVE" => "移库",
-        "COUNT" => "盘点",
-        "ADJUST" => "调整",
-        "REVERSAL" => "冲正",
-        other => other,
-      };
+      let txn_type_display = txn_type_label(&txn.txn_type);
 
-      // 尝试从来源库位获取货架/仓库信息，若无则使用目标库位
-      let mut warehouse_name = String::new();
-      let mut rack_name = String::new();
-      if let Some(from_slot_id) = &txn.from_slot_id {
-        if let Some(slot) = rack_repo::get_slot_by_id(pool, from_slot_id).await? {
-          if let Some(rack) = rack_repo::get_rack_by_id(pool, &slot.rack_id).await? {
-            rack_name = rack.name.clone();
-            if let Some(wid) = rack.warehouse_id.clone() {
-              if let Some(wh) = warehouse_repo::get_warehouse_by_id(pool, &wid).await? {
-                warehouse_name = wh.name.clone();
-              }
-            }
-          } else if let Some(wid) = slot.warehouse_id.clone() {
-            if let Some(wh) = warehouse_repo::get_warehouse_by_id(pool, &wid).await? {
-              warehouse_name = wh.name.clone();
-            }
-          }
-        }
-      }
-      if warehouse_name.is_empty() && rack_name.is_empty() {
-        if let Some(to_slot_id) = &txn.to_slot_id {
-          if let Some(slot) = rack_repo::get_slot_by_id(pool, to_slot_id).await? {
-            if let Some(rack) = rack_repo::get_rack_by_id(pool, &slot.rack_id).await? {
-              rack_name = rack.name.clone();
-              if let Some(wid) = rack.warehouse_id.clone() {
-                if let Some(wh) = warehouse_repo::get_warehouse_by_id(pool, &wid).await? {
-                  warehouse_name = wh.name.clone();
-                }
-              }
-            } else if let Some(wid) = slot.warehouse_id.clone() {
-              if let Some(wh) = warehouse_repo::get_warehouse_by_id(pool, &wid).await? {
-                warehouse_name = wh.name.clone();
-              }
-            }
-          }
-        }
-      }
+      // 优先取来源库位的货架/仓库，若无则使用目标库位
+      let (warehouse_name, rack_name) = if txn.from_warehouse_name.is_some() || txn.from_rack_name.is_some() {
+        (txn.from_warehouse_name.unwrap_or_default(), txn.from_rack_name.unwrap_or_default())
+      } else {
+        (txn.to_warehouse_name.unwrap_or_default(), txn.to_rack_name.unwrap_or_default())
+      };
 
       writer
         .write_record([
@@ -531,31 +898,149 @@ pub async fn export_txns(
           txn.item_code,
           txn.qty.to_string(),
           txn.actual_qty.map(|v| v.to_string()).unwrap_or_default(),
-          txn.occurred_at.to_string(),
+          timezone.format_timestamp(txn.occurred_at),
           txn.operator_name,
           txn.note.unwrap_or_default(),
           txn.ref_txn_no.unwrap_or_default(),
+          txn.location.unwrap_or_default(),
+          txn.reason_name.unwrap_or_default(),
         ])
-        .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+        .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
     }
 
-    let fetched_until = page.saturating_mul(page_size);
-    if fetched_until >= res.total || fetched_count < page_size {
+    if after_id.is_none() {
       break;
     }
-    page += 1;
   }
 
   writer
     .flush()
-    .map_err(|_| AppError::new(ErrorCode::IoError, "写入导出文件失败"))?;
+    .map_err(|_| AppError::keyed(ErrorCode::IoError, MessageKey::ExportWriteFailed))?;
 
   Ok(TxnExportResult {
     file_path: file_path.to_string_lossy().to_string(),
   })
 }
 
-async fn require_active_operator_by_id(
+/// 共享终端模式：扫码/选择记录人后登记，仅在复用窗口内可用于后续流水
+pub async fn select_kiosk_operator(pool: &SqlitePool, operator_id: &str) -> Result<(), AppError> {
+  let operator = require_active_operator_by_id(pool, operator_id).await?;
+  let now = Utc::now().timestamp();
+  meta_repo::set_meta_value(pool, "kiosk_operator_id", &operator.id).await?;
+  meta_repo::set_meta_value(pool, "kiosk_operator_at", &now.to_string()).await?;
+  Ok(())
+}
+
+/// 流水号格式：uuid（T+UUID，默认）/ sequence（IN-0012）/ warehouse（W1-IN-0012，前缀取库位所属仓库编码）
+pub const TXN_NO_SCHEMES: [&str; 3] = ["uuid", "sequence", "warehouse"];
+
+pub async fn txn_no_scheme(pool: &SqlitePool) -> Result<String, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "txn_no_scheme")
+      .await?
+      .filter(|value| TXN_NO_SCHEMES.contains(&value.as_str()))
+      .unwrap_or_else(|| "uuid".to_string()),
+  )
+}
+
+/// 系统生成文本（调整流水备注、错误信息等）的语言标签，见 messages::LOCALES
+pub async fn locale(pool: &SqlitePool) -> Result<String, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "locale")
+      .await?
+      .filter(|value| messages::LOCALES.contains(&value.as_str()))
+      .unwrap_or_else(|| messages::DEFAULT_LOCALE.to_string()),
+  )
+}
+
+/// 在事务内生成流水号；结构化格式按（前缀, 类型）分配序号，库位未归属仓库时不带前缀
+pub async fn next_txn_no(
+  tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+  scheme: &str,
+  txn_type: &str,
+  slot_id: Option<&str>,
+) -> Result<String, AppError> {
+  if scheme == "uuid" {
+    return Ok(format!("T{}", Uuid::new_v4()));
+  }
+  let prefix = match (scheme, slot_id) {
+    ("warehouse", Some(slot_id)) => txn_repo::get_slot_warehouse_code_tx(tx, slot_id)
+      .await?
+      .unwrap_or_default(),
+    _ => String::new(),
+  };
+  let seq = txn_repo::next_txn_seq_tx(tx, &prefix, txn_type).await?;
+  if prefix.is_empty() {
+    Ok(format!("{}-{:04}", txn_type, seq))
+  } else {
+    Ok(format!("{}-{}-{:04}", prefix, txn_type, seq))
+  }
+}
+
+pub async fn location_capture_enabled(pool: &SqlitePool) -> Result<bool, AppError> {
+  Ok(meta_repo::get_meta_value(pool, "txn_location_capture").await?.as_deref() == Some("1"))
+}
+
+/// 规范化流水位置：未开启采集时忽略；格式为 "纬度,经度"，统一保留两位小数只记录粗略位置
+pub async fn normalize_location(
+  pool: &SqlitePool,
+  location: Option<String>,
+) -> Result<Option<String>, AppError> {
+  let location = match location.map(|value| value.trim().to_string()).filter(|value| !value.is_empty()) {
+    Some(location) => location,
+    None => return Ok(None),
+  };
+  if !location_capture_enabled(pool).await? {
+    return Ok(None);
+  }
+  let invalid = || AppError::new(ErrorCode::ValidationError, "位置格式应为 纬度,经度");
+  let (lat, lng) = location.split_once(',').ok_or_else(invalid)?;
+  let lat = lat.trim().parse::<f64>().map_err(|_| invalid())?;
+  let lng = lng.trim().parse::<f64>().map_err(|_| invalid())?;
+  if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+    return Err(invalid());
+  }
+  Ok(Some(format!("{:.2},{:.2}", lat, lng)))
+}
+
+async fn kiosk_mode_enabled(pool: &SqlitePool) -> Result<bool, AppError> {
+  Ok(meta_repo::get_meta_value(pool, "kiosk_mode").await?.as_deref() == Some("1"))
+}
+
+/// 共享终端模式下校验记录人：必须是最近一次登记的记录人，且距登记或其上一笔流水未超过复用窗口
+pub async fn ensure_kiosk_operator(pool: &SqlitePool, operator_id: &str) -> Result<(), AppError> {
+  if !kiosk_mode_enabled(pool).await? {
+    return Ok(());
+  }
+  let window_secs = meta_repo::get_meta_i64(pool, "kiosk_operator_window_secs")
+    .await?
+    .filter(|value| *value > 0)
+    .unwrap_or(60);
+  let selected_id = meta_repo::get_meta_value(pool, "kiosk_operator_id").await?;
+  let selected_at = meta_repo::get_meta_i64(pool, "kiosk_operator_at").await?.unwrap_or(0);
+  let now = Utc::now().timestamp();
+  if selected_id.as_deref() != Some(operator_id) || now - selected_at > window_secs {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      "共享终端模式：请重新扫码或选择记录人",
+    ));
+  }
+  Ok(())
+}
+
+/// 流水成功后刷新复用窗口，便于同一人连续作业
+pub async fn touch_kiosk_operator(pool: &SqlitePool, operator_id: &str) -> Result<(), AppError> {
+  if !kiosk_mode_enabled(pool).await? {
+    return Ok(());
+  }
+  if meta_repo::get_meta_value(pool, "kiosk_operator_id").await?.as_deref() == Some(operator_id) {
+    let now = Utc::now().timestamp();
+    meta_repo::set_meta_value(pool, "kiosk_operator_at", &now.to_string()).await?;
+  }
+  Ok(())
+}
+
+pub async fn require_active_operator_by_id(
   pool: &SqlitePool,
   operator_id: &str,
 ) -> Result<operator_repo::OperatorRow, AppError> {
@@ -570,56 +1055,60 @@ async fn require_active_operator_by_id(
   Ok(operator)
 }
 
-#[allow(dead_code)]
-async fn require_active_item(
-  pool: &SqlitePool,
-  item_code: &str,
-) -> Result<item_repo::ItemRow, AppError> {
-  let item = item_repo::get_item_by_code(pool, item_code)
+/// 按 id 或物品编码解析物品，id 优先；供命令层把前端传入的编码统一换成 id
+pub async fn resolve_item(pool: &SqlitePool, reference: &str) -> Result<item_repo::ItemRow, AppError> {
+  let reference = reference.trim();
+  if let Some(item) = item_repo::get_item_by_id(pool, reference).await? {
+    return Ok(item);
+  }
+  item_repo::get_item_by_code(pool, reference)
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "物品不存在"))?;
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))
+}
 
-  if item.status != "active" {
-    return Err(AppError::new(ErrorCode::InactiveResource, "物品已停用"));
+/// 按 id 或库位编码解析库位，id 优先
+pub async fn resolve_slot(pool: &SqlitePool, reference: &str) -> Result<rack_repo::SlotRow, AppError> {
+  let reference = reference.trim();
+  if let Some(slot) = rack_repo::get_slot_by_id(pool, reference).await? {
+    return Ok(slot);
   }
-
-  Ok(item)
+  rack_repo::get_slot_by_code(pool, reference)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))
 }
 
-#[allow(dead_code)]
-async fn require_active_slot(
-  pool: &SqlitePool,
-  slot_code: &str,
-) -> Result<rack_repo::SlotRow, AppError> {
-  let slot = rack_repo::get_slot_by_code(pool, slot_code)
+/// 在记账事务内校验物品存在，与写入同一快照；入库要求物品启用，出库、移库、盘点与报废允许已停用物品，以便清理剩余库存
+async fn require_item(
+  tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+  item_id: &str,
+  require_active: bool,
+) -> Result<(), AppError> {
+  let status = item_repo::get_item_status_tx(tx, item_id)
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "库位不存在"))?;
-
-  if slot.status != "active" {
-    return Err(AppError::new(ErrorCode::InactiveResource, "库位已停用"));
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+  if require_active && status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::ItemInactive));
   }
-
-  Ok(slot)
+  Ok(())
 }
 
-#[allow(dead_code)]
-async fn require_active_item_by_id(
-  pool: &SqlitePool,
-  item_id: &str,
-) -> Result<item_repo::ItemRow, AppError> {
-  let item = item_repo::get_item_by_id(pool, item_id)
+/// 在记账事务内校验库位存在且启用（库位有库存时不能停用，停用库位上不会有待清理的库存）
+async fn require_active_slot(
+  tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+  slot_id: &str,
+) -> Result<(), AppError> {
+  let status = rack_repo::get_slot_status_tx(tx, slot_id)
     .await?
-    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "物品不存在"))?;
-
-  if item.status != "active" {
-    return Err(AppError::new(ErrorCode::InactiveResource, "物品已停用"));
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+  if status != "active" {
+    return Err(AppError::keyed(ErrorCode::InactiveResource, MessageKey::SlotInactive));
   }
-  Ok(item)
+  Ok(())
 }
 
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
   if page_index < 1 || page_size < 1 {
-    return Err(AppError::new(ErrorCode::ValidationError, "分页参数非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
   }
   Ok((page_index, page_size))
 }
@@ -635,7 +1124,11 @@ async fn apply_stock_delta(
   let current_qty = current.map(|s| s.qty).unwrap_or(0);
   let next_qty = current_qty + delta;
   if next_qty < 0 {
-    return Err(AppError::new(ErrorCode::InsufficientStock, "库存不足"));
+    return Err(AppError::keyed(ErrorCode::InsufficientStock, MessageKey::InsufficientStock));
+  }
+  // 扣减时与出库、移库一样只能使用可用库存，避免库存低于冻结数量
+  if delta < 0 {
+    stock_hold_service::ensure_available_tx(tx, item_id, slot_id, current_qty, -delta).await?;
   }
 
   stock_repo::upsert_stock_tx(tx, item_id, slot_id, next_qty, now).await?;
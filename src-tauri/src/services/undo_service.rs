@@ -0,0 +1,319 @@
+// 主数据修改撤销：按操作人保存最近 N 次物品/货架/仓库修改的逆操作（修改前后快照），支持撤销最近一次
+use std::collections::BTreeSet;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqlitePool, Transaction};
+use uuid::Uuid;
+
+use crate::domain::audit::AuditAction;
+use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
+use crate::repo::undo_repo::{self, UndoEntryRow};
+use crate::repo::{item_attribute_repo, item_repo, rack_repo, warehouse_repo};
+use crate::services::item_attribute_service::{self, ItemAttributes};
+use crate::services::rack_service;
+
+/// 每个操作人保留的撤销记录数
+pub const MAX_UNDO_ENTRIES: i64 = 20;
+
+/// 撤销时限（秒）：超过时限的修改不再允许撤销，避免覆盖后续的业务变化
+pub const UNDO_WINDOW_SECS: i64 = 30 * 60;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemSnapshot {
+  pub id: String,
+  pub item_code: String,
+  pub name: String,
+  pub model: Option<String>,
+  pub spec: Option<String>,
+  pub uom: Option<String>,
+  pub unit_cost: Option<f64>,
+  pub currency: Option<String>,
+  pub qty_precision: i64,
+  pub status: String,
+  pub remark: Option<String>,
+  pub attributes: ItemAttributes,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RackSnapshot {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  pub warehouse_id: Option<String>,
+  pub location: Option<String>,
+  pub level_count: i64,
+  pub slots_per_level: i64,
+  pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarehouseSnapshot {
+  pub id: String,
+  pub code: String,
+  pub name: String,
+  pub status: String,
+}
+
+/// 逆操作：恢复为修改前快照，仅当当前数据仍等于修改后快照时执行
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum UndoOp {
+  Items { before: Vec<ItemSnapshot>, after: Vec<ItemSnapshot> },
+  Rack { before: RackSnapshot, after: RackSnapshot },
+  Warehouse { before: WarehouseSnapshot, after: WarehouseSnapshot },
+}
+
+#[derive(Debug, Serialize)]
+pub struct UndoResult {
+  // 被撤销的原操作（审计动作）
+  pub action: String,
+  pub summary: String,
+}
+
+pub async fn capture_items(pool: &SqlitePool, ids: &[String]) -> Result<Vec<ItemSnapshot>, AppError> {
+  let mut attributes = item_attribute_service::values_by_item(pool, Some(ids)).await?;
+  let mut snapshots = Vec::with_capacity(ids.len());
+  for id in ids {
+    let Some(item) = item_repo::get_item_by_id(pool, id).await? else {
+      continue;
+    };
+    snapshots.push(ItemSnapshot {
+      attributes: attributes.remove(&item.id).unwrap_or_default(),
+      id: item.id,
+      item_code: item.item_code,
+      name: item.name,
+      model: item.model,
+      spec: item.spec,
+      uom: item.uom,
+      unit_cost: item.unit_cost,
+      currency: item.currency,
+      qty_precision: item.qty_precision,
+      status: item.status,
+      remark: item.remark,
+    });
+  }
+  Ok(snapshots)
+}
+
+pub async fn capture_rack(pool: &SqlitePool, id: &str) -> Result<RackSnapshot, AppError> {
+  let rack = rack_repo::get_rack_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::RackNotFound))?;
+  Ok(RackSnapshot {
+    id: rack.id,
+    code: rack.code,
+    name: rack.name,
+    warehouse_id: rack.warehouse_id,
+    location: rack.location,
+    level_count: rack.level_count,
+    slots_per_level: rack.slots_per_level,
+    status: rack.status,
+  })
+}
+
+pub async fn capture_warehouse(pool: &SqlitePool, id: &str) -> Result<WarehouseSnapshot, AppError> {
+  let warehouse = warehouse_repo::get_warehouse_by_id(pool, id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound))?;
+  Ok(WarehouseSnapshot {
+    id: warehouse.id,
+    code: warehouse.code,
+    name: warehouse.name,
+    status: warehouse.status,
+  })
+}
+
+/// 物品修改完成后记录撤销项（after 在此处读取）
+pub async fn record_items(
+  pool: &SqlitePool,
+  operator_id: &str,
+  action: AuditAction,
+  before: Vec<ItemSnapshot>,
+) -> Result<(), AppError> {
+  let ids: Vec<String> = before.iter().map(|item| item.id.clone()).collect();
+  let after = capture_items(pool, &ids).await?;
+  if before == after {
+    return Ok(());
+  }
+  let summary = match before.as_slice() {
+    [item] => format!("{} {}（{}）", action_label(action), item.item_code, item.name),
+    items => format!("{} {} 个物品", action_label(action), items.len()),
+  };
+  record(pool, operator_id, action, summary, &UndoOp::Items { before, after }).await
+}
+
+pub async fn record_rack(
+  pool: &SqlitePool,
+  operator_id: &str,
+  action: AuditAction,
+  before: RackSnapshot,
+) -> Result<(), AppError> {
+  let after = capture_rack(pool, &before.id).await?;
+  if before == after {
+    return Ok(());
+  }
+  let summary = format!("{} {}（{}）", action_label(action), before.code, before.name);
+  record(pool, operator_id, action, summary, &UndoOp::Rack { before, after }).await
+}
+
+pub async fn record_warehouse(
+  pool: &SqlitePool,
+  operator_id: &str,
+  action: AuditAction,
+  before: WarehouseSnapshot,
+) -> Result<(), AppError> {
+  let after = capture_warehouse(pool, &before.id).await?;
+  if before == after {
+    return Ok(());
+  }
+  let summary = format!("{} {}（{}）", action_label(action), before.code, before.name);
+  record(pool, operator_id, action, summary, &UndoOp::Warehouse { before, after }).await
+}
+
+/// 操作人最近一次可撤销的修改（已超过撤销时限的不返回）
+pub async fn last_change(pool: &SqlitePool, operator_id: &str) -> Result<Option<UndoEntryRow>, AppError> {
+  let now = Utc::now().timestamp();
+  Ok(
+    undo_repo::latest_pending(pool, operator_id)
+      .await?
+      .filter(|entry| now - entry.created_at <= UNDO_WINDOW_SECS),
+  )
+}
+
+/// 撤销操作人最近一次修改：校验时限与数据未被再次修改后恢复为修改前快照
+pub async fn undo_last_change(pool: &SqlitePool, operator_id: &str) -> Result<UndoResult, AppError> {
+  let entry = undo_repo::latest_pending(pool, operator_id)
+    .await?
+    .ok_or_else(|| AppError::new(ErrorCode::NotFound, "没有可撤销的修改"))?;
+  let now = Utc::now().timestamp();
+  if now - entry.created_at > UNDO_WINDOW_SECS {
+    return Err(AppError::new(
+      ErrorCode::ValidationError,
+      format!("仅能撤销 {} 分钟内的修改", UNDO_WINDOW_SECS / 60),
+    ));
+  }
+  let op: UndoOp = serde_json::from_str(&entry.payload)
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "撤销记录已损坏"))?;
+  match op {
+    UndoOp::Items { before, after } => {
+      let ids: Vec<String> = after.iter().map(|item| item.id.clone()).collect();
+      ensure_unchanged(capture_items(pool, &ids).await? == after)?;
+      // 各物品与撤销标记在同一事务内恢复：任一步失败（如降低精度无法整除）时整体回滚，撤销项保持待撤销
+      let mut tx = pool.begin().await?;
+      for item in &before {
+        let current = after
+          .iter()
+          .find(|current| current.id == item.id)
+          .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::ItemNotFound))?;
+        restore_item(&mut tx, item, current).await?;
+      }
+      undo_repo::mark_undone_tx(&mut tx, &entry.id, now).await?;
+      tx.commit().await?;
+    }
+    UndoOp::Rack { before, after } => {
+      ensure_unchanged(capture_rack(pool, &after.id).await? == after)?;
+      rack_repo::update_rack(
+        pool,
+        &before.id,
+        &before.name,
+        before.warehouse_id.clone(),
+        before.location.clone(),
+        before.level_count,
+        before.slots_per_level,
+      )
+      .await?;
+      if before.status != after.status {
+        rack_service::set_rack_status(pool, &before.id, &before.status).await?;
+      }
+      undo_repo::mark_undone(pool, &entry.id, now).await?;
+    }
+    UndoOp::Warehouse { before, after } => {
+      ensure_unchanged(capture_warehouse(pool, &after.id).await? == after)?;
+      warehouse_repo::update_warehouse(pool, &before.id, &before.name).await?;
+      if before.status != after.status {
+        warehouse_repo::set_warehouse_status(pool, &before.id, &before.status).await?;
+      }
+      undo_repo::mark_undone(pool, &entry.id, now).await?;
+    }
+  }
+  Ok(UndoResult {
+    action: entry.action,
+    summary: entry.summary,
+  })
+}
+
+async fn record(
+  pool: &SqlitePool,
+  operator_id: &str,
+  action: AuditAction,
+  summary: String,
+  op: &UndoOp,
+) -> Result<(), AppError> {
+  let payload = serde_json::to_string(op)
+    .map_err(|_| AppError::new(ErrorCode::ValidationError, "撤销记录序列化失败"))?;
+  let entry = UndoEntryRow {
+    id: Uuid::new_v4().to_string(),
+    operator_id: operator_id.to_string(),
+    action: action.as_str().to_string(),
+    summary,
+    payload,
+    created_at: Utc::now().timestamp(),
+    undone_at: None,
+  };
+  undo_repo::insert_entry(pool, &entry).await?;
+  undo_repo::prune_entries(pool, operator_id, MAX_UNDO_ENTRIES).await
+}
+
+/// 在事务内把物品恢复为修改前快照；current 为当前数据（已校验等于修改后快照）
+async fn restore_item(
+  tx: &mut Transaction<'_, Sqlite>,
+  item: &ItemSnapshot,
+  current: &ItemSnapshot,
+) -> Result<(), AppError> {
+  item_repo::update_item_tx(
+    tx,
+    &item.id,
+    &item.name,
+    item.model.clone(),
+    item.spec.clone(),
+    item.uom.clone(),
+    item.unit_cost,
+    item.currency.clone(),
+    item.remark.clone(),
+  )
+  .await?;
+  item_repo::set_item_status_tx(tx, &item.id, &item.status).await?;
+  if current.qty_precision != item.qty_precision {
+    item_repo::change_qty_precision_tx(tx, &item.id, current.qty_precision, item.qty_precision).await?;
+  }
+  // 属性按修改前取值整体恢复，修改时新增的属性取值一并清除
+  let attr_ids: BTreeSet<&String> = current.attributes.keys().chain(item.attributes.keys()).collect();
+  let values: Vec<(String, Option<String>)> = attr_ids
+    .into_iter()
+    .map(|attr_id| (attr_id.clone(), item.attributes.get(attr_id).cloned()))
+    .collect();
+  item_attribute_repo::set_values_tx(tx, &item.id, &values).await
+}
+
+fn ensure_unchanged(unchanged: bool) -> Result<(), AppError> {
+  if !unchanged {
+    return Err(AppError::new(ErrorCode::Conflict, "数据在此之后已被再次修改，无法撤销"));
+  }
+  Ok(())
+}
+
+fn action_label(action: AuditAction) -> &'static str {
+  match action {
+    AuditAction::ItemUpdate => "编辑物品",
+    AuditAction::ItemStatus => "启用/停用物品",
+    AuditAction::ItemBulkStatus => "批量启用/停用",
+    AuditAction::ItemBulkUpdate => "批量编辑",
+    AuditAction::RackUpdate => "编辑货架",
+    AuditAction::RackStatus => "启用/停用货架",
+    AuditAction::WarehouseUpdate => "编辑仓库",
+    AuditAction::WarehouseStatus => "启用/停用仓库",
+    _ => "修改",
+  }
+}
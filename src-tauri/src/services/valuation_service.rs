@@ -0,0 +1,206 @@
+// 库存计价：按发生时间回放流水得到物品单位成本，支持移动加权平均与先进先出
+use std::collections::{HashMap, VecDeque};
+
+use sqlx::SqlitePool;
+
+use crate::domain::errors::AppError;
+use crate::repo::{meta_repo, stock_query_repo, txn_repo};
+
+/// 支持的计价方法
+pub const VALUATION_METHODS: [&str; 2] = ["moving_average", "fifo"];
+
+#[derive(Debug, serde::Serialize)]
+pub struct ItemValuation {
+  pub item_id: String,
+  pub item_code: String,
+  pub item_name: String,
+  pub currency: Option<String>,
+  // 显示数量（按物品精度换算）
+  pub qty: f64,
+  // 计价单位成本
+  pub unit_cost: f64,
+  // 库存价值（负库存按 0 计）
+  pub total_value: f64,
+}
+
+/// 按币种汇总的库存价值（未设置币种的物品单独汇总）
+#[derive(Debug, serde::Serialize)]
+pub struct CurrencyTotal {
+  pub currency: Option<String>,
+  pub total_value: f64,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct StockValuation {
+  pub method: String,
+  pub items: Vec<ItemValuation>,
+  pub totals: Vec<CurrencyTotal>,
+}
+
+/// 单个物品的成本状态
+#[derive(Default)]
+struct CostState {
+  qty: i64,
+  // 是否已有成本来源（首次入库前使用物品当前成本）
+  has_cost: bool,
+  // 移动加权平均单价
+  avg_cost: f64,
+  // 先进先出成本层：(入库流水 id, 剩余数量, 单价)
+  layers: VecDeque<(String, i64, f64)>,
+}
+
+impl CostState {
+  fn receive(&mut self, txn_id: &str, qty: i64, cost: f64) {
+    if self.qty <= 0 {
+      self.avg_cost = cost;
+    } else {
+      self.avg_cost =
+        (self.avg_cost * self.qty as f64 + cost * qty as f64) / (self.qty + qty) as f64;
+    }
+    self.qty += qty;
+    self.has_cost = true;
+    self.layers.push_back((txn_id.to_string(), qty, cost));
+  }
+
+  /// 发出库存；冲正入库时优先扣减该笔入库形成的成本层
+  fn issue(&mut self, qty: i64, layer_txn_id: Option<&str>) {
+    self.qty -= qty;
+    let mut remaining = qty;
+    if let Some(txn_id) = layer_txn_id {
+      if let Some(layer) = self.layers.iter_mut().find(|layer| layer.0 == txn_id) {
+        let take = remaining.min(layer.1);
+        layer.1 -= take;
+        remaining -= take;
+      }
+    }
+    while remaining > 0 {
+      let Some(front) = self.layers.front_mut() else {
+        break;
+      };
+      let take = remaining.min(front.1);
+      front.1 -= take;
+      remaining -= take;
+      if front.1 == 0 {
+        self.layers.pop_front();
+      }
+    }
+    self.layers.retain(|layer| layer.1 > 0);
+  }
+
+  fn unit_cost(&self, method: &str, fallback: f64) -> f64 {
+    if !self.has_cost {
+      return fallback;
+    }
+    if method != "fifo" {
+      return self.avg_cost;
+    }
+    let qty: i64 = self.layers.iter().map(|layer| layer.1).sum();
+    if qty <= 0 {
+      return self.avg_cost;
+    }
+    let value: f64 = self.layers.iter().map(|layer| layer.1 as f64 * layer.2).sum();
+    value / qty as f64
+  }
+}
+
+/// 当前计价方法（未配置时为移动加权平均）
+pub async fn valuation_method(pool: &SqlitePool) -> Result<String, AppError> {
+  Ok(
+    meta_repo::get_meta_value(pool, "valuation_method")
+      .await?
+      .filter(|value| VALUATION_METHODS.contains(&value.as_str()))
+      .unwrap_or_else(|| "moving_average".to_string()),
+  )
+}
+
+/// 回放全部流水，返回物品 id → 计价单位成本
+///
+/// 回放使用存储值（放大后的整数），加权平均与先进先出的单价与放大倍数无关，即为每显示单位的成本。
+pub async fn item_unit_costs(
+  pool: &SqlitePool,
+  method: &str,
+) -> Result<HashMap<String, f64>, AppError> {
+  let rows = txn_repo::list_valuation_txns(pool).await?;
+  let mut states: HashMap<String, CostState> = HashMap::new();
+  for row in rows {
+    let state = states.entry(row.item_id.clone()).or_default();
+    // 非入库流水的 unit_cost 为物品当前成本，用于盘盈等无成本来源的补入
+    let fallback = row.unit_cost.unwrap_or(0.0);
+    match (row.txn_type.as_str(), row.ref_txn_type.as_deref()) {
+      ("IN", _) | ("OPENING", _) => state.receive(&row.id, row.qty, fallback),
+      ("OUT", _) | ("SCRAP", _) => state.issue(row.qty, None),
+      ("ADJUST", _) | ("REVERSAL", Some("ADJUST")) => {
+        // 冲正调整的数量与原流水相同，方向相反
+        let delta = if row.txn_type == "ADJUST" { row.qty } else { -row.qty };
+        if delta > 0 {
+          let cost = state.unit_cost(method, fallback);
+          state.receive(&row.id, delta, cost);
+        } else if delta < 0 {
+          state.issue(-delta, None);
+        }
+      }
+      ("REVERSAL", Some("IN")) | ("REVERSAL", Some("OPENING")) => state.issue(row.qty, row.ref_txn_id.as_deref()),
+      ("REVERSAL", Some("OUT")) | ("REVERSAL", Some("SCRAP")) => {
+        let cost = state.unit_cost(method, fallback);
+        state.receive(&row.id, row.qty, cost);
+      }
+      _ => {}
+    }
+  }
+  Ok(
+    states
+      .into_iter()
+      .filter(|(_, state)| state.has_cost)
+      .map(|(item_id, state)| {
+        let cost = state.unit_cost(method, 0.0);
+        (item_id, cost)
+      })
+      .collect(),
+  )
+}
+
+/// 库存计价：数量取当前库存（按仓库范围过滤），单位成本取全局回放结果
+pub async fn get_stock_valuation(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<StockValuation, AppError> {
+  let method = valuation_method(pool).await?;
+  let costs = item_unit_costs(pool, &method).await?;
+  let rows =
+    stock_query_repo::list_stock_qty_by_item(pool, warehouse_id, allowed_warehouse_ids).await?;
+
+  let mut totals: Vec<CurrencyTotal> = Vec::new();
+  let items = rows
+    .into_iter()
+    .map(|row| {
+      let unit_cost = costs
+        .get(&row.item_id)
+        .copied()
+        .unwrap_or_else(|| row.unit_cost.unwrap_or(0.0));
+      let total_value = row.qty.max(0.0) * unit_cost;
+      match totals.iter_mut().find(|total| total.currency == row.currency) {
+        Some(total) => total.total_value += total_value,
+        None => totals.push(CurrencyTotal {
+          currency: row.currency.clone(),
+          total_value,
+        }),
+      }
+      ItemValuation {
+        item_id: row.item_id,
+        item_code: row.item_code,
+        item_name: row.item_name,
+        currency: row.currency,
+        qty: row.qty,
+        unit_cost,
+        total_value,
+      }
+    })
+    .collect();
+
+  Ok(StockValuation {
+    method,
+    items,
+    totals,
+  })
+}
@@ -3,8 +3,16 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::domain::errors::{AppError, ErrorCode};
+use crate::domain::messages::MessageKey;
 use crate::repo::warehouse_repo::{WarehouseRow};
-use crate::repo::warehouse_repo;
+use crate::repo::{rack_repo, warehouse_repo};
+use crate::services::rack_service;
+
+/// 仓库类型：fixed 固定仓库 / mobile 移动仓库（车辆、工具箱）
+pub const WAREHOUSE_KINDS: [&str; 2] = ["fixed", "mobile"];
+
+/// 移动仓库的格口数上限（单层货架）
+const MAX_MOBILE_COMPARTMENTS: i64 = 50;
 
 #[derive(Debug, serde::Serialize)]
 pub struct WarehouseListResult {
@@ -28,22 +36,46 @@ pub async fn list_warehouses(
 
 fn normalize_page(page_index: i64, page_size: i64) -> Result<(i64, i64), AppError> {
   if page_index < 1 || page_size < 1 {
-    return Err(AppError::new(
-      ErrorCode::ValidationError,
-      "分页参数非法",
-    ));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidPage));
   }
   Ok((page_index, page_size))
 }
 
+/// 新建仓库；移动仓库需指定归属的固定仓库，并自动生成单层货架（compartments 个格口，默认 1）
 pub async fn create_warehouse(
   pool: &SqlitePool,
   code: &str,
   name: &str,
+  kind: Option<&str>,
+  base_warehouse_id: Option<String>,
+  compartments: Option<i64>,
 ) -> Result<(), AppError> {
   if code.trim().is_empty() || name.trim().is_empty() {
     return Err(AppError::new(ErrorCode::ValidationError, "仓库编号或名称不能为空"));
   }
+  let kind = kind.map(|value| value.trim()).filter(|value| !value.is_empty()).unwrap_or("fixed");
+  if !WAREHOUSE_KINDS.contains(&kind) {
+    return Err(AppError::new(ErrorCode::ValidationError, "仓库类型仅支持 fixed/mobile"));
+  }
+  let base_warehouse_id = if kind == "mobile" {
+    let base_id = base_warehouse_id
+      .map(|value| value.trim().to_string())
+      .filter(|value| !value.is_empty())
+      .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "请选择移动仓库的归属仓库"))?;
+    let base = warehouse_repo::get_warehouse_by_id(pool, &base_id)
+      .await?
+      .ok_or_else(|| AppError::new(ErrorCode::NotFound, "归属仓库不存在"))?;
+    if base.kind != "fixed" {
+      return Err(AppError::new(ErrorCode::ValidationError, "归属仓库必须为固定仓库"));
+    }
+    Some(base_id)
+  } else {
+    None
+  };
+  let compartments = compartments.unwrap_or(1);
+  if kind == "mobile" && !(1..=MAX_MOBILE_COMPARTMENTS).contains(&compartments) {
+    return Err(AppError::new(ErrorCode::ValidationError, "格口数必须在 1-50 之间"));
+  }
   let normalized_code = normalize_warehouse_code(code)?;
   if warehouse_repo::get_warehouse_by_code(pool, &normalized_code)
     .await?
@@ -53,7 +85,21 @@ pub async fn create_warehouse(
   }
   let id = Uuid::new_v4().to_string();
   let now = Utc::now().timestamp();
-  warehouse_repo::insert_warehouse(pool, &id, &normalized_code, name, "active", now).await?;
+  warehouse_repo::insert_warehouse(
+    pool,
+    &id,
+    &normalized_code,
+    name,
+    "active",
+    kind,
+    base_warehouse_id.as_deref(),
+    now,
+  )
+  .await?;
+  if kind == "mobile" {
+    // 移动仓库不单独维护货架，统一为 1 个单层货架，每个格口一个库位
+    rack_service::create_rack(pool, "1", name.trim(), Some(id), None, 1, compartments).await?;
+  }
   Ok(())
 }
 
@@ -75,7 +121,7 @@ pub async fn set_warehouse_status(
   status: &str,
 ) -> Result<(), AppError> {
   if !matches!(status, "active" | "inactive") {
-    return Err(AppError::new(ErrorCode::ValidationError, "状态非法"));
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::InvalidStatus));
   }
   warehouse_repo::set_warehouse_status(pool, id, status).await?;
   Ok(())
@@ -89,12 +135,12 @@ pub async fn ensure_warehouse_exists(
     .await?
     .is_none()
   {
-    return Err(AppError::new(ErrorCode::NotFound, "仓库不存在"));
+    return Err(AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound));
   }
   Ok(())
 }
 
-fn normalize_warehouse_code(code: &str) -> Result<String, AppError> {
+pub fn normalize_warehouse_code(code: &str) -> Result<String, AppError> {
   let trimmed = code.trim();
   let suffix = trimmed.trim_start_matches(|value: char| value == 'W' || value == 'w');
   if suffix.is_empty() {
@@ -108,3 +154,206 @@ fn normalize_warehouse_code(code: &str) -> Result<String, AppError> {
   }
   Ok(suffix.to_string())
 }
+
+/// 库位占用统计
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct SlotUtilization {
+  pub total_slots: i64,
+  pub empty_slots: i64,
+  pub occupied_slots: i64,
+  pub inactive_slots: i64,
+  // 填充率（0-100）：占用库位 / 启用库位
+  pub fill_rate: f64,
+}
+
+impl SlotUtilization {
+  fn add(&mut self, total_slots: i64, inactive_slots: i64, occupied_slots: i64) {
+    self.total_slots += total_slots;
+    self.inactive_slots += inactive_slots;
+    self.occupied_slots += occupied_slots;
+    self.empty_slots = self.total_slots - self.inactive_slots - self.occupied_slots;
+    let active_slots = self.total_slots - self.inactive_slots;
+    self.fill_rate = if active_slots > 0 {
+      self.occupied_slots as f64 / active_slots as f64 * 100.0
+    } else {
+      0.0
+    };
+  }
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct RackUtilization {
+  pub rack_id: String,
+  pub rack_code: String,
+  pub rack_name: String,
+  #[serde(flatten)]
+  pub slots: SlotUtilization,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct WarehouseUtilization {
+  pub warehouse_id: Option<String>,
+  pub warehouse_code: Option<String>,
+  pub warehouse_name: Option<String>,
+  #[serde(flatten)]
+  pub slots: SlotUtilization,
+  pub racks: Vec<RackUtilization>,
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct WarehouseUtilizationReport {
+  pub warehouses: Vec<WarehouseUtilization>,
+  // 全部范围汇总
+  pub summary: SlotUtilization,
+}
+
+/// 仓库/货架库位利用率报表（仅统计启用货架）
+pub async fn get_warehouse_utilization(
+  pool: &SqlitePool,
+  warehouse_id: Option<String>,
+  allowed_warehouse_ids: Option<Vec<String>>,
+) -> Result<WarehouseUtilizationReport, AppError> {
+  let rows =
+    warehouse_repo::list_rack_utilization(pool, warehouse_id, allowed_warehouse_ids).await?;
+  let mut warehouses: Vec<WarehouseUtilization> = Vec::new();
+  let mut summary = SlotUtilization::default();
+  for row in rows {
+    let mut slots = SlotUtilization::default();
+    slots.add(row.total_slots, row.inactive_slots, row.occupied_slots);
+    summary.add(row.total_slots, row.inactive_slots, row.occupied_slots);
+    let rack = RackUtilization {
+      rack_id: row.rack_id,
+      rack_code: row.rack_code,
+      rack_name: row.rack_name,
+      slots,
+    };
+    // 查询已按仓库排序，相邻行属于同一仓库
+    match warehouses.last_mut() {
+      Some(warehouse) if warehouse.warehouse_id == row.warehouse_id => {
+        warehouse
+          .slots
+          .add(row.total_slots, row.inactive_slots, row.occupied_slots);
+        warehouse.racks.push(rack);
+      }
+      _ => {
+        let mut warehouse_slots = SlotUtilization::default();
+        warehouse_slots.add(row.total_slots, row.inactive_slots, row.occupied_slots);
+        warehouses.push(WarehouseUtilization {
+          warehouse_id: row.warehouse_id,
+          warehouse_code: row.warehouse_code,
+          warehouse_name: row.warehouse_name,
+          slots: warehouse_slots,
+          racks: vec![rack],
+        });
+      }
+    }
+  }
+  Ok(WarehouseUtilizationReport {
+    warehouses,
+    summary,
+  })
+}
+
+/// 快捷调拨方向：load 装车（基地 → 移动仓库）/ unload 卸车（移动仓库 → 基地）
+#[derive(Debug)]
+pub struct QuickTransferPlan {
+  pub from_slot_id: String,
+  pub to_slot_id: String,
+  pub note: String,
+}
+
+/// 解析移动仓库与其归属仓库之间的调拨库位：
+/// 未指定时来源取存量最多且足够的库位，目标取已存放该物品的库位，装车时回退为第一个格口
+pub async fn resolve_quick_transfer(
+  pool: &SqlitePool,
+  vehicle_warehouse_id: &str,
+  direction: &str,
+  item_id: &str,
+  qty: i64,
+  base_slot_id: Option<String>,
+  vehicle_slot_id: Option<String>,
+) -> Result<QuickTransferPlan, AppError> {
+  if qty <= 0 {
+    return Err(AppError::keyed(ErrorCode::ValidationError, MessageKey::QtyMustBePositive));
+  }
+  let vehicle = warehouse_repo::get_warehouse_by_id(pool, vehicle_warehouse_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::WarehouseNotFound))?;
+  if vehicle.kind != "mobile" {
+    return Err(AppError::new(ErrorCode::ValidationError, "仅移动仓库支持快捷调拨"));
+  }
+  if vehicle.status != "active" {
+    return Err(AppError::new(ErrorCode::InactiveResource, "移动仓库已停用"));
+  }
+  let base_id = vehicle
+    .base_warehouse_id
+    .clone()
+    .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "移动仓库未设置归属仓库"))?;
+  let base_slot_id = match base_slot_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+    Some(slot_id) => Some(ensure_slot_in_warehouse(pool, &slot_id, &base_id).await?),
+    None => None,
+  };
+  let vehicle_slot_id = match vehicle_slot_id.map(|id| id.trim().to_string()).filter(|id| !id.is_empty()) {
+    Some(slot_id) => Some(ensure_slot_in_warehouse(pool, &slot_id, &vehicle.id).await?),
+    None => None,
+  };
+
+  match direction {
+    "load" => {
+      let from_slot_id = match base_slot_id {
+        Some(slot_id) => slot_id,
+        None => warehouse_repo::find_item_slot_in_warehouse(pool, &base_id, item_id, qty)
+          .await?
+          .ok_or_else(|| AppError::new(ErrorCode::InsufficientStock, "归属仓库没有足够库存的库位"))?,
+      };
+      let to_slot_id = match vehicle_slot_id {
+        Some(slot_id) => slot_id,
+        None => match warehouse_repo::find_item_slot_in_warehouse(pool, &vehicle.id, item_id, 1).await? {
+          Some(slot_id) => slot_id,
+          None => warehouse_repo::first_active_slot_in_warehouse(pool, &vehicle.id)
+            .await?
+            .ok_or_else(|| AppError::new(ErrorCode::NotFound, "移动仓库没有可用库位"))?,
+        },
+      };
+      Ok(QuickTransferPlan {
+        from_slot_id,
+        to_slot_id,
+        note: format!("装车：{}", vehicle.name),
+      })
+    }
+    "unload" => {
+      let from_slot_id = match vehicle_slot_id {
+        Some(slot_id) => slot_id,
+        None => warehouse_repo::find_item_slot_in_warehouse(pool, &vehicle.id, item_id, qty)
+          .await?
+          .ok_or_else(|| AppError::new(ErrorCode::InsufficientStock, "移动仓库库存不足"))?,
+      };
+      let to_slot_id = match base_slot_id {
+        Some(slot_id) => slot_id,
+        None => warehouse_repo::find_item_slot_in_warehouse(pool, &base_id, item_id, 1)
+          .await?
+          .ok_or_else(|| AppError::new(ErrorCode::ValidationError, "归属仓库中没有该物品的库位，请选择目标库位"))?,
+      };
+      Ok(QuickTransferPlan {
+        from_slot_id,
+        to_slot_id,
+        note: format!("卸车：{}", vehicle.name),
+      })
+    }
+    _ => Err(AppError::new(ErrorCode::ValidationError, "调拨方向仅支持 load/unload")),
+  }
+}
+
+async fn ensure_slot_in_warehouse(
+  pool: &SqlitePool,
+  slot_id: &str,
+  warehouse_id: &str,
+) -> Result<String, AppError> {
+  let slot = rack_repo::get_slot_by_id(pool, slot_id)
+    .await?
+    .ok_or_else(|| AppError::keyed(ErrorCode::NotFound, MessageKey::SlotNotFound))?;
+  if slot.warehouse_id.as_deref() != Some(warehouse_id) {
+    return Err(AppError::new(ErrorCode::ValidationError, "库位不属于调拨仓库"));
+  }
+  Ok(slot.id)
+}
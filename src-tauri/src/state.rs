@@ -1,8 +1,45 @@
+use std::sync::atomic::AtomicBool;
+
+use parking_lot::RwLock;
 use sqlx::SqlitePool;
 use tokio::sync::Mutex;
 
+use crate::services::dashboard_service::DashboardCache;
+use crate::services::filter_option_service::FilterOptionsCache;
+use crate::services::structure_tree_service::StructureTreeCache;
+
 pub struct AppState {
-  pub pool: SqlitePool,
+  // 存储目录迁移后会替换为新路径上的连接池，通过 pool() 取用
+  pool: RwLock<SqlitePool>,
   pub write_lock: Mutex<()>,
   pub migrating: Mutex<bool>,
+  // 存储目录迁移的取消标志，每次开始迁移时复位
+  pub migration_cancel: AtomicBool,
+  pub dashboard_cache: DashboardCache,
+  pub filter_options_cache: FilterOptionsCache,
+  pub structure_tree_cache: StructureTreeCache,
+}
+
+impl AppState {
+  pub fn new(pool: SqlitePool) -> Self {
+    Self {
+      pool: RwLock::new(pool),
+      write_lock: Mutex::new(()),
+      migrating: Mutex::new(false),
+      migration_cancel: AtomicBool::new(false),
+      dashboard_cache: Default::default(),
+      filter_options_cache: Default::default(),
+      structure_tree_cache: Default::default(),
+    }
+  }
+
+  /// 当前数据库连接池（克隆开销很小）；不要跨越存储目录迁移长期持有
+  pub fn pool(&self) -> SqlitePool {
+    self.pool.read().clone()
+  }
+
+  /// 替换连接池，返回旧连接池，由调用方负责关闭
+  pub fn replace_pool(&self, pool: SqlitePool) -> SqlitePool {
+    std::mem::replace(&mut *self.pool.write(), pool)
+  }
 }